@@ -622,7 +622,7 @@ pub async fn ai_add_vector(
     embedding: Vec<f32>,
 ) -> Result<String, String> {
     let mut svc = state.lock().await;
-    Ok(svc.add_vector(&text, embedding, HashMap::new()))
+    svc.add_vector(&text, embedding, HashMap::new()).await
 }
 
 #[tauri::command]
@@ -640,6 +640,54 @@ pub async fn ai_search_vectors(
     ))
 }
 
+#[tauri::command]
+pub async fn ai_upsert_vectors(
+    state: State<'_, AiAgentServiceState>,
+    collection: String,
+    items: Vec<VectorUpsert>,
+) -> Result<Vec<String>, String> {
+    let mut svc = state.lock().await;
+    svc.upsert_vectors(&collection, items).await
+}
+
+#[tauri::command]
+pub async fn ai_delete_vectors(
+    state: State<'_, AiAgentServiceState>,
+    collection: String,
+    ids: Option<Vec<String>>,
+    filter: Option<HashMap<String, serde_json::Value>>,
+) -> Result<usize, String> {
+    let mut svc = state.lock().await;
+    svc.delete_vectors(&collection, &ids.unwrap_or_default(), filter.as_ref())
+        .await
+}
+
+#[tauri::command]
+pub async fn ai_search_vector_collection(
+    state: State<'_, AiAgentServiceState>,
+    req: VectorSearchRequest,
+) -> Result<Vec<SimilarityResult>, String> {
+    let svc = state.lock().await;
+    Ok(svc.search_vector_collection(&req))
+}
+
+#[tauri::command]
+pub async fn ai_list_vector_collections(
+    state: State<'_, AiAgentServiceState>,
+) -> Result<Vec<VectorCollectionInfo>, String> {
+    let svc = state.lock().await;
+    Ok(svc.list_vector_collections())
+}
+
+#[tauri::command]
+pub async fn ai_drop_vector_collection(
+    state: State<'_, AiAgentServiceState>,
+    collection: String,
+) -> Result<bool, String> {
+    let mut svc = state.lock().await;
+    svc.drop_vector_collection(&collection).await
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // RAG
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -650,7 +698,7 @@ pub async fn ai_ingest_document(
     req: IngestDocumentRequest,
 ) -> Result<String, String> {
     let mut svc = state.lock().await;
-    svc.ingest_document(req)
+    svc.ingest_document(req).await
 }
 
 #[tauri::command]
@@ -659,7 +707,7 @@ pub async fn ai_remove_document(
     doc_id: String,
 ) -> Result<bool, String> {
     let mut svc = state.lock().await;
-    Ok(svc.remove_document(&doc_id))
+    svc.remove_document(&doc_id).await
 }
 
 #[tauri::command]
@@ -668,7 +716,7 @@ pub async fn ai_search_rag(
     req: RagSearchRequest,
 ) -> Result<Vec<RagSearchResult>, String> {
     let svc = state.lock().await;
-    Ok(svc.search_rag(&req))
+    svc.search_rag(&req).await
 }
 
 #[tauri::command]
@@ -771,6 +819,112 @@ pub async fn ai_reset_budget(state: State<'_, AiAgentServiceState>) -> Result<()
     Ok(())
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// MCP Servers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[tauri::command]
+pub async fn ai_mcp_add_server(
+    state: State<'_, AiAgentServiceState>,
+    definition: McpServerDefinition,
+) -> Result<McpServerStatus, String> {
+    let mut svc = state.lock().await;
+    svc.mcp_add_server(definition).await
+}
+
+#[tauri::command]
+pub async fn ai_mcp_remove_server(
+    state: State<'_, AiAgentServiceState>,
+    id: String,
+) -> Result<bool, String> {
+    let mut svc = state.lock().await;
+    Ok(svc.mcp_remove_server(&id).await)
+}
+
+#[tauri::command]
+pub async fn ai_mcp_list_servers(
+    state: State<'_, AiAgentServiceState>,
+) -> Result<Vec<McpServerStatus>, String> {
+    let svc = state.lock().await;
+    Ok(svc.mcp_list_servers())
+}
+
+#[tauri::command]
+pub async fn ai_mcp_set_server_enabled(
+    state: State<'_, AiAgentServiceState>,
+    id: String,
+    enabled: bool,
+) -> Result<McpServerStatus, String> {
+    let mut svc = state.lock().await;
+    svc.mcp_set_server_enabled(&id, enabled).await
+}
+
+#[tauri::command]
+pub async fn ai_mcp_connect_server(
+    state: State<'_, AiAgentServiceState>,
+    id: String,
+) -> Result<McpServerStatus, String> {
+    let mut svc = state.lock().await;
+    svc.mcp_connect_server(&id).await
+}
+
+#[tauri::command]
+pub async fn ai_mcp_disconnect_server(
+    state: State<'_, AiAgentServiceState>,
+    id: String,
+) -> Result<McpServerStatus, String> {
+    let mut svc = state.lock().await;
+    svc.mcp_disconnect_server(&id).await
+}
+
+#[tauri::command]
+pub async fn ai_mcp_list_tools(
+    state: State<'_, AiAgentServiceState>,
+    server_id: Option<String>,
+) -> Result<Vec<McpRemoteTool>, String> {
+    let svc = state.lock().await;
+    Ok(svc.mcp_list_tools(server_id.as_deref()))
+}
+
+#[tauri::command]
+pub async fn ai_mcp_list_resources(
+    state: State<'_, AiAgentServiceState>,
+    server_id: Option<String>,
+) -> Result<Vec<McpRemoteResource>, String> {
+    let svc = state.lock().await;
+    Ok(svc.mcp_list_resources(server_id.as_deref()))
+}
+
+#[tauri::command]
+pub async fn ai_mcp_list_prompts(
+    state: State<'_, AiAgentServiceState>,
+    server_id: Option<String>,
+) -> Result<Vec<McpRemotePrompt>, String> {
+    let svc = state.lock().await;
+    Ok(svc.mcp_list_prompts(server_id.as_deref()))
+}
+
+#[tauri::command]
+pub async fn ai_mcp_read_resource(
+    state: State<'_, AiAgentServiceState>,
+    server_id: String,
+    uri: String,
+) -> Result<serde_json::Value, String> {
+    let svc = state.lock().await;
+    svc.mcp_read_resource(&server_id, &uri).await
+}
+
+#[tauri::command]
+pub async fn ai_mcp_get_prompt(
+    state: State<'_, AiAgentServiceState>,
+    server_id: String,
+    name: String,
+    arguments: HashMap<String, String>,
+) -> Result<serde_json::Value, String> {
+    let svc = state.lock().await;
+    svc.mcp_get_prompt(&server_id, &name, &arguments).await
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Diagnostics
 // ═══════════════════════════════════════════════════════════════════════════════
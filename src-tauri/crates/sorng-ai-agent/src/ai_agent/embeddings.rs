@@ -1,10 +1,12 @@
 // ── Embeddings & Vector Operations ───────────────────────────────────────────
 //
-// In-memory vector store with cosine similarity search, batch operations,
-// and provider-agnostic embedding generation.
+// Vector collections with HNSW nearest-neighbour search, metadata filters,
+// batch operations, and optional on-disk persistence.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use super::hnsw::HnswIndex;
+use super::persistence::SnapshotStore;
 use super::types::*;
 use super::AI_VECTOR_STORE;
 
@@ -67,7 +69,7 @@ pub fn mean_vector(vectors: &[Vec<f32>]) -> Vec<f32> {
     result
 }
 
-// ── In-Memory Vector Store ───────────────────────────────────────────────────
+// ── Vector Store ─────────────────────────────────────────────────────────────
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -80,8 +82,51 @@ pub struct VectorEntry {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// One collection: its entries plus an HNSW index over their embeddings.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VectorCollection {
+    name: String,
+    entries: HashMap<String, VectorEntry>,
+    index: HnswIndex,
+}
+
+impl VectorCollection {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            entries: HashMap::new(),
+            index: HnswIndex::default(),
+        }
+    }
+
+    /// Reattaches a loaded snapshot's vectors to its index.
+    fn restore(&mut self) {
+        let Self { entries, index, .. } = self;
+        index.restore_vectors(|key| entries.get(key).map(|e| e.embedding.as_slice()));
+        // Entries the index lost track of (e.g. a snapshot from an
+        // interrupted compaction) are re-linked rather than dropped.
+        for entry in entries.values() {
+            if !index.contains(&entry.id) {
+                let _ = index.insert(&entry.id, &entry.embedding);
+            }
+        }
+    }
+}
+
+/// Vector collections with approximate nearest-neighbour search.
+///
+/// In-memory by default; [`VectorStore::open`] backs it with a
+/// [`SnapshotStore`] so collections survive restarts. Mutations mark the
+/// collection dirty and [`VectorStore::flush`] persists it.
 pub struct VectorStore {
-    collections: HashMap<String, Vec<VectorEntry>>,
+    collections: HashMap<String, VectorCollection>,
+    storage: Option<SnapshotStore>,
+    dirty: HashSet<String>,
+    dropped: HashSet<String>,
+    /// Encrypted snapshots were skipped at open because encryption was
+    /// locked; they are merged in before the next save.
+    unloaded: bool,
 }
 
 impl Default for VectorStore {
@@ -94,25 +139,83 @@ impl VectorStore {
     pub fn new() -> Self {
         Self {
             collections: HashMap::new(),
+            storage: None,
+            dirty: HashSet::new(),
+            dropped: HashSet::new(),
+            unloaded: false,
+        }
+    }
+
+    /// Loads every persisted collection from `storage` and keeps writing
+    /// there on [`flush`](Self::flush).
+    pub async fn open(storage: SnapshotStore) -> Result<Self, String> {
+        let mut collections = HashMap::new();
+        for mut coll in storage.load_all::<VectorCollection>().await? {
+            coll.restore();
+            collections.insert(coll.name.clone(), coll);
         }
+        Ok(Self {
+            collections,
+            unloaded: storage.has_locked_snapshots().await,
+            storage: Some(storage),
+            dirty: HashSet::new(),
+            dropped: HashSet::new(),
+        })
     }
 
-    pub fn upsert(&mut self, entry: VectorEntry) {
+    /// Merges in the encrypted collections `open` skipped, once encryption
+    /// is unlocked. Entries written since then win over persisted ones.
+    async fn load_unloaded(&mut self) -> Result<(), String> {
+        let Some(storage) = self.storage.as_ref().filter(|_| self.unloaded) else {
+            return Ok(());
+        };
+        if storage.has_locked_snapshots().await {
+            return Ok(());
+        }
+        for mut persisted in storage.load_encrypted::<VectorCollection>().await? {
+            if self.dropped.contains(&persisted.name) {
+                continue;
+            }
+            let Some(coll) = self.collections.get_mut(&persisted.name) else {
+                persisted.restore();
+                self.collections.insert(persisted.name.clone(), persisted);
+                continue;
+            };
+            for (id, entry) in persisted.entries {
+                if coll.entries.contains_key(&id) {
+                    continue;
+                }
+                match coll.index.insert(&id, &entry.embedding) {
+                    Ok(()) => {
+                        coll.entries.insert(id, entry);
+                    }
+                    Err(e) => log::warn!("Dropping stored vector {}: {}", id, e),
+                }
+            }
+        }
+        self.unloaded = false;
+        Ok(())
+    }
+
+    /// Inserts or replaces an entry. The first entry fixes the collection's
+    /// embedding dimensions.
+    pub fn upsert(&mut self, entry: VectorEntry) -> Result<(), String> {
         let coll = self
             .collections
             .entry(entry.collection.clone())
-            .or_default();
-        if let Some(pos) = coll.iter().position(|e| e.id == entry.id) {
-            coll[pos] = entry;
-        } else {
-            coll.push(entry);
-        }
+            .or_insert_with(|| VectorCollection::new(&entry.collection));
+        coll.index.insert(&entry.id, &entry.embedding)?;
+        self.dirty.insert(entry.collection.clone());
+        self.dropped.remove(&entry.collection);
+        coll.entries.insert(entry.id.clone(), entry);
+        Ok(())
     }
 
-    pub fn batch_upsert(&mut self, entries: Vec<VectorEntry>) {
+    pub fn batch_upsert(&mut self, entries: Vec<VectorEntry>) -> Result<(), String> {
         for entry in entries {
-            self.upsert(entry);
+            self.upsert(entry)?;
         }
+        Ok(())
     }
 
     pub fn search(
@@ -121,60 +224,112 @@ impl VectorStore {
         query_vec: &[f32],
         top_k: usize,
         min_score: Option<f32>,
+    ) -> Vec<SimilarityResult> {
+        self.search_filtered(collection, query_vec, top_k, min_score, None)
+    }
+
+    /// Nearest neighbours whose metadata matches every `filter` pair.
+    ///
+    /// Uses the HNSW index; when a selective filter leaves the graph search
+    /// short of `top_k`, the matching entries are scanned exactly instead.
+    pub fn search_filtered(
+        &self,
+        collection: &str,
+        query_vec: &[f32],
+        top_k: usize,
+        min_score: Option<f32>,
+        filter: Option<&HashMap<String, serde_json::Value>>,
     ) -> Vec<SimilarityResult> {
         let min = min_score.unwrap_or(0.0);
-        let entries = match self.collections.get(collection) {
-            Some(e) => e,
+        let coll = match self.collections.get(collection) {
+            Some(c) => c,
             None => return Vec::new(),
         };
+        let matches = |id: &str| match filter {
+            Some(f) => coll
+                .entries
+                .get(id)
+                .is_some_and(|e| metadata_matches(&e.metadata, f)),
+            None => true,
+        };
 
-        let mut results: Vec<SimilarityResult> = entries
-            .iter()
+        let mut hits = coll.index.search(query_vec, top_k, &matches);
+        let filtered_out = filter.is_some_and(|f| !f.is_empty());
+        if filtered_out && hits.len() < top_k {
+            hits = coll
+                .entries
+                .values()
+                .filter(|e| matches(&e.id))
+                .map(|e| (e.id.clone(), cosine_similarity(query_vec, &e.embedding)))
+                .collect();
+            hits.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            hits.truncate(top_k);
+        }
+
+        hits.into_iter()
+            .filter(|(_, score)| *score >= min)
+            .filter_map(|(id, score)| coll.entries.get(&id).map(|e| (e, score)))
             .enumerate()
-            .map(|(idx, e)| {
-                let score = cosine_similarity(query_vec, &e.embedding);
-                SimilarityResult {
-                    index: idx,
-                    text: e.text.clone(),
-                    score,
-                    metadata: e.metadata.clone(),
-                }
+            .map(|(rank, (e, score))| SimilarityResult {
+                index: rank,
+                id: e.id.clone(),
+                text: e.text.clone(),
+                score,
+                metadata: e.metadata.clone(),
             })
-            .filter(|r| r.score >= min)
-            .collect();
-
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        results.truncate(top_k);
-        results
+            .collect()
     }
 
     pub fn remove(&mut self, collection: &str, id: &str) -> bool {
-        if let Some(coll) = self.collections.get_mut(collection) {
-            let before = coll.len();
-            coll.retain(|e| e.id != id);
-            coll.len() < before
-        } else {
-            false
+        let Some(coll) = self.collections.get_mut(collection) else {
+            return false;
+        };
+        coll.index.remove(id);
+        let removed = coll.entries.remove(id).is_some();
+        if removed {
+            self.dirty.insert(collection.to_string());
         }
+        removed
+    }
+
+    /// Removes every entry whose metadata matches `filter`.
+    pub fn remove_where(
+        &mut self,
+        collection: &str,
+        filter: &HashMap<String, serde_json::Value>,
+    ) -> usize {
+        let ids: Vec<String> = self
+            .get_entries(collection, Some(filter))
+            .into_iter()
+            .map(|e| e.id.clone())
+            .collect();
+        ids.iter().filter(|id| self.remove(collection, id)).count()
     }
 
     pub fn drop_collection(&mut self, collection: &str) -> bool {
-        self.collections.remove(collection).is_some()
+        let existed = self.collections.remove(collection).is_some();
+        if existed {
+            self.dirty.remove(collection);
+            self.dropped.insert(collection.to_string());
+        }
+        existed
     }
 
     pub fn list_collections(&self) -> Vec<(String, usize)> {
         self.collections
             .iter()
-            .map(|(k, v)| (k.clone(), v.len()))
+            .map(|(k, v)| (k.clone(), v.entries.len()))
             .collect()
     }
 
+    pub fn collection_dimensions(&self, collection: &str) -> Option<usize> {
+        self.collections
+            .get(collection)
+            .and_then(|c| c.index.dimensions())
+    }
+
     pub fn total_entries(&self) -> usize {
-        self.collections.values().map(|v| v.len()).sum()
+        self.collections.values().map(|v| v.entries.len()).sum()
     }
 
     pub fn get_entries(
@@ -182,28 +337,54 @@ impl VectorStore {
         collection: &str,
         filter: Option<&HashMap<String, serde_json::Value>>,
     ) -> Vec<&VectorEntry> {
-        let entries = match self.collections.get(collection) {
-            Some(e) => e,
+        let coll = match self.collections.get(collection) {
+            Some(c) => c,
             None => return Vec::new(),
         };
-        match filter {
-            Some(f) => entries
-                .iter()
-                .filter(|e| {
-                    f.iter()
-                        .all(|(k, v)| e.metadata.get(k).map(|mv| mv == v).unwrap_or(false))
-                })
-                .collect(),
-            None => entries.iter().collect(),
+        let mut entries: Vec<&VectorEntry> = coll
+            .entries
+            .values()
+            .filter(|e| filter.is_none_or(|f| metadata_matches(&e.metadata, f)))
+            .collect();
+        entries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        entries
+    }
+
+    /// Persists dirty collections and deletes dropped ones. A no-op for an
+    /// in-memory store. Collections that fail to save stay dirty.
+    pub async fn flush(&mut self) -> Result<(), String> {
+        self.load_unloaded().await?;
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        for name in std::mem::take(&mut self.dropped) {
+            storage.remove(&name)?;
+        }
+        let mut names: Vec<String> = self.dirty.iter().cloned().collect();
+        names.sort();
+        for name in names {
+            if let Some(coll) = self.collections.get(&name) {
+                storage.save(&name, coll).await?;
+            }
+            self.dirty.remove(&name);
         }
+        Ok(())
     }
 }
 
+fn metadata_matches(
+    meta: &HashMap<String, serde_json::Value>,
+    filter: &HashMap<String, serde_json::Value>,
+) -> bool {
+    filter.iter().all(|(k, v)| meta.get(k) == Some(v))
+}
+
 // ── Global Vector Store Helpers ──────────────────────────────────────────────
 
-pub fn global_vector_upsert(entry: VectorEntry) {
-    if let Ok(mut store) = AI_VECTOR_STORE.lock() {
-        store.upsert(entry);
+pub fn global_vector_upsert(entry: VectorEntry) -> Result<(), String> {
+    match AI_VECTOR_STORE.lock() {
+        Ok(mut store) => store.upsert(entry),
+        Err(e) => Err(e.to_string()),
     }
 }
 
@@ -221,7 +221,7 @@ async fn run_react(
         let assistant_text = extract_text(&response.message);
 
         if !response.message.tool_calls.is_empty() {
-            let tool_results = tools.execute_tool_calls(&response.message.tool_calls);
+            let tool_results = tools.execute_tool_calls(&response.message.tool_calls).await;
 
             steps.push(make_step(
                 iteration as u32,
@@ -400,7 +400,7 @@ async fn run_plan_and_execute(
         let text = extract_text(&response.message);
 
         if !response.message.tool_calls.is_empty() {
-            let tool_results = tools.execute_tool_calls(&response.message.tool_calls);
+            let tool_results = tools.execute_tool_calls(&response.message.tool_calls).await;
             steps.push(make_step(
                 steps.len() as u32,
                 AgentStepType::Action,
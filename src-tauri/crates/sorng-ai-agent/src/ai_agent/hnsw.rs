@@ -0,0 +1,525 @@
+// ── HNSW Index ────────────────────────────────────────────────────────────────
+//
+// Hierarchical Navigable Small World graph for approximate nearest-neighbour
+// search by cosine similarity. Vectors are normalised on insert so distance
+// is `1 - dot`. Deletes leave tombstones that still route searches; the graph
+// is rebuilt from live nodes once tombstones reach a quarter of the index.
+//
+// Only the graph is serialised. Vectors are owned by the caller's entries and
+// re-attached with `restore_vectors` after loading, so a persisted collection
+// stores each embedding once.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::embeddings::normalize;
+
+const MAX_LEVEL: usize = 16;
+const MIN_NODES_BEFORE_COMPACT: usize = 64;
+
+/// Tuning knobs for an HNSW graph.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HnswParams {
+    /// Neighbours kept per node on upper layers (twice this on layer 0).
+    pub m: usize,
+    /// Candidate list size while inserting.
+    pub ef_construction: usize,
+    /// Minimum candidate list size while searching.
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 128,
+            ef_search: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Node {
+    key: String,
+    neighbors: Vec<Vec<u32>>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+impl Node {
+    fn level(&self) -> usize {
+        self.neighbors.len() - 1
+    }
+}
+
+/// Serialised form; lookups are rebuilt on load.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawIndex {
+    params: HnswParams,
+    dimensions: Option<usize>,
+    nodes: Vec<Node>,
+    entry_point: Option<u32>,
+}
+
+impl From<RawIndex> for HnswIndex {
+    fn from(raw: RawIndex) -> Self {
+        let by_key = raw
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.deleted)
+            .map(|(i, n)| (n.key.clone(), i as u32))
+            .collect();
+        Self {
+            params: raw.params,
+            dimensions: raw.dimensions,
+            vectors: vec![Vec::new(); raw.nodes.len()],
+            nodes: raw.nodes,
+            entry_point: raw.entry_point,
+            by_key,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Scored {
+    dist: f32,
+    id: u32,
+}
+
+impl Eq for Scored {}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// An HNSW graph keyed by caller-supplied string ids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "RawIndex")]
+pub struct HnswIndex {
+    params: HnswParams,
+    dimensions: Option<usize>,
+    nodes: Vec<Node>,
+    entry_point: Option<u32>,
+    #[serde(skip)]
+    vectors: Vec<Vec<f32>>,
+    #[serde(skip)]
+    by_key: HashMap<String, u32>,
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new(HnswParams::default())
+    }
+}
+
+impl HnswIndex {
+    pub fn new(params: HnswParams) -> Self {
+        Self {
+            params: HnswParams {
+                m: params.m.max(2),
+                ef_construction: params.ef_construction.max(params.m),
+                ef_search: params.ef_search.max(1),
+            },
+            dimensions: None,
+            nodes: Vec::new(),
+            entry_point: None,
+            vectors: Vec::new(),
+            by_key: HashMap::new(),
+        }
+    }
+
+    pub fn params(&self) -> HnswParams {
+        self.params
+    }
+
+    pub fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    /// Number of live (non-deleted) vectors.
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_key.is_empty()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.by_key.contains_key(key)
+    }
+
+    /// Inserts or replaces the vector stored under `key`.
+    pub fn insert(&mut self, key: &str, vector: &[f32]) -> Result<(), String> {
+        if vector.is_empty() {
+            return Err("Cannot index an empty vector".into());
+        }
+        match self.dimensions {
+            Some(dim) if dim != vector.len() => {
+                return Err(format!(
+                    "Vector has {} dimensions, index expects {}",
+                    vector.len(),
+                    dim
+                ))
+            }
+            _ => self.dimensions = Some(vector.len()),
+        }
+        if self.by_key.contains_key(key) {
+            self.remove(key);
+        }
+        self.link(key.to_string(), normalize(vector));
+        Ok(())
+    }
+
+    /// Tombstones `key`. Returns whether it was present.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(id) = self.by_key.remove(key) else {
+            return false;
+        };
+        self.nodes[id as usize].deleted = true;
+        let tombstones = self.nodes.len() - self.by_key.len();
+        if self.nodes.len() >= MIN_NODES_BEFORE_COMPACT && tombstones * 4 >= self.nodes.len() {
+            self.compact();
+        }
+        true
+    }
+
+    /// Returns up to `k` `(key, similarity)` pairs, best first, among keys
+    /// accepted by `accept`. With a selective filter fewer than `k` may be
+    /// returned; callers needing exact filtered recall should fall back to
+    /// a scan.
+    pub fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        accept: &dyn Fn(&str) -> bool,
+    ) -> Vec<(String, f32)> {
+        let Some(entry) = self.entry_point else {
+            return Vec::new();
+        };
+        if k == 0 || self.dimensions != Some(query.len()) {
+            return Vec::new();
+        }
+        let query = normalize(query);
+        let mut cur = entry;
+        for layer in (1..=self.nodes[entry as usize].level()).rev() {
+            cur = self.search_layer(&query, &[cur], 1, layer)[0].id;
+        }
+        let ef = self.params.ef_search.max(k);
+        self.search_layer(&query, &[cur], ef, 0)
+            .into_iter()
+            .filter_map(|s| {
+                let node = &self.nodes[s.id as usize];
+                (!node.deleted && accept(&node.key)).then(|| (node.key.clone(), 1.0 - s.dist))
+            })
+            .take(k)
+            .collect()
+    }
+
+    /// Re-attaches vectors after deserialisation. Nodes whose key yields
+    /// no vector of the right size are dropped, as are tombstones (their
+    /// vectors are gone, so they can no longer route searches).
+    pub fn restore_vectors<'a>(&mut self, lookup: impl Fn(&str) -> Option<&'a [f32]>) {
+        let mut stale = false;
+        for (i, node) in self.nodes.iter_mut().enumerate() {
+            match lookup(&node.key) {
+                Some(v) if !node.deleted && Some(v.len()) == self.dimensions => {
+                    self.vectors[i] = normalize(v);
+                }
+                _ => {
+                    stale = true;
+                    if !node.deleted {
+                        node.deleted = true;
+                        self.by_key.remove(&node.key);
+                    }
+                }
+            }
+        }
+        if stale {
+            self.compact();
+        }
+    }
+
+    /// Rebuilds the graph from live nodes, dropping tombstones.
+    pub fn compact(&mut self) {
+        let live: Vec<(String, Vec<f32>)> = self
+            .nodes
+            .iter()
+            .zip(std::mem::take(&mut self.vectors))
+            .filter(|(n, _)| !n.deleted)
+            .map(|(n, v)| (n.key.clone(), v))
+            .collect();
+        self.nodes.clear();
+        self.by_key.clear();
+        self.entry_point = None;
+        for (key, vector) in live {
+            self.link(key, vector);
+        }
+    }
+
+    // ── Graph construction ───────────────────────────────────────────────────
+
+    fn distance(&self, a: &[f32], id: u32) -> f32 {
+        let b = &self.vectors[id as usize];
+        if b.len() != a.len() {
+            return f32::MAX;
+        }
+        1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>()
+    }
+
+    /// Level drawn from the usual exponential distribution, seeded by the
+    /// key so rebuilds are reproducible.
+    fn level_for(&self, key: &str) -> usize {
+        let digest = Sha256::digest(key.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let u = ((u64::from_le_bytes(bytes) >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.params.m as f64).ln();
+        ((-u.ln() * ml) as usize).min(MAX_LEVEL)
+    }
+
+    fn link(&mut self, key: String, vector: Vec<f32>) {
+        let level = self.level_for(&key);
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            key: key.clone(),
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.vectors.push(vector);
+        self.by_key.insert(key, id);
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(id);
+            return;
+        };
+
+        let query = self.vectors[id as usize].clone();
+        let top = self.nodes[entry as usize].level();
+        let mut cur = entry;
+        for layer in (level + 1..=top).rev() {
+            cur = self.search_layer(&query, &[cur], 1, layer)[0].id;
+        }
+
+        let mut entry_points = vec![cur];
+        for layer in (0..=level.min(top)).rev() {
+            let found =
+                self.search_layer(&query, &entry_points, self.params.ef_construction, layer);
+            let selected: Vec<u32> = found
+                .iter()
+                .filter(|s| s.id != id)
+                .take(self.params.m)
+                .map(|s| s.id)
+                .collect();
+            self.nodes[id as usize].neighbors[layer] = selected.clone();
+            let max_links = if layer == 0 {
+                self.params.m * 2
+            } else {
+                self.params.m
+            };
+            for n in selected {
+                self.nodes[n as usize].neighbors[layer].push(id);
+                if self.nodes[n as usize].neighbors[layer].len() > max_links {
+                    self.prune(n, layer, max_links);
+                }
+            }
+            entry_points = found.iter().map(|s| s.id).collect();
+        }
+
+        if level > top {
+            self.entry_point = Some(id);
+        }
+    }
+
+    /// Keeps the `max_links` closest neighbours of `node` on `layer`.
+    fn prune(&mut self, node: u32, layer: usize, max_links: usize) {
+        let base = self.vectors[node as usize].clone();
+        let mut scored: Vec<Scored> = self.nodes[node as usize].neighbors[layer]
+            .iter()
+            .map(|&id| Scored {
+                dist: self.distance(&base, id),
+                id,
+            })
+            .collect();
+        scored.sort();
+        scored.truncate(max_links);
+        self.nodes[node as usize].neighbors[layer] = scored.into_iter().map(|s| s.id).collect();
+    }
+
+    /// Best-first search on one layer, returning up to `ef` nodes sorted by
+    /// ascending distance. Tombstoned nodes are traversed and returned.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: &[u32],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entry_points.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &id in entry_points {
+            let s = Scored {
+                dist: self.distance(query, id),
+                id,
+            };
+            candidates.push(Reverse(s));
+            results.push(s);
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            if let Some(worst) = results.peek() {
+                if results.len() >= ef && current.dist > worst.dist {
+                    break;
+                }
+            }
+            let Some(links) = self.nodes[current.id as usize].neighbors.get(layer) else {
+                continue;
+            };
+            for &n in links {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let s = Scored {
+                    dist: self.distance(query, n),
+                    id: n,
+                };
+                let admit = results.len() < ef || results.peek().is_some_and(|w| s.dist < w.dist);
+                if admit {
+                    candidates.push(Reverse(s));
+                    results.push(s);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random vectors (xorshift) so recall is stable.
+    fn vectors(n: usize, dim: usize, mut seed: u64) -> Vec<Vec<f32>> {
+        (0..n)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        seed ^= seed << 13;
+                        seed ^= seed >> 7;
+                        seed ^= seed << 17;
+                        (seed % 2000) as f32 / 1000.0 - 1.0
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn brute_force(data: &[Vec<f32>], query: &[f32], k: usize) -> Vec<String> {
+        let q = normalize(query);
+        let mut scored: Vec<(String, f32)> = data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let v = normalize(v);
+                (i.to_string(), q.iter().zip(&v).map(|(a, b)| a * b).sum())
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().take(k).map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn test_recall_against_brute_force() {
+        let data = vectors(500, 16, 0x9e37_79b9);
+        let mut index = HnswIndex::default();
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&i.to_string(), v).unwrap();
+        }
+        assert_eq!(index.len(), 500);
+        assert!(index.insert("bad", &[1.0; 8]).is_err());
+
+        let queries = vectors(20, 16, 0x1234_5678);
+        let mut hits = 0;
+        for q in &queries {
+            let expected = brute_force(&data, q, 10);
+            let got: Vec<String> = index
+                .search(q, 10, &|_| true)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect();
+            hits += got.iter().filter(|k| expected.contains(k)).count();
+        }
+        assert!(hits >= 180, "recall too low: {hits}/200");
+    }
+
+    #[test]
+    fn test_remove_and_compact() {
+        let data = vectors(100, 8, 42);
+        let mut index = HnswIndex::default();
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&i.to_string(), v).unwrap();
+        }
+        for i in 0..40 {
+            assert!(index.remove(&i.to_string()));
+        }
+        assert!(!index.remove("0"));
+        assert_eq!(index.len(), 60);
+        // 40% tombstones crosses the threshold, so the graph was rebuilt.
+        assert!(index.nodes.len() < 100);
+
+        let results = index.search(&data[0], 100, &|_| true);
+        assert_eq!(results.len(), 60);
+        assert!(results
+            .iter()
+            .all(|(k, _)| k.parse::<usize>().unwrap() >= 40));
+
+        let even = index.search(&data[50], 5, &|k| k.parse::<usize>().unwrap() % 2 == 0);
+        assert!(even
+            .iter()
+            .all(|(k, _)| k.parse::<usize>().unwrap() % 2 == 0));
+    }
+
+    #[test]
+    fn test_serde_round_trip_restores_vectors() {
+        let data = vectors(50, 8, 7);
+        let mut index = HnswIndex::default();
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&i.to_string(), v).unwrap();
+        }
+        let json = serde_json::to_string(&index).unwrap();
+        let mut restored: HnswIndex = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.len(), 50);
+
+        // Key "3" lost its embedding: it must be dropped, not left dangling.
+        restored.restore_vectors(|key| {
+            (key != "3").then(|| data[key.parse::<usize>().unwrap()].as_slice())
+        });
+        assert_eq!(restored.len(), 49);
+        assert!(!restored.contains("3"));
+        let top = restored.search(&data[10], 1, &|_| true);
+        assert_eq!(top[0].0, "10");
+    }
+}
@@ -0,0 +1,1298 @@
+// ── MCP Client ────────────────────────────────────────────────────────────────
+//
+// Connects the agent to external Model Context Protocol servers over stdio or
+// Streamable HTTP, imports their tools, resources and prompts, and registers
+// the remote tools in the agent's `ToolRegistry` so model tool calls are
+// routed to the owning server.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::oneshot;
+
+use super::tools::ToolRegistry;
+use super::types::*;
+
+/// Protocol revision offered during `initialize`.
+pub const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Remote tool names are prefixed with the server id and capped at the
+/// length most providers accept for function names.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Upper bound on `nextCursor` pages followed per list call.
+const MAX_LIST_PAGES: usize = 100;
+
+// ── Transport ────────────────────────────────────────────────────────────────
+
+/// A JSON-RPC channel to an MCP server.
+#[async_trait]
+pub trait McpTransport: Send + Sync {
+    /// Sends a request and waits for the matching response's `result`.
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String>;
+
+    /// Sends a notification (no response expected).
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String>;
+
+    /// Tears down the connection.
+    async fn close(&self);
+}
+
+fn request_message(id: u64, method: &str, params: Value) -> Value {
+    let mut msg = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+    if !params.is_null() {
+        msg["params"] = params;
+    }
+    msg
+}
+
+fn notification_message(method: &str, params: Value) -> Value {
+    let mut msg = json!({ "jsonrpc": "2.0", "method": method });
+    if !params.is_null() {
+        msg["params"] = params;
+    }
+    msg
+}
+
+/// Extracts `result` from a JSON-RPC response, mapping `error` to `Err`.
+fn into_result(mut response: Value) -> Result<Value, String> {
+    if let Some(error) = response.get("error") {
+        let code = error["code"].as_i64().unwrap_or(0);
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(format!("MCP error {}: {}", code, message));
+    }
+    Ok(response
+        .get_mut("result")
+        .map(Value::take)
+        .unwrap_or(Value::Null))
+}
+
+fn is_response(msg: &Value) -> bool {
+    msg.get("method").is_none() && (msg.get("result").is_some() || msg.get("error").is_some())
+}
+
+// ── stdio ────────────────────────────────────────────────────────────────────
+
+type PendingRequests = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+/// Newline-delimited JSON-RPC over a subprocess's stdin/stdout.
+pub struct StdioTransport {
+    writer: tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    pending: PendingRequests,
+    next_id: AtomicU64,
+    reader_task: tokio::task::JoinHandle<()>,
+    child: tokio::sync::Mutex<Option<tokio::process::Child>>,
+}
+
+impl StdioTransport {
+    /// Launches `command` and speaks MCP over its stdio. Stderr is discarded.
+    pub fn spawn(
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+        cwd: Option<&str>,
+    ) -> Result<Self, String> {
+        let mut cmd = tokio::process::Command::new(command);
+        cmd.args(args)
+            .envs(env)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start MCP server '{}': {}", command, e))?;
+        let stdin = child.stdin.take().ok_or("MCP server stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("MCP server stdout unavailable")?;
+        Ok(Self::from_streams(stdout, stdin, Some(child)))
+    }
+
+    /// Speaks MCP over an arbitrary byte stream pair.
+    pub fn from_streams<R, W>(reader: R, writer: W, child: Option<tokio::process::Child>) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let pending: PendingRequests = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let reader_pending = pending.clone();
+        let reader_task = tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let msg: Value = match serde_json::from_str(line.trim()) {
+                    Ok(v) => v,
+                    Err(_) => continue,
+                };
+                if !is_response(&msg) {
+                    log::debug!("Ignoring MCP server message: {}", line);
+                    continue;
+                }
+                let Some(id) = msg["id"].as_u64() else {
+                    continue;
+                };
+                let sender = reader_pending.lock().ok().and_then(|mut p| p.remove(&id));
+                if let Some(sender) = sender {
+                    let _ = sender.send(msg);
+                }
+            }
+            // Dropping the senders fails every in-flight request.
+            if let Ok(mut p) = reader_pending.lock() {
+                p.clear();
+            }
+        });
+
+        Self {
+            writer: tokio::sync::Mutex::new(Box::new(writer)),
+            pending,
+            next_id: AtomicU64::new(1),
+            reader_task,
+            child: tokio::sync::Mutex::new(child),
+        }
+    }
+
+    async fn write_message(&self, msg: &Value) -> Result<(), String> {
+        let mut line = msg.to_string();
+        line.push('\n');
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("MCP write failed: {}", e))?;
+        writer
+            .flush()
+            .await
+            .map_err(|e| format!("MCP write failed: {}", e))
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut p) = self.pending.lock() {
+            p.remove(&id);
+        }
+    }
+}
+
+#[async_trait]
+impl McpTransport for StdioTransport {
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(id, tx);
+
+        if let Err(e) = self
+            .write_message(&request_message(id, method, params))
+            .await
+        {
+            self.forget(id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => into_result(response),
+            Ok(Err(_)) => Err("MCP server closed the connection".into()),
+            Err(_) => {
+                self.forget(id);
+                let _ = self
+                    .notify(
+                        "notifications/cancelled",
+                        json!({ "requestId": id, "reason": "timeout" }),
+                    )
+                    .await;
+                Err(format!(
+                    "MCP request '{}' timed out after {}ms",
+                    method,
+                    timeout.as_millis()
+                ))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.write_message(&notification_message(method, params))
+            .await
+    }
+
+    async fn close(&self) {
+        self.reader_task.abort();
+        let _ = self.writer.lock().await.shutdown().await;
+        if let Some(mut child) = self.child.lock().await.take() {
+            let _ = child.start_kill();
+            let _ = child.wait().await;
+        }
+    }
+}
+
+// ── Streamable HTTP ──────────────────────────────────────────────────────────
+
+/// JSON-RPC POSTed to a single endpoint; responses arrive either as a JSON
+/// body or as an SSE stream.
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    session_id: std::sync::Mutex<Option<String>>,
+    next_id: AtomicU64,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str, headers: &HashMap<String, String>) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| format!("HTTP client error: {}", e))?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            headers: headers.clone(),
+            session_id: std::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    fn session_id(&self) -> Option<String> {
+        self.session_id.lock().ok().and_then(|s| s.clone())
+    }
+
+    async fn post(&self, body: &Value) -> Result<reqwest::Response, String> {
+        let mut req = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .body(body.to_string());
+        for (k, v) in &self.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        if let Some(sid) = self.session_id() {
+            req = req.header("Mcp-Session-Id", sid);
+        }
+
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| format!("MCP HTTP request failed: {}", e))?;
+        if let Some(sid) = resp
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            if let Ok(mut s) = self.session_id.lock() {
+                *s = Some(sid.to_string());
+            }
+        }
+        if !resp.status().is_success() {
+            return Err(format!("MCP server returned HTTP {}", resp.status()));
+        }
+        Ok(resp)
+    }
+
+    async fn exchange(&self, id: u64, body: &Value) -> Result<Value, String> {
+        let resp = self.post(body).await?;
+        let is_sse = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+        if !is_sse {
+            let payload: Value = resp
+                .json()
+                .await
+                .map_err(|e| format!("Invalid MCP response: {}", e))?;
+            return find_response(payload, id).ok_or_else(|| "MCP response missing".to_string());
+        }
+
+        let mut stream = resp.bytes_stream();
+        let mut buffer = String::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("MCP stream error: {}", e))?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+            while let Some(end) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..end + 2).collect();
+                let Some(data) = sse_data(&event) else {
+                    continue;
+                };
+                if let Ok(payload) = serde_json::from_str::<Value>(&data) {
+                    if let Some(found) = find_response(payload, id) {
+                        return Ok(found);
+                    }
+                }
+            }
+        }
+        Err("MCP stream ended without a response".into())
+    }
+}
+
+/// Picks the response with `id` out of a single message or a batch.
+fn find_response(payload: Value, id: u64) -> Option<Value> {
+    match payload {
+        Value::Array(items) => items
+            .into_iter()
+            .find(|m| is_response(m) && m["id"].as_u64() == Some(id)),
+        msg if is_response(&msg) && msg["id"].as_u64() == Some(id) => Some(msg),
+        _ => None,
+    }
+}
+
+/// Joins the `data:` lines of one SSE event.
+fn sse_data(event: &str) -> Option<String> {
+    let lines: Vec<&str> = event
+        .lines()
+        .filter_map(|l| l.strip_prefix("data:"))
+        .map(|l| l.strip_prefix(' ').unwrap_or(l))
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+#[async_trait]
+impl McpTransport for HttpTransport {
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let body = request_message(id, method, params);
+        match tokio::time::timeout(timeout, self.exchange(id, &body)).await {
+            Ok(response) => into_result(response?),
+            Err(_) => {
+                let _ = self
+                    .notify(
+                        "notifications/cancelled",
+                        json!({ "requestId": id, "reason": "timeout" }),
+                    )
+                    .await;
+                Err(format!(
+                    "MCP request '{}' timed out after {}ms",
+                    method,
+                    timeout.as_millis()
+                ))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.post(&notification_message(method, params))
+            .await
+            .map(|_| ())
+    }
+
+    async fn close(&self) {
+        let Some(sid) = self.session_id() else {
+            return;
+        };
+        let mut req = self.client.delete(&self.url).header("Mcp-Session-Id", sid);
+        for (k, v) in &self.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        let _ = req.timeout(Duration::from_secs(5)).send().await;
+    }
+}
+
+// ── Connection ───────────────────────────────────────────────────────────────
+
+/// An initialised session with one MCP server and its imported catalogue.
+pub struct McpConnection {
+    transport: Box<dyn McpTransport>,
+    timeout: Duration,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub tools: Vec<McpRemoteTool>,
+    pub resources: Vec<McpRemoteResource>,
+    pub prompts: Vec<McpRemotePrompt>,
+}
+
+impl McpConnection {
+    /// Opens the configured transport and performs the MCP handshake.
+    pub async fn open(definition: &McpServerDefinition) -> Result<Self, String> {
+        let transport: Box<dyn McpTransport> = match &definition.transport {
+            McpTransportConfig::Stdio {
+                command,
+                args,
+                env,
+                cwd,
+            } => Box::new(StdioTransport::spawn(command, args, env, cwd.as_deref())?),
+            McpTransportConfig::StreamableHttp { url, headers } => {
+                Box::new(HttpTransport::new(url, headers)?)
+            }
+        };
+        Self::initialize(definition, transport).await
+    }
+
+    /// Runs `initialize` over an open transport and imports the server's
+    /// tools, resources and prompts.
+    pub async fn initialize(
+        definition: &McpServerDefinition,
+        transport: Box<dyn McpTransport>,
+    ) -> Result<Self, String> {
+        let mut conn = Self {
+            transport,
+            timeout: Duration::from_secs(definition.timeout_secs.max(1)),
+            server_name: None,
+            server_version: None,
+            protocol_version: None,
+            tools: Vec::new(),
+            resources: Vec::new(),
+            prompts: Vec::new(),
+        };
+        if let Err(e) = conn.handshake(definition).await {
+            conn.close().await;
+            return Err(e);
+        }
+        Ok(conn)
+    }
+
+    async fn handshake(&mut self, definition: &McpServerDefinition) -> Result<(), String> {
+        let init = self
+            .transport
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "sortofremote-ng-agent",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+                self.timeout,
+            )
+            .await?;
+        self.transport
+            .notify("notifications/initialized", Value::Null)
+            .await?;
+
+        self.server_name = init["serverInfo"]["name"].as_str().map(String::from);
+        self.server_version = init["serverInfo"]["version"].as_str().map(String::from);
+        self.protocol_version = init["protocolVersion"].as_str().map(String::from);
+        let capabilities = &init["capabilities"];
+
+        if capabilities.get("tools").is_some() {
+            self.tools = self
+                .list_all("tools/list", "tools")
+                .await?
+                .into_iter()
+                .filter_map(|t| remote_tool(&definition.id, t))
+                .collect();
+        }
+        if capabilities.get("resources").is_some() {
+            self.resources = self
+                .list_all("resources/list", "resources")
+                .await?
+                .into_iter()
+                .filter_map(|r| {
+                    Some(McpRemoteResource {
+                        server_id: definition.id.clone(),
+                        uri: r["uri"].as_str()?.to_string(),
+                        name: r["name"].as_str().unwrap_or_default().to_string(),
+                        description: r["description"].as_str().map(String::from),
+                        mime_type: r["mimeType"].as_str().map(String::from),
+                    })
+                })
+                .collect();
+        }
+        if capabilities.get("prompts").is_some() {
+            self.prompts = self
+                .list_all("prompts/list", "prompts")
+                .await?
+                .into_iter()
+                .filter_map(|p| {
+                    Some(McpRemotePrompt {
+                        server_id: definition.id.clone(),
+                        name: p["name"].as_str()?.to_string(),
+                        description: p["description"].as_str().map(String::from),
+                        arguments: serde_json::from_value(p["arguments"].clone())
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Follows `nextCursor` pagination for a list method.
+    async fn list_all(&self, method: &str, key: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => Value::Null,
+            };
+            let mut page = self.transport.request(method, params, self.timeout).await?;
+            if let Some(Value::Array(batch)) = page.get_mut(key).map(Value::take) {
+                items.extend(batch);
+            }
+            cursor = page["nextCursor"].as_str().map(String::from);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Invokes a remote tool and flattens its content to text.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<String, String> {
+        let result = self
+            .transport
+            .request(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+                self.timeout,
+            )
+            .await?;
+        let text = flatten_content(&result);
+        if result["isError"].as_bool().unwrap_or(false) {
+            Err(text)
+        } else {
+            Ok(text)
+        }
+    }
+
+    /// Reads a resource and returns its `contents` array.
+    pub async fn read_resource(&self, uri: &str) -> Result<Value, String> {
+        let mut result = self
+            .transport
+            .request("resources/read", json!({ "uri": uri }), self.timeout)
+            .await?;
+        Ok(result
+            .get_mut("contents")
+            .map(Value::take)
+            .unwrap_or(Value::Array(Vec::new())))
+    }
+
+    /// Renders a prompt with the given arguments.
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<Value, String> {
+        self.transport
+            .request(
+                "prompts/get",
+                json!({ "name": name, "arguments": arguments }),
+                self.timeout,
+            )
+            .await
+    }
+
+    pub async fn close(&self) {
+        self.transport.close().await;
+    }
+}
+
+fn remote_tool(server_id: &str, tool: Value) -> Option<McpRemoteTool> {
+    let name = tool["name"].as_str()?.to_string();
+    Some(McpRemoteTool {
+        server_id: server_id.to_string(),
+        exposed_name: exposed_tool_name(server_id, &name),
+        description: tool["description"].as_str().map(String::from),
+        input_schema: match &tool["inputSchema"] {
+            Value::Null => json!({ "type": "object", "properties": {} }),
+            schema => schema.clone(),
+        },
+        read_only: tool["annotations"]["readOnlyHint"]
+            .as_bool()
+            .unwrap_or(false),
+        name,
+    })
+}
+
+/// `{server}__{tool}`, restricted to the characters LLM providers accept in
+/// function names.
+pub fn exposed_tool_name(server_id: &str, tool: &str) -> String {
+    let sanitize = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let mut name = format!("{}__{}", sanitize(server_id), sanitize(tool));
+    name.truncate(MAX_TOOL_NAME_LEN);
+    name
+}
+
+/// Joins the text parts of a `tools/call` result.
+fn flatten_content(result: &Value) -> String {
+    let parts: Vec<String> = result["content"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| match item["type"].as_str() {
+                    Some("text") => item["text"].as_str().unwrap_or_default().to_string(),
+                    Some("resource") => match item["resource"]["text"].as_str() {
+                        Some(text) => text.to_string(),
+                        None => format!(
+                            "[resource: {}]",
+                            item["resource"]["uri"].as_str().unwrap_or("?")
+                        ),
+                    },
+                    Some(kind) => format!(
+                        "[{}: {}]",
+                        kind,
+                        item["mimeType"].as_str().unwrap_or("binary")
+                    ),
+                    None => item.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if parts.is_empty() {
+        if let Some(structured) = result.get("structuredContent") {
+            return structured.to_string();
+        }
+    }
+    parts.join("\n")
+}
+
+// ── Client Manager ───────────────────────────────────────────────────────────
+
+struct ManagedServer {
+    definition: McpServerDefinition,
+    connection: Option<Arc<McpConnection>>,
+    connected_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl ManagedServer {
+    fn status(&self) -> McpServerStatus {
+        let conn = self.connection.as_deref();
+        McpServerStatus {
+            id: self.definition.id.clone(),
+            name: self.definition.name.clone(),
+            enabled: self.definition.enabled,
+            connected: conn.is_some(),
+            server_name: conn.and_then(|c| c.server_name.clone()),
+            server_version: conn.and_then(|c| c.server_version.clone()),
+            protocol_version: conn.and_then(|c| c.protocol_version.clone()),
+            tools: conn.map_or(0, |c| c.tools.len()),
+            resources: conn.map_or(0, |c| c.resources.len()),
+            prompts: conn.map_or(0, |c| c.prompts.len()),
+            connected_at: self.connected_at,
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    async fn disconnect(&mut self) {
+        if let Some(conn) = self.connection.take() {
+            conn.close().await;
+        }
+        self.connected_at = None;
+    }
+}
+
+/// Configured MCP servers and their live connections.
+#[derive(Default)]
+pub struct McpClientManager {
+    servers: HashMap<String, ManagedServer>,
+}
+
+impl McpClientManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a server definition. A replaced server is
+    /// disconnected.
+    pub async fn add_server(
+        &mut self,
+        definition: McpServerDefinition,
+    ) -> Result<McpServerStatus, String> {
+        if definition.id.trim().is_empty() {
+            return Err("MCP server id must not be empty".into());
+        }
+        if let Some(mut old) = self.servers.remove(&definition.id) {
+            old.disconnect().await;
+        }
+        let server = ManagedServer {
+            definition,
+            connection: None,
+            connected_at: None,
+            last_error: None,
+        };
+        let status = server.status();
+        self.servers.insert(status.id.clone(), server);
+        Ok(status)
+    }
+
+    pub async fn remove_server(&mut self, id: &str) -> bool {
+        match self.servers.remove(id) {
+            Some(mut server) => {
+                server.disconnect().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Enables or disables a server. Disabling drops its connection.
+    pub async fn set_enabled(
+        &mut self,
+        id: &str,
+        enabled: bool,
+    ) -> Result<McpServerStatus, String> {
+        let server = self.server_mut(id)?;
+        server.definition.enabled = enabled;
+        if !enabled {
+            server.disconnect().await;
+        }
+        Ok(server.status())
+    }
+
+    /// (Re)connects a server and imports its catalogue.
+    pub async fn connect(&mut self, id: &str) -> Result<McpServerStatus, String> {
+        let server = self.server_mut(id)?;
+        if !server.definition.enabled {
+            return Err(format!("MCP server '{}' is disabled", id));
+        }
+        server.disconnect().await;
+        match McpConnection::open(&server.definition).await {
+            Ok(conn) => {
+                server.connection = Some(Arc::new(conn));
+                server.connected_at = Some(Utc::now());
+                server.last_error = None;
+                Ok(server.status())
+            }
+            Err(e) => {
+                server.last_error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Attaches an already-open transport, bypassing the configured one.
+    pub async fn connect_with(
+        &mut self,
+        id: &str,
+        transport: Box<dyn McpTransport>,
+    ) -> Result<McpServerStatus, String> {
+        let server = self.server_mut(id)?;
+        server.disconnect().await;
+        let conn = McpConnection::initialize(&server.definition, transport).await?;
+        server.connection = Some(Arc::new(conn));
+        server.connected_at = Some(Utc::now());
+        server.last_error = None;
+        Ok(server.status())
+    }
+
+    pub async fn disconnect(&mut self, id: &str) -> Result<McpServerStatus, String> {
+        let server = self.server_mut(id)?;
+        server.disconnect().await;
+        Ok(server.status())
+    }
+
+    /// Connects every enabled server that is not connected yet. Failures are
+    /// recorded on the server status and logged.
+    pub async fn connect_enabled(&mut self) {
+        let ids: Vec<String> = self
+            .servers
+            .values()
+            .filter(|s| s.definition.enabled && s.connection.is_none())
+            .map(|s| s.definition.id.clone())
+            .collect();
+        for id in ids {
+            if let Err(e) = self.connect(&id).await {
+                log::warn!("MCP server '{}' unavailable: {}", id, e);
+            }
+        }
+    }
+
+    pub fn list_servers(&self) -> Vec<McpServerStatus> {
+        let mut list: Vec<_> = self.servers.values().map(ManagedServer::status).collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        list
+    }
+
+    pub fn list_tools(&self, server_id: Option<&str>) -> Vec<McpRemoteTool> {
+        self.connections(server_id)
+            .flat_map(|(_, c)| c.tools.iter().cloned())
+            .collect()
+    }
+
+    pub fn list_resources(&self, server_id: Option<&str>) -> Vec<McpRemoteResource> {
+        self.connections(server_id)
+            .flat_map(|(_, c)| c.resources.iter().cloned())
+            .collect()
+    }
+
+    pub fn list_prompts(&self, server_id: Option<&str>) -> Vec<McpRemotePrompt> {
+        self.connections(server_id)
+            .flat_map(|(_, c)| c.prompts.iter().cloned())
+            .collect()
+    }
+
+    pub async fn read_resource(&self, server_id: &str, uri: &str) -> Result<Value, String> {
+        self.connection(server_id)?.read_resource(uri).await
+    }
+
+    pub async fn get_prompt(
+        &self,
+        server_id: &str,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<Value, String> {
+        self.connection(server_id)?
+            .get_prompt(name, arguments)
+            .await
+    }
+
+    /// Registers the tools of every enabled, connected server.
+    ///
+    /// Remote tools require confirmation unless they are listed in
+    /// `auto_approve_tools`, or the server marks them read-only and is
+    /// configured with `trust_annotations`. Calls are bounded by the
+    /// server's `timeout_secs`.
+    pub fn register_tools(&self, registry: &mut ToolRegistry) {
+        for (definition, conn) in self.connections(None) {
+            for tool in &conn.tools {
+                let requires_confirmation = !((definition.trust_annotations && tool.read_only)
+                    || definition.auto_approve_tools.contains(&tool.name));
+                let description = match &tool.description {
+                    Some(d) => format!("[{}] {}", definition.name, d),
+                    None => format!("[{}] {}", definition.name, tool.name),
+                };
+                let conn = conn.clone();
+                let remote_name = tool.name.clone();
+                registry.register_async(
+                    ToolDefinition {
+                        name: tool.exposed_name.clone(),
+                        description,
+                        parameters: tool.input_schema.clone(),
+                        category: Some(format!("mcp:{}", definition.id)),
+                        requires_confirmation,
+                        estimated_output_tokens: None,
+                        timeout_secs: definition.timeout_secs,
+                    },
+                    Box::new(move |args| {
+                        let conn = conn.clone();
+                        let remote_name = remote_name.clone();
+                        Box::pin(async move {
+                            let arguments = if args.trim().is_empty() {
+                                json!({})
+                            } else {
+                                serde_json::from_str(&args)
+                                    .map_err(|e| format!("Invalid tool arguments: {}", e))?
+                            };
+                            conn.call_tool(&remote_name, arguments).await
+                        })
+                    }),
+                );
+            }
+        }
+    }
+
+    pub async fn shutdown(&mut self) {
+        for server in self.servers.values_mut() {
+            server.disconnect().await;
+        }
+    }
+
+    fn server_mut(&mut self, id: &str) -> Result<&mut ManagedServer, String> {
+        self.servers
+            .get_mut(id)
+            .ok_or_else(|| format!("MCP server '{}' not found", id))
+    }
+
+    fn connection(&self, id: &str) -> Result<Arc<McpConnection>, String> {
+        let server = self
+            .servers
+            .get(id)
+            .ok_or_else(|| format!("MCP server '{}' not found", id))?;
+        server
+            .connection
+            .clone()
+            .ok_or_else(|| format!("MCP server '{}' is not connected", id))
+    }
+
+    fn connections<'a>(
+        &'a self,
+        server_id: Option<&'a str>,
+    ) -> impl Iterator<Item = (&'a McpServerDefinition, &'a Arc<McpConnection>)> + 'a {
+        self.servers
+            .values()
+            .filter(move |s| server_id.is_none_or(|id| s.definition.id == id))
+            .filter(|s| s.definition.enabled)
+            .filter_map(|s| s.connection.as_ref().map(|c| (&s.definition, c)))
+    }
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// Tests
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, DuplexStream};
+    use tokio::net::TcpListener;
+
+    /// Canned replies of a small MCP server with one read-only tool, one
+    /// mutating tool (split across two `tools/list` pages), a resource and a
+    /// prompt. `hang` never answers.
+    fn mock_reply(msg: &Value) -> Option<Value> {
+        let id = msg.get("id")?.clone();
+        let result = match msg["method"].as_str()? {
+            "initialize" => json!({
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": { "tools": {}, "resources": {}, "prompts": {} },
+                "serverInfo": { "name": "mock-cmdb", "version": "0.1.0" }
+            }),
+            "tools/list" if msg["params"]["cursor"].is_null() => json!({
+                "tools": [{
+                    "name": "lookup_host",
+                    "description": "Find a host in the CMDB",
+                    "inputSchema": { "type": "object", "properties": { "host": { "type": "string" } } },
+                    "annotations": { "readOnlyHint": true }
+                }],
+                "nextCursor": "page-2"
+            }),
+            "tools/list" => json!({
+                "tools": [
+                    { "name": "open.ticket", "inputSchema": { "type": "object" } },
+                    { "name": "hang", "inputSchema": { "type": "object" } }
+                ]
+            }),
+            "tools/call" => match msg["params"]["name"].as_str()? {
+                "lookup_host" => json!({
+                    "content": [{
+                        "type": "text",
+                        "text": format!("{} is in rack 7", msg["params"]["arguments"]["host"].as_str().unwrap_or("?"))
+                    }]
+                }),
+                "open.ticket" => json!({ "content": [{ "type": "text", "text": "TICKET-1" }] }),
+                "hang" => return None,
+                _ => {
+                    json!({ "content": [{ "type": "text", "text": "no such tool" }], "isError": true })
+                }
+            },
+            "resources/list" => json!({
+                "resources": [{ "uri": "runbook://restart", "name": "Restart runbook", "mimeType": "text/markdown" }]
+            }),
+            "resources/read" => json!({
+                "contents": [{ "uri": msg["params"]["uri"], "text": "1. drain\n2. restart" }]
+            }),
+            "prompts/list" => json!({
+                "prompts": [{ "name": "triage", "arguments": [{ "name": "host", "required": true }] }]
+            }),
+            "prompts/get" => json!({
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": format!("Triage {}", msg["params"]["arguments"]["host"].as_str().unwrap_or("?")) }
+                }]
+            }),
+            _ => {
+                return Some(json!({
+                    "jsonrpc": "2.0", "id": id,
+                    "error": { "code": -32601, "message": "Method not found" }
+                }))
+            }
+        };
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    /// Serves `mock_reply` over an in-memory stdio pipe.
+    fn stdio_mock() -> StdioTransport {
+        let (client_out, server_in) = tokio::io::duplex(64 * 1024);
+        let (server_out, client_in) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let mut server_out: DuplexStream = server_out;
+            let mut lines = BufReader::new(server_in).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let msg: Value = serde_json::from_str(&line).unwrap();
+                if let Some(reply) = mock_reply(&msg) {
+                    let out = format!("{}\n", reply);
+                    server_out.write_all(out.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        StdioTransport::from_streams(client_in, client_out, None)
+    }
+
+    /// Serves `mock_reply` as a Streamable HTTP endpoint. Requests answer
+    /// with JSON bodies and `tools/call` with an SSE stream.
+    async fn http_mock() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut sock, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut raw = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    let (head_len, content_length) = loop {
+                        let n = sock.read(&mut buf).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        raw.extend_from_slice(&buf[..n]);
+                        if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                            let head = String::from_utf8_lossy(&raw[..pos]).to_lowercase();
+                            let len = head
+                                .lines()
+                                .find_map(|l| l.strip_prefix("content-length:"))
+                                .map_or(0, |v| v.trim().parse().unwrap());
+                            break (pos + 4, len);
+                        }
+                    };
+                    while raw.len() < head_len + content_length {
+                        let n = sock.read(&mut buf).await.unwrap();
+                        raw.extend_from_slice(&buf[..n]);
+                    }
+                    let head = String::from_utf8_lossy(&raw[..head_len]).to_lowercase();
+                    let body = &raw[head_len..head_len + content_length];
+
+                    let reply = if head.starts_with("delete") {
+                        None
+                    } else {
+                        let msg: Value = serde_json::from_slice(body).unwrap();
+                        assert!(
+                            msg["method"] == "initialize" || head.contains("mcp-session-id: s-1"),
+                            "session header missing"
+                        );
+                        mock_reply(&msg).map(|r| (msg["method"] == "tools/call", r))
+                    };
+                    let response = match reply {
+                        None => "HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                        Some((true, r)) => {
+                            let events = format!(
+                                "event: message\r\ndata: {}\r\n\r\ndata: {}\r\n\r\n",
+                                json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": { "progress": 1 } }),
+                                r
+                            );
+                            format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nMcp-Session-Id: s-1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                events.len(),
+                                events
+                            )
+                        }
+                        Some((false, r)) => {
+                            let body = r.to_string();
+                            format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nMcp-Session-Id: s-1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                body.len(),
+                                body
+                            )
+                        }
+                    };
+                    let _ = sock.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}/mcp", addr)
+    }
+
+    fn definition(id: &str, transport: McpTransportConfig) -> McpServerDefinition {
+        McpServerDefinition {
+            id: id.into(),
+            name: "CMDB".into(),
+            transport,
+            enabled: true,
+            timeout_secs: 1,
+            auto_approve_tools: vec!["open.ticket".into()],
+            trust_annotations: false,
+        }
+    }
+
+    fn stdio_definition(id: &str) -> McpServerDefinition {
+        definition(
+            id,
+            McpTransportConfig::Stdio {
+                command: "mock".into(),
+                args: Vec::new(),
+                env: HashMap::new(),
+                cwd: None,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn imports_catalogue_over_stdio() {
+        let conn = McpConnection::initialize(&stdio_definition("cmdb"), Box::new(stdio_mock()))
+            .await
+            .unwrap();
+        assert_eq!(conn.server_name.as_deref(), Some("mock-cmdb"));
+        let names: Vec<_> = conn.tools.iter().map(|t| t.exposed_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["cmdb__lookup_host", "cmdb__open_ticket", "cmdb__hang"]
+        );
+        assert!(conn.tools[0].read_only);
+        assert_eq!(conn.resources[0].uri, "runbook://restart");
+        assert!(conn.prompts[0].arguments[0].required);
+
+        let text = conn
+            .call_tool("lookup_host", json!({ "host": "db01" }))
+            .await
+            .unwrap();
+        assert_eq!(text, "db01 is in rack 7");
+        assert_eq!(
+            conn.call_tool("missing", json!({})).await.unwrap_err(),
+            "no such tool"
+        );
+        let contents = conn.read_resource("runbook://restart").await.unwrap();
+        assert_eq!(contents[0]["text"], "1. drain\n2. restart");
+    }
+
+    #[tokio::test]
+    async fn stdio_request_times_out() {
+        let transport = stdio_mock();
+        transport
+            .request("initialize", json!({}), Duration::from_secs(1))
+            .await
+            .unwrap();
+        let err = transport
+            .request(
+                "tools/call",
+                json!({ "name": "hang" }),
+                Duration::from_millis(100),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(transport.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn imports_catalogue_over_streamable_http() {
+        let url = http_mock().await;
+        let mut manager = McpClientManager::new();
+        manager
+            .add_server(McpServerDefinition {
+                trust_annotations: true,
+                ..definition(
+                    "cmdb",
+                    McpTransportConfig::StreamableHttp {
+                        url,
+                        headers: HashMap::new(),
+                    },
+                )
+            })
+            .await
+            .unwrap();
+        let status = manager.connect("cmdb").await.unwrap();
+        assert!(status.connected);
+        assert_eq!(status.tools, 3);
+        assert_eq!(status.resources, 1);
+        assert_eq!(status.prompts, 1);
+
+        let prompt = manager
+            .get_prompt(
+                "cmdb",
+                "triage",
+                &HashMap::from([("host".to_string(), "web02".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(prompt["messages"][0]["content"]["text"], "Triage web02");
+
+        // tools/call is answered over SSE after an unrelated notification.
+        let mut registry = ToolRegistry::new();
+        manager.register_tools(&mut registry);
+        let result = registry
+            .execute("cmdb__lookup_host", r#"{"host":"db01"}"#)
+            .await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.content, "db01 is in rack 7");
+
+        manager.disconnect("cmdb").await.unwrap();
+        assert!(manager.list_tools(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn registered_tools_follow_approval_rules() {
+        let mut manager = McpClientManager::new();
+        manager
+            .add_server(McpServerDefinition {
+                trust_annotations: true,
+                ..stdio_definition("cmdb")
+            })
+            .await
+            .unwrap();
+        manager.add_server(stdio_definition("other")).await.unwrap();
+        for id in ["cmdb", "other"] {
+            manager
+                .connect_with(id, Box::new(stdio_mock()))
+                .await
+                .unwrap();
+        }
+
+        let mut registry = ToolRegistry::new();
+        manager.register_tools(&mut registry);
+        assert_eq!(registry.count(), 6);
+        let defs = |name: &str| registry.get_definition(name).unwrap().clone();
+        assert!(!defs("cmdb__lookup_host").requires_confirmation);
+        // readOnlyHint is ignored unless the server is trusted.
+        assert!(defs("other__lookup_host").requires_confirmation);
+        assert!(!defs("other__open_ticket").requires_confirmation);
+        assert!(!defs("cmdb__open_ticket").requires_confirmation);
+        assert!(defs("cmdb__hang").requires_confirmation);
+        assert_eq!(defs("cmdb__hang").timeout_secs, 1);
+
+        let refused = registry.execute("cmdb__hang", "{}").await;
+        assert!(!refused.success);
+        assert!(refused
+            .error
+            .unwrap()
+            .contains("requires user confirmation"));
+
+        registry.set_approved_tools(&["cmdb__hang".to_string()]);
+        let timed_out = registry.execute("cmdb__hang", "{}").await;
+        assert!(timed_out.error.unwrap().contains("timed out"));
+
+        let ticket = registry.execute("cmdb__open_ticket", "").await;
+        assert_eq!(ticket.content, "TICKET-1");
+
+        manager.set_enabled("cmdb", false).await.unwrap();
+        let mut registry = ToolRegistry::new();
+        manager.register_tools(&mut registry);
+        assert_eq!(registry.count(), 3);
+        assert!(registry.get_definition("cmdb__lookup_host").is_none());
+        assert!(manager.connect("cmdb").await.is_err());
+    }
+
+    #[test]
+    fn exposed_names_are_sanitized_and_bounded() {
+        assert_eq!(
+            exposed_tool_name("my cmdb", "get.host"),
+            "my_cmdb__get_host"
+        );
+        assert_eq!(
+            exposed_tool_name("s", &"x".repeat(100)).len(),
+            MAX_TOOL_NAME_LEN
+        );
+    }
+}
@@ -7,7 +7,10 @@ pub mod code_assist;
 pub mod conversation;
 pub mod embeddings;
 pub mod engine;
+pub mod hnsw;
+pub mod mcp_client;
 pub mod memory;
+pub mod persistence;
 pub mod providers;
 pub mod rag;
 pub mod service;
@@ -0,0 +1,431 @@
+// ── Snapshot Persistence ──────────────────────────────────────────────────────
+//
+// One file per named snapshot (a vector collection, a RAG collection) in a
+// directory, written with `sorng-storage`'s durable atomic write so a crash
+// leaves either the old or the new snapshot, never a torn one.
+//
+// Encryption follows the recordings policy: while the encryption state is
+// unlocked snapshots are written as `<stem>.json.enc` under the
+// `AiVectors` sub-key and any plaintext shadow is removed; while locked they
+// are written as plaintext `<stem>.json`, unless an encrypted copy already
+// exists, in which case the write is refused so encrypted data is never
+// silently downgraded. Loads prefer the encrypted variant and skip it while
+// locked; stores opened that way fold the skipped snapshots in with
+// `load_encrypted` before their first save after unlock, so a flush never
+// replaces a snapshot it has not read.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sorng_encryption::artifacts::ai_vectors;
+use sorng_encryption::envelope::{MasterKeyStorage, SALT_LEN};
+use sorng_encryption::password_wrap::Argon2Params;
+use sorng_encryption::EncryptionState;
+use sorng_storage::durable::durable_write;
+
+const PLAIN_SUFFIX: &str = ".json";
+const ENC_SUFFIX: &str = ".json.enc";
+
+/// AI storage root, relative to the app data directory.
+pub const STORAGE_DIR: &str = "ai";
+/// Snapshot directories under the AI storage root.
+pub const VECTORS_DIR: &str = "vectors";
+pub const RAG_DIR: &str = "rag";
+
+/// A directory of JSON snapshots with optional encryption at rest.
+#[derive(Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+    encryption: Option<Arc<EncryptionState>>,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: impl Into<PathBuf>, encryption: Option<Arc<EncryptionState>>) -> Self {
+        Self {
+            dir: dir.into(),
+            encryption,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File stem for a snapshot name: a readable prefix plus a hash so
+    /// arbitrary collection names map to distinct, filesystem-safe files.
+    fn stem(name: &str) -> String {
+        let readable: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .take(32)
+            .collect();
+        let hash = hex::encode(Sha256::digest(name.as_bytes()));
+        format!("{}-{}", readable, &hash[..16])
+    }
+
+    fn plain_path(&self, name: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}{}", Self::stem(name), PLAIN_SUFFIX))
+    }
+
+    fn enc_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", Self::stem(name), ENC_SUFFIX))
+    }
+
+    async fn unlocked(&self) -> Option<&EncryptionState> {
+        match &self.encryption {
+            Some(enc) if enc.is_unlocked().await => Some(enc.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Durably writes the snapshot `name`.
+    pub async fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), String> {
+        let bytes = serde_json::to_vec(value).map_err(|e| format!("Serialize {}: {}", name, e))?;
+        let enc_path = self.enc_path(name);
+        let plain_path = self.plain_path(name);
+
+        if let Some(enc) = self.unlocked().await {
+            let blob = ai_vectors::write(
+                enc,
+                &bytes,
+                MasterKeyStorage::Vault,
+                Argon2Params::OWASP,
+                [0u8; SALT_LEN],
+            )
+            .await
+            .map_err(|e| format!("Encrypt {}: {}", name, e))?;
+            durable_write(&enc_path, &blob)?;
+            return remove_if_exists(&plain_path);
+        }
+
+        if enc_path.exists() {
+            return Err(format!(
+                "'{}' is stored encrypted; unlock encryption before saving changes",
+                name
+            ));
+        }
+        durable_write(&plain_path, &bytes)
+    }
+
+    /// Removes both variants of the snapshot `name`.
+    pub fn remove(&self, name: &str) -> Result<(), String> {
+        remove_if_exists(&self.enc_path(name))?;
+        remove_if_exists(&self.plain_path(name))
+    }
+
+    /// Whether encryption is locked and the directory holds encrypted
+    /// snapshots that [`load_all`](Self::load_all) therefore skips.
+    pub async fn has_locked_snapshots(&self) -> bool {
+        if self.encryption.is_none() || self.unlocked().await.is_some() {
+            return false;
+        }
+        std::fs::read_dir(&self.dir).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|n| !n.starts_with('.') && n.ends_with(ENC_SUFFIX))
+            })
+        })
+    }
+
+    /// Loads every readable snapshot in the directory. Encrypted snapshots
+    /// are skipped while locked; unreadable files are logged and skipped so
+    /// one bad collection does not hide the rest.
+    pub async fn load_all<T: DeserializeOwned>(&self) -> Result<Vec<T>, String> {
+        self.load(true).await
+    }
+
+    /// Loads only the encrypted snapshots; none while locked.
+    pub async fn load_encrypted<T: DeserializeOwned>(&self) -> Result<Vec<T>, String> {
+        self.load(false).await
+    }
+
+    async fn load<T: DeserializeOwned>(&self, with_plain: bool) -> Result<Vec<T>, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Read {}: {}", self.dir.display(), e)),
+        };
+        let unlocked = self.unlocked().await;
+
+        let mut encrypted = Vec::new();
+        let mut plain = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            if let Some(stem) = name.strip_suffix(ENC_SUFFIX) {
+                encrypted.push((stem.to_string(), path));
+            } else if !with_plain {
+                continue;
+            } else if let Some(stem) = name.strip_suffix(PLAIN_SUFFIX) {
+                plain.push((stem.to_string(), path));
+            }
+        }
+
+        let mut loaded = Vec::new();
+        for (stem, path) in &encrypted {
+            let Some(enc) = unlocked else {
+                log::warn!("Skipping encrypted snapshot {} while locked", stem);
+                continue;
+            };
+            let bytes = match std::fs::read(path) {
+                Ok(b) => b,
+                Err(e) => {
+                    log::warn!("Cannot read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            match ai_vectors::read(enc, &bytes).await {
+                Ok(plaintext) => push_parsed(&mut loaded, path, &plaintext),
+                Err(e) => log::warn!("Cannot decrypt {}: {}", path.display(), e),
+            }
+        }
+        for (stem, path) in &plain {
+            if encrypted.iter().any(|(s, _)| s == stem) {
+                continue;
+            }
+            match std::fs::read(path) {
+                Ok(bytes) => push_parsed(&mut loaded, path, &bytes),
+                Err(e) => log::warn!("Cannot read {}: {}", path.display(), e),
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+/// Every encrypted snapshot under the AI storage root. Key rotation
+/// re-encrypts each with [`rewrite_snapshot_with`].
+pub fn list_encrypted_snapshot_paths(root: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for dir in [VECTORS_DIR, RAG_DIR] {
+        let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if !name.starts_with('.') && name.ends_with(ENC_SUFFIX) {
+                    out.push(path);
+                }
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// Re-encrypt one snapshot under a new master DEK, in place. Returns the
+/// number of bytes written.
+pub async fn rewrite_snapshot_with(
+    path: &Path,
+    from: &EncryptionState,
+    to: &EncryptionState,
+) -> Result<u64, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+    let plaintext = ai_vectors::read(from, &bytes)
+        .await
+        .map_err(|e| format!("decrypt: {}", e))?;
+    let blob = ai_vectors::write(
+        to,
+        &plaintext,
+        MasterKeyStorage::Vault,
+        Argon2Params::OWASP,
+        [0u8; SALT_LEN],
+    )
+    .await
+    .map_err(|e| format!("encrypt: {}", e))?;
+    durable_write(path, &blob)?;
+    Ok(blob.len() as u64)
+}
+
+fn push_parsed<T: DeserializeOwned>(out: &mut Vec<T>, path: &Path, bytes: &[u8]) {
+    match serde_json::from_slice(bytes) {
+        Ok(value) => out.push(value),
+        Err(e) => log::warn!("Cannot parse {}: {}", path.display(), e),
+    }
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Remove {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_agent::embeddings::{VectorEntry, VectorStore};
+    use crate::ai_agent::rag::RagStore;
+    use crate::ai_agent::types::IngestDocumentRequest;
+    use sorng_encryption::dek::MasterDek;
+    use std::collections::HashMap;
+
+    fn document(id: &str) -> IngestDocumentRequest {
+        IngestDocumentRequest {
+            collection: "runbooks".into(),
+            document_id: id.into(),
+            content: format!("restart procedure {id}"),
+            title: None,
+            source: None,
+            metadata: HashMap::new(),
+            chunking: None,
+            embedding_provider_id: None,
+            embedding_model: None,
+        }
+    }
+
+    fn entry(id: &str, embedding: Vec<f32>) -> VectorEntry {
+        VectorEntry {
+            id: id.into(),
+            collection: "runbooks".into(),
+            text: format!("runbook {id}"),
+            embedding,
+            metadata: HashMap::from([("host".into(), serde_json::json!(id))]),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vector_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = SnapshotStore::new(dir.path(), None);
+
+        let mut store = VectorStore::open(storage.clone()).await.unwrap();
+        store.upsert(entry("a", vec![1.0, 0.0, 0.0])).unwrap();
+        store.upsert(entry("b", vec![0.0, 1.0, 0.0])).unwrap();
+        store.upsert(entry("c", vec![0.9, 0.1, 0.0])).unwrap();
+        assert!(store.remove("runbooks", "c"));
+        store.flush().await.unwrap();
+
+        let reopened = VectorStore::open(storage).await.unwrap();
+        assert_eq!(reopened.total_entries(), 2);
+        let hits = reopened.search("runbooks", &[1.0, 0.1, 0.0], 1, None);
+        assert_eq!(hits[0].id, "a");
+        let filter = HashMap::from([("host".into(), serde_json::json!("b"))]);
+        let hits = reopened.search_filtered("runbooks", &[1.0, 0.1, 0.0], 5, None, Some(&filter));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "b");
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_snapshots_are_not_downgraded() {
+        let dir = tempfile::tempdir().unwrap();
+        let enc = Arc::new(EncryptionState::new());
+        enc.install(MasterDek::generate()).await;
+        let storage = SnapshotStore::new(dir.path(), Some(enc.clone()));
+
+        let mut store = VectorStore::open(storage.clone()).await.unwrap();
+        store.upsert(entry("a", vec![1.0, 0.0])).unwrap();
+        store.flush().await.unwrap();
+
+        let files: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with(ENC_SUFFIX));
+        let raw = std::fs::read(dir.path().join(&files[0])).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("runbook a"));
+
+        assert_eq!(
+            VectorStore::open(storage.clone())
+                .await
+                .unwrap()
+                .total_entries(),
+            1
+        );
+
+        // A locked store neither sees nor overwrites the encrypted snapshot.
+        let locked = SnapshotStore::new(dir.path(), Some(Arc::new(EncryptionState::new())));
+        assert!(locked
+            .load_all::<serde_json::Value>()
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(locked
+            .save("runbooks", &serde_json::json!({}))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_skipped_while_locked_survive_unlock() {
+        let dir = tempfile::tempdir().unwrap();
+        let enc = Arc::new(EncryptionState::new());
+        enc.install(MasterDek::generate()).await;
+        let key = enc.master_bytes_raw().await.unwrap();
+        let vectors = SnapshotStore::new(dir.path().join("vectors"), Some(enc.clone()));
+        let rag = SnapshotStore::new(dir.path().join("rag"), Some(enc.clone()));
+
+        let mut store = VectorStore::open(vectors.clone()).await.unwrap();
+        store.upsert(entry("a", vec![1.0, 0.0])).unwrap();
+        store.flush().await.unwrap();
+        let mut docs = RagStore::open(rag.clone()).await.unwrap();
+        docs.ingest(document("old")).unwrap();
+        docs.flush().await.unwrap();
+
+        // Opened at startup, before the user unlocks.
+        enc.lock().await;
+        let mut store = VectorStore::open(vectors.clone()).await.unwrap();
+        let mut docs = RagStore::open(rag.clone()).await.unwrap();
+        assert_eq!(store.total_entries(), 0);
+        assert_eq!(docs.document_count(), 0);
+
+        enc.install(MasterDek::from_bytes(&key).unwrap()).await;
+        store.upsert(entry("b", vec![0.0, 1.0])).unwrap();
+        store.flush().await.unwrap();
+        docs.ingest(document("new")).unwrap();
+        docs.flush().await.unwrap();
+        assert_eq!(store.total_entries(), 2);
+
+        let reopened = VectorStore::open(vectors).await.unwrap();
+        assert_eq!(reopened.total_entries(), 2);
+        let hits = reopened.search("runbooks", &[1.0, 0.1], 1, None);
+        assert_eq!(hits[0].id, "a");
+        assert_eq!(RagStore::open(rag).await.unwrap().document_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_snapshot_moves_to_new_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = Arc::new(EncryptionState::new());
+        old.install(MasterDek::generate()).await;
+        let new = Arc::new(EncryptionState::new());
+        new.install(MasterDek::generate()).await;
+
+        let mut store = VectorStore::open(SnapshotStore::new(
+            dir.path().join(VECTORS_DIR),
+            Some(old.clone()),
+        ))
+        .await
+        .unwrap();
+        store.upsert(entry("a", vec![1.0, 0.0])).unwrap();
+        store.flush().await.unwrap();
+        std::fs::create_dir_all(dir.path().join(RAG_DIR)).unwrap();
+        std::fs::write(dir.path().join(RAG_DIR).join("plain.json"), b"{}").unwrap();
+
+        let paths = list_encrypted_snapshot_paths(dir.path());
+        assert_eq!(paths.len(), 1);
+        assert!(rewrite_snapshot_with(&paths[0], &old, &new).await.unwrap() > 0);
+
+        let bytes = std::fs::read(&paths[0]).unwrap();
+        assert!(ai_vectors::read(&old, &bytes).await.is_err());
+        let reopened =
+            VectorStore::open(SnapshotStore::new(dir.path().join(VECTORS_DIR), Some(new)))
+                .await
+                .unwrap();
+        assert_eq!(reopened.total_entries(), 1);
+    }
+}
@@ -1,13 +1,17 @@
 // ── RAG (Retrieval-Augmented Generation) ──────────────────────────────────────
 
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use super::hnsw::HnswIndex;
+use super::persistence::SnapshotStore;
 use super::types::*;
 
 // ── Document Store ───────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct StoredDocument {
     pub document_id: String,
     pub collection: String,
@@ -17,15 +21,50 @@ pub struct StoredDocument {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DocumentChunk {
     pub chunk_index: usize,
     pub content: String,
     pub embedding: Option<Vec<f32>>,
 }
 
+/// Persisted form of one collection.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectionSnapshotRef<'a> {
+    collection: &'a str,
+    documents: Vec<&'a StoredDocument>,
+    index: Option<&'a HnswIndex>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectionSnapshot {
+    collection: String,
+    documents: Vec<StoredDocument>,
+    index: Option<HnswIndex>,
+}
+
+/// Index key of a chunk within its collection's HNSW graph.
+fn chunk_key(doc_id: &str, chunk_index: usize) -> String {
+    format!("{}#{}", doc_id, chunk_index)
+}
+
+fn parse_chunk_key(key: &str) -> Option<(&str, usize)> {
+    let (doc_id, idx) = key.rsplit_once('#')?;
+    Some((doc_id, idx.parse().ok()?))
+}
+
 pub struct RagStore {
     documents: HashMap<String, StoredDocument>,
+    /// Per-collection HNSW graph over embedded chunks.
+    indexes: HashMap<String, HnswIndex>,
+    storage: Option<SnapshotStore>,
+    dirty: HashSet<String>,
+    /// Encrypted snapshots were skipped at open because encryption was
+    /// locked; they are merged in before the next save.
+    unloaded: bool,
 }
 
 impl Default for RagStore {
@@ -38,7 +77,117 @@ impl RagStore {
     pub fn new() -> Self {
         Self {
             documents: HashMap::new(),
+            indexes: HashMap::new(),
+            storage: None,
+            dirty: HashSet::new(),
+            unloaded: false,
+        }
+    }
+
+    /// Loads persisted collections from `storage` and keeps writing there
+    /// on [`flush`](Self::flush).
+    pub async fn open(storage: SnapshotStore) -> Result<Self, String> {
+        let mut store = Self::new();
+        for snapshot in storage.load_all::<CollectionSnapshot>().await? {
+            store.restore(snapshot);
+        }
+        store.unloaded = storage.has_locked_snapshots().await;
+        store.storage = Some(storage);
+        Ok(store)
+    }
+
+    fn restore(&mut self, snapshot: CollectionSnapshot) {
+        let mut index = snapshot.index.unwrap_or_default();
+        let embeddings: HashMap<String, &[f32]> = snapshot
+            .documents
+            .iter()
+            .flat_map(|d| {
+                d.chunks.iter().filter_map(|c| {
+                    c.embedding
+                        .as_deref()
+                        .map(|e| (chunk_key(&d.document_id, c.chunk_index), e))
+                })
+            })
+            .collect();
+        index.restore_vectors(|key| embeddings.get(key).copied());
+        for (key, embedding) in &embeddings {
+            if !index.contains(key) {
+                let _ = index.insert(key, embedding);
+            }
+        }
+        drop(embeddings);
+        self.indexes.insert(snapshot.collection, index);
+        for doc in snapshot.documents {
+            self.documents.insert(doc.document_id.clone(), doc);
+        }
+    }
+
+    /// Merges in the encrypted collections `open` skipped, once encryption
+    /// is unlocked. Documents written since then win over persisted ones.
+    async fn load_unloaded(&mut self) -> Result<(), String> {
+        let Some(storage) = self.storage.as_ref().filter(|_| self.unloaded) else {
+            return Ok(());
+        };
+        if storage.has_locked_snapshots().await {
+            return Ok(());
+        }
+        for snapshot in storage.load_encrypted::<CollectionSnapshot>().await? {
+            let known = self
+                .documents
+                .values()
+                .any(|d| d.collection == snapshot.collection);
+            if !known {
+                self.restore(snapshot);
+                continue;
+            }
+            let index = self.indexes.entry(snapshot.collection).or_default();
+            for doc in snapshot.documents {
+                if self.documents.contains_key(&doc.document_id) {
+                    continue;
+                }
+                for chunk in &doc.chunks {
+                    if let Some(embedding) = &chunk.embedding {
+                        let key = chunk_key(&doc.document_id, chunk.chunk_index);
+                        if let Err(e) = index.insert(&key, embedding) {
+                            log::warn!("Dropping stored chunk embedding {}: {}", key, e);
+                        }
+                    }
+                }
+                self.documents.insert(doc.document_id.clone(), doc);
+            }
+        }
+        self.unloaded = false;
+        Ok(())
+    }
+
+    /// Persists collections changed since the last flush. A no-op for an
+    /// in-memory store. Collections that fail to save stay dirty.
+    pub async fn flush(&mut self) -> Result<(), String> {
+        self.load_unloaded().await?;
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        let mut names: Vec<String> = self.dirty.iter().cloned().collect();
+        names.sort();
+        for name in names {
+            let documents: Vec<&StoredDocument> = self
+                .documents
+                .values()
+                .filter(|d| d.collection == name)
+                .collect();
+            if documents.is_empty() {
+                storage.remove(&name)?;
+            } else {
+                let snapshot = CollectionSnapshotRef {
+                    collection: &name,
+                    documents,
+                    index: self.indexes.get(&name),
+                };
+                storage.save(&name, &snapshot).await?;
+            }
+            self.dirty.remove(&name);
         }
+        Ok(())
     }
 
     // ── Ingestion ────────────────────────────────────────────────────────────
@@ -62,6 +211,8 @@ impl RagStore {
         } else {
             req.document_id.clone()
         };
+        // Re-ingesting a document replaces its chunks.
+        self.remove_document(&doc_id);
 
         let doc = StoredDocument {
             document_id: doc_id.clone(),
@@ -72,13 +223,22 @@ impl RagStore {
             metadata: req.metadata,
         };
         self.documents.insert(doc_id.clone(), doc);
+        self.dirty.insert(req.collection);
         Ok(doc_id)
     }
 
     pub fn remove_document(&mut self, doc_id: &str) -> bool {
-        self.documents.remove(doc_id).is_some()
+        let Some(doc) = self.documents.remove(doc_id) else {
+            return false;
+        };
+        if let Some(index) = self.indexes.get_mut(&doc.collection) {
+            for chunk in &doc.chunks {
+                index.remove(&chunk_key(doc_id, chunk.chunk_index));
+            }
+        }
+        self.dirty.insert(doc.collection);
+        true
     }
-
     pub fn get_document(&self, doc_id: &str) -> Option<&StoredDocument> {
         self.documents.get(doc_id)
     }
@@ -126,54 +286,85 @@ impl RagStore {
             .chunks
             .get_mut(chunk_index)
             .ok_or_else(|| format!("Chunk {} not found in document {}", chunk_index, doc_id))?;
+        let key = chunk_key(doc_id, chunk_index);
+        self.indexes
+            .entry(doc.collection.clone())
+            .or_default()
+            .insert(&key, &embedding)?;
         chunk.embedding = Some(embedding);
+        self.dirty.insert(doc.collection.clone());
         Ok(())
     }
 
     // ── Search ───────────────────────────────────────────────────────────────
 
+    /// Ranks chunks of `req.collection`. With a query embedding, embedded
+    /// chunks are retrieved through the collection's HNSW index (falling
+    /// back to an exact scan when a metadata filter starves it); chunks
+    /// without an embedding are always scored by text similarity.
     pub fn search(
         &self,
         req: &RagSearchRequest,
         query_embedding: Option<&[f32]>,
     ) -> Vec<RagSearchResult> {
         let mut results = Vec::new();
-
-        for doc in self.documents.values() {
-            if doc.collection != req.collection {
-                continue;
-            }
-
-            // Apply metadata filter
-            if !metadata_matches(&doc.metadata, &req.filter) {
-                continue;
+        let mut push = |doc: &StoredDocument, chunk: &DocumentChunk, score: f32| {
+            if score >= req.similarity_threshold {
+                results.push(RagSearchResult {
+                    document_id: doc.document_id.clone(),
+                    chunk_index: chunk.chunk_index,
+                    content: chunk.content.clone(),
+                    score,
+                    title: doc.title.clone(),
+                    source: doc.source.clone(),
+                    metadata: doc.metadata.clone(),
+                });
             }
+        };
+        let in_scope = |doc: &StoredDocument| {
+            doc.collection == req.collection && metadata_matches(&doc.metadata, &req.filter)
+        };
 
-            for chunk in &doc.chunks {
-                let score = match query_embedding {
-                    Some(qe) => {
-                        match &chunk.embedding {
-                            Some(ce) => cosine_similarity(qe, ce),
-                            None => {
-                                // Fallback: simple text similarity
-                                text_similarity(&req.query, &chunk.content)
-                            }
+        if let Some(qe) = query_embedding {
+            let accept = |key: &str| {
+                parse_chunk_key(key)
+                    .and_then(|(doc_id, _)| self.documents.get(doc_id))
+                    .is_some_and(in_scope)
+            };
+            let hits = self
+                .indexes
+                .get(&req.collection)
+                .map(|index| index.search(qe, req.top_k, &accept))
+                .unwrap_or_default();
+
+            if !req.filter.is_empty() && hits.len() < req.top_k {
+                for doc in self.documents.values().filter(|d| in_scope(d)) {
+                    for chunk in &doc.chunks {
+                        if let Some(ce) = &chunk.embedding {
+                            push(doc, chunk, cosine_similarity(qe, ce));
                         }
                     }
-                    None => text_similarity(&req.query, &chunk.content),
-                };
+                }
+            } else {
+                for (key, score) in hits {
+                    let Some((doc_id, idx)) = parse_chunk_key(&key) else {
+                        continue;
+                    };
+                    if let Some(doc) = self.documents.get(doc_id) {
+                        if let Some(chunk) = doc.chunks.get(idx) {
+                            push(doc, chunk, score);
+                        }
+                    }
+                }
+            }
+        }
 
-                if score >= req.similarity_threshold {
-                    results.push(RagSearchResult {
-                        document_id: doc.document_id.clone(),
-                        chunk_index: chunk.chunk_index,
-                        content: chunk.content.clone(),
-                        score,
-                        title: doc.title.clone(),
-                        source: doc.source.clone(),
-                        metadata: doc.metadata.clone(),
-                    });
+        for doc in self.documents.values().filter(|d| in_scope(d)) {
+            for chunk in &doc.chunks {
+                if query_embedding.is_some() && chunk.embedding.is_some() {
+                    continue;
                 }
+                push(doc, chunk, text_similarity(&req.query, &chunk.content));
             }
         }
 
@@ -194,6 +385,8 @@ impl RagStore {
             top_k,
             similarity_threshold: 0.0,
             filter: HashMap::new(),
+            embedding_provider_id: None,
+            embedding_model: None,
         };
         self.search(&req, None)
     }
@@ -4,19 +4,24 @@
 // Wrapped in `Arc<tokio::sync::Mutex<AiAgentService>>` as Tauri managed state.
 
 use chrono::Utc;
+use sorng_encryption::EncryptionState;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use super::code_assist;
 use super::conversation::ConversationStore;
 use super::embeddings::VectorStore;
 use super::engine;
+use super::mcp_client::McpClientManager;
 use super::memory::MemoryStore;
+use super::persistence::{SnapshotStore, RAG_DIR, VECTORS_DIR};
 use super::providers::{create_provider, LlmProvider};
-use super::rag::RagStore;
+use super::rag::{chunk_text, RagStore};
 use super::templates::TemplateRegistry;
 use super::tokens;
-use super::tools::ToolRegistry;
+use super::tools::{register_builtin_tools, ToolRegistry};
 use super::types::*;
 use super::workflows::{WorkflowExecutor, WorkflowRegistry};
 
@@ -32,6 +37,7 @@ pub struct AiAgentService {
     vectors: VectorStore,
     rag: RagStore,
     workflows: WorkflowRegistry,
+    mcp: McpClientManager,
     // tracking
     request_count: u64,
     total_tokens_used: u64,
@@ -69,6 +75,7 @@ impl AiAgentService {
             vectors: VectorStore::new(),
             rag: RagStore::new(),
             workflows: WorkflowRegistry::new(),
+            mcp: McpClientManager::new(),
             request_count: 0,
             total_tokens_used: 0,
             total_cost_usd: 0.0,
@@ -364,9 +371,13 @@ impl AiAgentService {
             metadata: HashMap::new(),
         }];
 
-        // The service has no global tool registry today; expose an empty one.
-        // Adding tool plumbing is tracked as a separate concern.
-        let tools = ToolRegistry::new();
+        // Built-in tools plus whatever the enabled MCP servers export.
+        // Confirmation-gated tools only run if the caller approved them.
+        self.mcp.connect_enabled().await;
+        let mut tools = ToolRegistry::new();
+        register_builtin_tools(&mut tools);
+        self.mcp.register_tools(&mut tools);
+        tools.set_approved_tools(&config.approved_tools);
 
         let result = engine::run_agent(
             &config,
@@ -488,23 +499,69 @@ impl AiAgentService {
     // Embeddings / Vectors
     // ═══════════════════════════════════════════════════════════════════════════
 
-    pub fn add_vector(
+    pub async fn add_vector(
         &mut self,
         text: &str,
         embedding: Vec<f32>,
         metadata: HashMap<String, serde_json::Value>,
-    ) -> String {
+    ) -> Result<String, String> {
+        let ids = self
+            .upsert_vectors(
+                "default",
+                vec![VectorUpsert {
+                    id: None,
+                    text: text.to_string(),
+                    embedding,
+                    metadata,
+                }],
+            )
+            .await?;
+        Ok(ids.into_iter().next().unwrap_or_default())
+    }
+
+    /// Inserts or replaces entries in `collection` and persists the change.
+    /// Returns the entry IDs in input order.
+    pub async fn upsert_vectors(
+        &mut self,
+        collection: &str,
+        items: Vec<VectorUpsert>,
+    ) -> Result<Vec<String>, String> {
         use super::embeddings::VectorEntry;
-        let id = Uuid::new_v4().to_string();
-        self.vectors.upsert(VectorEntry {
-            id: id.clone(),
-            collection: "default".into(),
-            text: text.to_string(),
-            embedding,
-            metadata,
-            created_at: Utc::now(),
-        });
-        id
+        let now = Utc::now();
+        let entries: Vec<VectorEntry> = items
+            .into_iter()
+            .map(|item| VectorEntry {
+                id: item.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                collection: collection.to_string(),
+                text: item.text,
+                embedding: item.embedding,
+                metadata: item.metadata,
+                created_at: now,
+            })
+            .collect();
+        let ids = entries.iter().map(|e| e.id.clone()).collect();
+        self.vectors.batch_upsert(entries)?;
+        self.vectors.flush().await?;
+        Ok(ids)
+    }
+
+    /// Deletes entries by ID, or every entry matching `filter` when given.
+    /// Returns the number of entries removed.
+    pub async fn delete_vectors(
+        &mut self,
+        collection: &str,
+        ids: &[String],
+        filter: Option<&HashMap<String, serde_json::Value>>,
+    ) -> Result<usize, String> {
+        let mut removed = ids
+            .iter()
+            .filter(|id| self.vectors.remove(collection, id))
+            .count();
+        if let Some(filter) = filter {
+            removed += self.vectors.remove_where(collection, filter);
+        }
+        self.vectors.flush().await?;
+        Ok(removed)
     }
 
     pub fn search_vectors(
@@ -517,24 +574,123 @@ impl AiAgentService {
             .search("default", query_embedding, top_k, Some(threshold))
     }
 
+    pub fn search_vector_collection(&self, req: &VectorSearchRequest) -> Vec<SimilarityResult> {
+        self.vectors.search_filtered(
+            &req.collection,
+            &req.query_embedding,
+            req.top_k,
+            req.min_score,
+            Some(&req.filter),
+        )
+    }
+
+    pub fn list_vector_collections(&self) -> Vec<VectorCollectionInfo> {
+        self.vectors
+            .list_collections()
+            .into_iter()
+            .map(|(name, entries)| VectorCollectionInfo {
+                dimensions: self.vectors.collection_dimensions(&name),
+                name,
+                entries,
+            })
+            .collect()
+    }
+
+    pub async fn drop_vector_collection(&mut self, collection: &str) -> Result<bool, String> {
+        let dropped = self.vectors.drop_collection(collection);
+        self.vectors.flush().await?;
+        Ok(dropped)
+    }
+
     pub fn vector_count(&self) -> usize {
         self.vectors.total_entries()
     }
 
+    /// Switches the vector and RAG stores to on-disk persistence under
+    /// `dir` (`vectors/` and `rag/`), loading whatever is already there.
+    pub async fn open_storage(
+        &mut self,
+        dir: PathBuf,
+        encryption: Option<Arc<EncryptionState>>,
+    ) -> Result<(), String> {
+        self.vectors = VectorStore::open(SnapshotStore::new(
+            dir.join(VECTORS_DIR),
+            encryption.clone(),
+        ))
+        .await?;
+        self.rag = RagStore::open(SnapshotStore::new(dir.join(RAG_DIR), encryption)).await?;
+        Ok(())
+    }
+
+    /// Embeds `texts` with the given provider, one vector per text.
+    async fn embed_texts(
+        &self,
+        provider_id: &str,
+        model: Option<&str>,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, String> {
+        let provider = self.resolve_provider(provider_id)?;
+        let resp = provider.generate_embeddings(texts, model, None).await?;
+        if resp.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Provider returned {} embeddings for {} texts",
+                resp.embeddings.len(),
+                texts.len()
+            ));
+        }
+        Ok(resp.embeddings)
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // RAG
     // ═══════════════════════════════════════════════════════════════════════════
 
-    pub fn ingest_document(&mut self, req: IngestDocumentRequest) -> Result<String, String> {
-        self.rag.ingest(req)
-    }
-
-    pub fn remove_document(&mut self, doc_id: &str) -> bool {
-        self.rag.remove_document(doc_id)
-    }
+    /// Chunks and stores a document. When `embedding_provider_id` is set the
+    /// chunks are embedded first, so a provider failure leaves the store
+    /// untouched.
+    pub async fn ingest_document(&mut self, req: IngestDocumentRequest) -> Result<String, String> {
+        let embeddings = match &req.embedding_provider_id {
+            Some(pid) => {
+                let chunks = chunk_text(&req.content, &req.chunking.clone().unwrap_or_default());
+                if chunks.is_empty() {
+                    None
+                } else {
+                    Some(
+                        self.embed_texts(pid, req.embedding_model.as_deref(), &chunks)
+                            .await?,
+                    )
+                }
+            }
+            None => None,
+        };
 
-    pub fn search_rag(&self, req: &RagSearchRequest) -> Vec<RagSearchResult> {
-        self.rag.search(req, None)
+        let doc_id = self.rag.ingest(req)?;
+        for (i, embedding) in embeddings.into_iter().flatten().enumerate() {
+            self.rag.set_chunk_embedding(&doc_id, i, embedding)?;
+        }
+        self.rag.flush().await?;
+        Ok(doc_id)
+    }
+
+    pub async fn remove_document(&mut self, doc_id: &str) -> Result<bool, String> {
+        let removed = self.rag.remove_document(doc_id);
+        self.rag.flush().await?;
+        Ok(removed)
+    }
+
+    pub async fn search_rag(&self, req: &RagSearchRequest) -> Result<Vec<RagSearchResult>, String> {
+        let query_embedding = match &req.embedding_provider_id {
+            Some(pid) => self
+                .embed_texts(
+                    pid,
+                    req.embedding_model.as_deref(),
+                    std::slice::from_ref(&req.query),
+                )
+                .await?
+                .pop(),
+            None => None,
+        };
+        Ok(self.rag.search(req, query_embedding.as_deref()))
     }
 
     pub fn list_rag_collections(&self) -> Vec<String> {
@@ -680,6 +836,70 @@ impl AiAgentService {
         self.request_count = 0;
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // MCP Servers
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    pub async fn mcp_add_server(
+        &mut self,
+        definition: McpServerDefinition,
+    ) -> Result<McpServerStatus, String> {
+        self.mcp.add_server(definition).await
+    }
+
+    pub async fn mcp_remove_server(&mut self, id: &str) -> bool {
+        self.mcp.remove_server(id).await
+    }
+
+    pub fn mcp_list_servers(&self) -> Vec<McpServerStatus> {
+        self.mcp.list_servers()
+    }
+
+    pub async fn mcp_set_server_enabled(
+        &mut self,
+        id: &str,
+        enabled: bool,
+    ) -> Result<McpServerStatus, String> {
+        self.mcp.set_enabled(id, enabled).await
+    }
+
+    pub async fn mcp_connect_server(&mut self, id: &str) -> Result<McpServerStatus, String> {
+        self.mcp.connect(id).await
+    }
+
+    pub async fn mcp_disconnect_server(&mut self, id: &str) -> Result<McpServerStatus, String> {
+        self.mcp.disconnect(id).await
+    }
+
+    pub fn mcp_list_tools(&self, server_id: Option<&str>) -> Vec<McpRemoteTool> {
+        self.mcp.list_tools(server_id)
+    }
+
+    pub fn mcp_list_resources(&self, server_id: Option<&str>) -> Vec<McpRemoteResource> {
+        self.mcp.list_resources(server_id)
+    }
+
+    pub fn mcp_list_prompts(&self, server_id: Option<&str>) -> Vec<McpRemotePrompt> {
+        self.mcp.list_prompts(server_id)
+    }
+
+    pub async fn mcp_read_resource(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> Result<serde_json::Value, String> {
+        self.mcp.read_resource(server_id, uri).await
+    }
+
+    pub async fn mcp_get_prompt(
+        &self,
+        server_id: &str,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<serde_json::Value, String> {
+        self.mcp.get_prompt(server_id, name, arguments).await
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // Diagnostics
     // ═══════════════════════════════════════════════════════════════════════════
@@ -850,19 +1070,14 @@ mod tests {
             include_reasoning: false,
             memory_config: None,
             rag_config: None,
+            approved_tools: Vec::new(),
             metadata: HashMap::new(),
         };
 
         let tools = ToolRegistry::new();
-        let result = engine::run_agent(
-            &config,
-            &provider,
-            &tools,
-            vec![user_msg("hi")],
-            None,
-        )
-        .await
-        .expect("agent run should succeed");
+        let result = engine::run_agent(&config, &provider, &tools, vec![user_msg("hi")], None)
+            .await
+            .expect("agent run should succeed");
 
         assert_eq!(
             calls.load(Ordering::SeqCst),
@@ -4,24 +4,38 @@
 // engine, argument validation, and result formatting.
 
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
 use uuid::Uuid;
 
 use super::types::*;
 
 // ── Tool Registry ────────────────────────────────────────────────────────────
 
+/// Future returned by an async tool handler.
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<String, String>> + Send>>;
+
+/// Handler fn receives JSON arguments and returns a JSON string result.
+#[allow(clippy::type_complexity)]
+pub enum ToolHandler {
+    Sync(Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>),
+    /// Bounded by the tool's `timeout_secs`.
+    Async(Box<dyn Fn(String) -> ToolFuture + Send + Sync>),
+}
+
 /// A registered tool with its handler.
 pub struct RegisteredTool {
     pub definition: ToolDefinition,
-    /// Handler fn receives JSON arguments and returns a JSON string result.
-    #[allow(clippy::type_complexity)]
-    pub handler: Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>,
+    pub handler: ToolHandler,
 }
 
 /// Manages available tools the agent can call.
 pub struct ToolRegistry {
     tools: HashMap<String, RegisteredTool>,
+    /// Confirmation-gated tools the user has approved for the current run.
+    approved: HashSet<String>,
 }
 
 impl Default for ToolRegistry {
@@ -34,6 +48,7 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            approved: HashSet::new(),
         }
     }
 
@@ -44,6 +59,19 @@ impl ToolRegistry {
         definition: ToolDefinition,
         handler: Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>,
     ) {
+        self.insert(definition, ToolHandler::Sync(handler));
+    }
+
+    /// Registers a tool with an async handler.
+    pub fn register_async(
+        &mut self,
+        definition: ToolDefinition,
+        handler: Box<dyn Fn(String) -> ToolFuture + Send + Sync>,
+    ) {
+        self.insert(definition, ToolHandler::Async(handler));
+    }
+
+    fn insert(&mut self, definition: ToolDefinition, handler: ToolHandler) {
         let name = definition.name.clone();
         self.tools.insert(
             name,
@@ -59,6 +87,11 @@ impl ToolRegistry {
         self.tools.remove(name).is_some()
     }
 
+    /// Replaces the set of confirmation-gated tools allowed to run.
+    pub fn set_approved_tools(&mut self, names: &[String]) {
+        self.approved = names.iter().cloned().collect();
+    }
+
     /// Lists all tool definitions (for sending to the LLM).
     pub fn list_definitions(&self) -> Vec<ToolDefinition> {
         self.tools.values().map(|t| t.definition.clone()).collect()
@@ -79,7 +112,10 @@ impl ToolRegistry {
     }
 
     /// Executes a tool by name using the provided arguments JSON.
-    pub fn execute(&self, name: &str, arguments: &str) -> ToolResult {
+    ///
+    /// Tools that require confirmation only run once approved via
+    /// [`set_approved_tools`](Self::set_approved_tools).
+    pub async fn execute(&self, name: &str, arguments: &str) -> ToolResult {
         let start = std::time::Instant::now();
         let tool = match self.tools.get(name) {
             Some(t) => t,
@@ -95,7 +131,37 @@ impl ToolRegistry {
             }
         };
 
-        match (tool.handler)(arguments) {
+        if tool.definition.requires_confirmation && !self.approved.contains(name) {
+            let error = format!(
+                "Tool '{}' requires user confirmation and was not approved for this run",
+                name
+            );
+            return ToolResult {
+                tool_call_id: String::new(),
+                name: name.to_string(),
+                content: error.clone(),
+                success: false,
+                execution_time_ms: 0,
+                error: Some(error),
+            };
+        }
+
+        let outcome = match &tool.handler {
+            ToolHandler::Sync(handler) => handler(arguments),
+            ToolHandler::Async(handler) => {
+                let timeout = Duration::from_secs(tool.definition.timeout_secs.max(1));
+                match tokio::time::timeout(timeout, handler(arguments.to_string())).await {
+                    Ok(result) => result,
+                    Err(_) => Err(format!(
+                        "Tool '{}' timed out after {}s",
+                        name,
+                        timeout.as_secs()
+                    )),
+                }
+            }
+        };
+
+        match outcome {
             Ok(result) => ToolResult {
                 tool_call_id: String::new(),
                 name: name.to_string(),
@@ -116,15 +182,16 @@ impl ToolRegistry {
     }
 
     /// Processes a list of tool calls from an LLM response.
-    pub fn execute_tool_calls(&self, tool_calls: &[ToolCall]) -> Vec<ToolResult> {
-        tool_calls
-            .iter()
-            .map(|tc| {
-                let mut result = self.execute(&tc.function.name, &tc.function.arguments);
-                result.tool_call_id = tc.id.clone();
-                result
-            })
-            .collect()
+    pub async fn execute_tool_calls(&self, tool_calls: &[ToolCall]) -> Vec<ToolResult> {
+        let mut results = Vec::with_capacity(tool_calls.len());
+        for tc in tool_calls {
+            let mut result = self
+                .execute(&tc.function.name, &tc.function.arguments)
+                .await;
+            result.tool_call_id = tc.id.clone();
+            results.push(result);
+        }
+        results
     }
 }
 
@@ -34,6 +34,9 @@ fn default_presence_penalty() -> f32 {
 fn default_timeout_secs() -> u64 {
     120
 }
+fn default_mcp_timeout_secs() -> u64 {
+    30
+}
 fn default_max_retries() -> u32 {
     3
 }
@@ -545,6 +548,10 @@ pub struct AgentConfig {
     /// Optional RAG configuration.
     #[serde(default)]
     pub rag_config: Option<RagConfig>,
+    /// Tools the user has confirmed for this run. Tools that require
+    /// confirmation and are not listed here are refused.
+    #[serde(default)]
+    pub approved_tools: Vec<String>,
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
 }
@@ -836,6 +843,12 @@ pub struct IngestDocumentRequest {
     pub metadata: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub chunking: Option<ChunkingConfig>,
+    /// Provider used to embed chunks at ingestion. Without one the
+    /// document is only searchable by text.
+    #[serde(default)]
+    pub embedding_provider_id: Option<String>,
+    #[serde(default)]
+    pub embedding_model: Option<String>,
 }
 
 /// A RAG search query.
@@ -851,6 +864,12 @@ pub struct RagSearchRequest {
     /// Optional metadata filter.
     #[serde(default)]
     pub filter: HashMap<String, serde_json::Value>,
+    /// Provider used to embed the query; must match the one used at
+    /// ingestion. Without one the search is text-only.
+    #[serde(default)]
+    pub embedding_provider_id: Option<String>,
+    #[serde(default)]
+    pub embedding_model: Option<String>,
 }
 
 /// A search result from the RAG pipeline.
@@ -898,12 +917,53 @@ pub struct EmbeddingResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SimilarityResult {
+    /// Rank within the result set (0 = best match).
     pub index: usize,
+    /// ID of the matched vector entry.
+    #[serde(default)]
+    pub id: String,
     pub text: String,
     pub score: f32,
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+/// A vector to insert or replace in a collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorUpsert {
+    /// Existing ID to replace; a new one is generated when absent.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub text: String,
+    pub embedding: Vec<f32>,
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Nearest-neighbour query against one vector collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorSearchRequest {
+    pub collection: String,
+    pub query_embedding: Vec<f32>,
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Only entries whose metadata equals every pair are returned.
+    #[serde(default)]
+    pub filter: HashMap<String, serde_json::Value>,
+}
+
+/// Summary of a vector collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorCollectionInfo {
+    pub name: String,
+    pub entries: usize,
+    pub dimensions: Option<usize>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Token Counting & Budget
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1234,6 +1294,128 @@ pub enum SuggestionSeverity {
     Critical,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// MCP Client
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// How to reach an external MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum McpTransportConfig {
+    /// Launch the server as a subprocess speaking newline-delimited JSON-RPC.
+    #[serde(rename = "stdio")]
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+        #[serde(default)]
+        cwd: Option<String>,
+    },
+    /// Streamable HTTP endpoint (JSON or SSE responses to POSTed requests).
+    #[serde(rename = "streamableHttp")]
+    StreamableHttp {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// A configured external MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerDefinition {
+    pub id: String,
+    pub name: String,
+    pub transport: McpTransportConfig,
+    /// Disabled servers are neither connected nor offered to the model.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Per-request timeout, also applied to tool calls.
+    #[serde(default = "default_mcp_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Remote tool names that run without confirmation.
+    #[serde(default)]
+    pub auto_approve_tools: Vec<String>,
+    /// Also run tools the server annotates `readOnlyHint` without
+    /// confirmation. Annotations are the server's own claim, so this is off
+    /// unless the server is trusted.
+    #[serde(default)]
+    pub trust_annotations: bool,
+}
+
+/// Connection state of a configured MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerStatus {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub connected: bool,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub tools: usize,
+    pub resources: usize,
+    pub prompts: usize,
+    pub connected_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// A tool imported from an MCP server's `tools/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpRemoteTool {
+    pub server_id: String,
+    pub name: String,
+    /// Name the tool is registered under in the agent's tool registry.
+    pub exposed_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub input_schema: serde_json::Value,
+    /// Server-declared `readOnlyHint` annotation; only honoured when the
+    /// server's `trust_annotations` is set.
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// A resource imported from an MCP server's `resources/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpRemoteResource {
+    pub server_id: String,
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// A prompt imported from an MCP server's `prompts/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpRemotePrompt {
+    pub server_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpRemotePromptArgument>,
+}
+
+/// A declared argument of a remote prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpRemotePromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Diagnostics & Health
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -14,6 +14,8 @@
 //!   chain-of-thought prompting, and self-correcting execution
 //! - **Tool/Function Calling** — Extensible tool registry with JSON schema validation,
 //!   parallel tool execution, and result parsing
+//! - **MCP Client** — External Model Context Protocol servers over stdio or Streamable
+//!   HTTP; their tools, resources and prompts are imported into the agent
 //! - **Streaming Responses** — Token-by-token delivery with progress callbacks,
 //!   partial response accumulation, and cancellation support
 //! - **Token Counting & Budgets** — Accurate token estimation per model family,
@@ -25,7 +27,8 @@
 //! - **RAG Pipeline** — Document ingestion, chunking strategies, embedding generation,
 //!   vector similarity search, and context-aware retrieval
 //! - **Embeddings** — Multi-provider embedding generation, cosine similarity,
-//!   nearest-neighbor search, and in-memory vector store
+//!   HNSW approximate nearest-neighbor search with metadata filters, and a
+//!   vector store persisted to disk (encrypted at rest when unlocked)
 //! - **Workflow Automation** — Multi-step AI workflows with conditional branching,
 //!   loop constructs, human-in-the-loop checkpoints, and retry policies
 //! - **Code Assistance** — Code generation, review, refactoring suggestions,
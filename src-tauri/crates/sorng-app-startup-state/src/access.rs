@@ -3,6 +3,22 @@ use super::*;
 pub(super) fn register(app: &mut tauri::App<tauri::Wry>) {
     let mut ai_service = ai_agent::service::AiAgentService::new();
     bootstrap_default_ai_provider(&mut ai_service);
+    match app.path().app_data_dir() {
+        Ok(app_dir) => {
+            let enc = app
+                .try_state::<sorng_encryption::EncryptionState>()
+                .map(|h| Arc::new(h.inner().clone()));
+            if let Err(e) = tauri::async_runtime::block_on(
+                ai_service.open_storage(app_dir.join(ai_agent::persistence::STORAGE_DIR), enc),
+            ) {
+                log::warn!(
+                    "AI vector storage unavailable, using in-memory stores: {}",
+                    e
+                );
+            }
+        }
+        Err(e) => log::warn!("AI vector storage unavailable: {}", e),
+    }
     let ai_agent_service: AiAgentServiceState = Arc::new(Mutex::new(ai_service));
     app.manage(ai_agent_service);
 
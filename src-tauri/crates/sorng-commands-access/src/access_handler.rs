@@ -331,6 +331,7 @@ pub fn is_command(command: &str) -> bool {
             | "keepass_get_attachment_pool_size"
             | "keepass_compact_attachment_pool"
             | "keepass_verify_attachment_integrity"
+            | "keepass_list_ssh_keys"
             | "keepass_list_recent_databases"
             | "keepass_add_recent_database"
             | "keepass_remove_recent_database"
@@ -816,6 +817,7 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         keepass_commands::keepass_get_attachment_pool_size,
         keepass_commands::keepass_compact_attachment_pool,
         keepass_commands::keepass_verify_attachment_integrity,
+        keepass_commands::keepass_list_ssh_keys,
         keepass_commands::keepass_list_recent_databases,
         keepass_commands::keepass_add_recent_database,
         keepass_commands::keepass_remove_recent_database,
@@ -120,6 +120,8 @@ pub fn is_command(command: &str) -> bool {
             | "updater_relaunch"
             | "connect_ssh"
             | "ssh_respond_to_host_key_prompt"
+            | "ssh_respond_to_inband_transfer"
+            | "ssh_cancel_inband_transfer"
             | "start_shell"
             | "execute_command"
             | "execute_command_interactive"
@@ -165,6 +167,11 @@ pub fn is_command(command: &str) -> bool {
             | "rdp_clipboard_copy_files"
             | "rdp_clipboard_paste"
             | "rdp_toggle_feature"
+            | "rdp_rail_exec"
+            | "rdp_rail_window_action"
+            | "rdp_rail_set_language_bar"
+            | "rdp_rail_attach_window"
+            | "rdp_rail_detach_window"
             | "get_rdp_logs"
             | "connect_vnc"
             | "disconnect_vnc"
@@ -530,6 +537,11 @@ pub fn is_command(command: &str) -> bool {
             | "get_vnc_tunnel_status"
             | "list_vnc_tunnels"
             | "list_session_vnc_tunnels"
+            | "setup_dynamic_tunnel"
+            | "stop_dynamic_tunnel"
+            | "get_dynamic_tunnel_status"
+            | "list_dynamic_tunnels"
+            | "list_session_dynamic_tunnels"
             | "connect_ssh3"
             | "disconnect_ssh3"
             | "start_ssh3_shell"
@@ -552,6 +564,9 @@ pub fn is_command(command: &str) -> bool {
             | "stop_proxy_command_cmd"
             | "expand_proxy_command"
             | "confirm_proxy_command"
+            | "list_ssh_config_hosts"
+            | "resolve_ssh_config_host"
+            | "import_ssh_config"
             | "http_fetch"
             | "http_get"
             | "http_post"
@@ -641,6 +656,14 @@ pub fn is_command(command: &str) -> bool {
             | "list_telnet_sessions"
             | "disconnect_all_telnet"
             | "is_telnet_connected"
+            | "send_telnet_input"
+            | "set_telnet_com_port"
+            | "set_telnet_com_port_lines"
+            | "purge_telnet_com_port"
+            | "get_telnet_com_port_state"
+            | "get_telnet_linemode_state"
+            | "telnet_respond_to_inband_transfer"
+            | "telnet_cancel_inband_transfer"
             // ── RLogin (9) ─────────────────────────────────────────
             | "connect_rlogin"
             | "send_rlogin_input"
@@ -1022,6 +1045,8 @@ pub fn is_command(command: &str) -> bool {
                     | "serial_get_modem_profiles"
                     | "serial_start_logging"
                     | "serial_stop_logging"
+                    | "serial_start_transfer"
+                    | "serial_cancel_transfer"
                     | "serial_get_baud_rates"
                     | "serial_hex_to_bytes"
                     | "serial_bytes_to_hex"
@@ -1498,6 +1523,8 @@ define_command_group!(
         // app-shell group remains below the bounded Tauri macro expansion.
         ssh_commands::connect_ssh,
         ssh_commands::ssh_respond_to_host_key_prompt,
+        ssh_commands::ssh_respond_to_inband_transfer,
+        ssh_commands::ssh_cancel_inband_transfer,
         ssh_commands::start_shell,
         ssh_commands::execute_command,
         ssh_commands::execute_command_interactive,
@@ -1543,6 +1570,11 @@ define_command_group!(
         rdp_commands::rdp_clipboard_copy_files,
         rdp_commands::rdp_clipboard_paste,
         rdp_commands::rdp_toggle_feature,
+        rdp_commands::rdp_rail_exec,
+        rdp_commands::rdp_rail_window_action,
+        rdp_commands::rdp_rail_set_language_bar,
+        rdp_commands::rdp_rail_attach_window,
+        rdp_commands::rdp_rail_detach_window,
         rdp_commands::get_rdp_logs,
         vnc_commands::connect_vnc,
         vnc_commands::disconnect_vnc,
@@ -1940,6 +1972,11 @@ define_command_group!(
         ssh_commands::get_vnc_tunnel_status,
         ssh_commands::list_vnc_tunnels,
         ssh_commands::list_session_vnc_tunnels,
+        ssh_commands::setup_dynamic_tunnel,
+        ssh_commands::stop_dynamic_tunnel,
+        ssh_commands::get_dynamic_tunnel_status,
+        ssh_commands::list_dynamic_tunnels,
+        ssh_commands::list_session_dynamic_tunnels,
         // SSH3 (SSH over HTTP/3 QUIC) commands
         ssh_commands::connect_ssh3,
         ssh_commands::disconnect_ssh3,
@@ -1966,6 +2003,10 @@ define_command_group!(
         ssh_commands::stop_proxy_command_cmd,
         ssh_commands::expand_proxy_command,
         ssh_commands::confirm_proxy_command,
+        // OpenSSH client config
+        ssh_commands::list_ssh_config_hosts,
+        ssh_commands::resolve_ssh_config_host,
+        ssh_commands::import_ssh_config,
         http_commands::http_fetch,
         http_commands::http_get,
         http_commands::http_post,
@@ -2094,6 +2135,14 @@ define_command_group!(
         telnet_commands::list_telnet_sessions,
         telnet_commands::disconnect_all_telnet,
         telnet_commands::is_telnet_connected,
+        telnet_commands::send_telnet_input,
+        telnet_commands::set_telnet_com_port,
+        telnet_commands::set_telnet_com_port_lines,
+        telnet_commands::purge_telnet_com_port,
+        telnet_commands::get_telnet_com_port_state,
+        telnet_commands::get_telnet_linemode_state,
+        telnet_commands::telnet_respond_to_inband_transfer,
+        telnet_commands::telnet_cancel_inband_transfer,
         // ── Serial (COM / RS-232) — gated on protocol-serial{,-dynamic} (t3-e4) ──
         #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
         serial_commands::serial_scan_ports,
@@ -2152,6 +2201,10 @@ define_command_group!(
         #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
         serial_commands::serial_stop_logging,
         #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
+        serial_commands::serial_start_transfer,
+        #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
+        serial_commands::serial_cancel_transfer,
+        #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
         serial_commands::serial_get_baud_rates,
         #[cfg(any(feature = "protocol-serial", feature = "protocol-serial-dynamic"))]
         serial_commands::serial_hex_to_bytes,
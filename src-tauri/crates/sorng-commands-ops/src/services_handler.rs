@@ -276,6 +276,7 @@ fn is_command_a(command: &str) -> bool {
             | "ssh_agent_update_key_comment"
             | "ssh_agent_update_key_constraints"
             | "ssh_agent_export_public_key"
+            | "ssh_agent_add_certificate"
             | "gpg_get_status"
             | "gpg_start_agent"
             | "gpg_stop_agent"
@@ -549,6 +550,10 @@ fn is_command_b(command: &str) -> bool {
             | "mcp_clear_logs"
             | "mcp_reset_metrics"
             | "mcp_handle_request"
+            | "mcp_list_pending_approvals"
+            | "mcp_resolve_approval"
+            | "mcp_set_connection_tags"
+            | "mcp_get_approval_log"
             | "snmp_get"
             | "snmp_get_next"
             | "snmp_get_bulk"
@@ -647,6 +652,7 @@ fn is_command_b(command: &str) -> bool {
             | "notif_test_channel"
             | "notif_acknowledge_escalation"
             | "topo_build_from_connections"
+            | "topo_discover"
             | "topo_get_graph"
             | "topo_add_node"
             | "topo_remove_node"
@@ -687,6 +693,8 @@ fn is_command_b(command: &str) -> bool {
             | "cred_get"
             | "cred_list"
             | "cred_record_rotation"
+            | "cred_rotate"
+            | "cred_list_rotators"
             | "cred_check_expiry"
             | "cred_check_all_expiries"
             | "cred_get_stale"
@@ -1564,6 +1572,7 @@ fn build_a() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync +
         ssh_agent_commands::ssh_agent_update_key_comment,
         ssh_agent_commands::ssh_agent_update_key_constraints,
         ssh_agent_commands::ssh_agent_export_public_key,
+        ssh_agent_commands::ssh_agent_add_certificate,
         // GPG Agent commands
         gpg_agent_commands::gpg_get_status,
         gpg_agent_commands::gpg_start_agent,
@@ -1842,6 +1851,10 @@ fn build_b() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync +
         mcp_server_commands::mcp_clear_logs,
         mcp_server_commands::mcp_reset_metrics,
         mcp_server_commands::mcp_handle_request,
+        mcp_server_commands::mcp_list_pending_approvals,
+        mcp_server_commands::mcp_resolve_approval,
+        mcp_server_commands::mcp_set_connection_tags,
+        mcp_server_commands::mcp_get_approval_log,
         // SNMP commands
         snmp_commands::snmp_get,
         snmp_commands::snmp_get_next,
@@ -1945,6 +1958,7 @@ fn build_b() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync +
         notifications_commands::notif_acknowledge_escalation,
         // ── Topology ───────────────────────────────────────────────────
         topology_commands::topo_build_from_connections,
+        topology_commands::topo_discover,
         topology_commands::topo_get_graph,
         topology_commands::topo_add_node,
         topology_commands::topo_remove_node,
@@ -1987,6 +2001,8 @@ fn build_b() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync +
         credentials_commands::cred_get,
         credentials_commands::cred_list,
         credentials_commands::cred_record_rotation,
+        credentials_commands::cred_rotate,
+        credentials_commands::cred_list_rotators,
         credentials_commands::cred_check_expiry,
         credentials_commands::cred_check_all_expiries,
         credentials_commands::cred_get_stale,
@@ -2,6 +2,10 @@ mod builder {
     pub use crate::topology::builder::*;
 }
 
+mod discovery {
+    pub use crate::topology::discovery::*;
+}
+
 mod service {
     pub use crate::topology::service::*;
 }
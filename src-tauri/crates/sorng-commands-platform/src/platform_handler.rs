@@ -69,6 +69,8 @@ pub fn is_command(command: &str) -> bool {
             | "rec_library_delete"
             | "rec_library_clear"
             | "rec_library_summary"
+            | "rec_library_search_content"
+            | "rec_library_rebuild_search_index"
             | "rec_list_active"
             | "rec_active_count"
             | "rec_stop_all"
@@ -318,6 +320,20 @@ pub fn is_command(command: &str) -> bool {
             | "k8s_evict_pod"
             | "k8s_update_pod_labels"
             | "k8s_update_pod_annotations"
+            | "k8s_exec_pod"
+            | "k8s_attach_pod"
+            | "k8s_exec_send_input"
+            | "k8s_exec_resize"
+            | "k8s_exec_close_stdin"
+            | "k8s_exec_close"
+            | "k8s_list_exec_sessions"
+            | "k8s_start_port_forward"
+            | "k8s_stop_port_forward"
+            | "k8s_list_port_forwards"
+            | "k8s_watch_resource"
+            | "k8s_follow_pod_logs"
+            | "k8s_stop_stream"
+            | "k8s_list_streams"
             | "k8s_list_deployments"
             | "k8s_list_all_deployments"
             | "k8s_get_deployment"
@@ -454,6 +470,16 @@ pub fn is_command(command: &str) -> bool {
             | "docker_container_exec"
             | "docker_container_update"
             | "docker_prune_containers"
+            | "docker_exec_interactive"
+            | "docker_attach_container"
+            | "docker_exec_send_input"
+            | "docker_exec_resize"
+            | "docker_exec_close_stdin"
+            | "docker_exec_close"
+            | "docker_list_exec_sessions"
+            | "docker_start_event_stream"
+            | "docker_stop_event_stream"
+            | "docker_list_event_streams"
             | "docker_list_images"
             | "docker_inspect_image"
             | "docker_image_history"
@@ -689,6 +715,8 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         recording_commands::rec_library_delete,
         recording_commands::rec_library_clear,
         recording_commands::rec_library_summary,
+        recording_commands::rec_library_search_content,
+        recording_commands::rec_library_rebuild_search_index,
         // Aggregate / status
         recording_commands::rec_list_active,
         recording_commands::rec_active_count,
@@ -951,6 +979,20 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         k8s_commands::k8s_evict_pod,
         k8s_commands::k8s_update_pod_labels,
         k8s_commands::k8s_update_pod_annotations,
+        k8s_commands::k8s_exec_pod,
+        k8s_commands::k8s_attach_pod,
+        k8s_commands::k8s_exec_send_input,
+        k8s_commands::k8s_exec_resize,
+        k8s_commands::k8s_exec_close_stdin,
+        k8s_commands::k8s_exec_close,
+        k8s_commands::k8s_list_exec_sessions,
+        k8s_commands::k8s_start_port_forward,
+        k8s_commands::k8s_stop_port_forward,
+        k8s_commands::k8s_list_port_forwards,
+        k8s_commands::k8s_watch_resource,
+        k8s_commands::k8s_follow_pod_logs,
+        k8s_commands::k8s_stop_stream,
+        k8s_commands::k8s_list_streams,
         k8s_commands::k8s_list_deployments,
         k8s_commands::k8s_list_all_deployments,
         k8s_commands::k8s_get_deployment,
@@ -1088,6 +1130,16 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         docker_commands::docker_container_exec,
         docker_commands::docker_container_update,
         docker_commands::docker_prune_containers,
+        docker_commands::docker_exec_interactive,
+        docker_commands::docker_attach_container,
+        docker_commands::docker_exec_send_input,
+        docker_commands::docker_exec_resize,
+        docker_commands::docker_exec_close_stdin,
+        docker_commands::docker_exec_close,
+        docker_commands::docker_list_exec_sessions,
+        docker_commands::docker_start_event_stream,
+        docker_commands::docker_stop_event_stream,
+        docker_commands::docker_list_event_streams,
         docker_commands::docker_list_images,
         docker_commands::docker_inspect_image,
         docker_commands::docker_image_history,
@@ -276,6 +276,7 @@ pub fn is_command(command: &str) -> bool {
             | "ssh_agent_update_key_comment"
             | "ssh_agent_update_key_constraints"
             | "ssh_agent_export_public_key"
+            | "ssh_agent_add_certificate"
             | "gpg_get_status"
             | "gpg_start_agent"
             | "gpg_stop_agent"
@@ -762,6 +763,7 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         ssh_agent_commands::ssh_agent_update_key_comment,
         ssh_agent_commands::ssh_agent_update_key_constraints,
         ssh_agent_commands::ssh_agent_export_public_key,
+        ssh_agent_commands::ssh_agent_add_certificate,
         // GPG Agent commands
         gpg_agent_commands::gpg_get_status,
         gpg_agent_commands::gpg_start_agent,
@@ -161,6 +161,21 @@ pub fn is_command(command: &str) -> bool {
             | "mongo_replica_set_status"
             | "mongo_current_op"
             | "mongo_kill_op"
+            | "mongo_find"
+            | "mongo_count_documents"
+            | "mongo_insert_one"
+            | "mongo_insert_many"
+            | "mongo_update_one"
+            | "mongo_update_many"
+            | "mongo_replace_one"
+            | "mongo_delete_one"
+            | "mongo_delete_many"
+            | "mongo_aggregate"
+            | "mongo_list_indexes"
+            | "mongo_create_index"
+            | "mongo_drop_index"
+            | "mongo_export_collection"
+            | "mongo_import_documents"
             | "redis_connect"
             | "redis_disconnect"
             | "redis_disconnect_all"
@@ -253,6 +268,11 @@ pub fn is_command(command: &str) -> bool {
             | "ai_update_memory_config"
             | "ai_add_vector"
             | "ai_search_vectors"
+            | "ai_upsert_vectors"
+            | "ai_delete_vectors"
+            | "ai_search_vector_collection"
+            | "ai_list_vector_collections"
+            | "ai_drop_vector_collection"
             | "ai_ingest_document"
             | "ai_remove_document"
             | "ai_search_rag"
@@ -267,6 +287,17 @@ pub fn is_command(command: &str) -> bool {
             | "ai_update_budget"
             | "ai_reset_budget"
             | "ai_diagnostics"
+            | "ai_mcp_add_server"
+            | "ai_mcp_remove_server"
+            | "ai_mcp_list_servers"
+            | "ai_mcp_set_server_enabled"
+            | "ai_mcp_connect_server"
+            | "ai_mcp_disconnect_server"
+            | "ai_mcp_list_tools"
+            | "ai_mcp_list_resources"
+            | "ai_mcp_list_prompts"
+            | "ai_mcp_read_resource"
+            | "ai_mcp_get_prompt"
             | "op_get_config"
             | "op_set_config"
             | "op_connect"
@@ -568,6 +599,21 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         mongodb_commands::mongo_replica_set_status,
         mongodb_commands::mongo_current_op,
         mongodb_commands::mongo_kill_op,
+        mongodb_commands::mongo_find,
+        mongodb_commands::mongo_count_documents,
+        mongodb_commands::mongo_insert_one,
+        mongodb_commands::mongo_insert_many,
+        mongodb_commands::mongo_update_one,
+        mongodb_commands::mongo_update_many,
+        mongodb_commands::mongo_replace_one,
+        mongodb_commands::mongo_delete_one,
+        mongodb_commands::mongo_delete_many,
+        mongodb_commands::mongo_aggregate,
+        mongodb_commands::mongo_list_indexes,
+        mongodb_commands::mongo_create_index,
+        mongodb_commands::mongo_drop_index,
+        mongodb_commands::mongo_export_collection,
+        mongodb_commands::mongo_import_documents,
         // ── Redis ───────────────────────────────────────────────────
         redis_commands::redis_connect,
         redis_commands::redis_disconnect,
@@ -662,6 +708,11 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         ai_agent_commands::ai_update_memory_config,
         ai_agent_commands::ai_add_vector,
         ai_agent_commands::ai_search_vectors,
+        ai_agent_commands::ai_upsert_vectors,
+        ai_agent_commands::ai_delete_vectors,
+        ai_agent_commands::ai_search_vector_collection,
+        ai_agent_commands::ai_list_vector_collections,
+        ai_agent_commands::ai_drop_vector_collection,
         ai_agent_commands::ai_ingest_document,
         ai_agent_commands::ai_remove_document,
         ai_agent_commands::ai_search_rag,
@@ -676,6 +727,17 @@ pub fn build() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync
         ai_agent_commands::ai_update_budget,
         ai_agent_commands::ai_reset_budget,
         ai_agent_commands::ai_diagnostics,
+        ai_agent_commands::ai_mcp_add_server,
+        ai_agent_commands::ai_mcp_remove_server,
+        ai_agent_commands::ai_mcp_list_servers,
+        ai_agent_commands::ai_mcp_set_server_enabled,
+        ai_agent_commands::ai_mcp_connect_server,
+        ai_agent_commands::ai_mcp_disconnect_server,
+        ai_agent_commands::ai_mcp_list_tools,
+        ai_agent_commands::ai_mcp_list_resources,
+        ai_agent_commands::ai_mcp_list_prompts,
+        ai_agent_commands::ai_mcp_read_resource,
+        ai_agent_commands::ai_mcp_get_prompt,
         // ── 1Password ────────────────────────────────────────────────
         onepassword_commands::op_get_config,
         onepassword_commands::op_set_config,
@@ -6,3 +6,11 @@ pub mod cpu_features;
 pub mod diagnostics;
 pub mod events;
 pub mod native_renderer;
+pub mod net;
+pub mod ssh_certificate;
+pub mod ssh_identities;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod utf8;
@@ -0,0 +1,7 @@
+//! Shared settings for the accept loops of local listeners.
+
+use std::time::Duration;
+
+/// Pause after a failed `accept` so a persistent error (EMFILE, ENFILE)
+/// does not spin the loop.
+pub const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
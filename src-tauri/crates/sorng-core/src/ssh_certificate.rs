@@ -0,0 +1,382 @@
+//! OpenSSH certificate (`*-cert-v01@openssh.com`) decoding, shared by the
+//! SSH client and the built-in agent (see OpenSSH `PROTOCOL.certkeys`).
+//!
+//! Blobs are decoded here rather than through `ssh_key::Certificate`, which
+//! rejects the `valid_before = forever` (`u64::MAX`) value that
+//! `ssh-keygen -s` issues by default. The CA signature is kept but not
+//! checked here; callers that trust a CA (host certificates) verify it.
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// Suffix shared by every OpenSSH certificate key type.
+pub const CERT_KEY_TYPE_SUFFIX: &str = "-cert-v01@openssh.com";
+
+/// Certificate type field (`SSH_CERT_TYPE_USER` / `SSH_CERT_TYPE_HOST`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateType {
+    User,
+    Host,
+}
+
+/// A decoded OpenSSH certificate.
+#[derive(Debug, Clone)]
+pub struct OpenSshCertificate {
+    /// Certificate key type, e.g. `ssh-ed25519-cert-v01@openssh.com`.
+    pub key_type: String,
+    /// Plain public-key blob of the certified key.
+    pub public_key_blob: Vec<u8>,
+    pub serial: u64,
+    pub cert_type: CertificateType,
+    pub key_id: String,
+    /// Empty means valid for any principal.
+    pub valid_principals: Vec<String>,
+    /// Unix seconds; `valid_after <= now < valid_before`.
+    pub valid_after: u64,
+    pub valid_before: u64,
+    /// Critical options as `(name, value)`, in wire order.
+    pub critical_options: Vec<(String, String)>,
+    /// Extensions as `(name, value)`, in wire order.
+    pub extensions: Vec<(String, String)>,
+    /// Public-key blob of the signing CA.
+    pub signature_key: Vec<u8>,
+    /// Everything up to the signature, which the CA signed.
+    pub signed_data: Vec<u8>,
+    /// CA signature blob over `signed_data`.
+    pub signature: Vec<u8>,
+}
+
+impl OpenSshCertificate {
+    /// Decode a certificate from its wire blob.
+    pub fn from_blob(blob: &[u8]) -> Result<Self, String> {
+        let mut reader = BlobReader::new(blob);
+        let key_type = reader.read_utf8()?;
+        let (base_type, key_fields) = certified_key_layout(&key_type)
+            .ok_or_else(|| format!("Unsupported certificate key type '{}'", key_type))?;
+
+        let _nonce = reader.read_string()?;
+        let mut public_key_blob = encode_string(base_type.as_bytes());
+        for _ in 0..key_fields {
+            public_key_blob.extend(encode_string(reader.read_string()?));
+        }
+        let serial = reader.read_u64()?;
+        let cert_type = match reader.read_u32()? {
+            1 => CertificateType::User,
+            2 => CertificateType::Host,
+            other => return Err(format!("Unknown certificate type {}", other)),
+        };
+        let key_id = reader.read_utf8()?;
+        let valid_principals = BlobReader::new(reader.read_string()?).read_utf8_list()?;
+        let valid_after = reader.read_u64()?;
+        let valid_before = reader.read_u64()?;
+        let critical_options = BlobReader::new(reader.read_string()?).read_options()?;
+        let extensions = BlobReader::new(reader.read_string()?).read_options()?;
+        let _reserved = reader.read_string()?;
+        let signature_key = reader.read_string()?.to_vec();
+        let signed_data = blob[..reader.pos].to_vec();
+        let signature = reader.read_string()?.to_vec();
+        if !reader.is_empty() {
+            return Err("Trailing bytes after certificate signature".to_string());
+        }
+
+        Ok(Self {
+            key_type,
+            public_key_blob,
+            serial,
+            cert_type,
+            key_id,
+            valid_principals,
+            valid_after,
+            valid_before,
+            critical_options,
+            extensions,
+            signature_key,
+            signed_data,
+            signature,
+        })
+    }
+
+    /// Decode a certificate from `<type> <base64> [comment]` text.
+    pub fn from_openssh(text: &str) -> Result<Self, String> {
+        let (blob, _comment) = decode_openssh(text)?;
+        Self::from_blob(&blob)
+    }
+
+    /// Whether `now` (Unix seconds) falls inside the validity window.
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        self.valid_after <= now && now < self.valid_before
+    }
+
+    /// Whether the certificate may be used as `principal`; as in OpenSSH, an
+    /// empty principal list allows any.
+    pub fn allows_principal(&self, principal: &str) -> bool {
+        self.valid_principals.is_empty() || self.valid_principals.iter().any(|p| p == principal)
+    }
+
+    /// SHA-256 fingerprint of the signing CA key, in OpenSSH notation.
+    pub fn ca_fingerprint(&self) -> String {
+        format!(
+            "SHA256:{}",
+            base64::engine::general_purpose::STANDARD_NO_PAD
+                .encode(Sha256::digest(&self.signature_key))
+        )
+    }
+}
+
+/// Split `<type> <base64> [comment]` text into the certificate blob and
+/// comment, checking the stated type against the blob's own.
+pub fn decode_openssh(text: &str) -> Result<(Vec<u8>, Option<String>), String> {
+    let mut fields = text.split_whitespace();
+    let key_type = fields.next().ok_or("Empty certificate")?;
+    let encoded = fields.next().ok_or("Certificate is missing its key data")?;
+    let blob = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid certificate encoding: {}", e))?;
+    if BlobReader::new(&blob).read_string()? != key_type.as_bytes() {
+        return Err("Certificate type does not match its encoded key type".to_string());
+    }
+    let comment = fields.collect::<Vec<_>>().join(" ");
+    Ok((blob, (!comment.is_empty()).then_some(comment)))
+}
+
+/// Whether `key_type` names an OpenSSH certificate.
+pub fn is_certificate_key_type(key_type: &str) -> bool {
+    key_type.ends_with(CERT_KEY_TYPE_SUFFIX)
+}
+
+/// The plain key type certified by a certificate key type.
+pub fn base_key_type(cert_type: &str) -> Option<&'static str> {
+    certified_key_layout(cert_type).map(|(base, _)| base)
+}
+
+/// The plain key type and the number of key fields embedded in a
+/// certificate of `cert_type`.
+fn certified_key_layout(cert_type: &str) -> Option<(&'static str, usize)> {
+    Some(match cert_type {
+        "ssh-rsa-cert-v01@openssh.com" => ("ssh-rsa", 2),
+        "ssh-dss-cert-v01@openssh.com" => ("ssh-dss", 4),
+        "ssh-ed25519-cert-v01@openssh.com" => ("ssh-ed25519", 1),
+        "ecdsa-sha2-nistp256-cert-v01@openssh.com" => ("ecdsa-sha2-nistp256", 2),
+        "ecdsa-sha2-nistp384-cert-v01@openssh.com" => ("ecdsa-sha2-nistp384", 2),
+        "ecdsa-sha2-nistp521-cert-v01@openssh.com" => ("ecdsa-sha2-nistp521", 2),
+        "sk-ssh-ed25519-cert-v01@openssh.com" => ("sk-ssh-ed25519@openssh.com", 2),
+        "sk-ecdsa-sha2-nistp256-cert-v01@openssh.com" => ("sk-ecdsa-sha2-nistp256@openssh.com", 3),
+        _ => return None,
+    })
+}
+
+fn encode_string(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + data.len());
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+struct BlobReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BlobReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Truncated certificate data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        let high = u64::from(self.read_u32()?);
+        let low = u64::from(self.read_u32()?);
+        Ok((high << 32) | low)
+    }
+
+    fn read_string(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    fn read_utf8(&mut self) -> Result<String, String> {
+        String::from_utf8(self.read_string()?.to_vec())
+            .map_err(|_| "Invalid UTF-8 in certificate".to_string())
+    }
+
+    fn read_utf8_list(&mut self) -> Result<Vec<String>, String> {
+        let mut items = Vec::new();
+        while !self.is_empty() {
+            items.push(self.read_utf8()?);
+        }
+        Ok(items)
+    }
+
+    /// Options are `string name, string data` pairs where `data` is itself a
+    /// string (or empty for flag-style extensions).
+    fn read_options(&mut self) -> Result<Vec<(String, String)>, String> {
+        let mut options = Vec::new();
+        while !self.is_empty() {
+            let name = self.read_utf8()?;
+            let data = self.read_string()?;
+            let value = if data.is_empty() {
+                String::new()
+            } else {
+                String::from_utf8_lossy(BlobReader::new(data).read_string()?).into_owned()
+            };
+            options.push((name, value));
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_key::certificate::{Builder, CertType};
+    use ssh_key::private::Ed25519Keypair;
+    use ssh_key::PrivateKey;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn key(seed: u8) -> PrivateKey {
+        PrivateKey::from(Ed25519Keypair::from_seed(&[seed; 32]))
+    }
+
+    fn builder(subject: &PrivateKey, cert_type: CertType) -> Builder {
+        let mut builder = Builder::new(
+            [7u8; 16],
+            subject.public_key().key_data().clone(),
+            NOW - 60,
+            NOW + 3600,
+        )
+        .unwrap();
+        builder.cert_type(cert_type).unwrap();
+        builder
+    }
+
+    #[test]
+    fn decodes_certificate_fields() {
+        let (ca, subject) = (key(1), key(2));
+        let mut builder = builder(&subject, CertType::User);
+        builder.serial(42).unwrap();
+        builder.key_id("alice@corp").unwrap();
+        builder.valid_principal("alice").unwrap();
+        builder.critical_option("force-command", "uptime").unwrap();
+        builder.extension("permit-pty", "").unwrap();
+        let signed = builder.sign(&ca).unwrap();
+
+        let text = format!("{} laptop", signed.to_openssh().unwrap());
+        let (blob, comment) = decode_openssh(&text).unwrap();
+        assert_eq!(blob, signed.to_bytes().unwrap());
+        assert_eq!(comment.as_deref(), Some("laptop"));
+
+        let cert = OpenSshCertificate::from_blob(&blob).unwrap();
+        assert_eq!(cert.key_type, "ssh-ed25519-cert-v01@openssh.com");
+        assert_eq!(cert.serial, 42);
+        assert_eq!(cert.cert_type, CertificateType::User);
+        assert_eq!(cert.key_id, "alice@corp");
+        assert_eq!(cert.valid_principals, vec!["alice".to_string()]);
+        assert_eq!(
+            cert.critical_options,
+            vec![("force-command".to_string(), "uptime".to_string())]
+        );
+        assert_eq!(
+            cert.extensions,
+            vec![("permit-pty".to_string(), String::new())]
+        );
+        assert_eq!(
+            cert.public_key_blob,
+            subject.public_key().to_bytes().unwrap()
+        );
+        assert_eq!(cert.signature_key, ca.public_key().to_bytes().unwrap());
+        assert_eq!(cert.signed_data, blob[..cert.signed_data.len()]);
+        assert_eq!(
+            &ssh_key::Signature::try_from(cert.signature.as_slice()).unwrap(),
+            signed.signature()
+        );
+        assert_eq!(
+            cert.ca_fingerprint(),
+            ca.public_key()
+                .fingerprint(ssh_key::HashAlg::Sha256)
+                .to_string()
+        );
+        assert!(cert.is_valid_at(NOW) && !cert.is_valid_at(NOW + 3600));
+    }
+
+    #[test]
+    fn forever_validity_is_accepted() {
+        let mut host = builder(&key(2), CertType::Host);
+        host.valid_principal("h").unwrap();
+        let mut blob = host.sign(&key(1)).unwrap().to_bytes().unwrap();
+        let cert = OpenSshCertificate::from_blob(&blob).unwrap();
+        let offset = blob
+            .windows(8)
+            .position(|w| w == cert.valid_before.to_be_bytes())
+            .unwrap();
+        blob[offset..offset + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+        let patched = OpenSshCertificate::from_blob(&blob).unwrap();
+        assert_eq!(patched.valid_before, u64::MAX);
+        assert!(patched.is_valid_at(u64::MAX - 1));
+    }
+
+    #[test]
+    fn empty_principals_allow_any_principal() {
+        let mut any = builder(&key(2), CertType::User);
+        any.all_principals_valid().unwrap();
+        let any =
+            OpenSshCertificate::from_blob(&any.sign(&key(1)).unwrap().to_bytes().unwrap()).unwrap();
+        assert!(any.valid_principals.is_empty());
+        assert!(any.allows_principal("alice") && any.allows_principal("root"));
+
+        let mut listed = builder(&key(2), CertType::User);
+        listed.valid_principal("alice").unwrap();
+        let listed =
+            OpenSshCertificate::from_blob(&listed.sign(&key(1)).unwrap().to_bytes().unwrap())
+                .unwrap();
+        assert!(listed.allows_principal("alice") && !listed.allows_principal("root"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let mut blob = encode_string(b"ssh-ed25519-cert-v01@openssh.com");
+        blob.extend(encode_string(&[0; 16]));
+        assert!(OpenSshCertificate::from_blob(&blob).is_err());
+
+        let mut user = builder(&key(2), CertType::User);
+        user.valid_principal("alice").unwrap();
+        let text = user.sign(&key(1)).unwrap().to_openssh().unwrap().replacen(
+            "ssh-ed25519-cert",
+            "ssh-rsa-cert",
+            1,
+        );
+        assert!(decode_openssh(&text)
+            .unwrap_err()
+            .contains("does not match"));
+    }
+
+    #[test]
+    fn maps_certificate_types() {
+        assert!(is_certificate_key_type("ssh-rsa-cert-v01@openssh.com"));
+        assert!(!is_certificate_key_type("ssh-rsa"));
+        assert_eq!(
+            base_key_type("ecdsa-sha2-nistp256-cert-v01@openssh.com"),
+            Some("ecdsa-sha2-nistp256")
+        );
+        assert_eq!(base_key_type("ssh-ed25519"), None);
+    }
+}
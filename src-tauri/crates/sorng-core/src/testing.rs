@@ -0,0 +1,186 @@
+//! Fake HTTP servers and an event recorder for service-crate tests.
+//!
+//! Enabled by the `testing` feature; crates pull it in as a dev-dependency.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::events::AppEventEmitter;
+
+/// What a fake server saw of a request.
+#[derive(Debug, Clone, Default)]
+pub struct FakeRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl FakeRequest {
+    /// The first value of header `name`, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Read each request and hand it, with the socket, to `handler`, which
+/// writes whatever response it likes.
+pub async fn fake_server<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(FakeRequest, TcpStream) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                if let Some(request) = read_request(&mut socket).await {
+                    handler(request, socket).await;
+                }
+            });
+        }
+    });
+    addr
+}
+
+/// Serve plain HTTP.  `handler` answers each request with a status and the
+/// body chunks, which are written (chunked) a little apart so the client
+/// sees them arrive separately; the connection closes after the last one.
+pub async fn http_server<F>(handler: F) -> SocketAddr
+where
+    F: Fn(FakeRequest) -> (u16, Vec<Vec<u8>>) + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    fake_server(move |request, mut socket| {
+        let (status, chunks) = handler(request);
+        async move {
+            let head = format!(
+                "HTTP/1.1 {} Fake\r\nContent-Type: application/json\r\n\
+                 Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+                status
+            );
+            if socket.write_all(head.as_bytes()).await.is_err() {
+                return;
+            }
+            for chunk in chunks {
+                let mut framed = format!("{:x}\r\n", chunk.len()).into_bytes();
+                framed.extend_from_slice(&chunk);
+                framed.extend_from_slice(b"\r\n");
+                if socket.write_all(&framed).await.is_err() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            let _ = socket.write_all(b"0\r\n\r\n").await;
+            let _ = socket.shutdown().await;
+        }
+    })
+    .await
+}
+
+/// Write a complete response with a `Content-Length` body and close.
+pub async fn respond(socket: &mut TcpStream, status: u16, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {} Fake\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+    let _ = socket.shutdown().await;
+}
+
+/// Read one request (head and `Content-Length` body) off `socket`.
+pub async fn read_request<S: AsyncRead + Unpin>(socket: &mut S) -> Option<FakeRequest> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if socket.read(&mut byte).await.ok()? == 0 {
+            return None;
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    let mut request = FakeRequest {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+    let length = request
+        .header("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    request.body = vec![0u8; length];
+    socket.read_exact(&mut request.body).await.ok()?;
+    Some(request)
+}
+
+/// Records emitted events for assertions.
+#[derive(Default)]
+pub struct RecordingEmitter {
+    events: StdMutex<Vec<(String, serde_json::Value)>>,
+}
+
+impl AppEventEmitter for RecordingEmitter {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        self.events
+            .lock()
+            .unwrap()
+            .push((event.to_string(), payload));
+        Ok(())
+    }
+}
+
+impl RecordingEmitter {
+    /// Payloads of every `event` emitted so far.
+    pub fn payloads(&self, event: &str) -> Vec<serde_json::Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    /// Wait until `event` has been emitted `count` times.
+    pub async fn wait_for(&self, event: &str, count: usize) -> Vec<serde_json::Value> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let payloads = self.payloads(event);
+            if payloads.len() >= count {
+                return payloads;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "expected {} {} events",
+                count,
+                event
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! rustls client configuration for connections that cannot go through
+//! reqwest: WebSocket upgrades and hijacked HTTP streams.
+//!
+//! Enabled by the `tls` feature.  Callers resolve their own settings into a
+//! [`ClientTls`] and map the returned messages onto their error type.
+
+use std::sync::Arc;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+
+/// Resolved TLS material for one connection.
+#[derive(Debug, Default)]
+pub struct ClientTls {
+    /// CA certificates (PEM, or a single DER certificate); the system roots
+    /// are trusted when unset.
+    pub ca: Option<Vec<u8>>,
+    /// PEM client certificate chain and private key.
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+    /// Accept any server certificate.
+    pub skip_verify: bool,
+}
+
+/// Build a ring-backed client configuration.
+pub fn client_config(tls: &ClientTls) -> Result<ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    let builder = if tls.skip_verify {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+    } else {
+        builder.with_root_certificates(root_store(tls.ca.as_deref())?)
+    };
+    match &tls.identity {
+        Some((cert, key)) => {
+            let certs = parse_certs(cert, "client cert")?;
+            let key = PrivateKeyDer::from_pem_slice(key)
+                .map_err(|e| format!("Invalid client key: {}", e))?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|e| format!("Invalid client cert: {}", e))
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// `ca` when given, the system roots otherwise.
+pub fn root_store(ca: Option<&[u8]>) -> Result<RootCertStore, String> {
+    let mut roots = RootCertStore::empty();
+    match ca {
+        Some(bytes) => {
+            for cert in parse_certs(bytes, "CA cert")? {
+                roots
+                    .add(cert)
+                    .map_err(|e| format!("Invalid CA cert: {}", e))?;
+            }
+        }
+        None => {
+            for cert in rustls_native_certs::load_native_certs().certs {
+                let _ = roots.add(cert);
+            }
+        }
+    }
+    Ok(roots)
+}
+
+/// Inline data wins over a file path.
+pub fn read_source(
+    inline: Option<Vec<u8>>,
+    path: Option<&str>,
+    what: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    if inline.is_some() {
+        return Ok(inline);
+    }
+    match path {
+        Some(path) => std::fs::read(path)
+            .map(Some)
+            .map_err(|e| format!("Cannot read {} '{}': {}", what, path, e)),
+        None => Ok(None),
+    }
+}
+
+/// PEM certificates, or a single DER certificate.
+pub fn parse_certs(bytes: &[u8], what: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_slice_iter(bytes)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid {}: {}", what, e))?;
+    if !certs.is_empty() {
+        return Ok(certs);
+    }
+    // A DER certificate is an ASN.1 SEQUENCE.
+    if bytes.first() == Some(&0x30) {
+        return Ok(vec![CertificateDer::from(bytes.to_vec())]);
+    }
+    Err(format!("Invalid {}: no certificates found", what))
+}
+
+/// Backs [`ClientTls::skip_verify`].
+#[derive(Debug)]
+struct NoCertificateVerification;
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            SignatureScheme::ED25519,
+            SignatureScheme::RSA_PSS_SHA256,
+            SignatureScheme::RSA_PSS_SHA384,
+            SignatureScheme::RSA_PSS_SHA512,
+            SignatureScheme::RSA_PKCS1_SHA256,
+            SignatureScheme::RSA_PKCS1_SHA384,
+            SignatureScheme::RSA_PKCS1_SHA512,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_verify_builds_without_roots() {
+        let tls = ClientTls {
+            skip_verify: true,
+            ..Default::default()
+        };
+        assert!(client_config(&tls).is_ok());
+    }
+
+    #[test]
+    fn parse_certs_rejects_non_certificate_input() {
+        let err = parse_certs(b"not a certificate", "CA cert").unwrap_err();
+        assert_eq!(err, "Invalid CA cert: no certificates found");
+        assert_eq!(
+            parse_certs(&[0x30, 0x03, 0x02, 0x01, 0x00], "CA cert")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn inline_source_wins_over_path() {
+        let inline = read_source(Some(b"pem".to_vec()), Some("/nonexistent"), "CA cert");
+        assert_eq!(inline.unwrap(), Some(b"pem".to_vec()));
+        let missing = read_source(None, Some("/nonexistent"), "CA cert").unwrap_err();
+        assert!(
+            missing.starts_with("Cannot read CA cert '/nonexistent'"),
+            "{}",
+            missing
+        );
+        assert_eq!(read_source(None, None, "CA cert").unwrap(), None);
+    }
+}
@@ -4,7 +4,7 @@
 // `#[tauri::command]` system. All commands are prefixed with `cred_`.
 
 use super::error::CredentialError;
-use super::service::CredentialServiceState;
+use super::service::{CredentialService, CredentialServiceState};
 use super::tracker::CredentialTracker;
 use super::types::*;
 use chrono::Utc;
@@ -119,6 +119,27 @@ pub async fn cred_record_rotation(
     .map_err(|e| e.to_string())
 }
 
+/// Rotate a credential on its target system with the matching rotator.
+/// The report carries the new secret, which the caller must store.
+#[tauri::command]
+pub async fn cred_rotate(
+    state: State<'_, CredentialServiceState>,
+    request: RotationRequest,
+) -> Result<RotationReport, String> {
+    CredentialService::rotate(state.inner(), request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the target kinds that have a registered rotator.
+#[tauri::command]
+pub async fn cred_list_rotators(
+    state: State<'_, CredentialServiceState>,
+) -> Result<Vec<RotatorKind>, String> {
+    let svc = state.lock().await;
+    Ok(svc.rotation.kinds())
+}
+
 // ── Expiry Analysis ─────────────────────────────────────────────────
 
 /// Check the expiry status of a single credential.
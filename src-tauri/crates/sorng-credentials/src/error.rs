@@ -29,6 +29,8 @@ pub enum CredentialError {
     Serialization(String),
     /// Durable credential metadata storage error.
     Persistence(String),
+    /// A rotation executor failed against the remote system.
+    Rotation(String),
     /// Generic / internal error.
     Internal(String),
 }
@@ -47,6 +49,7 @@ impl fmt::Display for CredentialError {
             Self::Validation(msg) => write!(f, "Validation error: {msg}"),
             Self::Serialization(msg) => write!(f, "Serialization error: {msg}"),
             Self::Persistence(msg) => write!(f, "Persistence error: {msg}"),
+            Self::Rotation(msg) => write!(f, "Rotation error: {msg}"),
             Self::Internal(msg) => write!(f, "Internal error: {msg}"),
         }
     }
@@ -108,6 +108,7 @@ mod tests {
             credential_ids: vec![],
             shared_policy_id: None,
             auto_rotate_together: false,
+            last_rotation: None,
         }
     }
 
@@ -17,6 +17,9 @@
 //!   requirements, maximum age, and automatic notifications.
 //! - **Expiry alerting** — Multi-severity alert generation with acknowledgement
 //!   tracking.
+//! - **Automated rotation** — Pluggable rotators (Linux `chpasswd`, SSH key
+//!   rollover, MySQL / PostgreSQL, LDAP password modify) with two-phase
+//!   verify / rollback.
 //! - **Duplicate detection** — SHA-256 fingerprint-based detection of reused
 //!   credential values.
 //! - **Tauri commands** — Complete IPC command surface for front-end integration.
@@ -27,6 +30,7 @@ pub mod error;
 pub mod groups;
 mod persistence;
 pub mod policies;
+pub mod rotation;
 pub mod service;
 pub mod tracker;
 pub mod types;
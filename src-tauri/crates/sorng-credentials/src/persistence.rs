@@ -185,6 +185,10 @@ fn validate(state: &PersistedCredentialState) -> Result<(), CredentialError> {
             group.credential_ids.len(),
             MAX_CREDENTIALS,
         )?;
+        if let Some(rotation) = &group.last_rotation {
+            validate_nonempty_id("group rotation credential", &rotation.credential_id)?;
+            validate_text("group rotation details", &rotation.details)?;
+        }
     }
 
     for alert in &state.alerts {
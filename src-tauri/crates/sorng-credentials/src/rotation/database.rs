@@ -0,0 +1,351 @@
+//! # Database Rotators
+//!
+//! MySQL / MariaDB (`ALTER USER`) and PostgreSQL (`ALTER ROLE`) account
+//! passwords, changed through an administrative login and verified by
+//! connecting as the account.
+
+use super::transport::{SqlClient, SqlDialect};
+use super::{validate_name, RotationJob, Rotator};
+use crate::error::CredentialError;
+use crate::types::{RotationTarget, SqlEndpoint};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+const VERIFY_STATEMENT: &str = "SELECT 1";
+
+fn wrong_target() -> CredentialError {
+    CredentialError::Internal("rotator received a target of another kind".to_string())
+}
+
+/// Backslashes are refused outright: MySQL treats them as escapes unless
+/// `NO_BACKSLASH_ESCAPES` is set, so no single encoding is correct for
+/// every server.
+fn check_literal(label: &str, value: &str) -> Result<(), CredentialError> {
+    if value.contains('\\') || value.contains('\0') {
+        return Err(CredentialError::Validation(format!(
+            "{label} must not contain backslashes or NUL"
+        )));
+    }
+    Ok(())
+}
+
+/// Single-quoted SQL string literal (see [`check_literal`]).
+fn string_literal(label: &str, value: &str) -> Result<String, CredentialError> {
+    check_literal(label, value)?;
+    Ok(format!("'{}'", value.replace('\'', "''")))
+}
+
+/// Double-quoted PostgreSQL identifier.
+fn pg_identifier(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+// ── MySQL ───────────────────────────────────────────────────────────
+
+/// Rotates a MySQL / MariaDB account with `ALTER USER … IDENTIFIED BY`.
+pub struct MysqlRotator {
+    client: Arc<dyn SqlClient>,
+}
+
+impl MysqlRotator {
+    pub fn new(client: Arc<dyn SqlClient>) -> Self {
+        Self { client }
+    }
+
+    fn target(job: &RotationJob) -> Result<(&SqlEndpoint, &str, &str), CredentialError> {
+        match &job.target {
+            RotationTarget::Mysql {
+                endpoint,
+                account,
+                account_host,
+            } => Ok((endpoint, account, account_host)),
+            _ => Err(wrong_target()),
+        }
+    }
+
+    async fn set_password(&self, job: &RotationJob, password: &str) -> Result<(), CredentialError> {
+        let (endpoint, account, account_host) = Self::target(job)?;
+        validate_name("MySQL account", account)?;
+        validate_name("MySQL account host", account_host)?;
+        let statement = format!(
+            "ALTER USER {}@{} IDENTIFIED BY {}",
+            string_literal("MySQL account", account)?,
+            string_literal("MySQL account host", account_host)?,
+            string_literal("password", password)?
+        );
+        self.client
+            .execute(
+                SqlDialect::Mysql,
+                endpoint,
+                &endpoint.admin_user,
+                &endpoint.admin_password,
+                &statement,
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl Rotator for MysqlRotator {
+    fn check_secret(&self, label: &str, value: &str) -> Result<(), CredentialError> {
+        check_literal(label, value)
+    }
+
+    async fn apply(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, &job.new_secret).await
+    }
+
+    async fn verify(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        let (endpoint, account, _) = Self::target(job)?;
+        self.client
+            .execute(
+                SqlDialect::Mysql,
+                endpoint,
+                account,
+                &job.new_secret,
+                VERIFY_STATEMENT,
+            )
+            .await
+    }
+
+    async fn rollback(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, job.current_secret()?).await
+    }
+}
+
+// ── PostgreSQL ──────────────────────────────────────────────────────
+
+/// Rotates a PostgreSQL login role with `ALTER ROLE … PASSWORD`.
+pub struct PostgresRotator {
+    client: Arc<dyn SqlClient>,
+}
+
+impl PostgresRotator {
+    pub fn new(client: Arc<dyn SqlClient>) -> Self {
+        Self { client }
+    }
+
+    fn target(job: &RotationJob) -> Result<(&SqlEndpoint, &str), CredentialError> {
+        match &job.target {
+            RotationTarget::Postgres { endpoint, role } => Ok((endpoint, role)),
+            _ => Err(wrong_target()),
+        }
+    }
+
+    async fn set_password(&self, job: &RotationJob, password: &str) -> Result<(), CredentialError> {
+        let (endpoint, role) = Self::target(job)?;
+        validate_name("PostgreSQL role", role)?;
+        let statement = format!(
+            "ALTER ROLE {} WITH PASSWORD {}",
+            pg_identifier(role),
+            string_literal("password", password)?
+        );
+        self.client
+            .execute(
+                SqlDialect::Postgres,
+                endpoint,
+                &endpoint.admin_user,
+                &endpoint.admin_password,
+                &statement,
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl Rotator for PostgresRotator {
+    async fn apply(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, &job.new_secret).await
+    }
+
+    async fn verify(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        let (endpoint, role) = Self::target(job)?;
+        self.client
+            .execute(
+                SqlDialect::Postgres,
+                endpoint,
+                role,
+                &job.new_secret,
+                VERIFY_STATEMENT,
+            )
+            .await
+    }
+
+    async fn rollback(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, job.current_secret()?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records statements and accepts a login only with `password`.
+    struct FakeServer {
+        account: String,
+        password: Mutex<String>,
+        statements: Mutex<Vec<(SqlDialect, String, String)>>,
+    }
+
+    #[async_trait]
+    impl SqlClient for FakeServer {
+        async fn execute(
+            &self,
+            dialect: SqlDialect,
+            endpoint: &SqlEndpoint,
+            user: &str,
+            password: &str,
+            statement: &str,
+        ) -> Result<(), CredentialError> {
+            let admin = user == endpoint.admin_user && password == endpoint.admin_password;
+            let account = user == self.account && password == *self.password.lock().unwrap();
+            if !admin && !account {
+                return Err(CredentialError::Rotation("access denied".to_string()));
+            }
+            self.statements.lock().unwrap().push((
+                dialect,
+                user.to_string(),
+                statement.to_string(),
+            ));
+            if let Some((_, literal)) = statement
+                .rsplit_once("BY ")
+                .or(statement.rsplit_once("PASSWORD "))
+            {
+                let value = literal.trim_matches('\'').replace("''", "'");
+                *self.password.lock().unwrap() = value;
+            }
+            Ok(())
+        }
+    }
+
+    fn server(account: &str) -> Arc<FakeServer> {
+        Arc::new(FakeServer {
+            account: account.to_string(),
+            password: Mutex::new("old".to_string()),
+            statements: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn endpoint() -> SqlEndpoint {
+        SqlEndpoint {
+            host: "db.example".to_string(),
+            port: 3306,
+            database: None,
+            admin_user: "admin".to_string(),
+            admin_password: "admin-pw".to_string(),
+            timeout_secs: 5,
+        }
+    }
+
+    fn job(target: RotationTarget, new: &str) -> RotationJob {
+        RotationJob {
+            credential_id: "cred-1".to_string(),
+            target,
+            current_secret: Some("old".to_string()),
+            new_secret: new.to_string(),
+            new_public_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn mysql_alter_user_and_rollback() {
+        let server = server("app");
+        let rotator = MysqlRotator::new(server.clone());
+        let job = job(
+            RotationTarget::Mysql {
+                endpoint: endpoint(),
+                account: "app".to_string(),
+                account_host: "%".to_string(),
+            },
+            "it's-new",
+        );
+
+        rotator.apply(&job).await.unwrap();
+        rotator.verify(&job).await.unwrap();
+        rotator.rollback(&job).await.unwrap();
+        assert!(rotator.verify(&job).await.is_err());
+
+        let statements = server.statements.lock().unwrap();
+        assert_eq!(
+            statements[0],
+            (
+                SqlDialect::Mysql,
+                "admin".to_string(),
+                "ALTER USER 'app'@'%' IDENTIFIED BY 'it''s-new'".to_string()
+            )
+        );
+        assert_eq!(statements[1].1, "app");
+        assert_eq!(statements[1].2, VERIFY_STATEMENT);
+    }
+
+    #[tokio::test]
+    async fn postgres_quotes_role_identifiers() {
+        let server = server("odd\"role");
+        let rotator = PostgresRotator::new(server.clone());
+        let job = job(
+            RotationTarget::Postgres {
+                endpoint: endpoint(),
+                role: "odd\"role".to_string(),
+            },
+            "fresh",
+        );
+        rotator.apply(&job).await.unwrap();
+        rotator.verify(&job).await.unwrap();
+        assert_eq!(
+            server.statements.lock().unwrap()[0].2,
+            "ALTER ROLE \"odd\"\"role\" WITH PASSWORD 'fresh'"
+        );
+    }
+
+    #[tokio::test]
+    async fn backslashes_are_refused() {
+        let rotator = MysqlRotator::new(server("app"));
+        let job = job(
+            RotationTarget::Mysql {
+                endpoint: endpoint(),
+                account: "app".to_string(),
+                account_host: "%".to_string(),
+            },
+            "a\\'; DROP USER root; --",
+        );
+        assert!(matches!(
+            rotator.apply(&job).await,
+            Err(CredentialError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn unusable_current_secret_is_refused_before_rotating() {
+        use super::super::tests::record;
+        use super::super::{RotationEngine, RotationRequest};
+        use crate::types::{CredentialType, RotatorKind};
+
+        let server = server("app");
+        let mut engine = RotationEngine::new();
+        engine.register(
+            RotatorKind::Mysql,
+            Arc::new(MysqlRotator::new(server.clone())),
+        );
+        let request = RotationRequest {
+            credential_id: "cred-1".to_string(),
+            target: RotationTarget::Mysql {
+                endpoint: endpoint(),
+                account: "app".to_string(),
+                account_host: "%".to_string(),
+            },
+            current_secret: Some("old\\pw".to_string()),
+            new_secret: None,
+            requested_by: None,
+        };
+        // Rolling back would need the current secret, which MySQL cannot be
+        // sent, so nothing may be changed.
+        assert!(matches!(
+            engine
+                .rotate(&record(CredentialType::Password), &request)
+                .await,
+            Err(CredentialError::Validation(_))
+        ));
+        assert!(server.statements.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+//! # LDAP Rotator
+//!
+//! RFC 3062 password modify through OpenLDAP's `ldappasswd`, verified with
+//! `ldapwhoami` bound as the user. The tools run locally, or on a host
+//! reached over SSH when the directory is not directly reachable.
+
+use super::transport::CommandRunner;
+use super::{shell_quote, validate_name, validate_secret, RotationJob, Rotator};
+use crate::error::CredentialError;
+use crate::types::RotationTarget;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Reads one secret per stdin line into private files `$d/1`, `$d/2`, …
+/// so no password ever appears in a command line. The LDAP command follows.
+const SECRET_FILES_PRELUDE: &str = "umask 077; d=$(mktemp -d) || exit 125; \
+trap 'rm -rf \"$d\"' EXIT; trap 'exit 130' HUP INT TERM; i=0; \
+while IFS= read -r line; do i=$((i+1)); printf '%s' \"$line\" >\"$d/$i\" || exit 125; done; \
+unset line; ";
+
+/// Sets an LDAP user's password with the directory administrator's bind.
+pub struct LdapRotator {
+    runner: Arc<dyn CommandRunner>,
+}
+
+struct LdapTarget<'a> {
+    ldap_uri: &'a str,
+    bind_dn: &'a str,
+    bind_password: &'a str,
+    user_dn: &'a str,
+    start_tls: bool,
+}
+
+impl LdapTarget<'_> {
+    /// Common `-x -H uri [-ZZ]` arguments.
+    fn connection_args(&self) -> String {
+        let mut args = format!("-x -H {}", shell_quote(self.ldap_uri));
+        if self.start_tls {
+            args.push_str(" -ZZ");
+        }
+        args
+    }
+}
+
+impl LdapRotator {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner }
+    }
+
+    fn target(job: &RotationJob) -> Result<LdapTarget<'_>, CredentialError> {
+        let RotationTarget::Ldap {
+            ldap_uri,
+            bind_dn,
+            bind_password,
+            user_dn,
+            start_tls,
+            ..
+        } = &job.target
+        else {
+            return Err(CredentialError::Internal(
+                "rotator received a target of another kind".to_string(),
+            ));
+        };
+        validate_name("LDAP URI", ldap_uri)?;
+        validate_name("LDAP bind DN", bind_dn)?;
+        validate_name("LDAP user DN", user_dn)?;
+        validate_secret("LDAP bind password", bind_password)?;
+        if !(ldap_uri.starts_with("ldap://") || ldap_uri.starts_with("ldaps://")) {
+            return Err(CredentialError::Validation(
+                "LDAP URI must use the ldap:// or ldaps:// scheme".to_string(),
+            ));
+        }
+        Ok(LdapTarget {
+            ldap_uri,
+            bind_dn,
+            bind_password,
+            user_dn,
+            start_tls: *start_tls,
+        })
+    }
+
+    async fn run(
+        &self,
+        job: &RotationJob,
+        command: &str,
+        secrets: &[&str],
+    ) -> Result<(), CredentialError> {
+        let RotationTarget::Ldap { ssh, .. } = &job.target else {
+            return Err(CredentialError::Internal(
+                "rotator received a target of another kind".to_string(),
+            ));
+        };
+        let mut input = Vec::new();
+        for secret in secrets {
+            input.extend_from_slice(secret.as_bytes());
+            input.push(b'\n');
+        }
+        self.runner
+            .run(
+                ssh.as_ref(),
+                &format!("{SECRET_FILES_PRELUDE}{command}"),
+                Some(input),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    /// `ldappasswd` bound as the administrator (`$d/1`), setting `$d/2`.
+    async fn set_password(&self, job: &RotationJob, password: &str) -> Result<(), CredentialError> {
+        let target = Self::target(job)?;
+        let command = format!(
+            "ldappasswd {} -D {} -y \"$d/1\" -T \"$d/2\" {}",
+            target.connection_args(),
+            shell_quote(target.bind_dn),
+            shell_quote(target.user_dn)
+        );
+        self.run(job, &command, &[target.bind_password, password])
+            .await
+    }
+}
+
+#[async_trait]
+impl Rotator for LdapRotator {
+    async fn apply(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, &job.new_secret).await
+    }
+
+    async fn verify(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        let target = Self::target(job)?;
+        let command = format!(
+            "ldapwhoami {} -D {} -y \"$d/1\"",
+            target.connection_args(),
+            shell_quote(target.user_dn)
+        );
+        self.run(job, &command, &[&job.new_secret]).await
+    }
+
+    async fn rollback(&self, job: &RotationJob) -> Result<(), CredentialError> {
+        self.set_password(job, job.current_secret()?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SshTarget;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<(Option<String>, String, String)>>,
+    }
+
+    #[async_trait]
+    impl CommandRunner for Recorder {
+        async fn run(
+            &self,
+            host: Option<&SshTarget>,
+            command: &str,
+            input: Option<Vec<u8>>,
+        ) -> Result<String, CredentialError> {
+            self.calls.lock().unwrap().push((
+                host.map(|h| h.host.clone()),
+                command.to_string(),
+                String::from_utf8(input.unwrap_or_default()).unwrap(),
+            ));
+            Ok(String::new())
+        }
+    }
+
+    fn job(uri: &str, ssh: Option<SshTarget>) -> RotationJob {
+        RotationJob {
+            credential_id: "cred-1".to_string(),
+            target: RotationTarget::Ldap {
+                ssh,
+                ldap_uri: uri.to_string(),
+                bind_dn: "cn=admin,dc=example,dc=org".to_string(),
+                bind_password: "admin-pw".to_string(),
+                user_dn: "uid=o'brien,ou=people,dc=example,dc=org".to_string(),
+                start_tls: true,
+            },
+            current_secret: Some("old-pw".to_string()),
+            new_secret: "new-pw".to_string(),
+            new_public_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn secrets_travel_on_stdin_only() {
+        let recorder = Arc::new(Recorder::default());
+        let rotator = LdapRotator::new(recorder.clone());
+        let job = job("ldap://dc.example.org", None);
+
+        rotator.apply(&job).await.unwrap();
+        rotator.verify(&job).await.unwrap();
+        rotator.rollback(&job).await.unwrap();
+
+        let calls = recorder.calls.lock().unwrap();
+        let (host, command, input) = &calls[0];
+        assert!(host.is_none());
+        assert!(command.starts_with(SECRET_FILES_PRELUDE));
+        assert!(command.ends_with(
+            "ldappasswd -x -H 'ldap://dc.example.org' -ZZ -D 'cn=admin,dc=example,dc=org' \
+             -y \"$d/1\" -T \"$d/2\" 'uid=o'\"'\"'brien,ou=people,dc=example,dc=org'"
+        ));
+        assert_eq!(input, "admin-pw\nnew-pw\n");
+        assert!(calls[1].1.contains("ldapwhoami"));
+        assert_eq!(calls[1].2, "new-pw\n");
+        assert_eq!(calls[2].2, "admin-pw\nold-pw\n");
+        for (_, command, _) in calls.iter() {
+            assert!(!command.contains("-pw"));
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_on_the_ssh_host_and_rejects_odd_uris() {
+        let recorder = Arc::new(Recorder::default());
+        let rotator = LdapRotator::new(recorder.clone());
+        let ssh = SshTarget {
+            host: "jump.example.org".to_string(),
+            port: 22,
+            username: "ops".to_string(),
+            password: None,
+            private_key_path: None,
+            timeout_secs: 5,
+        };
+        rotator
+            .apply(&job("ldaps://dc.example.org", Some(ssh)))
+            .await
+            .unwrap();
+        assert_eq!(
+            recorder.calls.lock().unwrap()[0].0.as_deref(),
+            Some("jump.example.org")
+        );
+
+        assert!(matches!(
+            rotator.apply(&job("file:///etc/passwd", None)).await,
+            Err(CredentialError::Validation(_))
+        ));
+    }
+}
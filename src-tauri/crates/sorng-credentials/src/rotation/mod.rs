@@ -0,0 +1,732 @@
+//! # Rotation Executors
+//!
+//! Pluggable rotators that change a credential on the system that accepts
+//! it, rather than only raising alerts. Every rotation follows the same
+//! two-phase protocol:
+//!
+//! 1. **Apply** the new credential through an administrative path.
+//! 2. **Verify** it by authenticating with the new credential.
+//!
+//! A failed verification triggers **rollback** to the previous credential;
+//! a successful one runs **finalize** (e.g. retiring a superseded SSH key).
+//! The resulting [`RotationReport`] is recorded in the audit log and on the
+//! credential's group by [`CredentialService::rotate`].
+//!
+//! [`CredentialService::rotate`]: crate::service::CredentialService::rotate
+
+pub mod database;
+pub mod ldap;
+pub mod linux;
+pub mod transport;
+
+use crate::error::CredentialError;
+use crate::tracker::CredentialTracker;
+use crate::types::*;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{info, warn};
+use rand::rngs::OsRng;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use zeroize::Zeroize;
+
+/// Length of generated replacement passwords.
+pub const GENERATED_PASSWORD_LEN: usize = 32;
+
+/// Upper bound on any secret handed to a rotator.
+const MAX_SECRET_BYTES: usize = 1024;
+
+/// Upper bound on step messages kept in a report.
+const MAX_STEP_MESSAGE_CHARS: usize = 1024;
+
+/// Password alphabet. Quotes, backslash, `$`, `:` and whitespace are left
+/// out so generated values survive every shell, SQL and `chpasswd` path.
+const PASSWORD_ALPHABET: &[u8] =
+    b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789-_.,+=@%^*!#";
+
+// ── Rotator Trait ───────────────────────────────────────────────────
+
+/// A replacement credential produced before a rotation starts.
+pub struct GeneratedSecret {
+    pub secret: String,
+    /// OpenSSH public key matching `secret`, for key-pair rotators.
+    pub public_key: Option<String>,
+}
+
+impl Drop for GeneratedSecret {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Everything a rotator needs for one rotation.
+pub struct RotationJob {
+    pub credential_id: String,
+    pub target: RotationTarget,
+    pub current_secret: Option<String>,
+    pub new_secret: String,
+    pub new_public_key: Option<String>,
+}
+
+impl RotationJob {
+    /// The previous secret, required by password rollbacks.
+    pub fn current_secret(&self) -> Result<&str, CredentialError> {
+        self.current_secret
+            .as_deref()
+            .ok_or_else(|| CredentialError::Rotation("the previous secret is unknown".to_string()))
+    }
+}
+
+impl Drop for RotationJob {
+    fn drop(&mut self) {
+        self.current_secret.zeroize();
+        self.new_secret.zeroize();
+    }
+}
+
+/// A rotation executor for one kind of target.
+#[async_trait]
+pub trait Rotator: Send + Sync {
+    /// Produce the replacement credential. Password rotators keep the
+    /// default generator; key-pair rotators override it.
+    fn generate(&self, _target: &RotationTarget) -> Result<GeneratedSecret, CredentialError> {
+        Ok(GeneratedSecret {
+            secret: generate_password(GENERATED_PASSWORD_LEN),
+            public_key: None,
+        })
+    }
+
+    /// Whether callers may supply their own replacement value.
+    fn accepts_supplied_secret(&self) -> bool {
+        true
+    }
+
+    /// Whether `rollback` needs the previous secret. Rotations that cannot
+    /// be rolled back are refused before anything is changed.
+    fn needs_current_secret(&self) -> bool {
+        true
+    }
+
+    /// Reject a secret this target cannot express. Runs on the current and
+    /// the supplied secret before anything is changed, so a rollback is
+    /// never left holding a value it cannot send.
+    fn check_secret(&self, _label: &str, _value: &str) -> Result<(), CredentialError> {
+        Ok(())
+    }
+
+    /// Phase one: make `job.new_secret` the live credential.
+    async fn apply(&self, job: &RotationJob) -> Result<(), CredentialError>;
+
+    /// Phase two: prove the new credential authenticates.
+    async fn verify(&self, job: &RotationJob) -> Result<(), CredentialError>;
+
+    /// Restore the previous credential after a failed verification.
+    async fn rollback(&self, job: &RotationJob) -> Result<(), CredentialError>;
+
+    /// Retire the previous credential once the new one is verified. A
+    /// failure here is reported as a warning; the rotation stands.
+    async fn finalize(&self, _job: &RotationJob) -> Result<(), CredentialError> {
+        Ok(())
+    }
+}
+
+// ── Engine ──────────────────────────────────────────────────────────
+
+/// Registry of rotators and driver for the two-phase protocol.
+pub struct RotationEngine {
+    rotators: HashMap<RotatorKind, Arc<dyn Rotator>>,
+    /// Credentials with a rotation in progress.
+    in_flight: Mutex<HashSet<String>>,
+}
+
+impl RotationEngine {
+    /// Create an engine with no rotators registered.
+    pub fn new() -> Self {
+        Self {
+            rotators: HashMap::new(),
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Create an engine with the built-in SSH, SQL and LDAP rotators.
+    pub fn with_defaults() -> Self {
+        let runner: Arc<dyn transport::CommandRunner> = Arc::new(transport::SshCommandRunner);
+        let sql: Arc<dyn transport::SqlClient> = Arc::new(transport::SqlxClient);
+        let mut engine = Self::new();
+        engine.register(
+            RotatorKind::LinuxPassword,
+            Arc::new(linux::LinuxPasswordRotator::new(runner.clone())),
+        );
+        engine.register(
+            RotatorKind::SshKey,
+            Arc::new(linux::SshKeyRotator::new(runner.clone())),
+        );
+        engine.register(
+            RotatorKind::Mysql,
+            Arc::new(database::MysqlRotator::new(sql.clone())),
+        );
+        engine.register(
+            RotatorKind::Postgres,
+            Arc::new(database::PostgresRotator::new(sql)),
+        );
+        engine.register(RotatorKind::Ldap, Arc::new(ldap::LdapRotator::new(runner)));
+        engine
+    }
+
+    /// Register (or replace) the rotator for a target kind.
+    pub fn register(&mut self, kind: RotatorKind, rotator: Arc<dyn Rotator>) {
+        self.rotators.insert(kind, rotator);
+    }
+
+    /// Target kinds with a registered rotator.
+    pub fn kinds(&self) -> Vec<RotatorKind> {
+        self.rotators.keys().copied().collect()
+    }
+
+    /// Rotate `record` as described by `request`.
+    ///
+    /// Problems detected before the target is touched (unknown rotator,
+    /// type mismatch, missing previous secret, concurrent rotation) are
+    /// returned as errors. Once the apply phase has started the outcome is
+    /// always a report, whose status says whether the new credential is live.
+    pub async fn rotate(
+        &self,
+        record: &CredentialRecord,
+        request: &RotationRequest,
+    ) -> Result<RotationReport, CredentialError> {
+        let kind = request.target.kind();
+        let rotator = self.rotators.get(&kind).cloned().ok_or_else(|| {
+            CredentialError::Validation(format!("no rotator registered for {kind}"))
+        })?;
+        if !kind.supports(record.credential_type) {
+            return Err(CredentialError::Validation(format!(
+                "{kind} rotation does not apply to {} credentials",
+                record.credential_type
+            )));
+        }
+        if rotator.needs_current_secret() && request.current_secret.is_none() {
+            return Err(CredentialError::Validation(
+                "the current secret is required so a failed rotation can be rolled back"
+                    .to_string(),
+            ));
+        }
+        if let Some(current) = &request.current_secret {
+            validate_secret("current secret", current)?;
+            rotator.check_secret("current secret", current)?;
+        }
+
+        let generated = match &request.new_secret {
+            Some(_) if !rotator.accepts_supplied_secret() => {
+                return Err(CredentialError::Validation(format!(
+                    "{kind} rotation generates its own replacement credential"
+                )));
+            }
+            Some(supplied) => {
+                validate_secret("new secret", supplied)?;
+                rotator.check_secret("new secret", supplied)?;
+                GeneratedSecret {
+                    secret: supplied.clone(),
+                    public_key: None,
+                }
+            }
+            None => rotator.generate(&request.target)?,
+        };
+
+        let _guard = InFlight::acquire(&self.in_flight, &record.id)?;
+        let job = RotationJob {
+            credential_id: record.id.clone(),
+            target: request.target.clone(),
+            current_secret: request.current_secret.clone(),
+            new_secret: generated.secret.clone(),
+            new_public_key: generated.public_key.clone(),
+        };
+        Ok(run_protocol(rotator.as_ref(), kind, &job).await)
+    }
+}
+
+impl Default for RotationEngine {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// Drive apply → verify → (rollback | finalize) and build the report.
+async fn run_protocol(
+    rotator: &dyn Rotator,
+    kind: RotatorKind,
+    job: &RotationJob,
+) -> RotationReport {
+    let started_at = Utc::now();
+    let mut steps = Vec::new();
+    let mut warnings = Vec::new();
+
+    let status = if let Err(error) =
+        record_step(&mut steps, RotationPhase::Apply, rotator.apply(job).await)
+    {
+        warn!("Rotation of {} failed to apply: {error}", job.credential_id);
+        RotationStatus::Failed
+    } else if let Err(error) =
+        record_step(&mut steps, RotationPhase::Verify, rotator.verify(job).await)
+    {
+        warn!(
+            "Rotation of {} failed verification, rolling back: {error}",
+            job.credential_id
+        );
+        match record_step(
+            &mut steps,
+            RotationPhase::Rollback,
+            rotator.rollback(job).await,
+        ) {
+            Ok(()) => RotationStatus::RolledBack,
+            Err(_) => RotationStatus::RollbackFailed,
+        }
+    } else {
+        if let Err(error) = record_step(
+            &mut steps,
+            RotationPhase::Finalize,
+            rotator.finalize(job).await,
+        ) {
+            warnings.push(bounded_message(&error.to_string()));
+        }
+        RotationStatus::Rotated
+    };
+    info!("Rotation of {} via {kind}: {status}", job.credential_id);
+
+    let rotated = status == RotationStatus::Rotated;
+    RotationReport {
+        credential_id: job.credential_id.clone(),
+        rotator: kind,
+        status,
+        started_at,
+        finished_at: Utc::now(),
+        steps,
+        warnings,
+        new_fingerprint: rotated.then(|| CredentialTracker::compute_fingerprint(&job.new_secret)),
+        new_secret: rotated.then(|| job.new_secret.clone()),
+        new_public_key: if rotated {
+            job.new_public_key.clone()
+        } else {
+            None
+        },
+    }
+}
+
+fn record_step(
+    steps: &mut Vec<RotationStep>,
+    phase: RotationPhase,
+    result: Result<(), CredentialError>,
+) -> Result<(), CredentialError> {
+    steps.push(RotationStep {
+        phase,
+        success: result.is_ok(),
+        message: match &result {
+            Ok(()) => "ok".to_string(),
+            Err(error) => bounded_message(&error.to_string()),
+        },
+    });
+    result
+}
+
+fn bounded_message(message: &str) -> String {
+    if message.chars().count() <= MAX_STEP_MESSAGE_CHARS {
+        return message.to_string();
+    }
+    let mut bounded: String = message.chars().take(MAX_STEP_MESSAGE_CHARS).collect();
+    bounded.push('…');
+    bounded
+}
+
+/// Marks a credential as being rotated until dropped.
+struct InFlight<'a> {
+    set: &'a Mutex<HashSet<String>>,
+    id: String,
+}
+
+impl<'a> InFlight<'a> {
+    fn acquire(set: &'a Mutex<HashSet<String>>, id: &str) -> Result<Self, CredentialError> {
+        let mut ids = set
+            .lock()
+            .map_err(|_| CredentialError::Internal("rotation registry poisoned".to_string()))?;
+        if !ids.insert(id.to_string()) {
+            return Err(CredentialError::Validation(format!(
+                "credential {id} is already being rotated"
+            )));
+        }
+        Ok(Self {
+            set,
+            id: id.to_string(),
+        })
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut ids) = self.set.lock() {
+            ids.remove(&self.id);
+        }
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+/// Generate a random password drawing at least one character from each of
+/// the upper-case, lower-case, digit and symbol classes.
+pub fn generate_password(len: usize) -> String {
+    let len = len.max(4);
+    loop {
+        let candidate: String = (0..len)
+            .map(|_| PASSWORD_ALPHABET[OsRng.gen_range(0..PASSWORD_ALPHABET.len())] as char)
+            .collect();
+        let classes = [
+            candidate.chars().any(|c| c.is_ascii_uppercase()),
+            candidate.chars().any(|c| c.is_ascii_lowercase()),
+            candidate.chars().any(|c| c.is_ascii_digit()),
+            candidate.chars().any(|c| !c.is_ascii_alphanumeric()),
+        ];
+        if classes.iter().all(|present| *present) {
+            return candidate;
+        }
+    }
+}
+
+/// Secrets are written to stdin line by line, so line breaks and NUL are
+/// never allowed.
+pub(crate) fn validate_secret(label: &str, value: &str) -> Result<(), CredentialError> {
+    if value.is_empty() || value.len() > MAX_SECRET_BYTES {
+        return Err(CredentialError::Validation(format!(
+            "{label} must be between 1 and {MAX_SECRET_BYTES} bytes"
+        )));
+    }
+    if value
+        .bytes()
+        .any(|byte| matches!(byte, b'\0' | b'\r' | b'\n'))
+    {
+        return Err(CredentialError::Validation(format!(
+            "{label} must not contain NUL or line breaks"
+        )));
+    }
+    Ok(())
+}
+
+/// Names passed to remote tooling: printable, single-line and bounded.
+pub(crate) fn validate_name(label: &str, value: &str) -> Result<(), CredentialError> {
+    if value.trim().is_empty() || value.len() > 512 || value.chars().any(char::is_control) {
+        return Err(CredentialError::Validation(format!(
+            "{label} is empty, too long, or contains control characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Shell-quote a string for safe embedding in a POSIX shell command.
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct ScriptedRotator {
+        fail_apply: bool,
+        fail_verify: bool,
+        fail_rollback: bool,
+        fail_finalize: bool,
+        rolled_back: AtomicBool,
+    }
+
+    #[async_trait]
+    impl Rotator for ScriptedRotator {
+        async fn apply(&self, _job: &RotationJob) -> Result<(), CredentialError> {
+            scripted(self.fail_apply, "apply refused")
+        }
+
+        async fn verify(&self, _job: &RotationJob) -> Result<(), CredentialError> {
+            scripted(self.fail_verify, "login refused")
+        }
+
+        async fn rollback(&self, job: &RotationJob) -> Result<(), CredentialError> {
+            job.current_secret()?;
+            self.rolled_back.store(true, Ordering::SeqCst);
+            scripted(self.fail_rollback, "rollback refused")
+        }
+
+        async fn finalize(&self, _job: &RotationJob) -> Result<(), CredentialError> {
+            scripted(self.fail_finalize, "old key still present")
+        }
+    }
+
+    fn scripted(fail: bool, message: &str) -> Result<(), CredentialError> {
+        if fail {
+            Err(CredentialError::Rotation(message.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn scripted_engine(rotator: ScriptedRotator) -> (RotationEngine, Arc<ScriptedRotator>) {
+        let rotator = Arc::new(rotator);
+        let mut engine = RotationEngine::new();
+        engine.register(RotatorKind::Postgres, rotator.clone());
+        (engine, rotator)
+    }
+
+    pub(super) fn record(credential_type: CredentialType) -> CredentialRecord {
+        CredentialRecord {
+            id: "cred-1".to_string(),
+            connection_id: "conn-1".to_string(),
+            credential_type,
+            label: "db".to_string(),
+            username: Some("app".to_string()),
+            fingerprint: CredentialTracker::compute_fingerprint("old"),
+            created_at: Utc::now(),
+            last_rotated_at: None,
+            expires_at: None,
+            rotation_policy_id: None,
+            group_id: None,
+            strength: None,
+            notes: String::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn request() -> RotationRequest {
+        RotationRequest {
+            credential_id: "cred-1".to_string(),
+            target: RotationTarget::Postgres {
+                endpoint: SqlEndpoint {
+                    host: "db.example".to_string(),
+                    port: 5432,
+                    database: None,
+                    admin_user: "postgres".to_string(),
+                    admin_password: "admin".to_string(),
+                    timeout_secs: 5,
+                },
+                role: "app".to_string(),
+            },
+            current_secret: Some("old".to_string()),
+            new_secret: None,
+            requested_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn successful_rotation_returns_new_secret() {
+        let (engine, _) = scripted_engine(ScriptedRotator::default());
+        let report = engine
+            .rotate(&record(CredentialType::Password), &request())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RotationStatus::Rotated);
+        let secret = report.new_secret.clone().unwrap();
+        assert_eq!(secret.len(), GENERATED_PASSWORD_LEN);
+        assert_eq!(
+            report.new_fingerprint.as_deref(),
+            Some(CredentialTracker::compute_fingerprint(&secret).as_str())
+        );
+        let phases: Vec<_> = report.steps.iter().map(|s| s.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                RotationPhase::Apply,
+                RotationPhase::Verify,
+                RotationPhase::Finalize
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_verification_rolls_back() {
+        let (engine, rotator) = scripted_engine(ScriptedRotator {
+            fail_verify: true,
+            ..Default::default()
+        });
+        let report = engine
+            .rotate(&record(CredentialType::Password), &request())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RotationStatus::RolledBack);
+        assert!(rotator.rolled_back.load(Ordering::SeqCst));
+        assert!(report.new_secret.is_none());
+        assert!(report.new_fingerprint.is_none());
+        assert_eq!(report.failure(), Some("Rotation error: login refused"));
+    }
+
+    #[tokio::test]
+    async fn failed_rollback_and_apply_are_distinguished() {
+        let (engine, _) = scripted_engine(ScriptedRotator {
+            fail_verify: true,
+            fail_rollback: true,
+            ..Default::default()
+        });
+        let report = engine
+            .rotate(&record(CredentialType::Password), &request())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RotationStatus::RollbackFailed);
+
+        let (engine, rotator) = scripted_engine(ScriptedRotator {
+            fail_apply: true,
+            ..Default::default()
+        });
+        let report = engine
+            .rotate(&record(CredentialType::Password), &request())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RotationStatus::Failed);
+        assert_eq!(report.steps.len(), 1);
+        assert!(!rotator.rolled_back.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn finalize_failure_is_a_warning() {
+        let (engine, _) = scripted_engine(ScriptedRotator {
+            fail_finalize: true,
+            ..Default::default()
+        });
+        let report = engine
+            .rotate(&record(CredentialType::Password), &request())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RotationStatus::Rotated);
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn preconditions_are_checked_before_touching_the_target() {
+        let (engine, _) = scripted_engine(ScriptedRotator::default());
+        assert!(engine
+            .rotate(&record(CredentialType::SshKey), &request())
+            .await
+            .is_err());
+
+        let mut without_current = request();
+        without_current.current_secret = None;
+        assert!(engine
+            .rotate(&record(CredentialType::Password), &without_current)
+            .await
+            .is_err());
+
+        let mut bad_secret = request();
+        bad_secret.new_secret = Some("two\nlines".to_string());
+        assert!(engine
+            .rotate(&record(CredentialType::Password), &bad_secret)
+            .await
+            .is_err());
+
+        let mut ldap = request();
+        ldap.target = RotationTarget::Ldap {
+            ssh: None,
+            ldap_uri: "ldap://dc".to_string(),
+            bind_dn: "cn=admin".to_string(),
+            bind_password: "x".to_string(),
+            user_dn: "uid=app".to_string(),
+            start_tls: false,
+        };
+        assert!(engine
+            .rotate(&record(CredentialType::Password), &ldap)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn service_records_outcome_in_audit_and_group() {
+        use crate::service::CredentialService;
+
+        let mut service = CredentialService::new();
+        let (engine, _) = scripted_engine(ScriptedRotator::default());
+        service.rotation = Arc::new(engine);
+        service
+            .tracker
+            .add_credential(record(CredentialType::Password))
+            .unwrap();
+        service
+            .groups
+            .create_group(CredentialGroup {
+                id: "g1".to_string(),
+                name: "db accounts".to_string(),
+                description: String::new(),
+                credential_ids: vec!["cred-1".to_string()],
+                shared_policy_id: None,
+                auto_rotate_together: true,
+                last_rotation: None,
+            })
+            .unwrap();
+        let state = Arc::new(tokio::sync::Mutex::new(service));
+
+        let mut req = request();
+        req.requested_by = Some("alice".to_string());
+        let report = CredentialService::rotate(&state, req).await.unwrap();
+        assert_eq!(report.status, RotationStatus::Rotated);
+
+        let svc = state.lock().await;
+        let stored = svc.tracker.get_credential("cred-1").unwrap();
+        assert_eq!(Some(&stored.fingerprint), report.new_fingerprint.as_ref());
+        assert!(stored.last_rotated_at.is_some());
+        assert!(stored.strength >= Some(PasswordStrength::Strong));
+        let entry = svc.audit.get_by_action(AuditAction::Rotated)[0];
+        assert_eq!(entry.user, "alice");
+        assert!(!entry
+            .details
+            .contains(report.new_secret.as_deref().unwrap()));
+        let group = svc.groups.get_group("g1").unwrap();
+        let last = group.last_rotation.as_ref().unwrap();
+        assert_eq!(last.status, RotationStatus::Rotated);
+        assert_eq!(last.rotator, RotatorKind::Postgres);
+    }
+
+    #[tokio::test]
+    async fn service_records_rollback_without_touching_the_record() {
+        use crate::service::CredentialService;
+
+        let mut service = CredentialService::new();
+        let (engine, _) = scripted_engine(ScriptedRotator {
+            fail_verify: true,
+            ..Default::default()
+        });
+        service.rotation = Arc::new(engine);
+        service
+            .tracker
+            .add_credential(record(CredentialType::Password))
+            .unwrap();
+        let state = Arc::new(tokio::sync::Mutex::new(service));
+
+        let report = CredentialService::rotate(&state, request()).await.unwrap();
+        assert_eq!(report.status, RotationStatus::RolledBack);
+        let svc = state.lock().await;
+        let stored = svc.tracker.get_credential("cred-1").unwrap();
+        assert_eq!(
+            stored.fingerprint,
+            CredentialTracker::compute_fingerprint("old")
+        );
+        assert!(stored.last_rotated_at.is_none());
+        assert_eq!(svc.audit.get_by_action(AuditAction::RolledBack).len(), 1);
+    }
+
+    #[test]
+    fn concurrent_rotation_of_one_credential_is_refused() {
+        let set = Mutex::new(HashSet::new());
+        let guard = InFlight::acquire(&set, "cred-1").unwrap();
+        assert!(InFlight::acquire(&set, "cred-1").is_err());
+        assert!(InFlight::acquire(&set, "cred-2").is_ok());
+        drop(guard);
+        assert!(InFlight::acquire(&set, "cred-1").is_ok());
+    }
+
+    #[test]
+    fn generated_passwords_are_strong_and_safe() {
+        for _ in 0..50 {
+            let password = generate_password(GENERATED_PASSWORD_LEN);
+            assert_eq!(password.len(), GENERATED_PASSWORD_LEN);
+            assert!(password.bytes().all(|b| PASSWORD_ALPHABET.contains(&b)));
+            assert!(
+                CredentialTracker::calculate_password_strength(&password)
+                    >= PasswordStrength::Strong
+            );
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! # Rotation Transports
+//!
+//! The narrow I/O surface rotators depend on: running a shell command on a
+//! host (over SSH, or locally) and running a SQL statement as a given login.
+//! Rotators take these as trait objects so the protocol logic can be tested
+//! without live servers.
+
+use crate::error::CredentialError;
+use crate::types::{SqlEndpoint, SshTarget};
+use async_trait::async_trait;
+use sorng_ssh::ssh::integration::{
+    output_bounded_with_input, ExternalSshConfig, IntegrationSshSession,
+};
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection};
+use sqlx::postgres::{PgConnectOptions, PgConnection};
+use sqlx::{Connection, Executor};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Upper bound on stderr quoted back in an error.
+const MAX_ERROR_OUTPUT_CHARS: usize = 512;
+
+// ── Commands ────────────────────────────────────────────────────────
+
+/// Runs POSIX shell command lines for rotators.
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// Run `command` on `host`, or on this machine when `host` is `None`,
+    /// writing `input` to its stdin. A non-zero exit status is an error.
+    async fn run(
+        &self,
+        host: Option<&SshTarget>,
+        command: &str,
+        input: Option<Vec<u8>>,
+    ) -> Result<String, CredentialError>;
+}
+
+/// Production runner: a one-shot [`IntegrationSshSession`] per command, or
+/// a bounded local `sh -c`.
+pub struct SshCommandRunner;
+
+#[async_trait]
+impl CommandRunner for SshCommandRunner {
+    async fn run(
+        &self,
+        host: Option<&SshTarget>,
+        command: &str,
+        input: Option<Vec<u8>>,
+    ) -> Result<String, CredentialError> {
+        match host {
+            Some(ssh) => run_remote(ssh, command, input.unwrap_or_default()).await,
+            None => run_local(command, input.unwrap_or_default()).await,
+        }
+    }
+}
+
+async fn run_remote(
+    ssh: &SshTarget,
+    command: &str,
+    input: Vec<u8>,
+) -> Result<String, CredentialError> {
+    let timeout_secs = ssh.timeout_secs.clamp(1, 300);
+    let session = IntegrationSshSession::new(ExternalSshConfig {
+        host: ssh.host.trim(),
+        username: ssh.username.trim(),
+        port: ssh.port,
+        private_key: ssh.private_key_path.as_deref(),
+        password: ssh.password.as_deref(),
+        connect_timeout_secs: timeout_secs,
+    });
+    let result = session
+        .execute_with_input(command, input, Some(timeout_secs.saturating_mul(1_000)))
+        .await;
+    let _ = session.disconnect().await;
+    result.map_err(|error| {
+        CredentialError::Rotation(format!(
+            "{}@{}: {}",
+            ssh.username.trim(),
+            ssh.host.trim(),
+            bounded(&error)
+        ))
+    })
+}
+
+async fn run_local(command: &str, mut input: Vec<u8>) -> Result<String, CredentialError> {
+    let mut child = Command::new("sh");
+    child.arg("-c").arg(command);
+    let output = output_bounded_with_input(&mut child, &input).await;
+    input.fill(0);
+    let output = output.map_err(|error| CredentialError::Rotation(error.to_string()))?;
+    if !output.status.success() {
+        return Err(CredentialError::Rotation(format!(
+            "local command failed ({}): {}",
+            output.status,
+            bounded(String::from_utf8_lossy(&output.stderr).trim())
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn bounded(text: &str) -> String {
+    text.chars().take(MAX_ERROR_OUTPUT_CHARS).collect()
+}
+
+// ── SQL ─────────────────────────────────────────────────────────────
+
+/// Database protocol spoken by a [`SqlEndpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Mysql,
+    Postgres,
+}
+
+/// Runs single SQL statements for rotators.
+#[async_trait]
+pub trait SqlClient: Send + Sync {
+    /// Connect to `endpoint` as `user` / `password` and run `statement`.
+    async fn execute(
+        &self,
+        dialect: SqlDialect,
+        endpoint: &SqlEndpoint,
+        user: &str,
+        password: &str,
+        statement: &str,
+    ) -> Result<(), CredentialError>;
+}
+
+/// Production client: a dedicated sqlx connection per statement.
+pub struct SqlxClient;
+
+#[async_trait]
+impl SqlClient for SqlxClient {
+    async fn execute(
+        &self,
+        dialect: SqlDialect,
+        endpoint: &SqlEndpoint,
+        user: &str,
+        password: &str,
+        statement: &str,
+    ) -> Result<(), CredentialError> {
+        let deadline = Duration::from_secs(endpoint.timeout_secs.clamp(1, 300));
+        let work = async {
+            match dialect {
+                SqlDialect::Mysql => {
+                    let options = MySqlConnectOptions::new()
+                        .host(&endpoint.host)
+                        .port(endpoint.port)
+                        .username(user)
+                        .password(password);
+                    execute_mysql(&options, statement).await
+                }
+                SqlDialect::Postgres => {
+                    let mut options = PgConnectOptions::new()
+                        .host(&endpoint.host)
+                        .port(endpoint.port)
+                        .username(user)
+                        .password(password);
+                    if let Some(database) = &endpoint.database {
+                        options = options.database(database);
+                    }
+                    execute_postgres(&options, statement).await
+                }
+            }
+        };
+        match tokio::time::timeout(deadline, work).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(error)) => Err(CredentialError::Rotation(format!(
+                "{user}@{}:{}: {}",
+                endpoint.host,
+                endpoint.port,
+                bounded(&error.to_string())
+            ))),
+            Err(_) => Err(CredentialError::Rotation(format!(
+                "{user}@{}:{}: timed out",
+                endpoint.host, endpoint.port
+            ))),
+        }
+    }
+}
+
+async fn execute_mysql(options: &MySqlConnectOptions, statement: &str) -> Result<(), sqlx::Error> {
+    let mut connection = MySqlConnection::connect_with(options).await?;
+    connection.execute(statement).await?;
+    connection.close().await
+}
+
+async fn execute_postgres(options: &PgConnectOptions, statement: &str) -> Result<(), sqlx::Error> {
+    let mut connection = PgConnection::connect_with(options).await?;
+    connection.execute(statement).await?;
+    connection.close().await
+}
@@ -1,8 +1,8 @@
 //! # Credential Service
 //!
 //! Top-level orchestration service that ties together the tracker, policy
-//! engine, group manager, audit log, and alert manager. Provides the
-//! Tauri-compatible `Arc<Mutex<…>>` state handle.
+//! engine, group manager, audit log, alert manager, and rotation engine.
+//! Provides the Tauri-compatible `Arc<Mutex<…>>` state handle.
 
 use crate::alerts::AlertManager;
 use crate::audit::AuditLog;
@@ -10,6 +10,7 @@ use crate::error::CredentialError;
 use crate::groups::GroupManager;
 use crate::persistence::{self, PersistedCredentialState};
 use crate::policies::PolicyEngine;
+use crate::rotation::RotationEngine;
 use crate::tracker::CredentialTracker;
 use crate::types::*;
 use chrono::Utc;
@@ -17,6 +18,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
 /// Tauri-managed state handle for the credential service.
 pub type CredentialServiceState = Arc<Mutex<CredentialService>>;
@@ -35,6 +37,8 @@ pub struct CredentialService {
     pub alerts: AlertManager,
     /// Global configuration.
     pub config: CredentialsConfig,
+    /// Automated rotation executors (not persisted).
+    pub rotation: Arc<RotationEngine>,
     /// Canonical app-managed persistence path. `None` is reserved for pure
     /// library/tests created with `new` or `with_config`.
     storage_path: Option<PathBuf>,
@@ -50,6 +54,7 @@ impl CredentialService {
             audit: AuditLog::new(10_000),
             alerts: AlertManager::new(),
             config: CredentialsConfig::default(),
+            rotation: Arc::new(RotationEngine::with_defaults()),
             storage_path: None,
         }
     }
@@ -63,6 +68,7 @@ impl CredentialService {
             audit: AuditLog::new(10_000),
             alerts: AlertManager::new(),
             config,
+            rotation: Arc::new(RotationEngine::with_defaults()),
             storage_path: None,
         }
     }
@@ -129,6 +135,7 @@ impl CredentialService {
                 alerts: snapshot.alerts,
             },
             config: snapshot.config,
+            rotation: Arc::new(RotationEngine::with_defaults()),
             storage_path: Some(storage_path),
         }
     }
@@ -146,6 +153,7 @@ impl CredentialService {
 
     fn restore_snapshot(&mut self, snapshot: PersistedCredentialState) {
         let storage_path = self.storage_path.clone();
+        let rotation = self.rotation.clone();
         *self = Self::from_snapshot(
             snapshot,
             storage_path
@@ -153,6 +161,7 @@ impl CredentialService {
                 .unwrap_or_else(|| PathBuf::from(persistence::STATE_FILENAME)),
         );
         self.storage_path = storage_path;
+        self.rotation = rotation;
     }
 
     fn persist_current(&self) -> Result<(), CredentialError> {
@@ -166,6 +175,126 @@ impl CredentialService {
         self.policy_engine = PolicyEngine::with_policies(self.tracker.policies.clone());
     }
 
+    /// Rotate a credential on its target system and record the outcome.
+    ///
+    /// The service lock is released while the rotator talks to the remote
+    /// system. Once the target may have changed the report is always
+    /// returned — it carries the only copy of the new secret — and a
+    /// failure to record it is added to its warnings instead.
+    pub async fn rotate(
+        state: &CredentialServiceState,
+        request: RotationRequest,
+    ) -> Result<RotationReport, CredentialError> {
+        let (engine, record) = {
+            let svc = state.lock().await;
+            let record = svc.tracker.get_credential(&request.credential_id)?.clone();
+            (svc.rotation.clone(), record)
+        };
+        let mut report = engine.rotate(&record, &request).await?;
+        let user = request
+            .requested_by
+            .clone()
+            .unwrap_or_else(|| "system".to_string());
+        let mut svc = state.lock().await;
+        if let Err(error) = svc.mutate_and_persist(|svc| svc.record_rotation_report(&report, &user))
+        {
+            report
+                .warnings
+                .push(format!("the rotation could not be recorded: {error}"));
+        }
+        Ok(report)
+    }
+
+    /// Apply a rotation outcome: refresh the record when the new credential
+    /// is live, and write the result to the audit log and the linked group.
+    pub fn record_rotation_report(
+        &mut self,
+        report: &RotationReport,
+        user: &str,
+    ) -> Result<(), CredentialError> {
+        let strength_checking = self.config.strength_checking;
+        let record = self
+            .tracker
+            .credentials
+            .get_mut(&report.credential_id)
+            .ok_or_else(|| CredentialError::NotFound(report.credential_id.clone()))?;
+        if report.status == RotationStatus::Rotated {
+            record.last_rotated_at = Some(report.finished_at);
+            if let Some(fingerprint) = &report.new_fingerprint {
+                record.fingerprint = fingerprint.clone();
+            }
+            if strength_checking && report.rotator != RotatorKind::SshKey {
+                record.strength = report
+                    .new_secret
+                    .as_deref()
+                    .map(CredentialTracker::calculate_password_strength);
+            }
+        }
+        let group_id = record.group_id.clone();
+
+        let failure = report.failure().unwrap_or("unknown error");
+        let (action, details) = match report.status {
+            RotationStatus::Rotated if report.warnings.is_empty() => (
+                AuditAction::Rotated,
+                format!("Rotated and verified by the {} rotator", report.rotator),
+            ),
+            RotationStatus::Rotated => (
+                AuditAction::Rotated,
+                format!(
+                    "Rotated and verified by the {} rotator with warnings: {}",
+                    report.rotator,
+                    report.warnings.join("; ")
+                ),
+            ),
+            RotationStatus::Failed => (
+                AuditAction::RotationFailed,
+                format!("{} rotation failed: {failure}", report.rotator),
+            ),
+            RotationStatus::RolledBack => (
+                AuditAction::RolledBack,
+                format!(
+                    "{} rotation failed verification and was rolled back: {failure}",
+                    report.rotator
+                ),
+            ),
+            RotationStatus::RollbackFailed => (
+                AuditAction::RotationFailed,
+                format!(
+                    "{} rotation failed verification and could not be rolled back; \
+                     manual attention required: {failure}",
+                    report.rotator
+                ),
+            ),
+        };
+        self.audit.log_action(CredentialAuditEntry {
+            id: Uuid::new_v4().to_string(),
+            credential_id: report.credential_id.clone(),
+            action,
+            timestamp: report.finished_at,
+            details: details.clone(),
+            user: user.to_string(),
+        });
+
+        let group = match group_id {
+            Some(id) => self.groups.groups.get_mut(&id),
+            None => self
+                .groups
+                .groups
+                .values_mut()
+                .find(|group| group.credential_ids.contains(&report.credential_id)),
+        };
+        if let Some(group) = group {
+            group.last_rotation = Some(GroupRotationRecord {
+                credential_id: report.credential_id.clone(),
+                rotator: report.rotator,
+                status: report.status,
+                finished_at: report.finished_at,
+                details,
+            });
+        }
+        Ok(())
+    }
+
     /// Compute aggregate statistics across all tracked credentials.
     pub fn get_stats(&self) -> CredentialStats {
         let now = Utc::now();
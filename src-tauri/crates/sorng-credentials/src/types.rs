@@ -5,6 +5,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use zeroize::Zeroize;
 
 // ── Credential Type ─────────────────────────────────────────────────
 
@@ -168,6 +169,24 @@ pub struct CredentialGroup {
     pub shared_policy_id: Option<String>,
     /// Whether all credentials in the group should be rotated together.
     pub auto_rotate_together: bool,
+    /// Outcome of the most recent automated rotation of any member.
+    #[serde(default)]
+    pub last_rotation: Option<GroupRotationRecord>,
+}
+
+/// Summary of an automated rotation, recorded on the member's group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRotationRecord {
+    /// The member credential that was rotated.
+    pub credential_id: String,
+    /// Which rotator performed the rotation.
+    pub rotator: RotatorKind,
+    /// How the rotation ended.
+    pub status: RotationStatus,
+    /// When the rotation finished.
+    pub finished_at: DateTime<Utc>,
+    /// Human-readable outcome (never contains secret material).
+    pub details: String,
 }
 
 // ── Expiry Status ───────────────────────────────────────────────────
@@ -288,6 +307,8 @@ pub enum AuditAction {
     StrengthChecked,
     GroupChanged,
     AlertAcknowledged,
+    RotationFailed,
+    RolledBack,
 }
 
 impl std::fmt::Display for AuditAction {
@@ -302,10 +323,329 @@ impl std::fmt::Display for AuditAction {
             Self::StrengthChecked => write!(f, "Strength Checked"),
             Self::GroupChanged => write!(f, "Group Changed"),
             Self::AlertAcknowledged => write!(f, "Alert Acknowledged"),
+            Self::RotationFailed => write!(f, "Rotation Failed"),
+            Self::RolledBack => write!(f, "Rolled Back"),
+        }
+    }
+}
+
+// ── Automated Rotation ──────────────────────────────────────────────
+
+/// The built-in rotation executors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotatorKind {
+    LinuxPassword,
+    SshKey,
+    Mysql,
+    Postgres,
+    Ldap,
+}
+
+impl RotatorKind {
+    /// Credential types this rotator is able to rotate.
+    pub fn supports(&self, credential_type: CredentialType) -> bool {
+        match self {
+            Self::SshKey => credential_type == CredentialType::SshKey,
+            Self::LinuxPassword | Self::Mysql | Self::Postgres | Self::Ldap => matches!(
+                credential_type,
+                CredentialType::Password | CredentialType::Passphrase
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for RotatorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LinuxPassword => write!(f, "Linux Password"),
+            Self::SshKey => write!(f, "SSH Key"),
+            Self::Mysql => write!(f, "MySQL"),
+            Self::Postgres => write!(f, "PostgreSQL"),
+            Self::Ldap => write!(f, "LDAP"),
+        }
+    }
+}
+
+/// An SSH login used to reach a host during rotation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SshTarget {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub private_key_path: Option<String>,
+    #[serde(default = "default_rotation_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl std::fmt::Debug for SshTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SshTarget")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("private_key_path", &self.private_key_path)
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+/// A database server reached with an administrative login.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SqlEndpoint {
+    pub host: String,
+    pub port: u16,
+    /// Database to connect to (PostgreSQL); ignored by MySQL.
+    #[serde(default)]
+    pub database: Option<String>,
+    pub admin_user: String,
+    pub admin_password: String,
+    #[serde(default = "default_rotation_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl std::fmt::Debug for SqlEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqlEndpoint")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("admin_user", &self.admin_user)
+            .field("admin_password", &"<redacted>")
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+impl Drop for SshTarget {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+impl Drop for SqlEndpoint {
+    fn drop(&mut self) {
+        self.admin_password.zeroize();
+    }
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+fn default_rotation_timeout() -> u64 {
+    30
+}
+
+fn default_mysql_account_host() -> String {
+    "%".to_string()
+}
+
+/// Where and how a credential is rotated. Administrative secrets carried
+/// here are used for the duration of one rotation and never persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RotationTarget {
+    /// Local account password, set with `chpasswd` over an admin SSH login
+    /// and verified by logging in as the account.
+    LinuxPassword {
+        ssh: SshTarget,
+        account: String,
+        #[serde(default)]
+        use_sudo: bool,
+    },
+    /// Key rollover for the SSH login itself: the new key is appended to
+    /// `authorized_keys`, proven by a fresh login, then the old key removed.
+    SshKey {
+        ssh: SshTarget,
+        /// OpenSSH public key being retired. Derived from
+        /// `ssh.private_key_path` when omitted.
+        #[serde(default)]
+        previous_public_key: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+    Mysql {
+        endpoint: SqlEndpoint,
+        account: String,
+        #[serde(default = "default_mysql_account_host")]
+        account_host: String,
+    },
+    Postgres {
+        endpoint: SqlEndpoint,
+        role: String,
+    },
+    /// RFC 3062 password modify through `ldappasswd`, run locally or on an
+    /// SSH-reachable host, and verified with `ldapwhoami`.
+    Ldap {
+        #[serde(default)]
+        ssh: Option<SshTarget>,
+        ldap_uri: String,
+        bind_dn: String,
+        bind_password: String,
+        user_dn: String,
+        #[serde(default)]
+        start_tls: bool,
+    },
+}
+
+impl RotationTarget {
+    /// The rotator responsible for this target.
+    pub fn kind(&self) -> RotatorKind {
+        match self {
+            Self::LinuxPassword { .. } => RotatorKind::LinuxPassword,
+            Self::SshKey { .. } => RotatorKind::SshKey,
+            Self::Mysql { .. } => RotatorKind::Mysql,
+            Self::Postgres { .. } => RotatorKind::Postgres,
+            Self::Ldap { .. } => RotatorKind::Ldap,
         }
     }
 }
 
+impl Drop for RotationTarget {
+    fn drop(&mut self) {
+        if let Self::Ldap { bind_password, .. } = self {
+            bind_password.zeroize();
+        }
+    }
+}
+
+/// A request to rotate one tracked credential.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RotationRequest {
+    pub credential_id: String,
+    pub target: RotationTarget,
+    /// The value being replaced. Password rotators need it to roll back.
+    #[serde(default)]
+    pub current_secret: Option<String>,
+    /// Explicit replacement password; one is generated when omitted.
+    #[serde(default)]
+    pub new_secret: Option<String>,
+    /// Who asked for the rotation (recorded in the audit log).
+    #[serde(default)]
+    pub requested_by: Option<String>,
+}
+
+impl std::fmt::Debug for RotationRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RotationRequest")
+            .field("credential_id", &self.credential_id)
+            .field("target", &self.target)
+            .field("requested_by", &self.requested_by)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RotationRequest {
+    fn drop(&mut self) {
+        self.current_secret.zeroize();
+        self.new_secret.zeroize();
+    }
+}
+
+/// How a rotation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStatus {
+    /// The new credential is live and verified.
+    Rotated,
+    /// The new credential could not be applied; nothing changed.
+    Failed,
+    /// Verification failed and the previous credential was restored.
+    RolledBack,
+    /// Verification failed and restoring the previous credential also
+    /// failed — the target needs manual attention.
+    RollbackFailed,
+}
+
+impl std::fmt::Display for RotationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rotated => write!(f, "Rotated"),
+            Self::Failed => write!(f, "Failed"),
+            Self::RolledBack => write!(f, "Rolled Back"),
+            Self::RollbackFailed => write!(f, "Rollback Failed"),
+        }
+    }
+}
+
+/// A step of the two-phase rotation protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationPhase {
+    Apply,
+    Verify,
+    Rollback,
+    Finalize,
+}
+
+/// Result of one rotation step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationStep {
+    pub phase: RotationPhase,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Outcome of a rotation, returned to the caller. `new_secret` is the only
+/// copy of the new credential and must be stored by the caller (e.g. in the
+/// vault); it is never persisted by this crate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RotationReport {
+    pub credential_id: String,
+    pub rotator: RotatorKind,
+    pub status: RotationStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub steps: Vec<RotationStep>,
+    /// Non-fatal problems, e.g. an old SSH key that could not be removed.
+    pub warnings: Vec<String>,
+    /// SHA-256 fingerprint of the new credential when it is live.
+    pub new_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_secret: Option<String>,
+    /// OpenSSH public key matching a rotated SSH private key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_public_key: Option<String>,
+}
+
+impl RotationReport {
+    /// The message of the first failed step, if any.
+    pub fn failure(&self) -> Option<&str> {
+        self.steps
+            .iter()
+            .find(|step| !step.success)
+            .map(|step| step.message.as_str())
+    }
+}
+
+impl std::fmt::Debug for RotationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RotationReport")
+            .field("credential_id", &self.credential_id)
+            .field("rotator", &self.rotator)
+            .field("status", &self.status)
+            .field("started_at", &self.started_at)
+            .field("finished_at", &self.finished_at)
+            .field("steps", &self.steps)
+            .field("warnings", &self.warnings)
+            .field("new_fingerprint", &self.new_fingerprint)
+            .field("new_public_key", &self.new_public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RotationReport {
+    fn drop(&mut self) {
+        self.new_secret.zeroize();
+    }
+}
+
 // ── Statistics ───────────────────────────────────────────────────────
 
 /// Aggregate statistics about all tracked credentials.
@@ -2,6 +2,7 @@
 //! Docker API HTTP client.
 
 use crate::error::{DockerError, DockerErrorKind, DockerResult};
+use crate::hijack::Transport;
 use crate::types::*;
 use reqwest::header::CONTENT_TYPE;
 use std::time::Duration;
@@ -20,10 +21,13 @@ fn identity_from_pem_parts(
 }
 
 /// Docker API client wrapping an HTTP client + base URL.
+#[derive(Clone)]
 pub struct DockerClient {
     pub http: reqwest::Client,
     pub base_url: String,
     pub api_version: String,
+    /// Raw connection to the same daemon, for hijacked and streamed requests.
+    pub(crate) transport: Transport,
 }
 
 impl DockerClient {
@@ -69,6 +73,7 @@ impl DockerClient {
             http,
             base_url,
             api_version: "v1.45".to_string(),
+            transport: Transport::from_config(config)?,
         })
     }
 
@@ -195,7 +200,7 @@ impl DockerClient {
         let status = resp.status().as_u16();
         if !resp.status().is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(self.map_status_error(status, &body));
+            return Err(map_status_error(status, &body));
         }
         let text = resp.text().await?;
         serde_json::from_str(&text).map_err(|e| {
@@ -207,7 +212,7 @@ impl DockerClient {
         let status = resp.status().as_u16();
         if !resp.status().is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(self.map_status_error(status, &body));
+            return Err(map_status_error(status, &body));
         }
         Ok(resp.text().await?)
     }
@@ -216,31 +221,11 @@ impl DockerClient {
         let status = resp.status().as_u16();
         if !resp.status().is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(self.map_status_error(status, &body));
+            return Err(map_status_error(status, &body));
         }
         Ok(())
     }
 
-    fn map_status_error(&self, status: u16, body: &str) -> DockerError {
-        // Try to extract message from {"message":"..."} JSON
-        let msg = serde_json::from_str::<serde_json::Value>(body)
-            .ok()
-            .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(String::from))
-            .unwrap_or_else(|| body.to_string());
-
-        match status {
-            304 => DockerError::other(&format!("Not modified: {}", msg)),
-            400 => DockerError::validation(&msg),
-            401 => DockerError::auth(&msg),
-            403 => DockerError::forbidden(&msg),
-            404 => DockerError::not_found(&msg),
-            409 => DockerError::conflict(&msg),
-            500 => DockerError::api(500, &msg),
-            503 => DockerError::connection(&format!("Service unavailable: {}", msg)),
-            _ => DockerError::api(status, &msg),
-        }
-    }
-
     // ── Convenience ───────────────────────────────────────────────
 
     /// Ping the Docker daemon.
@@ -263,3 +248,25 @@ impl DockerClient {
         self.get("/info").await
     }
 }
+
+/// Map an error response to a [`DockerError`], preferring the daemon's
+/// `{"message": ...}`.
+pub(crate) fn map_status_error(status: u16, body: &str) -> DockerError {
+    // Try to extract message from {"message":"..."} JSON
+    let msg = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(String::from))
+        .unwrap_or_else(|| body.to_string());
+
+    match status {
+        304 => DockerError::other(&format!("Not modified: {}", msg)),
+        400 => DockerError::validation(&msg),
+        401 => DockerError::auth(&msg),
+        403 => DockerError::forbidden(&msg),
+        404 => DockerError::not_found(&msg),
+        409 => DockerError::conflict(&msg),
+        500 => DockerError::api(500, &msg),
+        503 => DockerError::connection(&format!("Service unavailable: {}", msg)),
+        _ => DockerError::api(status, &msg),
+    }
+}
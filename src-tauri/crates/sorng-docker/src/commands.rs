@@ -349,6 +349,115 @@ pub async fn docker_prune_containers(
     svc.prune_containers(&id).await.map_err(|e| e.to_string())
 }
 
+// ── Interactive exec / attach ─────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn docker_exec_interactive(
+    state: State<'_, DockerServiceState>,
+    id: String,
+    container_id: String,
+    config: ExecConfig,
+) -> Result<TerminalSession, String> {
+    let svc = state.lock().await;
+    svc.exec_interactive(&id, &container_id, &config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_attach_container(
+    state: State<'_, DockerServiceState>,
+    id: String,
+    container_id: String,
+    options: Option<ContainerAttachOptions>,
+) -> Result<TerminalSession, String> {
+    let svc = state.lock().await;
+    svc.attach_container(&id, &container_id, &options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_exec_send_input(
+    state: State<'_, DockerServiceState>,
+    session_id: String,
+    data: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_send_input(&session_id, data.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_exec_resize(
+    state: State<'_, DockerServiceState>,
+    session_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_resize(&session_id, cols, rows)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_exec_close_stdin(
+    state: State<'_, DockerServiceState>,
+    session_id: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_close_stdin(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_exec_close(
+    state: State<'_, DockerServiceState>,
+    session_id: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_close(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_list_exec_sessions(
+    state: State<'_, DockerServiceState>,
+) -> Result<Vec<TerminalSession>, String> {
+    let svc = state.lock().await;
+    Ok(svc.list_exec_sessions())
+}
+
+// ── Live event streams ────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn docker_start_event_stream(
+    state: State<'_, DockerServiceState>,
+    id: String,
+    filter: Option<DockerEventFilter>,
+) -> Result<EventStreamInfo, String> {
+    let mut svc = state.lock().await;
+    svc.start_event_stream(&id, &filter.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_stop_event_stream(
+    state: State<'_, DockerServiceState>,
+    stream_id: String,
+) -> Result<(), String> {
+    let mut svc = state.lock().await;
+    svc.stop_event_stream(&stream_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn docker_list_event_streams(
+    state: State<'_, DockerServiceState>,
+) -> Result<Vec<EventStreamInfo>, String> {
+    let mut svc = state.lock().await;
+    Ok(svc.list_event_streams())
+}
+
 // ── Images ────────────────────────────────────────────────────────────────────
 
 #[tauri::command]
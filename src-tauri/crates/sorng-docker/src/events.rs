@@ -0,0 +1,361 @@
+// ── sorng-docker/src/events.rs ────────────────────────────────────────────────
+//! Live `/events` subscriptions for container, image, network and volume
+//! state changes.
+//!
+//! Each event is emitted as `docker-event` (`{ stream_id, event }`).  A
+//! dropped stream reconnects with `since` set to the last event seen, and
+//! events already forwarded are skipped, so a daemon restart does not lose
+//! or repeat anything.  `docker-event-stream-closed` (`{ stream_id, error }`)
+//! reports a stream ending for good; stopping a stream emits nothing.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use sorng_core::events::DynEventEmitter;
+use tokio::task::JoinHandle;
+
+use crate::client::DockerClient;
+use crate::error::{DockerError, DockerErrorKind, DockerResult};
+use crate::hijack::{self, Body};
+use crate::types::*;
+
+const EVENT: &str = "docker-event";
+const CLOSED_EVENT: &str = "docker-event-stream-closed";
+
+const RETRY_MIN: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct StreamEvent<'a> {
+    stream_id: &'a str,
+    event: DockerEvent,
+}
+
+#[derive(Serialize)]
+struct StreamClosed<'a> {
+    stream_id: &'a str,
+    error: Option<String>,
+}
+
+struct StreamEntry {
+    info: EventStreamInfo,
+    task: JoinHandle<()>,
+}
+
+impl Drop for StreamEntry {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Running event subscriptions.
+#[derive(Default)]
+pub struct EventStreamManager {
+    streams: HashMap<String, StreamEntry>,
+}
+
+impl EventStreamManager {
+    /// Subscribe to daemon events matching `filter`.  Without `until` the
+    /// stream runs until stopped.
+    pub async fn start(
+        &mut self,
+        client: &DockerClient,
+        connection_id: &str,
+        filter: &DockerEventFilter,
+        emitter: DynEventEmitter,
+    ) -> DockerResult<EventStreamInfo> {
+        let cursor = EventCursor {
+            filter: filter.clone(),
+            last_nano: None,
+        };
+        // The first request runs here so a bad filter is reported to the
+        // caller.
+        let first = hijack::get_stream(client, &cursor.path()?).await?;
+        self.prune();
+        let info = EventStreamInfo {
+            id: uuid::Uuid::new_v4().to_string(),
+            connection_id: connection_id.to_string(),
+            filter: filter.clone(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        info!("Streaming Docker events ({})", info.id);
+        let task = tokio::spawn(run_stream(
+            client.clone(),
+            cursor,
+            first,
+            info.id.clone(),
+            emitter,
+        ));
+        self.streams.insert(
+            info.id.clone(),
+            StreamEntry {
+                info: info.clone(),
+                task,
+            },
+        );
+        Ok(info)
+    }
+
+    pub fn stop(&mut self, stream_id: &str) -> DockerResult<()> {
+        self.streams
+            .remove(stream_id)
+            .map(|_| ())
+            .ok_or_else(|| DockerError::session(&format!("No event stream '{}'", stream_id)))
+    }
+
+    /// Stop every stream opened through a connection.
+    pub fn stop_connection(&mut self, connection_id: &str) {
+        self.streams
+            .retain(|_, entry| entry.info.connection_id != connection_id);
+    }
+
+    /// Streams that are still running.
+    pub fn list(&mut self) -> Vec<EventStreamInfo> {
+        self.prune();
+        self.streams
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    fn prune(&mut self) {
+        self.streams.retain(|_, entry| !entry.task.is_finished());
+    }
+}
+
+/// Where a stream resumes from.
+struct EventCursor {
+    filter: DockerEventFilter,
+    /// `timeNano` of the last event forwarded.
+    last_nano: Option<i64>,
+}
+
+impl EventCursor {
+    fn path(&self) -> DockerResult<String> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        match self.last_nano {
+            Some(nano) => {
+                let since = format!("{}.{:09}", nano / 1_000_000_000, nano % 1_000_000_000);
+                query.append_pair("since", &since);
+            }
+            None => {
+                if let Some(ref since) = self.filter.since {
+                    query.append_pair("since", since);
+                }
+            }
+        }
+        if let Some(ref until) = self.filter.until {
+            query.append_pair("until", until);
+        }
+        if let Some(ref filters) = self.filter.filters {
+            query.append_pair("filters", &serde_json::to_string(filters)?);
+        }
+        let query = query.finish();
+        Ok(match query.is_empty() {
+            true => "/events".to_string(),
+            false => format!("/events?{}", query),
+        })
+    }
+
+    /// Whether `event` is new, remembering it if so.  `since` only has
+    /// one-second resolution on older daemons, so a resumed stream can
+    /// replay events that were already forwarded.
+    fn advance(&mut self, event: &DockerEvent) -> bool {
+        let Some(nano) = event.time_nano else {
+            return true;
+        };
+        if self.last_nano.is_some_and(|last| nano <= last) {
+            return false;
+        }
+        self.last_nano = Some(nano);
+        true
+    }
+}
+
+async fn run_stream(
+    client: DockerClient,
+    mut cursor: EventCursor,
+    first: Body,
+    stream_id: String,
+    emitter: DynEventEmitter,
+) {
+    let mut body = Some(first);
+    let mut backoff = RETRY_MIN;
+    let error = loop {
+        let result = match body.take() {
+            Some(body) => read_events(body, &mut cursor, &stream_id, &emitter).await,
+            None => match cursor.path() {
+                Ok(path) => match hijack::get_stream(&client, &path).await {
+                    Ok(body) => read_events(body, &mut cursor, &stream_id, &emitter).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+        };
+        match result {
+            // With `until` set the daemon ends the stream once it is reached.
+            Ok(_) if cursor.filter.until.is_some() => break None,
+            Ok(true) => {
+                backoff = RETRY_MIN;
+                continue;
+            }
+            Ok(false) => debug!("Event stream {} closed by the daemon", stream_id),
+            Err(e) if is_fatal(&e) => break Some(e.to_string()),
+            Err(e) => warn!("Event stream {} dropped: {}", stream_id, e),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RETRY_MAX);
+    };
+    match error {
+        Some(ref e) => warn!("Event stream {} stopped: {}", stream_id, e),
+        None => info!("Event stream {} finished", stream_id),
+    }
+    let closed = StreamClosed {
+        stream_id: &stream_id,
+        error,
+    };
+    let _ = emitter.emit_event(
+        CLOSED_EVENT,
+        serde_json::to_value(&closed).unwrap_or_default(),
+    );
+}
+
+fn is_fatal(error: &DockerError) -> bool {
+    matches!(
+        error.kind,
+        DockerErrorKind::AuthError
+            | DockerErrorKind::Forbidden
+            | DockerErrorKind::NotFound
+            | DockerErrorKind::ValidationError
+    )
+}
+
+/// Forward the events of one response.  Returns whether any came through.
+async fn read_events(
+    mut body: Body,
+    cursor: &mut EventCursor,
+    stream_id: &str,
+    emitter: &DynEventEmitter,
+) -> DockerResult<bool> {
+    let mut pending = Vec::new();
+    let mut received = false;
+    while let Some(chunk) = body
+        .next_chunk()
+        .await
+        .map_err(|e| DockerError::connection(&e.to_string()))?
+    {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let event: DockerEvent = match serde_json::from_slice(&line) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(
+                        "Event stream {}: skipping malformed event: {}",
+                        stream_id, e
+                    );
+                    continue;
+                }
+            };
+            if !cursor.advance(&event) {
+                continue;
+            }
+            received = true;
+            let payload = StreamEvent { stream_id, event };
+            let _ = emitter.emit_event(EVENT, serde_json::to_value(&payload).unwrap_or_default());
+        }
+    }
+    Ok(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, RecordingEmitter};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn event_line(action: &str, nano: i64) -> Vec<u8> {
+        format!(
+            "{{\"Type\":\"container\",\"Action\":\"{}\",\"Actor\":{{\"ID\":\"abc\",\
+             \"Attributes\":{{\"name\":\"web\"}}}},\"scope\":\"local\",\
+             \"time\":{},\"timeNano\":{}}}\n",
+            action,
+            nano / 1_000_000_000,
+            nano
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_resumes_after_drop() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (seen_tx, mut seen) = tokio::sync::mpsc::unbounded_channel();
+        let counter = calls.clone();
+        let addr = testing::http_server(move |request| {
+            seen_tx.send(request.path).unwrap();
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => {
+                    // Split mid-line to exercise reassembly.
+                    let line = event_line("start", 1_700_000_000_500_000_000);
+                    let (a, b) = line.split_at(20);
+                    (200, vec![a.to_vec(), b.to_vec()])
+                }
+                // The resumed stream replays the last event before new ones.
+                1 => (
+                    200,
+                    vec![
+                        event_line("start", 1_700_000_000_500_000_000),
+                        event_line("die", 1_700_000_001_000_000_000),
+                    ],
+                ),
+                _ => (403, vec![br#"{"message":"denied"}"#.to_vec()]),
+            }
+        })
+        .await;
+
+        let client = testing::tcp_client(addr).await;
+        let emitter = Arc::new(RecordingEmitter::default());
+        let mut manager = EventStreamManager::default();
+        let mut filters = HashMap::new();
+        filters.insert("type".to_string(), vec!["container".to_string()]);
+        let filter = DockerEventFilter {
+            since: None,
+            until: None,
+            filters: Some(filters),
+        };
+        let info = manager
+            .start(&client, "conn", &filter, emitter.clone())
+            .await
+            .unwrap();
+        assert_eq!(manager.list().len(), 1);
+
+        let closed = emitter.wait_for(CLOSED_EVENT, 1).await;
+        assert_eq!(closed[0]["stream_id"], info.id.as_str());
+        assert!(closed[0]["error"].as_str().unwrap().contains("denied"));
+
+        let events = emitter.payloads(EVENT);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"]["type"], "container");
+        assert_eq!(events[0]["event"]["action"], "start");
+        assert_eq!(events[0]["event"]["actor"]["id"], "abc");
+        assert_eq!(events[0]["event"]["actor"]["attributes"]["name"], "web");
+        assert_eq!(events[1]["event"]["action"], "die");
+
+        assert_eq!(
+            seen.recv().await.unwrap(),
+            "/v1.45/events?filters=%7B%22type%22%3A%5B%22container%22%5D%7D"
+        );
+        assert_eq!(
+            seen.recv().await.unwrap(),
+            "/v1.45/events?since=1700000000.500000000\
+             &filters=%7B%22type%22%3A%5B%22container%22%5D%7D"
+        );
+        assert!(manager.list().is_empty());
+    }
+}
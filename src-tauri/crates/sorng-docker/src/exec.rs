@@ -0,0 +1,584 @@
+// ── sorng-docker/src/exec.rs ──────────────────────────────────────────────────
+//! Interactive `exec` and `attach` sessions over hijacked connections,
+//! streamed to the frontend through events.
+//!
+//! Output is emitted as `docker-exec-output` (`{ session_id, stream, data }`).
+//! TTY sessions report everything as `stdout`; without a TTY the daemon
+//! multiplexes stdout and stderr and the frames are split here.  When the
+//! process ends or the connection drops, `docker-exec-closed` carries the
+//! exit code or the error.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use sorng_core::events::DynEventEmitter;
+use sorng_core::utf8::StreamingUtf8Decoder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+use crate::client::DockerClient;
+use crate::containers::ContainerManager;
+use crate::error::{DockerError, DockerResult};
+use crate::hijack::{self, RawStream};
+use crate::types::*;
+
+const OUTPUT_EVENT: &str = "docker-exec-output";
+const CLOSED_EVENT: &str = "docker-exec-closed";
+
+#[derive(Serialize)]
+struct ExecOutput<'a> {
+    session_id: &'a str,
+    stream: &'static str,
+    data: String,
+}
+
+#[derive(Serialize)]
+struct ExecClosed<'a> {
+    session_id: &'a str,
+    exit_code: Option<i32>,
+    error: Option<String>,
+}
+
+enum ExecInput {
+    Data(Vec<u8>),
+    CloseStdin,
+}
+
+struct ExecEntry {
+    client: DockerClient,
+    info: TerminalSession,
+    /// Dropping the sender ends the session.
+    input: mpsc::UnboundedSender<ExecInput>,
+}
+
+type Sessions = Arc<StdMutex<HashMap<String, ExecEntry>>>;
+
+/// Live exec / attach sessions.  Finished sessions stay listed (with their
+/// exit code) until closed.
+#[derive(Default)]
+pub struct ExecManager {
+    sessions: Sessions,
+}
+
+impl ExecManager {
+    /// Create an exec instance and start it interactively.  The attach flags
+    /// default to on; `tty` defaults to off.
+    pub async fn exec(
+        &self,
+        client: &DockerClient,
+        connection_id: &str,
+        container_id: &str,
+        config: &ExecConfig,
+        emitter: DynEventEmitter,
+    ) -> DockerResult<TerminalSession> {
+        if config.cmd.is_empty() {
+            return Err(DockerError::validation("exec needs a command"));
+        }
+        let mut config = config.clone();
+        config.attach_stdin.get_or_insert(true);
+        config.attach_stdout.get_or_insert(true);
+        config.attach_stderr.get_or_insert(true);
+        let tty = *config.tty.get_or_insert(false);
+
+        let exec = ContainerManager::exec_create(client, container_id, &config).await?;
+        let stream = hijack::hijack(
+            client,
+            &format!("/exec/{}/start", exec.id),
+            &serde_json::json!({ "Detach": false, "Tty": tty }),
+        )
+        .await?;
+        info!("Exec in container '{}': {:?}", container_id, config.cmd);
+        let info = new_session(
+            connection_id,
+            container_id,
+            TerminalSessionKind::Exec,
+            Some(exec.id),
+            config.cmd,
+            tty,
+        );
+        Ok(self.open(client, stream, info, emitter))
+    }
+
+    /// Attach to the main process of a running container.
+    pub async fn attach(
+        &self,
+        client: &DockerClient,
+        connection_id: &str,
+        container_id: &str,
+        opts: &ContainerAttachOptions,
+        emitter: DynEventEmitter,
+    ) -> DockerResult<TerminalSession> {
+        // The attach stream is only multiplexed when the container was
+        // created without a TTY.
+        let inspect: serde_json::Value = client
+            .get(&format!("/containers/{}/json", container_id))
+            .await?;
+        let tty = inspect
+            .pointer("/Config/Tty")
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("stream", "1");
+        for (name, enabled) in [
+            ("stdin", opts.stdin.unwrap_or(true)),
+            ("stdout", opts.stdout.unwrap_or(true)),
+            ("stderr", opts.stderr.unwrap_or(true)),
+            ("logs", opts.logs.unwrap_or(false)),
+        ] {
+            query.append_pair(name, if enabled { "1" } else { "0" });
+        }
+        if let Some(ref keys) = opts.detach_keys {
+            query.append_pair("detachKeys", keys);
+        }
+        let stream = hijack::hijack(
+            client,
+            &format!("/containers/{}/attach?{}", container_id, query.finish()),
+            &serde_json::json!({}),
+        )
+        .await?;
+        info!("Attached to container '{}'", container_id);
+        let info = new_session(
+            connection_id,
+            container_id,
+            TerminalSessionKind::Attach,
+            None,
+            Vec::new(),
+            tty,
+        );
+        Ok(self.open(client, stream, info, emitter))
+    }
+
+    /// Write terminal input to the session's stdin.
+    pub fn send_input(&self, session_id: &str, data: &[u8]) -> DockerResult<()> {
+        self.send(session_id, ExecInput::Data(data.to_vec()))
+    }
+
+    /// Resize the session's TTY.
+    pub async fn resize(&self, session_id: &str, cols: u16, rows: u16) -> DockerResult<()> {
+        let (client, path) = {
+            let sessions = self.sessions.lock().unwrap();
+            let entry = sessions
+                .get(session_id)
+                .ok_or_else(|| no_session(session_id))?;
+            let target = match entry.info.exec_id {
+                Some(ref exec_id) => format!("/exec/{}", exec_id),
+                None => format!("/containers/{}", entry.info.container_id),
+            };
+            (
+                entry.client.clone(),
+                format!("{}/resize?h={}&w={}", target, rows, cols),
+            )
+        };
+        client.post_empty(&path).await
+    }
+
+    /// Half-close the connection so the process sees end of input.
+    pub fn close_stdin(&self, session_id: &str) -> DockerResult<()> {
+        self.send(session_id, ExecInput::CloseStdin)
+    }
+
+    /// Close a session and forget it.
+    pub fn close(&self, session_id: &str) -> DockerResult<()> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .map(|_| ())
+            .ok_or_else(|| no_session(session_id))
+    }
+
+    /// Close every session opened through a connection.
+    pub fn close_connection(&self, connection_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.info.connection_id != connection_id);
+    }
+
+    pub fn list(&self) -> Vec<TerminalSession> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    fn open(
+        &self,
+        client: &DockerClient,
+        stream: RawStream,
+        info: TerminalSession,
+        emitter: DynEventEmitter,
+    ) -> TerminalSession {
+        let (input, commands) = mpsc::unbounded_channel();
+        self.sessions.lock().unwrap().insert(
+            info.id.clone(),
+            ExecEntry {
+                client: client.clone(),
+                info: info.clone(),
+                input,
+            },
+        );
+        tokio::spawn(run_session(
+            client.clone(),
+            info.clone(),
+            stream,
+            commands,
+            emitter,
+            self.sessions.clone(),
+        ));
+        info
+    }
+
+    fn send(&self, session_id: &str, input: ExecInput) -> DockerResult<()> {
+        let sessions = self.sessions.lock().unwrap();
+        let entry = sessions
+            .get(session_id)
+            .ok_or_else(|| no_session(session_id))?;
+        entry
+            .input
+            .send(input)
+            .map_err(|_| DockerError::session(&format!("Exec session '{}' has ended", session_id)))
+    }
+}
+
+fn no_session(session_id: &str) -> DockerError {
+    DockerError::session(&format!("No exec session '{}'", session_id))
+}
+
+fn new_session(
+    connection_id: &str,
+    container_id: &str,
+    kind: TerminalSessionKind,
+    exec_id: Option<String>,
+    command: Vec<String>,
+    tty: bool,
+) -> TerminalSession {
+    TerminalSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        connection_id: connection_id.to_string(),
+        container_id: container_id.to_string(),
+        kind,
+        exec_id,
+        command,
+        tty,
+        status: TerminalSessionStatus::Running,
+        exit_code: None,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        ended_at: None,
+    }
+}
+
+/// Pump one session until the process ends, the connection drops or the
+/// session is closed.
+async fn run_session(
+    client: DockerClient,
+    info: TerminalSession,
+    stream: RawStream,
+    mut commands: mpsc::UnboundedReceiver<ExecInput>,
+    emitter: DynEventEmitter,
+    sessions: Sessions,
+) {
+    let session_id = info.id.as_str();
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut demuxer = (!info.tty).then(Demuxer::default);
+    let mut stdout = StreamingUtf8Decoder::new();
+    let mut stderr = StreamingUtf8Decoder::new();
+    let mut buf = vec![0u8; 8192];
+    let mut stdin_open = true;
+
+    let failure = loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                // Once stdin is closed only the output is left to drain.
+                Some(_) if !stdin_open => {}
+                Some(ExecInput::Data(data)) => {
+                    let written = async {
+                        writer.write_all(&data).await?;
+                        writer.flush().await
+                    };
+                    if let Err(e) = written.await {
+                        break Some(e.to_string());
+                    }
+                }
+                Some(ExecInput::CloseStdin) => {
+                    stdin_open = false;
+                    if let Err(e) = writer.shutdown().await {
+                        break Some(e.to_string());
+                    }
+                }
+                None => {
+                    let _ = writer.shutdown().await;
+                    return;
+                }
+            },
+            read = reader.read(&mut buf) => match read {
+                Ok(0) => break None,
+                Ok(n) => match demuxer.as_mut() {
+                    None => emit_output(&emitter, session_id, "stdout", stdout.push(&buf[..n])),
+                    Some(demuxer) => {
+                        for (stream, payload) in demuxer.push(&buf[..n]) {
+                            let data = match stream {
+                                StdStream::Stdout => stdout.push(&payload),
+                                StdStream::Stderr => stderr.push(&payload),
+                            };
+                            emit_output(&emitter, session_id, stream.name(), data);
+                        }
+                    }
+                },
+                Err(e) => break Some(e.to_string()),
+            }
+        }
+    };
+
+    emit_output(&emitter, session_id, "stdout", stdout.finish());
+    emit_output(&emitter, session_id, "stderr", stderr.finish());
+    let exit_code = match failure {
+        Some(_) => None,
+        None => exit_code(&client, &info).await,
+    };
+    debug!(
+        "Exec session {} ended (exit {:?}, error {:?})",
+        session_id, exit_code, failure
+    );
+    if let Some(entry) = sessions.lock().unwrap().get_mut(session_id) {
+        entry.info.status = if failure.is_some() {
+            TerminalSessionStatus::Error
+        } else {
+            TerminalSessionStatus::Completed
+        };
+        entry.info.exit_code = exit_code;
+        entry.info.ended_at = Some(chrono::Utc::now().to_rfc3339());
+    }
+    let closed = ExecClosed {
+        session_id,
+        exit_code,
+        error: failure,
+    };
+    let _ = emitter.emit_event(
+        CLOSED_EVENT,
+        serde_json::to_value(&closed).unwrap_or_default(),
+    );
+}
+
+/// The process's exit code once its stream has ended.  An attached
+/// container may still be running (after a detach), which has none.
+async fn exit_code(client: &DockerClient, info: &TerminalSession) -> Option<i32> {
+    let result = match info.exec_id {
+        Some(ref exec_id) => ContainerManager::exec_inspect(client, exec_id)
+            .await
+            .map(|inspect| inspect.exit_code.filter(|_| !inspect.running)),
+        None => client
+            .get::<serde_json::Value>(&format!("/containers/{}/json", info.container_id))
+            .await
+            .map(|inspect| {
+                let running = inspect.pointer("/State/Running").and_then(|r| r.as_bool());
+                let code = inspect.pointer("/State/ExitCode").and_then(|c| c.as_i64());
+                match running {
+                    Some(false) => code.map(|c| c as i32),
+                    _ => None,
+                }
+            }),
+    };
+    result.unwrap_or_else(|e| {
+        warn!("No exit code for exec session {}: {}", info.id, e);
+        None
+    })
+}
+
+fn emit_output(emitter: &DynEventEmitter, session_id: &str, stream: &'static str, data: String) {
+    if data.is_empty() {
+        return;
+    }
+    let output = ExecOutput {
+        session_id,
+        stream,
+        data,
+    };
+    let _ = emitter.emit_event(
+        OUTPUT_EVENT,
+        serde_json::to_value(&output).unwrap_or_default(),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StdStream {
+    Stdout,
+    Stderr,
+}
+
+impl StdStream {
+    fn name(self) -> &'static str {
+        match self {
+            StdStream::Stdout => "stdout",
+            StdStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Splits the multiplexed stream the daemon sends for non-TTY sessions.
+/// Each frame is an 8-byte header — stream type, three zero bytes and a
+/// big-endian payload length — followed by the payload.
+#[derive(Default)]
+struct Demuxer {
+    pending: Vec<u8>,
+}
+
+impl Demuxer {
+    const HEADER_LEN: usize = 8;
+
+    fn push(&mut self, bytes: &[u8]) -> Vec<(StdStream, Vec<u8>)> {
+        self.pending.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+        let mut start = 0;
+        while let Some(header) = self.pending.get(start..start + Self::HEADER_LEN) {
+            let len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            let body = start + Self::HEADER_LEN;
+            let Some(payload) = self.pending.get(body..body + len) else {
+                break;
+            };
+            // 0 is stdin echoed back and 1 stdout; 2 is stderr and 3 an
+            // error reported by the daemon itself.
+            let stream = match header[0] {
+                2 | 3 => StdStream::Stderr,
+                _ => StdStream::Stdout,
+            };
+            frames.push((stream, payload.to_vec()));
+            start = body + len;
+        }
+        self.pending.drain(..start);
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, RecordingEmitter};
+
+    fn frame(stream: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![stream, 0, 0, 0];
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn test_demuxer_reassembles_split_frames() {
+        let mut bytes = frame(1, b"out");
+        bytes.extend(frame(2, b"err\n"));
+        bytes.extend(frame(1, b""));
+        let mut demuxer = Demuxer::default();
+        assert!(demuxer.push(&bytes[..5]).is_empty());
+        assert_eq!(
+            demuxer.push(&bytes[5..13]),
+            vec![(StdStream::Stdout, b"out".to_vec())]
+        );
+        assert_eq!(
+            demuxer.push(&bytes[13..]),
+            vec![
+                (StdStream::Stderr, b"err\n".to_vec()),
+                (StdStream::Stdout, Vec::new())
+            ]
+        );
+        assert!(demuxer.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exec_session_over_fake_daemon() {
+        let (seen_tx, mut seen) = mpsc::unbounded_channel();
+        let addr = testing::fake_server(move |request, mut socket| {
+            let seen_tx = seen_tx.clone();
+            async move {
+                let path = request.path.clone();
+                seen_tx.send(request).unwrap();
+                if path.ends_with("/containers/web/exec") {
+                    testing::respond(&mut socket, 201, r#"{"Id":"e1"}"#).await;
+                } else if path.ends_with("/exec/e1/start") {
+                    socket
+                        .write_all(
+                            b"HTTP/1.1 101 UPGRADED\r\nConnection: Upgrade\r\n\
+                              Upgrade: tcp\r\n\r\n",
+                        )
+                        .await
+                        .unwrap();
+                    socket.write_all(&frame(1, b"$ ")).await.unwrap();
+                    let mut input = Vec::new();
+                    socket.read_to_end(&mut input).await.unwrap();
+                    assert_eq!(input, b"exit 3\n");
+                    socket
+                        .write_all(&frame(2, "bye ✓\n".as_bytes()))
+                        .await
+                        .unwrap();
+                } else if path.contains("/exec/e1/resize") {
+                    testing::respond(&mut socket, 201, "").await;
+                } else if path.ends_with("/exec/e1/json") {
+                    testing::respond(
+                        &mut socket,
+                        200,
+                        r#"{"ID":"e1","Running":false,"ExitCode":3,"Pid":42}"#,
+                    )
+                    .await;
+                }
+            }
+        })
+        .await;
+
+        let client = testing::tcp_client(addr).await;
+        let emitter = Arc::new(RecordingEmitter::default());
+        let manager = ExecManager::default();
+        let config = ExecConfig {
+            cmd: vec!["sh".to_string()],
+            attach_stdin: None,
+            attach_stdout: None,
+            attach_stderr: None,
+            tty: None,
+            env: None,
+            working_dir: None,
+            user: None,
+            privileged: None,
+        };
+        let session = manager
+            .exec(&client, "conn", "web", &config, emitter.clone())
+            .await
+            .unwrap();
+        assert_eq!(session.exec_id.as_deref(), Some("e1"));
+        assert!(!session.tty);
+
+        let create = seen.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&create.body).unwrap();
+        assert_eq!(body["attachStdin"], true);
+        assert_eq!(body["tty"], false);
+        let start = seen.recv().await.unwrap();
+        assert_eq!(start.method, "POST");
+        assert_eq!(start.body, br#"{"Detach":false,"Tty":false}"#);
+
+        manager.resize(&session.id, 120, 40).await.unwrap();
+        assert_eq!(
+            seen.recv().await.unwrap().path,
+            "/v1.45/exec/e1/resize?h=40&w=120"
+        );
+        manager.send_input(&session.id, b"exit 3\n").unwrap();
+        manager.close_stdin(&session.id).unwrap();
+
+        let closed = emitter.wait_for(CLOSED_EVENT, 1).await;
+        assert_eq!(closed[0]["session_id"], session.id.as_str());
+        assert_eq!(closed[0]["exit_code"], 3);
+        let output = emitter.payloads(OUTPUT_EVENT);
+        assert_eq!(output[0]["data"], "$ ");
+        assert_eq!(output[0]["stream"], "stdout");
+        assert_eq!(output[1]["data"], "bye ✓\n");
+        assert_eq!(output[1]["stream"], "stderr");
+
+        let listed = manager.list();
+        assert_eq!(listed[0].status, TerminalSessionStatus::Completed);
+        assert_eq!(listed[0].exit_code, Some(3));
+        assert!(manager.send_input(&session.id, b"x").is_err());
+        manager.close(&session.id).unwrap();
+        assert!(manager.list().is_empty());
+    }
+}
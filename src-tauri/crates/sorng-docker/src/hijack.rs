@@ -0,0 +1,439 @@
+// ── sorng-docker/src/hijack.rs ────────────────────────────────────────────────
+//! Raw HTTP/1.1 connections to the daemon for requests that outlive a single
+//! response: hijacked `exec` / `attach` streams and the `/events` feed.
+//!
+//! reqwest can neither hand the socket back after an upgrade nor dial a Unix
+//! socket, so these requests are written by hand over the endpoint's own
+//! transport (Unix socket, named pipe, TCP or TCP + TLS).
+
+use std::io;
+use std::sync::Arc;
+
+use rustls::pki_types::ServerName;
+use sorng_core::tls::ClientTls;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::client::{map_status_error, DockerClient};
+use crate::error::{DockerError, DockerResult};
+use crate::types::*;
+
+/// Largest response head accepted before giving up on the daemon.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+pub(crate) trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+/// A connection to the daemon; reads are buffered so bytes that arrive with
+/// the response head are not lost.
+pub(crate) type RawStream = BufReader<Box<dyn Io>>;
+
+/// How to reach the daemon at the socket level.
+#[derive(Clone)]
+pub(crate) enum Transport {
+    Unix {
+        path: String,
+    },
+    NamedPipe {
+        #[cfg_attr(not(windows), allow(dead_code))]
+        path: String,
+    },
+    Tcp {
+        host: String,
+        port: u16,
+        tls: Option<Arc<rustls::ClientConfig>>,
+    },
+}
+
+impl Transport {
+    pub(crate) fn from_config(config: &DockerConnectionConfig) -> DockerResult<Self> {
+        Ok(match &config.endpoint {
+            DockerEndpoint::Unix { path } => Transport::Unix { path: path.clone() },
+            DockerEndpoint::NamedPipe { path } => Transport::NamedPipe { path: path.clone() },
+            DockerEndpoint::Tcp { host, port } => Transport::Tcp {
+                host: host.clone(),
+                port: *port,
+                tls: match &config.tls {
+                    Some(tls) => Some(Arc::new(tls_config(tls)?)),
+                    None => None,
+                },
+            },
+            // Same as the HTTP client: the tunnel is set up externally.
+            DockerEndpoint::Ssh { host, .. } => Transport::Tcp {
+                host: host.clone(),
+                port: 2375,
+                tls: None,
+            },
+        })
+    }
+
+    async fn dial(&self) -> DockerResult<Box<dyn Io>> {
+        let failed = |e: io::Error| DockerError::connection(&e.to_string());
+        match self {
+            #[cfg(unix)]
+            Transport::Unix { path } => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(failed)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            Transport::Unix { .. } => Err(DockerError::connection(
+                "Unix sockets are not supported on this platform",
+            )),
+            #[cfg(windows)]
+            Transport::NamedPipe { path } => {
+                let pipe = tokio::net::windows::named_pipe::ClientOptions::new()
+                    .open(path.replace('/', "\\"))
+                    .map_err(failed)?;
+                Ok(Box::new(pipe))
+            }
+            #[cfg(not(windows))]
+            Transport::NamedPipe { .. } => Err(DockerError::connection(
+                "Named pipes are only supported on Windows",
+            )),
+            Transport::Tcp { host, port, tls } => {
+                let stream = tokio::net::TcpStream::connect((host.as_str(), *port))
+                    .await
+                    .map_err(failed)?;
+                let _ = stream.set_nodelay(true);
+                let Some(tls) = tls else {
+                    return Ok(Box::new(stream));
+                };
+                let server_name = ServerName::try_from(host.clone()).map_err(|e| {
+                    DockerError::connection(&format!("Invalid TLS server name: {}", e))
+                })?;
+                let stream = tokio_rustls::TlsConnector::from(tls.clone())
+                    .connect(server_name, stream)
+                    .await
+                    .map_err(failed)?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// Status and headers of a hand-rolled request.
+pub(crate) struct ResponseHead {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl ResponseHead {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Start a hijacked request (`exec/{id}/start`, `containers/{id}/attach`).
+/// On success the returned stream carries the process's raw I/O in both
+/// directions.
+pub(crate) async fn hijack(
+    client: &DockerClient,
+    path: &str,
+    body: &serde_json::Value,
+) -> DockerResult<RawStream> {
+    let body = serde_json::to_vec(body)?;
+    let (head, stream) = send(client, "POST", path, Some(&body), true).await?;
+    match head.status {
+        // 101 when the daemon honours the upgrade, 200 from older daemons
+        // that hijack the connection without announcing it.
+        101 | 200 => Ok(stream),
+        status => Err(read_error(status, Body::new(&head, stream)).await),
+    }
+}
+
+/// `GET` a long-lived streaming endpoint such as `/events`.
+pub(crate) async fn get_stream(client: &DockerClient, path: &str) -> DockerResult<Body> {
+    let (head, stream) = send(client, "GET", path, None, false).await?;
+    let body = Body::new(&head, stream);
+    if !(200..300).contains(&head.status) {
+        return Err(read_error(head.status, body).await);
+    }
+    Ok(body)
+}
+
+async fn send(
+    client: &DockerClient,
+    method: &str,
+    path: &str,
+    body: Option<&[u8]>,
+    upgrade: bool,
+) -> DockerResult<(ResponseHead, RawStream)> {
+    let mut stream = BufReader::new(client.transport.dial().await?);
+    let mut request = format!(
+        "{} /{}{} HTTP/1.1\r\nHost: docker\r\nUser-Agent: sorng-docker\r\n",
+        method, client.api_version, path
+    );
+    if let Some(body) = body {
+        request.push_str("Content-Type: application/json\r\n");
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    if upgrade {
+        request.push_str("Connection: Upgrade\r\nUpgrade: tcp\r\n");
+    }
+    request.push_str("\r\n");
+    let inner = stream.get_mut();
+    inner.write_all(request.as_bytes()).await?;
+    if let Some(body) = body {
+        inner.write_all(body).await?;
+    }
+    inner.flush().await?;
+    let head = read_head(&mut stream).await?;
+    Ok((head, stream))
+}
+
+async fn read_head(stream: &mut RawStream) -> DockerResult<ResponseHead> {
+    let mut read = 0;
+    let mut status_line = String::new();
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        let n = stream.read_line(&mut line).await?;
+        if n == 0 {
+            return Err(DockerError::connection(
+                "Daemon closed the connection before responding",
+            ));
+        }
+        read += n;
+        if read > MAX_HEAD_BYTES {
+            return Err(DockerError::parse("Response head too large"));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if status_line.is_empty() {
+            status_line = line.to_string();
+        } else if line.is_empty() {
+            break;
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let status = status_line
+        .split(' ')
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| DockerError::parse(&format!("Bad status line '{}'", status_line)))?;
+    Ok(ResponseHead { status, headers })
+}
+
+async fn read_error(status: u16, mut body: Body) -> DockerError {
+    let mut text = Vec::new();
+    while let Ok(Some(chunk)) = body.next_chunk().await {
+        text.extend_from_slice(&chunk);
+    }
+    map_status_error(status, String::from_utf8_lossy(&text).trim())
+}
+
+/// A response body, de-chunked as it arrives.
+pub(crate) struct Body {
+    stream: RawStream,
+    chunked: bool,
+    /// Bytes left when the length is known up front.
+    remaining: Option<u64>,
+    done: bool,
+}
+
+impl Body {
+    fn new(head: &ResponseHead, stream: RawStream) -> Self {
+        let chunked = head
+            .header("Transfer-Encoding")
+            .is_some_and(|te| te.eq_ignore_ascii_case("chunked"));
+        let remaining = match chunked {
+            true => None,
+            false => head
+                .header("Content-Length")
+                .and_then(|len| len.parse().ok()),
+        };
+        Self {
+            stream,
+            chunked,
+            remaining,
+            done: false,
+        }
+    }
+
+    /// The next piece of the body, or `None` once it has ended.
+    pub(crate) async fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.chunked {
+            return self.next_chunked().await;
+        }
+        let mut buf = vec![0u8; 8192];
+        let limit = match self.remaining {
+            Some(0) => 0,
+            Some(left) => buf.len().min(left as usize),
+            None => buf.len(),
+        };
+        let n = self.stream.read(&mut buf[..limit]).await?;
+        if n == 0 {
+            self.done = true;
+            return Ok(None);
+        }
+        if let Some(left) = self.remaining.as_mut() {
+            *left -= n as u64;
+        }
+        buf.truncate(n);
+        Ok(Some(buf))
+    }
+
+    async fn next_chunked(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            self.done = true;
+            return Ok(None);
+        }
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Bad chunk size '{}'", size),
+            )
+        })?;
+        if size == 0 {
+            // Skip any trailers up to the blank line that ends the body.
+            loop {
+                line.clear();
+                let n = self.stream.read_line(&mut line).await?;
+                if n == 0 || line.trim().is_empty() {
+                    break;
+                }
+            }
+            self.done = true;
+            return Ok(None);
+        }
+        let mut chunk = vec![0u8; size];
+        self.stream.read_exact(&mut chunk).await?;
+        let mut crlf = [0u8; 2];
+        self.stream.read_exact(&mut crlf).await?;
+        Ok(Some(chunk))
+    }
+}
+
+// ── TLS ───────────────────────────────────────────────────────────
+
+fn tls_config(tls: &DockerTlsConfig) -> DockerResult<rustls::ClientConfig> {
+    if !tls.verify {
+        return Err(DockerError::connection(
+            "TLS certificate verification cannot be disabled: tls.verify=false requires an explicit runtime acknowledgement contract",
+        ));
+    }
+    let client_tls = ClientTls {
+        ca: tls_source(
+            tls.ca_cert_pem.as_deref(),
+            tls.ca_cert_path.as_deref(),
+            "CA cert",
+        )?,
+        identity: client_identity(tls)?,
+        skip_verify: false,
+    };
+    sorng_core::tls::client_config(&client_tls).map_err(|e| DockerError::connection(&e))
+}
+
+fn client_identity(tls: &DockerTlsConfig) -> DockerResult<Option<(Vec<u8>, Vec<u8>)>> {
+    let cert = tls_source(
+        tls.client_cert_pem.as_deref(),
+        tls.client_cert_path.as_deref(),
+        "client cert",
+    )?;
+    let Some(cert) = cert else {
+        return Ok(None);
+    };
+    // As with the HTTP client, the key may sit in the certificate's file.
+    let key = match (&tls.client_cert_pem, &tls.client_key_pem) {
+        (Some(_), key_pem) => key_pem.clone().unwrap_or_default().into_bytes(),
+        (None, _) => {
+            let key_path = tls
+                .client_key_path
+                .as_deref()
+                .or(tls.client_cert_path.as_deref())
+                .unwrap_or_default();
+            tls_source(None, Some(key_path), "client key")?.unwrap_or_default()
+        }
+    };
+    Ok(Some((cert, key)))
+}
+
+fn tls_source(pem: Option<&str>, path: Option<&str>, what: &str) -> DockerResult<Option<Vec<u8>>> {
+    sorng_core::tls::read_source(pem.map(|pem| pem.as_bytes().to_vec()), path, what)
+        .map_err(|e| DockerError::connection(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[tokio::test]
+    async fn test_chunked_stream_and_error_mapping() {
+        let addr = testing::http_server(|request| {
+            if request.path.ends_with("/events") {
+                (200, vec![b"{\"a\":1}\n".to_vec(), b"{\"b\"".to_vec()])
+            } else {
+                (404, vec![br#"{"message":"No such container: x"}"#.to_vec()])
+            }
+        })
+        .await;
+        let client = testing::tcp_client(addr).await;
+
+        let mut body = get_stream(&client, "/events").await.unwrap();
+        assert_eq!(body.next_chunk().await.unwrap().unwrap(), b"{\"a\":1}\n");
+        assert_eq!(body.next_chunk().await.unwrap().unwrap(), b"{\"b\"");
+        assert!(body.next_chunk().await.unwrap().is_none());
+
+        let Err(err) = get_stream(&client, "/containers/x/json").await else {
+            panic!("expected a 404");
+        };
+        assert!(matches!(err.kind, crate::error::DockerErrorKind::NotFound));
+        assert_eq!(err.message, "No such container: x");
+    }
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hijack_over_unix_socket() {
+        let path = std::env::temp_dir().join(format!("sorng-docker-{}.sock", uuid::Uuid::new_v4()));
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = testing::read_request(&mut socket).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 101 UPGRADED\r\nUpgrade: tcp\r\n\r\nhello")
+                .await
+                .unwrap();
+            let mut input = [0u8; 4];
+            socket.read_exact(&mut input).await.unwrap();
+            (request, input)
+        });
+
+        let config = DockerConnectionConfig {
+            name: "test".to_string(),
+            endpoint: DockerEndpoint::Unix {
+                path: path.to_string_lossy().into_owned(),
+            },
+            tls: None,
+            timeout_seconds: None,
+            ssh: None,
+        };
+        let client = DockerClient::from_config(&config).await.unwrap();
+        let mut stream = hijack(
+            &client,
+            "/containers/c1/attach?stream=1",
+            &serde_json::json!({}),
+        )
+        .await
+        .unwrap();
+        let mut greeting = [0u8; 5];
+        stream.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(&greeting, b"hello");
+        stream.get_mut().write_all(b"ping").await.unwrap();
+
+        let (request, input) = server.await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1.45/containers/c1/attach?stream=1");
+        assert_eq!(request.body, b"{}");
+        assert_eq!(&input, b"ping");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -4,6 +4,9 @@ pub mod client;
 pub mod compose;
 pub mod containers;
 pub mod error;
+pub mod events;
+pub mod exec;
+pub(crate) mod hijack;
 pub mod images;
 pub mod networks;
 pub mod registry;
@@ -11,3 +14,6 @@ pub mod service;
 pub mod system;
 pub mod types;
 pub mod volumes;
+
+#[cfg(test)]
+mod testing;
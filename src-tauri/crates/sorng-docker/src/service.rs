@@ -6,12 +6,16 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use sorng_core::events::{DynEventEmitter, NoopEventEmitter};
+
 use crate::client::DockerClient;
 use crate::error::{DockerError, DockerResult};
 use crate::types::*;
 
 pub use crate::compose::ComposeManager;
 use crate::containers::ContainerManager;
+use crate::events::EventStreamManager;
+use crate::exec::ExecManager;
 use crate::images::ImageManager;
 use crate::networks::NetworkManager;
 use crate::registry::RegistryManager;
@@ -24,15 +28,27 @@ pub type DockerServiceState = Arc<Mutex<DockerService>>;
 /// Main Docker service managing connections.
 pub struct DockerService {
     connections: HashMap<String, DockerClient>,
+    exec: ExecManager,
+    event_streams: EventStreamManager,
+    /// Receives terminal output and daemon events.
+    emitter: DynEventEmitter,
 }
 
 impl DockerService {
     pub fn new() -> Self {
         Self {
             connections: HashMap::new(),
+            exec: ExecManager::default(),
+            event_streams: EventStreamManager::default(),
+            emitter: Arc::new(NoopEventEmitter),
         }
     }
 
+    /// Route session output and streamed events to the frontend.
+    pub fn set_event_emitter(&mut self, emitter: DynEventEmitter) {
+        self.emitter = emitter;
+    }
+
     // ── Connection lifecycle ──────────────────────────────────────
 
     pub async fn connect(
@@ -49,8 +65,10 @@ impl DockerService {
     pub fn disconnect(&mut self, id: &str) -> DockerResult<()> {
         self.connections
             .remove(id)
-            .map(|_| ())
-            .ok_or_else(|| DockerError::session(&format!("No connection '{}'", id)))
+            .ok_or_else(|| DockerError::session(&format!("No connection '{}'", id)))?;
+        self.exec.close_connection(id);
+        self.event_streams.stop_connection(id);
+        Ok(())
     }
 
     pub fn list_connections(&self) -> Vec<String> {
@@ -256,6 +274,83 @@ impl DockerService {
         ContainerManager::prune(self.client(id)?, None).await
     }
 
+    // ── Interactive exec / attach ─────────────────────────────────
+
+    pub async fn exec_interactive(
+        &self,
+        id: &str,
+        container_id: &str,
+        config: &ExecConfig,
+    ) -> DockerResult<TerminalSession> {
+        self.exec
+            .exec(
+                self.client(id)?,
+                id,
+                container_id,
+                config,
+                self.emitter.clone(),
+            )
+            .await
+    }
+
+    pub async fn attach_container(
+        &self,
+        id: &str,
+        container_id: &str,
+        opts: &ContainerAttachOptions,
+    ) -> DockerResult<TerminalSession> {
+        self.exec
+            .attach(
+                self.client(id)?,
+                id,
+                container_id,
+                opts,
+                self.emitter.clone(),
+            )
+            .await
+    }
+
+    pub fn exec_send_input(&self, session_id: &str, data: &[u8]) -> DockerResult<()> {
+        self.exec.send_input(session_id, data)
+    }
+
+    pub async fn exec_resize(&self, session_id: &str, cols: u16, rows: u16) -> DockerResult<()> {
+        self.exec.resize(session_id, cols, rows).await
+    }
+
+    pub fn exec_close_stdin(&self, session_id: &str) -> DockerResult<()> {
+        self.exec.close_stdin(session_id)
+    }
+
+    pub fn exec_close(&self, session_id: &str) -> DockerResult<()> {
+        self.exec.close(session_id)
+    }
+
+    pub fn list_exec_sessions(&self) -> Vec<TerminalSession> {
+        self.exec.list()
+    }
+
+    // ── Live event streams ────────────────────────────────────────
+
+    pub async fn start_event_stream(
+        &mut self,
+        id: &str,
+        filter: &DockerEventFilter,
+    ) -> DockerResult<EventStreamInfo> {
+        let client = self.client(id)?.clone();
+        self.event_streams
+            .start(&client, id, filter, self.emitter.clone())
+            .await
+    }
+
+    pub fn stop_event_stream(&mut self, stream_id: &str) -> DockerResult<()> {
+        self.event_streams.stop(stream_id)
+    }
+
+    pub fn list_event_streams(&mut self) -> Vec<EventStreamInfo> {
+        self.event_streams.list()
+    }
+
     // ── Images ────────────────────────────────────────────────────
 
     pub async fn list_images(
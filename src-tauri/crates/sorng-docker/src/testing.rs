@@ -0,0 +1,26 @@
+// ── sorng-docker/src/testing.rs ───────────────────────────────────────────────
+//! Test client for the fake daemons in `sorng_core::testing`.
+
+use std::net::SocketAddr;
+
+use crate::client::DockerClient;
+use crate::types::*;
+
+pub(crate) use sorng_core::testing::{
+    fake_server, http_server, read_request, respond, RecordingEmitter,
+};
+
+/// A client for the fake daemon over plain TCP.
+pub(crate) async fn tcp_client(addr: SocketAddr) -> DockerClient {
+    let config = DockerConnectionConfig {
+        name: "test".to_string(),
+        endpoint: DockerEndpoint::Tcp {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+        },
+        tls: None,
+        timeout_seconds: Some(5),
+        ssh: None,
+    };
+    DockerClient::from_config(&config).await.unwrap()
+}
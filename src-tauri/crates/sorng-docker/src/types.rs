@@ -506,6 +506,7 @@ pub struct ExecConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecCreateResponse {
+    #[serde(alias = "Id")]
     pub id: String,
 }
 
@@ -513,9 +514,13 @@ pub struct ExecCreateResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecInspect {
+    #[serde(alias = "ID")]
     pub id: String,
+    #[serde(alias = "Running")]
     pub running: bool,
+    #[serde(alias = "ExitCode")]
     pub exit_code: Option<i32>,
+    #[serde(alias = "Pid")]
     pub pid: Option<i64>,
 }
 
@@ -1036,13 +1041,16 @@ pub struct ImageTagList {
 // Events
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Docker daemon event.
+/// Docker daemon event.  The daemon sends `Type` / `Action` / `Actor`
+/// capitalised; those are accepted alongside the camelCase form.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DockerEvent {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", alias = "Type")]
     pub event_type: String,
+    #[serde(alias = "Action")]
     pub action: String,
+    #[serde(alias = "Actor", default)]
     pub actor: DockerEventActor,
     pub time: Option<i64>,
     pub time_nano: Option<i64>,
@@ -1052,10 +1060,12 @@ pub struct DockerEvent {
 }
 
 /// Docker event actor.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DockerEventActor {
+    #[serde(alias = "ID")]
     pub id: Option<String>,
+    #[serde(alias = "Attributes", default)]
     pub attributes: HashMap<String, String>,
 }
 
@@ -1067,3 +1077,66 @@ pub struct DockerEventFilter {
     pub until: Option<String>,
     pub filters: Option<HashMap<String, Vec<String>>>,
 }
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Interactive sessions & live streams
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Options for attaching to a running container's main process.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerAttachOptions {
+    /// Forward terminal input (default `true`).
+    pub stdin: Option<bool>,
+    pub stdout: Option<bool>,
+    pub stderr: Option<bool>,
+    /// Replay the output the container has already written.
+    pub logs: Option<bool>,
+    /// Key sequence that detaches instead of being forwarded, e.g. `ctrl-p,ctrl-q`.
+    pub detach_keys: Option<String>,
+}
+
+/// What a terminal session is connected to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalSessionKind {
+    Exec,
+    Attach,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalSessionStatus {
+    Running,
+    Completed,
+    Error,
+}
+
+/// An interactive exec or attach session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSession {
+    pub id: String,
+    pub connection_id: String,
+    pub container_id: String,
+    pub kind: TerminalSessionKind,
+    /// Exec instance id, for `exec` sessions.
+    pub exec_id: Option<String>,
+    pub command: Vec<String>,
+    /// Whether output is a raw TTY stream rather than separate stdout/stderr.
+    pub tty: bool,
+    pub status: TerminalSessionStatus,
+    pub exit_code: Option<i32>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+}
+
+/// A running `/events` subscription.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamInfo {
+    pub id: String,
+    pub connection_id: String,
+    pub filter: DockerEventFilter,
+    pub started_at: String,
+}
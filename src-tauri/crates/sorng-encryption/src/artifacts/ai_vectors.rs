@@ -0,0 +1,114 @@
+//! Encrypted AI agent vector index and RAG documents (`ai/**/*.enc`).
+//!
+//! Embeddings are derived from whatever the user indexed — runbooks,
+//! session transcripts — so they leak content if stored in the clear.
+//! Each collection snapshot is one envelope under
+//! [`ArtifactKind::AiVectors`]. Payloads are opaque bytes: the agent
+//! serialises its own snapshot format and this codec never parses it,
+//! which keeps large indexes out of an intermediate `serde_json::Value`.
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::dek::ArtifactKind;
+use crate::envelope::{self, EnvelopeError, EnvelopeHeader, MasterKeyStorage, NONCE_LEN, SALT_LEN};
+use crate::password_wrap::Argon2Params;
+use crate::state::EncryptionState;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AiVectorsError {
+    #[error("encryption state is locked; unlock before reading or writing the AI vector index")]
+    Locked,
+    #[error("envelope codec failed: {0}")]
+    Envelope(#[from] EnvelopeError),
+}
+
+pub async fn write(
+    state: &EncryptionState,
+    plaintext: &[u8],
+    mode: MasterKeyStorage,
+    argon2: Argon2Params,
+    argon2_salt: [u8; SALT_LEN],
+) -> Result<Vec<u8>, AiVectorsError> {
+    let sub_key = state
+        .sub_key(ArtifactKind::AiVectors)
+        .await
+        .ok_or(AiVectorsError::Locked)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let header = match mode {
+        MasterKeyStorage::Vault => EnvelopeHeader::new_vault(nonce),
+        MasterKeyStorage::Password | MasterKeyStorage::VaultAndPassword => {
+            EnvelopeHeader::new_password(
+                mode,
+                argon2.memory_kib,
+                argon2.time_cost,
+                argon2.parallelism,
+                argon2_salt,
+                nonce,
+            )
+        }
+    };
+    Ok(envelope::write_envelope(&sub_key, &header, plaintext)?)
+}
+
+pub async fn read(state: &EncryptionState, file_bytes: &[u8]) -> Result<Vec<u8>, AiVectorsError> {
+    let sub_key = state
+        .sub_key(ArtifactKind::AiVectors)
+        .await
+        .ok_or(AiVectorsError::Locked)?;
+    let (_header, plaintext) = envelope::read_envelope(&sub_key, file_bytes)?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dek::MasterDek;
+
+    #[tokio::test]
+    async fn round_trip() {
+        let state = EncryptionState::new();
+        state.install(MasterDek::generate()).await;
+        let payload = br#"{"name":"runbooks","entries":{}}"#;
+        let blob = write(
+            &state,
+            payload,
+            MasterKeyStorage::Vault,
+            Argon2Params::OWASP,
+            [0u8; SALT_LEN],
+        )
+        .await
+        .unwrap();
+        assert_eq!(read(&state, &blob).await.unwrap(), payload);
+    }
+
+    #[tokio::test]
+    async fn sub_key_isolation_from_logs() {
+        use crate::artifacts::logs;
+        let state = EncryptionState::new();
+        state.install(MasterDek::generate()).await;
+        let blob = write(
+            &state,
+            b"vectors",
+            MasterKeyStorage::Vault,
+            Argon2Params::OWASP,
+            [0u8; SALT_LEN],
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            logs::read(&state, &blob).await.unwrap_err(),
+            logs::LogError::Envelope(EnvelopeError::AuthenticationFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn locked_state_blocks_io() {
+        let state = EncryptionState::new();
+        assert!(matches!(
+            read(&state, &[0u8; 128]).await.unwrap_err(),
+            AiVectorsError::Locked
+        ));
+    }
+}
@@ -6,6 +6,7 @@
 //! sub-key and otherwise stay pure — they don't touch the vault,
 //! `dek.enc`, or the unlock UX.
 
+pub mod ai_vectors;
 pub mod backups;
 pub mod connections;
 pub mod logs;
@@ -60,6 +60,9 @@ pub enum ArtifactKind {
     /// the index slot fails authentication rather than decoding
     /// successfully under the wrong slot.
     DatabasesIndex,
+    /// `ai/vectors/*.enc`, `ai/rag/*.enc` — the AI agent's persisted
+    /// vector index and RAG documents.
+    AiVectors,
 }
 
 impl ArtifactKind {
@@ -75,6 +78,7 @@ impl ArtifactKind {
             ArtifactKind::Logs => "sorng-v1::logs",
             ArtifactKind::Macros => "sorng-v1::macros",
             ArtifactKind::DatabasesIndex => "sorng-v1::databases-index",
+            ArtifactKind::AiVectors => "sorng-v1::ai-vectors",
         }
     }
 
@@ -90,6 +94,7 @@ impl ArtifactKind {
             ArtifactKind::Logs,
             ArtifactKind::Macros,
             ArtifactKind::DatabasesIndex,
+            ArtifactKind::AiVectors,
         ]
     }
 }
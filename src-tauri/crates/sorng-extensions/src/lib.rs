@@ -4,8 +4,8 @@
 //!
 //! Provides a manifest-driven, sandboxed extension system with
 //! permission enforcement, lifecycle management, hook dispatch,
-//! per-extension storage, a JSON-based script runtime, and a
-//! comprehensive API surface.
+//! per-extension storage, a JSON-based script runtime, a WebAssembly
+//! runtime, and a comprehensive API surface.
 //!
 //! | Module        | Purpose                                      |
 //! |---------------|----------------------------------------------|
@@ -14,6 +14,7 @@
 //! | `permissions` | Permission checking and enforcement           |
 //! | `sandbox`     | Sandboxed execution with resource limits      |
 //! | `runtime`     | Script interpreter / VM                       |
+//! | `wasm_runtime`| WebAssembly module runtime and host ABI       |
 //! | `hooks`       | Event registration and dispatch               |
 //! | `registry`    | Extension lifecycle (install/enable/…)        |
 //! | `api`         | API surface exposed to extensions             |
@@ -31,3 +32,4 @@ pub mod sandbox;
 pub mod service;
 pub mod storage;
 pub mod types;
+pub mod wasm_runtime;
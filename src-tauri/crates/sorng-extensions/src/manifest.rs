@@ -230,6 +230,10 @@ pub fn create_manifest(
     extension_type: ExtensionType,
 ) -> ExtensionManifest {
     let now = Utc::now();
+    let entry_point = match extension_type {
+        ExtensionType::Wasm => "main.wasm",
+        _ => "main.json",
+    };
     ExtensionManifest {
         id: id.into(),
         name: name.into(),
@@ -244,7 +248,7 @@ pub fn create_manifest(
         extension_type,
         permissions: Vec::new(),
         hooks: Vec::new(),
-        entry_point: "main.json".to_string(),
+        entry_point: entry_point.to_string(),
         icon: None,
         settings_schema: Vec::new(),
         dependencies: Vec::new(),
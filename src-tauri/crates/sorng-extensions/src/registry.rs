@@ -14,6 +14,7 @@ use crate::hooks::HookManager;
 use crate::manifest::validate_manifest;
 use crate::permissions::PermissionChecker;
 use crate::types::*;
+use crate::wasm_runtime::validate_wasm_extension;
 
 // ─── ExtensionRegistry ──────────────────────────────────────────────
 
@@ -83,6 +84,11 @@ impl ExtensionRegistry {
         // Check dependencies.
         self.check_dependencies(&manifest)?;
 
+        let sandbox_config = sandbox_config.unwrap_or_default();
+        if manifest.extension_type == ExtensionType::Wasm {
+            validate_wasm_extension(&manifest, script_source.as_deref(), &sandbox_config)?;
+        }
+
         // Compute script hash.
         let script_hash = script_source.as_ref().map(|s| {
             let mut hasher = Sha256::new();
@@ -116,7 +122,7 @@ impl ExtensionRegistry {
             execution_count: 0,
             total_execution_time_ms: 0,
             settings: HashMap::new(),
-            sandbox_config: sandbox_config.unwrap_or_default(),
+            sandbox_config,
             script_source,
             script_hash,
         };
@@ -239,6 +245,14 @@ impl ExtensionRegistry {
 
         validate_manifest(&new_manifest)?;
 
+        if new_manifest.extension_type == ExtensionType::Wasm {
+            validate_wasm_extension(
+                &new_manifest,
+                new_script_source.as_deref(),
+                &state.sandbox_config,
+            )?;
+        }
+
         let was_enabled = state.status == ExtensionStatus::Enabled;
 
         // Update permissions.
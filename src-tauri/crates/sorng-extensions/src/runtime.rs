@@ -107,6 +107,11 @@ impl RuntimeEnv {
     pub fn emitted_events(&self) -> &[(String, ScriptValue)] {
         &self.emitted_events
     }
+
+    /// Record a custom event.
+    pub fn emit(&mut self, event_name: impl Into<String>, data: ScriptValue) {
+        self.emitted_events.push((event_name.into(), data));
+    }
 }
 
 impl Default for RuntimeEnv {
@@ -127,17 +132,16 @@ pub struct ScriptInterpreter {
 
 /// Type alias for an API function implementation.
 /// Takes (args, env) and returns a result value.
-type ApiFn = Box<dyn Fn(&[ScriptValue], &mut RuntimeEnv) -> ExtResult<ScriptValue> + Send + Sync>;
+pub type ApiFn =
+    Box<dyn Fn(&[ScriptValue], &mut RuntimeEnv) -> ExtResult<ScriptValue> + Send + Sync>;
 
 impl ScriptInterpreter {
     /// Create a new interpreter for the given script.
     pub fn new(script: ExtensionScript) -> Self {
-        let mut interp = Self {
+        Self {
             script,
-            api_functions: HashMap::new(),
-        };
-        interp.register_builtins();
-        interp
+            api_functions: builtin_api_functions(),
+        }
     }
 
     /// Register a custom API function.
@@ -502,15 +506,15 @@ impl ScriptInterpreter {
 
     // ── Built-in API Functions ──────────────────────────────────
 
-    fn register_builtins(&mut self) {
+    fn register_builtins(api: &mut HashMap<String, ApiFn>) {
         // string.length
-        self.register_api("string.length", |args, _env| {
+        register(api, "string.length", |args, _env| {
             let s = args.first().and_then(|v| v.as_str()).unwrap_or("");
             Ok(ScriptValue::Int(s.len() as i64))
         });
 
         // string.upper
-        self.register_api("string.upper", |args, _env| {
+        register(api, "string.upper", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -519,7 +523,7 @@ impl ScriptInterpreter {
         });
 
         // string.lower
-        self.register_api("string.lower", |args, _env| {
+        register(api, "string.lower", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -528,7 +532,7 @@ impl ScriptInterpreter {
         });
 
         // string.trim
-        self.register_api("string.trim", |args, _env| {
+        register(api, "string.trim", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -537,13 +541,13 @@ impl ScriptInterpreter {
         });
 
         // string.concat
-        self.register_api("string.concat", |args, _env| {
+        register(api, "string.concat", |args, _env| {
             let result: String = args.iter().map(|v| v.to_display_string()).collect();
             Ok(ScriptValue::String(result))
         });
 
         // string.split
-        self.register_api("string.split", |args, _env| {
+        register(api, "string.split", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -557,7 +561,7 @@ impl ScriptInterpreter {
         });
 
         // string.replace
-        self.register_api("string.replace", |args, _env| {
+        register(api, "string.replace", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -574,7 +578,7 @@ impl ScriptInterpreter {
         });
 
         // string.contains
-        self.register_api("string.contains", |args, _env| {
+        register(api, "string.contains", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -587,7 +591,7 @@ impl ScriptInterpreter {
         });
 
         // string.substring
-        self.register_api("string.substring", |args, _env| {
+        register(api, "string.substring", |args, _env| {
             let s = args
                 .first()
                 .map(|v| v.to_display_string())
@@ -603,28 +607,28 @@ impl ScriptInterpreter {
         });
 
         // math.add
-        self.register_api("math.add", |args, _env| {
+        register(api, "math.add", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(a + b))
         });
 
         // math.sub
-        self.register_api("math.sub", |args, _env| {
+        register(api, "math.sub", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(a - b))
         });
 
         // math.mul
-        self.register_api("math.mul", |args, _env| {
+        register(api, "math.mul", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(a * b))
         });
 
         // math.div
-        self.register_api("math.div", |args, _env| {
+        register(api, "math.div", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(1.0);
             if b == 0.0 {
@@ -635,7 +639,7 @@ impl ScriptInterpreter {
         });
 
         // math.mod
-        self.register_api("math.mod", |args, _env| {
+        register(api, "math.mod", |args, _env| {
             let a = args.first().and_then(|v| v.as_int()).unwrap_or(0);
             let b = args.get(1).and_then(|v| v.as_int()).unwrap_or(1);
             if b == 0 {
@@ -646,45 +650,45 @@ impl ScriptInterpreter {
         });
 
         // math.abs
-        self.register_api("math.abs", |args, _env| {
+        register(api, "math.abs", |args, _env| {
             let v = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(v.abs()))
         });
 
         // math.min
-        self.register_api("math.min", |args, _env| {
+        register(api, "math.min", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(a.min(b)))
         });
 
         // math.max
-        self.register_api("math.max", |args, _env| {
+        register(api, "math.max", |args, _env| {
             let a = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             let b = args.get(1).and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Float(a.max(b)))
         });
 
         // math.floor
-        self.register_api("math.floor", |args, _env| {
+        register(api, "math.floor", |args, _env| {
             let v = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Int(v.floor() as i64))
         });
 
         // math.ceil
-        self.register_api("math.ceil", |args, _env| {
+        register(api, "math.ceil", |args, _env| {
             let v = args.first().and_then(|v| v.as_float()).unwrap_or(0.0);
             Ok(ScriptValue::Int(v.ceil() as i64))
         });
 
         // array.length
-        self.register_api("array.length", |args, _env| match args.first() {
+        register(api, "array.length", |args, _env| match args.first() {
             Some(ScriptValue::Array(arr)) => Ok(ScriptValue::Int(arr.len() as i64)),
             _ => Ok(ScriptValue::Int(0)),
         });
 
         // array.push
-        self.register_api("array.push", |args, env| {
+        register(api, "array.push", |args, env| {
             let var_name = args.first().and_then(|v| v.as_str()).unwrap_or("");
             let item = args.get(1).cloned().unwrap_or(ScriptValue::Null);
             let mut arr = match env.get_var(var_name) {
@@ -697,7 +701,7 @@ impl ScriptInterpreter {
         });
 
         // array.join
-        self.register_api("array.join", |args, _env| match args.first() {
+        register(api, "array.join", |args, _env| match args.first() {
             Some(ScriptValue::Array(arr)) => {
                 let sep = args.get(1).and_then(|v| v.as_str()).unwrap_or(",");
                 let parts: Vec<String> = arr.iter().map(|v| v.to_display_string()).collect();
@@ -707,7 +711,7 @@ impl ScriptInterpreter {
         });
 
         // json.parse
-        self.register_api("json.parse", |args, _env| {
+        register(api, "json.parse", |args, _env| {
             let s = args.first().and_then(|v| v.as_str()).unwrap_or("null");
             let json: serde_json::Value = serde_json::from_str(s)
                 .map_err(|e| ExtError::script(format!("JSON parse error: {}", e)))?;
@@ -715,7 +719,7 @@ impl ScriptInterpreter {
         });
 
         // json.stringify
-        self.register_api("json.stringify", |args, _env| {
+        register(api, "json.stringify", |args, _env| {
             let val = args.first().cloned().unwrap_or(ScriptValue::Null);
             let json: serde_json::Value = val.into();
             let s = serde_json::to_string(&json)
@@ -724,17 +728,17 @@ impl ScriptInterpreter {
         });
 
         // time.now
-        self.register_api("time.now", |_args, _env| {
+        register(api, "time.now", |_args, _env| {
             Ok(ScriptValue::String(Utc::now().to_rfc3339()))
         });
 
         // time.unix
-        self.register_api("time.unix", |_args, _env| {
+        register(api, "time.unix", |_args, _env| {
             Ok(ScriptValue::Int(Utc::now().timestamp()))
         });
 
         // type.of
-        self.register_api("type.of", |args, _env| {
+        register(api, "type.of", |args, _env| {
             let type_name = match args.first() {
                 Some(ScriptValue::Null) => "null",
                 Some(ScriptValue::Bool(_)) => "bool",
@@ -749,13 +753,13 @@ impl ScriptInterpreter {
         });
 
         // env.get — get a variable from the environment
-        self.register_api("env.get", |args, env| {
+        register(api, "env.get", |args, env| {
             let name = args.first().and_then(|v| v.as_str()).unwrap_or("");
             Ok(env.get_var(name))
         });
 
         // env.set — set a variable in the environment
-        self.register_api("env.set", |args, env| {
+        register(api, "env.set", |args, env| {
             let name = args
                 .first()
                 .and_then(|v| v.as_str())
@@ -769,13 +773,28 @@ impl ScriptInterpreter {
         });
 
         // env.has — check if a variable exists
-        self.register_api("env.has", |args, env| {
+        register(api, "env.has", |args, env| {
             let name = args.first().and_then(|v| v.as_str()).unwrap_or("");
             Ok(ScriptValue::Bool(env.has_var(name)))
         });
     }
 }
 
+/// The built-in API functions available to every extension, whether it
+/// runs as a script or as a WASM module.
+pub(crate) fn builtin_api_functions() -> HashMap<String, ApiFn> {
+    let mut api = HashMap::new();
+    ScriptInterpreter::register_builtins(&mut api);
+    api
+}
+
+fn register<F>(api: &mut HashMap<String, ApiFn>, name: &str, func: F)
+where
+    F: Fn(&[ScriptValue], &mut RuntimeEnv) -> ExtResult<ScriptValue> + Send + Sync + 'static,
+{
+    api.insert(name.to_string(), Box::new(func));
+}
+
 // ─── Tests ──────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -100,7 +100,13 @@ impl Sandbox {
 
     /// Record that one instruction was executed and check the limit.
     pub fn tick_instruction(&mut self) -> ExtResult<()> {
-        self.metrics.instructions_executed += 1;
+        self.record_instructions(1)
+    }
+
+    /// Record a batch of executed instructions (e.g. WASM fuel consumed)
+    /// and check the limit.
+    pub fn record_instructions(&mut self, count: u64) -> ExtResult<()> {
+        self.metrics.instructions_executed += count;
         if self.metrics.instructions_executed > self.config.max_instructions {
             warn!(
                 "Sandbox violation: instruction limit exceeded ({} > {})",
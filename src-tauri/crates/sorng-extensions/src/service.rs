@@ -18,6 +18,7 @@ use crate::runtime::{parse_script, RuntimeEnv, ScriptInterpreter};
 use crate::sandbox::Sandbox;
 use crate::storage::ExtensionStorage;
 use crate::types::*;
+use crate::wasm_runtime::{ApiGate, WasmModule};
 
 /// Type alias for the Tauri managed state.
 pub type ExtensionsServiceState = Arc<Mutex<ExtensionsService>>;
@@ -30,6 +31,9 @@ pub struct ExtensionsService {
     pub storage: ExtensionStorage,
     pub api_registry: ApiRegistry,
     pub config: EngineConfig,
+    /// Compiled WASM modules by extension id, with the script hash they
+    /// were compiled from.
+    wasm_modules: HashMap<String, (Option<String>, Arc<WasmModule>)>,
 }
 
 impl ExtensionsService {
@@ -42,6 +46,7 @@ impl ExtensionsService {
             storage: ExtensionStorage::new(),
             api_registry: ApiRegistry::new(),
             config: EngineConfig::default(),
+            wasm_modules: HashMap::new(),
         };
         Arc::new(Mutex::new(service))
     }
@@ -59,6 +64,7 @@ impl ExtensionsService {
             ),
             api_registry: ApiRegistry::new(),
             config,
+            wasm_modules: HashMap::new(),
         };
         Arc::new(Mutex::new(service))
     }
@@ -113,6 +119,7 @@ impl ExtensionsService {
         self.registry
             .uninstall(extension_id, &mut self.permissions, &mut self.hooks)?;
         self.storage.remove_extension(extension_id);
+        self.wasm_modules.remove(extension_id);
         Ok(())
     }
 
@@ -159,21 +166,46 @@ impl ExtensionsService {
         })?;
 
         let sandbox_config = state.sandbox_config.clone();
+        let mut env = RuntimeEnv::new();
 
-        let script = parse_script(script_src)?;
-        let interp = ScriptInterpreter::new(script);
+        let result = if state.manifest.extension_type == ExtensionType::Wasm {
+            // Reuse the compiled module until the script changes.
+            let module = match self.wasm_modules.get(extension_id) {
+                Some((hash, module)) if *hash == state.script_hash => Arc::clone(module),
+                _ => {
+                    let module = Arc::new(WasmModule::from_source(script_src, &sandbox_config)?);
+                    self.wasm_modules.insert(
+                        extension_id.to_string(),
+                        (state.script_hash.clone(), Arc::clone(&module)),
+                    );
+                    module
+                }
+            };
 
-        if !interp.has_handler(handler_name) {
-            return Err(ExtError::script(format!(
-                "Handler '{}' not found in extension '{}'",
-                handler_name, extension_id
-            )));
-        }
+            if !module.has_handler(handler_name) {
+                return Err(ExtError::script(format!(
+                    "Handler '{}' not found in extension '{}'",
+                    handler_name, extension_id
+                )));
+            }
 
-        let mut sandbox = Sandbox::new(sandbox_config);
-        let mut env = RuntimeEnv::new();
+            let gate = ApiGate::new(extension_id, &self.api_registry, &self.permissions);
+            let mut sandbox = Sandbox::new(sandbox_config);
+            module.run_handler(handler_name, args, &mut sandbox, &mut env, &gate)?
+        } else {
+            let script = parse_script(script_src)?;
+            let interp = ScriptInterpreter::new(script);
+
+            if !interp.has_handler(handler_name) {
+                return Err(ExtError::script(format!(
+                    "Handler '{}' not found in extension '{}'",
+                    handler_name, extension_id
+                )));
+            }
 
-        let result = interp.run_handler(handler_name, args, &mut sandbox, &mut env)?;
+            let mut sandbox = Sandbox::new(sandbox_config);
+            interp.run_handler(handler_name, args, &mut sandbox, &mut env)?
+        };
 
         // Record execution.
         let _ = self
@@ -489,6 +521,93 @@ mod tests {
         assert_eq!(result.output, Some(serde_json::json!("Hello, World!")));
     }
 
+    #[test]
+    fn install_enable_execute_wasm() {
+        let state = ExtensionsService::new();
+        let mut svc = blocking_lock(&state);
+
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "sorng" "output" (func $output (param i32 i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "42")
+                (func (export "on_hello") (call $output (i32.const 0) (i32.const 2))))"#,
+        )
+        .unwrap();
+        let manifest = sample_manifest_json().replace("\"Tool\"", "\"Wasm\"");
+        let source = crate::wasm_runtime::encode_wasm_source(&wasm);
+
+        assert!(svc
+            .install_extension(&manifest, Some("AAAA".into()), None)
+            .is_err());
+        svc.install_extension(&manifest, Some(source), None)
+            .unwrap();
+        svc.enable_extension("com.test.hello").unwrap();
+
+        let result = svc
+            .execute_handler("com.test.hello", "on_hello", HashMap::new())
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, Some(serde_json::json!(42)));
+        assert!(svc
+            .execute_handler("com.test.hello", "missing", HashMap::new())
+            .is_err());
+    }
+
+    #[test]
+    fn wasm_extension_calls_builtins_with_cached_module() {
+        let state = ExtensionsService::new();
+        let mut svc = blocking_lock(&state);
+
+        let wasm = |data: &str| {
+            let wat = format!(
+                r#"(module
+                    (import "sorng" "call" (func $call (param i32 i32 i32 i32) (result i32)))
+                    (import "sorng" "result_read" (func $result_read (param i32)))
+                    (import "sorng" "output" (func $output (param i32 i32)))
+                    (memory (export "memory") 1)
+                    (data (i32.const 0) "string.upper")
+                    (data (i32.const 16) "{}")
+                    (func (export "on_hello") (local $n i32)
+                        (local.set $n (call $call (i32.const 0) (i32.const 12)
+                                                  (i32.const 16) (i32.const {})))
+                        (if (i32.lt_s (local.get $n) (i32.const 0)) (then unreachable))
+                        (call $result_read (i32.const 1024))
+                        (call $output (i32.const 1024) (local.get $n))))"#,
+                data.replace('"', "\\\""),
+                data.len()
+            );
+            crate::wasm_runtime::encode_wasm_source(&wat::parse_str(wat).unwrap())
+        };
+        let manifest = sample_manifest_json().replace("\"Tool\"", "\"Wasm\"");
+        svc.install_extension(&manifest, Some(wasm(r#"["bob"]"#)), None)
+            .unwrap();
+        svc.enable_extension("com.test.hello").unwrap();
+
+        let run = |svc: &mut ExtensionsService| {
+            let result = svc
+                .execute_handler("com.test.hello", "on_hello", HashMap::new())
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+            result.output
+        };
+        assert_eq!(run(&mut svc), Some(serde_json::json!("BOB")));
+        let compiled = Arc::clone(&svc.wasm_modules["com.test.hello"].1);
+        assert_eq!(run(&mut svc), Some(serde_json::json!("BOB")));
+        assert!(Arc::ptr_eq(
+            &compiled,
+            &svc.wasm_modules["com.test.hello"].1
+        ));
+
+        svc.update_extension("com.test.hello", &manifest, Some(wasm(r#"["amy"]"#)))
+            .unwrap();
+        assert_eq!(run(&mut svc), Some(serde_json::json!("AMY")));
+        assert!(!Arc::ptr_eq(
+            &compiled,
+            &svc.wasm_modules["com.test.hello"].1
+        ));
+    }
+
     #[test]
     fn install_disable_uninstall() {
         let state = ExtensionsService::new();
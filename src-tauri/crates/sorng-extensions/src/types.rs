@@ -213,6 +213,9 @@ pub enum ExtensionType {
     CredentialStore,
     /// A monitoring / health-check plugin.
     Monitor,
+    /// A compiled WebAssembly module run by the WASM runtime instead of
+    /// the JSON script interpreter.
+    Wasm,
 }
 
 impl fmt::Display for ExtensionType {
@@ -0,0 +1,806 @@
+//! WebAssembly runtime for [`ExtensionType::Wasm`] extensions.
+//!
+//! Instead of a JSON [`ExtensionScript`], a WASM extension ships a
+//! WASI-less WebAssembly module (base64-encoded in the script source).
+//! Every exported `() -> ()` function is a handler that hooks can name.
+//!
+//! Modules run under the same [`Sandbox`] as scripts:
+//!
+//! * `max_instructions` is the fuel budget of each execution.
+//! * `max_memory_mb` caps linear memory (initial size and `memory.grow`).
+//! * `max_call_depth` caps WASM recursion.
+//! * The execution timeout and API rate limit are checked on every host
+//!   call and once more when the handler returns.
+//!
+//! ## Host ABI
+//!
+//! Modules that use host functions must export their linear memory as
+//! `memory`.  Imports live in the `sorng` module; pointers and lengths are
+//! `i32` offsets into that memory and payloads are UTF-8 JSON.
+//!
+//! | Import        | Signature                                           | Purpose                                            |
+//! |---------------|-----------------------------------------------------|----------------------------------------------------|
+//! | `input_len`   | `() -> i32`                                         | Length of the handler arguments (a JSON object)    |
+//! | `input_read`  | `(ptr)`                                             | Copy the handler arguments to `ptr`                |
+//! | `output`      | `(ptr, len)`                                        | Set the handler's return value                     |
+//! | `log`         | `(level, ptr, len)`                                 | Log a message (0 debug, 1 info, 2 warn, 3 error)   |
+//! | `emit`        | `(name_ptr, name_len, data_ptr, data_len)`          | Emit a custom event (`events.emit` permission)     |
+//! | `call`        | `(name_ptr, name_len, args_ptr, args_len) -> i32`   | Call an API function with a JSON argument array    |
+//! | `result_read` | `(ptr)`                                             | Copy the last `call` result to `ptr`               |
+//!
+//! `call` returns the length of the JSON result, or `-1` if the API
+//! function failed, in which case `result_read` yields the error message.
+//! Permission and sandbox violations abort the handler.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use base64::Engine as _;
+use log::{debug, error, info, warn};
+use wasmi::core::TrapCode;
+use wasmi::{
+    Caller, Config, Engine, Extern, ExternType, Linker, Module, StackLimits, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+use crate::api::ApiRegistry;
+use crate::permissions::PermissionChecker;
+use crate::runtime::{builtin_api_functions, ApiFn, RuntimeEnv};
+use crate::sandbox::Sandbox;
+use crate::types::*;
+
+/// Import module name for host functions.
+pub const HOST_MODULE: &str = "sorng";
+
+/// Name of the exported linear memory.
+pub const MEMORY_EXPORT: &str = "memory";
+
+// ─── Source Encoding ────────────────────────────────────────────────
+
+/// Decode the base64 script source of a WASM extension.
+pub fn decode_wasm_source(source: &str) -> ExtResult<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(source.trim())
+        .map_err(|e| ExtError::script(format!("WASM module is not valid base64: {}", e)))
+}
+
+/// Encode a WASM module as script source.
+pub fn encode_wasm_source(wasm: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(wasm)
+}
+
+/// Check that a WASM extension's module compiles and exports every
+/// handler named by its hook registrations.
+pub fn validate_wasm_extension(
+    manifest: &ExtensionManifest,
+    script_source: Option<&str>,
+    config: &SandboxConfig,
+) -> ExtResult<()> {
+    let source = script_source.ok_or_else(|| {
+        ExtError::script(format!(
+            "WASM extension '{}' has no module source",
+            manifest.id
+        ))
+    })?;
+    let module = WasmModule::from_source(source, config)?;
+    for hook in &manifest.hooks {
+        if !module.has_handler(&hook.handler) {
+            return Err(ExtError::script(format!(
+                "WASM module does not export handler '{}' for event '{}'",
+                hook.handler, hook.event
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ─── ApiGate ────────────────────────────────────────────────────────
+
+/// The API functions an extension may not call, resolved once per
+/// execution from the [`ApiRegistry`] and [`PermissionChecker`].
+///
+/// Functions that are not in the registry (host-only helpers) are
+/// always allowed, matching the script interpreter.
+#[derive(Debug, Clone, Default)]
+pub struct ApiGate {
+    denied: HashMap<String, ExtError>,
+}
+
+impl ApiGate {
+    /// Resolve `extension_id`'s access to every registered API function.
+    pub fn new(
+        extension_id: &str,
+        registry: &ApiRegistry,
+        permissions: &PermissionChecker,
+    ) -> Self {
+        let denied = registry
+            .function_names()
+            .into_iter()
+            .filter_map(|name| {
+                registry
+                    .check_access(&name, extension_id, permissions)
+                    .err()
+                    .map(|e| (name, e))
+            })
+            .collect();
+        Self { denied }
+    }
+
+    /// A gate that allows every function.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Check whether `function` may be called.
+    pub fn check(&self, function: &str) -> ExtResult<()> {
+        match self.denied.get(function) {
+            Some(e) => Err(e.clone()),
+            None => Ok(()),
+        }
+    }
+}
+
+// ─── Host State ─────────────────────────────────────────────────────
+
+/// Per-execution data owned by the WASM store.
+struct HostState {
+    sandbox: Sandbox,
+    env: RuntimeEnv,
+    limits: StoreLimits,
+    api_functions: Arc<HashMap<String, ApiFn>>,
+    gate: ApiGate,
+    input: Vec<u8>,
+    output: Option<serde_json::Value>,
+    result: Vec<u8>,
+    /// Size of the guest's linear memory when the handler returned.
+    memory_bytes: u64,
+    /// The sandbox or permission error that aborted execution, if any.
+    violation: Option<ExtError>,
+}
+
+impl HostState {
+    /// Record a violation and turn it into a trap.
+    fn abort(&mut self, err: ExtError) -> wasmi::Error {
+        let trap = wasmi::Error::new(err.message.clone());
+        self.violation = Some(err);
+        trap
+    }
+
+    /// Checks run before every gated host call.
+    fn enter(&mut self, function: &str) -> ExtResult<()> {
+        self.sandbox.check_timeout()?;
+        self.gate.check(function)?;
+        self.sandbox.tick_api_call()
+    }
+}
+
+// ─── WasmModule ─────────────────────────────────────────────────────
+
+/// A compiled WASM extension module.
+pub struct WasmModule {
+    engine: Engine,
+    module: Module,
+    /// Host API function implementations, starting with the built-ins.
+    api_functions: Arc<HashMap<String, ApiFn>>,
+}
+
+impl WasmModule {
+    /// Compile a module.  The WASM call stack is limited to the config's
+    /// `max_call_depth`.
+    pub fn new(wasm: &[u8], config: &SandboxConfig) -> ExtResult<Self> {
+        let stack_limits = StackLimits {
+            maximum_recursion_depth: config.max_call_depth as usize,
+            ..StackLimits::default()
+        };
+
+        let mut engine_config = Config::default();
+        engine_config
+            .consume_fuel(true)
+            .set_stack_limits(stack_limits);
+        let engine = Engine::new(&engine_config);
+
+        let module = Module::new(&engine, wasm)
+            .map_err(|e| ExtError::script(format!("Invalid WASM module: {}", e)))?;
+
+        Ok(Self {
+            engine,
+            module,
+            api_functions: Arc::new(builtin_api_functions()),
+        })
+    }
+
+    /// Compile a module from base64 script source.
+    pub fn from_source(source: &str, config: &SandboxConfig) -> ExtResult<Self> {
+        Self::new(&decode_wasm_source(source)?, config)
+    }
+
+    /// Register a host API function callable through `sorng.call`.
+    pub fn register_api<F>(&mut self, name: impl Into<String>, func: F)
+    where
+        F: Fn(&[ScriptValue], &mut RuntimeEnv) -> ExtResult<ScriptValue> + Send + Sync + 'static,
+    {
+        // Stores (and their clones of the map) only live for the duration
+        // of `run_handler`, so the map is never shared here.
+        Arc::get_mut(&mut self.api_functions)
+            .expect("API functions cannot be registered during execution")
+            .insert(name.into(), Box::new(func));
+    }
+
+    /// Get the list of exported handler names.
+    pub fn handler_names(&self) -> Vec<String> {
+        self.module
+            .exports()
+            .filter(|export| is_handler(export.ty()))
+            .map(|export| export.name().to_string())
+            .collect()
+    }
+
+    /// Check whether the module exports a specific handler.
+    pub fn has_handler(&self, name: &str) -> bool {
+        self.module
+            .exports()
+            .any(|export| export.name() == name && is_handler(export.ty()))
+    }
+
+    /// Execute an exported handler with optional arguments.
+    ///
+    /// Guest traps and limit violations produce an unsuccessful
+    /// [`ExecutionResult`]; only a missing handler or an already active
+    /// sandbox is returned as an error.
+    pub fn run_handler(
+        &self,
+        handler_name: &str,
+        args: HashMap<String, ScriptValue>,
+        sandbox: &mut Sandbox,
+        env: &mut RuntimeEnv,
+        gate: &ApiGate,
+    ) -> ExtResult<ExecutionResult> {
+        if !self.has_handler(handler_name) {
+            return Err(ExtError::script(format!(
+                "Handler '{}' not found",
+                handler_name
+            )));
+        }
+
+        let input: serde_json::Map<String, serde_json::Value> =
+            args.into_iter().map(|(k, v)| (k, v.into())).collect();
+        let input = serde_json::to_vec(&input)
+            .map_err(|e| ExtError::script(format!("Failed to encode handler arguments: {}", e)))?;
+
+        let start = Instant::now();
+        sandbox.begin()?;
+
+        let config = sandbox.config().clone();
+        let memory_limit = (config.max_memory_mb as usize).saturating_mul(1024 * 1024);
+        let state = HostState {
+            sandbox: std::mem::replace(sandbox, Sandbox::new(config.clone())),
+            env: std::mem::take(env),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(memory_limit)
+                .instances(1)
+                .memories(1)
+                .tables(1)
+                .build(),
+            api_functions: Arc::clone(&self.api_functions),
+            gate: gate.clone(),
+            input,
+            output: None,
+            result: Vec::new(),
+            memory_bytes: 0,
+            violation: None,
+        };
+
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+
+        let outcome = store
+            .set_fuel(config.max_instructions)
+            .map_err(|e| wasmi::Error::new(e.to_string()))
+            .and_then(|()| self.invoke(&mut store, handler_name));
+
+        let fuel_used = config
+            .max_instructions
+            .saturating_sub(store.get_fuel().unwrap_or(0));
+
+        let mut state = store.into_data();
+        let outcome = match outcome {
+            Ok(()) => Ok(()),
+            Err(trap) => Err(state
+                .violation
+                .take()
+                .unwrap_or_else(|| map_trap(&trap, &config))),
+        };
+        *sandbox = state.sandbox;
+        *env = state.env;
+
+        let outcome = outcome
+            .and_then(|()| sandbox.record_instructions(fuel_used))
+            .and_then(|()| sandbox.allocate_memory(state.memory_bytes))
+            .and_then(|()| sandbox.check_timeout());
+
+        let metrics = sandbox.end().unwrap_or_default();
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        match outcome {
+            Ok(()) => Ok(ExecutionResult {
+                success: true,
+                output: state.output,
+                error: None,
+                duration_ms,
+                instructions_executed: fuel_used,
+                memory_used_bytes: metrics.memory_used_bytes,
+                log_output: env.log_output().to_vec(),
+            }),
+            Err(e) => {
+                debug!("WASM handler '{}' failed: {}", handler_name, e.message);
+                Ok(ExecutionResult {
+                    success: false,
+                    output: None,
+                    error: Some(e.message),
+                    duration_ms,
+                    instructions_executed: fuel_used,
+                    memory_used_bytes: metrics.memory_used_bytes,
+                    log_output: env.log_output().to_vec(),
+                })
+            }
+        }
+    }
+
+    // ── Execution ───────────────────────────────────────────────
+
+    fn invoke(&self, store: &mut Store<HostState>, handler_name: &str) -> Result<(), wasmi::Error> {
+        let linker = host_linker(&self.engine)?;
+        let instance = linker
+            .instantiate(&mut *store, &self.module)?
+            .start(&mut *store)?;
+        let result = instance
+            .get_typed_func::<(), ()>(&*store, handler_name)
+            .and_then(|handler| handler.call(&mut *store, ()));
+        if let Some(memory) = instance.get_memory(&*store, MEMORY_EXPORT) {
+            let bytes = memory.current_pages(&*store).to_bytes().unwrap_or(0);
+            store.data_mut().memory_bytes = bytes as u64;
+        }
+        result
+    }
+}
+
+fn is_handler(ty: &ExternType) -> bool {
+    matches!(ty, ExternType::Func(f) if f.params().is_empty() && f.results().is_empty())
+}
+
+fn map_trap(trap: &wasmi::Error, config: &SandboxConfig) -> ExtError {
+    match trap.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => ExtError::sandbox(format!(
+            "Instruction limit exceeded: fuel budget of {} exhausted",
+            config.max_instructions
+        )),
+        Some(TrapCode::StackOverflow) => ExtError::sandbox(format!(
+            "Call depth exceeded: limit is {}",
+            config.max_call_depth
+        )),
+        _ => ExtError::script(format!("WASM trap: {}", trap)),
+    }
+}
+
+// ─── Host Functions ─────────────────────────────────────────────────
+
+fn host_linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::<HostState>::new(engine);
+
+    linker.func_wrap(HOST_MODULE, "input_len", |caller: Caller<'_, HostState>| {
+        caller.data().input.len() as i32
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "input_read",
+        |mut caller: Caller<'_, HostState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let input = std::mem::take(&mut caller.data_mut().input);
+            let written = write_guest(&mut caller, ptr, &input);
+            caller.data_mut().input = input;
+            written
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "output",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let value = read_json(&mut caller, ptr, len)?;
+            caller.data_mut().output = Some(value);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>,
+         level: i32,
+         ptr: i32,
+         len: i32|
+         -> Result<(), wasmi::Error> {
+            let msg = read_string(&mut caller, ptr, len)?;
+            let level = match level {
+                0 => LogLevel::Debug,
+                1 => LogLevel::Info,
+                2 => LogLevel::Warn,
+                _ => LogLevel::Error,
+            };
+            match level {
+                LogLevel::Debug => debug!("[ext] {}", msg),
+                LogLevel::Info => info!("[ext] {}", msg),
+                LogLevel::Warn => warn!("[ext] {}", msg),
+                LogLevel::Error => error!("[ext] {}", msg),
+            }
+            caller.data_mut().env.log(level, msg);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "emit",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         data_ptr: i32,
+         data_len: i32|
+         -> Result<(), wasmi::Error> {
+            let name = read_string(&mut caller, name_ptr, name_len)?;
+            let data = read_json(&mut caller, data_ptr, data_len)?;
+            let state = caller.data_mut();
+            if let Err(e) = state.enter("events.emit") {
+                return Err(state.abort(e));
+            }
+            state.env.emit(name, data.into());
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "call",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         args_ptr: i32,
+         args_len: i32|
+         -> Result<i32, wasmi::Error> {
+            let name = read_string(&mut caller, name_ptr, name_len)?;
+            let args = read_json(&mut caller, args_ptr, args_len)?;
+            let state = caller.data_mut();
+            if let Err(e) = state.enter(&name) {
+                return Err(state.abort(e));
+            }
+            let args: Vec<ScriptValue> = match args {
+                serde_json::Value::Array(items) => items.into_iter().map(Into::into).collect(),
+                serde_json::Value::Null => Vec::new(),
+                _ => {
+                    return Err(state.abort(ExtError::script("API arguments must be a JSON array")))
+                }
+            };
+            if let Err(e) = state.sandbox.push_call() {
+                return Err(state.abort(e));
+            }
+
+            let result = match state.api_functions.get(name.as_str()) {
+                Some(func) => func(&args, &mut state.env),
+                None => Err(ExtError::api_unavailable(format!(
+                    "API function '{}' not found",
+                    name
+                ))),
+            };
+
+            state.sandbox.pop_call();
+
+            let (code, payload) = match result {
+                Ok(value) => {
+                    let json = serde_json::Value::from(value).to_string().into_bytes();
+                    (json.len() as i32, json)
+                }
+                Err(e) => (-1, e.message.into_bytes()),
+            };
+            state.result = payload;
+            Ok(code)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "result_read",
+        |mut caller: Caller<'_, HostState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let result = std::mem::take(&mut caller.data_mut().result);
+            let written = write_guest(&mut caller, ptr, &result);
+            caller.data_mut().result = result;
+            written
+        },
+    )?;
+
+    Ok(linker)
+}
+
+// ─── Guest Memory ───────────────────────────────────────────────────
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> Result<wasmi::Memory, wasmi::Error> {
+    match caller
+        .get_export(MEMORY_EXPORT)
+        .and_then(Extern::into_memory)
+    {
+        Some(memory) => Ok(memory),
+        None => Err(caller.data_mut().abort(ExtError::script(format!(
+            "WASM module does not export '{}'",
+            MEMORY_EXPORT
+        )))),
+    }
+}
+
+fn read_guest(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<Vec<u8>, wasmi::Error> {
+    let memory = guest_memory(caller)?;
+    let data = memory.data(&*caller);
+    let bytes = usize::try_from(ptr)
+        .ok()
+        .zip(usize::try_from(len).ok())
+        .and_then(|(ptr, len)| data.get(ptr..ptr.checked_add(len)?))
+        .map(<[u8]>::to_vec);
+    bytes.ok_or_else(|| {
+        caller.data_mut().abort(ExtError::script(format!(
+            "Out-of-bounds guest memory access at {} (+{})",
+            ptr, len
+        )))
+    })
+}
+
+fn write_guest(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    bytes: &[u8],
+) -> Result<(), wasmi::Error> {
+    let memory = guest_memory(caller)?;
+    let offset = usize::try_from(ptr).unwrap_or(usize::MAX);
+    if memory.write(&mut *caller, offset, bytes).is_err() {
+        return Err(caller.data_mut().abort(ExtError::script(format!(
+            "Out-of-bounds guest memory access at {} (+{})",
+            ptr,
+            bytes.len()
+        ))));
+    }
+    Ok(())
+}
+
+fn read_string(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<String, wasmi::Error> {
+    let bytes = read_guest(caller, ptr, len)?;
+    String::from_utf8(bytes).map_err(|_| {
+        caller
+            .data_mut()
+            .abort(ExtError::script("Guest string is not valid UTF-8"))
+    })
+}
+
+fn read_json(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<serde_json::Value, wasmi::Error> {
+    if len == 0 {
+        return Ok(serde_json::Value::Null);
+    }
+    let bytes = read_guest(caller, ptr, len)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        caller
+            .data_mut()
+            .abort(ExtError::script(format!("Guest sent invalid JSON: {}", e)))
+    })
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST_IMPORTS: &str = r#"
+        (import "sorng" "input_len" (func $input_len (result i32)))
+        (import "sorng" "input_read" (func $input_read (param i32)))
+        (import "sorng" "output" (func $output (param i32 i32)))
+        (import "sorng" "log" (func $log (param i32 i32 i32)))
+        (import "sorng" "emit" (func $emit (param i32 i32 i32 i32)))
+        (import "sorng" "call" (func $call (param i32 i32 i32 i32) (result i32)))
+        (import "sorng" "result_read" (func $result_read (param i32)))
+        (memory (export "memory") 1)
+    "#;
+
+    fn config() -> SandboxConfig {
+        SandboxConfig {
+            max_instructions: 100_000,
+            max_execution_time_ms: 5_000,
+            max_call_depth: 32,
+            max_memory_mb: 1,
+            ..SandboxConfig::default()
+        }
+    }
+
+    fn compile(body: &str) -> WasmModule {
+        let wat = format!("(module {} {})", HOST_IMPORTS, body);
+        WasmModule::new(&wat::parse_str(wat).unwrap(), &config()).unwrap()
+    }
+
+    fn run(module: &WasmModule, handler: &str, gate: &ApiGate) -> (ExecutionResult, RuntimeEnv) {
+        let mut sandbox = Sandbox::new(config());
+        let mut env = RuntimeEnv::new();
+        let mut args = HashMap::new();
+        args.insert("name".to_string(), ScriptValue::String("bob".into()));
+        let result = module
+            .run_handler(handler, args, &mut sandbox, &mut env, gate)
+            .unwrap();
+        assert!(!sandbox.is_active());
+        (result, env)
+    }
+
+    #[test]
+    fn echo_handler_reads_input_and_logs() {
+        let module = compile(
+            r#"
+            (data (i32.const 0) "hello")
+            (func (export "echo")
+                (call $log (i32.const 1) (i32.const 0) (i32.const 5))
+                (call $input_read (i32.const 1024))
+                (call $output (i32.const 1024) (call $input_len)))
+            "#,
+        );
+        assert_eq!(module.handler_names(), vec!["echo".to_string()]);
+
+        let (result, env) = run(&module, "echo", &ApiGate::allow_all());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, Some(serde_json::json!({ "name": "bob" })));
+        assert!(result.instructions_executed > 0);
+        assert_eq!(result.memory_used_bytes, 65_536);
+        assert_eq!(env.log_output()[0].message, "hello");
+    }
+
+    #[test]
+    fn fuel_limit_stops_infinite_loop() {
+        let module = compile(r#"(func (export "spin") (loop $l (br $l)))"#);
+        let (result, _) = run(&module, "spin", &ApiGate::allow_all());
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Instruction limit exceeded"));
+        assert!(result.instructions_executed > 99_000);
+    }
+
+    #[test]
+    fn memory_growth_is_capped() {
+        // 1 MB = 16 pages; the module already has one.
+        let module = compile(
+            r#"
+            (func (export "grow")
+                (if (i32.ne (memory.grow (i32.const 16)) (i32.const -1))
+                    (then unreachable)))
+            "#,
+        );
+        let (result, _) = run(&module, "grow", &ApiGate::allow_all());
+        assert!(result.success, "{:?}", result.error);
+    }
+
+    #[test]
+    fn recursion_depth_is_limited() {
+        let module = compile(r#"(func $f (export "recurse") (call $f))"#);
+        let (result, _) = run(&module, "recurse", &ApiGate::allow_all());
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Call depth exceeded"));
+    }
+
+    #[test]
+    fn call_dispatches_to_registered_api() {
+        let mut module = compile(
+            r#"
+            (data (i32.const 0) "greet")
+            (data (i32.const 16) "[\"bob\"]")
+            (func (export "run") (local $n i32)
+                (local.set $n (call $call (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 7)))
+                (if (i32.lt_s (local.get $n) (i32.const 0)) (then unreachable))
+                (call $result_read (i32.const 1024))
+                (call $output (i32.const 1024) (local.get $n)))
+            "#,
+        );
+        module.register_api("greet", |args, _env| {
+            let name = args.first().and_then(|v| v.as_str()).unwrap_or("?");
+            Ok(ScriptValue::String(format!("hi {}", name)))
+        });
+
+        let (result, _) = run(&module, "run", &ApiGate::allow_all());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, Some(serde_json::json!("hi bob")));
+    }
+
+    #[test]
+    fn call_reaches_builtin_api() {
+        let module = compile(
+            r#"
+            (data (i32.const 0) "math.add")
+            (data (i32.const 16) "[40, 2]")
+            (func (export "run") (local $n i32)
+                (local.set $n (call $call (i32.const 0) (i32.const 8) (i32.const 16) (i32.const 7)))
+                (if (i32.lt_s (local.get $n) (i32.const 0)) (then unreachable))
+                (call $result_read (i32.const 1024))
+                (call $output (i32.const 1024) (local.get $n)))
+            "#,
+        );
+        let (result, _) = run(&module, "run", &ApiGate::allow_all());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, Some(serde_json::json!(42.0)));
+    }
+
+    #[test]
+    fn unknown_api_returns_error_code() {
+        let module = compile(
+            r#"
+            (data (i32.const 0) "nope.nope")
+            (data (i32.const 64) "true")
+            (func (export "run")
+                (if (i32.eq (call $call (i32.const 0) (i32.const 9) (i32.const 0) (i32.const 0))
+                            (i32.const -1))
+                    (then (call $output (i32.const 64) (i32.const 4)))))
+            "#,
+        );
+        let (result, _) = run(&module, "run", &ApiGate::allow_all());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, Some(serde_json::json!(true)));
+    }
+
+    #[test]
+    fn permissions_gate_api_calls_and_events() {
+        let module = compile(
+            r#"
+            (data (i32.const 0) "storage.get")
+            (data (i32.const 16) "[\"k\"]")
+            (data (i32.const 32) "ping")
+            (func (export "read")
+                (drop (call $call (i32.const 0) (i32.const 11) (i32.const 16) (i32.const 5))))
+            (func (export "ping")
+                (call $emit (i32.const 32) (i32.const 4) (i32.const 0) (i32.const 0)))
+            "#,
+        );
+        let registry = ApiRegistry::new();
+        let mut permissions = PermissionChecker::new();
+        permissions.grant("com.test.wasm", &[Permission::EventEmit]);
+        let gate = ApiGate::new("com.test.wasm", &registry, &permissions);
+
+        let (result, _) = run(&module, "read", &gate);
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("lacks permission"));
+
+        let (result, env) = run(&module, "ping", &gate);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(env.emitted_events()[0].0, "ping");
+    }
+
+    #[test]
+    fn validate_requires_hook_handlers() {
+        let wasm = wat::parse_str(r#"(module (func (export "on_startup")))"#).unwrap();
+        let source = encode_wasm_source(&wasm);
+        let mut manifest = crate::manifest::create_manifest(
+            "com.test.wasm",
+            "Wasm",
+            "1.0.0",
+            "A WASM extension",
+            "Test",
+            ExtensionType::Wasm,
+        );
+        manifest.hooks = vec![HookRegistration::new(HookEvent::AppStartup, "on_startup")];
+        assert!(validate_wasm_extension(&manifest, Some(&source), &config()).is_ok());
+
+        manifest.hooks = vec![HookRegistration::new(HookEvent::AppShutdown, "on_shutdown")];
+        assert!(validate_wasm_extension(&manifest, Some(&source), &config()).is_err());
+        assert!(validate_wasm_extension(&manifest, None, &config()).is_err());
+        assert!(validate_wasm_extension(&manifest, Some("not base64!"), &config()).is_err());
+    }
+}
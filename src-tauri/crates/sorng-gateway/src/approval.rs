@@ -0,0 +1,602 @@
+//! # Approval Workflow
+//!
+//! Just-in-time access for `RequireApproval` policies. A connection attempt is
+//! parked as an [`ApprovalRequest`]; an approver turns it into a time-boxed
+//! [`AccessGrant`] (or rejects it). Every step is written to an append-only
+//! audit trail. Grants are removed once they expire or are revoked — the
+//! gateway service then tears down the sessions opened under them.
+
+use crate::types::*;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Audit entries kept in memory (the on-disk trail is unbounded).
+const MAX_AUDIT_IN_MEMORY: usize = 10_000;
+/// How long decided requests are kept for review.
+const DECIDED_REQUEST_RETENTION_DAYS: i64 = 7;
+
+/// Tracks approval requests, live grants, and the approval audit trail.
+pub struct ApprovalManager {
+    /// Requests indexed by request ID
+    requests: HashMap<String, ApprovalRequest>,
+    /// Live (unexpired, unrevoked) grants indexed by grant ID
+    grants: HashMap<String, AccessGrant>,
+    /// Recent audit entries, oldest first
+    audit: Vec<ApprovalAuditEntry>,
+    /// Persistence directory
+    data_dir: String,
+}
+
+impl ApprovalManager {
+    pub fn new(data_dir: &str) -> Self {
+        let mut manager = Self {
+            requests: HashMap::new(),
+            grants: HashMap::new(),
+            audit: Vec::new(),
+            data_dir: data_dir.to_string(),
+        };
+        manager.load_from_disk();
+        manager
+    }
+
+    /// Park a connection attempt until an approver decides it.
+    pub fn submit(
+        &mut self,
+        policy_id: &str,
+        settings: &ApprovalSettings,
+        request: &AccessRequest,
+    ) -> Result<ApprovalRequest, String> {
+        if request.reason.trim().is_empty() {
+            return Err("A reason is required to request access".to_string());
+        }
+        let ticket_id = request
+            .ticket_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        if settings.require_ticket && ticket_id.is_none() {
+            return Err("This policy requires a ticket ID".to_string());
+        }
+        if request.duration_secs == 0 {
+            return Err("Requested duration must be > 0".to_string());
+        }
+        if let Some(pending) = self.requests.values().find(|r| {
+            r.status == ApprovalStatus::Pending
+                && r.user_id == request.user_id
+                && r.protocol == request.protocol
+                && r.target_addr == request.target_addr
+        }) {
+            return Err(format!(
+                "Approval request {} is already pending for this target",
+                pending.id
+            ));
+        }
+
+        let now = Utc::now();
+        let approval = ApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            policy_id: policy_id.to_string(),
+            user_id: request.user_id.clone(),
+            username: request.username.clone(),
+            protocol: request.protocol,
+            target_addr: request.target_addr.clone(),
+            source_addr: request.source_addr.clone(),
+            reason: request.reason.trim().to_string(),
+            ticket_id: ticket_id.map(str::to_string),
+            requested_duration_secs: request.duration_secs.min(settings.max_grant_secs),
+            status: ApprovalStatus::Pending,
+            requested_at: now,
+            expires_at: now + secs(settings.request_ttl_secs),
+            decided_by: None,
+            decided_at: None,
+            decision_note: None,
+            grant_id: None,
+        };
+        self.requests.insert(approval.id.clone(), approval.clone());
+        self.record(
+            ApprovalAuditEvent::Requested,
+            &approval.user_id,
+            Some(&approval.id),
+            None,
+            Some(format!(
+                "{:?} {} for {}s: {}",
+                approval.protocol,
+                approval.target_addr,
+                approval.requested_duration_secs,
+                approval.reason
+            )),
+        );
+        self.persist();
+        Ok(approval)
+    }
+
+    /// Approve a pending request and issue its grant. `duration_secs`
+    /// overrides the requested duration; both are capped by the policy.
+    pub fn approve(
+        &mut self,
+        request_id: &str,
+        approver: &str,
+        settings: &ApprovalSettings,
+        duration_secs: Option<u64>,
+        note: Option<&str>,
+    ) -> Result<AccessGrant, String> {
+        let now = Utc::now();
+        self.check_decidable(request_id, approver, settings, now)?;
+
+        let request = self
+            .requests
+            .get_mut(request_id)
+            .ok_or("Approval request not found")?;
+        let duration = duration_secs
+            .unwrap_or(request.requested_duration_secs)
+            .min(settings.max_grant_secs);
+        if duration == 0 {
+            return Err("Grant duration must be > 0".to_string());
+        }
+        let grant = AccessGrant {
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: request.id.clone(),
+            policy_id: request.policy_id.clone(),
+            user_id: request.user_id.clone(),
+            protocol: request.protocol,
+            target_addr: request.target_addr.clone(),
+            reason: request.reason.clone(),
+            ticket_id: request.ticket_id.clone(),
+            approved_by: approver.to_string(),
+            granted_at: now,
+            expires_at: now + secs(duration),
+        };
+        request.status = ApprovalStatus::Approved;
+        request.decided_by = Some(approver.to_string());
+        request.decided_at = Some(now);
+        request.decision_note = note.map(str::to_string);
+        request.grant_id = Some(grant.id.clone());
+
+        self.grants.insert(grant.id.clone(), grant.clone());
+        self.record(
+            ApprovalAuditEvent::Approved,
+            approver,
+            Some(request_id),
+            Some(&grant.id),
+            Some(format!(
+                "granted for {}s until {}",
+                duration, grant.expires_at
+            )),
+        );
+        self.persist();
+        Ok(grant)
+    }
+
+    /// Reject a pending request.
+    pub fn deny(
+        &mut self,
+        request_id: &str,
+        approver: &str,
+        settings: &ApprovalSettings,
+        note: Option<&str>,
+    ) -> Result<ApprovalRequest, String> {
+        let now = Utc::now();
+        self.check_decidable(request_id, approver, settings, now)?;
+
+        let request = self
+            .requests
+            .get_mut(request_id)
+            .ok_or("Approval request not found")?;
+        request.status = ApprovalStatus::Denied;
+        request.decided_by = Some(approver.to_string());
+        request.decided_at = Some(now);
+        request.decision_note = note.map(str::to_string);
+        let request = request.clone();
+
+        self.record(
+            ApprovalAuditEvent::Denied,
+            approver,
+            Some(request_id),
+            None,
+            note.map(str::to_string),
+        );
+        self.persist();
+        Ok(request)
+    }
+
+    /// Withdraw a pending request. Only the requester may cancel it.
+    pub fn cancel(&mut self, request_id: &str, user_id: &str) -> Result<(), String> {
+        let request = self
+            .requests
+            .get_mut(request_id)
+            .ok_or("Approval request not found")?;
+        if request.user_id != user_id {
+            return Err("Only the requester can cancel an approval request".to_string());
+        }
+        if request.status != ApprovalStatus::Pending {
+            return Err(format!("Approval request is {:?}", request.status));
+        }
+        request.status = ApprovalStatus::Cancelled;
+        request.decided_at = Some(Utc::now());
+
+        self.record(
+            ApprovalAuditEvent::Cancelled,
+            user_id,
+            Some(request_id),
+            None,
+            None,
+        );
+        self.persist();
+        Ok(())
+    }
+
+    /// Revoke a live grant before it expires.
+    pub fn revoke_grant(
+        &mut self,
+        grant_id: &str,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<AccessGrant, String> {
+        let grant = self.grants.remove(grant_id).ok_or("Grant not found")?;
+        self.record(
+            ApprovalAuditEvent::GrantRevoked,
+            actor,
+            Some(&grant.request_id),
+            Some(grant_id),
+            reason.map(str::to_string),
+        );
+        self.persist();
+        Ok(grant)
+    }
+
+    /// Find a live grant covering this user, protocol and target.
+    pub fn find_grant(
+        &self,
+        user_id: &str,
+        protocol: GatewayProtocol,
+        target_addr: &str,
+    ) -> Option<&AccessGrant> {
+        let now = Utc::now();
+        self.grants
+            .values()
+            .filter(|g| {
+                g.user_id == user_id
+                    && g.protocol == protocol
+                    && g.target_addr == target_addr
+                    && g.is_active_at(now)
+            })
+            .max_by_key(|g| g.expires_at)
+    }
+
+    /// Audit that a session was opened under a grant.
+    pub fn record_grant_use(&mut self, grant_id: &str, session_id: &str) {
+        let request_id = self.grants.get(grant_id).map(|g| g.request_id.clone());
+        self.record(
+            ApprovalAuditEvent::GrantUsed,
+            "system",
+            request_id.as_deref(),
+            Some(grant_id),
+            Some(format!("session {}", session_id)),
+        );
+    }
+
+    /// Audit that a session was torn down because its grant ended.
+    pub fn record_teardown(&mut self, grant: &AccessGrant, session_id: &str) {
+        self.record(
+            ApprovalAuditEvent::SessionTornDown,
+            "system",
+            Some(&grant.request_id),
+            Some(&grant.id),
+            Some(format!("session {}", session_id)),
+        );
+    }
+
+    /// Lapse undecided requests and remove expired grants.
+    /// Returns the requests and grants that expired in this pass.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> (Vec<ApprovalRequest>, Vec<AccessGrant>) {
+        let mut lapsed = Vec::new();
+        for request in self.requests.values_mut() {
+            if request.status == ApprovalStatus::Pending && now >= request.expires_at {
+                request.status = ApprovalStatus::Expired;
+                request.decided_at = Some(now);
+                lapsed.push(request.clone());
+            }
+        }
+        let expired_ids: Vec<String> = self
+            .grants
+            .values()
+            .filter(|g| !g.is_active_at(now))
+            .map(|g| g.id.clone())
+            .collect();
+        let expired: Vec<AccessGrant> = expired_ids
+            .iter()
+            .filter_map(|id| self.grants.remove(id))
+            .collect();
+
+        let retention = Duration::days(DECIDED_REQUEST_RETENTION_DAYS);
+        let before = self.requests.len();
+        self.requests.retain(|_, r| {
+            r.status == ApprovalStatus::Pending
+                || !r.decided_at.is_some_and(|at| now - at >= retention)
+        });
+
+        for request in &lapsed {
+            self.record(
+                ApprovalAuditEvent::RequestExpired,
+                "system",
+                Some(&request.id),
+                None,
+                None,
+            );
+        }
+        for grant in &expired {
+            self.record(
+                ApprovalAuditEvent::GrantExpired,
+                "system",
+                Some(&grant.request_id),
+                Some(&grant.id),
+                None,
+            );
+        }
+        if !lapsed.is_empty() || !expired.is_empty() || self.requests.len() != before {
+            self.persist();
+        }
+        (lapsed, expired)
+    }
+
+    /// Get a request by ID.
+    pub fn get_request(&self, request_id: &str) -> Option<&ApprovalRequest> {
+        self.requests.get(request_id)
+    }
+
+    /// The pending request for this user and target, if any.
+    pub fn pending_for(
+        &self,
+        user_id: &str,
+        protocol: GatewayProtocol,
+        target_addr: &str,
+    ) -> Option<&ApprovalRequest> {
+        self.requests.values().find(|r| {
+            r.status == ApprovalStatus::Pending
+                && r.user_id == user_id
+                && r.protocol == protocol
+                && r.target_addr == target_addr
+        })
+    }
+
+    /// List pending requests, oldest first.
+    pub fn list_pending(&self) -> Vec<&ApprovalRequest> {
+        let mut pending: Vec<&ApprovalRequest> = self
+            .requests
+            .values()
+            .filter(|r| r.status == ApprovalStatus::Pending)
+            .collect();
+        pending.sort_by_key(|r| r.requested_at);
+        pending
+    }
+
+    /// List live grants, soonest to expire first.
+    pub fn list_grants(&self) -> Vec<&AccessGrant> {
+        let mut grants: Vec<&AccessGrant> = self.grants.values().collect();
+        grants.sort_by_key(|g| g.expires_at);
+        grants
+    }
+
+    /// The most recent audit entries, newest last.
+    pub fn audit_log(&self, limit: usize) -> &[ApprovalAuditEntry] {
+        &self.audit[self.audit.len().saturating_sub(limit)..]
+    }
+
+    /// Ensure a request is pending, unexpired, and `approver` may decide it.
+    fn check_decidable(
+        &mut self,
+        request_id: &str,
+        approver: &str,
+        settings: &ApprovalSettings,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let request = self
+            .requests
+            .get(request_id)
+            .ok_or("Approval request not found")?;
+        if request.status != ApprovalStatus::Pending {
+            return Err(format!("Approval request is {:?}", request.status));
+        }
+        if request.user_id == approver {
+            return Err("Requesters cannot approve their own access".to_string());
+        }
+        if !settings.approvers.is_empty() && !settings.approvers.iter().any(|a| a == approver) {
+            return Err(format!("{} is not an approver for this policy", approver));
+        }
+        if now >= request.expires_at {
+            self.expire(now);
+            return Err("Approval request has expired".to_string());
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        event: ApprovalAuditEvent,
+        actor: &str,
+        request_id: Option<&str>,
+        grant_id: Option<&str>,
+        details: Option<String>,
+    ) {
+        let entry = ApprovalAuditEntry {
+            timestamp: Utc::now(),
+            event,
+            actor: actor.to_string(),
+            request_id: request_id.map(str::to_string),
+            grant_id: grant_id.map(str::to_string),
+            details,
+        };
+        log::info!(
+            "[APPROVAL] {:?} by {} (request={:?}, grant={:?})",
+            entry.event,
+            entry.actor,
+            entry.request_id,
+            entry.grant_id
+        );
+        self.append_audit(&entry);
+        self.audit.push(entry);
+        if self.audit.len() > MAX_AUDIT_IN_MEMORY {
+            let excess = self.audit.len() - MAX_AUDIT_IN_MEMORY;
+            self.audit.drain(..excess);
+        }
+    }
+
+    // ── Persistence ─────────────────────────────────────────────────
+
+    fn persist(&self) {
+        let path = std::path::Path::new(&self.data_dir).join("gateway_approvals.json");
+        let state = serde_json::json!({
+            "requests": self.requests,
+            "grants": self.grants,
+        });
+        if let Ok(json) = serde_json::to_string_pretty(&state) {
+            let _ = std::fs::create_dir_all(&self.data_dir);
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    fn append_audit(&self, entry: &ApprovalAuditEntry) {
+        let path = std::path::Path::new(&self.data_dir).join("gateway_approval_audit.jsonl");
+        let Ok(line) = serde_json::to_string(entry) else {
+            return;
+        };
+        let _ = std::fs::create_dir_all(&self.data_dir);
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = written {
+            log::error!("[APPROVAL] Failed to write audit trail: {}", e);
+        }
+    }
+
+    fn load_from_disk(&mut self) {
+        let dir = std::path::Path::new(&self.data_dir);
+        if let Ok(data) = std::fs::read_to_string(dir.join("gateway_approvals.json")) {
+            if let Ok(mut state) = serde_json::from_str::<serde_json::Value>(&data) {
+                if let Ok(requests) = serde_json::from_value(state["requests"].take()) {
+                    self.requests = requests;
+                }
+                if let Ok(grants) = serde_json::from_value(state["grants"].take()) {
+                    self.grants = grants;
+                }
+            }
+        }
+        if let Ok(data) = std::fs::read_to_string(dir.join("gateway_approval_audit.jsonl")) {
+            self.audit = data
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect();
+            let excess = self.audit.len().saturating_sub(MAX_AUDIT_IN_MEMORY);
+            self.audit.drain(..excess);
+        }
+    }
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::seconds(
+        i64::try_from(secs)
+            .unwrap_or(i64::MAX)
+            .min(100 * 365 * 86_400),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access_request(user: &str) -> AccessRequest {
+        AccessRequest {
+            user_id: user.to_string(),
+            username: user.to_string(),
+            protocol: GatewayProtocol::Ssh,
+            target_addr: "10.0.0.5:22".to_string(),
+            source_addr: "192.168.1.10".to_string(),
+            reason: "hotfix".to_string(),
+            ticket_id: Some("CHG-42".to_string()),
+            duration_secs: 7200,
+        }
+    }
+
+    fn settings() -> ApprovalSettings {
+        ApprovalSettings {
+            approvers: vec!["lead".to_string()],
+            max_grant_secs: 3600,
+            request_ttl_secs: 600,
+            require_ticket: true,
+        }
+    }
+
+    #[test]
+    fn approval_issues_capped_grant_and_audits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut mgr = ApprovalManager::new(dir.path().to_str().unwrap());
+        let request = mgr
+            .submit("p1", &settings(), &access_request("alice"))
+            .unwrap();
+        assert_eq!(request.requested_duration_secs, 3600);
+        assert!(mgr
+            .submit("p1", &settings(), &access_request("alice"))
+            .is_err());
+
+        assert!(mgr
+            .approve(&request.id, "alice", &settings(), None, None)
+            .is_err());
+        assert!(mgr
+            .approve(&request.id, "mallory", &settings(), None, None)
+            .is_err());
+        let grant = mgr
+            .approve(&request.id, "lead", &settings(), Some(600), Some("ok"))
+            .unwrap();
+        assert_eq!((grant.expires_at - grant.granted_at).num_seconds(), 600);
+        assert_eq!(grant.ticket_id.as_deref(), Some("CHG-42"));
+        assert!(mgr
+            .find_grant("alice", GatewayProtocol::Ssh, "10.0.0.5:22")
+            .is_some());
+        assert!(mgr
+            .find_grant("alice", GatewayProtocol::Rdp, "10.0.0.5:22")
+            .is_none());
+
+        // State and audit trail survive a restart.
+        let reloaded = ApprovalManager::new(dir.path().to_str().unwrap());
+        assert_eq!(reloaded.list_grants().len(), 1);
+        let events: Vec<_> = reloaded.audit_log(10).iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            vec![ApprovalAuditEvent::Requested, ApprovalAuditEvent::Approved]
+        );
+    }
+
+    #[test]
+    fn ticket_is_enforced_and_expiry_removes_grants() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut mgr = ApprovalManager::new(dir.path().to_str().unwrap());
+        let mut no_ticket = access_request("bob");
+        no_ticket.ticket_id = None;
+        assert!(mgr.submit("p1", &settings(), &no_ticket).is_err());
+
+        let request = mgr
+            .submit("p1", &settings(), &access_request("bob"))
+            .unwrap();
+        let grant = mgr
+            .approve(&request.id, "lead", &settings(), None, None)
+            .unwrap();
+        let (lapsed, expired) = mgr.expire(grant.expires_at);
+        assert!(lapsed.is_empty());
+        assert_eq!(expired.len(), 1);
+        assert!(mgr.list_grants().is_empty());
+        assert_eq!(mgr.audit_log(1)[0].event, ApprovalAuditEvent::GrantExpired);
+
+        let request = mgr
+            .submit("p1", &settings(), &access_request("bob"))
+            .unwrap();
+        let (lapsed, _) = mgr.expire(request.expires_at);
+        assert_eq!(lapsed.len(), 1);
+        assert_eq!(
+            mgr.get_request(&request.id).unwrap().status,
+            ApprovalStatus::Expired
+        );
+    }
+}
@@ -194,6 +194,9 @@ ENVIRONMENT VARIABLES:
     SORNG_GATEWAY_PORT       Listen port
     SORNG_GATEWAY_DATA_DIR   Data directory
     SORNG_GATEWAY_LOG_LEVEL  Log level
+    SORNG_GATEWAY_DEK_PASSWORD
+                             Password unlocking <data-dir>/dek.enc, the key
+                             session recordings are encrypted under
 
 EXAMPLES:
     sorng-gateway-server --config /etc/sorng/gateway.json
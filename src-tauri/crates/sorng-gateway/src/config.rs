@@ -6,6 +6,7 @@
 use crate::letsencrypt_bridge::GatewayLetsEncryptConfig;
 use crate::types::TlsConfig;
 use serde::{Deserialize, Serialize};
+use sorng_notifications::types::ChannelConfig;
 
 /// Complete gateway configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,8 +58,16 @@ pub struct GatewayConfig {
     pub metrics_enabled: bool,
     /// Metrics endpoint path
     pub metrics_path: String,
+    /// Require an API key with the `ViewMetrics` permission
+    /// (`Authorization: Bearer <key>`) to scrape the metrics endpoint
+    #[serde(default)]
+    pub metrics_auth_required: bool,
     /// CORS allowed origins (for management API)
     pub cors_origins: Vec<String>,
+    /// Channels that tell approvers about access requests, decisions and
+    /// expired grants
+    #[serde(default)]
+    pub approval_channels: Vec<ChannelConfig>,
     /// Let's Encrypt auto-TLS configuration
     pub letsencrypt: GatewayLetsEncryptConfig,
 }
@@ -88,7 +97,9 @@ impl GatewayConfig {
             health_check_path: "/health".to_string(),
             metrics_enabled: true,
             metrics_path: "/metrics".to_string(),
+            metrics_auth_required: false,
             cors_origins: vec!["http://localhost:3001".to_string()],
+            approval_channels: Vec::new(),
             letsencrypt: GatewayLetsEncryptConfig::default(),
         }
     }
@@ -136,6 +147,12 @@ impl GatewayConfig {
         if self.data_dir.is_empty() {
             errors.push("data_dir must not be empty".to_string());
         }
+        if self.metrics_enabled && !self.metrics_path.starts_with('/') {
+            errors.push("metrics_path must start with '/'".to_string());
+        }
+        if self.health_check_enabled && !self.health_check_path.starts_with('/') {
+            errors.push("health_check_path must start with '/'".to_string());
+        }
         if self.tls.enabled {
             if self.tls.cert_path.is_none() {
                 errors.push("TLS enabled but cert_path is not set".to_string());
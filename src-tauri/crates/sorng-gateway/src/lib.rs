@@ -13,9 +13,11 @@
 //! - **Connection Proxying** — TCP/UDP relay for SSH, RDP, VNC, and database traffic
 //! - **SSH Tunnel Management** — Dynamic SSH tunnel creation and lifecycle management
 //! - **Access Policies** — Per-user, per-host access control with time-based restrictions
+//! - **Just-in-Time Access** — Approval workflow issuing time-boxed, audited access grants
 //! - **Session Management** — Track, limit, and audit all gateway sessions
 //! - **Health Monitoring** — Self-diagnostics, uptime tracking, and health check endpoints
-//! - **Metrics** — Connection stats, bandwidth tracking, latency measurement
+//! - **Metrics** — Connection stats, bandwidth tracking, latency measurement, and an
+//!   OpenMetrics `/metrics` endpoint for Prometheus
 //! - **Gateway Authentication** — API keys, JWT tokens, and mutual TLS support
 //! - **TLS Termination** — Certificate management for encrypted gateway connections
 //! - **Recording Bridge** — Integration with sorng-recording for gateway-level capture
@@ -36,12 +38,14 @@
 //!                      └──────────────┘
 //! ```
 
+pub mod approval;
 pub mod auth;
 pub mod cli;
 pub mod config;
 pub mod health;
 pub mod letsencrypt_bridge;
 pub mod metrics;
+pub mod openmetrics;
 pub mod policy;
 pub mod proxy;
 pub mod recording_bridge;
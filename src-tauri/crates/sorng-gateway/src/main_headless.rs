@@ -3,6 +3,28 @@
 //! Standalone binary for running the gateway in headless mode (no GUI).
 //! This binary is built with `cargo build --features headless -p sorng-gateway`.
 
+use std::sync::Arc;
+
+use sorng_encryption::EncryptionState;
+
+/// Unlock the key session recordings are encrypted under: `dek.enc` in the
+/// data directory, unwrapped with `SORNG_GATEWAY_DEK_PASSWORD`. Without the
+/// password the key stays locked, and recordings are only saved if the
+/// recording `encrypt_at_rest` option has been turned off.
+async fn load_encryption_state(data_dir: &str) -> Result<Arc<EncryptionState>, String> {
+    let state = Arc::new(EncryptionState::new());
+    let Ok(password) = std::env::var("SORNG_GATEWAY_DEK_PASSWORD") else {
+        return Ok(state);
+    };
+    let path = std::path::Path::new(data_dir).join("dek.enc");
+    let blob =
+        std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let dek = sorng_encryption::password_wrap::unwrap(&password, &blob)
+        .map_err(|e| format!("Failed to unlock {}: {}", path.display(), e))?;
+    state.install(dek).await;
+    Ok(state)
+}
+
 fn main() {
     let cli_args = sorng_gateway::cli::CliArgs::parse();
 
@@ -61,7 +83,20 @@ fn main() {
     // Create the tokio runtime and run the gateway
     let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
     rt.block_on(async {
-        let gateway = sorng_gateway::service::GatewayService::new(config);
+        let encryption = match load_encryption_state(&config.data_dir).await {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Encryption error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if config.recording_enabled && !encryption.is_unlocked().await {
+            eprintln!(
+                "Warning: SORNG_GATEWAY_DEK_PASSWORD is not set; encrypted session \
+                 recordings cannot be saved"
+            );
+        }
+        let gateway = sorng_gateway::service::GatewayService::new(config, encryption).await;
         let mut gw = gateway.lock().await;
 
         if let Err(e) = gw.start().await {
@@ -69,15 +104,47 @@ fn main() {
             std::process::exit(1);
         }
 
+        // Serve the health and metrics endpoints on the management port
+        let config = gw.config().clone();
+        drop(gw);
+        let mut endpoints = None;
+        if config.health_check_enabled || config.metrics_enabled {
+            let started = match sorng_gateway::server::HeadlessServer::from_config(&config) {
+                Ok(mut server) => server.serve(gateway.clone()).await.map(|h| (server, h)),
+                Err(e) => Err(e),
+            };
+            match started {
+                Ok((server, handle)) => {
+                    println!("Management endpoints on http://{}", server.addr());
+                    endpoints = Some(handle);
+                }
+                Err(e) => {
+                    eprintln!("Failed to start management endpoints: {}", e);
+                    let _ = gateway.lock().await.stop().await;
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        // Lapse stale approval requests and tear down sessions whose
+        // just-in-time grants have expired
+        let enforcer = sorng_gateway::service::GatewayService::spawn_grant_enforcer(
+            gateway.clone(),
+            std::time::Duration::from_secs(15),
+        );
+
         println!("Gateway started. Press Ctrl+C to stop.");
 
         // Wait for shutdown signal
-        drop(gw);
         tokio::signal::ctrl_c()
             .await
             .expect("Failed to listen for ctrl+c");
 
         println!("\nShutting down gateway...");
+        enforcer.abort();
+        if let Some(handle) = endpoints {
+            handle.abort();
+        }
         let mut gw = gateway.lock().await;
         let _ = gw.stop().await;
         println!("Gateway stopped.");
@@ -1,11 +1,16 @@
 //! # Metrics Collector
 //!
 //! Connection metrics, bandwidth stats, latency tracking, and per-protocol/per-user
-//! aggregation for gateway observability.
+//! aggregation for gateway observability, with an OpenMetrics exposition for
+//! Prometheus scraping.
 
+use crate::openmetrics::{Encoder, Histogram, MetricType};
 use crate::types::*;
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// Upper bounds (seconds) of the session duration histogram buckets.
+const SESSION_DURATION_BUCKETS: &[f64] = &[1.0, 10.0, 60.0, 300.0, 900.0, 3600.0, 14400.0, 86400.0];
 
 /// Collects and aggregates gateway metrics.
 pub struct MetricsCollector {
@@ -33,6 +38,10 @@ pub struct MetricsCollector {
     connections_by_protocol: HashMap<String, u64>,
     /// Per-user connection counts
     connections_by_user: HashMap<String, u64>,
+    /// Policy decisions keyed by (policy ID, action)
+    policy_decisions: HashMap<(String, String), u64>,
+    /// Per-protocol session duration histograms
+    session_duration_by_protocol: HashMap<String, Histogram>,
 }
 
 impl Default for MetricsCollector {
@@ -56,6 +65,8 @@ impl MetricsCollector {
             peak_concurrent_sessions: 0,
             connections_by_protocol: HashMap::new(),
             connections_by_user: HashMap::new(),
+            policy_decisions: HashMap::new(),
+            session_duration_by_protocol: HashMap::new(),
         }
     }
 
@@ -95,6 +106,10 @@ impl MetricsCollector {
                 .num_seconds()
                 .max(0) as f64;
             self.session_durations.push(duration);
+            self.session_duration_by_protocol
+                .entry(format!("{:?}", session.protocol))
+                .or_insert_with(|| Histogram::new(SESSION_DURATION_BUCKETS))
+                .observe(duration);
             // Keep only last 1000 durations for averaging
             if self.session_durations.len() > 1000 {
                 self.session_durations.remove(0);
@@ -112,6 +127,19 @@ impl MetricsCollector {
         self.policy_denials += 1;
     }
 
+    /// Record the outcome of a policy evaluation. `policy_id` is `None` when
+    /// no policy matched and the default deny applied.
+    pub fn record_policy_decision(&mut self, policy_id: Option<&str>, action: PolicyAction) {
+        let key = (
+            policy_id.unwrap_or("default").to_string(),
+            format!("{:?}", action),
+        );
+        *self.policy_decisions.entry(key).or_insert(0) += 1;
+        if action == PolicyAction::Deny {
+            self.record_denial();
+        }
+    }
+
     /// Record an auth failure.
     pub fn record_auth_failure(&mut self) {
         self.auth_failures += 1;
@@ -165,4 +193,168 @@ impl MetricsCollector {
             self.session_durations.iter().sum::<f64>() / self.session_durations.len() as f64
         }
     }
+
+    /// Render all metrics plus the given health report in the OpenMetrics
+    /// text format.
+    pub fn encode_openmetrics(&self, health: &GatewayHealth) -> String {
+        let mut enc = Encoder::new();
+
+        enc.family(
+            "sorng_gateway_connections",
+            MetricType::Counter,
+            None,
+            "Connections handled, by protocol.",
+        );
+        for (protocol, count) in sorted(&self.connections_by_protocol) {
+            enc.sample(
+                "sorng_gateway_connections_total",
+                &[("protocol", protocol)],
+                count,
+            );
+        }
+
+        enc.family(
+            "sorng_gateway_user_connections",
+            MetricType::Counter,
+            None,
+            "Connections handled, by user.",
+        );
+        for (user, count) in sorted(&self.connections_by_user) {
+            enc.sample(
+                "sorng_gateway_user_connections_total",
+                &[("user", user)],
+                count,
+            );
+        }
+
+        enc.family(
+            "sorng_gateway_policy_decisions",
+            MetricType::Counter,
+            None,
+            "Access policy evaluations, by matching policy and resulting action.",
+        );
+        let decisions: BTreeMap<_, _> = self.policy_decisions.iter().collect();
+        for ((policy, action), count) in decisions {
+            enc.sample(
+                "sorng_gateway_policy_decisions_total",
+                &[("policy", policy), ("action", action)],
+                count,
+            );
+        }
+
+        for (name, help, value) in [
+            (
+                "sorng_gateway_policy_denials",
+                "Connections denied by access policy.",
+                self.policy_denials,
+            ),
+            (
+                "sorng_gateway_auth_failures",
+                "Failed gateway authentication attempts.",
+                self.auth_failures,
+            ),
+            (
+                "sorng_gateway_connection_errors",
+                "Connection errors.",
+                self.connection_errors,
+            ),
+        ] {
+            enc.family(name, MetricType::Counter, None, help);
+            enc.sample(&format!("{}_total", name), &[], value);
+        }
+
+        enc.family(
+            "sorng_gateway_transfer_bytes",
+            MetricType::Counter,
+            Some("bytes"),
+            "Bytes relayed through the gateway, by direction.",
+        );
+        enc.sample(
+            "sorng_gateway_transfer_bytes_total",
+            &[("direction", "received")],
+            self.total_bytes_received,
+        );
+        enc.sample(
+            "sorng_gateway_transfer_bytes_total",
+            &[("direction", "sent")],
+            self.total_bytes_sent,
+        );
+
+        enc.family(
+            "sorng_gateway_session_duration_seconds",
+            MetricType::Histogram,
+            Some("seconds"),
+            "Duration of ended sessions, by protocol.",
+        );
+        for (protocol, histogram) in sorted(&self.session_duration_by_protocol) {
+            enc.histogram(
+                "sorng_gateway_session_duration_seconds",
+                &[("protocol", protocol)],
+                histogram,
+            );
+        }
+
+        enc.family(
+            "sorng_gateway_uptime_seconds",
+            MetricType::Gauge,
+            Some("seconds"),
+            "Gateway uptime.",
+        );
+        enc.sample("sorng_gateway_uptime_seconds", &[], health.uptime_secs);
+        for (name, help, value) in [
+            (
+                "sorng_gateway_active_sessions",
+                "Currently active sessions.",
+                health.active_sessions,
+            ),
+            (
+                "sorng_gateway_active_connections",
+                "Currently active connections.",
+                self.active_connections,
+            ),
+            (
+                "sorng_gateway_peak_concurrent_sessions",
+                "Highest number of concurrent connections seen.",
+                self.peak_concurrent_sessions,
+            ),
+        ] {
+            enc.family(name, MetricType::Gauge, None, help);
+            enc.sample(name, &[], value);
+        }
+
+        enc.family(
+            "sorng_gateway_health_status",
+            MetricType::StateSet,
+            None,
+            "Overall and per-check gateway health.",
+        );
+        let checks = std::iter::once(("overall", health.status)).chain(
+            health
+                .checks
+                .iter()
+                .map(|check| (check.name.as_str(), check.status)),
+        );
+        for (check, status) in checks {
+            for (state, variant) in [
+                ("healthy", HealthStatus::Healthy),
+                ("degraded", HealthStatus::Degraded),
+                ("unhealthy", HealthStatus::Unhealthy),
+            ] {
+                enc.sample(
+                    "sorng_gateway_health_status",
+                    &[("check", check), ("sorng_gateway_health_status", state)],
+                    u8::from(status == variant),
+                );
+            }
+        }
+
+        enc.finish()
+    }
+}
+
+/// Iterate a map in key order so the exposition is stable between scrapes.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&str, &V)> {
+    let mut entries: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
 }
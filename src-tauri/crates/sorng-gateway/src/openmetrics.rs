@@ -0,0 +1,196 @@
+//! # OpenMetrics Encoding
+//!
+//! Minimal writer for the OpenMetrics text exposition format (the format
+//! Prometheus scrapes), plus the fixed-bucket histogram used by the metrics
+//! collector.
+
+use std::fmt::{Display, Write};
+
+/// Content type of an OpenMetrics text exposition.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Metric family types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    StateSet,
+}
+
+impl MetricType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Histogram => "histogram",
+            Self::StateSet => "stateset",
+        }
+    }
+}
+
+/// A cumulative histogram over fixed upper bounds.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket (not cumulative); the last slot is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+        }
+    }
+
+    /// Record one observation.
+    pub fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Total number of observations.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Sum of all observations.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+}
+
+/// Builds an OpenMetrics exposition one family at a time.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a metric family. Samples for it must follow before the next
+    /// family starts.
+    pub fn family(&mut self, name: &str, kind: MetricType, unit: Option<&str>, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind.as_str());
+        if let Some(unit) = unit {
+            let _ = writeln!(self.out, "# UNIT {} {}", name, unit);
+        }
+        let _ = writeln!(self.out, "# HELP {} {}", name, escape(help, false));
+    }
+
+    /// Write one sample line.
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        self.write_labels(labels.iter().map(|(k, v)| (*k, *v)));
+        let _ = writeln!(self.out, " {}", value);
+    }
+
+    /// Write the `_bucket`, `_count` and `_sum` samples of a histogram.
+    pub fn histogram(&mut self, name: &str, labels: &[(&str, &str)], histogram: &Histogram) {
+        let bucket_name = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (index, count) in histogram.counts.iter().enumerate() {
+            cumulative += count;
+            let le = match histogram.bounds.get(index) {
+                Some(bound) => format!("{:?}", bound),
+                None => "+Inf".to_string(),
+            };
+            self.out.push_str(&bucket_name);
+            self.write_labels(
+                labels
+                    .iter()
+                    .map(|(k, v)| (*k, *v))
+                    .chain(std::iter::once(("le", le.as_str()))),
+            );
+            let _ = writeln!(self.out, " {}", cumulative);
+        }
+        self.sample(&format!("{}_count", name), labels, histogram.count());
+        self.sample(
+            &format!("{}_sum", name),
+            labels,
+            format!("{:?}", histogram.sum()),
+        );
+    }
+
+    /// Terminate the exposition.
+    pub fn finish(mut self) -> String {
+        self.out.push_str("# EOF\n");
+        self.out
+    }
+
+    fn write_labels<'a>(&mut self, labels: impl Iterator<Item = (&'a str, &'a str)>) {
+        let mut first = true;
+        for (key, value) in labels {
+            self.out.push(if first { '{' } else { ',' });
+            first = false;
+            let _ = write!(self.out, "{}=\"{}\"", key, escape(value, true));
+        }
+        if !first {
+            self.out.push('}');
+        }
+    }
+}
+
+/// Escape a label value (`quote = true`) or HELP text.
+fn escape(value: &str, quote: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '"' if quote => escaped.push_str("\\\""),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_families_and_escapes_labels() {
+        let mut encoder = Encoder::new();
+        encoder.family("jobs", MetricType::Counter, None, "Jobs\nrun");
+        encoder.sample("jobs_total", &[("user", "a\"b\\c")], 3);
+        encoder.family("temp", MetricType::Gauge, None, "Temperature");
+        encoder.sample("temp", &[], 1.5);
+        assert_eq!(
+            encoder.finish(),
+            "# TYPE jobs counter\n# HELP jobs Jobs\\nrun\n\
+             jobs_total{user=\"a\\\"b\\\\c\"} 3\n\
+             # TYPE temp gauge\n# HELP temp Temperature\ntemp 1.5\n# EOF\n"
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::new(&[1.0, 10.0]);
+        for value in [0.5, 3.0, 7.0, 50.0] {
+            histogram.observe(value);
+        }
+        let mut encoder = Encoder::new();
+        encoder.family("d_seconds", MetricType::Histogram, Some("seconds"), "D");
+        encoder.histogram("d_seconds", &[("p", "ssh")], &histogram);
+        let text = encoder.finish();
+        assert!(text.contains("# UNIT d_seconds seconds\n"));
+        assert!(text.contains("d_seconds_bucket{p=\"ssh\",le=\"1.0\"} 1\n"));
+        assert!(text.contains("d_seconds_bucket{p=\"ssh\",le=\"10.0\"} 3\n"));
+        assert!(text.contains("d_seconds_bucket{p=\"ssh\",le=\"+Inf\"} 4\n"));
+        assert!(text.contains("d_seconds_count{p=\"ssh\"} 4\n"));
+        assert!(text.contains("d_seconds_sum{p=\"ssh\"} 60.5\n"));
+    }
+}
@@ -114,6 +114,19 @@ impl PolicyEngine {
         protocol: GatewayProtocol,
         source_ip: &str,
     ) -> Result<PolicyAction, String> {
+        self.evaluate_with_policy(user_id, target_addr, protocol, source_ip)
+            .map(|(action, _)| action)
+    }
+
+    /// Like [`evaluate`](Self::evaluate), but also returns the ID of the
+    /// matching policy (`None` when the default deny applied).
+    pub fn evaluate_with_policy(
+        &self,
+        user_id: &str,
+        target_addr: &str,
+        protocol: GatewayProtocol,
+        source_ip: &str,
+    ) -> Result<(PolicyAction, Option<String>), String> {
         let mut policies: Vec<&AccessPolicy> =
             self.policies.values().filter(|p| p.enabled).collect();
         policies.sort_by_key(|p| p.priority);
@@ -134,12 +147,12 @@ impl PolicyEngine {
                     protocol,
                     policy.action
                 );
-                return Ok(policy.action);
+                return Ok((policy.action, Some(policy.id.clone())));
             }
         }
 
         // Default: deny if no policies match (secure-by-default)
-        Ok(PolicyAction::Deny)
+        Ok((PolicyAction::Deny, None))
     }
 
     /// Check if user conditions match.
@@ -3,8 +3,11 @@
 //! Connection proxying engine — manages proxy routes and TCP/UDP relay for
 //! forwarding connections through the gateway.
 
+use crate::recording_bridge::{check_recordable, StreamTap};
 use crate::types::*;
 use std::collections::HashMap;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
 
 /// The proxy engine manages proxy routes and forwards traffic.
 pub struct ProxyEngine {
@@ -30,6 +33,10 @@ impl ProxyEngine {
 
     /// Add a new proxy route.
     pub fn add_route(&mut self, route: ProxyRoute) -> Result<(), String> {
+        if route.record_sessions {
+            check_recordable(route.protocol)?;
+        }
+
         // Check for port conflicts
         if self.port_map.contains_key(&route.listen_port) {
             return Err(format!(
@@ -126,3 +133,43 @@ impl ProxyEngine {
         Ok((route.target_host.clone(), route.target_port))
     }
 }
+
+/// Relay a client connection to its target until both sides close.
+///
+/// When `taps` is given (client → target, target → client) every chunk is
+/// teed into the session recording after it has been forwarded.
+/// Returns `(bytes_sent, bytes_received)` from the client's point of view.
+pub async fn relay_connection(
+    mut inbound: TcpStream,
+    mut outbound: TcpStream,
+    taps: Option<(StreamTap, StreamTap)>,
+) -> std::io::Result<(u64, u64)> {
+    let Some((client_tap, target_tap)) = taps else {
+        return tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+    };
+    let (client_read, client_write) = inbound.split();
+    let (target_read, target_write) = outbound.split();
+    tokio::try_join!(
+        pump(client_read, target_write, client_tap),
+        pump(target_read, client_write, target_tap)
+    )
+}
+
+async fn pump<R, W>(mut reader: R, mut writer: W, mut tap: StreamTap) -> std::io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0u8; 16 * 1024];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            writer.shutdown().await?;
+            return Ok(total);
+        }
+        writer.write_all(&buf[..n]).await?;
+        tap.record(&buf[..n]).await;
+        total += n as u64;
+    }
+}
@@ -2,9 +2,29 @@
 //!
 //! Bridge to the sorng-recording crate for gateway-level session capture.
 //! The gateway can record sessions passing through it for compliance and audit.
+//!
+//! Each chunk relayed for a recorded session is teed through a [`StreamTap`],
+//! which decodes it and hands the text to the `sorng-recording` terminal
+//! recorder (where the `redact.rs` rules are applied before anything is
+//! buffered). When the recording stops it is encoded as asciicast v2 and saved
+//! to the recording library — encrypted at rest according to the recording
+//! service's policy — tagged with the gateway session so it can be found with
+//! `search_library` and replayed through `sorng-replay`.
+//!
+//! Telnet streams are captured with option negotiation stripped. Proxied SSH
+//! is encrypted end to end and the gateway does not terminate it, so SSH
+//! recording is refused (see [`check_recordable`]) rather than saving a
+//! library entry that holds nothing but the version banner.
 
+use crate::types::{GatewayProtocol, GatewaySession};
 use serde::{Deserialize, Serialize};
+use sorng_core::utf8::StreamingUtf8Decoder;
+use sorng_encryption::EncryptionState;
+use sorng_recording::service::RecordingService;
+use sorng_recording::types::{CompressionAlgorithm, ExportFormat, RecordingProtocol};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Status of a recording.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +46,8 @@ pub struct GatewayRecording {
     pub id: String,
     /// Associated session ID
     pub session_id: String,
+    /// Protocol of the recorded session
+    pub protocol: GatewayProtocol,
     /// Recording status
     pub status: RecordingStatus,
     /// When the recording started
@@ -34,72 +56,219 @@ pub struct GatewayRecording {
     pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
     /// Recorded bytes so far
     pub bytes_recorded: u64,
+    /// Terminal recording ID in the sorng-recording engine (if the stream
+    /// content is being captured)
+    pub capture_id: Option<String>,
+    /// Recording library entry ID once the capture has been saved
+    pub library_id: Option<String>,
     /// Storage path for the recording file
     pub storage_path: Option<String>,
 }
 
+/// Which way a relayed chunk is travelling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TapDirection {
+    /// Client → target (keystrokes)
+    ClientToTarget,
+    /// Target → client (terminal output)
+    TargetToClient,
+}
+
+type SharedRecordings = Arc<Mutex<HashMap<String, GatewayRecording>>>;
+
 /// Bridges gateway session recording to the sorng-recording engine.
+///
+/// Cloning is cheap and every clone shares the same recordings and enabled
+/// flag, so proxy listeners can hold their own handle.
+#[derive(Clone)]
 pub struct RecordingBridge {
     /// Whether recording is globally enabled
-    enabled: bool,
+    enabled: Arc<AtomicBool>,
     /// Active recordings indexed by session ID
-    recordings: HashMap<String, GatewayRecording>,
+    recordings: SharedRecordings,
+    /// Recording service that receives captured streams. Without one the
+    /// bridge only counts bytes.
+    service: Option<RecordingService>,
 }
 
 impl RecordingBridge {
     pub fn new(enabled: bool) -> Self {
         Self {
-            enabled,
-            recordings: HashMap::new(),
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            recordings: Arc::new(Mutex::new(HashMap::new())),
+            service: None,
+        }
+    }
+
+    /// Create a bridge that captures stream content into `service`.
+    pub fn with_service(enabled: bool, service: RecordingService) -> Self {
+        Self {
+            service: Some(service),
+            ..Self::new(enabled)
+        }
+    }
+
+    /// The recording service captures are saved to, if any. Use this to
+    /// install the encryption state or to search the library.
+    pub fn service(&self) -> Option<&RecordingService> {
+        self.service.as_ref()
+    }
+
+    /// Encrypt saved captures under `state`. The recording service refuses
+    /// to save while no unlocked key is installed, unless its
+    /// `encrypt_at_rest` option has been turned off.
+    pub async fn set_encryption_state(&self, state: Arc<EncryptionState>) {
+        if let Some(service) = &self.service {
+            service.set_encryption_state(state).await;
+        }
+    }
+
+    /// Load the recording service's configuration and library.
+    pub async fn init(&self) -> Result<(), String> {
+        match &self.service {
+            Some(service) => service.init().await.map_err(|e| e.to_string()),
+            None => Ok(()),
         }
     }
 
     /// Check if recording is enabled.
     pub fn is_enabled(&self) -> bool {
-        self.enabled
+        self.enabled.load(Ordering::Relaxed)
     }
 
-    /// Enable or disable recording globally.
-    pub fn set_enabled(&mut self, enabled: bool) {
-        self.enabled = enabled;
+    /// Enable or disable recording globally, for every clone of the bridge.
+    /// Recordings already running are not affected.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, GatewayRecording>> {
+        self.recordings.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     /// Start recording for a session.
-    pub fn start_recording(&mut self, session_id: &str) -> Result<GatewayRecording, String> {
-        if !self.enabled {
+    pub async fn start_recording(
+        &self,
+        session: &GatewaySession,
+    ) -> Result<GatewayRecording, String> {
+        self.start_capture(
+            &session.id,
+            session.protocol,
+            &session.target_addr,
+            &session.username,
+            vec![
+                "gateway".to_string(),
+                format!("gateway-session:{}", session.id),
+                format!("gateway-user:{}", session.user_id),
+            ],
+        )
+        .await
+    }
+
+    /// Start recording a relayed connection. Terminal protocols get a
+    /// sorng-recording capture; everything else is byte-counted only.
+    pub async fn start_capture(
+        &self,
+        session_id: &str,
+        protocol: GatewayProtocol,
+        target: &str,
+        username: &str,
+        tags: Vec<String>,
+    ) -> Result<GatewayRecording, String> {
+        if !self.is_enabled() {
             return Err("Recording is globally disabled".to_string());
         }
+        check_recordable(protocol)?;
 
-        if self.recordings.contains_key(session_id) {
+        if self.lock().contains_key(session_id) {
             return Err("Recording already active for this session".to_string());
         }
 
+        let capture_id = match (&self.service, recording_protocol(protocol)) {
+            (Some(service), Some(recording_protocol)) => Some(
+                service
+                    .start_terminal_recording(
+                        session_id.to_string(),
+                        recording_protocol,
+                        target.to_string(),
+                        username.to_string(),
+                        80,
+                        24,
+                        // Keystrokes typed at a no-echo password prompt cannot
+                        // be told apart from commands, so only output is kept.
+                        false,
+                        tags,
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to start session capture: {}", e))?,
+            ),
+            _ => None,
+        };
+
         let recording = GatewayRecording {
             id: uuid::Uuid::new_v4().to_string(),
             session_id: session_id.to_string(),
+            protocol,
             status: RecordingStatus::Active,
             started_at: chrono::Utc::now(),
             ended_at: None,
             bytes_recorded: 0,
+            capture_id,
+            library_id: None,
             storage_path: None,
         };
 
-        self.recordings
+        self.lock()
             .insert(session_id.to_string(), recording.clone());
         log::info!("[RECORDING] Started recording for session {}", session_id);
         Ok(recording)
     }
 
-    /// Stop recording for a session.
-    pub fn stop_recording(&mut self, session_id: &str) -> Result<GatewayRecording, String> {
-        let recording = self
-            .recordings
+    /// Stop recording for a session, saving any captured stream to the
+    /// recording library.
+    pub async fn stop_recording(&self, session_id: &str) -> Result<GatewayRecording, String> {
+        let recording = {
+            let mut recordings = self.lock();
+            let recording = recordings
+                .get_mut(session_id)
+                .ok_or("No recording found for this session")?;
+            if matches!(
+                recording.status,
+                RecordingStatus::Complete | RecordingStatus::Failed
+            ) {
+                return Err("Recording is not active".to_string());
+            }
+            recording.status = RecordingStatus::Complete;
+            recording.ended_at = Some(chrono::Utc::now());
+            recording.clone()
+        };
+
+        let saved = match (&self.service, &recording.capture_id) {
+            (Some(service), Some(_)) => Some(save_capture(service, &recording).await),
+            _ => None,
+        };
+
+        let mut recordings = self.lock();
+        let entry = recordings
             .get_mut(session_id)
             .ok_or("No recording found for this session")?;
-
-        recording.status = RecordingStatus::Complete;
-        recording.ended_at = Some(chrono::Utc::now());
-        let result = recording.clone();
+        match saved {
+            Some(Ok((library_id, storage_path))) => {
+                entry.library_id = Some(library_id);
+                entry.storage_path = Some(storage_path);
+            }
+            Some(Err(e)) => {
+                entry.status = RecordingStatus::Failed;
+                log::error!(
+                    "[RECORDING] Failed to save recording for session {}: {}",
+                    session_id,
+                    e
+                );
+                return Err(e);
+            }
+            None => {}
+        }
+        let result = entry.clone();
 
         log::info!(
             "[RECORDING] Stopped recording for session {} ({} bytes)",
@@ -110,9 +279,9 @@ impl RecordingBridge {
     }
 
     /// Record data passing through a session.
-    pub fn record_data(&mut self, session_id: &str, bytes: u64) -> Result<(), String> {
-        let recording = self
-            .recordings
+    pub fn record_data(&self, session_id: &str, bytes: u64) -> Result<(), String> {
+        let mut recordings = self.lock();
+        let recording = recordings
             .get_mut(session_id)
             .ok_or("No recording found for this session")?;
 
@@ -121,26 +290,43 @@ impl RecordingBridge {
         }
 
         recording.bytes_recorded += bytes;
-        // In production, this would pipe data to the sorng-recording engine
         Ok(())
     }
 
-    /// Pause a recording.
-    pub fn pause_recording(&mut self, session_id: &str) -> Result<(), String> {
+    /// A tap for one direction of a recorded session's relay.
+    pub fn tap(&self, session_id: &str, direction: TapDirection) -> Result<StreamTap, String> {
         let recording = self
-            .recordings
-            .get_mut(session_id)
-            .ok_or("No recording found")?;
+            .get_recording(session_id)
+            .ok_or("No recording found for this session")?;
+        let filter = match (direction, recording.capture_id.is_some()) {
+            (_, false) => StreamFilter::Discard,
+            (TapDirection::ClientToTarget, true) => StreamFilter::Discard,
+            (TapDirection::TargetToClient, true) => match recording.protocol {
+                GatewayProtocol::Telnet => StreamFilter::Telnet(TelnetState::Data),
+                _ => StreamFilter::Discard,
+            },
+        };
+        Ok(StreamTap {
+            session_id: session_id.to_string(),
+            recordings: self.recordings.clone(),
+            service: self.service.clone(),
+            filter,
+            decoder: StreamingUtf8Decoder::new(),
+        })
+    }
+
+    /// Pause a recording.
+    pub fn pause_recording(&self, session_id: &str) -> Result<(), String> {
+        let mut recordings = self.lock();
+        let recording = recordings.get_mut(session_id).ok_or("No recording found")?;
         recording.status = RecordingStatus::Paused;
         Ok(())
     }
 
     /// Resume a paused recording.
-    pub fn resume_recording(&mut self, session_id: &str) -> Result<(), String> {
-        let recording = self
-            .recordings
-            .get_mut(session_id)
-            .ok_or("No recording found")?;
+    pub fn resume_recording(&self, session_id: &str) -> Result<(), String> {
+        let mut recordings = self.lock();
+        let recording = recordings.get_mut(session_id).ok_or("No recording found")?;
         if recording.status != RecordingStatus::Paused {
             return Err("Recording is not paused".to_string());
         }
@@ -149,15 +335,16 @@ impl RecordingBridge {
     }
 
     /// Get recording info for a session.
-    pub fn get_recording(&self, session_id: &str) -> Option<&GatewayRecording> {
-        self.recordings.get(session_id)
+    pub fn get_recording(&self, session_id: &str) -> Option<GatewayRecording> {
+        self.lock().get(session_id).cloned()
     }
 
     /// List all active recordings.
-    pub fn list_active(&self) -> Vec<&GatewayRecording> {
-        self.recordings
+    pub fn list_active(&self) -> Vec<GatewayRecording> {
+        self.lock()
             .values()
             .filter(|r| r.status == RecordingStatus::Active)
+            .cloned()
             .collect()
     }
 
@@ -168,7 +355,393 @@ impl RecordingBridge {
 
     /// Clean up completed recordings from memory.
     pub fn cleanup_completed(&mut self) {
-        self.recordings
+        self.lock()
             .retain(|_, r| matches!(r.status, RecordingStatus::Active | RecordingStatus::Paused));
     }
 }
+
+/// Refuse protocols the gateway relays but cannot see into. SSH is
+/// encrypted between client and target, so a gateway recording of it would
+/// be empty.
+pub fn check_recordable(protocol: GatewayProtocol) -> Result<(), String> {
+    match protocol {
+        GatewayProtocol::Ssh => Err(
+            "SSH sessions are encrypted end to end through the gateway and cannot be \
+             recorded here; record them in the SSH client instead"
+                .to_string(),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// The sorng-recording protocol for gateway protocols with a terminal stream.
+fn recording_protocol(protocol: GatewayProtocol) -> Option<RecordingProtocol> {
+    match protocol {
+        GatewayProtocol::Telnet => Some(RecordingProtocol::Telnet),
+        _ => None,
+    }
+}
+
+/// Encode the finished capture as asciicast and save it to the library.
+/// Returns the library entry ID and the directory it was written to.
+async fn save_capture(
+    service: &RecordingService,
+    recording: &GatewayRecording,
+) -> Result<(String, String), String> {
+    let capture = service
+        .stop_terminal_recording(&recording.session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let name = format!(
+        "Gateway {:?} session {}@{}",
+        recording.protocol, capture.metadata.username, capture.metadata.host
+    );
+    let tags = capture.metadata.tags.clone();
+    let library_id = service
+        .encode_compress_save_terminal(
+            capture,
+            name,
+            Some(format!(
+                "Captured by the gateway for session {}",
+                recording.session_id
+            )),
+            ExportFormat::Asciicast,
+            CompressionAlgorithm::Gzip,
+            Some(recording.session_id.clone()),
+            tags,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let storage_path = service.storage_root_snapshot().await.join("recordings");
+    Ok((library_id, storage_path.display().to_string()))
+}
+
+// ── Stream taps ─────────────────────────────────────────────────────
+
+const TELNET_IAC: u8 = 255;
+const TELNET_SB: u8 = 250;
+const TELNET_SE: u8 = 240;
+const TELNET_WILL: u8 = 251;
+const TELNET_DONT: u8 = 254;
+
+/// Position inside a Telnet command sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TelnetState {
+    Data,
+    Iac,
+    Option,
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+/// What a tap keeps from the bytes it sees.
+#[derive(Debug)]
+enum StreamFilter {
+    /// Count bytes only.
+    Discard,
+    /// Telnet data with IAC command sequences removed.
+    Telnet(TelnetState),
+}
+
+impl StreamFilter {
+    fn apply(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        match self {
+            Self::Discard => {}
+            Self::Telnet(state) => strip_telnet_commands(state, data, out),
+        }
+    }
+}
+
+fn strip_telnet_commands(state: &mut TelnetState, data: &[u8], out: &mut Vec<u8>) {
+    for &byte in data {
+        *state = match *state {
+            TelnetState::Data if byte == TELNET_IAC => TelnetState::Iac,
+            TelnetState::Data => {
+                out.push(byte);
+                TelnetState::Data
+            }
+            TelnetState::Iac => match byte {
+                TELNET_IAC => {
+                    out.push(TELNET_IAC);
+                    TelnetState::Data
+                }
+                TELNET_SB => TelnetState::Subnegotiation,
+                TELNET_WILL..=TELNET_DONT => TelnetState::Option,
+                _ => TelnetState::Data,
+            },
+            TelnetState::Option => TelnetState::Data,
+            TelnetState::Subnegotiation if byte == TELNET_IAC => TelnetState::SubnegotiationIac,
+            TelnetState::Subnegotiation => TelnetState::Subnegotiation,
+            TelnetState::SubnegotiationIac if byte == TELNET_SE => TelnetState::Data,
+            TelnetState::SubnegotiationIac => TelnetState::Subnegotiation,
+        };
+    }
+}
+
+/// Tees one direction of a recorded relay into the session's recording.
+///
+/// Every chunk goes through the filter and decoder, so protocol and UTF-8
+/// state stay in step across a pause; paused recordings just neither count
+/// nor capture what comes out.
+pub struct StreamTap {
+    session_id: String,
+    recordings: SharedRecordings,
+    service: Option<RecordingService>,
+    filter: StreamFilter,
+    decoder: StreamingUtf8Decoder,
+}
+
+impl StreamTap {
+    /// Record a relayed chunk.
+    pub async fn record(&mut self, data: &[u8]) {
+        let mut kept = Vec::new();
+        self.filter.apply(data, &mut kept);
+        let text = self.decoder.push(&kept);
+
+        {
+            let mut recordings = self.recordings.lock().unwrap_or_else(|e| e.into_inner());
+            match recordings.get_mut(&self.session_id) {
+                Some(recording) if recording.status == RecordingStatus::Active => {
+                    recording.bytes_recorded += data.len() as u64;
+                }
+                _ => return,
+            }
+        }
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(service) = &self.service {
+            service
+                .append_terminal_output(&self.session_id, &text)
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionManager;
+
+    fn bridge(dir: &tempfile::TempDir) -> RecordingBridge {
+        RecordingBridge::with_service(true, RecordingService::new(dir.path().to_str().unwrap()))
+    }
+
+    async fn allow_plaintext(bridge: &RecordingBridge) {
+        let service = bridge.service().unwrap();
+        let mut config = service.get_config().await;
+        config.encrypt_at_rest = false;
+        service.update_config(config).await.unwrap();
+    }
+
+    #[test]
+    fn strips_telnet_negotiation_across_chunks() {
+        let mut state = TelnetState::Data;
+        let mut out = Vec::new();
+        strip_telnet_commands(&mut state, b"lo\xff\xfb", &mut out);
+        strip_telnet_commands(&mut state, b"\x01gin\xff\xfa\x18\x01\xff", &mut out);
+        strip_telnet_commands(&mut state, b"\xf0: \xff\xff", &mut out);
+        assert_eq!(out, b"login: \xff");
+        assert_eq!(state, TelnetState::Data);
+    }
+
+    #[tokio::test]
+    async fn captures_telnet_session_into_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let bridge = bridge(&dir);
+        allow_plaintext(&bridge).await;
+        let session = SessionManager::new().create_session(
+            "u1",
+            "alice",
+            GatewayProtocol::Telnet,
+            "10.0.0.5:50000",
+            "router:23",
+            true,
+        );
+
+        let recording = bridge.start_recording(&session).await.unwrap();
+        assert!(recording.capture_id.is_some());
+        let mut output = bridge
+            .tap(&session.id, TapDirection::TargetToClient)
+            .unwrap();
+        let mut input = bridge
+            .tap(&session.id, TapDirection::ClientToTarget)
+            .unwrap();
+        output.record(b"\xff\xfb\x01Password: ").await;
+        input.record(b"hunter2\r\n").await;
+        output.record(b"\r\nrouter# show \xe2\x86").await;
+        output.record(b"\x92 token=abc123\r\n").await;
+
+        let stopped = bridge.stop_recording(&session.id).await.unwrap();
+        assert_eq!(stopped.bytes_recorded, 55);
+        let library_id = stopped.library_id.unwrap();
+
+        let service = bridge.service().unwrap();
+        let found = service
+            .search_library(&format!("gateway-session:{}", session.id))
+            .await;
+        assert_eq!(found.len(), 1);
+        let envelope = &found[0];
+        assert_eq!(envelope.id, library_id);
+        assert_eq!(envelope.connection_id.as_deref(), Some(session.id.as_str()));
+
+        let cast = sorng_recording::compression::decompress_from_b64(
+            &envelope.data,
+            &envelope.compression,
+        )
+        .unwrap();
+        let frames = sorng_replay::terminal_replay::parse_asciicast(&cast).unwrap();
+        let transcript: String = frames.iter().map(|f| f.data.as_str()).collect();
+        assert_eq!(
+            transcript,
+            "Password:[redacted]\r\nrouter# show → token=[redacted]\r\n"
+        );
+        assert!(!cast.contains("hunter2"));
+        assert_eq!(
+            sorng_replay::search::search_terminal(&frames, "router#", true).len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_save_marks_recording_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let bridge = bridge(&dir);
+        let session = SessionManager::new().create_session(
+            "u1",
+            "alice",
+            GatewayProtocol::Telnet,
+            "10.0.0.5:50000",
+            "router:23",
+            true,
+        );
+
+        bridge.start_recording(&session).await.unwrap();
+        let err = bridge.stop_recording(&session.id).await.unwrap_err();
+        assert!(err.contains("encrypt"), "got: {err}");
+        assert_eq!(
+            bridge.get_recording(&session.id).unwrap().status,
+            RecordingStatus::Failed
+        );
+    }
+
+    #[tokio::test]
+    async fn saves_encrypted_once_key_is_installed() {
+        let dir = tempfile::tempdir().unwrap();
+        let bridge = bridge(&dir);
+        let enc = Arc::new(EncryptionState::new());
+        enc.install(sorng_encryption::MasterDek::generate()).await;
+        bridge.set_encryption_state(enc).await;
+
+        bridge
+            .start_capture(
+                "s1",
+                GatewayProtocol::Telnet,
+                "router:23",
+                "bob",
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+        let mut tap = bridge.tap("s1", TapDirection::TargetToClient).unwrap();
+        tap.record(b"router> ").await;
+        let stopped = bridge.stop_recording("s1").await.unwrap();
+        assert_eq!(stopped.status, RecordingStatus::Complete);
+        assert!(stopped.library_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn clones_share_enabled_flag() {
+        let bridge = RecordingBridge::new(true);
+        let listener_handle = bridge.clone();
+        bridge.set_enabled(false);
+        assert!(!listener_handle.is_enabled());
+        assert!(listener_handle
+            .start_capture(
+                "s1",
+                GatewayProtocol::Telnet,
+                "router:23",
+                "bob",
+                Vec::new()
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn refuses_to_record_ssh() {
+        let dir = tempfile::tempdir().unwrap();
+        let bridge = bridge(&dir);
+        allow_plaintext(&bridge).await;
+        let err = bridge
+            .start_capture("s1", GatewayProtocol::Ssh, "db01:22", "bob", Vec::new())
+            .await
+            .unwrap_err();
+        assert!(err.contains("cannot be recorded"), "got: {err}");
+        assert!(bridge.get_recording("s1").is_none());
+        assert!(bridge.tap("s1", TapDirection::TargetToClient).is_err());
+        assert!(bridge
+            .service()
+            .unwrap()
+            .search_library("gateway")
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn telnet_state_survives_a_pause() {
+        let dir = tempfile::tempdir().unwrap();
+        let bridge = bridge(&dir);
+        allow_plaintext(&bridge).await;
+        bridge
+            .start_capture(
+                "s1",
+                GatewayProtocol::Telnet,
+                "router:23",
+                "bob",
+                vec!["gateway".to_string()],
+            )
+            .await
+            .unwrap();
+        let mut tap = bridge.tap("s1", TapDirection::TargetToClient).unwrap();
+        tap.record(b"router> ").await;
+        bridge.pause_recording("s1").unwrap();
+        // A subnegotiation starts while paused and ends after the resume.
+        tap.record(b"secret \xff\xfa\x18\x01").await;
+        bridge.resume_recording("s1").unwrap();
+        tap.record(b"VT100\xff\xf0ok").await;
+        let stopped = bridge.stop_recording("s1").await.unwrap();
+
+        let found = bridge.service().unwrap().search_library("gateway").await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, stopped.library_id.unwrap());
+        let cast = sorng_recording::compression::decompress_from_b64(
+            &found[0].data,
+            &found[0].compression,
+        )
+        .unwrap();
+        let frames = sorng_replay::terminal_replay::parse_asciicast(&cast).unwrap();
+        let transcript: String = frames.iter().map(|f| f.data.as_str()).collect();
+        assert_eq!(transcript, "router> ok");
+    }
+
+    #[tokio::test]
+    async fn paused_recordings_skip_data() {
+        let bridge = RecordingBridge::new(true);
+        bridge
+            .start_capture("s1", GatewayProtocol::Rdp, "host:3389", "bob", Vec::new())
+            .await
+            .unwrap();
+        let mut tap = bridge.tap("s1", TapDirection::TargetToClient).unwrap();
+        tap.record(&[0; 10]).await;
+        bridge.pause_recording("s1").unwrap();
+        tap.record(&[0; 10]).await;
+        bridge.resume_recording("s1").unwrap();
+        bridge.record_data("s1", 5).unwrap();
+
+        let stopped = bridge.stop_recording("s1").await.unwrap();
+        assert_eq!(stopped.bytes_recorded, 15);
+        assert!(stopped.capture_id.is_none() && stopped.library_id.is_none());
+    }
+}
@@ -4,8 +4,25 @@
 //! Provides endpoints for session management, health checks, metrics,
 //! route configuration, and policy management.
 
+use crate::auth::GatewayAuthService;
+use crate::config::GatewayConfig;
+use crate::openmetrics;
 use crate::types::*;
 use serde::{Deserialize, Serialize};
+use sorng_core::net::ACCEPT_ERROR_BACKOFF;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Largest request head accepted by the observability endpoints.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+/// Largest request body accepted by the management API.
+const MAX_REQUEST_BODY: usize = 64 * 1024;
+/// Just-in-time access request routes.
+const ACCESS_REQUESTS_PATH: &str = "/api/v1/access-requests";
+/// How long a client may take to send its request head.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Server status information returned by the management API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +44,31 @@ pub struct CreateSessionRequest {
     pub target_addr: String,
 }
 
+/// Body of `POST /api/v1/access-requests`. The requester is the user the
+/// API key belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAccessRequest {
+    pub protocol: GatewayProtocol,
+    pub target_addr: String,
+    /// Business justification
+    pub reason: String,
+    #[serde(default)]
+    pub ticket_id: Option<String>,
+    /// Requested access duration in seconds
+    pub duration_secs: u64,
+}
+
+/// Body of the approve and deny routes. The approver is the user the API
+/// key belongs to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessDecision {
+    /// Grant duration in seconds (approve only; defaults to the requested one)
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
 /// Request to create a new proxy route.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateRouteRequest {
@@ -74,10 +116,19 @@ impl<T: Serialize> ApiResponse<T> {
 /// In headless mode, the gateway runs as a standalone server process without
 /// any GUI. The management API is the primary interface for controlling it.
 ///
-/// ## Endpoints (when running with Axum)
+/// [`serve`](Self::serve) currently answers the health and metrics endpoints
+/// and the access request routes; the remaining management routes are
+/// reserved for the REST API.
+///
+/// ## Endpoints
 ///
 /// - `GET  /health`          — Health check (configurable path)
-/// - `GET  /metrics`         — Metrics snapshot (configurable path)
+/// - `GET  /metrics`         — OpenMetrics exposition (configurable path)
+/// - `GET  /api/v1/access-requests` — List requests awaiting a decision
+/// - `POST /api/v1/access-requests` — Request just-in-time access
+/// - `POST /api/v1/access-requests/:id/approve` — Approve and issue a grant
+/// - `POST /api/v1/access-requests/:id/deny` — Reject a request
+/// - `DELETE /api/v1/access-requests/:id` — Withdraw one's own request
 /// - `GET  /api/v1/status`   — Full server status
 /// - `GET  /api/v1/sessions` — List active sessions
 /// - `POST /api/v1/sessions` — Create a new proxied session
@@ -103,6 +154,159 @@ pub struct HeadlessServer {
     pub bind_port: u16,
     /// Whether the server is running
     pub running: bool,
+    /// Health and metrics endpoint settings
+    pub endpoints: ObservabilityEndpoints,
+}
+
+/// Paths and access rules for the health and metrics endpoints.
+#[derive(Debug, Clone)]
+pub struct ObservabilityEndpoints {
+    /// Health check path (`None` = disabled)
+    pub health_path: Option<String>,
+    /// Metrics path (`None` = disabled)
+    pub metrics_path: Option<String>,
+    /// Require a `ViewMetrics` API key to scrape metrics
+    pub metrics_auth_required: bool,
+}
+
+impl Default for ObservabilityEndpoints {
+    fn default() -> Self {
+        Self {
+            health_path: Some("/health".to_string()),
+            metrics_path: Some("/metrics".to_string()),
+            metrics_auth_required: false,
+        }
+    }
+}
+
+impl ObservabilityEndpoints {
+    /// Build the endpoint settings from a gateway configuration.
+    pub fn from_config(config: &GatewayConfig) -> Self {
+        Self {
+            health_path: config
+                .health_check_enabled
+                .then(|| config.health_check_path.clone()),
+            metrics_path: config.metrics_enabled.then(|| config.metrics_path.clone()),
+            metrics_auth_required: config.metrics_auth_required,
+        }
+    }
+}
+
+/// A parsed HTTP request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Request path without the query string
+    pub path: String,
+    /// Value of the `Authorization` header, if present
+    pub authorization: Option<String>,
+    /// Value of the `Content-Length` header (0 when absent)
+    pub content_length: usize,
+    /// Request body
+    pub body: Vec<u8>,
+    /// IP address of the client
+    pub peer_ip: Option<String>,
+}
+
+impl HttpRequest {
+    /// Parse an HTTP/1.x request head (request line and headers).
+    pub fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        if !request_line.next()?.starts_with("HTTP/1.") {
+            return None;
+        }
+        let path = target.split('?').next().unwrap_or(target).to_string();
+        let mut authorization = None;
+        let mut content_length = 0;
+        for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+        Some(Self {
+            method,
+            path,
+            authorization,
+            content_length,
+            body: Vec::new(),
+            peer_ip: None,
+        })
+    }
+
+    /// The token of a `Bearer` authorization header.
+    fn bearer_token(&self) -> Option<&str> {
+        let value = self.authorization.as_deref()?;
+        let (scheme, token) = value.split_once(' ')?;
+        scheme
+            .eq_ignore_ascii_case("bearer")
+            .then(|| token.trim())
+            .filter(|token| !token.is_empty())
+    }
+}
+
+/// A response produced by the observability endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+    /// Ask the client for bearer credentials (401 responses)
+    pub bearer_challenge: bool,
+}
+
+impl HttpResponse {
+    fn new(status: u16, content_type: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+            bearer_challenge: false,
+        }
+    }
+
+    fn text(status: u16, body: &str) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", format!("{}\n", body))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// Serialize as an HTTP/1.1 response that closes the connection.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            self.reason(),
+            self.content_type,
+            self.body.len()
+        );
+        if self.bearer_challenge {
+            head.push_str("WWW-Authenticate: Bearer realm=\"sorng-gateway\"\r\n");
+        }
+        if self.status == 405 {
+            head.push_str("Allow: GET\r\n");
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes
+    }
 }
 
 impl HeadlessServer {
@@ -111,11 +315,503 @@ impl HeadlessServer {
             bind_addr: bind_addr.to_string(),
             bind_port,
             running: false,
+            endpoints: ObservabilityEndpoints::default(),
         }
     }
 
+    /// Create a server for the management port of a gateway configuration.
+    /// Applies the same loopback-only bind rule as the proxy listeners.
+    pub fn from_config(config: &GatewayConfig) -> Result<Self, String> {
+        let host = crate::service::resolve_listen_bind_host(
+            &config.listen_host,
+            config.allow_non_loopback_bind,
+        )?;
+        let mut server = Self::new(&host, config.listen_port);
+        server.endpoints = ObservabilityEndpoints::from_config(config);
+        Ok(server)
+    }
+
+    /// Bind the management port and answer requests in the background.
+    /// Abort the returned handle to stop serving.
+    pub async fn serve(&mut self, gateway: GatewayServiceState) -> Result<JoinHandle<()>, String> {
+        let listener = TcpListener::bind(self.addr())
+            .await
+            .map_err(|e| format!("Failed to bind {}: {}", self.addr(), e))?;
+        // Report the real port when bound to port 0.
+        if let Ok(local) = listener.local_addr() {
+            self.bind_port = local.port();
+        }
+        log::info!(
+            "[GATEWAY] Management endpoints listening on {}",
+            self.addr()
+        );
+        self.running = true;
+
+        let endpoints = self.endpoints.clone();
+        Ok(tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::warn!("[GATEWAY] Management accept failed: {}", e);
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                        continue;
+                    }
+                };
+                let endpoints = endpoints.clone();
+                let gateway = gateway.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(stream, &endpoints, &gateway).await {
+                        log::debug!("[GATEWAY] Management request from {} failed: {}", peer, e);
+                    }
+                });
+            }
+        }))
+    }
+
     /// Get the full bind address.
     pub fn addr(&self) -> String {
         format!("{}:{}", self.bind_addr, self.bind_port)
     }
 }
+
+/// Read one request from `stream`, answer it and close the connection.
+async fn serve_connection(
+    mut stream: TcpStream,
+    endpoints: &ObservabilityEndpoints,
+    gateway: &GatewayServiceState,
+) -> std::io::Result<()> {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(Some(mut request))) => {
+            request.peer_ip = stream.peer_addr().ok().map(|addr| addr.ip().to_string());
+            handle_request(endpoints, gateway, &request).await
+        }
+        Ok(Ok(None)) => HttpResponse::text(400, "Bad Request"),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => return Ok(()),
+    };
+    stream.write_all(&response.to_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Read a request head and its `Content-Length` body. Returns `None` if the
+/// request is malformed or oversized, or the client closed early.
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<HttpRequest>> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    let end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buf.len() > MAX_REQUEST_HEAD {
+            return Ok(None);
+        }
+    };
+    let mut body = buf.split_off(end + 4);
+    buf.truncate(end);
+    let Some(mut request) = String::from_utf8(buf)
+        .ok()
+        .and_then(|head| HttpRequest::parse(&head))
+    else {
+        return Ok(None);
+    };
+    if request.content_length > MAX_REQUEST_BODY {
+        return Ok(None);
+    }
+    while body.len() < request.content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(request.content_length);
+    request.body = body;
+    Ok(Some(request))
+}
+
+/// Route a request to the health or metrics endpoint or the access request
+/// routes.
+pub async fn handle_request(
+    endpoints: &ObservabilityEndpoints,
+    gateway: &GatewayServiceState,
+    request: &HttpRequest,
+) -> HttpResponse {
+    let path = request.path.as_str();
+    if let Some(rest) = path.strip_prefix(ACCESS_REQUESTS_PATH) {
+        if rest.is_empty() || rest.starts_with('/') {
+            return handle_access_requests(gateway, request, rest).await;
+        }
+    }
+    let is_metrics = endpoints.metrics_path.as_deref() == Some(path);
+    let is_health = endpoints.health_path.as_deref() == Some(path);
+    if !is_metrics && !is_health {
+        return HttpResponse::text(404, "Not Found");
+    }
+    if request.method != "GET" {
+        return HttpResponse::text(405, "Method Not Allowed");
+    }
+
+    let mut gw = gateway.lock().await;
+    if is_metrics {
+        if endpoints.metrics_auth_required {
+            let Some(token) = request.bearer_token() else {
+                gw.metrics.record_auth_failure();
+                return unauthorized();
+            };
+            match gw.authenticate_api_key(token) {
+                Ok(key)
+                    if GatewayAuthService::has_permission(&key, GatewayPermission::ViewMetrics) => {
+                }
+                Ok(_) => return HttpResponse::text(403, "Forbidden"),
+                Err(_) => return unauthorized(),
+            }
+        }
+        return HttpResponse::new(200, openmetrics::CONTENT_TYPE, gw.render_metrics());
+    }
+
+    let health = gw.get_health();
+    let status = if health.status == HealthStatus::Unhealthy {
+        503
+    } else {
+        200
+    };
+    match serde_json::to_string(&health) {
+        Ok(body) => HttpResponse::new(status, "application/json", body),
+        Err(e) => HttpResponse::text(500, &e.to_string()),
+    }
+}
+
+/// Serve the just-in-time access routes. `rest` is the path after
+/// `/api/v1/access-requests`.
+async fn handle_access_requests(
+    gateway: &GatewayServiceState,
+    request: &HttpRequest,
+    rest: &str,
+) -> HttpResponse {
+    let mut gw = gateway.lock().await;
+    let Some(token) = request.bearer_token() else {
+        gw.metrics.record_auth_failure();
+        return unauthorized();
+    };
+    let Ok(key) = gw.authenticate_api_key(token) else {
+        return unauthorized();
+    };
+
+    let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", []) => json_response(200, &ApiResponse::ok(gw.list_pending_approvals())),
+        ("POST", []) => {
+            if !GatewayAuthService::has_permission(&key, GatewayPermission::Connect) {
+                return HttpResponse::text(403, "Forbidden");
+            }
+            let body: FileAccessRequest = match serde_json::from_slice(&request.body) {
+                Ok(body) => body,
+                Err(e) => return json_response(400, &ApiResponse::<()>::err(e.to_string())),
+            };
+            api_result(gw.request_access(AccessRequest {
+                user_id: key.user_id.clone(),
+                username: key.user_id.clone(),
+                protocol: body.protocol,
+                target_addr: body.target_addr,
+                source_addr: request.peer_ip.clone().unwrap_or_default(),
+                reason: body.reason,
+                ticket_id: body.ticket_id,
+                duration_secs: body.duration_secs,
+            }))
+        }
+        ("POST", [id, action @ ("approve" | "deny")]) => {
+            let decision: AccessDecision = match request.body.is_empty() {
+                true => AccessDecision::default(),
+                false => match serde_json::from_slice(&request.body) {
+                    Ok(body) => body,
+                    Err(e) => return json_response(400, &ApiResponse::<()>::err(e.to_string())),
+                },
+            };
+            let note = decision.note.as_deref();
+            match *action {
+                "approve" => {
+                    api_result(gw.approve_access(id, &key.user_id, decision.duration_secs, note))
+                }
+                _ => api_result(gw.deny_access(id, &key.user_id, note)),
+            }
+        }
+        ("DELETE", [id]) => api_result(gw.cancel_access_request(id, &key.user_id)),
+        _ => HttpResponse::text(404, "Not Found"),
+    }
+}
+
+/// Wrap a service result in an [`ApiResponse`]; errors are reported as 400.
+fn api_result<T: Serialize>(result: Result<T, String>) -> HttpResponse {
+    match result {
+        Ok(data) => json_response(200, &ApiResponse::ok(data)),
+        Err(e) => json_response(400, &ApiResponse::<()>::err(e)),
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, body: &T) -> HttpResponse {
+    match serde_json::to_string(body) {
+        Ok(body) => HttpResponse::new(status, "application/json", body),
+        Err(e) => HttpResponse::text(500, &e.to_string()),
+    }
+}
+
+fn unauthorized() -> HttpResponse {
+    let mut response = HttpResponse::text(401, "Unauthorized");
+    response.bearer_challenge = true;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::GatewayService;
+
+    async fn gateway(dir: &tempfile::TempDir) -> GatewayServiceState {
+        GatewayService::new_default(
+            dir.path().to_string_lossy().into_owned(),
+            Default::default(),
+        )
+        .await
+    }
+
+    fn get(path: &str, authorization: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            authorization: authorization.map(str::to_string),
+            content_length: 0,
+            body: Vec::new(),
+            peer_ip: None,
+        }
+    }
+
+    #[test]
+    fn parses_request_head() {
+        let request = HttpRequest::parse(
+            "GET /metrics?x=1 HTTP/1.1\r\nHost: gw\r\nauthorization: Bearer abc",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/metrics");
+        assert_eq!(request.bearer_token(), Some("abc"));
+        assert!(HttpRequest::parse("GET /metrics").is_none());
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_exposes_openmetrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let gw = gateway(&dir).await;
+        let endpoints = ObservabilityEndpoints::default();
+
+        // No policies: the default deny is recorded against the "default" policy.
+        let denied = gw
+            .lock()
+            .await
+            .create_session(
+                "alice",
+                "alice",
+                GatewayProtocol::Ssh,
+                "10.0.0.5:22",
+                "127.0.0.1",
+            )
+            .await;
+        assert!(denied.is_err());
+
+        let response = handle_request(&endpoints, &gw, &get("/metrics", None)).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, openmetrics::CONTENT_TYPE);
+        assert!(response.body.contains(
+            "sorng_gateway_policy_decisions_total{policy=\"default\",action=\"Deny\"} 1\n"
+        ));
+        assert!(response
+            .body
+            .contains("sorng_gateway_policy_denials_total 1\n"));
+        assert!(response.body.contains(
+            "sorng_gateway_health_status{check=\"overall\",sorng_gateway_health_status=\"healthy\"} 1\n"
+        ));
+        assert!(response.body.ends_with("# EOF\n"));
+
+        let health = handle_request(&endpoints, &gw, &get("/health", None)).await;
+        assert_eq!(health.status, 200);
+        assert_eq!(health.content_type, "application/json");
+
+        assert_eq!(
+            handle_request(&endpoints, &gw, &get("/nope", None))
+                .await
+                .status,
+            404
+        );
+        let mut post = get("/metrics", None);
+        post.method = "POST".to_string();
+        assert_eq!(handle_request(&endpoints, &gw, &post).await.status, 405);
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_requires_view_metrics_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let gw = gateway(&dir).await;
+        let endpoints = ObservabilityEndpoints {
+            metrics_auth_required: true,
+            ..Default::default()
+        };
+        let (viewer, limited) = {
+            let mut svc = gw.lock().await;
+            let (_, viewer) = svc
+                .create_api_key("prom", "ops", vec![GatewayPermission::ViewMetrics])
+                .unwrap();
+            let (_, limited) = svc
+                .create_api_key("client", "ops", vec![GatewayPermission::Connect])
+                .unwrap();
+            (viewer, limited)
+        };
+
+        let missing = handle_request(&endpoints, &gw, &get("/metrics", None)).await;
+        assert_eq!(missing.status, 401);
+        assert!(String::from_utf8(missing.to_bytes())
+            .unwrap()
+            .contains("WWW-Authenticate: Bearer"));
+        let bad = handle_request(&endpoints, &gw, &get("/metrics", Some("Bearer nope"))).await;
+        assert_eq!(bad.status, 401);
+
+        let forbidden_auth = format!("Bearer {}", limited);
+        let forbidden =
+            handle_request(&endpoints, &gw, &get("/metrics", Some(&forbidden_auth))).await;
+        assert_eq!(forbidden.status, 403);
+
+        let viewer_auth = format!("Bearer {}", viewer);
+        let ok = handle_request(&endpoints, &gw, &get("/metrics", Some(&viewer_auth))).await;
+        assert_eq!(ok.status, 200);
+        assert!(ok.body.contains("sorng_gateway_auth_failures_total 2\n"));
+    }
+
+    fn post(path: &str, authorization: &str, body: serde_json::Value) -> HttpRequest {
+        let body = body.to_string().into_bytes();
+        HttpRequest {
+            method: "POST".to_string(),
+            content_length: body.len(),
+            body,
+            peer_ip: Some("127.0.0.1".to_string()),
+            ..get(path, Some(authorization))
+        }
+    }
+
+    #[tokio::test]
+    async fn access_requests_are_filed_and_decided_over_the_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let gw = gateway(&dir).await;
+        let endpoints = ObservabilityEndpoints::default();
+        let (alice, lead) = {
+            let mut svc = gw.lock().await;
+            svc.add_policy(AccessPolicy {
+                id: "prod-ssh".to_string(),
+                name: "Production SSH".to_string(),
+                description: None,
+                enabled: true,
+                priority: 10,
+                action: PolicyAction::RequireApproval,
+                user_conditions: vec![UserCondition::AnyAuthenticated],
+                target_conditions: vec![TargetCondition::Protocol(GatewayProtocol::Ssh)],
+                time_conditions: Vec::new(),
+                connection_limits: None,
+                approval: Some(ApprovalSettings {
+                    approvers: vec!["lead".to_string()],
+                    ..Default::default()
+                }),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            })
+            .unwrap();
+            let (_, alice) = svc
+                .create_api_key("alice", "alice", vec![GatewayPermission::Connect])
+                .unwrap();
+            let (_, lead) = svc.create_api_key("lead", "lead", Vec::new()).unwrap();
+            (format!("Bearer {}", alice), format!("Bearer {}", lead))
+        };
+
+        let unauthenticated =
+            handle_request(&endpoints, &gw, &get("/api/v1/access-requests", None)).await;
+        assert_eq!(unauthenticated.status, 401);
+
+        let filed = handle_request(
+            &endpoints,
+            &gw,
+            &post(
+                "/api/v1/access-requests",
+                &alice,
+                serde_json::json!({
+                    "protocol": "Ssh",
+                    "target_addr": "10.0.0.5:22",
+                    "reason": "rotate certificates",
+                    "duration_secs": 900,
+                }),
+            ),
+        )
+        .await;
+        assert_eq!(filed.status, 200, "{}", filed.body);
+        let filed: serde_json::Value = serde_json::from_str(&filed.body).unwrap();
+        let request_id = filed["data"]["id"].as_str().unwrap().to_string();
+        assert_eq!(filed["data"]["user_id"], "alice");
+        assert_eq!(filed["data"]["source_addr"], "127.0.0.1");
+
+        let pending = handle_request(
+            &endpoints,
+            &gw,
+            &get("/api/v1/access-requests", Some(&lead)),
+        )
+        .await;
+        assert!(pending.body.contains(&request_id));
+
+        // The requester cannot approve their own request.
+        let approve_path = format!("/api/v1/access-requests/{}/approve", request_id);
+        let own = handle_request(
+            &endpoints,
+            &gw,
+            &post(&approve_path, &alice, serde_json::json!({})),
+        )
+        .await;
+        assert_eq!(own.status, 400);
+
+        let approved = handle_request(
+            &endpoints,
+            &gw,
+            &post(
+                &approve_path,
+                &lead,
+                serde_json::json!({ "duration_secs": 300, "note": "go ahead" }),
+            ),
+        )
+        .await;
+        assert_eq!(approved.status, 200, "{}", approved.body);
+        assert!(approved.body.contains("\"approved_by\":\"lead\""));
+        assert_eq!(gw.lock().await.list_access_grants().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn serves_metrics_over_http() {
+        let dir = tempfile::tempdir().unwrap();
+        let gw = gateway(&dir).await;
+        let mut server = HeadlessServer::new("127.0.0.1", 0);
+        let handle = server.serve(gw).await.unwrap();
+        assert!(server.running);
+
+        let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        handle.abort();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/openmetrics-text"));
+        assert!(response.contains("# TYPE sorng_gateway_uptime_seconds gauge\n"));
+        assert!(response.ends_with("# EOF\n"));
+    }
+}
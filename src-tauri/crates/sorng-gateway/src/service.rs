@@ -3,6 +3,7 @@
 //! Main entry point for the gateway system. Orchestrates all sub-modules
 //! and provides both Tauri-compatible and standalone server interfaces.
 
+use crate::approval::ApprovalManager;
 use crate::auth::GatewayAuthService;
 use crate::config::GatewayConfig;
 use crate::health::HealthMonitor;
@@ -10,14 +11,31 @@ use crate::letsencrypt_bridge::LetsEncryptBridge;
 use crate::metrics::MetricsCollector;
 use crate::policy::PolicyEngine;
 use crate::proxy::ProxyEngine;
-use crate::recording_bridge::RecordingBridge;
+use crate::recording_bridge::{check_recordable, RecordingBridge, StreamTap, TapDirection};
 use crate::session::SessionManager;
 use crate::tls::TlsManager;
 use crate::tunnel::TunnelManager;
 use crate::types::*;
 use chrono::Utc;
+use sorng_core::net::ACCEPT_ERROR_BACKOFF;
+use sorng_encryption::EncryptionState;
+use sorng_notifications::service::{NotificationService, NotificationServiceState};
+use sorng_notifications::types::{
+    ChannelConfig, NotificationPriority, NotificationRule, NotificationTrigger,
+};
+use sorng_recording::service::RecordingService;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{watch, Mutex};
+
+/// Approval workflow events raised through the notifier.
+const APPROVAL_EVENTS: [&str; 4] = [
+    "gateway.approval_requested",
+    "gateway.approval_decided",
+    "gateway.approval_expired",
+    "gateway.grant_expired",
+];
 
 /// Resolve and validate the bind host for the gateway's proxy-route listeners.
 ///
@@ -56,6 +74,76 @@ pub(crate) fn resolve_listen_bind_host(
     }
 }
 
+/// Start recording a connection accepted on a proxy route. Route listeners
+/// have no `GatewaySession`, so the capture is keyed by a fresh connection ID
+/// and tagged with the route and client address instead.
+///
+/// Returns the connection ID and its (client → target, target → client) taps,
+/// or `None` if the capture could not be started — the connection is then
+/// relayed unrecorded.
+async fn start_route_capture(
+    bridge: &RecordingBridge,
+    route_id: &str,
+    protocol: GatewayProtocol,
+    target: &str,
+    source_addr: &str,
+) -> Option<(String, (StreamTap, StreamTap))> {
+    let connection_id = uuid::Uuid::new_v4().to_string();
+    let tags = vec![
+        "gateway".to_string(),
+        format!("gateway-route:{}", route_id),
+        format!("gateway-source:{}", source_addr),
+    ];
+    let started = bridge
+        .start_capture(&connection_id, protocol, target, source_addr, tags)
+        .await
+        .and_then(|_| {
+            Ok((
+                bridge.tap(&connection_id, TapDirection::ClientToTarget)?,
+                bridge.tap(&connection_id, TapDirection::TargetToClient)?,
+            ))
+        });
+    match started {
+        Ok(taps) => Some((connection_id, taps)),
+        Err(e) => {
+            log::warn!(
+                "[GATEWAY] Not recording connection from {} on route {}: {}",
+                source_addr,
+                route_id,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// A notifier delivering every approval workflow event to `channels`.
+async fn approval_notifier(channels: &[ChannelConfig]) -> NotificationServiceState {
+    let notifier = NotificationService::new();
+    let rule = NotificationRule {
+        id: "gateway-approvals".to_string(),
+        name: "Gateway access approvals".to_string(),
+        description: Some("Tell approvers about just-in-time access requests".to_string()),
+        enabled: true,
+        triggers: APPROVAL_EVENTS
+            .iter()
+            .map(|event| NotificationTrigger::CustomHookEvent(event.to_string()))
+            .collect(),
+        conditions: Vec::new(),
+        channels: channels.to_vec(),
+        throttle: None,
+        escalation: None,
+        template_id: None,
+        priority: NotificationPriority::High,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    if let Err(e) = notifier.lock().await.rules.add_rule(rule) {
+        log::warn!("[GATEWAY] Approval notifications disabled: {}", e);
+    }
+    notifier
+}
+
 /// The top-level gateway service that coordinates all gateway features.
 pub struct GatewayService {
     /// Gateway instance info
@@ -82,13 +170,24 @@ pub struct GatewayService {
     pub letsencrypt: LetsEncryptBridge,
     /// Recording bridge
     pub recording: RecordingBridge,
+    /// Just-in-time access approvals and grants
+    pub approvals: ApprovalManager,
+    /// Notification service used to reach approvers
+    notifier: Option<NotificationServiceState>,
+    /// Bumped whenever an approval request is decided, cancelled or lapses,
+    /// waking connections held by [`open_session`](Self::open_session)
+    decisions: watch::Sender<u64>,
     /// Whether the gateway server is running
     server_running: bool,
 }
 
 impl GatewayService {
-    /// Create a new gateway service with the given configuration.
-    pub fn new(config: GatewayConfig) -> GatewayServiceState {
+    /// Create a new gateway service with the given configuration. Session
+    /// recordings are encrypted at rest under `encryption`.
+    pub async fn new(
+        config: GatewayConfig,
+        encryption: Arc<EncryptionState>,
+    ) -> GatewayServiceState {
         let info = GatewayInfo {
             id: uuid::Uuid::new_v4().to_string(),
             name: config.name.clone(),
@@ -100,6 +199,12 @@ impl GatewayService {
         };
 
         let data_dir = config.data_dir.clone();
+        let recording = RecordingBridge::with_service(
+            config.recording_enabled,
+            RecordingService::new(&data_dir),
+        );
+        recording.set_encryption_state(encryption).await;
+        let notifier = approval_notifier(&config.approval_channels).await;
         let service = GatewayService {
             info,
             config: config.clone(),
@@ -112,7 +217,10 @@ impl GatewayService {
             auth: GatewayAuthService::new(&data_dir),
             tls: TlsManager::new(config.tls),
             letsencrypt: LetsEncryptBridge::new(config.letsencrypt),
-            recording: RecordingBridge::new(config.recording_enabled),
+            recording,
+            approvals: ApprovalManager::new(&data_dir),
+            notifier: Some(notifier),
+            decisions: watch::Sender::new(0),
             server_running: false,
         };
 
@@ -120,9 +228,12 @@ impl GatewayService {
     }
 
     /// Create with default settings (for Tauri integration).
-    pub fn new_default(data_dir: String) -> GatewayServiceState {
+    pub async fn new_default(
+        data_dir: String,
+        encryption: Arc<EncryptionState>,
+    ) -> GatewayServiceState {
         let config = GatewayConfig::default_with_dir(data_dir);
-        Self::new(config)
+        Self::new(config, encryption).await
     }
 
     /// Get gateway info.
@@ -140,6 +251,14 @@ impl GatewayService {
         self.server_running
     }
 
+    /// Deliver approval workflow events through another notification service,
+    /// such as the desktop app's, instead of the one built from
+    /// `approval_channels`. Events are raised as
+    /// `CustomHookEvent("gateway.approval_*")` triggers.
+    pub fn set_notifier(&mut self, notifier: NotificationServiceState) {
+        self.notifier = Some(notifier);
+    }
+
     // ── Session Operations ──────────────────────────────────────────
 
     /// Create a new proxy session after policy evaluation.
@@ -152,13 +271,14 @@ impl GatewayService {
         source_addr: &str,
     ) -> Result<GatewaySession, String> {
         // Evaluate access policies
-        let policy_result = self
-            .policy
-            .evaluate(user_id, target_addr, protocol, source_addr)?;
+        let (policy_result, policy_id) =
+            self.policy
+                .evaluate_with_policy(user_id, target_addr, protocol, source_addr)?;
+        self.metrics
+            .record_policy_decision(policy_id.as_deref(), policy_result);
 
         match policy_result {
             PolicyAction::Deny => {
-                self.metrics.record_denial();
                 return Err(format!(
                     "Access denied by policy: user {} to {}",
                     user_id, target_addr
@@ -167,13 +287,41 @@ impl GatewayService {
             PolicyAction::RequireMfa => {
                 return Err("MFA required for this connection".to_string());
             }
-            _ => {} // Allow, AllowWithRecording, AllowThrottled
+            _ => {} // Allow, AllowWithRecording, AllowThrottled, RequireApproval
         }
 
-        let record = matches!(policy_result, PolicyAction::AllowWithRecording)
-            || self.config.recording_enabled;
+        let grant_id = if policy_result == PolicyAction::RequireApproval {
+            match self.approvals.find_grant(user_id, protocol, target_addr) {
+                Some(grant) => Some(grant.id.clone()),
+                None => {
+                    return Err(
+                        match self.approvals.pending_for(user_id, protocol, target_addr) {
+                            Some(pending) => format!(
+                                "Approval required: request {} is awaiting a decision",
+                                pending.id
+                            ),
+                            None => {
+                                "Approval required: request access with a reason first".to_string()
+                            }
+                        },
+                    );
+                }
+            }
+        } else {
+            None
+        };
+
+        // A policy that demands recording denies what cannot be recorded;
+        // the global switch only covers protocols the gateway can capture.
+        let record = if policy_result == PolicyAction::AllowWithRecording {
+            check_recordable(protocol)
+                .map_err(|e| format!("Policy requires session recording: {}", e))?;
+            true
+        } else {
+            self.config.recording_enabled && check_recordable(protocol).is_ok()
+        };
 
-        let session = self.sessions.create_session(
+        let mut session = self.sessions.create_session(
             user_id,
             username,
             protocol,
@@ -182,10 +330,18 @@ impl GatewayService {
             record,
         );
 
-        self.metrics.record_connection(protocol);
+        self.metrics.record_connection_with_user(protocol, user_id);
+
+        if let Some(grant_id) = grant_id {
+            self.sessions.set_grant_id(&session.id, &grant_id)?;
+            self.approvals.record_grant_use(&grant_id, &session.id);
+            session.grant_id = Some(grant_id);
+        }
 
         if record {
-            self.recording.start_recording(&session.id)?;
+            let recording = self.recording.start_recording(&session).await?;
+            self.sessions.set_recording_id(&session.id, &recording.id)?;
+            session.recording_id = Some(recording.id);
         }
 
         log::info!(
@@ -199,16 +355,84 @@ impl GatewayService {
         Ok(session)
     }
 
-    /// Terminate a session.
-    pub async fn terminate_session(&mut self, session_id: &str) -> Result<(), String> {
-        let session = self.sessions.get_session(session_id)?;
-
-        if session.recording {
-            self.recording.stop_recording(session_id)?;
+    /// Like [`create_session`](Self::create_session), but a connection
+    /// waiting on an approval request is held until the request is decided,
+    /// withdrawn or lapses, or until `hold` runs out, instead of being
+    /// rejected straight away.
+    pub async fn open_session(
+        state: GatewayServiceState,
+        user_id: &str,
+        username: &str,
+        protocol: GatewayProtocol,
+        target_addr: &str,
+        source_addr: &str,
+        hold: Duration,
+    ) -> Result<GatewaySession, String> {
+        let deadline = tokio::time::Instant::now() + hold;
+        let mut waiting_on: Option<String> = None;
+        loop {
+            let mut decisions = {
+                let mut gw = state.lock().await;
+                if let Some(request) = waiting_on
+                    .as_deref()
+                    .and_then(|id| gw.approvals.get_request(id))
+                {
+                    match request.status {
+                        ApprovalStatus::Pending | ApprovalStatus::Approved => {}
+                        status => {
+                            return Err(format!(
+                                "Approval required: request {} is {:?}",
+                                request.id, status
+                            ))
+                        }
+                    }
+                }
+                // Subscribe before checking so a decision made in between is
+                // not missed.
+                let decisions = gw.decisions.subscribe();
+                let error = match gw
+                    .create_session(user_id, username, protocol, target_addr, source_addr)
+                    .await
+                {
+                    Ok(session) => return Ok(session),
+                    Err(e) => e,
+                };
+                let pending = gw
+                    .approvals
+                    .pending_for(user_id, protocol, target_addr)
+                    .map(|request| request.id.clone());
+                match pending {
+                    Some(id) if error.starts_with("Approval required") => {
+                        log::info!(
+                            "[GATEWAY] Holding connection from {} to {} until request {} is decided",
+                            user_id,
+                            target_addr,
+                            id
+                        );
+                        waiting_on = Some(id);
+                        decisions
+                    }
+                    _ => return Err(error),
+                }
+            };
+            match tokio::time::timeout_at(deadline, decisions.changed()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => return Err("Gateway is shutting down".to_string()),
+                Err(_) => {
+                    return Err(format!(
+                        "Approval required: timed out waiting for request {}",
+                        waiting_on.unwrap_or_default()
+                    ))
+                }
+            }
         }
+    }
 
-        self.sessions.terminate_session(session_id)?;
-        self.metrics.record_session_end(&session);
+    /// Terminate a session.
+    pub async fn terminate_session(&mut self, session_id: &str) -> Result<(), String> {
+        let session = self
+            .end_session(session_id, SessionState::Terminated)
+            .await?;
 
         log::info!(
             "[GATEWAY] Session {} terminated: {} -> {}",
@@ -220,6 +444,97 @@ impl GatewayService {
         Ok(())
     }
 
+    /// Relay a client connection for a session created with
+    /// [`create_session`](Self::create_session) to the session's target.
+    /// Traffic is teed into the session's recording, and the session is
+    /// closed (saving the recording) once both sides have hung up.
+    ///
+    /// Returns `(bytes_sent, bytes_received)` from the client's point of view.
+    pub async fn relay_session(
+        state: GatewayServiceState,
+        session_id: &str,
+        inbound: TcpStream,
+    ) -> Result<(u64, u64), String> {
+        let (session, recording) = {
+            let gw = state.lock().await;
+            (gw.sessions.get_session(session_id)?, gw.recording.clone())
+        };
+        if session.state != SessionState::Pending {
+            return Err("Session is not waiting for a connection".to_string());
+        }
+
+        let outbound = match TcpStream::connect(&session.target_addr).await {
+            Ok(outbound) => outbound,
+            Err(e) => {
+                let mut gw = state.lock().await;
+                gw.metrics.record_error();
+                gw.end_session(session_id, SessionState::Error).await?;
+                return Err(format!(
+                    "Failed to connect to {}: {}",
+                    session.target_addr, e
+                ));
+            }
+        };
+        let taps = match session.recording {
+            true => Some((
+                recording.tap(session_id, TapDirection::ClientToTarget)?,
+                recording.tap(session_id, TapDirection::TargetToClient)?,
+            )),
+            false => None,
+        };
+        state.lock().await.sessions.activate_session(session_id)?;
+
+        let relayed = crate::proxy::relay_connection(inbound, outbound, taps).await;
+
+        let mut gw = state.lock().await;
+        // A session terminated while relaying has already been wound up.
+        if !gw.sessions.is_active(session_id) {
+            return relayed.map_err(|e| e.to_string());
+        }
+        match relayed {
+            Ok((sent, received)) => {
+                gw.sessions.update_bytes(session_id, sent, received)?;
+                gw.end_session(session_id, SessionState::Closed).await?;
+                Ok((sent, received))
+            }
+            Err(e) => {
+                gw.metrics.record_error();
+                gw.end_session(session_id, SessionState::Error).await?;
+                Err(format!("Relay for session {} failed: {}", session_id, e))
+            }
+        }
+    }
+
+    /// Stop a session's recording and move it to `outcome` (`Closed`,
+    /// `Error` or `Terminated`).
+    async fn end_session(
+        &mut self,
+        session_id: &str,
+        outcome: SessionState,
+    ) -> Result<GatewaySession, String> {
+        let session = self.sessions.get_session(session_id)?;
+
+        if session.recording {
+            // A recording that cannot be saved must not keep the session alive.
+            if let Err(e) = self.recording.stop_recording(session_id).await {
+                log::warn!(
+                    "[GATEWAY] Recording for session {} not saved: {}",
+                    session_id,
+                    e
+                );
+            }
+        }
+
+        match outcome {
+            SessionState::Closed => self.sessions.close_session(session_id)?,
+            SessionState::Error => self.sessions.error_session(session_id)?,
+            _ => self.sessions.terminate_session(session_id)?,
+        }
+        let session = self.sessions.get_session(session_id)?;
+        self.metrics.record_session_end(&session);
+        Ok(session)
+    }
+
     /// List all active sessions.
     pub fn list_active_sessions(&self) -> Vec<&GatewaySession> {
         self.sessions.list_active()
@@ -230,6 +545,205 @@ impl GatewayService {
         self.sessions.list_by_user(user_id)
     }
 
+    // ── Just-in-Time Access ─────────────────────────────────────────
+
+    /// File an access request against a `RequireApproval` policy and notify
+    /// its approvers. The connection stays parked until a grant is issued.
+    pub fn request_access(&mut self, request: AccessRequest) -> Result<ApprovalRequest, String> {
+        let (action, policy_id) = self.policy.evaluate_with_policy(
+            &request.user_id,
+            &request.target_addr,
+            request.protocol,
+            &request.source_addr,
+        )?;
+        let policy = match (action, policy_id) {
+            (PolicyAction::RequireApproval, Some(id)) => {
+                self.policy.get_policy(&id).ok_or("Policy not found")?
+            }
+            (PolicyAction::Deny, _) => {
+                return Err(format!(
+                    "Access denied by policy: user {} to {}",
+                    request.user_id, request.target_addr
+                ));
+            }
+            _ => return Err("This connection does not require approval".to_string()),
+        };
+        let policy_id = policy.id.clone();
+        let policy_name = policy.name.clone();
+        let settings = policy.approval.clone().unwrap_or_default();
+
+        let approval = self.approvals.submit(&policy_id, &settings, &request)?;
+        self.notify(
+            "gateway.approval_requested",
+            serde_json::json!({
+                "request": approval,
+                "policy": policy_name,
+                "approvers": settings.approvers,
+            }),
+        );
+        Ok(approval)
+    }
+
+    /// Approve a pending request and issue a time-boxed grant.
+    pub fn approve_access(
+        &mut self,
+        request_id: &str,
+        approver: &str,
+        duration_secs: Option<u64>,
+        note: Option<&str>,
+    ) -> Result<AccessGrant, String> {
+        let settings = self.approval_settings(request_id)?;
+        let grant = self
+            .approvals
+            .approve(request_id, approver, &settings, duration_secs, note)?;
+        self.notify(
+            "gateway.approval_decided",
+            serde_json::json!({ "decision": "approved", "grant": grant }),
+        );
+        self.wake_held_sessions();
+        Ok(grant)
+    }
+
+    /// Reject a pending request.
+    pub fn deny_access(
+        &mut self,
+        request_id: &str,
+        approver: &str,
+        note: Option<&str>,
+    ) -> Result<ApprovalRequest, String> {
+        let settings = self.approval_settings(request_id)?;
+        let request = self.approvals.deny(request_id, approver, &settings, note)?;
+        self.notify(
+            "gateway.approval_decided",
+            serde_json::json!({ "decision": "denied", "request": request }),
+        );
+        self.wake_held_sessions();
+        Ok(request)
+    }
+
+    /// Withdraw one's own pending request.
+    pub fn cancel_access_request(&mut self, request_id: &str, user_id: &str) -> Result<(), String> {
+        self.approvals.cancel(request_id, user_id)?;
+        self.wake_held_sessions();
+        Ok(())
+    }
+
+    /// Revoke a grant early and tear down the sessions opened under it.
+    /// Returns the IDs of the terminated sessions.
+    pub async fn revoke_grant(
+        &mut self,
+        grant_id: &str,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<Vec<String>, String> {
+        let grant = self.approvals.revoke_grant(grant_id, actor, reason)?;
+        Ok(self.tear_down_grant_sessions(&grant).await)
+    }
+
+    /// Lapse undecided requests, expire grants, and tear down the sessions
+    /// of expired grants. Returns the IDs of the terminated sessions.
+    pub async fn enforce_grants(&mut self) -> Vec<String> {
+        let (lapsed, expired) = self.approvals.expire(Utc::now());
+        if !lapsed.is_empty() {
+            self.wake_held_sessions();
+        }
+        for request in lapsed {
+            self.notify(
+                "gateway.approval_expired",
+                serde_json::json!({ "request": request }),
+            );
+        }
+        let mut terminated = Vec::new();
+        for grant in expired {
+            terminated.extend(self.tear_down_grant_sessions(&grant).await);
+            self.notify(
+                "gateway.grant_expired",
+                serde_json::json!({ "grant": grant }),
+            );
+        }
+        terminated
+    }
+
+    /// Run [`enforce_grants`](Self::enforce_grants) on a fixed interval until
+    /// the returned handle is aborted.
+    pub fn spawn_grant_enforcer(
+        state: GatewayServiceState,
+        every: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            loop {
+                ticker.tick().await;
+                let terminated = state.lock().await.enforce_grants().await;
+                if !terminated.is_empty() {
+                    log::info!(
+                        "[GATEWAY] Tore down {} session(s) with expired grants",
+                        terminated.len()
+                    );
+                }
+            }
+        })
+    }
+
+    /// List requests waiting for a decision.
+    pub fn list_pending_approvals(&self) -> Vec<&ApprovalRequest> {
+        self.approvals.list_pending()
+    }
+
+    /// List live access grants.
+    pub fn list_access_grants(&self) -> Vec<&AccessGrant> {
+        self.approvals.list_grants()
+    }
+
+    async fn tear_down_grant_sessions(&mut self, grant: &AccessGrant) -> Vec<String> {
+        let session_ids = self.sessions.open_sessions_for_grant(&grant.id);
+        for session_id in &session_ids {
+            if let Err(e) = self.terminate_session(session_id).await {
+                log::warn!(
+                    "[GATEWAY] Failed to tear down session {}: {}",
+                    session_id,
+                    e
+                );
+                continue;
+            }
+            self.approvals.record_teardown(grant, session_id);
+        }
+        session_ids
+    }
+
+    fn approval_settings(&self, request_id: &str) -> Result<ApprovalSettings, String> {
+        let request = self
+            .approvals
+            .get_request(request_id)
+            .ok_or("Approval request not found")?;
+        let policy = self
+            .policy
+            .get_policy(&request.policy_id)
+            .ok_or("The policy behind this request no longer exists")?;
+        Ok(policy.approval.clone().unwrap_or_default())
+    }
+
+    fn wake_held_sessions(&self) {
+        self.decisions.send_modify(|generation| *generation += 1);
+    }
+
+    /// Raise an approval workflow event without blocking the gateway lock on
+    /// channel delivery.
+    fn notify(&self, event: &str, data: serde_json::Value) {
+        let Some(notifier) = self.notifier.clone() else {
+            log::debug!("[GATEWAY] No notifier configured for {}", event);
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("[GATEWAY] No async runtime to deliver {}", event);
+            return;
+        };
+        let trigger = NotificationTrigger::CustomHookEvent(event.to_string());
+        runtime.spawn(async move {
+            notifier.lock().await.process_event(trigger, data).await;
+        });
+    }
+
     // ── Route Management ────────────────────────────────────────────
 
     /// Add a proxy route.
@@ -281,6 +795,11 @@ impl GatewayService {
         self.metrics.snapshot()
     }
 
+    /// Render metrics and health in the OpenMetrics text format.
+    pub fn render_metrics(&self) -> String {
+        self.metrics.encode_openmetrics(&self.get_health())
+    }
+
     // ── API Key Management ──────────────────────────────────────────
 
     /// Create a new API key for a user.
@@ -300,7 +819,11 @@ impl GatewayService {
 
     /// Authenticate with an API key.
     pub fn authenticate_api_key(&mut self, key: &str) -> Result<GatewayApiKey, String> {
-        self.auth.authenticate(key)
+        let result = self.auth.authenticate(key);
+        if result.is_err() {
+            self.metrics.record_auth_failure();
+        }
+        result
     }
 
     // ── Server Lifecycle ────────────────────────────────────────────
@@ -325,6 +848,10 @@ impl GatewayService {
             log::warn!("[GATEWAY] Let's Encrypt bridge init failed: {e}");
         }
 
+        if let Err(e) = self.recording.init().await {
+            log::warn!("[GATEWAY] Recording service init failed: {e}");
+        }
+
         // Resolve the bind host once for all proxy routes. Honors the configured
         // `listen_host` (default 127.0.0.1); a non-loopback host requires the
         // explicit `allow_non_loopback_bind` opt-in (t40-e7).
@@ -352,6 +879,12 @@ impl GatewayService {
             let listen_addr = format!("{}:{}", bind_host, route.listen_port);
             let backend_addr = format!("{}:{}", route.target_host, route.target_port);
             let protocol = route.protocol;
+            let route_id = route.id.clone();
+            // The bridge is checked per connection so that disabling
+            // recording on reload reaches listeners that are already running.
+            let recording = ((route.record_sessions || self.config.recording_enabled)
+                && check_recordable(protocol).is_ok())
+            .then(|| self.recording.clone());
             tokio::spawn(async move {
                 use tokio::net::TcpListener;
                 let listener = match TcpListener::bind(&listen_addr).await {
@@ -364,16 +897,42 @@ impl GatewayService {
                 log::info!("[GATEWAY] Listening on {} for {:?}", listen_addr, protocol);
                 loop {
                     match listener.accept().await {
-                        Ok((mut inbound, _addr)) => {
+                        Ok((inbound, addr)) => {
                             let backend_addr = backend_addr.clone();
+                            let route_id = route_id.clone();
+                            let recording = recording.clone();
                             tokio::spawn(async move {
                                 match tokio::net::TcpStream::connect(&backend_addr).await {
-                                    Ok(mut outbound) => {
-                                        let (mut ri, mut wi) = inbound.split();
-                                        let (mut ro, mut wo) = outbound.split();
-                                        let c1 = tokio::io::copy(&mut ri, &mut wo);
-                                        let c2 = tokio::io::copy(&mut ro, &mut wi);
-                                        let _ = tokio::try_join!(c1, c2);
+                                    Ok(outbound) => {
+                                        let capture = match &recording {
+                                            Some(bridge) if bridge.is_enabled() => {
+                                                start_route_capture(
+                                                    bridge,
+                                                    &route_id,
+                                                    protocol,
+                                                    &backend_addr,
+                                                    &addr.to_string(),
+                                                )
+                                                .await
+                                            }
+                                            _ => None,
+                                        };
+                                        let (capture_id, taps) = capture.unzip();
+                                        let _ =
+                                            crate::proxy::relay_connection(inbound, outbound, taps)
+                                                .await;
+                                        if let (Some(bridge), Some(capture_id)) =
+                                            (&recording, capture_id)
+                                        {
+                                            if let Err(e) = bridge.stop_recording(&capture_id).await
+                                            {
+                                                log::warn!(
+                                                    "[GATEWAY] Recording for connection {} not saved: {}",
+                                                    capture_id,
+                                                    e
+                                                );
+                                            }
+                                        }
                                     }
                                     Err(e) => {
                                         log::error!(
@@ -387,6 +946,7 @@ impl GatewayService {
                         }
                         Err(e) => {
                             log::error!("[GATEWAY] Accept error on {}: {}", listen_addr, e);
+                            tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
                         }
                     }
                 }
@@ -429,6 +989,7 @@ impl GatewayService {
     pub fn reload_config(&mut self, new_config: GatewayConfig) -> Result<(), String> {
         log::info!("[GATEWAY] Reloading configuration");
         self.config = new_config;
+        self.recording.set_enabled(self.config.recording_enabled);
         // Re-apply TLS settings
         self.tls = TlsManager::new(self.config.tls.clone());
         // Re-apply Let's Encrypt settings
@@ -439,7 +1000,9 @@ impl GatewayService {
 
 #[cfg(test)]
 mod tests {
-    use super::resolve_listen_bind_host;
+    use super::{resolve_listen_bind_host, GatewayService};
+    use crate::types::*;
+    use chrono::Utc;
 
     #[test]
     fn loopback_hosts_are_allowed_without_opt_in() {
@@ -476,4 +1039,302 @@ mod tests {
             Ok("0.0.0.0")
         );
     }
+
+    fn approval_policy() -> AccessPolicy {
+        AccessPolicy {
+            id: "prod-ssh".to_string(),
+            name: "Production SSH".to_string(),
+            description: None,
+            enabled: true,
+            priority: 10,
+            action: PolicyAction::RequireApproval,
+            user_conditions: vec![UserCondition::AnyAuthenticated],
+            target_conditions: vec![TargetCondition::Protocol(GatewayProtocol::Ssh)],
+            time_conditions: Vec::new(),
+            connection_limits: None,
+            approval: Some(ApprovalSettings {
+                approvers: vec!["lead".to_string()],
+                ..Default::default()
+            }),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn require_approval_parks_until_granted_and_revocation_tears_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = GatewayService::new_default(
+            dir.path().to_string_lossy().into_owned(),
+            Default::default(),
+        )
+        .await;
+        let mut gw = state.lock().await;
+        gw.add_policy(approval_policy()).unwrap();
+
+        let target = "10.0.0.5:22";
+        let err = gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .unwrap_err();
+        assert!(err.contains("Approval required"), "got: {err}");
+
+        let request = gw
+            .request_access(AccessRequest {
+                user_id: "alice".to_string(),
+                username: "alice".to_string(),
+                protocol: GatewayProtocol::Ssh,
+                target_addr: target.to_string(),
+                source_addr: "127.0.0.1".to_string(),
+                reason: "rotate certificates".to_string(),
+                ticket_id: Some("INC-7".to_string()),
+                duration_secs: 900,
+            })
+            .unwrap();
+        let err = gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .unwrap_err();
+        assert!(err.contains(&request.id), "got: {err}");
+
+        let grant = gw
+            .approve_access(&request.id, "lead", None, Some("go ahead"))
+            .unwrap();
+        let session = gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .unwrap();
+        assert_eq!(session.grant_id.as_deref(), Some(grant.id.as_str()));
+
+        let torn_down = gw.revoke_grant(&grant.id, "lead", None).await.unwrap();
+        assert_eq!(torn_down, vec![session.id.clone()]);
+        assert_eq!(
+            gw.sessions.get_session(&session.id).unwrap().state,
+            SessionState::Terminated
+        );
+        assert_eq!(
+            gw.approvals.audit_log(1)[0].event,
+            ApprovalAuditEvent::SessionTornDown
+        );
+        assert!(gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .is_err());
+    }
+
+    fn access_request(target: &str) -> AccessRequest {
+        AccessRequest {
+            user_id: "alice".to_string(),
+            username: "alice".to_string(),
+            protocol: GatewayProtocol::Ssh,
+            target_addr: target.to_string(),
+            source_addr: "127.0.0.1".to_string(),
+            reason: "patch openssl".to_string(),
+            ticket_id: None,
+            duration_secs: 600,
+        }
+    }
+
+    fn hold(
+        state: &super::GatewayServiceState,
+        target: &str,
+        wait: std::time::Duration,
+    ) -> tokio::task::JoinHandle<Result<GatewaySession, String>> {
+        let (state, target) = (state.clone(), target.to_string());
+        tokio::spawn(async move {
+            GatewayService::open_session(
+                state,
+                "alice",
+                "alice",
+                GatewayProtocol::Ssh,
+                &target,
+                "127.0.0.1",
+                wait,
+            )
+            .await
+        })
+    }
+
+    #[tokio::test]
+    async fn pending_connection_is_held_until_decided() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = GatewayService::new_default(
+            dir.path().to_string_lossy().into_owned(),
+            Default::default(),
+        )
+        .await;
+        let target = "10.0.0.7:22";
+        let (approved, denied) = {
+            let mut gw = state.lock().await;
+            gw.add_policy(approval_policy()).unwrap();
+            let notifier = gw.notifier.clone().unwrap();
+            assert!(notifier
+                .lock()
+                .await
+                .rules
+                .get_rule("gateway-approvals")
+                .is_ok());
+            (
+                gw.request_access(access_request(target)).unwrap(),
+                gw.request_access(access_request("10.0.0.8:22")).unwrap(),
+            )
+        };
+
+        let held = hold(&state, target, std::time::Duration::from_secs(10));
+        let rejected = hold(&state, "10.0.0.8:22", std::time::Duration::from_secs(10));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!held.is_finished() && !rejected.is_finished());
+
+        let grant = {
+            let mut gw = state.lock().await;
+            gw.deny_access(&denied.id, "lead", None).unwrap();
+            gw.approve_access(&approved.id, "lead", None, None).unwrap()
+        };
+        let session = held.await.unwrap().unwrap();
+        assert_eq!(session.grant_id.as_deref(), Some(grant.id.as_str()));
+        let err = rejected.await.unwrap().unwrap_err();
+        assert!(err.contains("Denied"), "got: {err}");
+
+        // Nothing pending: rejected straight away.
+        let err = hold(&state, "10.0.0.9:22", std::time::Duration::from_secs(10))
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.contains("request access"), "got: {err}");
+
+        // Undecided: released when the hold runs out.
+        state
+            .lock()
+            .await
+            .request_access(access_request("10.0.0.9:22"))
+            .unwrap();
+        let err = hold(&state, "10.0.0.9:22", std::time::Duration::from_millis(50))
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.contains("timed out"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn relayed_session_is_recorded_and_closed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::{TcpListener, TcpStream};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = crate::config::GatewayConfig::default_with_dir(
+            dir.path().to_string_lossy().into_owned(),
+        );
+        config.recording_enabled = true;
+        let encryption = std::sync::Arc::new(sorng_encryption::EncryptionState::new());
+        encryption
+            .install(sorng_encryption::MasterDek::generate())
+            .await;
+        let state = GatewayService::new(config, encryption).await;
+
+        let target = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target_addr = target.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = target.accept().await.unwrap();
+            socket.write_all(b"router> ").await.unwrap();
+            let mut line = [0u8; 4];
+            socket.read_exact(&mut line).await.unwrap();
+            socket.write_all(b"uptime 3 days\r\n").await.unwrap();
+        });
+
+        let session = {
+            let mut gw = state.lock().await;
+            gw.add_policy(AccessPolicy {
+                id: "telnet".to_string(),
+                name: "Telnet".to_string(),
+                action: PolicyAction::Allow,
+                target_conditions: vec![TargetCondition::Protocol(GatewayProtocol::Telnet)],
+                approval: None,
+                ..approval_policy()
+            })
+            .unwrap();
+            gw.create_session(
+                "alice",
+                "alice",
+                GatewayProtocol::Telnet,
+                &target_addr,
+                "127.0.0.1",
+            )
+            .await
+            .unwrap()
+        };
+        assert!(session.recording_id.is_some());
+
+        let front = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(front.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (inbound, _) = front.accept().await.unwrap();
+        let relay = {
+            let (state, session_id) = (state.clone(), session.id.clone());
+            tokio::spawn(
+                async move { GatewayService::relay_session(state, &session_id, inbound).await },
+            )
+        };
+        client.write_all(b"up\r\n").await.unwrap();
+        let mut output = String::new();
+        client.read_to_string(&mut output).await.unwrap();
+        drop(client);
+        assert_eq!(output, "router> uptime 3 days\r\n");
+        assert_eq!(relay.await.unwrap().unwrap(), (4, 23));
+
+        let gw = state.lock().await;
+        let ended = gw.sessions.get_session(&session.id).unwrap();
+        assert_eq!(ended.state, SessionState::Closed);
+        assert_eq!((ended.bytes_sent, ended.bytes_received), (4, 23));
+        let recording = gw.recording.get_recording(&session.id).unwrap();
+        assert_eq!(recording.bytes_recorded, 27);
+        let library_id = recording.library_id.unwrap();
+        let found = gw
+            .recording
+            .service()
+            .unwrap()
+            .search_library(&format!("gateway-session:{}", session.id))
+            .await;
+        assert_eq!(found[0].id, library_id);
+    }
+
+    #[tokio::test]
+    async fn ssh_sessions_are_never_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = crate::config::GatewayConfig::default_with_dir(
+            dir.path().to_string_lossy().into_owned(),
+        );
+        config.recording_enabled = true;
+        let state = GatewayService::new(config, Default::default()).await;
+        let mut gw = state.lock().await;
+        let target = "db01:22";
+        gw.add_policy(AccessPolicy {
+            action: PolicyAction::Allow,
+            approval: None,
+            ..approval_policy()
+        })
+        .unwrap();
+
+        // The global switch skips SSH instead of saving an empty recording.
+        let session = gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .unwrap();
+        assert!(!session.recording && session.recording_id.is_none());
+        assert!(gw.recording.get_recording(&session.id).is_none());
+
+        // A policy that requires recording refuses the session.
+        gw.remove_policy("prod-ssh").unwrap();
+        gw.add_policy(AccessPolicy {
+            action: PolicyAction::AllowWithRecording,
+            approval: None,
+            ..approval_policy()
+        })
+        .unwrap();
+        let err = gw
+            .create_session("alice", "alice", GatewayProtocol::Ssh, target, "127.0.0.1")
+            .await
+            .unwrap_err();
+        assert!(err.contains("cannot be recorded"), "got: {err}");
+    }
 }
@@ -53,6 +53,7 @@ impl SessionManager {
             bytes_received: 0,
             recording,
             recording_id: None,
+            grant_id: None,
             metadata: HashMap::new(),
         };
 
@@ -139,6 +140,41 @@ impl SessionManager {
             .collect()
     }
 
+    /// Link a session to its gateway recording.
+    pub fn set_recording_id(&mut self, session_id: &str, recording_id: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or("Session not found")?;
+        session.recording_id = Some(recording_id.to_string());
+        Ok(())
+    }
+
+    /// Link a session to the just-in-time grant that allowed it.
+    pub fn set_grant_id(&mut self, session_id: &str, grant_id: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or("Session not found")?;
+        session.grant_id = Some(grant_id.to_string());
+        Ok(())
+    }
+
+    /// IDs of sessions opened under a grant that have not ended yet.
+    pub fn open_sessions_for_grant(&self, grant_id: &str) -> Vec<String> {
+        self.sessions
+            .values()
+            .filter(|s| {
+                s.grant_id.as_deref() == Some(grant_id)
+                    && !matches!(
+                        s.state,
+                        SessionState::Closed | SessionState::Error | SessionState::Terminated
+                    )
+            })
+            .map(|s| s.id.clone())
+            .collect()
+    }
+
     /// Update bytes transferred for a session.
     pub fn update_bytes(
         &mut self,
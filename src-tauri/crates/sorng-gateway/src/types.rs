@@ -67,6 +67,9 @@ pub struct GatewaySession {
     pub recording: bool,
     /// Recording ID (if recording is active)
     pub recording_id: Option<String>,
+    /// Just-in-time access grant the session was opened under
+    #[serde(default)]
+    pub grant_id: Option<String>,
     /// Additional session metadata
     pub metadata: HashMap<String, String>,
 }
@@ -194,6 +197,9 @@ pub struct AccessPolicy {
     pub time_conditions: Vec<TimeCondition>,
     /// Connection limits
     pub connection_limits: Option<ConnectionLimits>,
+    /// Approval workflow settings (used with `PolicyAction::RequireApproval`)
+    #[serde(default)]
+    pub approval: Option<ApprovalSettings>,
     /// When this policy was created
     pub created_at: DateTime<Utc>,
     /// When this policy was last modified
@@ -213,6 +219,8 @@ pub enum PolicyAction {
     AllowWithRecording,
     /// Allow with bandwidth throttling
     AllowThrottled,
+    /// Park the connection until an approver issues a time-boxed grant
+    RequireApproval,
 }
 
 /// A condition matching users.
@@ -271,6 +279,167 @@ pub struct ConnectionLimits {
     pub max_bandwidth: Option<u64>,
 }
 
+// ── Approvals & Just-in-Time Access ─────────────────────────────────
+
+/// Approval workflow settings for a `RequireApproval` policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalSettings {
+    /// User IDs allowed to decide requests. Empty = any user except the requester.
+    pub approvers: Vec<String>,
+    /// Longest grant an approver may issue, in seconds
+    pub max_grant_secs: u64,
+    /// How long a request may wait for a decision, in seconds
+    pub request_ttl_secs: u64,
+    /// Whether requests must reference a change/incident ticket
+    pub require_ticket: bool,
+}
+
+impl Default for ApprovalSettings {
+    fn default() -> Self {
+        Self {
+            approvers: Vec::new(),
+            max_grant_secs: 3600,
+            request_ttl_secs: 900,
+            require_ticket: false,
+        }
+    }
+}
+
+/// A user's request for just-in-time access to a target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub user_id: String,
+    pub username: String,
+    pub protocol: GatewayProtocol,
+    pub target_addr: String,
+    pub source_addr: String,
+    /// Business justification
+    pub reason: String,
+    /// Change/incident ticket reference
+    pub ticket_id: Option<String>,
+    /// Requested access duration in seconds
+    pub duration_secs: u64,
+}
+
+/// A parked connection attempt waiting for an approver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Unique request ID (UUID v4)
+    pub id: String,
+    /// Policy that required approval
+    pub policy_id: String,
+    /// Requesting user
+    pub user_id: String,
+    /// Requesting username (display purposes)
+    pub username: String,
+    /// Protocol to be proxied
+    pub protocol: GatewayProtocol,
+    /// Target host:port
+    pub target_addr: String,
+    /// Source address of the attempt
+    pub source_addr: String,
+    /// Business justification
+    pub reason: String,
+    /// Change/incident ticket reference
+    pub ticket_id: Option<String>,
+    /// Requested access duration in seconds
+    pub requested_duration_secs: u64,
+    /// Request status
+    pub status: ApprovalStatus,
+    /// When the request was filed
+    pub requested_at: DateTime<Utc>,
+    /// When the request lapses if undecided
+    pub expires_at: DateTime<Utc>,
+    /// Approver who decided the request
+    pub decided_by: Option<String>,
+    /// When the request was decided
+    pub decided_at: Option<DateTime<Utc>>,
+    /// Approver's note
+    pub decision_note: Option<String>,
+    /// Grant issued on approval
+    pub grant_id: Option<String>,
+}
+
+/// Lifecycle of an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalStatus {
+    /// Waiting for an approver
+    Pending,
+    /// Approved; a grant was issued
+    Approved,
+    /// Rejected by an approver
+    Denied,
+    /// Lapsed without a decision
+    Expired,
+    /// Withdrawn by the requester
+    Cancelled,
+}
+
+/// A time-boxed grant allowing one user to reach one target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessGrant {
+    /// Unique grant ID (UUID v4)
+    pub id: String,
+    /// Request this grant answers
+    pub request_id: String,
+    /// Policy that required approval
+    pub policy_id: String,
+    /// Granted user
+    pub user_id: String,
+    /// Granted protocol
+    pub protocol: GatewayProtocol,
+    /// Granted target host:port
+    pub target_addr: String,
+    /// Business justification (copied from the request)
+    pub reason: String,
+    /// Ticket reference (copied from the request)
+    pub ticket_id: Option<String>,
+    /// Approver who issued the grant
+    pub approved_by: String,
+    /// When the grant was issued
+    pub granted_at: DateTime<Utc>,
+    /// When the grant ends; sessions using it are torn down
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AccessGrant {
+    /// Whether the grant permits sessions at `now`.
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// One entry in the approval audit trail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalAuditEntry {
+    /// When it happened
+    pub timestamp: DateTime<Utc>,
+    /// What happened
+    pub event: ApprovalAuditEvent,
+    /// Who did it (`"system"` for automatic actions)
+    pub actor: String,
+    /// Request involved
+    pub request_id: Option<String>,
+    /// Grant involved
+    pub grant_id: Option<String>,
+    /// Free-form details
+    pub details: Option<String>,
+}
+
+/// Approval audit events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalAuditEvent {
+    Requested,
+    Approved,
+    Denied,
+    Cancelled,
+    RequestExpired,
+    GrantUsed,
+    GrantRevoked,
+    GrantExpired,
+    SessionTornDown,
+}
+
 // ── Health & Metrics ────────────────────────────────────────────────
 
 /// Gateway health status.
@@ -13,9 +13,16 @@ use tokio::sync::RwLock;
 #[derive(Clone)]
 pub struct K8sClient {
     pub(crate) http: reqwest::Client,
+    /// Client without the overall request timeout, for watches and
+    /// followed logs.
+    pub(crate) stream_http: reqwest::Client,
     pub(crate) base_url: String,
     pub(crate) _default_namespace: String,
     pub(crate) auth: Arc<RwLock<K8sAuth>>,
+    /// TLS settings, reused for WebSocket subresource connections.
+    pub(crate) tls: Option<K8sTlsConfig>,
+    /// HTTP proxy, reused for WebSocket subresource connections.
+    pub(crate) proxy_url: Option<String>,
 }
 
 /// Authentication state.
@@ -48,6 +55,7 @@ impl K8sClient {
             config.request_timeout_secs,
             config.proxy_url.as_deref(),
         )?;
+        let stream_http = Self::build_http_client(&tls_config, None, config.proxy_url.as_deref())?;
         let namespace = config
             .namespace
             .clone()
@@ -57,9 +65,12 @@ impl K8sClient {
 
         Ok(Self {
             http,
+            stream_http,
             base_url: base_url.trim_end_matches('/').to_string(),
             _default_namespace: namespace,
             auth: Arc::new(RwLock::new(auth)),
+            tls: tls_config,
+            proxy_url: config.proxy_url.clone(),
         })
     }
 
@@ -225,7 +236,7 @@ impl K8sClient {
     }
 
     /// Build authorization headers for the current auth state.
-    async fn auth_headers(&self) -> K8sResult<HeaderMap> {
+    pub(crate) async fn auth_headers(&self) -> K8sResult<HeaderMap> {
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
 
@@ -354,6 +365,20 @@ impl K8sClient {
         resp.text().await.map_err(K8sError::from)
     }
 
+    /// GET request for a long-lived streaming response (watches, followed
+    /// logs).  The caller reads the body with `bytes_stream()`.
+    pub async fn get_stream(&self, url: &str) -> K8sResult<reqwest::Response> {
+        let headers = self.auth_headers().await?;
+        debug!("GET (stream) {}", url);
+        let resp = self.stream_http.get(url).headers(headers).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Self::status_error(status.as_u16(), body));
+        }
+        Ok(resp)
+    }
+
     /// POST request with JSON body.
     pub async fn post<T: serde::de::DeserializeOwned>(
         &self,
@@ -476,20 +501,24 @@ impl K8sClient {
         let status = resp.status();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            let code = status.as_u16();
-            return match code {
-                401 => Err(K8sError::auth(body)),
-                403 => Err(K8sError::forbidden(body)),
-                404 => Err(K8sError::not_found(body)),
-                409 => Err(K8sError::conflict(body)),
-                _ => Err(K8sError::api(code, body)),
-            };
+            return Err(Self::status_error(status.as_u16(), body));
         }
         let body = resp.text().await.map_err(K8sError::from)?;
         serde_json::from_str(&body)
             .map_err(|e| K8sError::parse(format!("{}: {}", e, &body[..body.len().min(200)])))
     }
 
+    /// Map a non-success HTTP status to an error.
+    pub(crate) fn status_error(code: u16, body: String) -> K8sError {
+        match code {
+            401 => K8sError::auth(body),
+            403 => K8sError::forbidden(body),
+            404 => K8sError::not_found(body),
+            409 => K8sError::conflict(body),
+            _ => K8sError::api(code, body),
+        }
+    }
+
     /// Build query string from ListOptions.
     pub fn list_query(opts: &ListOptions) -> String {
         let mut params = Vec::new();
@@ -362,6 +362,169 @@ pub async fn k8s_update_pod_annotations(
         .map_err(|e| e.to_string())
 }
 
+// ── Exec / Attach ─────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn k8s_exec_pod(
+    state: State<'_, K8sServiceState>,
+    id: String,
+    namespace: String,
+    name: String,
+    opts: PodExecOptions,
+) -> Result<ExecSession, String> {
+    let svc = state.lock().await;
+    svc.exec_pod(&id, &namespace, &name, &opts)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_attach_pod(
+    state: State<'_, K8sServiceState>,
+    id: String,
+    namespace: String,
+    name: String,
+    opts: PodAttachOptions,
+) -> Result<ExecSession, String> {
+    let svc = state.lock().await;
+    svc.attach_pod(&id, &namespace, &name, &opts)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_exec_send_input(
+    state: State<'_, K8sServiceState>,
+    session_id: String,
+    data: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_send_input(&session_id, data.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_exec_resize(
+    state: State<'_, K8sServiceState>,
+    session_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_resize(&session_id, cols, rows)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_exec_close_stdin(
+    state: State<'_, K8sServiceState>,
+    session_id: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_close_stdin(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_exec_close(
+    state: State<'_, K8sServiceState>,
+    session_id: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.exec_close(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_list_exec_sessions(
+    state: State<'_, K8sServiceState>,
+) -> Result<Vec<ExecSession>, String> {
+    let svc = state.lock().await;
+    Ok(svc.list_exec_sessions())
+}
+
+// ── Port-forward ──────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn k8s_start_port_forward(
+    state: State<'_, K8sServiceState>,
+    id: String,
+    request: PortForwardRequest,
+) -> Result<PortForwardSession, String> {
+    let mut svc = state.lock().await;
+    svc.start_port_forward(&id, &request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_stop_port_forward(
+    state: State<'_, K8sServiceState>,
+    forward_id: String,
+) -> Result<(), String> {
+    let mut svc = state.lock().await;
+    svc.stop_port_forward(&forward_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_list_port_forwards(
+    state: State<'_, K8sServiceState>,
+) -> Result<Vec<PortForwardSession>, String> {
+    let svc = state.lock().await;
+    Ok(svc.list_port_forwards())
+}
+
+// ── Watches / log streams ─────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn k8s_watch_resource(
+    state: State<'_, K8sServiceState>,
+    id: String,
+    resource: WatchResource,
+    namespace: Option<String>,
+    opts: Option<ListOptions>,
+) -> Result<WatchStreamInfo, String> {
+    let mut svc = state.lock().await;
+    svc.watch_resource(
+        &id,
+        resource,
+        namespace.as_deref(),
+        &opts.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_follow_pod_logs(
+    state: State<'_, K8sServiceState>,
+    id: String,
+    namespace: String,
+    name: String,
+    log_opts: Option<PodLogOptions>,
+) -> Result<WatchStreamInfo, String> {
+    let mut svc = state.lock().await;
+    svc.follow_pod_logs(&id, &namespace, &name, &log_opts.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_stop_stream(
+    state: State<'_, K8sServiceState>,
+    stream_id: String,
+) -> Result<(), String> {
+    let mut svc = state.lock().await;
+    svc.stop_stream(&stream_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn k8s_list_streams(
+    state: State<'_, K8sServiceState>,
+) -> Result<Vec<WatchStreamInfo>, String> {
+    let mut svc = state.lock().await;
+    Ok(svc.list_streams())
+}
+
 // ── Deployments ───────────────────────────────────────────────────────────────
 
 #[tauri::command]
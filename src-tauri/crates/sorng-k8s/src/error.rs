@@ -209,5 +209,24 @@ impl From<url::ParseError> for K8sError {
     }
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for K8sError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        use tokio_tungstenite::tungstenite::Error as WsError;
+        match e {
+            WsError::Http(resp) => {
+                let code = resp.status().as_u16();
+                let body = resp
+                    .body()
+                    .as_deref()
+                    .map(|b| String::from_utf8_lossy(b).into_owned())
+                    .unwrap_or_default();
+                crate::client::K8sClient::status_error(code, body)
+            }
+            WsError::Io(io) => Self::connection(format!("WebSocket: {}", io)),
+            other => Self::session(format!("WebSocket: {}", other)),
+        }
+    }
+}
+
 /// Convenience result alias.
 pub type K8sResult<T> = Result<T, K8sError>;
@@ -0,0 +1,434 @@
+// ── sorng-k8s/src/exec.rs ───────────────────────────────────────────────────
+//! Interactive `exec` and `attach` sessions over the WebSocket channel
+//! protocol, streamed to the frontend through events.
+//!
+//! Output is emitted as `k8s-exec-output` (`{ session_id, stream, data }`).
+//! When the remote process ends or the socket drops, `k8s-exec-closed`
+//! carries the exit code or the error.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use futures::{SinkExt, StreamExt};
+use log::{debug, info};
+use serde::Serialize;
+use sorng_core::events::DynEventEmitter;
+use sorng_core::utf8::StreamingUtf8Decoder;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::client::K8sClient;
+use crate::error::{K8sError, K8sResult};
+use crate::types::*;
+use crate::ws::{self, ChannelSocket};
+
+const OUTPUT_EVENT: &str = "k8s-exec-output";
+const CLOSED_EVENT: &str = "k8s-exec-closed";
+
+#[derive(Serialize)]
+struct ExecOutput<'a> {
+    session_id: &'a str,
+    stream: &'static str,
+    data: String,
+}
+
+#[derive(Serialize)]
+struct ExecClosed<'a> {
+    session_id: &'a str,
+    exit_code: Option<i32>,
+    error: Option<String>,
+}
+
+enum ExecInput {
+    Data(Vec<u8>),
+    Resize { cols: u16, rows: u16 },
+    CloseStdin,
+}
+
+struct ExecEntry {
+    connection_id: String,
+    info: ExecSession,
+    /// Dropping the sender ends the session.
+    input: mpsc::UnboundedSender<ExecInput>,
+}
+
+type Sessions = Arc<StdMutex<HashMap<String, ExecEntry>>>;
+
+/// Live exec / attach sessions.  Finished sessions stay listed (with their
+/// exit code) until closed.
+#[derive(Default)]
+pub struct ExecManager {
+    sessions: Sessions,
+}
+
+impl ExecManager {
+    /// Run a command in a container.
+    pub async fn exec(
+        &self,
+        client: &K8sClient,
+        connection_id: &str,
+        namespace: &str,
+        pod: &str,
+        opts: &PodExecOptions,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<ExecSession> {
+        if opts.command.is_empty() {
+            return Err(K8sError::validation("exec needs a command"));
+        }
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        for arg in &opts.command {
+            query.append_pair("command", arg);
+        }
+        Self::stream_params(
+            &mut query,
+            opts.container.as_deref(),
+            [opts.stdin, opts.stdout, opts.stderr, opts.tty],
+        );
+        let url = format!(
+            "{}/{}/exec?{}",
+            client.namespaced_url(namespace, "pods"),
+            pod,
+            query.finish()
+        );
+        info!("Exec in pod '{}/{}': {:?}", namespace, pod, opts.command);
+        let info = Self::new_session(namespace, pod, opts.container.clone(), opts.command.clone());
+        self.open(client, &url, connection_id, info, emitter).await
+    }
+
+    /// Attach to the main process of a running container.
+    pub async fn attach(
+        &self,
+        client: &K8sClient,
+        connection_id: &str,
+        namespace: &str,
+        pod: &str,
+        opts: &PodAttachOptions,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<ExecSession> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        Self::stream_params(
+            &mut query,
+            opts.container.as_deref(),
+            [opts.stdin, opts.stdout, opts.stderr, opts.tty],
+        );
+        let url = format!(
+            "{}/{}/attach?{}",
+            client.namespaced_url(namespace, "pods"),
+            pod,
+            query.finish()
+        );
+        info!("Attaching to pod '{}/{}'", namespace, pod);
+        let info = Self::new_session(namespace, pod, opts.container.clone(), Vec::new());
+        self.open(client, &url, connection_id, info, emitter).await
+    }
+
+    /// Write terminal input to the session's stdin.
+    pub fn send_input(&self, session_id: &str, data: &[u8]) -> K8sResult<()> {
+        self.send(session_id, ExecInput::Data(data.to_vec()))
+    }
+
+    /// Resize the session's TTY.
+    pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> K8sResult<()> {
+        self.send(session_id, ExecInput::Resize { cols, rows })
+    }
+
+    /// Signal end of input (needs `v5.channel.k8s.io` on the server).
+    pub fn close_stdin(&self, session_id: &str) -> K8sResult<()> {
+        self.send(session_id, ExecInput::CloseStdin)
+    }
+
+    /// Close a session and forget it.
+    pub fn close(&self, session_id: &str) -> K8sResult<()> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .map(|_| ())
+            .ok_or_else(|| K8sError::session(format!("No exec session '{}'", session_id)))
+    }
+
+    /// Close every session opened through a connection.
+    pub fn close_connection(&self, connection_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.connection_id != connection_id);
+    }
+
+    pub fn list(&self) -> Vec<ExecSession> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    fn stream_params(
+        query: &mut url::form_urlencoded::Serializer<'_, String>,
+        container: Option<&str>,
+        [stdin, stdout, stderr, tty]: [bool; 4],
+    ) {
+        if let Some(container) = container {
+            query.append_pair("container", container);
+        }
+        for (name, enabled) in [
+            ("stdin", stdin),
+            ("stdout", stdout),
+            ("stderr", stderr),
+            ("tty", tty),
+        ] {
+            if enabled {
+                query.append_pair(name, "true");
+            }
+        }
+    }
+
+    fn new_session(
+        namespace: &str,
+        pod: &str,
+        container: Option<String>,
+        command: Vec<String>,
+    ) -> ExecSession {
+        ExecSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            pod_name: pod.to_string(),
+            namespace: namespace.to_string(),
+            container,
+            command,
+            status: ExecSessionStatus::Running,
+            exit_code: None,
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+        }
+    }
+
+    async fn open(
+        &self,
+        client: &K8sClient,
+        url: &str,
+        connection_id: &str,
+        info: ExecSession,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<ExecSession> {
+        let socket = ws::connect(client, url, &[ws::V5_PROTOCOL, ws::V4_PROTOCOL]).await?;
+        debug!("Exec session {} speaks '{}'", info.id, socket.protocol);
+        let (input, commands) = mpsc::unbounded_channel();
+        self.sessions.lock().unwrap().insert(
+            info.id.clone(),
+            ExecEntry {
+                connection_id: connection_id.to_string(),
+                info: info.clone(),
+                input,
+            },
+        );
+        tokio::spawn(run_session(
+            info.id.clone(),
+            socket,
+            commands,
+            emitter,
+            self.sessions.clone(),
+        ));
+        Ok(info)
+    }
+
+    fn send(&self, session_id: &str, input: ExecInput) -> K8sResult<()> {
+        let sessions = self.sessions.lock().unwrap();
+        let entry = sessions
+            .get(session_id)
+            .ok_or_else(|| K8sError::session(format!("No exec session '{}'", session_id)))?;
+        entry
+            .input
+            .send(input)
+            .map_err(|_| K8sError::session(format!("Exec session '{}' has ended", session_id)))
+    }
+}
+
+/// Pump one session until the process ends, the socket drops or the
+/// session is closed.
+async fn run_session(
+    session_id: String,
+    socket: ChannelSocket,
+    mut commands: mpsc::UnboundedReceiver<ExecInput>,
+    emitter: DynEventEmitter,
+    sessions: Sessions,
+) {
+    let supports_close = socket.protocol == ws::V5_PROTOCOL;
+    let (mut sink, mut source) = socket.stream.split();
+    let mut stdout = StreamingUtf8Decoder::new();
+    let mut stderr = StreamingUtf8Decoder::new();
+    let mut status = Vec::new();
+
+    let failure = loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let frame = match command {
+                    Some(ExecInput::Data(data)) => ws::channel_frame(ws::STDIN_CHANNEL, &data),
+                    Some(ExecInput::Resize { cols, rows }) => ws::resize_frame(cols, rows),
+                    Some(ExecInput::CloseStdin) if supports_close => {
+                        ws::close_frame(ws::STDIN_CHANNEL)
+                    }
+                    Some(ExecInput::CloseStdin) => continue,
+                    None => {
+                        let _ = sink.send(Message::Close(None)).await;
+                        return;
+                    }
+                };
+                if let Err(e) = sink.send(frame).await {
+                    break Some(e.to_string());
+                }
+            }
+            message = source.next() => match message {
+                Some(Ok(Message::Binary(frame))) => match frame.split_first() {
+                    Some((&ws::STDOUT_CHANNEL, data)) => {
+                        emit_output(&emitter, &session_id, "stdout", stdout.push(data));
+                    }
+                    Some((&ws::STDERR_CHANNEL, data)) => {
+                        emit_output(&emitter, &session_id, "stderr", stderr.push(data));
+                    }
+                    Some((&ws::ERROR_CHANNEL, data)) => status.extend_from_slice(data),
+                    _ => {}
+                },
+                Some(Ok(Message::Close(_))) | None => break None,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break Some(e.to_string()),
+            }
+        }
+    };
+
+    emit_output(&emitter, &session_id, "stdout", stdout.finish());
+    emit_output(&emitter, &session_id, "stderr", stderr.finish());
+    let (exit_code, error) = match failure {
+        Some(error) => (None, Some(error)),
+        None if status.is_empty() => (None, None),
+        None => match ws::exit_status(&status) {
+            Ok(code) => (Some(code), None),
+            Err(message) => (None, Some(message)),
+        },
+    };
+    debug!(
+        "Exec session {} ended (exit {:?}, error {:?})",
+        session_id, exit_code, error
+    );
+    if let Some(entry) = sessions.lock().unwrap().get_mut(&session_id) {
+        entry.info.status = if error.is_some() {
+            ExecSessionStatus::Error
+        } else {
+            ExecSessionStatus::Completed
+        };
+        entry.info.exit_code = exit_code;
+        entry.info.ended_at = Some(chrono::Utc::now());
+    }
+    let closed = ExecClosed {
+        session_id: &session_id,
+        exit_code,
+        error,
+    };
+    let _ = emitter.emit_event(
+        CLOSED_EVENT,
+        serde_json::to_value(&closed).unwrap_or_default(),
+    );
+}
+
+fn emit_output(emitter: &DynEventEmitter, session_id: &str, stream: &'static str, data: String) {
+    if data.is_empty() {
+        return;
+    }
+    let output = ExecOutput {
+        session_id,
+        stream,
+        data,
+    };
+    let _ = emitter.emit_event(
+        OUTPUT_EVENT,
+        serde_json::to_value(&output).unwrap_or_default(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, RecordingEmitter};
+
+    #[tokio::test]
+    async fn test_exec_session_over_fake_api_server() {
+        let (seen_tx, mut seen) = mpsc::unbounded_channel();
+        let addr = testing::ws_server(ws::V5_PROTOCOL, move |request, mut socket| {
+            let seen_tx = seen_tx.clone();
+            async move {
+                seen_tx.send(request).unwrap();
+                socket
+                    .send(ws::channel_frame(ws::STDOUT_CHANNEL, b"$ "))
+                    .await
+                    .unwrap();
+                let mut received = Vec::new();
+                while received.len() < 3 {
+                    match socket.next().await {
+                        Some(Ok(Message::Binary(frame))) => received.push(frame.to_vec()),
+                        Some(Ok(_)) => {}
+                        _ => panic!("client went away"),
+                    }
+                }
+                assert_eq!(received[0], b"\x00exit 3\n");
+                assert_eq!(received[1][0], ws::RESIZE_CHANNEL);
+                assert_eq!(received[2], b"\xff\x00");
+                socket
+                    .send(ws::channel_frame(ws::STDERR_CHANNEL, b"bye\n"))
+                    .await
+                    .unwrap();
+                let status = br#"{"status":"Failure","reason":"NonZeroExitCode",
+                    "details":{"causes":[{"reason":"ExitCode","message":"3"}]}}"#;
+                socket
+                    .send(ws::channel_frame(ws::ERROR_CHANNEL, status))
+                    .await
+                    .unwrap();
+                let _ = socket.close(None).await;
+            }
+        })
+        .await;
+
+        let client = testing::test_client(addr).await;
+        let emitter = Arc::new(RecordingEmitter::default());
+        let manager = ExecManager::default();
+        let opts = PodExecOptions {
+            container: Some("app".to_string()),
+            command: vec!["sh".to_string(), "-c".to_string(), "echo a b".to_string()],
+            stdin: true,
+            stdout: true,
+            stderr: true,
+            tty: true,
+        };
+        let session = manager
+            .exec(&client, "conn", "default", "web-0", &opts, emitter.clone())
+            .await
+            .unwrap();
+
+        let request = seen.recv().await.unwrap();
+        assert_eq!(
+            request.path,
+            "/api/v1/namespaces/default/pods/web-0/exec?command=sh&command=-c\
+             &command=echo+a+b&container=app&stdin=true&stdout=true&stderr=true&tty=true"
+        );
+        assert_eq!(request.header("authorization"), Some("Bearer test-token"));
+
+        manager.send_input(&session.id, b"exit 3\n").unwrap();
+        manager.resize(&session.id, 100, 30).unwrap();
+        manager.close_stdin(&session.id).unwrap();
+
+        let closed = emitter.wait_for(CLOSED_EVENT, 1).await;
+        assert_eq!(closed[0]["session_id"], session.id.as_str());
+        assert_eq!(closed[0]["exit_code"], 3);
+        let output = emitter.payloads(OUTPUT_EVENT);
+        assert_eq!(output[0]["data"], "$ ");
+        assert_eq!(output[0]["stream"], "stdout");
+        assert_eq!(output[1]["data"], "bye\n");
+        assert_eq!(output[1]["stream"], "stderr");
+
+        let listed = manager.list();
+        assert!(matches!(listed[0].status, ExecSessionStatus::Completed));
+        assert_eq!(listed[0].exit_code, Some(3));
+        assert!(manager.send_input(&session.id, b"x").is_err());
+        manager.close(&session.id).unwrap();
+        assert!(manager.list().is_empty());
+    }
+}
@@ -11,6 +11,10 @@
 //! - **kubeconfig** — Kubeconfig parsing, context switching, credential management
 //! - **client** — HTTP client for the Kubernetes API with auth, TLS, token refresh
 //! - **pods** — Pod lifecycle, logs, exec, port-forward, ephemeral containers
+//! - **ws** — WebSocket channel protocol for the streaming pod subresources
+//! - **exec** — Interactive exec / attach sessions bridged to terminals
+//! - **portforward** — Pod port-forwards served on local listeners
+//! - **watch** — resourceVersion watches and follow-mode log streams
 //! - **deployments** — Deployment CRUD, scaling, rollouts, rollback
 //! - **services** — Service CRUD, type management, endpoint resolution
 //! - **configmaps** — ConfigMap CRUD with data/binaryData support
@@ -31,6 +35,7 @@ pub mod configmaps;
 pub mod deployments;
 pub mod error;
 pub mod events;
+pub mod exec;
 pub mod helm;
 pub mod ingress;
 pub mod jobs;
@@ -39,8 +44,13 @@ pub mod metrics;
 pub mod namespaces;
 pub mod nodes;
 pub mod pods;
+pub mod portforward;
 pub mod rbac;
 pub mod secrets;
 pub mod service;
 pub mod services;
+#[cfg(test)]
+mod testing;
 pub mod types;
+pub mod watch;
+pub mod ws;
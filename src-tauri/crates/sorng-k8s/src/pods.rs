@@ -67,6 +67,18 @@ impl PodManager {
         name: &str,
         opts: &PodLogOptions,
     ) -> K8sResult<String> {
+        let url = Self::log_url(client, namespace, name, opts);
+        debug!("Fetching logs for pod '{}/{}'", namespace, name);
+        client.get_text(&url).await
+    }
+
+    /// Build the `log` subresource URL for the given options.
+    pub(crate) fn log_url(
+        client: &K8sClient,
+        namespace: &str,
+        name: &str,
+        opts: &PodLogOptions,
+    ) -> String {
         let mut params = Vec::new();
         if let Some(ref container) = opts.container {
             params.push(format!("container={}", container));
@@ -95,14 +107,12 @@ impl PodManager {
         } else {
             format!("?{}", params.join("&"))
         };
-        let url = format!(
+        format!(
             "{}/{}/log{}",
             client.namespaced_url(namespace, "pods"),
             name,
             query
-        );
-        debug!("Fetching logs for pod '{}/{}'", namespace, name);
-        client.get_text(&url).await
+        )
     }
 
     /// Evict a pod (for drain operations).
@@ -0,0 +1,325 @@
+// ── sorng-k8s/src/portforward.rs ────────────────────────────────────────────
+//! Pod port-forwarding exposed as local TCP listeners, like SSH local
+//! tunnels.
+//!
+//! Every accepted connection opens its own `portforward` WebSocket
+//! (`v4.channel.k8s.io`) for one remote port.  Channel 0 carries data and
+//! channel 1 errors; the server starts each channel with the port number
+//! as a little-endian `u16`, which is stripped.  Relay failures are
+//! reported as `k8s-port-forward-error` (`{ forward_id, message }`).
+
+use std::collections::HashMap;
+
+use futures::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use sorng_core::events::DynEventEmitter;
+use sorng_core::net::ACCEPT_ERROR_BACKOFF;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::client::K8sClient;
+use crate::error::{K8sError, K8sResult};
+use crate::types::*;
+use crate::ws;
+
+const ERROR_EVENT: &str = "k8s-port-forward-error";
+const DATA_CHANNEL: u8 = 0;
+const ERROR_CHANNEL: u8 = 1;
+
+struct ForwardEntry {
+    connection_id: String,
+    info: PortForwardSession,
+    listeners: Vec<JoinHandle<()>>,
+}
+
+impl Drop for ForwardEntry {
+    fn drop(&mut self) {
+        // Aborting a listener also drops the relays it spawned.
+        for listener in &self.listeners {
+            listener.abort();
+        }
+    }
+}
+
+/// Active port-forwards.
+#[derive(Default)]
+pub struct PortForwardManager {
+    forwards: HashMap<String, ForwardEntry>,
+}
+
+impl PortForwardManager {
+    /// Bind a local listener per mapping and start forwarding.  A mapping
+    /// with `local_port` 0 gets an ephemeral port, reported in the result.
+    pub async fn start(
+        &mut self,
+        client: &K8sClient,
+        connection_id: &str,
+        request: &PortForwardRequest,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<PortForwardSession> {
+        if request.ports.is_empty() {
+            return Err(K8sError::validation("port-forward needs at least one port"));
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        let url = format!(
+            "{}/{}/portforward",
+            client.namespaced_url(&request.namespace, "pods"),
+            request.pod_name
+        );
+
+        let mut bound = Vec::with_capacity(request.ports.len());
+        for mapping in &request.ports {
+            if mapping
+                .protocol
+                .as_deref()
+                .is_some_and(|p| !p.eq_ignore_ascii_case("tcp"))
+            {
+                return Err(K8sError::validation("port-forward only supports TCP"));
+            }
+            let address = mapping
+                .local_address
+                .clone()
+                .unwrap_or_else(|| "127.0.0.1".to_string());
+            let listener = TcpListener::bind((address.as_str(), mapping.local_port))
+                .await
+                .map_err(|e| {
+                    K8sError::session(format!(
+                        "Cannot listen on {}:{}: {}",
+                        address, mapping.local_port, e
+                    ))
+                })?;
+            let local_port = listener.local_addr()?.port();
+            let mapping = PortForwardMapping {
+                local_port,
+                local_address: Some(address),
+                ..mapping.clone()
+            };
+            bound.push((listener, mapping));
+        }
+
+        let mut mappings = Vec::with_capacity(bound.len());
+        let mut listeners = Vec::with_capacity(bound.len());
+        for (listener, mapping) in bound {
+            info!(
+                "Forwarding {}:{} -> pod '{}/{}' port {}",
+                mapping.local_address.as_deref().unwrap_or_default(),
+                mapping.local_port,
+                request.namespace,
+                request.pod_name,
+                mapping.remote_port
+            );
+            listeners.push(tokio::spawn(accept_loop(
+                listener,
+                client.clone(),
+                format!("{}?ports={}", url, mapping.remote_port),
+                id.clone(),
+                emitter.clone(),
+            )));
+            mappings.push(mapping);
+        }
+
+        let session = PortForwardSession {
+            id: id.clone(),
+            pod_name: request.pod_name.clone(),
+            namespace: request.namespace.clone(),
+            mappings,
+            status: PortForwardStatus::Active,
+            started_at: chrono::Utc::now(),
+            error: None,
+        };
+        self.forwards.insert(
+            id,
+            ForwardEntry {
+                connection_id: connection_id.to_string(),
+                info: session.clone(),
+                listeners,
+            },
+        );
+        Ok(session)
+    }
+
+    /// Stop a forward, closing its listeners and open connections.
+    pub fn stop(&mut self, forward_id: &str) -> K8sResult<()> {
+        self.forwards
+            .remove(forward_id)
+            .map(|_| ())
+            .ok_or_else(|| K8sError::session(format!("No port-forward '{}'", forward_id)))
+    }
+
+    /// Stop every forward opened through a connection.
+    pub fn stop_connection(&mut self, connection_id: &str) {
+        self.forwards
+            .retain(|_, entry| entry.connection_id != connection_id);
+    }
+
+    pub fn list(&self) -> Vec<PortForwardSession> {
+        self.forwards
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    client: K8sClient,
+    url: String,
+    forward_id: String,
+    emitter: DynEventEmitter,
+) {
+    let mut relays = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((socket, peer)) => {
+                    debug!("Port-forward {} accepted {}", forward_id, peer);
+                    let (client, url) = (client.clone(), url.clone());
+                    let (forward_id, emitter) = (forward_id.clone(), emitter.clone());
+                    relays.spawn(async move {
+                        if let Err(message) = relay(&client, &url, socket).await {
+                            warn!("Port-forward {}: {}", forward_id, message);
+                            let _ = emitter.emit_event(
+                                ERROR_EVENT,
+                                serde_json::json!({ "forward_id": forward_id, "message": message }),
+                            );
+                        }
+                    });
+                }
+                Err(e) => {
+                    warn!("Port-forward {} accept failed: {}", forward_id, e);
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                }
+            },
+            Some(_) = relays.join_next(), if !relays.is_empty() => {}
+        }
+    }
+}
+
+/// Copy one local connection to and from the pod port.
+async fn relay(client: &K8sClient, url: &str, mut socket: TcpStream) -> Result<(), String> {
+    let ws = ws::connect(client, url, &[ws::V4_PROTOCOL])
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut sink, mut source) = ws.stream.split();
+    let (mut reader, mut writer) = socket.split();
+    let mut buf = vec![0u8; 16 * 1024];
+    // Port-number bytes still to skip on the data and error channels.
+    let mut prefix = [2usize; 2];
+    let mut error = Vec::new();
+
+    loop {
+        tokio::select! {
+            read = reader.read(&mut buf) => match read {
+                Ok(0) => break,
+                Ok(n) => sink
+                    .send(ws::channel_frame(DATA_CHANNEL, &buf[..n]))
+                    .await
+                    .map_err(|e| e.to_string())?,
+                Err(e) => return Err(e.to_string()),
+            },
+            message = source.next() => match message {
+                Some(Ok(Message::Binary(frame))) => {
+                    let Some((&channel, data)) = frame.split_first() else {
+                        continue;
+                    };
+                    if channel != DATA_CHANNEL && channel != ERROR_CHANNEL {
+                        continue;
+                    }
+                    let skip = prefix[channel as usize].min(data.len());
+                    prefix[channel as usize] -= skip;
+                    let data = &data[skip..];
+                    if channel == DATA_CHANNEL {
+                        writer.write_all(data).await.map_err(|e| e.to_string())?;
+                    } else {
+                        error.extend_from_slice(data);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.to_string()),
+            }
+        }
+    }
+    let _ = sink.send(Message::Close(None)).await;
+    if error.is_empty() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&error).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_forward_relays_through_fake_api_server() {
+        let addr = testing::ws_server(ws::V4_PROTOCOL, |request, mut socket| async move {
+            assert_eq!(
+                request.path,
+                "/api/v1/namespaces/apps/pods/db-0/portforward?ports=5432"
+            );
+            let port = 5432u16.to_le_bytes();
+            for channel in [DATA_CHANNEL, ERROR_CHANNEL] {
+                socket
+                    .send(ws::channel_frame(channel, &port))
+                    .await
+                    .unwrap();
+            }
+            // Echo data back, upper-cased.
+            while let Some(Ok(message)) = socket.next().await {
+                if let Message::Binary(frame) = message {
+                    if frame[0] == DATA_CHANNEL {
+                        let reply = frame[1..].to_ascii_uppercase();
+                        socket
+                            .send(ws::channel_frame(DATA_CHANNEL, &reply))
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
+        })
+        .await;
+
+        let client = testing::test_client(addr).await;
+        let mut manager = PortForwardManager::default();
+        let request = PortForwardRequest {
+            pod_name: "db-0".to_string(),
+            namespace: "apps".to_string(),
+            ports: vec![PortForwardMapping {
+                local_port: 0,
+                remote_port: 5432,
+                local_address: None,
+                protocol: None,
+            }],
+        };
+        let session = manager
+            .start(
+                &client,
+                "conn",
+                &request,
+                Arc::new(sorng_core::events::NoopEventEmitter),
+            )
+            .await
+            .unwrap();
+        let local_port = session.mappings[0].local_port;
+        assert_ne!(local_port, 0);
+
+        for _ in 0..2 {
+            let mut conn = TcpStream::connect(("127.0.0.1", local_port)).await.unwrap();
+            conn.write_all(b"ping").await.unwrap();
+            let mut reply = [0u8; 4];
+            conn.read_exact(&mut reply).await.unwrap();
+            assert_eq!(&reply, b"PING");
+        }
+
+        assert_eq!(manager.list().len(), 1);
+        manager.stop(&session.id).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(TcpStream::connect(("127.0.0.1", local_port)).await.is_err());
+    }
+}
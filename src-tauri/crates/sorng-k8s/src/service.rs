@@ -2,6 +2,7 @@
 //! Aggregate K8s façade – single entry point that holds the connection
 //! and delegates to the domain managers.
 
+use sorng_core::events::{DynEventEmitter, NoopEventEmitter};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -14,6 +15,7 @@ use crate::types::*;
 use crate::configmaps::ConfigMapManager;
 use crate::deployments::DeploymentManager;
 use crate::events::EventManager;
+use crate::exec::ExecManager;
 use crate::helm::HelmManager;
 use crate::ingress::IngressManager;
 use crate::jobs::JobManager;
@@ -21,9 +23,11 @@ use crate::metrics::MetricsManager;
 use crate::namespaces::NamespaceManager;
 use crate::nodes::NodeManager;
 use crate::pods::PodManager;
+use crate::portforward::PortForwardManager;
 use crate::rbac::RbacManager;
 use crate::secrets::SecretManager;
 use crate::services::ServiceManager;
+use crate::watch::WatchManager;
 
 /// Shared Tauri state handle.
 pub type K8sServiceState = Arc<Mutex<K8sService>>;
@@ -34,6 +38,14 @@ pub struct K8sService {
     connections: HashMap<String, K8sClient>,
     /// Helm manager (stateless CLI wrapper, shared across connections).
     _helm: HelmManager,
+    /// Interactive exec / attach sessions.
+    exec: ExecManager,
+    /// Port-forwards served on local listeners.
+    port_forwards: PortForwardManager,
+    /// Watches and followed log streams.
+    watches: WatchManager,
+    /// Receives streamed output (exec, watches, logs).
+    emitter: DynEventEmitter,
 }
 
 impl K8sService {
@@ -41,9 +53,19 @@ impl K8sService {
         Self {
             connections: HashMap::new(),
             _helm: HelmManager,
+            exec: ExecManager::default(),
+            port_forwards: PortForwardManager::default(),
+            watches: WatchManager::default(),
+            emitter: Arc::new(NoopEventEmitter),
         }
     }
 
+    /// Set the event emitter used by exec sessions, port-forwards and
+    /// streams started afterwards.
+    pub fn set_event_emitter(&mut self, emitter: DynEventEmitter) {
+        self.emitter = emitter;
+    }
+
     // ── Connection lifecycle ──────────────────────────────────────
 
     /// Connect to a cluster.  Returns the connection id.
@@ -138,12 +160,15 @@ impl K8sService {
         self.connect(id, config).await
     }
 
-    /// Disconnect a cluster.
+    /// Disconnect a cluster, ending its sessions, forwards and streams.
     pub fn disconnect(&mut self, id: &str) -> K8sResult<()> {
         self.connections
             .remove(id)
-            .map(|_| ())
-            .ok_or_else(|| K8sError::session(format!("No connection with id '{}'", id)))
+            .ok_or_else(|| K8sError::session(format!("No connection with id '{}'", id)))?;
+        self.exec.close_connection(id);
+        self.port_forwards.stop_connection(id);
+        self.watches.stop_connection(id);
+        Ok(())
     }
 
     /// List active connection ids.
@@ -360,6 +385,118 @@ impl K8sService {
         PodManager::update_annotations(self.client(id)?, ns, name, annotations).await
     }
 
+    // ── Exec / Attach ─────────────────────────────────────────────
+
+    pub async fn exec_pod(
+        &self,
+        id: &str,
+        ns: &str,
+        name: &str,
+        opts: &PodExecOptions,
+    ) -> K8sResult<ExecSession> {
+        self.exec
+            .exec(self.client(id)?, id, ns, name, opts, self.emitter.clone())
+            .await
+    }
+
+    pub async fn attach_pod(
+        &self,
+        id: &str,
+        ns: &str,
+        name: &str,
+        opts: &PodAttachOptions,
+    ) -> K8sResult<ExecSession> {
+        self.exec
+            .attach(self.client(id)?, id, ns, name, opts, self.emitter.clone())
+            .await
+    }
+
+    pub fn exec_send_input(&self, session_id: &str, data: &[u8]) -> K8sResult<()> {
+        self.exec.send_input(session_id, data)
+    }
+
+    pub fn exec_resize(&self, session_id: &str, cols: u16, rows: u16) -> K8sResult<()> {
+        self.exec.resize(session_id, cols, rows)
+    }
+
+    pub fn exec_close_stdin(&self, session_id: &str) -> K8sResult<()> {
+        self.exec.close_stdin(session_id)
+    }
+
+    pub fn exec_close(&self, session_id: &str) -> K8sResult<()> {
+        self.exec.close(session_id)
+    }
+
+    pub fn list_exec_sessions(&self) -> Vec<ExecSession> {
+        self.exec.list()
+    }
+
+    // ── Port-forward ──────────────────────────────────────────────
+
+    pub async fn start_port_forward(
+        &mut self,
+        id: &str,
+        request: &PortForwardRequest,
+    ) -> K8sResult<PortForwardSession> {
+        let client = self
+            .connections
+            .get(id)
+            .ok_or_else(|| K8sError::session(format!("No connection with id '{}'", id)))?;
+        self.port_forwards
+            .start(client, id, request, self.emitter.clone())
+            .await
+    }
+
+    pub fn stop_port_forward(&mut self, forward_id: &str) -> K8sResult<()> {
+        self.port_forwards.stop(forward_id)
+    }
+
+    pub fn list_port_forwards(&self) -> Vec<PortForwardSession> {
+        self.port_forwards.list()
+    }
+
+    // ── Watches / log streams ─────────────────────────────────────
+
+    pub async fn watch_resource(
+        &mut self,
+        id: &str,
+        resource: WatchResource,
+        ns: Option<&str>,
+        opts: &ListOptions,
+    ) -> K8sResult<WatchStreamInfo> {
+        let client = self
+            .connections
+            .get(id)
+            .ok_or_else(|| K8sError::session(format!("No connection with id '{}'", id)))?;
+        self.watches
+            .watch(client, id, resource, ns, opts, self.emitter.clone())
+            .await
+    }
+
+    pub async fn follow_pod_logs(
+        &mut self,
+        id: &str,
+        ns: &str,
+        name: &str,
+        log_opts: &PodLogOptions,
+    ) -> K8sResult<WatchStreamInfo> {
+        let client = self
+            .connections
+            .get(id)
+            .ok_or_else(|| K8sError::session(format!("No connection with id '{}'", id)))?;
+        self.watches
+            .follow_logs(client, id, ns, name, log_opts, self.emitter.clone())
+            .await
+    }
+
+    pub fn stop_stream(&mut self, stream_id: &str) -> K8sResult<()> {
+        self.watches.stop(stream_id)
+    }
+
+    pub fn list_streams(&mut self) -> Vec<WatchStreamInfo> {
+        self.watches.list()
+    }
+
     // ── Deployments ───────────────────────────────────────────────
 
     pub async fn list_deployments(
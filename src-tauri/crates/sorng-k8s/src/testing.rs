@@ -0,0 +1,84 @@
+// ── sorng-k8s/src/testing.rs ────────────────────────────────────────────────
+//! Fake API server for the streaming tests; the plain-HTTP fakes and the
+//! event recorder come from `sorng_core::testing`.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, SEC_WEBSOCKET_PROTOCOL};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::client::K8sClient;
+use crate::types::*;
+
+pub(crate) use sorng_core::testing::{http_server, read_request, FakeRequest, RecordingEmitter};
+
+pub(crate) const TEST_TOKEN: &str = "test-token";
+
+/// A client for the fake server, authenticating with [`TEST_TOKEN`].
+pub(crate) async fn test_client(addr: SocketAddr) -> K8sClient {
+    let now = chrono::Utc::now();
+    let config = K8sConnectionConfig {
+        id: "test".to_string(),
+        name: "test".to_string(),
+        kubeconfig_path: None,
+        kubeconfig_inline: None,
+        context_name: None,
+        api_server_url: Some(format!("http://{}", addr)),
+        auth_method: K8sAuthMethod::Token(TEST_TOKEN.to_string()),
+        namespace: None,
+        tls_config: None,
+        proxy_url: None,
+        request_timeout_secs: Some(5),
+        watch_timeout_secs: None,
+        labels: Default::default(),
+        annotations: Default::default(),
+        created_at: now,
+        updated_at: now,
+    };
+    K8sClient::from_config(&config).await.unwrap()
+}
+
+/// Accept WebSocket upgrades, answering with `protocol`, and hand every
+/// socket to `handler`.
+pub(crate) async fn ws_server<F, Fut>(protocol: &'static str, handler: F) -> SocketAddr
+where
+    F: Fn(FakeRequest, WebSocketStream<TcpStream>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut seen = FakeRequest::default();
+                // The handshake callback's error type is tungstenite's own.
+                #[allow(clippy::result_large_err)]
+                let callback = |req: &Request, mut resp: Response| {
+                    seen.method = req.method().to_string();
+                    seen.path = req.uri().to_string();
+                    seen.headers = req
+                        .headers()
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.to_string(), value.to_str().ok()?.to_string()))
+                        })
+                        .collect();
+                    resp.headers_mut()
+                        .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(protocol));
+                    Ok(resp)
+                };
+                let ws = tokio_tungstenite::accept_hdr_async(socket, callback)
+                    .await
+                    .unwrap();
+                handler(seen, ws).await;
+            });
+        }
+    });
+    addr
+}
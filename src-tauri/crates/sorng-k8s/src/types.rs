@@ -838,6 +838,15 @@ pub struct PodExecOptions {
     pub tty: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PodAttachOptions {
+    pub container: Option<String>,
+    pub stdin: bool,
+    pub stdout: bool,
+    pub stderr: bool,
+    pub tty: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortForwardRequest {
     pub pod_name: String,
@@ -2191,6 +2200,33 @@ pub enum WatchEventType {
     Error,
 }
 
+/// Resource kinds that can be followed with a `watch` stream.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum WatchResource {
+    Pods,
+    Events,
+    Deployments,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WatchStreamKind {
+    Resource(WatchResource),
+    PodLogs {
+        pod_name: String,
+        container: Option<String>,
+    },
+}
+
+/// A running watch or followed log stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchStreamInfo {
+    pub id: String,
+    pub connection_id: String,
+    pub kind: WatchStreamKind,
+    pub namespace: Option<String>,
+    pub started_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct K8sStatus {
     pub api_version: String,
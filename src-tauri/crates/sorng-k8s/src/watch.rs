@@ -0,0 +1,580 @@
+// ── sorng-k8s/src/watch.rs ──────────────────────────────────────────────────
+//! Long-lived HTTP streams: `resourceVersion`-based watches for pods,
+//! events and deployments, and follow-mode pod logs.
+//!
+//! Watch events are emitted as `k8s-watch-event`
+//! (`{ stream_id, event_type, object }`).  A dropped watch resumes from the
+//! last seen `resourceVersion` (bookmarks included); when that version has
+//! expired (410 Gone) the watch restarts without one, which replays the
+//! current objects as `Added`.  Followed logs arrive as `k8s-log-lines`
+//! (`{ stream_id, lines }`).  `k8s-stream-closed` (`{ stream_id, error }`)
+//! reports a log stream ending or a watch failing for good; stopping a
+//! stream emits nothing.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use log::{debug, info, warn};
+use serde::Serialize;
+use sorng_core::events::DynEventEmitter;
+use tokio::task::JoinHandle;
+
+use crate::client::K8sClient;
+use crate::error::{K8sError, K8sErrorKind, K8sResult};
+use crate::pods::PodManager;
+use crate::types::*;
+
+const WATCH_EVENT: &str = "k8s-watch-event";
+const LOG_EVENT: &str = "k8s-log-lines";
+const CLOSED_EVENT: &str = "k8s-stream-closed";
+
+const RETRY_MIN: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct StreamWatchEvent<'a> {
+    stream_id: &'a str,
+    #[serde(flatten)]
+    event: WatchEvent<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct StreamLogLines<'a> {
+    stream_id: &'a str,
+    lines: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct StreamClosed<'a> {
+    stream_id: &'a str,
+    error: Option<String>,
+}
+
+struct StreamEntry {
+    info: WatchStreamInfo,
+    task: JoinHandle<()>,
+}
+
+impl Drop for StreamEntry {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Running watches and followed log streams.
+#[derive(Default)]
+pub struct WatchManager {
+    streams: HashMap<String, StreamEntry>,
+}
+
+impl WatchManager {
+    /// Watch a resource kind in one namespace, or in all when `namespace`
+    /// is `None`.  Selectors, `resourceVersion` and `timeoutSeconds` are
+    /// taken from `opts`.
+    pub async fn watch(
+        &mut self,
+        client: &K8sClient,
+        connection_id: &str,
+        resource: WatchResource,
+        namespace: Option<&str>,
+        opts: &ListOptions,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<WatchStreamInfo> {
+        let cursor = WatchCursor {
+            base_url: resource_url(client, resource, namespace),
+            opts: opts.clone(),
+        };
+        // The first request runs here so a bad namespace or missing
+        // permission is reported to the caller.
+        let first = client.get_stream(&cursor.url()).await?;
+        let info = self.register_info(
+            connection_id,
+            WatchStreamKind::Resource(resource),
+            namespace,
+        );
+        info!("Watching {:?} ({})", resource, info.id);
+        let task = tokio::spawn(run_watch(
+            client.clone(),
+            cursor,
+            first,
+            info.id.clone(),
+            emitter,
+        ));
+        self.insert(info.clone(), task);
+        Ok(info)
+    }
+
+    /// Stream a container's log as it is written.
+    pub async fn follow_logs(
+        &mut self,
+        client: &K8sClient,
+        connection_id: &str,
+        namespace: &str,
+        pod: &str,
+        opts: &PodLogOptions,
+        emitter: DynEventEmitter,
+    ) -> K8sResult<WatchStreamInfo> {
+        let opts = PodLogOptions {
+            follow: true,
+            ..opts.clone()
+        };
+        let url = PodManager::log_url(client, namespace, pod, &opts);
+        let response = client.get_stream(&url).await?;
+        let info = self.register_info(
+            connection_id,
+            WatchStreamKind::PodLogs {
+                pod_name: pod.to_string(),
+                container: opts.container.clone(),
+            },
+            Some(namespace),
+        );
+        info!(
+            "Following logs of pod '{}/{}' ({})",
+            namespace, pod, info.id
+        );
+        let task = tokio::spawn(run_logs(response, info.id.clone(), emitter));
+        self.insert(info.clone(), task);
+        Ok(info)
+    }
+
+    pub fn stop(&mut self, stream_id: &str) -> K8sResult<()> {
+        self.streams
+            .remove(stream_id)
+            .map(|_| ())
+            .ok_or_else(|| K8sError::watch(format!("No stream '{}'", stream_id)))
+    }
+
+    /// Stop every stream opened through a connection.
+    pub fn stop_connection(&mut self, connection_id: &str) {
+        self.streams
+            .retain(|_, entry| entry.info.connection_id != connection_id);
+    }
+
+    /// Streams that are still running.
+    pub fn list(&mut self) -> Vec<WatchStreamInfo> {
+        self.prune();
+        self.streams
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    fn register_info(
+        &mut self,
+        connection_id: &str,
+        kind: WatchStreamKind,
+        namespace: Option<&str>,
+    ) -> WatchStreamInfo {
+        self.prune();
+        WatchStreamInfo {
+            id: uuid::Uuid::new_v4().to_string(),
+            connection_id: connection_id.to_string(),
+            kind,
+            namespace: namespace.map(String::from),
+            started_at: chrono::Utc::now(),
+        }
+    }
+
+    fn insert(&mut self, info: WatchStreamInfo, task: JoinHandle<()>) {
+        self.streams
+            .insert(info.id.clone(), StreamEntry { info, task });
+    }
+
+    fn prune(&mut self) {
+        self.streams.retain(|_, entry| !entry.task.is_finished());
+    }
+}
+
+fn resource_url(client: &K8sClient, resource: WatchResource, namespace: Option<&str>) -> String {
+    match (resource, namespace) {
+        (WatchResource::Pods, Some(ns)) => client.namespaced_url(ns, "pods"),
+        (WatchResource::Pods, None) => format!("{}/api/v1/pods", client.base_url),
+        (WatchResource::Events, Some(ns)) => client.namespaced_url(ns, "events"),
+        (WatchResource::Events, None) => format!("{}/api/v1/events", client.base_url),
+        (WatchResource::Deployments, Some(ns)) => client.apps_v1_url(ns, "deployments"),
+        (WatchResource::Deployments, None) => {
+            format!("{}/apis/apps/v1/deployments", client.base_url)
+        }
+    }
+}
+
+/// Where a watch resumes from.
+struct WatchCursor {
+    base_url: String,
+    opts: ListOptions,
+}
+
+impl WatchCursor {
+    fn url(&self) -> String {
+        let opts = ListOptions {
+            watch: true,
+            allow_watch_bookmarks: true,
+            limit: None,
+            continue_token: None,
+            ..self.opts.clone()
+        };
+        format!("{}{}", self.base_url, K8sClient::list_query(&opts))
+    }
+}
+
+enum WatchEnd {
+    /// The server ended the response; `received` tells whether any event
+    /// came through first.
+    Closed { received: bool },
+    /// The resource version is too old (410 Gone).
+    Expired,
+}
+
+async fn run_watch(
+    client: K8sClient,
+    mut cursor: WatchCursor,
+    first: reqwest::Response,
+    stream_id: String,
+    emitter: DynEventEmitter,
+) {
+    let mut response = Some(first);
+    let mut backoff = RETRY_MIN;
+    let error = loop {
+        let result = match response.take() {
+            Some(resp) => read_events(resp, &mut cursor, &stream_id, &emitter).await,
+            None => match client.get_stream(&cursor.url()).await {
+                Ok(resp) => read_events(resp, &mut cursor, &stream_id, &emitter).await,
+                Err(e) if e.kind == K8sErrorKind::ApiError(410) => Ok(WatchEnd::Expired),
+                Err(e) => Err(e),
+            },
+        };
+        match result {
+            Ok(WatchEnd::Expired) => {
+                info!(
+                    "Watch {} resourceVersion expired; restarting from current state",
+                    stream_id
+                );
+                cursor.opts.resource_version = None;
+                continue;
+            }
+            Ok(WatchEnd::Closed { received: true }) => {
+                backoff = RETRY_MIN;
+                continue;
+            }
+            Ok(WatchEnd::Closed { received: false }) => {}
+            Err(e) if is_fatal(&e) => break e.to_string(),
+            Err(e) => warn!("Watch {} dropped: {}", stream_id, e),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RETRY_MAX);
+    };
+    warn!("Watch {} stopped: {}", stream_id, error);
+    emit_closed(&emitter, &stream_id, Some(error));
+}
+
+fn is_fatal(error: &K8sError) -> bool {
+    matches!(
+        error.kind,
+        K8sErrorKind::AuthError | K8sErrorKind::Forbidden | K8sErrorKind::NotFound
+    )
+}
+
+/// Forward the events of one watch response, tracking the resource version.
+async fn read_events(
+    response: reqwest::Response,
+    cursor: &mut WatchCursor,
+    stream_id: &str,
+    emitter: &DynEventEmitter,
+) -> K8sResult<WatchEnd> {
+    let mut lines = LineReader::new(Box::pin(response.bytes_stream()));
+    let mut received = false;
+    while let Some(line) = lines.next_line().await? {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let event: serde_json::Value = match serde_json::from_slice(&line) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Watch {}: skipping malformed event: {}", stream_id, e);
+                continue;
+            }
+        };
+        let object = event.get("object").cloned().unwrap_or_default();
+        let event_type = match event.get("type").and_then(|t| t.as_str()) {
+            Some("ADDED") => WatchEventType::Added,
+            Some("MODIFIED") => WatchEventType::Modified,
+            Some("DELETED") => WatchEventType::Deleted,
+            Some("BOOKMARK") => WatchEventType::Bookmark,
+            Some("ERROR") => {
+                if object.get("code").and_then(|c| c.as_u64()) == Some(410) {
+                    return Ok(WatchEnd::Expired);
+                }
+                let message = object
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("watch error");
+                return Err(K8sError::watch(message));
+            }
+            other => {
+                debug!("Watch {}: ignoring event type {:?}", stream_id, other);
+                continue;
+            }
+        };
+        if let Some(rv) = object
+            .pointer("/metadata/resourceVersion")
+            .and_then(|v| v.as_str())
+        {
+            cursor.opts.resource_version = Some(rv.to_string());
+        }
+        if matches!(event_type, WatchEventType::Bookmark) {
+            continue;
+        }
+        received = true;
+        let payload = StreamWatchEvent {
+            stream_id,
+            event: WatchEvent { event_type, object },
+        };
+        let _ = emitter.emit_event(
+            WATCH_EVENT,
+            serde_json::to_value(&payload).unwrap_or_default(),
+        );
+    }
+    Ok(WatchEnd::Closed { received })
+}
+
+async fn run_logs(response: reqwest::Response, stream_id: String, emitter: DynEventEmitter) {
+    let mut lines = LineReader::new(Box::pin(response.bytes_stream()));
+    let error = loop {
+        match lines.next_line().await {
+            Ok(Some(first)) => {
+                // Send whatever else is already buffered in the same event.
+                let mut batch = vec![String::from_utf8_lossy(&first).into_owned()];
+                while let Some(line) = lines.take_line() {
+                    batch.push(String::from_utf8_lossy(&line).into_owned());
+                }
+                let payload = StreamLogLines {
+                    stream_id: &stream_id,
+                    lines: batch,
+                };
+                let _ = emitter.emit_event(
+                    LOG_EVENT,
+                    serde_json::to_value(&payload).unwrap_or_default(),
+                );
+            }
+            Ok(None) => break None,
+            Err(e) => break Some(e.to_string()),
+        }
+    };
+    debug!("Log stream {} ended ({:?})", stream_id, error);
+    emit_closed(&emitter, &stream_id, error);
+}
+
+fn emit_closed(emitter: &DynEventEmitter, stream_id: &str, error: Option<String>) {
+    let payload = StreamClosed { stream_id, error };
+    let _ = emitter.emit_event(
+        CLOSED_EVENT,
+        serde_json::to_value(&payload).unwrap_or_default(),
+    );
+}
+
+/// Splits a streamed response body into lines.
+struct LineReader<S> {
+    body: S,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl<S, B> LineReader<S>
+where
+    S: Stream<Item = reqwest::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    fn new(body: S) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// The next line, or the unterminated tail once the body has ended.
+    async fn next_line(&mut self) -> K8sResult<Option<Vec<u8>>> {
+        loop {
+            if let Some(line) = self.take_line() {
+                return Ok(Some(line));
+            }
+            if self.done {
+                return Ok((!self.buffer.is_empty()).then(|| std::mem::take(&mut self.buffer)));
+            }
+            match self.body.next().await {
+                Some(chunk) => self.buffer.extend_from_slice(chunk?.as_ref()),
+                None => self.done = true,
+            }
+        }
+    }
+
+    /// A complete line that is already buffered.
+    fn take_line(&mut self) -> Option<Vec<u8>> {
+        let end = self.buffer.iter().position(|&b| b == b'\n')?;
+        let mut line: Vec<u8> = self.buffer.drain(..=end).collect();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, RecordingEmitter};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    fn pod_event(kind: &str, name: &str, rv: &str) -> Vec<u8> {
+        let event = serde_json::json!({
+            "type": kind,
+            "object": { "kind": "Pod", "metadata": { "name": name, "resourceVersion": rv } },
+        });
+        format!("{}\n", event).into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_watch_resumes_and_relists_after_expiry() {
+        let uris = Arc::new(StdMutex::new(Vec::new()));
+        let calls = AtomicUsize::new(0);
+        let seen = uris.clone();
+        let addr = testing::http_server(move |request| {
+            seen.lock().unwrap().push(request.path);
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                // Initial state, then a bookmark; the event line is split
+                // across two chunks.
+                0 => {
+                    let added = pod_event("ADDED", "web-0", "5");
+                    let (head, tail) = added.split_at(20);
+                    (
+                        200,
+                        vec![head.to_vec(), tail.to_vec(), pod_event("BOOKMARK", "", "7")],
+                    )
+                }
+                1 => {
+                    let gone = serde_json::json!({
+                        "type": "ERROR",
+                        "object": { "kind": "Status", "code": 410, "message": "too old" },
+                    });
+                    (200, vec![format!("{}\n", gone).into_bytes()])
+                }
+                2 => (200, vec![pod_event("MODIFIED", "web-0", "12")]),
+                _ => (200, Vec::new()),
+            }
+        })
+        .await;
+
+        let client = testing::test_client(addr).await;
+        let emitter = Arc::new(RecordingEmitter::default());
+        let mut manager = WatchManager::default();
+        let opts = ListOptions {
+            label_selector: Some("app=web".to_string()),
+            ..Default::default()
+        };
+        let info = manager
+            .watch(
+                &client,
+                "conn",
+                WatchResource::Pods,
+                Some("default"),
+                &opts,
+                emitter.clone(),
+            )
+            .await
+            .unwrap();
+
+        let events = emitter.wait_for(WATCH_EVENT, 2).await;
+        assert_eq!(events[0]["stream_id"], info.id.as_str());
+        assert_eq!(events[0]["event_type"], "Added");
+        assert_eq!(events[0]["object"]["metadata"]["name"], "web-0");
+        assert_eq!(events[1]["event_type"], "Modified");
+
+        let uris = uris.lock().unwrap().clone();
+        let base = "/api/v1/namespaces/default/pods?labelSelector=app=web";
+        assert_eq!(
+            uris[0],
+            format!("{}&watch=true&allowWatchBookmarks=true", base)
+        );
+        assert_eq!(
+            uris[1],
+            format!(
+                "{}&resourceVersion=7&watch=true&allowWatchBookmarks=true",
+                base
+            )
+        );
+        assert_eq!(uris[2], uris[0]);
+
+        assert_eq!(manager.list().len(), 1);
+        manager.stop(&info.id).unwrap();
+        assert!(manager.list().is_empty());
+        assert!(emitter.payloads(CLOSED_EVENT).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_forbidden_up_front() {
+        let addr = testing::http_server(|_| (403, vec![b"forbidden".to_vec()])).await;
+        let client = testing::test_client(addr).await;
+        let error = WatchManager::default()
+            .watch(
+                &client,
+                "conn",
+                WatchResource::Deployments,
+                None,
+                &ListOptions::default(),
+                Arc::new(RecordingEmitter::default()),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, K8sErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs_streams_lines() {
+        let uri = Arc::new(StdMutex::new(String::new()));
+        let seen = uri.clone();
+        let addr = testing::http_server(move |request| {
+            *seen.lock().unwrap() = request.path;
+            (
+                200,
+                vec![
+                    b"starting\nlisten".to_vec(),
+                    b"ing on :80\r\n".to_vec(),
+                    b"bye".to_vec(),
+                ],
+            )
+        })
+        .await;
+
+        let client = testing::test_client(addr).await;
+        let emitter = Arc::new(RecordingEmitter::default());
+        let mut manager = WatchManager::default();
+        let opts = PodLogOptions {
+            container: Some("nginx".to_string()),
+            tail_lines: Some(10),
+            ..Default::default()
+        };
+        let info = manager
+            .follow_logs(&client, "conn", "web", "nginx-1", &opts, emitter.clone())
+            .await
+            .unwrap();
+
+        let closed = emitter.wait_for(CLOSED_EVENT, 1).await;
+        assert_eq!(closed[0]["stream_id"], info.id.as_str());
+        assert!(closed[0]["error"].is_null());
+        let lines: Vec<String> = emitter
+            .payloads(LOG_EVENT)
+            .iter()
+            .flat_map(|p| p["lines"].as_array().unwrap().clone())
+            .map(|l| l.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(lines, ["starting", "listening on :80", "bye"]);
+        assert_eq!(
+            *uri.lock().unwrap(),
+            "/api/v1/namespaces/web/pods/nginx-1/log?container=nginx&follow=true&tailLines=10"
+        );
+        assert!(manager.list().is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+// ── sorng-k8s/src/ws.rs ─────────────────────────────────────────────────────
+//! WebSocket transport for the streaming pod subresources (`exec`,
+//! `attach`, `portforward`).
+//!
+//! The API server multiplexes several streams over one socket with the
+//! `channel.k8s.io` protocols: every binary frame starts with a channel
+//! byte (0 stdin, 1 stdout, 2 stderr, 3 error, 4 resize).  The error
+//! channel carries a `Status` object once the process ends.
+//! `v5.channel.k8s.io` adds channel 255, whose payload names a channel the
+//! client has finished writing to.
+//!
+//! A configured `proxy_url` is honoured with an HTTP `CONNECT` tunnel; other
+//! proxy schemes are rejected rather than silently bypassed.
+
+use std::sync::Arc;
+
+use percent_encoding::percent_decode_str;
+use sorng_core::tls::ClientTls;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{
+    HeaderValue, AUTHORIZATION, SEC_WEBSOCKET_PROTOCOL,
+};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+
+use crate::client::K8sClient;
+use crate::error::{K8sError, K8sResult};
+use crate::types::K8sTlsConfig;
+
+pub const STDIN_CHANNEL: u8 = 0;
+pub const STDOUT_CHANNEL: u8 = 1;
+pub const STDERR_CHANNEL: u8 = 2;
+pub const ERROR_CHANNEL: u8 = 3;
+pub const RESIZE_CHANNEL: u8 = 4;
+pub const CLOSE_CHANNEL: u8 = 255;
+
+pub const V5_PROTOCOL: &str = "v5.channel.k8s.io";
+pub const V4_PROTOCOL: &str = "v4.channel.k8s.io";
+
+/// Largest proxy response head accepted before giving up on the proxy.
+const MAX_PROXY_HEAD_BYTES: usize = 8 * 1024;
+
+pub(crate) type ChannelStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// An open subresource socket and the protocol the server picked.
+pub(crate) struct ChannelSocket {
+    pub stream: ChannelStream,
+    pub protocol: String,
+}
+
+/// Open a WebSocket to an API server URL, offering `protocols` in order of
+/// preference.
+pub(crate) async fn connect(
+    client: &K8sClient,
+    url: &str,
+    protocols: &[&str],
+) -> K8sResult<ChannelSocket> {
+    let mut ws_url = url::Url::parse(url)?;
+    let secure = match ws_url.scheme() {
+        "https" => true,
+        "http" => false,
+        other => {
+            return Err(K8sError::connection(format!(
+                "Unsupported API server scheme '{}'",
+                other
+            )))
+        }
+    };
+    ws_url
+        .set_scheme(if secure { "wss" } else { "ws" })
+        .map_err(|_| K8sError::connection("Could not build WebSocket URL"))?;
+    let authority = match (ws_url.host_str(), ws_url.port_or_known_default()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        _ => return Err(K8sError::connection("API server URL has no host")),
+    };
+
+    let mut request = ws_url.as_str().into_client_request()?;
+    let auth = client.auth_headers().await?;
+    if let Some(value) = auth.get(reqwest::header::AUTHORIZATION) {
+        let value = HeaderValue::from_bytes(value.as_bytes())
+            .map_err(|e| K8sError::auth(format!("Invalid auth header: {}", e)))?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+    let offered = HeaderValue::from_str(&protocols.join(", "))
+        .map_err(|e| K8sError::validation(format!("Invalid subprotocol: {}", e)))?;
+    request
+        .headers_mut()
+        .insert(SEC_WEBSOCKET_PROTOCOL, offered);
+
+    let connector = if secure {
+        Some(Connector::Rustls(Arc::new(tls_config(
+            client.tls.as_ref(),
+        )?)))
+    } else {
+        None
+    };
+    let socket = dial(client.proxy_url.as_deref(), &authority).await?;
+    let (stream, response) =
+        tokio_tungstenite::client_async_tls_with_config(request, socket, None, connector).await?;
+    let protocol = response
+        .headers()
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    Ok(ChannelSocket { stream, protocol })
+}
+
+/// Open a TCP connection to `authority` (`host:port`), through the HTTP
+/// proxy when one is configured.
+async fn dial(proxy_url: Option<&str>, authority: &str) -> K8sResult<TcpStream> {
+    let Some(proxy_url) = proxy_url else {
+        return TcpStream::connect(authority)
+            .await
+            .map_err(|e| K8sError::connection(format!("Connect to {} failed: {}", authority, e)));
+    };
+    let proxy = url::Url::parse(proxy_url)
+        .map_err(|e| K8sError::connection(format!("Invalid proxy URL: {}", e)))?;
+    if proxy.scheme() != "http" {
+        return Err(K8sError::connection(format!(
+            "Proxy scheme '{}' is not supported for exec, attach and port-forward; use an http:// proxy",
+            proxy.scheme()
+        )));
+    }
+    let proxy_authority = match (proxy.host_str(), proxy.port_or_known_default()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        _ => return Err(K8sError::connection("Proxy URL has no host")),
+    };
+    let mut socket = TcpStream::connect(&proxy_authority).await.map_err(|e| {
+        K8sError::connection(format!(
+            "Connect to proxy {} failed: {}",
+            proxy_authority, e
+        ))
+    })?;
+
+    let mut head = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if !proxy.username().is_empty() {
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let credentials = format!(
+            "{}:{}",
+            decode(proxy.username()),
+            decode(proxy.password().unwrap_or_default())
+        );
+        head.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, credentials)
+        ));
+    }
+    head.push_str("\r\n");
+    socket.write_all(head.as_bytes()).await?;
+
+    // Read the response head a byte at a time so nothing the tunnel carries
+    // afterwards is consumed.
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_HEAD_BYTES {
+            return Err(K8sError::connection("Proxy response head too large"));
+        }
+        if socket.read(&mut byte).await? == 0 {
+            return Err(K8sError::connection("Proxy closed the connection"));
+        }
+        response.push(byte[0]);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split(' ').nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(K8sError::connection(format!(
+            "Proxy refused tunnel to {}: {}",
+            authority, status_line
+        )));
+    }
+    Ok(socket)
+}
+
+/// A binary frame for `channel`.
+pub(crate) fn channel_frame(channel: u8, data: &[u8]) -> Message {
+    let mut frame = Vec::with_capacity(data.len() + 1);
+    frame.push(channel);
+    frame.extend_from_slice(data);
+    Message::Binary(frame.into())
+}
+
+/// A terminal resize message.
+pub(crate) fn resize_frame(cols: u16, rows: u16) -> Message {
+    let size = serde_json::json!({ "Width": cols, "Height": rows });
+    channel_frame(RESIZE_CHANNEL, size.to_string().as_bytes())
+}
+
+/// Tell a v5 server that the client is done writing to `channel`.
+pub(crate) fn close_frame(channel: u8) -> Message {
+    channel_frame(CLOSE_CHANNEL, &[channel])
+}
+
+/// Exit code carried by the error channel's `Status` object.  A failure
+/// other than a non-zero exit comes back as the status message.
+pub(crate) fn exit_status(payload: &[u8]) -> Result<i32, String> {
+    let status: serde_json::Value = serde_json::from_slice(payload)
+        .map_err(|_| String::from_utf8_lossy(payload).into_owned())?;
+    if status.get("status").and_then(|s| s.as_str()) == Some("Success") {
+        return Ok(0);
+    }
+    if status.get("reason").and_then(|r| r.as_str()) == Some("NonZeroExitCode") {
+        let code = status
+            .pointer("/details/causes")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|cause| cause.get("reason").and_then(|r| r.as_str()) == Some("ExitCode"))
+            .find_map(|cause| cause.get("message")?.as_str()?.parse::<i32>().ok());
+        if let Some(code) = code {
+            return Ok(code);
+        }
+    }
+    Err(status
+        .get("message")
+        .and_then(|m| m.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| status.to_string()))
+}
+
+// ── TLS ───────────────────────────────────────────────────────────────────────
+
+/// rustls configuration matching the connection's TLS settings (the
+/// WebSocket connector cannot reuse reqwest's).
+fn tls_config(tls: Option<&K8sTlsConfig>) -> K8sResult<rustls::ClientConfig> {
+    let client_tls = match tls {
+        Some(t) => {
+            let cert = tls_source(
+                t.client_cert_data.as_deref(),
+                t.client_cert_path.as_deref(),
+                "client cert",
+            )?;
+            let key = tls_source(
+                t.client_key_data.as_deref(),
+                t.client_key_path.as_deref(),
+                "client key",
+            )?;
+            ClientTls {
+                ca: tls_source(
+                    t.ca_cert_data.as_deref(),
+                    t.ca_cert_path.as_deref(),
+                    "CA cert",
+                )?,
+                identity: cert.zip(key),
+                skip_verify: t.insecure_skip_verify,
+            }
+        }
+        None => ClientTls::default(),
+    };
+    sorng_core::tls::client_config(&client_tls).map_err(K8sError::validation)
+}
+
+/// Inline base64 data wins over a file path, as in kubeconfig.
+fn tls_source(data: Option<&str>, path: Option<&str>, what: &str) -> K8sResult<Option<Vec<u8>>> {
+    let data = data
+        .map(|data| {
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data.trim())
+                .map_err(|e| K8sError::validation(format!("Invalid base64 {} data: {}", what, e)))
+        })
+        .transpose()?;
+    sorng_core::tls::read_source(data, path, what).map_err(K8sError::validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_exit_status_from_status_object() {
+        assert_eq!(exit_status(br#"{"metadata":{},"status":"Success"}"#), Ok(0));
+        let failed = br#"{"status":"Failure","reason":"NonZeroExitCode",
+            "message":"command terminated with non-zero exit code",
+            "details":{"causes":[{"reason":"ExitCode","message":"42"}]}}"#;
+        assert_eq!(exit_status(failed), Ok(42));
+        let error =
+            br#"{"status":"Failure","reason":"InternalError","message":"no such container"}"#;
+        assert_eq!(exit_status(error), Err("no such container".to_string()));
+    }
+
+    #[test]
+    fn test_frames_carry_channel_prefix() {
+        assert_eq!(
+            channel_frame(STDIN_CHANNEL, b"ls\n").into_data().as_ref(),
+            b"\x00ls\n"
+        );
+        assert_eq!(close_frame(STDIN_CHANNEL).into_data().as_ref(), b"\xff\x00");
+        let resize = resize_frame(120, 40).into_data();
+        assert_eq!(resize[0], RESIZE_CHANNEL);
+        let size: serde_json::Value = serde_json::from_slice(&resize[1..]).unwrap();
+        assert_eq!(size, serde_json::json!({ "Width": 120, "Height": 40 }));
+    }
+
+    #[test]
+    fn test_insecure_tls_config_builds() {
+        let tls = K8sTlsConfig {
+            ca_cert_data: None,
+            ca_cert_path: None,
+            client_cert_data: None,
+            client_cert_path: None,
+            client_key_data: None,
+            client_key_path: None,
+            insecure_skip_verify: true,
+            server_name: None,
+        };
+        assert!(tls_config(Some(&tls)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_connect_tunnels_through_http_proxy() {
+        let target = testing::ws_server(V5_PROTOCOL, |_, mut socket| async move {
+            socket
+                .send(channel_frame(STDOUT_CHANNEL, b"hi"))
+                .await
+                .unwrap();
+        })
+        .await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = listener.local_addr().unwrap();
+        let tunnel = tokio::spawn(async move {
+            let (mut inbound, _) = listener.accept().await.unwrap();
+            let request = testing::read_request(&mut inbound).await.unwrap();
+            let mut outbound = TcpStream::connect(target).await.unwrap();
+            inbound
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+            request
+        });
+
+        let mut client = testing::test_client(target).await;
+        client.proxy_url = Some(format!("http://user:p%40ss@{}", proxy));
+        let url = format!("http://{}/api/v1/namespaces/default/pods/web/exec", target);
+        let mut socket = connect(&client, &url, &[V5_PROTOCOL]).await.unwrap();
+        assert_eq!(socket.protocol, V5_PROTOCOL);
+        let frame = socket.stream.next().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().as_ref(), b"\x01hi");
+        drop(socket);
+
+        let request = tunnel.await.unwrap();
+        assert_eq!(request.method, "CONNECT");
+        assert_eq!(request.path, target.to_string());
+        assert_eq!(request.header("host"), Some(target.to_string().as_str()));
+        // base64("user:p@ss")
+        assert_eq!(
+            request.header("proxy-authorization"),
+            Some("Basic dXNlcjpwQHNz")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_unsupported_proxy() {
+        let mut client = testing::test_client("127.0.0.1:1".parse().unwrap()).await;
+        client.proxy_url = Some("socks5://127.0.0.1:1080".to_string());
+        let err = match connect(&client, "http://127.0.0.1:1/api", &[V5_PROTOCOL]).await {
+            Ok(_) => panic!("connected through a SOCKS proxy"),
+            Err(e) => e,
+        };
+        assert!(err.to_string().contains("not supported"), "{}", err);
+    }
+}
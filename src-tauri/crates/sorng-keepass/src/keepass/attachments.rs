@@ -99,6 +99,7 @@ impl KeePassService {
         });
 
         db.mark_modified();
+        self.refresh_ssh_keys(db_id);
 
         Ok(attachment)
     }
@@ -198,6 +199,7 @@ impl KeePassService {
         }
 
         db.mark_modified();
+        self.refresh_ssh_keys(db_id);
         Ok(())
     }
 
@@ -232,6 +234,7 @@ impl KeePassService {
 
         att_ref.filename = new_filename;
         db.mark_modified();
+        self.refresh_ssh_keys(db_id);
         Ok(())
     }
 
@@ -862,6 +862,17 @@ pub async fn keepass_verify_attachment_integrity(
     svc.verify_attachment_integrity(&db_id)
 }
 
+// ─── SSH Keys ────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn keepass_list_ssh_keys(
+    state: tauri::State<'_, KeePassServiceState>,
+    db_id: String,
+) -> Result<Vec<KeePassSshKey>, String> {
+    let svc = state.lock().await;
+    svc.list_ssh_keys(&db_id)
+}
+
 // ─── Recent Databases ────────────────────────────────────────────────────────
 
 #[tauri::command]
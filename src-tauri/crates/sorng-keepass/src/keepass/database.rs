@@ -47,6 +47,7 @@ impl KeePassService {
         let info = instance.info.clone();
         self.register_database(instance);
         self.add_recent_database(&path_string, &info.name);
+        self.publish_ssh_keys(&info.id);
         Ok(info)
     }
 
@@ -85,6 +86,7 @@ impl KeePassService {
         let info = instance.info.clone();
         self.register_database(instance);
         self.add_recent_database(&path_string, &info.name);
+        self.publish_ssh_keys(&info.id);
         Ok(info)
     }
 
@@ -100,6 +102,7 @@ impl KeePassService {
             self.save_database(db_id, None)?;
         }
 
+        self.withdraw_ssh_keys(db_id);
         let mut db = self.unregister_database(db_id)?;
         db.clear_sensitive();
         log::info!("Closed database: {} ({})", db.info.name, db.info.file_path);
@@ -213,6 +216,7 @@ impl KeePassService {
         if requires_save {
             self.save_database(db_id, None)?;
         }
+        self.withdraw_ssh_keys(db_id);
         let db = self.get_database_mut(db_id)?;
         db.clear_sensitive();
         db.info.locked = true;
@@ -246,6 +250,7 @@ impl KeePassService {
         )?;
         *self.get_database_mut(db_id)? = refreshed;
         if !self.get_database(db_id)?.info.locked {
+            self.publish_ssh_keys(db_id);
             return Ok(());
         }
         Err("Failed to unlock KeePass database".to_string())
@@ -259,6 +259,7 @@ impl KeePassService {
             &updated_entry.title,
             "Updated entry",
         );
+        self.refresh_ssh_keys(db_id);
 
         Ok(updated_entry)
     }
@@ -311,6 +312,7 @@ impl KeePassService {
                     &title,
                     "Moved entry to recycle bin",
                 );
+                self.refresh_ssh_keys(db_id);
 
                 return Ok(());
             }
@@ -335,6 +337,7 @@ impl KeePassService {
             &title,
             "Permanently deleted entry",
         );
+        self.refresh_ssh_keys(db_id);
 
         Ok(())
     }
@@ -380,6 +383,7 @@ impl KeePassService {
             &restored.title,
             "Restored entry from recycle bin",
         );
+        self.refresh_ssh_keys(db_id);
 
         Ok(restored)
     }
@@ -436,6 +440,7 @@ impl KeePassService {
         db.mark_modified();
         db.rebuild_counts();
         db.rebuild_tree();
+        self.refresh_ssh_keys(db_id);
 
         Ok(count)
     }
@@ -516,6 +521,7 @@ impl KeePassService {
             &new_entry.title,
             &format!("Copied from entry {}", entry_uuid),
         );
+        self.refresh_ssh_keys(db_id);
 
         Ok(new_entry)
     }
@@ -622,34 +628,34 @@ impl KeePassService {
         let now = Utc::now().to_rfc3339();
 
         let db = self.get_database_mut(db_id)?;
-        if let Some(entry) = db.entries.get_mut(entry_uuid) {
-            entry.group_uuid = historical.entry.group_uuid;
-            entry.icon_id = historical.entry.icon_id;
-            entry.custom_icon_uuid = historical.entry.custom_icon_uuid;
-            entry.foreground_color = historical.entry.foreground_color;
-            entry.background_color = historical.entry.background_color;
-            entry.override_url = historical.entry.override_url;
-            entry.password_quality = historical.entry.password_quality;
-            entry.title = historical.entry.title;
-            entry.username = historical.entry.username;
-            entry.password = historical.entry.password;
-            entry.url = historical.entry.url;
-            entry.notes = historical.entry.notes;
-            entry.custom_fields = historical.entry.custom_fields;
-            entry.attachments = historical.entry.attachments;
-            entry.tags = historical.entry.tags;
-            entry.auto_type = historical.entry.auto_type;
-            entry.otp = historical.entry.otp;
-            entry.times.last_modified = now;
-
-            let restored = entry.clone();
-            db.mark_modified();
-            db.rebuild_counts();
-            db.rebuild_tree();
-            return Ok(restored);
-        }
+        let Some(entry) = db.entries.get_mut(entry_uuid) else {
+            return Err(format!("Entry not found: {}", entry_uuid));
+        };
+        entry.group_uuid = historical.entry.group_uuid;
+        entry.icon_id = historical.entry.icon_id;
+        entry.custom_icon_uuid = historical.entry.custom_icon_uuid;
+        entry.foreground_color = historical.entry.foreground_color;
+        entry.background_color = historical.entry.background_color;
+        entry.override_url = historical.entry.override_url;
+        entry.password_quality = historical.entry.password_quality;
+        entry.title = historical.entry.title;
+        entry.username = historical.entry.username;
+        entry.password = historical.entry.password;
+        entry.url = historical.entry.url;
+        entry.notes = historical.entry.notes;
+        entry.custom_fields = historical.entry.custom_fields;
+        entry.attachments = historical.entry.attachments;
+        entry.tags = historical.entry.tags;
+        entry.auto_type = historical.entry.auto_type;
+        entry.otp = historical.entry.otp;
+        entry.times.last_modified = now;
 
-        Err(format!("Entry not found: {}", entry_uuid))
+        let restored = entry.clone();
+        db.mark_modified();
+        db.rebuild_counts();
+        db.rebuild_tree();
+        self.refresh_ssh_keys(db_id);
+        Ok(restored)
     }
 
     /// Delete all history for an entry.
@@ -352,6 +352,7 @@ impl KeePassService {
                     &group_name,
                     &format!("Moved group to recycle bin ({} entries)", entry_uuids.len()),
                 );
+                self.refresh_ssh_keys(db_id);
 
                 return Ok(total_affected);
             }
@@ -393,6 +394,7 @@ impl KeePassService {
                 descendant_groups.len() - 1
             ),
         );
+        self.refresh_ssh_keys(db_id);
 
         Ok(total_affected)
     }
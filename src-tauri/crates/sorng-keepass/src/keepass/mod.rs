@@ -13,6 +13,7 @@
 //   • Database merge/synchronization
 //   • Entry history tracking and comparison
 //   • OTP (TOTP/HOTP) integration
+//   • KeeAgent-style SSH keys served through the built-in SSH agent
 //   • Tauri command bindings for the frontend
 
 pub mod attachments;
@@ -25,6 +26,7 @@ pub mod groups;
 pub mod import_export;
 pub mod search;
 pub mod service;
+pub mod ssh_keys;
 pub mod types;
 
 pub use service::KeePassService;
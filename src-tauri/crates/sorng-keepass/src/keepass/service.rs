@@ -27,6 +27,10 @@ pub struct KeePassService {
     change_log: Vec<ChangeLogEntry>,
     /// Global settings
     settings: KeePassSettings,
+    /// Receives SSH keys from unlocked databases (the built-in SSH agent)
+    pub(crate) ssh_key_sink: Option<Arc<dyn sorng_core::ssh_identities::SshKeySink>>,
+    /// Digest of the SSH keys last published per database (db_id → digest)
+    pub(crate) published_ssh_keys: HashMap<String, [u8; 32]>,
 }
 
 /// An open database instance with its in-memory state.
@@ -152,6 +156,8 @@ impl KeePassService {
             password_profiles: Self::builtin_profiles(),
             change_log: Vec::new(),
             settings: KeePassSettings::default(),
+            ssh_key_sink: None,
+            published_ssh_keys: HashMap::new(),
         };
         Arc::new(Mutex::new(service))
     }
@@ -376,6 +382,9 @@ impl KeePassService {
     /// Close all open databases and clean up.
     pub fn shutdown(&mut self) -> Vec<String> {
         let db_ids: Vec<String> = self.databases.keys().cloned().collect();
+        for db_id in &db_ids {
+            self.withdraw_ssh_keys(db_id);
+        }
         for (_, mut database) in self.databases.drain() {
             database.clear_sensitive();
         }
@@ -0,0 +1,291 @@
+// ── sorng-keepass / ssh_keys ───────────────────────────────────────────────────
+//
+// KeeAgent-compatible SSH keys. An entry carrying a `KeeAgent.settings`
+// attachment (as written by KeeAgent and KeePassXC) names another attachment
+// holding an OpenSSH private key; the entry password is its passphrase.
+// While a database is unlocked its keys are published to the registered
+// `SshKeySink` (the built-in SSH agent), republished when an edit changes
+// them, and withdrawn when the database locks or closes.
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sorng_core::ssh_identities::{SshKeyMaterial, SshKeySink};
+use std::sync::Arc;
+
+use super::service::{DatabaseInstance, KeePassService};
+use super::types::*;
+
+/// Attachment name KeeAgent stores its per-entry settings under.
+pub const KEEAGENT_SETTINGS_ATTACHMENT: &str = "KeeAgent.settings";
+
+const MAX_SETTINGS_BYTES: usize = 64 * 1024;
+const MAX_KEY_BYTES: usize = 64 * 1024;
+
+/// The subset of KeeAgent's `EntrySettings` document used here.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct KeeAgentSettings {
+    pub allow_use_of_ssh_key: bool,
+    pub add_at_database_open: bool,
+    pub use_confirm_constraint_when_adding: bool,
+    pub use_lifetime_constraint_when_adding: bool,
+    pub lifetime_constraint_duration: u64,
+    pub location: KeeAgentLocation,
+}
+
+/// Where the private key lives. Only `attachment` locations are supported;
+/// KeeAgent's `file` locations point outside the database.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct KeeAgentLocation {
+    pub selected_type: String,
+    pub attachment_name: String,
+}
+
+impl KeeAgentSettings {
+    /// Parse a `KeeAgent.settings` attachment. KeeAgent writes UTF-16 with a
+    /// BOM; KeePassXC writes UTF-8.
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() > MAX_SETTINGS_BYTES {
+            return Err("KeeAgent settings exceed the size limit".to_string());
+        }
+        let text = decode_text(bytes)?;
+        let mut xml = text.trim_start();
+        if xml.starts_with("<?xml") {
+            let end = xml
+                .find("?>")
+                .ok_or_else(|| "Malformed XML declaration".to_string())?;
+            xml = &xml[end + 2..];
+        }
+        quick_xml::de::from_str(xml).map_err(|e| format!("Invalid KeeAgent settings: {}", e))
+    }
+
+    /// Name of the attachment holding the private key, if the key is stored
+    /// in the entry itself.
+    pub fn key_attachment(&self) -> Option<&str> {
+        let name = self.location.attachment_name.trim();
+        (self
+            .location
+            .selected_type
+            .eq_ignore_ascii_case("attachment")
+            && !name.is_empty())
+        .then_some(name)
+    }
+
+    /// Lifetime constraint in seconds, if enabled.
+    pub fn lifetime_secs(&self) -> Option<u64> {
+        (self.use_lifetime_constraint_when_adding && self.lifetime_constraint_duration > 0)
+            .then_some(self.lifetime_constraint_duration)
+    }
+}
+
+fn decode_text(bytes: &[u8]) -> Result<String, String> {
+    let utf16 = |le: bool| {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| {
+                if le {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                }
+            })
+            .collect();
+        String::from_utf16(&units).map_err(|_| "Invalid UTF-16 text".to_string())
+    };
+    match bytes {
+        [0xFF, 0xFE, ..] => utf16(true),
+        [0xFE, 0xFF, ..] => utf16(false),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|_| "Invalid UTF-8 text".to_string())
+        }
+        _ => String::from_utf8(bytes.to_vec()).map_err(|_| "Invalid UTF-8 text".to_string()),
+    }
+}
+
+/// Sink owner under which a database's keys are published.
+pub fn ssh_key_owner(db_id: &str) -> String {
+    format!("keepass:{}", db_id)
+}
+
+fn attachment_bytes<'a>(
+    db: &'a DatabaseInstance,
+    entry: &KeePassEntry,
+    name: &str,
+) -> Option<&'a [u8]> {
+    let attachment = entry.attachments.iter().find(|a| a.filename == name)?;
+    db.attachment_pool
+        .get(&attachment.ref_id)
+        .map(|data| data.data.as_slice())
+}
+
+/// Digest of the published keys, so edits that leave them unchanged do not
+/// reload them into the agent (which would restart their lifetimes).
+fn material_digest(keys: &[SshKeyMaterial]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for key in keys {
+        for field in [
+            key.id.as_str(),
+            key.comment.as_str(),
+            key.private_key_openssh.as_str(),
+            key.passphrase.as_deref().unwrap_or_default(),
+        ] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hasher.update([
+            u8::from(key.passphrase.is_some()),
+            u8::from(key.confirm_before_use),
+        ]);
+        hasher.update(key.lifetime_secs.unwrap_or_default().to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Entries of an unlocked database that carry KeeAgent settings, with the
+/// parsed settings and the key attachment name.
+fn ssh_key_entries(db: &DatabaseInstance) -> Vec<(&KeePassEntry, KeeAgentSettings, String)> {
+    if db.info.locked {
+        return Vec::new();
+    }
+    let mut found: Vec<_> = db
+        .entries
+        .values()
+        .filter(|entry| !entry.is_recycled)
+        .filter_map(|entry| {
+            let raw = attachment_bytes(db, entry, KEEAGENT_SETTINGS_ATTACHMENT)?;
+            let settings = match KeeAgentSettings::parse(raw) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    log::warn!("Skipping SSH key settings of '{}': {}", entry.title, e);
+                    return None;
+                }
+            };
+            let attachment = settings.key_attachment()?.to_string();
+            Some((entry, settings, attachment))
+        })
+        .collect();
+    found.sort_by(|a, b| a.0.title.cmp(&b.0.title));
+    found
+}
+
+impl KeePassService {
+    // ─── SSH Agent Integration ───────────────────────────────────────
+
+    /// Register (or clear) the sink that receives SSH keys from unlocked
+    /// databases. Keys of databases that are already unlocked are published
+    /// immediately.
+    pub fn set_ssh_key_sink(&mut self, sink: Option<Arc<dyn SshKeySink>>) {
+        if let Some(previous) = self.ssh_key_sink.take() {
+            for db in self.list_databases() {
+                previous.withdraw(&ssh_key_owner(&db.id));
+            }
+        }
+        self.published_ssh_keys.clear();
+        self.ssh_key_sink = sink;
+        for db in self.list_databases() {
+            if !db.locked {
+                self.publish_ssh_keys(&db.id);
+            }
+        }
+    }
+
+    /// List entries carrying SSH keys, without secret material.
+    pub fn list_ssh_keys(&self, db_id: &str) -> Result<Vec<KeePassSshKey>, String> {
+        let db = self.get_database(db_id)?;
+        if db.info.locked {
+            return Err("Database is locked".to_string());
+        }
+        Ok(ssh_key_entries(db)
+            .into_iter()
+            .map(|(entry, settings, attachment)| KeePassSshKey {
+                entry_uuid: entry.uuid.clone(),
+                title: entry.title.clone(),
+                attachment_name: attachment,
+                allow_use: settings.allow_use_of_ssh_key,
+                add_at_open: settings.add_at_database_open,
+                confirm_before_use: settings.use_confirm_constraint_when_adding,
+                lifetime_secs: settings.lifetime_secs(),
+            })
+            .collect())
+    }
+
+    /// Private keys the database offers to the SSH agent on unlock: entries
+    /// that allow agent use and ask to be added at open.
+    pub fn ssh_key_material(&self, db_id: &str) -> Result<Vec<SshKeyMaterial>, String> {
+        let db = self.get_database(db_id)?;
+        if db.info.locked {
+            return Err("Database is locked".to_string());
+        }
+        let mut keys = Vec::new();
+        for (entry, settings, attachment) in ssh_key_entries(db) {
+            if !settings.allow_use_of_ssh_key || !settings.add_at_database_open {
+                continue;
+            }
+            let Some(raw) = attachment_bytes(db, entry, &attachment) else {
+                log::warn!(
+                    "SSH key attachment '{}' of '{}' is missing",
+                    attachment,
+                    entry.title
+                );
+                continue;
+            };
+            if raw.len() > MAX_KEY_BYTES {
+                log::warn!(
+                    "SSH key attachment of '{}' exceeds the size limit",
+                    entry.title
+                );
+                continue;
+            }
+            let Ok(private_key) = std::str::from_utf8(raw) else {
+                log::warn!("SSH key attachment of '{}' is not text", entry.title);
+                continue;
+            };
+            keys.push(SshKeyMaterial {
+                id: entry.uuid.clone(),
+                comment: entry.title.clone(),
+                private_key_openssh: private_key.to_string(),
+                passphrase: (!entry.password.is_empty()).then(|| entry.password.clone()),
+                confirm_before_use: settings.use_confirm_constraint_when_adding,
+                lifetime_secs: settings.lifetime_secs(),
+            });
+        }
+        Ok(keys)
+    }
+
+    /// Publish the database's SSH keys to the registered sink.
+    pub(crate) fn publish_ssh_keys(&mut self, db_id: &str) {
+        let Some(sink) = self.ssh_key_sink.clone() else {
+            return;
+        };
+        match self.ssh_key_material(db_id) {
+            Ok(keys) => {
+                self.published_ssh_keys
+                    .insert(db_id.to_string(), material_digest(&keys));
+                sink.publish(&ssh_key_owner(db_id), keys);
+            }
+            Err(e) => log::warn!("Cannot publish SSH keys of {}: {}", db_id, e),
+        }
+    }
+
+    /// Republish the database's SSH keys after an edit, if they were
+    /// published and the edit changed them.
+    pub(crate) fn refresh_ssh_keys(&mut self, db_id: &str) {
+        let Some(&published) = self.published_ssh_keys.get(db_id) else {
+            return;
+        };
+        match self.ssh_key_material(db_id) {
+            Ok(keys) if material_digest(&keys) == published => {}
+            Ok(_) => self.publish_ssh_keys(db_id),
+            Err(e) => log::warn!("Cannot refresh SSH keys of {}: {}", db_id, e),
+        }
+    }
+
+    /// Withdraw the database's SSH keys from the registered sink.
+    pub(crate) fn withdraw_ssh_keys(&mut self, db_id: &str) {
+        self.published_ssh_keys.remove(db_id);
+        if let Some(sink) = &self.ssh_key_sink {
+            sink.withdraw(&ssh_key_owner(db_id));
+        }
+    }
+}
@@ -627,6 +627,27 @@ pub struct AddAttachmentRequest {
     pub mime_type: Option<String>,
 }
 
+// ─── SSH Key Types ────────────────────────────────────────────────────────────
+
+/// An entry carrying a KeeAgent-style SSH key (no secret material).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeePassSshKey {
+    /// Entry UUID
+    pub entry_uuid: String,
+    /// Entry title
+    pub title: String,
+    /// Attachment holding the private key
+    pub attachment_name: String,
+    /// Whether the key is offered to the SSH agent at all
+    pub allow_use: bool,
+    /// Whether the key is loaded into the agent when the database unlocks
+    pub add_at_open: bool,
+    /// Agent asks for confirmation before each use
+    pub confirm_before_use: bool,
+    /// Agent drops the key after this many seconds
+    pub lifetime_secs: Option<u64>,
+}
+
 // ─── Search Types ─────────────────────────────────────────────────────────────
 
 /// Advanced search query.
@@ -0,0 +1,562 @@
+//! # MCP Tool Approval
+//!
+//! Gates `tools/call` according to the configured [`ToolApprovalPolicy`].
+//! Read-only tools run straight away. Every other tool must match the
+//! command allowlist, target only connections carrying an allowed tag, or
+//! be confirmed by the user in the app; a confirmed call runs once, when
+//! the client retries it with the same arguments. A [headless] gate has
+//! nobody to ask, so it denies such calls instead of holding them.
+//!
+//! [headless]: ApprovalGate::headless
+//!
+//! Every evaluation yields an [`ApprovalRecord`] for the audit log.
+
+use crate::types::*;
+
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Argument keys holding the command or query a tool runs.
+const COMMAND_KEYS: &[&str] = &["command", "query"];
+
+/// Argument keys naming the connections or sessions a tool targets.
+const TARGET_KEYS: &[&str] = &["connection_id", "session_id", "session_ids"];
+
+/// Shell sequences that chain or redirect commands. A command containing
+/// any of them never matches the allowlist.
+const SHELL_CONTROL: &[&str] = &[";", "&", "|", "`", "$(", ">", "<", "\n", "\r"];
+
+/// Argument keys redacted from pending approvals shown in the UI.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "token",
+    "api_key",
+    "private_key",
+];
+
+/// A held call plus the fingerprint used to match the client's retry.
+#[derive(Debug, Clone)]
+struct Held {
+    approval: PendingApproval,
+    fingerprint: String,
+}
+
+/// Pending and granted approvals, plus the tags of known connections.
+#[derive(Debug, Default)]
+pub struct ApprovalGate {
+    pending: Vec<Held>,
+    granted: Vec<Held>,
+    connection_tags: HashMap<String, Vec<String>>,
+    /// No user can confirm calls, so those needing it are denied.
+    headless: bool,
+}
+
+impl ApprovalGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A gate for a process without the app's UI (the stdio transport),
+    /// where a held call could never be confirmed.
+    pub fn headless() -> Self {
+        Self {
+            headless: true,
+            ..Self::default()
+        }
+    }
+
+    /// Replace the tag map used for connection-tag allowlisting. Keys are
+    /// connection IDs or active session IDs.
+    pub fn set_connection_tags(&mut self, tags: HashMap<String, Vec<String>>) {
+        self.connection_tags = tags;
+    }
+
+    /// Calls currently waiting for confirmation, oldest first.
+    pub fn pending(&mut self) -> Vec<PendingApproval> {
+        self.prune();
+        self.pending.iter().map(|h| h.approval.clone()).collect()
+    }
+
+    /// Decide whether a tool call may run. `tool` is `None` when the tool
+    /// does not exist or is disabled.
+    pub fn evaluate(
+        &mut self,
+        policy: &ToolApprovalPolicy,
+        tool: Option<&McpTool>,
+        tool_name: &str,
+        arguments: &Value,
+        session_id: Option<&str>,
+    ) -> ApprovalRecord {
+        self.prune();
+        let summary = summarize(arguments);
+        let record = |id: String, outcome, reason: String| ApprovalRecord {
+            id,
+            tool_name: tool_name.to_string(),
+            session_id: session_id.map(str::to_string),
+            outcome,
+            reason,
+            summary: summary.clone(),
+            timestamp: Utc::now(),
+        };
+        let new_id = || uuid::Uuid::new_v4().to_string();
+
+        let Some(tool) = tool else {
+            return record(
+                new_id(),
+                ApprovalOutcome::Denied,
+                "unknown or disabled tool".to_string(),
+            );
+        };
+
+        let read_only = tool
+            .annotations
+            .as_ref()
+            .and_then(|a| a.read_only)
+            .unwrap_or(false);
+        match policy.tool_modes.get(tool_name) {
+            Some(ApprovalMode::Deny) => {
+                return record(
+                    new_id(),
+                    ApprovalOutcome::Denied,
+                    "tool is denied by policy".to_string(),
+                );
+            }
+            Some(ApprovalMode::Allow) => {
+                return record(
+                    new_id(),
+                    ApprovalOutcome::Allowed,
+                    "tool is allowed by policy".to_string(),
+                );
+            }
+            Some(ApprovalMode::Confirm) => {}
+            None if read_only && policy.auto_allow_read_only => {
+                return record(
+                    new_id(),
+                    ApprovalOutcome::Allowed,
+                    "read-only tool".to_string(),
+                );
+            }
+            None => {}
+        }
+
+        if let Some(pattern) = allowlisted_command(policy, arguments) {
+            return record(
+                new_id(),
+                ApprovalOutcome::Allowed,
+                format!("command matches allowlist pattern `{pattern}`"),
+            );
+        }
+        if let Some(tags) = self.allowed_targets(policy, arguments) {
+            return record(
+                new_id(),
+                ApprovalOutcome::Allowed,
+                format!("targets carry allowed tags: {}", tags.join(", ")),
+            );
+        }
+
+        let fingerprint = fingerprint(tool_name, arguments);
+        if let Some(pos) = self
+            .granted
+            .iter()
+            .position(|h| h.fingerprint == fingerprint)
+        {
+            let grant = self.granted.remove(pos);
+            return record(
+                grant.approval.id,
+                ApprovalOutcome::Allowed,
+                "confirmed by user".to_string(),
+            );
+        }
+        if self.headless {
+            return record(
+                new_id(),
+                ApprovalOutcome::Denied,
+                "it needs confirmation, which is not available to this process; \
+                 allow it in the policy's `tool_modes`, `command_allowlist` or \
+                 `allowed_connection_tags`"
+                    .to_string(),
+            );
+        }
+        if let Some(held) = self.pending.iter().find(|h| h.fingerprint == fingerprint) {
+            return record(
+                held.approval.id.clone(),
+                ApprovalOutcome::Pending,
+                "awaiting user confirmation".to_string(),
+            );
+        }
+
+        let now = Utc::now();
+        let id = new_id();
+        self.pending.push(Held {
+            approval: PendingApproval {
+                id: id.clone(),
+                tool_name: tool_name.to_string(),
+                session_id: session_id.map(str::to_string),
+                summary: summary.clone(),
+                arguments: redact(arguments),
+                requested_at: now,
+                expires_at: now + ttl(policy),
+            },
+            fingerprint,
+        });
+        record(
+            id,
+            ApprovalOutcome::Pending,
+            "confirmation required".to_string(),
+        )
+    }
+
+    /// Approve or reject a pending call. An approved call runs once, the
+    /// next time the client sends it within the policy's TTL.
+    pub fn resolve(
+        &mut self,
+        policy: &ToolApprovalPolicy,
+        approval_id: &str,
+        approve: bool,
+    ) -> Result<ApprovalRecord, String> {
+        self.prune();
+        let pos = self
+            .pending
+            .iter()
+            .position(|h| h.approval.id == approval_id)
+            .ok_or_else(|| format!("Approval request not found or expired: {approval_id}"))?;
+        let mut held = self.pending.remove(pos);
+
+        let (outcome, reason) = if approve {
+            (ApprovalOutcome::Approved, "approved by user")
+        } else {
+            (ApprovalOutcome::Rejected, "rejected by user")
+        };
+        let record = ApprovalRecord {
+            id: held.approval.id.clone(),
+            tool_name: held.approval.tool_name.clone(),
+            session_id: held.approval.session_id.clone(),
+            outcome,
+            reason: reason.to_string(),
+            summary: held.approval.summary.clone(),
+            timestamp: Utc::now(),
+        };
+        if approve {
+            held.approval.expires_at = Utc::now() + ttl(policy);
+            self.granted.push(held);
+        }
+        Ok(record)
+    }
+
+    /// Tags that allow every target of the call, or `None` if the call has
+    /// no targets or any target lacks an allowed tag.
+    fn allowed_targets(
+        &self,
+        policy: &ToolApprovalPolicy,
+        arguments: &Value,
+    ) -> Option<Vec<String>> {
+        if policy.allowed_connection_tags.is_empty() {
+            return None;
+        }
+        let targets = targets(arguments);
+        if targets.is_empty() {
+            return None;
+        }
+        let mut matched = Vec::new();
+        for target in targets {
+            let tag = self.connection_tags.get(&target)?.iter().find(|tag| {
+                policy
+                    .allowed_connection_tags
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(tag))
+            })?;
+            if !matched.contains(tag) {
+                matched.push(tag.clone());
+            }
+        }
+        Some(matched)
+    }
+
+    fn prune(&mut self) {
+        let now = Utc::now();
+        self.pending.retain(|h| h.approval.expires_at > now);
+        self.granted.retain(|h| h.approval.expires_at > now);
+    }
+}
+
+fn ttl(policy: &ToolApprovalPolicy) -> Duration {
+    Duration::seconds(policy.confirmation_ttl_secs.min(i64::MAX as u64) as i64)
+}
+
+/// The allowlist pattern matching the call's command or query, if any.
+fn allowlisted_command<'a>(policy: &'a ToolApprovalPolicy, arguments: &Value) -> Option<&'a str> {
+    let (key, command) = COMMAND_KEYS
+        .iter()
+        .find_map(|key| Some((*key, arguments.get(key)?.as_str()?)))?;
+    let command = command.trim();
+    let chained = if key == "command" {
+        SHELL_CONTROL.iter().any(|c| command.contains(c))
+    } else {
+        command.trim_end_matches(';').contains(';')
+    };
+    if chained || command.is_empty() {
+        return None;
+    }
+    policy
+        .command_allowlist
+        .iter()
+        .find(|pattern| glob_match(pattern, command))
+        .map(String::as_str)
+}
+
+/// Connection and session IDs named by the call.
+fn targets(arguments: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    for key in TARGET_KEYS {
+        match arguments.get(key) {
+            Some(Value::String(id)) => out.push(id.clone()),
+            Some(Value::Array(ids)) => {
+                out.extend(ids.iter().filter_map(|v| v.as_str()).map(str::to_string))
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Short description of what the call acts on, for logs and prompts.
+fn summarize(arguments: &Value) -> Option<String> {
+    let mut parts = Vec::new();
+    for key in COMMAND_KEYS.iter().chain(TARGET_KEYS).chain(&["path"]) {
+        match arguments.get(key) {
+            Some(Value::String(v)) => parts.push(format!("{key}={v}")),
+            Some(Value::Array(vs)) => {
+                let vs: Vec<&str> = vs.iter().filter_map(|v| v.as_str()).collect();
+                parts.push(format!("{key}={}", vs.join(",")));
+            }
+            _ => {}
+        }
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+fn fingerprint(tool_name: &str, arguments: &Value) -> String {
+    format!("{tool_name}:{arguments}")
+}
+
+fn redact(arguments: &Value) -> Value {
+    match arguments {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = if SECRET_KEYS.contains(&k.as_str()) {
+                        Value::String("********".to_string())
+                    } else {
+                        redact(v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Glob match where `*` matches any run of characters and `?` exactly one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::get_tool;
+    use serde_json::json;
+
+    fn evaluate(
+        gate: &mut ApprovalGate,
+        policy: &ToolApprovalPolicy,
+        name: &str,
+        arguments: Value,
+    ) -> ApprovalRecord {
+        let tool = get_tool(name);
+        gate.evaluate(policy, tool.as_ref(), name, &arguments, Some("s1"))
+    }
+
+    #[test]
+    fn test_read_only_and_overrides() {
+        let mut gate = ApprovalGate::new();
+        let mut policy = ToolApprovalPolicy::default();
+
+        let rec = evaluate(&mut gate, &policy, "list_connections", json!({}));
+        assert_eq!(rec.outcome, ApprovalOutcome::Allowed);
+        let rec = evaluate(&mut gate, &policy, "no_such_tool", json!({}));
+        assert_eq!(rec.outcome, ApprovalOutcome::Denied);
+
+        policy
+            .tool_modes
+            .insert("list_connections".to_string(), ApprovalMode::Deny);
+        policy
+            .tool_modes
+            .insert("ssh_disconnect".to_string(), ApprovalMode::Allow);
+        let rec = evaluate(&mut gate, &policy, "list_connections", json!({}));
+        assert_eq!(rec.outcome, ApprovalOutcome::Denied);
+        let rec = evaluate(
+            &mut gate,
+            &policy,
+            "ssh_disconnect",
+            json!({"session_id": "a"}),
+        );
+        assert_eq!(rec.outcome, ApprovalOutcome::Allowed);
+    }
+
+    #[test]
+    fn test_confirmation_runs_once() {
+        let mut gate = ApprovalGate::new();
+        let policy = ToolApprovalPolicy::default();
+        let args = json!({"session_id": "ssh-1", "command": "rm -rf /tmp/x"});
+
+        let first = evaluate(&mut gate, &policy, "ssh_execute", args.clone());
+        assert_eq!(first.outcome, ApprovalOutcome::Pending);
+        let again = evaluate(&mut gate, &policy, "ssh_execute", args.clone());
+        assert_eq!(again.outcome, ApprovalOutcome::Pending);
+        assert_eq!(again.id, first.id);
+        assert_eq!(gate.pending().len(), 1);
+
+        let resolved = gate.resolve(&policy, &first.id, true).unwrap();
+        assert_eq!(resolved.outcome, ApprovalOutcome::Approved);
+        assert!(gate.pending().is_empty());
+
+        let retry = evaluate(&mut gate, &policy, "ssh_execute", args.clone());
+        assert_eq!(retry.outcome, ApprovalOutcome::Allowed);
+        assert_eq!(retry.id, first.id);
+        let replay = evaluate(&mut gate, &policy, "ssh_execute", args);
+        assert_eq!(replay.outcome, ApprovalOutcome::Pending);
+        assert_ne!(replay.id, first.id);
+
+        let rejected = gate.resolve(&policy, &replay.id, false).unwrap();
+        assert_eq!(rejected.outcome, ApprovalOutcome::Rejected);
+        assert!(gate.resolve(&policy, &replay.id, true).is_err());
+    }
+
+    #[test]
+    fn test_headless_denies_instead_of_holding() {
+        let mut gate = ApprovalGate::headless();
+        let policy = ToolApprovalPolicy {
+            command_allowlist: vec!["uptime".to_string()],
+            ..ToolApprovalPolicy::default()
+        };
+        let run = |gate: &mut ApprovalGate, cmd: &str| {
+            evaluate(
+                gate,
+                &policy,
+                "ssh_execute",
+                json!({"session_id": "a", "command": cmd}),
+            )
+        };
+        assert_eq!(run(&mut gate, "uptime").outcome, ApprovalOutcome::Allowed);
+        let rec = run(&mut gate, "reboot");
+        assert_eq!(rec.outcome, ApprovalOutcome::Denied);
+        assert!(rec.reason.contains("command_allowlist"));
+        assert!(gate.pending().is_empty());
+    }
+
+    #[test]
+    fn test_command_allowlist() {
+        let mut gate = ApprovalGate::new();
+        let policy = ToolApprovalPolicy {
+            command_allowlist: vec!["uptime".to_string(), "df -h*".to_string()],
+            ..ToolApprovalPolicy::default()
+        };
+        let run = |gate: &mut ApprovalGate, cmd: &str| {
+            evaluate(
+                gate,
+                &policy,
+                "bulk_ssh_execute",
+                json!({"session_ids": ["a", "b"], "command": cmd}),
+            )
+            .outcome
+        };
+        assert_eq!(run(&mut gate, "uptime"), ApprovalOutcome::Allowed);
+        assert_eq!(run(&mut gate, "df -h /var"), ApprovalOutcome::Allowed);
+        assert_eq!(run(&mut gate, "uptime; reboot"), ApprovalOutcome::Pending);
+        assert_eq!(run(&mut gate, "df -h $(reboot)"), ApprovalOutcome::Pending);
+        assert_eq!(run(&mut gate, "reboot"), ApprovalOutcome::Pending);
+    }
+
+    #[test]
+    fn test_connection_tags() {
+        let mut gate = ApprovalGate::new();
+        let policy = ToolApprovalPolicy {
+            allowed_connection_tags: vec!["lab".to_string()],
+            ..ToolApprovalPolicy::default()
+        };
+        gate.set_connection_tags(HashMap::from([
+            ("a".to_string(), vec!["Lab".to_string()]),
+            ("b".to_string(), vec!["prod".to_string()]),
+        ]));
+
+        let write = |gate: &mut ApprovalGate, session: &str| {
+            evaluate(
+                gate,
+                &policy,
+                "sftp_write_file",
+                json!({"session_id": session, "path": "/etc/motd", "content": "hi"}),
+            )
+        };
+        let rec = write(&mut gate, "a");
+        assert_eq!(rec.outcome, ApprovalOutcome::Allowed);
+        assert!(rec.reason.contains("Lab"));
+        assert_eq!(write(&mut gate, "b").outcome, ApprovalOutcome::Pending);
+        assert_eq!(
+            write(&mut gate, "unknown").outcome,
+            ApprovalOutcome::Pending
+        );
+    }
+
+    #[test]
+    fn test_pending_arguments_are_redacted() {
+        let mut gate = ApprovalGate::new();
+        let policy = ToolApprovalPolicy::default();
+        evaluate(
+            &mut gate,
+            &policy,
+            "ssh_connect",
+            json!({"hostname": "h", "username": "u", "password": "hunter2"}),
+        );
+        let pending = gate.pending();
+        assert_eq!(pending[0].arguments["password"], "********");
+        assert_eq!(pending[0].arguments["hostname"], "h");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("systemctl status *", "systemctl status nginx"));
+        assert!(glob_match("ls -?", "ls -l"));
+        assert!(!glob_match("ls -?", "ls -la"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("cat", "cat /etc/shadow"));
+    }
+}
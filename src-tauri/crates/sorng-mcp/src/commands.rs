@@ -154,6 +154,47 @@ pub fn mcp_reset_metrics(state: tauri::State<'_, McpServiceState>) -> Result<(),
     Ok(())
 }
 
+/// List tool calls waiting for the user's approval.
+#[tauri::command]
+pub fn mcp_list_pending_approvals(
+    state: tauri::State<'_, McpServiceState>,
+) -> Result<Vec<PendingApproval>, String> {
+    let mut service = state.lock().map_err(|e| e.to_string())?;
+    Ok(service.list_pending_approvals())
+}
+
+/// Approve or reject a pending tool call.
+#[tauri::command]
+pub fn mcp_resolve_approval(
+    state: tauri::State<'_, McpServiceState>,
+    approval_id: String,
+    approve: bool,
+) -> Result<ApprovalRecord, String> {
+    let mut service = state.lock().map_err(|e| e.to_string())?;
+    service.resolve_approval(&approval_id, approve)
+}
+
+/// Publish connection and session tags for connection-tag allowlisting.
+#[tauri::command]
+pub fn mcp_set_connection_tags(
+    state: tauri::State<'_, McpServiceState>,
+    tags: HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let mut service = state.lock().map_err(|e| e.to_string())?;
+    service.set_connection_tags(tags);
+    Ok(())
+}
+
+/// Get recent tool approval decisions.
+#[tauri::command]
+pub fn mcp_get_approval_log(
+    state: tauri::State<'_, McpServiceState>,
+    limit: Option<usize>,
+) -> Result<Vec<McpLogEntry>, String> {
+    let service = state.lock().map_err(|e| e.to_string())?;
+    Ok(service.get_approval_log(limit.unwrap_or(100)))
+}
+
 /// Proxy an HTTP request to the MCP server (for testing from frontend).
 #[tauri::command]
 pub fn mcp_handle_request(
@@ -204,6 +245,10 @@ pub fn get_command_names() -> Vec<&'static str> {
         "mcp_clear_logs",
         "mcp_reset_metrics",
         "mcp_handle_request",
+        "mcp_list_pending_approvals",
+        "mcp_resolve_approval",
+        "mcp_set_connection_tags",
+        "mcp_get_approval_log",
     ]
 }
 
@@ -219,5 +264,6 @@ mod tests {
         assert!(names.contains(&"mcp_start_server"));
         assert!(names.contains(&"mcp_stop_server"));
         assert!(names.contains(&"mcp_handle_request"));
+        assert!(names.contains(&"mcp_resolve_approval"));
     }
 }
@@ -12,7 +12,7 @@
 //! ┌──────────────────┐     JSON-RPC 2.0      ┌───────────────────┐
 //! │  AI Client        │◄──────────────────────►│  MCP Server       │
 //! │  (Claude, etc.)   │   Streamable HTTP     │  (sorng-mcp)      │
-//! └──────────────────┘   + SSE                └───────┬───────────┘
+//! └──────────────────┘   + SSE / stdio        └───────┬───────────┘
 //!                                                      │
 //!                                              ┌───────▼───────────┐
 //!                                              │  Tauri App State   │
@@ -26,17 +26,20 @@
 //! - **types** — MCP protocol types (JSON-RPC, Tool, Resource, Prompt, etc.)
 //! - **protocol** — JSON-RPC message parsing, routing, and response building
 //! - **transport** — Streamable HTTP transport with SSE support
+//! - **stdio** — Newline-delimited stdio transport (`mcp-stdio` subcommand)
 //! - **session** — MCP session lifecycle management
 //! - **server** — Main MCP server start/stop/configure
 //! - **tools** — Tool definitions (connection mgmt, SSH, SFTP, network, DB, system)
 //! - **resources** — Resource definitions (connections, sessions, settings, logs)
 //! - **prompts** — Prompt templates (troubleshoot, bulk command, audit)
 //! - **auth** — API key / bearer token authentication
+//! - **approval** — Per-tool approval policy and confirmation gate
 //! - **capabilities** — Server capability negotiation
 //! - **logging** — MCP logging notifications
 //! - **service** — Central McpService orchestrator + state
 //! - **commands** — Tauri command handlers
 
+pub mod approval;
 pub mod auth;
 pub mod capabilities;
 pub mod logging;
@@ -46,6 +49,7 @@ pub mod resources;
 pub mod server;
 pub mod service;
 pub mod session;
+pub mod stdio;
 pub mod tools;
 pub mod transport;
 pub mod types;
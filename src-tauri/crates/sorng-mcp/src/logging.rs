@@ -1,7 +1,8 @@
 //! # MCP Logging
 //!
 //! Structured logging via MCP notifications. Allows log messages to be
-//! sent to connected clients that have the logging capability.
+//! sent to connected clients that have the logging capability. Tool
+//! approval decisions are recorded here too, as the approval audit trail.
 
 use crate::protocol;
 use crate::types::*;
@@ -11,6 +12,9 @@ use std::collections::VecDeque;
 /// Maximum number of log entries to keep in the buffer.
 const MAX_LOG_BUFFER: usize = 500;
 
+/// Logger name for tool approval decisions.
+pub const APPROVAL_LOGGER: &str = "mcp.approval";
+
 /// Log buffer for recent MCP log entries.
 #[derive(Debug)]
 pub struct McpLogBuffer {
@@ -68,12 +72,55 @@ impl McpLogBuffer {
             timestamp: Utc::now(),
             data,
         };
+        self.push(entry.clone());
+        Some(entry)
+    }
+
+    /// Record a tool approval decision. Decisions form an audit trail, so
+    /// they are kept regardless of the minimum level.
+    pub fn record_approval(&mut self, record: &ApprovalRecord) -> McpLogEntry {
+        let level = match record.outcome {
+            ApprovalOutcome::Allowed | ApprovalOutcome::Approved | ApprovalOutcome::Pending => {
+                McpLogLevel::Info
+            }
+            ApprovalOutcome::Denied | ApprovalOutcome::Rejected => McpLogLevel::Warning,
+        };
+        let entry = McpLogEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            level,
+            logger: APPROVAL_LOGGER.to_string(),
+            message: format!(
+                "Tool {} {}: {}",
+                record.tool_name,
+                record.outcome.as_str(),
+                record.reason
+            ),
+            timestamp: record.timestamp,
+            data: serde_json::to_value(record).ok(),
+        };
+        self.push(entry.clone());
+        entry
+    }
 
+    /// Get recent approval decisions, oldest first.
+    pub fn get_approval_entries(&self, limit: usize) -> Vec<McpLogEntry> {
+        let mut entries: Vec<McpLogEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| e.logger == APPROVAL_LOGGER)
+            .take(limit)
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+
+    fn push(&mut self, entry: McpLogEntry) {
         if self.entries.len() >= MAX_LOG_BUFFER {
             self.entries.pop_front();
         }
-        self.entries.push_back(entry.clone());
-        Some(entry)
+        self.entries.push_back(entry);
     }
 
     /// Check if a level should be logged.
@@ -177,4 +224,29 @@ mod tests {
         assert_eq!(entries[0].message, "second");
         assert_eq!(entries[1].message, "third");
     }
+
+    #[test]
+    fn test_approval_decisions_bypass_level() {
+        let mut buf = McpLogBuffer::new(McpLogLevel::Error);
+        buf.log(McpLogLevel::Info, "mcp.tools", "dropped", None);
+        let entry = buf.record_approval(&ApprovalRecord {
+            id: "a1".to_string(),
+            tool_name: "ssh_execute".to_string(),
+            session_id: None,
+            outcome: ApprovalOutcome::Pending,
+            reason: "confirmation required".to_string(),
+            summary: Some("command=reboot".to_string()),
+            timestamp: Utc::now(),
+        });
+        assert_eq!(entry.logger, APPROVAL_LOGGER);
+        assert_eq!(
+            entry.message,
+            "Tool ssh_execute pending: confirmation required"
+        );
+        assert_eq!(buf.count(), 1);
+
+        let entries = buf.get_approval_entries(10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].data.as_ref().unwrap()["outcome"], "pending");
+    }
 }
@@ -13,7 +13,11 @@
 //! | DELETE  | `/mcp`  | Session termination                          |
 //! | GET     | `/health` | Health check endpoint                      |
 //! | OPTIONS | `*`     | CORS preflight                               |
+//!
+//! JSON-RPC routing itself lives in [`handle_messages`], which the stdio
+//! transport shares. Every `tools/call` passes the approval gate first.
 
+use crate::approval::ApprovalGate;
 use crate::auth::{AuthManager, AuthResult};
 use crate::capabilities::build_initialize_result;
 use crate::logging::McpLogBuffer;
@@ -31,6 +35,20 @@ fn rpc_json(v: impl serde::Serialize) -> Value {
     serde_json::to_value(v).unwrap_or_default()
 }
 
+/// Tool result returned instead of running a call the approval gate held
+/// or refused.
+fn approval_result(text: &str, decision: &ApprovalRecord) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": true,
+        "_approval": {
+            "id": decision.id,
+            "status": decision.outcome,
+            "reason": decision.reason,
+        }
+    })
+}
+
 /// Result of processing a single MCP request.
 pub struct RequestOutcome {
     /// The primary HTTP response to send back.
@@ -52,6 +70,7 @@ pub fn handle_request(
     config: &McpServerConfig,
     sessions: &mut SessionManager,
     auth: &mut AuthManager,
+    approvals: &mut ApprovalGate,
     log_buffer: &mut McpLogBuffer,
 ) -> RequestOutcome {
     let notifications = Vec::new();
@@ -227,13 +246,68 @@ pub fn handle_request(
 
     // Resolve session from header
     let session_id = req.headers.get("mcp-session-id").cloned();
-    let _new_session_id: Option<String> = None; // Will be set below if Initialize
+    let outcome = handle_messages(
+        &messages,
+        session_id.as_deref(),
+        config,
+        sessions,
+        approvals,
+        log_buffer,
+    );
+    let responses = outcome.responses;
+    let created_session = outcome.created_session;
+    events.extend(outcome.events);
+
+    // Build final response
+    let http_response = if responses.is_empty() {
+        McpHttpResponse::accepted()
+    } else if responses.len() == 1 {
+        McpHttpResponse::json(200, &responses[0])
+    } else {
+        McpHttpResponse::json(200, &Value::Array(responses))
+    };
 
-    // Process each message
+    // Apply CORS and session headers
+    let transport_config = TransportConfig::from(config);
+    let mut response = http_response.with_cors(&transport_config, None);
+    if let Some(ref sid) = created_session {
+        response = response.with_session_id(sid);
+    }
+
+    RequestOutcome {
+        response,
+        notifications,
+        new_session_id: created_session,
+        events,
+    }
+}
+
+/// Result of routing a batch of JSON-RPC messages.
+pub struct MessagesOutcome {
+    /// One response per request (notifications produce none).
+    pub responses: Vec<Value>,
+    /// Session created by an `initialize` request in the batch.
+    pub created_session: Option<String>,
+    /// Events to record.
+    pub events: Vec<McpEvent>,
+}
+
+/// Route parsed JSON-RPC messages. Shared by the HTTP and stdio transports;
+/// `session_id` is the session the transport associates with the batch.
+pub fn handle_messages(
+    messages: &[JsonRpcRequest],
+    session_id: Option<&str>,
+    config: &McpServerConfig,
+    sessions: &mut SessionManager,
+    approvals: &mut ApprovalGate,
+    log_buffer: &mut McpLogBuffer,
+) -> MessagesOutcome {
+    let session_id = session_id.map(str::to_string);
+    let mut events = Vec::new();
     let mut responses: Vec<Value> = Vec::new();
     let mut created_session: Option<String> = None;
 
-    for msg in &messages {
+    for msg in messages {
         if protocol::is_notification(msg) {
             // Notifications have no response
             let method = msg.method.as_str();
@@ -333,43 +407,106 @@ pub fn handle_request(
             }
 
             MethodCategory::ToolsCall => {
-                // Tool calls are dispatched to the Tauri app via events.
-                // We return a placeholder — actual execution happens upstream.
                 let tool_name = msg
                     .params
                     .as_ref()
                     .and_then(|p| p.get("name"))
                     .and_then(|n| n.as_str())
                     .unwrap_or("unknown");
+                let arguments = msg
+                    .params
+                    .as_ref()
+                    .and_then(|p| p.get("arguments"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let call_session = session_id.as_ref().or(created_session.as_ref()).cloned();
+
+                // Every call passes the approval gate before it is dispatched.
+                let tool = crate::tools::get_tool(tool_name)
+                    .filter(|t| crate::capabilities::is_tool_enabled(config, &t.name));
+                let decision = approvals.evaluate(
+                    &config.approval,
+                    tool.as_ref(),
+                    tool_name,
+                    &arguments,
+                    call_session.as_deref(),
+                );
+                log_buffer.record_approval(&decision);
 
-                events.push(McpEvent {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    event_type: McpEventType::ToolCalled,
-                    timestamp: chrono::Utc::now(),
-                    session_id: session_id.clone(),
-                    details: json!({ "tool": tool_name, "request_id": &id }),
-                });
+                match decision.outcome {
+                    ApprovalOutcome::Allowed | ApprovalOutcome::Approved => {
+                        events.push(McpEvent {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            event_type: McpEventType::ToolCalled,
+                            timestamp: chrono::Utc::now(),
+                            session_id: call_session,
+                            details: json!({ "tool": tool_name, "request_id": &id }),
+                        });
 
-                log_buffer.log(
-                    McpLogLevel::Info,
-                    "mcp.tools",
-                    &format!("Tool called: {}", tool_name),
-                    Some(json!({ "params": msg.params })),
-                );
+                        log_buffer.log(
+                            McpLogLevel::Info,
+                            "mcp.tools",
+                            &format!("Tool called: {}", tool_name),
+                            Some(json!({ "params": msg.params })),
+                        );
 
-                // The actual tool execution is handled by the service layer
-                // which has access to app state. Return a deferred placeholder.
-                rpc_json(protocol::build_response(
-                    id,
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("Tool '{}' execution is handled by the application layer. This response is a placeholder for the MCP server module.", tool_name)
-                        }],
-                        "isError": false,
-                        "_deferred": true
-                    }),
-                ))
+                        // The actual tool execution is handled by the service layer
+                        // which has access to app state. Return a deferred placeholder.
+                        rpc_json(protocol::build_response(
+                            id,
+                            json!({
+                                "content": [{
+                                    "type": "text",
+                                    "text": format!("Tool '{}' execution is handled by the application layer. This response is a placeholder for the MCP server module.", tool_name)
+                                }],
+                                "isError": false,
+                                "_deferred": true
+                            }),
+                        ))
+                    }
+                    ApprovalOutcome::Pending => {
+                        events.push(McpEvent {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            event_type: McpEventType::ApprovalRequested,
+                            timestamp: chrono::Utc::now(),
+                            session_id: call_session,
+                            details: json!({
+                                "tool": tool_name,
+                                "approval_id": decision.id,
+                                "summary": decision.summary,
+                            }),
+                        });
+                        rpc_json(protocol::build_response(
+                            id,
+                            approval_result(
+                                &format!(
+                                    "Tool '{}' needs the user's approval in SortOfRemote NG (request {}). Ask the user to approve it, then call the tool again with the same arguments.",
+                                    tool_name, decision.id
+                                ),
+                                &decision,
+                            ),
+                        ))
+                    }
+                    ApprovalOutcome::Denied | ApprovalOutcome::Rejected => {
+                        events.push(McpEvent {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            event_type: McpEventType::ToolDenied,
+                            timestamp: chrono::Utc::now(),
+                            session_id: call_session,
+                            details: json!({ "tool": tool_name, "reason": decision.reason }),
+                        });
+                        rpc_json(protocol::build_response(
+                            id,
+                            approval_result(
+                                &format!(
+                                    "Tool '{}' was blocked by the approval policy: {}.",
+                                    tool_name, decision.reason
+                                ),
+                                &decision,
+                            ),
+                        ))
+                    }
+                }
             }
 
             MethodCategory::ResourcesList => {
@@ -583,26 +720,9 @@ pub fn handle_request(
         }
     }
 
-    // Build final response
-    let http_response = if responses.is_empty() {
-        McpHttpResponse::accepted()
-    } else if responses.len() == 1 {
-        McpHttpResponse::json(200, &responses[0])
-    } else {
-        McpHttpResponse::json(200, &Value::Array(responses))
-    };
-
-    // Apply CORS and session headers
-    let transport_config = TransportConfig::from(config);
-    let mut response = http_response.with_cors(&transport_config, None);
-    if let Some(ref sid) = created_session {
-        response = response.with_session_id(sid);
-    }
-
-    RequestOutcome {
-        response,
-        notifications,
-        new_session_id: created_session,
+    MessagesOutcome {
+        responses,
+        created_session,
         events,
     }
 }
@@ -649,9 +769,17 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 204);
     }
 
@@ -666,9 +794,17 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 200);
     }
 
@@ -693,9 +829,17 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 200);
         assert!(outcome.new_session_id.is_some());
 
@@ -716,9 +860,17 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 200);
     }
 
@@ -735,9 +887,17 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 200);
         let body_str = outcome.response.body.unwrap();
         assert!(body_str.contains("tools"));
@@ -761,10 +921,18 @@ mod tests {
         };
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
         // Without auth header → denied
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 401);
     }
 
@@ -773,6 +941,7 @@ mod tests {
         let config = test_config();
         let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
         let mut auth = AuthManager::new(config.api_key.clone(), config.require_auth);
+        let mut approvals = ApprovalGate::new();
         let mut log_buf = McpLogBuffer::new(config.log_level);
 
         // Create a session first
@@ -794,11 +963,70 @@ mod tests {
             path: Some("/mcp".to_string()),
         };
 
-        let outcome = handle_request(&req, &config, &mut sessions, &mut auth, &mut log_buf);
+        let outcome = handle_request(
+            &req,
+            &config,
+            &mut sessions,
+            &mut auth,
+            &mut approvals,
+            &mut log_buf,
+        );
         assert_eq!(outcome.response.status, 202);
         assert!(sessions.get_session(&sid).is_none());
     }
 
+    #[test]
+    fn test_tool_call_waits_for_approval() {
+        let config = test_config();
+        let mut sessions = SessionManager::new(config.max_sessions, config.session_timeout_secs);
+        let mut approvals = ApprovalGate::new();
+        let mut log_buf = McpLogBuffer::new(config.log_level);
+        let call = |name: &str, args: Value| {
+            protocol::parse_message(
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": 7,
+                    "method": "tools/call",
+                    "params": { "name": name, "arguments": args }
+                })
+                .to_string(),
+            )
+            .unwrap()
+        };
+
+        let outcome = handle_messages(
+            &call("ping_host", json!({ "host": "10.0.0.1" })),
+            None,
+            &config,
+            &mut sessions,
+            &mut approvals,
+            &mut log_buf,
+        );
+        assert_eq!(outcome.responses[0]["result"]["_deferred"], true);
+        assert_eq!(outcome.events[0].event_type, McpEventType::ToolCalled);
+
+        let outcome = handle_messages(
+            &call(
+                "db_query",
+                json!({ "connection_id": "c1", "query": "DROP TABLE t" }),
+            ),
+            None,
+            &config,
+            &mut sessions,
+            &mut approvals,
+            &mut log_buf,
+        );
+        let result = &outcome.responses[0]["result"];
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["_approval"]["status"], "pending");
+        assert_eq!(
+            outcome.events[0].event_type,
+            McpEventType::ApprovalRequested
+        );
+        assert_eq!(approvals.pending().len(), 1);
+        assert_eq!(log_buf.get_approval_entries(10).len(), 2);
+    }
+
     #[test]
     fn test_listen_address() {
         let config = McpServerConfig::default();
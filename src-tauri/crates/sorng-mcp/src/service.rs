@@ -1,14 +1,17 @@
 //! # MCP Service
 //!
 //! Central orchestrator for the MCP server. Owns the config, session manager,
-//! auth manager, log buffer, metrics, and event history. Provides a unified
-//! interface for the Tauri command layer.
+//! auth manager, approval gate, log buffer, metrics, and event history.
+//! Provides a unified interface for the Tauri command layer and the stdio
+//! transport.
 //!
 //! The `McpServiceState` type alias follows the standard crate pattern:
 //! `Arc<Mutex<McpService>>` for thread-safe sharing across Tauri commands.
 
+use crate::approval::ApprovalGate;
 use crate::auth::AuthManager;
 use crate::logging::McpLogBuffer;
+use crate::protocol;
 use crate::server;
 use crate::session::SessionManager;
 use crate::transport::{HttpMethod, McpHttpRequest};
@@ -30,6 +33,8 @@ pub struct McpService {
     pub sessions: SessionManager,
     /// Authentication manager.
     pub auth: AuthManager,
+    /// Tool approval gate.
+    pub approvals: ApprovalGate,
     /// Structured log buffer.
     pub log_buffer: McpLogBuffer,
     /// Aggregate metrics.
@@ -57,6 +62,7 @@ impl McpService {
             config,
             sessions,
             auth,
+            approvals: ApprovalGate::new(),
             log_buffer: McpLogBuffer::new(McpLogLevel::Info),
             metrics: McpMetrics::default(),
             events: Vec::new(),
@@ -76,6 +82,7 @@ impl McpService {
             config,
             sessions,
             auth,
+            approvals: ApprovalGate::new(),
             log_buffer: McpLogBuffer::new(McpLogLevel::Info),
             metrics: McpMetrics::default(),
             events: Vec::new(),
@@ -275,24 +282,10 @@ impl McpService {
             &self.config,
             &mut self.sessions,
             &mut self.auth,
+            &mut self.approvals,
             &mut self.log_buffer,
         );
-
-        // Update metrics
-        self.metrics.total_requests += 1;
-        for event in &outcome.events {
-            match event.event_type {
-                McpEventType::ToolCalled => self.metrics.total_tool_calls += 1,
-                McpEventType::ResourceRead => self.metrics.total_resource_reads += 1,
-                _ => {}
-            }
-        }
-
-        // Record events
-        for event in outcome.events {
-            self.events.push(event);
-        }
-        self.trim_events();
+        self.absorb_events(outcome.events);
 
         (
             outcome.response.body.unwrap_or_default(),
@@ -301,6 +294,48 @@ impl McpService {
         )
     }
 
+    /// Handle one line from the stdio transport. `session_id` carries the
+    /// implicit session between lines; it is set once the client has sent
+    /// `initialize`. Returns the line to write back, if any.
+    pub fn handle_stdio_message(
+        &mut self,
+        line: &str,
+        session_id: &mut Option<String>,
+    ) -> Option<String> {
+        let messages = match protocol::parse_message(line) {
+            Ok(messages) => messages,
+            Err(e) => {
+                self.metrics.total_requests += 1;
+                self.metrics.total_errors += 1;
+                let error = protocol::build_error(Value::Null, e.code, &e.message, None);
+                return serde_json::to_string(&error).ok();
+            }
+        };
+
+        let outcome = server::handle_messages(
+            &messages,
+            session_id.as_deref(),
+            &self.config,
+            &mut self.sessions,
+            &mut self.approvals,
+            &mut self.log_buffer,
+        );
+        if let Some(created) = outcome.created_session {
+            *session_id = Some(created);
+        }
+        if let Some(sid) = session_id.as_deref() {
+            self.sessions.touch_session(sid);
+        }
+        self.absorb_events(outcome.events);
+
+        let mut responses = outcome.responses;
+        match responses.len() {
+            0 => None,
+            1 => serde_json::to_string(&responses.remove(0)).ok(),
+            _ => serde_json::to_string(&responses).ok(),
+        }
+    }
+
     // ── Sessions ─────────────────────────────────────────────────
 
     /// List all active sessions.
@@ -384,6 +419,44 @@ impl McpService {
         }
     }
 
+    // ── Tool Approval ────────────────────────────────────────────
+
+    /// List tool calls waiting for confirmation.
+    pub fn list_pending_approvals(&mut self) -> Vec<PendingApproval> {
+        self.approvals.pending()
+    }
+
+    /// Approve or reject a pending tool call.
+    pub fn resolve_approval(
+        &mut self,
+        approval_id: &str,
+        approve: bool,
+    ) -> Result<ApprovalRecord, String> {
+        let record = self
+            .approvals
+            .resolve(&self.config.approval, approval_id, approve)?;
+        self.log_buffer.record_approval(&record);
+        self.record_event(
+            McpEventType::ApprovalResolved,
+            json!({
+                "approval_id": record.id,
+                "tool": record.tool_name,
+                "outcome": record.outcome,
+            }),
+        );
+        Ok(record)
+    }
+
+    /// Replace the connection/session tag map used by the approval policy.
+    pub fn set_connection_tags(&mut self, tags: HashMap<String, Vec<String>>) {
+        self.approvals.set_connection_tags(tags);
+    }
+
+    /// Get recent approval decisions.
+    pub fn get_approval_log(&self, limit: usize) -> Vec<crate::logging::McpLogEntry> {
+        self.log_buffer.get_approval_entries(limit)
+    }
+
     // ── Logs ─────────────────────────────────────────────────────
 
     /// Get log entries.
@@ -436,6 +509,20 @@ impl McpService {
 
     // ── Helpers ──────────────────────────────────────────────────
 
+    /// Fold a routed request's events into metrics and history.
+    fn absorb_events(&mut self, events: Vec<McpEvent>) {
+        self.metrics.total_requests += 1;
+        for event in &events {
+            match event.event_type {
+                McpEventType::ToolCalled => self.metrics.total_tool_calls += 1,
+                McpEventType::ResourceRead => self.metrics.total_resource_reads += 1,
+                _ => {}
+            }
+        }
+        self.events.extend(events);
+        self.trim_events();
+    }
+
     fn record_event(&mut self, event_type: McpEventType, details: Value) {
         self.events.push(McpEvent {
             id: uuid::Uuid::new_v4().to_string(),
@@ -584,6 +671,69 @@ mod tests {
         assert_eq!(filtered.len(), 2);
     }
 
+    #[test]
+    fn test_stdio_session_and_approval() {
+        let mut service = McpService::new();
+        let mut session = None;
+
+        let init = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "host", "version": "1" }
+            }
+        });
+        let reply = service
+            .handle_stdio_message(&init.to_string(), &mut session)
+            .unwrap();
+        assert!(reply.contains("serverInfo"));
+        assert!(session.is_some());
+        let notified = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(service
+            .handle_stdio_message(&notified.to_string(), &mut session)
+            .is_none());
+
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "ssh_execute",
+                "arguments": { "session_id": "ssh-1", "command": "reboot" }
+            }
+        })
+        .to_string();
+        let reply: Value =
+            serde_json::from_str(&service.handle_stdio_message(&call, &mut session).unwrap())
+                .unwrap();
+        assert_eq!(reply["result"]["_approval"]["status"], "pending");
+
+        let pending = service.list_pending_approvals();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].session_id, session);
+        service.resolve_approval(&pending[0].id, true).unwrap();
+
+        let reply: Value =
+            serde_json::from_str(&service.handle_stdio_message(&call, &mut session).unwrap())
+                .unwrap();
+        assert_eq!(reply["result"]["isError"], false);
+        assert_eq!(service.get_metrics().total_tool_calls, 1);
+        let outcomes: Vec<Value> = service
+            .get_approval_log(10)
+            .into_iter()
+            .map(|e| e.data.unwrap()["outcome"].clone())
+            .collect();
+        assert_eq!(outcomes, vec!["pending", "approved", "allowed"]);
+
+        let reply = service
+            .handle_stdio_message("{not json", &mut session)
+            .unwrap();
+        assert!(reply.contains("-32700"));
+    }
+
     #[test]
     fn test_create_service_state() {
         let state = create_service_state();
@@ -0,0 +1,210 @@
+//! # MCP Transport — stdio
+//!
+//! Newline-delimited JSON-RPC over stdin/stdout, the transport desktop MCP
+//! hosts use when they launch a server as a subprocess:
+//!
+//! ```text
+//! sortOfRemoteNG mcp-stdio [--config <mcp-config.json>]
+//! ```
+//!
+//! The process serves exactly one client, so there is no authentication,
+//! CORS, or `Mcp-Session-Id` header: the session created by `initialize` is
+//! implied for every later line. Nothing but JSON-RPC is written to stdout;
+//! diagnostics go to stderr.
+//!
+//! Tool calls are gated by the configured approval policy. Confirmations
+//! are resolved through the app's approval commands, which a standalone
+//! stdio process does not have, so a call needing confirmation is denied
+//! with an explanation; only tools the policy allows can run.
+
+use crate::approval::ApprovalGate;
+use crate::service::{create_service_state_with_config, McpServiceState};
+use crate::types::*;
+
+use log::info;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Subcommand that starts the stdio transport.
+pub const STDIO_SUBCOMMAND: &str = "mcp-stdio";
+
+/// Parse the arguments following the subcommand into a server config.
+///
+/// `--config <file>` loads an `McpServerConfig` JSON document; without it
+/// the defaults apply.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<McpServerConfig, String> {
+    let mut config_path = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            config_path = Some(
+                args.next()
+                    .ok_or_else(|| "--config requires a file path".to_string())?,
+            );
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            config_path = Some(path.to_string());
+        } else {
+            return Err(format!("Unknown argument: {arg}"));
+        }
+    }
+
+    match config_path {
+        Some(path) => {
+            let raw =
+                std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {path}: {e}"))?;
+            serde_json::from_str(&raw).map_err(|e| format!("Invalid MCP config in {path}: {e}"))
+        }
+        None => Ok(McpServerConfig::default()),
+    }
+}
+
+/// Service state for a stdio process. Its approval gate is headless.
+pub fn stdio_state(config: McpServerConfig) -> McpServiceState {
+    let state = create_service_state_with_config(config);
+    if let Ok(mut service) = state.lock() {
+        service.approvals = ApprovalGate::headless();
+    }
+    state
+}
+
+/// Serve JSON-RPC lines from `reader` until EOF, writing one line per
+/// response to `writer`.
+pub async fn serve<R, W>(state: McpServiceState, reader: R, mut writer: W) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    let mut session_id: Option<String> = None;
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let reply = {
+            let mut service = state
+                .lock()
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            service.handle_stdio_message(line, &mut session_id)
+        };
+        if let Some(reply) = reply {
+            writer.write_all(reply.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await?;
+        }
+    }
+
+    if let Some(sid) = session_id {
+        if let Ok(mut service) = state.lock() {
+            let _ = service.disconnect_session(&sid);
+        }
+    }
+    info!("MCP stdio client disconnected");
+    Ok(())
+}
+
+/// Entry point for the `mcp-stdio` subcommand. Returns the process exit code.
+pub fn run_from_args(args: impl IntoIterator<Item = String>) -> i32 {
+    let config = match parse_args(args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{STDIO_SUBCOMMAND}: {e}");
+            return 2;
+        }
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{STDIO_SUBCOMMAND}: cannot start runtime: {e}");
+            return 1;
+        }
+    };
+
+    let state = stdio_state(config);
+    let result = runtime.block_on(serve(
+        state,
+        BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    ));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{STDIO_SUBCOMMAND}: {e}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_parse_args() {
+        let config = parse_args(Vec::new()).unwrap();
+        assert_eq!(config.port, 3100);
+        assert!(parse_args(vec!["--config".to_string()]).is_err());
+        assert!(parse_args(vec!["--verbose".to_string()]).is_err());
+
+        let path = std::env::temp_dir().join(format!("sorng-mcp-{}.json", uuid::Uuid::new_v4()));
+        let mut custom = McpServerConfig::default();
+        custom.approval.command_allowlist = vec!["uptime".to_string()];
+        std::fs::write(&path, serde_json::to_string(&custom).unwrap()).unwrap();
+        let loaded = parse_args(vec![format!("--config={}", path.display())]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.approval.command_allowlist, vec!["uptime"]);
+    }
+
+    #[tokio::test]
+    async fn test_serve_round_trip() {
+        let input = [
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "host", "version": "1" }
+                }
+            }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": {
+                    "name": "db_query",
+                    "arguments": { "connection_id": "c1", "query": "DROP TABLE t" }
+                }
+            }),
+        ]
+        .iter()
+        .map(|v| format!("{v}\n\n"))
+        .collect::<String>();
+
+        let state = stdio_state(McpServerConfig::default());
+        let mut output = Vec::new();
+        serve(state.clone(), input.as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        let replies: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["id"], 1);
+        assert!(replies[1]["result"]["tools"].is_array());
+        // Nobody can confirm the call, so it is refused rather than held.
+        assert_eq!(replies[2]["result"]["_approval"]["status"], "denied");
+        assert!(state.lock().unwrap().list_pending_approvals().is_empty());
+        assert!(state.lock().unwrap().list_sessions().is_empty());
+    }
+}
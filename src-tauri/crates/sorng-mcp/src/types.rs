@@ -458,6 +458,9 @@ pub struct McpServerConfig {
     pub sse_enabled: bool,
     /// Auto-start the MCP server when the app launches.
     pub auto_start: bool,
+    /// Approval policy applied to every `tools/call`.
+    #[serde(default)]
+    pub approval: ToolApprovalPolicy,
 }
 
 impl Default for McpServerConfig {
@@ -483,6 +486,7 @@ impl Default for McpServerConfig {
             server_instructions: "SortOfRemote NG MCP Server — manage remote connections, execute SSH commands, transfer files, and query databases through AI assistant integration.".to_string(),
             sse_enabled: true,
             auto_start: false,
+            approval: ToolApprovalPolicy::default(),
         }
     }
 }
@@ -594,9 +598,120 @@ pub enum McpEventType {
     AuthFailure,
     RateLimited,
     ConfigChanged,
+    ApprovalRequested,
+    ApprovalResolved,
+    ToolDenied,
     Error,
 }
 
+// ── Tool Approval ───────────────────────────────────────────────────
+
+/// How calls to a tool are gated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalMode {
+    /// Run without asking.
+    Allow,
+    /// Run when an allowlist matches, otherwise ask the user first.
+    Confirm,
+    /// Never run.
+    Deny,
+}
+
+/// Approval policy for tool calls (persisted with the server config).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolApprovalPolicy {
+    /// Run tools annotated as read-only without confirmation.
+    pub auto_allow_read_only: bool,
+    /// Per-tool overrides keyed by tool name.
+    pub tool_modes: HashMap<String, ApprovalMode>,
+    /// Glob patterns (`*`, `?`) for commands and queries that may run
+    /// without confirmation. Chained commands never match.
+    pub command_allowlist: Vec<String>,
+    /// Connection tags whose connections and sessions may be targeted
+    /// without confirmation.
+    pub allowed_connection_tags: Vec<String>,
+    /// How long a pending request, or a granted approval awaiting the
+    /// client's retry, stays valid.
+    pub confirmation_ttl_secs: u64,
+}
+
+impl Default for ToolApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            auto_allow_read_only: true,
+            tool_modes: HashMap::new(),
+            command_allowlist: vec![],
+            allowed_connection_tags: vec![],
+            confirmation_ttl_secs: 300,
+        }
+    }
+}
+
+/// Result of an approval decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    /// The call may run.
+    Allowed,
+    /// The call is held until the user confirms it.
+    Pending,
+    /// The call was refused by policy.
+    Denied,
+    /// The user confirmed a pending call.
+    Approved,
+    /// The user refused a pending call.
+    Rejected,
+}
+
+impl ApprovalOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allowed => "allowed",
+            Self::Pending => "pending",
+            Self::Denied => "denied",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// A single approval decision, as written to the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRecord {
+    /// Approval request ID (shared by the pending, resolved, and retried
+    /// decisions of one confirmation).
+    pub id: String,
+    pub tool_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub outcome: ApprovalOutcome,
+    /// Which rule produced the outcome.
+    pub reason: String,
+    /// The command or targets the call acts on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A tool call waiting for the user's confirmation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    pub id: String,
+    pub tool_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Call arguments with secrets redacted.
+    pub arguments: serde_json::Value,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
 /// Tool call log entry for the UI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -3,6 +3,7 @@
 use crate::mongodb::types::*;
 use chrono::Utc;
 use log::info;
+use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::ffi::OsString;
@@ -13,7 +14,7 @@ use std::pin::Pin;
 use std::process::Stdio;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tempfile::NamedTempFile;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::process::{Child, Command};
@@ -34,6 +35,12 @@ const MAX_FIELD_BYTES: usize = 1024;
 const MAX_PATH_BYTES: usize = 4096;
 const MAX_SESSION_ID_BYTES: usize = 128;
 const MAX_TIMEOUT_SECS: u64 = 300;
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 1000;
+const IMPORT_BATCH_DOCS: usize = 1000;
+/// Each batch is embedded as escaped string literals, so stay well under
+/// `MAX_SCRIPT_BYTES`.
+const IMPORT_BATCH_BYTES: usize = 96 * 1024;
 const PROCESS_TIMEOUT: Duration = Duration::from_secs(45);
 const REAP_TIMEOUT: Duration = Duration::from_secs(3);
 
@@ -496,6 +503,443 @@ print(JSON.stringify({{ ok: true }}));
         self.run_session_json(session_id, &script).await.map(|_| ())
     }
 
+    pub async fn find_documents(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        options: &FindOptions,
+    ) -> Result<DocumentPage, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let skip = options.skip.unwrap_or(0);
+        let limit = page_limit(options.limit);
+        let script = format!(
+            r#"{COLLECT_PAGE_JS}
+const cursor = {collection}
+  .find({}, {})
+  .sort({})
+  .skip({skip})
+  .limit({});
+print(JSON.stringify(collectPage(cursor, {limit}, true)));
+"#,
+            ejson_document("filter", options.filter.as_deref())?,
+            ejson_document("projection", options.projection.as_deref())?,
+            ejson_document("sort", options.sort.as_deref())?,
+            limit + 1
+        );
+
+        self.run_page(session_id, &script, skip).await
+    }
+
+    pub async fn count_documents(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: Option<&str>,
+    ) -> Result<u64, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let script = format!(
+            r#"
+const count = {collection}.countDocuments({});
+print(JSON.stringify({{ count: Number(count) }}));
+"#,
+            ejson_document("filter", filter)?
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        value
+            .get("count")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| serialization_error("count"))
+    }
+
+    pub async fn insert_one(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        document: &str,
+    ) -> Result<InsertResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let script = format!(
+            r#"
+const result = {collection}.insertOne({});
+if (!result.acknowledged) {{
+  throw new Error('insertOne was not acknowledged');
+}}
+print(EJSON.stringify({{ inserted_count: 1, inserted_ids: [result.insertedId] }}));
+"#,
+            ejson_literal(
+                "document",
+                document,
+                "an Extended JSON object",
+                Value::is_object
+            )?
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        serde_json::from_value(value).map_err(serialization_error)
+    }
+
+    pub async fn insert_many(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        documents: &[String],
+    ) -> Result<InsertResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let documents = documents.iter().map(String::as_str).collect::<Vec<_>>();
+        self.insert_documents(session_id, &collection, &documents)
+            .await
+    }
+
+    pub async fn update_one(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: &str,
+        update: &str,
+        upsert: bool,
+    ) -> Result<UpdateResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        self.update_documents(
+            session_id,
+            &collection,
+            "updateOne",
+            filter,
+            update_literal(update)?,
+            upsert,
+        )
+        .await
+    }
+
+    pub async fn update_many(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: &str,
+        update: &str,
+        upsert: bool,
+    ) -> Result<UpdateResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        self.update_documents(
+            session_id,
+            &collection,
+            "updateMany",
+            filter,
+            update_literal(update)?,
+            upsert,
+        )
+        .await
+    }
+
+    pub async fn replace_one(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: &str,
+        replacement: &str,
+        upsert: bool,
+    ) -> Result<UpdateResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let replacement = ejson_literal(
+            "replacement",
+            replacement,
+            "an Extended JSON object",
+            Value::is_object,
+        )?;
+        self.update_documents(
+            session_id,
+            &collection,
+            "replaceOne",
+            filter,
+            replacement,
+            upsert,
+        )
+        .await
+    }
+
+    pub async fn delete_one(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: &str,
+    ) -> Result<DeleteResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        self.delete_documents(session_id, &collection, "deleteOne", filter)
+            .await
+    }
+
+    /// Delete every matching document.  An empty filter has to be passed
+    /// explicitly as `{}` to clear the collection.
+    pub async fn delete_many(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        filter: &str,
+    ) -> Result<DeleteResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        self.delete_documents(session_id, &collection, "deleteMany", filter)
+            .await
+    }
+
+    /// Run an aggregation pipeline and return one page of its output.
+    /// Pipelines ending in `$out` or `$merge` run unchanged and return no
+    /// documents.
+    pub async fn aggregate(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        pipeline: &str,
+        skip: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<DocumentPage, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let skip = skip.unwrap_or(0);
+        let limit = page_limit(limit);
+        let script = format!(
+            r#"{COLLECT_PAGE_JS}
+const pipeline = {};
+const last = pipeline.length > 0 ? pipeline[pipeline.length - 1] : {{}};
+const writes = '$out' in last || '$merge' in last;
+const stages = writes
+  ? pipeline
+  : pipeline.concat([{{ $skip: {skip} }}, {{ $limit: {} }}]);
+const cursor = {collection}.aggregate(stages, {{ allowDiskUse: true }});
+print(JSON.stringify(collectPage(cursor, {limit}, true)));
+"#,
+            ejson_literal(
+                "pipeline",
+                pipeline,
+                "an array of pipeline stages",
+                is_object_array
+            )?,
+            limit + 1
+        );
+
+        self.run_page(session_id, &script, skip).await
+    }
+
+    pub async fn list_indexes(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+    ) -> Result<Vec<IndexInfo>, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let script = format!(
+            r#"
+print(JSON.stringify({collection}.getIndexes().map(index => ({{
+  name: index.name || '',
+  keys: EJSON.stringify(index.key || {{}}),
+  unique: Boolean(index.unique),
+  sparse: Boolean(index.sparse),
+  expire_after_secs: index.expireAfterSeconds == null ? null : Number(index.expireAfterSeconds),
+  partial_filter: index.partialFilterExpression == null
+    ? null
+    : EJSON.stringify(index.partialFilterExpression)
+}}))));
+"#
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        serde_json::from_value(value).map_err(serialization_error)
+    }
+
+    /// Create an index and return its name.
+    pub async fn create_index(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        spec: &IndexSpec,
+    ) -> Result<String, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let keys = ejson_literal(
+            "index keys",
+            &spec.keys,
+            "a non-empty Extended JSON object",
+            |value| value.as_object().is_some_and(|keys| !keys.is_empty()),
+        )?;
+        let mut options = serde_json::Map::new();
+        if let Some(name) = &spec.name {
+            validate_required_field("MongoDB index name", name, MAX_FIELD_BYTES)?;
+            options.insert("name".into(), name.clone().into());
+        }
+        if spec.unique {
+            options.insert("unique".into(), true.into());
+        }
+        if spec.sparse {
+            options.insert("sparse".into(), true.into());
+        }
+        if let Some(secs) = spec.expire_after_secs {
+            options.insert("expireAfterSeconds".into(), secs.into());
+        }
+        let partial_filter = match spec.partial_filter.as_deref() {
+            Some(filter) => format!(
+                "options.partialFilterExpression = {};",
+                ejson_literal(
+                    "partial filter",
+                    filter,
+                    "an Extended JSON object",
+                    Value::is_object
+                )?
+            ),
+            None => String::new(),
+        };
+        let script = format!(
+            r#"
+const options = {};
+{partial_filter}
+const name = {collection}.createIndex({keys}, options);
+print(JSON.stringify({{ name: String(name) }}));
+"#,
+            serde_json::to_string(&options).map_err(serialization_error)?
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        value
+            .get("name")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| serialization_error("name"))
+    }
+
+    pub async fn drop_index(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        index_name: &str,
+    ) -> Result<(), MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let script = format!(
+            r#"
+const result = {collection}.dropIndex({});
+if (result.ok !== 1) {{
+  throw new Error(result.errmsg || 'dropIndex failed');
+}}
+print(JSON.stringify({{ ok: true }}));
+"#,
+            js_string(index_name)?
+        );
+
+        self.run_session_json(session_id, &script).await.map(|_| ())
+    }
+
+    /// Export a collection as Extended JSON, walking it in `_id` order one
+    /// chunk per `mongosh` run.
+    ///
+    /// Chunks resume with an aggregation `$gt`, which compares across BSON
+    /// types in sort order; a query `$gt` only matches `_id`s of the same
+    /// type and would drop the rest of a collection with mixed `_id` types.
+    pub async fn export_collection(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        options: &ExportOptions,
+    ) -> Result<String, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let filter = ejson_document("filter", options.filter.as_deref())?;
+        let chunk = page_limit(Some(u64::from(options.chunk_size)));
+        let mut documents = Vec::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let after_js = match after.as_deref() {
+                Some(last_id) => ejson_literal("cursor", last_id, "an _id value", |_| true)?,
+                None => "null".to_string(),
+            };
+            let script = format!(
+                r#"{COLLECT_PAGE_JS}
+const filter = {filter};
+const after = {after_js};
+const query = after === null
+  ? filter
+  : {{ $and: [filter, {{ $expr: {{ $gt: ['$_id', {{ $literal: after }}] }} }}] }};
+const cursor = {collection}.find(query).sort({{ _id: 1 }}).limit({});
+print(JSON.stringify(collectPage(cursor, {chunk}, {})));
+"#,
+                chunk + 1,
+                options.relaxed
+            );
+
+            let value = self.run_session_json(session_id, &script).await?;
+            let page: RawPage = serde_json::from_value(value).map_err(serialization_error)?;
+            documents.extend(page.documents);
+            if !page.has_more {
+                break;
+            }
+            after = Some(page.last_id.ok_or_else(|| {
+                MongoError::new(
+                    MongoErrorKind::CommandError,
+                    "Cannot page past a document without an _id",
+                )
+            })?);
+        }
+
+        Ok(match options.format {
+            ExportFormat::JsonArray => format!("[{}]\n", documents.join(",\n")),
+            ExportFormat::JsonLines => documents.iter().map(|doc| format!("{doc}\n")).collect(),
+        })
+    }
+
+    /// Import a JSON array or JSON Lines file of Extended JSON documents,
+    /// inserting in ordered batches.  Batches already written stay in
+    /// place if a later one fails; the error details say how many.
+    pub async fn import_documents(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+        data: &str,
+    ) -> Result<ImportResult, MongoError> {
+        let collection = self.collection_js(session_id, db_name, collection_name)?;
+        let documents = split_import_documents(data)?;
+        let mut result = ImportResult {
+            inserted_count: 0,
+            batches: 0,
+        };
+
+        let mut start = 0;
+        while start < documents.len() {
+            let mut end = start;
+            let mut bytes = 0;
+            while end < documents.len()
+                && end - start < IMPORT_BATCH_DOCS
+                && (end == start || bytes + documents[end].len() <= IMPORT_BATCH_BYTES)
+            {
+                bytes += documents[end].len();
+                end += 1;
+            }
+
+            let inserted = self
+                .insert_documents(session_id, &collection, &documents[start..end])
+                .await
+                .map_err(|mut error| {
+                    error.details = Some(format!(
+                        "{} documents were imported before the failure",
+                        result.inserted_count
+                    ));
+                    error
+                })?;
+            result.inserted_count += inserted.inserted_count;
+            result.batches += 1;
+            start = end;
+        }
+
+        Ok(result)
+    }
+
     fn connection_string(&self, session_id: &str) -> Result<&str, MongoError> {
         validate_required_field("MongoDB session ID", session_id, MAX_SESSION_ID_BYTES)?;
         self.sessions
@@ -524,6 +968,140 @@ print(JSON.stringify({{ ok: true }}));
         validate_runner_input(connection_string, script)?;
         self.runner.run_json(connection_string, script).await
     }
+
+    fn collection_js(
+        &self,
+        session_id: &str,
+        db_name: Option<&str>,
+        collection_name: &str,
+    ) -> Result<String, MongoError> {
+        let selected_db = self.resolve_db_name(session_id, db_name)?;
+        Ok(format!(
+            "db.getSiblingDB({}).getCollection({})",
+            js_string(&selected_db)?,
+            js_string(collection_name)?
+        ))
+    }
+
+    async fn insert_documents(
+        &self,
+        session_id: &str,
+        collection: &str,
+        documents: &[&str],
+    ) -> Result<InsertResult, MongoError> {
+        if documents.is_empty() {
+            return Err(MongoError::new(
+                MongoErrorKind::InvalidConfig,
+                "No documents to insert",
+            ));
+        }
+        let documents = documents
+            .iter()
+            .map(|document| {
+                ejson_literal(
+                    "document",
+                    document,
+                    "an Extended JSON object",
+                    Value::is_object,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let script = format!(
+            r#"
+const result = {collection}.insertMany([
+{}
+], {{ ordered: true }});
+if (!result.acknowledged) {{
+  throw new Error('insertMany was not acknowledged');
+}}
+const ids = Object.values(result.insertedIds);
+print(EJSON.stringify({{ inserted_count: ids.length, inserted_ids: ids }}));
+"#,
+            documents.join(",\n")
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        serde_json::from_value(value).map_err(serialization_error)
+    }
+
+    async fn update_documents(
+        &self,
+        session_id: &str,
+        collection: &str,
+        method: &str,
+        filter: &str,
+        update_js: String,
+        upsert: bool,
+    ) -> Result<UpdateResult, MongoError> {
+        let script = format!(
+            r#"
+const result = {collection}.{method}({}, {update_js}, {{ upsert: {upsert} }});
+if (!result.acknowledged) {{
+  throw new Error('{method} was not acknowledged');
+}}
+print(EJSON.stringify({{
+  matched_count: Number(result.matchedCount || 0),
+  modified_count: Number(result.modifiedCount || 0),
+  upserted_id: result.upsertedId ?? null
+}}));
+"#,
+            ejson_literal(
+                "filter",
+                filter,
+                "an Extended JSON object",
+                Value::is_object
+            )?
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        serde_json::from_value(value).map_err(serialization_error)
+    }
+
+    async fn delete_documents(
+        &self,
+        session_id: &str,
+        collection: &str,
+        method: &str,
+        filter: &str,
+    ) -> Result<DeleteResult, MongoError> {
+        let script = format!(
+            r#"
+const result = {collection}.{method}({});
+if (!result.acknowledged) {{
+  throw new Error('{method} was not acknowledged');
+}}
+print(JSON.stringify({{ deleted_count: Number(result.deletedCount || 0) }}));
+"#,
+            ejson_literal(
+                "filter",
+                filter,
+                "an Extended JSON object",
+                Value::is_object
+            )?
+        );
+
+        let value = self.run_session_json(session_id, &script).await?;
+        serde_json::from_value(value).map_err(serialization_error)
+    }
+
+    async fn run_page(
+        &self,
+        session_id: &str,
+        script: &str,
+        skip: u64,
+    ) -> Result<DocumentPage, MongoError> {
+        let started = Instant::now();
+        let value = self.run_session_json(session_id, script).await?;
+        let page: RawPage = serde_json::from_value(value).map_err(serialization_error)?;
+        let returned = page.documents.len() as u64;
+        Ok(DocumentPage {
+            documents: page.documents,
+            skip,
+            has_more: page.has_more,
+            next_skip: page.has_more.then(|| skip + returned),
+            execution_time_ms: started.elapsed().as_millis() as u64,
+        })
+    }
 }
 
 struct ParsedMongoUri {
@@ -1454,6 +2032,158 @@ fn js_string(value: &str) -> Result<String, MongoError> {
     serde_json::to_string(value).map_err(serialization_error)
 }
 
+/// Reads up to `max` documents from a cursor as Extended JSON text.  The
+/// page stops early at a quarter of `MAX_CAPTURE_BYTES` (measured in
+/// escaped UTF-16 units) so a page of large documents still fits the
+/// output capture.
+const COLLECT_PAGE_JS: &str = r#"
+function collectPage(cursor, max, relaxed) {
+  const documents = [];
+  let bytes = 0;
+  let lastId = null;
+  while (documents.length < max && bytes < 262144 && cursor.hasNext()) {
+    const doc = cursor.next();
+    const text = EJSON.stringify(doc, { relaxed });
+    documents.push(text);
+    bytes += JSON.stringify(text).length;
+    lastId = doc._id === undefined ? null : doc._id;
+  }
+  return {
+    documents,
+    has_more: cursor.hasNext(),
+    last_id: lastId === null ? null : EJSON.stringify(lastId, { relaxed: false })
+  };
+}"#;
+
+#[derive(Deserialize)]
+struct RawPage {
+    documents: Vec<String>,
+    has_more: bool,
+    last_id: Option<String>,
+}
+
+/// Embed Extended JSON text as an `EJSON.parse(...)` call so user input
+/// only reaches the script as a string literal.  Canonical parsing keeps
+/// integers, doubles and longs apart.
+fn ejson_literal(
+    label: &str,
+    text: &str,
+    expected: &str,
+    accepts: fn(&Value) -> bool,
+) -> Result<String, MongoError> {
+    let invalid = || {
+        MongoError::new(
+            MongoErrorKind::InvalidConfig,
+            format!("MongoDB {label} must be {expected}"),
+        )
+    };
+    let value: Value = serde_json::from_str(text).map_err(|_| invalid())?;
+    if !accepts(&value) {
+        return Err(invalid());
+    }
+    let literal = serde_json::to_string(text).map_err(serialization_error)?;
+    Ok(format!("EJSON.parse({literal}, {{ relaxed: false }})"))
+}
+
+/// Like [`ejson_literal`] for an optional object; blank means `{}`.
+fn ejson_document(label: &str, text: Option<&str>) -> Result<String, MongoError> {
+    match text.map(str::trim).filter(|text| !text.is_empty()) {
+        Some(text) => ejson_literal(label, text, "an Extended JSON object", Value::is_object),
+        None => Ok("{}".to_string()),
+    }
+}
+
+/// Update documents (`$set`, ...) and update pipelines are both accepted.
+fn update_literal(update: &str) -> Result<String, MongoError> {
+    ejson_literal(
+        "update",
+        update,
+        "an update document or pipeline",
+        |value| value.is_object() || is_object_array(value),
+    )
+}
+
+fn is_object_array(value: &Value) -> bool {
+    value
+        .as_array()
+        .is_some_and(|items| items.iter().all(Value::is_object))
+}
+
+fn page_limit(limit: Option<u64>) -> u64 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Split an import file into per-document text: a single JSON array, or
+/// one document per line as written by `mongoexport`.
+fn split_import_documents(data: &str) -> Result<Vec<&str>, MongoError> {
+    let data = data.trim_start_matches('\u{feff}').trim();
+    if data.starts_with('[') {
+        split_json_array(data)
+    } else {
+        Ok(data
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
+
+/// Slice the elements out of a top-level JSON array without parsing them,
+/// so object keys keep their order.
+fn split_json_array(text: &str) -> Result<Vec<&str>, MongoError> {
+    let malformed = || {
+        MongoError::new(
+            MongoErrorKind::InvalidConfig,
+            "Import data is not a valid JSON array",
+        )
+    };
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = None;
+
+    for (index, byte) in text.bytes().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if depth == 1
+            && start.is_none()
+            && !matches!(byte, b',' | b']')
+            && !byte.is_ascii_whitespace()
+        {
+            start = Some(index);
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => {
+                depth = depth.checked_sub(1).ok_or_else(malformed)?;
+                if depth == 0 {
+                    items.extend(start.take().map(|from| text[from..index].trim()));
+                    return if text[index + 1..].trim().is_empty() {
+                        Ok(items)
+                    } else {
+                        Err(malformed())
+                    };
+                }
+            }
+            b',' if depth == 1 => {
+                let from = start.take().ok_or_else(malformed)?;
+                items.push(text[from..index].trim());
+            }
+            _ => {}
+        }
+    }
+    Err(malformed())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1800,4 +2530,244 @@ mod tests {
             .any(|(candidate, _)| candidate
                 .ends_with(Path::new("MongoDB/Server/8.0/bin/mongosh.exe"))));
     }
+
+    async fn connected_service(
+        responses: Vec<Result<Value, MongoError>>,
+    ) -> (MongoService, Arc<FakeRunner>, String) {
+        let mut all = vec![Ok(serde_json::json!({ "ok": true, "version": "8.0" }))];
+        all.extend(responses);
+        let runner = Arc::new(FakeRunner::new(all));
+        let mut service = MongoService::with_runner(runner.clone());
+        let session_id = service
+            .connect(config_with_uri("mongodb://db.example/admin?tls=true"))
+            .await
+            .unwrap();
+        (service, runner, session_id)
+    }
+
+    #[tokio::test]
+    async fn find_embeds_user_json_as_string_literals_and_pages() {
+        let (service, runner, session_id) = connected_service(vec![Ok(serde_json::json!({
+            "documents": ["{\"_id\":1,\"b\":2,\"a\":1}", "{\"_id\":2}"],
+            "has_more": true,
+            "last_id": "{\"$numberInt\":\"2\"}"
+        }))])
+        .await;
+        let filter = r#"{ "name": "x'); db.dropDatabase(); ('" }"#;
+        let page = service
+            .find_documents(
+                &session_id,
+                None,
+                "people",
+                &FindOptions {
+                    filter: Some(filter.into()),
+                    sort: Some(r#"{ "b": -1, "a": 1 }"#.into()),
+                    skip: Some(20),
+                    limit: Some(2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(page.documents[0], r#"{"_id":1,"b":2,"a":1}"#);
+        assert!(page.has_more);
+        assert_eq!(page.next_skip, Some(22));
+
+        let script = &runner.calls()[1].1;
+        assert!(script.contains(&serde_json::to_string(filter).unwrap()));
+        assert!(script.contains(r#"EJSON.parse("{ \"b\": -1, \"a\": 1 }", { relaxed: false })"#));
+        assert!(script.contains(".skip(20)"));
+        assert!(script.contains(".limit(3)"));
+        assert!(script.contains("collectPage(cursor, 2, true)"));
+    }
+
+    #[tokio::test]
+    async fn malformed_documents_are_rejected_before_the_runner() {
+        let (service, runner, session_id) = connected_service(vec![]).await;
+        let options = FindOptions {
+            filter: Some("[1, 2]".into()),
+            ..Default::default()
+        };
+        let error = service
+            .find_documents(&session_id, None, "people", &options)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, MongoErrorKind::InvalidConfig);
+        assert!(service
+            .replace_one(
+                &session_id,
+                None,
+                "people",
+                "{}",
+                r#"[{ "$set": {} }]"#,
+                false
+            )
+            .await
+            .is_err());
+        assert!(service
+            .aggregate(
+                &session_id,
+                None,
+                "people",
+                r#"{ "$match": {} }"#,
+                None,
+                None
+            )
+            .await
+            .is_err());
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn update_and_delete_results_are_typed() {
+        let (service, runner, session_id) = connected_service(vec![
+            Ok(serde_json::json!({
+                "matched_count": 0,
+                "modified_count": 0,
+                "upserted_id": { "$oid": "65f000000000000000000001" }
+            })),
+            Ok(serde_json::json!({ "deleted_count": 3 })),
+        ])
+        .await;
+        let updated = service
+            .update_many(
+                &session_id,
+                Some("app"),
+                "people",
+                r#"{ "active": false }"#,
+                r#"{ "$set": { "archived": true } }"#,
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.matched_count, 0);
+        assert!(updated.upserted_id.is_some());
+        let deleted = service
+            .delete_many(&session_id, Some("app"), "people", "{}")
+            .await
+            .unwrap();
+        assert_eq!(deleted.deleted_count, 3);
+
+        let calls = runner.calls();
+        assert!(calls[1].1.contains(".updateMany("));
+        assert!(calls[1].1.contains("{ upsert: true }"));
+        assert!(calls[1].1.contains(r#"db.getSiblingDB("app")"#));
+        assert!(calls[2].1.contains(".deleteMany("));
+    }
+
+    #[tokio::test]
+    async fn export_walks_the_collection_by_id() {
+        let (service, runner, session_id) = connected_service(vec![
+            Ok(serde_json::json!({
+                "documents": ["{\"_id\":{\"$oid\":\"65f000000000000000000001\"}}"],
+                "has_more": true,
+                "last_id": "{\"$oid\":\"65f000000000000000000001\"}"
+            })),
+            Ok(serde_json::json!({
+                "documents": ["{\"_id\":{\"$oid\":\"65f000000000000000000002\"}}"],
+                "has_more": false,
+                "last_id": "{\"$oid\":\"65f000000000000000000002\"}"
+            })),
+        ])
+        .await;
+        let options = ExportOptions {
+            format: ExportFormat::JsonLines,
+            relaxed: false,
+            filter: None,
+            chunk_size: 1,
+        };
+        let exported = service
+            .export_collection(&session_id, None, "people", &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            exported,
+            "{\"_id\":{\"$oid\":\"65f000000000000000000001\"}}\n{\"_id\":{\"$oid\":\"65f000000000000000000002\"}}\n"
+        );
+
+        let calls = runner.calls();
+        assert!(calls[1].1.contains("const after = null;"));
+        assert!(calls[1].1.contains("collectPage(cursor, 1, false)"));
+        assert!(calls[2].1.contains("65f000000000000000000001"));
+        assert!(calls[2].1.contains("$gt: ['$_id', { $literal: after }]"));
+    }
+
+    #[tokio::test]
+    async fn export_pages_across_id_types() {
+        let page = |id: &str, has_more: bool| {
+            Ok(serde_json::json!({
+                "documents": [format!(r#"{{"_id":{id}}}"#)],
+                "has_more": has_more,
+                "last_id": id
+            }))
+        };
+        // _id sort order across types: numbers, strings, then ObjectIds.
+        let (service, runner, session_id) = connected_service(vec![
+            page(r#"{"$numberInt":"7"}"#, true),
+            page(r#""$admin""#, true),
+            page(r#"{"$oid":"65f000000000000000000001"}"#, false),
+        ])
+        .await;
+        let options = ExportOptions {
+            format: ExportFormat::JsonLines,
+            relaxed: false,
+            filter: None,
+            chunk_size: 1,
+        };
+        let exported = service
+            .export_collection(&session_id, None, "people", &options)
+            .await
+            .unwrap();
+        assert_eq!(exported.lines().count(), 3);
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 4);
+        assert!(calls[2]
+            .1
+            .contains(r#"EJSON.parse("{\"$numberInt\":\"7\"}""#));
+        // A string _id that looks like a field path stays a literal.
+        assert!(calls[3].1.contains(r#"EJSON.parse("\"$admin\"""#));
+        for (_, script) in &calls[2..] {
+            assert!(script.contains("$expr: { $gt: ['$_id', { $literal: after }] }"));
+        }
+    }
+
+    #[tokio::test]
+    async fn import_accepts_arrays_and_json_lines() {
+        let (service, runner, session_id) = connected_service(vec![
+            Ok(serde_json::json!({ "inserted_count": 2, "inserted_ids": [1, 2] })),
+            Ok(serde_json::json!({ "inserted_count": 1, "inserted_ids": [3] })),
+        ])
+        .await;
+        let imported = service
+            .import_documents(
+                &session_id,
+                None,
+                "people",
+                r#"[{ "_id": 1, "tags": ["a,b", "]"] }, { "_id": 2, "note": "say \"}\"" }]"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(imported.inserted_count, 2);
+        assert_eq!(imported.batches, 1);
+        let imported = service
+            .import_documents(&session_id, None, "people", "\n{\"_id\": 3}\n\n")
+            .await
+            .unwrap();
+        assert_eq!(imported.inserted_count, 1);
+        assert!(runner.calls()[1].1.contains("insertMany"));
+    }
+
+    #[test]
+    fn json_array_splitter_keeps_element_text() {
+        let items = split_json_array(r#"[ {"b":1,"a":"x]}"} , {"c":[1,{"d":2}]}, 3 ]"#).unwrap();
+        assert_eq!(
+            items,
+            vec![r#"{"b":1,"a":"x]}"}"#, r#"{"c":[1,{"d":2}]}"#, "3"]
+        );
+        assert!(split_json_array("[]").unwrap().is_empty());
+        assert!(split_json_array(r#"[{"a":1}"#).is_err());
+        assert!(split_json_array(r#"[{"a":1}] trailing"#).is_err());
+    }
 }
@@ -1,6 +1,7 @@
 //! Types for simple MongoDB connection and server management.
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 pub const INVALID_CERTIFICATE_ACKNOWLEDGEMENT: &str =
     "I understand that MongoDB certificate verification is disabled for this connection only";
@@ -294,6 +295,127 @@ pub struct SessionInfo {
     pub replica_set: Option<String>,
 }
 
+// Filters, documents, pipelines and index keys travel as Extended JSON
+// text rather than `serde_json::Value`, which would sort object keys and
+// break sort specifications and compound keys.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FindOptions {
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub projection: Option<String>,
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub skip: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+/// One page of a `find` or `aggregate` cursor.  Documents are relaxed
+/// Extended JSON text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentPage {
+    pub documents: Vec<String>,
+    pub skip: u64,
+    pub has_more: bool,
+    /// `skip` value for the following page, if there is one.
+    pub next_skip: Option<u64>,
+    pub execution_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertResult {
+    pub inserted_count: u64,
+    pub inserted_ids: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateResult {
+    pub matched_count: u64,
+    pub modified_count: u64,
+    pub upserted_id: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteResult {
+    pub deleted_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub keys: String,
+    pub unique: bool,
+    pub sparse: bool,
+    pub expire_after_secs: Option<i64>,
+    pub partial_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSpec {
+    /// Key pattern such as `{ "email": 1 }` or `{ "body": "text" }`.
+    pub keys: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub sparse: bool,
+    #[serde(default)]
+    pub expire_after_secs: Option<i64>,
+    #[serde(default)]
+    pub partial_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum ExportFormat {
+    /// One JSON array holding every document.
+    #[default]
+    JsonArray,
+    /// One document per line, as written by `mongoexport`.
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Relaxed Extended JSON (plain numbers and dates) instead of canonical.
+    #[serde(default = "default_relaxed")]
+    pub relaxed: bool,
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: u32,
+}
+
+const fn default_relaxed() -> bool {
+    true
+}
+
+const fn default_chunk_size() -> u32 {
+    500
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::JsonArray,
+            relaxed: default_relaxed(),
+            filter: None,
+            chunk_size: default_chunk_size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub inserted_count: u64,
+    pub batches: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,507 @@
+//! ClearCodec (RDPGFX_CODECID_CLEARCODEC) — MS-RDPEGFX 2.2.4.1.
+//!
+//! A bitmap is composed from three layers decoded in order: a run-length
+//! residual background, vertical-bar "bands" (mostly text) backed by two
+//! V-bar caches, and subcodec rectangles (raw, NSCodec or RLEX). Whole
+//! bitmaps may also be stored in and replayed from a glyph cache.
+
+use super::{nsc, ByteReader};
+use crate::gfx::pdu::GfxParseError;
+
+const FLAG_GLYPH_INDEX: u8 = 0x01;
+const FLAG_GLYPH_HIT: u8 = 0x02;
+const FLAG_CACHE_RESET: u8 = 0x04;
+
+const GLYPH_CACHE_SIZE: usize = 4000;
+/// Largest bitmap (in pixels) the server may store as a glyph.
+const GLYPH_MAX_PIXELS: usize = 1024;
+const VBAR_CACHE_SIZE: usize = 32768;
+const SHORT_VBAR_CACHE_SIZE: usize = 16384;
+const VBAR_MAX_HEIGHT: usize = 52;
+
+const SUBCODEC_UNCOMPRESSED: u8 = 0;
+const SUBCODEC_NSCODEC: u8 = 1;
+const SUBCODEC_RLEX: u8 = 2;
+
+/// Decoder state that persists across ClearCodec bitmaps on a channel.
+pub struct ClearCodecDecoder {
+    /// Full-height V-bars as RGBA pixels.
+    vbars: Vec<Vec<u8>>,
+    vbar_cursor: usize,
+    /// Short V-bar pixel runs as RGBA pixels.
+    short_vbars: Vec<Vec<u8>>,
+    short_vbar_cursor: usize,
+    glyphs: Vec<Option<(u16, u16, Vec<u8>)>>,
+    expected_seq: Option<u8>,
+}
+
+impl Default for ClearCodecDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClearCodecDecoder {
+    pub fn new() -> Self {
+        Self {
+            vbars: vec![Vec::new(); VBAR_CACHE_SIZE],
+            vbar_cursor: 0,
+            short_vbars: vec![Vec::new(); SHORT_VBAR_CACHE_SIZE],
+            short_vbar_cursor: 0,
+            glyphs: vec![None; GLYPH_CACHE_SIZE],
+            expected_seq: None,
+        }
+    }
+
+    /// Drop every cache (e.g. on RDPGFX_RESET_GRAPHICS).
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Decode a ClearCodec bitmap of `width x height` on top of `dst`, which
+    /// holds the current RGBA content of the destination rectangle. Layers
+    /// the stream omits leave those pixels untouched.
+    pub fn decode(
+        &mut self,
+        data: &[u8],
+        width: u16,
+        height: u16,
+        dst: &mut [u8],
+    ) -> Result<(), GfxParseError> {
+        const ERR: &str = "ClearCodec header truncated";
+        let (w, h) = (width as usize, height as usize);
+        if dst.len() != w * h * 4 {
+            return Err(GfxParseError("ClearCodec destination size mismatch"));
+        }
+        let mut reader = ByteReader::new(data);
+        let flags = reader.u8(ERR)?;
+        let seq = reader.u8(ERR)?;
+        if let Some(expected) = self.expected_seq {
+            if seq != expected {
+                log::debug!("GFX: ClearCodec sequence {seq} (expected {expected}), resyncing");
+            }
+        }
+        self.expected_seq = Some(seq.wrapping_add(1));
+        if flags & FLAG_CACHE_RESET != 0 {
+            self.vbar_cursor = 0;
+            self.short_vbar_cursor = 0;
+        }
+
+        let glyph_index = if flags & FLAG_GLYPH_INDEX != 0 {
+            let index = reader.u16(ERR)? as usize;
+            if index >= GLYPH_CACHE_SIZE {
+                return Err(GfxParseError("ClearCodec glyph index out of range"));
+            }
+            Some(index)
+        } else {
+            None
+        };
+        if flags & FLAG_GLYPH_HIT != 0 {
+            let index = glyph_index.ok_or(GfxParseError("ClearCodec glyph hit without index"))?;
+            return match &self.glyphs[index] {
+                Some((gw, gh, pixels)) if (*gw as usize) * (*gh as usize) == w * h => {
+                    dst.copy_from_slice(pixels);
+                    Ok(())
+                }
+                Some(_) => Err(GfxParseError("ClearCodec glyph size mismatch")),
+                None => Err(GfxParseError("ClearCodec glyph cache miss")),
+            };
+        }
+
+        if reader.remaining() > 0 {
+            let residual_len = reader.u32(ERR)? as usize;
+            let bands_len = reader.u32(ERR)? as usize;
+            let subcodec_len = reader.u32(ERR)? as usize;
+            let residual = reader.bytes(residual_len, "ClearCodec residual truncated")?;
+            let bands = reader.bytes(bands_len, "ClearCodec bands truncated")?;
+            let subcodecs = reader.bytes(subcodec_len, "ClearCodec subcodecs truncated")?;
+            if !residual.is_empty() {
+                decode_residual(residual, dst)?;
+            }
+            if !bands.is_empty() {
+                self.decode_bands(bands, w, h, dst)?;
+            }
+            if !subcodecs.is_empty() {
+                decode_subcodecs(subcodecs, w, h, dst)?;
+            }
+        }
+
+        if let Some(index) = glyph_index {
+            if w * h <= GLYPH_MAX_PIXELS {
+                self.glyphs[index] = Some((width, height, dst.to_vec()));
+            } else {
+                log::debug!("GFX: ClearCodec glyph {index} too large to cache ({w}x{h})");
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_bands(
+        &mut self,
+        data: &[u8],
+        width: usize,
+        height: usize,
+        dst: &mut [u8],
+    ) -> Result<(), GfxParseError> {
+        const ERR: &str = "ClearCodec band truncated";
+        let mut reader = ByteReader::new(data);
+        while reader.remaining() > 0 {
+            let x_start = reader.u16(ERR)? as usize;
+            let x_end = reader.u16(ERR)? as usize;
+            let y_start = reader.u16(ERR)? as usize;
+            let y_end = reader.u16(ERR)? as usize;
+            let background = read_bgr(&mut reader, ERR)?;
+            if x_end < x_start || y_end < y_start {
+                return Err(GfxParseError("ClearCodec band has inverted bounds"));
+            }
+            let vbar_height = y_end - y_start + 1;
+            if vbar_height > VBAR_MAX_HEIGHT {
+                return Err(GfxParseError("ClearCodec band taller than 52 rows"));
+            }
+
+            for i in 0..=(x_end - x_start) {
+                let header = reader.u16(ERR)?;
+                let vbar = if header & 0x8000 != 0 {
+                    let entry = &self.vbars[(header & 0x7FFF) as usize];
+                    if entry.is_empty() {
+                        vec![0; vbar_height * 4]
+                    } else if entry.len() == vbar_height * 4 {
+                        entry.clone()
+                    } else {
+                        return Err(GfxParseError("ClearCodec V-bar height mismatch"));
+                    }
+                } else {
+                    let (y_on, short) = if header & 0xC000 == 0x4000 {
+                        let short = self.short_vbars[(header & 0x3FFF) as usize].clone();
+                        (reader.u8(ERR)? as usize, short)
+                    } else {
+                        let y_on = (header & 0xFF) as usize;
+                        let y_off = ((header >> 8) & 0x3F) as usize;
+                        if y_off < y_on || y_off > VBAR_MAX_HEIGHT {
+                            return Err(GfxParseError("ClearCodec short V-bar bounds invalid"));
+                        }
+                        let mut short = Vec::with_capacity((y_off - y_on) * 4);
+                        for _ in y_on..y_off {
+                            short.extend_from_slice(&read_bgr(&mut reader, ERR)?);
+                        }
+                        self.short_vbars[self.short_vbar_cursor] = short.clone();
+                        self.short_vbar_cursor =
+                            (self.short_vbar_cursor + 1) % SHORT_VBAR_CACHE_SIZE;
+                        (y_on, short)
+                    };
+                    if y_on * 4 + short.len() > vbar_height * 4 {
+                        return Err(GfxParseError("ClearCodec short V-bar overflows band"));
+                    }
+                    let mut vbar = background.repeat(vbar_height);
+                    vbar[y_on * 4..y_on * 4 + short.len()].copy_from_slice(&short);
+                    self.vbars[self.vbar_cursor] = vbar.clone();
+                    self.vbar_cursor = (self.vbar_cursor + 1) % VBAR_CACHE_SIZE;
+                    vbar
+                };
+
+                let x = x_start + i;
+                if x >= width {
+                    continue;
+                }
+                for (row, px) in vbar.as_chunks::<4>().0.iter().enumerate() {
+                    let y = y_start + row;
+                    if y >= height {
+                        break;
+                    }
+                    let off = (y * width + x) * 4;
+                    dst[off..off + 4].copy_from_slice(px);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_bgr(reader: &mut ByteReader<'_>, err: &'static str) -> Result<[u8; 4], GfxParseError> {
+    let b = reader.bytes(3, err)?;
+    Ok([b[2], b[1], b[0], 0xFF])
+}
+
+/// Read a ClearCodec run length: a byte, widened to u16 and then u32 when
+/// saturated.
+fn read_run_length(reader: &mut ByteReader<'_>, err: &'static str) -> Result<usize, GfxParseError> {
+    let short = reader.u8(err)?;
+    if short < 0xFF {
+        return Ok(short as usize);
+    }
+    let medium = reader.u16(err)?;
+    if medium < 0xFFFF {
+        return Ok(medium as usize);
+    }
+    Ok(reader.u32(err)? as usize)
+}
+
+fn decode_residual(data: &[u8], dst: &mut [u8]) -> Result<(), GfxParseError> {
+    const ERR: &str = "ClearCodec residual segment truncated";
+    let mut reader = ByteReader::new(data);
+    let total = dst.len() / 4;
+    let mut pos = 0;
+    while reader.remaining() > 0 {
+        let color = read_bgr(&mut reader, ERR)?;
+        let run = read_run_length(&mut reader, ERR)?;
+        if pos + run > total {
+            return Err(GfxParseError("ClearCodec residual overruns bitmap"));
+        }
+        for px in dst[pos * 4..(pos + run) * 4].as_chunks_mut::<4>().0 {
+            px.copy_from_slice(&color);
+        }
+        pos += run;
+    }
+    if pos != total {
+        return Err(GfxParseError("ClearCodec residual does not cover bitmap"));
+    }
+    Ok(())
+}
+
+fn decode_subcodecs(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    dst: &mut [u8],
+) -> Result<(), GfxParseError> {
+    const ERR: &str = "ClearCodec subcodec truncated";
+    let mut reader = ByteReader::new(data);
+    while reader.remaining() > 0 {
+        let x = reader.u16(ERR)? as usize;
+        let y = reader.u16(ERR)? as usize;
+        let w = reader.u16(ERR)?;
+        let h = reader.u16(ERR)?;
+        let len = reader.u32(ERR)? as usize;
+        let codec = reader.u8(ERR)?;
+        let payload = reader.bytes(len, ERR)?;
+        let (wu, hu) = (w as usize, h as usize);
+        if x + wu > width || y + hu > height {
+            return Err(GfxParseError("ClearCodec subcodec rect outside bitmap"));
+        }
+        let pixels = match codec {
+            SUBCODEC_UNCOMPRESSED => {
+                if payload.len() != wu * hu * 3 {
+                    return Err(GfxParseError("ClearCodec raw subcodec size mismatch"));
+                }
+                payload
+                    .as_chunks::<3>()
+                    .0
+                    .iter()
+                    .flat_map(|&[b, g, r]| [r, g, b, 0xFF])
+                    .collect()
+            }
+            SUBCODEC_NSCODEC => nsc::decode(payload, w, h)?,
+            SUBCODEC_RLEX => decode_rlex(payload, wu * hu)?,
+            _ => return Err(GfxParseError("ClearCodec subcodec id unknown")),
+        };
+        for row in 0..hu {
+            let off = ((y + row) * width + x) * 4;
+            dst[off..off + wu * 4].copy_from_slice(&pixels[row * wu * 4..(row + 1) * wu * 4]);
+        }
+    }
+    Ok(())
+}
+
+/// RLEX subcodec: palette runs followed by short ascending "suites" of
+/// palette entries.
+fn decode_rlex(data: &[u8], pixel_count: usize) -> Result<Vec<u8>, GfxParseError> {
+    const ERR: &str = "ClearCodec RLEX truncated";
+    let mut reader = ByteReader::new(data);
+    let palette_count = reader.u8(ERR)? as usize;
+    if palette_count == 0 || palette_count > 127 {
+        return Err(GfxParseError("ClearCodec RLEX palette size invalid"));
+    }
+    let palette = (0..palette_count)
+        .map(|_| read_bgr(&mut reader, ERR))
+        .collect::<Result<Vec<_>, _>>()?;
+    let num_bits = match palette_count - 1 {
+        0 => 1,
+        n => n.ilog2() + 1,
+    };
+    let index_mask = (1u8 << num_bits) - 1;
+
+    let mut out = Vec::with_capacity(pixel_count * 4);
+    while reader.remaining() > 0 {
+        let packed = reader.u8(ERR)?;
+        let stop = (packed & index_mask) as usize;
+        let suite_depth = (packed >> num_bits) as usize;
+        let run = read_run_length(&mut reader, ERR)?;
+        let start = stop
+            .checked_sub(suite_depth)
+            .ok_or(GfxParseError("ClearCodec RLEX suite underflows palette"))?;
+        if stop >= palette_count {
+            return Err(GfxParseError("ClearCodec RLEX index out of range"));
+        }
+        if out.len() / 4 + run + suite_depth + 1 > pixel_count {
+            return Err(GfxParseError("ClearCodec RLEX overruns bitmap"));
+        }
+        for _ in 0..run {
+            out.extend_from_slice(&palette[start]);
+        }
+        for color in &palette[start..=stop] {
+            out.extend_from_slice(color);
+        }
+    }
+    if out.len() != pixel_count * 4 {
+        return Err(GfxParseError("ClearCodec RLEX does not cover bitmap"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(flags: u8, seq: u8, glyph: Option<u16>, layers: [&[u8]; 3]) -> Vec<u8> {
+        let mut out = vec![flags, seq];
+        if let Some(index) = glyph {
+            out.extend_from_slice(&index.to_le_bytes());
+        }
+        for layer in layers {
+            out.extend_from_slice(&(layer.len() as u32).to_le_bytes());
+        }
+        for layer in layers {
+            out.extend_from_slice(layer);
+        }
+        out
+    }
+
+    const RED: [u8; 4] = [0xFF, 0, 0, 0xFF];
+    const BLUE: [u8; 4] = [0, 0, 0xFF, 0xFF];
+    const WHITE: [u8; 4] = [0xFF; 4];
+
+    fn px(dst: &[u8], width: usize, x: usize, y: usize) -> &[u8] {
+        &dst[(y * width + x) * 4..(y * width + x) * 4 + 4]
+    }
+
+    #[test]
+    fn residual_runs_fill_bitmap() {
+        // 4x2: 3 red, then 5 blue via the u16 run form.
+        let residual = [0, 0, 0xFF, 3, 0xFF, 0, 0, 0xFF, 5, 0];
+        let mut dst = vec![0u8; 4 * 2 * 4];
+        let mut codec = ClearCodecDecoder::new();
+        codec
+            .decode(&stream(0, 0, None, [&residual, &[], &[]]), 4, 2, &mut dst)
+            .unwrap();
+        assert_eq!(px(&dst, 4, 2, 0), RED);
+        assert_eq!(px(&dst, 4, 3, 0), BLUE);
+        assert_eq!(px(&dst, 4, 3, 1), BLUE);
+        // Short residual is rejected.
+        let mut codec = ClearCodecDecoder::new();
+        let short = stream(0, 0, None, [&residual[..4], &[], &[]]);
+        assert!(codec.decode(&short, 4, 2, &mut dst).is_err());
+    }
+
+    #[test]
+    fn bands_use_and_fill_vbar_caches() {
+        // Band over columns 0..=1, rows 1..=3 with a white background.
+        // Column 0: short miss, yOn 1, yOff 2 => one red pixel at row 2.
+        // Column 1: V-bar cache hit on entry 0 (the bar just built).
+        let mut bands = Vec::new();
+        for v in [0u16, 1, 1, 3] {
+            bands.extend_from_slice(&v.to_le_bytes());
+        }
+        bands.extend_from_slice(&[0xFF, 0xFF, 0xFF]);
+        bands.extend_from_slice(&(0x0201u16).to_le_bytes());
+        bands.extend_from_slice(&[0, 0, 0xFF]);
+        bands.extend_from_slice(&(0x8000u16).to_le_bytes());
+        let mut dst = vec![0u8; 3 * 4 * 4];
+        let mut codec = ClearCodecDecoder::new();
+        codec
+            .decode(&stream(0, 0, None, [&[], &bands, &[]]), 3, 4, &mut dst)
+            .unwrap();
+        for x in 0..2 {
+            assert_eq!(px(&dst, 3, x, 0), [0; 4], "row 0 untouched");
+            assert_eq!(px(&dst, 3, x, 1), WHITE);
+            assert_eq!(px(&dst, 3, x, 2), RED);
+            assert_eq!(px(&dst, 3, x, 3), WHITE);
+        }
+        assert_eq!(px(&dst, 3, 2, 2), [0; 4]);
+
+        // Next bitmap: short V-bar hit on entry 0 with yOn 0.
+        let mut bands = Vec::new();
+        for v in [0u16, 0, 0, 1] {
+            bands.extend_from_slice(&v.to_le_bytes());
+        }
+        bands.extend_from_slice(&[0, 0, 0]);
+        bands.extend_from_slice(&(0x4000u16).to_le_bytes());
+        bands.push(0);
+        let mut dst = vec![0u8; 4 * 2];
+        codec
+            .decode(&stream(0, 1, None, [&[], &bands, &[]]), 1, 2, &mut dst)
+            .unwrap();
+        assert_eq!(&dst[..4], RED);
+        assert_eq!(&dst[4..], [0, 0, 0, 0xFF]);
+    }
+
+    #[test]
+    fn subcodecs_raw_and_rlex() {
+        let mut subcodecs = Vec::new();
+        // Raw 1x1 blue at (0,0).
+        for v in [0u16, 0, 1, 1] {
+            subcodecs.extend_from_slice(&v.to_le_bytes());
+        }
+        subcodecs.extend_from_slice(&3u32.to_le_bytes());
+        subcodecs.push(SUBCODEC_UNCOMPRESSED);
+        subcodecs.extend_from_slice(&[0xFF, 0, 0]);
+        // RLEX 4x1 at (1,0): palette [red, blue, white], numBits 2.
+        // Segment: stop 2, depth 1 (start 1), run 1 => blue, blue, white.
+        // Segment: stop 0, depth 0, run 0 => red.
+        let rlex = [
+            3,
+            0,
+            0,
+            0xFF,
+            0xFF,
+            0,
+            0,
+            0xFF,
+            0xFF,
+            0xFF,
+            0b0000_0110,
+            1,
+            0,
+            0,
+        ];
+        for v in [1u16, 0, 4, 1] {
+            subcodecs.extend_from_slice(&v.to_le_bytes());
+        }
+        subcodecs.extend_from_slice(&(rlex.len() as u32).to_le_bytes());
+        subcodecs.push(SUBCODEC_RLEX);
+        subcodecs.extend_from_slice(&rlex);
+        let mut dst = vec![0u8; 5 * 4];
+        let mut codec = ClearCodecDecoder::new();
+        codec
+            .decode(&stream(0, 0, None, [&[], &[], &subcodecs]), 5, 1, &mut dst)
+            .unwrap();
+        let pixels: Vec<&[u8]> = dst.chunks(4).collect();
+        assert_eq!(pixels, [&BLUE[..], &BLUE, &BLUE, &WHITE, &RED]);
+    }
+
+    #[test]
+    fn glyph_cache_round_trip() {
+        let residual = [0, 0, 0xFF, 4];
+        let mut codec = ClearCodecDecoder::new();
+        let mut dst = vec![0u8; 2 * 2 * 4];
+        let data = stream(FLAG_GLYPH_INDEX, 0, Some(7), [&residual, &[], &[]]);
+        codec.decode(&data, 2, 2, &mut dst).unwrap();
+
+        let mut replay = vec![0u8; 2 * 2 * 4];
+        let hit = [FLAG_GLYPH_INDEX | FLAG_GLYPH_HIT, 1, 7, 0];
+        codec.decode(&hit, 2, 2, &mut replay).unwrap();
+        assert_eq!(replay, dst);
+        // Different size or unknown index fail.
+        assert!(codec.decode(&hit, 1, 1, &mut replay[..4]).is_err());
+        let miss = [FLAG_GLYPH_INDEX | FLAG_GLYPH_HIT, 2, 8, 0];
+        assert!(codec.decode(&miss, 2, 2, &mut replay).is_err());
+    }
+
+    #[test]
+    fn header_only_stream_keeps_destination() {
+        let mut dst = vec![9u8; 4];
+        ClearCodecDecoder::new()
+            .decode(&[0, 0], 1, 1, &mut dst)
+            .unwrap();
+        assert_eq!(dst, [9; 4]);
+    }
+}
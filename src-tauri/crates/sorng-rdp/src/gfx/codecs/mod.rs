@@ -0,0 +1,87 @@
+//! Software decoders for the non-H.264 RDPGFX codecs.
+//!
+//! Servers without AVC support (older Windows Server, xrdp, GPU-less VDI
+//! hosts) encode surface updates with ClearCodec, Planar, RemoteFX and
+//! RemoteFX Progressive instead. Every decoder here produces RGBA32 pixels in
+//! the same layout as [`super::surfaces::GfxSurface::rgba`].
+
+pub mod clear;
+pub mod nsc;
+pub mod planar;
+pub mod progressive;
+pub mod rfx;
+
+use super::pdu::GfxParseError;
+
+/// Little-endian reader over a codec bitstream. Every read is bounds-checked
+/// and fails with the supplied static error instead of panicking.
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    pub(crate) fn bytes(
+        &mut self,
+        len: usize,
+        err: &'static str,
+    ) -> Result<&'a [u8], GfxParseError> {
+        if self.remaining() < len {
+            return Err(GfxParseError(err));
+        }
+        let out = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(out)
+    }
+
+    pub(crate) fn u8(&mut self, err: &'static str) -> Result<u8, GfxParseError> {
+        Ok(self.bytes(1, err)?[0])
+    }
+
+    pub(crate) fn u16(&mut self, err: &'static str) -> Result<u16, GfxParseError> {
+        let b = self.bytes(2, err)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32(&mut self, err: &'static str) -> Result<u32, GfxParseError> {
+        let b = self.bytes(4, err)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// Clamp a widened intermediate colour value to a byte.
+#[inline]
+pub(crate) fn clamp_u8(v: i32) -> u8 {
+    v.clamp(0, 255) as u8
+}
+
+/// Convert a Y/Co/Cg triple (chroma already scaled back by the colour-loss
+/// shift) to RGB, per MS-RDPEGDI 3.1.9.1.2.
+#[inline]
+pub(crate) fn ycocg_to_rgb(y: u8, co: i32, cg: i32) -> [u8; 3] {
+    let y = y as i32;
+    [
+        clamp_u8(y + co - cg),
+        clamp_u8(y + cg),
+        clamp_u8(y - co - cg),
+    ]
+}
+
+/// Undo colour-loss reduction on a stored chroma byte: shift left by
+/// `ColorLossLevel - 1` and reinterpret as signed.
+#[inline]
+pub(crate) fn restore_chroma(value: u8, shift: u32) -> i32 {
+    ((value as u32) << shift) as u8 as i8 as i32
+}
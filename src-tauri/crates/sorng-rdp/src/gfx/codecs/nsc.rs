@@ -0,0 +1,170 @@
+//! NSCodec (MS-RDPNSC), used by ClearCodec as its subcodec 1.
+//!
+//! Four planes (luma, orange chroma, green chroma, alpha) each stored raw or
+//! with NSCodec's byte RLE, converted from YCoCg with optional 2x2 chroma
+//! subsampling.
+
+use super::{restore_chroma, ycocg_to_rgb, ByteReader};
+use crate::gfx::pdu::GfxParseError;
+
+/// Decode an NSCodec bitmap stream into `width * height` RGBA pixels.
+pub fn decode(data: &[u8], width: u16, height: u16) -> Result<Vec<u8>, GfxParseError> {
+    const ERR: &str = "NSCodec header truncated";
+    let (w, h) = (width as usize, height as usize);
+    let mut reader = ByteReader::new(data);
+    let plane_sizes = [
+        reader.u32(ERR)? as usize,
+        reader.u32(ERR)? as usize,
+        reader.u32(ERR)? as usize,
+        reader.u32(ERR)? as usize,
+    ];
+    let color_loss = reader.u8(ERR)? as u32;
+    let subsampled = reader.u8(ERR)? != 0;
+    reader.u16(ERR)?;
+    if !(1..=7).contains(&color_loss) {
+        return Err(GfxParseError("NSCodec colour loss level out of range"));
+    }
+
+    // Subsampled luma rows are padded to a multiple of 8 and chroma planes
+    // cover the padded size at half resolution.
+    let luma_stride = if subsampled { w.div_ceil(8) * 8 } else { w };
+    let chroma_stride = if subsampled { luma_stride / 2 } else { w };
+    let chroma_size = if subsampled {
+        chroma_stride * h.div_ceil(2)
+    } else {
+        w * h
+    };
+    let original_sizes = [luma_stride * h, chroma_size, chroma_size, w * h];
+
+    let mut planes: [Vec<u8>; 4] = Default::default();
+    for (plane, (&size, &original)) in planes
+        .iter_mut()
+        .zip(plane_sizes.iter().zip(&original_sizes))
+    {
+        let data = reader.bytes(size, "NSCodec plane data truncated")?;
+        *plane = if size == 0 {
+            vec![0xFF; original]
+        } else if size < original {
+            decode_rle(data, original)?
+        } else {
+            data[..original].to_vec()
+        };
+    }
+
+    let shift = color_loss - 1;
+    let mut rgba = vec![0u8; w * h * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let ci = if subsampled {
+                (y >> 1) * chroma_stride + (x >> 1)
+            } else {
+                y * w + x
+            };
+            let rgb = ycocg_to_rgb(
+                planes[0][y * luma_stride + x],
+                restore_chroma(planes[1][ci], shift),
+                restore_chroma(planes[2][ci], shift),
+            );
+            let i = y * w + x;
+            rgba[i * 4..i * 4 + 3].copy_from_slice(&rgb);
+            rgba[i * 4 + 3] = planes[3][i];
+        }
+    }
+    Ok(rgba)
+}
+
+/// NSCodec plane RLE (MS-RDPNSC 2.2.2.1): a repeated byte introduces a run
+/// whose length follows; the final four bytes are always stored raw.
+fn decode_rle(data: &[u8], original_size: usize) -> Result<Vec<u8>, GfxParseError> {
+    const ERR: &str = "NSCodec RLE plane truncated";
+    let mut reader = ByteReader::new(data);
+    let mut out = Vec::with_capacity(original_size);
+    let mut left = original_size;
+    while left > 4 {
+        let value = reader.u8(ERR)?;
+        if left == 5 {
+            out.push(value);
+            left -= 1;
+        } else if reader.peek() == Some(value) {
+            reader.u8(ERR)?;
+            let len = match reader.u8(ERR)? {
+                0xFF => reader.u32(ERR)? as usize,
+                n => n as usize + 2,
+            };
+            if len > left {
+                return Err(GfxParseError("NSCodec RLE run overruns plane"));
+            }
+            out.resize(out.len() + len, value);
+            left -= len;
+        } else {
+            out.push(value);
+            left -= 1;
+        }
+    }
+    out.extend_from_slice(reader.bytes(left, ERR)?);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(planes: [&[u8]; 4], color_loss: u8, subsampled: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        for p in planes {
+            out.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        }
+        out.extend_from_slice(&[color_loss, subsampled as u8, 0, 0]);
+        for p in planes {
+            out.extend_from_slice(p);
+        }
+        out
+    }
+
+    #[test]
+    fn raw_planes_with_default_alpha() {
+        // 2x1: Y 50/60, Co +4, Cg -2 (CLL 1), alpha plane omitted => 0xFF.
+        let co = [4u8, 4];
+        let cg = [(-2i8) as u8; 2];
+        let data = stream([&[50, 60], &co, &cg, &[]], 1, false);
+        assert_eq!(
+            decode(&data, 2, 1).unwrap(),
+            [56, 48, 48, 0xFF, 66, 58, 58, 0xFF]
+        );
+    }
+
+    #[test]
+    fn rle_plane_expands_runs_and_raw_tail() {
+        // 12 bytes: 7 x run(9) then literal 3, then raw tail 1,2,3,4.
+        let rle = [9, 9, 5, 3, 1, 2, 3, 4];
+        assert_eq!(
+            decode_rle(&rle, 12).unwrap(),
+            [9, 9, 9, 9, 9, 9, 9, 3, 1, 2, 3, 4]
+        );
+        // Long run form.
+        let rle = [7, 7, 0xFF, 10, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(decode_rle(&rle, 14).unwrap()[..10], [7; 10]);
+        // Run longer than the plane.
+        assert!(decode_rle(&[7, 7, 40, 0, 0, 0, 0], 10).is_err());
+    }
+
+    #[test]
+    fn subsampled_chroma_uses_padded_luma_stride() {
+        // 2x2, luma stride rounds up to 8; one chroma sample covers all four.
+        let mut luma = [0u8; 16];
+        luma[..2].copy_from_slice(&[10, 20]);
+        luma[8..10].copy_from_slice(&[30, 40]);
+        let chroma = [0u8; 4];
+        let data = stream([&luma, &chroma, &chroma, &[]], 1, true);
+        let rgba = decode(&data, 2, 2).unwrap();
+        let reds: Vec<u8> = rgba.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn bad_headers_are_rejected() {
+        assert!(decode(&[0; 19], 1, 1).is_err());
+        assert!(decode(&stream([&[], &[], &[], &[]], 0, false), 1, 1).is_err());
+        assert!(decode(&stream([&[1, 2], &[], &[], &[]], 1, false)[..21], 2, 1).is_err());
+    }
+}
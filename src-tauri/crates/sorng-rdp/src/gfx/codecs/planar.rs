@@ -0,0 +1,274 @@
+//! Planar codec (RDPGFX_CODECID_PLANAR) — MS-RDPEGDI 2.2.2.5.1 / 3.1.9.
+//!
+//! The bitmap is split into an optional alpha plane plus three colour planes,
+//! either ARGB or (with colour loss) AYCoCg with optional 2x2 chroma
+//! subsampling. Each plane is raw or RLE-compressed with scanline deltas.
+
+use super::{restore_chroma, ycocg_to_rgb, ByteReader};
+use crate::gfx::pdu::GfxParseError;
+
+const FORMAT_CLL_MASK: u8 = 0x07;
+const FORMAT_CS: u8 = 0x08;
+const FORMAT_RLE: u8 = 0x10;
+const FORMAT_NA: u8 = 0x20;
+
+/// Decode a planar bitmap stream into `width * height` RGBA pixels.
+pub fn decode(data: &[u8], width: u16, height: u16) -> Result<Vec<u8>, GfxParseError> {
+    let (w, h) = (width as usize, height as usize);
+    let mut reader = ByteReader::new(data);
+    let header = reader.u8("Planar format header missing")?;
+    let color_loss = (header & FORMAT_CLL_MASK) as u32;
+    let subsampled = header & FORMAT_CS != 0;
+    let rle = header & FORMAT_RLE != 0;
+    let no_alpha = header & FORMAT_NA != 0;
+    if subsampled && color_loss == 0 {
+        return Err(GfxParseError("Planar chroma subsampling requires YCoCg"));
+    }
+
+    let (cw, ch) = if subsampled {
+        (w.div_ceil(2), h.div_ceil(2))
+    } else {
+        (w, h)
+    };
+    let mut read_plane = |pw: usize, ph: usize| {
+        if rle {
+            decode_rle_plane(&mut reader, pw, ph)
+        } else {
+            Ok(reader
+                .bytes(pw * ph, "Planar raw plane truncated")?
+                .to_vec())
+        }
+    };
+    let alpha = if no_alpha {
+        None
+    } else {
+        Some(read_plane(w, h)?)
+    };
+    let luma_or_red = read_plane(w, h)?;
+    let orange_or_green = read_plane(cw, ch)?;
+    let green_or_blue = read_plane(cw, ch)?;
+    // Raw streams end with a pad byte, which carries no data.
+
+    let shift = color_loss.saturating_sub(1);
+    let mut rgba = vec![0u8; w * h * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let ci = if subsampled { (y / 2) * cw + x / 2 } else { i };
+            let rgb = if color_loss == 0 {
+                [luma_or_red[i], orange_or_green[ci], green_or_blue[ci]]
+            } else {
+                ycocg_to_rgb(
+                    luma_or_red[i],
+                    restore_chroma(orange_or_green[ci], shift),
+                    restore_chroma(green_or_blue[ci], shift),
+                )
+            };
+            let px = &mut rgba[i * 4..i * 4 + 4];
+            px[..3].copy_from_slice(&rgb);
+            px[3] = alpha.as_ref().map_or(0xFF, |a| a[i]);
+        }
+    }
+    Ok(rgba)
+}
+
+/// Decode one RDP6 RLE plane (MS-RDPEGDI 2.2.2.5.1.1). The first scanline
+/// holds absolute values; later scanlines hold sign-folded deltas from the
+/// scanline above.
+fn decode_rle_plane(
+    reader: &mut ByteReader<'_>,
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>, GfxParseError> {
+    let mut plane = vec![0u8; width * height];
+    for y in 0..height {
+        let row = y * width;
+        let mut x = 0;
+        // Last raw value (first scanline) or last delta (later scanlines);
+        // runs repeat it.
+        let mut value: i32 = 0;
+        while x < width {
+            let control = reader.u8("Planar RLE segment truncated")?;
+            let mut run = (control >> 4) as usize;
+            let mut raw = (control & 0x0F) as usize;
+            if run == 1 {
+                run = raw + 16;
+                raw = 0;
+            } else if run == 2 {
+                run = raw + 32;
+                raw = 0;
+            }
+            if x + raw + run > width {
+                return Err(GfxParseError("Planar RLE segment overruns scanline"));
+            }
+            for &byte in reader.bytes(raw, "Planar RLE raw bytes truncated")? {
+                value = if y == 0 {
+                    byte as i32
+                } else if byte & 1 != 0 {
+                    -((byte >> 1) as i32) - 1
+                } else {
+                    (byte >> 1) as i32
+                };
+                plane[row + x] = apply(&plane, row, width, x, y, value);
+                x += 1;
+            }
+            for _ in 0..run {
+                plane[row + x] = apply(&plane, row, width, x, y, value);
+                x += 1;
+            }
+        }
+    }
+    Ok(plane)
+}
+
+#[inline]
+fn apply(plane: &[u8], row: usize, width: usize, x: usize, y: usize, value: i32) -> u8 {
+    if y == 0 {
+        value as u8
+    } else {
+        (plane[row - width + x] as i32 + value) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode one plane as RLE using raw segments only, exercising the
+    /// scanline delta folding.
+    fn encode_rle_plane(plane: &[u8], width: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (y, row) in plane.chunks(width).enumerate() {
+            let values: Vec<u8> = row
+                .iter()
+                .enumerate()
+                .map(|(x, &v)| {
+                    if y == 0 {
+                        return v;
+                    }
+                    let delta = v.wrapping_sub(plane[(y - 1) * width + x]) as i8 as i32;
+                    if delta < 0 {
+                        (((-delta) - 1) * 2 + 1) as u8
+                    } else {
+                        (delta * 2) as u8
+                    }
+                })
+                .collect();
+            for chunk in values.chunks(15) {
+                out.push(chunk.len() as u8);
+                out.extend_from_slice(chunk);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn raw_argb_fixture() {
+        // 2x1, no RLE, alpha present: A plane, R plane, G plane, B plane, pad.
+        let data = [0x00, 0x80, 0xFF, 1, 2, 3, 4, 5, 6, 0x00];
+        let rgba = decode(&data, 2, 1).unwrap();
+        assert_eq!(rgba, [1, 3, 5, 0x80, 2, 4, 6, 0xFF]);
+    }
+
+    #[test]
+    fn rle_fixture_with_runs_and_deltas() {
+        // 4x2, RLE, no alpha, RGB.
+        let data = [
+            FORMAT_RLE | FORMAT_NA,
+            // Red: row 0 = raw 10 then a run of 3; row 1 = delta +1, repeated.
+            0x31,
+            10,
+            0x31,
+            2,
+            // Green: row 0 = run of 4 zeros with no raw (0x40); row 1 = raw
+            // deltas -1,+0,+1,+2 -> 255,0,1,2.
+            0x40,
+            0x04,
+            1,
+            0,
+            2,
+            4,
+            // Blue: row 0 = raw 7 then a run of 3; row 1 = a run of the
+            // initial zero delta.
+            0x31,
+            7,
+            0x40,
+        ];
+        let rgba = decode(&data, 4, 2).unwrap();
+        let px = |x: usize, y: usize| &rgba[(y * 4 + x) * 4..(y * 4 + x) * 4 + 4];
+        assert_eq!(px(0, 0), [10, 0, 7, 0xFF]);
+        assert_eq!(px(3, 0), [10, 0, 7, 0xFF]);
+        assert_eq!(px(0, 1), [11, 255, 7, 0xFF]);
+        assert_eq!(px(1, 1), [11, 0, 7, 0xFF]);
+        assert_eq!(px(3, 1), [11, 2, 7, 0xFF]);
+    }
+
+    #[test]
+    fn long_run_forms_expand() {
+        // 20x1 single plane of value 9: raw 9 then run 19 (0x13 = 16 + 3).
+        let plane = [0x01, 9, 0x13];
+        let mut reader = ByteReader::new(&plane);
+        assert_eq!(decode_rle_plane(&mut reader, 20, 1).unwrap(), vec![9; 20]);
+        let plane = [0x01, 9, 0x23];
+        let mut reader = ByteReader::new(&plane);
+        assert_eq!(decode_rle_plane(&mut reader, 36, 1).unwrap(), vec![9; 36]);
+    }
+
+    #[test]
+    fn rle_roundtrip_random_planes() {
+        let (w, h) = (37usize, 5usize);
+        let mut seed = 0x1234_5678u32;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        };
+        let planes: Vec<Vec<u8>> = (0..4)
+            .map(|_| (0..w * h).map(|_| next()).collect())
+            .collect();
+        let mut data = vec![FORMAT_RLE];
+        for plane in &planes {
+            data.extend(encode_rle_plane(plane, w));
+        }
+        let rgba = decode(&data, w as u16, h as u16).unwrap();
+        for i in 0..w * h {
+            assert_eq!(
+                &rgba[i * 4..i * 4 + 4],
+                [planes[1][i], planes[2][i], planes[3][i], planes[0][i]]
+            );
+        }
+    }
+
+    #[test]
+    fn ycocg_with_subsampling() {
+        // 2x2, raw, no alpha, CLL=1 (shift 0), chroma subsampled to 1x1.
+        // Y=100 everywhere, Co=+20, Cg=-10 => R=130, G=90, B=90.
+        let data = [
+            FORMAT_NA | FORMAT_CS | 1,
+            100,
+            100,
+            100,
+            100,
+            20,
+            (-10i8) as u8,
+            0,
+        ];
+        let rgba = decode(&data, 2, 2).unwrap();
+        for px in rgba.chunks(4) {
+            assert_eq!(px, [130, 90, 90, 0xFF]);
+        }
+        // Colour loss level 3 shifts chroma left by 2.
+        let data = [FORMAT_NA | 3, 100, 5, (-3i8) as u8, 0];
+        assert_eq!(decode(&data, 1, 1).unwrap(), [132, 88, 92, 0xFF]);
+    }
+
+    #[test]
+    fn malformed_streams_are_rejected() {
+        assert!(decode(&[], 1, 1).is_err());
+        assert!(decode(&[FORMAT_NA, 1, 2], 1, 1).is_err());
+        assert!(decode(&[FORMAT_NA | FORMAT_CS, 1, 2, 3, 0], 1, 1).is_err());
+        // Segment longer than the scanline.
+        assert!(decode(&[FORMAT_RLE | FORMAT_NA, 0x50], 4, 1).is_err());
+    }
+}
@@ -0,0 +1,540 @@
+//! RemoteFX Progressive codec (RDPGFX_CODECID_CAPROGRESSIVE) — MS-RDPEGFX
+//! 2.2.4.2.
+//!
+//! Carried in WireToSurface2 PDUs. The surface is split into 64x64 tiles;
+//! a tile arrives as a simple (single pass) or first progressive pass and may
+//! then be refined by upgrade passes that add more precision to the cached
+//! DWT coefficients. One [`ProgressiveContext`] holds that tile cache for a
+//! codec context on a surface.
+
+use std::collections::HashMap;
+
+use super::rfx::{self, BitReader, Quant, SrlDecoder, TILE_COEFFS, TILE_SIZE};
+use super::ByteReader;
+use crate::gfx::pdu::{GfxParseError, GfxRect16};
+
+const BLOCK_SYNC: u16 = 0xCCC0;
+const BLOCK_FRAME_BEGIN: u16 = 0xCCC1;
+const BLOCK_FRAME_END: u16 = 0xCCC2;
+const BLOCK_CONTEXT: u16 = 0xCCC3;
+const BLOCK_REGION: u16 = 0xCCC4;
+const BLOCK_TILE_SIMPLE: u16 = 0xCCC5;
+const BLOCK_TILE_FIRST: u16 = 0xCCC6;
+const BLOCK_TILE_UPGRADE: u16 = 0xCCC7;
+
+const SYNC_MAGIC: u32 = 0xCACC_ACCA;
+const SYNC_VERSION: u16 = 0x0100;
+const REGION_REDUCE_EXTRAPOLATE: u8 = 0x01;
+const TILE_DIFFERENCE: u8 = 0x01;
+/// Quality index meaning "full quality" (no progressive quantization).
+const QUALITY_FULL: u8 = 0xFF;
+
+/// `RFX_PROGRESSIVE_CODEC_QUANT`: extra quantization for one quality level.
+#[derive(Debug, Clone, Copy, Default)]
+struct ProgQuant {
+    components: [Quant; 3],
+}
+
+/// Cached state for one 64x64 tile.
+struct Tile {
+    /// Dequantized DWT coefficients per component (Y, Cb, Cr).
+    current: Vec<i16>,
+    /// Coefficient signs from the first pass; zero means "still unknown".
+    sign: Vec<i16>,
+    /// Bit position reached per component and band.
+    bit_pos: [[u8; 10]; 3],
+    rgba: Vec<u8>,
+}
+
+impl Tile {
+    fn new() -> Self {
+        Self {
+            current: vec![0; 3 * TILE_COEFFS],
+            sign: vec![0; 3 * TILE_COEFFS],
+            bit_pos: [[0; 10]; 3],
+            rgba: vec![0; TILE_COEFFS * 4],
+        }
+    }
+
+    /// Run the inverse DWT over a copy of the coefficients and refresh the
+    /// tile's RGBA pixels.
+    fn reconstruct(&mut self, extrapolate: bool) {
+        let mut planes = self.current.clone();
+        for plane in planes.as_chunks_mut::<TILE_COEFFS>().0 {
+            rfx::idwt_2d(plane, extrapolate);
+        }
+        let (y, rest) = planes.split_at(TILE_COEFFS);
+        let (cb, cr) = rest.split_at(TILE_COEFFS);
+        rfx::ycbcr_to_rgba(y, cb, cr, &mut self.rgba);
+    }
+}
+
+/// Per-region parameters needed while decoding its tiles.
+struct Region {
+    rects: Vec<GfxRect16>,
+    quants: Vec<Quant>,
+    prog_quants: Vec<ProgQuant>,
+    extrapolate: bool,
+}
+
+impl Region {
+    fn quant(&self, index: u8) -> Result<&Quant, GfxParseError> {
+        self.quants
+            .get(index as usize)
+            .ok_or(GfxParseError("Progressive quant index out of range"))
+    }
+
+    fn prog_quant(&self, quality: u8) -> Result<ProgQuant, GfxParseError> {
+        if quality == QUALITY_FULL {
+            return Ok(ProgQuant::default());
+        }
+        self.prog_quants
+            .get(quality as usize)
+            .copied()
+            .ok_or(GfxParseError("Progressive quality index out of range"))
+    }
+
+    /// Per-component bit positions for a tile's quant indices and quality.
+    fn bit_pos(&self, quant_idx: [u8; 3], quality: u8) -> Result<[[u8; 10]; 3], GfxParseError> {
+        let prog = self.prog_quant(quality)?;
+        let mut out = [[0; 10]; 3];
+        for (c, bit_pos) in out.iter_mut().enumerate() {
+            *bit_pos = self.quant(quant_idx[c])?.add(&prog.components[c]);
+        }
+        Ok(out)
+    }
+}
+
+/// Tile cache and stream state for one progressive codec context.
+#[derive(Default)]
+pub struct ProgressiveContext {
+    tiles: HashMap<(u16, u16), Tile>,
+}
+
+impl ProgressiveContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached tiles.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Decode one WireToSurface2 bitmap stream onto an RGBA surface of
+    /// `width x height`, returning the surface rects that changed.
+    pub fn decode(
+        &mut self,
+        data: &[u8],
+        surface: &mut [u8],
+        width: u16,
+        height: u16,
+    ) -> Result<Vec<GfxRect16>, GfxParseError> {
+        let mut updated = Vec::new();
+        let mut reader = ByteReader::new(data);
+        while reader.remaining() > 0 {
+            let (block_type, mut body) = read_block(&mut reader)?;
+            match block_type {
+                BLOCK_SYNC => {
+                    let magic = body.u32("Progressive sync truncated")?;
+                    let version = body.u16("Progressive sync truncated")?;
+                    if magic != SYNC_MAGIC || version != SYNC_VERSION {
+                        return Err(GfxParseError("Progressive sync magic/version mismatch"));
+                    }
+                }
+                BLOCK_CONTEXT => {
+                    body.u8("Progressive context truncated")?;
+                    let tile_size = body.u16("Progressive context truncated")?;
+                    if tile_size as usize != TILE_SIZE {
+                        return Err(GfxParseError("Progressive context tile size is not 64"));
+                    }
+                }
+                BLOCK_FRAME_BEGIN | BLOCK_FRAME_END => {}
+                BLOCK_REGION => {
+                    updated.extend(self.decode_region(&mut body, surface, width, height)?);
+                }
+                _ => return Err(GfxParseError("Progressive block type unknown")),
+            }
+        }
+        Ok(updated)
+    }
+
+    fn decode_region(
+        &mut self,
+        body: &mut ByteReader<'_>,
+        surface: &mut [u8],
+        width: u16,
+        height: u16,
+    ) -> Result<Vec<GfxRect16>, GfxParseError> {
+        const ERR: &str = "Progressive region truncated";
+        if body.u8(ERR)? as usize != TILE_SIZE {
+            return Err(GfxParseError("Progressive region tile size is not 64"));
+        }
+        let num_rects = body.u16(ERR)?;
+        let num_quant = body.u8(ERR)?;
+        let num_prog_quant = body.u8(ERR)?;
+        let flags = body.u8(ERR)?;
+        let num_tiles = body.u16(ERR)?;
+        let tile_data_size = body.u32(ERR)? as usize;
+
+        let mut rects = Vec::with_capacity(num_rects as usize);
+        for _ in 0..num_rects {
+            let b = body.bytes(8, ERR)?;
+            let (x, y) = (
+                u16::from_le_bytes([b[0], b[1]]),
+                u16::from_le_bytes([b[2], b[3]]),
+            );
+            let (w, h) = (
+                u16::from_le_bytes([b[4], b[5]]),
+                u16::from_le_bytes([b[6], b[7]]),
+            );
+            rects.push(GfxRect16 {
+                left: x.min(width),
+                top: y.min(height),
+                right: x.saturating_add(w).min(width),
+                bottom: y.saturating_add(h).min(height),
+            });
+        }
+        let quants = (0..num_quant)
+            .map(|_| body.bytes(5, ERR).map(Quant::parse))
+            .collect::<Result<Vec<_>, _>>()?;
+        let prog_quants = (0..num_prog_quant)
+            .map(|_| {
+                let b = body.bytes(16, ERR)?;
+                Ok(ProgQuant {
+                    components: [
+                        Quant::parse(&b[1..6]),
+                        Quant::parse(&b[6..11]),
+                        Quant::parse(&b[11..16]),
+                    ],
+                })
+            })
+            .collect::<Result<Vec<_>, GfxParseError>>()?;
+        let region = Region {
+            rects,
+            quants,
+            prog_quants,
+            extrapolate: flags & REGION_REDUCE_EXTRAPOLATE != 0,
+        };
+
+        let mut tiles = ByteReader::new(body.bytes(tile_data_size, ERR)?);
+        for _ in 0..num_tiles {
+            let (block_type, mut tile) = read_block(&mut tiles)?;
+            let key = match block_type {
+                BLOCK_TILE_SIMPLE => self.decode_tile_first(&region, &mut tile, false)?,
+                BLOCK_TILE_FIRST => self.decode_tile_first(&region, &mut tile, true)?,
+                BLOCK_TILE_UPGRADE => self.decode_tile_upgrade(&region, &mut tile)?,
+                _ => return Err(GfxParseError("Progressive tile block type unknown")),
+            };
+            self.blit_tile(key, &region.rects, surface, width, height);
+        }
+        Ok(region
+            .rects
+            .into_iter()
+            .filter(|r| r.right > r.left && r.bottom > r.top)
+            .collect())
+    }
+
+    /// Decode a TILE_SIMPLE or TILE_FIRST block, replacing (or, with
+    /// coefficient differencing, adding to) the cached coefficients.
+    fn decode_tile_first(
+        &mut self,
+        region: &Region,
+        body: &mut ByteReader<'_>,
+        has_quality: bool,
+    ) -> Result<(u16, u16), GfxParseError> {
+        const ERR: &str = "Progressive tile truncated";
+        let quant_idx = [body.u8(ERR)?, body.u8(ERR)?, body.u8(ERR)?];
+        let key = (body.u16(ERR)?, body.u16(ERR)?);
+        let flags = body.u8(ERR)?;
+        let quality = if has_quality {
+            body.u8(ERR)?
+        } else {
+            QUALITY_FULL
+        };
+        let lens = [body.u16(ERR)?, body.u16(ERR)?, body.u16(ERR)?];
+        let _tail_len = body.u16(ERR)?;
+        let bit_pos = region.bit_pos(quant_idx, quality)?;
+        let coeff_diff = flags & TILE_DIFFERENCE != 0;
+        let layout = rfx::band_layout(region.extrapolate);
+
+        let tile = self.tiles.entry(key).or_insert_with(Tile::new);
+        let mut buffer = vec![0i16; TILE_COEFFS];
+        for (c, len) in lens.into_iter().enumerate() {
+            let data = body.bytes(len as usize, ERR)?;
+            rfx::rlgr1_decode(data, &mut buffer);
+            let range = c * TILE_COEFFS..(c + 1) * TILE_COEFFS;
+            tile.sign[range.clone()].copy_from_slice(&buffer);
+
+            let (ll3, ll3_len) = layout[9];
+            rfx::differential_decode(&mut buffer[ll3..ll3 + ll3_len]);
+            rfx::dequantize(&mut buffer, &bit_pos[c], region.extrapolate);
+            let current = &mut tile.current[range];
+            if coeff_diff {
+                for (cur, &v) in current.iter_mut().zip(&buffer) {
+                    *cur = cur.wrapping_add(v);
+                }
+            } else {
+                current.copy_from_slice(&buffer);
+            }
+        }
+        tile.bit_pos = bit_pos;
+        tile.reconstruct(region.extrapolate);
+        Ok(key)
+    }
+
+    /// Decode a TILE_UPGRADE block, refining a previously decoded tile.
+    fn decode_tile_upgrade(
+        &mut self,
+        region: &Region,
+        body: &mut ByteReader<'_>,
+    ) -> Result<(u16, u16), GfxParseError> {
+        const ERR: &str = "Progressive upgrade tile truncated";
+        let quant_idx = [body.u8(ERR)?, body.u8(ERR)?, body.u8(ERR)?];
+        let key = (body.u16(ERR)?, body.u16(ERR)?);
+        let quality = body.u8(ERR)?;
+        let mut lens = [0u16; 6];
+        for len in &mut lens {
+            *len = body.u16(ERR)?;
+        }
+        let bit_pos = region.bit_pos(quant_idx, quality)?;
+        let tile = self.tiles.get_mut(&key).ok_or(GfxParseError(
+            "Progressive upgrade for a tile never decoded",
+        ))?;
+        let layout = rfx::band_layout(region.extrapolate);
+
+        for c in 0..3 {
+            let srl_data = body.bytes(lens[c * 2] as usize, ERR)?;
+            let raw_data = body.bytes(lens[c * 2 + 1] as usize, ERR)?;
+            let mut srl = SrlDecoder::new(srl_data);
+            let mut raw = BitReader::new(raw_data);
+            let base = c * TILE_COEFFS;
+            for (band, &(offset, len)) in layout.iter().enumerate() {
+                let new_pos = bit_pos[c][band];
+                let num_bits = tile.bit_pos[c][band].saturating_sub(new_pos) as u32;
+                if num_bits == 0 {
+                    continue;
+                }
+                let shift = new_pos.saturating_sub(1) as u32;
+                let is_ll3 = band == 9;
+                for i in base + offset..base + offset + len {
+                    let input = if is_ll3 || tile.sign[i] > 0 {
+                        raw.bits(num_bits) as i32
+                    } else if tile.sign[i] < 0 {
+                        -(raw.bits(num_bits) as i32)
+                    } else {
+                        let v = srl.read(num_bits);
+                        tile.sign[i] = v;
+                        v as i32
+                    };
+                    tile.current[i] = tile.current[i].wrapping_add((input << shift) as i16);
+                }
+            }
+        }
+        tile.bit_pos = bit_pos;
+        tile.reconstruct(region.extrapolate);
+        Ok(key)
+    }
+
+    /// Copy a tile's pixels onto the surface, clipped to the region rects.
+    fn blit_tile(
+        &self,
+        key: (u16, u16),
+        rects: &[GfxRect16],
+        surface: &mut [u8],
+        width: u16,
+        height: u16,
+    ) {
+        if let Some(tile) = self.tiles.get(&key) {
+            rfx::blit_tile(
+                &tile.rgba,
+                key.0 as usize * TILE_SIZE,
+                key.1 as usize * TILE_SIZE,
+                rects,
+                surface,
+                width,
+                height,
+            );
+        }
+    }
+}
+
+/// Read a `blockType`/`blockLen` framed block, returning a reader over its
+/// body.
+fn read_block<'a>(reader: &mut ByteReader<'a>) -> Result<(u16, ByteReader<'a>), GfxParseError> {
+    let block_type = reader.u16("Progressive block header truncated")?;
+    let block_len = reader.u32("Progressive block header truncated")? as usize;
+    if block_len < 6 {
+        return Err(GfxParseError("Progressive block length too small"));
+    }
+    let body = reader.bytes(block_len - 6, "Progressive block body truncated")?;
+    Ok((block_type, ByteReader::new(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::codecs::rfx::tests::{rlgr1_encode, BitWriter};
+
+    fn block(block_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut out = block_type.to_le_bytes().to_vec();
+        out.extend_from_slice(&(body.len() as u32 + 6).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// Quant with every band at 6 (a shift of 5 at full quality).
+    const QUANT_6: [u8; 5] = [0x66; 5];
+
+    fn region(rects: &[[u16; 4]], prog: &[[u8; 16]], flags: u8, tiles: &[Vec<u8>]) -> Vec<u8> {
+        let tile_data: Vec<u8> = tiles.concat();
+        let mut body = vec![64];
+        body.extend_from_slice(&(rects.len() as u16).to_le_bytes());
+        body.push(1);
+        body.push(prog.len() as u8);
+        body.push(flags);
+        body.extend_from_slice(&(tiles.len() as u16).to_le_bytes());
+        body.extend_from_slice(&(tile_data.len() as u32).to_le_bytes());
+        for rect in rects {
+            for v in rect {
+                body.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        body.extend_from_slice(&QUANT_6);
+        for p in prog {
+            body.extend_from_slice(p);
+        }
+        body.extend_from_slice(&tile_data);
+        block(BLOCK_REGION, &body)
+    }
+
+    /// Y component whose LL3 band is `dc` everywhere (differentially coded).
+    fn dc_component(dc: i16, extrapolate: bool) -> Vec<u8> {
+        let mut coeffs = vec![0i16; TILE_COEFFS];
+        coeffs[rfx::band_layout(extrapolate)[9].0] = dc;
+        rlgr1_encode(&coeffs)
+    }
+
+    fn tile_first(
+        block_type: u16,
+        x: u16,
+        y: u16,
+        quality: Option<u8>,
+        comps: [&[u8]; 3],
+    ) -> Vec<u8> {
+        let mut body = vec![0, 0, 0];
+        body.extend_from_slice(&x.to_le_bytes());
+        body.extend_from_slice(&y.to_le_bytes());
+        body.push(0);
+        if let Some(q) = quality {
+            body.push(q);
+        }
+        for c in comps {
+            body.extend_from_slice(&(c.len() as u16).to_le_bytes());
+        }
+        body.extend_from_slice(&0u16.to_le_bytes());
+        for c in comps {
+            body.extend_from_slice(c);
+        }
+        block(block_type, &body)
+    }
+
+    fn frame(blocks: &[Vec<u8>]) -> Vec<u8> {
+        let mut sync = SYNC_MAGIC.to_le_bytes().to_vec();
+        sync.extend_from_slice(&SYNC_VERSION.to_le_bytes());
+        let mut out = block(BLOCK_SYNC, &sync);
+        out.extend(block(BLOCK_CONTEXT, &[0, 64, 0, 0]));
+        out.extend(block(BLOCK_FRAME_BEGIN, &[0, 0, 0, 0, 1, 0]));
+        for b in blocks {
+            out.extend_from_slice(b);
+        }
+        out.extend(block(BLOCK_FRAME_END, &[]));
+        out
+    }
+
+    fn pixel(surface: &[u8], width: usize, x: usize, y: usize) -> &[u8] {
+        &surface[(y * width + x) * 4..(y * width + x) * 4 + 4]
+    }
+
+    #[test]
+    fn simple_tile_fills_clipped_region() {
+        // Y LL3 = 64 at shift 5 => luma +2048 in 11.5 fixed point => 192.
+        for extrapolate in [false, true] {
+            let y = dc_component(64, extrapolate);
+            let tile = tile_first(BLOCK_TILE_SIMPLE, 1, 0, None, [&y, &[], &[]]);
+            let flags = if extrapolate {
+                REGION_REDUCE_EXTRAPOLATE
+            } else {
+                0
+            };
+            let data = frame(&[region(&[[70, 2, 10, 4]], &[], flags, &[tile])]);
+            let (w, h) = (100u16, 10u16);
+            let mut surface = vec![0u8; w as usize * h as usize * 4];
+            let mut ctx = ProgressiveContext::new();
+            let rects = ctx.decode(&data, &mut surface, w, h).unwrap();
+            assert_eq!(rects.len(), 1);
+            assert_eq!((rects[0].left, rects[0].right), (70, 80));
+            assert_eq!(pixel(&surface, 100, 70, 2), [192, 192, 192, 0xFF]);
+            assert_eq!(pixel(&surface, 100, 79, 5), [192, 192, 192, 0xFF]);
+            assert_eq!(pixel(&surface, 100, 69, 2), [0, 0, 0, 0]);
+            assert_eq!(pixel(&surface, 100, 70, 6), [0, 0, 0, 0]);
+            assert_eq!(ctx.tile_count(), 1);
+        }
+    }
+
+    #[test]
+    fn first_pass_then_upgrade_refines_tile() {
+        // Quality 0 adds 2 to the LL3 quant, so the first pass lands at shift
+        // 7 and the full-quality upgrade supplies two more raw bits at shift 5.
+        let mut prog = [0u8; 16];
+        prog[1] = 0x02;
+        let y = dc_component(1, true);
+        let first = tile_first(BLOCK_TILE_FIRST, 0, 0, Some(0), [&y, &[], &[]]);
+        let data = frame(&[region(&[[0, 0, 8, 8]], &[prog], 1, &[first])]);
+        let mut surface = vec![0u8; 8 * 8 * 4];
+        let mut ctx = ProgressiveContext::new();
+        ctx.decode(&data, &mut surface, 8, 8).unwrap();
+        // 1 << 7 = 128 => +4.
+        assert_eq!(pixel(&surface, 8, 3, 3), [132, 132, 132, 0xFF]);
+
+        let mut raw = BitWriter::default();
+        for _ in 0..81 {
+            raw.put(0b11, 2);
+        }
+        let mut body = vec![0, 0, 0, 0, 0, 0, 0, QUALITY_FULL];
+        for len in [0, raw.bytes.len(), 0, 0, 0, 0] {
+            body.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        body.extend_from_slice(&raw.bytes);
+        let upgrade = block(BLOCK_TILE_UPGRADE, &body);
+        let data = frame(&[region(&[[0, 0, 8, 8]], &[prog], 1, &[upgrade])]);
+        ctx.decode(&data, &mut surface, 8, 8).unwrap();
+        // 128 + (3 << 5) = 224 => +7.
+        assert_eq!(pixel(&surface, 8, 3, 3), [135, 135, 135, 0xFF]);
+    }
+
+    #[test]
+    fn malformed_streams_are_rejected() {
+        let mut ctx = ProgressiveContext::new();
+        let mut surface = vec![0u8; 64 * 64 * 4];
+        // Upgrade without a first pass.
+        let mut body = [0u8; 20];
+        body[7] = QUALITY_FULL;
+        let upgrade = block(BLOCK_TILE_UPGRADE, &body);
+        let data = frame(&[region(&[[0, 0, 64, 64]], &[], 0, &[upgrade])]);
+        assert!(ctx.decode(&data, &mut surface, 64, 64).is_err());
+        // Bad sync magic.
+        assert!(ctx
+            .decode(&block(BLOCK_SYNC, &[0; 6]), &mut surface, 64, 64)
+            .is_err());
+        // Truncated block body.
+        assert!(ctx
+            .decode(&[0xC1, 0xCC, 20, 0, 0, 0], &mut surface, 64, 64)
+            .is_err());
+        // Quant index past the region's quant table.
+        let mut tile = tile_first(BLOCK_TILE_SIMPLE, 0, 0, None, [&[], &[], &[]]);
+        tile[6] = 3;
+        let data = frame(&[region(&[[0, 0, 64, 64]], &[], 0, &[tile])]);
+        assert!(ctx.decode(&data, &mut surface, 64, 64).is_err());
+    }
+}
@@ -0,0 +1,1121 @@
+//! RemoteFX (RDPGFX_CODECID_CAVIDEO) — MS-RDPRFX.
+//!
+//! Carried in WireToSurface1 PDUs as a stream of RemoteFX messages: a
+//! region of clipping rects and a tileset of 64x64 tiles, each tile three
+//! entropy-coded components of DWT coefficients. [`RfxContext`] decodes
+//! those messages; the building blocks below (RLGR entropy decoding, the
+//! SRL/raw upgrade bitstreams, band quantization, the two inverse DWT
+//! variants and YCbCr to RGB conversion, MS-RDPRFX 3.1.8.1) are shared with
+//! the progressive codec.
+
+use super::{clamp_u8, ByteReader};
+use crate::gfx::pdu::{GfxParseError, GfxRect16};
+
+/// Width and height of a tile.
+pub const TILE_SIZE: usize = 64;
+
+/// Coefficients per 64x64 tile component.
+pub const TILE_COEFFS: usize = 4096;
+
+const LSGR: u32 = 3;
+const KPMAX: u32 = 80;
+const UP_GR: u32 = 4;
+const DN_GR: u32 = 6;
+const UQ_GR: u32 = 3;
+const DQ_GR: u32 = 3;
+
+/// MSB-first bit reader. Reads past the end yield zero bits so a truncated
+/// stream degrades into zero coefficients rather than an error.
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        (self.data.len() * 8).saturating_sub(self.pos)
+    }
+
+    pub(crate) fn bit(&mut self) -> u32 {
+        let bit = self
+            .data
+            .get(self.pos / 8)
+            .map_or(0, |b| (b >> (7 - self.pos % 8)) & 1);
+        self.pos += 1;
+        bit as u32
+    }
+
+    pub(crate) fn bits(&mut self, count: u32) -> u32 {
+        (0..count).fold(0, |acc, _| (acc << 1) | self.bit())
+    }
+
+    /// Count (and consume) consecutive `value` bits, stopping at the end of
+    /// the stream.
+    fn run_of(&mut self, value: u32) -> u32 {
+        let mut count = 0;
+        while self.remaining() > 0 && self.peek() == value {
+            self.pos += 1;
+            count += 1;
+        }
+        count
+    }
+
+    fn peek(&self) -> u32 {
+        self.data
+            .get(self.pos / 8)
+            .map_or(0, |b| ((b >> (7 - self.pos % 8)) & 1) as u32)
+    }
+}
+
+fn update(param: &mut u32, delta: i32) -> u32 {
+    *param = (*param as i32 + delta).clamp(0, KPMAX as i32) as u32;
+    *param >> LSGR
+}
+
+/// Read a Golomb-Rice code with parameter `kr`, adapting `krp`.
+fn read_gr(reader: &mut BitReader<'_>, kr: &mut u32, krp: &mut u32) -> u32 {
+    let vk = reader.run_of(1);
+    reader.bit();
+    let code = reader.bits(*kr) | (vk << *kr);
+    if vk == 0 {
+        *kr = update(krp, -2);
+    } else if vk > 1 {
+        *kr = update(krp, vk as i32);
+    }
+    code
+}
+
+/// The entropy coder of a tile stream. RLGR1 and RLGR3 only differ in how
+/// Golomb-Rice mode codes values: one per code, or two packed together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rlgr {
+    Rlgr1,
+    Rlgr3,
+}
+
+/// Decode an RLGR1 stream into `out`, zero-filling whatever the stream does
+/// not cover.
+pub fn rlgr1_decode(data: &[u8], out: &mut [i16]) {
+    rlgr_decode(data, out, Rlgr::Rlgr1);
+}
+
+/// Decode an RLGR stream into `out`, zero-filling whatever the stream does
+/// not cover.
+pub fn rlgr_decode(data: &[u8], out: &mut [i16], mode: Rlgr) {
+    let mut reader = BitReader::new(data);
+    let (mut k, mut kp) = (1u32, 1u32 << LSGR);
+    let (mut kr, mut krp) = (1u32, 1u32 << LSGR);
+    let mut idx = 0;
+
+    while reader.remaining() > 0 && idx < out.len() {
+        if k > 0 {
+            // Run-length mode: a unary count of full runs, then k remainder bits.
+            let mut run = 0usize;
+            for _ in 0..reader.run_of(0) {
+                run += 1 << k;
+                k = update(&mut kp, UP_GR as i32);
+            }
+            if reader.remaining() == 0 {
+                break;
+            }
+            reader.bit();
+            run += reader.bits(k) as usize;
+            let negative = reader.bit() == 1;
+            let code = read_gr(&mut reader, &mut kr, &mut krp);
+            k = update(&mut kp, -(DN_GR as i32));
+
+            let zeros = run.min(out.len() - idx);
+            out[idx..idx + zeros].fill(0);
+            idx += zeros;
+            if idx < out.len() {
+                let magnitude = (code + 1) as i32;
+                out[idx] = if negative { -magnitude } else { magnitude } as i16;
+                idx += 1;
+            }
+        } else if mode == Rlgr::Rlgr1 {
+            // Golomb-Rice mode over the folded 2*|v| - sign value.
+            let code = read_gr(&mut reader, &mut kr, &mut krp);
+            k = if code == 0 {
+                update(&mut kp, UQ_GR as i32)
+            } else {
+                update(&mut kp, -(DQ_GR as i32))
+            };
+            out[idx] = unfold(code);
+            idx += 1;
+        } else {
+            // RLGR3 codes the sum of two folded values; the first follows in
+            // as many bits as the sum needs.
+            let code = read_gr(&mut reader, &mut kr, &mut krp);
+            let first = reader.bits(u32::BITS - code.leading_zeros());
+            let second = code.saturating_sub(first);
+            if first != 0 && second != 0 {
+                k = update(&mut kp, -2 * DQ_GR as i32);
+            } else if first == 0 && second == 0 {
+                k = update(&mut kp, 2 * UQ_GR as i32);
+            }
+            for value in [first, second] {
+                if idx < out.len() {
+                    out[idx] = unfold(value);
+                    idx += 1;
+                }
+            }
+        }
+    }
+    out[idx..].fill(0);
+}
+
+/// Undo the 2*|v| - sign folding of Golomb-Rice mode.
+fn unfold(code: u32) -> i16 {
+    if code & 1 != 0 {
+        -(((code + 1) >> 1) as i32) as i16
+    } else {
+        (code >> 1) as i16
+    }
+}
+
+/// Simplified run-length decoder for coefficients that were zero in every
+/// earlier progressive pass.
+pub(crate) struct SrlDecoder<'a> {
+    reader: BitReader<'a>,
+    kp: u32,
+    nz: u32,
+    unary_next: bool,
+}
+
+impl<'a> SrlDecoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self {
+            reader: BitReader::new(data),
+            kp: 8,
+            nz: 0,
+            unary_next: false,
+        }
+    }
+
+    pub(crate) fn read(&mut self, num_bits: u32) -> i16 {
+        if self.nz > 0 {
+            self.nz -= 1;
+            return 0;
+        }
+        let k = self.kp / 8;
+        if !self.unary_next {
+            if self.reader.bit() == 0 {
+                // A full run of 1 << k zeros.
+                self.nz = (1 << k) - 1;
+                self.kp = (self.kp + 4).min(KPMAX);
+                return 0;
+            }
+            // A short run of k-bit length, followed by a non-zero value.
+            self.nz = self.reader.bits(k);
+            self.unary_next = true;
+            if self.nz > 0 {
+                self.nz -= 1;
+                return 0;
+            }
+        }
+        self.unary_next = false;
+        let negative = self.reader.bit() == 1;
+        self.kp = self.kp.saturating_sub(6);
+        let magnitude = if num_bits == 1 {
+            1
+        } else {
+            let max = (1i32 << num_bits) - 1;
+            let mut magnitude = 1;
+            while magnitude < max && self.reader.bit() == 0 {
+                magnitude += 1;
+            }
+            magnitude
+        };
+        if negative {
+            -magnitude as i16
+        } else {
+            magnitude as i16
+        }
+    }
+}
+
+/// Per-band quantization values in the order the DWT lays bands out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quant {
+    pub hl1: u8,
+    pub lh1: u8,
+    pub hh1: u8,
+    pub hl2: u8,
+    pub lh2: u8,
+    pub hh2: u8,
+    pub hl3: u8,
+    pub lh3: u8,
+    pub hh3: u8,
+    pub ll3: u8,
+}
+
+impl Quant {
+    /// Parse a 5-byte progressive `RFX_COMPONENT_CODEC_QUANT`.
+    pub fn parse(b: &[u8]) -> Self {
+        Self {
+            ll3: b[0] & 0x0F,
+            hl3: b[0] >> 4,
+            lh3: b[1] & 0x0F,
+            hh3: b[1] >> 4,
+            hl2: b[2] & 0x0F,
+            lh2: b[2] >> 4,
+            hh2: b[3] & 0x0F,
+            hl1: b[3] >> 4,
+            lh1: b[4] & 0x0F,
+            hh1: b[4] >> 4,
+        }
+    }
+
+    /// Parse a 5-byte RemoteFX `TS_RFX_CODEC_QUANT`, which orders the LH and
+    /// HL nibbles the other way round from the progressive codec.
+    pub fn parse_rfx(b: &[u8]) -> Self {
+        Self {
+            ll3: b[0] & 0x0F,
+            lh3: b[0] >> 4,
+            hl3: b[1] & 0x0F,
+            hh3: b[1] >> 4,
+            lh2: b[2] & 0x0F,
+            hl2: b[2] >> 4,
+            hh2: b[3] & 0x0F,
+            lh1: b[3] >> 4,
+            hl1: b[4] & 0x0F,
+            hh1: b[4] >> 4,
+        }
+    }
+
+    /// Band values in [`band_layout`] order.
+    pub fn bands(&self) -> [u8; 10] {
+        [
+            self.hl1, self.lh1, self.hh1, self.hl2, self.lh2, self.hh2, self.hl3, self.lh3,
+            self.hh3, self.ll3,
+        ]
+    }
+
+    pub fn add(&self, other: &Quant) -> [u8; 10] {
+        let (a, b) = (self.bands(), other.bands());
+        std::array::from_fn(|i| a[i] + b[i])
+    }
+}
+
+/// `(offset, length)` of each band in a tile buffer, in the order HL1, LH1,
+/// HH1, HL2, LH2, HH2, HL3, LH3, HH3, LL3. The "reduce extrapolate" layout
+/// keeps an extra row or column of low-pass coefficients per level.
+pub fn band_layout(extrapolate: bool) -> [(usize, usize); 10] {
+    if extrapolate {
+        [
+            (0, 1023),
+            (1023, 1023),
+            (2046, 961),
+            (3007, 272),
+            (3279, 272),
+            (3551, 256),
+            (3807, 72),
+            (3879, 72),
+            (3951, 64),
+            (4015, 81),
+        ]
+    } else {
+        [
+            (0, 1024),
+            (1024, 1024),
+            (2048, 1024),
+            (3072, 256),
+            (3328, 256),
+            (3584, 256),
+            (3840, 64),
+            (3904, 64),
+            (3968, 64),
+            (4032, 64),
+        ]
+    }
+}
+
+/// Scale every band back up by its quantization value (a left shift of
+/// `value - 1`).
+pub fn dequantize(buffer: &mut [i16], bands: &[u8; 10], extrapolate: bool) {
+    for (&(offset, len), &value) in band_layout(extrapolate).iter().zip(bands) {
+        let shift = value.saturating_sub(1) as u32;
+        for v in &mut buffer[offset..offset + len] {
+            *v = ((*v as i32) << shift) as i16;
+        }
+    }
+}
+
+/// Undo the differential coding of the LL3 band.
+pub fn differential_decode(band: &mut [i16]) {
+    for i in 1..band.len() {
+        band[i] = band[i].wrapping_add(band[i - 1]);
+    }
+}
+
+/// Inverse 2D DWT of a full 64x64 tile in place.
+pub fn idwt_2d(buffer: &mut [i16], extrapolate: bool) {
+    let mut temp = vec![0i16; TILE_COEFFS];
+    if extrapolate {
+        for (offset, level) in [(3807, 3), (3007, 2), (0, 1)] {
+            idwt_extrapolate_block(&mut buffer[offset..], &mut temp, level);
+        }
+    } else {
+        for (offset, width) in [(3840, 8), (3072, 16), (0, 32)] {
+            idwt_block(&mut buffer[offset..], &mut temp, width);
+        }
+    }
+}
+
+/// One level of the classic RemoteFX inverse DWT. The sub-bands are stored
+/// HL, LH, HH, LL, each `width * width`.
+fn idwt_block(buffer: &mut [i16], idwt: &mut [i16], width: usize) {
+    let total = width * 2;
+    let band = width * width;
+    let (hl, lh, hh, ll) = (0, band, band * 2, band * 3);
+    let h_base = band * 2;
+
+    // Horizontal: LL+HL -> L (top half of idwt), LH+HH -> H (bottom half).
+    for y in 0..width {
+        for (low, high, dst) in [(ll, hl, 0), (lh, hh, h_base)] {
+            let lo = |n: usize| buffer[low + y * width + n] as i32;
+            let hi = |n: usize| buffer[high + y * width + n] as i32;
+            let row = dst + y * total;
+            idwt[row] = (lo(0) - ((hi(0) * 2 + 1) >> 1)) as i16;
+            for n in 1..width {
+                idwt[row + 2 * n] = (lo(n) - ((hi(n - 1) + hi(n) + 1) >> 1)) as i16;
+            }
+            for n in 0..width - 1 {
+                let even = idwt[row + 2 * n] as i32 + idwt[row + 2 * n + 2] as i32;
+                idwt[row + 2 * n + 1] = ((hi(n) << 1) + (even >> 1)) as i16;
+            }
+            let n = width - 1;
+            idwt[row + 2 * n + 1] = ((hi(n) << 1) + idwt[row + 2 * n] as i32) as i16;
+        }
+    }
+
+    // Vertical: L+H -> buffer.
+    for x in 0..total {
+        let l = |n: usize| idwt[n * total + x] as i32;
+        let h = |n: usize| idwt[h_base + n * total + x] as i32;
+        buffer[x] = (l(0) - ((h(0) * 2 + 1) >> 1)) as i16;
+        for n in 1..width {
+            let even = (l(n) - ((h(n - 1) + h(n) + 1) >> 1)) as i16;
+            buffer[(2 * n) * total + x] = even;
+            let odd =
+                (h(n - 1) << 1) + ((buffer[(2 * n - 2) * total + x] as i32 + even as i32) >> 1);
+            buffer[(2 * n - 1) * total + x] = odd as i16;
+        }
+        let n = width - 1;
+        buffer[(2 * n + 1) * total + x] = ((h(n) << 1) + buffer[(2 * n) * total + x] as i32) as i16;
+    }
+}
+
+/// Band sizes for one level of the extrapolating DWT.
+fn extrapolate_band_counts(level: u32) -> (usize, usize) {
+    let low = (64 >> level) + 1;
+    let high = if level == 1 {
+        31
+    } else {
+        (64 + (1 << (level - 1))) >> level
+    };
+    (low, high)
+}
+
+/// One level of the "reduce extrapolate" inverse DWT. Bands are stored HL
+/// (high x low), LH, HH, LL and the output is `(low + high)^2` coefficients
+/// written from the start of `buffer`.
+fn idwt_extrapolate_block(buffer: &mut [i16], temp: &mut [i16], level: u32) {
+    let (nl, nh) = extrapolate_band_counts(level);
+    let step = nl + nh;
+    let hl = 0;
+    let lh = hl + nh * nl;
+    let hh = lh + nl * nh;
+    let ll = hh + nh * nh;
+    let h_base = nl * step;
+
+    // Horizontal passes: LL + HL -> L rows, LH + HH -> H rows.
+    for (low, high, dst, rows) in [(ll, hl, 0, nl), (lh, hh, h_base, nh)] {
+        for row in 0..rows {
+            let lo = &buffer[low + row * nl..low + row * nl + nl];
+            let hi = &buffer[high + row * nh..high + row * nh + nh];
+            let out = &mut temp[dst + row * step..dst + row * step + step];
+            idwt_extrapolate_line(lo, hi, |i, v| out[i] = v);
+        }
+    }
+
+    // Vertical pass: L + H -> buffer.
+    let mut lo = vec![0i16; nl];
+    let mut hi = vec![0i16; nh];
+    for x in 0..step {
+        for (n, v) in lo.iter_mut().enumerate() {
+            *v = temp[n * step + x];
+        }
+        for (n, v) in hi.iter_mut().enumerate() {
+            *v = temp[h_base + n * step + x];
+        }
+        idwt_extrapolate_line(&lo, &hi, |i, v| buffer[i * step + x] = v);
+    }
+}
+
+/// Reconstruct one line of `low.len() + high.len()` samples.
+fn idwt_extrapolate_line(low: &[i16], high: &[i16], mut put: impl FnMut(usize, i16)) {
+    let (nl, nh) = (low.len(), high.len());
+    let w = |v: i32| v as i16 as i32;
+    let mut h0 = high[0] as i32;
+    let mut x0 = w(low[0] as i32 - h0);
+    let mut x2 = x0;
+    let mut pos = 0;
+    for j in 1..nh {
+        let h1 = high[j] as i32;
+        x2 = w(low[j] as i32 - (h0 + h1) / 2);
+        let x1 = w((x0 + x2) / 2 + 2 * h0);
+        put(pos, x0 as i16);
+        put(pos + 1, x1 as i16);
+        pos += 2;
+        x0 = x2;
+        h0 = h1;
+    }
+    if nl <= nh {
+        put(pos, x2 as i16);
+        put(pos + 1, w(x2 + 2 * h0) as i16);
+    } else if nl == nh + 1 {
+        let x0 = w(low[nh] as i32 - h0);
+        put(pos, x2 as i16);
+        put(pos + 1, w((x0 + x2) / 2 + 2 * h0) as i16);
+        put(pos + 2, x0 as i16);
+    } else {
+        let x0 = w(low[nh] as i32 - h0 / 2);
+        put(pos, x2 as i16);
+        put(pos + 1, w((x0 + x2) / 2 + 2 * h0) as i16);
+        put(pos + 2, x0 as i16);
+        put(pos + 3, w((x0 + low[nh + 1] as i32) / 2) as i16);
+    }
+}
+
+/// Convert decoded Y/Cb/Cr tile planes (11.5 fixed point, Y centred on 0)
+/// to a 64x64 RGBA tile.
+pub fn ycbcr_to_rgba(y: &[i16], cb: &[i16], cr: &[i16], out: &mut [u8]) {
+    for (i, px) in out
+        .as_chunks_mut::<4>()
+        .0
+        .iter_mut()
+        .enumerate()
+        .take(TILE_COEFFS)
+    {
+        let luma = ((y[i] as i64) + 4096) << 16;
+        let (cb, cr) = (cb[i] as i64, cr[i] as i64);
+        let r = ((luma + cr * 91915) >> 16) >> 5;
+        let g = ((luma - cb * 22526 - cr * 46818) >> 16) >> 5;
+        let b = ((luma + cb * 115992) >> 16) >> 5;
+        px[0] = clamp_u8(r as i32);
+        px[1] = clamp_u8(g as i32);
+        px[2] = clamp_u8(b as i32);
+        px[3] = 0xFF;
+    }
+}
+
+/// Copy a decoded 64x64 RGBA tile whose top-left corner sits at `(tx, ty)`
+/// onto a `width x height` surface, clipped to `rects`.
+pub fn blit_tile(
+    tile: &[u8],
+    tx: usize,
+    ty: usize,
+    rects: &[GfxRect16],
+    surface: &mut [u8],
+    width: u16,
+    height: u16,
+) {
+    for rect in rects {
+        let left = (rect.left as usize).max(tx);
+        let top = (rect.top as usize).max(ty);
+        let right = (rect.right as usize)
+            .min(tx + TILE_SIZE)
+            .min(width as usize);
+        let bottom = (rect.bottom as usize)
+            .min(ty + TILE_SIZE)
+            .min(height as usize);
+        if left >= right || top >= bottom {
+            continue;
+        }
+        let len = (right - left) * 4;
+        for y in top..bottom {
+            let src = ((y - ty) * TILE_SIZE + (left - tx)) * 4;
+            let dst = (y * width as usize + left) * 4;
+            surface[dst..dst + len].copy_from_slice(&tile[src..src + len]);
+        }
+    }
+}
+
+// ─── RemoteFX Messages ──────────────────────────────────────────────────
+
+const WBT_SYNC: u16 = 0xCCC0;
+const WBT_CODEC_VERSIONS: u16 = 0xCCC1;
+const WBT_CHANNELS: u16 = 0xCCC2;
+const WBT_CONTEXT: u16 = 0xCCC3;
+const WBT_FRAME_BEGIN: u16 = 0xCCC4;
+const WBT_FRAME_END: u16 = 0xCCC5;
+const WBT_REGION: u16 = 0xCCC6;
+const WBT_EXTENSION: u16 = 0xCCC7;
+const CBT_TILESET: u16 = 0xCAC2;
+const CBT_TILE: u16 = 0xCAC3;
+
+const SYNC_MAGIC: u32 = 0xCACC_ACCA;
+const SYNC_VERSION: u16 = 0x0100;
+const ENTROPY_RLGR1: u16 = 0x01;
+const ENTROPY_RLGR3: u16 = 0x04;
+
+/// Stream state for RemoteFX WireToSurface1 bitmaps. Servers send the
+/// `TS_RFX_CONTEXT` block that picks the entropy coder with the first
+/// message only, so the choice has to outlive a single bitmap.
+pub struct RfxContext {
+    entropy: Rlgr,
+}
+
+impl Default for RfxContext {
+    fn default() -> Self {
+        // Windows servers encode with RLGR3.
+        Self {
+            entropy: Rlgr::Rlgr3,
+        }
+    }
+}
+
+impl RfxContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode one WireToSurface1 bitmap stream targeting `dest` onto an RGBA
+    /// surface of `width x height`, returning the surface rects that changed.
+    pub fn decode(
+        &mut self,
+        data: &[u8],
+        dest: GfxRect16,
+        surface: &mut [u8],
+        width: u16,
+        height: u16,
+    ) -> Result<Vec<GfxRect16>, GfxParseError> {
+        let clip = GfxRect16 {
+            left: dest.left,
+            top: dest.top,
+            right: dest.right.min(width),
+            bottom: dest.bottom.min(height),
+        };
+        let mut rects: Option<Vec<GfxRect16>> = None;
+        let mut updated = Vec::new();
+        let mut reader = ByteReader::new(data);
+        while reader.remaining() > 0 {
+            let (block_type, mut body) = read_block(&mut reader)?;
+            match block_type {
+                WBT_SYNC => {
+                    let magic = body.u32("RemoteFX sync truncated")?;
+                    let version = body.u16("RemoteFX sync truncated")?;
+                    if magic != SYNC_MAGIC || version != SYNC_VERSION {
+                        return Err(GfxParseError("RemoteFX sync magic/version mismatch"));
+                    }
+                }
+                WBT_CONTEXT => {
+                    const ERR: &str = "RemoteFX context truncated";
+                    body.bytes(3, ERR)?; // codecId, channelId, ctxId
+                    if body.u16(ERR)? as usize != TILE_SIZE {
+                        return Err(GfxParseError("RemoteFX context tile size is not 64"));
+                    }
+                    self.entropy = match (body.u16(ERR)? >> 9) & 0x0F {
+                        ENTROPY_RLGR1 => Rlgr::Rlgr1,
+                        ENTROPY_RLGR3 => Rlgr::Rlgr3,
+                        _ => return Err(GfxParseError("RemoteFX entropy algorithm unknown")),
+                    };
+                }
+                WBT_CODEC_VERSIONS | WBT_CHANNELS | WBT_FRAME_BEGIN | WBT_FRAME_END => {}
+                WBT_REGION => rects = Some(Self::region(&mut body, clip)?),
+                WBT_EXTENSION => {
+                    let rects = rects
+                        .as_deref()
+                        .ok_or(GfxParseError("RemoteFX tileset without a region"))?;
+                    self.tileset(&mut body, dest, rects, surface, width, height)?;
+                    updated.extend(
+                        rects
+                            .iter()
+                            .filter(|r| r.right > r.left && r.bottom > r.top),
+                    );
+                }
+                _ => return Err(GfxParseError("RemoteFX block type unknown")),
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Parse a `TS_RFX_REGION` into surface rects clipped to `clip`. A region
+    /// without rects covers the whole destination.
+    fn region(body: &mut ByteReader<'_>, clip: GfxRect16) -> Result<Vec<GfxRect16>, GfxParseError> {
+        const ERR: &str = "RemoteFX region truncated";
+        body.bytes(3, ERR)?; // codecId, channelId, regionFlags
+        let num_rects = body.u16(ERR)?;
+        if num_rects == 0 {
+            return Ok(vec![clip]);
+        }
+        let mut rects = Vec::with_capacity(num_rects as usize);
+        for _ in 0..num_rects {
+            let (x, y) = (body.u16(ERR)?, body.u16(ERR)?);
+            let (w, h) = (body.u16(ERR)?, body.u16(ERR)?);
+            let left = clip.left.saturating_add(x);
+            let top = clip.top.saturating_add(y);
+            rects.push(GfxRect16 {
+                left: left.min(clip.right),
+                top: top.min(clip.bottom),
+                right: left.saturating_add(w).min(clip.right),
+                bottom: top.saturating_add(h).min(clip.bottom),
+            });
+        }
+        Ok(rects)
+    }
+
+    /// Decode a `TS_RFX_TILESET` and blit its tiles, clipped to `rects`.
+    fn tileset(
+        &self,
+        body: &mut ByteReader<'_>,
+        dest: GfxRect16,
+        rects: &[GfxRect16],
+        surface: &mut [u8],
+        width: u16,
+        height: u16,
+    ) -> Result<(), GfxParseError> {
+        const ERR: &str = "RemoteFX tileset truncated";
+        body.bytes(2, ERR)?; // codecId, channelId
+        if body.u16(ERR)? != CBT_TILESET {
+            return Err(GfxParseError("RemoteFX extension is not a tileset"));
+        }
+        body.bytes(4, ERR)?; // idx, properties
+        let num_quant = body.u8(ERR)?;
+        if body.u8(ERR)? as usize != TILE_SIZE {
+            return Err(GfxParseError("RemoteFX tileset tile size is not 64"));
+        }
+        let num_tiles = body.u16(ERR)?;
+        let tile_data_size = body.u32(ERR)? as usize;
+        let quants = (0..num_quant)
+            .map(|_| body.bytes(5, ERR).map(Quant::parse_rfx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tiles = ByteReader::new(body.bytes(tile_data_size, ERR)?);
+        let mut rgba = vec![0u8; TILE_COEFFS * 4];
+        for _ in 0..num_tiles {
+            let (block_type, mut tile) = read_block(&mut tiles)?;
+            if block_type != CBT_TILE {
+                return Err(GfxParseError("RemoteFX tile block type unknown"));
+            }
+            let (x_idx, y_idx) = self.decode_tile(&mut tile, &quants, &mut rgba)?;
+            blit_tile(
+                &rgba,
+                dest.left as usize + x_idx as usize * TILE_SIZE,
+                dest.top as usize + y_idx as usize * TILE_SIZE,
+                rects,
+                surface,
+                width,
+                height,
+            );
+        }
+        Ok(())
+    }
+
+    /// Decode a `TS_RFX_TILE` into `rgba`, returning its tile indices.
+    fn decode_tile(
+        &self,
+        body: &mut ByteReader<'_>,
+        quants: &[Quant],
+        rgba: &mut [u8],
+    ) -> Result<(u16, u16), GfxParseError> {
+        const ERR: &str = "RemoteFX tile truncated";
+        let quant_idx = [body.u8(ERR)?, body.u8(ERR)?, body.u8(ERR)?];
+        let key = (body.u16(ERR)?, body.u16(ERR)?);
+        let lens = [body.u16(ERR)?, body.u16(ERR)?, body.u16(ERR)?];
+
+        let mut planes = vec![0i16; 3 * TILE_COEFFS];
+        let (ll3, ll3_len) = band_layout(false)[9];
+        for (c, plane) in planes
+            .as_chunks_mut::<TILE_COEFFS>()
+            .0
+            .iter_mut()
+            .enumerate()
+        {
+            let quant = quants
+                .get(quant_idx[c] as usize)
+                .ok_or(GfxParseError("RemoteFX quant index out of range"))?;
+            rlgr_decode(body.bytes(lens[c] as usize, ERR)?, plane, self.entropy);
+            differential_decode(&mut plane[ll3..ll3 + ll3_len]);
+            dequantize(plane, &quant.bands(), false);
+            idwt_2d(plane, false);
+        }
+        let (y, rest) = planes.split_at(TILE_COEFFS);
+        let (cb, cr) = rest.split_at(TILE_COEFFS);
+        ycbcr_to_rgba(y, cb, cr, rgba);
+        Ok(key)
+    }
+}
+
+/// Read a `blockType`/`blockLen` framed block, returning a reader over its
+/// body.
+fn read_block<'a>(reader: &mut ByteReader<'a>) -> Result<(u16, ByteReader<'a>), GfxParseError> {
+    let block_type = reader.u16("RemoteFX block header truncated")?;
+    let block_len = reader.u32("RemoteFX block header truncated")? as usize;
+    if block_len < 6 {
+        return Err(GfxParseError("RemoteFX block length too small"));
+    }
+    let body = reader.bytes(block_len - 6, "RemoteFX block body truncated")?;
+    Ok((block_type, ByteReader::new(body)))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// MSB-first bit writer used to build codec fixtures.
+    #[derive(Default)]
+    pub(crate) struct BitWriter {
+        pub(crate) bytes: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        pub(crate) fn put(&mut self, value: u32, count: u32) {
+            for i in (0..count).rev() {
+                if self.bits.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                if (value >> i) & 1 != 0 {
+                    *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+                }
+                self.bits += 1;
+            }
+        }
+
+        fn put_gr(&mut self, value: u32, krp: &mut u32) {
+            let kr = *krp >> LSGR;
+            let vk = value >> kr;
+            for _ in 0..vk {
+                self.put(1, 1);
+            }
+            self.put(0, 1);
+            self.put(value & ((1 << kr) - 1), kr);
+            if vk == 0 {
+                update(krp, -2);
+            } else if vk > 1 {
+                update(krp, vk as i32);
+            }
+        }
+    }
+
+    /// Reference RLGR1 encoder (MS-RDPRFX 3.1.8.1.7.1). Trailing zeros are
+    /// left implicit, matching the decoder's zero fill.
+    pub(crate) fn rlgr1_encode(input: &[i16]) -> Vec<u8> {
+        rlgr_encode(input, Rlgr::Rlgr1)
+    }
+
+    fn fold(value: i16) -> u32 {
+        let value = value as i32;
+        if value >= 0 {
+            2 * value as u32
+        } else {
+            (-2 * value - 1) as u32
+        }
+    }
+
+    /// Reference RLGR encoder (MS-RDPRFX 3.1.8.1.7.1).
+    pub(crate) fn rlgr_encode(input: &[i16], mode: Rlgr) -> Vec<u8> {
+        let end = input.iter().rposition(|&v| v != 0).map_or(0, |p| p + 1);
+        let input = &input[..end];
+        let mut w = BitWriter::default();
+        let (mut k, mut kp, mut krp) = (1u32, 8u32, 8u32);
+        let mut i = 0;
+        while i < input.len() {
+            if k > 0 {
+                let mut zeros = 0;
+                while input[i] == 0 {
+                    zeros += 1;
+                    i += 1;
+                }
+                let value = input[i] as i32;
+                i += 1;
+                while zeros >= 1 << k {
+                    w.put(0, 1);
+                    zeros -= 1 << k;
+                    k = update(&mut kp, UP_GR as i32);
+                }
+                w.put(1, 1);
+                w.put(zeros, k);
+                w.put((value < 0) as u32, 1);
+                w.put_gr(value.unsigned_abs() - 1, &mut krp);
+                k = update(&mut kp, -(DN_GR as i32));
+            } else if mode == Rlgr::Rlgr1 {
+                let folded = fold(input[i]);
+                i += 1;
+                w.put_gr(folded, &mut krp);
+                k = if folded == 0 {
+                    update(&mut kp, UQ_GR as i32)
+                } else {
+                    update(&mut kp, -(DQ_GR as i32))
+                };
+            } else {
+                let first = fold(input[i]);
+                let second = input.get(i + 1).map_or(0, |&v| fold(v));
+                i += 2;
+                let code = first + second;
+                w.put_gr(code, &mut krp);
+                w.put(first, u32::BITS - code.leading_zeros());
+                if first != 0 && second != 0 {
+                    k = update(&mut kp, -2 * DQ_GR as i32);
+                } else if first == 0 && second == 0 {
+                    k = update(&mut kp, 2 * UQ_GR as i32);
+                }
+            }
+        }
+        w.bytes
+    }
+
+    fn pseudo_random(seed: &mut u32) -> u32 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        *seed
+    }
+
+    #[test]
+    fn rlgr1_roundtrips_sparse_coefficients() {
+        let mut seed = 0xC0FF_EE11;
+        for density in [2, 5, 40] {
+            let input: Vec<i16> = (0..TILE_COEFFS)
+                .map(|_| {
+                    let r = pseudo_random(&mut seed);
+                    if r.is_multiple_of(density) {
+                        ((r >> 8) % 600) as i16 - 300
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            let mut out = vec![0x55i16; TILE_COEFFS];
+            rlgr1_decode(&rlgr1_encode(&input), &mut out);
+            assert_eq!(out, input, "density 1/{density}");
+        }
+    }
+
+    #[test]
+    fn rlgr3_roundtrips_dense_coefficients() {
+        let mut seed = 0x5EED_0003;
+        for density in [1, 2, 5] {
+            let input: Vec<i16> = (0..TILE_COEFFS)
+                .map(|_| {
+                    let r = pseudo_random(&mut seed);
+                    if r.is_multiple_of(density) {
+                        ((r >> 8) % 40) as i16 - 20
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            let mut out = vec![0x55i16; TILE_COEFFS];
+            rlgr_decode(&rlgr_encode(&input, Rlgr::Rlgr3), &mut out, Rlgr::Rlgr3);
+            assert_eq!(out, input, "density 1/{density}");
+        }
+    }
+
+    #[test]
+    fn rlgr1_empty_stream_is_all_zero() {
+        let mut out = vec![7i16; 64];
+        rlgr1_decode(&[], &mut out);
+        assert!(out.iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn srl_decodes_runs_and_magnitudes() {
+        // k=1: '1' plus one length bit => a single zero, then sign '1' and
+        // unary "00" => -3 (capped by num_bits). kp drops to 2, so k=0 and
+        // the next '0' is a full run of just one zero.
+        let mut w = BitWriter::default();
+        w.put(0b1_1, 2);
+        w.put(1, 1);
+        w.put(0b00, 2);
+        w.put(0, 1);
+        let mut srl = SrlDecoder::new(&w.bytes);
+        assert_eq!(srl.read(2), 0);
+        assert_eq!(srl.read(2), -3);
+        assert_eq!(srl.read(2), 0);
+        // Single-bit upgrades carry only a sign.
+        let mut srl = SrlDecoder::new(&[0b1000_0000]);
+        assert_eq!(srl.read(1), 1);
+    }
+
+    #[test]
+    fn quant_nibbles_follow_progressive_order() {
+        let q = Quant::parse(&[0x21, 0x43, 0x65, 0x87, 0xA9]);
+        assert_eq!(q.bands(), [8, 9, 10, 5, 6, 7, 2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn rfx_quant_nibbles_swap_lh_and_hl() {
+        let q = Quant::parse_rfx(&[0x21, 0x43, 0x65, 0x87, 0xA9]);
+        assert_eq!(q.bands(), [9, 8, 10, 6, 5, 7, 3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn band_layouts_tile_the_buffer() {
+        for extrapolate in [false, true] {
+            let layout = band_layout(extrapolate);
+            let mut end = 0;
+            for (offset, len) in layout {
+                assert_eq!(offset, end);
+                end += len;
+            }
+            assert_eq!(end, TILE_COEFFS);
+        }
+    }
+
+    #[test]
+    fn dc_only_tiles_reconstruct_flat() {
+        for extrapolate in [false, true] {
+            let mut buffer = vec![0i16; TILE_COEFFS];
+            let (ll, len) = band_layout(extrapolate)[9];
+            buffer[ll..ll + len].fill(-321);
+            idwt_2d(&mut buffer, extrapolate);
+            assert!(
+                buffer.iter().all(|&v| v == -321),
+                "extrapolate={extrapolate}"
+            );
+        }
+    }
+
+    #[test]
+    fn ycbcr_conversion_matches_reference_points() {
+        let mut out = vec![0u8; TILE_COEFFS * 4];
+        let zeros = vec![0i16; TILE_COEFFS];
+        let mut y = vec![0i16; TILE_COEFFS];
+        y[1] = 127 << 5;
+        y[2] = -128 << 5;
+        let mut cr = zeros.clone();
+        cr[3] = 64 << 5;
+        ycbcr_to_rgba(&y, &zeros, &cr, &mut out);
+        assert_eq!(&out[0..4], [128, 128, 128, 0xFF]);
+        assert_eq!(&out[4..8], [255, 255, 255, 0xFF]);
+        assert_eq!(&out[8..12], [0, 0, 0, 0xFF]);
+        assert_eq!(&out[12..16], [217, 82, 128, 0xFF]);
+    }
+
+    fn block(block_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut out = block_type.to_le_bytes().to_vec();
+        out.extend_from_slice(&(body.len() as u32 + 6).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// A message with an RLGR1 context, one region rect and a single tile
+    /// at `(x_idx, 0)` whose luma is a flat `dc`.
+    fn message(rect: [u16; 4], x_idx: u16, dc: i16) -> Vec<u8> {
+        let mut luma = vec![0i16; TILE_COEFFS];
+        luma[band_layout(false)[9].0] = dc;
+        let luma = rlgr_encode(&luma, Rlgr::Rlgr1);
+        let mut tile = vec![0, 0, 0];
+        for v in [x_idx, 0, luma.len() as u16, 0, 0] {
+            tile.extend_from_slice(&v.to_le_bytes());
+        }
+        tile.extend_from_slice(&luma);
+        let tile = block(CBT_TILE, &tile);
+
+        let mut tileset = vec![0x01, 0x00];
+        for v in [CBT_TILESET, 0, 0] {
+            tileset.extend_from_slice(&v.to_le_bytes());
+        }
+        tileset.extend_from_slice(&[1, 64]);
+        tileset.extend_from_slice(&1u16.to_le_bytes());
+        tileset.extend_from_slice(&(tile.len() as u32).to_le_bytes());
+        tileset.extend_from_slice(&[0x66; 5]);
+        tileset.extend_from_slice(&tile);
+
+        let mut region = vec![0x01, 0x00, 0x01];
+        region.extend_from_slice(&1u16.to_le_bytes());
+        for v in rect {
+            region.extend_from_slice(&v.to_le_bytes());
+        }
+        region.extend_from_slice(&[0xC1, 0xCA, 0x01, 0x00]);
+
+        let mut sync = SYNC_MAGIC.to_le_bytes().to_vec();
+        sync.extend_from_slice(&SYNC_VERSION.to_le_bytes());
+        let mut context = vec![0x01, 0xFF, 0x00];
+        context.extend_from_slice(&64u16.to_le_bytes());
+        context.extend_from_slice(&(ENTROPY_RLGR1 << 9).to_le_bytes());
+        [
+            block(WBT_SYNC, &sync),
+            block(WBT_CONTEXT, &context),
+            block(WBT_FRAME_BEGIN, &[0x01, 0x00, 0, 0, 0, 0, 1, 0]),
+            block(WBT_REGION, &region),
+            block(WBT_EXTENSION, &tileset),
+            block(WBT_FRAME_END, &[0x01, 0x00]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn message_tiles_are_offset_by_dest_and_clipped() {
+        let mut ctx = RfxContext::new();
+        let (width, height) = (128u16, 32u16);
+        let mut surface = vec![0u8; width as usize * height as usize * 4];
+        let dest = GfxRect16 {
+            left: 16,
+            top: 8,
+            right: 128,
+            bottom: 32,
+        };
+        let rects = ctx
+            .decode(
+                &message([60, 0, 10, 40], 1, 32),
+                dest,
+                &mut surface,
+                width,
+                height,
+            )
+            .unwrap();
+        assert_eq!(ctx.entropy, Rlgr::Rlgr1);
+        // The rect is clipped by the surface's bottom edge; the tile starts
+        // at x = 16 + 64, so only its four rightmost columns are drawn.
+        let [r] = rects[..] else { panic!("{rects:?}") };
+        assert_eq!((r.left, r.top, r.right, r.bottom), (76, 8, 86, 32));
+        let px = |x: usize, y: usize| &surface[(y * width as usize + x) * 4..][..4];
+        assert_eq!(px(79, 8), [0, 0, 0, 0]);
+        assert_eq!(px(80, 8), [160, 160, 160, 0xFF]);
+        assert_eq!(px(85, 31), [160, 160, 160, 0xFF]);
+        assert_eq!(px(86, 8), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let mut ctx = RfxContext::new();
+        let mut surface = vec![0u8; 64 * 64 * 4];
+        let dest = GfxRect16 {
+            left: 0,
+            top: 0,
+            right: 64,
+            bottom: 64,
+        };
+        let mut decode = |data: &[u8]| ctx.decode(data, dest, &mut surface, 64, 64);
+        // Bad sync magic.
+        assert!(decode(&block(WBT_SYNC, &[0; 6])).is_err());
+        // Truncated block body.
+        assert!(decode(&[0xC0, 0xCC, 20, 0, 0, 0]).is_err());
+        // A tileset without a region to clip against.
+        let data = message([0, 0, 64, 64], 0, 1);
+        let region_at = data
+            .windows(2)
+            .position(|w| w == WBT_REGION.to_le_bytes())
+            .unwrap();
+        let region_len = u32::from_le_bytes(data[region_at + 2..region_at + 6].try_into().unwrap());
+        let mut no_region = data[..region_at].to_vec();
+        no_region.extend_from_slice(&data[region_at + region_len as usize..]);
+        assert!(decode(&no_region).is_err());
+        // Quant index past the tileset's quant table.
+        let mut data = message([0, 0, 64, 64], 0, 1);
+        let tile_at = data
+            .windows(2)
+            .position(|w| w == CBT_TILE.to_le_bytes())
+            .unwrap();
+        data[tile_at + 6] = 1;
+        assert!(decode(&data).is_err());
+    }
+}
@@ -1,8 +1,10 @@
 //! RDPGFX (MS-RDPEGFX) Graphics Pipeline Extension.
 //!
 //! Implements the RDP Graphics Pipeline Dynamic Virtual Channel for H.264
-//! hardware-accelerated video decoding.
+//! hardware-accelerated video decoding, with software ClearCodec, Planar and
+//! RemoteFX Progressive decoders for servers that do not use AVC.
 
+pub mod codecs;
 pub mod pdu;
 pub mod processor;
 pub mod surfaces;
@@ -62,13 +62,20 @@ pub const CAPVERSION_102: u32 = 0x000A0200;
 pub const CAPVERSION_103: u32 = 0x000A0301;
 pub const CAPVERSION_104: u32 = 0x000A0400;
 
+// Capability set flags.
+pub const CAPS_FLAG_AVC420_ENABLED: u32 = 0x0000_0010; // CAPVERSION_81
+pub const CAPS_FLAG_AVC_DISABLED: u32 = 0x0000_0020; // CAPVERSION_10+
+
 // ─── Codec IDs ──────────────────────────────────────────────────────────
 
 pub const CODEC_UNCOMPRESSED: u16 = 0x0000;
-pub const CODEC_PLANAR: u16 = 0x0001;
-pub const CODEC_CAVIDEO: u16 = 0x0003; // AVC420
+pub const CODEC_CAVIDEO: u16 = 0x0003; // RemoteFX
 pub const CODEC_CLEARCODEC: u16 = 0x0008;
+pub const CODEC_CAPROGRESSIVE: u16 = 0x0009;
+pub const CODEC_PLANAR: u16 = 0x000A;
+pub const CODEC_AVC420: u16 = 0x000B;
 pub const CODEC_ALPHA: u16 = 0x000C;
+pub const CODEC_CAPROGRESSIVE_V2: u16 = 0x000D;
 pub const CODEC_AVC444: u16 = 0x000E;
 pub const CODEC_AVC444V2: u16 = 0x000F;
 
@@ -265,6 +272,49 @@ impl WireToSurface1 {
     }
 }
 
+#[derive(Debug)]
+pub struct WireToSurface2 {
+    pub surface_id: u16,
+    pub codec_id: u16,
+    pub codec_context_id: u32,
+    pub pixel_format: u8,
+    pub bitmap_data: Vec<u8>,
+}
+
+impl WireToSurface2 {
+    pub fn parse(body: &[u8]) -> Result<Self, GfxParseError> {
+        // surfaceId(2) + codecId(2) + codecContextId(4) + pixelFormat(1) = 9 bytes header
+        if body.len() < 9 {
+            return Err(GfxParseError("WireToSurface2 too short"));
+        }
+        Ok(WireToSurface2 {
+            surface_id: u16::from_le_bytes([body[0], body[1]]),
+            codec_id: u16::from_le_bytes([body[2], body[3]]),
+            codec_context_id: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+            pixel_format: body[8],
+            bitmap_data: body[9..].to_vec(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteEncodingContext {
+    pub surface_id: u16,
+    pub codec_context_id: u32,
+}
+
+impl DeleteEncodingContext {
+    pub fn parse(body: &[u8]) -> Result<Self, GfxParseError> {
+        if body.len() < 6 {
+            return Err(GfxParseError("DeleteEncodingContext too short"));
+        }
+        Ok(DeleteEncodingContext {
+            surface_id: u16::from_le_bytes([body[0], body[1]]),
+            codec_context_id: u32::from_le_bytes([body[2], body[3], body[4], body[5]]),
+        })
+    }
+}
+
 // ─── AVC420 Bitmap Stream ───────────────────────────────────────────────
 
 #[derive(Debug)]
@@ -350,6 +400,42 @@ impl CapsAdvertisePdu {
 
         Self { data }
     }
+
+    /// Build a CAPS_ADVERTISE offering CAPVERSION_8, 8.1 and 10. Every set
+    /// supports the ClearCodec, Planar and Progressive codecs; when `avc` is
+    /// false (no H.264 decoder) AVC is disabled so the server falls back to
+    /// them instead of sending frames we cannot decode.
+    pub fn new(avc: bool) -> Self {
+        let capsets = [
+            (CAPVERSION_8, 0),
+            (
+                CAPVERSION_81,
+                if avc { CAPS_FLAG_AVC420_ENABLED } else { 0 },
+            ),
+            (CAPVERSION_10, if avc { 0 } else { CAPS_FLAG_AVC_DISABLED }),
+        ];
+        let mut data = Vec::new();
+        data.extend_from_slice(&(capsets.len() as u16).to_le_bytes());
+        for (version, flags) in capsets {
+            data.extend_from_slice(&version.to_le_bytes());
+            data.extend_from_slice(&4u32.to_le_bytes()); // capsDataLength
+            data.extend_from_slice(&flags.to_le_bytes()); // flags
+        }
+        Self { data }
+    }
+
+    /// Capability sets as `(version, flags)` pairs.
+    pub fn capsets(&self) -> Vec<(u32, u32)> {
+        self.data[2..]
+            .chunks(12)
+            .map(|c| {
+                (
+                    u32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+                    u32::from_le_bytes([c[8], c[9], c[10], c[11]]),
+                )
+            })
+            .collect()
+    }
 }
 
 impl Encode for CapsAdvertisePdu {
@@ -664,6 +750,38 @@ mod tests {
         assert!(WireToSurface1::parse(&[0; 12]).is_err());
     }
 
+    // ── WireToSurface2 / DeleteEncodingContext ──────────────────────────
+
+    #[test]
+    fn wire_to_surface2_parse_with_bitmap_data() {
+        let body = [
+            0x02, 0x00, // surface_id = 2
+            0x09, 0x00, // codec_id = CODEC_CAPROGRESSIVE
+            0x07, 0x00, 0x00, 0x00, // codec_context_id = 7
+            0x20, // pixel_format
+            0xC0, 0xCC, // bitmap data
+        ];
+        let wts = WireToSurface2::parse(&body).unwrap();
+        assert_eq!(wts.surface_id, 2);
+        assert_eq!(wts.codec_id, CODEC_CAPROGRESSIVE);
+        assert_eq!(wts.codec_context_id, 7);
+        assert_eq!(wts.pixel_format, 0x20);
+        assert_eq!(wts.bitmap_data, vec![0xC0, 0xCC]);
+    }
+
+    #[test]
+    fn wire_to_surface2_parse_too_short() {
+        assert!(WireToSurface2::parse(&[0; 8]).is_err());
+    }
+
+    #[test]
+    fn delete_encoding_context_parse() {
+        let dec = DeleteEncodingContext::parse(&[0x02, 0x00, 0x07, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(dec.surface_id, 2);
+        assert_eq!(dec.codec_context_id, 7);
+        assert!(DeleteEncodingContext::parse(&[0; 5]).is_err());
+    }
+
     // ── Avc420BitmapStream ──────────────────────────────────────────────
 
     #[test]
@@ -727,10 +845,13 @@ mod tests {
     fn codec_ids_are_distinct() {
         let ids = [
             CODEC_UNCOMPRESSED,
-            CODEC_PLANAR,
             CODEC_CAVIDEO,
             CODEC_CLEARCODEC,
+            CODEC_CAPROGRESSIVE,
+            CODEC_PLANAR,
+            CODEC_AVC420,
             CODEC_ALPHA,
+            CODEC_CAPROGRESSIVE_V2,
             CODEC_AVC444,
             CODEC_AVC444V2,
         ];
@@ -766,6 +887,29 @@ mod tests {
         assert_eq!(pdu.size(), RDPGFX_HEADER_SIZE + 2 + 24);
     }
 
+    #[test]
+    fn caps_advertise_toggles_avc_flags() {
+        let with_avc = CapsAdvertisePdu::new(true);
+        assert_eq!(with_avc.size(), RDPGFX_HEADER_SIZE + 2 + 36);
+        assert_eq!(
+            with_avc.capsets(),
+            [
+                (CAPVERSION_8, 0),
+                (CAPVERSION_81, CAPS_FLAG_AVC420_ENABLED),
+                (CAPVERSION_10, 0),
+            ]
+        );
+        let without_avc = CapsAdvertisePdu::new(false);
+        assert_eq!(
+            without_avc.capsets(),
+            [
+                (CAPVERSION_8, 0),
+                (CAPVERSION_81, 0),
+                (CAPVERSION_10, CAPS_FLAG_AVC_DISABLED),
+            ]
+        );
+    }
+
     #[test]
     fn frame_acknowledge_pdu_size() {
         let pdu = FrameAcknowledgePdu {
@@ -1,5 +1,6 @@
 //! RDPGFX DVC processor — core state machine implementing the Graphics Pipeline Extension.
 
+use std::collections::HashMap;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
@@ -11,6 +12,10 @@ use crate::ironrdp_dvc::{DvcClientProcessor, DvcMessage, DvcProcessor};
 
 use crate::h264::{self, DecodedFrame, H264Decoder, H264DecoderPreference};
 
+use super::codecs::clear::ClearCodecDecoder;
+use super::codecs::planar;
+use super::codecs::progressive::ProgressiveContext;
+use super::codecs::rfx::RfxContext;
 use super::pdu::*;
 use super::surfaces::SurfaceManager;
 
@@ -41,7 +46,7 @@ pub struct GfxDiagnostics {
     /// Negotiated capability version (CAPVERSION_*), once CapsConfirm arrives.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cap_version: Option<u32>,
-    /// Negotiated codec name ("AVC444" | "AVC420" | "uncompressed" | …).
+    /// Negotiated codec name ("AVC444" | "AVC420" | "progressive" | …).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codec: Option<&'static str>,
     /// Surfaces currently allocated by the server.
@@ -134,6 +139,12 @@ pub struct GfxProcessor {
     frame_tx: mpsc::Sender<GfxOutput>,
     /// When true, send raw H.264 NALs instead of decoded RGBA.
     nal_passthrough: bool,
+    /// ClearCodec V-bar and glyph caches (shared by all surfaces).
+    clear_codec: ClearCodecDecoder,
+    /// Progressive tile caches keyed by (surface_id, codec_context_id).
+    progressive: HashMap<(u16, u32), ProgressiveContext>,
+    /// RemoteFX stream state (shared by all surfaces).
+    rfx: RfxContext,
     /// Frame acknowledge tracking.
     total_frames_decoded: u32,
     current_frame_id: Option<u32>,
@@ -180,6 +191,9 @@ impl GfxProcessor {
             cap_version: None,
             frame_tx,
             nal_passthrough,
+            clear_codec: ClearCodecDecoder::new(),
+            progressive: HashMap::new(),
+            rfx: RfxContext::new(),
             total_frames_decoded: 0,
             current_frame_id: None,
             channel_state,
@@ -264,6 +278,15 @@ impl GfxProcessor {
         }
     }
 
+    /// Label the in-use codec from the first frame if caps negotiation
+    /// didn't already.
+    fn note_codec(&mut self, codec: &'static str) {
+        if self.codec.is_none() {
+            self.codec = Some(codec);
+            self.publish();
+        }
+    }
+
     fn handle_caps_confirm(&mut self, body: &[u8]) -> Vec<DvcMessage> {
         match CapsConfirm::parse(body) {
             Ok(caps) => {
@@ -306,6 +329,8 @@ impl GfxProcessor {
         match DeleteSurface::parse(body) {
             Ok(ds) => {
                 self.surfaces.delete_surface(ds.surface_id);
+                self.progressive
+                    .retain(|&(surface_id, _), _| surface_id != ds.surface_id);
                 self.publish();
             }
             Err(e) => {
@@ -379,6 +404,9 @@ impl GfxProcessor {
                 );
                 self.surfaces.reset();
                 self.h264_decoder = None;
+                self.clear_codec.reset();
+                self.progressive.clear();
+                self.rfx = RfxContext::new();
                 // Surfaces were dropped; refresh the snapshot (stay Ready).
                 self.publish();
             }
@@ -401,22 +429,26 @@ impl GfxProcessor {
         };
 
         match wts.codec_id {
-            CODEC_CAVIDEO => {
-                // Surface the in-use codec from the first AVC420 frame if caps
-                // negotiation didn't already label it.
-                if self.codec.is_none() {
-                    self.codec = Some("AVC420");
-                    self.publish();
-                }
+            CODEC_AVC420 => {
+                self.note_codec("AVC420");
                 self.decode_avc420(&wts)
             }
+            CODEC_CAVIDEO => {
+                self.note_codec("RemoteFX");
+                self.decode_remotefx(&wts)
+            }
             CODEC_UNCOMPRESSED => {
-                if self.codec.is_none() {
-                    self.codec = Some("uncompressed");
-                    self.publish();
-                }
+                self.note_codec("uncompressed");
                 self.handle_uncompressed(&wts)
             }
+            CODEC_PLANAR => {
+                self.note_codec("planar");
+                self.decode_planar(&wts)
+            }
+            CODEC_CLEARCODEC => {
+                self.note_codec("ClearCodec");
+                self.decode_clearcodec(&wts)
+            }
             other => {
                 log::debug!("GFX: unsupported codec_id 0x{other:04X} in WireToSurface1");
             }
@@ -425,6 +457,203 @@ impl GfxProcessor {
         Vec::new()
     }
 
+    fn handle_wire_to_surface_2(&mut self, body: &[u8]) -> Vec<DvcMessage> {
+        let wts = match WireToSurface2::parse(body) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("GFX: WireToSurface2 parse error: {e}");
+                self.record_pipeline_error("wire_to_surface_parse_error");
+                return Vec::new();
+            }
+        };
+        if wts.codec_id != CODEC_CAPROGRESSIVE {
+            log::debug!(
+                "GFX: unsupported codec_id 0x{:04X} in WireToSurface2",
+                wts.codec_id
+            );
+            return Vec::new();
+        }
+        self.note_codec("progressive");
+
+        let Some(surface) = self.surfaces.get_surface_mut(wts.surface_id) else {
+            return Vec::new();
+        };
+        let context = self
+            .progressive
+            .entry((wts.surface_id, wts.codec_context_id))
+            .or_default();
+        match context.decode(
+            &wts.bitmap_data,
+            &mut surface.rgba,
+            surface.width,
+            surface.height,
+        ) {
+            Ok(rects) => {
+                for rect in rects {
+                    self.emit_surface_rect(
+                        wts.surface_id,
+                        rect.left,
+                        rect.top,
+                        rect.right - rect.left,
+                        rect.bottom - rect.top,
+                    );
+                }
+            }
+            Err(e) => {
+                log::warn!("GFX: progressive decode error: {e}");
+                self.record_pipeline_error("progressive_decode_error");
+            }
+        }
+        Vec::new()
+    }
+
+    fn handle_delete_encoding_ctx(&mut self, body: &[u8]) -> Vec<DvcMessage> {
+        match DeleteEncodingContext::parse(body) {
+            Ok(dec) => {
+                self.progressive
+                    .remove(&(dec.surface_id, dec.codec_context_id));
+            }
+            Err(e) => {
+                log::warn!("GFX: DeleteEncodingContext parse error: {e}");
+                self.record_pipeline_error("delete_encoding_ctx_parse_error");
+            }
+        }
+        Vec::new()
+    }
+
+    fn decode_planar(&mut self, wts: &WireToSurface1) {
+        let dest_w = wts.dest_rect.right.saturating_sub(wts.dest_rect.left);
+        let dest_h = wts.dest_rect.bottom.saturating_sub(wts.dest_rect.top);
+        match planar::decode(&wts.bitmap_data, dest_w, dest_h) {
+            Ok(rgba) => {
+                self.surfaces.blit_to_surface(
+                    wts.surface_id,
+                    &rgba,
+                    dest_w as u32,
+                    wts.dest_rect.left,
+                    wts.dest_rect.top,
+                    dest_w,
+                    dest_h,
+                );
+                self.emit_surface_rect(
+                    wts.surface_id,
+                    wts.dest_rect.left,
+                    wts.dest_rect.top,
+                    dest_w,
+                    dest_h,
+                );
+            }
+            Err(e) => {
+                log::warn!("GFX: planar decode error: {e}");
+                self.record_pipeline_error("planar_decode_error");
+            }
+        }
+    }
+
+    fn decode_remotefx(&mut self, wts: &WireToSurface1) {
+        let Some(surface) = self.surfaces.get_surface_mut(wts.surface_id) else {
+            return;
+        };
+        match self.rfx.decode(
+            &wts.bitmap_data,
+            wts.dest_rect,
+            &mut surface.rgba,
+            surface.width,
+            surface.height,
+        ) {
+            Ok(rects) => {
+                for rect in rects {
+                    self.emit_surface_rect(
+                        wts.surface_id,
+                        rect.left,
+                        rect.top,
+                        rect.right - rect.left,
+                        rect.bottom - rect.top,
+                    );
+                }
+            }
+            Err(e) => {
+                log::warn!("GFX: RemoteFX decode error: {e}");
+                self.record_pipeline_error("remotefx_decode_error");
+            }
+        }
+    }
+
+    /// ClearCodec layers draw over what is already on the surface, so the
+    /// destination rect is read back, decoded onto and written again.
+    fn decode_clearcodec(&mut self, wts: &WireToSurface1) {
+        let dest_w = wts.dest_rect.right.saturating_sub(wts.dest_rect.left);
+        let dest_h = wts.dest_rect.bottom.saturating_sub(wts.dest_rect.top);
+        let Some((w, h, mut rgba)) = self.surfaces.read_rect(
+            wts.surface_id,
+            wts.dest_rect.left,
+            wts.dest_rect.top,
+            dest_w,
+            dest_h,
+        ) else {
+            return;
+        };
+        if (w, h) != (dest_w, dest_h) {
+            log::warn!(
+                "GFX: ClearCodec dest rect exceeds surface {}",
+                wts.surface_id
+            );
+            self.record_pipeline_error("clearcodec_decode_error");
+            return;
+        }
+        if let Err(e) = self
+            .clear_codec
+            .decode(&wts.bitmap_data, dest_w, dest_h, &mut rgba)
+        {
+            log::warn!("GFX: ClearCodec decode error: {e}");
+            self.record_pipeline_error("clearcodec_decode_error");
+            return;
+        }
+        self.surfaces.blit_to_surface(
+            wts.surface_id,
+            &rgba,
+            dest_w as u32,
+            wts.dest_rect.left,
+            wts.dest_rect.top,
+            dest_w,
+            dest_h,
+        );
+        self.emit_surface_rect(
+            wts.surface_id,
+            wts.dest_rect.left,
+            wts.dest_rect.top,
+            dest_w,
+            dest_h,
+        );
+    }
+
+    /// Send an updated surface rect to the session loop if the surface is
+    /// mapped to the output.
+    fn emit_surface_rect(&self, surface_id: u16, left: u16, top: u16, width: u16, height: u16) {
+        let Some((ox, oy)) = self
+            .surfaces
+            .get_surface(surface_id)
+            .and_then(|s| s.output_origin)
+        else {
+            return;
+        };
+        if let Some((width, height, rgba)) = self
+            .surfaces
+            .read_rect(surface_id, left, top, width, height)
+        {
+            if width == 0 || height == 0 {
+                return;
+            }
+            let _ = self.frame_tx.send(GfxOutput::Rgba(GfxFrame {
+                screen_x: ox as u16 + left,
+                screen_y: oy as u16 + top,
+                width,
+                height,
+                rgba,
+            }));
+        }
+    }
+
     fn decode_avc420(&mut self, wts: &WireToSurface1) {
         let avc = match Avc420BitmapStream::parse(&wts.bitmap_data) {
             Ok(a) => a,
@@ -561,7 +790,14 @@ impl DvcProcessor for GfxProcessor {
         log::info!("GFX: DVC channel opened (id={channel_id}), sending CAPS_ADVERTISE");
         // Channel opened, CAPS_ADVERTISE sent → Negotiating.
         self.set_channel_state(VirtualChannelState::Negotiating);
-        let caps = CapsAdvertisePdu::new_avc420();
+        // Only offer AVC if frames can actually be decoded (on the frontend
+        // in passthrough mode, otherwise by a local H.264 decoder); without it
+        // the server falls back to ClearCodec/Planar/Progressive.
+        if !self.nal_passthrough {
+            self.ensure_decoder();
+        }
+        let avc = self.nal_passthrough || self.h264_decoder.is_some();
+        let caps = CapsAdvertisePdu::new(avc);
         Ok(vec![Box::new(caps) as DvcMessage])
     }
 
@@ -600,6 +836,10 @@ impl DvcProcessor for GfxProcessor {
                 x if x == GfxCmdId::StartFrame as u16 => self.handle_start_frame(body),
                 x if x == GfxCmdId::EndFrame as u16 => self.handle_end_frame(body),
                 x if x == GfxCmdId::WireToSurface1 as u16 => self.handle_wire_to_surface_1(body),
+                x if x == GfxCmdId::WireToSurface2 as u16 => self.handle_wire_to_surface_2(body),
+                x if x == GfxCmdId::DeleteEncodingCtx as u16 => {
+                    self.handle_delete_encoding_ctx(body)
+                }
                 x if x == GfxCmdId::ResetGraphics as u16 => self.handle_reset_graphics(body),
                 other => {
                     log::debug!("GFX: unhandled cmd_id 0x{other:04X}");
@@ -618,6 +858,8 @@ impl DvcProcessor for GfxProcessor {
         log::info!("GFX: DVC channel closed (id={channel_id})");
         self.surfaces.reset();
         self.h264_decoder = None;
+        self.clear_codec.reset();
+        self.progressive.clear();
         // Channel closed but re-openable → back to Registered (mirrors AUDIN).
         self.set_channel_state(VirtualChannelState::Registered);
     }
@@ -729,8 +971,8 @@ mod tests {
         assert_eq!(handle.lock().unwrap().summary.ready_count, 1);
 
         // WireToSurface1 with a bitmap that fails AVC420 parse: a malformed
-        // CAVIDEO frame. This must increment pipeline_errors but NOT fault.
-        // Build a minimal WireToSurface1 body with codec=CAVIDEO and a too-short
+        // AVC420 frame. This must increment pipeline_errors but NOT fault.
+        // Build a minimal WireToSurface1 body with codec=AVC420 and a too-short
         // bitmap stream so Avc420BitmapStream::parse fails.
         // (We invoke the recoverable path directly to keep the test focused on
         // the fault distinction rather than the wire layout.)
@@ -82,6 +82,10 @@ impl SurfaceManager {
         self.surfaces.get(&surface_id)
     }
 
+    pub fn get_surface_mut(&mut self, surface_id: u16) -> Option<&mut GfxSurface> {
+        self.surfaces.get_mut(&surface_id)
+    }
+
     /// Number of currently-allocated surfaces. Surfaced into the GFX
     /// diagnostics snapshot so the panel can show "surfaces active".
     pub fn active_count(&self) -> u16 {
@@ -129,6 +133,30 @@ impl SurfaceManager {
         true
     }
 
+    /// Copy a rect out of a surface as contiguous RGBA, clamped to the
+    /// surface bounds. Returns the clamped `(width, height)` with the pixels,
+    /// or None if the surface does not exist.
+    pub fn read_rect(
+        &self,
+        surface_id: u16,
+        left: u16,
+        top: u16,
+        width: u16,
+        height: u16,
+    ) -> Option<(u16, u16, Vec<u8>)> {
+        let surface = self.surfaces.get(&surface_id)?;
+        let cw = width.min(surface.width.saturating_sub(left));
+        let ch = height.min(surface.height.saturating_sub(top));
+        let dst_stride = surface.width as usize * 4;
+        let row_len = cw as usize * 4;
+        let mut out = Vec::with_capacity(row_len * ch as usize);
+        for row in 0..ch as usize {
+            let offset = (top as usize + row) * dst_stride + left as usize * 4;
+            out.extend_from_slice(&surface.rgba[offset..offset + row_len]);
+        }
+        Some((cw, ch, out))
+    }
+
     /// Reset all surfaces (e.g. on RDPGFX_RESET_GRAPHICS).
     /// Recycled buffers are returned to the pool for reuse.
     pub fn reset(&mut self) {
@@ -294,6 +322,19 @@ mod tests {
         // Second row may or may not be written depending on src_data bounds
     }
 
+    #[test]
+    fn read_rect_returns_clamped_region() {
+        let mut mgr = SurfaceManager::new();
+        mgr.create_surface(1, 4, 4);
+        mgr.blit_to_surface(1, &[0xCC; 2 * 2 * 4], 2, 2, 2, 2, 2);
+        let (w, h, rgba) = mgr.read_rect(1, 2, 1, 4, 4).unwrap();
+        assert_eq!((w, h), (2, 3));
+        assert_eq!(rgba.len(), 2 * 3 * 4);
+        assert_eq!(&rgba[0..8], &[0; 8]);
+        assert_eq!(&rgba[8..], &[0xCC; 16]);
+        assert!(mgr.read_rect(99, 0, 0, 1, 1).is_none());
+    }
+
     #[test]
     fn reset_clears_all_surfaces() {
         let mut mgr = SurfaceManager::new();
@@ -11,6 +11,18 @@ pub struct ClipboardFilePayload {
     pub is_directory: bool,
 }
 
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RdpRailExecPayload {
+    pub program: String,
+    #[serde(default)]
+    pub arguments: String,
+    #[serde(default)]
+    pub working_dir: String,
+    #[serde(default)]
+    pub expand_arguments: bool,
+}
+
 const MAX_RDP_INPUT_ACTIONS: usize = 4_096;
 const MAX_RDP_CLIPBOARD_TEXT_BYTES: usize = 4 * 1024 * 1024;
 const MAX_RDP_CLIPBOARD_FILES: usize = 1_024;
@@ -913,6 +925,121 @@ pub async fn rdp_toggle_feature(
     }
 }
 
+// ── RemoteApp (RAIL) ────────────────────────────────────────────────
+
+/// Launch a program in the session's RemoteApp channel. The outcome arrives
+/// as an `rdp://rail-exec-result` event.
+#[tauri::command]
+pub async fn rdp_rail_exec(
+    state: tauri::State<'_, RdpServiceState>,
+    session_id: String,
+    request: RdpRailExecPayload,
+) -> Result<(), String> {
+    let request = crate::rdp::rail::pdu::ExecRequest {
+        program: request.program,
+        arguments: request.arguments,
+        working_dir: request.working_dir,
+        flags: if request.expand_arguments {
+            crate::rdp::rail::pdu::TS_RAIL_EXEC_FLAG_EXPAND_ARGUMENTS
+        } else {
+            0
+        },
+    };
+    // Reject oversized fields here rather than in the session worker log.
+    crate::rdp::rail::pdu::encode_exec(&request)?;
+
+    let service = state.lock().await;
+    if let Some(conn) = service.connections.get(&session_id) {
+        enqueue_session_command(
+            &conn.cmd_tx,
+            RdpCommand::Rail(crate::rdp::rail::RailCommand::Exec(Box::new(request))),
+        )?;
+        Ok(())
+    } else {
+        Err(format!("RDP session {session_id} not found"))
+    }
+}
+
+/// Activate, move, minimize/close or otherwise act on a RemoteApp window.
+#[tauri::command]
+pub async fn rdp_rail_window_action(
+    state: tauri::State<'_, RdpServiceState>,
+    session_id: String,
+    window_id: u32,
+    action: crate::rdp::rail::RailWindowAction,
+) -> Result<(), String> {
+    let service = state.lock().await;
+    if let Some(conn) = service.connections.get(&session_id) {
+        enqueue_session_command(
+            &conn.cmd_tx,
+            RdpCommand::Rail(crate::rdp::rail::RailCommand::Window { window_id, action }),
+        )?;
+        Ok(())
+    } else {
+        Err(format!("RDP session {session_id} not found"))
+    }
+}
+
+/// Report the local language bar state to the RemoteApp server.
+#[tauri::command]
+pub async fn rdp_rail_set_language_bar(
+    state: tauri::State<'_, RdpServiceState>,
+    session_id: String,
+    status: u32,
+) -> Result<(), String> {
+    let service = state.lock().await;
+    if let Some(conn) = service.connections.get(&session_id) {
+        enqueue_session_command(
+            &conn.cmd_tx,
+            RdpCommand::Rail(crate::rdp::rail::RailCommand::LanguageBar { status }),
+        )?;
+        Ok(())
+    } else {
+        Err(format!("RDP session {session_id} not found"))
+    }
+}
+
+/// Stream one RemoteApp window's pixels to its own frame channel. Frames use
+/// the desktop RGBA rect format with window-relative coordinates.
+#[tauri::command]
+pub async fn rdp_rail_attach_window(
+    state: tauri::State<'_, RdpServiceState>,
+    session_id: String,
+    window_id: u32,
+    frame_channel: Channel<InvokeResponseBody>,
+) -> Result<(), String> {
+    let service = state.lock().await;
+    if let Some(conn) = service.connections.get(&session_id) {
+        let channel: DynFrameChannel = std::sync::Arc::new(TauriFrameChannel(frame_channel));
+        enqueue_session_command(
+            &conn.cmd_tx,
+            RdpCommand::Rail(crate::rdp::rail::RailCommand::AttachWindow { window_id, channel }),
+        )?;
+        Ok(())
+    } else {
+        Err(format!("RDP session {session_id} not found"))
+    }
+}
+
+/// Stop streaming a RemoteApp window.
+#[tauri::command]
+pub async fn rdp_rail_detach_window(
+    state: tauri::State<'_, RdpServiceState>,
+    session_id: String,
+    window_id: u32,
+) -> Result<(), String> {
+    let service = state.lock().await;
+    if let Some(conn) = service.connections.get(&session_id) {
+        enqueue_session_command(
+            &conn.cmd_tx,
+            RdpCommand::Rail(crate::rdp::rail::RailCommand::DetachWindow { window_id }),
+        )?;
+        Ok(())
+    } else {
+        Err(format!("RDP session {session_id} not found"))
+    }
+}
+
 #[cfg(test)]
 mod command_queue_surface_tests {
     use super::*;
@@ -108,6 +108,7 @@ pub fn run_diagnostics(
         pointer_software_rendering: false,
         allow_hybrid_ex: settings.allow_hybrid_ex,
         sspi_package_list: None,
+        remote_app_program: None,
     };
 
     let server_socket_addr = std::net::SocketAddr::new(socket_addr.ip(), port);
@@ -464,6 +465,7 @@ fn probe_color_depth(
         pointer_software_rendering: false,
         allow_hybrid_ex: settings.allow_hybrid_ex,
         sspi_package_list: None,
+        remote_app_program: None,
     };
 
     let server_addr = std::net::SocketAddr::new(socket_addr.ip(), port);
@@ -697,6 +699,7 @@ fn probe_alternative_protocols(
                         pointer_software_rendering: false,
                         allow_hybrid_ex: hybrid_ex,
                         sspi_package_list: None,
+                        remote_app_program: None,
                     };
 
                     let server_addr = std::net::SocketAddr::new(socket_addr.ip(), port);
@@ -12,6 +12,7 @@ pub mod input;
 #[cfg(feature = "rdp-multimon")]
 pub mod multimon;
 mod network;
+pub mod rail;
 pub mod rdpdr;
 pub mod session_poller;
 pub mod session_runner;
@@ -0,0 +1,1299 @@
+//! RemoteApp (RAIL) static virtual channel implementation.
+//!
+//! Implements the client side of MS-RDPERP: the `rail` channel handshake,
+//! program launch (Execute / Execute Result), system parameters, window
+//! commands and the language bar. Window and notification-icon state arrives
+//! as windowing orders on fast-path output; the session runner feeds those
+//! frames through [`RailClient::ingest_fastpath`]. Each remote window can have
+//! its own frame channel attached, and desktop updates are cropped to the
+//! window and delivered with window-relative coordinates.
+
+pub mod orders;
+pub mod pdu;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::ironrdp::pdu::gcc::ChannelName;
+use crate::ironrdp::pdu::PduResult;
+use crate::ironrdp_core::impl_as_any;
+use crate::ironrdp_svc::{SvcClientProcessor, SvcEncode, SvcMessage, SvcProcessor};
+use serde::{Deserialize, Serialize};
+use sorng_core::events::DynEventEmitter;
+
+use self::orders::{FastPathOrderSniffer, NotifyIconUpdate, NotifyInfoTip, WindowOrder};
+use self::pdu::*;
+use super::frame_channel::{
+    send_accounted_frame, DynFrameChannel, FrameDeliveryAccounting, FramePayloadKind,
+};
+use super::session_state::ChannelSummary;
+use super::settings::RemoteAppConfig;
+use super::virtual_channels::{
+    VirtualChannelDescriptor, VirtualChannelKind, VirtualChannelPriority, VirtualChannelState,
+};
+
+const RAIL_CHANNEL_NAME: &str = "rail";
+
+/// Window show state for a hidden window (SW_HIDE).
+const SW_HIDE: u8 = 0;
+
+// ── Frontend commands ───────────────────────────────────────────────
+
+/// Window system commands accepted by the Client System Command PDU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RailSysCommand {
+    Size,
+    Move,
+    Minimize,
+    Maximize,
+    Close,
+    KeyMenu,
+    Restore,
+    Default,
+}
+
+impl RailSysCommand {
+    /// The SC_* value sent on the wire (MS-RDPERP 2.2.2.6.3).
+    pub fn code(self) -> u16 {
+        match self {
+            Self::Size => 0xF000,
+            Self::Move => 0xF010,
+            Self::Minimize => 0xF020,
+            Self::Maximize => 0xF030,
+            Self::Close => 0xF060,
+            Self::KeyMenu => 0xF100,
+            Self::Restore => 0xF120,
+            Self::Default => 0xF160,
+        }
+    }
+}
+
+/// Per-window actions the frontend can request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "action",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum RailWindowAction {
+    Activate {
+        enabled: bool,
+    },
+    SysCommand {
+        command: RailSysCommand,
+    },
+    SysMenu {
+        x: i16,
+        y: i16,
+    },
+    Move {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    },
+    NotifyEvent {
+        notify_icon_id: u32,
+        message: u32,
+    },
+    GetAppId,
+}
+
+/// Commands routed to the RAIL channel by the session runner.
+pub enum RailCommand {
+    Exec(Box<ExecRequest>),
+    Window {
+        window_id: u32,
+        action: RailWindowAction,
+    },
+    LanguageBar {
+        status: u32,
+    },
+    AttachWindow {
+        window_id: u32,
+        channel: DynFrameChannel,
+    },
+    DetachWindow {
+        window_id: u32,
+    },
+}
+
+impl RailCommand {
+    /// Heap bytes carried by the command, for command-queue accounting.
+    pub fn payload_len(&self) -> usize {
+        match self {
+            Self::Exec(request) => request
+                .program
+                .len()
+                .saturating_add(request.working_dir.len())
+                .saturating_add(request.arguments.len()),
+            _ => 0,
+        }
+    }
+}
+
+// ── Window state ────────────────────────────────────────────────────
+
+/// Last known state of one remote window, as reported to the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RailWindow {
+    pub window_id: u32,
+    pub owner_window_id: u32,
+    pub root_parent_id: u32,
+    pub style: u32,
+    pub extended_style: u32,
+    pub show_state: u8,
+    pub title: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub client_offset_x: i32,
+    pub client_offset_y: i32,
+    pub client_width: u32,
+    pub client_height: u32,
+    pub client_delta_x: i32,
+    pub client_delta_y: i32,
+    pub visible_offset_x: i32,
+    pub visible_offset_y: i32,
+    pub window_rects: Vec<RailRect>,
+    pub visibility_rects: Vec<RailRect>,
+}
+
+impl RailWindow {
+    fn new(window_id: u32) -> Self {
+        Self {
+            window_id,
+            ..Default::default()
+        }
+    }
+
+    /// Apply an order; returns true when the on-screen area changed.
+    fn apply(&mut self, update: &orders::WindowStateUpdate) -> bool {
+        let before = (self.x, self.y, self.width, self.height, self.show_state);
+        if let Some(v) = update.owner_window_id {
+            self.owner_window_id = v;
+        }
+        if let Some(v) = update.root_parent {
+            self.root_parent_id = v;
+        }
+        if let Some((style, extended)) = update.style {
+            self.style = style;
+            self.extended_style = extended;
+        }
+        if let Some(v) = update.show_state {
+            self.show_state = v;
+        }
+        if let Some(ref v) = update.title {
+            self.title.clone_from(v);
+        }
+        if let Some((x, y)) = update.window_offset {
+            self.x = x;
+            self.y = y;
+        }
+        if let Some((w, h)) = update.window_size {
+            self.width = w;
+            self.height = h;
+        }
+        if let Some((x, y)) = update.client_offset {
+            self.client_offset_x = x;
+            self.client_offset_y = y;
+        }
+        if let Some((w, h)) = update.client_size {
+            self.client_width = w;
+            self.client_height = h;
+        }
+        if let Some((x, y)) = update.window_client_delta {
+            self.client_delta_x = x;
+            self.client_delta_y = y;
+        }
+        if let Some((x, y)) = update.visible_offset {
+            self.visible_offset_x = x;
+            self.visible_offset_y = y;
+        }
+        if let Some(ref v) = update.window_rects {
+            self.window_rects.clone_from(v);
+        }
+        let visibility_changed = match update.visibility_rects {
+            Some(ref v) if *v != self.visibility_rects => {
+                self.visibility_rects.clone_from(v);
+                true
+            }
+            _ => false,
+        };
+        visibility_changed || before != (self.x, self.y, self.width, self.height, self.show_state)
+    }
+
+    /// The part of the window inside a `desktop_width x desktop_height`
+    /// desktop as (x, y, w, h), or `None` when hidden or fully off-screen.
+    fn desktop_bounds(&self, desktop_width: u16, desktop_height: u16) -> Option<Rect> {
+        if self.show_state == SW_HIDE {
+            return None;
+        }
+        let right = (self.x as i64 + self.width as i64).min(desktop_width as i64);
+        let bottom = (self.y as i64 + self.height as i64).min(desktop_height as i64);
+        let left = (self.x as i64).max(0);
+        let top = (self.y as i64).max(0);
+        (right > left && bottom > top).then(|| Rect {
+            x: left as u16,
+            y: top as u16,
+            w: (right - left) as u16,
+            h: (bottom - top) as u16,
+        })
+    }
+}
+
+/// Last known state of one notification-area icon.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RailNotifyIcon {
+    pub window_id: u32,
+    pub notify_icon_id: u32,
+    pub version: u32,
+    pub tooltip: String,
+    pub info_tip: Option<NotifyInfoTip>,
+    pub state: u32,
+}
+
+impl RailNotifyIcon {
+    fn apply(&mut self, update: NotifyIconUpdate) {
+        if let Some(v) = update.version {
+            self.version = v;
+        }
+        if let Some(v) = update.tooltip {
+            self.tooltip = v;
+        }
+        if update.info_tip.is_some() {
+            self.info_tip = update.info_tip;
+        }
+        if let Some(v) = update.state {
+            self.state = v;
+        }
+    }
+}
+
+/// Desktop-space rectangle (exclusive extent).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub w: u16,
+    pub h: u16,
+}
+
+impl Rect {
+    fn intersect(self, other: Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x as u32 + self.w as u32).min(other.x as u32 + other.w as u32);
+        let bottom = (self.y as u32 + self.h as u32).min(other.y as u32 + other.h as u32);
+        (right > left as u32 && bottom > top as u32).then(|| Rect {
+            x: left,
+            y: top,
+            w: (right - left as u32) as u16,
+            h: (bottom - top as u32) as u16,
+        })
+    }
+}
+
+// ── Diagnostics ─────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RailChannelCounters {
+    pub payloads_received: u64,
+    pub pdus_sent: u64,
+    pub malformed_payloads: u64,
+    pub window_orders: u64,
+    pub malformed_orders: u64,
+    pub exec_failures: u64,
+    pub window_frames: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RailChannelDiagnostics {
+    pub descriptor: VirtualChannelDescriptor,
+    pub ready: bool,
+    pub failed: bool,
+    pub server_build: Option<u32>,
+    pub window_count: u16,
+    pub notify_icon_count: u16,
+    pub attached_window_streams: u16,
+    pub counters: RailChannelCounters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RailState {
+    WaitingHandshake,
+    Ready,
+}
+
+// ── Channel processor ───────────────────────────────────────────────
+
+/// RAIL static virtual channel processor.
+pub struct RailClient {
+    session_id: String,
+    emitter: DynEventEmitter,
+    remote_app: RemoteAppConfig,
+    desktop_width: u16,
+    desktop_height: u16,
+    state: RailState,
+    server_build: Option<u32>,
+    pending_exec: Vec<ExecRequest>,
+    windows: BTreeMap<u32, RailWindow>,
+    notify_icons: BTreeMap<(u32, u32), RailNotifyIcon>,
+    window_streams: HashMap<u32, DynFrameChannel>,
+    sniffer: FastPathOrderSniffer,
+    counters: RailChannelCounters,
+    last_error_class: Option<&'static str>,
+}
+
+impl fmt::Debug for RailClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RailClient")
+            .field("session_id", &self.session_id)
+            .field("program", &self.remote_app.program)
+            .field("state", &self.state)
+            .field("windows", &self.windows.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl_as_any!(RailClient);
+impl SvcClientProcessor for RailClient {}
+
+impl RailClient {
+    pub fn new(
+        session_id: String,
+        emitter: DynEventEmitter,
+        remote_app: RemoteAppConfig,
+        desktop_width: u16,
+        desktop_height: u16,
+    ) -> Self {
+        let launch = ExecRequest {
+            program: remote_app.program.clone(),
+            working_dir: remote_app.working_dir.clone(),
+            arguments: remote_app.arguments.clone(),
+            flags: if remote_app.expand_arguments {
+                TS_RAIL_EXEC_FLAG_EXPAND_ARGUMENTS
+            } else {
+                0
+            },
+        };
+        Self {
+            session_id,
+            emitter,
+            remote_app,
+            desktop_width,
+            desktop_height,
+            state: RailState::WaitingHandshake,
+            server_build: None,
+            pending_exec: vec![launch],
+            windows: BTreeMap::new(),
+            notify_icons: BTreeMap::new(),
+            window_streams: HashMap::new(),
+            sniffer: FastPathOrderSniffer::new(),
+            counters: RailChannelCounters::default(),
+            last_error_class: None,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.state == RailState::Ready
+    }
+
+    pub fn window(&self, window_id: u32) -> Option<&RailWindow> {
+        self.windows.get(&window_id)
+    }
+
+    pub fn channel_summary(&self) -> ChannelSummary {
+        let failed = self.last_error_class.is_some();
+        ChannelSummary {
+            enabled_count: 1,
+            ready_count: u16::from(self.is_ready() && !failed),
+            failed_count: u16::from(failed),
+        }
+    }
+
+    pub fn channel_diagnostics(&self) -> RailChannelDiagnostics {
+        let failed = self.last_error_class.is_some();
+        let ready = self.is_ready() && !failed;
+        let mut descriptor = VirtualChannelDescriptor::new(
+            RAIL_CHANNEL_NAME,
+            VirtualChannelKind::Static,
+            VirtualChannelPriority::High,
+            true,
+        );
+        descriptor.state = if failed {
+            VirtualChannelState::Faulted
+        } else if ready {
+            VirtualChannelState::Ready
+        } else {
+            VirtualChannelState::Negotiating
+        };
+        descriptor.messages_received = self.counters.payloads_received;
+        descriptor.messages_sent = self.counters.pdus_sent;
+        descriptor.last_error_class = self.last_error_class.map(str::to_string);
+
+        let count = |n: usize| n.min(u16::MAX as usize) as u16;
+        RailChannelDiagnostics {
+            descriptor,
+            ready,
+            failed,
+            server_build: self.server_build,
+            window_count: count(self.windows.len()),
+            notify_icon_count: count(self.notify_icons.len()),
+            attached_window_streams: count(self.window_streams.len()),
+            counters: self.counters,
+        }
+    }
+
+    /// Track a desktop resize. Once the channel is up the server is told
+    /// about the new work area so maximized windows fit the new size.
+    pub fn set_desktop_size(&mut self, width: u16, height: u16) -> Vec<SvcMessage> {
+        let changed = (width, height) != (self.desktop_width, self.desktop_height);
+        self.desktop_width = width;
+        self.desktop_height = height;
+        if !changed || !self.is_ready() {
+            return Vec::new();
+        }
+        let area = self.desktop_rect();
+        self.make_messages(vec![
+            encode_sysparam(ClientSysParam::DisplayChange(area)),
+            encode_sysparam(ClientSysParam::WorkArea(area)),
+        ])
+    }
+
+    fn desktop_rect(&self) -> RailRect {
+        RailRect {
+            left: 0,
+            top: 0,
+            right: self.desktop_width.min(i16::MAX as u16) as i16,
+            bottom: self.desktop_height.min(i16::MAX as u16) as i16,
+        }
+    }
+
+    fn emit(&self, event: &str, mut payload: serde_json::Value) {
+        if let Some(map) = payload.as_object_mut() {
+            map.insert("sessionId".into(), self.session_id.clone().into());
+        }
+        let _ = self.emitter.emit_event(event, payload);
+    }
+
+    fn make_messages(&mut self, pdus: Vec<Vec<u8>>) -> Vec<SvcMessage> {
+        self.counters.pdus_sent = self.counters.pdus_sent.saturating_add(pdus.len() as u64);
+        pdus.into_iter()
+            .map(|data| SvcMessage::from(RailPdu(data)))
+            .collect()
+    }
+
+    // ── Server PDUs ──
+
+    /// Transport-agnostic RAIL PDU processing. Returns raw response PDUs.
+    pub fn process_rail_payload(&mut self, payload: &[u8]) -> Vec<Vec<u8>> {
+        self.counters.payloads_received = self.counters.payloads_received.saturating_add(1);
+        let pdu = match parse_server_pdu(payload) {
+            Ok(pdu) => pdu,
+            Err(e) => {
+                log::warn!("RAIL session {}: {e}", self.session_id);
+                self.counters.malformed_payloads =
+                    self.counters.malformed_payloads.saturating_add(1);
+                return Vec::new();
+            }
+        };
+
+        match pdu {
+            ServerRailPdu::Handshake { build_number } => self.on_handshake(build_number, None),
+            ServerRailPdu::HandshakeEx {
+                build_number,
+                flags,
+            } => self.on_handshake(build_number, Some(flags)),
+            ServerRailPdu::ExecResult(result) => {
+                let success = result.exec_result == RAIL_EXEC_S_OK;
+                if success {
+                    log::info!(
+                        "RAIL session {}: launched '{}'",
+                        self.session_id,
+                        result.program
+                    );
+                } else {
+                    self.counters.exec_failures = self.counters.exec_failures.saturating_add(1);
+                    log::warn!(
+                        "RAIL session {}: launching '{}' failed: {} (0x{:08X})",
+                        self.session_id,
+                        result.program,
+                        exec_result_name(result.exec_result),
+                        result.raw_result
+                    );
+                }
+                self.emit(
+                    "rdp://rail-exec-result",
+                    serde_json::json!({
+                        "program": result.program,
+                        "success": success,
+                        "result": exec_result_name(result.exec_result),
+                        "resultCode": result.exec_result,
+                        "rawResult": result.raw_result,
+                    }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::SysParam { param, value } => {
+                let name = match param {
+                    SPI_SETSCREENSAVEACTIVE => "screenSaveActive",
+                    SPI_SETSCREENSAVESECURE => "screenSaveSecure",
+                    _ => "unknown",
+                };
+                self.emit(
+                    "rdp://rail-sysparam",
+                    serde_json::json!({ "param": name, "paramId": param, "value": value }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::LocalMoveSize(m) => {
+                self.emit(
+                    "rdp://rail-move-size",
+                    serde_json::json!({
+                        "windowId": m.window_id,
+                        "phase": if m.is_move_size_start { "start" } else { "end" },
+                        "moveSizeType": m.move_size_type,
+                        "x": m.pos_x,
+                        "y": m.pos_y,
+                    }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::MinMaxInfo(m) => {
+                self.emit(
+                    "rdp://rail-min-max-info",
+                    serde_json::json!({
+                        "windowId": m.window_id,
+                        "maxWidth": m.max_width,
+                        "maxHeight": m.max_height,
+                        "maxPosX": m.max_pos_x,
+                        "maxPosY": m.max_pos_y,
+                        "minTrackWidth": m.min_track_width,
+                        "minTrackHeight": m.min_track_height,
+                        "maxTrackWidth": m.max_track_width,
+                        "maxTrackHeight": m.max_track_height,
+                    }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::LanguageBarInfo { status } => {
+                self.emit(
+                    "rdp://rail-language-bar",
+                    serde_json::json!({ "status": status }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::GetAppIdResponse {
+                window_id,
+                application_id,
+            } => {
+                self.emit(
+                    "rdp://rail-app-id",
+                    serde_json::json!({ "windowId": window_id, "applicationId": application_id }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::Cloak { window_id, cloaked } => {
+                self.emit(
+                    "rdp://rail-cloak",
+                    serde_json::json!({ "windowId": window_id, "cloaked": cloaked }),
+                );
+                Vec::new()
+            }
+            ServerRailPdu::ZOrderSync { .. } => Vec::new(),
+            ServerRailPdu::Unknown { order_type } => {
+                log::debug!(
+                    "RAIL session {}: ignoring order type 0x{order_type:04X}",
+                    self.session_id
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Answer the server handshake with the client start-up sequence
+    /// (MS-RDPERP 1.3.2.1), then launch the configured program and any
+    /// Execute requests queued before the channel came up.
+    fn on_handshake(&mut self, build_number: u32, ex_flags: Option<u32>) -> Vec<Vec<u8>> {
+        log::info!(
+            "RAIL session {}: server handshake (build {build_number}, ex_flags={ex_flags:?})",
+            self.session_id
+        );
+        self.server_build = Some(build_number);
+        self.state = RailState::Ready;
+
+        let area = self.desktop_rect();
+        let mut out = vec![
+            encode_handshake(CLIENT_BUILD_NUMBER),
+            encode_client_status(TS_RAIL_CLIENTSTATUS_ALLOWLOCALMOVESIZE),
+            encode_sysparam(ClientSysParam::HighContrast),
+            encode_sysparam(ClientSysParam::KeyboardPref(false)),
+            encode_sysparam(ClientSysParam::DragFullWindows(false)),
+            encode_sysparam(ClientSysParam::KeyboardCues(false)),
+            encode_sysparam(ClientSysParam::MouseButtonSwap(false)),
+            encode_sysparam(ClientSysParam::WorkArea(area)),
+            encode_sysparam(ClientSysParam::DisplayChange(area)),
+        ];
+        for request in std::mem::take(&mut self.pending_exec) {
+            match encode_exec(&request) {
+                Ok(pdu) => out.push(pdu),
+                Err(e) => log::warn!("RAIL session {}: {e}", self.session_id),
+            }
+        }
+
+        self.emit(
+            "rdp://rail-ready",
+            serde_json::json!({
+                "serverBuild": build_number,
+                "handshakeEx": ex_flags.is_some(),
+                "handshakeFlags": ex_flags.unwrap_or(0),
+                "program": self.remote_app.program,
+            }),
+        );
+        out
+    }
+
+    // ── Commands ──
+
+    /// Apply a frontend command, returning the PDUs to send.
+    pub fn handle_command(&mut self, command: RailCommand) -> Result<Vec<SvcMessage>, String> {
+        let pdus = self.command_pdus(command)?;
+        Ok(self.make_messages(pdus))
+    }
+
+    fn command_pdus(&mut self, command: RailCommand) -> Result<Vec<Vec<u8>>, String> {
+        match command {
+            RailCommand::Exec(request) => {
+                // Validate now so the caller hears about bad input even when
+                // the request has to wait for the handshake.
+                let pdu = encode_exec(&request)?;
+                if self.is_ready() {
+                    Ok(vec![pdu])
+                } else {
+                    self.pending_exec.push(*request);
+                    Ok(Vec::new())
+                }
+            }
+            RailCommand::Window { window_id, action } => {
+                if !self.is_ready() {
+                    return Err("RemoteApp channel is not ready".to_string());
+                }
+                if !self.windows.contains_key(&window_id)
+                    && !matches!(action, RailWindowAction::NotifyEvent { .. })
+                {
+                    return Err(format!("Unknown RemoteApp window {window_id:#x}"));
+                }
+                Ok(vec![match action {
+                    RailWindowAction::Activate { enabled } => encode_activate(window_id, enabled),
+                    RailWindowAction::SysCommand { command } => {
+                        encode_syscommand(window_id, command.code())
+                    }
+                    RailWindowAction::SysMenu { x, y } => encode_sysmenu(window_id, x, y),
+                    RailWindowAction::Move {
+                        left,
+                        top,
+                        right,
+                        bottom,
+                    } => encode_window_move(
+                        window_id,
+                        RailRect {
+                            left,
+                            top,
+                            right,
+                            bottom,
+                        },
+                    ),
+                    RailWindowAction::NotifyEvent {
+                        notify_icon_id,
+                        message,
+                    } => encode_notify_event(window_id, notify_icon_id, message),
+                    RailWindowAction::GetAppId => encode_get_appid_request(window_id),
+                }])
+            }
+            RailCommand::LanguageBar { status } => {
+                if !self.is_ready() {
+                    return Err("RemoteApp channel is not ready".to_string());
+                }
+                Ok(vec![encode_langbar_info(status)])
+            }
+            RailCommand::AttachWindow { window_id, channel } => {
+                self.window_streams.insert(window_id, channel);
+                Ok(Vec::new())
+            }
+            RailCommand::DetachWindow { window_id } => {
+                self.window_streams.remove(&window_id);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    // ── Windowing orders ──
+
+    /// Feed one fast-path output frame. Returns the attached windows whose
+    /// on-screen area changed and therefore need a full repaint.
+    pub fn ingest_fastpath(&mut self, frame: &[u8]) -> Vec<u32> {
+        let orders = match self.sniffer.ingest(frame) {
+            Ok(orders) => orders,
+            Err(e) => {
+                log::debug!("RAIL session {}: {e}", self.session_id);
+                self.counters.malformed_orders = self.counters.malformed_orders.saturating_add(1);
+                return Vec::new();
+            }
+        };
+        let mut repaint = Vec::new();
+        for order in orders {
+            self.counters.window_orders = self.counters.window_orders.saturating_add(1);
+            if let Some(window_id) = self.apply_order(order) {
+                if self.window_streams.contains_key(&window_id) && !repaint.contains(&window_id) {
+                    repaint.push(window_id);
+                }
+            }
+        }
+        repaint
+    }
+
+    /// Returns the window id when its on-screen area changed.
+    fn apply_order(&mut self, order: WindowOrder) -> Option<u32> {
+        match order {
+            WindowOrder::Window {
+                window_id,
+                new,
+                update,
+            } => {
+                let created = new || !self.windows.contains_key(&window_id);
+                let window = self
+                    .windows
+                    .entry(window_id)
+                    .or_insert_with(|| RailWindow::new(window_id));
+                let moved = window.apply(&update);
+                let window = window.clone();
+                self.emit(
+                    "rdp://rail-window",
+                    serde_json::json!({
+                        "action": if created { "created" } else { "updated" },
+                        "windowId": window_id,
+                        "window": window,
+                    }),
+                );
+                moved.then_some(window_id)
+            }
+            WindowOrder::WindowDeleted { window_id } => {
+                self.windows.remove(&window_id);
+                self.window_streams.remove(&window_id);
+                self.notify_icons
+                    .retain(|&(owner, _), _| owner != window_id);
+                self.emit(
+                    "rdp://rail-window",
+                    serde_json::json!({ "action": "deleted", "windowId": window_id }),
+                );
+                None
+            }
+            WindowOrder::NotifyIcon {
+                window_id,
+                notify_icon_id,
+                new,
+                update,
+            } => {
+                let key = (window_id, notify_icon_id);
+                let created = new || !self.notify_icons.contains_key(&key);
+                let icon = self
+                    .notify_icons
+                    .entry(key)
+                    .or_insert_with(|| RailNotifyIcon {
+                        window_id,
+                        notify_icon_id,
+                        ..Default::default()
+                    });
+                icon.apply(update);
+                let icon = icon.clone();
+                self.emit(
+                    "rdp://rail-notify-icon",
+                    serde_json::json!({
+                        "action": if created { "created" } else { "updated" },
+                        "windowId": window_id,
+                        "notifyIconId": notify_icon_id,
+                        "icon": icon,
+                    }),
+                );
+                None
+            }
+            WindowOrder::NotifyIconDeleted {
+                window_id,
+                notify_icon_id,
+            } => {
+                self.notify_icons.remove(&(window_id, notify_icon_id));
+                self.emit(
+                    "rdp://rail-notify-icon",
+                    serde_json::json!({
+                        "action": "deleted",
+                        "windowId": window_id,
+                        "notifyIconId": notify_icon_id,
+                    }),
+                );
+                None
+            }
+            WindowOrder::Desktop {
+                active_window_id,
+                z_order,
+            } => {
+                self.emit(
+                    "rdp://rail-desktop",
+                    serde_json::json!({ "activeWindowId": active_window_id, "zOrder": z_order }),
+                );
+                None
+            }
+        }
+    }
+
+    // ── Per-window frame streams ──
+
+    pub fn has_window_streams(&self) -> bool {
+        !self.window_streams.is_empty()
+    }
+
+    /// Desktop area of an attached window, for a Refresh Rect request.
+    pub fn window_area(&self, window_id: u32) -> Option<Rect> {
+        self.windows
+            .get(&window_id)?
+            .desktop_bounds(self.desktop_width, self.desktop_height)
+    }
+
+    /// Deliver dirty rects of the full desktop image (RGBA, `image_width`
+    /// pixels per row) to every attached window they overlap.
+    pub fn route_dirty_rects(
+        &mut self,
+        image: &[u8],
+        image_width: u16,
+        rects: &[(u16, u16, u16, u16)],
+        accounting: &FrameDeliveryAccounting,
+    ) {
+        for &(x, y, w, h) in rects {
+            self.route_pixels(image, image_width, (0, 0), Rect { x, y, w, h }, accounting);
+        }
+    }
+
+    /// Deliver a standalone RGBA rectangle (e.g. a decoded RDPGFX frame)
+    /// positioned at desktop (`x`, `y`).
+    pub fn route_rgba_rect(
+        &mut self,
+        rgba: &[u8],
+        rect: Rect,
+        accounting: &FrameDeliveryAccounting,
+    ) {
+        self.route_pixels(rgba, rect.w, (rect.x, rect.y), rect, accounting);
+    }
+
+    /// Push a window's whole current area from the desktop image.
+    pub fn push_window(
+        &mut self,
+        window_id: u32,
+        image: &[u8],
+        image_width: u16,
+        accounting: &FrameDeliveryAccounting,
+    ) {
+        let Some(area) = self.window_area(window_id) else {
+            return;
+        };
+        let Some(channel) = self.window_streams.get(&window_id) else {
+            return;
+        };
+        let window = &self.windows[&window_id];
+        if send_window_crop(
+            window,
+            channel,
+            image,
+            image_width,
+            (0, 0),
+            area,
+            accounting,
+        ) {
+            self.counters.window_frames = self.counters.window_frames.saturating_add(1);
+        }
+    }
+
+    fn route_pixels(
+        &mut self,
+        src: &[u8],
+        src_stride: u16,
+        src_origin: (u16, u16),
+        rect: Rect,
+        accounting: &FrameDeliveryAccounting,
+    ) {
+        let mut sent = 0u64;
+        for (window_id, channel) in &self.window_streams {
+            let Some(window) = self.windows.get(window_id) else {
+                continue;
+            };
+            let Some(area) = window
+                .desktop_bounds(self.desktop_width, self.desktop_height)
+                .and_then(|bounds| bounds.intersect(rect))
+            else {
+                continue;
+            };
+            if send_window_crop(
+                window, channel, src, src_stride, src_origin, area, accounting,
+            ) {
+                sent += 1;
+            }
+        }
+        self.counters.window_frames = self.counters.window_frames.saturating_add(sent);
+    }
+}
+
+/// Copy `area` (desktop coordinates) out of `src` and send it with a
+/// window-relative `[x, y, w, h]` u16 LE header, matching the desktop
+/// stream's RGBA rect format.
+fn send_window_crop(
+    window: &RailWindow,
+    channel: &DynFrameChannel,
+    src: &[u8],
+    src_stride: u16,
+    src_origin: (u16, u16),
+    area: Rect,
+    accounting: &FrameDeliveryAccounting,
+) -> bool {
+    let stride = src_stride as usize * 4;
+    let row_len = area.w as usize * 4;
+    let mut payload = Vec::with_capacity(8 + row_len * area.h as usize);
+    let rel_x = (area.x as i64 - window.x as i64) as u16;
+    let rel_y = (area.y as i64 - window.y as i64) as u16;
+    for v in [rel_x, rel_y, area.w, area.h] {
+        payload.extend_from_slice(&v.to_le_bytes());
+    }
+    let x0 = (area.x - src_origin.0) as usize * 4;
+    for row in 0..area.h as usize {
+        let start = (area.y - src_origin.1) as usize * stride + row * stride + x0;
+        let Some(pixels) = src.get(start..start + row_len) else {
+            return false;
+        };
+        payload.extend_from_slice(pixels);
+    }
+    send_accounted_frame(accounting, channel, FramePayloadKind::RgbaRect, payload).is_ok()
+}
+
+/// Wrapper to make raw bytes encodable as an SVC message.
+struct RailPdu(Vec<u8>);
+
+impl crate::ironrdp_core::Encode for RailPdu {
+    fn encode(
+        &self,
+        dst: &mut crate::ironrdp_core::WriteCursor<'_>,
+    ) -> crate::ironrdp_core::EncodeResult<()> {
+        crate::ironrdp_core::ensure_size!(in: dst, size: self.0.len());
+        dst.write_slice(&self.0);
+        Ok(())
+    }
+    fn name(&self) -> &'static str {
+        "RailPdu"
+    }
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl SvcEncode for RailPdu {}
+
+impl SvcProcessor for RailClient {
+    fn channel_name(&self) -> ChannelName {
+        ChannelName::from_static(b"rail\0\0\0\0")
+    }
+
+    fn start(&mut self) -> PduResult<Vec<SvcMessage>> {
+        log::info!(
+            "RAIL session {}: channel started, waiting for server handshake",
+            self.session_id
+        );
+        Ok(Vec::new())
+    }
+
+    fn process(&mut self, payload: &[u8]) -> PduResult<Vec<SvcMessage>> {
+        let pdus = self.process_rail_payload(payload);
+        Ok(self.make_messages(pdus))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::orders::test_support::*;
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use sorng_core::events::AppEventEmitter;
+
+    use crate::rdp::frame_channel::FrameChannel;
+
+    #[derive(Default)]
+    struct RecordingEmitter {
+        events: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl AppEventEmitter for RecordingEmitter {
+        fn emit_event(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+            self.events
+                .lock()
+                .expect("recording emitter lock poisoned")
+                .push((event.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    impl RecordingEmitter {
+        fn take(&self, name: &str) -> Vec<serde_json::Value> {
+            let mut events = self.events.lock().unwrap();
+            let (matching, rest) = events.drain(..).partition(|(n, _)| n == name);
+            *events = rest;
+            matching.into_iter().map(|(_, v)| v).collect()
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        frames: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl FrameChannel for RecordingChannel {
+        fn send_raw(&self, data: Vec<u8>) -> Result<(), String> {
+            self.frames.lock().unwrap().push(data);
+            Ok(())
+        }
+    }
+
+    fn client() -> (RailClient, Arc<RecordingEmitter>) {
+        let emitter = Arc::new(RecordingEmitter::default());
+        let client = RailClient::new(
+            "s1".to_string(),
+            emitter.clone(),
+            RemoteAppConfig {
+                program: "||notepad".to_string(),
+                arguments: String::new(),
+                working_dir: String::new(),
+                expand_arguments: false,
+            },
+            64,
+            48,
+        );
+        (client, emitter)
+    }
+
+    fn server_handshake() -> Vec<u8> {
+        let mut pdu = vec![0x05, 0x00, 0x08, 0x00];
+        pdu.extend_from_slice(&9600u32.to_le_bytes());
+        pdu
+    }
+
+    fn order_type(pdu: &[u8]) -> u16 {
+        u16::from_le_bytes([pdu[0], pdu[1]])
+    }
+
+    #[test]
+    fn handshake_sends_startup_sequence_and_queued_exec() {
+        let (mut rail, emitter) = client();
+        // Queued before the channel is up, sent after the configured program.
+        let queued = RailCommand::Exec(Box::new(ExecRequest {
+            program: "||calc".to_string(),
+            ..Default::default()
+        }));
+        assert!(rail.command_pdus(queued).unwrap().is_empty());
+        assert!(rail
+            .command_pdus(RailCommand::LanguageBar { status: 1 })
+            .is_err());
+
+        let out = rail.process_rail_payload(&server_handshake());
+        let types: Vec<u16> = out.iter().map(|p| order_type(p)).collect();
+        assert_eq!(
+            types[..2],
+            [TS_RAIL_ORDER_HANDSHAKE, TS_RAIL_ORDER_CLIENTSTATUS]
+        );
+        assert!(types[2..types.len() - 2]
+            .iter()
+            .all(|&t| t == TS_RAIL_ORDER_SYSPARAM));
+        assert_eq!(types[types.len() - 2..], [TS_RAIL_ORDER_EXEC; 2]);
+        assert!(rail.is_ready());
+        assert_eq!(rail.channel_summary().ready_count, 1);
+
+        let ready = emitter.take("rdp://rail-ready");
+        assert_eq!(ready[0]["serverBuild"], 9600);
+        assert_eq!(ready[0]["sessionId"], "s1");
+    }
+
+    #[test]
+    fn exec_result_is_reported() {
+        let (mut rail, emitter) = client();
+        let exe: Vec<u8> = "calc".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut body = vec![0, 0, 3, 0, 0, 0, 0, 0, 0, 0];
+        body.extend_from_slice(&(exe.len() as u16).to_le_bytes());
+        body.extend_from_slice(&exe);
+        let mut pdu = TS_RAIL_ORDER_EXEC_RESULT.to_le_bytes().to_vec();
+        pdu.extend_from_slice(&((body.len() + 4) as u16).to_le_bytes());
+        pdu.extend_from_slice(&body);
+
+        assert!(rail.process_rail_payload(&pdu).is_empty());
+        let events = emitter.take("rdp://rail-exec-result");
+        assert_eq!(events[0]["result"], "not_in_allowlist");
+        assert_eq!(events[0]["success"], false);
+        assert_eq!(rail.channel_diagnostics().counters.exec_failures, 1);
+    }
+
+    #[test]
+    fn window_orders_track_state_and_emit_events() {
+        let (mut rail, emitter) = client();
+        let frame = fastpath_orders_frame(&[window_order(7, true, "Doc", (10, 5), (20, 10))]);
+        assert!(rail.ingest_fastpath(&frame).is_empty());
+        let window = rail.window(7).expect("window tracked");
+        assert_eq!(
+            (window.x, window.y, window.width, window.height),
+            (10, 5, 20, 10)
+        );
+        let events = emitter.take("rdp://rail-window");
+        assert_eq!(events[0]["action"], "created");
+        assert_eq!(events[0]["window"]["title"], "Doc");
+
+        rail.ingest_fastpath(&fastpath_orders_frame(&[window_deleted_order(7)]));
+        assert!(rail.window(7).is_none());
+        assert_eq!(emitter.take("rdp://rail-window")[0]["action"], "deleted");
+    }
+
+    #[test]
+    fn window_commands_require_a_known_window() {
+        let (mut rail, _) = client();
+        rail.process_rail_payload(&server_handshake());
+        let close = |window_id| RailCommand::Window {
+            window_id,
+            action: RailWindowAction::SysCommand {
+                command: RailSysCommand::Close,
+            },
+        };
+        assert!(rail.command_pdus(close(3)).is_err());
+        rail.ingest_fastpath(&fastpath_orders_frame(&[window_order(
+            3,
+            true,
+            "A",
+            (0, 0),
+            (8, 8),
+        )]));
+        let pdus = rail.command_pdus(close(3)).unwrap();
+        assert_eq!(pdus, [encode_syscommand(3, 0xF060)]);
+
+        let action: RailWindowAction =
+            serde_json::from_value(serde_json::json!({ "action": "sysMenu", "x": 4, "y": -2 }))
+                .unwrap();
+        assert_eq!(action, RailWindowAction::SysMenu { x: 4, y: -2 });
+    }
+
+    #[test]
+    fn dirty_rects_are_cropped_into_window_streams() {
+        let (mut rail, _) = client();
+        // Window partially off the left edge: desktop columns 0..6 visible.
+        rail.ingest_fastpath(&fastpath_orders_frame(&[window_order(
+            1,
+            true,
+            "W",
+            (-2, 4),
+            (8, 4),
+        )]));
+        let channel = Arc::new(RecordingChannel::default());
+        rail.command_pdus(RailCommand::AttachWindow {
+            window_id: 1,
+            channel: channel.clone(),
+        })
+        .unwrap();
+
+        // Desktop image where each pixel's red byte is its x coordinate.
+        let (w, h) = (64u16, 48u16);
+        let mut image = vec![0u8; w as usize * h as usize * 4];
+        for (i, px) in image.chunks_mut(4).enumerate() {
+            px[0] = (i % w as usize) as u8;
+            px[1] = (i / w as usize) as u8;
+        }
+        let accounting = FrameDeliveryAccounting::new();
+        rail.route_dirty_rects(&image, w, &[(3, 5, 10, 1), (40, 40, 2, 2)], &accounting);
+
+        let frames = channel.frames.lock().unwrap();
+        assert_eq!(frames.len(), 1);
+        let frame = &frames[0];
+        // Crop is desktop x 3..6, y 5 => window-relative (5, 1), 3x1.
+        assert_eq!(&frame[..8], &[5, 0, 1, 0, 3, 0, 1, 0]);
+        let reds: Vec<u8> = frame[8..].chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, [3, 4, 5]);
+        drop(frames);
+
+        // GFX-style standalone rect at (0, 4).
+        let rgba = vec![9u8; 2 * 2 * 4];
+        rail.route_rgba_rect(
+            &rgba,
+            Rect {
+                x: 0,
+                y: 4,
+                w: 2,
+                h: 2,
+            },
+            &accounting,
+        );
+        let frames = channel.frames.lock().unwrap();
+        assert_eq!(&frames[1][..8], &[2, 0, 0, 0, 2, 0, 2, 0]);
+        assert_eq!(rail.channel_diagnostics().counters.window_frames, 2);
+    }
+
+    #[test]
+    fn geometry_changes_request_repaint_of_attached_windows() {
+        let (mut rail, _) = client();
+        rail.ingest_fastpath(&fastpath_orders_frame(&[window_order(
+            2,
+            true,
+            "W",
+            (0, 0),
+            (8, 8),
+        )]));
+        // Not attached: nothing to repaint.
+        assert!(rail
+            .ingest_fastpath(&fastpath_orders_frame(&[window_order(
+                2,
+                false,
+                "W",
+                (1, 0),
+                (8, 8)
+            )]))
+            .is_empty());
+        rail.command_pdus(RailCommand::AttachWindow {
+            window_id: 2,
+            channel: Arc::new(RecordingChannel::default()),
+        })
+        .unwrap();
+        assert_eq!(
+            rail.ingest_fastpath(&fastpath_orders_frame(&[window_order(
+                2,
+                false,
+                "W",
+                (4, 4),
+                (8, 8)
+            )])),
+            [2]
+        );
+        // Title-only change keeps the geometry.
+        assert!(rail
+            .ingest_fastpath(&fastpath_orders_frame(&[window_order(
+                2,
+                false,
+                "X",
+                (4, 4),
+                (8, 8)
+            )]))
+            .is_empty());
+        assert_eq!(
+            rail.window_area(2),
+            Some(Rect {
+                x: 4,
+                y: 4,
+                w: 8,
+                h: 8
+            })
+        );
+    }
+}
@@ -0,0 +1,659 @@
+//! Windowing alternate secondary drawing orders (MS-RDPERP 2.2.1.3).
+//!
+//! RAIL window, notification-icon and desktop state travels in fast-path
+//! ORDERS updates rather than on the `rail` channel. ironrdp's fast-path
+//! processor does not decode these, so the session runner hands every
+//! fast-path frame to a [`FastPathOrderSniffer`] before the active stage sees
+//! it. Only windowing orders are understood: the client advertises no other
+//! drawing-order support, so anything else ends the scan of that update.
+
+use serde::Serialize;
+
+use super::pdu::{utf16_string, RailParseError, RailRect};
+
+// Fast-path output (MS-RDPBCGR 2.2.9.1.2).
+const FASTPATH_OUTPUT_ENCRYPTED: u8 = 0x80;
+const FASTPATH_UPDATETYPE_ORDERS: u8 = 0x0;
+const FASTPATH_FRAGMENT_SINGLE: u8 = 0x0;
+const FASTPATH_FRAGMENT_LAST: u8 = 0x1;
+const FASTPATH_FRAGMENT_FIRST: u8 = 0x2;
+const FASTPATH_OUTPUT_COMPRESSION_USED: u8 = 0x2;
+const PACKET_COMPRESSED: u8 = 0x20;
+
+// Alternate secondary order header.
+const TS_SECONDARY: u8 = 0x02;
+const TS_ALTSEC_WINDOW: u8 = 0x0B;
+
+// Order type and state flags in fieldsPresentFlags (2.2.1.3.1.1).
+pub const WINDOW_ORDER_TYPE_WINDOW: u32 = 0x0100_0000;
+pub const WINDOW_ORDER_TYPE_NOTIFY: u32 = 0x0200_0000;
+pub const WINDOW_ORDER_TYPE_DESKTOP: u32 = 0x0400_0000;
+pub const WINDOW_ORDER_STATE_NEW: u32 = 0x1000_0000;
+pub const WINDOW_ORDER_STATE_DELETED: u32 = 0x2000_0000;
+pub const WINDOW_ORDER_ICON: u32 = 0x4000_0000;
+pub const WINDOW_ORDER_CACHEDICON: u32 = 0x8000_0000;
+
+// Window information fields, listed in wire order.
+pub const WINDOW_ORDER_FIELD_OWNER: u32 = 0x0000_0002;
+pub const WINDOW_ORDER_FIELD_STYLE: u32 = 0x0000_0008;
+pub const WINDOW_ORDER_FIELD_SHOW: u32 = 0x0000_0010;
+pub const WINDOW_ORDER_FIELD_TITLE: u32 = 0x0000_0004;
+pub const WINDOW_ORDER_FIELD_CLIENT_AREA_OFFSET: u32 = 0x0000_4000;
+pub const WINDOW_ORDER_FIELD_CLIENT_AREA_SIZE: u32 = 0x0001_0000;
+pub const WINDOW_ORDER_FIELD_RESIZE_MARGIN_X: u32 = 0x0000_0080;
+pub const WINDOW_ORDER_FIELD_RESIZE_MARGIN_Y: u32 = 0x0800_0000;
+pub const WINDOW_ORDER_FIELD_RP_CONTENT: u32 = 0x0002_0000;
+pub const WINDOW_ORDER_FIELD_ROOT_PARENT: u32 = 0x0004_0000;
+pub const WINDOW_ORDER_FIELD_WND_OFFSET: u32 = 0x0000_0800;
+pub const WINDOW_ORDER_FIELD_WND_CLIENT_DELTA: u32 = 0x0000_8000;
+pub const WINDOW_ORDER_FIELD_WND_SIZE: u32 = 0x0000_0400;
+pub const WINDOW_ORDER_FIELD_WND_RECTS: u32 = 0x0000_0100;
+pub const WINDOW_ORDER_FIELD_VIS_OFFSET: u32 = 0x0000_1000;
+pub const WINDOW_ORDER_FIELD_VISIBILITY: u32 = 0x0000_0200;
+
+// Notification icon fields (2.2.1.3.2.2.1), in wire order.
+pub const WINDOW_ORDER_FIELD_NOTIFY_VERSION: u32 = 0x0000_0008;
+pub const WINDOW_ORDER_FIELD_NOTIFY_TIP: u32 = 0x0000_0001;
+pub const WINDOW_ORDER_FIELD_NOTIFY_INFO_TIP: u32 = 0x0000_0002;
+pub const WINDOW_ORDER_FIELD_NOTIFY_STATE: u32 = 0x0000_0004;
+
+// Desktop fields (2.2.1.3.3.2.1).
+pub const WINDOW_ORDER_FIELD_DESKTOP_NONE: u32 = 0x0000_0001;
+pub const WINDOW_ORDER_FIELD_DESKTOP_ZORDER: u32 = 0x0000_0010;
+pub const WINDOW_ORDER_FIELD_DESKTOP_ACTIVEWND: u32 = 0x0000_0020;
+
+/// Fields carried by one Window Information order. `None` means the field
+/// was not present and the previous value stands.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WindowStateUpdate {
+    pub owner_window_id: Option<u32>,
+    pub style: Option<(u32, u32)>,
+    pub show_state: Option<u8>,
+    pub title: Option<String>,
+    pub client_offset: Option<(i32, i32)>,
+    pub client_size: Option<(u32, u32)>,
+    pub root_parent: Option<u32>,
+    pub window_offset: Option<(i32, i32)>,
+    pub window_client_delta: Option<(i32, i32)>,
+    pub window_size: Option<(u32, u32)>,
+    pub window_rects: Option<Vec<RailRect>>,
+    pub visible_offset: Option<(i32, i32)>,
+    pub visibility_rects: Option<Vec<RailRect>>,
+}
+
+/// Balloon tooltip of a notification icon.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotifyInfoTip {
+    pub timeout: u32,
+    pub flags: u32,
+    pub text: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NotifyIconUpdate {
+    pub version: Option<u32>,
+    pub tooltip: Option<String>,
+    pub info_tip: Option<NotifyInfoTip>,
+    pub state: Option<u32>,
+}
+
+/// One decoded windowing order. Window icon orders are consumed but not
+/// reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowOrder {
+    Window {
+        window_id: u32,
+        new: bool,
+        update: WindowStateUpdate,
+    },
+    WindowDeleted {
+        window_id: u32,
+    },
+    NotifyIcon {
+        window_id: u32,
+        notify_icon_id: u32,
+        new: bool,
+        update: NotifyIconUpdate,
+    },
+    NotifyIconDeleted {
+        window_id: u32,
+        notify_icon_id: u32,
+    },
+    Desktop {
+        active_window_id: Option<u32>,
+        z_order: Option<Vec<u32>>,
+    },
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn bytes(&mut self, len: usize, err: &'static str) -> Result<&'a [u8], RailParseError> {
+        if len > self.remaining() {
+            return Err(RailParseError(err));
+        }
+        let out = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(out)
+    }
+
+    fn u8(&mut self, err: &'static str) -> Result<u8, RailParseError> {
+        Ok(self.bytes(1, err)?[0])
+    }
+
+    fn u16(&mut self, err: &'static str) -> Result<u16, RailParseError> {
+        let b = self.bytes(2, err)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self, err: &'static str) -> Result<u32, RailParseError> {
+        let b = self.bytes(4, err)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32_pair(&mut self, err: &'static str) -> Result<(i32, i32), RailParseError> {
+        Ok((self.u32(err)? as i32, self.u32(err)? as i32))
+    }
+
+    fn u32_pair(&mut self, err: &'static str) -> Result<(u32, u32), RailParseError> {
+        Ok((self.u32(err)?, self.u32(err)?))
+    }
+
+    /// TS_UNICODE_STRING: cbString u16 followed by UTF-16LE.
+    fn unicode_string(&mut self, err: &'static str) -> Result<String, RailParseError> {
+        let len = self.u16(err)? as usize;
+        Ok(utf16_string(self.bytes(len, err)?))
+    }
+
+    fn rects(&mut self, err: &'static str) -> Result<Vec<RailRect>, RailParseError> {
+        let count = self.u16(err)? as usize;
+        let data = self.bytes(count * 8, err)?;
+        Ok(data
+            .chunks(8)
+            .map(|r| RailRect {
+                left: i16::from_le_bytes([r[0], r[1]]),
+                top: i16::from_le_bytes([r[2], r[3]]),
+                right: i16::from_le_bytes([r[4], r[5]]),
+                bottom: i16::from_le_bytes([r[6], r[7]]),
+            })
+            .collect())
+    }
+}
+
+/// Pulls windowing orders out of fast-path output frames, reassembling
+/// fragmented ORDERS updates across frames.
+#[derive(Debug, Default)]
+pub struct FastPathOrderSniffer {
+    fragment: Option<Vec<u8>>,
+}
+
+impl FastPathOrderSniffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan one complete fast-path output PDU (header included).
+    pub fn ingest(&mut self, frame: &[u8]) -> Result<Vec<WindowOrder>, RailParseError> {
+        const ERR: &str = "fast-path header truncated";
+        let mut r = Reader::new(frame);
+        let header = r.u8(ERR)?;
+        if header & FASTPATH_OUTPUT_ENCRYPTED != 0 {
+            // Standard RDP security: nothing readable without the session keys.
+            return Ok(Vec::new());
+        }
+        let len1 = r.u8(ERR)?;
+        let length = if len1 & 0x80 != 0 {
+            (((len1 & 0x7F) as usize) << 8) | r.u8(ERR)? as usize
+        } else {
+            len1 as usize
+        };
+        let end = length.min(frame.len());
+
+        let mut orders = Vec::new();
+        while r.pos < end {
+            const UPDATE_ERR: &str = "fast-path update truncated";
+            let update_header = r.u8(UPDATE_ERR)?;
+            let code = update_header & 0x0F;
+            let fragmentation = (update_header >> 4) & 0x03;
+            let compression = (update_header >> 6) & 0x03;
+            let compressed = compression == FASTPATH_OUTPUT_COMPRESSION_USED
+                && r.u8(UPDATE_ERR)? & PACKET_COMPRESSED != 0;
+            let size = r.u16(UPDATE_ERR)? as usize;
+            let data = r.bytes(size, UPDATE_ERR)?;
+            if code != FASTPATH_UPDATETYPE_ORDERS {
+                continue;
+            }
+            if compressed {
+                // Bulk compression is not negotiated; drop anything
+                // half-assembled rather than splice in unreadable bytes.
+                self.fragment = None;
+                continue;
+            }
+            match fragmentation {
+                FASTPATH_FRAGMENT_SINGLE => {
+                    self.fragment = None;
+                    parse_orders_update(data, &mut orders)?;
+                }
+                FASTPATH_FRAGMENT_FIRST => self.fragment = Some(data.to_vec()),
+                FASTPATH_FRAGMENT_LAST => {
+                    if let Some(mut buf) = self.fragment.take() {
+                        buf.extend_from_slice(data);
+                        parse_orders_update(&buf, &mut orders)?;
+                    }
+                }
+                _ => {
+                    if let Some(buf) = self.fragment.as_mut() {
+                        buf.extend_from_slice(data);
+                    }
+                }
+            }
+        }
+        Ok(orders)
+    }
+}
+
+/// TS_FP_UPDATE_ORDERS body: numberOrders u16 then the orders.
+fn parse_orders_update(data: &[u8], out: &mut Vec<WindowOrder>) -> Result<(), RailParseError> {
+    let mut r = Reader::new(data);
+    let count = r.u16("orders update truncated")?;
+    for _ in 0..count {
+        let start = r.pos;
+        let control_flags = r.u8("order truncated")?;
+        if control_flags & 0x03 != TS_SECONDARY || control_flags >> 2 != TS_ALTSEC_WINDOW {
+            break;
+        }
+        let order_size = r.u16("window order truncated")? as usize;
+        if order_size < 7 || start + order_size > data.len() {
+            return Err(RailParseError("window orderSize out of range"));
+        }
+        let mut body = Reader::new(&data[start + 3..start + order_size]);
+        if let Some(order) = parse_window_order(&mut body)? {
+            out.push(order);
+        }
+        r.pos = start + order_size;
+    }
+    Ok(())
+}
+
+fn parse_window_order(r: &mut Reader<'_>) -> Result<Option<WindowOrder>, RailParseError> {
+    const ERR: &str = "window order body truncated";
+    let fields = r.u32(ERR)?;
+    let new = fields & WINDOW_ORDER_STATE_NEW != 0;
+    let deleted = fields & WINDOW_ORDER_STATE_DELETED != 0;
+
+    if fields & WINDOW_ORDER_TYPE_WINDOW != 0 {
+        let window_id = r.u32(ERR)?;
+        if fields & (WINDOW_ORDER_ICON | WINDOW_ORDER_CACHEDICON) != 0 {
+            return Ok(None);
+        }
+        if deleted {
+            return Ok(Some(WindowOrder::WindowDeleted { window_id }));
+        }
+        let update = parse_window_info(r, fields)?;
+        return Ok(Some(WindowOrder::Window {
+            window_id,
+            new,
+            update,
+        }));
+    }
+
+    if fields & WINDOW_ORDER_TYPE_NOTIFY != 0 {
+        let window_id = r.u32(ERR)?;
+        let notify_icon_id = r.u32(ERR)?;
+        if deleted {
+            return Ok(Some(WindowOrder::NotifyIconDeleted {
+                window_id,
+                notify_icon_id,
+            }));
+        }
+        let mut update = NotifyIconUpdate::default();
+        if fields & WINDOW_ORDER_FIELD_NOTIFY_VERSION != 0 {
+            update.version = Some(r.u32(ERR)?);
+        }
+        if fields & WINDOW_ORDER_FIELD_NOTIFY_TIP != 0 {
+            update.tooltip = Some(r.unicode_string(ERR)?);
+        }
+        if fields & WINDOW_ORDER_FIELD_NOTIFY_INFO_TIP != 0 {
+            update.info_tip = Some(NotifyInfoTip {
+                timeout: r.u32(ERR)?,
+                flags: r.u32(ERR)?,
+                text: r.unicode_string(ERR)?,
+                title: r.unicode_string(ERR)?,
+            });
+        }
+        if fields & WINDOW_ORDER_FIELD_NOTIFY_STATE != 0 {
+            update.state = Some(r.u32(ERR)?);
+        }
+        // The icon itself (if present) follows; it is not rendered.
+        return Ok(Some(WindowOrder::NotifyIcon {
+            window_id,
+            notify_icon_id,
+            new,
+            update,
+        }));
+    }
+
+    if fields & WINDOW_ORDER_TYPE_DESKTOP != 0 {
+        if fields & WINDOW_ORDER_FIELD_DESKTOP_NONE != 0 {
+            return Ok(Some(WindowOrder::Desktop {
+                active_window_id: None,
+                z_order: None,
+            }));
+        }
+        let active_window_id = if fields & WINDOW_ORDER_FIELD_DESKTOP_ACTIVEWND != 0 {
+            Some(r.u32(ERR)?)
+        } else {
+            None
+        };
+        let z_order = if fields & WINDOW_ORDER_FIELD_DESKTOP_ZORDER != 0 {
+            let count = r.u8(ERR)?;
+            Some((0..count).map(|_| r.u32(ERR)).collect::<Result<_, _>>()?)
+        } else {
+            None
+        };
+        return Ok(Some(WindowOrder::Desktop {
+            active_window_id,
+            z_order,
+        }));
+    }
+
+    Ok(None)
+}
+
+fn parse_window_info(r: &mut Reader<'_>, fields: u32) -> Result<WindowStateUpdate, RailParseError> {
+    const ERR: &str = "window information truncated";
+    let has = |flag: u32| fields & flag != 0;
+    let mut update = WindowStateUpdate::default();
+
+    if has(WINDOW_ORDER_FIELD_OWNER) {
+        update.owner_window_id = Some(r.u32(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_STYLE) {
+        update.style = Some(r.u32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_SHOW) {
+        update.show_state = Some(r.u8(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_TITLE) {
+        update.title = Some(r.unicode_string(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_CLIENT_AREA_OFFSET) {
+        update.client_offset = Some(r.i32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_CLIENT_AREA_SIZE) {
+        update.client_size = Some(r.u32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_RESIZE_MARGIN_X) {
+        r.u32_pair(ERR)?;
+    }
+    if has(WINDOW_ORDER_FIELD_RESIZE_MARGIN_Y) {
+        r.u32_pair(ERR)?;
+    }
+    if has(WINDOW_ORDER_FIELD_RP_CONTENT) {
+        r.u8(ERR)?;
+    }
+    if has(WINDOW_ORDER_FIELD_ROOT_PARENT) {
+        update.root_parent = Some(r.u32(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_WND_OFFSET) {
+        update.window_offset = Some(r.i32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_WND_CLIENT_DELTA) {
+        update.window_client_delta = Some(r.i32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_WND_SIZE) {
+        update.window_size = Some(r.u32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_WND_RECTS) {
+        update.window_rects = Some(r.rects(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_VIS_OFFSET) {
+        update.visible_offset = Some(r.i32_pair(ERR)?);
+    }
+    if has(WINDOW_ORDER_FIELD_VISIBILITY) {
+        update.visibility_rects = Some(r.rects(ERR)?);
+    }
+    // Overlay, taskbar-button and app-bar fields trail the order and are
+    // skipped via orderSize.
+    Ok(update)
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    /// Wrap window order bodies (fieldsPresentFlags onwards) into a single,
+    /// unfragmented fast-path ORDERS frame.
+    pub fn fastpath_orders_frame(orders: &[Vec<u8>]) -> Vec<u8> {
+        let mut update = (orders.len() as u16).to_le_bytes().to_vec();
+        for body in orders {
+            update.push(TS_SECONDARY | (TS_ALTSEC_WINDOW << 2));
+            update.extend_from_slice(&((body.len() + 3) as u16).to_le_bytes());
+            update.extend_from_slice(body);
+        }
+        let mut payload = vec![FASTPATH_UPDATETYPE_ORDERS];
+        payload.extend_from_slice(&(update.len() as u16).to_le_bytes());
+        payload.extend_from_slice(&update);
+        let total = payload.len() + 3;
+        let mut frame = vec![0x00, 0x80 | (total >> 8) as u8, total as u8];
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    /// A new/updated window order carrying show state (SW_SHOWNORMAL),
+    /// title, offset and size.
+    pub fn window_order(
+        window_id: u32,
+        new: bool,
+        title: &str,
+        offset: (i32, i32),
+        size: (u32, u32),
+    ) -> Vec<u8> {
+        let mut fields = WINDOW_ORDER_TYPE_WINDOW
+            | WINDOW_ORDER_FIELD_SHOW
+            | WINDOW_ORDER_FIELD_TITLE
+            | WINDOW_ORDER_FIELD_WND_OFFSET
+            | WINDOW_ORDER_FIELD_WND_SIZE;
+        if new {
+            fields |= WINDOW_ORDER_STATE_NEW;
+        }
+        let mut body = fields.to_le_bytes().to_vec();
+        body.extend_from_slice(&window_id.to_le_bytes());
+        body.push(5);
+        let title: Vec<u8> = title.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        body.extend_from_slice(&(title.len() as u16).to_le_bytes());
+        body.extend_from_slice(&title);
+        body.extend_from_slice(&offset.0.to_le_bytes());
+        body.extend_from_slice(&offset.1.to_le_bytes());
+        body.extend_from_slice(&size.0.to_le_bytes());
+        body.extend_from_slice(&size.1.to_le_bytes());
+        body
+    }
+
+    pub fn window_deleted_order(window_id: u32) -> Vec<u8> {
+        let mut body = (WINDOW_ORDER_TYPE_WINDOW | WINDOW_ORDER_STATE_DELETED)
+            .to_le_bytes()
+            .to_vec();
+        body.extend_from_slice(&window_id.to_le_bytes());
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+
+    #[test]
+    fn window_orders_decode_in_field_order() {
+        let frame =
+            fastpath_orders_frame(&[window_order(0x10, true, "Notepad", (-8, 40), (640, 480))]);
+        let orders = FastPathOrderSniffer::new().ingest(&frame).unwrap();
+        assert_eq!(
+            orders,
+            [WindowOrder::Window {
+                window_id: 0x10,
+                new: true,
+                update: WindowStateUpdate {
+                    show_state: Some(5),
+                    title: Some("Notepad".to_string()),
+                    window_offset: Some((-8, 40)),
+                    window_size: Some((640, 480)),
+                    ..Default::default()
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn rects_style_and_skipped_fields_are_walked() {
+        let fields = WINDOW_ORDER_TYPE_WINDOW
+            | WINDOW_ORDER_FIELD_STYLE
+            | WINDOW_ORDER_FIELD_SHOW
+            | WINDOW_ORDER_FIELD_RESIZE_MARGIN_X
+            | WINDOW_ORDER_FIELD_RP_CONTENT
+            | WINDOW_ORDER_FIELD_VISIBILITY;
+        let mut body = fields.to_le_bytes().to_vec();
+        body.extend_from_slice(&7u32.to_le_bytes());
+        body.extend_from_slice(&0x14CF_0000u32.to_le_bytes());
+        body.extend_from_slice(&0x0000_0100u32.to_le_bytes());
+        body.push(5);
+        body.extend_from_slice(&[0; 8]);
+        body.push(1);
+        body.extend_from_slice(&1u16.to_le_bytes());
+        for v in [0i16, 0, 100, 50] {
+            body.extend_from_slice(&v.to_le_bytes());
+        }
+        // Trailing, unparsed field bytes are skipped via orderSize.
+        body.extend_from_slice(&[0xAA, 0xBB]);
+
+        let frame = fastpath_orders_frame(&[body, window_deleted_order(9)]);
+        let orders = FastPathOrderSniffer::new().ingest(&frame).unwrap();
+        assert_eq!(orders.len(), 2);
+        match &orders[0] {
+            WindowOrder::Window { update, .. } => {
+                assert_eq!(update.style, Some((0x14CF_0000, 0x100)));
+                assert_eq!(update.show_state, Some(5));
+                assert_eq!(
+                    update.visibility_rects.as_deref(),
+                    Some(
+                        &[RailRect {
+                            left: 0,
+                            top: 0,
+                            right: 100,
+                            bottom: 50
+                        }][..]
+                    )
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(orders[1], WindowOrder::WindowDeleted { window_id: 9 });
+    }
+
+    #[test]
+    fn notify_icon_and_desktop_orders_decode() {
+        let fields = WINDOW_ORDER_TYPE_NOTIFY
+            | WINDOW_ORDER_STATE_NEW
+            | WINDOW_ORDER_FIELD_NOTIFY_VERSION
+            | WINDOW_ORDER_FIELD_NOTIFY_TIP
+            | WINDOW_ORDER_FIELD_NOTIFY_STATE;
+        let mut notify = fields.to_le_bytes().to_vec();
+        notify.extend_from_slice(&3u32.to_le_bytes());
+        notify.extend_from_slice(&1u32.to_le_bytes());
+        notify.extend_from_slice(&4u32.to_le_bytes());
+        notify.extend_from_slice(&4u16.to_le_bytes());
+        notify.extend_from_slice(&[b'h', 0, b'i', 0]);
+        notify.extend_from_slice(&2u32.to_le_bytes());
+
+        let fields = WINDOW_ORDER_TYPE_DESKTOP
+            | WINDOW_ORDER_FIELD_DESKTOP_ACTIVEWND
+            | WINDOW_ORDER_FIELD_DESKTOP_ZORDER;
+        let mut desktop = fields.to_le_bytes().to_vec();
+        desktop.extend_from_slice(&3u32.to_le_bytes());
+        desktop.push(2);
+        desktop.extend_from_slice(&3u32.to_le_bytes());
+        desktop.extend_from_slice(&8u32.to_le_bytes());
+
+        let orders = FastPathOrderSniffer::new()
+            .ingest(&fastpath_orders_frame(&[notify, desktop]))
+            .unwrap();
+        assert_eq!(
+            orders,
+            [
+                WindowOrder::NotifyIcon {
+                    window_id: 3,
+                    notify_icon_id: 1,
+                    new: true,
+                    update: NotifyIconUpdate {
+                        version: Some(4),
+                        tooltip: Some("hi".to_string()),
+                        info_tip: None,
+                        state: Some(2),
+                    },
+                },
+                WindowOrder::Desktop {
+                    active_window_id: Some(3),
+                    z_order: Some(vec![3, 8]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fragmented_orders_are_reassembled() {
+        let frame = fastpath_orders_frame(&[window_order(1, true, "A", (0, 0), (10, 10))]);
+        // Re-split the single update into FIRST + LAST fragments.
+        let update = &frame[6..];
+        let (a, b) = update.split_at(update.len() / 2);
+        let fragment = |fragmentation: u8, data: &[u8]| {
+            let mut payload = vec![fragmentation << 4];
+            payload.extend_from_slice(&(data.len() as u16).to_le_bytes());
+            payload.extend_from_slice(data);
+            let mut out = vec![0x00, (payload.len() + 2) as u8];
+            out.extend_from_slice(&payload);
+            out
+        };
+        let mut sniffer = FastPathOrderSniffer::new();
+        assert!(sniffer
+            .ingest(&fragment(FASTPATH_FRAGMENT_FIRST, a))
+            .unwrap()
+            .is_empty());
+        let orders = sniffer
+            .ingest(&fragment(FASTPATH_FRAGMENT_LAST, b))
+            .unwrap();
+        assert!(matches!(
+            orders[..],
+            [WindowOrder::Window { window_id: 1, .. }]
+        ));
+    }
+
+    #[test]
+    fn other_updates_and_bad_sizes_are_handled() {
+        // A bitmap update (code 1) is skipped entirely.
+        let frame = [0x00, 0x07, 0x01, 0x02, 0x00, 0xAA, 0xBB];
+        assert!(FastPathOrderSniffer::new()
+            .ingest(&frame)
+            .unwrap()
+            .is_empty());
+
+        // A window order whose orderSize runs past the update is malformed.
+        let mut frame = fastpath_orders_frame(&[window_deleted_order(1)]);
+        let size_at = 3 + 3 + 2 + 1;
+        frame[size_at] = 0x40;
+        assert!(FastPathOrderSniffer::new().ingest(&frame).is_err());
+    }
+}
@@ -0,0 +1,594 @@
+//! RAIL virtual channel PDUs (MS-RDPERP 2.2.2).
+//!
+//! Every PDU starts with a TS_RAIL_PDU_HEADER (orderType u16, orderLength
+//! u16 including the header). Client PDUs are built as raw byte vectors and
+//! server PDUs are decoded into [`ServerRailPdu`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+pub const TS_RAIL_ORDER_EXEC: u16 = 0x0001;
+pub const TS_RAIL_ORDER_ACTIVATE: u16 = 0x0002;
+pub const TS_RAIL_ORDER_SYSPARAM: u16 = 0x0003;
+pub const TS_RAIL_ORDER_SYSCOMMAND: u16 = 0x0004;
+pub const TS_RAIL_ORDER_HANDSHAKE: u16 = 0x0005;
+pub const TS_RAIL_ORDER_NOTIFY_EVENT: u16 = 0x0006;
+pub const TS_RAIL_ORDER_WINDOWMOVE: u16 = 0x0008;
+pub const TS_RAIL_ORDER_LOCALMOVESIZE: u16 = 0x0009;
+pub const TS_RAIL_ORDER_MINMAXINFO: u16 = 0x000A;
+pub const TS_RAIL_ORDER_CLIENTSTATUS: u16 = 0x000B;
+pub const TS_RAIL_ORDER_SYSMENU: u16 = 0x000C;
+pub const TS_RAIL_ORDER_LANGBARINFO: u16 = 0x000D;
+pub const TS_RAIL_ORDER_GET_APPID_REQ: u16 = 0x000E;
+pub const TS_RAIL_ORDER_GET_APPID_RESP: u16 = 0x000F;
+pub const TS_RAIL_ORDER_HANDSHAKE_EX: u16 = 0x0013;
+pub const TS_RAIL_ORDER_ZORDER_SYNC: u16 = 0x0014;
+pub const TS_RAIL_ORDER_CLOAK: u16 = 0x0015;
+pub const TS_RAIL_ORDER_EXEC_RESULT: u16 = 0x0080;
+
+pub const RAIL_HEADER_SIZE: usize = 4;
+
+/// Build number the client reports in its Handshake PDU (Windows 7 RTM, as
+/// sent by mstsc and FreeRDP).
+pub const CLIENT_BUILD_NUMBER: u32 = 7600;
+
+// Client Information PDU flags (2.2.2.6.1).
+pub const TS_RAIL_CLIENTSTATUS_ALLOWLOCALMOVESIZE: u32 = 0x0000_0001;
+pub const TS_RAIL_CLIENTSTATUS_AUTORECONNECT: u32 = 0x0000_0002;
+
+// Client Execute PDU flags (2.2.2.3.1).
+pub const TS_RAIL_EXEC_FLAG_EXPAND_WORKINGDIRECTORY: u16 = 0x0001;
+pub const TS_RAIL_EXEC_FLAG_TRANSLATE_FILES: u16 = 0x0002;
+pub const TS_RAIL_EXEC_FLAG_FILE: u16 = 0x0004;
+pub const TS_RAIL_EXEC_FLAG_EXPAND_ARGUMENTS: u16 = 0x0008;
+
+/// Maximum encoded lengths (bytes) for the Execute PDU strings.
+const EXEC_MAX_PATH_BYTES: usize = 520;
+const EXEC_MAX_ARGUMENTS_BYTES: usize = 16_000;
+
+// System parameters (2.2.2.4.1 / 2.2.2.4.2).
+pub const SPI_SETDRAGFULLWINDOWS: u32 = 0x0000_0025;
+pub const SPI_SETKEYBOARDCUES: u32 = 0x0000_100B;
+pub const SPI_SETKEYBOARDPREF: u32 = 0x0000_0045;
+pub const SPI_SETMOUSEBUTTONSWAP: u32 = 0x0000_0021;
+pub const SPI_SETWORKAREA: u32 = 0x0000_002F;
+pub const SPI_SETHIGHCONTRAST: u32 = 0x0000_0043;
+pub const RAIL_SPI_DISPLAYCHANGE: u32 = 0x0000_F001;
+pub const RAIL_SPI_TASKBARPOS: u32 = 0x0000_F000;
+pub const SPI_SETSCREENSAVEACTIVE: u32 = 0x0000_0011;
+pub const SPI_SETSCREENSAVESECURE: u32 = 0x0000_0077;
+
+/// HCF_AVAILABLE | HCF_HOTKEYACTIVE | HCF_CONFIRMHOTKEY | HCF_HOTKEYSOUND |
+/// HCF_INDICATOR | HCF_HOTKEYAVAILABLE, with high contrast itself off.
+const HIGH_CONTRAST_DEFAULT_FLAGS: u32 = 0x0000_007E;
+
+// Server Execute Result codes (2.2.2.3.2).
+pub const RAIL_EXEC_S_OK: u16 = 0x0000;
+pub const RAIL_EXEC_E_HOOK_NOT_LOADED: u16 = 0x0001;
+pub const RAIL_EXEC_E_DECODE_FAILED: u16 = 0x0002;
+pub const RAIL_EXEC_E_NOT_IN_ALLOWLIST: u16 = 0x0003;
+pub const RAIL_EXEC_E_FILE_NOT_FOUND: u16 = 0x0005;
+pub const RAIL_EXEC_E_FAIL: u16 = 0x0006;
+pub const RAIL_EXEC_E_SESSION_LOCKED: u16 = 0x0007;
+
+/// Stable name for an Execute Result code, used in events and diagnostics.
+pub fn exec_result_name(code: u16) -> &'static str {
+    match code {
+        RAIL_EXEC_S_OK => "ok",
+        RAIL_EXEC_E_HOOK_NOT_LOADED => "hook_not_loaded",
+        RAIL_EXEC_E_DECODE_FAILED => "decode_failed",
+        RAIL_EXEC_E_NOT_IN_ALLOWLIST => "not_in_allowlist",
+        RAIL_EXEC_E_FILE_NOT_FOUND => "file_not_found",
+        RAIL_EXEC_E_FAIL => "fail",
+        RAIL_EXEC_E_SESSION_LOCKED => "session_locked",
+        _ => "unknown",
+    }
+}
+
+/// Desktop rectangle with exclusive right/bottom edges (TS_RECTANGLE_16).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RailRect {
+    pub left: i16,
+    pub top: i16,
+    pub right: i16,
+    pub bottom: i16,
+}
+
+/// Client Execute PDU contents.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExecRequest {
+    pub program: String,
+    pub working_dir: String,
+    pub arguments: String,
+    pub flags: u16,
+}
+
+/// Client System Parameters Update PDU payloads sent during RAIL start-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientSysParam {
+    HighContrast,
+    DragFullWindows(bool),
+    KeyboardCues(bool),
+    KeyboardPref(bool),
+    MouseButtonSwap(bool),
+    WorkArea(RailRect),
+    DisplayChange(RailRect),
+    TaskbarPos(RailRect),
+}
+
+/// Server Execute Result PDU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecResult {
+    pub flags: u16,
+    pub exec_result: u16,
+    pub raw_result: u32,
+    pub program: String,
+}
+
+/// Server Move/Size Start and End PDU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalMoveSize {
+    pub window_id: u32,
+    pub is_move_size_start: bool,
+    pub move_size_type: u16,
+    pub pos_x: i16,
+    pub pos_y: i16,
+}
+
+/// Server Min Max Info PDU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinMaxInfo {
+    pub window_id: u32,
+    pub max_width: i16,
+    pub max_height: i16,
+    pub max_pos_x: i16,
+    pub max_pos_y: i16,
+    pub min_track_width: i16,
+    pub min_track_height: i16,
+    pub max_track_width: i16,
+    pub max_track_height: i16,
+}
+
+/// A decoded server → client RAIL PDU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerRailPdu {
+    Handshake {
+        build_number: u32,
+    },
+    HandshakeEx {
+        build_number: u32,
+        flags: u32,
+    },
+    ExecResult(ExecResult),
+    SysParam {
+        param: u32,
+        value: bool,
+    },
+    LocalMoveSize(LocalMoveSize),
+    MinMaxInfo(MinMaxInfo),
+    LanguageBarInfo {
+        status: u32,
+    },
+    GetAppIdResponse {
+        window_id: u32,
+        application_id: String,
+    },
+    ZOrderSync {
+        window_id_marker: u32,
+    },
+    Cloak {
+        window_id: u32,
+        cloaked: bool,
+    },
+    Unknown {
+        order_type: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RailParseError(pub &'static str);
+
+impl fmt::Display for RailParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RAIL parse error: {}", self.0)
+    }
+}
+
+impl std::error::Error for RailParseError {}
+
+// ── Client PDUs ─────────────────────────────────────────────────────
+
+fn rail_pdu(order_type: u16, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(RAIL_HEADER_SIZE + body.len());
+    out.extend_from_slice(&order_type.to_le_bytes());
+    out.extend_from_slice(&((RAIL_HEADER_SIZE + body.len()) as u16).to_le_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+fn utf16_bytes(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn rect_bytes(rect: RailRect) -> Vec<u8> {
+    [rect.left, rect.top, rect.right, rect.bottom]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+pub fn encode_handshake(build_number: u32) -> Vec<u8> {
+    rail_pdu(TS_RAIL_ORDER_HANDSHAKE, &build_number.to_le_bytes())
+}
+
+pub fn encode_client_status(flags: u32) -> Vec<u8> {
+    rail_pdu(TS_RAIL_ORDER_CLIENTSTATUS, &flags.to_le_bytes())
+}
+
+/// Client Execute PDU. Strings are sent as UTF-16LE without terminators.
+pub fn encode_exec(request: &ExecRequest) -> Result<Vec<u8>, String> {
+    let program = utf16_bytes(&request.program);
+    let working_dir = utf16_bytes(&request.working_dir);
+    let arguments = utf16_bytes(&request.arguments);
+    if program.is_empty() {
+        return Err("RemoteApp program must not be empty".to_string());
+    }
+    if program.len() > EXEC_MAX_PATH_BYTES {
+        return Err("RemoteApp program path is too long".to_string());
+    }
+    if working_dir.len() > EXEC_MAX_PATH_BYTES {
+        return Err("RemoteApp working directory is too long".to_string());
+    }
+    if arguments.len() > EXEC_MAX_ARGUMENTS_BYTES {
+        return Err("RemoteApp arguments are too long".to_string());
+    }
+
+    let mut body = Vec::with_capacity(8 + program.len() + working_dir.len() + arguments.len());
+    body.extend_from_slice(&request.flags.to_le_bytes());
+    body.extend_from_slice(&(program.len() as u16).to_le_bytes());
+    body.extend_from_slice(&(working_dir.len() as u16).to_le_bytes());
+    body.extend_from_slice(&(arguments.len() as u16).to_le_bytes());
+    body.extend_from_slice(&program);
+    body.extend_from_slice(&working_dir);
+    body.extend_from_slice(&arguments);
+    Ok(rail_pdu(TS_RAIL_ORDER_EXEC, &body))
+}
+
+pub fn encode_sysparam(param: ClientSysParam) -> Vec<u8> {
+    let (id, value) = match param {
+        ClientSysParam::HighContrast => {
+            // TS_HIGHCONTRAST with an empty colour scheme: the scheme length
+            // counts the terminating NUL of the (empty) unicode string.
+            let mut value = HIGH_CONTRAST_DEFAULT_FLAGS.to_le_bytes().to_vec();
+            value.extend_from_slice(&2u32.to_le_bytes());
+            value.extend_from_slice(&0u16.to_le_bytes());
+            (SPI_SETHIGHCONTRAST, value)
+        }
+        ClientSysParam::DragFullWindows(v) => (SPI_SETDRAGFULLWINDOWS, vec![v as u8]),
+        ClientSysParam::KeyboardCues(v) => (SPI_SETKEYBOARDCUES, vec![v as u8]),
+        ClientSysParam::KeyboardPref(v) => (SPI_SETKEYBOARDPREF, vec![v as u8]),
+        ClientSysParam::MouseButtonSwap(v) => (SPI_SETMOUSEBUTTONSWAP, vec![v as u8]),
+        ClientSysParam::WorkArea(rect) => (SPI_SETWORKAREA, rect_bytes(rect)),
+        ClientSysParam::DisplayChange(rect) => (RAIL_SPI_DISPLAYCHANGE, rect_bytes(rect)),
+        ClientSysParam::TaskbarPos(rect) => (RAIL_SPI_TASKBARPOS, rect_bytes(rect)),
+    };
+    let mut body = id.to_le_bytes().to_vec();
+    body.extend_from_slice(&value);
+    rail_pdu(TS_RAIL_ORDER_SYSPARAM, &body)
+}
+
+pub fn encode_activate(window_id: u32, enabled: bool) -> Vec<u8> {
+    let mut body = window_id.to_le_bytes().to_vec();
+    body.push(enabled as u8);
+    rail_pdu(TS_RAIL_ORDER_ACTIVATE, &body)
+}
+
+pub fn encode_syscommand(window_id: u32, command: u16) -> Vec<u8> {
+    let mut body = window_id.to_le_bytes().to_vec();
+    body.extend_from_slice(&command.to_le_bytes());
+    rail_pdu(TS_RAIL_ORDER_SYSCOMMAND, &body)
+}
+
+pub fn encode_sysmenu(window_id: u32, left: i16, top: i16) -> Vec<u8> {
+    let mut body = window_id.to_le_bytes().to_vec();
+    body.extend_from_slice(&left.to_le_bytes());
+    body.extend_from_slice(&top.to_le_bytes());
+    rail_pdu(TS_RAIL_ORDER_SYSMENU, &body)
+}
+
+pub fn encode_notify_event(window_id: u32, notify_icon_id: u32, message: u32) -> Vec<u8> {
+    let mut body = window_id.to_le_bytes().to_vec();
+    body.extend_from_slice(&notify_icon_id.to_le_bytes());
+    body.extend_from_slice(&message.to_le_bytes());
+    rail_pdu(TS_RAIL_ORDER_NOTIFY_EVENT, &body)
+}
+
+pub fn encode_window_move(window_id: u32, rect: RailRect) -> Vec<u8> {
+    let mut body = window_id.to_le_bytes().to_vec();
+    body.extend_from_slice(&rect_bytes(rect));
+    rail_pdu(TS_RAIL_ORDER_WINDOWMOVE, &body)
+}
+
+pub fn encode_langbar_info(status: u32) -> Vec<u8> {
+    rail_pdu(TS_RAIL_ORDER_LANGBARINFO, &status.to_le_bytes())
+}
+
+pub fn encode_get_appid_request(window_id: u32) -> Vec<u8> {
+    rail_pdu(TS_RAIL_ORDER_GET_APPID_REQ, &window_id.to_le_bytes())
+}
+
+// ── Server PDUs ─────────────────────────────────────────────────────
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], RailParseError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(RailParseError("PDU body truncated"))?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, RailParseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, RailParseError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, RailParseError> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, RailParseError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// Decode UTF-16LE, stopping at the first NUL.
+pub(crate) fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .as_chunks::<2>()
+        .0
+        .iter()
+        .map(|c| u16::from_le_bytes(*c))
+        .take_while(|&u| u != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode one server RAIL PDU.
+pub fn parse_server_pdu(payload: &[u8]) -> Result<ServerRailPdu, RailParseError> {
+    if payload.len() < RAIL_HEADER_SIZE {
+        return Err(RailParseError("header truncated"));
+    }
+    let order_type = u16::from_le_bytes([payload[0], payload[1]]);
+    let order_length = u16::from_le_bytes([payload[2], payload[3]]) as usize;
+    if order_length < RAIL_HEADER_SIZE || order_length > payload.len() {
+        return Err(RailParseError("orderLength out of range"));
+    }
+    let mut r = Reader {
+        data: &payload[RAIL_HEADER_SIZE..order_length],
+        pos: 0,
+    };
+
+    Ok(match order_type {
+        TS_RAIL_ORDER_HANDSHAKE => ServerRailPdu::Handshake {
+            build_number: r.u32()?,
+        },
+        TS_RAIL_ORDER_HANDSHAKE_EX => ServerRailPdu::HandshakeEx {
+            build_number: r.u32()?,
+            flags: r.u32()?,
+        },
+        TS_RAIL_ORDER_EXEC_RESULT => {
+            let flags = r.u16()?;
+            let exec_result = r.u16()?;
+            let raw_result = r.u32()?;
+            r.u16()?; // padding
+            let exe_len = r.u16()? as usize;
+            let program = utf16_string(r.bytes(exe_len)?);
+            ServerRailPdu::ExecResult(ExecResult {
+                flags,
+                exec_result,
+                raw_result,
+                program,
+            })
+        }
+        TS_RAIL_ORDER_SYSPARAM => ServerRailPdu::SysParam {
+            param: r.u32()?,
+            value: r.u8()? != 0,
+        },
+        TS_RAIL_ORDER_LOCALMOVESIZE => ServerRailPdu::LocalMoveSize(LocalMoveSize {
+            window_id: r.u32()?,
+            is_move_size_start: r.u16()? != 0,
+            move_size_type: r.u16()?,
+            pos_x: r.i16()?,
+            pos_y: r.i16()?,
+        }),
+        TS_RAIL_ORDER_MINMAXINFO => ServerRailPdu::MinMaxInfo(MinMaxInfo {
+            window_id: r.u32()?,
+            max_width: r.i16()?,
+            max_height: r.i16()?,
+            max_pos_x: r.i16()?,
+            max_pos_y: r.i16()?,
+            min_track_width: r.i16()?,
+            min_track_height: r.i16()?,
+            max_track_width: r.i16()?,
+            max_track_height: r.i16()?,
+        }),
+        TS_RAIL_ORDER_LANGBARINFO => ServerRailPdu::LanguageBarInfo { status: r.u32()? },
+        TS_RAIL_ORDER_GET_APPID_RESP => {
+            let window_id = r.u32()?;
+            // applicationId is a fixed 520-byte NUL-padded buffer.
+            let rest = r.data.len() - r.pos;
+            let application_id = utf16_string(r.bytes(rest.min(EXEC_MAX_PATH_BYTES))?);
+            ServerRailPdu::GetAppIdResponse {
+                window_id,
+                application_id,
+            }
+        }
+        TS_RAIL_ORDER_ZORDER_SYNC => ServerRailPdu::ZOrderSync {
+            window_id_marker: r.u32()?,
+        },
+        TS_RAIL_ORDER_CLOAK => ServerRailPdu::Cloak {
+            window_id: r.u32()?,
+            cloaked: r.u8()? != 0,
+        },
+        other => ServerRailPdu::Unknown { order_type: other },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_pdu(order_type: u16, body: &[u8]) -> Vec<u8> {
+        rail_pdu(order_type, body)
+    }
+
+    #[test]
+    fn exec_encodes_utf16_strings_with_lengths() {
+        let pdu = encode_exec(&ExecRequest {
+            program: "||calc".to_string(),
+            working_dir: String::new(),
+            arguments: "-x".to_string(),
+            flags: TS_RAIL_EXEC_FLAG_EXPAND_ARGUMENTS,
+        })
+        .unwrap();
+        assert_eq!(&pdu[..2], &TS_RAIL_ORDER_EXEC.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([pdu[2], pdu[3]]) as usize, pdu.len());
+        assert_eq!(&pdu[4..12], &[0x08, 0, 12, 0, 0, 0, 4, 0]);
+        assert_eq!(&pdu[12..16], &[b'|', 0, b'|', 0]);
+        assert_eq!(&pdu[24..], &[b'-', 0, b'x', 0]);
+
+        assert!(encode_exec(&ExecRequest::default()).is_err());
+        let long = ExecRequest {
+            program: "a".repeat(261),
+            ..Default::default()
+        };
+        assert!(encode_exec(&long).is_err());
+    }
+
+    #[test]
+    fn sysparams_encode_bool_rect_and_high_contrast_bodies() {
+        assert_eq!(
+            encode_sysparam(ClientSysParam::DragFullWindows(true)),
+            [0x03, 0, 9, 0, 0x25, 0, 0, 0, 1]
+        );
+        let rect = RailRect {
+            left: 0,
+            top: 0,
+            right: 1024,
+            bottom: 768,
+        };
+        let work_area = encode_sysparam(ClientSysParam::WorkArea(rect));
+        assert_eq!(work_area.len(), 16);
+        assert_eq!(&work_area[12..], &[0, 4, 0, 3]);
+        let high_contrast = encode_sysparam(ClientSysParam::HighContrast);
+        assert_eq!(high_contrast.len(), 18);
+        assert_eq!(&high_contrast[4..8], &SPI_SETHIGHCONTRAST.to_le_bytes());
+    }
+
+    #[test]
+    fn window_commands_have_fixed_layouts() {
+        assert_eq!(encode_activate(7, true), [2, 0, 9, 0, 7, 0, 0, 0, 1]);
+        assert_eq!(
+            encode_syscommand(7, 0xF020),
+            [4, 0, 10, 0, 7, 0, 0, 0, 0x20, 0xF0]
+        );
+        assert_eq!(encode_sysmenu(7, -1, 2).len(), 12);
+        assert_eq!(encode_notify_event(1, 2, 0x0201).len(), 16);
+        assert_eq!(encode_window_move(1, RailRect::default()).len(), 16);
+        assert_eq!(encode_langbar_info(0x8), [0x0D, 0, 8, 0, 8, 0, 0, 0]);
+    }
+
+    #[test]
+    fn server_handshakes_and_exec_result_decode() {
+        assert_eq!(
+            parse_server_pdu(&server_pdu(TS_RAIL_ORDER_HANDSHAKE, &7601u32.to_le_bytes())),
+            Ok(ServerRailPdu::Handshake { build_number: 7601 })
+        );
+        let mut ex = 9600u32.to_le_bytes().to_vec();
+        ex.extend_from_slice(&1u32.to_le_bytes());
+        assert_eq!(
+            parse_server_pdu(&server_pdu(TS_RAIL_ORDER_HANDSHAKE_EX, &ex)),
+            Ok(ServerRailPdu::HandshakeEx {
+                build_number: 9600,
+                flags: 1
+            })
+        );
+
+        let exe = utf16_bytes("notepad");
+        let mut body = vec![0, 0, 5, 0, 2, 0, 0, 0x80, 0, 0];
+        body.extend_from_slice(&(exe.len() as u16).to_le_bytes());
+        body.extend_from_slice(&exe);
+        let parsed = parse_server_pdu(&server_pdu(TS_RAIL_ORDER_EXEC_RESULT, &body)).unwrap();
+        assert_eq!(
+            parsed,
+            ServerRailPdu::ExecResult(ExecResult {
+                flags: 0,
+                exec_result: RAIL_EXEC_E_FILE_NOT_FOUND,
+                raw_result: 0x8000_0002,
+                program: "notepad".to_string(),
+            })
+        );
+        assert_eq!(
+            exec_result_name(RAIL_EXEC_E_FILE_NOT_FOUND),
+            "file_not_found"
+        );
+    }
+
+    #[test]
+    fn move_size_and_min_max_decode_signed_fields() {
+        let mut body = 3u32.to_le_bytes().to_vec();
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&9u16.to_le_bytes());
+        body.extend_from_slice(&(-5i16).to_le_bytes());
+        body.extend_from_slice(&20i16.to_le_bytes());
+        assert_eq!(
+            parse_server_pdu(&server_pdu(TS_RAIL_ORDER_LOCALMOVESIZE, &body)),
+            Ok(ServerRailPdu::LocalMoveSize(LocalMoveSize {
+                window_id: 3,
+                is_move_size_start: true,
+                move_size_type: 9,
+                pos_x: -5,
+                pos_y: 20,
+            }))
+        );
+
+        let mut body = 3u32.to_le_bytes().to_vec();
+        for v in [800i16, 600, -4, -4, 100, 50, 1600, 1200] {
+            body.extend_from_slice(&v.to_le_bytes());
+        }
+        match parse_server_pdu(&server_pdu(TS_RAIL_ORDER_MINMAXINFO, &body)).unwrap() {
+            ServerRailPdu::MinMaxInfo(info) => {
+                assert_eq!((info.max_width, info.max_pos_x), (800, -4));
+                assert_eq!(info.max_track_height, 1200);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn malformed_server_pdus_are_rejected() {
+        assert!(parse_server_pdu(&[5, 0]).is_err());
+        assert!(parse_server_pdu(&[5, 0, 40, 0, 0, 0, 0, 0]).is_err());
+        assert!(parse_server_pdu(&server_pdu(TS_RAIL_ORDER_HANDSHAKE, &[1, 2])).is_err());
+        assert_eq!(
+            parse_server_pdu(&server_pdu(0x0042, &[])),
+            Ok(ServerRailPdu::Unknown { order_type: 0x42 })
+        );
+    }
+}
@@ -1406,6 +1406,7 @@ fn establish_rdp_connection(
                 Some(explicit.clone())
             }
         },
+        remote_app_program: settings.remote_app.as_ref().map(|app| app.program.clone()),
     };
 
     let server_socket_addr = std::net::SocketAddr::new(socket_addr.ip(), port);
@@ -1533,6 +1534,21 @@ fn establish_rdp_connection(
         }
     }
 
+    // -- Register RAIL SVC (RemoteApp) --
+    if let Some(ref remote_app) = settings.remote_app {
+        connector.attach_static_channel(super::rail::RailClient::new(
+            session_id.to_string(),
+            event_emitter.clone(),
+            remote_app.clone(),
+            settings.width,
+            settings.height,
+        ));
+        log::info!(
+            "RDP session {session_id}: RAIL SVC registered (program='{}')",
+            remote_app.program
+        );
+    }
+
     // Log gateway / Hyper-V / negotiation settings
     if settings.gateway_enabled {
         log::info!(
@@ -1796,6 +1812,12 @@ fn establish_rdp_connection(
 
     let mut active_stage = ActiveStage::new(connection_result);
 
+    // The server may grant a different desktop than requested; RAIL crops
+    // window streams and reports the work area against the granted size.
+    if let Some(rail) = active_stage.get_svc_processor_mut::<super::rail::RailClient>() {
+        let _ = rail.set_desktop_size(desktop_width, desktop_height);
+    }
+
     // Override pointer settings if the server negotiated different values
     // than what we requested.  This is critical for local cursor mode
     // where we need PointerBitmap events (requires software_rendering=false).
@@ -1909,18 +1931,27 @@ fn send_activity_protocol_step(
         }
     };
 
+    write_share_data_pdu(est, stats, pdu, &step)
+}
+
+fn write_share_data_pdu(
+    est: &mut EstablishedSession,
+    stats: &RdpSessionStats,
+    pdu: ShareDataPdu,
+    label: &dyn std::fmt::Debug,
+) -> Result<(), String> {
     let mut output = WriteBuf::new();
     let written = est
         .active_stage
         .encode_static(&mut output, pdu)
-        .map_err(|error| format!("Failed to encode {step:?}: {error}"))?;
+        .map_err(|error| format!("Failed to encode {label:?}: {error}"))?;
     let frame = output.filled();
     let data = frame
         .get(..written)
-        .ok_or_else(|| format!("Encoded {step:?} length exceeded output buffer"))?;
+        .ok_or_else(|| format!("Encoded {label:?} length exceeded output buffer"))?;
     est.tls_framed
         .write_all(data)
-        .map_err(|error| format!("Failed to write {step:?}: {error}"))?;
+        .map_err(|error| format!("Failed to write {label:?}: {error}"))?;
     stats
         .bytes_sent
         .fetch_add(data.len() as u64, Ordering::Relaxed);
@@ -1928,6 +1959,77 @@ fn send_activity_protocol_step(
     Ok(())
 }
 
+// ── RemoteApp (RAIL) output ─────────────────────────────────────────
+
+fn send_rail_messages(
+    est: &mut EstablishedSession,
+    stats: &RdpSessionStats,
+    messages: Vec<crate::ironrdp_svc::SvcMessage>,
+) -> Result<(), String> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let data = est
+        .active_stage
+        .process_svc_processor_messages(crate::ironrdp_svc::SvcProcessorMessages::<
+            super::rail::RailClient,
+        >::new(messages))
+        .map_err(|e| format!("RAIL encode error: {e}"))?;
+    est.tls_framed
+        .write_all(&data)
+        .map_err(|e| format!("Failed to write RAIL PDU: {e}"))?;
+    stats
+        .bytes_sent
+        .fetch_add(data.len() as u64, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Deliver this batch's desktop updates to attached RemoteApp window
+/// streams, and repaint windows that were just attached or moved: via a
+/// Refresh Rect when the server supports it (so GFX sessions repaint too),
+/// otherwise straight from the desktop image.
+fn route_rail_output(
+    est: &mut EstablishedSession,
+    stats: &RdpSessionStats,
+    accounting: &FrameDeliveryAccounting,
+    dirty_rects: &mut Vec<(u16, u16, u16, u16)>,
+    repaint: &mut Vec<u32>,
+) {
+    let refresh_supported = est.refresh_rectangle_support;
+    let mut refresh_areas = Vec::new();
+    if let Some(rail) = est
+        .active_stage
+        .get_svc_processor_mut::<super::rail::RailClient>()
+    {
+        if rail.has_window_streams() && !dirty_rects.is_empty() {
+            merge_dirty_regions(dirty_rects);
+            rail.route_dirty_rects(est.image.data(), est.desktop_width, dirty_rects, accounting);
+        }
+        for window_id in repaint.drain(..) {
+            if !refresh_supported {
+                rail.push_window(window_id, est.image.data(), est.desktop_width, accounting);
+            } else if let Some(area) = rail.window_area(window_id) {
+                refresh_areas.push(InclusiveRectangle {
+                    left: area.x,
+                    top: area.y,
+                    right: area.x + area.w - 1,
+                    bottom: area.y + area.h - 1,
+                });
+            }
+        }
+    }
+    dirty_rects.clear();
+    repaint.clear();
+    if !refresh_areas.is_empty() {
+        let pdu = ShareDataPdu::RefreshRectangle(RefreshRectanglePdu {
+            areas_to_refresh: refresh_areas,
+        });
+        if let Err(e) = write_share_data_pdu(est, stats, pdu, &"RAIL window refresh") {
+            log::warn!("{e}");
+        }
+    }
+}
+
 fn reconcile_shared_activity_to_session(
     state: &mut RdpActivityState,
     activity_control: &SharedRdpSessionActivityControl,
@@ -2087,6 +2189,11 @@ fn run_active_session_loop(
     let mut batch_dirty_rects: Vec<(u16, u16, u16, u16)> = Vec::new();
     let mut gfx_frames: Vec<crate::gfx::processor::GfxOutput> = Vec::new();
 
+    // RemoteApp window streams are fed independently of the desktop viewer.
+    let rail_enabled = settings.remote_app.is_some();
+    let mut rail_dirty_rects: Vec<(u16, u16, u16, u16)> = Vec::new();
+    let mut rail_repaint: Vec<u32> = Vec::new();
+
     /// Maximum input events coalesced per loop iteration.
     const INPUT_BACKLOG_LIMIT: usize = 512;
 
@@ -2487,6 +2594,32 @@ fn run_active_session_loop(
                         ),
                     }
                 }
+                Ok(RdpCommand::Rail(command)) => {
+                    let attached = match command {
+                        super::rail::RailCommand::AttachWindow { window_id, .. } => Some(window_id),
+                        _ => None,
+                    };
+                    let Some(rail) = est
+                        .active_stage
+                        .get_svc_processor_mut::<super::rail::RailClient>()
+                    else {
+                        log::warn!(
+                            "RDP session {session_id}: RemoteApp command ignored (RAIL not enabled)"
+                        );
+                        continue;
+                    };
+                    match rail.handle_command(command) {
+                        Ok(messages) => {
+                            if let Err(e) = send_rail_messages(est, stats, messages) {
+                                log::warn!("RDP session {session_id}: {e}");
+                            }
+                            rail_repaint.extend(attached);
+                        }
+                        Err(e) => {
+                            log::warn!("RDP session {session_id}: RemoteApp command failed: {e}")
+                        }
+                    }
+                }
                 Err(mpsc::error::TryRecvError::Empty) => break,
                 Err(mpsc::error::TryRecvError::Disconnected) => {
                     log::info!("RDP session {session_id}: command channel closed");
@@ -2666,6 +2799,12 @@ fn run_active_session_loop(
             {
                 merge_channel_summary(&mut channel_summary, rdpsnd.channel_summary());
             }
+            if let Some(rail) = est
+                .active_stage
+                .get_svc_processor_mut::<super::rail::RailClient>()
+            {
+                merge_channel_summary(&mut channel_summary, rail.channel_summary());
+            }
             // AUDIN: the DVC processor is owned by DRDYNVC inside the active stage
             // and is not directly retrievable here, but it publishes its live
             // channel state into the runner-held shared summary handle on every
@@ -2785,6 +2924,24 @@ fn run_active_session_loop(
             }
             for gfx_output in gfx_frames.drain(..) {
                 stats.record_frame();
+                if let crate::gfx::processor::GfxOutput::Rgba(ref gfx_frame) = gfx_output {
+                    if let Some(rail) = est
+                        .active_stage
+                        .get_svc_processor_mut::<super::rail::RailClient>()
+                        .filter(|rail| rail.has_window_streams())
+                    {
+                        rail.route_rgba_rect(
+                            &gfx_frame.rgba,
+                            super::rail::Rect {
+                                x: gfx_frame.screen_x,
+                                y: gfx_frame.screen_y,
+                                w: gfx_frame.width,
+                                h: gfx_frame.height,
+                            },
+                            &frame_accounting,
+                        );
+                    }
+                }
                 if !activity_state.should_emit_output(viewer_detached, activity_control) {
                     continue;
                 }
@@ -2881,6 +3038,17 @@ fn run_active_session_loop(
                         .fetch_add(payload_len, Ordering::Relaxed);
                     stats.pdus_received.fetch_add(1, Ordering::Relaxed);
 
+                    // IronRDP drops windowing orders, so RAIL window state is
+                    // sniffed from the raw fast-path frame first.
+                    if rail_enabled && matches!(action, crate::ironrdp::pdu::Action::FastPath) {
+                        if let Some(rail) = est
+                            .active_stage
+                            .get_svc_processor_mut::<super::rail::RailClient>()
+                        {
+                            rail_repaint.extend(rail.ingest_fastpath(payload.as_ref()));
+                        }
+                    }
+
                     match est
                         .active_stage
                         .process(&mut est.image, action, payload.as_ref())
@@ -2904,6 +3072,16 @@ fn run_active_session_loop(
                                         }
                                     }
                                     ActiveStageOutput::GraphicsUpdate(region) => {
+                                        let rw = region.right.saturating_sub(region.left) + 1;
+                                        let rh = region.bottom.saturating_sub(region.top) + 1;
+                                        if rail_enabled {
+                                            rail_dirty_rects.push((
+                                                region.left,
+                                                region.top,
+                                                rw,
+                                                rh,
+                                            ));
+                                        }
                                         if !activity_state
                                             .should_emit_output(viewer_detached, activity_control)
                                         {
@@ -2911,8 +3089,6 @@ fn run_active_session_loop(
                                         }
                                         stats.record_frame();
                                         batch_had_graphics = true;
-                                        let rw = region.right.saturating_sub(region.left) + 1;
-                                        let rh = region.bottom.saturating_sub(region.top) + 1;
                                         if frame_batching {
                                             // A graphics update landing on a
                                             // non-empty backlog is coalesced into
@@ -3330,6 +3506,16 @@ fn run_active_session_loop(
             }
         }
 
+        if rail_enabled && (!rail_dirty_rects.is_empty() || !rail_repaint.is_empty()) {
+            route_rail_output(
+                est,
+                stats,
+                &frame_accounting,
+                &mut rail_dirty_rects,
+                &mut rail_repaint,
+            );
+        }
+
         if batch_should_terminate {
             return SessionLoopExit::ServerClosed;
         }
@@ -3371,6 +3557,15 @@ fn run_active_session_loop(
                         est.desktop_height,
                     );
                     est.active_stage = ActiveStage::new(new_result);
+                    if let Some(rail) = est
+                        .active_stage
+                        .get_svc_processor_mut::<super::rail::RailClient>()
+                    {
+                        let messages = rail.set_desktop_size(est.desktop_width, est.desktop_height);
+                        if let Err(e) = send_rail_messages(est, stats, messages) {
+                            log::warn!("RDP session {session_id}: {e}");
+                        }
+                    }
                     if let Err(error) = reconcile_shared_activity_to_session(
                         activity_state,
                         activity_control,
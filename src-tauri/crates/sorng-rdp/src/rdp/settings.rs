@@ -30,6 +30,8 @@ pub struct RdpSettingsPayload {
     pub advanced: Option<RdpAdvancedPayload>,
     #[serde(default)]
     pub tcp: Option<RdpTcpPayload>,
+    #[serde(default)]
+    pub remote_app: Option<RdpRemoteAppPayload>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -202,6 +204,31 @@ mod tests {
         assert!(build_bitmap_codecs(&settings).0.is_empty());
     }
 
+    #[test]
+    fn remote_app_resolves_only_when_enabled_with_a_program() {
+        let resolve = |value: serde_json::Value| {
+            let payload = serde_json::from_value::<RdpSettingsPayload>(value)
+                .expect("remote app payload");
+            ResolvedSettings::from_payload(&payload, 1280, 720).remote_app
+        };
+
+        let app = resolve(serde_json::json!({
+            "remoteApp": { "program": " ||notepad ", "arguments": "C:\\a.txt" }
+        }))
+        .expect("remote app config");
+        assert_eq!(app.program, "||notepad");
+        assert_eq!(app.arguments, "C:\\a.txt");
+        assert!(app.working_dir.is_empty());
+        assert!(!app.expand_arguments);
+
+        assert!(resolve(serde_json::json!({ "remoteApp": { "program": "  " } })).is_none());
+        assert!(resolve(serde_json::json!({
+            "remoteApp": { "enabled": false, "program": "||calc" }
+        }))
+        .is_none());
+        assert!(resolve(serde_json::json!({})).is_none());
+    }
+
     #[cfg(feature = "rdp-multimon")]
     #[test]
     fn monitor_layout_payload_is_carried_into_resolved_settings() {
@@ -372,6 +399,16 @@ pub struct RdpTcpPayload {
     pub send_buffer_size: Option<u32>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RdpRemoteAppPayload {
+    pub enabled: Option<bool>,
+    pub program: Option<String>, // "||alias" or a full path, e.g. "C:\\Windows\\notepad.exe"
+    pub arguments: Option<String>,
+    pub working_dir: Option<String>,
+    pub expand_arguments: Option<bool>,
+}
+
 /// Build IronRDP PerformanceFlags from the frontend settings
 pub fn build_performance_flags(perf: &RdpPerformancePayload) -> PerformanceFlags {
     let mut flags = PerformanceFlags::empty();
@@ -560,6 +597,8 @@ pub struct ResolvedSettings {
     pub ports_enabled: bool,
     pub smart_cards_enabled: bool,
     pub drive_redirections: Vec<DriveRedirectionConfig>,
+    // RemoteApp (RAIL)
+    pub remote_app: Option<RemoteAppConfig>,
 }
 
 /// Resolved drive redirection configuration.
@@ -571,6 +610,16 @@ pub struct DriveRedirectionConfig {
     pub preferred_letter: Option<char>,
 }
 
+/// Resolved RemoteApp launch: present only when RAIL is enabled and a
+/// program is configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteAppConfig {
+    pub program: String,
+    pub arguments: String,
+    pub working_dir: String,
+    pub expand_arguments: bool,
+}
+
 impl ResolvedSettings {
     pub fn from_payload(payload: &RdpSettingsPayload, width: u16, height: u16) -> Self {
         let display = payload.display.as_ref();
@@ -826,6 +875,20 @@ impl ResolvedSettings {
                         .collect()
                 })
                 .unwrap_or_default(),
+            // RemoteApp
+            remote_app: payload
+                .remote_app
+                .as_ref()
+                .filter(|r| r.enabled.unwrap_or(true))
+                .and_then(|r| {
+                    let program = r.program.as_deref().map(str::trim).unwrap_or_default();
+                    (!program.is_empty()).then(|| RemoteAppConfig {
+                        program: program.to_string(),
+                        arguments: r.arguments.clone().unwrap_or_default(),
+                        working_dir: r.working_dir.clone().unwrap_or_default(),
+                        expand_arguments: r.expand_arguments.unwrap_or(false),
+                    })
+                }),
         }
     }
 }
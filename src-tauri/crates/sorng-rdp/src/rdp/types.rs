@@ -549,6 +549,8 @@ pub enum RdpCommand {
         width: u16,
         height: u16,
     },
+    /// RemoteApp (RAIL) launch, window action or window-stream change.
+    Rail(crate::rdp::rail::RailCommand),
 }
 
 /// File entry for clipboard file transfer.
@@ -90,6 +90,7 @@ fn regular_command_byte_weight(command: &RdpCommand) -> usize {
             },
         ),
         RdpCommand::ToggleFeature { feature, .. } => feature.len(),
+        RdpCommand::Rail(command) => command.payload_len(),
         _ => 0,
     };
     std::mem::size_of::<RdpCommand>()
@@ -0,0 +1,314 @@
+//! Integration coverage for the software RDPGFX codecs.
+//!
+//! Feeds hand-built CreateSurface / MapSurfaceToOutput / WireToSurface PDU
+//! fixtures through a `GfxProcessor` and checks the RGBA dirty rects it emits
+//! for Planar, ClearCodec and RemoteFX (WireToSurface1) and RemoteFX
+//! Progressive (WireToSurface2), plus encoding-context teardown.
+//! Deterministic and independent of any H.264 decoder on the host.
+
+use std::sync::mpsc;
+
+use sorng_rdp::gfx::pdu::{
+    GfxCmdId, CODEC_CAPROGRESSIVE, CODEC_CAVIDEO, CODEC_CLEARCODEC, CODEC_PLANAR,
+    RDPGFX_HEADER_SIZE,
+};
+use sorng_rdp::gfx::processor::{GfxDvcProcessor, GfxFrame, GfxOutput, GfxProcessor};
+use sorng_rdp::h264::H264DecoderPreference;
+
+const SURFACE: u16 = 1;
+
+fn new_processor() -> (GfxProcessor, mpsc::Receiver<GfxOutput>) {
+    let (tx, rx) = mpsc::channel::<GfxOutput>();
+    let proc = GfxProcessor::new(H264DecoderPreference::Auto, tx, false);
+    (proc, rx)
+}
+
+fn gfx_pdu(cmd_id: GfxCmdId, body: &[u8]) -> Vec<u8> {
+    let pdu_len = (RDPGFX_HEADER_SIZE + body.len()) as u32;
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(cmd_id as u16).to_le_bytes());
+    buf.extend_from_slice(&0u16.to_le_bytes());
+    buf.extend_from_slice(&pdu_len.to_le_bytes());
+    buf.extend_from_slice(body);
+    buf
+}
+
+/// Create a `width x height` surface mapped to the output at (100, 50).
+fn mapped_surface(proc: &mut GfxProcessor, width: u16, height: u16) {
+    let mut create = Vec::new();
+    create.extend_from_slice(&SURFACE.to_le_bytes());
+    create.extend_from_slice(&width.to_le_bytes());
+    create.extend_from_slice(&height.to_le_bytes());
+    create.push(0x20); // XRGB_8888
+    let mut map = Vec::new();
+    map.extend_from_slice(&SURFACE.to_le_bytes());
+    map.extend_from_slice(&0u16.to_le_bytes());
+    map.extend_from_slice(&100u32.to_le_bytes());
+    map.extend_from_slice(&50u32.to_le_bytes());
+    let mut payload = gfx_pdu(GfxCmdId::CreateSurface, &create);
+    payload.extend(gfx_pdu(GfxCmdId::MapSurfaceToOutput, &map));
+    proc.process(7, &payload).expect("create + map surface");
+}
+
+fn wire_to_surface_1(codec_id: u16, rect: [u16; 4], bitmap: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&SURFACE.to_le_bytes());
+    body.extend_from_slice(&codec_id.to_le_bytes());
+    body.push(0x20);
+    for v in rect {
+        body.extend_from_slice(&v.to_le_bytes());
+    }
+    body.extend_from_slice(bitmap);
+    gfx_pdu(GfxCmdId::WireToSurface1, &body)
+}
+
+fn wire_to_surface_2(context_id: u32, bitmap: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&SURFACE.to_le_bytes());
+    body.extend_from_slice(&CODEC_CAPROGRESSIVE.to_le_bytes());
+    body.extend_from_slice(&context_id.to_le_bytes());
+    body.push(0x20);
+    body.extend_from_slice(bitmap);
+    gfx_pdu(GfxCmdId::WireToSurface2, &body)
+}
+
+fn next_rgba(rx: &mpsc::Receiver<GfxOutput>) -> GfxFrame {
+    match rx.try_recv().expect("an emitted frame") {
+        GfxOutput::Rgba(frame) => frame,
+        GfxOutput::Nal(_) => panic!("unexpected NAL output"),
+    }
+}
+
+fn progressive_block(block_type: u16, body: &[u8]) -> Vec<u8> {
+    let mut out = block_type.to_le_bytes().to_vec();
+    out.extend_from_slice(&(body.len() as u32 + 6).to_le_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+/// A luma component whose LL3 band is a flat 64. The RLGR1 and RLGR3
+/// encodings are identical: the stream never leaves run-length mode.
+const LUMA_DC_64: [u8; 8] = [0x00, 0x00, 0x1F, 0x11, 0xFF, 0xFF, 0xFF, 0xFD];
+
+/// One progressive frame: a single TILE_SIMPLE at tile (0, 0) whose luma
+/// LL3 band is a flat 64, clipped to an 8x4 region rect.
+fn progressive_frame() -> Vec<u8> {
+    let mut tile = vec![0, 0, 0]; // quant indices Y/Cb/Cr
+    tile.extend_from_slice(&0u16.to_le_bytes()); // xIdx
+    tile.extend_from_slice(&0u16.to_le_bytes()); // yIdx
+    tile.push(0); // flags
+    for len in [LUMA_DC_64.len() as u16, 0, 0, 0] {
+        tile.extend_from_slice(&len.to_le_bytes());
+    }
+    tile.extend_from_slice(&LUMA_DC_64);
+    let tile = progressive_block(0xCCC5, &tile);
+
+    let mut region = vec![64];
+    region.extend_from_slice(&1u16.to_le_bytes()); // numRects
+    region.push(1); // numQuant
+    region.push(0); // numProgQuant
+    region.push(0); // flags
+    region.extend_from_slice(&1u16.to_le_bytes()); // numTiles
+    region.extend_from_slice(&(tile.len() as u32).to_le_bytes());
+    for v in [0u16, 0, 8, 4] {
+        region.extend_from_slice(&v.to_le_bytes());
+    }
+    region.extend_from_slice(&[0x66; 5]); // every band quantized at 6
+    region.extend_from_slice(&tile);
+
+    let mut sync = 0xCACC_ACCAu32.to_le_bytes().to_vec();
+    sync.extend_from_slice(&0x0100u16.to_le_bytes());
+    let mut frame = progressive_block(0xCCC0, &sync);
+    frame.extend(progressive_block(0xCCC3, &[0, 64, 0, 0]));
+    frame.extend(progressive_block(0xCCC1, &[0, 0, 0, 0, 1, 0]));
+    frame.extend(progressive_block(0xCCC4, &region));
+    frame.extend(progressive_block(0xCCC2, &[]));
+    frame
+}
+
+/// One RemoteFX message laid out the way Windows sends the first frame of a
+/// stream: sync, codec versions, channels and an RLGR3 context, then a frame
+/// with a 4x2 region rect and a single tile at (0, 0).
+///
+/// Hand-assembled from MS-RDPRFX 2.2.2 rather than captured from a server
+/// (no Windows host is reachable from the test environment).
+fn remotefx_message() -> Vec<u8> {
+    let mut tile = vec![0, 0, 0]; // quant indices Y/Cb/Cr
+    for v in [0u16, 0, LUMA_DC_64.len() as u16, 0, 0] {
+        tile.extend_from_slice(&v.to_le_bytes()); // xIdx, yIdx, YLen, CbLen, CrLen
+    }
+    tile.extend_from_slice(&LUMA_DC_64);
+    let tile = progressive_block(0xCAC3, &tile);
+
+    let mut tileset = vec![0x01, 0x00]; // codecId, channelId
+    tileset.extend_from_slice(&0xCAC2u16.to_le_bytes());
+    tileset.extend_from_slice(&0u16.to_le_bytes()); // idx
+    tileset.extend_from_slice(&0x5051u16.to_le_bytes()); // lt, cct, xft, RLGR3, qt
+    tileset.extend_from_slice(&[1, 64]); // numQuant, tileSize
+    tileset.extend_from_slice(&1u16.to_le_bytes()); // numTiles
+    tileset.extend_from_slice(&(tile.len() as u32).to_le_bytes());
+    tileset.extend_from_slice(&[0x66; 5]); // every band quantized at 6
+    tileset.extend_from_slice(&tile);
+
+    let mut region = vec![0x01, 0x00, 0x01]; // codecId, channelId, lrf
+    region.extend_from_slice(&1u16.to_le_bytes());
+    for v in [2u16, 1, 4, 2] {
+        region.extend_from_slice(&v.to_le_bytes());
+    }
+    region.extend_from_slice(&0xCAC1u16.to_le_bytes()); // regionType
+    region.extend_from_slice(&1u16.to_le_bytes()); // numTilesets
+
+    let mut sync = 0xCACC_ACCAu32.to_le_bytes().to_vec();
+    sync.extend_from_slice(&0x0100u16.to_le_bytes());
+    let mut channels = vec![1, 0];
+    channels.extend_from_slice(&64u16.to_le_bytes());
+    channels.extend_from_slice(&64u16.to_le_bytes());
+    let mut context = vec![0x01, 0xFF, 0x00]; // codecId, channelId, ctxId
+    context.extend_from_slice(&64u16.to_le_bytes());
+    context.extend_from_slice(&0x2828u16.to_le_bytes()); // cct, xft, RLGR3, qt
+    let mut frame_begin = vec![0x01, 0x00];
+    frame_begin.extend_from_slice(&0u32.to_le_bytes());
+    frame_begin.extend_from_slice(&1u16.to_le_bytes());
+    [
+        progressive_block(0xCCC0, &sync),
+        progressive_block(0xCCC1, &[1, 0x01, 0x00, 0x01]),
+        progressive_block(0xCCC2, &channels),
+        progressive_block(0xCCC3, &context),
+        progressive_block(0xCCC4, &frame_begin),
+        progressive_block(0xCCC6, &region),
+        progressive_block(0xCCC7, &tileset),
+        progressive_block(0xCCC5, &[0x01, 0x00]),
+    ]
+    .concat()
+}
+
+#[test]
+fn planar_frame_is_blitted_and_emitted() {
+    let (mut proc, rx) = new_processor();
+    let handle = proc.shared_diagnostics();
+    mapped_surface(&mut proc, 16, 16);
+
+    // 2x1 raw ARGB: alpha plane, R, G, B planes, then the pad byte.
+    let planar = [0x00, 0x80, 0xFF, 1, 2, 3, 4, 5, 6, 0x00];
+    proc.process(7, &wire_to_surface_1(CODEC_PLANAR, [2, 3, 4, 4], &planar))
+        .expect("planar wts1");
+
+    let frame = next_rgba(&rx);
+    assert_eq!((frame.screen_x, frame.screen_y), (102, 53));
+    assert_eq!((frame.width, frame.height), (2, 1));
+    assert_eq!(frame.rgba, [1, 3, 5, 0x80, 2, 4, 6, 0xFF]);
+    let d = handle.lock().unwrap();
+    assert_eq!(d.codec, Some("planar"));
+    assert_eq!(d.pipeline_errors, 0);
+}
+
+#[test]
+fn clearcodec_layers_draw_over_the_surface() {
+    let (mut proc, rx) = new_processor();
+    mapped_surface(&mut proc, 8, 8);
+
+    // 2x2 residual: all red. Subcodec: raw blue pixel at (1, 1).
+    let residual = [0x00, 0x00, 0xFF, 4];
+    let mut subcodec = Vec::new();
+    for v in [1u16, 1, 1, 1] {
+        subcodec.extend_from_slice(&v.to_le_bytes());
+    }
+    subcodec.extend_from_slice(&3u32.to_le_bytes());
+    subcodec.push(0); // uncompressed
+    subcodec.extend_from_slice(&[0xFF, 0x00, 0x00]);
+    let mut clear = vec![0x00, 0x00]; // flags, seq
+    for len in [residual.len(), 0, subcodec.len()] {
+        clear.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+    clear.extend_from_slice(&residual);
+    clear.extend_from_slice(&subcodec);
+    proc.process(
+        7,
+        &wire_to_surface_1(CODEC_CLEARCODEC, [4, 4, 6, 6], &clear),
+    )
+    .expect("clearcodec wts1");
+
+    let frame = next_rgba(&rx);
+    assert_eq!((frame.screen_x, frame.screen_y), (104, 54));
+    assert_eq!((frame.width, frame.height), (2, 2));
+    let red = [0xFF, 0x00, 0x00, 0xFF];
+    let blue = [0x00, 0x00, 0xFF, 0xFF];
+    assert_eq!(frame.rgba, [red, red, red, blue].concat());
+
+    // A header-only bitmap keeps what is already on the surface.
+    proc.process(
+        7,
+        &wire_to_surface_1(CODEC_CLEARCODEC, [4, 4, 6, 6], &[0, 1]),
+    )
+    .expect("clearcodec wts1");
+    assert_eq!(next_rgba(&rx).rgba, [red, red, red, blue].concat());
+}
+
+#[test]
+fn remotefx_tiles_decode_instead_of_h264() {
+    let (mut proc, rx) = new_processor();
+    let handle = proc.shared_diagnostics();
+    mapped_surface(&mut proc, 16, 8);
+
+    proc.process(
+        7,
+        &wire_to_surface_1(CODEC_CAVIDEO, [8, 4, 16, 8], &remotefx_message()),
+    )
+    .expect("remotefx wts1");
+    let frame = next_rgba(&rx);
+    // Region rects are relative to the destination rect.
+    assert_eq!((frame.screen_x, frame.screen_y), (110, 55));
+    assert_eq!((frame.width, frame.height), (4, 2));
+    assert!(frame.rgba.chunks(4).all(|px| px == [192, 192, 192, 0xFF]));
+    let d = handle.lock().unwrap();
+    assert_eq!(d.codec, Some("RemoteFX"));
+    assert_eq!(d.pipeline_errors, 0);
+}
+
+#[test]
+fn progressive_tiles_decode_and_contexts_are_torn_down() {
+    let (mut proc, rx) = new_processor();
+    let handle = proc.shared_diagnostics();
+    mapped_surface(&mut proc, 16, 8);
+
+    proc.process(7, &wire_to_surface_2(3, &progressive_frame()))
+        .expect("progressive wts2");
+    let frame = next_rgba(&rx);
+    assert_eq!((frame.screen_x, frame.screen_y), (100, 50));
+    assert_eq!((frame.width, frame.height), (8, 4));
+    // Luma 64 << 5 in 11.5 fixed point over a 128 midpoint.
+    assert!(frame.rgba.chunks(4).all(|px| px == [192, 192, 192, 0xFF]));
+    assert_eq!(handle.lock().unwrap().codec, Some("progressive"));
+
+    // After DeleteEncodingContext the tile cache is gone, so an upgrade pass
+    // for the same context is a (recoverable) decode error.
+    let mut delete = SURFACE.to_le_bytes().to_vec();
+    delete.extend_from_slice(&3u32.to_le_bytes());
+    proc.process(7, &gfx_pdu(GfxCmdId::DeleteEncodingCtx, &delete))
+        .expect("delete encoding context");
+    let mut upgrade = vec![0u8; 20];
+    upgrade[7] = 0xFF; // full quality
+    let mut region = vec![64, 1, 0, 1, 0, 0, 1, 0];
+    region.extend_from_slice(&26u32.to_le_bytes());
+    region.extend_from_slice(&[0, 0, 0, 0, 8, 0, 4, 0]);
+    region.extend_from_slice(&[0x66; 5]);
+    region.extend(progressive_block(0xCCC7, &upgrade));
+    proc.process(
+        7,
+        &wire_to_surface_2(3, &progressive_block(0xCCC4, &region)),
+    )
+    .expect("progressive upgrade");
+    assert!(rx.try_recv().is_err());
+
+    // Pipeline errors reach the shared handle on the next publish.
+    proc.process(7, &gfx_pdu(GfxCmdId::DeleteSurface, &SURFACE.to_le_bytes()))
+        .expect("delete surface");
+    let d = handle.lock().unwrap();
+    assert_eq!(d.surfaces_active, 0);
+    assert_eq!(d.pipeline_errors, 1);
+    assert_eq!(
+        d.last_error_class.as_deref(),
+        Some("progressive_decode_error")
+    );
+    assert_eq!(d.summary.failed_count, 0);
+}
@@ -1,7 +1,7 @@
 //! Convert between [`RdpFile`] and the app's connection format.
 
 use crate::error::RdpFileError;
-use crate::types::{ConnectionImport, RdpFile};
+use crate::types::{ConnectionImport, RdpFile, RemoteAppImport};
 
 /// Default RDP port.
 const DEFAULT_RDP_PORT: u16 = 3389;
@@ -24,6 +24,19 @@ pub fn rdp_to_connection(rdp: &RdpFile) -> ConnectionImport {
     // Serialize the entire RdpFile to JSON for flexible storage
     let rdp_settings = serde_json::to_value(rdp).unwrap_or(serde_json::Value::Null);
 
+    // RemoteApp mode without a program has nothing to launch
+    let remote_app = match (rdp.remoteapplicationmode, &rdp.remoteapplicationprogram) {
+        (Some(true), Some(program)) if !program.is_empty() => Some(RemoteAppImport {
+            enabled: true,
+            program: program.clone(),
+            arguments: rdp
+                .remoteapplicationcmdline
+                .clone()
+                .filter(|args| !args.is_empty()),
+        }),
+        _ => None,
+    };
+
     ConnectionImport {
         name,
         hostname,
@@ -31,6 +44,7 @@ pub fn rdp_to_connection(rdp: &RdpFile) -> ConnectionImport {
         username: rdp.username.clone(),
         domain: rdp.domain.clone(),
         rdp_settings,
+        remote_app,
     }
 }
 
@@ -167,6 +181,22 @@ pub fn connection_to_rdp(connection_json: &serde_json::Value) -> Result<RdpFile,
         }
     }
 
+    // RemoteApp, in the shape `rdp_to_connection` produces
+    if let Some(app) = connection_json
+        .get("remote_app")
+        .and_then(|v| v.as_object())
+    {
+        if let Some(v) = app.get("enabled").and_then(|v| v.as_bool()) {
+            rdp.remoteapplicationmode = Some(v);
+        }
+        if let Some(v) = app.get("program").and_then(|v| v.as_str()) {
+            rdp.remoteapplicationprogram = Some(v.to_string());
+        }
+        if let Some(v) = app.get("arguments").and_then(|v| v.as_str()) {
+            rdp.remoteapplicationcmdline = Some(v.to_string());
+        }
+    }
+
     Ok(rdp)
 }
 
@@ -204,6 +234,49 @@ mod tests {
         assert_eq!(conn.name, "myhost");
     }
 
+    #[test]
+    fn rdp_to_connection_remote_app() {
+        let rdp = crate::parser::parse_rdp_file(
+            "full address:s:apps.example\r\n\
+             remoteapplicationmode:i:1\r\n\
+             remoteapplicationprogram:s:||notepad\r\n\
+             remoteapplicationname:s:Notepad\r\n\
+             remoteapplicationcmdline:s:C:\\notes.txt\r\n",
+        )
+        .unwrap()
+        .rdp_file;
+        let conn = rdp_to_connection(&rdp);
+        assert_eq!(
+            conn.remote_app,
+            Some(RemoteAppImport {
+                enabled: true,
+                program: "||notepad".to_string(),
+                arguments: Some("C:\\notes.txt".to_string()),
+            })
+        );
+
+        let json = serde_json::to_value(&conn).unwrap();
+        assert_eq!(json["remote_app"]["program"], "||notepad");
+        let back = connection_to_rdp(&serde_json::json!({
+            "hostname": conn.hostname,
+            "remote_app": json["remote_app"],
+        }))
+        .unwrap();
+        assert_eq!(back.remoteapplicationmode, Some(true));
+        assert_eq!(back.remoteapplicationprogram.as_deref(), Some("||notepad"));
+        assert_eq!(
+            back.remoteapplicationcmdline.as_deref(),
+            Some("C:\\notes.txt")
+        );
+
+        let desktop = RdpFile {
+            full_address: "apps.example".to_string(),
+            remoteapplicationmode: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(rdp_to_connection(&desktop).remote_app, None);
+    }
+
     #[test]
     fn connection_to_rdp_basic() {
         let json = serde_json::json!({
@@ -235,6 +235,21 @@ pub fn generate_with_options(rdp: &RdpFile, options: &GenerateOptions) -> String
         "remoteapplicationmode",
         rdp.remoteapplicationmode,
     );
+    write_opt_str(
+        &mut output,
+        "remoteapplicationprogram",
+        &rdp.remoteapplicationprogram,
+    );
+    write_opt_str(
+        &mut output,
+        "remoteapplicationname",
+        &rdp.remoteapplicationname,
+    );
+    write_opt_str(
+        &mut output,
+        "remoteapplicationcmdline",
+        &rdp.remoteapplicationcmdline,
+    );
     write_opt_str(&mut output, "alternate shell", &rdp.alternate_shell);
     write_opt_str(
         &mut output,
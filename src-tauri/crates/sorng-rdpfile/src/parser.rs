@@ -129,6 +129,9 @@ const KNOWN_SETTINGS: &[&str] = &[
     "negotiate security layer",
     "enablecredsspsupport",
     "remoteapplicationmode",
+    "remoteapplicationprogram",
+    "remoteapplicationname",
+    "remoteapplicationcmdline",
     "alternate shell",
     "shell working directory",
     "gatewayhostname",
@@ -305,6 +308,15 @@ pub fn parse_rdp_file(content: &str) -> Result<RdpParseResult, RdpFileError> {
             "remoteapplicationmode" => {
                 rdp.remoteapplicationmode = val_to_bool(value);
             }
+            "remoteapplicationprogram" => {
+                rdp.remoteapplicationprogram = val_to_string(value);
+            }
+            "remoteapplicationname" => {
+                rdp.remoteapplicationname = val_to_string(value);
+            }
+            "remoteapplicationcmdline" => {
+                rdp.remoteapplicationcmdline = val_to_string(value);
+            }
             "alternate shell" => {
                 rdp.alternate_shell = val_to_string(value);
             }
@@ -418,6 +430,27 @@ redirectclipboard:i:1
         assert!(result.warnings.is_empty());
     }
 
+    #[test]
+    fn parse_remote_app_settings() {
+        let content = "\
+full address:s:apps.example.com
+remoteapplicationmode:i:1
+remoteapplicationprogram:s:||notepad
+remoteapplicationname:s:Notepad
+remoteapplicationcmdline:s:C:\\notes.txt
+";
+        let result = parse_rdp_file(content).unwrap();
+        let rdp = &result.rdp_file;
+        assert_eq!(rdp.remoteapplicationmode, Some(true));
+        assert_eq!(rdp.remoteapplicationprogram.as_deref(), Some("||notepad"));
+        assert_eq!(rdp.remoteapplicationname.as_deref(), Some("Notepad"));
+        assert_eq!(
+            rdp.remoteapplicationcmdline.as_deref(),
+            Some("C:\\notes.txt")
+        );
+        assert!(result.unknown_settings.is_empty());
+    }
+
     #[test]
     fn parse_unknown_settings_collected() {
         let content = "\
@@ -145,6 +145,12 @@ pub struct RdpFile {
     // ── RemoteApp ───────────────────────────────────────────────
     /// Enable RemoteApp/published application mode.
     pub remoteapplicationmode: Option<bool>,
+    /// Published program to launch, e.g. `||notepad` or a full path.
+    pub remoteapplicationprogram: Option<String>,
+    /// Display name of the published program.
+    pub remoteapplicationname: Option<String>,
+    /// Command-line arguments for the published program.
+    pub remoteapplicationcmdline: Option<String>,
     /// Alternate shell (program to start on connection).
     pub alternate_shell: Option<String>,
     /// Shell working directory.
@@ -209,4 +215,19 @@ pub struct ConnectionImport {
     pub domain: Option<String>,
     /// All RDP-specific settings as a JSON value for flexible storage.
     pub rdp_settings: serde_json::Value,
+    /// RemoteApp launch settings, when the file opens a published program
+    /// instead of a desktop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_app: Option<RemoteAppImport>,
+}
+
+/// RemoteApp settings of an imported connection, shaped like the app's
+/// `RdpRemoteAppSettings`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteAppImport {
+    pub enabled: bool,
+    /// `||alias` of a published program, or a full path.
+    pub program: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
 }
@@ -862,6 +862,23 @@ pub async fn rec_library_summary(
     Ok(svc.library_summary().await)
 }
 
+#[tauri::command]
+pub async fn rec_library_search_content(
+    state: tauri::State<'_, RecordingServiceState>,
+    query: RecordingSearchQuery,
+) -> Result<Vec<RecordingSearchHit>, String> {
+    let svc = state.lock().await;
+    svc.search_recordings(query).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rec_library_rebuild_search_index(
+    state: tauri::State<'_, RecordingServiceState>,
+) -> Result<usize, String> {
+    let svc = state.lock().await;
+    svc.rebuild_search_index().await.map_err(|e| e.to_string())
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 //  Aggregate / status commands
 // ═══════════════════════════════════════════════════════════════════════
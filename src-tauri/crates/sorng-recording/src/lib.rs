@@ -11,6 +11,7 @@ pub mod encoders;
 pub mod engine;
 pub mod error;
 pub mod redact;
+pub mod search_index;
 pub mod service;
 pub mod storage;
 pub mod types;
@@ -6,5 +6,6 @@ pub mod engine;
 pub mod encoders;
 pub mod compression;
 pub mod redact;
+pub mod search_index;
 pub mod storage;
 pub mod service;
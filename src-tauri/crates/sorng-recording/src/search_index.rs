@@ -0,0 +1,876 @@
+// sorng-recording – Full-text search index
+//
+// Persistent inverted index over every envelope in the library: terminal
+// output lines, typed commands, HTTP request URLs, DB queries and serial
+// traffic, plus the library metadata. Each segment keeps its offset into
+// the recording so a hit can seek the player to the right moment.
+//
+// Only the documents (segment text + recording metadata) are persisted;
+// the postings are rebuilt in memory on load. The index holds recording
+// content, so the service persists it under the same encrypt-at-rest
+// policy as the envelopes themselves (see `storage::save_search_index_*`).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compression;
+use crate::error::{RecordingError, RecordingResult};
+use crate::types::*;
+
+/// Bumped whenever extraction rules change; an index written by an older
+/// version is discarded and rebuilt from the library.
+pub const INDEX_VERSION: u32 = 1;
+
+const DEFAULT_LIMIT: usize = 200;
+/// Segments longer than this are truncated before indexing.
+const MAX_SEGMENT_CHARS: usize = 2048;
+const SNIPPET_CHARS: usize = 160;
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Documents
+// ═══════════════════════════════════════════════════════════════════════
+
+/// One searchable unit of text — a terminal line, a typed command, a
+/// request, a query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSegment {
+    pub position_ms: u64,
+    pub kind: IndexSegmentKind,
+    pub text: String,
+}
+
+/// Everything the index knows about one library recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedRecording {
+    pub id: String,
+    pub name: String,
+    pub protocol: RecordingProtocol,
+    pub host: Option<String>,
+    pub connection_id: Option<String>,
+    pub connection_name: Option<String>,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub segments: Vec<IndexSegment>,
+}
+
+impl IndexedRecording {
+    /// Extract the searchable text of `envelope`. `payload` is the
+    /// encoded (compressed, base64) recording — the inline `data`, or
+    /// the media sidecar bytes for peeled envelopes. Payloads that can't
+    /// be decoded still index their metadata.
+    pub fn from_envelope(envelope: &SavedRecordingEnvelope, payload: &str) -> Self {
+        let mut segments = vec![metadata_segment(envelope)];
+        let binary = matches!(
+            envelope.format,
+            ExportFormat::FrameSequence | ExportFormat::Custom(_)
+        );
+        if !binary && !payload.is_empty() {
+            match decode_payload(payload, &envelope.compression) {
+                Some(text) => segments.extend(extract_segments(&envelope.format, &text)),
+                None => log::debug!(
+                    "search index: payload of recording {} is not decodable text",
+                    envelope.id
+                ),
+            }
+        }
+        Self {
+            id: envelope.id.clone(),
+            name: envelope.name.clone(),
+            protocol: envelope.protocol.clone(),
+            host: envelope.host.clone(),
+            connection_id: envelope.connection_id.clone(),
+            connection_name: envelope.connection_name.clone(),
+            saved_at: envelope.saved_at,
+            segments,
+        }
+    }
+
+    /// Pick up a rename / retag without re-reading the payload.
+    fn refresh_metadata(&mut self, envelope: &SavedRecordingEnvelope) {
+        self.name = envelope.name.clone();
+        self.host = envelope.host.clone();
+        self.connection_id = envelope.connection_id.clone();
+        self.connection_name = envelope.connection_name.clone();
+        self.segments
+            .retain(|segment| segment.kind != IndexSegmentKind::Metadata);
+        self.segments.insert(0, metadata_segment(envelope));
+    }
+}
+
+fn metadata_segment(envelope: &SavedRecordingEnvelope) -> IndexSegment {
+    let mut parts = vec![envelope.name.as_str()];
+    parts.extend(envelope.description.as_deref());
+    parts.extend(envelope.host.as_deref());
+    parts.extend(envelope.connection_name.as_deref());
+    parts.extend(envelope.tags.iter().map(String::as_str));
+    segment(0, IndexSegmentKind::Metadata, &parts.join(" "))
+}
+
+fn decode_payload(payload: &str, algo: &CompressionAlgorithm) -> Option<String> {
+    match compression::decompress_from_b64(payload, algo) {
+        Ok(text) => Some(text),
+        // Envelopes saved straight from the frontend without compression
+        // may carry the encoded recording as-is.
+        Err(_) if *algo == CompressionAlgorithm::None => Some(payload.to_string()),
+        Err(_) => None,
+    }
+}
+
+fn segment(position_ms: u64, kind: IndexSegmentKind, text: &str) -> IndexSegment {
+    IndexSegment {
+        position_ms,
+        kind,
+        text: normalise(text),
+    }
+}
+
+/// Collapse whitespace runs and cap the length.
+fn normalise(text: &str) -> String {
+    let mut out = String::new();
+    for word in text.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if let Some((cut, _)) = out.char_indices().nth(MAX_SEGMENT_CHARS) {
+        out.truncate(cut);
+    }
+    out
+}
+
+/// Lower-cased alphanumeric runs.
+fn tokens(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Extraction  (one reader per export format)
+// ═══════════════════════════════════════════════════════════════════════
+
+fn extract_segments(format: &ExportFormat, text: &str) -> Vec<IndexSegment> {
+    let mut segments = match format {
+        ExportFormat::Asciicast => extract_asciicast(text),
+        ExportFormat::Script => {
+            let mut terminal = TerminalText::new();
+            terminal.output(0, text);
+            terminal.finish()
+        }
+        ExportFormat::Har => extract_har(text),
+        ExportFormat::Csv => extract_csv(text),
+        ExportFormat::Json => extract_json(text),
+        ExportFormat::Raw => extract_serial_raw(text),
+        ExportFormat::FrameSequence | ExportFormat::Custom(_) => return Vec::new(),
+    };
+    segments.retain(|segment| !segment.text.is_empty());
+    segments.sort_by_key(|segment| segment.position_ms);
+    segments
+}
+
+/// Asciicast v2: header line, then `[seconds, "o" | "i", data]` events.
+fn extract_asciicast(text: &str) -> Vec<IndexSegment> {
+    let mut terminal = TerminalText::new();
+    for line in text.lines().skip(1) {
+        let Ok((seconds, code, data)) = serde_json::from_str::<(f64, String, String)>(line) else {
+            continue;
+        };
+        let at_ms = (seconds * 1000.0).round() as u64;
+        match code.as_str() {
+            "o" => terminal.output(at_ms, &data),
+            "i" => terminal.input(at_ms, &data),
+            _ => {}
+        }
+    }
+    terminal.finish()
+}
+
+fn extract_har(text: &str) -> Vec<IndexSegment> {
+    let Ok(har) = serde_json::from_str::<serde_json::Value>(text) else {
+        return Vec::new();
+    };
+    let Some(entries) = har.pointer("/log/entries").and_then(|e| e.as_array()) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let request = entry.get("request")?;
+            Some(request_segment(
+                0,
+                request.get("method")?.as_str()?,
+                request.get("url")?.as_str()?,
+            ))
+        })
+        .collect()
+}
+
+/// DB query and HTTP CSV exports, told apart by their header.
+fn extract_csv(text: &str) -> Vec<IndexSegment> {
+    let mut records = parse_csv(text).into_iter();
+    let header = records.next().unwrap_or_default();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let timestamp = column("timestamp_ms");
+    let at_ms = |record: &[String]| {
+        timestamp
+            .and_then(|i| record.get(i))
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(0)
+    };
+
+    if let Some(query) = column("query") {
+        records
+            .filter_map(|record| {
+                let text = record.get(query)?;
+                Some(segment(at_ms(&record), IndexSegmentKind::Query, text))
+            })
+            .collect()
+    } else if let (Some(method), Some(url)) = (column("method"), column("url")) {
+        records
+            .filter_map(|record| {
+                Some(request_segment(
+                    at_ms(&record),
+                    record.get(method)?,
+                    record.get(url)?,
+                ))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Minimal RFC 4180 reader — quoted fields may contain commas, doubled
+/// quotes and newlines (multi-line SQL).
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// The generic JSON encoders: terminal, telnet, serial, HTTP and DB
+/// recordings all serialise an `entries` array.
+fn extract_json(text: &str) -> Vec<IndexSegment> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return Vec::new();
+    };
+    let Some(entries) = value.get("entries").and_then(|e| e.as_array()) else {
+        return Vec::new();
+    };
+    let mut terminal = TerminalText::new();
+    let mut segments = Vec::new();
+    for entry in entries {
+        let at_ms = entry
+            .get("timestamp_ms")
+            .and_then(|t| t.as_u64())
+            .unwrap_or(0);
+        let field = |name: &str| entry.get(name).and_then(|v| v.as_str());
+        if let Some(query) = field("query") {
+            segments.push(segment(at_ms, IndexSegmentKind::Query, query));
+        } else if let (Some(method), Some(url)) = (field("method"), field("url")) {
+            segments.push(request_segment(at_ms, method, url));
+        } else if let Some(data) = field("data") {
+            match field("entry_type") {
+                Some("Output" | "Received") => terminal.output(at_ms, data),
+                Some("Input" | "Sent") => terminal.input(at_ms, data),
+                _ => {}
+            }
+        }
+    }
+    segments.extend(terminal.finish());
+    segments
+}
+
+/// `encode_serial_raw`: received data verbatim, sent data prefixed
+/// `[TX] `, control-line changes as `[CTRL] …`, `#` header/footer.
+fn extract_serial_raw(text: &str) -> Vec<IndexSegment> {
+    let mut terminal = TerminalText::new();
+    for line in text.split_inclusive('\n') {
+        if let Some(sent) = line.strip_prefix("[TX] ") {
+            terminal.input(0, sent);
+            terminal.input(0, "\n");
+        } else if !line.starts_with("[CTRL] ") && !line.starts_with("# ") {
+            terminal.output(0, line);
+        }
+    }
+    terminal.finish()
+}
+
+fn request_segment(position_ms: u64, method: &str, url: &str) -> IndexSegment {
+    segment(
+        position_ms,
+        IndexSegmentKind::Url,
+        &format!("{} {}", method, url),
+    )
+}
+
+/// Splits a terminal byte stream into lines: output as the screen showed
+/// it (escape sequences stripped, carriage-return overwrites applied),
+/// input as the line the user submitted (line editing applied).
+struct TerminalText {
+    output: LineBuffer,
+    input: LineBuffer,
+    segments: Vec<IndexSegment>,
+}
+
+impl TerminalText {
+    fn new() -> Self {
+        Self {
+            output: LineBuffer::new(IndexSegmentKind::Output),
+            input: LineBuffer::new(IndexSegmentKind::Input),
+            segments: Vec::new(),
+        }
+    }
+
+    fn output(&mut self, at_ms: u64, data: &str) {
+        self.output.feed(at_ms, data, &mut self.segments);
+    }
+
+    fn input(&mut self, at_ms: u64, data: &str) {
+        self.input.feed(at_ms, data, &mut self.segments);
+    }
+
+    fn finish(mut self) -> Vec<IndexSegment> {
+        self.output.flush(&mut self.segments);
+        self.input.flush(&mut self.segments);
+        self.segments
+    }
+}
+
+struct LineBuffer {
+    kind: IndexSegmentKind,
+    line: String,
+    started_ms: Option<u64>,
+    escape: Escape,
+    pending_cr: bool,
+}
+
+impl LineBuffer {
+    fn new(kind: IndexSegmentKind) -> Self {
+        Self {
+            kind,
+            line: String::new(),
+            started_ms: None,
+            escape: Escape::None,
+            pending_cr: false,
+        }
+    }
+
+    fn feed(&mut self, at_ms: u64, data: &str, out: &mut Vec<IndexSegment>) {
+        let typed = self.kind == IndexSegmentKind::Input;
+        for c in data.chars() {
+            if self.escape.swallow(c) {
+                continue;
+            }
+            // A bare CR in output returns to column 0: what follows
+            // overwrites the line (progress bars, prompt redraws).
+            if std::mem::take(&mut self.pending_cr) && c != '\n' {
+                self.clear();
+            }
+            match c {
+                '\n' => self.flush(out),
+                '\r' if typed => self.flush(out),
+                '\r' => self.pending_cr = true,
+                '\x08' | '\x7f' => {
+                    self.line.pop();
+                }
+                // Ctrl-U / Ctrl-C discard the line being typed.
+                '\x15' | '\x03' if typed => self.clear(),
+                // Ctrl-W deletes the previous word.
+                '\x17' if typed => {
+                    let kept = self
+                        .line
+                        .trim_end()
+                        .char_indices()
+                        .rev()
+                        .find(|(_, c)| c.is_whitespace())
+                        .map_or(0, |(i, c)| i + c.len_utf8());
+                    self.line.truncate(kept);
+                }
+                '\t' => self.push(at_ms, ' '),
+                c if c.is_control() => {}
+                c => self.push(at_ms, c),
+            }
+        }
+    }
+
+    fn push(&mut self, at_ms: u64, c: char) {
+        self.started_ms.get_or_insert(at_ms);
+        if self.line.len() < MAX_SEGMENT_CHARS * 4 {
+            self.line.push(c);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.line.clear();
+        self.started_ms = None;
+    }
+
+    fn flush(&mut self, out: &mut Vec<IndexSegment>) {
+        let line = std::mem::take(&mut self.line);
+        if let Some(at_ms) = self.started_ms.take() {
+            out.push(segment(at_ms, self.kind, &line));
+        }
+    }
+}
+
+/// ANSI / VT escape-sequence skipper.
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    Start,
+    Csi,
+    Ss3,
+    /// OSC / DCS / PM / APC payload, terminated by BEL or ST.
+    String,
+    StringEsc,
+}
+
+impl Escape {
+    /// Advance the state machine; `true` when `c` belongs to a sequence.
+    fn swallow(&mut self, c: char) -> bool {
+        *self = match (*self, c) {
+            (Escape::None, '\x1b') => Escape::Start,
+            (Escape::None, _) => return false,
+            (Escape::Start, '[') => Escape::Csi,
+            (Escape::Start, 'O') => Escape::Ss3,
+            (Escape::Start, ']' | 'P' | 'X' | '^' | '_') => Escape::String,
+            (Escape::Start, ' '..='/') => Escape::Start,
+            (Escape::Start, _) => Escape::None,
+            (Escape::Csi, '@'..='~') => Escape::None,
+            (Escape::Csi, _) => Escape::Csi,
+            (Escape::Ss3, _) => Escape::None,
+            (Escape::String, '\x07') => Escape::None,
+            (Escape::String, '\x1b') => Escape::StringEsc,
+            (Escape::String, _) => Escape::String,
+            (Escape::StringEsc, _) => Escape::None,
+        };
+        true
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Index
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Inverted index over the recording library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredSearchIndex")]
+pub struct SearchIndex {
+    version: u32,
+    documents: HashMap<String, IndexedRecording>,
+    /// term → recording id → ascending segment indices.
+    #[serde(skip)]
+    postings: HashMap<String, HashMap<String, Vec<u32>>>,
+}
+
+#[derive(Deserialize)]
+struct StoredSearchIndex {
+    version: u32,
+    documents: HashMap<String, IndexedRecording>,
+}
+
+impl From<StoredSearchIndex> for SearchIndex {
+    fn from(stored: StoredSearchIndex) -> Self {
+        let mut index = Self {
+            version: stored.version,
+            documents: HashMap::new(),
+            postings: HashMap::new(),
+        };
+        for (_, document) in stored.documents {
+            index.insert(document);
+        }
+        index
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            documents: HashMap::new(),
+            postings: HashMap::new(),
+        }
+    }
+
+    /// Whether this index was built with the current extraction rules.
+    pub fn is_current(&self) -> bool {
+        self.version == INDEX_VERSION
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.documents.contains_key(id)
+    }
+
+    /// Add or replace a recording.
+    pub fn insert(&mut self, document: IndexedRecording) {
+        self.remove(&document.id);
+        for (position, segment) in document.segments.iter().enumerate() {
+            let terms: HashSet<String> = tokens(&segment.text).collect();
+            for term in terms {
+                self.postings
+                    .entry(term)
+                    .or_default()
+                    .entry(document.id.clone())
+                    .or_default()
+                    .push(position as u32);
+            }
+        }
+        self.documents.insert(document.id.clone(), document);
+    }
+
+    /// Drop a recording. Returns whether it was indexed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(document) = self.documents.remove(id) else {
+            return false;
+        };
+        let terms: HashSet<String> = document
+            .segments
+            .iter()
+            .flat_map(|segment| tokens(&segment.text))
+            .collect();
+        for term in terms {
+            if let Some(documents) = self.postings.get_mut(&term) {
+                documents.remove(id);
+                if documents.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    /// Re-index the metadata of an already-indexed recording. Returns
+    /// whether it was indexed.
+    pub fn refresh_metadata(&mut self, envelope: &SavedRecordingEnvelope) -> bool {
+        let Some(mut document) = self.documents.get(&envelope.id).cloned() else {
+            return false;
+        };
+        document.refresh_metadata(envelope);
+        self.insert(document);
+        true
+    }
+
+    /// Drop every recording for which `keep` returns false. Returns the
+    /// number removed.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) -> usize {
+        let stale: Vec<String> = self
+            .documents
+            .keys()
+            .filter(|id| !keep(id))
+            .cloned()
+            .collect();
+        for id in &stale {
+            self.remove(id);
+        }
+        stale.len()
+    }
+
+    /// Matching segments, newest recording first and in playback order
+    /// within a recording.
+    pub fn search(&self, query: &RecordingSearchQuery) -> RecordingResult<Vec<RecordingSearchHit>> {
+        let phrase = normalise(&query.text).to_lowercase();
+        let terms: HashSet<String> = tokens(&phrase).collect();
+        if terms.is_empty() {
+            return Err(RecordingError::InvalidParameter(
+                "search text must contain at least one letter or digit".into(),
+            ));
+        }
+        let mut postings = Vec::with_capacity(terms.len());
+        for term in &terms {
+            match self.postings.get(term) {
+                Some(documents) => postings.push(documents),
+                None => return Ok(Vec::new()),
+            }
+        }
+        // Walk the rarest term; every other term must share the segment.
+        postings.sort_by_key(|documents| documents.values().map(Vec::len).sum::<usize>());
+        let (rarest, others) = postings.split_first().expect("at least one term");
+        let host = query.host.as_deref().map(str::to_lowercase);
+
+        let mut hits = Vec::new();
+        for (id, positions) in *rarest {
+            let document = &self.documents[id];
+            if !matches_filters(document, query, host.as_deref()) {
+                continue;
+            }
+            for &position in positions {
+                let segment = &document.segments[position as usize];
+                if !query.kinds.is_empty() && !query.kinds.contains(&segment.kind) {
+                    continue;
+                }
+                let shares_segment = others.iter().all(|documents| {
+                    documents
+                        .get(id)
+                        .is_some_and(|positions| positions.binary_search(&position).is_ok())
+                });
+                if !shares_segment {
+                    continue;
+                }
+                let lower = segment.text.to_lowercase();
+                let Some(at) = lower.find(&phrase) else {
+                    continue;
+                };
+                hits.push(RecordingSearchHit {
+                    recording_id: document.id.clone(),
+                    name: document.name.clone(),
+                    protocol: document.protocol.clone(),
+                    host: document.host.clone(),
+                    connection_id: document.connection_id.clone(),
+                    connection_name: document.connection_name.clone(),
+                    saved_at: document.saved_at,
+                    position_ms: segment.position_ms,
+                    kind: segment.kind,
+                    snippet: snippet(
+                        &segment.text,
+                        lower[..at].chars().count(),
+                        phrase.chars().count(),
+                    ),
+                });
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.saved_at
+                .cmp(&a.saved_at)
+                .then_with(|| a.recording_id.cmp(&b.recording_id))
+                .then_with(|| a.position_ms.cmp(&b.position_ms))
+        });
+        hits.truncate(query.limit.unwrap_or(DEFAULT_LIMIT));
+        Ok(hits)
+    }
+}
+
+fn matches_filters(
+    document: &IndexedRecording,
+    query: &RecordingSearchQuery,
+    host: Option<&str>,
+) -> bool {
+    if let Some(protocol) = &query.protocol {
+        if *protocol != document.protocol {
+            return false;
+        }
+    }
+    if query.since.is_some_and(|since| document.saved_at < since)
+        || query.until.is_some_and(|until| document.saved_at > until)
+    {
+        return false;
+    }
+    if let Some(host) = host {
+        let on_host = [&document.host, &document.connection_name]
+            .into_iter()
+            .flatten()
+            .any(|candidate| candidate.to_lowercase().contains(host));
+        if !on_host {
+            return false;
+        }
+    }
+    true
+}
+
+/// A window of the segment centred on the match.
+fn snippet(text: &str, match_char: usize, match_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= SNIPPET_CHARS {
+        return text.to_string();
+    }
+    let context = SNIPPET_CHARS.saturating_sub(match_chars) / 2;
+    let start = match_char
+        .saturating_sub(context)
+        .min(total - SNIPPET_CHARS);
+    let mut out: String = text.chars().skip(start).take(SNIPPET_CHARS).collect();
+    if start > 0 {
+        out.insert(0, '…');
+    }
+    if start + SNIPPET_CHARS < total {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(
+        id: &str,
+        protocol: RecordingProtocol,
+        format: ExportFormat,
+        host: &str,
+        data: &str,
+    ) -> SavedRecordingEnvelope {
+        SavedRecordingEnvelope {
+            id: id.to_string(),
+            name: format!("rec-{}", id),
+            description: None,
+            protocol,
+            saved_at: chrono::Utc::now(),
+            duration_ms: 0,
+            size_bytes: data.len() as u64,
+            compression: CompressionAlgorithm::None,
+            format,
+            tags: vec!["nightly".to_string()],
+            connection_id: None,
+            connection_name: None,
+            host: Some(host.to_string()),
+            data: data.to_string(),
+            media_blob_basename: None,
+        }
+    }
+
+    fn query(text: &str) -> RecordingSearchQuery {
+        RecordingSearchQuery {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn index_of(envelopes: &[SavedRecordingEnvelope]) -> SearchIndex {
+        let mut index = SearchIndex::new();
+        for envelope in envelopes {
+            index.insert(IndexedRecording::from_envelope(envelope, &envelope.data));
+        }
+        index
+    }
+
+    #[test]
+    fn typed_commands_apply_line_editing_and_keep_timestamps() {
+        let cast = [
+            r#"{"version":2,"width":80,"height":24}"#,
+            r#"[0.5,"o","\u001b[1;32mroot@prod-db\u001b[0m:~# "]"#,
+            r#"[1.0,"i","ls"]"#,
+            r#"[1.2,"i","\u0015rm -rg\u007ff /var/lib/old"]"#,
+            r#"[1.4,"i","\r"]"#,
+            r#"[2.0,"o","rm -f /tmp/rf\r\n"]"#,
+        ]
+        .join("\n");
+        let index = index_of(&[envelope(
+            "a",
+            RecordingProtocol::Ssh,
+            ExportFormat::Asciicast,
+            "prod-db",
+            &cast,
+        )]);
+
+        let hits = index
+            .search(&RecordingSearchQuery {
+                kinds: vec![IndexSegmentKind::Input],
+                ..query("RM -RF")
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].position_ms, 1200);
+        assert_eq!(hits[0].snippet, "rm -rf /var/lib/old");
+
+        // Both words occur in the output line, but not as the phrase.
+        let output = index
+            .search(&RecordingSearchQuery {
+                kinds: vec![IndexSegmentKind::Output],
+                ..query("rm -rf")
+            })
+            .unwrap();
+        assert!(output.is_empty());
+
+        let prompt = index.search(&query("root@prod-db")).unwrap();
+        assert_eq!(prompt[0].kind, IndexSegmentKind::Output);
+        assert_eq!(prompt[0].position_ms, 500);
+        assert!(index.search(&query("-")).is_err());
+    }
+
+    #[test]
+    fn filters_and_persisted_round_trip() {
+        let csv = "timestamp_ms,query,duration_ms,rows_affected,database,error\n\
+                   42,\"DELETE FROM users\nWHERE id = 7\",3,1,app,\n";
+        let har = r#"{"log":{"entries":[{"request":{"method":"POST","url":"https://api.example/v1/users"}}]}}"#;
+        let mut old = envelope(
+            "old",
+            RecordingProtocol::DatabaseQuery,
+            ExportFormat::Csv,
+            "prod-db",
+            csv,
+        );
+        old.saved_at = chrono::Utc::now() - chrono::Duration::days(120);
+        let index = index_of(&[
+            old,
+            envelope(
+                "web",
+                RecordingProtocol::Http,
+                ExportFormat::Har,
+                "api.example",
+                har,
+            ),
+        ]);
+
+        let users = index.search(&query("users")).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].recording_id, "web");
+        assert_eq!(users[0].kind, IndexSegmentKind::Url);
+        assert_eq!(users[1].kind, IndexSegmentKind::Query);
+        assert_eq!(users[1].position_ms, 42);
+
+        let recent = RecordingSearchQuery {
+            since: Some(chrono::Utc::now() - chrono::Duration::days(90)),
+            ..query("users")
+        };
+        assert_eq!(index.search(&recent).unwrap().len(), 1);
+        let on_db = RecordingSearchQuery {
+            host: Some("PROD".into()),
+            ..query("delete from users where")
+        };
+        assert_eq!(index.search(&on_db).unwrap()[0].recording_id, "old");
+        let wrong_protocol = RecordingSearchQuery {
+            protocol: Some(RecordingProtocol::Ssh),
+            ..query("users")
+        };
+        assert!(index.search(&wrong_protocol).unwrap().is_empty());
+
+        let json = serde_json::to_string(&index).unwrap();
+        let mut restored: SearchIndex = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_current());
+        assert_eq!(restored.search(&query("nightly")).unwrap().len(), 2);
+        assert!(restored.remove("web"));
+        assert_eq!(restored.search(&query("users")).unwrap().len(), 1);
+        assert!(!restored.postings.contains_key("api"));
+    }
+}
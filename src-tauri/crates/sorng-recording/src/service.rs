@@ -4,6 +4,7 @@
 // compression, and storage modules.  All heavy work (encode, compress,
 // save) is dispatched onto blocking tokio threads.
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,6 +16,7 @@ use crate::compression;
 use crate::encoders;
 use crate::engine::{RecordingEngine, RecordingEngineState};
 use crate::error::{RecordingError, RecordingResult};
+use crate::search_index::{IndexedRecording, SearchIndex};
 use crate::storage;
 use crate::types::*;
 use sorng_encryption::EncryptionState;
@@ -38,6 +40,9 @@ pub struct RecordingService {
     /// `*_with_progress` helpers. Survives independent service
     /// clones because it's behind an `Arc`.
     migration_cancel: Arc<AtomicBool>,
+    /// Full-text index over the library. `None` until first loaded from
+    /// disk (or built by the first search).
+    search_index: Arc<Mutex<Option<SearchIndex>>>,
 }
 
 /// How a single recording / macro / media payload should be written,
@@ -87,6 +92,7 @@ impl RecordingService {
             storage_root: Arc::new(Mutex::new(root)),
             encryption_state: Arc::new(Mutex::new(None)),
             migration_cancel: Arc::new(AtomicBool::new(false)),
+            search_index: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -396,6 +402,8 @@ impl RecordingService {
             eng.library = envelopes;
             eng.macro_library = macros;
         }
+        // Re-save the search index so it moves to its encrypted variant too.
+        self.update_search_index(|_| {}).await;
         Ok((em, es, mm, ms))
     }
 
@@ -1018,6 +1026,8 @@ impl RecordingService {
 
     pub async fn save_to_library(&self, envelope: SavedRecordingEnvelope) -> RecordingResult<()> {
         let root = self.storage_root.lock().await.clone();
+        // Extract the searchable text while the payload is still inline.
+        let document = self.extract_search_document(envelope.clone()).await?;
         // Persist first so we have the post-peel envelope shape (with
         // `data` cleared and `media_blob_basename` populated when the
         // sidecar codec ran). Caching the pre-peel envelope in the
@@ -1025,8 +1035,9 @@ impl RecordingService {
         // disk — every subsequent `get_from_library` would hand the
         // UI inline bytes the next process restart can't reproduce.
         let persisted = self.persist_envelope(root, envelope).await?;
-        let mut eng = self.engine.lock().await;
-        eng.save_to_library(persisted);
+        self.engine.lock().await.save_to_library(persisted);
+        self.update_search_index(|index| index.insert(document))
+            .await;
         Ok(())
     }
 
@@ -1062,7 +1073,11 @@ impl RecordingService {
         // peel and only rewrites the metadata file.
         let envelope = self.engine.lock().await.get_from_library(id);
         if let Some(env) = envelope {
-            let _ = self.persist_envelope(root, env).await?;
+            let env = self.persist_envelope(root, env).await?;
+            self.update_search_index(|index| {
+                index.refresh_metadata(&env);
+            })
+            .await;
         }
         Ok(())
     }
@@ -1075,7 +1090,11 @@ impl RecordingService {
         }
         let envelope = self.engine.lock().await.get_from_library(id);
         if let Some(env) = envelope {
-            let _ = self.persist_envelope(root, env).await?;
+            let env = self.persist_envelope(root, env).await?;
+            self.update_search_index(|index| {
+                index.refresh_metadata(&env);
+            })
+            .await;
         }
         Ok(())
     }
@@ -1113,6 +1132,10 @@ impl RecordingService {
             .await
             .map_err(|e| RecordingError::Internal(e.to_string()))??;
         }
+        self.update_search_index(|index| {
+            index.remove(id);
+        })
+        .await;
         Ok(())
     }
 
@@ -1123,9 +1146,14 @@ impl RecordingService {
             let mut eng = self.engine.lock().await;
             count = eng.clear_library();
         }
-        tokio::task::spawn_blocking(move || storage::clear_envelopes(&root))
-            .await
-            .map_err(|e| RecordingError::Internal(e.to_string()))??;
+        let mut search_index = self.search_index.lock().await;
+        *search_index = None;
+        tokio::task::spawn_blocking(move || {
+            storage::clear_envelopes(&root)?;
+            storage::delete_search_index_all_variants(&root)
+        })
+        .await
+        .map_err(|e| RecordingError::Internal(e.to_string()))??;
         Ok(count)
     }
 
@@ -1133,6 +1161,132 @@ impl RecordingService {
         self.engine.lock().await.library_summary()
     }
 
+    // ──────────────────────────────────────────────────────────────────
+    //  Full-text search  (index over recording content)
+    // ──────────────────────────────────────────────────────────────────
+
+    /// Search the content of every library recording. The index is
+    /// loaded on first use, built from the library when there is none on
+    /// disk, and reconciled with the library before each query.
+    pub async fn search_recordings(
+        &self,
+        query: RecordingSearchQuery,
+    ) -> RecordingResult<Vec<RecordingSearchHit>> {
+        let mut slot = self.search_index.lock().await;
+        if slot.is_none() {
+            *slot = Some(self.load_search_index().await?.unwrap_or_default());
+        }
+        let index = slot.as_mut().expect("search index loaded above");
+        if self.sync_search_index(index).await? {
+            if let Err(e) = self.persist_search_index(index).await {
+                log::warn!("recording search index not saved: {}", e);
+            }
+        }
+        index.search(&query)
+    }
+
+    /// Discard the search index and re-extract every library recording.
+    /// Returns the number of recordings indexed.
+    pub async fn rebuild_search_index(&self) -> RecordingResult<usize> {
+        let mut slot = self.search_index.lock().await;
+        let mut index = SearchIndex::new();
+        self.sync_search_index(&mut index).await?;
+        self.persist_search_index(&index).await?;
+        let count = index.len();
+        *slot = Some(index);
+        Ok(count)
+    }
+
+    async fn load_search_index(&self) -> RecordingResult<Option<SearchIndex>> {
+        let root = self.storage_root.lock().await.clone();
+        let enc = self.enc_handle().await;
+        storage::load_search_index_dispatched(&root, enc.as_deref()).await
+    }
+
+    /// Persist the index under the same policy as the envelopes — it
+    /// carries recording content.
+    async fn persist_search_index(&self, index: &SearchIndex) -> RecordingResult<()> {
+        let root = self.storage_root.lock().await.clone();
+        match self.resolve_persist_mode().await? {
+            PersistMode::Encrypted(enc) => {
+                storage::save_search_index_dispatched(&root, index, &enc).await
+            }
+            PersistMode::Plaintext => storage::save_search_index(&root, index),
+        }
+    }
+
+    /// Apply a library change to the index and persist it. A no-op when
+    /// no index exists yet — the first search builds it from the library.
+    /// Index upkeep never fails the library operation that triggered it.
+    async fn update_search_index(&self, update: impl FnOnce(&mut SearchIndex)) {
+        let mut slot = self.search_index.lock().await;
+        let result: RecordingResult<()> = async {
+            if slot.is_none() {
+                match self.load_search_index().await? {
+                    Some(index) => *slot = Some(index),
+                    None => return Ok(()),
+                }
+            }
+            let index = slot.as_mut().expect("search index loaded above");
+            update(index);
+            self.persist_search_index(index).await
+        }
+        .await;
+        if let Err(e) = result {
+            log::warn!("recording search index update failed: {}", e);
+        }
+    }
+
+    /// Drop recordings the library no longer has (auto-cleanup, external
+    /// deletes) and index the ones it is missing. Returns whether the
+    /// index changed.
+    async fn sync_search_index(&self, index: &mut SearchIndex) -> RecordingResult<bool> {
+        let (library, missing) = {
+            let eng = self.engine.lock().await;
+            let library: HashSet<String> = eng.library.iter().map(|e| e.id.clone()).collect();
+            let missing: Vec<SavedRecordingEnvelope> = eng
+                .library
+                .iter()
+                .filter(|e| !index.contains(&e.id))
+                .cloned()
+                .collect();
+            (library, missing)
+        };
+        let mut changed = index.retain(|id| library.contains(id)) > 0;
+        for envelope in missing {
+            index.insert(self.extract_search_document(envelope).await?);
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    async fn extract_search_document(
+        &self,
+        envelope: SavedRecordingEnvelope,
+    ) -> RecordingResult<IndexedRecording> {
+        let sidecar = if envelope.has_media_sidecar() {
+            match self.read_envelope_media(&envelope).await {
+                Ok(bytes) => String::from_utf8(bytes).unwrap_or_default(),
+                Err(e) => {
+                    log::warn!("search index: cannot read recording {}: {}", envelope.id, e);
+                    String::new()
+                }
+            }
+        } else {
+            String::new()
+        };
+        tokio::task::spawn_blocking(move || {
+            let payload = if envelope.has_media_sidecar() {
+                &sidecar
+            } else {
+                &envelope.data
+            };
+            IndexedRecording::from_envelope(&envelope, payload)
+        })
+        .await
+        .map_err(|e| RecordingError::Internal(e.to_string()))
+    }
+
     // ──────────────────────────────────────────────────────────────────
     //  Aggregate helpers
     // ──────────────────────────────────────────────────────────────────
@@ -2217,3 +2371,92 @@ mod phase_2c_engine_e2e_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod search_index_tests {
+    use super::*;
+    use sorng_encryption::{EncryptionState, MasterDek};
+    use tempfile::tempdir;
+
+    async fn unlocked_service(root: &std::path::Path) -> RecordingService {
+        let svc = RecordingService::new(root.to_string_lossy().as_ref());
+        let state = EncryptionState::new();
+        state
+            .install(MasterDek::from_bytes(&[9u8; 32]).unwrap())
+            .await;
+        svc.set_encryption_state(Arc::new(state)).await;
+        svc
+    }
+
+    fn session(id: &str, typed: &str) -> SavedRecordingEnvelope {
+        let cast = format!(
+            "{{\"version\":2}}\n[0.25,\"o\",\"$ \"]\n[3.5,\"i\",{}]",
+            serde_json::to_string(typed).unwrap()
+        );
+        let data = compression::compress_to_b64(&cast, &CompressionAlgorithm::Gzip).unwrap();
+        SavedRecordingEnvelope {
+            id: id.to_string(),
+            name: format!("session-{}", id),
+            description: None,
+            protocol: RecordingProtocol::Ssh,
+            saved_at: Utc::now(),
+            duration_ms: 4000,
+            size_bytes: data.len() as u64,
+            compression: CompressionAlgorithm::Gzip,
+            format: ExportFormat::Asciicast,
+            tags: vec![],
+            connection_id: None,
+            connection_name: None,
+            host: Some("prod-db".to_string()),
+            data,
+            media_blob_basename: None,
+        }
+    }
+
+    fn query(text: &str) -> RecordingSearchQuery {
+        RecordingSearchQuery {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn index_follows_the_library_and_is_encrypted_at_rest() {
+        let tmp = tempdir().unwrap();
+        let svc = unlocked_service(tmp.path()).await;
+        svc.save_to_library(session("a", "rm -rf /srv/old\r"))
+            .await
+            .unwrap();
+
+        // First search builds the index from the library.
+        let hits = svc.search_recordings(query("rm -rf")).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].position_ms, 3500);
+        let root = tmp.path().join("recording");
+        assert!(root.join("search_index.json.enc").exists());
+        assert!(!root.join("search_index.json").exists());
+
+        // Later saves and renames update the persisted index in place.
+        svc.save_to_library(session("b", "DROP TABLE audit;\r"))
+            .await
+            .unwrap();
+        svc.rename_in_library("b", "cleanup night".to_string())
+            .await
+            .unwrap();
+        let reloaded = unlocked_service(tmp.path()).await;
+        reloaded.init().await.unwrap();
+        let drops = reloaded
+            .search_recordings(query("drop table"))
+            .await
+            .unwrap();
+        assert_eq!(drops[0].name, "cleanup night");
+        assert_eq!(reloaded.rebuild_search_index().await.unwrap(), 2);
+
+        reloaded.delete_from_library("a").await.unwrap();
+        assert!(reloaded
+            .search_recordings(query("rm -rf"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -8,10 +8,12 @@
 //     macros/
 //       <id>.json          – MacroRecording (JSON)
 //     config.json          – RecordingGlobalConfig
+//     search_index.json    – full-text SearchIndex (`.json.enc` when encrypted)
 
 use std::path::{Path, PathBuf};
 
 use crate::error::{RecordingError, RecordingResult};
+use crate::search_index::SearchIndex;
 use crate::types::*;
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -655,6 +657,120 @@ pub fn delete_envelope_all_variants(root: &Path, id: &str) -> RecordingResult<()
     })
 }
 
+// ── Search index ───────────────────────────────────────────────────────
+//
+// `<root>/search_index.json[.enc]`. The index is derived data: anything
+// that can't be read back (older format, stale key after a rotation) is
+// reported as absent so the service rebuilds it from the library.
+
+fn search_index_enc_path(root: &Path) -> PathBuf {
+    root.join(format!("search_index{}", ENC_SUFFIX))
+}
+fn search_index_plain_path(root: &Path) -> PathBuf {
+    root.join("search_index.json")
+}
+
+fn current_search_index(
+    parsed: Result<SearchIndex, serde_json::Error>,
+    path: &Path,
+) -> Option<SearchIndex> {
+    match parsed {
+        Ok(index) if index.is_current() => Some(index),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("parse {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Persist the search index as plaintext. Only used under the explicit
+/// encrypt-at-rest opt-out; drops any encrypted copy so the loader can't
+/// prefer a stale one.
+pub fn save_search_index(root: &Path, index: &SearchIndex) -> RecordingResult<()> {
+    let json = serde_json::to_string(index)?;
+    durable_write(&search_index_plain_path(root), json.as_bytes())?;
+    remove_optional_path_with(
+        &search_index_enc_path(root),
+        &mut |candidate: &Path| std::fs::remove_file(candidate),
+        "stale encrypted search index could not be removed",
+    )
+}
+
+/// Persist the search index through the recording-meta codec and remove
+/// any plaintext copy.
+pub async fn save_search_index_dispatched(
+    root: &Path,
+    index: &SearchIndex,
+    enc: &EncryptionState,
+) -> RecordingResult<()> {
+    let value = serde_json::to_value(index)?;
+    let blob = meta_codec::write(
+        enc,
+        &value,
+        MasterKeyStorage::Vault,
+        Argon2Params::OWASP,
+        [0u8; SALT_LEN],
+    )
+    .await
+    .map_err(|e| RecordingError::StorageError(format!("encrypt search index: {}", e)))?;
+    durable_write(&search_index_enc_path(root), &blob)?;
+    remove_plaintext_after_encryption(&search_index_plain_path(root))
+}
+
+/// Load the search index, preferring the encrypted variant. `Ok(None)`
+/// when there is no usable index on disk. Errors only when an encrypted
+/// index exists but the state is locked.
+pub async fn load_search_index_dispatched(
+    root: &Path,
+    enc: Option<&EncryptionState>,
+) -> RecordingResult<Option<SearchIndex>> {
+    let enc_path = search_index_enc_path(root);
+    if enc_path.exists() {
+        let enc = match enc {
+            Some(enc) if enc.is_unlocked().await => enc,
+            _ => {
+                return Err(RecordingError::StorageError(
+                    "recording search index is encrypted; unlock first".into(),
+                ))
+            }
+        };
+        let bytes = std::fs::read(&enc_path).map_err(|e| {
+            RecordingError::StorageError(format!("read {}: {}", enc_path.display(), e))
+        })?;
+        return Ok(match meta_codec::read(enc, &bytes).await {
+            Ok(Some(value)) => current_search_index(serde_json::from_value(value), &enc_path),
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("decrypt {} failed: {}", enc_path.display(), e);
+                None
+            }
+        });
+    }
+    let plain_path = search_index_plain_path(root);
+    match std::fs::read_to_string(&plain_path) {
+        Ok(json) => Ok(current_search_index(
+            serde_json::from_str(&json),
+            &plain_path,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(RecordingError::StorageError(format!(
+            "read {}: {}",
+            plain_path.display(),
+            e
+        ))),
+    }
+}
+
+/// Delete both variants of the search index. Safe no-op for either.
+pub fn delete_search_index_all_variants(root: &Path) -> RecordingResult<()> {
+    delete_encrypted_variants_with(
+        &search_index_plain_path(root),
+        &search_index_enc_path(root),
+        |candidate: &Path| std::fs::remove_file(candidate),
+    )
+}
+
 // ── Macros ─────────────────────────────────────────────────────────────
 
 pub async fn save_macro_dispatched(
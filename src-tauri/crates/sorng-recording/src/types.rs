@@ -642,3 +642,65 @@ pub struct JobInfo {
     pub progress_pct: f64,
     pub message: Option<String>,
 }
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Full-text search over the recording library
+// ═══════════════════════════════════════════════════════════════════════
+
+/// What part of a recording an indexed text segment came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSegmentKind {
+    /// Terminal / serial output, one line per segment.
+    Output,
+    /// A line typed by the user (terminal input, serial TX).
+    Input,
+    /// `METHOD url` of a recorded HTTP request.
+    Url,
+    /// A recorded database query.
+    Query,
+    /// Library metadata: name, description, host, connection, tags.
+    Metadata,
+}
+
+/// Query against the recording full-text index.
+///
+/// Every word of `text` must appear as a whole word in the same segment,
+/// and the (whitespace-normalised) phrase must appear verbatim, so
+/// `rm -rf /var` matches that command but not `rm -f /var/rf`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingSearchQuery {
+    pub text: String,
+    #[serde(default)]
+    pub protocol: Option<RecordingProtocol>,
+    /// Case-insensitive substring of the recording's host or
+    /// connection name.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Restrict to these segment kinds; empty means all.
+    #[serde(default)]
+    pub kinds: Vec<IndexSegmentKind>,
+    #[serde(default)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Maximum number of hits (default 200).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// One matching segment. `position_ms` is the offset into the recording
+/// the player should seek to (0 for formats without timing).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingSearchHit {
+    pub recording_id: String,
+    pub name: String,
+    pub protocol: RecordingProtocol,
+    pub host: Option<String>,
+    pub connection_id: Option<String>,
+    pub connection_name: Option<String>,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub position_ms: u64,
+    pub kind: IndexSegmentKind,
+    pub snippet: String,
+}
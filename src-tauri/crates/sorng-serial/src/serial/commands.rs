@@ -263,6 +263,25 @@ pub async fn serial_stop_logging(
     service.stop_logging(&session_id).await
 }
 
+// ── File transfers ────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn serial_start_transfer(
+    service: tauri::State<'_, SerialServiceState>,
+    session_id: String,
+    config: TransferConfig,
+) -> Result<String, String> {
+    service.start_transfer(&session_id, config).await
+}
+
+#[tauri::command]
+pub async fn serial_cancel_transfer(
+    service: tauri::State<'_, SerialServiceState>,
+    transfer_id: String,
+) -> Result<(), String> {
+    service.cancel_transfer(&transfer_id).await
+}
+
 // ── Utilities ─────────────────────────────────────────────────────
 
 #[tauri::command]
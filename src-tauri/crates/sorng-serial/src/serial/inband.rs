@@ -0,0 +1,1041 @@
+//! In-band file transfers inside interactive shells.
+//!
+//! SSH and telnet terminals run their output through an [`InbandDetector`].
+//! When the remote shell starts `sz` / `rz` (ZMODEM) or `tsz` / `trz`
+//! (trzsz), the terminal stops rendering, pushes the incoming bytes into
+//! an [`InbandInput`] and writes whatever the transfer produces back to
+//! the shell.  The transfer runs the ZMODEM engine from `transfer` or the
+//! client from `trzsz` over a pipe that implements `SerialTransport`,
+//! after asking the frontend for files with a
+//! `<prefix>-inband-transfer-request` event.
+
+use crate::serial::protocols::ProgressCallback;
+use crate::serial::transfer::{
+    FileSink, OutgoingFile, TransferEngine, TransferOptions, TransferSummary, ABORT_SEQUENCE,
+};
+use crate::serial::transport::SerialTransport;
+use crate::serial::trzsz::{TrzszClient, TrzszMode, TRZSZ_MAGIC};
+use crate::serial::types::*;
+use sorng_core::events::DynEventEmitter;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Notify};
+
+/// `**` ZPAD ZPAD, ZDLE, `B` (hex header), then the first type digit.
+const ZMODEM_HEX_HEADER: &[u8] = b"**\x18B0";
+const MAX_TRIGGER_BYTES: usize = 18;
+const PIPE_READ_WAIT: Duration = Duration::from_millis(20);
+const OUTPUT_QUEUE_DEPTH: usize = 64;
+const MAX_PENDING_INPUT_BYTES: usize = 8 * 1024 * 1024;
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const INBAND_MAX_RETRIES: u32 = 10;
+const INBAND_TIMEOUT: Duration = Duration::from_secs(20);
+const OVER_AND_OUT_WAIT: Duration = Duration::from_millis(500);
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Detection
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// What the remote shell asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InbandTrigger {
+    pub protocol: TransferProtocol,
+    pub direction: InbandDirection,
+}
+
+/// A handshake found in terminal output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InbandMatch {
+    pub trigger: InbandTrigger,
+    /// Offset in the scanned chunk where the handshake starts.  Bytes
+    /// before it are ordinary terminal output.
+    pub offset: usize,
+    /// Start of the handshake that arrived with the previous chunk (and
+    /// has already been rendered).
+    pub carried: Vec<u8>,
+}
+
+impl InbandMatch {
+    /// Handshake bytes to feed to the transfer: the carried prefix plus
+    /// the rest of `chunk`.
+    pub fn handshake(&self, chunk: &[u8]) -> Vec<u8> {
+        let mut bytes = self.carried.clone();
+        bytes.extend_from_slice(&chunk[self.offset.min(chunk.len())..]);
+        bytes
+    }
+}
+
+/// Spots ZMODEM ZRQINIT / ZRINIT hex headers (`**\x18B00` from `sz`,
+/// `**\x18B01` from `rz`) and the trzsz magic in terminal output, even
+/// when they straddle two reads.
+#[derive(Debug, Default)]
+pub struct InbandDetector {
+    tail: Vec<u8>,
+}
+
+impl InbandDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan the next chunk of output.
+    pub fn scan(&mut self, chunk: &[u8]) -> Option<InbandMatch> {
+        let carried_len = self.tail.len();
+        let mut window = std::mem::take(&mut self.tail);
+        window.extend_from_slice(chunk);
+
+        match find_trigger(&window) {
+            Some((at, trigger)) => Some(InbandMatch {
+                trigger,
+                offset: at.saturating_sub(carried_len),
+                carried: window[at.min(carried_len)..carried_len].to_vec(),
+            }),
+            None => {
+                let keep = window.len().min(MAX_TRIGGER_BYTES - 1);
+                self.tail = window.split_off(window.len() - keep);
+                None
+            }
+        }
+    }
+
+    /// Forget any partial handshake (e.g. after a transfer).
+    pub fn reset(&mut self) {
+        self.tail.clear();
+    }
+}
+
+fn find_trigger(window: &[u8]) -> Option<(usize, InbandTrigger)> {
+    (0..window.len()).find_map(|at| {
+        let rest = &window[at..];
+        let (protocol, direction) = if let Some(kind) = rest.strip_prefix(ZMODEM_HEX_HEADER) {
+            match kind.first()? {
+                b'0' => (TransferProtocol::Zmodem, InbandDirection::Download),
+                b'1' => (TransferProtocol::Zmodem, InbandDirection::Upload),
+                _ => return None,
+            }
+        } else {
+            match rest.strip_prefix(TRZSZ_MAGIC)?.first()? {
+                b'S' => (TransferProtocol::Trzsz, InbandDirection::Download),
+                b'R' | b'D' => (TransferProtocol::Trzsz, InbandDirection::Upload),
+                _ => return None,
+            }
+        };
+        Some((
+            at,
+            InbandTrigger {
+                protocol,
+                direction,
+            },
+        ))
+    })
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Terminal pipe
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+struct PipeShared {
+    input: StdMutex<VecDeque<u8>>,
+    ready: Notify,
+    finished: AtomicBool,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Terminal side of a running in-band transfer.
+pub struct InbandInput {
+    transfer_id: String,
+    shared: Arc<PipeShared>,
+}
+
+impl InbandInput {
+    pub fn transfer_id(&self) -> &str {
+        &self.transfer_id
+    }
+
+    /// Hand bytes read from the remote shell to the transfer.
+    pub fn push(&self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        if let Ok(mut input) = self.shared.input.lock() {
+            if input.len() + data.len() > MAX_PENDING_INPUT_BYTES {
+                log::warn!(
+                    "In-band transfer {} is not keeping up; dropping {} bytes",
+                    self.transfer_id,
+                    data.len()
+                );
+                return;
+            }
+            input.extend(data);
+        }
+        self.shared.ready.notify_one();
+    }
+
+    /// Whether the transfer is over and the terminal may render again.
+    pub fn is_finished(&self) -> bool {
+        self.shared.finished.load(Ordering::SeqCst)
+    }
+
+    /// Abort the transfer (e.g. the shell is closing).
+    pub fn cancel(&self) {
+        self.shared.cancel.store(true, Ordering::SeqCst);
+        self.shared.ready.notify_one();
+    }
+
+    /// Output the transfer did not consume; the terminal renders it.
+    pub fn take_leftover(&self) -> Vec<u8> {
+        self.shared
+            .input
+            .lock()
+            .map(|mut input| input.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Transfer side of the pipe.
+struct PipeTransport {
+    shared: Arc<PipeShared>,
+    output: mpsc::Sender<Vec<u8>>,
+}
+
+impl PipeTransport {
+    fn pending(&self) -> usize {
+        self.shared
+            .input
+            .lock()
+            .map(|input| input.len())
+            .unwrap_or(0)
+    }
+
+    /// Abort the remote ZMODEM side without starting the engine, dropping
+    /// the handshake it may have resent meanwhile.
+    async fn abort(&self) {
+        if let Ok(mut input) = self.shared.input.lock() {
+            input.clear();
+        }
+        let _ = self.write(&ABORT_SEQUENCE).await;
+    }
+
+    /// Drop the "OO" (over and out) that `sz` sends after ZFIN so it
+    /// does not show up in the terminal.
+    async fn discard_over_and_out(&self) {
+        let deadline = Instant::now() + OVER_AND_OUT_WAIT;
+        while self.pending() < 2 && Instant::now() < deadline {
+            let _ = tokio::time::timeout(PIPE_READ_WAIT, self.shared.ready.notified()).await;
+        }
+        if let Ok(mut input) = self.shared.input.lock() {
+            if input.iter().take(2).eq(b"OO".iter()) {
+                input.drain(..2);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SerialTransport for PipeTransport {
+    async fn open(&self, _config: &SerialConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn read(&self, buf: &mut [u8]) -> Result<usize, String> {
+        if self.pending() == 0 {
+            let _ = tokio::time::timeout(PIPE_READ_WAIT, self.shared.ready.notified()).await;
+        }
+        let mut input = self
+            .shared
+            .input
+            .lock()
+            .map_err(|_| "In-band transfer input is unavailable".to_string())?;
+        let n = input.len().min(buf.len());
+        for (slot, byte) in buf.iter_mut().zip(input.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize, String> {
+        self.output
+            .send(buf.to_vec())
+            .await
+            .map_err(|_| "Terminal session closed".to_string())?;
+        Ok(buf.len())
+    }
+
+    async fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn drain(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn send_break(&self, _duration_ms: u32) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn set_dtr(&self, _state: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn set_rts(&self, _state: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn read_control_lines(&self) -> Result<ControlLines, String> {
+        Ok(ControlLines::default())
+    }
+
+    async fn bytes_available(&self) -> Result<usize, String> {
+        Ok(self.pending())
+    }
+
+    async fn reconfigure(&self, _config: &SerialConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        !self.shared.finished.load(Ordering::SeqCst)
+    }
+
+    fn port_name(&self) -> &str {
+        "in-band"
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Transfer registry
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+struct InbandEntry {
+    session_id: String,
+    cancel: Arc<AtomicBool>,
+    selection: Option<oneshot::Sender<InbandSelection>>,
+}
+
+/// Everything a running transfer needs besides its pipe.
+struct InbandJob {
+    transfer_id: String,
+    session_id: String,
+    trigger: InbandTrigger,
+    emitter: DynEventEmitter,
+    event_prefix: &'static str,
+    cancel: Arc<AtomicBool>,
+    selection: oneshot::Receiver<InbandSelection>,
+}
+
+impl InbandJob {
+    fn emit<T: serde::Serialize>(&self, event: &str, payload: &T) {
+        let _ = self.emitter.emit_event(
+            &format!("{}-inband-transfer-{}", self.event_prefix, event),
+            serde_json::to_value(payload).unwrap_or_default(),
+        );
+    }
+
+    /// Ask the frontend for files; `None` means declined.
+    async fn pick(&mut self) -> Option<InbandSelection> {
+        self.emit(
+            "request",
+            &InbandTransferRequestEvent {
+                session_id: self.session_id.clone(),
+                transfer_id: self.transfer_id.clone(),
+                protocol: self.trigger.protocol,
+                direction: self.trigger.direction,
+            },
+        );
+        match tokio::time::timeout(SELECTION_TIMEOUT, &mut self.selection).await {
+            Ok(Ok(selection)) if !self.cancel.load(Ordering::SeqCst) => Some(selection),
+            _ => None,
+        }
+    }
+
+    fn options(&self) -> TransferOptions {
+        TransferOptions {
+            transfer_id: self.transfer_id.clone(),
+            session_id: self.session_id.clone(),
+            protocol: self.trigger.protocol,
+            max_retries: INBAND_MAX_RETRIES,
+            timeout: INBAND_TIMEOUT,
+            in_band: true,
+        }
+    }
+
+    fn progress(&self) -> ProgressCallback {
+        let emitter = self.emitter.clone();
+        let event = format!("{}-inband-transfer-progress", self.event_prefix);
+        Box::new(move |progress| {
+            let payload = TransferProgressEvent {
+                session_id: progress.session_id.clone(),
+                progress: progress.clone(),
+            };
+            let _ = emitter.emit_event(&event, serde_json::to_value(&payload).unwrap_or_default());
+        })
+    }
+
+    fn declined(&self) -> Result<TransferSummary, String> {
+        self.cancel.store(true, Ordering::SeqCst);
+        Err("Transfer declined".to_string())
+    }
+
+    async fn run_zmodem(&mut self, transport: &PipeTransport) -> Result<TransferSummary, String> {
+        let Some(selection) = self.pick().await else {
+            transport.abort().await;
+            return self.declined();
+        };
+        let mut engine = TransferEngine::new(transport, self.options(), self.cancel.clone())
+            .with_progress(self.progress());
+        match self.trigger.direction {
+            InbandDirection::Upload => match load_files(&selection) {
+                Ok(files) => engine.send(&files).await,
+                Err(error) => {
+                    transport.abort().await;
+                    Err(error)
+                }
+            },
+            InbandDirection::Download => match download_sink(&selection) {
+                Ok(mut sink) => {
+                    let summary = engine.receive(&mut sink).await?;
+                    transport.discard_over_and_out().await;
+                    Ok(summary)
+                }
+                Err(error) => {
+                    transport.abort().await;
+                    Err(error)
+                }
+            },
+        }
+    }
+
+    async fn run_trzsz(&mut self, transport: &PipeTransport) -> Result<TransferSummary, String> {
+        let mut client = TrzszClient::new(transport, self.options(), self.cancel.clone())
+            .with_progress(self.progress());
+        if client.read_header().await? == TrzszMode::Directory {
+            client
+                .refuse("Directory transfers are not supported by this client")
+                .await;
+            return Err("trzsz directory transfers are not supported".to_string());
+        }
+        let Some(selection) = self.pick().await else {
+            client.decline().await?;
+            return self.declined();
+        };
+        match self.trigger.direction {
+            InbandDirection::Upload => match load_files(&selection) {
+                Ok(files) => client.upload(&files).await,
+                Err(error) => {
+                    client.refuse(&error).await;
+                    Err(error)
+                }
+            },
+            InbandDirection::Download => match download_sink(&selection) {
+                Ok(mut sink) => client.download(&mut sink).await,
+                Err(error) => {
+                    client.refuse(&error).await;
+                    Err(error)
+                }
+            },
+        }
+    }
+}
+
+fn load_files(selection: &InbandSelection) -> Result<Vec<OutgoingFile>, String> {
+    if selection.files.is_empty() {
+        return Err("No files selected".to_string());
+    }
+    selection
+        .files
+        .iter()
+        .map(|path| OutgoingFile::from_path(Path::new(path)))
+        .collect()
+}
+
+fn download_sink(selection: &InbandSelection) -> Result<FileSink, String> {
+    let directory = selection
+        .directory
+        .as_deref()
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| "No download directory selected".to_string())?;
+    if !Path::new(directory).is_dir() {
+        return Err(format!("{} is not a directory", directory));
+    }
+    Ok(FileSink::new(directory, true, selection.overwrite, false))
+}
+
+/// Tracks in-band transfers so the frontend can answer file requests
+/// and cancel them.
+#[derive(Default)]
+pub struct InbandTransfers {
+    entries: StdMutex<HashMap<String, InbandEntry>>,
+}
+
+impl InbandTransfers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a transfer for a detected handshake.
+    ///
+    /// `handshake` holds the output from the handshake onwards.  The
+    /// returned channel yields the bytes to write to the remote shell and
+    /// closes once the transfer is over.  Events go out as
+    /// `<event_prefix>-inband-transfer-{request,progress,finished}`.
+    pub fn start(
+        self: &Arc<Self>,
+        runtime: &tokio::runtime::Handle,
+        session_id: &str,
+        trigger: InbandTrigger,
+        handshake: Vec<u8>,
+        emitter: DynEventEmitter,
+        event_prefix: &'static str,
+    ) -> (InbandInput, mpsc::Receiver<Vec<u8>>) {
+        let transfer_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (selection_tx, selection_rx) = oneshot::channel();
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                transfer_id.clone(),
+                InbandEntry {
+                    session_id: session_id.to_string(),
+                    cancel: cancel.clone(),
+                    selection: Some(selection_tx),
+                },
+            );
+        }
+
+        let shared = Arc::new(PipeShared {
+            input: StdMutex::new(handshake.into()),
+            ready: Notify::new(),
+            finished: AtomicBool::new(false),
+            cancel: cancel.clone(),
+        });
+        let (output_tx, output_rx) = mpsc::channel(OUTPUT_QUEUE_DEPTH);
+        let transport = PipeTransport {
+            shared: shared.clone(),
+            output: output_tx,
+        };
+        let mut job = InbandJob {
+            transfer_id: transfer_id.clone(),
+            session_id: session_id.to_string(),
+            trigger,
+            emitter,
+            event_prefix,
+            cancel,
+            selection: selection_rx,
+        };
+        log::info!(
+            "[{}] {} {:?} started in session {}",
+            transfer_id,
+            trigger.protocol.label(),
+            trigger.direction,
+            session_id
+        );
+
+        let transfers = self.clone();
+        runtime.spawn(async move {
+            let result = match trigger.protocol {
+                TransferProtocol::Trzsz => job.run_trzsz(&transport).await,
+                _ => job.run_zmodem(&transport).await,
+            };
+            let (state, summary, message) = match result {
+                Ok(summary) => {
+                    let message = summary.message.clone();
+                    (TransferState::Completed, summary, message)
+                }
+                Err(error) => {
+                    log::warn!("[{}] in-band transfer ended: {}", job.transfer_id, error);
+                    let state = if job.cancel.load(Ordering::SeqCst) {
+                        TransferState::Cancelled
+                    } else {
+                        TransferState::Failed
+                    };
+                    (state, TransferSummary::default(), Some(error))
+                }
+            };
+            if let Ok(mut entries) = transfers.entries.lock() {
+                entries.remove(&job.transfer_id);
+            }
+            job.emit(
+                "finished",
+                &InbandTransferFinishedEvent {
+                    session_id: job.session_id.clone(),
+                    transfer_id: job.transfer_id.clone(),
+                    protocol: trigger.protocol,
+                    direction: trigger.direction,
+                    state,
+                    files: summary.files,
+                    bytes_transferred: summary.bytes_transferred,
+                    message,
+                },
+            );
+            // Flag completion before closing the output channel so the
+            // terminal sees `is_finished` once the channel drains.
+            transport.shared.finished.store(true, Ordering::SeqCst);
+            drop(transport);
+        });
+
+        (
+            InbandInput {
+                transfer_id,
+                shared,
+            },
+            output_rx,
+        )
+    }
+
+    /// Deliver the user's file choice for a pending request.
+    pub fn respond(&self, transfer_id: &str, selection: InbandSelection) -> Result<(), String> {
+        let sender = self
+            .entries
+            .lock()
+            .map_err(|_| "In-band transfer registry is unavailable".to_string())?
+            .get_mut(transfer_id)
+            .ok_or_else(|| "In-band transfer not found".to_string())?
+            .selection
+            .take()
+            .ok_or_else(|| "In-band transfer is not waiting for a file choice".to_string())?;
+        sender
+            .send(selection)
+            .map_err(|_| "In-band transfer is no longer active".to_string())
+    }
+
+    /// Cancel a transfer, whether it is waiting for a choice or running.
+    pub fn cancel(&self, transfer_id: &str) -> Result<(), String> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| "In-band transfer registry is unavailable".to_string())?;
+        let entry = entries
+            .get_mut(transfer_id)
+            .ok_or_else(|| "In-band transfer not found".to_string())?;
+        entry.cancel.store(true, Ordering::SeqCst);
+        entry.selection = None;
+        Ok(())
+    }
+
+    /// Cancel every transfer of a session (e.g. on disconnect).
+    pub fn cancel_session(&self, session_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            for entry in entries.values_mut().filter(|e| e.session_id == session_id) {
+                entry.cancel.store(true, Ordering::SeqCst);
+                entry.selection = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::protocols::build_zrqinit;
+    use crate::serial::trzsz::{decode_trzsz_bytes, encode_trzsz_bytes};
+    use sorng_core::events::AppEventEmitter;
+
+    #[derive(Default)]
+    struct RecordingEmitter {
+        events: StdMutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl AppEventEmitter for RecordingEmitter {
+        fn emit_event(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+            self.events
+                .lock()
+                .unwrap()
+                .push((event.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    impl RecordingEmitter {
+        async fn wait_for(&self, event: &str) -> serde_json::Value {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                if let Some((_, payload)) = self
+                    .events
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|(name, _)| name == event)
+                {
+                    return payload.clone();
+                }
+                assert!(Instant::now() < deadline, "no {} event", event);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    }
+
+    /// The remote shell's end of a running in-band transfer.
+    struct RemoteShell {
+        input: InbandInput,
+        output: tokio::sync::Mutex<mpsc::Receiver<Vec<u8>>>,
+        pending: StdMutex<VecDeque<u8>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SerialTransport for RemoteShell {
+        async fn open(&self, _config: &SerialConfig) -> Result<(), String> {
+            Ok(())
+        }
+        async fn close(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn read(&self, buf: &mut [u8]) -> Result<usize, String> {
+            if self.pending.lock().unwrap().is_empty() {
+                let mut output = self.output.lock().await;
+                if let Ok(Some(chunk)) =
+                    tokio::time::timeout(Duration::from_millis(20), output.recv()).await
+                {
+                    self.pending.lock().unwrap().extend(chunk);
+                }
+            }
+            let mut pending = self.pending.lock().unwrap();
+            let n = pending.len().min(buf.len());
+            for (slot, byte) in buf.iter_mut().zip(pending.drain(..n)) {
+                *slot = byte;
+            }
+            Ok(n)
+        }
+        async fn write(&self, buf: &[u8]) -> Result<usize, String> {
+            self.input.push(buf);
+            Ok(buf.len())
+        }
+        async fn flush(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn drain(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn send_break(&self, _duration_ms: u32) -> Result<(), String> {
+            Ok(())
+        }
+        async fn set_dtr(&self, _state: bool) -> Result<(), String> {
+            Ok(())
+        }
+        async fn set_rts(&self, _state: bool) -> Result<(), String> {
+            Ok(())
+        }
+        async fn read_control_lines(&self) -> Result<ControlLines, String> {
+            Ok(ControlLines::default())
+        }
+        async fn bytes_available(&self) -> Result<usize, String> {
+            Ok(self.pending.lock().unwrap().len())
+        }
+        async fn reconfigure(&self, _config: &SerialConfig) -> Result<(), String> {
+            Ok(())
+        }
+        fn is_open(&self) -> bool {
+            true
+        }
+        fn port_name(&self) -> &str {
+            "remote"
+        }
+    }
+
+    impl RemoteShell {
+        async fn line(&self) -> String {
+            let mut line = Vec::new();
+            let mut byte = [0u8; 1];
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                assert!(Instant::now() < deadline, "remote read timed out");
+                if self.read(&mut byte).await.unwrap() == 0 {
+                    continue;
+                }
+                if byte[0] == b'\n' {
+                    return String::from_utf8(line).unwrap();
+                }
+                line.push(byte[0]);
+            }
+        }
+
+        async fn send(&self, line: &str) {
+            self.write(format!("{}\n", line).as_bytes()).await.unwrap();
+        }
+    }
+
+    fn start(
+        trigger: InbandTrigger,
+        handshake: &[u8],
+    ) -> (Arc<InbandTransfers>, Arc<RecordingEmitter>, RemoteShell) {
+        let transfers = Arc::new(InbandTransfers::new());
+        let emitter = Arc::new(RecordingEmitter::default());
+        let (input, output) = transfers.start(
+            &tokio::runtime::Handle::current(),
+            "sess-1",
+            trigger,
+            handshake.to_vec(),
+            emitter.clone(),
+            "test",
+        );
+        let remote = RemoteShell {
+            input,
+            output: tokio::sync::Mutex::new(output),
+            pending: StdMutex::new(VecDeque::new()),
+        };
+        (transfers, emitter, remote)
+    }
+
+    fn scratch_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sorng-inband-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn value(line: &str, frame: &str) -> String {
+        line.strip_prefix(&format!("#{}:", frame))
+            .unwrap_or_else(|| panic!("expected {} frame, got {}", frame, line))
+            .to_string()
+    }
+
+    #[test]
+    fn test_detector_finds_handshakes() {
+        let mut detector = InbandDetector::new();
+        assert!(detector
+            .scan(b"ls -l\r\ntotal 0\r\n**** bold ****\r\n")
+            .is_none());
+
+        let found = detector
+            .scan(b"rz\r**\x18B00000000000000\r\x8a\x11")
+            .unwrap();
+        assert_eq!(found.offset, 3);
+        assert_eq!(found.trigger.protocol, TransferProtocol::Zmodem);
+        assert_eq!(found.trigger.direction, InbandDirection::Download);
+
+        detector.reset();
+        let found = detector
+            .scan(b"rz waiting to receive.**\x18B0100000023be50\r\x8a\x11")
+            .unwrap();
+        assert_eq!(found.trigger.direction, InbandDirection::Upload);
+
+        detector.reset();
+        let found = detector
+            .scan(b"\x1b7\x07::TRZSZ:TRANSFER:S:1.1.5:1697623456789\r\n")
+            .unwrap();
+        assert_eq!(found.offset, 3);
+        assert_eq!(found.trigger.protocol, TransferProtocol::Trzsz);
+        assert_eq!(found.trigger.direction, InbandDirection::Download);
+    }
+
+    #[test]
+    fn test_detector_handles_split_handshake() {
+        let mut detector = InbandDetector::new();
+        assert!(detector.scan(b"$ trz\r\n\x1b7\x07::TRZSZ:TRA").is_none());
+        let chunk = b"NSFER:R:1.1.5:1\r\n";
+        let found = detector.scan(chunk).unwrap();
+        assert_eq!(found.offset, 0);
+        assert_eq!(found.trigger.direction, InbandDirection::Upload);
+        assert_eq!(
+            found.handshake(chunk),
+            b"::TRZSZ:TRANSFER:R:1.1.5:1\r\n".to_vec()
+        );
+
+        assert!(detector.scan(b"**\x18B").is_none());
+        assert!(detector.scan(b"02whatever").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_zmodem_download_writes_files() {
+        let handshake = build_zrqinit();
+        let (transfers, emitter, remote) = start(
+            InbandTrigger {
+                protocol: TransferProtocol::Zmodem,
+                direction: InbandDirection::Download,
+            },
+            &handshake,
+        );
+        let request = emitter.wait_for("test-inband-transfer-request").await;
+        assert_eq!(request["direction"], "download");
+        let dir = scratch_dir();
+        transfers
+            .respond(
+                remote.input.transfer_id(),
+                InbandSelection {
+                    directory: Some(dir.to_string_lossy().to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31) as u8).collect();
+        let options = TransferOptions {
+            transfer_id: "remote".to_string(),
+            session_id: "remote".to_string(),
+            protocol: TransferProtocol::Zmodem,
+            max_retries: 5,
+            timeout: Duration::from_secs(5),
+            in_band: true,
+        };
+        let files = vec![OutgoingFile {
+            name: "report.bin".to_string(),
+            data: data.clone(),
+        }];
+        TransferEngine::new(&remote, options, Arc::new(AtomicBool::new(false)))
+            .send(&files)
+            .await
+            .unwrap();
+
+        let finished = emitter.wait_for("test-inband-transfer-finished").await;
+        assert_eq!(finished["state"], "completed");
+        assert_eq!(std::fs::read(dir.join("report.bin")).unwrap(), data);
+        assert!(remote.input.is_finished());
+        assert!(remote.input.take_leftover().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_declined_zmodem_upload_aborts_remote() {
+        let (transfers, emitter, remote) = start(
+            InbandTrigger {
+                protocol: TransferProtocol::Zmodem,
+                direction: InbandDirection::Upload,
+            },
+            b"**\x18B0100000023be50\r\x8a\x11",
+        );
+        emitter.wait_for("test-inband-transfer-request").await;
+        transfers.cancel(remote.input.transfer_id()).unwrap();
+
+        let finished = emitter.wait_for("test-inband-transfer-finished").await;
+        assert_eq!(finished["state"], "cancelled");
+        let mut aborted = Vec::new();
+        let mut buf = [0u8; 64];
+        while aborted.len() < ABORT_SEQUENCE.len() {
+            let n = remote.read(&mut buf).await.unwrap();
+            assert!(n > 0, "abort sequence not sent");
+            aborted.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(aborted, ABORT_SEQUENCE);
+        assert!(transfers
+            .respond(remote.input.transfer_id(), InbandSelection::default())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trzsz_upload() {
+        let (transfers, emitter, remote) = start(
+            InbandTrigger {
+                protocol: TransferProtocol::Trzsz,
+                direction: InbandDirection::Upload,
+            },
+            b"::TRZSZ:TRANSFER:R:1.1.5:1697623456789\r\n",
+        );
+        emitter.wait_for("test-inband-transfer-request").await;
+        let dir = scratch_dir();
+        let path = dir.join("notes.txt");
+        let data = b"hello over trzsz\n".repeat(3000);
+        std::fs::write(&path, &data).unwrap();
+        transfers
+            .respond(
+                remote.input.transfer_id(),
+                InbandSelection {
+                    files: vec![path.to_string_lossy().to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Play `trz`.
+        let action = value(&remote.line().await, "ACT");
+        let action: serde_json::Value =
+            serde_json::from_slice(&decode_trzsz_bytes(&action).unwrap()).unwrap();
+        assert_eq!(action["confirm"], true);
+        let config = serde_json::json!({ "binary": false, "max_buf_size": 4096 });
+        remote
+            .send(&format!(
+                "#CFG:{}",
+                encode_trzsz_bytes(config.to_string().as_bytes())
+            ))
+            .await;
+        assert_eq!(value(&remote.line().await, "NUM"), "1");
+        remote.send("#SUCC:1").await;
+        let name = decode_trzsz_bytes(&value(&remote.line().await, "NAME")).unwrap();
+        assert_eq!(name, b"notes.txt");
+        remote
+            .send(&format!("#SUCC:{}", encode_trzsz_bytes(&name)))
+            .await;
+        let size = value(&remote.line().await, "SIZE");
+        assert_eq!(size, data.len().to_string());
+        remote.send(&format!("#SUCC:{}", size)).await;
+        let mut received = Vec::new();
+        while received.len() < data.len() {
+            let chunk = decode_trzsz_bytes(&value(&remote.line().await, "DATA")).unwrap();
+            assert!(chunk.len() <= 4096);
+            remote.send(&format!("#SUCC:{}", chunk.len())).await;
+            received.extend(chunk);
+        }
+        assert_eq!(received, data);
+        let digest = value(&remote.line().await, "MD5");
+        remote.send(&format!("#SUCC:{}", digest)).await;
+        remote
+            .send(&format!(
+                "#EXIT:{}",
+                encode_trzsz_bytes(b"Received notes.txt")
+            ))
+            .await;
+
+        let finished = emitter.wait_for("test-inband-transfer-finished").await;
+        assert_eq!(finished["state"], "completed");
+        assert_eq!(finished["files"][0], "notes.txt");
+        assert_eq!(finished["message"], "Received notes.txt");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_trzsz_download_rejects_bad_md5() {
+        let (transfers, emitter, remote) = start(
+            InbandTrigger {
+                protocol: TransferProtocol::Trzsz,
+                direction: InbandDirection::Download,
+            },
+            b"::TRZSZ:TRANSFER:S:1.1.5:1697623456789\r\n",
+        );
+        emitter.wait_for("test-inband-transfer-request").await;
+        let dir = scratch_dir();
+        transfers
+            .respond(
+                remote.input.transfer_id(),
+                InbandSelection {
+                    directory: Some(dir.to_string_lossy().to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Play `tsz`.
+        value(&remote.line().await, "ACT");
+        remote
+            .send(&format!("#CFG:{}", encode_trzsz_bytes(b"{}")))
+            .await;
+        remote.send("#NUM:1").await;
+        assert_eq!(value(&remote.line().await, "SUCC"), "1");
+        remote
+            .send(&format!("#NAME:{}", encode_trzsz_bytes(b"../etc/data.txt")))
+            .await;
+        let local = decode_trzsz_bytes(&value(&remote.line().await, "SUCC")).unwrap();
+        assert_eq!(local, b"data.txt");
+        remote.send("#SIZE:5").await;
+        assert_eq!(value(&remote.line().await, "SUCC"), "5");
+        remote
+            .send(&format!("#DATA:{}", encode_trzsz_bytes(b"hello")))
+            .await;
+        assert_eq!(value(&remote.line().await, "SUCC"), "5");
+        remote
+            .send(&format!("#MD5:{}", encode_trzsz_bytes(&[0u8; 16])))
+            .await;
+
+        let failure = remote.line().await;
+        assert!(failure.starts_with("#fail:"), "got {}", failure);
+        let finished = emitter.wait_for("test-inband-transfer-finished").await;
+        assert_eq!(finished["state"], "failed");
+        assert!(finished["message"]
+            .as_str()
+            .unwrap()
+            .contains("MD5 check of data.txt failed"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
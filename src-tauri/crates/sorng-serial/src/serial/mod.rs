@@ -1,5 +1,6 @@
 //! Serial crate: sub-modules.
 
+pub mod inband;
 pub mod logging;
 pub mod modem;
 pub mod native_transport;
@@ -8,7 +9,9 @@ pub mod protocols;
 pub mod runtime_check;
 pub mod service;
 pub mod session;
+pub mod transfer;
 pub mod transport;
+pub mod trzsz;
 pub mod types;
 
 // Re-export top-level items for convenience.
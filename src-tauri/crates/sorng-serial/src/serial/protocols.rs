@@ -41,6 +41,28 @@ pub const ZDATA: u8 = 0x0A;
 pub const ZEOF: u8 = 0x0B;
 pub const ZFERR: u8 = 0x0C;
 pub const ZCRC: u8 = 0x0D;
+pub const ZCHALLENGE: u8 = 0x0E;
+pub const ZCOMPL: u8 = 0x0F;
+pub const ZCAN: u8 = 0x10;
+pub const ZFREECNT: u8 = 0x11;
+pub const ZCOMMAND: u8 = 0x12;
+
+// ZMODEM data subpacket terminators (sent after ZDLE)
+pub const ZCRCE: u8 = b'h'; // End of frame, header follows
+pub const ZCRCG: u8 = b'i'; // Frame continues nonstop
+pub const ZCRCQ: u8 = b'j'; // Frame continues, ZACK expected
+pub const ZCRCW: u8 = b'k'; // End of frame, ZACK expected
+pub const ZRUB0: u8 = b'l'; // Escaped 0x7F
+pub const ZRUB1: u8 = b'm'; // Escaped 0xFF
+
+// ZRINIT capability flags (ZF0)
+pub const CANFDX: u8 = 0x01; // Full duplex
+pub const CANOVIO: u8 = 0x02; // Can overlap disk and serial I/O
+pub const CANBRK: u8 = 0x04; // Can send a break signal
+pub const CANFC32: u8 = 0x20; // Can use 32-bit frame check
+
+// ZFILE conversion option (ZF0)
+pub const ZCBIN: u8 = 1; // Binary transfer, no conversion
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 //  CRC calculations
@@ -327,6 +349,104 @@ pub fn build_zrinit(bufsize: u16) -> Vec<u8> {
     encode_zhex_header(ZRINIT, &data)
 }
 
+/// Build a ZMODEM ZRINIT frame advertising receiver capability flags.
+pub fn build_zrinit_with_flags(bufsize: u16, flags: u8) -> Vec<u8> {
+    let data: [u8; 4] = [(bufsize & 0xFF) as u8, (bufsize >> 8) as u8, 0, flags];
+    encode_zhex_header(ZRINIT, &data)
+}
+
+/// Encode a ZMODEM binary header with a 16-bit CRC (`ZBIN`).
+pub fn encode_zbin_header(frame_type: u8, data: &[u8; 4]) -> Vec<u8> {
+    let mut payload = vec![frame_type];
+    payload.extend_from_slice(data);
+    let crc = crc16_xmodem(&payload);
+    payload.extend_from_slice(&crc.to_be_bytes());
+
+    let mut header = vec![ZPAD, ZDLE, ZBIN];
+    header.extend(zmodem_escape_block(&payload));
+    header
+}
+
+/// Encode a ZMODEM binary header with a 32-bit CRC (`ZBIN32`).
+pub fn encode_zbin32_header(frame_type: u8, data: &[u8; 4]) -> Vec<u8> {
+    let mut payload = vec![frame_type];
+    payload.extend_from_slice(data);
+    let crc = crc32(&payload);
+    payload.extend_from_slice(&crc.to_le_bytes());
+
+    let mut header = vec![ZPAD, ZDLE, ZBIN32];
+    header.extend(zmodem_escape_block(&payload));
+    header
+}
+
+/// Encode a ZMODEM data subpacket terminated by `frame_end`.
+///
+/// The CRC covers the data and the terminator byte; CRC-16 is sent
+/// big-endian, CRC-32 little-endian, both ZDLE-escaped.
+pub fn encode_zmodem_subpacket(data: &[u8], frame_end: u8, use_crc32: bool) -> Vec<u8> {
+    let mut checked = Vec::with_capacity(data.len() + 1);
+    checked.extend_from_slice(data);
+    checked.push(frame_end);
+
+    let mut packet = zmodem_escape_block(data);
+    packet.push(ZDLE);
+    packet.push(frame_end);
+    if use_crc32 {
+        packet.extend(zmodem_escape_block(&crc32(&checked).to_le_bytes()));
+    } else {
+        packet.extend(zmodem_escape_block(&crc16_xmodem(&checked).to_be_bytes()));
+    }
+    packet
+}
+
+/// Verify the CRC trailing a decoded ZMODEM data subpacket.
+pub fn verify_zmodem_subpacket(data: &[u8], frame_end: u8, crc: &[u8]) -> bool {
+    let mut checked = Vec::with_capacity(data.len() + 1);
+    checked.extend_from_slice(data);
+    checked.push(frame_end);
+    match crc.len() {
+        4 => crc32(&checked).to_le_bytes() == crc,
+        2 => crc16_xmodem(&checked).to_be_bytes() == crc,
+        _ => false,
+    }
+}
+
+/// Build the ZFILE subpacket payload (`name NUL size NUL`).
+pub fn build_zfile_info(file_name: &str, file_size: u64) -> Vec<u8> {
+    let mut info = Vec::with_capacity(file_name.len() + 24);
+    info.extend_from_slice(file_name.as_bytes());
+    info.push(0x00);
+    info.extend_from_slice(file_size.to_string().as_bytes());
+    info.push(0x00);
+    info
+}
+
+/// Parse a ZFILE subpacket payload into file name and (optional) size.
+pub fn parse_zfile_info(data: &[u8]) -> Result<(String, Option<u64>), String> {
+    let name_end = data
+        .iter()
+        .position(|&b| b == 0x00)
+        .ok_or("No NUL terminator in ZFILE info")?;
+    let file_name = String::from_utf8_lossy(&data[..name_end]).to_string();
+    if file_name.is_empty() {
+        return Err("ZFILE info carries an empty file name".to_string());
+    }
+    let rest = &data[name_end + 1..];
+    let size_end = rest
+        .iter()
+        .position(|&b| b == 0x00 || b == b' ')
+        .unwrap_or(rest.len());
+    let file_size = std::str::from_utf8(&rest[..size_end])
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok());
+    Ok((file_name, file_size))
+}
+
+/// Pack a file position into ZMODEM header bytes (ZP0..ZP3, little-endian).
+pub fn zmodem_position(position: u32) -> [u8; 4] {
+    position.to_le_bytes()
+}
+
 /// Build a ZMODEM ZRQINIT frame.
 pub fn build_zrqinit() -> Vec<u8> {
     encode_zhex_header(ZRQINIT, &[0, 0, 0, 0])
@@ -649,6 +769,45 @@ mod tests {
         assert!(frame.starts_with(b"**\x18B"));
     }
 
+    #[test]
+    fn test_zbin32_header_escapes_payload() {
+        let frame = encode_zbin32_header(ZDATA, &zmodem_position(0x18));
+        assert!(frame.starts_with(&[ZPAD, ZDLE, ZBIN32]));
+        // The 0x18 position byte must be ZDLE-escaped.
+        assert_eq!(&frame[4..6], &[ZDLE, 0x18 ^ 0x40]);
+    }
+
+    #[test]
+    fn test_zmodem_subpacket_crc_roundtrip() {
+        let data = [0x11, 0x18, b'a', 0x93];
+        for use_crc32 in [false, true] {
+            let packet = encode_zmodem_subpacket(&data, ZCRCW, use_crc32);
+            let escaped = zmodem_escape_block(&data);
+            assert_eq!(&packet[..escaped.len()], escaped.as_slice());
+            assert_eq!(&packet[escaped.len()..escaped.len() + 2], &[ZDLE, ZCRCW]);
+            let crc = if use_crc32 {
+                crc32(&[&data[..], &[ZCRCW]].concat())
+                    .to_le_bytes()
+                    .to_vec()
+            } else {
+                crc16_xmodem(&[&data[..], &[ZCRCW]].concat())
+                    .to_be_bytes()
+                    .to_vec()
+            };
+            assert!(verify_zmodem_subpacket(&data, ZCRCW, &crc));
+            assert!(!verify_zmodem_subpacket(&data, ZCRCG, &crc));
+        }
+    }
+
+    #[test]
+    fn test_zfile_info_roundtrip() {
+        let info = build_zfile_info("firmware.bin", 4096);
+        let (name, size) = parse_zfile_info(&info).unwrap();
+        assert_eq!(name, "firmware.bin");
+        assert_eq!(size, Some(4096));
+        assert!(parse_zfile_info(b"\x00123\x00").is_err());
+    }
+
     #[test]
     fn test_calculate_total_blocks() {
         assert_eq!(calculate_total_blocks(128, TransferProtocol::Xmodem), 1);
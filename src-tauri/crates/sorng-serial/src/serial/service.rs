@@ -7,10 +7,14 @@ use crate::serial::logging::{DataDirection, LogEntry, LogWriter};
 use crate::serial::modem::{ModemController, ModemInfo, SignalQuality};
 use crate::serial::native_transport::NativeTransport;
 use crate::serial::port_scanner::{self, ScanOptions, ScanResult};
+use crate::serial::protocols::ProgressCallback;
 use crate::serial::session::{self, SerialSessionHandle, SessionCommand, SessionEvent};
+use crate::serial::transfer::{self, FileSink, OutgoingFile, TransferEngine, TransferOptions};
 use crate::serial::types::*;
 use sorng_core::events::DynEventEmitter;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -54,6 +58,31 @@ async fn await_command_completion(
     }
 }
 
+async fn set_reads_suspended(handle: &SerialSessionHandle, suspended: bool) -> Result<(), String> {
+    let (completion, completed) = tokio::sync::oneshot::channel();
+    handle
+        .send_command(SessionCommand::SuspendReads {
+            suspended,
+            completion,
+        })
+        .await?;
+    completed
+        .await
+        .map_err(|_| "Serial session closed before the transfer could start".to_string())
+}
+
+/// A file transfer currently holding a session's port.
+struct ActiveTransfer {
+    session_id: String,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Work handed to a transfer task.
+enum TransferJob {
+    Send(Vec<OutgoingFile>),
+    Receive(FileSink),
+}
+
 /// Type alias used as Tauri managed state.
 pub type SerialServiceState = Arc<SerialService>;
 
@@ -61,6 +90,7 @@ pub type SerialServiceState = Arc<SerialService>;
 pub struct SerialService {
     sessions: RwLock<HashMap<String, Arc<SerialSessionHandle>>>,
     log_writers: Arc<RwLock<HashMap<String, tokio::sync::Mutex<LogWriter>>>>,
+    transfers: Arc<RwLock<HashMap<String, ActiveTransfer>>>,
     event_emitter: Option<DynEventEmitter>,
 }
 
@@ -70,6 +100,7 @@ impl SerialService {
         Arc::new(Self {
             sessions: RwLock::new(HashMap::new()),
             log_writers: Arc::new(RwLock::new(HashMap::new())),
+            transfers: Arc::new(RwLock::new(HashMap::new())),
             event_emitter: None,
         })
     }
@@ -79,6 +110,7 @@ impl SerialService {
         Arc::new(Self {
             sessions: RwLock::new(HashMap::new()),
             log_writers: Arc::new(RwLock::new(HashMap::new())),
+            transfers: Arc::new(RwLock::new(HashMap::new())),
             event_emitter: Some(emitter),
         })
     }
@@ -202,6 +234,11 @@ impl SerialService {
     /// Disconnect a session.
     pub async fn disconnect(&self, session_id: &str) -> Result<(), String> {
         let handle = self.get_session(session_id).await?;
+        for transfer in self.transfers.read().await.values() {
+            if transfer.session_id == session_id {
+                transfer.cancel.store(true, Ordering::SeqCst);
+            }
+        }
         handle.send_command(SessionCommand::Disconnect).await?;
 
         // Remove from sessions map
@@ -481,6 +518,158 @@ impl SerialService {
         controller.hangup().await
     }
 
+    // ── File transfers ────────────────────────────────────────────
+
+    /// Start an XMODEM / YMODEM / ZMODEM transfer on a session.
+    ///
+    /// The session stops reading the port until the transfer ends.
+    /// Progress is emitted as `serial:transfer-progress`; the last event
+    /// carries the final state.  Batch receives (YMODEM / ZMODEM) write
+    /// into `file_path` as a directory.  Returns the transfer ID.
+    pub async fn start_transfer(
+        &self,
+        session_id: &str,
+        config: TransferConfig,
+    ) -> Result<String, String> {
+        transfer::ensure_supported(config.protocol)?;
+        if config.timeout_ms == 0 || config.timeout_ms > MAX_SERIAL_TIMEOUT_MS {
+            return Err(format!(
+                "Transfer timeout must be between 1 and {} ms",
+                MAX_SERIAL_TIMEOUT_MS
+            ));
+        }
+        if config.max_retries > MAX_SERIAL_TRANSFER_RETRIES {
+            return Err(format!(
+                "Transfer retries cannot exceed {}",
+                MAX_SERIAL_TRANSFER_RETRIES
+            ));
+        }
+        if config.file_path.is_empty() {
+            return Err("Transfer file path is empty".to_string());
+        }
+        let handle = self.get_session(session_id).await?;
+        if !handle.is_connected() {
+            return Err("Serial session is not connected".to_string());
+        }
+
+        let path = PathBuf::from(&config.file_path);
+        let batch = matches!(
+            config.protocol,
+            TransferProtocol::Ymodem | TransferProtocol::YmodemG | TransferProtocol::Zmodem
+        );
+        let job = match config.direction {
+            TransferDirection::Send => TransferJob::Send(vec![OutgoingFile::from_path(&path)?]),
+            TransferDirection::Receive => {
+                if batch && !path.is_dir() {
+                    return Err(format!(
+                        "{} receives into a directory; {} is not one",
+                        config.protocol.label(),
+                        path.display()
+                    ));
+                }
+                if !batch && path.is_dir() {
+                    return Err(format!("{} is a directory", path.display()));
+                }
+                if !batch && path.exists() && !config.overwrite {
+                    return Err(format!("{} already exists", path.display()));
+                }
+                let resume = config.resume && config.protocol == TransferProtocol::Zmodem;
+                TransferJob::Receive(FileSink::new(path, batch, config.overwrite, resume))
+            }
+        };
+
+        let transfer_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let mut transfers = self.transfers.write().await;
+            if transfers.values().any(|t| t.session_id == session_id) {
+                return Err("A file transfer is already running on this session".to_string());
+            }
+            transfers.insert(
+                transfer_id.clone(),
+                ActiveTransfer {
+                    session_id: session_id.to_string(),
+                    cancel: cancel.clone(),
+                },
+            );
+        }
+        if let Err(error) = set_reads_suspended(&handle, true).await {
+            self.transfers.write().await.remove(&transfer_id);
+            return Err(error);
+        }
+
+        let options = TransferOptions::from_config(&transfer_id, session_id, &config);
+        let transfers = self.transfers.clone();
+        let emitter = self.event_emitter.clone();
+        let id = transfer_id.clone();
+        tokio::spawn(async move {
+            let progress_emitter = emitter.clone();
+            let progress: ProgressCallback = Box::new(move |progress| {
+                if let Some(emitter) = &progress_emitter {
+                    let payload = TransferProgressEvent {
+                        session_id: progress.session_id.clone(),
+                        progress: progress.clone(),
+                    };
+                    let _ = emitter.emit_event(
+                        "serial:transfer-progress",
+                        serde_json::to_value(&payload).unwrap_or_default(),
+                    );
+                }
+            });
+            let mut engine = TransferEngine::new(handle.transport.as_ref(), options, cancel)
+                .with_progress(progress);
+            let result = match job {
+                TransferJob::Send(files) => engine.send(&files).await,
+                TransferJob::Receive(mut sink) => engine.receive(&mut sink).await,
+            };
+            drop(engine);
+
+            match result {
+                Ok(summary) => log::info!(
+                    "Serial transfer {} finished: {} file(s), {} bytes",
+                    id,
+                    summary.files.len(),
+                    summary.bytes_transferred
+                ),
+                Err(error) => {
+                    let message = bounded_event_text(
+                        format!("File transfer failed: {}", error),
+                        MAX_SERIAL_ERROR_BYTES,
+                    );
+                    log::warn!("Serial transfer {}: {}", id, message);
+                    if let Some(emitter) = &emitter {
+                        let payload = SerialErrorEvent {
+                            session_id: handle.id.clone(),
+                            message,
+                            recoverable: true,
+                        };
+                        let _ = emitter.emit_event(
+                            "serial:error",
+                            serde_json::to_value(&payload).unwrap_or_default(),
+                        );
+                    }
+                }
+            }
+            if handle.is_connected() {
+                let _ = set_reads_suspended(&handle, false).await;
+            }
+            transfers.write().await.remove(&id);
+        });
+
+        Ok(transfer_id)
+    }
+
+    /// Cancel a running transfer.  The engine aborts the remote side and
+    /// reports a final `cancelled` progress event.
+    pub async fn cancel_transfer(&self, transfer_id: &str) -> Result<(), String> {
+        let transfers = self.transfers.read().await;
+        let transfer = transfers
+            .get(transfer_id)
+            .ok_or_else(|| "Serial transfer not found".to_string())?;
+        transfer.cancel.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
     // ── Logging ───────────────────────────────────────────────────
 
     /// Start logging for a session.
@@ -845,4 +1034,83 @@ mod tests {
         service.send_break(&info.id, 250).await.unwrap();
         service.disconnect(&info.id).await.unwrap();
     }
+
+    fn transfer_config(direction: TransferDirection, file_path: String) -> TransferConfig {
+        TransferConfig {
+            protocol: TransferProtocol::XmodemCrc,
+            direction,
+            file_path,
+            overwrite: false,
+            resume: false,
+            max_retries: 3,
+            timeout_ms: 1000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_transfer_rejects_invalid_requests() {
+        let service = SerialService::new();
+        let config = SerialConfig {
+            port_name: "COM11".to_string(),
+            ..Default::default()
+        };
+        let info = service.connect_simulated(config).await.unwrap();
+
+        let missing = transfer_config(
+            TransferDirection::Send,
+            "/nonexistent/serial-transfer.bin".to_string(),
+        );
+        assert!(service.start_transfer(&info.id, missing).await.is_err());
+
+        let mut kermit = transfer_config(TransferDirection::Receive, "out.bin".to_string());
+        kermit.protocol = TransferProtocol::Kermit;
+        assert!(service.start_transfer(&info.id, kermit).await.is_err());
+
+        // Batch receives need a directory.
+        let mut zmodem = transfer_config(
+            TransferDirection::Receive,
+            "/nonexistent/serial-transfer.bin".to_string(),
+        );
+        zmodem.protocol = TransferProtocol::Zmodem;
+        assert!(service.start_transfer(&info.id, zmodem).await.is_err());
+
+        assert!(service.cancel_transfer("unknown").await.is_err());
+        service.disconnect(&info.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_service_transfer_cancel_resumes_session() {
+        let service = SerialService::new();
+        let config = SerialConfig {
+            port_name: "COM12".to_string(),
+            ..Default::default()
+        };
+        let info = service.connect_simulated(config).await.unwrap();
+        let target = std::env::temp_dir().join(format!("serial-xfer-{}.bin", info.id));
+        let receive = transfer_config(
+            TransferDirection::Receive,
+            target.to_string_lossy().to_string(),
+        );
+
+        let transfer_id = service
+            .start_transfer(&info.id, receive.clone())
+            .await
+            .unwrap();
+        // The port belongs to the transfer until it ends.
+        assert!(service.send_raw(&info.id, b"x".to_vec()).await.is_err());
+        assert!(service.start_transfer(&info.id, receive).await.is_err());
+
+        service.cancel_transfer(&transfer_id).await.unwrap();
+        for _ in 0..50 {
+            if service.transfers.read().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        }
+        assert!(service.transfers.read().await.is_empty());
+        service.send_raw(&info.id, b"x".to_vec()).await.unwrap();
+
+        service.disconnect(&info.id).await.unwrap();
+        let _ = std::fs::remove_file(target);
+    }
 }
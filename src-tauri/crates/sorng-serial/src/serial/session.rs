@@ -78,6 +78,12 @@ pub enum SessionCommand {
     Flush,
     /// Get session statistics.
     GetStats(oneshot::Sender<SessionStats>),
+    /// Stop (or resume) reading the port so a file transfer can own it.
+    /// Completes once no read is in flight.
+    SuspendReads {
+        suspended: bool,
+        completion: oneshot::Sender<()>,
+    },
     /// Disconnect and clean up.
     Disconnect,
 }
@@ -209,6 +215,7 @@ struct SessionRunner {
     errored: Arc<AtomicBool>,
     stats: SessionStats,
     started_at: std::time::Instant,
+    reads_suspended: bool,
 }
 
 impl SessionRunner {
@@ -254,6 +261,7 @@ impl SessionRunner {
             errored,
             stats: SessionStats::default(),
             started_at: std::time::Instant::now(),
+            reads_suspended: false,
         }
     }
 
@@ -273,6 +281,9 @@ impl SessionRunner {
                     if !self.connected.load(Ordering::SeqCst) {
                         break;
                     }
+                    if self.reads_suspended {
+                        continue;
+                    }
                     match self.transport.read(&mut read_buf).await {
                         Ok(0) => {},
                         Ok(n) => {
@@ -503,6 +514,13 @@ impl SessionRunner {
                             self.stats.uptime_seconds = self.started_at.elapsed().as_secs();
                             let _ = reply.send(self.stats.clone());
                         }
+                        SessionCommand::SuspendReads {
+                            suspended,
+                            completion,
+                        } => {
+                            self.reads_suspended = suspended;
+                            let _ = completion.send(());
+                        }
                         SessionCommand::Disconnect => {
                             break;
                         }
@@ -540,6 +558,9 @@ impl SessionRunner {
     }
 
     async fn handle_send_raw(&mut self, data: &[u8]) -> Result<(), String> {
+        if self.reads_suspended {
+            return Err("A file transfer is in progress on this session".to_string());
+        }
         if data.len() > MAX_SERIAL_PAYLOAD_BYTES {
             return Err(format!(
                 "Serial write exceeds {} bytes",
@@ -928,4 +949,61 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_session_suspend_reads_for_transfer() {
+        let transport = SimulatedTransport::new("COM1");
+        let config = SerialConfig {
+            port_name: "COM1".to_string(),
+            ..Default::default()
+        };
+        let t = transport.clone();
+        let handle = create_session("sess-8".to_string(), transport, config)
+            .await
+            .unwrap();
+
+        let (completion, completed) = oneshot::channel();
+        handle
+            .send_command(SessionCommand::SuspendReads {
+                suspended: true,
+                completion,
+            })
+            .await
+            .unwrap();
+        completed.await.unwrap();
+
+        // Incoming bytes stay on the port for the transfer engine.
+        t.inject_rx(b"C").await;
+        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        assert_eq!(t.bytes_available().await.unwrap(), 1);
+
+        // Terminal writes would corrupt the transfer stream.
+        let (completion, completed) = oneshot::channel();
+        handle
+            .send_command(SessionCommand::SendRaw {
+                data: b"typed".to_vec(),
+                deadline: tokio::time::Instant::now() + tokio::time::Duration::from_secs(1),
+                completion,
+            })
+            .await
+            .unwrap();
+        assert!(completed.await.unwrap().is_err());
+
+        let (completion, completed) = oneshot::channel();
+        handle
+            .send_command(SessionCommand::SuspendReads {
+                suspended: false,
+                completion,
+            })
+            .await
+            .unwrap();
+        completed.await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(400)).await;
+        assert_eq!(t.bytes_available().await.unwrap(), 0);
+
+        handle
+            .send_command(SessionCommand::Disconnect)
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,1997 @@
+//! File transfer engines for serial sessions.
+//!
+//! Drives the framing helpers in `protocols` over a `SerialTransport`:
+//! XMODEM (checksum, CRC-16 and 1K), YMODEM / YMODEM-G batch and ZMODEM
+//! with 32-bit CRC data subpackets and ZRPOS crash recovery.  An engine
+//! expects exclusive use of the port, so the session read loop has to be
+//! suspended while a transfer runs (see `SessionCommand::SuspendReads`).
+
+use crate::serial::protocols::*;
+use crate::serial::transport::SerialTransport;
+use crate::serial::types::*;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const READ_CHUNK_BYTES: usize = 4096;
+const IDLE_READ_BACKOFF: Duration = Duration::from_millis(2);
+const MAX_BYTE_GAP: Duration = Duration::from_secs(2);
+const MAX_START_PROBE_INTERVAL: Duration = Duration::from_secs(3);
+const CRC_PROBES_BEFORE_CHECKSUM: u32 = 3;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const ZMODEM_SUBPACKET_BYTES: usize = 1024;
+const MAX_ZMODEM_SUBPACKET_BYTES: usize = 8192;
+const RECEIVER_CAPABILITIES: u8 = CANFDX | CANOVIO | CANFC32;
+
+/// Eight CANs followed by ten backspaces — aborts XMODEM-family and
+/// ZMODEM peers alike.
+pub(crate) const ABORT_SEQUENCE: [u8; 18] = [
+    CAN, CAN, CAN, CAN, CAN, CAN, CAN, CAN, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08,
+    0x08,
+];
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Public types
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Settings for a single transfer run.
+#[derive(Debug, Clone)]
+pub struct TransferOptions {
+    pub transfer_id: String,
+    pub session_id: String,
+    pub protocol: TransferProtocol,
+    /// Retries allowed per block / header before the transfer fails.
+    pub max_retries: u32,
+    /// How long to wait for the peer to answer.
+    pub timeout: Duration,
+    /// The remote `rz` is already running (started from a shell), so the
+    /// ZMODEM sender must not type `rz\r` to launch it.
+    pub in_band: bool,
+}
+
+impl TransferOptions {
+    pub fn from_config(transfer_id: &str, session_id: &str, config: &TransferConfig) -> Self {
+        Self {
+            transfer_id: transfer_id.to_string(),
+            session_id: session_id.to_string(),
+            protocol: config.protocol,
+            max_retries: config.max_retries,
+            timeout: Duration::from_millis(config.timeout_ms.max(1)),
+            in_band: false,
+        }
+    }
+}
+
+/// A file queued for sending.
+#[derive(Debug, Clone)]
+pub struct OutgoingFile {
+    /// Name announced to the receiver (YMODEM / ZMODEM).
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl OutgoingFile {
+    /// Load a file from disk, announcing it under its base name.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?;
+        if !metadata.is_file() {
+            return Err(format!("{} is not a regular file", path.display()));
+        }
+        if metadata.len() > MAX_SERIAL_TRANSFER_FILE_BYTES {
+            return Err(format!(
+                "File exceeds the {} byte serial transfer limit",
+                MAX_SERIAL_TRANSFER_FILE_BYTES
+            ));
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| format!("{} has no file name", path.display()))?;
+        let data =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(Self { name, data })
+    }
+}
+
+/// Result of a finished transfer.
+#[derive(Debug, Clone, Default)]
+pub struct TransferSummary {
+    /// Names of the files that completed.
+    pub files: Vec<String>,
+    /// Payload bytes moved (excluding resumed prefixes).
+    pub bytes_transferred: u64,
+    pub error_count: u32,
+    pub retry_count: u32,
+    /// Closing message from the remote side (trzsz only).
+    pub message: Option<String>,
+}
+
+/// Destination for received files.
+pub trait TransferSink: Send {
+    /// Start a new file.  Returns the offset to resume from, or `None`
+    /// to refuse the file.  Only ZMODEM honours non-zero offsets (via
+    /// ZRPOS) and skips refused files; the other protocols abort.
+    fn begin_file(&mut self, name: &str, size: Option<u64>) -> Result<Option<u64>, String>;
+
+    /// Append data to the current file.
+    fn write(&mut self, data: &[u8]) -> Result<(), String>;
+
+    /// Finish the current file.
+    fn finish_file(&mut self) -> Result<(), String>;
+}
+
+/// Writes received files to disk.
+///
+/// Batch protocols (YMODEM / ZMODEM) treat the target as a directory
+/// and use the sender's base file name; XMODEM writes to the target
+/// path itself.
+pub struct FileSink {
+    target: PathBuf,
+    batch: bool,
+    overwrite: bool,
+    resume: bool,
+    current: Option<std::fs::File>,
+}
+
+impl FileSink {
+    pub fn new(target: impl Into<PathBuf>, batch: bool, overwrite: bool, resume: bool) -> Self {
+        Self {
+            target: target.into(),
+            batch,
+            overwrite,
+            resume,
+            current: None,
+        }
+    }
+
+    fn destination(&self, name: &str) -> Result<PathBuf, String> {
+        if !self.batch {
+            return Ok(self.target.clone());
+        }
+        let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+        if base.is_empty() || base == "." || base == ".." || base.contains('\0') {
+            return Err(format!("Refusing unsafe file name {:?}", name));
+        }
+        Ok(self.target.join(base))
+    }
+}
+
+impl TransferSink for FileSink {
+    fn begin_file(&mut self, name: &str, size: Option<u64>) -> Result<Option<u64>, String> {
+        let path = self.destination(name)?;
+        let existing = std::fs::metadata(&path)
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len());
+        let (file, offset) = match existing {
+            Some(len) if self.resume && size.is_some_and(|size| len < size) => {
+                (std::fs::OpenOptions::new().append(true).open(&path), len)
+            }
+            Some(_) if !self.overwrite => return Ok(None),
+            _ => (std::fs::File::create(&path), 0),
+        };
+        let file = file.map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        self.current = Some(file);
+        Ok(Some(offset))
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        self.current
+            .as_mut()
+            .ok_or("No file is open for writing")?
+            .write_all(data)
+            .map_err(|e| format!("Failed to write received data: {}", e))
+    }
+
+    fn finish_file(&mut self) -> Result<(), String> {
+        if let Some(mut file) = self.current.take() {
+            file.flush()
+                .map_err(|e| format!("Failed to flush received file: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Errors
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[derive(Debug)]
+enum EngineError {
+    /// Cancelled locally through the cancel flag.
+    Cancelled,
+    /// The peer sent a cancel sequence.
+    RemoteCancelled,
+    /// Nothing arrived before the deadline.
+    Timeout,
+    /// Corrupt framing; the protocol may retry.
+    Garbled(String),
+    /// Unrecoverable failure.
+    Failed(String),
+}
+
+impl EngineError {
+    fn message(&self) -> String {
+        match self {
+            Self::Cancelled => "Transfer cancelled".to_string(),
+            Self::RemoteCancelled => "Transfer cancelled by the remote side".to_string(),
+            Self::Timeout => "Transfer timed out waiting for the remote side".to_string(),
+            Self::Garbled(message) | Self::Failed(message) => message.clone(),
+        }
+    }
+}
+
+type EngineResult<T> = Result<T, EngineError>;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Byte link
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Buffered, cancellable byte I/O over a transport.
+///
+/// Transport reads are never raced against a timer: `NativeTransport`
+/// reads on a blocking thread, and dropping that future would lose data.
+struct Link<'a> {
+    transport: &'a dyn SerialTransport,
+    cancel: Arc<AtomicBool>,
+    pending: VecDeque<u8>,
+    scratch: Vec<u8>,
+}
+
+impl<'a> Link<'a> {
+    fn new(transport: &'a dyn SerialTransport, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            transport,
+            cancel,
+            pending: VecDeque::new(),
+            scratch: vec![0u8; READ_CHUNK_BYTES],
+        }
+    }
+
+    fn check_cancel(&self) -> EngineResult<()> {
+        if self.cancel.load(Ordering::SeqCst) {
+            Err(EngineError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Next byte, or `None` once `timeout` elapses.
+    async fn byte(&mut self, timeout: Duration) -> EngineResult<Option<u8>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(byte) = self.pending.pop_front() {
+                return Ok(Some(byte));
+            }
+            self.check_cancel()?;
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            let n = self
+                .transport
+                .read(&mut self.scratch)
+                .await
+                .map_err(EngineError::Failed)?;
+            if n == 0 {
+                tokio::time::sleep(IDLE_READ_BACKOFF).await;
+            } else {
+                let n = n.min(self.scratch.len());
+                self.pending.extend(&self.scratch[..n]);
+            }
+        }
+    }
+
+    async fn byte_or_timeout(&mut self, timeout: Duration) -> EngineResult<u8> {
+        self.byte(timeout).await?.ok_or(EngineError::Timeout)
+    }
+
+    fn unread(&mut self, byte: u8) {
+        self.pending.push_front(byte);
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.pending.front().copied()
+    }
+
+    async fn input_pending(&self) -> bool {
+        !self.pending.is_empty() || self.transport.bytes_available().await.is_ok_and(|n| n > 0)
+    }
+
+    async fn write_all(&mut self, mut data: &[u8]) -> EngineResult<()> {
+        while !data.is_empty() {
+            self.check_cancel()?;
+            let n = self
+                .transport
+                .write(data)
+                .await
+                .map_err(EngineError::Failed)?;
+            if n == 0 {
+                return Err(EngineError::Failed(
+                    "Serial transport accepted no bytes".to_string(),
+                ));
+            }
+            data = &data[n.min(data.len())..];
+        }
+        Ok(())
+    }
+
+    /// Discard input until the line has been quiet for `quiet`.
+    async fn purge(&mut self, quiet: Duration) -> EngineResult<()> {
+        let deadline = Instant::now() + quiet * 10;
+        self.pending.clear();
+        while Instant::now() < deadline && self.byte(quiet).await?.is_some() {
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    /// Best-effort abort of the remote side.
+    async fn abort(&mut self) {
+        let _ = self.transport.write(&ABORT_SEQUENCE).await;
+        let _ = self.transport.flush().await;
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Progress tracking
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+pub(crate) struct ProgressTracker {
+    transfer_id: String,
+    session_id: String,
+    protocol: TransferProtocol,
+    pub(crate) direction: TransferDirection,
+    pub(crate) callback: Option<ProgressCallback>,
+    pub(crate) started: Instant,
+    last_report: Option<Instant>,
+    file_name: String,
+    file_size: u64,
+    file_start: u64,
+    file_bytes: u64,
+    files: Vec<String>,
+    total_bytes: u64,
+    pub(crate) errors: u32,
+    retries: u32,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(options: &TransferOptions, direction: TransferDirection) -> Self {
+        Self {
+            transfer_id: options.transfer_id.clone(),
+            session_id: options.session_id.clone(),
+            protocol: options.protocol,
+            direction,
+            callback: None,
+            started: Instant::now(),
+            last_report: None,
+            file_name: String::new(),
+            file_size: 0,
+            file_start: 0,
+            file_bytes: 0,
+            files: Vec::new(),
+            total_bytes: 0,
+            errors: 0,
+            retries: 0,
+        }
+    }
+
+    pub(crate) fn begin_file(&mut self, name: &str, size: u64) {
+        self.file_name = name.to_string();
+        self.file_size = size;
+        self.file_start = 0;
+        self.file_bytes = 0;
+        self.report(TransferState::InProgress, true);
+    }
+
+    /// Jump to `position` without counting it as transferred (resume / rewind).
+    fn seek(&mut self, position: u64) {
+        if self.file_bytes == self.file_start {
+            self.file_start = position;
+        }
+        self.file_bytes = position;
+    }
+
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.advance_to(self.file_bytes.saturating_add(bytes));
+    }
+
+    fn advance_to(&mut self, position: u64) {
+        self.file_bytes = position;
+        self.report(TransferState::InProgress, false);
+    }
+
+    pub(crate) fn complete_file(&mut self) {
+        self.files.push(self.file_name.clone());
+        self.total_bytes = self
+            .total_bytes
+            .saturating_add(self.file_bytes.saturating_sub(self.file_start));
+        self.report(TransferState::InProgress, true);
+    }
+
+    pub(crate) fn report(&mut self, state: TransferState, force: bool) {
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        let now = Instant::now();
+        if !force
+            && self
+                .last_report
+                .is_some_and(|last| now.duration_since(last) < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.last_report = Some(now);
+        let block_size = self.protocol.block_size().max(1) as u64;
+        let block_number = self.file_bytes.div_ceil(block_size).min(u32::MAX as u64) as u32;
+        let progress = build_progress(
+            &self.transfer_id,
+            &self.session_id,
+            &self.file_name,
+            self.file_size,
+            self.file_bytes,
+            block_number,
+            calculate_total_blocks(self.file_size, self.protocol),
+            self.protocol,
+            self.direction,
+            state,
+            self.errors,
+            self.retries,
+            self.started,
+        );
+        callback(&progress);
+    }
+
+    pub(crate) fn summary(&self) -> TransferSummary {
+        TransferSummary {
+            files: self.files.clone(),
+            bytes_transferred: self.total_bytes,
+            error_count: self.errors,
+            retry_count: self.retries,
+            message: None,
+        }
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Engine
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Decoded ZMODEM header.
+#[derive(Debug, Clone, Copy)]
+struct ZHeader {
+    frame_type: u8,
+    data: [u8; 4],
+    encoding: u8,
+}
+
+impl ZHeader {
+    fn position(&self) -> u64 {
+        u32::from_le_bytes(self.data) as u64
+    }
+
+    fn uses_crc32(&self) -> bool {
+        self.encoding == ZBIN32
+    }
+}
+
+/// A ZMODEM-decoded byte: data or a subpacket terminator.
+enum ZByte {
+    Data(u8),
+    End(u8),
+}
+
+/// Receiver-side state of the file currently being received over ZMODEM.
+struct ZReceiveFile {
+    name: String,
+    offset: u64,
+}
+
+/// Runs one transfer over a transport.
+pub struct TransferEngine<'a> {
+    link: Link<'a>,
+    options: TransferOptions,
+    progress: ProgressTracker,
+    zmodem_crc32: bool,
+}
+
+impl<'a> TransferEngine<'a> {
+    pub fn new(
+        transport: &'a dyn SerialTransport,
+        options: TransferOptions,
+        cancel: Arc<AtomicBool>,
+    ) -> Self {
+        let progress = ProgressTracker::new(&options, TransferDirection::Send);
+        Self {
+            link: Link::new(transport, cancel),
+            options,
+            progress,
+            zmodem_crc32: false,
+        }
+    }
+
+    /// Report progress through `callback` (throttled, plus every state change).
+    pub fn with_progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress.callback = Some(callback);
+        self
+    }
+
+    /// Send `files`.  XMODEM variants accept exactly one file.
+    pub async fn send(&mut self, files: &[OutgoingFile]) -> Result<TransferSummary, String> {
+        ensure_supported(self.options.protocol)?;
+        if files.is_empty() {
+            return Err("No files to send".to_string());
+        }
+        self.progress.direction = TransferDirection::Send;
+        self.progress.started = Instant::now();
+        let result = match self.options.protocol {
+            TransferProtocol::Xmodem | TransferProtocol::XmodemCrc | TransferProtocol::Xmodem1k => {
+                match files {
+                    [file] => self.xmodem_send(file).await,
+                    _ => Err(EngineError::Failed(
+                        "XMODEM can only send a single file".to_string(),
+                    )),
+                }
+            }
+            TransferProtocol::Ymodem | TransferProtocol::YmodemG => self.ymodem_send(files).await,
+            _ => self.zmodem_send(files).await,
+        };
+        self.finish(result).await
+    }
+
+    /// Receive files into `sink`.
+    pub async fn receive(
+        &mut self,
+        sink: &mut dyn TransferSink,
+    ) -> Result<TransferSummary, String> {
+        ensure_supported(self.options.protocol)?;
+        self.progress.direction = TransferDirection::Receive;
+        self.progress.started = Instant::now();
+        let result = match self.options.protocol {
+            TransferProtocol::Xmodem | TransferProtocol::XmodemCrc | TransferProtocol::Xmodem1k => {
+                self.xmodem_receive(sink).await
+            }
+            TransferProtocol::Ymodem | TransferProtocol::YmodemG => self.ymodem_receive(sink).await,
+            _ => self.zmodem_receive(sink).await,
+        };
+        if result.is_err() {
+            let _ = sink.finish_file();
+        }
+        self.finish(result).await
+    }
+
+    async fn finish(&mut self, result: EngineResult<()>) -> Result<TransferSummary, String> {
+        match result {
+            Ok(()) => {
+                self.progress.report(TransferState::Completed, true);
+                Ok(self.progress.summary())
+            }
+            Err(error) => {
+                if !matches!(error, EngineError::RemoteCancelled) {
+                    self.link.abort().await;
+                }
+                let state = if matches!(error, EngineError::Cancelled) {
+                    TransferState::Cancelled
+                } else {
+                    TransferState::Failed
+                };
+                self.progress.report(state, true);
+                Err(error.message())
+            }
+        }
+    }
+
+    /// Count an error and a retry; fail once `attempts` exceeds the budget.
+    fn retry(&mut self, attempts: &mut u32, what: &str) -> EngineResult<()> {
+        *attempts += 1;
+        self.progress.errors = self.progress.errors.saturating_add(1);
+        self.progress.retries = self.progress.retries.saturating_add(1);
+        if *attempts > self.options.max_retries {
+            Err(EngineError::Failed(format!(
+                "{} failed after {} retries",
+                what, self.options.max_retries
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    // ── XMODEM / YMODEM sender ────────────────────────────────────
+
+    /// Wait for the receiver's start request (`C`, `G` or NAK).
+    async fn await_start(&mut self) -> EngineResult<u8> {
+        let deadline = Instant::now() + self.options.timeout;
+        let mut cans = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let byte = self
+                .link
+                .byte(remaining)
+                .await?
+                .ok_or(EngineError::Timeout)?;
+            match byte {
+                C_BYTE | NAK => return Ok(byte),
+                b'G' if self.options.protocol == TransferProtocol::YmodemG => return Ok(byte),
+                CAN => {
+                    cans += 1;
+                    if cans >= 2 {
+                        return Err(EngineError::RemoteCancelled);
+                    }
+                }
+                _ => cans = 0,
+            }
+        }
+    }
+
+    /// Wait for ACK or NAK, ignoring line noise.  `None` on timeout.
+    async fn await_reply(&mut self) -> EngineResult<Option<u8>> {
+        let deadline = Instant::now() + self.options.timeout;
+        let mut cans = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Some(byte) = self.link.byte(remaining).await? else {
+                return Ok(None);
+            };
+            match byte {
+                ACK | NAK => return Ok(Some(byte)),
+                CAN => {
+                    cans += 1;
+                    if cans >= 2 {
+                        return Err(EngineError::RemoteCancelled);
+                    }
+                }
+                _ => cans = 0,
+            }
+        }
+    }
+
+    async fn send_block(&mut self, block: &[u8], streaming: bool) -> EngineResult<()> {
+        let mut attempts = 0;
+        loop {
+            self.link.write_all(block).await?;
+            if streaming {
+                return Ok(());
+            }
+            if self.await_reply().await? == Some(ACK) {
+                return Ok(());
+            }
+            self.retry(&mut attempts, "Block transmission")?;
+        }
+    }
+
+    async fn send_eot(&mut self) -> EngineResult<()> {
+        let mut attempts = 0;
+        loop {
+            self.link.write_all(&[EOT]).await?;
+            match self.await_reply().await? {
+                Some(ACK) => return Ok(()),
+                // YMODEM receivers NAK the first EOT by design.
+                Some(_) if attempts == 0 => attempts += 1,
+                _ => self.retry(&mut attempts, "End of transmission")?,
+            }
+        }
+    }
+
+    /// Send `data` as numbered blocks starting at 1, then EOT.
+    async fn send_blocks(
+        &mut self,
+        data: &[u8],
+        crc: bool,
+        large: bool,
+        streaming: bool,
+    ) -> EngineResult<()> {
+        let mut block_num: u8 = 1;
+        let mut offset = 0;
+        while offset < data.len() {
+            let remaining = data.len() - offset;
+            let mode = if !crc {
+                XmodemMode::Checksum
+            } else if large && remaining > 128 {
+                XmodemMode::OneK
+            } else {
+                XmodemMode::Crc
+            };
+            let end = (offset + mode.block_size()).min(data.len());
+            let block = build_xmodem_block(block_num, &data[offset..end], mode);
+            self.send_block(&block, streaming).await?;
+            self.progress.advance((end - offset) as u64);
+            offset = end;
+            block_num = block_num.wrapping_add(1);
+        }
+        self.send_eot().await
+    }
+
+    async fn xmodem_send(&mut self, file: &OutgoingFile) -> EngineResult<()> {
+        self.progress.begin_file(&file.name, file.data.len() as u64);
+        let start = self.await_start().await?;
+        let crc = start != NAK;
+        if !crc && self.options.protocol != TransferProtocol::Xmodem {
+            log::debug!("XMODEM receiver requested checksum mode; falling back");
+        }
+        let large = self.options.protocol == TransferProtocol::Xmodem1k;
+        self.send_blocks(&file.data, crc, large, false).await?;
+        self.progress.complete_file();
+        Ok(())
+    }
+
+    async fn ymodem_send(&mut self, files: &[OutgoingFile]) -> EngineResult<()> {
+        for file in files {
+            // name NUL size NUL must fit in the 128-byte block 0.
+            let size = file.data.len() as u64;
+            if file.name.len() + size.to_string().len() + 2 > 128 {
+                return Err(EngineError::Failed(format!(
+                    "File name {:?} is too long for a YMODEM header",
+                    file.name
+                )));
+            }
+            self.progress.begin_file(&file.name, size);
+            if self.await_start().await? == NAK {
+                return Err(EngineError::Failed(
+                    "Receiver requested checksum mode, which YMODEM does not support".to_string(),
+                ));
+            }
+            self.send_block(&build_ymodem_header(&file.name, size), false)
+                .await?;
+            let streaming = self.await_start().await? == b'G';
+            self.send_blocks(&file.data, true, true, streaming).await?;
+            self.progress.complete_file();
+        }
+        self.await_start().await?;
+        self.send_block(&build_ymodem_end_header(), false).await
+    }
+
+    // ── XMODEM / YMODEM receiver ──────────────────────────────────
+
+    /// Send `request` until the sender starts.  Returns the first header
+    /// byte and whether CRC mode was negotiated.
+    async fn request_start(
+        &mut self,
+        mut request: u8,
+        allow_checksum: bool,
+    ) -> EngineResult<(u8, bool)> {
+        let probe = self.options.timeout.min(MAX_START_PROBE_INTERVAL);
+        let mut probes = 0;
+        loop {
+            self.link.write_all(&[request]).await?;
+            probes += 1;
+            match self.link.byte(probe).await? {
+                Some(header @ (SOH | STX | EOT)) => return Ok((header, request != NAK)),
+                Some(CAN) if self.link.byte(probe).await? == Some(CAN) => {
+                    return Err(EngineError::RemoteCancelled);
+                }
+                _ => {}
+            }
+            if allow_checksum && request == C_BYTE && probes >= CRC_PROBES_BEFORE_CHECKSUM {
+                request = NAK;
+            }
+            if probes > self.options.max_retries {
+                return Err(EngineError::Failed(
+                    "Sender did not start the transfer".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Read the remainder of a block whose header byte was already consumed.
+    async fn read_block(&mut self, header: u8, crc: bool) -> EngineResult<(u8, Vec<u8>)> {
+        let mode = match header {
+            STX if crc => XmodemMode::OneK,
+            STX => {
+                return Err(EngineError::Garbled(
+                    "1K block received in checksum mode".to_string(),
+                ))
+            }
+            _ if crc => XmodemMode::Crc,
+            _ => XmodemMode::Checksum,
+        };
+        let len = mode.block_size() + if crc { 4 } else { 3 };
+        let gap = self.options.timeout.min(MAX_BYTE_GAP);
+        let mut block = Vec::with_capacity(len + 1);
+        block.push(header);
+        for _ in 0..len {
+            block.push(self.link.byte_or_timeout(gap).await?);
+        }
+        verify_xmodem_block(&block, mode).map_err(EngineError::Garbled)
+    }
+
+    /// Receive data blocks until EOT.  `size` truncates the padding of
+    /// YMODEM files; without it trailing SUB padding is stripped from the
+    /// final block.
+    async fn receive_blocks(
+        &mut self,
+        first: u8,
+        crc: bool,
+        ymodem: bool,
+        size: Option<u64>,
+        sink: &mut dyn TransferSink,
+    ) -> EngineResult<()> {
+        let streaming = self.options.protocol == TransferProtocol::YmodemG;
+        let gap = self.options.timeout.min(MAX_BYTE_GAP);
+        let mut next = Some(first);
+        let mut expected: u8 = 1;
+        let mut failures = 0;
+        let mut eot_seen = false;
+        let mut remaining = size;
+        let mut held: Option<Vec<u8>> = None;
+
+        loop {
+            let header = match next.take() {
+                Some(header) => header,
+                None => match self.link.byte(self.options.timeout).await? {
+                    Some(header) => header,
+                    None => {
+                        if streaming {
+                            return Err(EngineError::Timeout);
+                        }
+                        self.retry(&mut failures, "Block reception")?;
+                        self.link.write_all(&[NAK]).await?;
+                        continue;
+                    }
+                },
+            };
+            match header {
+                SOH | STX => match self.read_block(header, crc).await {
+                    Ok((num, data)) if num == expected => {
+                        failures = 0;
+                        eot_seen = false;
+                        if !streaming {
+                            self.link.write_all(&[ACK]).await?;
+                        }
+                        let delivered = match remaining.as_mut() {
+                            Some(left) => {
+                                let take = (*left).min(data.len() as u64) as usize;
+                                *left -= take as u64;
+                                sink.write(&data[..take]).map_err(EngineError::Failed)?;
+                                take
+                            }
+                            None => {
+                                if let Some(previous) = held.replace(data) {
+                                    sink.write(&previous).map_err(EngineError::Failed)?;
+                                }
+                                held.as_ref().map_or(0, Vec::len)
+                            }
+                        };
+                        self.progress.advance(delivered as u64);
+                        expected = expected.wrapping_add(1);
+                    }
+                    // The sender missed our ACK and repeated the block.
+                    Ok((num, _)) if num == expected.wrapping_sub(1) => {
+                        if !streaming {
+                            self.link.write_all(&[ACK]).await?;
+                        }
+                    }
+                    Ok((num, _)) => {
+                        return Err(EngineError::Failed(format!(
+                            "Block sequence error: expected {}, got {}",
+                            expected, num
+                        )))
+                    }
+                    Err(EngineError::Garbled(message)) if streaming => {
+                        return Err(EngineError::Failed(message))
+                    }
+                    Err(EngineError::Garbled(_) | EngineError::Timeout) => {
+                        self.retry(&mut failures, "Block reception")?;
+                        self.link.purge(gap.min(Duration::from_millis(500))).await?;
+                        self.link.write_all(&[NAK]).await?;
+                    }
+                    Err(error) => return Err(error),
+                },
+                EOT if ymodem && !eot_seen => {
+                    eot_seen = true;
+                    self.link.write_all(&[NAK]).await?;
+                }
+                EOT => {
+                    self.link.write_all(&[ACK]).await?;
+                    break;
+                }
+                CAN if self.link.byte(gap).await? == Some(CAN) => {
+                    return Err(EngineError::RemoteCancelled);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(mut last) = held {
+            while last.last() == Some(&SUB) {
+                last.pop();
+            }
+            sink.write(&last).map_err(EngineError::Failed)?;
+        }
+        Ok(())
+    }
+
+    async fn xmodem_receive(&mut self, sink: &mut dyn TransferSink) -> EngineResult<()> {
+        match sink.begin_file("", None).map_err(EngineError::Failed)? {
+            Some(0) => {}
+            Some(_) => {
+                return Err(EngineError::Failed(
+                    "XMODEM cannot resume a partial file".to_string(),
+                ))
+            }
+            None => {
+                return Err(EngineError::Failed(
+                    "Destination file already exists".to_string(),
+                ))
+            }
+        }
+        self.progress.begin_file("", 0);
+        let allow_checksum = self.options.protocol == TransferProtocol::Xmodem;
+        let (first, crc) = self.request_start(C_BYTE, allow_checksum).await?;
+        self.receive_blocks(first, crc, false, None, sink).await?;
+        sink.finish_file().map_err(EngineError::Failed)?;
+        self.progress.complete_file();
+        Ok(())
+    }
+
+    async fn ymodem_receive(&mut self, sink: &mut dyn TransferSink) -> EngineResult<()> {
+        let request = if self.options.protocol == TransferProtocol::YmodemG {
+            b'G'
+        } else {
+            C_BYTE
+        };
+        let mut failures = 0;
+        loop {
+            let (header, _) = self.request_start(request, false).await?;
+            if header == EOT {
+                // Repeated EOT from a sender that missed our final ACK.
+                self.link.write_all(&[ACK]).await?;
+                continue;
+            }
+            let (name, size) = match self.read_block(header, true).await {
+                Ok((0, data)) => parse_ymodem_header(&data).map_err(EngineError::Failed)?,
+                Ok((num, _)) => {
+                    return Err(EngineError::Failed(format!(
+                        "Expected YMODEM header block, got block {}",
+                        num
+                    )))
+                }
+                Err(EngineError::Garbled(_) | EngineError::Timeout) => {
+                    self.retry(&mut failures, "YMODEM header")?;
+                    self.link.purge(Duration::from_millis(200)).await?;
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+            failures = 0;
+            self.link.write_all(&[ACK]).await?;
+            if name.is_empty() {
+                return Ok(());
+            }
+
+            match sink
+                .begin_file(&name, Some(size))
+                .map_err(EngineError::Failed)?
+            {
+                Some(0) => {}
+                Some(_) => {
+                    return Err(EngineError::Failed(
+                        "YMODEM cannot resume a partial file".to_string(),
+                    ))
+                }
+                None => {
+                    return Err(EngineError::Failed(format!(
+                        "Refusing to overwrite existing file {}",
+                        name
+                    )))
+                }
+            }
+            self.progress.begin_file(&name, size);
+            let (first, _) = self.request_start(request, false).await?;
+            self.receive_blocks(first, true, true, Some(size), sink)
+                .await?;
+            sink.finish_file().map_err(EngineError::Failed)?;
+            self.progress.complete_file();
+        }
+    }
+
+    // ── ZMODEM framing ────────────────────────────────────────────
+
+    async fn send_zbin(&mut self, frame_type: u8, data: [u8; 4]) -> EngineResult<()> {
+        let header = if self.zmodem_crc32 {
+            encode_zbin32_header(frame_type, &data)
+        } else {
+            encode_zbin_header(frame_type, &data)
+        };
+        self.link.write_all(&header).await
+    }
+
+    async fn send_zhex(&mut self, frame_type: u8, data: [u8; 4]) -> EngineResult<()> {
+        self.link
+            .write_all(&encode_zhex_header(frame_type, &data))
+            .await
+    }
+
+    async fn send_zrpos(&mut self, position: u64) -> EngineResult<()> {
+        self.send_zhex(ZRPOS, zmodem_position(position as u32))
+            .await
+    }
+
+    async fn send_zrinit(&mut self) -> EngineResult<()> {
+        self.link
+            .write_all(&build_zrinit_with_flags(0, RECEIVER_CAPABILITIES))
+            .await
+    }
+
+    /// Read one ZDLE-decoded byte, dropping flow-control characters.
+    async fn read_escaped(&mut self) -> EngineResult<ZByte> {
+        let gap = self.options.timeout.min(MAX_BYTE_GAP);
+        loop {
+            match self.link.byte_or_timeout(gap).await? {
+                ZDLE => break,
+                0x11 | 0x13 | 0x91 | 0x93 => continue,
+                byte => return Ok(ZByte::Data(byte)),
+            }
+        }
+        let mut cans = 1;
+        loop {
+            let byte = self.link.byte_or_timeout(gap).await?;
+            match byte {
+                CAN => {
+                    cans += 1;
+                    if cans >= 5 {
+                        return Err(EngineError::RemoteCancelled);
+                    }
+                }
+                ZCRCE | ZCRCG | ZCRCQ | ZCRCW => return Ok(ZByte::End(byte)),
+                ZRUB0 => return Ok(ZByte::Data(0x7F)),
+                ZRUB1 => return Ok(ZByte::Data(0xFF)),
+                0x11 | 0x13 | 0x91 | 0x93 => {}
+                _ if byte & 0x60 == 0x40 => return Ok(ZByte::Data(byte ^ 0x40)),
+                _ => {
+                    return Err(EngineError::Garbled(format!(
+                        "Invalid ZDLE escape 0x{:02X}",
+                        byte
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Wait for the next header.  `None` on timeout.
+    async fn read_zheader(&mut self, timeout: Duration) -> EngineResult<Option<ZHeader>> {
+        let deadline = Instant::now() + timeout;
+        let gap = self.options.timeout.min(MAX_BYTE_GAP);
+        let mut cans = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Some(byte) = self.link.byte(remaining).await? else {
+                return Ok(None);
+            };
+            match byte {
+                ZPAD => {}
+                CAN => {
+                    cans += 1;
+                    if cans >= 5 {
+                        return Err(EngineError::RemoteCancelled);
+                    }
+                    continue;
+                }
+                _ => {
+                    cans = 0;
+                    continue;
+                }
+            }
+            cans = 0;
+            let mut next = self.link.byte_or_timeout(gap).await?;
+            while next == ZPAD {
+                next = self.link.byte_or_timeout(gap).await?;
+            }
+            if next != ZDLE {
+                self.link.unread(next);
+                continue;
+            }
+            let encoding = self.link.byte_or_timeout(gap).await?;
+            let header = match encoding {
+                ZHEX => self.read_hex_header().await?,
+                ZBIN | ZBIN32 => self.read_bin_header(encoding).await?,
+                _ => {
+                    self.link.unread(encoding);
+                    continue;
+                }
+            };
+            return Ok(Some(header));
+        }
+    }
+
+    async fn read_hex_header(&mut self) -> EngineResult<ZHeader> {
+        let gap = self.options.timeout.min(MAX_BYTE_GAP);
+        let mut raw = [0u8; 7];
+        for slot in raw.iter_mut() {
+            let hi = self.link.byte_or_timeout(gap).await?;
+            let lo = self.link.byte_or_timeout(gap).await?;
+            match (hex_value(hi & 0x7F), hex_value(lo & 0x7F)) {
+                (Some(hi), Some(lo)) => *slot = (hi << 4) | lo,
+                _ => {
+                    return Err(EngineError::Garbled(
+                        "Invalid digit in ZMODEM hex header".to_string(),
+                    ))
+                }
+            }
+        }
+        if crc16_xmodem(&raw[..5]).to_be_bytes() != raw[5..7] {
+            return Err(EngineError::Garbled(
+                "ZMODEM hex header CRC mismatch".to_string(),
+            ));
+        }
+        // Swallow the CR LF trailer (and an XON if it has already arrived)
+        // so a following data subpacket starts clean.
+        for _ in 0..2 {
+            match self.link.byte(gap).await? {
+                Some(0x0D | 0x8D | 0x0A | 0x8A) => {}
+                Some(other) => {
+                    self.link.unread(other);
+                    break;
+                }
+                None => break,
+            }
+        }
+        if self.link.peek() == Some(0x11) {
+            self.link.byte(Duration::ZERO).await?;
+        }
+        Ok(ZHeader {
+            frame_type: raw[0],
+            data: [raw[1], raw[2], raw[3], raw[4]],
+            encoding: ZHEX,
+        })
+    }
+
+    async fn read_bin_header(&mut self, encoding: u8) -> EngineResult<ZHeader> {
+        let crc_len = if encoding == ZBIN32 { 4 } else { 2 };
+        let mut raw = Vec::with_capacity(5 + crc_len);
+        for _ in 0..5 + crc_len {
+            match self.read_escaped().await? {
+                ZByte::Data(byte) => raw.push(byte),
+                ZByte::End(_) => {
+                    return Err(EngineError::Garbled(
+                        "Subpacket terminator inside ZMODEM header".to_string(),
+                    ))
+                }
+            }
+        }
+        let valid = if encoding == ZBIN32 {
+            crc32(&raw[..5]).to_le_bytes() == raw[5..9]
+        } else {
+            crc16_xmodem(&raw[..5]).to_be_bytes() == raw[5..7]
+        };
+        if !valid {
+            return Err(EngineError::Garbled(
+                "ZMODEM binary header CRC mismatch".to_string(),
+            ));
+        }
+        Ok(ZHeader {
+            frame_type: raw[0],
+            data: [raw[1], raw[2], raw[3], raw[4]],
+            encoding,
+        })
+    }
+
+    /// Read one data subpacket and verify its CRC.
+    async fn read_subpacket(&mut self, use_crc32: bool) -> EngineResult<(Vec<u8>, u8)> {
+        let mut data = Vec::with_capacity(ZMODEM_SUBPACKET_BYTES);
+        loop {
+            match self.read_escaped().await? {
+                ZByte::Data(byte) => {
+                    if data.len() >= MAX_ZMODEM_SUBPACKET_BYTES {
+                        return Err(EngineError::Garbled(
+                            "ZMODEM subpacket exceeds the maximum length".to_string(),
+                        ));
+                    }
+                    data.push(byte);
+                }
+                ZByte::End(frame_end) => {
+                    let mut crc = Vec::with_capacity(4);
+                    for _ in 0..if use_crc32 { 4 } else { 2 } {
+                        match self.read_escaped().await? {
+                            ZByte::Data(byte) => crc.push(byte),
+                            ZByte::End(_) => {
+                                return Err(EngineError::Garbled(
+                                    "Subpacket terminator inside CRC".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    if !verify_zmodem_subpacket(&data, frame_end, &crc) {
+                        return Err(EngineError::Garbled(
+                            "ZMODEM subpacket CRC mismatch".to_string(),
+                        ));
+                    }
+                    return Ok((data, frame_end));
+                }
+            }
+        }
+    }
+
+    // ── ZMODEM sender ─────────────────────────────────────────────
+
+    async fn zmodem_send(&mut self, files: &[OutgoingFile]) -> EngineResult<()> {
+        if let Some(file) = files.iter().find(|f| f.data.len() as u64 > u32::MAX as u64) {
+            return Err(EngineError::Failed(format!(
+                "{} is too large for ZMODEM",
+                file.name
+            )));
+        }
+        if !self.options.in_band {
+            self.link.write_all(b"rz\r").await?;
+        }
+        let zrinit = self.zmodem_await_zrinit().await?;
+        let flags = zrinit.data[3];
+        let bufsize = u16::from_le_bytes([zrinit.data[0], zrinit.data[1]]);
+        self.zmodem_crc32 = flags & CANFC32 != 0;
+        // Receivers that cannot overlap I/O get one acknowledged subpacket at a time.
+        let windowed = bufsize != 0 || flags & (CANFDX | CANOVIO) != (CANFDX | CANOVIO);
+        for file in files {
+            self.zmodem_send_file(file, windowed).await?;
+        }
+        self.zmodem_finish_session().await
+    }
+
+    async fn zmodem_await_zrinit(&mut self) -> EngineResult<ZHeader> {
+        let mut attempts = 0;
+        loop {
+            self.link.write_all(&build_zrqinit()).await?;
+            match self.read_zheader(self.options.timeout).await {
+                Ok(Some(header)) => match header.frame_type {
+                    ZRINIT => return Ok(header),
+                    ZCHALLENGE => {
+                        self.send_zhex(ZACK, header.data).await?;
+                        continue;
+                    }
+                    ZRQINIT => {
+                        return Err(EngineError::Failed(
+                            "Remote side is also trying to send".to_string(),
+                        ))
+                    }
+                    ZABORT | ZFERR | ZCAN => {
+                        return Err(EngineError::Failed(
+                            "Receiver aborted the session".to_string(),
+                        ))
+                    }
+                    _ => {}
+                },
+                Ok(None) | Err(EngineError::Timeout | EngineError::Garbled(_)) => {}
+                Err(error) => return Err(error),
+            }
+            self.retry(&mut attempts, "ZMODEM session start")?;
+        }
+    }
+
+    async fn zmodem_send_file(&mut self, file: &OutgoingFile, windowed: bool) -> EngineResult<()> {
+        let size = file.data.len();
+        self.progress.begin_file(&file.name, size as u64);
+        let info = build_zfile_info(&file.name, size as u64);
+        let mut attempts = 0;
+
+        let mut position = 'negotiate: loop {
+            self.send_zbin(ZFILE, [0, 0, 0, ZCBIN]).await?;
+            let subpacket = encode_zmodem_subpacket(&info, ZCRCW, self.zmodem_crc32);
+            self.link.write_all(&subpacket).await?;
+            loop {
+                match self.read_zheader(self.options.timeout).await {
+                    Ok(Some(header)) => match header.frame_type {
+                        ZRPOS => break 'negotiate header.position() as usize,
+                        ZSKIP => {
+                            log::info!("ZMODEM receiver skipped {}", file.name);
+                            return Ok(());
+                        }
+                        ZCRC => {
+                            let crc = crc32(&file.data).to_le_bytes();
+                            self.send_zhex(ZCRC, crc).await?;
+                            continue;
+                        }
+                        // Stale ZRINIT from session start; keep waiting.
+                        ZRINIT => continue,
+                        ZABORT | ZFERR | ZCAN => {
+                            return Err(EngineError::Failed(
+                                "Receiver aborted the session".to_string(),
+                            ))
+                        }
+                        _ => {}
+                    },
+                    Ok(None) | Err(EngineError::Timeout | EngineError::Garbled(_)) => {}
+                    Err(error) => return Err(error),
+                }
+                self.retry(&mut attempts, "ZFILE")?;
+                continue 'negotiate;
+            }
+        };
+
+        attempts = 0;
+        'frame: loop {
+            position = position.min(size);
+            self.progress.seek(position as u64);
+            self.send_zbin(ZDATA, zmodem_position(position as u32))
+                .await?;
+            loop {
+                let start = position;
+                let end = (position + ZMODEM_SUBPACKET_BYTES).min(size);
+                let last = end == size;
+                let frame_end = if last {
+                    ZCRCE
+                } else if windowed {
+                    ZCRCW
+                } else {
+                    ZCRCG
+                };
+                let subpacket =
+                    encode_zmodem_subpacket(&file.data[start..end], frame_end, self.zmodem_crc32);
+                self.link.write_all(&subpacket).await?;
+                position = end;
+                self.progress.advance_to(position as u64);
+                if last {
+                    break;
+                }
+                if windowed {
+                    match self.read_zheader(self.options.timeout).await {
+                        Ok(Some(header)) if header.frame_type == ZACK => {}
+                        Ok(Some(header)) if header.frame_type == ZRPOS => {
+                            self.retry(&mut attempts, "ZMODEM data")?;
+                            position = header.position() as usize;
+                        }
+                        Ok(_) | Err(EngineError::Timeout | EngineError::Garbled(_)) => {
+                            self.retry(&mut attempts, "ZMODEM data")?;
+                            position = start;
+                        }
+                        Err(error) => return Err(error),
+                    }
+                    continue 'frame;
+                }
+                if self.link.input_pending().await {
+                    match self.read_zheader(MAX_BYTE_GAP).await {
+                        Ok(Some(header)) if header.frame_type == ZRPOS => {
+                            self.retry(&mut attempts, "ZMODEM data")?;
+                            // Close the open frame before restarting it.
+                            let close = encode_zmodem_subpacket(&[], ZCRCE, self.zmodem_crc32);
+                            self.link.write_all(&close).await?;
+                            position = header.position() as usize;
+                            continue 'frame;
+                        }
+                        Ok(Some(header)) if matches!(header.frame_type, ZABORT | ZFERR | ZCAN) => {
+                            return Err(EngineError::Failed(
+                                "Receiver aborted the session".to_string(),
+                            ))
+                        }
+                        Ok(_) | Err(EngineError::Timeout | EngineError::Garbled(_)) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+
+            loop {
+                self.send_zbin(ZEOF, zmodem_position(size as u32)).await?;
+                loop {
+                    match self.read_zheader(self.options.timeout).await {
+                        Ok(Some(header)) => match header.frame_type {
+                            ZRINIT | ZSKIP => {
+                                self.progress.complete_file();
+                                return Ok(());
+                            }
+                            ZRPOS => {
+                                self.retry(&mut attempts, "ZMODEM data")?;
+                                position = header.position() as usize;
+                                continue 'frame;
+                            }
+                            ZABORT | ZFERR | ZCAN => {
+                                return Err(EngineError::Failed(
+                                    "Receiver aborted the session".to_string(),
+                                ))
+                            }
+                            // Stale ZACKs from the data phase.
+                            _ => continue,
+                        },
+                        Ok(None) | Err(EngineError::Timeout | EngineError::Garbled(_)) => break,
+                        Err(error) => return Err(error),
+                    }
+                }
+                self.retry(&mut attempts, "ZEOF")?;
+            }
+        }
+    }
+
+    async fn zmodem_finish_session(&mut self) -> EngineResult<()> {
+        let mut attempts = 0;
+        loop {
+            self.send_zhex(ZFIN, [0; 4]).await?;
+            loop {
+                match self.read_zheader(self.options.timeout).await {
+                    Ok(Some(header)) if header.frame_type == ZFIN => {
+                        self.link.write_all(b"OO").await?;
+                        return Ok(());
+                    }
+                    // Late ZRINITs answering an earlier ZEOF.
+                    Ok(Some(_)) => continue,
+                    Ok(None) | Err(EngineError::Timeout | EngineError::Garbled(_)) => break,
+                    Err(error) => return Err(error),
+                }
+            }
+            self.retry(&mut attempts, "ZFIN")?;
+        }
+    }
+
+    // ── ZMODEM receiver ───────────────────────────────────────────
+
+    async fn zmodem_receive(&mut self, sink: &mut dyn TransferSink) -> EngineResult<()> {
+        self.send_zrinit().await?;
+        let mut current: Option<ZReceiveFile> = None;
+        let mut failures = 0;
+
+        loop {
+            let header = match self.read_zheader(self.options.timeout).await {
+                Ok(Some(header)) => header,
+                Ok(None) | Err(EngineError::Timeout | EngineError::Garbled(_)) => {
+                    self.retry(&mut failures, "ZMODEM reception")?;
+                    match &current {
+                        Some(file) => self.send_zrpos(file.offset).await?,
+                        None => self.send_zrinit().await?,
+                    }
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            match header.frame_type {
+                ZRQINIT if current.is_none() => self.send_zrinit().await?,
+                ZSINIT => match self.read_subpacket(header.uses_crc32()).await {
+                    Ok(_) => self.send_zhex(ZACK, [0; 4]).await?,
+                    Err(EngineError::Garbled(_) | EngineError::Timeout) => {
+                        self.retry(&mut failures, "ZSINIT")?;
+                        self.send_zhex(ZNAK, [0; 4]).await?;
+                    }
+                    Err(error) => return Err(error),
+                },
+                ZFILE => {
+                    let info = match self.read_subpacket(header.uses_crc32()).await {
+                        Ok((info, _)) => info,
+                        Err(EngineError::Garbled(_) | EngineError::Timeout) => {
+                            self.retry(&mut failures, "ZFILE")?;
+                            self.send_zhex(ZNAK, [0; 4]).await?;
+                            continue;
+                        }
+                        Err(error) => return Err(error),
+                    };
+                    let (name, size) = parse_zfile_info(&info).map_err(EngineError::Failed)?;
+                    if let Some(file) = &current {
+                        if file.name == name {
+                            // Our ZRPOS was lost; repeat it.
+                            let offset = file.offset;
+                            self.send_zrpos(offset).await?;
+                            continue;
+                        }
+                        sink.finish_file().map_err(EngineError::Failed)?;
+                    }
+                    match sink.begin_file(&name, size).map_err(EngineError::Failed)? {
+                        None => {
+                            current = None;
+                            self.send_zhex(ZSKIP, [0; 4]).await?;
+                        }
+                        Some(offset) => {
+                            let offset = size.map_or(offset, |size| offset.min(size));
+                            self.progress.begin_file(&name, size.unwrap_or(0));
+                            self.progress.seek(offset);
+                            current = Some(ZReceiveFile { name, offset });
+                            self.send_zrpos(offset).await?;
+                        }
+                    }
+                }
+                ZDATA => {
+                    let Some(file) = current.as_mut() else {
+                        self.send_zrinit().await?;
+                        continue;
+                    };
+                    if header.position() != file.offset {
+                        let offset = file.offset;
+                        self.send_zrpos(offset).await?;
+                        continue;
+                    }
+                    loop {
+                        match self.read_subpacket(header.uses_crc32()).await {
+                            Ok((data, frame_end)) => {
+                                failures = 0;
+                                sink.write(&data).map_err(EngineError::Failed)?;
+                                file.offset += data.len() as u64;
+                                self.progress.advance_to(file.offset);
+                                let ack = zmodem_position(file.offset as u32);
+                                match frame_end {
+                                    ZCRCW => {
+                                        self.send_zhex(ZACK, ack).await?;
+                                        break;
+                                    }
+                                    ZCRCQ => self.send_zhex(ZACK, ack).await?,
+                                    ZCRCE => break,
+                                    _ => {}
+                                }
+                            }
+                            Err(EngineError::Garbled(_) | EngineError::Timeout) => {
+                                self.retry(&mut failures, "ZMODEM data")?;
+                                let offset = file.offset;
+                                self.send_zrpos(offset).await?;
+                                break;
+                            }
+                            Err(error) => return Err(error),
+                        }
+                    }
+                }
+                ZEOF => {
+                    let Some(file) = &current else {
+                        // Our ZRINIT after the last ZEOF was lost.
+                        self.send_zrinit().await?;
+                        continue;
+                    };
+                    // A ZEOF ahead of our offset is stale: data is still
+                    // being resent after a ZRPOS.
+                    if header.position() != file.offset {
+                        continue;
+                    }
+                    sink.finish_file().map_err(EngineError::Failed)?;
+                    self.progress.complete_file();
+                    current = None;
+                    self.send_zrinit().await?;
+                }
+                ZFIN => {
+                    self.send_zhex(ZFIN, [0; 4]).await?;
+                    // Consume the sender's "OO" so it doesn't leak into the terminal.
+                    for _ in 0..2 {
+                        if self.link.byte(Duration::from_millis(500)).await?.is_none() {
+                            break;
+                        }
+                    }
+                    return Ok(());
+                }
+                ZFREECNT => self.send_zhex(ZACK, [0xFF; 4]).await?,
+                ZCOMMAND => {
+                    // Remote command execution is never honoured.
+                    let _ = self.read_subpacket(header.uses_crc32()).await;
+                    self.send_zhex(ZCOMPL, [1, 0, 0, 0]).await?;
+                }
+                ZABORT | ZFERR | ZCAN => {
+                    return Err(EngineError::Failed(
+                        "Sender aborted the session".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Check that `protocol` has a transfer engine.
+pub fn ensure_supported(protocol: TransferProtocol) -> Result<(), String> {
+    match protocol {
+        TransferProtocol::Xmodem
+        | TransferProtocol::XmodemCrc
+        | TransferProtocol::Xmodem1k
+        | TransferProtocol::Ymodem
+        | TransferProtocol::YmodemG
+        | TransferProtocol::Zmodem => Ok(()),
+        other => Err(format!(
+            "{} transfers are not supported by the transfer engine",
+            other.label()
+        )),
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::sync::Notify;
+
+    /// One direction of an in-memory serial line.
+    #[derive(Default)]
+    struct Pipe {
+        buf: Mutex<VecDeque<u8>>,
+        notify: Notify,
+    }
+
+    /// In-memory transport whose writes land in the peer's receive pipe.
+    struct MemoryTransport {
+        rx: Arc<Pipe>,
+        tx: Arc<Pipe>,
+        /// Absolute TX offset of a byte to corrupt once.
+        corrupt_at: Mutex<Option<usize>>,
+        written: Mutex<usize>,
+    }
+
+    fn memory_pair() -> (MemoryTransport, MemoryTransport) {
+        let a_to_b = Arc::new(Pipe::default());
+        let b_to_a = Arc::new(Pipe::default());
+        let a = MemoryTransport {
+            rx: b_to_a.clone(),
+            tx: a_to_b.clone(),
+            corrupt_at: Mutex::new(None),
+            written: Mutex::new(0),
+        };
+        let b = MemoryTransport {
+            rx: a_to_b,
+            tx: b_to_a,
+            corrupt_at: Mutex::new(None),
+            written: Mutex::new(0),
+        };
+        (a, b)
+    }
+
+    #[async_trait::async_trait]
+    impl SerialTransport for MemoryTransport {
+        async fn open(&self, _config: &SerialConfig) -> Result<(), String> {
+            Ok(())
+        }
+        async fn close(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn read(&self, buf: &mut [u8]) -> Result<usize, String> {
+            if self.rx.buf.lock().unwrap().is_empty() {
+                let _ = tokio::time::timeout(Duration::from_millis(20), self.rx.notify.notified())
+                    .await;
+            }
+            let mut rx = self.rx.buf.lock().unwrap();
+            let n = rx.len().min(buf.len());
+            for (slot, byte) in buf.iter_mut().zip(rx.drain(..n)) {
+                *slot = byte;
+            }
+            Ok(n)
+        }
+        async fn write(&self, buf: &[u8]) -> Result<usize, String> {
+            let mut data = buf.to_vec();
+            let mut written = self.written.lock().unwrap();
+            let mut corrupt_at = self.corrupt_at.lock().unwrap();
+            if let Some(at) = *corrupt_at {
+                if (*written..*written + data.len()).contains(&at) {
+                    data[at - *written] ^= 0x01;
+                    *corrupt_at = None;
+                }
+            }
+            *written += data.len();
+            self.tx.buf.lock().unwrap().extend(data);
+            self.tx.notify.notify_waiters();
+            Ok(buf.len())
+        }
+        async fn flush(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn drain(&self) -> Result<(), String> {
+            Ok(())
+        }
+        async fn send_break(&self, _duration_ms: u32) -> Result<(), String> {
+            Ok(())
+        }
+        async fn set_dtr(&self, _state: bool) -> Result<(), String> {
+            Ok(())
+        }
+        async fn set_rts(&self, _state: bool) -> Result<(), String> {
+            Ok(())
+        }
+        async fn read_control_lines(&self) -> Result<ControlLines, String> {
+            Ok(ControlLines::default())
+        }
+        async fn bytes_available(&self) -> Result<usize, String> {
+            Ok(self.rx.buf.lock().unwrap().len())
+        }
+        async fn reconfigure(&self, _config: &SerialConfig) -> Result<(), String> {
+            Ok(())
+        }
+        fn is_open(&self) -> bool {
+            true
+        }
+        fn port_name(&self) -> &str {
+            "memory"
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct MemorySink {
+        partial: HashMap<String, Vec<u8>>,
+        files: Vec<(String, Vec<u8>)>,
+        current: Option<(String, Vec<u8>)>,
+    }
+
+    impl TransferSink for MemorySink {
+        fn begin_file(&mut self, name: &str, _size: Option<u64>) -> Result<Option<u64>, String> {
+            let data = self.partial.remove(name).unwrap_or_default();
+            let offset = data.len() as u64;
+            self.current = Some((name.to_string(), data));
+            Ok(Some(offset))
+        }
+        fn write(&mut self, data: &[u8]) -> Result<(), String> {
+            self.current.as_mut().unwrap().1.extend_from_slice(data);
+            Ok(())
+        }
+        fn finish_file(&mut self) -> Result<(), String> {
+            if let Some(file) = self.current.take() {
+                self.files.push(file);
+            }
+            Ok(())
+        }
+    }
+
+    fn options(protocol: TransferProtocol) -> TransferOptions {
+        TransferOptions {
+            transfer_id: "xfer-1".to_string(),
+            session_id: "sess-1".to_string(),
+            protocol,
+            max_retries: 5,
+            timeout: Duration::from_secs(2),
+            in_band: false,
+        }
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 256) as u8).collect()
+    }
+
+    fn file(name: &str, data: Vec<u8>) -> OutgoingFile {
+        OutgoingFile {
+            name: name.to_string(),
+            data,
+        }
+    }
+
+    async fn run(
+        protocol: TransferProtocol,
+        files: Vec<OutgoingFile>,
+        sender: &MemoryTransport,
+        receiver: &MemoryTransport,
+        sink: &mut MemorySink,
+    ) -> (
+        Result<TransferSummary, String>,
+        Result<TransferSummary, String>,
+    ) {
+        let no_cancel = Arc::new(AtomicBool::new(false));
+        let mut tx = TransferEngine::new(sender, options(protocol), no_cancel.clone());
+        let mut rx = TransferEngine::new(receiver, options(protocol), no_cancel);
+        tokio::join!(tx.send(&files), rx.receive(sink))
+    }
+
+    #[tokio::test]
+    async fn test_xmodem_crc_roundtrip_strips_padding() {
+        let (a, b) = memory_pair();
+        let data = pattern(1000);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::XmodemCrc,
+            vec![file("a.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        assert_eq!(sent.unwrap().bytes_transferred, 1000);
+        received.unwrap();
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_xmodem_1k_roundtrip() {
+        let (a, b) = memory_pair();
+        // Two 1K blocks followed by a 128-byte tail block.
+        let data = pattern(2100);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::Xmodem1k,
+            vec![file("a.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        sent.unwrap();
+        received.unwrap();
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_xmodem_recovers_from_corrupt_block() {
+        let (a, b) = memory_pair();
+        *a.corrupt_at.lock().unwrap() = Some(300);
+        let data = pattern(640);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::XmodemCrc,
+            vec![file("a.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        assert!(sent.unwrap().retry_count >= 1);
+        assert!(received.unwrap().error_count >= 1);
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_ymodem_batch_roundtrip() {
+        let (a, b) = memory_pair();
+        let first = pattern(3000);
+        let second = vec![SUB; 10];
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::Ymodem,
+            vec![
+                file("first.bin", first.clone()),
+                file("second.bin", second.clone()),
+                file("empty.txt", Vec::new()),
+            ],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        assert_eq!(sent.unwrap().files.len(), 3);
+        assert_eq!(received.unwrap().bytes_transferred, 3010);
+        assert_eq!(sink.files.len(), 3);
+        assert_eq!(sink.files[0], ("first.bin".to_string(), first));
+        // YMODEM carries the size, so trailing SUB bytes survive.
+        assert_eq!(sink.files[1], ("second.bin".to_string(), second));
+        assert_eq!(sink.files[2], ("empty.txt".to_string(), Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_ymodem_g_roundtrip() {
+        let (a, b) = memory_pair();
+        let data = pattern(5000);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::YmodemG,
+            vec![file("stream.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        sent.unwrap();
+        received.unwrap();
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_zmodem_batch_roundtrip() {
+        let (a, b) = memory_pair();
+        // Every byte value, so ZDLE and flow-control escaping is exercised.
+        let first: Vec<u8> = (0..=255u8).cycle().take(5000).collect();
+        let second = pattern(1024);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::Zmodem,
+            vec![
+                file("first.bin", first.clone()),
+                file("second.bin", second.clone()),
+            ],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        assert_eq!(sent.unwrap().bytes_transferred, 6024);
+        assert_eq!(received.unwrap().files, vec!["first.bin", "second.bin"]);
+        assert_eq!(sink.files[0], ("first.bin".to_string(), first));
+        assert_eq!(sink.files[1], ("second.bin".to_string(), second));
+    }
+
+    #[tokio::test]
+    async fn test_zmodem_resumes_partial_file() {
+        let (a, b) = memory_pair();
+        let data = pattern(8000);
+        let mut sink = MemorySink::default();
+        sink.partial
+            .insert("big.bin".to_string(), data[..3000].to_vec());
+        let (sent, received) = run(
+            TransferProtocol::Zmodem,
+            vec![file("big.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        // Only the missing tail crosses the line.
+        assert_eq!(sent.unwrap().bytes_transferred, 5000);
+        assert_eq!(received.unwrap().bytes_transferred, 5000);
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_zmodem_rewinds_after_corrupt_subpacket() {
+        let (a, b) = memory_pair();
+        *a.corrupt_at.lock().unwrap() = Some(4000);
+        let data = pattern(10_000);
+        let mut sink = MemorySink::default();
+        let (sent, received) = run(
+            TransferProtocol::Zmodem,
+            vec![file("noisy.bin", data.clone())],
+            &a,
+            &b,
+            &mut sink,
+        )
+        .await;
+        assert!(sent.unwrap().retry_count >= 1);
+        assert!(received.unwrap().error_count >= 1);
+        assert_eq!(sink.files[0].1, data);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_sender_aborts_receiver() {
+        let (a, b) = memory_pair();
+        let cancel = Arc::new(AtomicBool::new(true));
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let recorded = states.clone();
+        let mut tx = TransferEngine::new(&a, options(TransferProtocol::Ymodem), cancel)
+            .with_progress(Box::new(move |p| recorded.lock().unwrap().push(p.state)));
+        let mut rx = TransferEngine::new(
+            &b,
+            options(TransferProtocol::Ymodem),
+            Arc::new(AtomicBool::new(false)),
+        );
+        let mut sink = MemorySink::default();
+        let files = [file("a.bin", pattern(100))];
+        let (sent, received) = tokio::join!(tx.send(&files), rx.receive(&mut sink));
+        assert_eq!(sent.unwrap_err(), "Transfer cancelled");
+        assert_eq!(
+            received.unwrap_err(),
+            "Transfer cancelled by the remote side"
+        );
+        assert_eq!(
+            states.lock().unwrap().last(),
+            Some(&TransferState::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_completion() {
+        let (a, b) = memory_pair();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let no_cancel = Arc::new(AtomicBool::new(false));
+        let mut tx = TransferEngine::new(&a, options(TransferProtocol::Zmodem), no_cancel.clone())
+            .with_progress(Box::new(move |p| recorded.lock().unwrap().push(p.clone())));
+        let mut rx = TransferEngine::new(&b, options(TransferProtocol::Zmodem), no_cancel);
+        let mut sink = MemorySink::default();
+        let files = [file("p.bin", pattern(4096))];
+        let (sent, received) = tokio::join!(tx.send(&files), rx.receive(&mut sink));
+        sent.unwrap();
+        received.unwrap();
+        let reports = reports.lock().unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(last.state, TransferState::Completed);
+        assert_eq!(last.direction, TransferDirection::Send);
+        assert_eq!(last.bytes_transferred, 4096);
+        assert_eq!(last.file_name, "p.bin");
+        assert_eq!(last.total_blocks, 4);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_protocol() {
+        let (a, _b) = memory_pair();
+        let mut engine = TransferEngine::new(
+            &a,
+            options(TransferProtocol::Kermit),
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert!(engine.send(&[file("a", vec![1])]).await.is_err());
+    }
+
+    #[test]
+    fn test_file_sink_rejects_unsafe_names() {
+        let sink = FileSink::new("/tmp", true, false, false);
+        assert!(sink.destination("..").is_err());
+        assert_eq!(
+            sink.destination("../etc/passwd").unwrap(),
+            PathBuf::from("/tmp/passwd")
+        );
+        let single = FileSink::new("/tmp/out.bin", false, false, false);
+        assert_eq!(
+            single.destination("ignored").unwrap(),
+            PathBuf::from("/tmp/out.bin")
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! trzsz client for in-band shell transfers.
+//!
+//! Talks to `trz` / `tsz` running in a remote shell.  The protocol is
+//! line based: every frame is `#TYPE:value\n`, where strings and file
+//! data are zlib-compressed and then base64-encoded.  Only the default
+//! text mode is implemented — servers started with `-b` (binary) or
+//! `-d` (directories) are refused.
+
+use crate::serial::protocols::ProgressCallback;
+use crate::serial::transfer::{
+    OutgoingFile, ProgressTracker, TransferOptions, TransferSink, TransferSummary,
+};
+use crate::serial::transport::SerialTransport;
+use crate::serial::types::*;
+use base64::Engine as _;
+use md5::{Digest, Md5};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Marker printed by `trz` / `tsz` before the mode, version and id.
+pub const TRZSZ_MAGIC: &[u8] = b"::TRZSZ:TRANSFER:";
+
+const CLIENT_VERSION: &str = "1.1.6";
+const READ_CHUNK_BYTES: usize = 4096;
+const IDLE_READ_BACKOFF: Duration = Duration::from_millis(2);
+const DATA_CHUNK_BYTES: usize = 16 * 1024;
+const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+const MAX_DECODED_BYTES: u64 = 16 * 1024 * 1024;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Encoding helpers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// zlib-compress then base64-encode, as trzsz does for every payload.
+pub fn encode_trzsz_bytes(data: &[u8]) -> String {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing into a Vec cannot fail.
+    let _ = encoder.write_all(data);
+    let compressed = encoder.finish().unwrap_or_default();
+    base64::engine::general_purpose::STANDARD.encode(compressed)
+}
+
+/// Reverse of [`encode_trzsz_bytes`].
+pub fn decode_trzsz_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .map_err(|e| format!("Invalid trzsz payload: {}", e))?;
+    let mut data = Vec::new();
+    flate2::read::ZlibDecoder::new(compressed.as_slice())
+        .take(MAX_DECODED_BYTES + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Invalid trzsz payload: {}", e))?;
+    if data.len() as u64 > MAX_DECODED_BYTES {
+        return Err("trzsz payload exceeds the size limit".to_string());
+    }
+    Ok(data)
+}
+
+fn decode_trzsz_string(value: &str) -> Result<String, String> {
+    String::from_utf8(decode_trzsz_bytes(value)?)
+        .map_err(|_| "trzsz string is not valid UTF-8".to_string())
+}
+
+/// Transfer mode announced in the trzsz header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrzszMode {
+    /// `tsz`: the remote side sends files to us.
+    Send,
+    /// `trz`: the remote side receives files from us.
+    Receive,
+    /// `trz -d`: the remote side wants a directory.
+    Directory,
+}
+
+/// Parse `::TRZSZ:TRANSFER:<mode>:<version>[:<id>]` from a header line.
+pub fn parse_trzsz_header(line: &[u8]) -> Option<(TrzszMode, String)> {
+    let start = line
+        .windows(TRZSZ_MAGIC.len())
+        .position(|w| w == TRZSZ_MAGIC)?;
+    let rest = String::from_utf8_lossy(&line[start + TRZSZ_MAGIC.len()..]);
+    let mut fields = rest.trim_end().split(':');
+    let mode = match fields.next()? {
+        "S" => TrzszMode::Send,
+        "R" => TrzszMode::Receive,
+        "D" => TrzszMode::Directory,
+        _ => return None,
+    };
+    let version = fields.next()?.to_string();
+    let valid = version.split('.').count() == 3
+        && version
+            .split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    valid.then_some((mode, version))
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//  Client
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[derive(Debug)]
+enum TrzszError {
+    Cancelled,
+    /// The server reported a failure; it has already given up.
+    Remote(String),
+    Failed(String),
+}
+
+impl TrzszError {
+    fn message(&self) -> String {
+        match self {
+            Self::Cancelled => "Transfer cancelled".to_string(),
+            Self::Remote(message) => format!("Remote trzsz failed: {}", message),
+            Self::Failed(message) => message.clone(),
+        }
+    }
+}
+
+impl From<String> for TrzszError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+type TrzszResult<T> = Result<T, TrzszError>;
+
+/// Server settings from the `#CFG:` frame that the client honours.
+#[derive(Debug)]
+struct TrzszConfig {
+    max_buf_size: usize,
+}
+
+/// One trzsz session, started once the header has been detected.
+pub struct TrzszClient<'a> {
+    transport: &'a dyn SerialTransport,
+    cancel: Arc<AtomicBool>,
+    options: TransferOptions,
+    progress: ProgressTracker,
+    pending: Vec<u8>,
+    scratch: Vec<u8>,
+}
+
+impl<'a> TrzszClient<'a> {
+    pub fn new(
+        transport: &'a dyn SerialTransport,
+        options: TransferOptions,
+        cancel: Arc<AtomicBool>,
+    ) -> Self {
+        let progress = ProgressTracker::new(&options, TransferDirection::Send);
+        Self {
+            transport,
+            cancel,
+            options,
+            progress,
+            pending: Vec::new(),
+            scratch: vec![0u8; READ_CHUNK_BYTES],
+        }
+    }
+
+    /// Report progress through `callback` (throttled, plus every state change).
+    pub fn with_progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress.callback = Some(callback);
+        self
+    }
+
+    /// Read the header and return the mode the server asked for.
+    pub async fn read_header(&mut self) -> Result<TrzszMode, String> {
+        let line = self.read_line().await.map_err(|e| e.message())?;
+        parse_trzsz_header(&line)
+            .map(|(mode, _)| mode)
+            .ok_or_else(|| "Malformed trzsz header".to_string())
+    }
+
+    /// Turn the transfer down; the server prints "Cancelled" and exits.
+    pub async fn decline(&mut self) -> Result<(), String> {
+        self.send_action(false).await.map_err(|e| e.message())
+    }
+
+    /// Refuse the transfer with an error the server shows to the user.
+    pub async fn refuse(&mut self, message: &str) {
+        let _ = self.send_string("fail", message).await;
+    }
+
+    /// Upload `files` to a waiting `trz`.
+    pub async fn upload(&mut self, files: &[OutgoingFile]) -> Result<TransferSummary, String> {
+        self.progress.direction = TransferDirection::Send;
+        self.progress.started = Instant::now();
+        let result = self.run_upload(files).await;
+        self.finish(result).await
+    }
+
+    /// Receive the files offered by `tsz` into `sink`.
+    pub async fn download(
+        &mut self,
+        sink: &mut dyn TransferSink,
+    ) -> Result<TransferSummary, String> {
+        self.progress.direction = TransferDirection::Receive;
+        self.progress.started = Instant::now();
+        let result = self.run_download(sink).await;
+        if result.is_err() {
+            let _ = sink.finish_file();
+        }
+        self.finish(result).await
+    }
+
+    async fn finish(&mut self, result: TrzszResult<String>) -> Result<TransferSummary, String> {
+        match result {
+            Ok(message) => {
+                self.progress.report(TransferState::Completed, true);
+                let mut summary = self.progress.summary();
+                summary.message = Some(message).filter(|m| !m.is_empty());
+                Ok(summary)
+            }
+            Err(error) => {
+                let state = match error {
+                    TrzszError::Cancelled => {
+                        self.refuse("Stopped").await;
+                        TransferState::Cancelled
+                    }
+                    TrzszError::Failed(ref message) => {
+                        self.refuse(message).await;
+                        TransferState::Failed
+                    }
+                    TrzszError::Remote(_) => TransferState::Failed,
+                };
+                self.progress.errors = self.progress.errors.saturating_add(1);
+                self.progress.report(state, true);
+                Err(error.message())
+            }
+        }
+    }
+
+    async fn handshake(&mut self) -> TrzszResult<TrzszConfig> {
+        self.send_action(true).await?;
+        let value = self.recv_string("CFG", true).await?;
+        let config: serde_json::Value = serde_json::from_str(&value)
+            .map_err(|e| TrzszError::Failed(format!("Invalid trzsz config: {}", e)))?;
+        let flag = |key: &str| config.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        if flag("binary") {
+            return Err(TrzszError::Failed(
+                "trzsz binary mode (-b) is not supported".to_string(),
+            ));
+        }
+        if flag("directory") {
+            return Err(TrzszError::Failed(
+                "trzsz directory transfers (-d) are not supported".to_string(),
+            ));
+        }
+        let max_buf_size = config
+            .get("max_buf_size")
+            .and_then(|v| v.as_u64())
+            .filter(|&n| n > 0)
+            .map_or(DATA_CHUNK_BYTES, |n| (n as usize).min(DATA_CHUNK_BYTES));
+        Ok(TrzszConfig { max_buf_size })
+    }
+
+    async fn run_upload(&mut self, files: &[OutgoingFile]) -> TrzszResult<String> {
+        let config = self.handshake().await?;
+        self.send_line("NUM", &files.len().to_string()).await?;
+        self.check_integer(files.len() as u64).await?;
+
+        for file in files {
+            self.send_string("NAME", &file.name).await?;
+            self.recv_string("SUCC", false).await?;
+            let size = file.data.len() as u64;
+            self.send_line("SIZE", &size.to_string()).await?;
+            self.check_integer(size).await?;
+
+            self.progress.begin_file(&file.name, size);
+            let mut hasher = Md5::new();
+            for chunk in file.data.chunks(config.max_buf_size) {
+                self.check_cancel()?;
+                self.send_line("DATA", &encode_trzsz_bytes(chunk)).await?;
+                hasher.update(chunk);
+                self.check_integer(chunk.len() as u64).await?;
+                self.progress.advance(chunk.len() as u64);
+            }
+            let digest = hasher.finalize();
+            self.send_line("MD5", &encode_trzsz_bytes(&digest)).await?;
+            let echoed = self.recv_line_of("SUCC", false).await?;
+            if decode_trzsz_bytes(&echoed)? != digest.as_slice() {
+                return Err(TrzszError::Failed(format!(
+                    "MD5 check of {} failed",
+                    file.name
+                )));
+            }
+            self.progress.complete_file();
+        }
+
+        self.recv_string("EXIT", false).await
+    }
+
+    async fn run_download(&mut self, sink: &mut dyn TransferSink) -> TrzszResult<String> {
+        self.handshake().await?;
+        let count = self.recv_integer("NUM").await?;
+        self.send_line("SUCC", &count.to_string()).await?;
+
+        for _ in 0..count {
+            let name = self.recv_string("NAME", false).await?;
+            let local = name
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            self.send_string("SUCC", &local).await?;
+            let size = self.recv_integer("SIZE").await?;
+            if size > MAX_SERIAL_TRANSFER_FILE_BYTES {
+                return Err(TrzszError::Failed(format!(
+                    "{} exceeds the {} byte transfer limit",
+                    local, MAX_SERIAL_TRANSFER_FILE_BYTES
+                )));
+            }
+            match sink.begin_file(&local, Some(size))? {
+                Some(0) => {}
+                Some(_) => {
+                    return Err(TrzszError::Failed(
+                        "trzsz cannot resume partial files".to_string(),
+                    ))
+                }
+                None => return Err(TrzszError::Failed(format!("{} already exists", local))),
+            }
+            self.send_line("SUCC", &size.to_string()).await?;
+
+            self.progress.begin_file(&local, size);
+            let mut hasher = Md5::new();
+            let mut received = 0u64;
+            while received < size {
+                self.check_cancel()?;
+                let value = self.recv_line_of("DATA", false).await?;
+                let data = decode_trzsz_bytes(&value)?;
+                received = received.saturating_add(data.len() as u64);
+                if received > size {
+                    return Err(TrzszError::Failed(format!(
+                        "{} is larger than announced",
+                        local
+                    )));
+                }
+                sink.write(&data)?;
+                hasher.update(&data);
+                self.send_line("SUCC", &data.len().to_string()).await?;
+                self.progress.advance(data.len() as u64);
+            }
+            let digest = hasher.finalize();
+            let expected = decode_trzsz_bytes(&self.recv_line_of("MD5", false).await?)?;
+            if expected != digest.as_slice() {
+                return Err(TrzszError::Failed(format!("MD5 check of {} failed", local)));
+            }
+            self.send_line("SUCC", &encode_trzsz_bytes(&digest)).await?;
+            sink.finish_file()?;
+            self.progress.complete_file();
+        }
+
+        self.recv_string("EXIT", false).await
+    }
+
+    // ── Frames ────────────────────────────────────────────────────
+
+    async fn send_action(&mut self, confirm: bool) -> TrzszResult<()> {
+        let action = serde_json::json!({
+            "lang": "rust",
+            "confirm": confirm,
+            "version": CLIENT_VERSION,
+            "support_dir": false,
+        });
+        self.send_string("ACT", &action.to_string()).await
+    }
+
+    async fn send_line(&mut self, frame: &str, value: &str) -> TrzszResult<()> {
+        let line = format!("#{}:{}\n", frame, value);
+        let mut data = line.as_bytes();
+        while !data.is_empty() {
+            let n = self
+                .transport
+                .write(data)
+                .await
+                .map_err(TrzszError::Failed)?;
+            if n == 0 {
+                return Err(TrzszError::Failed("Terminal accepted no bytes".to_string()));
+            }
+            data = &data[n.min(data.len())..];
+        }
+        Ok(())
+    }
+
+    async fn send_string(&mut self, frame: &str, value: &str) -> TrzszResult<()> {
+        self.send_line(frame, &encode_trzsz_bytes(value.as_bytes()))
+            .await
+    }
+
+    /// Value of the next `#<frame>:` line.  `junk` tolerates shell noise
+    /// in front of the frame on the same line.
+    async fn recv_line_of(&mut self, frame: &str, junk: bool) -> TrzszResult<String> {
+        let line = self.read_line().await?;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\r');
+        let start = if junk {
+            line.rfind(&format!("#{}:", frame))
+                .or_else(|| line.rfind('#'))
+        } else {
+            line.find('#')
+        }
+        .ok_or_else(|| TrzszError::Failed(format!("Expected trzsz {} frame", frame)))?;
+        let (kind, value) = line[start + 1..]
+            .split_once(':')
+            .ok_or_else(|| TrzszError::Failed(format!("Expected trzsz {} frame", frame)))?;
+        if kind.eq_ignore_ascii_case("fail") {
+            let message = decode_trzsz_string(value).unwrap_or_else(|_| value.to_string());
+            return Err(TrzszError::Remote(message));
+        }
+        if kind != frame {
+            return Err(TrzszError::Failed(format!(
+                "Expected trzsz {} frame, got {}",
+                frame, kind
+            )));
+        }
+        Ok(value.to_string())
+    }
+
+    async fn recv_string(&mut self, frame: &str, junk: bool) -> TrzszResult<String> {
+        let value = self.recv_line_of(frame, junk).await?;
+        Ok(decode_trzsz_string(&value)?)
+    }
+
+    async fn recv_integer(&mut self, frame: &str) -> TrzszResult<u64> {
+        let value = self.recv_line_of(frame, false).await?;
+        value
+            .trim()
+            .parse()
+            .map_err(|_| TrzszError::Failed(format!("Invalid trzsz {} value", frame)))
+    }
+
+    async fn check_integer(&mut self, expected: u64) -> TrzszResult<()> {
+        let value = self.recv_integer("SUCC").await?;
+        if value != expected {
+            return Err(TrzszError::Failed(format!(
+                "trzsz acknowledged {} instead of {}",
+                value, expected
+            )));
+        }
+        Ok(())
+    }
+
+    // ── Byte input ────────────────────────────────────────────────
+
+    fn check_cancel(&self) -> TrzszResult<()> {
+        if self.cancel.load(Ordering::SeqCst) {
+            Err(TrzszError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Next `\n`-terminated line, without the terminator.
+    ///
+    /// Like the ZMODEM link, transport reads are never raced against a
+    /// timer; the deadline is checked between reads.
+    async fn read_line(&mut self) -> TrzszResult<Vec<u8>> {
+        let deadline = Instant::now() + self.options.timeout;
+        let mut searched = 0;
+        loop {
+            if let Some(end) = self.pending[searched..].iter().position(|&b| b == b'\n') {
+                let end = searched + end;
+                let line = self.pending[..end].to_vec();
+                self.pending.drain(..=end);
+                return Ok(line);
+            }
+            searched = self.pending.len();
+            if self.pending.len() > MAX_LINE_BYTES {
+                return Err(TrzszError::Failed(
+                    "trzsz line exceeds the size limit".to_string(),
+                ));
+            }
+            self.check_cancel()?;
+            if Instant::now() >= deadline {
+                return Err(TrzszError::Failed(
+                    "Transfer timed out waiting for the remote side".to_string(),
+                ));
+            }
+            let n = self
+                .transport
+                .read(&mut self.scratch)
+                .await
+                .map_err(TrzszError::Failed)?;
+            if n == 0 {
+                tokio::time::sleep(IDLE_READ_BACKOFF).await;
+            } else {
+                let n = n.min(self.scratch.len());
+                self.pending.extend_from_slice(&self.scratch[..n]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trzsz_payload_roundtrip() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let encoded = encode_trzsz_bytes(&data);
+        assert!(encoded.bytes().all(|b| b.is_ascii_graphic()));
+        assert_eq!(decode_trzsz_bytes(&encoded).unwrap(), data);
+        assert!(decode_trzsz_bytes("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_parse_trzsz_header() {
+        let (mode, version) =
+            parse_trzsz_header(b"\x1b7\x07::TRZSZ:TRANSFER:R:1.1.5:1697623456789\r").unwrap();
+        assert_eq!(mode, TrzszMode::Receive);
+        assert_eq!(version, "1.1.5");
+        assert_eq!(
+            parse_trzsz_header(b"::TRZSZ:TRANSFER:S:1.0.0").unwrap().0,
+            TrzszMode::Send
+        );
+        assert!(parse_trzsz_header(b"::TRZSZ:TRANSFER:X:1.0.0").is_none());
+        assert!(parse_trzsz_header(b"::TRZSZ:TRANSFER:S:latest").is_none());
+    }
+}
@@ -224,6 +224,8 @@ pub const MAX_SERIAL_SESSION_ID_BYTES: usize = 128;
 pub const MAX_SERIAL_BREAK_MS: u32 = 60 * 1000;
 pub const MAX_SERIAL_MODEM_COMMAND_BYTES: usize = 4096;
 pub const MAX_SERIAL_MODEM_RESPONSE_BYTES: usize = 1024 * 1024;
+pub const MAX_SERIAL_TRANSFER_FILE_BYTES: u64 = 64 * 1024 * 1024;
+pub const MAX_SERIAL_TRANSFER_RETRIES: u32 = 100;
 
 /// Complete serial port configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -687,6 +689,8 @@ pub enum TransferProtocol {
     Ascii,
     Kermit,
     Raw,
+    /// trzsz (`trz` / `tsz`) in-band shell transfers.
+    Trzsz,
 }
 
 impl TransferProtocol {
@@ -701,6 +705,7 @@ impl TransferProtocol {
             Self::Ascii => "ASCII",
             Self::Kermit => "Kermit",
             Self::Raw => "Raw",
+            Self::Trzsz => "trzsz",
         }
     }
 
@@ -708,7 +713,7 @@ impl TransferProtocol {
         match self {
             Self::Xmodem | Self::XmodemCrc => 128,
             Self::Xmodem1k | Self::Ymodem | Self::YmodemG => 1024,
-            Self::Zmodem => 1024,
+            Self::Zmodem | Self::Trzsz => 1024,
             Self::Kermit => 94,
             Self::Ascii | Self::Raw => 0,
         }
@@ -765,6 +770,9 @@ pub struct TransferConfig {
     pub file_path: String,
     #[serde(default)]
     pub overwrite: bool,
+    /// Resume partially received files (ZMODEM only, via ZRPOS).
+    #[serde(default)]
+    pub resume: bool,
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
     #[serde(default = "default_timeout_ms")]
@@ -778,6 +786,31 @@ fn default_timeout_ms() -> u64 {
     30000
 }
 
+/// Direction of an in-band shell transfer, seen from the local side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InbandDirection {
+    /// The remote shell runs `rz` / `trz`; local files are sent.
+    Upload,
+    /// The remote shell runs `sz` / `tsz`; remote files are received.
+    Download,
+}
+
+/// The user's answer to an in-band transfer request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InbandSelection {
+    /// Local files to upload.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Directory that receives downloaded files.
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// Replace existing files when downloading.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 //  Logging
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -942,6 +975,30 @@ pub struct TransferProgressEvent {
     pub progress: TransferProgress,
 }
 
+/// A remote shell started an in-band transfer and needs a file choice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InbandTransferRequestEvent {
+    pub session_id: String,
+    pub transfer_id: String,
+    pub protocol: TransferProtocol,
+    pub direction: InbandDirection,
+}
+
+/// An in-band transfer ended and the terminal is live again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InbandTransferFinishedEvent {
+    pub session_id: String,
+    pub transfer_id: String,
+    pub protocol: TransferProtocol,
+    pub direction: InbandDirection,
+    pub state: TransferState,
+    pub files: Vec<String>,
+    pub bytes_transferred: u64,
+    pub message: Option<String>,
+}
+
 /// Modem event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -5,7 +5,7 @@
 
 use super::dir_ops::DiskUsageResult;
 use super::types::*;
-use super::watch::{SyncResult, WatchInfo};
+use super::watch::{SyncOptions, SyncResult, WatchInfo};
 
 // ── Connection / session ─────────────────────────────────────────────────────
 
@@ -542,9 +542,16 @@ pub async fn sftp_sync_pull(
     session_id: String,
     remote_path: String,
     local_path: String,
+    options: Option<SyncOptions>,
 ) -> Result<SyncResult, String> {
     let mut svc = state.lock().await;
-    svc.sync_pull(&session_id, &remote_path, &local_path).await
+    svc.sync_pull(
+        &session_id,
+        &remote_path,
+        &local_path,
+        options.unwrap_or_default(),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -553,9 +560,16 @@ pub async fn sftp_sync_push(
     session_id: String,
     local_path: String,
     remote_path: String,
+    options: Option<SyncOptions>,
 ) -> Result<SyncResult, String> {
     let mut svc = state.lock().await;
-    svc.sync_push(&session_id, &local_path, &remote_path).await
+    svc.sync_push(
+        &session_id,
+        &local_path,
+        &remote_path,
+        options.unwrap_or_default(),
+    )
+    .await
 }
 
 // ── Bookmarks ────────────────────────────────────────────────────────────────
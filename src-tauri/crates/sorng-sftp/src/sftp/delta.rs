@@ -0,0 +1,1119 @@
+// ── Delta synchronisation – rolling block signatures & reconstruction ──────
+//
+// rsync-style delta transfer for `sync_pull` / `sync_push`. One side splits
+// its file into blocks and sends a signature: a weak rolling checksum
+// (Adler-32, as computed by zlib) and a SHA-256 per block. The other side
+// slides a window over its file one byte at a time, rolling the weak
+// checksum, and confirms every weak hit with the SHA-256 before reusing the
+// block. Matches are found at any offset, so data shifted by an insertion or
+// deletion is still reused. The remote side is handled by small helpers run
+// over an exec channel (python3, falling back to perl), so the server needs a
+// POSIX shell but no rsync. Reconstruction is always atomic:
+//
+//   • pull: the remote helper signs the new version; the old local file is
+//     scanned for its blocks. Located blocks are copied locally, the rest are
+//     fetched over SFTP into a staging file, which is verified against the
+//     remote whole-file hash and renamed over the destination.
+//   • push: the remote helper signs the old version; the new local file is
+//     scanned against it. The remote file is copied server-side to a hidden
+//     temp file, which a second helper rewrites from the streamed
+//     instructions (copy a range of the old file, or take literal bytes),
+//     then reports its whole-file hash. Once verified, it is `mv`-ed over the
+//     destination.
+//
+// Any failure leaves the destination untouched; callers fall back to a
+// whole-file transfer.
+
+use crate::sftp::service::SftpService;
+use crate::sftp::transfer::{
+    commit_staged_download, staging_path, validate_remote_file_path, PartialDownloadGuard,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use uuid::Uuid;
+
+pub const MIN_BLOCK_SIZE: u64 = 4 * 1024;
+pub const MAX_BLOCK_SIZE: u64 = 16 * 1024 * 1024;
+const DEFAULT_MIN_BLOCK_SIZE: u64 = 64 * 1024;
+/// Automatic block sizes keep a file under this many blocks.
+const TARGET_MAX_BLOCKS: u64 = 16 * 1024;
+const MAX_HELPER_OUTPUT: u64 = 64 * 1024 * 1024;
+/// Largest range a single reconstruction instruction covers.
+const MAX_INSTRUCTION_LEN: u64 = 1024 * 1024;
+const SCAN_READ_SIZE: usize = 256 * 1024;
+const ADLER_MOD: u32 = 65521;
+
+/// Prints the file size, one `<adler32> <sha256>` line per block, then `=`
+/// and the whole-file SHA-256. The helpers are written without single quotes
+/// so they can be shell-quoted as is.
+const SIGNATURE_PYTHON: &str = "import hashlib,os,sys,zlib
+f=open(sys.argv[1],\"rb\");b=int(sys.argv[2]);w=hashlib.sha256()
+print(os.fstat(f.fileno()).st_size)
+while True:
+    d=f.read(b)
+    if not d: break
+    w.update(d);print(\"%08x %s\"%(zlib.adler32(d),hashlib.sha256(d).hexdigest()))
+print(\"=\"+w.hexdigest())";
+
+const SIGNATURE_PERL: &str = "use Digest::SHA;use Compress::Zlib;\
+open(F,\"<\",$ARGV[0]) or exit 2;binmode F;\
+my $w=Digest::SHA->new(256);print((-s F),\"\\n\");\
+while(read(F,my $d,$ARGV[1])){$w->add($d);\
+printf(\"%08x %s\\n\",Compress::Zlib::adler32($d),Digest::SHA::sha256_hex($d));}\
+print \"=\",$w->hexdigest,\"\\n\";";
+
+/// Rewrites the target (a copy of the basis) from instructions on stdin:
+/// `R` + u64 offset + u32 length copies a range of the basis, `S` + u32
+/// length + bytes writes literal data. Truncates the target to what was
+/// written and prints `=` and its SHA-256.
+const APPLY_PYTHON: &str = "import hashlib,struct,sys
+s=open(sys.argv[1],\"rb\");d=open(sys.argv[2],\"r+b\");i=sys.stdin.buffer;w=hashlib.sha256()
+def rd(f,n):
+    b=f.read(n)
+    if len(b)!=n: sys.exit(3)
+    return b
+while True:
+    op=i.read(1)
+    if not op: break
+    if op==b\"R\":
+        o,n=struct.unpack(\">QI\",rd(i,12));s.seek(o);b=rd(s,n)
+    elif op==b\"S\":
+        b=rd(i,struct.unpack(\">I\",rd(i,4))[0])
+    else: sys.exit(3)
+    w.update(b);d.write(b)
+d.truncate();d.close()
+print(\"=\"+w.hexdigest())";
+
+const APPLY_PERL: &str = "use Digest::SHA;\
+open(S,\"<\",$ARGV[0]) or exit 2;open(D,\"+<\",$ARGV[1]) or exit 2;\
+binmode S;binmode D;binmode STDIN;my $w=Digest::SHA->new(256);\
+sub rd{my($f,$n)=@_;my $b=\"\";\
+while(length($b)<$n){read($f,$b,$n-length($b),length($b)) or exit 3;}return $b;}\
+while(read(STDIN,my $op,1)){my $b;\
+if($op eq \"R\"){my($h,$l,$n)=unpack(\"NNN\",rd(\\*STDIN,12));\
+seek(S,$h*4294967296+$l,0) or exit 3;$b=rd(\\*S,$n);}\
+elsif($op eq \"S\"){$b=rd(\\*STDIN,unpack(\"N\",rd(\\*STDIN,4)));}\
+else{exit 3;}\
+$w->add($b);print D $b or exit 3;}\
+truncate(D,tell(D)) or exit 3;close(D) or exit 3;print \"=\",$w->hexdigest,\"\\n\";";
+
+/// Weak and strong checksum of one block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHash {
+    /// Adler-32, as computed by zlib.
+    pub weak: u32,
+    /// SHA-256, hex-encoded.
+    pub strong: String,
+}
+
+/// Per-block checksums of one file version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSignature {
+    pub block_size: u64,
+    pub size: u64,
+    pub blocks: Vec<BlockHash>,
+    pub file_sha256: String,
+}
+
+impl BlockSignature {
+    fn block_len(&self, index: usize) -> u64 {
+        self.block_size
+            .min(self.size.saturating_sub(index as u64 * self.block_size))
+    }
+}
+
+/// One step of rebuilding the new version, in new-file order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaOp {
+    /// Copy `len` bytes at `offset` of the version the destination holds.
+    Reuse { offset: u64, len: u64 },
+    /// Send `len` bytes at `offset` of the new version.
+    Send { offset: u64, len: u64 },
+}
+
+/// How to rebuild the new version from the destination's current content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaPlan {
+    pub size: u64,
+    pub ops: Vec<DeltaOp>,
+    pub bytes_transferred: u64,
+    pub bytes_reused: u64,
+    /// SHA-256 of the new version, checked after reconstruction.
+    pub file_sha256: String,
+}
+
+impl DeltaPlan {
+    fn new(file_sha256: String) -> Self {
+        Self {
+            size: 0,
+            ops: Vec::new(),
+            bytes_transferred: 0,
+            bytes_reused: 0,
+            file_sha256,
+        }
+    }
+
+    /// Append an op, merging it into the previous one when they are
+    /// contiguous.
+    fn push(&mut self, op: DeltaOp) {
+        match op {
+            DeltaOp::Reuse { len, .. } => self.bytes_reused += len,
+            DeltaOp::Send { len, .. } => self.bytes_transferred += len,
+        }
+        self.size = self.bytes_reused + self.bytes_transferred;
+        match (self.ops.last_mut(), op) {
+            (
+                Some(DeltaOp::Reuse { offset, len }),
+                DeltaOp::Reuse {
+                    offset: next,
+                    len: more,
+                },
+            )
+            | (
+                Some(DeltaOp::Send { offset, len }),
+                DeltaOp::Send {
+                    offset: next,
+                    len: more,
+                },
+            ) if *offset + *len == next => *len += more,
+            _ => self.ops.push(op),
+        }
+    }
+}
+
+/// Block size for a file of `size` bytes, honouring an explicit request.
+pub fn choose_block_size(size: u64, requested: Option<u64>) -> u64 {
+    if let Some(requested) = requested {
+        return requested.clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE);
+    }
+    size.div_ceil(TARGET_MAX_BLOCKS)
+        .max(DEFAULT_MIN_BLOCK_SIZE)
+        .next_power_of_two()
+        .min(MAX_BLOCK_SIZE)
+}
+
+/// Adler-32 over a sliding window, updated one byte at a time.
+#[derive(Debug, Clone, Copy)]
+struct RollingChecksum {
+    a: u32,
+    b: u32,
+    /// Window length, reduced modulo `ADLER_MOD`.
+    len: u32,
+}
+
+impl RollingChecksum {
+    fn new(window: &[u8]) -> Self {
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in window {
+            a = (a + u32::from(byte)) % ADLER_MOD;
+            b = (b + a) % ADLER_MOD;
+        }
+        Self {
+            a,
+            b,
+            len: (window.len() as u64 % u64::from(ADLER_MOD)) as u32,
+        }
+    }
+
+    fn value(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+
+    /// Slide the window one byte: `out` leaves at the front, `next` enters
+    /// at the back.
+    fn roll(&mut self, out: u8, next: u8) {
+        let modulus = u64::from(ADLER_MOD);
+        self.a = (self.a + ADLER_MOD - u32::from(out) + u32::from(next)) % ADLER_MOD;
+        let removed = u64::from(self.len) * u64::from(out) % modulus;
+        self.b =
+            ((u64::from(self.b) + 2 * modulus - removed + u64::from(self.a) - 1) % modulus) as u32;
+    }
+}
+
+/// A file read through a window that slides forward over it, hashing every
+/// byte as it is read.
+struct Window<R> {
+    reader: R,
+    buf: Vec<u8>,
+    start: usize,
+    /// File offset of `buf[0]`.
+    base: u64,
+    eof: bool,
+    whole: Sha256,
+}
+
+impl<R: Read> Window<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            start: 0,
+            base: 0,
+            eof: false,
+            whole: Sha256::new(),
+        }
+    }
+
+    /// Read ahead until `need` bytes are buffered past the window start or
+    /// the file ends; returns how many are buffered.
+    fn fill(&mut self, need: usize) -> std::io::Result<usize> {
+        while self.buf.len() - self.start < need && !self.eof {
+            self.buf.drain(..self.start);
+            self.base += self.start as u64;
+            self.start = 0;
+            let filled = self.buf.len();
+            let want = need.max(SCAN_READ_SIZE);
+            self.buf.resize(filled + want, 0);
+            let n = read_full(&mut self.reader, &mut self.buf[filled..])?;
+            self.buf.truncate(filled + n);
+            self.whole.update(&self.buf[filled..]);
+            self.eof = n < want;
+        }
+        Ok(self.buf.len() - self.start)
+    }
+
+    fn offset(&self) -> u64 {
+        self.base + self.start as u64
+    }
+
+    fn bytes(&self, len: usize) -> &[u8] {
+        &self.buf[self.start..self.start + len]
+    }
+}
+
+/// Blocks of `signature` found in a file, with the file's size and hash.
+struct Scan {
+    /// `(offset in the scanned file, block index)`, in file order and
+    /// non-overlapping.
+    matches: Vec<(u64, usize)>,
+    size: u64,
+    file_sha256: String,
+}
+
+/// Slide a block-sized window over `reader` looking for the blocks of
+/// `signature`. A weak-checksum hit counts only if the SHA-256 agrees. A
+/// short final block can only match at the end of the scanned file.
+fn scan_for_blocks(reader: impl Read, signature: &BlockSignature) -> std::io::Result<Scan> {
+    let block_size = signature.block_size as usize;
+    let mut by_weak: HashMap<u32, Vec<usize>> = HashMap::new();
+    let mut short_block = None;
+    for (index, block) in signature.blocks.iter().enumerate() {
+        if signature.block_len(index) == signature.block_size {
+            by_weak.entry(block.weak).or_default().push(index);
+        } else {
+            short_block = Some(index);
+        }
+    }
+    let lookup = |window: &[u8], weak: u32| {
+        let candidates = by_weak.get(&weak)?;
+        let strong = hex::encode(Sha256::digest(window));
+        candidates
+            .iter()
+            .copied()
+            .find(|&index| signature.blocks[index].strong == strong)
+    };
+
+    let mut window = Window::new(reader);
+    let mut matches = Vec::new();
+    let mut rolling: Option<RollingChecksum> = None;
+    while window.fill(block_size)? >= block_size {
+        let checksum =
+            *rolling.get_or_insert_with(|| RollingChecksum::new(window.bytes(block_size)));
+        if let Some(index) = lookup(window.bytes(block_size), checksum.value()) {
+            matches.push((window.offset(), index));
+            window.start += block_size;
+            rolling = None;
+            continue;
+        }
+        if window.fill(block_size + 1)? <= block_size {
+            break;
+        }
+        let bytes = window.bytes(block_size + 1);
+        let (out, next) = (bytes[0], bytes[block_size]);
+        if let Some(checksum) = rolling.as_mut() {
+            checksum.roll(out, next);
+        }
+        window.start += 1;
+    }
+
+    // The window no longer fits, so the whole file has been read.
+    let remaining = window.buf.len() - window.start;
+    if let Some(index) = short_block {
+        let len = signature.block_len(index) as usize;
+        let tail = &window.buf[window.buf.len() - len.min(remaining)..];
+        if len <= remaining && hex::encode(Sha256::digest(tail)) == signature.blocks[index].strong {
+            matches.push((window.offset() + (remaining - len) as u64, index));
+        }
+    }
+    Ok(Scan {
+        matches,
+        size: window.offset() + remaining as u64,
+        file_sha256: hex::encode(window.whole.finalize()),
+    })
+}
+
+fn open_local(path: &Path) -> Result<std::fs::File, String> {
+    std::fs::File::open(path).map_err(|e| format!("Cannot open '{}': {}", path.display(), e))
+}
+
+/// Plan sending the local file `new` to a destination whose current content
+/// has signature `old`: `old`'s blocks are reused wherever they occur in
+/// `new`, everything in between is sent.
+pub fn plan_delta(new: &Path, old: &BlockSignature) -> Result<DeltaPlan, String> {
+    let scan = scan_for_blocks(std::io::BufReader::new(open_local(new)?), old)
+        .map_err(|e| format!("Cannot read '{}': {}", new.display(), e))?;
+    let mut plan = DeltaPlan::new(scan.file_sha256);
+    let mut position = 0;
+    for (offset, index) in scan.matches {
+        if offset > position {
+            plan.push(DeltaOp::Send {
+                offset: position,
+                len: offset - position,
+            });
+        }
+        let len = old.block_len(index);
+        plan.push(DeltaOp::Reuse {
+            offset: index as u64 * old.block_size,
+            len,
+        });
+        position = offset + len;
+    }
+    if scan.size > position {
+        plan.push(DeltaOp::Send {
+            offset: position,
+            len: scan.size - position,
+        });
+    }
+    Ok(plan)
+}
+
+/// Plan fetching the version with signature `new` onto the local file
+/// `old`: each block of `new` found anywhere in `old` is copied locally,
+/// the others are fetched.
+pub fn plan_delta_from_signature(new: &BlockSignature, old: &Path) -> Result<DeltaPlan, String> {
+    let scan = scan_for_blocks(std::io::BufReader::new(open_local(old)?), new)
+        .map_err(|e| format!("Cannot read '{}': {}", old.display(), e))?;
+    let located: HashMap<&str, u64> = scan
+        .matches
+        .iter()
+        .map(|&(offset, index)| (new.blocks[index].strong.as_str(), offset))
+        .collect();
+    let mut plan = DeltaPlan::new(new.file_sha256.clone());
+    for (index, block) in new.blocks.iter().enumerate() {
+        let len = new.block_len(index);
+        plan.push(match located.get(block.strong.as_str()) {
+            Some(&offset) => DeltaOp::Reuse { offset, len },
+            None => DeltaOp::Send {
+                offset: index as u64 * new.block_size,
+                len,
+            },
+        });
+    }
+    Ok(plan)
+}
+
+/// Checksum a local file block by block.
+pub fn local_signature(path: &Path, block_size: u64) -> Result<BlockSignature, String> {
+    let mut file = open_local(path)?;
+    let mut whole = Sha256::new();
+    let mut blocks = Vec::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; block_size as usize];
+    loop {
+        let n = read_full(&mut file, &mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        whole.update(&buf[..n]);
+        blocks.push(BlockHash {
+            weak: RollingChecksum::new(&buf[..n]).value(),
+            strong: hex::encode(Sha256::digest(&buf[..n])),
+        });
+        size += n as u64;
+        if n < buf.len() {
+            break;
+        }
+    }
+    Ok(BlockSignature {
+        block_size,
+        size,
+        blocks,
+        file_sha256: hex::encode(whole.finalize()),
+    })
+}
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parse the signature helper's output.
+pub fn parse_signature(output: &str, block_size: u64) -> Result<BlockSignature, String> {
+    let invalid = || "Remote hash helper produced malformed output".to_string();
+    let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+    let size: u64 = lines
+        .next()
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(invalid)?;
+    let mut blocks = Vec::new();
+    let mut file_sha256 = None;
+    for line in lines {
+        if let Some(hash) = line.strip_prefix('=') {
+            file_sha256 = Some(hash.to_string());
+            break;
+        }
+        let block = line
+            .split_once(' ')
+            .filter(|(weak, strong)| weak.len() == 8 && is_sha256_hex(strong))
+            .and_then(|(weak, strong)| {
+                Some(BlockHash {
+                    weak: u32::from_str_radix(weak, 16).ok()?,
+                    strong: strong.to_string(),
+                })
+            })
+            .ok_or_else(invalid)?;
+        blocks.push(block);
+    }
+    let file_sha256 = file_sha256.ok_or_else(invalid)?;
+    if !is_sha256_hex(&file_sha256) || blocks.len() as u64 != size.div_ceil(block_size) {
+        return Err(invalid());
+    }
+    Ok(BlockSignature {
+        block_size,
+        size,
+        blocks,
+        file_sha256,
+    })
+}
+
+/// Shell command that runs the signature helper on `path`.
+pub fn signature_command(path: &str, block_size: u64) -> String {
+    helper_command(signature_invocations(path, block_size))
+}
+
+/// Shell command that runs the reconstruction helper, reading from `basis`
+/// and rewriting `target`.
+pub fn apply_command(basis: &str, target: &str) -> String {
+    helper_command(apply_invocations(basis, target))
+}
+
+fn signature_invocations(path: &str, block_size: u64) -> [String; 2] {
+    helper_invocations(
+        SIGNATURE_PYTHON,
+        SIGNATURE_PERL,
+        &[helper_path_arg(path), block_size.to_string()],
+    )
+}
+
+fn apply_invocations(basis: &str, target: &str) -> [String; 2] {
+    helper_invocations(
+        APPLY_PYTHON,
+        APPLY_PERL,
+        &[helper_path_arg(basis), helper_path_arg(target)],
+    )
+}
+
+/// The python3 and perl invocations of a helper. Perl keeps parsing switches
+/// after `-e`, so its arguments follow `--`.
+fn helper_invocations(python: &str, perl: &str, args: &[String]) -> [String; 2] {
+    let args = args.join(" ");
+    [
+        format!("python3 -c {} {args}", shell_quote(python)),
+        format!("perl -e {} -- {args}", shell_quote(perl)),
+    ]
+}
+
+fn helper_command([python, perl]: [String; 2]) -> String {
+    format!(
+        "if command -v python3 >/dev/null 2>&1; then exec {python}; \
+         elif command -v perl >/dev/null 2>&1; then exec {perl}; \
+         else exit 127; fi"
+    )
+}
+
+/// Quote a path for a helper's argument list; a leading `-` gets `./` so no
+/// interpreter can read it as an option.
+fn helper_path_arg(path: &str) -> String {
+    if path.starts_with('-') {
+        shell_quote(&format!("./{}", path))
+    } else {
+        shell_quote(path)
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Pass `len` bytes at `offset` of `reader` to `sink`, at most `buf.len()`
+/// at a time. `name` labels read errors.
+fn copy_range(
+    reader: &mut (impl Read + Seek),
+    name: &str,
+    offset: u64,
+    len: u64,
+    buf: &mut [u8],
+    mut sink: impl FnMut(&[u8]) -> Result<(), String>,
+) -> Result<(), String> {
+    reader
+        .seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Cannot read '{}': {}", name, e))?;
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(buf.len() as u64) as usize;
+        let chunk = &mut buf[..n];
+        reader
+            .read_exact(chunk)
+            .map_err(|e| format!("Cannot read '{}': {}", name, e))?;
+        sink(chunk)?;
+        left -= chunk.len() as u64;
+    }
+    Ok(())
+}
+
+/// Encode `plan` as instructions for the reconstruction helper, taking
+/// literal data from `source` (the new version).
+fn write_instructions(
+    plan: &DeltaPlan,
+    source: &mut (impl Read + Seek),
+    source_name: &str,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let write_err = |e: std::io::Error| format!("Cannot send delta instructions: {}", e);
+    let mut buf = vec![0u8; MAX_INSTRUCTION_LEN as usize];
+    for op in &plan.ops {
+        match *op {
+            DeltaOp::Reuse { offset, len } => {
+                let mut done = 0;
+                while done < len {
+                    let chunk = (len - done).min(MAX_INSTRUCTION_LEN);
+                    out.write_all(b"R")
+                        .and_then(|_| out.write_all(&(offset + done).to_be_bytes()))
+                        .and_then(|_| out.write_all(&(chunk as u32).to_be_bytes()))
+                        .map_err(write_err)?;
+                    done += chunk;
+                }
+            }
+            DeltaOp::Send { offset, len } => {
+                copy_range(source, source_name, offset, len, &mut buf, |chunk| {
+                    out.write_all(b"S")
+                        .and_then(|_| out.write_all(&(chunk.len() as u32).to_be_bytes()))
+                        .and_then(|_| out.write_all(chunk))
+                        .map_err(write_err)
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remote_temp_path(remote_path: &str) -> Result<String, String> {
+    let (parent, name) = remote_path
+        .rsplit_once('/')
+        .ok_or_else(|| "Remote path has no parent directory".to_string())?;
+    Ok(format!(
+        "{}/.{}.sorng-delta-{}",
+        parent,
+        name,
+        Uuid::new_v4().simple()
+    ))
+}
+
+impl SftpService {
+    /// Run `command` on the remote host and return its stdout. Fails on a
+    /// non-zero exit status.
+    pub(crate) fn exec_remote(
+        &mut self,
+        session_id: &str,
+        command: &str,
+    ) -> Result<String, String> {
+        self.exec_remote_with_input(session_id, command, |_| Ok(()))
+    }
+
+    /// Like `exec_remote`, but first streams the command's stdin through
+    /// `input`; the command sees end-of-file once it returns.
+    pub(crate) fn exec_remote_with_input(
+        &mut self,
+        session_id: &str,
+        command: &str,
+        input: impl FnOnce(&mut ssh2::Channel) -> Result<(), String>,
+    ) -> Result<String, String> {
+        let handle = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
+        let mut channel = handle
+            .session
+            .channel_session()
+            .map_err(|e| format!("Exec channel error: {}", e))?;
+        channel
+            .exec(command)
+            .map_err(|e| format!("Remote exec failed: {}", e))?;
+        input(&mut channel)?;
+        channel
+            .send_eof()
+            .map_err(|e| format!("Remote exec write failed: {}", e))?;
+        let mut output = Vec::new();
+        (&mut channel)
+            .take(MAX_HELPER_OUTPUT + 1)
+            .read_to_end(&mut output)
+            .map_err(|e| format!("Remote exec read failed: {}", e))?;
+        let _ = channel.wait_close();
+        if output.len() as u64 > MAX_HELPER_OUTPUT {
+            return Err("Remote command output exceeds the size limit".to_string());
+        }
+        let status = channel
+            .exit_status()
+            .map_err(|e| format!("Remote exec status unavailable: {}", e))?;
+        if status != 0 {
+            return Err(format!("Remote command exited with status {}", status));
+        }
+        handle.info.last_activity = chrono::Utc::now();
+        handle.info.operations_count += 1;
+        String::from_utf8(output).map_err(|_| "Remote command output is not UTF-8".to_string())
+    }
+
+    /// Block signature of a remote file, computed by the remote hash helper.
+    pub(crate) fn remote_signature(
+        &mut self,
+        session_id: &str,
+        remote_path: &str,
+        block_size: u64,
+    ) -> Result<BlockSignature, String> {
+        validate_remote_file_path(remote_path)?;
+        let output = self.exec_remote(session_id, &signature_command(remote_path, block_size))?;
+        parse_signature(&output, block_size)
+    }
+
+    /// Rebuild `local_path` from its current content plus the remote ranges
+    /// listed in `plan`, verify it, and swap it in.
+    pub(crate) fn delta_download(
+        &mut self,
+        session_id: &str,
+        remote_path: &str,
+        local_path: &str,
+        plan: &DeltaPlan,
+        remote_mtime: Option<u64>,
+    ) -> Result<(), String> {
+        let destination = Path::new(local_path);
+        let transfer_id = Uuid::new_v4().simple().to_string();
+        let staging = staging_path(destination, &transfer_id)?;
+        let mut guard = PartialDownloadGuard::new(staging.clone());
+
+        let (sftp, handle) = self.sftp_channel(session_id)?;
+        let mut source = sftp
+            .open(Path::new(remote_path))
+            .map_err(|e| format!("open '{}' failed: {}", remote_path, e))?;
+        let mut old = open_local(destination)?;
+        let mut output = std::fs::File::create(&staging)
+            .map_err(|e| format!("Cannot create staging file: {}", e))?;
+
+        let mut whole = Sha256::new();
+        let mut buf = vec![0u8; MAX_INSTRUCTION_LEN as usize];
+        let mut write = |chunk: &[u8]| {
+            whole.update(chunk);
+            output
+                .write_all(chunk)
+                .map_err(|e| format!("Cannot write staging file: {}", e))
+        };
+        for op in &plan.ops {
+            match *op {
+                DeltaOp::Reuse { offset, len } => {
+                    copy_range(&mut old, local_path, offset, len, &mut buf, &mut write)?
+                }
+                DeltaOp::Send { offset, len } => {
+                    copy_range(&mut source, remote_path, offset, len, &mut buf, &mut write)?
+                }
+            }
+        }
+        handle.info.bytes_downloaded += plan.bytes_transferred;
+
+        if hex::encode(whole.finalize()) != plan.file_sha256 {
+            return Err("Delta reconstruction checksum mismatch; staged file was discarded".into());
+        }
+        output
+            .sync_all()
+            .map_err(|e| format!("Cannot flush staging file: {}", e))?;
+        drop(output);
+        drop(old);
+        if let Some(mtime) = remote_mtime {
+            let time = filetime::FileTime::from_unix_time(mtime as i64, 0);
+            let _ = filetime::set_file_mtime(&staging, time);
+        }
+        commit_staged_download(&staging, destination, &transfer_id, true)?;
+        guard.disarm();
+        Ok(())
+    }
+
+    /// Rebuild `remote_path` server-side from its current content plus the
+    /// local ranges listed in `plan`, verify it, and swap it in with an
+    /// atomic rename.
+    pub(crate) fn delta_upload(
+        &mut self,
+        session_id: &str,
+        local_path: &str,
+        remote_path: &str,
+        plan: &DeltaPlan,
+        local_mtime: u64,
+    ) -> Result<(), String> {
+        validate_remote_file_path(remote_path)?;
+        let temp = remote_temp_path(remote_path)?;
+        self.exec_remote(
+            session_id,
+            &format!(
+                "cp -p -- {} {}",
+                shell_quote(remote_path),
+                shell_quote(&temp)
+            ),
+        )?;
+
+        let result = self.rebuild_remote_copy(
+            session_id,
+            local_path,
+            remote_path,
+            &temp,
+            plan,
+            local_mtime,
+        );
+        let result = result.and_then(|_| {
+            self.exec_remote(
+                session_id,
+                &format!(
+                    "mv -f -- {} {}",
+                    shell_quote(&temp),
+                    shell_quote(remote_path)
+                ),
+            )
+            .map(|_| ())
+        });
+        if result.is_err() {
+            if let Ok((sftp, _handle)) = self.sftp_channel(session_id) {
+                let _ = sftp.unlink(Path::new(&temp));
+            }
+        }
+        result
+    }
+
+    /// Stream `plan` to the reconstruction helper, which rewrites `temp`
+    /// from `remote_path` and the literal data, then check its hash.
+    fn rebuild_remote_copy(
+        &mut self,
+        session_id: &str,
+        local_path: &str,
+        remote_path: &str,
+        temp: &str,
+        plan: &DeltaPlan,
+        local_mtime: u64,
+    ) -> Result<(), String> {
+        let mut source = open_local(Path::new(local_path))?;
+        let output = self.exec_remote_with_input(
+            session_id,
+            &apply_command(remote_path, temp),
+            |channel| write_instructions(plan, &mut source, local_path, channel),
+        )?;
+        if output.trim().strip_prefix('=') != Some(plan.file_sha256.as_str()) {
+            return Err(
+                "Delta reconstruction checksum mismatch; remote file was not replaced".into(),
+            );
+        }
+
+        let (sftp, handle) = self.sftp_channel(session_id)?;
+        handle.info.bytes_uploaded += plan.bytes_transferred;
+        sftp.setstat(
+            Path::new(temp),
+            ssh2::FileStat {
+                size: None,
+                uid: None,
+                gid: None,
+                perm: None,
+                atime: Some(local_mtime),
+                mtime: Some(local_mtime),
+            },
+        )
+        .map_err(|e| format!("setstat '{}' failed: {}", temp, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("sorng-sftp-delta-{}", Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Deterministic bytes without repeating blocks.
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Apply `plan` to `old`, taking sent ranges from `new`.
+    fn rebuild(plan: &DeltaPlan, old: &[u8], new: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for op in &plan.ops {
+            match *op {
+                DeltaOp::Reuse { offset, len } => {
+                    out.extend_from_slice(&old[offset as usize..(offset + len) as usize])
+                }
+                DeltaOp::Send { offset, len } => {
+                    out.extend_from_slice(&new[offset as usize..(offset + len) as usize])
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn block_size_is_bounded_and_honours_requests() {
+        assert_eq!(choose_block_size(0, None), DEFAULT_MIN_BLOCK_SIZE);
+        assert_eq!(choose_block_size(10 << 30, None), 1 << 20);
+        assert_eq!(choose_block_size(u64::MAX, None), MAX_BLOCK_SIZE);
+        assert_eq!(choose_block_size(1 << 30, Some(1)), MIN_BLOCK_SIZE);
+        assert_eq!(choose_block_size(1 << 30, Some(8192)), 8192);
+    }
+
+    #[test]
+    fn rolling_checksum_matches_a_fresh_one() {
+        let data = noise(5000, 1);
+        assert_eq!(RollingChecksum::new(b"Wikipedia").value(), 0x11E6_0398);
+        let mut rolling = RollingChecksum::new(&data[..4096]);
+        for start in 1..=data.len() - 4096 {
+            rolling.roll(data[start - 1], data[start + 4095]);
+            assert_eq!(
+                rolling.value(),
+                RollingChecksum::new(&data[start..start + 4096]).value()
+            );
+        }
+        let ones = [0xFFu8; 6000];
+        let mut rolling = RollingChecksum::new(&ones[..4096]);
+        rolling.roll(0xFF, 0xFF);
+        assert_eq!(
+            rolling.value(),
+            RollingChecksum::new(&ones[1..4097]).value()
+        );
+    }
+
+    #[test]
+    fn plan_sends_only_changed_and_appended_data() {
+        let old = noise(4096 * 3, 2);
+        let mut new = old.clone();
+        new[4096] ^= 0xFF;
+        new.extend_from_slice(b"appended");
+        let old_path = temp_file(&old);
+        let new_path = temp_file(&new);
+
+        let old_sig = local_signature(&old_path, 4096).unwrap();
+        let plan = plan_delta(&new_path, &old_sig).unwrap();
+        assert_eq!(
+            plan.ops,
+            [
+                DeltaOp::Reuse {
+                    offset: 0,
+                    len: 4096
+                },
+                DeltaOp::Send {
+                    offset: 4096,
+                    len: 4096
+                },
+                DeltaOp::Reuse {
+                    offset: 8192,
+                    len: 4096
+                },
+                DeltaOp::Send {
+                    offset: 4096 * 3,
+                    len: 8
+                },
+            ]
+        );
+        assert_eq!(plan.bytes_transferred, 4096 + 8);
+        assert_eq!(plan.bytes_reused, 4096 * 2);
+        assert_eq!(plan.size, new.len() as u64);
+        assert_eq!(plan.file_sha256, hex::encode(Sha256::digest(&new)));
+        assert_eq!(rebuild(&plan, &old, &new), new);
+
+        // Pulling the old version back onto the changed file.
+        let new_sig = local_signature(&old_path, 4096).unwrap();
+        let pull = plan_delta_from_signature(&new_sig, &new_path).unwrap();
+        assert_eq!(pull.bytes_transferred, 4096);
+        assert_eq!(pull.size, 4096 * 3);
+        assert_eq!(rebuild(&pull, &new, &old), old);
+
+        let _ = std::fs::remove_file(old_path);
+        let _ = std::fs::remove_file(new_path);
+    }
+
+    #[test]
+    fn insertion_near_the_start_reuses_the_shifted_blocks() {
+        let old = noise(4096 * 3 + 100, 3);
+        let mut new = old[..10].to_vec();
+        new.extend_from_slice(b"inserted");
+        new.extend_from_slice(&old[10..]);
+        let old_path = temp_file(&old);
+        let new_path = temp_file(&new);
+
+        let push = plan_delta(&new_path, &local_signature(&old_path, 4096).unwrap()).unwrap();
+        assert_eq!(
+            push.ops,
+            [
+                DeltaOp::Send {
+                    offset: 0,
+                    len: 4096 + 8
+                },
+                DeltaOp::Reuse {
+                    offset: 4096,
+                    len: 4096 * 2 + 100
+                },
+            ]
+        );
+        assert_eq!(rebuild(&push, &old, &new), new);
+
+        let pull = plan_delta_from_signature(&local_signature(&new_path, 4096).unwrap(), &old_path)
+            .unwrap();
+        assert_eq!(pull.bytes_transferred, 4096);
+        assert_eq!(pull.bytes_reused, 4096 * 2 + 108);
+        assert_eq!(rebuild(&pull, &old, &new), new);
+
+        let _ = std::fs::remove_file(old_path);
+        let _ = std::fs::remove_file(new_path);
+    }
+
+    #[test]
+    fn helper_output_is_validated() {
+        let hash = "ab".repeat(32);
+        let ok = format!("5000\n0a0b0c0d {hash}\n00000001 {hash}\n={hash}\n");
+        let signature = parse_signature(&ok, 4096).unwrap();
+        assert_eq!(signature.blocks.len(), 2);
+        assert_eq!(signature.blocks[0].weak, 0x0a0b_0c0d);
+        assert!(parse_signature(&format!("5000\n0a0b0c0d {hash}\n={hash}\n"), 4096).is_err());
+        assert!(parse_signature(&format!("0\n={hash}\n"), 4096).is_ok());
+        assert!(parse_signature("python3: not found", 4096).is_err());
+        assert!(parse_signature(&format!("10\n{hash}\n={hash}\n"), 4096).is_err());
+        assert!(parse_signature(&format!("10\nzz {hash}\n={hash}\n"), 4096).is_err());
+    }
+
+    #[test]
+    fn shell_quoting_survives_hostile_paths() {
+        assert_eq!(shell_quote("/tmp/it's"), "'/tmp/it'\\''s'");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+        assert_eq!(helper_path_arg("-v"), "'./-v'");
+        assert_eq!(helper_path_arg("/srv/-v"), "'/srv/-v'");
+        let [python, perl] = signature_invocations("--help", 4096);
+        assert!(python.ends_with(" './--help' 4096"));
+        assert!(perl.ends_with(" -- './--help' 4096"));
+    }
+
+    /// Runs `script` through a local POSIX shell; `None` if the interpreter
+    /// it needs is not installed here.
+    #[cfg(unix)]
+    fn run_shell(dir: &Path, script: &str, stdin: &[u8]) -> Option<String> {
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .current_dir(dir)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(stdin).unwrap();
+        let output = child.wait_with_output().unwrap();
+        // 127: interpreter not installed on this machine.
+        if output.status.code() == Some(127) {
+            return None;
+        }
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
+    /// Runs the signature helper (and each interpreter it can pick) on a file
+    /// whose name starts with `-`, and checks it agrees with the local
+    /// hasher.
+    #[cfg(unix)]
+    #[test]
+    fn remote_helper_matches_local_signature() {
+        let mut content = Vec::new();
+        for i in 0..20_000u32 {
+            content.extend_from_slice(&i.to_le_bytes());
+        }
+        let dir = temp_file(b"");
+        std::fs::remove_file(&dir).unwrap();
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("-v");
+        std::fs::write(&path, &content).unwrap();
+        let expected = local_signature(&path, 4096).unwrap();
+
+        let [python, perl] = signature_invocations("-v", 4096);
+        for script in [signature_command("-v", 4096), python, perl] {
+            if let Some(stdout) = run_shell(&dir, &script, b"") {
+                assert_eq!(parse_signature(&stdout, 4096).unwrap(), expected);
+            }
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    /// Streams a plan to each reconstruction helper and checks the rebuilt
+    /// file and the hash it reports.
+    #[cfg(unix)]
+    #[test]
+    fn remote_reconstruction_rebuilds_the_new_version() {
+        let old = noise(4096 * 600 + 5, 4);
+        let mut new = noise(300, 5);
+        new.extend_from_slice(&old[..4096 * 400]);
+        new.extend_from_slice(&noise(5000, 6));
+        new.extend_from_slice(&old[4096 * 450..]);
+        let dir = temp_file(b"");
+        std::fs::remove_file(&dir).unwrap();
+        std::fs::create_dir(&dir).unwrap();
+        let (basis, target, source) = (dir.join("basis"), dir.join("-t"), dir.join("new"));
+        std::fs::write(&basis, &old).unwrap();
+        std::fs::write(&source, &new).unwrap();
+
+        let plan = plan_delta(&source, &local_signature(&basis, 4096).unwrap()).unwrap();
+        assert_eq!(plan.bytes_transferred, 300 + 5000);
+        assert!(plan.ops.iter().any(|op| matches!(
+            op,
+            DeltaOp::Reuse { len, .. } if *len > MAX_INSTRUCTION_LEN
+        )));
+        let mut instructions = Vec::new();
+        let mut reader = std::fs::File::open(&source).unwrap();
+        write_instructions(&plan, &mut reader, "new", &mut instructions).unwrap();
+
+        let [python, perl] = apply_invocations("basis", "-t");
+        for script in [python, perl] {
+            std::fs::write(&target, &old).unwrap();
+            if let Some(stdout) = run_shell(&dir, &script, &instructions) {
+                assert_eq!(stdout.trim(), format!("={}", plan.file_sha256));
+                assert!(std::fs::read(&target).unwrap() == new);
+            }
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -7,10 +7,12 @@
 //   • Chunked & resumable uploads / downloads with progress events
 //   • Transfer queue with concurrency management
 //   • File watching / sync helpers
+//   • Delta (rolling block-signature) sync with atomic reconstruction
 //   • Bookmark / favourite-path management
 //   • Tauri command bindings for the frontend
 
 pub mod bookmarks;
+pub mod delta;
 pub mod diagnostics;
 pub mod dir_ops;
 pub mod file_ops;
@@ -95,7 +95,7 @@ fn reject_local_symlink(path: &Path) -> Result<(), String> {
     }
 }
 
-fn staging_path(destination: &Path, transfer_id: &str) -> Result<PathBuf, String> {
+pub(crate) fn staging_path(destination: &Path, transfer_id: &str) -> Result<PathBuf, String> {
     let parent = destination.parent().unwrap_or_else(|| Path::new("."));
     let name = destination
         .file_name()
@@ -104,17 +104,17 @@ fn staging_path(destination: &Path, transfer_id: &str) -> Result<PathBuf, String
     Ok(parent.join(format!(".{}.sorng-part-{}", name, transfer_id)))
 }
 
-struct PartialDownloadGuard {
+pub(crate) struct PartialDownloadGuard {
     path: PathBuf,
     armed: bool,
 }
 
 impl PartialDownloadGuard {
-    fn new(path: PathBuf) -> Self {
+    pub(crate) fn new(path: PathBuf) -> Self {
         Self { path, armed: true }
     }
 
-    fn disarm(&mut self) {
+    pub(crate) fn disarm(&mut self) {
         self.armed = false;
     }
 }
@@ -127,7 +127,7 @@ impl Drop for PartialDownloadGuard {
     }
 }
 
-fn commit_staged_download(
+pub(crate) fn commit_staged_download(
     staging: &Path,
     destination: &Path,
     transfer_id: &str,
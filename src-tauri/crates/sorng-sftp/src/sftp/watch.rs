@@ -1,5 +1,6 @@
 // ── File watching / sync ─────────────────────────────────────────────────────
 
+use crate::sftp::delta::{self, BlockSignature, DeltaPlan};
 use crate::sftp::service::SftpService;
 use crate::sftp::types::*;
 use crate::sftp::ACTIVE_WATCHES;
@@ -24,13 +25,6 @@ enum RemoteDestination {
     Existing { size: u64, modified: u64 },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PushFileOutcome {
-    Uploaded,
-    Skipped,
-    Errored,
-}
-
 trait PushSession {
     async fn inspect_remote_destination(
         &mut self,
@@ -44,7 +38,26 @@ trait PushSession {
         remote_parent: &str,
     ) -> Result<(), String>;
 
-    async fn perform_push_upload(&mut self, request: SftpTransferRequest) -> bool;
+    async fn perform_push_upload(
+        &mut self,
+        request: SftpTransferRequest,
+    ) -> Result<TransferResult, String>;
+
+    async fn remote_block_signature(
+        &mut self,
+        session_id: &str,
+        remote_path: &str,
+        block_size: u64,
+    ) -> Result<BlockSignature, String>;
+
+    async fn perform_delta_push(
+        &mut self,
+        session_id: &str,
+        local_path: &str,
+        remote_path: &str,
+        plan: &DeltaPlan,
+        local_mtime: u64,
+    ) -> Result<(), String>;
 }
 
 impl PushSession for SftpService {
@@ -72,8 +85,31 @@ impl PushSession for SftpService {
         self.mkdir_p(session_id, remote_parent, None).await
     }
 
-    async fn perform_push_upload(&mut self, request: SftpTransferRequest) -> bool {
-        matches!(self.upload(request).await, Ok(result) if result.success)
+    async fn perform_push_upload(
+        &mut self,
+        request: SftpTransferRequest,
+    ) -> Result<TransferResult, String> {
+        self.upload(request).await
+    }
+
+    async fn remote_block_signature(
+        &mut self,
+        session_id: &str,
+        remote_path: &str,
+        block_size: u64,
+    ) -> Result<BlockSignature, String> {
+        self.remote_signature(session_id, remote_path, block_size)
+    }
+
+    async fn perform_delta_push(
+        &mut self,
+        session_id: &str,
+        local_path: &str,
+        remote_path: &str,
+        plan: &DeltaPlan,
+        local_mtime: u64,
+    ) -> Result<(), String> {
+        self.delta_upload(session_id, local_path, remote_path, plan, local_mtime)
     }
 }
 
@@ -146,6 +182,7 @@ impl SftpService {
         session_id: &str,
         remote_path: &str,
         local_path: &str,
+        sync: SyncOptions,
     ) -> Result<SyncResult, String> {
         let options = SftpListOptions {
             include_hidden: false,
@@ -161,9 +198,7 @@ impl SftpService {
             .list_directory(session_id, remote_path, options)
             .await?;
 
-        let mut downloaded = 0u64;
-        let mut skipped = 0u64;
-        let mut errors = 0u64;
+        let mut files = Vec::new();
 
         for entry in &remote_entries {
             if entry.entry_type != SftpEntryType::File {
@@ -179,6 +214,7 @@ impl SftpService {
             let local_dest = format!("{}/{}", local_path, relative);
 
             // Check if local file is up-to-date
+            let mut local_size = None;
             if let Ok(local_meta) = std::fs::metadata(&local_dest) {
                 let local_mtime = local_meta
                     .modified()
@@ -189,9 +225,32 @@ impl SftpService {
 
                 let remote_mtime = entry.modified.unwrap_or(0);
                 if local_meta.len() == entry.size && local_mtime >= remote_mtime {
-                    skipped += 1;
+                    files.push(SyncFileReport::new(
+                        &entry.path,
+                        SyncFileAction::Skipped,
+                        entry.size,
+                    ));
                     continue;
                 }
+                local_size = local_meta.is_file().then_some(local_meta.len());
+            }
+
+            let mut report = SyncFileReport::new(&entry.path, SyncFileAction::Whole, entry.size);
+
+            if sync.mode == SyncMode::Delta && local_size.unwrap_or(0) > 0 && entry.size > 0 {
+                match self.pull_file_delta(session_id, entry, &local_dest, &sync) {
+                    Ok(delta) => {
+                        files.push(delta);
+                        continue;
+                    }
+                    Err(reason) => report.fallback_reason = Some(reason),
+                }
+            }
+
+            if sync.dry_run {
+                report.bytes_transferred = entry.size;
+                files.push(report);
+                continue;
             }
 
             // Ensure parent dir
@@ -212,22 +271,32 @@ impl SftpService {
                 bandwidth_limit_kbps: None,
                 retry_count: 1,
                 retry_delay_ms: 1000,
-                verify_checksum: false,
+                verify_checksum: sync.verify_checksum,
             };
 
-            match self.download(req).await {
-                Ok(r) if r.success => downloaded += 1,
-                _ => errors += 1,
-            }
+            report.record_whole_transfer(self.download(req).await, sync.verify_checksum);
+            files.push(report);
         }
 
-        Ok(SyncResult {
-            direction: "pull".to_string(),
-            files_transferred: downloaded,
-            files_skipped: skipped,
-            files_errored: errors,
-            timestamp: Utc::now(),
-        })
+        Ok(SyncResult::from_reports("pull", &sync, files))
+    }
+
+    /// Delta-download one file, or only plan it in dry-run mode. Errors mean
+    /// the local file is untouched and a whole-file download should follow.
+    fn pull_file_delta(
+        &mut self,
+        session_id: &str,
+        entry: &SftpDirEntry,
+        local_dest: &str,
+        sync: &SyncOptions,
+    ) -> Result<SyncFileReport, String> {
+        let block_size = delta::choose_block_size(entry.size, sync.block_size);
+        let remote = self.remote_signature(session_id, &entry.path, block_size)?;
+        let plan = delta::plan_delta_from_signature(&remote, std::path::Path::new(local_dest))?;
+        if !sync.dry_run {
+            self.delta_download(session_id, &entry.path, local_dest, &plan, entry.modified)?;
+        }
+        Ok(SyncFileReport::delta(&entry.path, &plan, sync.dry_run))
     }
 
     /// One-shot sync: push local changes to remote.
@@ -236,10 +305,9 @@ impl SftpService {
         session_id: &str,
         local_path: &str,
         remote_path: &str,
+        sync: SyncOptions,
     ) -> Result<SyncResult, String> {
-        let mut uploaded = 0u64;
-        let mut skipped = 0u64;
-        let mut errors = 0u64;
+        let mut files = Vec::new();
 
         let local_files = collect_local_files(local_path)?;
 
@@ -256,22 +324,20 @@ impl SftpService {
             );
             let local_snapshot = local_file_snapshot(local_file)?;
 
-            match push_local_file(self, session_id, local_file, &remote_dest, local_snapshot)
-                .await?
-            {
-                PushFileOutcome::Uploaded => uploaded += 1,
-                PushFileOutcome::Skipped => skipped += 1,
-                PushFileOutcome::Errored => errors += 1,
-            }
+            files.push(
+                push_local_file(
+                    self,
+                    session_id,
+                    local_file,
+                    &remote_dest,
+                    local_snapshot,
+                    &sync,
+                )
+                .await?,
+            );
         }
 
-        Ok(SyncResult {
-            direction: "push".to_string(),
-            files_transferred: uploaded,
-            files_skipped: skipped,
-            files_errored: errors,
-            timestamp: Utc::now(),
-        })
+        Ok(SyncResult::from_reports("push", &sync, files))
     }
 }
 
@@ -288,6 +354,104 @@ pub struct WatchInfo {
     pub interval_secs: u64,
 }
 
+/// How changed files are transferred by `sync_pull` / `sync_push`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncMode {
+    /// Re-transfer every changed file in full.
+    #[default]
+    Whole,
+    /// Transfer only changed blocks of files present on both sides, falling
+    /// back to whole-file when the remote hash helper is unavailable. Blocks
+    /// are matched at fixed offsets, so inserted or removed bytes resend the
+    /// rest of the file.
+    Delta,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncOptions {
+    pub mode: SyncMode,
+    /// Report what would be transferred without changing either side.
+    pub dry_run: bool,
+    /// Verify whole-file transfers by SHA-256 (delta transfers always are).
+    pub verify_checksum: bool,
+    /// Delta block size in bytes; chosen from the file size when unset.
+    pub block_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncFileAction {
+    Skipped,
+    Whole,
+    Delta,
+    Errored,
+}
+
+/// Per-file outcome of a sync (or, in dry-run mode, the planned outcome).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFileReport {
+    /// Remote path of the file.
+    pub path: String,
+    pub action: SyncFileAction,
+    pub size: u64,
+    pub bytes_transferred: u64,
+    /// Bytes reconstructed from the destination's existing content.
+    pub bytes_reused: u64,
+    /// SHA-256 of the transferred file, when it was computed.
+    pub checksum: Option<String>,
+    pub verified: bool,
+    /// Why a delta transfer fell back to whole-file.
+    pub fallback_reason: Option<String>,
+    pub error: Option<String>,
+}
+
+impl SyncFileReport {
+    fn new(path: &str, action: SyncFileAction, size: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            action,
+            size,
+            bytes_transferred: 0,
+            bytes_reused: 0,
+            checksum: None,
+            verified: false,
+            fallback_reason: None,
+            error: None,
+        }
+    }
+
+    fn delta(path: &str, plan: &DeltaPlan, dry_run: bool) -> Self {
+        Self {
+            bytes_transferred: plan.bytes_transferred,
+            bytes_reused: plan.bytes_reused,
+            checksum: Some(plan.file_sha256.clone()),
+            verified: !dry_run,
+            ..Self::new(path, SyncFileAction::Delta, plan.size)
+        }
+    }
+
+    fn record_whole_transfer(&mut self, result: Result<TransferResult, String>, verify: bool) {
+        match result {
+            Ok(result) if result.success => {
+                self.bytes_transferred = result.bytes_transferred;
+                self.verified = verify && result.checksum.is_some();
+                self.checksum = result.checksum;
+            }
+            Ok(result) => {
+                self.action = SyncFileAction::Errored;
+                self.error = result.error;
+            }
+            Err(error) => {
+                self.action = SyncFileAction::Errored;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncResult {
@@ -296,6 +460,29 @@ pub struct SyncResult {
     pub files_skipped: u64,
     pub files_errored: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub dry_run: bool,
+    pub bytes_transferred: u64,
+    pub bytes_reused: u64,
+    pub files_verified: u64,
+    pub files: Vec<SyncFileReport>,
+}
+
+impl SyncResult {
+    fn from_reports(direction: &str, options: &SyncOptions, files: Vec<SyncFileReport>) -> Self {
+        let count = |action| files.iter().filter(|f| f.action == action).count() as u64;
+        Self {
+            direction: direction.to_string(),
+            files_transferred: count(SyncFileAction::Whole) + count(SyncFileAction::Delta),
+            files_skipped: count(SyncFileAction::Skipped),
+            files_errored: count(SyncFileAction::Errored),
+            timestamp: Utc::now(),
+            dry_run: options.dry_run,
+            bytes_transferred: files.iter().map(|f| f.bytes_transferred).sum(),
+            bytes_reused: files.iter().map(|f| f.bytes_reused).sum(),
+            files_verified: files.iter().filter(|f| f.verified).count() as u64,
+            files,
+        }
+    }
 }
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
@@ -353,7 +540,8 @@ async fn push_local_file<S: PushSession>(
     local_path: &str,
     remote_path: &str,
     local: LocalFileSnapshot,
-) -> Result<PushFileOutcome, String> {
+    sync: &SyncOptions,
+) -> Result<SyncFileReport, String> {
     let destination = session
         .inspect_remote_destination(session_id, remote_path)
         .await
@@ -366,7 +554,29 @@ async fn push_local_file<S: PushSession>(
         }
     };
     if !needs_upload {
-        return Ok(PushFileOutcome::Skipped);
+        return Ok(SyncFileReport::new(
+            remote_path,
+            SyncFileAction::Skipped,
+            local.size,
+        ));
+    }
+
+    let mut report = SyncFileReport::new(remote_path, SyncFileAction::Whole, local.size);
+
+    let remote_size = match destination {
+        RemoteDestination::Existing { size, .. } => size,
+        RemoteDestination::Missing => 0,
+    };
+    if sync.mode == SyncMode::Delta && remote_size > 0 && local.size > 0 {
+        match push_file_delta(session, session_id, local_path, remote_path, local, sync).await {
+            Ok(delta) => return Ok(delta),
+            Err(reason) => report.fallback_reason = Some(reason),
+        }
+    }
+
+    if sync.dry_run {
+        report.bytes_transferred = local.size;
+        return Ok(report);
     }
 
     if let Some(parent) = remote_parent(remote_path) {
@@ -389,27 +599,54 @@ async fn push_local_file<S: PushSession>(
         bandwidth_limit_kbps: None,
         retry_count: 1,
         retry_delay_ms: 1000,
-        verify_checksum: false,
+        verify_checksum: sync.verify_checksum,
     };
 
-    if session.perform_push_upload(request).await {
-        Ok(PushFileOutcome::Uploaded)
-    } else {
-        Ok(PushFileOutcome::Errored)
+    report.record_whole_transfer(
+        session.perform_push_upload(request).await,
+        sync.verify_checksum,
+    );
+    Ok(report)
+}
+
+/// Delta-upload one file, or only plan it in dry-run mode. Errors mean the
+/// remote file is untouched and a whole-file upload should follow.
+async fn push_file_delta<S: PushSession>(
+    session: &mut S,
+    session_id: &str,
+    local_path: &str,
+    remote_path: &str,
+    local: LocalFileSnapshot,
+    sync: &SyncOptions,
+) -> Result<SyncFileReport, String> {
+    let block_size = delta::choose_block_size(local.size, sync.block_size);
+    let remote = session
+        .remote_block_signature(session_id, remote_path, block_size)
+        .await?;
+    let plan = delta::plan_delta(std::path::Path::new(local_path), &remote)?;
+    if !sync.dry_run {
+        session
+            .perform_delta_push(session_id, local_path, remote_path, &plan, local.modified)
+            .await?;
     }
+    Ok(SyncFileReport::delta(remote_path, &plan, sync.dry_run))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sftp::delta::DeltaOp;
+    use sha2::Digest;
 
     struct FakePushSession {
         inspection: Option<Result<RemoteDestination, String>>,
         parent_result: Result<(), String>,
         upload_success: bool,
+        remote_content: Result<Vec<u8>, String>,
         inspected_paths: Vec<String>,
         prepared_parents: Vec<String>,
         uploads: Vec<SftpTransferRequest>,
+        delta_pushes: Vec<DeltaPlan>,
     }
 
     impl FakePushSession {
@@ -418,9 +655,11 @@ mod tests {
                 inspection: Some(inspection),
                 parent_result: Ok(()),
                 upload_success: true,
+                remote_content: Err("hash helper unavailable".to_string()),
                 inspected_paths: Vec::new(),
                 prepared_parents: Vec::new(),
                 uploads: Vec::new(),
+                delta_pushes: Vec::new(),
             }
         }
     }
@@ -446,9 +685,60 @@ mod tests {
             self.parent_result.clone()
         }
 
-        async fn perform_push_upload(&mut self, request: SftpTransferRequest) -> bool {
+        async fn perform_push_upload(
+            &mut self,
+            request: SftpTransferRequest,
+        ) -> Result<TransferResult, String> {
+            let verify = request.verify_checksum;
             self.uploads.push(request);
-            self.upload_success
+            Ok(TransferResult {
+                transfer_id: "transfer-1".to_string(),
+                success: self.upload_success,
+                bytes_transferred: 12,
+                duration_ms: 1,
+                average_speed_bps: 0.0,
+                checksum: verify.then(|| "ab".repeat(32)),
+                error: (!self.upload_success).then(|| "upload failed".to_string()),
+            })
+        }
+
+        async fn remote_block_signature(
+            &mut self,
+            _session_id: &str,
+            _remote_path: &str,
+            block_size: u64,
+        ) -> Result<BlockSignature, String> {
+            let content = self.remote_content.clone()?;
+            let path = temp_file(&content);
+            let signature = delta::local_signature(&path, block_size);
+            let _ = std::fs::remove_file(path);
+            signature
+        }
+
+        async fn perform_delta_push(
+            &mut self,
+            _session_id: &str,
+            _local_path: &str,
+            _remote_path: &str,
+            plan: &DeltaPlan,
+            _local_mtime: u64,
+        ) -> Result<(), String> {
+            self.delta_pushes.push(plan.clone());
+            Ok(())
+        }
+    }
+
+    fn temp_file(content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("sorng-sftp-sync-{}", Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn delta_options() -> SyncOptions {
+        SyncOptions {
+            mode: SyncMode::Delta,
+            block_size: Some(4096),
+            ..SyncOptions::default()
         }
     }
 
@@ -474,11 +764,12 @@ mod tests {
             "C:/safe/file.txt",
             "/remote/file.txt",
             snapshot(12, 20),
+            &SyncOptions::default(),
         )
         .await
         .expect("a genuinely missing destination should be uploadable");
 
-        assert_eq!(outcome, PushFileOutcome::Uploaded);
+        assert_eq!(outcome.action, SyncFileAction::Whole);
         assert_eq!(session.prepared_parents, ["/remote"]);
         assert_eq!(session.uploads.len(), 1);
         assert!(matches!(
@@ -499,6 +790,7 @@ mod tests {
             "C:/safe/file.txt",
             "/remote/private.txt",
             snapshot(12, 20),
+            &SyncOptions::default(),
         )
         .await
         .expect_err("an ambiguous stat failure must abort the push");
@@ -521,6 +813,7 @@ mod tests {
             "C:/safe/file.txt",
             "/remote/secret/file.txt",
             snapshot(12, 20),
+            &SyncOptions::default(),
         )
         .await
         .expect_err("parent creation failure must abort the push");
@@ -543,10 +836,11 @@ mod tests {
             "C:/safe/file.txt",
             "/remote/file.txt",
             snapshot(12, 20),
+            &SyncOptions::default(),
         )
         .await
         .expect("an unchanged existing destination should be skipped");
-        assert_eq!(outcome, PushFileOutcome::Skipped);
+        assert_eq!(outcome.action, SyncFileAction::Skipped);
         assert!(unchanged.prepared_parents.is_empty());
         assert!(unchanged.uploads.is_empty());
 
@@ -560,14 +854,180 @@ mod tests {
             "C:/safe/file.txt",
             "/remote/file.txt",
             snapshot(12, 20),
+            &SyncOptions::default(),
         )
         .await
         .expect("a changed existing destination should be overwritten");
-        assert_eq!(outcome, PushFileOutcome::Uploaded);
+        assert_eq!(outcome.action, SyncFileAction::Whole);
         assert_eq!(changed.uploads.len(), 1);
         assert!(matches!(
             changed.uploads[0].on_conflict,
             ConflictResolution::Overwrite
         ));
     }
+
+    #[tokio::test]
+    async fn failed_upload_is_reported_with_its_error() {
+        let mut session = FakePushSession::new(Ok(RemoteDestination::Missing));
+        session.upload_success = false;
+        let report = push_local_file(
+            &mut session,
+            "session-1",
+            "C:/safe/file.txt",
+            "/remote/file.txt",
+            snapshot(12, 20),
+            &SyncOptions {
+                verify_checksum: true,
+                ..SyncOptions::default()
+            },
+        )
+        .await
+        .expect("an upload failure is reported per file");
+        assert_eq!(report.action, SyncFileAction::Errored);
+        assert_eq!(report.error.as_deref(), Some("upload failed"));
+        assert!(session.uploads[0].verify_checksum);
+        assert!(!report.verified);
+    }
+
+    #[tokio::test]
+    async fn delta_push_sends_only_changed_blocks() {
+        let old: Vec<u8> = (0..4096u32).flat_map(u32::to_le_bytes).collect();
+        let mut new = old.clone();
+        new[4096 * 2 + 10] ^= 0xFF;
+        let local = temp_file(&new);
+        let mut session = FakePushSession::new(Ok(RemoteDestination::Existing {
+            size: old.len() as u64,
+            modified: 10,
+        }));
+        session.remote_content = Ok(old);
+
+        let report = push_local_file(
+            &mut session,
+            "session-1",
+            &local.to_string_lossy(),
+            "/remote/file.bin",
+            snapshot(new.len() as u64, 20),
+            &delta_options(),
+        )
+        .await
+        .expect("delta push should succeed");
+        let _ = std::fs::remove_file(local);
+
+        assert_eq!(report.action, SyncFileAction::Delta);
+        assert_eq!(report.bytes_transferred, 4096);
+        assert_eq!(report.bytes_reused, 4096 * 3);
+        assert!(report.verified);
+        assert_eq!(
+            report.checksum.as_deref(),
+            Some(hex::encode(sha2::Sha256::digest(&new)).as_str())
+        );
+        assert_eq!(session.delta_pushes.len(), 1);
+        assert_eq!(
+            session.delta_pushes[0].ops,
+            [
+                DeltaOp::Reuse {
+                    offset: 0,
+                    len: 4096 * 2
+                },
+                DeltaOp::Send {
+                    offset: 4096 * 2,
+                    len: 4096
+                },
+                DeltaOp::Reuse {
+                    offset: 4096 * 3,
+                    len: 4096
+                },
+            ]
+        );
+        assert!(session.uploads.is_empty());
+    }
+
+    #[tokio::test]
+    async fn delta_push_falls_back_to_whole_file_without_the_helper() {
+        let local = temp_file(&[1u8; 100]);
+        let mut session = FakePushSession::new(Ok(RemoteDestination::Existing {
+            size: 90,
+            modified: 10,
+        }));
+
+        let report = push_local_file(
+            &mut session,
+            "session-1",
+            &local.to_string_lossy(),
+            "/remote/file.bin",
+            snapshot(100, 20),
+            &delta_options(),
+        )
+        .await
+        .expect("fallback upload should succeed");
+        let _ = std::fs::remove_file(local);
+
+        assert_eq!(report.action, SyncFileAction::Whole);
+        assert_eq!(
+            report.fallback_reason.as_deref(),
+            Some("hash helper unavailable")
+        );
+        assert!(session.delta_pushes.is_empty());
+        assert_eq!(session.uploads.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_without_touching_the_remote() {
+        let old = vec![1u8; 8192];
+        let mut new = old.clone();
+        new.extend_from_slice(&[2u8; 100]);
+        let local = temp_file(&new);
+        let mut session = FakePushSession::new(Ok(RemoteDestination::Existing {
+            size: 8192,
+            modified: 10,
+        }));
+        session.remote_content = Ok(old);
+        let dry_run = SyncOptions {
+            dry_run: true,
+            ..delta_options()
+        };
+
+        let report = push_local_file(
+            &mut session,
+            "session-1",
+            &local.to_string_lossy(),
+            "/remote/dir/file.bin",
+            snapshot(new.len() as u64, 20),
+            &dry_run,
+        )
+        .await
+        .expect("dry run should plan the delta");
+        assert_eq!(report.action, SyncFileAction::Delta);
+        assert_eq!(report.bytes_transferred, 100);
+        assert_eq!(report.bytes_reused, 8192);
+        assert!(!report.verified);
+
+        let mut missing = FakePushSession::new(Ok(RemoteDestination::Missing));
+        let whole = push_local_file(
+            &mut missing,
+            "session-1",
+            &local.to_string_lossy(),
+            "/remote/dir/file.bin",
+            snapshot(new.len() as u64, 20),
+            &dry_run,
+        )
+        .await
+        .expect("dry run should plan the upload");
+        let _ = std::fs::remove_file(local);
+        assert_eq!(whole.action, SyncFileAction::Whole);
+        assert_eq!(whole.bytes_transferred, new.len() as u64);
+
+        for session in [&session, &missing] {
+            assert!(session.prepared_parents.is_empty());
+            assert!(session.uploads.is_empty());
+            assert!(session.delta_pushes.is_empty());
+        }
+
+        let result = SyncResult::from_reports("push", &dry_run, vec![report, whole]);
+        assert!(result.dry_run);
+        assert_eq!(result.files_transferred, 2);
+        assert_eq!(result.bytes_transferred, 100 + new.len() as u64);
+        assert_eq!(result.bytes_reused, 8192);
+        assert_eq!(result.files_verified, 0);
+    }
 }
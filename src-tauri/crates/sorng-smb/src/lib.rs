@@ -21,9 +21,10 @@
 //! - **Windows** (`#[cfg(windows)]`): Uses native Windows SMB redirector via
 //!   UNC paths (`\\server\share\path`) and `std::fs`. Share enumeration
 //!   shells out to `net view \\server`.
-//! - **Unix** (`#[cfg(unix)]`): Uses the `smbclient` CLI subprocess.
-//!   Share enumeration uses `smbclient -L //server`; file ops use
-//!   `smbclient //server/share -c "command"`.
+//! - **Unix** (`#[cfg(unix)]`): Uses the native SMB 2.1/3.x client in
+//!   `smb::smb2` (NTLMv2, signing, SMB3 encryption, srvsvc share
+//!   enumeration). Kerberos sessions fall back to the `smbclient` CLI
+//!   subprocess (`smbclient -L //server`, `smbclient //server/share -c`).
 //!
 //! The public `smb::service::SmbService` API is identical on both platforms
 //! so Tauri command wrappers (`commands.rs`) and the frontend hook
@@ -1,10 +1,13 @@
 // ── File operations (platform-split) ─────────────────────────────────────────
 //
 // This module owns ALL platform-dependent code. Everything else in the
-// crate is portable. Two independent implementations live here:
+// crate is portable. Two independent implementations live here, plus
+// the portable native client in `smb2`:
 //
 //   • `windows` module — native redirector + UNC I/O + NetShareEnum.
-//   • `unix`    module — bounded smbclient with private auth files.
+//   • `unix`    module — bounded smbclient with private auth files,
+//                        used on Unix only for Kerberos sessions.
+//   • `smb2::backend`  — native SMB 2.1/3.x client, the Unix default.
 //
 // Both expose the same `OpsBackend` trait surface so `service.rs` can
 // swap them at `cfg` boundaries. Blocking work (subprocess spawn,
@@ -15,10 +18,10 @@ use super::types::*;
 use async_trait::async_trait;
 
 const MAX_SERVER_HOST_LEN: usize = 253;
-const MAX_INLINE_FILE_BYTES: u64 = 16 * 1024 * 1024;
+pub(super) const MAX_INLINE_FILE_BYTES: u64 = 16 * 1024 * 1024;
 const MAX_INLINE_BASE64_BYTES: usize = 24 * 1024 * 1024;
 
-fn inline_read_limit(requested: Option<u64>) -> u64 {
+pub(super) fn inline_read_limit(requested: Option<u64>) -> u64 {
     requested
         .unwrap_or(MAX_INLINE_FILE_BYTES)
         .min(MAX_INLINE_FILE_BYTES)
@@ -29,7 +32,7 @@ fn inline_base64_lengths_allowed(content_len: usize, symbols: usize) -> bool {
     content_len <= MAX_INLINE_BASE64_BYTES && decoded_upper_bound as u64 <= MAX_INLINE_FILE_BYTES
 }
 
-fn validate_inline_base64(content: &str) -> SmbResult<()> {
+pub(super) fn validate_inline_base64(content: &str) -> SmbResult<()> {
     if content.len() > MAX_INLINE_BASE64_BYTES {
         return Err(SmbError::Other(
             "inline SMB payload exceeds the 16 MiB safety limit; use file transfer instead".into(),
@@ -48,7 +51,7 @@ fn validate_inline_base64(content: &str) -> SmbResult<()> {
     Ok(())
 }
 
-fn atomic_download_temp(
+pub(super) fn atomic_download_temp(
     local_path: &str,
 ) -> SmbResult<(std::path::PathBuf, tempfile::NamedTempFile)> {
     let destination = std::path::PathBuf::from(local_path);
@@ -73,7 +76,7 @@ fn atomic_download_temp(
     Ok((destination, temp))
 }
 
-fn persist_atomic_download(
+pub(super) fn persist_atomic_download(
     temp: tempfile::NamedTempFile,
     destination: &std::path::Path,
 ) -> SmbResult<()> {
@@ -88,7 +91,7 @@ fn persist_atomic_download(
 /// Keep server names unambiguous before they are embedded in a UNC path or
 /// smbclient target. Internationalised DNS names must be supplied in their
 /// ASCII (punycode) form; scoped IPv6 literals are deliberately unsupported.
-fn validate_server_host(host: &str) -> SmbResult<()> {
+pub(super) fn validate_server_host(host: &str) -> SmbResult<()> {
     let valid_chars = host
         .chars()
         .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_' | ':' | '[' | ']'));
@@ -124,6 +127,13 @@ pub trait OpsBackend: Send + Sync {
     /// Release any platform-native connection state created by `probe`.
     async fn disconnect(&self, session: &SmbSession) -> SmbResult<()>;
 
+    /// Whether sessions to the same host/share/port share one underlying
+    /// connection (the Windows redirector), so `disconnect` must wait for
+    /// the last of them.
+    fn shares_connections(&self) -> bool {
+        true
+    }
+
     async fn list_shares(&self, session: &SmbSession) -> SmbResult<Vec<SmbShareInfo>>;
 
     async fn list_dir(
@@ -263,18 +273,26 @@ pub fn default_backend() -> Box<dyn OpsBackend> {
     }
     #[cfg(not(windows))]
     {
-        Box::new(unix_impl::UnixBackend::new())
+        // Native SMB2/3 for NTLM sessions; smbclient only for Kerberos.
+        Box::new(super::smb2::backend::NativeBackend::with_fallback(
+            Box::new(unix_impl::UnixBackend::new()),
+        ))
     }
 }
 
-pub fn backend_name() -> &'static str {
+pub fn backend_name(config: &SmbConnectionConfig) -> &'static str {
     #[cfg(windows)]
     {
+        let _ = config;
         "windows-unc"
     }
     #[cfg(not(windows))]
     {
-        "unix-smbclient"
+        if config.use_kerberos {
+            "unix-smbclient"
+        } else {
+            "native-smb2"
+        }
     }
 }
 
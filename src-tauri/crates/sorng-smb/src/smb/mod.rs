@@ -10,7 +10,8 @@
 //   • Tauri command bindings for the frontend
 //
 // Platform split: Windows uses UNC paths + std::fs + `net view`; Unix
-// uses the `smbclient` CLI subprocess. See `lib.rs` top docstring for the
+// uses the native SMB2/3 client in `smb2`, falling back to the `smbclient`
+// CLI subprocess for Kerberos. See `lib.rs` top docstring for the
 // full rationale (pavao rejected due to libsmbclient C-library dependency
 // unavailable on Windows).
 
@@ -24,6 +25,7 @@
 pub mod file_ops;
 pub mod service;
 pub mod session;
+pub mod smb2;
 pub mod types;
 
 pub use service::{SmbService, SmbServiceState};
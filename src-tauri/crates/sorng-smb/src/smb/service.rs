@@ -40,7 +40,8 @@ impl SmbService {
 
     pub async fn connect(&mut self, config: SmbConnectionConfig) -> SmbResult<SmbSessionInfo> {
         let id = Uuid::new_v4().to_string();
-        let session = SmbSession::new(id.clone(), config, backend_name());
+        let backend = backend_name(&config);
+        let session = SmbSession::new(id.clone(), config, backend);
         // Probe once so bad creds surface immediately.
         self.backend.probe(&session).await?;
         let info = session.info.clone();
@@ -58,7 +59,7 @@ impl SmbService {
                 && other.config.share == session.config.share
                 && other.config.port == session.config.port
         });
-        if redirector_still_used {
+        if redirector_still_used && self.backend.shares_connections() {
             return Ok(());
         }
         if let Err(error) = self.backend.disconnect(&session).await {
@@ -0,0 +1,839 @@
+// ── NativeBackend ────────────────────────────────────────────────────────────
+//
+// `OpsBackend` on top of `Smb2Client`. Each service session owns one
+// authenticated connection, created by `probe` and reused until it breaks
+// or the session disconnects. Sessions that request Kerberos are delegated
+// to the fallback backend (smbclient) because this client speaks NTLM only.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use base64::Engine as _;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+use super::client::{ClientConfig, Smb2Client};
+use super::wire::{self, CreateRequest, CreateResponse, DirectoryInfo};
+use crate::smb::file_ops::{
+    atomic_download_temp, inline_read_limit, persist_atomic_download, validate_inline_base64,
+    validate_server_host, OpsBackend, MAX_INLINE_FILE_BYTES,
+};
+use crate::smb::session::SmbSession;
+use crate::smb::types::*;
+
+/// Upper bound on entries returned by one directory listing.
+const MAX_DIR_ENTRIES: usize = 100_000;
+
+type SharedClient = Arc<Mutex<Smb2Client>>;
+
+pub struct NativeBackend {
+    clients: Mutex<HashMap<String, SharedClient>>,
+    fallback: Option<Box<dyn OpsBackend>>,
+}
+
+impl Default for NativeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NativeBackend {
+    pub fn new() -> Self {
+        Self {
+            clients: Mutex::new(HashMap::new()),
+            fallback: None,
+        }
+    }
+
+    /// Delegate Kerberos sessions to `fallback`.
+    pub fn with_fallback(fallback: Box<dyn OpsBackend>) -> Self {
+        Self {
+            fallback: Some(fallback),
+            ..Self::new()
+        }
+    }
+
+    fn fallback_for(&self, session: &SmbSession) -> SmbResult<Option<&dyn OpsBackend>> {
+        if !session.config.use_kerberos {
+            return Ok(None);
+        }
+        self.fallback.as_deref().map(Some).ok_or_else(|| {
+            SmbError::Unsupported("the native SMB client does not support Kerberos".into())
+        })
+    }
+
+    async fn connect(session: &SmbSession) -> SmbResult<Smb2Client> {
+        validate_server_host(&session.config.host)?;
+        Smb2Client::connect(&ClientConfig::from_connection(&session.config)).await
+    }
+
+    /// The session's live connection, reconnecting if the last one broke.
+    async fn client(&self, session: &SmbSession) -> SmbResult<SharedClient> {
+        if let Some(client) = self.clients.lock().await.get(&session.id) {
+            if !client.lock().await.is_broken() {
+                return Ok(client.clone());
+            }
+        }
+        let client = Arc::new(Mutex::new(Self::connect(session).await?));
+        self.clients
+            .lock()
+            .await
+            .insert(session.id.clone(), client.clone());
+        Ok(client)
+    }
+}
+
+/// Share-relative SMB path: separators normalised to `\`, no leading
+/// separator, `.` segments dropped and `..` rejected.
+fn smb_path(path: &str) -> SmbResult<String> {
+    let mut parts = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                return Err(SmbError::InvalidPath(
+                    "parent-directory segments are not allowed in SMB paths".into(),
+                ))
+            }
+            _ => parts.push(part),
+        }
+    }
+    Ok(parts.join("\\"))
+}
+
+fn child_path(parent: &str, name: &str) -> String {
+    let mut path = parent.trim_end_matches('/').to_string();
+    if !path.is_empty() && !path.ends_with('/') {
+        path.push('/');
+    }
+    path.push_str(name);
+    path
+}
+
+fn entry_type(attributes: u32) -> SmbEntryType {
+    if attributes & wire::ATTR_REPARSE_POINT != 0 {
+        SmbEntryType::Symlink
+    } else if attributes & wire::ATTR_DIRECTORY != 0 {
+        SmbEntryType::Directory
+    } else {
+        SmbEntryType::File
+    }
+}
+
+fn open_request(
+    path: &str,
+    desired_access: u32,
+    disposition: u32,
+    options: u32,
+) -> SmbResult<CreateRequest> {
+    Ok(CreateRequest {
+        desired_access: desired_access | wire::SYNCHRONIZE,
+        file_attributes: if options & wire::OPTION_DIRECTORY_FILE != 0 {
+            wire::ATTR_DIRECTORY
+        } else {
+            wire::ATTR_NORMAL
+        },
+        share_access: wire::SHARE_ALL,
+        create_disposition: disposition,
+        create_options: options,
+        name: smb_path(path)?,
+    })
+}
+
+async fn open(
+    client: &mut Smb2Client,
+    tree: u32,
+    path: &str,
+    desired_access: u32,
+    disposition: u32,
+    options: u32,
+) -> SmbResult<CreateResponse> {
+    let request = open_request(path, desired_access, disposition, options)?;
+    client.create(tree, &request).await
+}
+
+/// Close `handle`, preferring the operation's error over the close error.
+async fn finish<T>(
+    client: &mut Smb2Client,
+    tree: u32,
+    handle: &CreateResponse,
+    result: SmbResult<T>,
+) -> SmbResult<T> {
+    let closed = client.close(tree, handle.file_id).await;
+    let value = result?;
+    closed?;
+    Ok(value)
+}
+
+async fn list_entries(
+    client: &mut Smb2Client,
+    tree: u32,
+    path: &str,
+) -> SmbResult<Vec<DirectoryInfo>> {
+    let dir = open(
+        client,
+        tree,
+        path,
+        wire::FILE_READ_DATA | wire::FILE_READ_ATTRIBUTES,
+        wire::DISPOSITION_OPEN,
+        wire::OPTION_DIRECTORY_FILE,
+    )
+    .await?;
+    let mut entries = Vec::new();
+    let mut restart = true;
+    let listed = loop {
+        match client.query_directory(tree, dir.file_id, restart).await {
+            Ok(Some(batch)) => {
+                entries.extend(
+                    batch
+                        .into_iter()
+                        .filter(|entry| entry.name != "." && entry.name != ".."),
+                );
+                if entries.len() > MAX_DIR_ENTRIES {
+                    break Err(SmbError::Backend(
+                        "SMB directory listing exceeded safety limits".into(),
+                    ));
+                }
+                restart = false;
+            }
+            Ok(None) => break Ok(entries),
+            Err(error) => break Err(error),
+        }
+    };
+    finish(client, tree, &dir, listed).await
+}
+
+async fn delete(client: &mut Smb2Client, tree: u32, path: &str, options: u32) -> SmbResult<()> {
+    let handle = open(
+        client,
+        tree,
+        path,
+        wire::DELETE | wire::FILE_READ_ATTRIBUTES,
+        wire::DISPOSITION_OPEN,
+        options,
+    )
+    .await?;
+    let result = client
+        .set_info(
+            tree,
+            handle.file_id,
+            wire::FILE_DISPOSITION_INFORMATION,
+            &wire::delete_on_close_information(),
+        )
+        .await;
+    finish(client, tree, &handle, result).await
+}
+
+/// Remove a directory tree without following reparse points.
+async fn delete_tree(client: &mut Smb2Client, tree: u32, root: &str) -> SmbResult<()> {
+    let mut dirs = vec![root.to_string()];
+    let mut next = 0;
+    while next < dirs.len() {
+        let dir = dirs[next].clone();
+        for entry in list_entries(client, tree, &dir).await? {
+            let path = child_path(&dir, &entry.name);
+            match entry_type(entry.file_attributes) {
+                SmbEntryType::Directory => dirs.push(path),
+                SmbEntryType::Symlink => {
+                    delete(client, tree, &path, wire::OPTION_OPEN_REPARSE_POINT).await?
+                }
+                _ => delete(client, tree, &path, wire::OPTION_NON_DIRECTORY_FILE).await?,
+            }
+            if dirs.len() > MAX_DIR_ENTRIES {
+                return Err(SmbError::Backend(
+                    "SMB recursive delete exceeded safety limits".into(),
+                ));
+            }
+        }
+        next += 1;
+    }
+    for dir in dirs.iter().rev() {
+        delete(client, tree, dir, wire::OPTION_DIRECTORY_FILE).await?;
+    }
+    Ok(())
+}
+
+fn read_limit_error(size: u64, max: u64) -> SmbError {
+    SmbError::Other(format!(
+        "file size {size} exceeds the inline limit {max}; use smb_download_file"
+    ))
+}
+
+#[async_trait]
+impl OpsBackend for NativeBackend {
+    async fn probe(&self, session: &SmbSession) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.probe(session).await;
+        }
+        let mut client = Self::connect(session).await?;
+        if let Some(share) = session.config.share.as_deref() {
+            client.tree(share).await?;
+        }
+        self.clients
+            .lock()
+            .await
+            .insert(session.id.clone(), Arc::new(Mutex::new(client)));
+        Ok(())
+    }
+
+    async fn disconnect(&self, session: &SmbSession) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.disconnect(session).await;
+        }
+        let client = self.clients.lock().await.remove(&session.id);
+        if let Some(client) = client {
+            // The session is gone either way; a failed LOGOFF only means the
+            // server will reap it on its own.
+            let _ = client.lock().await.logoff().await;
+        }
+        Ok(())
+    }
+
+    fn shares_connections(&self) -> bool {
+        false
+    }
+
+    async fn list_shares(&self, session: &SmbSession) -> SmbResult<Vec<SmbShareInfo>> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.list_shares(session).await;
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        client.list_shares().await
+    }
+
+    async fn list_dir(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        path: &str,
+    ) -> SmbResult<Vec<SmbDirEntry>> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.list_dir(session, share, path).await;
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let entries = list_entries(&mut client, tree, path).await?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| SmbDirEntry {
+                path: child_path(path, &entry.name),
+                entry_type: entry_type(entry.file_attributes),
+                size: entry.end_of_file,
+                modified: wire::filetime_to_millis(entry.times.last_write),
+                is_hidden: entry.file_attributes & wire::ATTR_HIDDEN != 0
+                    || entry.name.starts_with('.'),
+                is_readonly: entry.file_attributes & wire::ATTR_READONLY != 0,
+                is_system: entry.file_attributes & wire::ATTR_SYSTEM != 0,
+                name: entry.name,
+            })
+            .collect())
+    }
+
+    async fn stat(&self, session: &SmbSession, share: &str, path: &str) -> SmbResult<SmbStat> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.stat(session, share, path).await;
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            path,
+            wire::FILE_READ_ATTRIBUTES,
+            wire::DISPOSITION_OPEN,
+            0,
+        )
+        .await?;
+        finish(&mut client, tree, &handle, Ok(())).await?;
+        let attributes = handle.file_attributes;
+        Ok(SmbStat {
+            path: path.to_string(),
+            entry_type: entry_type(attributes),
+            size: handle.end_of_file,
+            modified: wire::filetime_to_millis(handle.times.last_write),
+            created: wire::filetime_to_millis(handle.times.creation),
+            accessed: wire::filetime_to_millis(handle.times.last_access),
+            is_hidden: attributes & wire::ATTR_HIDDEN != 0,
+            is_readonly: attributes & wire::ATTR_READONLY != 0,
+            is_system: attributes & wire::ATTR_SYSTEM != 0,
+        })
+    }
+
+    async fn read_file(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        path: &str,
+        max_bytes: Option<u64>,
+    ) -> SmbResult<SmbReadResult> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.read_file(session, share, path, max_bytes).await;
+        }
+        let max = inline_read_limit(max_bytes);
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            path,
+            wire::FILE_READ_DATA | wire::FILE_READ_ATTRIBUTES,
+            wire::DISPOSITION_OPEN,
+            wire::OPTION_NON_DIRECTORY_FILE,
+        )
+        .await?;
+        let result = async {
+            if handle.end_of_file > max {
+                return Err(read_limit_error(handle.end_of_file, max));
+            }
+            let mut bytes = Vec::with_capacity(handle.end_of_file as usize);
+            loop {
+                let chunk_size = client.max_io();
+                let chunk = client
+                    .read(tree, handle.file_id, bytes.len() as u64, chunk_size)
+                    .await?;
+                if chunk.is_empty() {
+                    break;
+                }
+                bytes.extend_from_slice(&chunk);
+                if bytes.len() as u64 > max {
+                    // The file grew after it was opened.
+                    return Err(read_limit_error(bytes.len() as u64, max));
+                }
+            }
+            Ok(bytes)
+        }
+        .await;
+        let bytes = finish(&mut client, tree, &handle, result).await?;
+        Ok(SmbReadResult {
+            path: path.to_string(),
+            size: bytes.len() as u64,
+            content_b64: base64::engine::general_purpose::STANDARD.encode(&bytes),
+        })
+    }
+
+    async fn write_file(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        path: &str,
+        content_b64: &str,
+        overwrite: bool,
+    ) -> SmbResult<SmbWriteResult> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback
+                .write_file(session, share, path, content_b64, overwrite)
+                .await;
+        }
+        validate_inline_base64(content_b64)?;
+        let cleaned: String = content_b64
+            .chars()
+            .filter(|ch| !ch.is_ascii_whitespace())
+            .collect();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(cleaned)
+            .map_err(|e| SmbError::Other(format!("base64 decode: {e}")))?;
+        if bytes.len() as u64 > MAX_INLINE_FILE_BYTES {
+            return Err(SmbError::Other(
+                "decoded SMB payload exceeds the 16 MiB safety limit".into(),
+            ));
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        // CREATE fails with a name collision if the file exists, which makes
+        // `overwrite = false` atomic on the server.
+        let disposition = if overwrite {
+            wire::DISPOSITION_OVERWRITE_IF
+        } else {
+            wire::DISPOSITION_CREATE
+        };
+        let handle = open(
+            &mut client,
+            tree,
+            path,
+            wire::FILE_WRITE_DATA | wire::FILE_READ_ATTRIBUTES | wire::FILE_WRITE_ATTRIBUTES,
+            disposition,
+            wire::OPTION_NON_DIRECTORY_FILE,
+        )
+        .await?;
+        let result = client.write_all(tree, handle.file_id, 0, &bytes).await;
+        let bytes_written = finish(&mut client, tree, &handle, result).await?;
+        Ok(SmbWriteResult {
+            path: path.to_string(),
+            bytes_written,
+        })
+    }
+
+    async fn download_file(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        remote_path: &str,
+        local_path: &str,
+    ) -> SmbResult<SmbTransferResult> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback
+                .download_file(session, share, remote_path, local_path)
+                .await;
+        }
+        let started = Instant::now();
+        let (destination, temp) = atomic_download_temp(local_path)?;
+        let mut local = tokio::fs::File::from_std(
+            temp.reopen()
+                .map_err(|_| SmbError::Backend("unable to open SMB download file".into()))?,
+        );
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            remote_path,
+            wire::FILE_READ_DATA | wire::FILE_READ_ATTRIBUTES,
+            wire::DISPOSITION_OPEN,
+            wire::OPTION_NON_DIRECTORY_FILE,
+        )
+        .await?;
+        let result = async {
+            let mut offset = 0u64;
+            loop {
+                let chunk_size = client.max_io();
+                let chunk = client
+                    .read(tree, handle.file_id, offset, chunk_size)
+                    .await?;
+                if chunk.is_empty() {
+                    break;
+                }
+                local
+                    .write_all(&chunk)
+                    .await
+                    .map_err(|_| SmbError::Backend("unable to write SMB download file".into()))?;
+                offset += chunk.len() as u64;
+            }
+            local
+                .flush()
+                .await
+                .map_err(|_| SmbError::Backend("unable to write SMB download file".into()))?;
+            Ok(offset)
+        }
+        .await;
+        let bytes_transferred = finish(&mut client, tree, &handle, result).await?;
+        drop(local);
+        persist_atomic_download(temp, &destination)?;
+        Ok(SmbTransferResult {
+            remote_path: remote_path.to_string(),
+            local_path: local_path.to_string(),
+            bytes_transferred,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn upload_file(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        local_path: &str,
+        remote_path: &str,
+    ) -> SmbResult<SmbTransferResult> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback
+                .upload_file(session, share, local_path, remote_path)
+                .await;
+        }
+        let started = Instant::now();
+        let mut local = tokio::fs::File::open(local_path)
+            .await
+            .map_err(|e| SmbError::Backend(format!("local open: {e}")))?;
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            remote_path,
+            wire::FILE_WRITE_DATA | wire::FILE_READ_ATTRIBUTES | wire::FILE_WRITE_ATTRIBUTES,
+            wire::DISPOSITION_OVERWRITE_IF,
+            wire::OPTION_NON_DIRECTORY_FILE,
+        )
+        .await?;
+        let result = async {
+            let mut buf = vec![0u8; client.max_io() as usize];
+            let mut offset = 0u64;
+            loop {
+                let n = local
+                    .read(&mut buf)
+                    .await
+                    .map_err(|e| SmbError::Backend(format!("local read: {e}")))?;
+                if n == 0 {
+                    break;
+                }
+                offset += client
+                    .write_all(tree, handle.file_id, offset, &buf[..n])
+                    .await?;
+            }
+            Ok(offset)
+        }
+        .await;
+        let bytes_transferred = finish(&mut client, tree, &handle, result).await?;
+        Ok(SmbTransferResult {
+            remote_path: remote_path.to_string(),
+            local_path: local_path.to_string(),
+            bytes_transferred,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn mkdir(&self, session: &SmbSession, share: &str, path: &str) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.mkdir(session, share, path).await;
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            path,
+            wire::FILE_READ_ATTRIBUTES,
+            wire::DISPOSITION_CREATE,
+            wire::OPTION_DIRECTORY_FILE,
+        )
+        .await?;
+        finish(&mut client, tree, &handle, Ok(())).await
+    }
+
+    async fn rmdir(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        path: &str,
+        recursive: bool,
+    ) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.rmdir(session, share, path, recursive).await;
+        }
+        if smb_path(path)?.is_empty() {
+            return Err(SmbError::InvalidPath(
+                "refusing to remove the root of an SMB share".into(),
+            ));
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        if recursive {
+            delete_tree(&mut client, tree, path).await
+        } else {
+            delete(&mut client, tree, path, wire::OPTION_DIRECTORY_FILE).await
+        }
+    }
+
+    async fn delete_file(&self, session: &SmbSession, share: &str, path: &str) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.delete_file(session, share, path).await;
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        delete(&mut client, tree, path, wire::OPTION_NON_DIRECTORY_FILE).await
+    }
+
+    async fn rename(
+        &self,
+        session: &SmbSession,
+        share: &str,
+        from: &str,
+        to: &str,
+    ) -> SmbResult<()> {
+        if let Some(fallback) = self.fallback_for(session)? {
+            return fallback.rename(session, share, from, to).await;
+        }
+        let target = smb_path(to)?;
+        if target.is_empty() {
+            return Err(SmbError::InvalidPath("rename target is empty".into()));
+        }
+        let client = self.client(session).await?;
+        let mut client = client.lock().await;
+        let tree = client.tree(share).await?;
+        let handle = open(
+            &mut client,
+            tree,
+            from,
+            wire::DELETE | wire::FILE_READ_ATTRIBUTES,
+            wire::DISPOSITION_OPEN,
+            0,
+        )
+        .await?;
+        let result = client
+            .set_info(
+                tree,
+                handle.file_id,
+                wire::FILE_RENAME_INFORMATION,
+                &wire::rename_information(&target, false),
+            )
+            .await;
+        finish(&mut client, tree, &handle, result).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fake_server::FakeServer;
+    use super::*;
+
+    fn session(server: &FakeServer, password: &str, encrypt: bool) -> SmbSession {
+        let config: SmbConnectionConfig = serde_json::from_value(serde_json::json!({
+            "host": "127.0.0.1",
+            "port": server.port(),
+            "username": "alice",
+            "domain": "WORKGROUP",
+            "password": password,
+            "share": "data",
+            "disablePlaintext": encrypt,
+        }))
+        .unwrap();
+        SmbSession::new("s1".into(), config, "native-smb2")
+    }
+
+    #[test]
+    fn smb_paths_are_normalised_and_confined() {
+        assert_eq!(smb_path("/a//b/./c.txt").unwrap(), r"a\b\c.txt");
+        assert_eq!(smb_path("").unwrap(), "");
+        assert!(smb_path("a/../../etc").is_err());
+        assert_eq!(child_path("/", "x"), "x");
+        assert_eq!(child_path("docs/", "x"), "docs/x");
+    }
+
+    #[tokio::test]
+    async fn file_lifecycle_against_fake_server() {
+        for (dialect, encrypt) in [
+            (wire::DIALECT_210, false),
+            (wire::DIALECT_302, true),
+            (wire::DIALECT_311, true),
+        ] {
+            let server = FakeServer::start("alice", "s3cret", dialect).await;
+            let backend = NativeBackend::new();
+            let session = session(&server, "s3cret", encrypt);
+            backend.probe(&session).await.unwrap();
+
+            let content = base64::engine::general_purpose::STANDARD.encode(b"hello smb");
+            backend
+                .write_file(&session, "data", "/docs/a.txt", &content, false)
+                .await
+                .unwrap_err();
+            backend.mkdir(&session, "data", "/docs").await.unwrap();
+            let written = backend
+                .write_file(&session, "data", "/docs/a.txt", &content, false)
+                .await
+                .unwrap();
+            assert_eq!(written.bytes_written, 9);
+            // overwrite=false must not clobber.
+            assert!(backend
+                .write_file(&session, "data", "/docs/a.txt", &content, false)
+                .await
+                .is_err());
+
+            let listing = backend.list_dir(&session, "data", "/docs").await.unwrap();
+            assert_eq!(listing.len(), 1);
+            assert_eq!(listing[0].path, "/docs/a.txt");
+            assert_eq!(listing[0].size, 9);
+            assert_eq!(listing[0].entry_type, SmbEntryType::File);
+
+            let read = backend
+                .read_file(&session, "data", "/docs/a.txt", None)
+                .await
+                .unwrap();
+            assert_eq!(read.content_b64, content);
+            assert!(backend
+                .read_file(&session, "data", "/docs/a.txt", Some(4))
+                .await
+                .is_err());
+
+            backend
+                .rename(&session, "data", "/docs/a.txt", "/docs/b.txt")
+                .await
+                .unwrap();
+            let stat = backend.stat(&session, "data", "/docs/b.txt").await.unwrap();
+            assert_eq!(stat.size, 9);
+            assert!(stat.modified.is_some());
+
+            assert!(backend
+                .rmdir(&session, "data", "/docs", false)
+                .await
+                .is_err());
+            backend
+                .rmdir(&session, "data", "/docs", true)
+                .await
+                .unwrap();
+            assert!(backend
+                .list_dir(&session, "data", "/")
+                .await
+                .unwrap()
+                .is_empty());
+
+            let shares = backend.list_shares(&session).await.unwrap();
+            assert!(shares
+                .iter()
+                .any(|s| s.name == "data" && s.share_type == SmbShareType::Disk));
+            assert!(shares.iter().any(|s| s.name == "IPC$" && s.is_admin));
+
+            assert_eq!(server.saw_encrypted(), encrypt);
+            backend.disconnect(&session).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn large_transfers_use_multi_credit_io() {
+        let server = FakeServer::start("alice", "s3cret", wire::DIALECT_311).await;
+        let backend = NativeBackend::new();
+        let session = session(&server, "s3cret", true);
+        backend.probe(&session).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("big.bin");
+        let payload: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&source, &payload).unwrap();
+        let up = backend
+            .upload_file(&session, "data", source.to_str().unwrap(), "/big.bin")
+            .await
+            .unwrap();
+        assert_eq!(up.bytes_transferred, payload.len() as u64);
+
+        let target = dir.path().join("copy.bin");
+        let down = backend
+            .download_file(&session, "data", "/big.bin", target.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(down.bytes_transferred, payload.len() as u64);
+        assert_eq!(std::fs::read(&target).unwrap(), payload);
+        assert!(server.max_credit_charge() > 1);
+    }
+
+    #[tokio::test]
+    async fn bad_password_is_an_auth_failure() {
+        let server = FakeServer::start("alice", "s3cret", wire::DIALECT_311).await;
+        let backend = NativeBackend::new();
+        let error = backend
+            .probe(&session(&server, "wrong", true))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SmbError::AuthFailed(_)), "{error}");
+    }
+
+    #[tokio::test]
+    async fn kerberos_without_fallback_is_unsupported() {
+        let config: SmbConnectionConfig = serde_json::from_value(serde_json::json!({
+            "host": "files.internal",
+            "useKerberos": true,
+        }))
+        .unwrap();
+        let session = SmbSession::new("k".into(), config, "unix-smbclient");
+        let error = NativeBackend::new().probe(&session).await.unwrap_err();
+        assert!(matches!(error, SmbError::Unsupported(_)));
+    }
+}
@@ -0,0 +1,817 @@
+// ── SMB2/3 connection ────────────────────────────────────────────────────────
+//
+// One TCP connection (Direct TCP, port 445) carrying one authenticated
+// session. Requests are issued strictly one at a time, so a message id is
+// always answered by the next non-interim response. The client tracks the
+// credit window (multi-credit requests when the server offers LARGE_MTU),
+// signs every request once a session key exists and seals requests in a
+// TRANSFORM_HEADER when the session, the tree or the caller's policy asks
+// for SMB3 encryption.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use zeroize::Zeroize;
+
+use super::crypto::{Cipher, PreauthHash, SessionKeys};
+use super::ntlm;
+use super::rpc;
+use super::wire::{self, status, CreateRequest, CreateResponse, DirectoryInfo, FileId, Header};
+use crate::smb::types::{SmbConnectionConfig, SmbError, SmbResult, SmbShareInfo};
+
+/// Per-exchange socket timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest single READ / WRITE / QUERY_DIRECTORY the client issues.
+const MAX_IO_SIZE: u32 = 1024 * 1024;
+/// Without LARGE_MTU every request is limited to one 64 KiB credit.
+const SINGLE_CREDIT_IO: u32 = 64 * 1024;
+/// Reject frames larger than this instead of buffering them.
+const MAX_FRAME_LEN: usize = 2 * MAX_IO_SIZE as usize + 64 * 1024;
+/// Extra credits requested with every message so large I/O stays possible.
+const CREDIT_REQUEST: u16 = 64;
+/// Unsolicited oplock-break notifications use this message id.
+const OPLOCK_BREAK_MESSAGE_ID: u64 = u64::MAX;
+
+/// Connection parameters derived from an `SmbConnectionConfig`.
+pub struct ClientConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub domain: String,
+    pub password: String,
+    /// Refuse unencrypted sessions and encrypt every request.
+    pub require_encryption: bool,
+    pub timeout: Duration,
+}
+
+impl ClientConfig {
+    pub fn from_connection(config: &SmbConnectionConfig) -> Self {
+        let raw_user = config.username.clone().unwrap_or_default();
+        // Accept `DOMAIN\user` as well as a separate domain field.
+        let (user, domain) = match raw_user.split_once('\\') {
+            Some((domain, user)) => (user.to_string(), domain.to_string()),
+            None => (
+                raw_user,
+                config
+                    .domain
+                    .clone()
+                    .or_else(|| config.workgroup.clone())
+                    .unwrap_or_default(),
+            ),
+        };
+        Self {
+            host: config.host.clone(),
+            port: config.port,
+            user,
+            domain,
+            password: config.password.clone().unwrap_or_default(),
+            require_encryption: config.disable_plaintext,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Drop for ClientConfig {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tree {
+    id: u32,
+    encrypt: bool,
+}
+
+/// An authenticated SMB2/3 connection.
+pub struct Smb2Client {
+    stream: TcpStream,
+    host: String,
+    timeout: Duration,
+    dialect: u16,
+    next_message_id: u64,
+    credits: u16,
+    large_mtu: bool,
+    max_read: u32,
+    max_write: u32,
+    max_transact: u32,
+    cipher: Option<Cipher>,
+    session_id: u64,
+    keys: Option<SessionKeys>,
+    encrypt_session: bool,
+    require_encryption: bool,
+    guest: bool,
+    trees: HashMap<String, Tree>,
+    rpc_call_id: u32,
+    broken: bool,
+}
+
+impl Smb2Client {
+    /// Connect, negotiate a dialect and authenticate with NTLMv2.
+    pub async fn connect(config: &ClientConfig) -> SmbResult<Self> {
+        let host = config.host.trim_start_matches('[').trim_end_matches(']');
+        let stream = tokio::time::timeout(config.timeout, TcpStream::connect((host, config.port)))
+            .await
+            .map_err(|_| SmbError::Network("timed out connecting to the SMB server".into()))?
+            .map_err(|e| SmbError::Network(format!("unable to reach the SMB server: {e}")))?;
+        let _ = stream.set_nodelay(true);
+
+        let mut client = Self {
+            stream,
+            host: config.host.clone(),
+            timeout: config.timeout,
+            dialect: 0,
+            next_message_id: 0,
+            credits: 1,
+            large_mtu: false,
+            max_read: SINGLE_CREDIT_IO,
+            max_write: SINGLE_CREDIT_IO,
+            max_transact: SINGLE_CREDIT_IO,
+            cipher: None,
+            session_id: 0,
+            keys: None,
+            encrypt_session: false,
+            require_encryption: config.require_encryption,
+            guest: false,
+            trees: HashMap::new(),
+            rpc_call_id: 0,
+            broken: false,
+        };
+        let preauth = client.negotiate().await?;
+        client.session_setup(config, preauth).await?;
+        Ok(client)
+    }
+
+    /// Negotiated dialect (`wire::DIALECT_*`).
+    pub fn dialect(&self) -> u16 {
+        self.dialect
+    }
+
+    /// True once a transport or protocol error left the connection unusable.
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// True if requests on this session are sealed with SMB3 encryption.
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypt_session
+    }
+
+    pub fn is_guest(&self) -> bool {
+        self.guest
+    }
+
+    // ── Negotiate / session setup ────────────────────────────────────────────
+
+    async fn negotiate(&mut self) -> SmbResult<PreauthHash> {
+        let dialects: &[u16] = if self.require_encryption {
+            &[wire::DIALECT_300, wire::DIALECT_302, wire::DIALECT_311]
+        } else {
+            &[
+                wire::DIALECT_202,
+                wire::DIALECT_210,
+                wire::DIALECT_300,
+                wire::DIALECT_302,
+                wire::DIALECT_311,
+            ]
+        };
+        let salt: [u8; 32] = rand::random();
+        let contexts = vec![
+            (wire::CTX_PREAUTH_INTEGRITY, wire::preauth_context(&salt)),
+            (
+                wire::CTX_ENCRYPTION,
+                wire::encryption_context(&[wire::CIPHER_AES128_GCM, wire::CIPHER_AES128_CCM]),
+            ),
+        ];
+        let body = wire::negotiate_request(
+            dialects,
+            wire::SIGNING_ENABLED,
+            wire::CAP_LARGE_MTU | wire::CAP_ENCRYPTION,
+            rand::random(),
+            &contexts,
+        );
+        let (request, message_id) = self.build(wire::NEGOTIATE, 0, &body, 0);
+        self.send(request.clone(), false).await?;
+        let (header, response) = self.receive(message_id, false).await?;
+        if header.status != status::SUCCESS {
+            return Err(wire::status_error("NEGOTIATE", header.status));
+        }
+        let negotiated = wire::NegotiateResponse::decode(&response)?;
+        if !dialects.contains(&negotiated.dialect) {
+            return Err(SmbError::Unsupported(format!(
+                "server selected SMB dialect 0x{:04X}, which was not offered",
+                negotiated.dialect
+            )));
+        }
+        self.dialect = negotiated.dialect;
+        self.large_mtu = negotiated.dialect != wire::DIALECT_202
+            && negotiated.capabilities & wire::CAP_LARGE_MTU != 0;
+        let io_cap = if self.large_mtu {
+            MAX_IO_SIZE
+        } else {
+            SINGLE_CREDIT_IO
+        };
+        let clamp = |value: u32| value.clamp(4096, io_cap);
+        self.max_read = clamp(negotiated.max_read_size);
+        self.max_write = clamp(negotiated.max_write_size);
+        self.max_transact = clamp(negotiated.max_transact_size);
+
+        let mut preauth = PreauthHash::default();
+        self.cipher = match negotiated.dialect {
+            wire::DIALECT_311 => {
+                let mut hash_ok = false;
+                let mut cipher = None;
+                for (kind, data) in &negotiated.contexts {
+                    match *kind {
+                        wire::CTX_PREAUTH_INTEGRITY => {
+                            hash_ok = wire::first_context_value(data, 4) == Some(wire::HASH_SHA512)
+                        }
+                        wire::CTX_ENCRYPTION => {
+                            cipher = wire::first_context_value(data, 2).and_then(Cipher::from_id)
+                        }
+                        _ => {}
+                    }
+                }
+                if !hash_ok {
+                    return Err(SmbError::Backend(
+                        "SMB 3.1.1 server did not select SHA-512 pre-authentication integrity"
+                            .into(),
+                    ));
+                }
+                preauth.update(&request);
+                preauth.update(&response);
+                cipher
+            }
+            wire::DIALECT_300 | wire::DIALECT_302
+                if negotiated.capabilities & wire::CAP_ENCRYPTION != 0 =>
+            {
+                Some(Cipher::Aes128Ccm)
+            }
+            _ => None,
+        };
+        if self.require_encryption && self.cipher.is_none() {
+            return Err(SmbError::Unsupported(
+                "the SMB server does not support SMB3 encryption, which this connection requires"
+                    .into(),
+            ));
+        }
+        Ok(preauth)
+    }
+
+    async fn session_setup(
+        &mut self,
+        config: &ClientConfig,
+        mut preauth: PreauthHash,
+    ) -> SmbResult<()> {
+        let is_311 = self.dialect == wire::DIALECT_311;
+        let negotiate_token = ntlm::negotiate_message();
+        let body = wire::session_setup_request(
+            wire::SIGNING_ENABLED as u8,
+            &ntlm::spnego_init(&negotiate_token),
+        );
+        let (request, message_id) = self.build(wire::SESSION_SETUP, 0, &body, 0);
+        self.send(request.clone(), false).await?;
+        let (header, response) = self.receive(message_id, false).await?;
+        if header.status != status::MORE_PROCESSING_REQUIRED {
+            return Err(wire::status_error("SESSION_SETUP", header.status));
+        }
+        if is_311 {
+            preauth.update(&request);
+            preauth.update(&response);
+        }
+        self.session_id = header.session_id;
+        let (_, blob) = wire::decode_session_setup_response(&response)?;
+        let challenge = match ntlm::spnego_unwrap(&blob) {
+            (Some(token), _) => token,
+            (None, _) => {
+                return Err(SmbError::AuthFailed(
+                    "the SMB server did not offer NTLM authentication".into(),
+                ))
+            }
+        };
+
+        let output = ntlm::authenticate(
+            &ntlm::NtlmCredentials {
+                user: &config.user,
+                domain: &config.domain,
+                password: &config.password,
+                workstation: "",
+            },
+            &negotiate_token,
+            &challenge,
+            rand::random(),
+            ntlm::filetime_now(),
+        )?;
+        let body = wire::session_setup_request(
+            wire::SIGNING_ENABLED as u8,
+            &ntlm::spnego_response(None, Some(&output.message)),
+        );
+        let (request, message_id) = self.build(wire::SESSION_SETUP, 0, &body, 0);
+        self.send(request.clone(), false).await?;
+        let (header, response) = self.receive(message_id, false).await?;
+        if header.status != status::SUCCESS {
+            return Err(wire::status_error("SESSION_SETUP", header.status));
+        }
+        if is_311 {
+            // The final response is excluded from the hash.
+            preauth.update(&request);
+        }
+        let (session_flags, blob) = wire::decode_session_setup_response(&response)?;
+        if let (_, Some(ntlm::NEG_REJECT)) = ntlm::spnego_unwrap(&blob) {
+            return Err(SmbError::AuthFailed(
+                "SMB authentication was rejected".into(),
+            ));
+        }
+
+        self.guest =
+            session_flags & (wire::SESSION_FLAG_IS_GUEST | wire::SESSION_FLAG_IS_NULL) != 0;
+        if self.guest {
+            if self.require_encryption {
+                return Err(SmbError::AuthFailed(
+                    "the SMB server granted only a guest or anonymous session, which cannot be encrypted"
+                        .into(),
+                ));
+            }
+            return Ok(());
+        }
+
+        let keys = SessionKeys::derive(self.dialect, &output.session_key, &preauth.0);
+        let signed = header.flags & wire::FLAG_SIGNED != 0;
+        if (signed || is_311) && !keys.verify(&response) {
+            return Err(SmbError::AuthFailed(
+                "the SMB server's session setup signature did not verify".into(),
+            ));
+        }
+        self.encrypt_session = self.cipher.is_some()
+            && (self.require_encryption || session_flags & wire::SESSION_FLAG_ENCRYPT_DATA != 0);
+        if session_flags & wire::SESSION_FLAG_ENCRYPT_DATA != 0 && self.cipher.is_none() {
+            return Err(SmbError::Unsupported(
+                "the SMB server requires encryption but no cipher was negotiated".into(),
+            ));
+        }
+        self.keys = Some(keys);
+        Ok(())
+    }
+
+    // ── Framing ──────────────────────────────────────────────────────────────
+
+    /// Credit charge for a request moving `payload` bytes.
+    fn charge(&self, payload: u32) -> u16 {
+        if self.dialect == 0 || self.dialect == wire::DIALECT_202 {
+            return 0;
+        }
+        payload.max(1).div_ceil(SINGLE_CREDIT_IO) as u16
+    }
+
+    fn build(&mut self, command: u16, tree_id: u32, body: &[u8], payload: u32) -> (Vec<u8>, u64) {
+        let charge = self.charge(payload);
+        let message_id = self.next_message_id;
+        self.next_message_id += u64::from(charge.max(1));
+        self.credits = self.credits.saturating_sub(charge.max(1));
+        let header = Header {
+            credit_charge: charge,
+            command,
+            credits: charge.max(1) + CREDIT_REQUEST,
+            message_id,
+            tree_id,
+            session_id: self.session_id,
+            ..Header::default()
+        };
+        (wire::message(&header, body), message_id)
+    }
+
+    /// Largest I/O that the credit window and negotiated limit allow.
+    fn io_size(&self, limit: u32) -> u32 {
+        if !self.large_mtu {
+            return limit.min(SINGLE_CREDIT_IO);
+        }
+        let window = u32::from(self.credits.max(1)).saturating_mul(SINGLE_CREDIT_IO);
+        limit.min(window)
+    }
+
+    async fn send(&mut self, mut msg: Vec<u8>, encrypt: bool) -> SmbResult<()> {
+        let payload = match (&self.keys, encrypt) {
+            (Some(keys), true) => {
+                let cipher = self.cipher.ok_or_else(|| {
+                    SmbError::Unsupported("SMB3 encryption was not negotiated".into())
+                })?;
+                keys.encrypt(cipher, self.session_id, &msg)?
+            }
+            (Some(keys), false) => {
+                keys.sign(&mut msg);
+                msg
+            }
+            (None, _) => msg,
+        };
+        let frame = wire::frame(&payload);
+        let result = tokio::time::timeout(self.timeout, self.stream.write_all(&frame)).await;
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => {
+                self.broken = true;
+                Err(SmbError::Network(format!(
+                    "SMB connection write failed: {e}"
+                )))
+            }
+            Err(_) => {
+                self.broken = true;
+                Err(SmbError::Network("SMB connection write timed out".into()))
+            }
+        }
+    }
+
+    async fn read_frame(&mut self) -> SmbResult<Vec<u8>> {
+        let timeout = self.timeout;
+        let stream = &mut self.stream;
+        let read = async {
+            let mut prefix = [0u8; 4];
+            stream.read_exact(&mut prefix).await?;
+            let len = u32::from_be_bytes(prefix) as usize;
+            if prefix[0] != 0 || len > MAX_FRAME_LEN {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "oversized or invalid SMB frame",
+                ));
+            }
+            let mut buf = vec![0u8; len];
+            stream.read_exact(&mut buf).await?;
+            Ok(buf)
+        };
+        match tokio::time::timeout(timeout, read).await {
+            Ok(Ok(frame)) => Ok(frame),
+            Ok(Err(e)) => {
+                self.broken = true;
+                Err(SmbError::Network(format!(
+                    "SMB connection read failed: {e}"
+                )))
+            }
+            Err(_) => {
+                self.broken = true;
+                Err(SmbError::Network(
+                    "SMB server did not respond in time".into(),
+                ))
+            }
+        }
+    }
+
+    fn protocol_error(&mut self, what: &str) -> SmbError {
+        self.broken = true;
+        SmbError::Backend(format!("SMB protocol error: {what}"))
+    }
+
+    /// Wait for the final response to `message_id`.
+    async fn receive(&mut self, message_id: u64, encrypted: bool) -> SmbResult<(Header, Vec<u8>)> {
+        loop {
+            let frame = self.read_frame().await?;
+            let (msg, was_encrypted) = if frame.starts_with(&wire::TRANSFORM_PROTOCOL_ID) {
+                let (Some(keys), Some(cipher)) = (&self.keys, self.cipher) else {
+                    return Err(self.protocol_error("unexpected encrypted message"));
+                };
+                if super::crypto::transform_session_id(&frame) != Some(self.session_id) {
+                    return Err(self.protocol_error("encrypted message for another session"));
+                }
+                match keys.decrypt(cipher, &frame) {
+                    Ok(msg) => (msg, true),
+                    Err(_) => return Err(self.protocol_error("message failed to decrypt")),
+                }
+            } else {
+                (frame, false)
+            };
+            let header = match Header::decode(&msg) {
+                Ok(header) => header,
+                Err(_) => return Err(self.protocol_error("malformed response header")),
+            };
+            if header.flags & wire::FLAG_SERVER_TO_REDIR == 0 {
+                return Err(self.protocol_error("received a request instead of a response"));
+            }
+            self.credits = self.credits.saturating_add(header.credits);
+            if header.message_id == OPLOCK_BREAK_MESSAGE_ID {
+                // No oplocks are requested; ignore stray break notifications.
+                continue;
+            }
+            if header.message_id != message_id {
+                return Err(self.protocol_error("response to an unexpected message id"));
+            }
+            if header.status == status::PENDING && header.flags & wire::FLAG_ASYNC != 0 {
+                continue;
+            }
+            if encrypted && !was_encrypted {
+                return Err(
+                    self.protocol_error("server answered an encrypted request in plaintext")
+                );
+            }
+            if !was_encrypted {
+                if let Some(keys) = &self.keys {
+                    if header.flags & wire::FLAG_SIGNED == 0 || !keys.verify(&msg) {
+                        return Err(self.protocol_error("response signature did not verify"));
+                    }
+                }
+            }
+            return Ok((header, msg));
+        }
+    }
+
+    fn tree_encrypted(&self, tree_id: u32) -> bool {
+        self.trees
+            .values()
+            .any(|tree| tree.id == tree_id && tree.encrypt)
+    }
+
+    /// Send one request and return the final response, whatever its status.
+    async fn transact(
+        &mut self,
+        command: u16,
+        tree_id: u32,
+        body: &[u8],
+        payload: u32,
+    ) -> SmbResult<(Header, Vec<u8>)> {
+        if self.broken {
+            return Err(SmbError::Network("the SMB connection was lost".into()));
+        }
+        let encrypt = self.keys.is_some() && (self.encrypt_session || self.tree_encrypted(tree_id));
+        let (msg, message_id) = self.build(command, tree_id, body, payload);
+        self.send(msg, encrypt).await?;
+        self.receive(message_id, encrypt).await
+    }
+
+    /// Like `transact`, failing on any status other than success.
+    async fn call(
+        &mut self,
+        name: &str,
+        command: u16,
+        tree_id: u32,
+        body: &[u8],
+        payload: u32,
+    ) -> SmbResult<Vec<u8>> {
+        let (header, msg) = self.transact(command, tree_id, body, payload).await?;
+        if header.status != status::SUCCESS {
+            return Err(wire::status_error(name, header.status));
+        }
+        Ok(msg)
+    }
+
+    // ── Trees ────────────────────────────────────────────────────────────────
+
+    /// Tree id for `share`, connecting on first use.
+    pub async fn tree(&mut self, share: &str) -> SmbResult<u32> {
+        let key = share.to_ascii_lowercase();
+        if let Some(tree) = self.trees.get(&key) {
+            return Ok(tree.id);
+        }
+        let path = format!(r"\\{}\{}", self.host, share);
+        let (header, msg) = self
+            .transact(wire::TREE_CONNECT, 0, &wire::tree_connect_request(&path), 0)
+            .await?;
+        if header.status != status::SUCCESS {
+            return Err(wire::status_error("TREE_CONNECT", header.status));
+        }
+        let response = wire::TreeConnectResponse::decode(&msg)?;
+        let encrypt = response.share_flags & wire::SHARE_FLAG_ENCRYPT_DATA != 0;
+        if encrypt && (self.cipher.is_none() || self.keys.is_none()) {
+            return Err(SmbError::Unsupported(
+                "the SMB share requires encryption, which this session cannot provide".into(),
+            ));
+        }
+        self.trees.insert(
+            key,
+            Tree {
+                id: header.tree_id,
+                encrypt,
+            },
+        );
+        Ok(header.tree_id)
+    }
+
+    // ── File primitives ──────────────────────────────────────────────────────
+
+    pub async fn create(
+        &mut self,
+        tree_id: u32,
+        request: &CreateRequest,
+    ) -> SmbResult<CreateResponse> {
+        let msg = self
+            .call("CREATE", wire::CREATE, tree_id, &request.encode(), 0)
+            .await?;
+        CreateResponse::decode(&msg)
+    }
+
+    pub async fn close(&mut self, tree_id: u32, file_id: FileId) -> SmbResult<()> {
+        self.call(
+            "CLOSE",
+            wire::CLOSE,
+            tree_id,
+            &wire::close_request(file_id),
+            0,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Read up to `length` bytes; an empty result means end of file.
+    pub async fn read(
+        &mut self,
+        tree_id: u32,
+        file_id: FileId,
+        offset: u64,
+        length: u32,
+    ) -> SmbResult<Vec<u8>> {
+        let length = self.io_size(self.max_read.min(length));
+        let body = wire::read_request(file_id, offset, length);
+        let (header, msg) = self.transact(wire::READ, tree_id, &body, length).await?;
+        match header.status {
+            // Named pipes report a message larger than the read as overflow.
+            status::SUCCESS | status::BUFFER_OVERFLOW => wire::decode_read_response(&msg),
+            status::END_OF_FILE => Ok(Vec::new()),
+            other => Err(wire::status_error("READ", other)),
+        }
+    }
+
+    /// Write `data` at `offset`, splitting it into negotiated-size requests.
+    pub async fn write_all(
+        &mut self,
+        tree_id: u32,
+        file_id: FileId,
+        mut offset: u64,
+        mut data: &[u8],
+    ) -> SmbResult<u64> {
+        let mut written = 0u64;
+        while !data.is_empty() {
+            let chunk = (self.io_size(self.max_write) as usize).min(data.len());
+            let body = wire::write_request(file_id, offset, &data[..chunk]);
+            let msg = self
+                .call("WRITE", wire::WRITE, tree_id, &body, chunk as u32)
+                .await?;
+            let count = wire::decode_write_response(&msg)? as usize;
+            if count == 0 || count > chunk {
+                return Err(SmbError::Backend("SMB2 WRITE made no progress".into()));
+            }
+            offset += count as u64;
+            written += count as u64;
+            data = &data[count..];
+        }
+        Ok(written)
+    }
+
+    /// Preferred chunk size for streaming reads and writes.
+    pub fn max_io(&self) -> u32 {
+        self.max_read.min(self.max_write)
+    }
+
+    /// One batch of directory entries, or `None` once the listing is done.
+    pub async fn query_directory(
+        &mut self,
+        tree_id: u32,
+        file_id: FileId,
+        restart: bool,
+    ) -> SmbResult<Option<Vec<DirectoryInfo>>> {
+        let length = self.io_size(self.max_transact);
+        let flags = if restart {
+            wire::QUERY_RESTART_SCANS
+        } else {
+            0
+        };
+        let body = wire::query_directory_request(
+            file_id,
+            wire::FILE_DIRECTORY_INFORMATION,
+            flags,
+            "*",
+            length,
+        );
+        let (header, msg) = self
+            .transact(wire::QUERY_DIRECTORY, tree_id, &body, length)
+            .await?;
+        match header.status {
+            status::SUCCESS => {
+                let buf = wire::decode_output_buffer_response(&msg)?;
+                if buf.is_empty() {
+                    return Ok(None);
+                }
+                DirectoryInfo::decode_list(&buf).map(Some)
+            }
+            status::NO_MORE_FILES => Ok(None),
+            other => Err(wire::status_error("QUERY_DIRECTORY", other)),
+        }
+    }
+
+    pub async fn set_info(
+        &mut self,
+        tree_id: u32,
+        file_id: FileId,
+        info_class: u8,
+        buffer: &[u8],
+    ) -> SmbResult<()> {
+        let body = wire::set_info_request(file_id, wire::INFO_FILE, info_class, buffer);
+        self.call("SET_INFO", wire::SET_INFO, tree_id, &body, 0)
+            .await
+            .map(|_| ())
+    }
+
+    // ── Share enumeration ────────────────────────────────────────────────────
+
+    async fn read_rpc_fragment(
+        &mut self,
+        tree_id: u32,
+        file_id: FileId,
+        pending: &mut Vec<u8>,
+    ) -> SmbResult<rpc::Fragment> {
+        loop {
+            if let Some(fragment) = rpc::Fragment::take(pending)? {
+                return Ok(fragment);
+            }
+            let data = self.read(tree_id, file_id, 0, SINGLE_CREDIT_IO).await?;
+            if data.is_empty() {
+                return Err(SmbError::Backend("srvsvc pipe closed mid-response".into()));
+            }
+            pending.extend_from_slice(&data);
+            if pending.len() > MAX_FRAME_LEN {
+                return Err(SmbError::Backend("srvsvc response is too large".into()));
+            }
+        }
+    }
+
+    async fn share_enum_on_pipe(
+        &mut self,
+        tree_id: u32,
+        pipe: FileId,
+    ) -> SmbResult<Vec<SmbShareInfo>> {
+        let mut pending = Vec::new();
+        self.rpc_call_id += 1;
+        self.write_all(tree_id, pipe, 0, &rpc::bind(self.rpc_call_id))
+            .await?;
+        let ack = self.read_rpc_fragment(tree_id, pipe, &mut pending).await?;
+        rpc::check_bind_ack(&ack)?;
+
+        self.rpc_call_id += 1;
+        let call_id = self.rpc_call_id;
+        let host = self.host.clone();
+        self.write_all(tree_id, pipe, 0, &rpc::share_enum_request(call_id, &host))
+            .await?;
+        let mut stub = Vec::new();
+        loop {
+            let fragment = self.read_rpc_fragment(tree_id, pipe, &mut pending).await?;
+            match fragment.ptype {
+                rpc::PTYPE_RESPONSE if fragment.call_id == call_id => {
+                    stub.extend_from_slice(fragment.stub());
+                    if stub.len() > MAX_FRAME_LEN {
+                        return Err(SmbError::Backend("srvsvc response is too large".into()));
+                    }
+                    if fragment.is_last() {
+                        break;
+                    }
+                }
+                rpc::PTYPE_FAULT => {
+                    return Err(SmbError::Backend(format!(
+                        "srvsvc call failed (fault 0x{:08X})",
+                        rpc::fault_status(&fragment)
+                    )))
+                }
+                _ => return Err(SmbError::Backend("unexpected srvsvc response".into())),
+            }
+        }
+        rpc::parse_share_enum(&stub)
+    }
+
+    /// Enumerate shares via NetrShareEnum on `IPC$`.
+    pub async fn list_shares(&mut self) -> SmbResult<Vec<SmbShareInfo>> {
+        let tree_id = self.tree("IPC$").await?;
+        let open = self
+            .create(
+                tree_id,
+                &CreateRequest {
+                    desired_access: wire::FILE_READ_DATA
+                        | wire::FILE_WRITE_DATA
+                        | wire::FILE_READ_ATTRIBUTES
+                        | wire::READ_CONTROL
+                        | wire::SYNCHRONIZE,
+                    file_attributes: 0,
+                    share_access: wire::SHARE_ALL,
+                    create_disposition: wire::DISPOSITION_OPEN,
+                    create_options: wire::OPTION_NON_DIRECTORY_FILE,
+                    name: rpc::SRVSVC_PIPE.into(),
+                },
+            )
+            .await?;
+        let result = self.share_enum_on_pipe(tree_id, open.file_id).await;
+        let closed = self.close(tree_id, open.file_id).await;
+        let shares = result?;
+        closed?;
+        Ok(shares)
+    }
+
+    /// Log the session off. Best effort: the socket is dropped either way.
+    pub async fn logoff(&mut self) -> SmbResult<()> {
+        if self.broken || self.session_id == 0 {
+            return Ok(());
+        }
+        let result = self
+            .call("LOGOFF", wire::LOGOFF, 0, &wire::empty_request(), 0)
+            .await
+            .map(|_| ());
+        self.broken = true;
+        let _ = self.stream.shutdown().await;
+        result
+    }
+}
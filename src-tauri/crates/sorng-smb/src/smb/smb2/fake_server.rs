@@ -0,0 +1,628 @@
+// ── In-process SMB2 test server ──────────────────────────────────────────────
+//
+// A deliberately small SMB2/3 server for exercising the native client end
+// to end without Samba: one configured dialect, NTLMv2 verification
+// (including the MIC), signing and encryption checks, an in-memory `data`
+// share and an `IPC$` share whose `srvsvc` pipe answers NetrShareEnum.
+// It reuses the client's codecs, so it validates framing and crypto
+// symmetry rather than interoperability — the ignored Samba test in
+// `tests/golden_path.rs` covers that.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::crypto::{Cipher, PreauthHash, SessionKeys};
+use super::ntlm;
+use super::rpc;
+use super::wire::{self, status, CreateRequest, CreateResponse, DirectoryInfo, FileId, Header};
+
+const SESSION_ID: u64 = 0x0000_4000_0000_0001;
+const FIXED_TIME: u64 = 133_500_000_000_000_000;
+
+#[derive(Default)]
+struct Stats {
+    saw_encrypted: AtomicBool,
+    max_credit_charge: AtomicU16,
+}
+
+/// Share-relative path (`\`-separated, root = "") → file bytes, or `None`
+/// for a directory.
+type Fs = BTreeMap<String, Option<Vec<u8>>>;
+
+pub struct FakeServer {
+    port: u16,
+    stats: Arc<Stats>,
+}
+
+impl FakeServer {
+    /// Listen on an ephemeral loopback port, accepting `user` / `password`
+    /// and negotiating exactly `dialect`.
+    pub async fn start(user: &str, password: &str, dialect: u16) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let stats = Arc::new(Stats::default());
+        let fs: Arc<Mutex<Fs>> = Arc::new(Mutex::new(BTreeMap::from([(String::new(), None)])));
+        let (user, password) = (user.to_string(), password.to_string());
+        let task_stats = stats.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let conn = Connection::new(
+                    dialect,
+                    user.clone(),
+                    password.clone(),
+                    fs.clone(),
+                    task_stats.clone(),
+                );
+                tokio::spawn(conn.run(stream));
+            }
+        });
+        Self { port, stats }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn saw_encrypted(&self) -> bool {
+        self.stats.saw_encrypted.load(Ordering::SeqCst)
+    }
+
+    pub fn max_credit_charge(&self) -> u16 {
+        self.stats.max_credit_charge.load(Ordering::SeqCst)
+    }
+}
+
+enum Open {
+    Node {
+        path: String,
+        listed: bool,
+        delete_on_close: bool,
+    },
+    Pipe {
+        output: Vec<u8>,
+        input: Vec<u8>,
+    },
+}
+
+struct Connection {
+    dialect: u16,
+    user: String,
+    password: String,
+    fs: Arc<Mutex<Fs>>,
+    stats: Arc<Stats>,
+    cipher: Option<Cipher>,
+    preauth: PreauthHash,
+    negotiate_token: Vec<u8>,
+    challenge_token: Vec<u8>,
+    server_challenge: [u8; 8],
+    keys: Option<SessionKeys>,
+    trees: HashMap<u32, String>,
+    opens: HashMap<FileId, Open>,
+    next_id: u32,
+}
+
+fn error_body() -> Vec<u8> {
+    vec![9, 0, 0, 0, 0, 0, 0, 0, 0]
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('\\').map(|(p, _)| p).unwrap_or("")
+}
+
+fn name_of(path: &str) -> &str {
+    path.rsplit_once('\\').map(|(_, n)| n).unwrap_or(path)
+}
+
+fn children<'a>(
+    fs: &'a Fs,
+    dir: &'a str,
+) -> impl Iterator<Item = (&'a String, &'a Option<Vec<u8>>)> {
+    fs.iter()
+        .filter(move |(path, _)| !path.is_empty() && parent(path) == dir)
+}
+
+fn node_info(name: &str, node: &Option<Vec<u8>>) -> DirectoryInfo {
+    DirectoryInfo {
+        name: name.to_string(),
+        times: wire::FileTimes {
+            creation: FIXED_TIME,
+            last_access: FIXED_TIME,
+            last_write: FIXED_TIME,
+            change: FIXED_TIME,
+        },
+        end_of_file: node.as_ref().map_or(0, |data| data.len() as u64),
+        allocation_size: 0,
+        file_attributes: if node.is_some() {
+            wire::ATTR_NORMAL
+        } else {
+            wire::ATTR_DIRECTORY
+        },
+    }
+}
+
+async fn read_frame(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut prefix = [0u8; 4];
+    stream.read_exact(&mut prefix).await.ok()?;
+    let mut buf = vec![0u8; u32::from_be_bytes(prefix) as usize];
+    stream.read_exact(&mut buf).await.ok()?;
+    Some(buf)
+}
+
+impl Connection {
+    fn new(
+        dialect: u16,
+        user: String,
+        password: String,
+        fs: Arc<Mutex<Fs>>,
+        stats: Arc<Stats>,
+    ) -> Self {
+        Self {
+            dialect,
+            user,
+            password,
+            fs,
+            stats,
+            cipher: None,
+            preauth: PreauthHash::default(),
+            negotiate_token: Vec::new(),
+            challenge_token: Vec::new(),
+            server_challenge: rand::random(),
+            keys: None,
+            trees: HashMap::new(),
+            opens: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    async fn run(mut self, mut stream: TcpStream) {
+        while let Some(frame) = read_frame(&mut stream).await {
+            let (msg, encrypted) = if frame.starts_with(&wire::TRANSFORM_PROTOCOL_ID) {
+                self.stats.saw_encrypted.store(true, Ordering::SeqCst);
+                let (Some(keys), Some(cipher)) = (&self.keys, self.cipher) else {
+                    return;
+                };
+                match keys.decrypt(cipher, &frame) {
+                    Ok(msg) => (msg, true),
+                    Err(_) => return,
+                }
+            } else {
+                (frame, false)
+            };
+            let Ok(header) = Header::decode(&msg) else {
+                return;
+            };
+            self.stats
+                .max_credit_charge
+                .fetch_max(header.credit_charge, Ordering::SeqCst);
+
+            let signed_ok = encrypted
+                || header.command == wire::NEGOTIATE
+                || header.command == wire::SESSION_SETUP
+                || self
+                    .keys
+                    .as_ref()
+                    .is_none_or(|keys| header.flags & wire::FLAG_SIGNED != 0 && keys.verify(&msg));
+            let (status, body, tree_id) = if signed_ok {
+                self.dispatch(&header, &msg)
+            } else {
+                (status::ACCESS_DENIED, error_body(), header.tree_id)
+            };
+
+            let response_header = Header {
+                credit_charge: header.credit_charge,
+                status,
+                command: header.command,
+                credits: header.credits.max(1),
+                flags: wire::FLAG_SERVER_TO_REDIR,
+                message_id: header.message_id,
+                tree_id,
+                session_id: if header.command == wire::NEGOTIATE {
+                    0
+                } else {
+                    SESSION_ID
+                },
+                ..Header::default()
+            };
+            let mut response = wire::message(&response_header, &body);
+            match header.command {
+                wire::NEGOTIATE if self.dialect == wire::DIALECT_311 => {
+                    self.preauth.update(&msg);
+                    self.preauth.update(&response);
+                }
+                wire::SESSION_SETUP
+                    if status == status::MORE_PROCESSING_REQUIRED
+                        && self.dialect == wire::DIALECT_311 =>
+                {
+                    self.preauth.update(&msg);
+                    self.preauth.update(&response);
+                }
+                _ => {}
+            }
+            let payload = match &self.keys {
+                Some(keys) if encrypted => keys
+                    .encrypt(self.cipher.unwrap(), SESSION_ID, &response)
+                    .unwrap(),
+                Some(keys) => {
+                    keys.sign(&mut response);
+                    response
+                }
+                None => response,
+            };
+            if stream.write_all(&wire::frame(&payload)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    fn dispatch(&mut self, header: &Header, msg: &[u8]) -> (u32, Vec<u8>, u32) {
+        let tree_id = header.tree_id;
+        let result = match header.command {
+            wire::NEGOTIATE => self.negotiate(msg),
+            wire::SESSION_SETUP => self.session_setup(msg),
+            wire::TREE_CONNECT => return self.tree_connect(msg),
+            wire::LOGOFF => Ok((status::SUCCESS, wire::empty_request())),
+            wire::CREATE => self.create(tree_id, msg),
+            wire::CLOSE => self.close(msg),
+            wire::READ => self.read(msg),
+            wire::WRITE => self.write(msg),
+            wire::QUERY_DIRECTORY => self.query_directory(msg),
+            wire::SET_INFO => self.set_info(msg),
+            _ => Err(status::NOT_SUPPORTED),
+        };
+        match result {
+            Ok((status, body)) => (status, body, tree_id),
+            Err(status) => (status, error_body(), tree_id),
+        }
+    }
+
+    fn negotiate(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let body = &msg[wire::HEADER_LEN..];
+        let count = wire::u16_at(body, 2).unwrap_or_default() as usize;
+        let offered: Vec<u16> = (0..count)
+            .filter_map(|i| wire::u16_at(body, 36 + 2 * i))
+            .collect();
+        if !offered.contains(&self.dialect) {
+            return Err(status::NOT_SUPPORTED);
+        }
+        let mut contexts = Vec::new();
+        if self.dialect == wire::DIALECT_311 {
+            self.cipher = Some(Cipher::Aes128Gcm);
+            contexts.push((wire::CTX_PREAUTH_INTEGRITY, wire::preauth_context(&[7; 32])));
+            contexts.push((
+                wire::CTX_ENCRYPTION,
+                wire::encryption_context(&[wire::CIPHER_AES128_GCM]),
+            ));
+        } else if self.dialect >= wire::DIALECT_300 {
+            self.cipher = Some(Cipher::Aes128Ccm);
+        }
+        let response = wire::NegotiateResponse {
+            security_mode: wire::SIGNING_ENABLED,
+            dialect: self.dialect,
+            server_guid: [0x5A; 16],
+            capabilities: wire::CAP_LARGE_MTU | wire::CAP_ENCRYPTION,
+            max_transact_size: 8 * 1024 * 1024,
+            max_read_size: 8 * 1024 * 1024,
+            max_write_size: 8 * 1024 * 1024,
+            security_buffer: Vec::new(),
+            contexts,
+        };
+        Ok((status::SUCCESS, response.encode()))
+    }
+
+    fn session_setup(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let blob = wire::session_setup_request_token(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        let (Some(token), _) = ntlm::spnego_unwrap(&blob) else {
+            return Err(status::INVALID_PARAMETER);
+        };
+        match wire::u32_at(&token, 8) {
+            Some(1) => {
+                self.negotiate_token = token;
+                self.challenge_token = ntlm::Challenge {
+                    flags: wire::u32_at(&self.negotiate_token, 12).unwrap_or_default(),
+                    server_challenge: self.server_challenge,
+                    target_name: "FAKE".into(),
+                    target_info: ntlm::encode_av_pairs(&[
+                        (ntlm::AV_NB_DOMAIN_NAME, wire::utf16le("FAKE")),
+                        (ntlm::AV_NB_COMPUTER_NAME, wire::utf16le("FAKESRV")),
+                        (
+                            ntlm::AV_TIMESTAMP,
+                            ntlm::filetime_now().to_le_bytes().to_vec(),
+                        ),
+                    ]),
+                }
+                .encode();
+                let blob = ntlm::spnego_response(
+                    Some(ntlm::NEG_ACCEPT_INCOMPLETE),
+                    Some(&self.challenge_token),
+                );
+                Ok((
+                    status::MORE_PROCESSING_REQUIRED,
+                    wire::session_setup_response(0, &blob),
+                ))
+            }
+            Some(3) => {
+                let auth = ntlm::Authenticate::decode(&token).map_err(|_| status::LOGON_FAILURE)?;
+                if !auth.user.eq_ignore_ascii_case(&self.user) || auth.nt_response.len() < 48 {
+                    return Err(status::LOGON_FAILURE);
+                }
+                let key = ntlm::ntowfv2(&self.password, &auth.user, &auth.domain);
+                let (proof, temp) = auth.nt_response.split_at(16);
+                if ntlm::hmac_md5(&key, &[&self.server_challenge, temp])[..] != *proof {
+                    return Err(status::LOGON_FAILURE);
+                }
+                let session_key = ntlm::hmac_md5(&key, &[proof]);
+                let mut zeroed = token.clone();
+                zeroed[72..88].fill(0);
+                let mic = ntlm::hmac_md5(
+                    &session_key,
+                    &[&self.negotiate_token, &self.challenge_token, &zeroed],
+                );
+                if mic != auth.mic {
+                    return Err(status::LOGON_FAILURE);
+                }
+                if self.dialect == wire::DIALECT_311 {
+                    self.preauth.update(msg);
+                }
+                self.keys = Some(
+                    SessionKeys::derive(self.dialect, &session_key, &self.preauth.0).reversed(),
+                );
+                let blob = ntlm::spnego_response(Some(ntlm::NEG_ACCEPT_COMPLETED), None);
+                Ok((status::SUCCESS, wire::session_setup_response(0, &blob)))
+            }
+            _ => Err(status::INVALID_PARAMETER),
+        }
+    }
+
+    fn tree_connect(&mut self, msg: &[u8]) -> (u32, Vec<u8>, u32) {
+        let path = wire::tree_connect_request_path(msg).unwrap_or_default();
+        let share = path.rsplit('\\').next().unwrap_or_default().to_string();
+        let share_type = match share.as_str() {
+            "data" => wire::SHARE_TYPE_DISK,
+            "IPC$" => wire::SHARE_TYPE_PIPE,
+            _ => return (status::BAD_NETWORK_NAME, error_body(), 0),
+        };
+        self.next_id += 1;
+        self.trees.insert(self.next_id, share);
+        let response = wire::TreeConnectResponse {
+            share_type,
+            maximal_access: 0x001F_01FF,
+            ..Default::default()
+        };
+        (status::SUCCESS, response.encode(), self.next_id)
+    }
+
+    fn new_file_id(&mut self) -> FileId {
+        self.next_id += 1;
+        let mut id = [0u8; 16];
+        id[..4].copy_from_slice(&self.next_id.to_le_bytes());
+        FileId(id)
+    }
+
+    fn create(&mut self, tree_id: u32, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let request = CreateRequest::decode(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        let share = self.trees.get(&tree_id).ok_or(status::INVALID_PARAMETER)?;
+        if share == "IPC$" {
+            if request.name != rpc::SRVSVC_PIPE {
+                return Err(status::OBJECT_NAME_NOT_FOUND);
+            }
+            let file_id = self.new_file_id();
+            self.opens.insert(
+                file_id,
+                Open::Pipe {
+                    output: Vec::new(),
+                    input: Vec::new(),
+                },
+            );
+            let response = CreateResponse {
+                file_id,
+                file_attributes: wire::ATTR_NORMAL,
+                ..Default::default()
+            };
+            return Ok((status::SUCCESS, response.encode()));
+        }
+
+        let path = request.name.clone();
+        let wants_dir = request.create_options & wire::OPTION_DIRECTORY_FILE != 0;
+        let wants_file = request.create_options & wire::OPTION_NON_DIRECTORY_FILE != 0;
+        let mut fs = self.fs.lock().unwrap();
+        let existing = fs.get(&path).cloned();
+        let node = match (existing, request.create_disposition) {
+            (Some(_), wire::DISPOSITION_CREATE) => return Err(status::OBJECT_NAME_COLLISION),
+            (Some(None), _) if wants_file => return Err(status::FILE_IS_A_DIRECTORY),
+            (Some(Some(_)), _) if wants_dir => return Err(status::NOT_A_DIRECTORY),
+            (Some(Some(_)), wire::DISPOSITION_OVERWRITE_IF) => {
+                fs.insert(path.clone(), Some(Vec::new()));
+                Some(Vec::new())
+            }
+            (Some(node), _) => node,
+            (None, wire::DISPOSITION_OPEN) => return Err(status::OBJECT_NAME_NOT_FOUND),
+            (None, _) => {
+                if !matches!(fs.get(parent(&path)), Some(None)) {
+                    return Err(status::OBJECT_PATH_NOT_FOUND);
+                }
+                let node = if wants_dir { None } else { Some(Vec::new()) };
+                fs.insert(path.clone(), node.clone());
+                node
+            }
+        };
+        drop(fs);
+        let info = node_info(name_of(&path), &node);
+        let file_id = self.new_file_id();
+        self.opens.insert(
+            file_id,
+            Open::Node {
+                path,
+                listed: false,
+                delete_on_close: false,
+            },
+        );
+        let response = CreateResponse {
+            create_action: 1,
+            times: info.times,
+            end_of_file: info.end_of_file,
+            file_attributes: info.file_attributes,
+            file_id,
+            ..Default::default()
+        };
+        Ok((status::SUCCESS, response.encode()))
+    }
+
+    fn close(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let file_id =
+            wire::request_file_id(msg, wire::CLOSE).map_err(|_| status::INVALID_PARAMETER)?;
+        match self.opens.remove(&file_id) {
+            Some(Open::Node {
+                path,
+                delete_on_close: true,
+                ..
+            }) => {
+                self.fs.lock().unwrap().remove(&path);
+            }
+            Some(_) => {}
+            None => return Err(status::INVALID_PARAMETER),
+        }
+        Ok((status::SUCCESS, wire::close_response()))
+    }
+
+    fn read(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let file_id =
+            wire::request_file_id(msg, wire::READ).map_err(|_| status::INVALID_PARAMETER)?;
+        let (offset, length) =
+            wire::decode_read_request(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        match self.opens.get_mut(&file_id) {
+            Some(Open::Pipe { output, .. }) => {
+                let n = output.len().min(length as usize);
+                let data: Vec<u8> = output.drain(..n).collect();
+                let status = if output.is_empty() {
+                    status::SUCCESS
+                } else {
+                    status::BUFFER_OVERFLOW
+                };
+                Ok((status, wire::read_response(&data)))
+            }
+            Some(Open::Node { path, .. }) => {
+                let fs = self.fs.lock().unwrap();
+                let Some(Some(data)) = fs.get(path.as_str()) else {
+                    return Err(status::INVALID_PARAMETER);
+                };
+                let start = offset as usize;
+                if start >= data.len() {
+                    return Err(status::END_OF_FILE);
+                }
+                let end = data.len().min(start + length as usize);
+                Ok((status::SUCCESS, wire::read_response(&data[start..end])))
+            }
+            None => Err(status::INVALID_PARAMETER),
+        }
+    }
+
+    fn write(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let file_id =
+            wire::request_file_id(msg, wire::WRITE).map_err(|_| status::INVALID_PARAMETER)?;
+        let (offset, data) =
+            wire::decode_write_request(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        match self.opens.get_mut(&file_id) {
+            Some(Open::Pipe { output, input }) => {
+                input.extend_from_slice(&data);
+                while let Ok(Some(fragment)) = rpc::Fragment::take(input) {
+                    match fragment.ptype {
+                        rpc::PTYPE_BIND => output.extend(rpc::bind_ack(fragment.call_id, true)),
+                        rpc::PTYPE_REQUEST
+                            if fragment.opnum() == Some(rpc::OPNUM_NETR_SHARE_ENUM) =>
+                        {
+                            output.extend(rpc::share_enum_response(
+                                fragment.call_id,
+                                &[
+                                    ("data".into(), 0, "Test data".into()),
+                                    ("IPC$".into(), 0x8000_0003, "IPC Service".into()),
+                                ],
+                            ))
+                        }
+                        _ => return Err(status::INVALID_PARAMETER),
+                    }
+                }
+            }
+            Some(Open::Node { path, .. }) => {
+                let mut fs = self.fs.lock().unwrap();
+                let Some(Some(file)) = fs.get_mut(path.as_str()) else {
+                    return Err(status::INVALID_PARAMETER);
+                };
+                let end = offset as usize + data.len();
+                if file.len() < end {
+                    file.resize(end, 0);
+                }
+                file[offset as usize..end].copy_from_slice(&data);
+            }
+            None => return Err(status::INVALID_PARAMETER),
+        }
+        Ok((status::SUCCESS, wire::write_response(data.len() as u32)))
+    }
+
+    fn query_directory(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let file_id = wire::request_file_id(msg, wire::QUERY_DIRECTORY)
+            .map_err(|_| status::INVALID_PARAMETER)?;
+        let (flags, _) =
+            wire::decode_query_directory_request(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        let Some(Open::Node { path, listed, .. }) = self.opens.get_mut(&file_id) else {
+            return Err(status::INVALID_PARAMETER);
+        };
+        if *listed && flags & wire::QUERY_RESTART_SCANS == 0 {
+            return Err(status::NO_MORE_FILES);
+        }
+        *listed = true;
+        let fs = self.fs.lock().unwrap();
+        let mut entries = vec![node_info(".", &None), node_info("..", &None)];
+        entries.extend(children(&fs, path).map(|(child, node)| node_info(name_of(child), node)));
+        Ok((
+            status::SUCCESS,
+            wire::output_buffer_response(&DirectoryInfo::encode_list(&entries)),
+        ))
+    }
+
+    fn set_info(&mut self, msg: &[u8]) -> Result<(u32, Vec<u8>), u32> {
+        let file_id =
+            wire::request_file_id(msg, wire::SET_INFO).map_err(|_| status::INVALID_PARAMETER)?;
+        let (_, class, buffer) =
+            wire::decode_set_info_request(msg).map_err(|_| status::INVALID_PARAMETER)?;
+        let Some(Open::Node {
+            path,
+            delete_on_close,
+            ..
+        }) = self.opens.get_mut(&file_id)
+        else {
+            return Err(status::INVALID_PARAMETER);
+        };
+        let mut fs = self.fs.lock().unwrap();
+        match class {
+            wire::FILE_DISPOSITION_INFORMATION => {
+                if children(&fs, path).next().is_some() {
+                    return Err(status::DIRECTORY_NOT_EMPTY);
+                }
+                *delete_on_close = buffer.first() == Some(&1);
+            }
+            wire::FILE_RENAME_INFORMATION => {
+                let (replace, target) = wire::decode_rename_information(&buffer)
+                    .map_err(|_| status::INVALID_PARAMETER)?;
+                if fs.contains_key(&target) && !replace {
+                    return Err(status::OBJECT_NAME_COLLISION);
+                }
+                let moved: Vec<String> = fs
+                    .keys()
+                    .filter(|key| *key == path || key.starts_with(&format!("{path}\\")))
+                    .cloned()
+                    .collect();
+                for old in moved {
+                    let node = fs.remove(&old).unwrap();
+                    fs.insert(format!("{target}{}", &old[path.len()..]), node);
+                }
+                *path = target;
+            }
+            _ => return Err(status::NOT_SUPPORTED),
+        }
+        Ok((status::SUCCESS, wire::set_info_response()))
+    }
+}
@@ -0,0 +1,22 @@
+// ── Native SMB2/SMB3 client ──────────────────────────────────────────────────
+//
+// Pure-Rust SMB 2.0.2 – 3.1.1 client used as the Unix `OpsBackend`:
+//   • wire     — packet header, command bodies, NT status mapping
+//   • crypto   — signing (HMAC-SHA256 / AES-CMAC), SP800-108 key derivation,
+//                AES-CCM / AES-GCM transform encryption, 3.1.1 preauth hash
+//   • ntlm     — NTLMv2 authentication wrapped in SPNEGO
+//   • rpc      — DCE/RPC over the `srvsvc` pipe for share enumeration
+//   • client   — one authenticated connection (credits, signing, trees)
+//   • backend  — `NativeBackend`, the `OpsBackend` built on top of it
+//
+// Kerberos is not implemented here; sessions that ask for it are handed to
+// the smbclient backend instead (see `file_ops::default_backend`).
+
+pub mod backend;
+pub mod client;
+pub mod crypto;
+#[cfg(test)]
+mod fake_server;
+pub mod ntlm;
+pub mod rpc;
+pub mod wire;
@@ -0,0 +1,557 @@
+// ── NTLMv2 over SPNEGO ───────────────────────────────────────────────────────
+//
+// Client side of NTLMSSP (MS-NLMP) as carried in SMB2 SESSION_SETUP
+// security buffers: NEGOTIATE → CHALLENGE → AUTHENTICATE with an NTLMv2
+// response and a MIC. Only the small SPNEGO (RFC 4178) subset SMB servers
+// use is implemented — NegTokenInit offering the NTLMSSP mechanism and
+// NegTokenResp carrying the follow-up token.
+
+use hmac::{Hmac, Mac};
+use md4::{Digest, Md4};
+use md5::Md5;
+
+use super::wire::{u16_at, u32_at, utf16le};
+use crate::smb::types::{SmbError, SmbResult};
+
+pub const SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+
+pub const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+pub const REQUEST_TARGET: u32 = 0x0000_0004;
+pub const NEGOTIATE_SIGN: u32 = 0x0000_0010;
+pub const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+pub const NEGOTIATE_ANONYMOUS: u32 = 0x0000_0800;
+pub const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+pub const NEGOTIATE_EXTENDED_SESSIONSECURITY: u32 = 0x0008_0000;
+pub const NEGOTIATE_TARGET_INFO: u32 = 0x0080_0000;
+pub const NEGOTIATE_VERSION: u32 = 0x0200_0000;
+pub const NEGOTIATE_128: u32 = 0x2000_0000;
+pub const NEGOTIATE_56: u32 = 0x8000_0000;
+
+const CLIENT_FLAGS: u32 = NEGOTIATE_UNICODE
+    | REQUEST_TARGET
+    | NEGOTIATE_SIGN
+    | NEGOTIATE_NTLM
+    | NEGOTIATE_ALWAYS_SIGN
+    | NEGOTIATE_EXTENDED_SESSIONSECURITY
+    | NEGOTIATE_TARGET_INFO
+    | NEGOTIATE_VERSION
+    | NEGOTIATE_128
+    | NEGOTIATE_56;
+
+/// Windows 10 build 19041, NTLM revision 15 — what the VERSION field of
+/// every message reports.
+const VERSION: [u8; 8] = [10, 0, 0x61, 0x4A, 0, 0, 0, 15];
+
+pub const AV_EOL: u16 = 0;
+pub const AV_NB_COMPUTER_NAME: u16 = 1;
+pub const AV_NB_DOMAIN_NAME: u16 = 2;
+pub const AV_FLAGS: u16 = 6;
+pub const AV_TIMESTAMP: u16 = 7;
+
+/// MsvAvFlags bit announcing that the AUTHENTICATE message carries a MIC.
+const AV_FLAG_MIC_PRESENT: u32 = 0x0000_0002;
+
+/// OID 1.3.6.1.4.1.311.2.2.10 (NTLMSSP), DER content bytes.
+const NTLMSSP_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x02, 0x02, 0x0A];
+/// OID 1.3.6.1.5.5.2 (SPNEGO), DER content bytes.
+const SPNEGO_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x05, 0x05, 0x02];
+
+type HmacMd5 = Hmac<Md5>;
+
+fn malformed(what: &str) -> SmbError {
+    SmbError::AuthFailed(format!("malformed NTLM {what}"))
+}
+
+pub fn hmac_md5(key: &[u8], parts: &[&[u8]]) -> [u8; 16] {
+    let mut mac = <HmacMd5 as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// NTOWFv2: HMAC-MD5(MD4(UTF-16LE(password)), UTF-16LE(UPPER(user) + domain)).
+pub fn ntowfv2(password: &str, user: &str, domain: &str) -> [u8; 16] {
+    let nt_hash: [u8; 16] = Md4::digest(utf16le(password)).into();
+    hmac_md5(
+        &nt_hash,
+        &[&utf16le(&user.to_uppercase()), &utf16le(domain)],
+    )
+}
+
+/// NTLMv2 response and session base key for a response key, challenge,
+/// client challenge, FILETIME timestamp and (final) target info.
+pub fn ntlmv2_response(
+    response_key: &[u8; 16],
+    server_challenge: &[u8; 8],
+    client_challenge: &[u8; 8],
+    timestamp: u64,
+    target_info: &[u8],
+) -> (Vec<u8>, [u8; 16]) {
+    let mut temp = Vec::with_capacity(32 + target_info.len());
+    temp.extend_from_slice(&[1, 1, 0, 0, 0, 0, 0, 0]);
+    temp.extend_from_slice(&timestamp.to_le_bytes());
+    temp.extend_from_slice(client_challenge);
+    temp.extend_from_slice(&[0; 4]);
+    temp.extend_from_slice(target_info);
+    temp.extend_from_slice(&[0; 4]);
+    let proof = hmac_md5(response_key, &[server_challenge, &temp]);
+    let session_base_key = hmac_md5(response_key, &[&proof]);
+    let mut response = proof.to_vec();
+    response.extend_from_slice(&temp);
+    (response, session_base_key)
+}
+
+// ── AV pairs ─────────────────────────────────────────────────────────────────
+
+pub fn parse_av_pairs(target_info: &[u8]) -> Vec<(u16, Vec<u8>)> {
+    let mut pairs = Vec::new();
+    let mut at = 0;
+    while let (Some(id), Some(len)) = (u16_at(target_info, at), u16_at(target_info, at + 2)) {
+        if id == AV_EOL {
+            break;
+        }
+        let Some(value) = target_info.get(at + 4..at + 4 + len as usize) else {
+            break;
+        };
+        pairs.push((id, value.to_vec()));
+        at += 4 + len as usize;
+    }
+    pairs
+}
+
+pub fn encode_av_pairs(pairs: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (id, value) in pairs {
+        out.extend_from_slice(&id.to_le_bytes());
+        out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        out.extend_from_slice(value);
+    }
+    out.extend_from_slice(&[0; 4]);
+    out
+}
+
+// ── Messages ─────────────────────────────────────────────────────────────────
+
+fn push_field(fields: &mut Vec<u8>, payload: &mut Vec<u8>, payload_base: usize, data: &[u8]) {
+    fields.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    fields.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    fields.extend_from_slice(&((payload_base + payload.len()) as u32).to_le_bytes());
+    payload.extend_from_slice(data);
+}
+
+fn field(msg: &[u8], at: usize) -> Option<&[u8]> {
+    let len = u16_at(msg, at)? as usize;
+    let offset = u32_at(msg, at + 4)? as usize;
+    if len == 0 {
+        return Some(&[]);
+    }
+    msg.get(offset..offset.checked_add(len)?)
+}
+
+/// NTLMSSP NEGOTIATE_MESSAGE (no domain / workstation supplied).
+pub fn negotiate_message() -> Vec<u8> {
+    let mut msg = Vec::with_capacity(40);
+    msg.extend_from_slice(SIGNATURE);
+    msg.extend_from_slice(&1u32.to_le_bytes());
+    msg.extend_from_slice(&CLIENT_FLAGS.to_le_bytes());
+    msg.extend_from_slice(&[0; 16]);
+    msg.extend_from_slice(&VERSION);
+    msg
+}
+
+/// Decoded NTLMSSP CHALLENGE_MESSAGE.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Challenge {
+    pub flags: u32,
+    pub server_challenge: [u8; 8],
+    pub target_name: String,
+    pub target_info: Vec<u8>,
+}
+
+impl Challenge {
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        if msg.len() < 48 || &msg[..8] != SIGNATURE || u32_at(msg, 8) != Some(2) {
+            return Err(malformed("CHALLENGE message"));
+        }
+        let target_name = field(msg, 12).ok_or_else(|| malformed("CHALLENGE target name"))?;
+        let target_info = field(msg, 40).ok_or_else(|| malformed("CHALLENGE target info"))?;
+        let mut server_challenge = [0u8; 8];
+        server_challenge.copy_from_slice(&msg[24..32]);
+        Ok(Self {
+            flags: u32_at(msg, 20).unwrap_or_default(),
+            server_challenge,
+            target_name: super::wire::from_utf16le(target_name),
+            target_info: target_info.to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        const BASE: usize = 56;
+        let mut fields = Vec::with_capacity(BASE);
+        let mut payload = Vec::new();
+        fields.extend_from_slice(SIGNATURE);
+        fields.extend_from_slice(&2u32.to_le_bytes());
+        push_field(&mut fields, &mut payload, BASE, &utf16le(&self.target_name));
+        fields.extend_from_slice(&self.flags.to_le_bytes());
+        fields.extend_from_slice(&self.server_challenge);
+        fields.extend_from_slice(&[0; 8]);
+        push_field(&mut fields, &mut payload, BASE, &self.target_info);
+        fields.extend_from_slice(&VERSION);
+        fields.extend_from_slice(&payload);
+        fields
+    }
+
+    /// MsvAvTimestamp from the target info, if the server sent one.
+    pub fn timestamp(&self) -> Option<u64> {
+        parse_av_pairs(&self.target_info)
+            .into_iter()
+            .find(|(id, value)| *id == AV_TIMESTAMP && value.len() == 8)
+            .map(|(_, value)| u64::from_le_bytes(value.try_into().unwrap_or_default()))
+    }
+}
+
+/// Decoded NTLMSSP AUTHENTICATE_MESSAGE.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Authenticate {
+    pub flags: u32,
+    pub lm_response: Vec<u8>,
+    pub nt_response: Vec<u8>,
+    pub domain: String,
+    pub user: String,
+    pub workstation: String,
+    pub mic: [u8; 16],
+}
+
+impl Authenticate {
+    const BASE: usize = 88;
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut fields = Vec::with_capacity(Self::BASE);
+        let mut payload = Vec::new();
+        fields.extend_from_slice(SIGNATURE);
+        fields.extend_from_slice(&3u32.to_le_bytes());
+        for data in [
+            self.lm_response.clone(),
+            self.nt_response.clone(),
+            utf16le(&self.domain),
+            utf16le(&self.user),
+            utf16le(&self.workstation),
+            Vec::new(), // EncryptedRandomSessionKey: no KEY_EXCH
+        ] {
+            push_field(&mut fields, &mut payload, Self::BASE, &data);
+        }
+        fields.extend_from_slice(&self.flags.to_le_bytes());
+        fields.extend_from_slice(&VERSION);
+        fields.extend_from_slice(&self.mic);
+        fields.extend_from_slice(&payload);
+        fields
+    }
+
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        if msg.len() < Self::BASE || &msg[..8] != SIGNATURE || u32_at(msg, 8) != Some(3) {
+            return Err(malformed("AUTHENTICATE message"));
+        }
+        let get = |at| field(msg, at).ok_or_else(|| malformed("AUTHENTICATE field"));
+        let mut mic = [0u8; 16];
+        mic.copy_from_slice(&msg[72..88]);
+        Ok(Self {
+            lm_response: get(12)?.to_vec(),
+            nt_response: get(20)?.to_vec(),
+            domain: super::wire::from_utf16le(get(28)?),
+            user: super::wire::from_utf16le(get(36)?),
+            workstation: super::wire::from_utf16le(get(44)?),
+            flags: u32_at(msg, 60).unwrap_or_default(),
+            mic,
+        })
+    }
+}
+
+/// Credentials for one NTLM exchange. An empty user name requests an
+/// anonymous (null session) logon.
+pub struct NtlmCredentials<'a> {
+    pub user: &'a str,
+    pub domain: &'a str,
+    pub password: &'a str,
+    pub workstation: &'a str,
+}
+
+/// Output of the final leg: the AUTHENTICATE token and the exported
+/// session key (all-zero for anonymous logons).
+pub struct AuthenticateOutput {
+    pub message: Vec<u8>,
+    pub session_key: [u8; 16],
+}
+
+/// Build the AUTHENTICATE message answering `challenge_msg`. `negotiate_msg`
+/// is the NEGOTIATE we sent earlier, needed for the MIC.
+pub fn authenticate(
+    creds: &NtlmCredentials<'_>,
+    negotiate_msg: &[u8],
+    challenge_msg: &[u8],
+    client_challenge: [u8; 8],
+    now_filetime: u64,
+) -> SmbResult<AuthenticateOutput> {
+    let challenge = Challenge::decode(challenge_msg)?;
+    let flags = CLIENT_FLAGS & (challenge.flags | NEGOTIATE_VERSION);
+    if creds.user.is_empty() {
+        let message = Authenticate {
+            flags: flags | NEGOTIATE_ANONYMOUS,
+            lm_response: vec![0],
+            workstation: creds.workstation.to_string(),
+            ..Authenticate::default()
+        }
+        .encode();
+        return Ok(AuthenticateOutput {
+            message,
+            session_key: [0; 16],
+        });
+    }
+
+    let response_key = ntowfv2(creds.password, creds.user, creds.domain);
+    let server_timestamp = challenge.timestamp();
+    let timestamp = server_timestamp.unwrap_or(now_filetime);
+
+    // Re-emit the server's AV pairs and flag that a MIC is present.
+    let mut pairs = parse_av_pairs(&challenge.target_info);
+    match pairs.iter_mut().find(|(id, _)| *id == AV_FLAGS) {
+        Some((_, value)) if value.len() == 4 => {
+            let bits = u32::from_le_bytes(value[..4].try_into().unwrap_or_default());
+            *value = (bits | AV_FLAG_MIC_PRESENT).to_le_bytes().to_vec();
+        }
+        _ => pairs.push((AV_FLAGS, AV_FLAG_MIC_PRESENT.to_le_bytes().to_vec())),
+    }
+    let target_info = encode_av_pairs(&pairs);
+
+    let (nt_response, session_key) = ntlmv2_response(
+        &response_key,
+        &challenge.server_challenge,
+        &client_challenge,
+        timestamp,
+        &target_info,
+    );
+    let lm_response = if server_timestamp.is_some() {
+        vec![0; 24]
+    } else {
+        let mut lm = hmac_md5(
+            &response_key,
+            &[&challenge.server_challenge, &client_challenge],
+        )
+        .to_vec();
+        lm.extend_from_slice(&client_challenge);
+        lm
+    };
+
+    let mut auth = Authenticate {
+        flags,
+        lm_response,
+        nt_response,
+        domain: creds.domain.to_string(),
+        user: creds.user.to_string(),
+        workstation: creds.workstation.to_string(),
+        mic: [0; 16],
+    };
+    let unsigned = auth.encode();
+    auth.mic = hmac_md5(&session_key, &[negotiate_msg, challenge_msg, &unsigned]);
+    Ok(AuthenticateOutput {
+        message: auth.encode(),
+        session_key,
+    })
+}
+
+// ── SPNEGO ───────────────────────────────────────────────────────────────────
+
+fn der_len(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(content.len() + 6);
+    out.push(tag);
+    der_len(content.len(), &mut out);
+    out.extend_from_slice(content);
+    out
+}
+
+/// GSS-API InitialContextToken wrapping a NegTokenInit that offers NTLMSSP.
+pub fn spnego_init(mech_token: &[u8]) -> Vec<u8> {
+    let mech_types = der(0xA0, &der(0x30, &der(0x06, NTLMSSP_OID)));
+    let token = der(0xA2, &der(0x04, mech_token));
+    let neg_token_init = der(0xA0, &der(0x30, &[mech_types, token].concat()));
+    der(0x60, &[der(0x06, SPNEGO_OID), neg_token_init].concat())
+}
+
+/// NegTokenResp with an optional negState, supportedMech and responseToken.
+pub fn spnego_response(neg_state: Option<u8>, token: Option<&[u8]>) -> Vec<u8> {
+    let mut fields = Vec::new();
+    if let Some(state) = neg_state {
+        fields.extend(der(0xA0, &der(0x0A, &[state])));
+        fields.extend(der(0xA1, &der(0x06, NTLMSSP_OID)));
+    }
+    if let Some(token) = token {
+        fields.extend(der(0xA2, &der(0x04, token)));
+    }
+    der(0xA1, &der(0x30, &fields))
+}
+
+/// SPNEGO negState values.
+pub const NEG_ACCEPT_COMPLETED: u8 = 0;
+pub const NEG_ACCEPT_INCOMPLETE: u8 = 1;
+pub const NEG_REJECT: u8 = 2;
+
+/// Read one DER TLV: `(tag, content, rest)`.
+fn der_read(buf: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = buf.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let n = (first & 0x7F) as usize;
+        if n == 0 || n > 4 {
+            return None;
+        }
+        let len = rest
+            .get(..n)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, &rest[n..])
+    };
+    Some((tag, rest.get(..len)?, &rest[len..]))
+}
+
+/// Walk the token tree and return the first OCTET STRING holding an
+/// NTLMSSP message, plus any negState seen along the way.
+fn find_ntlm(mut buf: &[u8], depth: usize, neg_state: &mut Option<u8>) -> Option<Vec<u8>> {
+    if depth > 8 {
+        return None;
+    }
+    while let Some((tag, content, rest)) = der_read(buf) {
+        match tag {
+            0x04 if content.starts_with(SIGNATURE) => return Some(content.to_vec()),
+            0x0A if content.len() == 1 => *neg_state = Some(content[0]),
+            t if t & 0x20 != 0 => {
+                if let Some(found) = find_ntlm(content, depth + 1, neg_state) {
+                    return Some(found);
+                }
+            }
+            _ => {}
+        }
+        buf = rest;
+    }
+    None
+}
+
+/// Extract the NTLMSSP token and negState from a SPNEGO blob. A bare
+/// NTLMSSP message (no SPNEGO wrapping) is returned as-is.
+pub fn spnego_unwrap(blob: &[u8]) -> (Option<Vec<u8>>, Option<u8>) {
+    if blob.starts_with(SIGNATURE) {
+        return (Some(blob.to_vec()), None);
+    }
+    let mut neg_state = None;
+    let token = find_ntlm(blob, 0, &mut neg_state);
+    (token, neg_state)
+}
+
+/// Current time as a Windows FILETIME.
+pub fn filetime_now() -> u64 {
+    let unix = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    (unix.as_nanos() / 100) as u64 + 116_444_736_000_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ntlmv2_matches_ms_nlmp_test_vectors() {
+        // MS-NLMP §4.2.4.
+        let key = ntowfv2("Password", "User", "Domain");
+        assert_eq!(key.to_vec(), hex("0c868a403bfd7a93a3001ef22ef02e3f"));
+        let target_info = encode_av_pairs(&[
+            (AV_NB_DOMAIN_NAME, utf16le("Domain")),
+            (AV_NB_COMPUTER_NAME, utf16le("Server")),
+        ]);
+        let (response, session_base_key) = ntlmv2_response(
+            &key,
+            &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+            &[0xaa; 8],
+            0,
+            &target_info,
+        );
+        assert_eq!(
+            response[..16].to_vec(),
+            hex("68cd0ab851e51c96aabc927bebef6a1c")
+        );
+        assert_eq!(
+            session_base_key.to_vec(),
+            hex("8de40ccadbc14a82f15cb0ad0de95ca3")
+        );
+    }
+
+    #[test]
+    fn authenticate_message_roundtrip_and_mic() {
+        let negotiate = negotiate_message();
+        let challenge = Challenge {
+            flags: CLIENT_FLAGS,
+            server_challenge: [5; 8],
+            target_name: "SAMBA".into(),
+            target_info: encode_av_pairs(&[
+                (AV_NB_DOMAIN_NAME, utf16le("SAMBA")),
+                (AV_TIMESTAMP, 1234u64.to_le_bytes().to_vec()),
+            ]),
+        }
+        .encode();
+        let creds = NtlmCredentials {
+            user: "alice",
+            domain: "SAMBA",
+            password: "secret",
+            workstation: "WS",
+        };
+        let out = authenticate(&creds, &negotiate, &challenge, [9; 8], 99).unwrap();
+        let auth = Authenticate::decode(&out.message).unwrap();
+        assert_eq!(auth.user, "alice");
+        assert_eq!(auth.lm_response, vec![0; 24]);
+        // The server timestamp wins over the local clock.
+        assert_eq!(auth.nt_response[24..32], 1234u64.to_le_bytes());
+        let pairs = parse_av_pairs(&auth.nt_response[44..]);
+        assert!(pairs.contains(&(AV_FLAGS, AV_FLAG_MIC_PRESENT.to_le_bytes().to_vec())));
+
+        let mut zeroed = out.message.clone();
+        zeroed[72..88].fill(0);
+        let mic = hmac_md5(&out.session_key, &[&negotiate, &challenge, &zeroed]);
+        assert_eq!(auth.mic, mic);
+    }
+
+    #[test]
+    fn spnego_wrap_and_unwrap() {
+        let token = negotiate_message();
+        let (found, _) = spnego_unwrap(&spnego_init(&token));
+        assert_eq!(found.as_deref(), Some(&token[..]));
+
+        let big = [SIGNATURE.to_vec(), vec![0x42; 600]].concat();
+        let resp = spnego_response(Some(NEG_ACCEPT_INCOMPLETE), Some(&big));
+        let (found, state) = spnego_unwrap(&resp);
+        assert_eq!(found, Some(big));
+        assert_eq!(state, Some(NEG_ACCEPT_INCOMPLETE));
+
+        let (found, state) = spnego_unwrap(&spnego_response(Some(NEG_ACCEPT_COMPLETED), None));
+        assert_eq!(found, None);
+        assert_eq!(state, Some(NEG_ACCEPT_COMPLETED));
+    }
+}
@@ -0,0 +1,435 @@
+// ── DCE/RPC share enumeration ────────────────────────────────────────────────
+//
+// SMB2 has no "list shares" command; clients bind to the `srvsvc` named
+// pipe on `IPC$` and call NetrShareEnum (MS-SRVS opnum 15) at level 1.
+// This module encodes the connection-oriented RPC PDUs (C706 §12) and the
+// NDR stubs for that one call. The server-side encoders exist for the
+// in-process test server.
+
+use super::wire::{u16_at, u32_at};
+use crate::smb::types::{SmbError, SmbResult, SmbShareInfo, SmbShareType};
+
+pub const SRVSVC_PIPE: &str = "srvsvc";
+
+pub const PTYPE_REQUEST: u8 = 0;
+pub const PTYPE_RESPONSE: u8 = 2;
+pub const PTYPE_FAULT: u8 = 3;
+pub const PTYPE_BIND: u8 = 11;
+pub const PTYPE_BIND_ACK: u8 = 12;
+
+pub const PFC_FIRST_FRAG: u8 = 0x01;
+pub const PFC_LAST_FRAG: u8 = 0x02;
+
+pub const OPNUM_NETR_SHARE_ENUM: u16 = 15;
+
+pub const HEADER_LEN: usize = 16;
+pub const MAX_FRAG: u16 = 4280;
+
+/// Upper bound on shares accepted from one enumeration.
+const MAX_SHARES: usize = 4096;
+
+/// srvsvc interface 4b324fc8-1670-01d3-1278-5a47bf6ee188 v3.0.
+const SRVSVC_SYNTAX: [u8; 20] = [
+    0xC8, 0x4F, 0x32, 0x4B, 0x70, 0x16, 0xD3, 0x01, 0x12, 0x78, 0x5A, 0x47, 0xBF, 0x6E, 0xE1, 0x88,
+    3, 0, 0, 0,
+];
+/// NDR transfer syntax 8a885d04-1ceb-11c9-9fe8-08002b104860 v2.
+const NDR_SYNTAX: [u8; 20] = [
+    0x04, 0x5D, 0x88, 0x8A, 0xEB, 0x1C, 0xC9, 0x11, 0x9F, 0xE8, 0x08, 0x00, 0x2B, 0x10, 0x48, 0x60,
+    2, 0, 0, 0,
+];
+
+const STYPE_MASK: u32 = 0x0000_00FF;
+const STYPE_DISKTREE: u32 = 0;
+const STYPE_PRINTQ: u32 = 1;
+const STYPE_DEVICE: u32 = 2;
+const STYPE_IPC: u32 = 3;
+const STYPE_SPECIAL: u32 = 0x8000_0000;
+
+fn malformed(what: &str) -> SmbError {
+    SmbError::Backend(format!("malformed DCE/RPC {what}"))
+}
+
+fn pdu(ptype: u8, flags: u8, call_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+    out.extend_from_slice(&[5, 0, ptype, flags, 0x10, 0, 0, 0]);
+    out.extend_from_slice(&((HEADER_LEN + body.len()) as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&call_id.to_le_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+/// One decoded PDU fragment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub ptype: u8,
+    pub flags: u8,
+    pub call_id: u32,
+    /// Everything after the 16-byte common header.
+    pub body: Vec<u8>,
+}
+
+impl Fragment {
+    /// Split the first fragment off `buf`; `None` until it is complete.
+    pub fn take(buf: &mut Vec<u8>) -> SmbResult<Option<Self>> {
+        if buf.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        if buf[0] != 5 {
+            return Err(malformed("PDU header"));
+        }
+        let frag_len = u16_at(buf, 8).unwrap_or_default() as usize;
+        if frag_len < HEADER_LEN {
+            return Err(malformed("fragment length"));
+        }
+        if buf.len() < frag_len {
+            return Ok(None);
+        }
+        let rest = buf.split_off(frag_len);
+        let frag = std::mem::replace(buf, rest);
+        Ok(Some(Self {
+            ptype: frag[2],
+            flags: frag[3],
+            call_id: u32_at(&frag, 12).unwrap_or_default(),
+            body: frag[HEADER_LEN..].to_vec(),
+        }))
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.flags & PFC_LAST_FRAG != 0
+    }
+
+    /// Stub data of a REQUEST (8-byte prefix) or RESPONSE (8-byte prefix).
+    pub fn stub(&self) -> &[u8] {
+        self.body.get(8..).unwrap_or_default()
+    }
+
+    pub fn opnum(&self) -> Option<u16> {
+        (self.ptype == PTYPE_REQUEST).then(|| u16_at(&self.body, 6).unwrap_or_default())
+    }
+}
+
+// ── Bind ─────────────────────────────────────────────────────────────────────
+
+pub fn bind(call_id: u32) -> Vec<u8> {
+    let mut body = Vec::with_capacity(56);
+    body.extend_from_slice(&MAX_FRAG.to_le_bytes());
+    body.extend_from_slice(&MAX_FRAG.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes()); // assoc_group_id
+    body.extend_from_slice(&[1, 0, 0, 0]); // one context item
+    body.extend_from_slice(&0u16.to_le_bytes()); // context id
+    body.extend_from_slice(&[1, 0]); // one transfer syntax
+    body.extend_from_slice(&SRVSVC_SYNTAX);
+    body.extend_from_slice(&NDR_SYNTAX);
+    pdu(PTYPE_BIND, PFC_FIRST_FRAG | PFC_LAST_FRAG, call_id, &body)
+}
+
+pub fn bind_ack(call_id: u32, accepted: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(60);
+    body.extend_from_slice(&MAX_FRAG.to_le_bytes());
+    body.extend_from_slice(&MAX_FRAG.to_le_bytes());
+    body.extend_from_slice(&0x1234u32.to_le_bytes());
+    let port = b"\\PIPE\\srvsvc\0";
+    body.extend_from_slice(&(port.len() as u16).to_le_bytes());
+    body.extend_from_slice(port);
+    body.resize(
+        (HEADER_LEN + body.len()).next_multiple_of(4) - HEADER_LEN,
+        0,
+    );
+    body.extend_from_slice(&[1, 0, 0, 0]);
+    let (result, reason) = if accepted { (0u16, 0u16) } else { (2, 2) };
+    body.extend_from_slice(&result.to_le_bytes());
+    body.extend_from_slice(&reason.to_le_bytes());
+    body.extend_from_slice(&NDR_SYNTAX);
+    pdu(
+        PTYPE_BIND_ACK,
+        PFC_FIRST_FRAG | PFC_LAST_FRAG,
+        call_id,
+        &body,
+    )
+}
+
+/// Accept a BIND_ACK whose first presentation result is "acceptance".
+pub fn check_bind_ack(frag: &Fragment) -> SmbResult<()> {
+    if frag.ptype != PTYPE_BIND_ACK {
+        return Err(SmbError::Backend("srvsvc bind was rejected".into()));
+    }
+    let port_len = u16_at(&frag.body, 8).ok_or_else(|| malformed("BIND_ACK"))? as usize;
+    let results_at = (HEADER_LEN + 10 + port_len).next_multiple_of(4) - HEADER_LEN;
+    let count = *frag
+        .body
+        .get(results_at)
+        .ok_or_else(|| malformed("BIND_ACK"))?;
+    match u16_at(&frag.body, results_at + 4) {
+        Some(0) if count > 0 => Ok(()),
+        Some(_) => Err(SmbError::Backend(
+            "srvsvc bind was rejected (transfer syntax not accepted)".into(),
+        )),
+        None => Err(malformed("BIND_ACK")),
+    }
+}
+
+// ── NDR helpers ──────────────────────────────────────────────────────────────
+
+struct NdrWriter(Vec<u8>);
+
+impl NdrWriter {
+    fn align(&mut self, n: usize) {
+        self.0.resize(self.0.len().next_multiple_of(n), 0);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.align(4);
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Conformant varying NUL-terminated UTF-16 string (deferred pointee).
+    fn wstr(&mut self, s: &str) {
+        let units: Vec<u16> = s.encode_utf16().chain(std::iter::once(0)).collect();
+        self.u32(units.len() as u32);
+        self.u32(0);
+        self.u32(units.len() as u32);
+        for unit in units {
+            self.0.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+}
+
+struct NdrReader<'a> {
+    buf: &'a [u8],
+    at: usize,
+}
+
+impl NdrReader<'_> {
+    fn u32(&mut self) -> SmbResult<u32> {
+        self.at = self.at.next_multiple_of(4);
+        let v = u32_at(self.buf, self.at).ok_or_else(|| malformed("NDR stub"))?;
+        self.at += 4;
+        Ok(v)
+    }
+
+    fn wstr(&mut self) -> SmbResult<String> {
+        let _max = self.u32()?;
+        let _offset = self.u32()?;
+        let actual = self.u32()? as usize;
+        let bytes = self
+            .buf
+            .get(self.at..self.at + actual * 2)
+            .ok_or_else(|| malformed("NDR string"))?;
+        self.at += actual * 2;
+        let mut s = super::wire::from_utf16le(bytes);
+        if s.ends_with('\0') {
+            s.pop();
+        }
+        Ok(s)
+    }
+}
+
+// ── NetrShareEnum ────────────────────────────────────────────────────────────
+
+/// REQUEST PDU calling NetrShareEnum level 1 against `\\server`.
+pub fn share_enum_request(call_id: u32, server: &str) -> Vec<u8> {
+    let mut stub = NdrWriter(Vec::with_capacity(96));
+    stub.u32(0x0002_0000); // ServerName referent
+    stub.wstr(&format!(r"\\{server}"));
+    stub.u32(1); // Level
+    stub.u32(1); // union switch
+    stub.u32(0x0002_0004); // SHARE_INFO_1_CONTAINER referent
+    stub.u32(0); // EntriesRead
+    stub.u32(0); // Buffer: null
+    stub.u32(u32::MAX); // PreferedMaximumLength
+    stub.u32(0); // ResumeHandle: null
+    let mut body = Vec::with_capacity(8 + stub.0.len());
+    body.extend_from_slice(&(stub.0.len() as u32).to_le_bytes()); // alloc_hint
+    body.extend_from_slice(&0u16.to_le_bytes()); // context id
+    body.extend_from_slice(&OPNUM_NETR_SHARE_ENUM.to_le_bytes());
+    body.extend_from_slice(&stub.0);
+    pdu(
+        PTYPE_REQUEST,
+        PFC_FIRST_FRAG | PFC_LAST_FRAG,
+        call_id,
+        &body,
+    )
+}
+
+/// RESPONSE PDU fragments answering NetrShareEnum with `shares`
+/// (name, STYPE value, remark), split at `MAX_FRAG`.
+pub fn share_enum_response(call_id: u32, shares: &[(String, u32, String)]) -> Vec<u8> {
+    let mut stub = NdrWriter(Vec::new());
+    stub.u32(1); // Level
+    stub.u32(1); // union switch
+    stub.u32(0x0002_0000); // container referent
+    stub.u32(shares.len() as u32);
+    stub.u32(0x0002_0004); // array referent
+    stub.u32(shares.len() as u32); // conformance
+    for (i, (_, stype, _)) in shares.iter().enumerate() {
+        stub.u32(0x0002_0008 + 8 * i as u32);
+        stub.u32(*stype);
+        stub.u32(0x0002_000C + 8 * i as u32);
+    }
+    for (name, _, remark) in shares {
+        stub.wstr(name);
+        stub.wstr(remark);
+    }
+    stub.u32(shares.len() as u32); // TotalEntries
+    stub.u32(0); // ResumeHandle: null
+    stub.u32(0); // WERR_OK
+
+    let max_stub = MAX_FRAG as usize - HEADER_LEN - 8;
+    let chunks: Vec<&[u8]> = stub.0.chunks(max_stub).collect();
+    let mut out = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut flags = 0;
+        if i == 0 {
+            flags |= PFC_FIRST_FRAG;
+        }
+        if i + 1 == chunks.len() {
+            flags |= PFC_LAST_FRAG;
+        }
+        let mut body = Vec::with_capacity(8 + chunk.len());
+        body.extend_from_slice(&(stub.0.len() as u32).to_le_bytes());
+        body.extend_from_slice(&[0, 0, 0, 0]);
+        body.extend_from_slice(chunk);
+        out.extend(pdu(PTYPE_RESPONSE, flags, call_id, &body));
+    }
+    out
+}
+
+fn share_type(raw: u32) -> SmbShareType {
+    match raw & STYPE_MASK {
+        STYPE_DISKTREE => SmbShareType::Disk,
+        STYPE_PRINTQ => SmbShareType::Printer,
+        STYPE_DEVICE => SmbShareType::Device,
+        STYPE_IPC => SmbShareType::Ipc,
+        _ if raw & STYPE_SPECIAL != 0 => SmbShareType::Special,
+        _ => SmbShareType::Unknown,
+    }
+}
+
+/// Decode the reassembled NetrShareEnum response stub.
+pub fn parse_share_enum(stub: &[u8]) -> SmbResult<Vec<SmbShareInfo>> {
+    let mut r = NdrReader { buf: stub, at: 0 };
+    let _level = r.u32()?;
+    let _switch = r.u32()?;
+    if r.u32()? == 0 {
+        return Ok(Vec::new());
+    }
+    let count = r.u32()? as usize;
+    if r.u32()? == 0 || count == 0 {
+        return Ok(Vec::new());
+    }
+    if count > MAX_SHARES || r.u32()? as usize != count {
+        return Err(malformed("share array"));
+    }
+    let mut fixed = Vec::with_capacity(count);
+    for _ in 0..count {
+        fixed.push((r.u32()?, r.u32()?, r.u32()?));
+    }
+    let mut shares = Vec::with_capacity(count);
+    for (name_ptr, stype, remark_ptr) in fixed {
+        let name = if name_ptr != 0 {
+            r.wstr()?
+        } else {
+            String::new()
+        };
+        let remark = if remark_ptr != 0 {
+            r.wstr()?
+        } else {
+            String::new()
+        };
+        if name.is_empty() {
+            continue;
+        }
+        shares.push(SmbShareInfo {
+            is_admin: name.ends_with('$') || stype & STYPE_SPECIAL != 0,
+            share_type: share_type(stype),
+            comment: Some(remark).filter(|c| !c.is_empty()),
+            name,
+        });
+    }
+    let _total = r.u32()?;
+    if r.u32()? != 0 {
+        let _resume = r.u32()?;
+    }
+    match r.u32()? {
+        0 => Ok(shares),
+        5 => Err(SmbError::AuthFailed(
+            "SMB share enumeration was rejected".into(),
+        )),
+        werr => Err(SmbError::Backend(format!(
+            "NetrShareEnum failed (WERROR 0x{werr:08X})"
+        ))),
+    }
+}
+
+/// Fault status carried by a FAULT PDU.
+pub fn fault_status(frag: &Fragment) -> u32 {
+    u32_at(&frag.body, 8).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reassemble(mut bytes: Vec<u8>) -> Vec<u8> {
+        let mut stub = Vec::new();
+        while let Some(frag) = Fragment::take(&mut bytes).unwrap() {
+            assert_eq!(frag.ptype, PTYPE_RESPONSE);
+            stub.extend_from_slice(frag.stub());
+            if frag.is_last() {
+                break;
+            }
+        }
+        stub
+    }
+
+    #[test]
+    fn bind_ack_is_checked() {
+        let mut ok = bind_ack(1, true);
+        assert!(check_bind_ack(&Fragment::take(&mut ok).unwrap().unwrap()).is_ok());
+        let mut rejected = bind_ack(1, false);
+        assert!(check_bind_ack(&Fragment::take(&mut rejected).unwrap().unwrap()).is_err());
+        let mut partial = bind(1);
+        partial.truncate(20);
+        assert!(Fragment::take(&mut partial).unwrap().is_none());
+    }
+
+    #[test]
+    fn share_enum_roundtrip_across_fragments() {
+        let mut shares = vec![
+            (
+                "IPC$".to_string(),
+                STYPE_IPC | STYPE_SPECIAL,
+                "IPC Service".to_string(),
+            ),
+            ("public".to_string(), STYPE_DISKTREE, String::new()),
+        ];
+        for i in 0..150 {
+            shares.push((format!("share{i:03}"), STYPE_DISKTREE, "x".repeat(i % 7)));
+        }
+        let bytes = share_enum_response(2, &shares);
+        assert!(bytes.len() > MAX_FRAG as usize);
+        let parsed = parse_share_enum(&reassemble(bytes)).unwrap();
+        assert_eq!(parsed.len(), shares.len());
+        assert_eq!(parsed[0].share_type, SmbShareType::Ipc);
+        assert!(parsed[0].is_admin);
+        assert_eq!(parsed[0].comment.as_deref(), Some("IPC Service"));
+        assert_eq!(parsed[1].comment, None);
+        assert_eq!(parsed[151].name, "share149");
+    }
+
+    #[test]
+    fn share_enum_request_names_server() {
+        let mut bytes = share_enum_request(3, "nas");
+        let frag = Fragment::take(&mut bytes).unwrap().unwrap();
+        assert_eq!(frag.opnum(), Some(OPNUM_NETR_SHARE_ENUM));
+        let mut r = NdrReader {
+            buf: frag.stub(),
+            at: 0,
+        };
+        assert_ne!(r.u32().unwrap(), 0);
+        assert_eq!(r.wstr().unwrap(), r"\\nas");
+        assert_eq!(r.u32().unwrap(), 1);
+    }
+}
@@ -0,0 +1,1186 @@
+// ── SMB2 wire format ─────────────────────────────────────────────────────────
+//
+// The 64-byte packet header, command codes, NT status values and the
+// request / response bodies the native client speaks (MS-SMB2 §2.2).
+// Encoders return the body that follows the header; decoders take the
+// whole message because most buffer offsets are relative to the start of
+// the header. All integers are little-endian.
+
+use crate::smb::types::{SmbError, SmbResult};
+
+pub const PROTOCOL_ID: [u8; 4] = [0xFE, b'S', b'M', b'B'];
+pub const TRANSFORM_PROTOCOL_ID: [u8; 4] = [0xFD, b'S', b'M', b'B'];
+pub const HEADER_LEN: usize = 64;
+
+// ── Dialects ─────────────────────────────────────────────────────────────────
+
+pub const DIALECT_202: u16 = 0x0202;
+pub const DIALECT_210: u16 = 0x0210;
+pub const DIALECT_300: u16 = 0x0300;
+pub const DIALECT_302: u16 = 0x0302;
+pub const DIALECT_311: u16 = 0x0311;
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+pub const NEGOTIATE: u16 = 0x0000;
+pub const SESSION_SETUP: u16 = 0x0001;
+pub const LOGOFF: u16 = 0x0002;
+pub const TREE_CONNECT: u16 = 0x0003;
+pub const TREE_DISCONNECT: u16 = 0x0004;
+pub const CREATE: u16 = 0x0005;
+pub const CLOSE: u16 = 0x0006;
+pub const READ: u16 = 0x0008;
+pub const WRITE: u16 = 0x0009;
+pub const QUERY_DIRECTORY: u16 = 0x000E;
+pub const SET_INFO: u16 = 0x0011;
+
+// ── Header flags ─────────────────────────────────────────────────────────────
+
+pub const FLAG_SERVER_TO_REDIR: u32 = 0x0000_0001;
+pub const FLAG_ASYNC: u32 = 0x0000_0002;
+pub const FLAG_SIGNED: u32 = 0x0000_0008;
+
+// ── Negotiate ────────────────────────────────────────────────────────────────
+
+pub const SIGNING_ENABLED: u16 = 0x0001;
+pub const SIGNING_REQUIRED: u16 = 0x0002;
+
+pub const CAP_LARGE_MTU: u32 = 0x0000_0004;
+pub const CAP_ENCRYPTION: u32 = 0x0000_0040;
+
+pub const CTX_PREAUTH_INTEGRITY: u16 = 0x0001;
+pub const CTX_ENCRYPTION: u16 = 0x0002;
+
+pub const HASH_SHA512: u16 = 0x0001;
+pub const CIPHER_AES128_CCM: u16 = 0x0001;
+pub const CIPHER_AES128_GCM: u16 = 0x0002;
+
+// ── Session / tree flags ─────────────────────────────────────────────────────
+
+pub const SESSION_FLAG_IS_GUEST: u16 = 0x0001;
+pub const SESSION_FLAG_IS_NULL: u16 = 0x0002;
+pub const SESSION_FLAG_ENCRYPT_DATA: u16 = 0x0004;
+
+pub const SHARE_TYPE_DISK: u8 = 0x01;
+pub const SHARE_TYPE_PIPE: u8 = 0x02;
+pub const SHARE_TYPE_PRINT: u8 = 0x03;
+pub const SHARE_FLAG_ENCRYPT_DATA: u32 = 0x0000_8000;
+
+// ── Create ───────────────────────────────────────────────────────────────────
+
+pub const FILE_READ_DATA: u32 = 0x0000_0001;
+pub const FILE_WRITE_DATA: u32 = 0x0000_0002;
+pub const FILE_READ_ATTRIBUTES: u32 = 0x0000_0080;
+pub const FILE_WRITE_ATTRIBUTES: u32 = 0x0000_0100;
+pub const DELETE: u32 = 0x0001_0000;
+pub const READ_CONTROL: u32 = 0x0002_0000;
+pub const SYNCHRONIZE: u32 = 0x0010_0000;
+pub const GENERIC_WRITE: u32 = 0x4000_0000;
+pub const GENERIC_READ: u32 = 0x8000_0000;
+
+pub const SHARE_ALL: u32 = 0x0000_0007;
+
+pub const DISPOSITION_OPEN: u32 = 1;
+pub const DISPOSITION_CREATE: u32 = 2;
+pub const DISPOSITION_OVERWRITE_IF: u32 = 5;
+
+pub const OPTION_DIRECTORY_FILE: u32 = 0x0000_0001;
+pub const OPTION_NON_DIRECTORY_FILE: u32 = 0x0000_0040;
+pub const OPTION_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+pub const IMPERSONATION_IMPERSONATE: u32 = 2;
+
+pub const ATTR_READONLY: u32 = 0x0000_0001;
+pub const ATTR_HIDDEN: u32 = 0x0000_0002;
+pub const ATTR_SYSTEM: u32 = 0x0000_0004;
+pub const ATTR_DIRECTORY: u32 = 0x0000_0010;
+pub const ATTR_NORMAL: u32 = 0x0000_0080;
+pub const ATTR_REPARSE_POINT: u32 = 0x0000_0400;
+
+// ── Query / set info ─────────────────────────────────────────────────────────
+
+pub const FILE_DIRECTORY_INFORMATION: u8 = 0x01;
+pub const QUERY_RESTART_SCANS: u8 = 0x01;
+
+pub const INFO_FILE: u8 = 0x01;
+pub const FILE_RENAME_INFORMATION: u8 = 10;
+pub const FILE_DISPOSITION_INFORMATION: u8 = 13;
+
+// ── NT status ────────────────────────────────────────────────────────────────
+
+pub mod status {
+    pub const SUCCESS: u32 = 0x0000_0000;
+    pub const PENDING: u32 = 0x0000_0103;
+    pub const BUFFER_OVERFLOW: u32 = 0x8000_0005;
+    pub const NO_MORE_FILES: u32 = 0x8000_0006;
+    pub const INVALID_PARAMETER: u32 = 0xC000_000D;
+    pub const END_OF_FILE: u32 = 0xC000_0011;
+    pub const MORE_PROCESSING_REQUIRED: u32 = 0xC000_0016;
+    pub const ACCESS_DENIED: u32 = 0xC000_0022;
+    pub const OBJECT_NAME_INVALID: u32 = 0xC000_0033;
+    pub const OBJECT_NAME_NOT_FOUND: u32 = 0xC000_0034;
+    pub const OBJECT_NAME_COLLISION: u32 = 0xC000_0035;
+    pub const OBJECT_PATH_NOT_FOUND: u32 = 0xC000_003A;
+    pub const SHARING_VIOLATION: u32 = 0xC000_0043;
+    pub const DELETE_PENDING: u32 = 0xC000_0056;
+    pub const LOGON_FAILURE: u32 = 0xC000_006D;
+    pub const ACCOUNT_RESTRICTION: u32 = 0xC000_006E;
+    pub const PASSWORD_EXPIRED: u32 = 0xC000_0071;
+    pub const ACCOUNT_DISABLED: u32 = 0xC000_0072;
+    pub const FILE_IS_A_DIRECTORY: u32 = 0xC000_00BA;
+    pub const NOT_SUPPORTED: u32 = 0xC000_00BB;
+    pub const BAD_NETWORK_NAME: u32 = 0xC000_00CC;
+    pub const DIRECTORY_NOT_EMPTY: u32 = 0xC000_0101;
+    pub const NOT_A_DIRECTORY: u32 = 0xC000_0103;
+    pub const USER_SESSION_DELETED: u32 = 0xC000_0203;
+    pub const NETWORK_SESSION_EXPIRED: u32 = 0xC000_035C;
+    pub const ACCOUNT_LOCKED_OUT: u32 = 0xC000_0234;
+
+    /// Short symbolic name for the statuses the client reports.
+    pub fn name(status: u32) -> Option<&'static str> {
+        Some(match status {
+            SUCCESS => "STATUS_SUCCESS",
+            PENDING => "STATUS_PENDING",
+            BUFFER_OVERFLOW => "STATUS_BUFFER_OVERFLOW",
+            NO_MORE_FILES => "STATUS_NO_MORE_FILES",
+            INVALID_PARAMETER => "STATUS_INVALID_PARAMETER",
+            END_OF_FILE => "STATUS_END_OF_FILE",
+            MORE_PROCESSING_REQUIRED => "STATUS_MORE_PROCESSING_REQUIRED",
+            ACCESS_DENIED => "STATUS_ACCESS_DENIED",
+            OBJECT_NAME_INVALID => "STATUS_OBJECT_NAME_INVALID",
+            OBJECT_NAME_NOT_FOUND => "STATUS_OBJECT_NAME_NOT_FOUND",
+            OBJECT_NAME_COLLISION => "STATUS_OBJECT_NAME_COLLISION",
+            OBJECT_PATH_NOT_FOUND => "STATUS_OBJECT_PATH_NOT_FOUND",
+            SHARING_VIOLATION => "STATUS_SHARING_VIOLATION",
+            DELETE_PENDING => "STATUS_DELETE_PENDING",
+            LOGON_FAILURE => "STATUS_LOGON_FAILURE",
+            ACCOUNT_RESTRICTION => "STATUS_ACCOUNT_RESTRICTION",
+            PASSWORD_EXPIRED => "STATUS_PASSWORD_EXPIRED",
+            ACCOUNT_DISABLED => "STATUS_ACCOUNT_DISABLED",
+            FILE_IS_A_DIRECTORY => "STATUS_FILE_IS_A_DIRECTORY",
+            NOT_SUPPORTED => "STATUS_NOT_SUPPORTED",
+            BAD_NETWORK_NAME => "STATUS_BAD_NETWORK_NAME",
+            DIRECTORY_NOT_EMPTY => "STATUS_DIRECTORY_NOT_EMPTY",
+            NOT_A_DIRECTORY => "STATUS_NOT_A_DIRECTORY",
+            USER_SESSION_DELETED => "STATUS_USER_SESSION_DELETED",
+            NETWORK_SESSION_EXPIRED => "STATUS_NETWORK_SESSION_EXPIRED",
+            ACCOUNT_LOCKED_OUT => "STATUS_ACCOUNT_LOCKED_OUT",
+            _ => return None,
+        })
+    }
+}
+
+/// Map a failing NT status to the crate error type.
+pub fn status_error(command: &str, status: u32) -> SmbError {
+    let name = status::name(status)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("NTSTATUS 0x{status:08X}"));
+    match status {
+        status::LOGON_FAILURE
+        | status::ACCOUNT_RESTRICTION
+        | status::PASSWORD_EXPIRED
+        | status::ACCOUNT_DISABLED
+        | status::ACCOUNT_LOCKED_OUT => {
+            SmbError::AuthFailed(format!("SMB authentication was rejected ({name})"))
+        }
+        status::OBJECT_NAME_INVALID
+        | status::OBJECT_NAME_NOT_FOUND
+        | status::OBJECT_PATH_NOT_FOUND
+        | status::BAD_NETWORK_NAME
+        | status::NOT_A_DIRECTORY
+        | status::FILE_IS_A_DIRECTORY => {
+            SmbError::InvalidPath(format!("the requested SMB path was rejected ({name})"))
+        }
+        status::OBJECT_NAME_COLLISION => {
+            SmbError::Other(format!("the SMB path already exists ({name})"))
+        }
+        status::USER_SESSION_DELETED | status::NETWORK_SESSION_EXPIRED => {
+            SmbError::Network(format!("the SMB session ended ({name})"))
+        }
+        _ => SmbError::Backend(format!("SMB2 {command} failed ({name})")),
+    }
+}
+
+fn malformed(what: &str) -> SmbError {
+    SmbError::Backend(format!("malformed SMB2 {what}"))
+}
+
+// ── Little-endian helpers ────────────────────────────────────────────────────
+
+pub fn u16_at(buf: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(buf.get(at..at + 2)?.try_into().ok()?))
+}
+
+pub fn u32_at(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+pub fn u64_at(buf: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(buf.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// `len` bytes at a header-relative `offset`.
+fn slice_at(msg: &[u8], offset: usize, len: usize) -> Option<&[u8]> {
+    msg.get(offset..offset.checked_add(len)?)
+}
+
+/// UTF-16LE encoding used for every name on the wire.
+pub fn utf16le(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+pub fn from_utf16le(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Windows FILETIME (100 ns ticks since 1601) to Unix millis.
+pub fn filetime_to_millis(filetime: u64) -> Option<i64> {
+    if filetime == 0 {
+        return None;
+    }
+    Some((filetime / 10_000) as i64 - 11_644_473_600_000)
+}
+
+// ── Header ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    pub credit_charge: u16,
+    pub status: u32,
+    pub command: u16,
+    /// CreditRequest on requests, CreditResponse on responses.
+    pub credits: u16,
+    pub flags: u32,
+    pub next_command: u32,
+    pub message_id: u64,
+    /// Only meaningful when `FLAG_ASYNC` is set.
+    pub async_id: u64,
+    pub tree_id: u32,
+    pub session_id: u64,
+    pub signature: [u8; 16],
+}
+
+impl Header {
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&PROTOCOL_ID);
+        out.extend_from_slice(&64u16.to_le_bytes());
+        out.extend_from_slice(&self.credit_charge.to_le_bytes());
+        out.extend_from_slice(&self.status.to_le_bytes());
+        out.extend_from_slice(&self.command.to_le_bytes());
+        out.extend_from_slice(&self.credits.to_le_bytes());
+        out.extend_from_slice(&self.flags.to_le_bytes());
+        out.extend_from_slice(&self.next_command.to_le_bytes());
+        out.extend_from_slice(&self.message_id.to_le_bytes());
+        if self.flags & FLAG_ASYNC != 0 {
+            out.extend_from_slice(&self.async_id.to_le_bytes());
+        } else {
+            out.extend_from_slice(&0u32.to_le_bytes());
+            out.extend_from_slice(&self.tree_id.to_le_bytes());
+        }
+        out.extend_from_slice(&self.session_id.to_le_bytes());
+        out.extend_from_slice(&self.signature);
+    }
+
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        if msg.len() < HEADER_LEN || msg[..4] != PROTOCOL_ID || u16_at(msg, 4) != Some(64) {
+            return Err(malformed("header"));
+        }
+        let flags = u32_at(msg, 16).unwrap_or_default();
+        let (async_id, tree_id) = if flags & FLAG_ASYNC != 0 {
+            (u64_at(msg, 32).unwrap_or_default(), 0)
+        } else {
+            (0, u32_at(msg, 36).unwrap_or_default())
+        };
+        let mut signature = [0u8; 16];
+        signature.copy_from_slice(&msg[48..64]);
+        Ok(Self {
+            credit_charge: u16_at(msg, 6).unwrap_or_default(),
+            status: u32_at(msg, 8).unwrap_or_default(),
+            command: u16_at(msg, 12).unwrap_or_default(),
+            credits: u16_at(msg, 14).unwrap_or_default(),
+            flags,
+            next_command: u32_at(msg, 20).unwrap_or_default(),
+            message_id: u64_at(msg, 24).unwrap_or_default(),
+            async_id,
+            tree_id,
+            session_id: u64_at(msg, 40).unwrap_or_default(),
+            signature,
+        })
+    }
+}
+
+/// The 16-byte persistent + volatile handle returned by CREATE.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FileId(pub [u8; 16]);
+
+// ── NEGOTIATE ────────────────────────────────────────────────────────────────
+
+pub fn negotiate_request(
+    dialects: &[u16],
+    security_mode: u16,
+    capabilities: u32,
+    client_guid: [u8; 16],
+    contexts: &[(u16, Vec<u8>)],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(128);
+    body.extend_from_slice(&36u16.to_le_bytes());
+    body.extend_from_slice(&(dialects.len() as u16).to_le_bytes());
+    body.extend_from_slice(&security_mode.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&capabilities.to_le_bytes());
+    body.extend_from_slice(&client_guid);
+    let context_offset_at = body.len();
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&(contexts.len() as u16).to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    for dialect in dialects {
+        body.extend_from_slice(&dialect.to_le_bytes());
+    }
+    if !contexts.is_empty() {
+        pad_to_8(&mut body);
+        let offset = (HEADER_LEN + body.len()) as u32;
+        body[context_offset_at..context_offset_at + 4].copy_from_slice(&offset.to_le_bytes());
+        encode_contexts(&mut body, contexts);
+    }
+    body
+}
+
+fn pad_to_8(body: &mut Vec<u8>) {
+    // Bodies follow a 64-byte header, so body alignment equals message alignment.
+    body.resize(body.len().next_multiple_of(8), 0);
+}
+
+fn encode_contexts(body: &mut Vec<u8>, contexts: &[(u16, Vec<u8>)]) {
+    for (i, (kind, data)) in contexts.iter().enumerate() {
+        if i > 0 {
+            pad_to_8(body);
+        }
+        body.extend_from_slice(&kind.to_le_bytes());
+        body.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(data);
+    }
+}
+
+fn decode_contexts(msg: &[u8], offset: usize, count: usize) -> SmbResult<Vec<(u16, Vec<u8>)>> {
+    let mut contexts = Vec::with_capacity(count);
+    let mut at = offset;
+    for _ in 0..count {
+        at = at.next_multiple_of(8);
+        let kind = u16_at(msg, at).ok_or_else(|| malformed("negotiate context"))?;
+        let len = u16_at(msg, at + 2).ok_or_else(|| malformed("negotiate context"))? as usize;
+        let data = slice_at(msg, at + 8, len).ok_or_else(|| malformed("negotiate context"))?;
+        contexts.push((kind, data.to_vec()));
+        at += 8 + len;
+    }
+    Ok(contexts)
+}
+
+/// PREAUTH_INTEGRITY_CAPABILITIES context data offering SHA-512.
+pub fn preauth_context(salt: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(6 + salt.len());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&(salt.len() as u16).to_le_bytes());
+    data.extend_from_slice(&HASH_SHA512.to_le_bytes());
+    data.extend_from_slice(salt);
+    data
+}
+
+/// ENCRYPTION_CAPABILITIES context data listing `ciphers` in preference order.
+pub fn encryption_context(ciphers: &[u16]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(2 + ciphers.len() * 2);
+    data.extend_from_slice(&(ciphers.len() as u16).to_le_bytes());
+    for cipher in ciphers {
+        data.extend_from_slice(&cipher.to_le_bytes());
+    }
+    data
+}
+
+/// First entry of a PREAUTH_INTEGRITY or ENCRYPTION context list.
+pub fn first_context_value(data: &[u8], list_at: usize) -> Option<u16> {
+    (u16_at(data, 0)? > 0).then_some(())?;
+    u16_at(data, list_at)
+}
+
+#[derive(Debug, Clone)]
+pub struct NegotiateResponse {
+    pub security_mode: u16,
+    pub dialect: u16,
+    pub server_guid: [u8; 16],
+    pub capabilities: u32,
+    pub max_transact_size: u32,
+    pub max_read_size: u32,
+    pub max_write_size: u32,
+    pub security_buffer: Vec<u8>,
+    pub contexts: Vec<(u16, Vec<u8>)>,
+}
+
+impl NegotiateResponse {
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        let body = &msg[HEADER_LEN..];
+        if u16_at(body, 0) != Some(65) || body.len() < 64 {
+            return Err(malformed("NEGOTIATE response"));
+        }
+        let dialect = u16_at(body, 4).unwrap_or_default();
+        let mut server_guid = [0u8; 16];
+        server_guid.copy_from_slice(&body[8..24]);
+        let sec_offset = u16_at(body, 56).unwrap_or_default() as usize;
+        let sec_len = u16_at(body, 58).unwrap_or_default() as usize;
+        let security_buffer = if sec_len == 0 {
+            Vec::new()
+        } else {
+            slice_at(msg, sec_offset, sec_len)
+                .ok_or_else(|| malformed("NEGOTIATE security buffer"))?
+                .to_vec()
+        };
+        let contexts = if dialect == DIALECT_311 {
+            let count = u16_at(body, 6).unwrap_or_default() as usize;
+            let offset = u32_at(body, 60).unwrap_or_default() as usize;
+            decode_contexts(msg, offset, count)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            security_mode: u16_at(body, 2).unwrap_or_default(),
+            dialect,
+            server_guid,
+            capabilities: u32_at(body, 24).unwrap_or_default(),
+            max_transact_size: u32_at(body, 28).unwrap_or_default(),
+            max_read_size: u32_at(body, 32).unwrap_or_default(),
+            max_write_size: u32_at(body, 36).unwrap_or_default(),
+            security_buffer,
+            contexts,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(128);
+        body.extend_from_slice(&65u16.to_le_bytes());
+        body.extend_from_slice(&self.security_mode.to_le_bytes());
+        body.extend_from_slice(&self.dialect.to_le_bytes());
+        body.extend_from_slice(&(self.contexts.len() as u16).to_le_bytes());
+        body.extend_from_slice(&self.server_guid);
+        body.extend_from_slice(&self.capabilities.to_le_bytes());
+        body.extend_from_slice(&self.max_transact_size.to_le_bytes());
+        body.extend_from_slice(&self.max_read_size.to_le_bytes());
+        body.extend_from_slice(&self.max_write_size.to_le_bytes());
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(&((HEADER_LEN + 64) as u16).to_le_bytes());
+        body.extend_from_slice(&(self.security_buffer.len() as u16).to_le_bytes());
+        let context_offset_at = body.len();
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&self.security_buffer);
+        if self.contexts.is_empty() {
+            body.push(0);
+        } else {
+            pad_to_8(&mut body);
+            let offset = (HEADER_LEN + body.len()) as u32;
+            body[context_offset_at..context_offset_at + 4].copy_from_slice(&offset.to_le_bytes());
+            encode_contexts(&mut body, &self.contexts);
+        }
+        body
+    }
+}
+
+// ── SESSION_SETUP ────────────────────────────────────────────────────────────
+
+pub fn session_setup_request(security_mode: u8, security_buffer: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(24 + security_buffer.len());
+    body.extend_from_slice(&25u16.to_le_bytes());
+    body.push(0);
+    body.push(security_mode);
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 24) as u16).to_le_bytes());
+    body.extend_from_slice(&(security_buffer.len() as u16).to_le_bytes());
+    body.extend_from_slice(&0u64.to_le_bytes());
+    body.extend_from_slice(security_buffer);
+    body
+}
+
+/// Security blob of a SESSION_SETUP request.
+pub fn session_setup_request_token(msg: &[u8]) -> SmbResult<Vec<u8>> {
+    let body = &msg[HEADER_LEN..];
+    let offset = u16_at(body, 12).ok_or_else(|| malformed("SESSION_SETUP request"))? as usize;
+    let len = u16_at(body, 14).unwrap_or_default() as usize;
+    slice_at(msg, offset, len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| malformed("SESSION_SETUP request"))
+}
+
+pub fn session_setup_response(session_flags: u16, security_buffer: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(8 + security_buffer.len());
+    body.extend_from_slice(&9u16.to_le_bytes());
+    body.extend_from_slice(&session_flags.to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 8) as u16).to_le_bytes());
+    body.extend_from_slice(&(security_buffer.len() as u16).to_le_bytes());
+    body.extend_from_slice(security_buffer);
+    if security_buffer.is_empty() {
+        body.push(0);
+    }
+    body
+}
+
+/// `(session_flags, security_buffer)` from a SESSION_SETUP response.
+pub fn decode_session_setup_response(msg: &[u8]) -> SmbResult<(u16, Vec<u8>)> {
+    let body = &msg[HEADER_LEN..];
+    if u16_at(body, 0) != Some(9) {
+        return Err(malformed("SESSION_SETUP response"));
+    }
+    let flags = u16_at(body, 2).unwrap_or_default();
+    let offset = u16_at(body, 4).unwrap_or_default() as usize;
+    let len = u16_at(body, 6).unwrap_or_default() as usize;
+    let token = if len == 0 {
+        Vec::new()
+    } else {
+        slice_at(msg, offset, len)
+            .ok_or_else(|| malformed("SESSION_SETUP security buffer"))?
+            .to_vec()
+    };
+    Ok((flags, token))
+}
+
+// ── LOGOFF / TREE_DISCONNECT ─────────────────────────────────────────────────
+
+/// Body shared by LOGOFF and TREE_DISCONNECT requests and responses.
+pub fn empty_request() -> Vec<u8> {
+    let mut body = Vec::with_capacity(4);
+    body.extend_from_slice(&4u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body
+}
+
+// ── TREE_CONNECT ─────────────────────────────────────────────────────────────
+
+pub fn tree_connect_request(unc_path: &str) -> Vec<u8> {
+    let path = utf16le(unc_path);
+    let mut body = Vec::with_capacity(8 + path.len());
+    body.extend_from_slice(&9u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 8) as u16).to_le_bytes());
+    body.extend_from_slice(&(path.len() as u16).to_le_bytes());
+    body.extend_from_slice(&path);
+    body
+}
+
+pub fn tree_connect_request_path(msg: &[u8]) -> SmbResult<String> {
+    let body = &msg[HEADER_LEN..];
+    let offset = u16_at(body, 4).ok_or_else(|| malformed("TREE_CONNECT request"))? as usize;
+    let len = u16_at(body, 6).unwrap_or_default() as usize;
+    slice_at(msg, offset, len)
+        .map(from_utf16le)
+        .ok_or_else(|| malformed("TREE_CONNECT request"))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeConnectResponse {
+    pub share_type: u8,
+    pub share_flags: u32,
+    pub capabilities: u32,
+    pub maximal_access: u32,
+}
+
+impl TreeConnectResponse {
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        let body = &msg[HEADER_LEN..];
+        if u16_at(body, 0) != Some(16) || body.len() < 16 {
+            return Err(malformed("TREE_CONNECT response"));
+        }
+        Ok(Self {
+            share_type: body[2],
+            share_flags: u32_at(body, 4).unwrap_or_default(),
+            capabilities: u32_at(body, 8).unwrap_or_default(),
+            maximal_access: u32_at(body, 12).unwrap_or_default(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(16);
+        body.extend_from_slice(&16u16.to_le_bytes());
+        body.push(self.share_type);
+        body.push(0);
+        body.extend_from_slice(&self.share_flags.to_le_bytes());
+        body.extend_from_slice(&self.capabilities.to_le_bytes());
+        body.extend_from_slice(&self.maximal_access.to_le_bytes());
+        body
+    }
+}
+
+// ── CREATE / CLOSE ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreateRequest {
+    pub desired_access: u32,
+    pub file_attributes: u32,
+    pub share_access: u32,
+    pub create_disposition: u32,
+    pub create_options: u32,
+    /// Share-relative path with backslash separators and no leading slash.
+    pub name: String,
+}
+
+impl CreateRequest {
+    pub fn encode(&self) -> Vec<u8> {
+        let name = utf16le(&self.name);
+        let mut body = Vec::with_capacity(56 + name.len().max(1));
+        body.extend_from_slice(&57u16.to_le_bytes());
+        body.push(0); // SecurityFlags
+        body.push(0); // RequestedOplockLevel: none
+        body.extend_from_slice(&IMPERSONATION_IMPERSONATE.to_le_bytes());
+        body.extend_from_slice(&0u64.to_le_bytes()); // SmbCreateFlags
+        body.extend_from_slice(&0u64.to_le_bytes()); // Reserved
+        body.extend_from_slice(&self.desired_access.to_le_bytes());
+        body.extend_from_slice(&self.file_attributes.to_le_bytes());
+        body.extend_from_slice(&self.share_access.to_le_bytes());
+        body.extend_from_slice(&self.create_disposition.to_le_bytes());
+        body.extend_from_slice(&self.create_options.to_le_bytes());
+        body.extend_from_slice(&((HEADER_LEN + 56) as u16).to_le_bytes());
+        body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes()); // CreateContextsOffset
+        body.extend_from_slice(&0u32.to_le_bytes()); // CreateContextsLength
+        if name.is_empty() {
+            // The variable part must be at least one byte long.
+            body.push(0);
+        } else {
+            body.extend_from_slice(&name);
+        }
+        body
+    }
+
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        let body = &msg[HEADER_LEN..];
+        if u16_at(body, 0) != Some(57) || body.len() < 56 {
+            return Err(malformed("CREATE request"));
+        }
+        let offset = u16_at(body, 44).unwrap_or_default() as usize;
+        let len = u16_at(body, 46).unwrap_or_default() as usize;
+        let name = if len == 0 {
+            String::new()
+        } else {
+            slice_at(msg, offset, len)
+                .map(from_utf16le)
+                .ok_or_else(|| malformed("CREATE name"))?
+        };
+        Ok(Self {
+            desired_access: u32_at(body, 24).unwrap_or_default(),
+            file_attributes: u32_at(body, 28).unwrap_or_default(),
+            share_access: u32_at(body, 32).unwrap_or_default(),
+            create_disposition: u32_at(body, 36).unwrap_or_default(),
+            create_options: u32_at(body, 40).unwrap_or_default(),
+            name,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileTimes {
+    pub creation: u64,
+    pub last_access: u64,
+    pub last_write: u64,
+    pub change: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreateResponse {
+    pub create_action: u32,
+    pub times: FileTimes,
+    pub allocation_size: u64,
+    pub end_of_file: u64,
+    pub file_attributes: u32,
+    pub file_id: FileId,
+}
+
+impl CreateResponse {
+    pub fn decode(msg: &[u8]) -> SmbResult<Self> {
+        let body = &msg[HEADER_LEN..];
+        if u16_at(body, 0) != Some(89) || body.len() < 88 {
+            return Err(malformed("CREATE response"));
+        }
+        let mut file_id = [0u8; 16];
+        file_id.copy_from_slice(&body[64..80]);
+        Ok(Self {
+            create_action: u32_at(body, 4).unwrap_or_default(),
+            times: FileTimes {
+                creation: u64_at(body, 8).unwrap_or_default(),
+                last_access: u64_at(body, 16).unwrap_or_default(),
+                last_write: u64_at(body, 24).unwrap_or_default(),
+                change: u64_at(body, 32).unwrap_or_default(),
+            },
+            allocation_size: u64_at(body, 40).unwrap_or_default(),
+            end_of_file: u64_at(body, 48).unwrap_or_default(),
+            file_attributes: u32_at(body, 56).unwrap_or_default(),
+            file_id: FileId(file_id),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(89);
+        body.extend_from_slice(&89u16.to_le_bytes());
+        body.push(0);
+        body.push(0);
+        body.extend_from_slice(&self.create_action.to_le_bytes());
+        for time in [
+            self.times.creation,
+            self.times.last_access,
+            self.times.last_write,
+            self.times.change,
+            self.allocation_size,
+            self.end_of_file,
+        ] {
+            body.extend_from_slice(&time.to_le_bytes());
+        }
+        body.extend_from_slice(&self.file_attributes.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&self.file_id.0);
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.push(0);
+        body
+    }
+}
+
+pub fn close_request(file_id: FileId) -> Vec<u8> {
+    let mut body = Vec::with_capacity(24);
+    body.extend_from_slice(&24u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&file_id.0);
+    body
+}
+
+pub fn close_response() -> Vec<u8> {
+    let mut body = vec![0u8; 60];
+    body[..2].copy_from_slice(&60u16.to_le_bytes());
+    body
+}
+
+/// FileId carried by CLOSE, READ, WRITE, QUERY_DIRECTORY and SET_INFO
+/// requests, at its command-specific body offset.
+pub fn request_file_id(msg: &[u8], command: u16) -> SmbResult<FileId> {
+    let at = match command {
+        CLOSE => 8,
+        READ => 16,
+        WRITE => 16,
+        QUERY_DIRECTORY => 8,
+        SET_INFO => 16,
+        _ => return Err(malformed("request")),
+    };
+    let body = &msg[HEADER_LEN..];
+    let mut id = [0u8; 16];
+    id.copy_from_slice(body.get(at..at + 16).ok_or_else(|| malformed("request"))?);
+    Ok(FileId(id))
+}
+
+// ── READ / WRITE ─────────────────────────────────────────────────────────────
+
+pub fn read_request(file_id: FileId, offset: u64, length: u32) -> Vec<u8> {
+    let mut body = Vec::with_capacity(49);
+    body.extend_from_slice(&49u16.to_le_bytes());
+    body.push(0x50); // Padding: place returned data right after the response body
+    body.push(0); // Flags
+    body.extend_from_slice(&length.to_le_bytes());
+    body.extend_from_slice(&offset.to_le_bytes());
+    body.extend_from_slice(&file_id.0);
+    body.extend_from_slice(&0u32.to_le_bytes()); // MinimumCount
+    body.extend_from_slice(&0u32.to_le_bytes()); // Channel
+    body.extend_from_slice(&0u32.to_le_bytes()); // RemainingBytes
+    body.extend_from_slice(&0u16.to_le_bytes()); // ReadChannelInfoOffset
+    body.extend_from_slice(&0u16.to_le_bytes()); // ReadChannelInfoLength
+    body.push(0);
+    body
+}
+
+/// `(offset, length)` of a READ request.
+pub fn decode_read_request(msg: &[u8]) -> SmbResult<(u64, u32)> {
+    let body = &msg[HEADER_LEN..];
+    Ok((
+        u64_at(body, 8).ok_or_else(|| malformed("READ request"))?,
+        u32_at(body, 4).ok_or_else(|| malformed("READ request"))?,
+    ))
+}
+
+pub fn read_response(data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(16 + data.len());
+    body.extend_from_slice(&17u16.to_le_bytes());
+    body.push((HEADER_LEN + 16) as u8);
+    body.push(0);
+    body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(data);
+    if data.is_empty() {
+        body.push(0);
+    }
+    body
+}
+
+pub fn decode_read_response(msg: &[u8]) -> SmbResult<Vec<u8>> {
+    let body = &msg[HEADER_LEN..];
+    if u16_at(body, 0) != Some(17) {
+        return Err(malformed("READ response"));
+    }
+    let offset = body[2] as usize;
+    let len = u32_at(body, 4).unwrap_or_default() as usize;
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    slice_at(msg, offset, len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| malformed("READ data"))
+}
+
+pub fn write_request(file_id: FileId, offset: u64, data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(48 + data.len());
+    body.extend_from_slice(&49u16.to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 48) as u16).to_le_bytes());
+    body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    body.extend_from_slice(&offset.to_le_bytes());
+    body.extend_from_slice(&file_id.0);
+    body.extend_from_slice(&0u32.to_le_bytes()); // Channel
+    body.extend_from_slice(&0u32.to_le_bytes()); // RemainingBytes
+    body.extend_from_slice(&0u16.to_le_bytes()); // WriteChannelInfoOffset
+    body.extend_from_slice(&0u16.to_le_bytes()); // WriteChannelInfoLength
+    body.extend_from_slice(&0u32.to_le_bytes()); // Flags
+    body.extend_from_slice(data);
+    if data.is_empty() {
+        body.push(0);
+    }
+    body
+}
+
+/// `(offset, data)` of a WRITE request.
+pub fn decode_write_request(msg: &[u8]) -> SmbResult<(u64, Vec<u8>)> {
+    let body = &msg[HEADER_LEN..];
+    let data_offset = u16_at(body, 2).ok_or_else(|| malformed("WRITE request"))? as usize;
+    let len = u32_at(body, 4).unwrap_or_default() as usize;
+    let offset = u64_at(body, 8).unwrap_or_default();
+    let data = if len == 0 {
+        Vec::new()
+    } else {
+        slice_at(msg, data_offset, len)
+            .ok_or_else(|| malformed("WRITE data"))?
+            .to_vec()
+    };
+    Ok((offset, data))
+}
+
+pub fn write_response(count: u32) -> Vec<u8> {
+    let mut body = Vec::with_capacity(17);
+    body.extend_from_slice(&17u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&count.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.push(0);
+    body
+}
+
+pub fn decode_write_response(msg: &[u8]) -> SmbResult<u32> {
+    let body = &msg[HEADER_LEN..];
+    if u16_at(body, 0) != Some(17) {
+        return Err(malformed("WRITE response"));
+    }
+    u32_at(body, 4).ok_or_else(|| malformed("WRITE response"))
+}
+
+// ── QUERY_DIRECTORY ──────────────────────────────────────────────────────────
+
+pub fn query_directory_request(
+    file_id: FileId,
+    info_class: u8,
+    flags: u8,
+    pattern: &str,
+    output_buffer_length: u32,
+) -> Vec<u8> {
+    let pattern = utf16le(pattern);
+    let mut body = Vec::with_capacity(32 + pattern.len());
+    body.extend_from_slice(&33u16.to_le_bytes());
+    body.push(info_class);
+    body.push(flags);
+    body.extend_from_slice(&0u32.to_le_bytes()); // FileIndex
+    body.extend_from_slice(&file_id.0);
+    body.extend_from_slice(&((HEADER_LEN + 32) as u16).to_le_bytes());
+    body.extend_from_slice(&(pattern.len() as u16).to_le_bytes());
+    body.extend_from_slice(&output_buffer_length.to_le_bytes());
+    body.extend_from_slice(&pattern);
+    body
+}
+
+/// `(flags, output_buffer_length)` of a QUERY_DIRECTORY request.
+pub fn decode_query_directory_request(msg: &[u8]) -> SmbResult<(u8, u32)> {
+    let body = &msg[HEADER_LEN..];
+    Ok((
+        *body
+            .get(3)
+            .ok_or_else(|| malformed("QUERY_DIRECTORY request"))?,
+        u32_at(body, 28).ok_or_else(|| malformed("QUERY_DIRECTORY request"))?,
+    ))
+}
+
+/// Response body for QUERY_DIRECTORY and QUERY_INFO (same layout).
+pub fn output_buffer_response(buffer: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(8 + buffer.len());
+    body.extend_from_slice(&9u16.to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 8) as u16).to_le_bytes());
+    body.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    body.extend_from_slice(buffer);
+    if buffer.is_empty() {
+        body.push(0);
+    }
+    body
+}
+
+pub fn decode_output_buffer_response(msg: &[u8]) -> SmbResult<Vec<u8>> {
+    let body = &msg[HEADER_LEN..];
+    if u16_at(body, 0) != Some(9) {
+        return Err(malformed("QUERY_DIRECTORY response"));
+    }
+    let offset = u16_at(body, 2).unwrap_or_default() as usize;
+    let len = u32_at(body, 4).unwrap_or_default() as usize;
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    slice_at(msg, offset, len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| malformed("QUERY_DIRECTORY buffer"))
+}
+
+/// One `FILE_DIRECTORY_INFORMATION` record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectoryInfo {
+    pub name: String,
+    pub times: FileTimes,
+    pub end_of_file: u64,
+    pub allocation_size: u64,
+    pub file_attributes: u32,
+}
+
+impl DirectoryInfo {
+    pub fn encode_list(entries: &[DirectoryInfo]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let start = out.len();
+            let name = utf16le(&entry.name);
+            out.extend_from_slice(&0u32.to_le_bytes());
+            out.extend_from_slice(&0u32.to_le_bytes());
+            for value in [
+                entry.times.creation,
+                entry.times.last_access,
+                entry.times.last_write,
+                entry.times.change,
+                entry.end_of_file,
+                entry.allocation_size,
+            ] {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            out.extend_from_slice(&entry.file_attributes.to_le_bytes());
+            out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            out.extend_from_slice(&name);
+            if i + 1 < entries.len() {
+                out.resize(out.len().next_multiple_of(8), 0);
+                let next = (out.len() - start) as u32;
+                out[start..start + 4].copy_from_slice(&next.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    pub fn decode_list(buf: &[u8]) -> SmbResult<Vec<DirectoryInfo>> {
+        let mut entries = Vec::new();
+        let mut at = 0usize;
+        loop {
+            let record = buf.get(at..).ok_or_else(|| malformed("directory entry"))?;
+            let next = u32_at(record, 0).ok_or_else(|| malformed("directory entry"))? as usize;
+            let name_len = u32_at(record, 60).ok_or_else(|| malformed("directory entry"))?;
+            let name = record
+                .get(64..64 + name_len as usize)
+                .ok_or_else(|| malformed("directory entry name"))?;
+            entries.push(DirectoryInfo {
+                name: from_utf16le(name),
+                times: FileTimes {
+                    creation: u64_at(record, 8).unwrap_or_default(),
+                    last_access: u64_at(record, 16).unwrap_or_default(),
+                    last_write: u64_at(record, 24).unwrap_or_default(),
+                    change: u64_at(record, 32).unwrap_or_default(),
+                },
+                end_of_file: u64_at(record, 40).unwrap_or_default(),
+                allocation_size: u64_at(record, 48).unwrap_or_default(),
+                file_attributes: u32_at(record, 56).unwrap_or_default(),
+            });
+            if next == 0 {
+                return Ok(entries);
+            }
+            at += next;
+        }
+    }
+}
+
+// ── SET_INFO ─────────────────────────────────────────────────────────────────
+
+pub fn set_info_request(file_id: FileId, info_type: u8, info_class: u8, buffer: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(32 + buffer.len());
+    body.extend_from_slice(&33u16.to_le_bytes());
+    body.push(info_type);
+    body.push(info_class);
+    body.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    body.extend_from_slice(&((HEADER_LEN + 32) as u16).to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes()); // AdditionalInformation
+    body.extend_from_slice(&file_id.0);
+    body.extend_from_slice(buffer);
+    body
+}
+
+/// `(info_type, info_class, buffer)` of a SET_INFO request.
+pub fn decode_set_info_request(msg: &[u8]) -> SmbResult<(u8, u8, Vec<u8>)> {
+    let body = &msg[HEADER_LEN..];
+    let len = u32_at(body, 4).ok_or_else(|| malformed("SET_INFO request"))? as usize;
+    let offset = u16_at(body, 8).unwrap_or_default() as usize;
+    let buffer = slice_at(msg, offset, len).ok_or_else(|| malformed("SET_INFO buffer"))?;
+    Ok((body[2], body[3], buffer.to_vec()))
+}
+
+pub fn set_info_response() -> Vec<u8> {
+    2u16.to_le_bytes().to_vec()
+}
+
+/// FILE_RENAME_INFORMATION (SMB2 variant) for a share-relative target.
+pub fn rename_information(target: &str, replace_if_exists: bool) -> Vec<u8> {
+    let name = utf16le(target);
+    let mut buf = Vec::with_capacity(20 + name.len());
+    buf.push(replace_if_exists as u8);
+    buf.extend_from_slice(&[0u8; 7]);
+    buf.extend_from_slice(&0u64.to_le_bytes()); // RootDirectory
+    buf.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&name);
+    buf
+}
+
+/// `(replace_if_exists, target)` of FILE_RENAME_INFORMATION.
+pub fn decode_rename_information(buf: &[u8]) -> SmbResult<(bool, String)> {
+    let len = u32_at(buf, 16).ok_or_else(|| malformed("rename information"))? as usize;
+    let name = buf
+        .get(20..20 + len)
+        .ok_or_else(|| malformed("rename information"))?;
+    Ok((buf[0] != 0, from_utf16le(name)))
+}
+
+/// FILE_DISPOSITION_INFORMATION marking the open for deletion.
+pub fn delete_on_close_information() -> Vec<u8> {
+    vec![1]
+}
+
+// ── Message framing ──────────────────────────────────────────────────────────
+
+/// Assemble header + body into one message.
+pub fn message(header: &Header, body: &[u8]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(HEADER_LEN + body.len());
+    header.encode(&mut msg);
+    msg.extend_from_slice(body);
+    msg
+}
+
+/// Prefix a message with the 4-byte Direct TCP transport header.
+pub fn frame(msg: &[u8]) -> Vec<u8> {
+    let len = msg.len() as u32;
+    let mut out = Vec::with_capacity(4 + msg.len());
+    out.push(0);
+    out.extend_from_slice(&len.to_be_bytes()[1..]);
+    out.extend_from_slice(msg);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_roundtrip_sync_and_async() {
+        let header = Header {
+            credit_charge: 2,
+            status: status::PENDING,
+            command: READ,
+            credits: 31,
+            flags: FLAG_SERVER_TO_REDIR,
+            message_id: 7,
+            tree_id: 9,
+            session_id: 0x1122_3344_5566_7788,
+            signature: [0xAB; 16],
+            ..Header::default()
+        };
+        let mut buf = Vec::new();
+        header.encode(&mut buf);
+        assert_eq!(buf.len(), HEADER_LEN);
+        assert_eq!(Header::decode(&buf).unwrap(), header);
+
+        let async_header = Header {
+            flags: FLAG_ASYNC,
+            async_id: 42,
+            ..Header::default()
+        };
+        let mut buf = Vec::new();
+        async_header.encode(&mut buf);
+        let decoded = Header::decode(&buf).unwrap();
+        assert_eq!(decoded.async_id, 42);
+        assert_eq!(decoded.tree_id, 0);
+    }
+
+    #[test]
+    fn negotiate_contexts_are_8_byte_aligned() {
+        let contexts = vec![
+            (CTX_PREAUTH_INTEGRITY, preauth_context(&[1; 32])),
+            (CTX_ENCRYPTION, encryption_context(&[CIPHER_AES128_GCM])),
+        ];
+        let body = negotiate_request(
+            &[DIALECT_210, DIALECT_311],
+            SIGNING_ENABLED,
+            0,
+            [0; 16],
+            &contexts,
+        );
+        let msg = message(&Header::default(), &body);
+        let offset = u32_at(&msg, HEADER_LEN + 28).unwrap() as usize;
+        assert_eq!(offset % 8, 0);
+        assert_eq!(decode_contexts(&msg, offset, 2).unwrap(), contexts);
+    }
+
+    #[test]
+    fn directory_list_roundtrip() {
+        let entries = vec![
+            DirectoryInfo {
+                name: "a.txt".into(),
+                end_of_file: 5,
+                file_attributes: ATTR_NORMAL,
+                ..DirectoryInfo::default()
+            },
+            DirectoryInfo {
+                name: "sub dir".into(),
+                file_attributes: ATTR_DIRECTORY,
+                ..DirectoryInfo::default()
+            },
+        ];
+        let buf = DirectoryInfo::encode_list(&entries);
+        assert_eq!(DirectoryInfo::decode_list(&buf).unwrap(), entries);
+        assert!(DirectoryInfo::decode_list(&buf[..70]).is_err());
+    }
+
+    #[test]
+    fn status_errors_map_to_crate_errors() {
+        assert!(matches!(
+            status_error("SESSION_SETUP", status::LOGON_FAILURE),
+            SmbError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            status_error("CREATE", status::OBJECT_NAME_NOT_FOUND),
+            SmbError::InvalidPath(_)
+        ));
+        let other = status_error("READ", 0xC000_9999).to_string();
+        assert!(other.contains("NTSTATUS 0xC0009999"));
+    }
+}
@@ -88,7 +88,8 @@ pub struct SmbSessionInfo {
     pub label: Option<String>,
     pub connected_at: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
-    /// Platform backing this session: "windows-unc" or "unix-smbclient".
+    /// Backend serving this session: "windows-unc", "native-smb2", or
+    /// "unix-smbclient" (Kerberos sessions on Unix).
     pub backend: String,
 }
 
@@ -4,6 +4,7 @@
 //! loading from files, key generation, signing operations (RSA-SHA256/512,
 //! Ed25519, ECDSA), certificate support, and request dispatch.
 
+use crate::certificate;
 use crate::keystore::KeyStore;
 use crate::protocol::{self, msg, AgentMessage, ProtocolIdentity};
 use crate::types::*;
@@ -13,6 +14,7 @@ use rsa::pkcs1v15;
 use rsa::BigUint;
 use sha2::{Digest, Sha256};
 use signature::{SignatureEncoding, Signer};
+use sorng_core::ssh_identities::SshAuthIdentity;
 use ssh_key::private::{Ed25519Keypair, KeypairData, RsaKeypair, RsaPrivateKey};
 use ssh_key::public::{Ed25519PublicKey, RsaPublicKey};
 use ssh_key::{Mpint, PrivateKey};
@@ -20,6 +22,8 @@ use std::collections::HashMap;
 use tokio::sync::broadcast;
 
 const MAX_PENDING_CONFIRMATIONS: usize = 128;
+/// How long a signing confirmation stays answerable.
+pub const CONFIRMATION_TIMEOUT_SECS: i64 = 30;
 
 /// Built-in SSH agent that manages keys and handles protocol requests.
 pub struct BuiltinAgent {
@@ -31,6 +35,8 @@ pub struct BuiltinAgent {
     event_tx: broadcast::Sender<AgentEvent>,
     /// Pending confirmations: request_id → (key fingerprint, data hash).
     pending_confirmations: HashMap<String, PendingSignRequest>,
+    /// In-process callers blocked on a confirmation: request_id → answer.
+    confirmation_waiters: HashMap<String, std::sync::mpsc::Sender<bool>>,
 }
 
 impl BuiltinAgent {
@@ -41,6 +47,7 @@ impl BuiltinAgent {
             config,
             event_tx,
             pending_confirmations: HashMap::new(),
+            confirmation_waiters: HashMap::new(),
         }
     }
 
@@ -196,7 +203,8 @@ impl BuiltinAgent {
                 data_hash: data_hash.clone(),
                 client_info: None,
                 requested_at: chrono::Utc::now(),
-                expires_at: chrono::Utc::now() + chrono::Duration::seconds(30),
+                expires_at: chrono::Utc::now()
+                    + chrono::Duration::seconds(CONFIRMATION_TIMEOUT_SECS),
             };
             self.pending_confirmations
                 .insert(request_id.clone(), pending.clone());
@@ -272,12 +280,6 @@ impl BuiltinAgent {
         let Some(algorithm) = KeyAlgorithm::try_from_ssh_name(key_type) else {
             return AgentMessage::Failure;
         };
-        if (!self.config.allowed_algorithms.is_empty()
-            && !self.config.allowed_algorithms.contains(&algorithm))
-            || (algorithm == KeyAlgorithm::Dsa && !self.config.allow_dsa)
-        {
-            return AgentMessage::Failure;
-        }
         if keystore::validate_text_field("Key comment", &parsed.comment, protocol::MAX_TEXT_LEN)
             .is_err()
         {
@@ -291,27 +293,11 @@ impl BuiltinAgent {
             Some(KeypairData::Ed25519(_)) => 256,
             _ => return AgentMessage::Failure,
         };
-        if algorithm == KeyAlgorithm::Rsa && bits < self.config.min_rsa_bits {
-            warn!("Rejected RSA key below the configured size");
+        if let Err(e) = self.check_key_policy(algorithm, bits) {
+            warn!("Rejected key: {}", e);
             return AgentMessage::Failure;
         }
-        let mut constraints = constraints;
-        if self.config.default_lifetime_secs > 0
-            && !constraints
-                .iter()
-                .any(|item| matches!(item, KeyConstraint::Lifetime(_)))
-        {
-            constraints.push(KeyConstraint::Lifetime(
-                self.config.default_lifetime_secs as u64,
-            ));
-        }
-        if self.config.default_confirm
-            && !constraints
-                .iter()
-                .any(|item| matches!(item, KeyConstraint::ConfirmBeforeUse))
-        {
-            constraints.push(KeyConstraint::ConfirmBeforeUse);
-        }
+        let constraints = self.with_default_constraints(constraints);
         if constraints::validate_key_constraints(&constraints).is_err() {
             return AgentMessage::Failure;
         }
@@ -334,7 +320,7 @@ impl BuiltinAgent {
             public_key_openssh: String::new(),
             source: KeySource::Imported,
             constraints,
-            certificate: None,
+            certificate: parsed.certificate,
             added_at: chrono::Utc::now(),
             last_used_at: None,
             sign_count: 0,
@@ -491,10 +477,25 @@ impl BuiltinAgent {
             .pending_confirmations
             .remove(request_id)
             .ok_or_else(|| "No pending confirmation found".to_string())?;
+        let waiter = self.confirmation_waiters.remove(request_id);
         if pending.expires_at <= chrono::Utc::now() {
             return Err("Signing confirmation has expired".to_string());
         }
 
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(approved);
+            let _ = self.event_tx.send(AgentEvent::ConfirmationResponse {
+                request_id: request_id.to_string(),
+                approved,
+            });
+            info!(
+                "Confirmation {} for {}",
+                if approved { "approved" } else { "denied" },
+                pending.key_fingerprint
+            );
+            return Ok(());
+        }
+
         let _ = self.event_tx.send(AgentEvent::ConfirmationResponse {
             request_id: request_id.to_string(),
             approved: false,
@@ -527,6 +528,8 @@ impl BuiltinAgent {
         let count = expired.len();
         for id in expired {
             self.pending_confirmations.remove(&id);
+            // Dropping the sender wakes the waiter with a denial.
+            self.confirmation_waiters.remove(&id);
         }
         count
     }
@@ -617,16 +620,294 @@ impl BuiltinAgent {
         Ok(())
     }
 
+    /// Attach an OpenSSH certificate to a loaded private key, adding it as a
+    /// separate certificate identity that signs with the same private key.
+    pub fn add_certificate(
+        &mut self,
+        key_id: &str,
+        certificate_text: &str,
+    ) -> Result<String, String> {
+        let key = self
+            .store
+            .find_by_id(key_id)
+            .cloned()
+            .ok_or_else(|| format!("Key not found: {}", key_id))?;
+        let private_key = self
+            .store
+            .find_private_by_blob(&key.public_key_blob)
+            .cloned()
+            .ok_or_else(|| "Key has no private signing material".to_string())?;
+        let (blob, parsed, comment) = certificate::parse_openssh_certificate(certificate_text)?;
+        if parsed.public_key_blob != key.public_key_blob {
+            return Err("Certificate was not issued for this key".to_string());
+        }
+
+        let fingerprint = format!(
+            "SHA256:{}",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD_NO_PAD,
+                Sha256::digest(&blob),
+            )
+        );
+        let comment = comment.unwrap_or_else(|| key.comment.clone());
+        let cert_key = AgentKey {
+            id: uuid::Uuid::new_v4().to_string(),
+            public_key_openssh: format!(
+                "{} {} {}",
+                parsed.key_type,
+                base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &blob),
+                comment
+            ),
+            comment,
+            fingerprint_sha256: fingerprint.clone(),
+            public_key_blob: blob,
+            certificate: Some(parsed.info),
+            added_at: chrono::Utc::now(),
+            last_used_at: None,
+            sign_count: 0,
+            ..key
+        };
+        let id = self
+            .store
+            .add_key_with_private(cert_key, Some(private_key))?;
+        info!("Added certificate identity {}", id);
+        let _ = self.event_tx.send(AgentEvent::KeyAdded {
+            key_id: id.clone(),
+            fingerprint,
+        });
+        Ok(id)
+    }
+
     /// List all loaded keys (convenience wrapper over the key store).
     pub fn list_keys(&self) -> Vec<AgentKey> {
         self.store.all_keys().into_iter().cloned().collect()
     }
+
+    // ── Key Policy ──────────────────────────────────────────────────
+
+    /// Enforce the configured algorithm allow-list and minimum RSA size.
+    fn check_key_policy(&self, algorithm: KeyAlgorithm, bits: u32) -> Result<(), String> {
+        if (!self.config.allowed_algorithms.is_empty()
+            && !self.config.allowed_algorithms.contains(&algorithm))
+            || (algorithm == KeyAlgorithm::Dsa && !self.config.allow_dsa)
+        {
+            return Err("Key algorithm is not allowed by the agent policy".to_string());
+        }
+        if algorithm == KeyAlgorithm::Rsa && bits < self.config.min_rsa_bits {
+            return Err("RSA key is below the configured size".to_string());
+        }
+        Ok(())
+    }
+
+    /// Add the configured default lifetime / confirmation constraints when
+    /// the caller did not supply its own.
+    fn with_default_constraints(&self, mut constraints: Vec<KeyConstraint>) -> Vec<KeyConstraint> {
+        if self.config.default_lifetime_secs > 0
+            && !constraints
+                .iter()
+                .any(|item| matches!(item, KeyConstraint::Lifetime(_)))
+        {
+            constraints.push(KeyConstraint::Lifetime(
+                self.config.default_lifetime_secs as u64,
+            ));
+        }
+        if self.config.default_confirm
+            && !constraints
+                .iter()
+                .any(|item| matches!(item, KeyConstraint::ConfirmBeforeUse))
+        {
+            constraints.push(KeyConstraint::ConfirmBeforeUse);
+        }
+        constraints
+    }
+
+    // ── In-Process Identities ───────────────────────────────────────
+
+    /// Load an OpenSSH-format private key (as stored in a credential store),
+    /// decrypting it with `passphrase` when it is encrypted. The same policy
+    /// as protocol add-identity requests applies.
+    pub fn add_private_key(
+        &mut self,
+        private_key_openssh: &str,
+        passphrase: Option<&str>,
+        comment: &str,
+        source: KeySource,
+        constraints: Vec<KeyConstraint>,
+    ) -> Result<String, String> {
+        let mut private_key = PrivateKey::from_openssh(private_key_openssh)
+            .map_err(|e| format!("Invalid OpenSSH private key: {}", e))?;
+        if private_key.is_encrypted() {
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or_else(|| "Private key is encrypted and no passphrase is set".to_string())?;
+            private_key = private_key
+                .decrypt(passphrase)
+                .map_err(|_| "Incorrect private key passphrase".to_string())?;
+        }
+        let (algorithm, bits) = match private_key.key_data() {
+            KeypairData::Ed25519(_) => (KeyAlgorithm::Ed25519, 256),
+            KeypairData::Rsa(keypair) => {
+                validate_rsa_keypair(keypair)?;
+                let bits = u32::try_from(keypair.public.n.as_bytes().len().saturating_mul(8))
+                    .unwrap_or(u32::MAX);
+                (KeyAlgorithm::Rsa, bits)
+            }
+            other => {
+                return Err(format!(
+                    "Unsupported private key algorithm: {:?}",
+                    other.algorithm()
+                ))
+            }
+        };
+        self.check_key_policy(algorithm, bits)?;
+        let constraints = self.with_default_constraints(constraints);
+        constraints::validate_key_constraints(&constraints)?;
+
+        let comment = if comment.is_empty() {
+            private_key.comment().to_string()
+        } else {
+            comment.to_string()
+        };
+        private_key.set_comment(comment.clone());
+        let public_key = private_key.public_key();
+        let public_key_blob = public_key
+            .to_bytes()
+            .map_err(|e| format!("Cannot encode public key: {}", e))?;
+        let public_key_openssh = public_key
+            .to_openssh()
+            .map_err(|e| format!("Cannot encode public key: {}", e))?;
+        let fingerprint = format!(
+            "SHA256:{}",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD_NO_PAD,
+                Sha256::digest(&public_key_blob),
+            )
+        );
+
+        let key = AgentKey {
+            id: uuid::Uuid::new_v4().to_string(),
+            comment,
+            algorithm,
+            bits,
+            fingerprint_sha256: fingerprint.clone(),
+            fingerprint_md5: String::new(),
+            public_key_blob,
+            public_key_openssh,
+            source,
+            constraints,
+            certificate: None,
+            added_at: chrono::Utc::now(),
+            last_used_at: None,
+            sign_count: 0,
+            metadata: HashMap::new(),
+        };
+        let id = self.store.add_key_with_private(key, Some(private_key))?;
+        info!("Added key {}", id);
+        let _ = self.event_tx.send(AgentEvent::KeyAdded {
+            key_id: id.clone(),
+            fingerprint,
+        });
+        Ok(id)
+    }
+
+    /// Keys the in-process SSH client may use to log in as `user` on `host`:
+    /// plain keys with private material that pass the algorithm policy and
+    /// their constraints. Expired keys are purged first.
+    pub fn auth_identities(&mut self, host: &str, user: &str) -> Vec<SshAuthIdentity> {
+        self.expire_keys();
+        let mut keys: Vec<&AgentKey> = self
+            .store
+            .all_keys()
+            .into_iter()
+            .filter(|k| k.certificate.is_none())
+            .filter(|k| self.check_key_policy(k.algorithm, k.bits).is_ok())
+            .collect();
+        keys.sort_by_key(|k| k.added_at);
+        keys.into_iter()
+            .filter_map(|key| {
+                let result = constraints::can_sign(key, Some(host), Some(user), 0);
+                if !result.allowed {
+                    return None;
+                }
+                let private_key = self.store.find_private_by_blob(&key.public_key_blob)?;
+                let pem = private_key.to_openssh(ssh_key::LineEnding::LF).ok()?;
+                Some(SshAuthIdentity {
+                    id: key.id.clone(),
+                    comment: key.comment.clone(),
+                    private_key_openssh: pem.to_string(),
+                    requires_confirmation: result.needs_confirmation,
+                })
+            })
+            .collect()
+    }
+
+    /// Register a confirmation for using `key_id` to log in as `user` on
+    /// `host`. The answer given to
+    /// [`resolve_confirmation`](Self::resolve_confirmation) is delivered on
+    /// the returned receiver; expiry drops the sender, which reads as a
+    /// denial.
+    pub fn request_confirmation(
+        &mut self,
+        key_id: &str,
+        host: &str,
+        user: &str,
+    ) -> Result<std::sync::mpsc::Receiver<bool>, String> {
+        let fingerprint = self
+            .store
+            .find_by_id(key_id)
+            .map(|k| k.fingerprint_sha256.clone())
+            .ok_or_else(|| "Key not found".to_string())?;
+        self.cleanup_expired_confirmations();
+        if self.pending_confirmations.len() >= MAX_PENDING_CONFIRMATIONS {
+            return Err("Pending signing confirmation limit reached".to_string());
+        }
+        let destination = format!("{}@{}", user, host);
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now();
+        let pending = PendingSignRequest {
+            id: request_id.clone(),
+            key_fingerprint: fingerprint,
+            data_hash: hex::encode(Sha256::digest(destination.as_bytes())),
+            client_info: Some(ClientInfo {
+                process_name: None,
+                pid: None,
+                address: Some(destination),
+            }),
+            requested_at: now,
+            expires_at: now + chrono::Duration::seconds(CONFIRMATION_TIMEOUT_SECS),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pending_confirmations
+            .insert(request_id.clone(), pending.clone());
+        self.confirmation_waiters.insert(request_id, tx);
+        let _ = self
+            .event_tx
+            .send(AgentEvent::ConfirmationRequested(pending));
+        Ok(rx)
+    }
+
+    /// Count a successful in-process authentication against the key's
+    /// signature limit.
+    pub fn record_auth_use(&mut self, key_id: &str) -> Result<(), String> {
+        let (blob, fingerprint) = self
+            .store
+            .find_by_id(key_id)
+            .map(|k| (k.public_key_blob.clone(), k.fingerprint_sha256.clone()))
+            .ok_or_else(|| "Key not found".to_string())?;
+        self.store.record_sign(&blob)?;
+        let _ = self.event_tx.send(AgentEvent::SignCompleted {
+            key_fingerprint: fingerprint,
+            success: true,
+        });
+        Ok(())
+    }
 }
 
 struct ParsedIdentity {
     public_key_blob: Vec<u8>,
     private_key: Option<PrivateKey>,
     comment: String,
+    certificate: Option<CertificateInfo>,
 }
 
 fn parse_add_identity_key(
@@ -639,10 +920,46 @@ fn parse_add_identity_key(
         "ssh-rsa" | "rsa-sha2-256" | "rsa-sha2-512" => {
             parse_rsa_identity(key_data, fallback_comment)
         }
+        "ssh-ed25519-cert-v01@openssh.com" | "ssh-rsa-cert-v01@openssh.com" => {
+            parse_certificate_identity(key_type, key_data, fallback_comment)
+        }
         other => Err(format!("Unsupported add-identity key type: {}", other)),
     }
 }
 
+/// Certificate identities carry the certificate blob in place of the public
+/// key fields: `string cert, <private fields>, string comment`. RSA private
+/// fields omit `n`/`e`, which come from the certificate.
+fn parse_certificate_identity(
+    key_type: &str,
+    key_data: &[u8],
+    fallback_comment: &str,
+) -> Result<ParsedIdentity, String> {
+    let (cert_blob, offset) = protocol::read_string(key_data, 0)?;
+    let cert = certificate::parse_certificate(&cert_blob)?;
+    if cert.key_type != key_type {
+        return Err("Certificate type does not match add-identity key type".to_string());
+    }
+    let private_fields = &key_data[offset..];
+    let mut parsed = if key_type.starts_with("ssh-rsa") {
+        let (_, offset) = protocol::read_string(&cert.public_key_blob, 0)?;
+        let (e, offset) = read_mpint_field(&cert.public_key_blob, offset)?;
+        let (n, _) = read_mpint_field(&cert.public_key_blob, offset)?;
+        let mut rsa_fields = protocol::write_string(&n);
+        rsa_fields.extend(protocol::write_string(&e));
+        rsa_fields.extend_from_slice(private_fields);
+        parse_rsa_identity(&rsa_fields, fallback_comment)?
+    } else {
+        parse_ed25519_identity(private_fields, fallback_comment)?
+    };
+    if parsed.public_key_blob != cert.public_key_blob {
+        return Err("Certificate was not issued for the supplied private key".to_string());
+    }
+    parsed.public_key_blob = cert_blob;
+    parsed.certificate = Some(cert.info);
+    Ok(parsed)
+}
+
 fn parse_ed25519_identity(
     key_data: &[u8],
     fallback_comment: &str,
@@ -675,6 +992,7 @@ fn parse_ed25519_identity(
         public_key_blob: make_public_key_blob("ssh-ed25519", &[protocol::write_string(&public)]),
         private_key: Some(private_key),
         comment,
+        certificate: None,
     })
 }
 
@@ -713,6 +1031,7 @@ fn parse_rsa_identity(key_data: &[u8], fallback_comment: &str) -> Result<ParsedI
         ),
         private_key: Some(private_key),
         comment,
+        certificate: None,
     })
 }
 
@@ -1147,4 +1466,113 @@ mod tests {
             .await;
         assert!(matches!(legacy_sha1, AgentMessage::Failure));
     }
+
+    fn user_certificate(subject: &PrivateKey, principal: &str) -> ssh_key::Certificate {
+        use ssh_key::certificate::{Builder, CertType};
+        let ca = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut builder = Builder::new(
+            [5u8; 16],
+            subject.public_key().key_data().clone(),
+            now - 60,
+            now + 3600,
+        )
+        .unwrap();
+        builder.cert_type(CertType::User).unwrap();
+        builder.key_id("cert-test").unwrap();
+        builder.valid_principal(principal).unwrap();
+        builder.sign(&ca).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_add_identity_ed25519_certificate_signs_with_cert_blob() {
+        let mut agent = make_agent();
+        let private_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let cert_blob = user_certificate(&private_key, "alice").to_bytes().unwrap();
+        let (private_fields, _) = ed25519_agent_key_data(&private_key, "cert-key");
+        let mut key_data = protocol::write_string(&cert_blob);
+        key_data.extend(private_fields);
+
+        let resp = agent
+            .process_message(AgentMessage::AddIdentity {
+                key_type: "ssh-ed25519-cert-v01@openssh.com".to_string(),
+                key_data,
+                comment: String::new(),
+            })
+            .await;
+        assert!(matches!(resp, AgentMessage::Success));
+
+        let key = agent.list_keys().pop().unwrap();
+        assert_eq!(key.public_key_blob, cert_blob);
+        assert_eq!(key.algorithm, KeyAlgorithm::Ed25519);
+        let certificate = key.certificate.unwrap();
+        assert_eq!(certificate.key_id, "cert-test");
+        assert_eq!(certificate.valid_principals, vec!["alice".to_string()]);
+
+        let resp = agent
+            .process_message(AgentMessage::SignRequest {
+                key_blob: cert_blob,
+                data: b"cert session data".to_vec(),
+                flags: 0,
+            })
+            .await;
+        let AgentMessage::SignResponse { signature } = resp else {
+            unreachable!("Expected SignResponse");
+        };
+        assert_eq!(signature_algorithm(&signature), "ssh-ed25519");
+    }
+
+    #[tokio::test]
+    async fn test_add_identity_certificate_rejects_foreign_private_key() {
+        let mut agent = make_agent();
+        let subject = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let other = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let cert_blob = user_certificate(&subject, "alice").to_bytes().unwrap();
+        let (private_fields, _) = ed25519_agent_key_data(&other, "other");
+        let mut key_data = protocol::write_string(&cert_blob);
+        key_data.extend(private_fields);
+
+        let resp = agent
+            .process_message(AgentMessage::AddIdentity {
+                key_type: "ssh-ed25519-cert-v01@openssh.com".to_string(),
+                key_data,
+                comment: String::new(),
+            })
+            .await;
+        assert!(matches!(resp, AgentMessage::Failure));
+        assert_eq!(agent.store.key_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_add_certificate_to_loaded_key() {
+        let mut agent = make_agent();
+        let private_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let (key_data, _) = ed25519_agent_key_data(&private_key, "plain");
+        agent
+            .process_message(AgentMessage::AddIdentity {
+                key_type: "ssh-ed25519".to_string(),
+                key_data,
+                comment: String::new(),
+            })
+            .await;
+        let plain_id = agent.list_keys().pop().unwrap().id;
+
+        let cert = user_certificate(&private_key, "alice");
+        let cert_id = agent
+            .add_certificate(&plain_id, &cert.to_openssh().unwrap())
+            .unwrap();
+        assert_eq!(agent.store.key_count(), 2);
+        let cert_key = agent.get_key(&cert_id).unwrap();
+        assert!(cert_key
+            .public_key_openssh
+            .starts_with("ssh-ed25519-cert-v01@openssh.com "));
+        assert!(agent
+            .store
+            .find_private_by_blob(&cert_key.public_key_blob)
+            .is_some());
+
+        let foreign = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let foreign_cert = user_certificate(&foreign, "alice").to_openssh().unwrap();
+        assert!(agent.add_certificate(&plain_id, &foreign_cert).is_err());
+    }
 }
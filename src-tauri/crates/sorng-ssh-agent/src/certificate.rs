@@ -0,0 +1,129 @@
+//! # SSH Certificates
+//!
+//! Maps OpenSSH `*-cert-v01@openssh.com` blobs, decoded by
+//! [`sorng_core::ssh_certificate`], into [`CertificateInfo`] so certificate
+//! identities can be added to, listed by and signed with the agent. The CA
+//! signature is not checked here — that is the server's job; the agent only
+//! needs the metadata and the certified public key.
+
+use chrono::{DateTime, Utc};
+use sorng_core::ssh_certificate::{self, OpenSshCertificate};
+
+use crate::types::{CertificateInfo, CertificateType};
+
+pub use sorng_core::ssh_certificate::{base_key_type, CERT_KEY_TYPE_SUFFIX};
+
+/// A decoded certificate blob.
+#[derive(Debug, Clone)]
+pub struct ParsedCertificate {
+    /// Certificate key type, e.g. `ssh-ed25519-cert-v01@openssh.com`.
+    pub key_type: String,
+    /// Plain public-key blob of the certified key.
+    pub public_key_blob: Vec<u8>,
+    /// Certificate metadata.
+    pub info: CertificateInfo,
+}
+
+/// Whether `key_type` names an OpenSSH certificate.
+pub fn is_certificate_type(key_type: &str) -> bool {
+    ssh_certificate::is_certificate_key_type(key_type)
+}
+
+/// Decode a certificate blob.
+pub fn parse_certificate(blob: &[u8]) -> Result<ParsedCertificate, String> {
+    OpenSshCertificate::from_blob(blob).map(ParsedCertificate::from)
+}
+
+/// Decode a certificate from `<type> <base64> [comment]` text, returning the
+/// blob, the parsed certificate and the comment (if any).
+pub fn parse_openssh_certificate(
+    text: &str,
+) -> Result<(Vec<u8>, ParsedCertificate, Option<String>), String> {
+    let (blob, comment) = ssh_certificate::decode_openssh(text)?;
+    let parsed = parse_certificate(&blob)?;
+    Ok((blob, parsed, comment))
+}
+
+impl From<OpenSshCertificate> for ParsedCertificate {
+    fn from(cert: OpenSshCertificate) -> Self {
+        let valid_after = unix_to_datetime(cert.valid_after);
+        let valid_before = unix_to_datetime(cert.valid_before);
+        let now = Utc::now();
+        let ca_fingerprint = cert.ca_fingerprint();
+        Self {
+            key_type: cert.key_type,
+            public_key_blob: cert.public_key_blob,
+            info: CertificateInfo {
+                serial: cert.serial,
+                cert_type: match cert.cert_type {
+                    ssh_certificate::CertificateType::User => CertificateType::User,
+                    ssh_certificate::CertificateType::Host => CertificateType::Host,
+                },
+                key_id: cert.key_id,
+                valid_principals: cert.valid_principals,
+                valid_after,
+                valid_before,
+                critical_options: cert.critical_options.into_iter().collect(),
+                extensions: cert.extensions.into_iter().collect(),
+                ca_fingerprint,
+                is_valid: valid_after <= now && now < valid_before,
+            },
+        }
+    }
+}
+
+/// `valid_before = u64::MAX` means "forever".
+fn unix_to_datetime(secs: u64) -> DateTime<Utc> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_key::certificate::{Builder, CertType};
+    use ssh_key::private::Ed25519Keypair;
+    use ssh_key::PrivateKey;
+
+    #[test]
+    fn maps_user_certificate_into_info() {
+        let ca = PrivateKey::from(Ed25519Keypair::from_seed(&[1; 32]));
+        let subject = PrivateKey::from(Ed25519Keypair::from_seed(&[2; 32]));
+        let now = Utc::now().timestamp() as u64;
+        let mut builder = Builder::new(
+            [3u8; 16],
+            subject.public_key().key_data().clone(),
+            now - 60,
+            now + 3600,
+        )
+        .unwrap();
+        builder.serial(42).unwrap();
+        builder.cert_type(CertType::User).unwrap();
+        builder.key_id("alice@corp").unwrap();
+        builder.valid_principal("alice").unwrap();
+        builder.critical_option("force-command", "uptime").unwrap();
+        builder.extension("permit-pty", "").unwrap();
+        let cert = builder.sign(&ca).unwrap();
+
+        let text = format!("{} laptop", cert.to_openssh().unwrap());
+        let (blob, parsed, comment) = parse_openssh_certificate(&text).unwrap();
+        assert_eq!(blob, cert.to_bytes().unwrap());
+        assert_eq!(comment.as_deref(), Some("laptop"));
+        assert_eq!(
+            parsed.public_key_blob,
+            subject.public_key().to_bytes().unwrap()
+        );
+        let info = parsed.info;
+        assert_eq!(info.cert_type, CertificateType::User);
+        assert_eq!(info.critical_options["force-command"], "uptime");
+        assert_eq!(info.extensions["permit-pty"], "");
+        assert!(info.is_valid);
+    }
+
+    #[test]
+    fn forever_maps_to_max_datetime() {
+        assert_eq!(unix_to_datetime(u64::MAX), DateTime::<Utc>::MAX_UTC);
+    }
+}
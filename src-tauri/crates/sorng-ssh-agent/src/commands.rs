@@ -365,6 +365,17 @@ pub async fn ssh_agent_update_key_constraints(
     svc.update_key_constraints(&key_id, constraints)
 }
 
+/// Attach an OpenSSH certificate to a loaded private key.
+#[tauri::command]
+pub async fn ssh_agent_add_certificate(
+    state: State<'_, SshAgentServiceState>,
+    key_id: String,
+    certificate: String,
+) -> CmdResult<String> {
+    let mut svc = state.lock().await;
+    svc.add_certificate(&key_id, &certificate)
+}
+
 /// Export a public key in the requested format ("openssh" or "pem").
 #[tauri::command]
 pub async fn ssh_agent_export_public_key(
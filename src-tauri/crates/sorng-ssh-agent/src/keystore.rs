@@ -183,6 +183,33 @@ impl KeyStore {
         count
     }
 
+    /// Remove every key matching `pred`, returning the removed keys.
+    ///
+    /// Unlike the other removal methods this also works while the agent is
+    /// locked: it is used to withdraw keys whose source (e.g. a credential
+    /// store database) has been locked or closed, which must never be
+    /// blocked by the agent's own lock state.
+    pub fn remove_keys_where(&mut self, pred: impl Fn(&AgentKey) -> bool) -> Vec<AgentKey> {
+        let ids: Vec<String> = self
+            .keys
+            .values()
+            .filter(|key| pred(key))
+            .map(|key| key.id.clone())
+            .collect();
+        let mut removed = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(key) = self.keys.remove(&id) {
+                self.blob_index.remove(&key.public_key_blob);
+                self.private_keys.remove(&key.public_key_blob);
+                removed.push(key);
+            }
+        }
+        if !removed.is_empty() {
+            info!("Removed {} keys", removed.len());
+        }
+        removed
+    }
+
     /// Find a key by its public key blob.
     pub fn find_by_blob(&self, blob: &[u8]) -> Option<&AgentKey> {
         if self.locked {
@@ -408,6 +435,10 @@ fn validate_key_source(source: &KeySource) -> Result<(), String> {
         KeySource::Pkcs11 { provider, .. } => Some(provider.as_str()),
         KeySource::SecurityKey { device } => Some(device.as_str()),
         KeySource::Forwarded { session_id } => Some(session_id.as_str()),
+        KeySource::CredentialStore { owner, item_id } => {
+            validate_text_field("Key source field", item_id, MAX_SOURCE_FIELD_LEN)?;
+            Some(owner.as_str())
+        }
         KeySource::Generated | KeySource::SystemAgent | KeySource::Imported => None,
     };
     if let Some(value) = value {
@@ -22,6 +22,9 @@
 //! - **PKCS#11 / Security Keys** — Load keys from PKCS#11 tokens and FIDO2
 //!   security keys (SK-ED25519, SK-ECDSA)
 //! - **Session Binding** — Bind keys to specific sessions for isolation
+//! - **Credential-Store Keys** — Keys published by unlocked KeePass databases
+//!   are loaded with their confirm/lifetime constraints, withdrawn when the
+//!   database locks, and offered to the in-process SSH client
 //! - **Audit Trail** — Full event log of all agent operations (sign, add,
 //!   remove, lock, forward) with timestamps and metadata
 //! - **Configurable Socket** — Custom socket path, Windows named pipe, or
@@ -54,8 +57,10 @@
 pub mod agent;
 pub mod audit;
 pub mod bridge;
+pub mod certificate;
 pub mod constraints;
 pub mod forwarding;
+pub mod identities;
 pub mod keystore;
 pub mod protocol;
 pub mod service;
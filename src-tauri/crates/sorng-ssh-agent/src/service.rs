@@ -9,8 +9,9 @@ use crate::audit::AuditLogger;
 use crate::bridge::SystemAgentBridge;
 use crate::forwarding::ForwardingManager;
 use crate::types::*;
-use log::info;
+use log::{info, warn};
 use sha2::Digest;
+use sorng_core::ssh_identities::SshKeyMaterial;
 use tokio::sync::broadcast;
 
 const MAX_CONFIG_KEYS: usize = 1024;
@@ -270,7 +271,9 @@ impl SshAgentService {
                         public_key_openssh: String::new(),
                         source: KeySource::SystemAgent,
                         constraints: Vec::new(),
-                        certificate: None,
+                        certificate: crate::certificate::parse_certificate(&id.key_blob)
+                            .ok()
+                            .map(|cert| cert.info),
                         added_at: chrono::Utc::now(),
                         last_used_at: None,
                         sign_count: 0,
@@ -293,6 +296,16 @@ impl SshAgentService {
         result
     }
 
+    /// Attach an OpenSSH certificate (`*-cert.pub` contents) to a loaded
+    /// private key. Returns the ID of the new certificate identity.
+    pub fn add_certificate(&mut self, key_id: &str, certificate: &str) -> Result<String, String> {
+        let result = self.agent.add_certificate(key_id, certificate);
+        if result.is_ok() {
+            self.status.loaded_keys = self.agent.store.key_count() as u32;
+        }
+        result
+    }
+
     /// Remove a key by ID.
     pub fn remove_key(&mut self, id: &str) -> Result<(), String> {
         self.agent.store.remove_key(id)?;
@@ -325,6 +338,78 @@ impl SshAgentService {
         Ok(())
     }
 
+    // ── Credential-Store Keys ───────────────────────────────────────
+
+    /// Replace the keys published by a credential store (`owner`, e.g.
+    /// `keepass:<db id>`) with `keys`. Keys that fail to load are logged and
+    /// skipped. Returns the number of keys loaded.
+    pub fn load_store_keys(&mut self, owner: &str, keys: Vec<SshKeyMaterial>) -> usize {
+        self.remove_store_keys(owner);
+        let mut loaded = 0;
+        for material in keys {
+            let mut constraints = Vec::new();
+            if material.confirm_before_use {
+                constraints.push(KeyConstraint::ConfirmBeforeUse);
+            }
+            if let Some(secs) = material.lifetime_secs.filter(|secs| *secs > 0) {
+                constraints.push(KeyConstraint::Lifetime(secs));
+            }
+            let source = KeySource::CredentialStore {
+                owner: owner.to_string(),
+                item_id: material.id.clone(),
+            };
+            match self.agent.add_private_key(
+                &material.private_key_openssh,
+                material.passphrase.as_deref(),
+                &material.comment,
+                source,
+                constraints,
+            ) {
+                Ok(key_id) => {
+                    if let Some(key) = self.agent.store.find_by_id(&key_id) {
+                        self.audit.log_event(&AgentEvent::KeyAdded {
+                            key_id: key_id.clone(),
+                            fingerprint: key.fingerprint_sha256.clone(),
+                        });
+                    }
+                    loaded += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        "Cannot load key '{}' from {}: {}",
+                        material.comment, owner, e
+                    );
+                    self.audit.log_custom(
+                        "store_key_rejected",
+                        None,
+                        false,
+                        &format!("Key '{}' from {} was rejected", material.comment, owner),
+                    );
+                }
+            }
+        }
+        self.status.loaded_keys = self.agent.store.key_count() as u32;
+        loaded
+    }
+
+    /// Remove every key published by `owner`, even while the agent is
+    /// locked. Returns the number of keys removed.
+    pub fn remove_store_keys(&mut self, owner: &str) -> usize {
+        let removed = self.agent.store.remove_keys_where(
+            |key| matches!(&key.source, KeySource::CredentialStore { owner: o, .. } if o == owner),
+        );
+        for key in &removed {
+            let event = AgentEvent::KeyRemoved {
+                key_id: key.id.clone(),
+                fingerprint: key.fingerprint_sha256.clone(),
+            };
+            let _ = self.event_tx.send(event.clone());
+            self.audit.log_event(&event);
+        }
+        self.status.loaded_keys = self.agent.store.key_count() as u32;
+        removed.len()
+    }
+
     // ── Forwarding ──────────────────────────────────────────────────
 
     /// Start a forwarding session.
@@ -525,7 +610,7 @@ impl SshAgentService {
         match &result {
             Ok(()) => self.audit.log_event(&AgentEvent::ConfirmationResponse {
                 request_id: request_id.to_string(),
-                approved: false,
+                approved,
             }),
             Err(_) => self.audit.log_custom(
                 "confirmation_response_rejected",
@@ -568,7 +653,11 @@ impl SshAgentService {
             "openssh" if !key.public_key_openssh.is_empty() => Ok(key.public_key_openssh),
             "openssh" => Ok(format!(
                 "{} {} {}",
-                key.algorithm.ssh_name(),
+                crate::protocol::read_utf8_string(&key.public_key_blob, 0)
+                    .ok()
+                    .filter(|_| key.certificate.is_some())
+                    .map(|(name, _)| name)
+                    .unwrap_or_else(|| key.algorithm.ssh_name().to_string()),
                 base64::Engine::encode(
                     &base64::engine::general_purpose::STANDARD,
                     key.public_key_blob.as_slice(),
@@ -88,6 +88,10 @@ impl KeyAlgorithm {
             "sk-ssh-ed25519@openssh.com" => Some(Self::SkEd25519),
             "sk-ecdsa-sha2-nistp256@openssh.com" => Some(Self::SkEcdsaP256),
             "ssh-dss" => Some(Self::Dsa),
+            // Certificates sign with the certified key's algorithm.
+            cert if crate::certificate::is_certificate_type(cert) => {
+                crate::certificate::base_key_type(cert).and_then(Self::try_from_ssh_name)
+            }
             _ => None,
         }
     }
@@ -164,6 +168,9 @@ pub enum KeySource {
     Imported,
     /// Received through agent forwarding.
     Forwarded { session_id: String },
+    /// Published by a credential store (e.g. a KeePass entry). `owner`
+    /// identifies the store instance, `item_id` the entry within it.
+    CredentialStore { owner: String, item_id: String },
 }
 
 // ── SSH Certificates ────────────────────────────────────────────────
@@ -287,6 +287,7 @@ impl ScriptService {
                                                                     password: password.map(SecretString::new),
                                                                     private_key_path: None,
                                                                     private_key_passphrase: None,
+                                                                    certificate_path: None,
                                                                     jump_hosts: vec![],
                                                                     proxy_config: None,
                                                                     proxy_chain: None,
@@ -0,0 +1,789 @@
+//! # OpenSSH certificates
+//!
+//! Support for `*-cert-v01@openssh.com` keys (see OpenSSH `PROTOCOL.certkeys`),
+//! decoded by [`sorng_core::ssh_certificate`]:
+//!
+//! - **User certificates** — an explicit `certificate_path` or the
+//!   conventional `<private key>-cert.pub` is pre-checked (type, validity
+//!   window, principals) and offered together with the private key. Certs
+//!   held by an SSH agent are used automatically by agent authentication.
+//! - **Host certificates** — libssh2 never negotiates certificate host keys,
+//!   so when a host is not in `known_hosts` but a `@cert-authority` line
+//!   covers it, the server's certificate for its plain key is fetched over a
+//!   separate probe connection and validated (CA signature, host principal,
+//!   validity window, critical options) instead of prompting for
+//!   trust-on-first-use. The probe needs a direct TCP route; behind proxies,
+//!   jump hosts or a ProxyCommand the plain-key prompt is used and a warning
+//!   logged. `@revoked` keys are always refused.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sorng_core::ssh_certificate::{
+    is_certificate_key_type, CertificateType, OpenSshCertificate, CERT_KEY_TYPE_SUFFIX,
+};
+
+use super::ssh_config::wildcard_match;
+
+const MARKER_CERT_AUTHORITY: &str = "@cert-authority";
+const MARKER_REVOKED: &str = "@revoked";
+
+// ── User certificates ─────────────────────────────────────────────────
+
+/// Pick the certificate to offer alongside `private_key_path`.
+///
+/// Uses `explicit` when set, otherwise `<private_key_path>-cert.pub` if it
+/// exists. A certificate that is clearly unusable (host cert, outside its
+/// validity window, or not valid for `username`) is skipped with a warning so
+/// authentication falls back to the plain key, as OpenSSH does. Certificates
+/// we cannot decode are still offered and left for the server to judge.
+pub fn user_certificate_for_key(
+    explicit: Option<&str>,
+    private_key_path: &str,
+    username: &str,
+) -> Option<PathBuf> {
+    let path = match explicit {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let candidate = PathBuf::from(format!("{}-cert.pub", private_key_path));
+            if !candidate.is_file() {
+                return None;
+            }
+            candidate
+        }
+    };
+
+    let cert = match read_certificate(&path) {
+        Ok(cert) => cert,
+        Err(e) => {
+            log::debug!("Offering certificate {} unchecked: {}", path.display(), e);
+            return Some(path);
+        }
+    };
+    match check_user_certificate(&cert, username, unix_now()) {
+        Ok(()) => Some(path),
+        Err(reason) => {
+            log::warn!(
+                "Not offering certificate {} (key id '{}'): {}",
+                path.display(),
+                cert.key_id,
+                reason
+            );
+            None
+        }
+    }
+}
+
+fn read_certificate(path: &Path) -> Result<OpenSshCertificate, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read certificate {}: {}", path.display(), e))?;
+    OpenSshCertificate::from_openssh(&text)
+}
+
+fn check_user_certificate(
+    cert: &OpenSshCertificate,
+    username: &str,
+    now: u64,
+) -> Result<(), String> {
+    if cert.cert_type != CertificateType::User {
+        return Err("not a user certificate".to_string());
+    }
+    if !cert.is_valid_at(now) {
+        return Err("outside its validity window".to_string());
+    }
+    if !cert.allows_principal(username) {
+        return Err(format!("'{}' is not a listed principal", username));
+    }
+    Ok(())
+}
+
+// ── Host keys ─────────────────────────────────────────────────────────
+
+/// A `@cert-authority` line from known_hosts.
+#[derive(Debug, Clone)]
+pub struct CertAuthority {
+    pub host_patterns: Vec<String>,
+    pub key_blob: Vec<u8>,
+}
+
+/// The marker lines of a known_hosts file, which libssh2 cannot parse.
+#[derive(Debug, Clone, Default)]
+pub struct KnownHostsMarkers {
+    pub cert_authorities: Vec<CertAuthority>,
+    /// Key blobs listed under `@revoked`.
+    pub revoked: Vec<Vec<u8>>,
+}
+
+/// A host certificate accepted on the strength of a `@cert-authority` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustedHostCertificate {
+    pub ca_fingerprint: String,
+    pub key_id: String,
+}
+
+impl KnownHostsMarkers {
+    pub fn parse(content: &str) -> Self {
+        let mut markers = Self::default();
+        for line in content.lines().filter(|line| is_marker_line(line)) {
+            let mut fields = line.split_whitespace();
+            let (Some(marker), Some(hosts), Some(_key_type), Some(encoded)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(key_blob) =
+                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+            else {
+                continue;
+            };
+            if marker.eq_ignore_ascii_case(MARKER_CERT_AUTHORITY) {
+                markers.cert_authorities.push(CertAuthority {
+                    host_patterns: hosts.split(',').map(str::to_string).collect(),
+                    key_blob,
+                });
+            } else if marker.eq_ignore_ascii_case(MARKER_REVOKED) {
+                markers.revoked.push(key_blob);
+            }
+        }
+        markers
+    }
+
+    /// Refuse `host_key` if it is listed under `@revoked`.
+    pub fn check_not_revoked(&self, host_key: &[u8], host: &str) -> Result<(), String> {
+        if self.is_revoked(host_key) {
+            return Err(format!(
+                "Host key verification failed for {}: host key {} is marked @revoked",
+                host,
+                blob_fingerprint(host_key)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether any `@cert-authority` line covers `host:port`.
+    pub fn has_authority_for(&self, host: &str, port: u16) -> bool {
+        let name = known_hosts_name(host, port);
+        self.cert_authorities
+            .iter()
+            .any(|ca| host_patterns_match(&ca.host_patterns, &name))
+    }
+
+    /// Validate the host certificate `cert_blob` the server presented for
+    /// `host_key`, the plain key the SSH session itself verified.
+    ///
+    /// Returns `Ok(None)` when no `@cert-authority` for `host:port` signed it,
+    /// so the caller falls back to the plain key as OpenSSH does. Any other
+    /// failure (signature, validity window, principal, critical options,
+    /// revocation) is a hard error.
+    pub fn verify_host_certificate(
+        &self,
+        cert_blob: &[u8],
+        host_key: &[u8],
+        host: &str,
+        port: u16,
+        now: u64,
+    ) -> Result<Option<TrustedHostCertificate>, String> {
+        let fail = |reason: String| format!("Host certificate for {} rejected: {}", host, reason);
+        let cert = OpenSshCertificate::from_blob(cert_blob).map_err(fail)?;
+        if cert.public_key_blob != host_key {
+            return Err(fail(
+                "it certifies a different key than the server's host key".to_string(),
+            ));
+        }
+
+        let name = known_hosts_name(host, port);
+        let trusted = self.cert_authorities.iter().any(|ca| {
+            ca.key_blob == cert.signature_key && host_patterns_match(&ca.host_patterns, &name)
+        });
+        if !trusted {
+            log::info!(
+                "Host certificate for {} is signed by {}, which is not a @cert-authority for {}",
+                host,
+                cert.ca_fingerprint(),
+                name
+            );
+            return Ok(None);
+        }
+
+        if self.is_revoked(&cert.signature_key) {
+            return Err(fail("its CA is marked @revoked".to_string()));
+        }
+        if cert.cert_type != CertificateType::Host {
+            return Err(fail("not a host certificate".to_string()));
+        }
+        verify_signature(&cert).map_err(fail)?;
+        if !cert.is_valid_at(now) {
+            return Err(fail("outside its validity window".to_string()));
+        }
+        if !cert.allows_principal(host) {
+            return Err(fail(format!("'{}' is not a listed principal", host)));
+        }
+        // No critical options are defined for host certificates.
+        if let Some((option, _)) = cert.critical_options.first() {
+            return Err(fail(format!("unsupported critical option '{}'", option)));
+        }
+
+        Ok(Some(TrustedHostCertificate {
+            ca_fingerprint: cert.ca_fingerprint(),
+            key_id: cert.key_id,
+        }))
+    }
+
+    fn is_revoked(&self, blob: &[u8]) -> bool {
+        self.revoked.iter().any(|revoked| revoked == blob)
+    }
+}
+
+/// `@cert-authority` / `@revoked` lines are handled here; everything else is
+/// left to libssh2's known_hosts parser.
+pub fn is_marker_line(line: &str) -> bool {
+    line.trim_start().starts_with('@')
+}
+
+fn verify_signature(cert: &OpenSshCertificate) -> Result<(), String> {
+    use signature::Verifier;
+
+    let ca = ssh_key::PublicKey::from_bytes(&cert.signature_key)
+        .map_err(|e| format!("unsupported CA key: {}", e))?;
+    let signature = ssh_key::Signature::try_from(cert.signature.as_slice())
+        .map_err(|e| format!("malformed CA signature: {}", e))?;
+    ca.key_data()
+        .verify(&cert.signed_data, &signature)
+        .map_err(|_| "CA signature does not verify".to_string())
+}
+
+/// The name a host is recorded under in known_hosts.
+fn known_hosts_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+/// known_hosts pattern-list semantics: a negated match vetoes, otherwise any
+/// positive match wins. Hashed (`|1|`) patterns are not supported on marker
+/// lines and never match.
+fn host_patterns_match(patterns: &[String], name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let mut matched = false;
+    for pattern in patterns {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern.as_str()),
+        };
+        if pattern.starts_with('|') {
+            continue;
+        }
+        if wildcard_match(&pattern.to_ascii_lowercase(), &name) {
+            if negated {
+                return false;
+            }
+            matched = true;
+        }
+    }
+    matched
+}
+
+// ── Host certificate probe ────────────────────────────────────────────
+
+const MSG_DISCONNECT: u8 = 1;
+const MSG_KEXINIT: u8 = 20;
+const MSG_KEX_ECDH_INIT: u8 = 30;
+const MSG_KEX_ECDH_REPLY: u8 = 31;
+const PROBE_KEX: &str = "curve25519-sha256,curve25519-sha256@libssh.org";
+const PROBE_CIPHERS: &str = "chacha20-poly1305@openssh.com,aes128-ctr,aes256-ctr,\
+                             aes128-gcm@openssh.com,aes256-gcm@openssh.com";
+const PROBE_MACS: &str = "hmac-sha2-256-etm@openssh.com,hmac-sha2-512-etm@openssh.com,\
+                          hmac-sha2-256,hmac-sha2-512";
+const MAX_PROBE_PACKET: usize = 256 * 1024;
+
+/// Fetch the host certificate `host:port` holds for `host_key` over a fresh
+/// TCP connection. See [`fetch_host_certificate`].
+pub fn probe_host_certificate(
+    host: &str,
+    port: u16,
+    host_key: &[u8],
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let addresses = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+    let mut last_error = format!("{} did not resolve to any address", host);
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(mut stream) => {
+                stream
+                    .set_read_timeout(Some(timeout))
+                    .and_then(|()| stream.set_write_timeout(Some(timeout)))
+                    .map_err(|e| format!("Failed to set probe timeouts: {}", e))?;
+                return fetch_host_certificate(&mut stream, host_key);
+            }
+            Err(e) => last_error = format!("Failed to connect to {}: {}", address, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// Ask an SSH server for its certificate for `host_key`, the way
+/// `ssh-keyscan -c` does.
+///
+/// libssh2 never negotiates `*-cert-v01@openssh.com` host keys, so the
+/// session itself only ever sees the plain key. This runs the start of a
+/// second key exchange offering just the certificate algorithms for that key
+/// type and returns `K_S` from the server's ECDH reply. The exchange is
+/// abandoned there, so the server's signature is not checked: the blob only
+/// counts once it certifies the plain key the real session verified.
+pub fn fetch_host_certificate<S: Read + Write>(
+    stream: &mut S,
+    host_key: &[u8],
+) -> Result<Vec<u8>, String> {
+    let host_key_algorithms = certificate_algorithms(host_key)?;
+
+    stream
+        .write_all(b"SSH-2.0-sorng_certprobe\r\n")
+        .map_err(|e| format!("Failed to send probe banner: {}", e))?;
+    read_server_banner(stream)?;
+
+    let mut kexinit = vec![MSG_KEXINIT];
+    kexinit.extend(rand::random::<[u8; 16]>());
+    for list in [
+        PROBE_KEX,
+        host_key_algorithms.as_str(),
+        PROBE_CIPHERS,
+        PROBE_CIPHERS,
+        PROBE_MACS,
+        PROBE_MACS,
+        "none",
+        "none",
+        "",
+        "",
+    ] {
+        put_string(&mut kexinit, list.as_bytes());
+    }
+    kexinit.push(0); // first_kex_packet_follows
+    kexinit.extend(0u32.to_be_bytes());
+    write_packet(stream, &kexinit)?;
+    read_packet_of_type(stream, MSG_KEXINIT)?;
+
+    let mut ecdh_init = vec![MSG_KEX_ECDH_INIT];
+    put_string(&mut ecdh_init, &rand::random::<[u8; 32]>());
+    write_packet(stream, &ecdh_init)?;
+    let reply = read_packet_of_type(stream, MSG_KEX_ECDH_REPLY)?;
+    let cert_blob = read_string(&reply[1..])
+        .ok_or("Malformed key exchange reply")?
+        .to_vec();
+    let key_type = read_string(&cert_blob).unwrap_or_default();
+    if !is_certificate_key_type(&String::from_utf8_lossy(key_type)) {
+        return Err("Server answered with a plain host key".to_string());
+    }
+    Ok(cert_blob)
+}
+
+/// The certificate host-key algorithms for the plain key `host_key`.
+fn certificate_algorithms(host_key: &[u8]) -> Result<String, String> {
+    let key_type = read_string(host_key)
+        .and_then(|t| std::str::from_utf8(t).ok())
+        .ok_or("Malformed host key")?;
+    Ok(match key_type {
+        "ssh-rsa" => "rsa-sha2-512-cert-v01@openssh.com,rsa-sha2-256-cert-v01@openssh.com,\
+                      ssh-rsa-cert-v01@openssh.com"
+            .to_string(),
+        other => format!("{}{}", other, CERT_KEY_TYPE_SUFFIX),
+    })
+}
+
+fn read_server_banner<S: Read>(stream: &mut S) -> Result<(), String> {
+    // Servers may send other lines before the identification string.
+    for _ in 0..32 {
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        while line.len() < 255 {
+            stream
+                .read_exact(&mut byte)
+                .map_err(|e| format!("Failed to read server banner: {}", e))?;
+            if byte[0] == b'\n' {
+                break;
+            }
+            line.push(byte[0]);
+        }
+        if line.starts_with(b"SSH-") {
+            return Ok(());
+        }
+    }
+    Err("Server sent no SSH identification string".to_string())
+}
+
+fn write_packet<S: Write>(stream: &mut S, payload: &[u8]) -> Result<(), String> {
+    // Unencrypted binary packet: length, padding length, payload, padding,
+    // with the whole packet a multiple of 8 and at least 4 padding bytes.
+    let mut padding = 8 - (5 + payload.len()) % 8;
+    if padding < 4 {
+        padding += 8;
+    }
+    let mut packet = Vec::with_capacity(5 + payload.len() + padding);
+    packet.extend(((1 + payload.len() + padding) as u32).to_be_bytes());
+    packet.push(padding as u8);
+    packet.extend_from_slice(payload);
+    packet.resize(packet.len() + padding, 0);
+    stream
+        .write_all(&packet)
+        .map_err(|e| format!("Failed to send probe packet: {}", e))
+}
+
+fn read_packet<S: Read>(stream: &mut S) -> Result<Vec<u8>, String> {
+    let read_error = |e: std::io::Error| format!("Failed to read probe packet: {}", e);
+    let mut header = [0u8; 5];
+    stream.read_exact(&mut header).map_err(read_error)?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let padding = header[4] as usize;
+    if length > MAX_PROBE_PACKET || padding + 1 > length {
+        return Err("Malformed probe packet".to_string());
+    }
+    let mut body = vec![0u8; length - 1];
+    stream.read_exact(&mut body).map_err(read_error)?;
+    body.truncate(length - 1 - padding);
+    if body.is_empty() {
+        return Err("Empty probe packet".to_string());
+    }
+    Ok(body)
+}
+
+/// Read packets until one of `message` type, skipping ignore/debug messages.
+fn read_packet_of_type<S: Read>(stream: &mut S, message: u8) -> Result<Vec<u8>, String> {
+    loop {
+        let packet = read_packet(stream)?;
+        match packet[0] {
+            found if found == message => return Ok(packet),
+            MSG_DISCONNECT => {
+                let reason = packet
+                    .get(5..)
+                    .and_then(read_string)
+                    .map(String::from_utf8_lossy)
+                    .unwrap_or_default();
+                return Err(format!(
+                    "Server offers no certificate host key ({})",
+                    reason
+                ));
+            }
+            MSG_KEXINIT..=u8::MAX => {
+                return Err(format!("Unexpected message {} during probe", packet[0]));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn put_string(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+fn read_string(data: &[u8]) -> Option<&[u8]> {
+    let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    data.get(4..4usize.checked_add(len)?)
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────
+
+fn blob_fingerprint(blob: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!(
+        "SHA256:{}",
+        base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD_NO_PAD,
+            Sha256::digest(blob),
+        )
+    )
+}
+
+pub(crate) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_key::certificate::{Builder, CertType};
+    use ssh_key::private::Ed25519Keypair;
+    use ssh_key::PrivateKey;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn key(seed: u8) -> PrivateKey {
+        PrivateKey::from(Ed25519Keypair::from_seed(&[seed; 32]))
+    }
+
+    fn user_cert(principals: &[&str]) -> OpenSshCertificate {
+        let mut builder = Builder::new(
+            [9u8; 16],
+            key(2).public_key().key_data().clone(),
+            NOW - 60,
+            NOW + 60,
+        )
+        .unwrap();
+        builder.cert_type(CertType::User).unwrap();
+        if principals.is_empty() {
+            builder.all_principals_valid().unwrap();
+        }
+        for principal in principals {
+            builder.valid_principal(*principal).unwrap();
+        }
+        let cert = builder.sign(&key(1)).unwrap();
+        OpenSshCertificate::from_openssh(&cert.to_openssh().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn user_certificate_checks() {
+        let cert = user_cert(&["alice"]);
+        assert!(check_user_certificate(&cert, "alice", NOW).is_ok());
+        assert!(check_user_certificate(&cert, "bob", NOW).is_err());
+        assert!(check_user_certificate(&cert, "alice", NOW + 120).is_err());
+    }
+
+    #[test]
+    fn user_certificate_without_principals_is_valid_for_anyone() {
+        let cert = user_cert(&[]);
+        assert!(check_user_certificate(&cert, "alice", NOW).is_ok());
+        assert!(check_user_certificate(&cert, "root", NOW).is_ok());
+    }
+
+    #[test]
+    fn user_certificate_is_found_next_to_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("id_ed25519");
+        let key_path = key_path.to_str().unwrap();
+        assert_eq!(user_certificate_for_key(None, key_path, "alice"), None);
+
+        std::fs::write(format!("{}-cert.pub", key_path), "not a certificate").unwrap();
+        assert_eq!(
+            user_certificate_for_key(None, key_path, "alice"),
+            Some(PathBuf::from(format!("{}-cert.pub", key_path))),
+            "undecodable certificates are still offered"
+        );
+    }
+
+    fn host_cert(subject: &PrivateKey, principals: &[&str], ca: &PrivateKey) -> Vec<u8> {
+        let mut builder = Builder::new(
+            [7u8; 16],
+            subject.public_key().key_data().clone(),
+            NOW - 60,
+            NOW + 3600,
+        )
+        .unwrap();
+        builder.cert_type(CertType::Host).unwrap();
+        builder.key_id("web-host").unwrap();
+        if principals.is_empty() {
+            builder.all_principals_valid().unwrap();
+        }
+        for principal in principals {
+            builder.valid_principal(*principal).unwrap();
+        }
+        builder.sign(ca).unwrap().to_bytes().unwrap()
+    }
+
+    fn markers_for(ca: &PrivateKey, patterns: &str) -> KnownHostsMarkers {
+        KnownHostsMarkers::parse(&format!(
+            "# comment\nplain.example.com ssh-ed25519 AAAA\n@cert-authority {} {}\n",
+            patterns,
+            ca.public_key().to_openssh().unwrap()
+        ))
+    }
+
+    fn plain(key: &PrivateKey) -> Vec<u8> {
+        key.public_key().to_bytes().unwrap()
+    }
+
+    #[test]
+    fn trusted_host_certificate_is_accepted() {
+        let (ca, subject) = (key(1), key(2));
+        let markers = markers_for(&ca, "*.example.com,!bad.example.com");
+        assert!(markers.has_authority_for("web.example.com", 22));
+        assert!(!markers.has_authority_for("bad.example.com", 22));
+        assert!(!markers.has_authority_for("web.example.com", 2222));
+
+        let cert = host_cert(&subject, &["web.example.com"], &ca);
+        let trusted = markers
+            .verify_host_certificate(&cert, &plain(&subject), "web.example.com", 22, NOW)
+            .unwrap()
+            .expect("trusted");
+        assert_eq!(trusted.key_id, "web-host");
+        assert_eq!(
+            trusted.ca_fingerprint,
+            ca.public_key()
+                .fingerprint(ssh_key::HashAlg::Sha256)
+                .to_string()
+        );
+
+        let any_host = host_cert(&subject, &[], &ca);
+        assert!(markers
+            .verify_host_certificate(&any_host, &plain(&subject), "web.example.com", 22, NOW)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn untrusted_ca_falls_back_to_the_plain_key() {
+        let (ca, subject) = (key(1), key(2));
+        let cert = host_cert(&subject, &["web.example.com"], &ca);
+        let host_key = plain(&subject);
+
+        let other_ca = markers_for(&key(3), "*");
+        assert_eq!(
+            other_ca.verify_host_certificate(&cert, &host_key, "web.example.com", 22, NOW),
+            Ok(None)
+        );
+        let wrong_port = markers_for(&ca, "web.example.com");
+        assert_eq!(
+            wrong_port.verify_host_certificate(&cert, &host_key, "web.example.com", 2222, NOW),
+            Ok(None),
+            "[host]:port must match the CA pattern"
+        );
+    }
+
+    #[test]
+    fn host_certificate_failures_are_hard_errors() {
+        let (ca, subject) = (key(1), key(2));
+        let markers = markers_for(&ca, "*.example.com");
+        let cert = host_cert(&subject, &["web.example.com"], &ca);
+        let host_key = plain(&subject);
+        let check = |markers: &KnownHostsMarkers, cert: &[u8], host_key: &[u8], now| {
+            markers
+                .verify_host_certificate(cert, host_key, "web.example.com", 22, now)
+                .unwrap_err()
+        };
+
+        assert!(check(&markers, &cert, &plain(&key(4)), NOW).contains("different key"));
+        assert!(check(&markers, &cert, &host_key, NOW + 7200).contains("validity window"));
+        let other_host = host_cert(&subject, &["db.example.com"], &ca);
+        assert!(check(&markers, &other_host, &host_key, NOW).contains("principal"));
+
+        let mut tampered = cert.clone();
+        let key_id = tampered.windows(8).position(|w| w == b"web-host").unwrap();
+        tampered[key_id] = b'x';
+        assert!(check(&markers, &tampered, &host_key, NOW).contains("signature"));
+
+        let mut revoked = markers.clone();
+        revoked.revoked.push(plain(&ca));
+        assert!(check(&revoked, &cert, &host_key, NOW).contains("@revoked"));
+
+        let mut user = Builder::new(
+            [7u8; 16],
+            subject.public_key().key_data().clone(),
+            NOW - 60,
+            NOW + 3600,
+        )
+        .unwrap();
+        user.cert_type(CertType::User).unwrap();
+        user.valid_principal("web.example.com").unwrap();
+        let user = user.sign(&ca).unwrap().to_bytes().unwrap();
+        assert!(check(&markers, &user, &host_key, NOW).contains("not a host certificate"));
+    }
+
+    #[test]
+    fn revoked_host_keys_are_refused() {
+        let revoked = key(2).public_key().clone();
+        let markers = KnownHostsMarkers::parse(&format!(
+            "# comment\nplain.example.com ssh-ed25519 AAAA\n\
+             @cert-authority * {}\n@revoked * {}\n",
+            key(1).public_key().to_openssh().unwrap(),
+            revoked.to_openssh().unwrap()
+        ));
+        assert_eq!(markers.cert_authorities.len(), 1);
+        assert_eq!(markers.revoked.len(), 1);
+
+        let err = markers
+            .check_not_revoked(&revoked.to_bytes().unwrap(), "h")
+            .unwrap_err();
+        assert!(err.contains("@revoked"), "{}", err);
+        let unrelated = key(4).public_key().to_bytes().unwrap();
+        assert!(markers.check_not_revoked(&unrelated, "h").is_ok());
+    }
+
+    /// Plays the server side of the probe: banner, KEXINIT, then an ECDH
+    /// reply carrying `host_key_blob`.
+    fn fake_server(host_key_blob: Vec<u8>) -> (u16, std::thread::JoinHandle<String>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .write_all(b"pre-banner notice\r\nSSH-2.0-Fake\r\n")
+                .unwrap();
+            read_server_banner(&mut stream).unwrap();
+            let client_kexinit = read_packet(&mut stream).unwrap();
+            // Skip the type byte and cookie, then the KEX list.
+            let kex_len = read_string(&client_kexinit[17..]).unwrap().len();
+            let offered = read_string(&client_kexinit[21 + kex_len..]).unwrap();
+            let offered = String::from_utf8(offered.to_vec()).unwrap();
+
+            let mut kexinit = vec![MSG_KEXINIT];
+            kexinit.extend([0u8; 16]);
+            write_packet(&mut stream, &kexinit).unwrap();
+            assert_eq!(read_packet(&mut stream).unwrap()[0], MSG_KEX_ECDH_INIT);
+            let mut reply = vec![MSG_KEX_ECDH_REPLY];
+            put_string(&mut reply, &host_key_blob);
+            put_string(&mut reply, &[9u8; 32]);
+            put_string(&mut reply, b"signature");
+            write_packet(&mut stream, &[2, 0, 0, 0, 0]).unwrap(); // SSH_MSG_IGNORE
+            write_packet(&mut stream, &reply).unwrap();
+            offered
+        });
+        (port, server)
+    }
+
+    #[test]
+    fn probe_fetches_the_host_certificate() {
+        let (ca, subject) = (key(1), key(2));
+        let cert = host_cert(&subject, &["localhost"], &ca);
+        let (port, server) = fake_server(cert.clone());
+
+        let fetched =
+            probe_host_certificate("127.0.0.1", port, &plain(&subject), Duration::from_secs(5))
+                .unwrap();
+        assert_eq!(fetched, cert);
+        assert_eq!(
+            server.join().unwrap(),
+            "ssh-ed25519-cert-v01@openssh.com",
+            "only the certificate algorithm for the plain key is offered"
+        );
+    }
+
+    #[test]
+    fn probe_rejects_a_plain_key_reply() {
+        let subject = key(2);
+        let (port, server) = fake_server(plain(&subject));
+        let err =
+            probe_host_certificate("127.0.0.1", port, &plain(&subject), Duration::from_secs(5))
+                .unwrap_err();
+        assert!(err.contains("plain host key"), "{}", err);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn packets_are_padded_to_the_block_size() {
+        for len in 0..20 {
+            let mut wire = Vec::new();
+            write_packet(&mut wire, &vec![MSG_KEXINIT; len + 1]).unwrap();
+            assert_eq!(wire.len() % 8, 0);
+            assert!(wire[4] >= 4);
+            assert_eq!(
+                read_packet(&mut wire.as_slice()).unwrap(),
+                vec![MSG_KEXINIT; len + 1]
+            );
+        }
+    }
+
+    #[test]
+    fn marker_lines_are_recognised() {
+        assert!(is_marker_line("@cert-authority * ssh-ed25519 AAAA"));
+        assert!(is_marker_line("  @revoked * ssh-ed25519 AAAA"));
+        assert!(!is_marker_line("host ssh-ed25519 AAAA"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Public-key authentication with an in-process signer.
+//!
+//! `userauth_pubkey_memory` signs up front, so anything gating the signature
+//! (a confirm-before-use prompt) would run for keys the server is about to
+//! refuse. `libssh2_userauth_publickey` instead offers the public key on its
+//! own and calls back for a signature only after the server answers
+//! `SSH_MSG_USERAUTH_PK_OK`, which is when ssh-agent asks for confirmation.
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+use rsa::pkcs1v15;
+use sha2::Sha256;
+use signature::{SignatureEncoding, Signer};
+use ssh2::Session;
+use ssh_key::private::KeypairData;
+use ssh_key::PrivateKey;
+
+/// `LIBSSH2_ERROR_PUBLICKEY_UNVERIFIED`: the callback produced no signature.
+const SIGN_FAILED: c_int = -19;
+
+type SignCallback = unsafe extern "C" fn(
+    session: *mut c_void,
+    sig: *mut *mut c_uchar,
+    sig_len: *mut usize,
+    data: *const c_uchar,
+    data_len: usize,
+    abstract_: *mut *mut c_void,
+) -> c_int;
+
+// Part of libssh2's public API, but not bound by libssh2-sys.
+extern "C" {
+    fn libssh2_userauth_publickey(
+        session: *mut c_void,
+        username: *const c_char,
+        pubkeydata: *const c_uchar,
+        pubkeydata_len: usize,
+        sign_callback: Option<SignCallback>,
+        abstract_: *mut *mut c_void,
+    ) -> c_int;
+}
+
+/// Produces the raw signature over `data` for signature algorithm `method`,
+/// or `None` to abandon the attempt.
+pub(crate) type Sign<'a> = dyn FnMut(&str, &[u8]) -> Option<Vec<u8>> + 'a;
+
+/// Authenticate as `username` with the key whose wire-format public blob is
+/// `public_key`. `sign` runs only once the server has accepted the key.
+pub(crate) fn userauth_with_signer(
+    session: &Session,
+    username: &str,
+    public_key: &[u8],
+    sign: &mut Sign<'_>,
+) -> Result<(), ssh2::Error> {
+    // LIBSSH2_ERROR_INVAL
+    let username = CString::new(username)
+        .map_err(|_| ssh2::Error::new(ssh2::ErrorCode::Session(-34), "invalid username"))?;
+    let mut sign: &mut Sign<'_> = sign;
+    let mut context = (&mut sign as *mut &mut Sign<'_>).cast::<c_void>();
+    let rc = {
+        let mut raw = session.raw();
+        // SAFETY: the session pointer is held locked for the duration of the
+        // call, and `context` outlives it; libssh2 hands it back unchanged to
+        // `sign_trampoline` and does not retain it afterwards.
+        unsafe {
+            libssh2_userauth_publickey(
+                std::ptr::from_mut(&mut *raw).cast::<c_void>(),
+                username.as_ptr(),
+                public_key.as_ptr(),
+                public_key.len(),
+                Some(sign_trampoline),
+                &mut context,
+            )
+        }
+    };
+    if rc == 0 {
+        return Ok(());
+    }
+    Err(ssh2::Error::last_session_error(session)
+        .unwrap_or_else(|| ssh2::Error::from_session_error(session, rc)))
+}
+
+unsafe extern "C" fn sign_trampoline(
+    _session: *mut c_void,
+    sig: *mut *mut c_uchar,
+    sig_len: *mut usize,
+    data: *const c_uchar,
+    data_len: usize,
+    abstract_: *mut *mut c_void,
+) -> c_int {
+    // SAFETY: `abstract_` points at the `context` set up by
+    // `userauth_with_signer`, and `data` at `data_len` bytes owned by libssh2.
+    let sign = &mut **(*abstract_).cast::<&mut Sign<'_>>();
+    let data = std::slice::from_raw_parts(data, data_len);
+    let signature = panic::catch_unwind(AssertUnwindSafe(|| {
+        let method = signed_method(data)?;
+        sign(&method, data)
+    }));
+    let Ok(Some(signature)) = signature else {
+        return SIGN_FAILED;
+    };
+    // libssh2 releases the signature with its default allocator, `free`.
+    let buffer = libc::malloc(signature.len().max(1)).cast::<c_uchar>();
+    if buffer.is_null() {
+        return SIGN_FAILED;
+    }
+    std::ptr::copy_nonoverlapping(signature.as_ptr(), buffer, signature.len());
+    *sig = buffer;
+    *sig_len = signature.len();
+    0
+}
+
+/// The signature algorithm named in a `publickey` userauth request, as
+/// signed: session id, request type, user, service, method, `TRUE`,
+/// algorithm, public key (RFC 4252 §7).
+fn signed_method(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    skip_string(&mut rest)?; // session identifier
+    rest = rest.get(1..)?; // SSH_MSG_USERAUTH_REQUEST
+    skip_string(&mut rest)?; // user name
+    skip_string(&mut rest)?; // service name
+    skip_string(&mut rest)?; // "publickey"
+    rest = rest.get(1..)?; // TRUE
+    let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    let method = rest.get(4..4 + len)?;
+    String::from_utf8(method.to_vec()).ok()
+}
+
+fn skip_string(rest: &mut &[u8]) -> Option<()> {
+    let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    *rest = rest.get(4 + len..)?;
+    Some(())
+}
+
+/// Sign `data` with `key` for signature algorithm `method`. RSA keys follow
+/// the negotiated `rsa-sha2-*` hash; legacy SHA-1 `ssh-rsa` is refused.
+pub(crate) fn sign_with_key(
+    key: &PrivateKey,
+    method: &str,
+    data: &[u8],
+) -> Result<Vec<u8>, String> {
+    match (key.key_data(), method) {
+        (KeypairData::Rsa(keypair), "rsa-sha2-256") => {
+            pkcs1v15::SigningKey::<Sha256>::try_from(keypair)
+                .map_err(|e| format!("Invalid RSA key: {}", e))?
+                .try_sign(data)
+                .map(|signature| signature.to_vec())
+                .map_err(|e| format!("RSA-SHA256 signing failed: {}", e))
+        }
+        (KeypairData::Rsa(keypair), "rsa-sha2-512") => keypair
+            .try_sign(data)
+            .map(|signature| signature.as_bytes().to_vec())
+            .map_err(|e| format!("RSA-SHA512 signing failed: {}", e)),
+        (KeypairData::Rsa(_), _) => Err(format!("Signature algorithm {} is not supported", method)),
+        (keypair, _) => {
+            let signature: ssh_key::Signature = keypair
+                .try_sign(data)
+                .map_err(|e| format!("Signing failed: {}", e))?;
+            if signature.algorithm().as_str() != method {
+                return Err(format!("Signature algorithm {} is not supported", method));
+            }
+            Ok(signature.as_bytes().to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use signature::Verifier;
+    use ssh_key::{Algorithm, Signature};
+
+    fn write_string(buf: &mut Vec<u8>, data: &[u8]) {
+        buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        buf.extend_from_slice(data);
+    }
+
+    fn signed_data(method: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        write_string(&mut data, &[7; 32]);
+        data.push(50);
+        write_string(&mut data, b"alice");
+        write_string(&mut data, b"ssh-connection");
+        write_string(&mut data, b"publickey");
+        data.push(1);
+        write_string(&mut data, method.as_bytes());
+        write_string(&mut data, b"key blob");
+        data
+    }
+
+    #[test]
+    fn signed_method_reads_the_request_algorithm() {
+        assert_eq!(
+            signed_method(&signed_data("rsa-sha2-512")).as_deref(),
+            Some("rsa-sha2-512")
+        );
+        let mut truncated = signed_data("ssh-ed25519");
+        truncated.truncate(truncated.len() - 20);
+        assert_eq!(signed_method(&truncated), None);
+    }
+
+    #[test]
+    fn ed25519_signature_verifies_and_other_methods_are_refused() {
+        let key = PrivateKey::random(&mut rand::rngs::OsRng, Algorithm::Ed25519).unwrap();
+        let data = signed_data("ssh-ed25519");
+        let raw = sign_with_key(&key, "ssh-ed25519", &data).unwrap();
+        let signature = Signature::new(Algorithm::Ed25519, raw).unwrap();
+        key.public_key()
+            .key_data()
+            .verify(&data, &signature)
+            .unwrap();
+        assert!(sign_with_key(&key, "rsa-sha2-256", &data).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! In-band file transfers (`sz` / `rz`, `tsz` / `trz`) for interactive shells.
+//!
+//! The shell worker routes every chunk it reads through [`ShellInband`].
+//! Once a ZMODEM or trzsz handshake shows up, output stops reaching the
+//! renderer and feeds the transfer instead, while the transfer's replies
+//! are written back to the channel.  The frontend answers the
+//! `ssh-inband-transfer-request` event through
+//! `ssh_respond_to_inband_transfer`.
+
+use super::INBAND_TRANSFERS;
+use sorng_core::events::DynEventEmitter;
+use sorng_serial::serial::inband::{InbandDetector, InbandInput};
+use std::io::{ErrorKind, Write};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+const EVENT_PREFIX: &str = "ssh";
+
+struct ActiveTransfer {
+    input: InbandInput,
+    output: mpsc::Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+/// Per-shell transfer state, owned by the shell worker thread.
+pub(crate) struct ShellInband {
+    runtime: tokio::runtime::Handle,
+    session_id: String,
+    emitter: DynEventEmitter,
+    detector: InbandDetector,
+    active: Option<ActiveTransfer>,
+}
+
+impl ShellInband {
+    pub(crate) fn new(
+        runtime: tokio::runtime::Handle,
+        session_id: String,
+        emitter: DynEventEmitter,
+    ) -> Self {
+        Self {
+            runtime,
+            session_id,
+            emitter,
+            detector: InbandDetector::new(),
+            active: None,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Route a chunk read from the shell and return the part to render.
+    pub(crate) fn route<'b>(&mut self, chunk: &'b [u8]) -> &'b [u8] {
+        if let Some(active) = &self.active {
+            active.input.push(chunk);
+            return &[];
+        }
+        let Some(found) = self.detector.scan(chunk) else {
+            return chunk;
+        };
+        let (input, output) = INBAND_TRANSFERS.start(
+            &self.runtime,
+            &self.session_id,
+            found.trigger,
+            found.handshake(chunk),
+            self.emitter.clone(),
+            EVENT_PREFIX,
+        );
+        self.active = Some(ActiveTransfer {
+            input,
+            output,
+            pending: Vec::new(),
+        });
+        &chunk[..found.offset]
+    }
+
+    /// Write queued transfer output to the shell.  Once the transfer is
+    /// over, returns the output it left unread so the caller can render it.
+    pub(crate) fn pump(&mut self, writer: &mut impl Write) -> Result<Option<Vec<u8>>, String> {
+        let Some(active) = self.active.as_mut() else {
+            return Ok(None);
+        };
+        loop {
+            if active.pending.is_empty() {
+                match active.output.try_recv() {
+                    Ok(bytes) => active.pending = bytes,
+                    Err(TryRecvError::Empty) => return Ok(None),
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+            match writer.write(&active.pending) {
+                Ok(0) => return Ok(None),
+                Ok(written) => {
+                    active.pending.drain(..written);
+                }
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) =>
+                {
+                    return Ok(None)
+                }
+                Err(error) => return Err(error.to_string()),
+            }
+        }
+        let _ = writer.flush();
+        let leftover = active.input.take_leftover();
+        self.active = None;
+        self.detector.reset();
+        Ok(Some(leftover))
+    }
+
+    /// Abort a running transfer (the shell is going away).
+    pub(crate) fn cancel(&mut self) {
+        if let Some(active) = self.active.take() {
+            active.input.cancel();
+            let _ = INBAND_TRANSFERS.cancel(active.input.transfer_id());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_declined_transfer_hands_terminal_back() {
+        let mut inband = ShellInband::new(
+            tokio::runtime::Handle::current(),
+            "ssh-inband-test".to_string(),
+            std::sync::Arc::new(sorng_core::events::NoopEventEmitter),
+        );
+        assert_eq!(inband.route(b"$ ls\r\n"), b"$ ls\r\n");
+        assert!(!inband.is_active());
+
+        let rendered = inband.route(b"$ rz\r\n**\x18B0100000023be50\r\x8a\x11");
+        assert_eq!(rendered, b"$ rz\r\n");
+        assert!(inband.is_active());
+        assert!(inband.route(b"more handshake").is_empty());
+
+        let transfer_id = inband
+            .active
+            .as_ref()
+            .unwrap()
+            .input
+            .transfer_id()
+            .to_string();
+        INBAND_TRANSFERS.cancel(&transfer_id).unwrap();
+
+        let mut written = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let leftover = loop {
+            if let Some(leftover) = inband.pump(&mut written).unwrap() {
+                break leftover;
+            }
+            assert!(Instant::now() < deadline, "transfer did not finish");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        // `rz` is told to abort with a run of CAN bytes.
+        assert!(written.starts_with(&[0x18; 5]));
+        assert!(!inband.is_active());
+        assert!(leftover.is_empty());
+        assert_eq!(inband.route(b"$ "), b"$ ");
+    }
+}
@@ -410,6 +410,7 @@ fn actor_config(config: ExternalSshConfig<'_>) -> SshConnectionConfig {
             .map(|value| SecretString::new(value.to_string())),
         private_key_path: config.private_key.map(str::to_string),
         private_key_passphrase: None,
+        certificate_path: None,
         jump_hosts: vec![],
         proxy_config: None,
         proxy_chain: None,
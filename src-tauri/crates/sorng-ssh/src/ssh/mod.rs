@@ -1,11 +1,14 @@
 use std::collections::HashMap;
-use std::sync::Mutex as StdMutex;
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::oneshot;
 
 pub mod automation;
+pub mod certificates;
 pub mod diagnostics;
 pub mod fido2;
 pub mod highlighting;
+mod identity_auth;
+mod inband;
 pub mod integration;
 pub mod output_state;
 pub mod proxy_command;
@@ -13,6 +16,8 @@ pub mod recording;
 pub mod service;
 mod shell_runtime;
 pub mod sk_keys;
+pub mod socks;
+pub mod ssh_config;
 pub mod tunnels;
 pub mod types;
 pub mod x11;
@@ -36,6 +41,12 @@ lazy_static::lazy_static! {
         StdMutex::new(HashMap::new());
 }
 
+// Global registry of in-band (ZMODEM / trzsz) transfers started from shells
+lazy_static::lazy_static! {
+    pub static ref INBAND_TRANSFERS: Arc<sorng_serial::serial::inband::InbandTransfers> =
+        Arc::new(sorng_serial::serial::inband::InbandTransfers::new());
+}
+
 // Global storage for active FTP tunnels
 lazy_static::lazy_static! {
     pub static ref FTP_TUNNELS: StdMutex<HashMap<String, types::FtpTunnelStatus>> = StdMutex::new(HashMap::new());
@@ -51,6 +62,11 @@ lazy_static::lazy_static! {
     pub static ref VNC_TUNNELS: StdMutex<HashMap<String, types::VncTunnelStatus>> = StdMutex::new(HashMap::new());
 }
 
+// Global storage for active dynamic (SOCKS) tunnels
+lazy_static::lazy_static! {
+    pub static ref DYNAMIC_TUNNELS: StdMutex<HashMap<String, socks::DynamicTunnelEntry>> = StdMutex::new(HashMap::new());
+}
+
 // Re-export everything so that `use crate::ssh::*` still works.
 
 // All types
@@ -17,6 +17,8 @@ use super::types::{
 };
 use super::MAX_BUFFER_SIZE;
 
+pub use sorng_core::utf8::StreamingUtf8Decoder;
+
 pub const DEFAULT_RECORDING_MAX_BYTES: u64 = 8 * 1024 * 1024;
 pub const DEFAULT_RECORDING_MAX_ENTRIES: usize = 100_000;
 pub const DEFAULT_RECORDING_MAX_DURATION_MS: u64 = 8 * 60 * 60 * 1_000;
@@ -86,71 +88,6 @@ impl RecordingLimits {
     }
 }
 
-/// Stateful UTF-8 decoder for arbitrary SSH read boundaries.
-///
-/// Valid incomplete suffixes are retained until the next read. Invalid byte
-/// sequences produce one replacement character and decoding continues.
-#[derive(Debug, Default)]
-pub struct StreamingUtf8Decoder {
-    pending: Vec<u8>,
-}
-
-impl StreamingUtf8Decoder {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn push(&mut self, bytes: &[u8]) -> String {
-        let mut combined = std::mem::take(&mut self.pending);
-        combined.extend_from_slice(bytes);
-        let mut decoded = String::new();
-        let mut cursor = 0usize;
-
-        while cursor < combined.len() {
-            match std::str::from_utf8(&combined[cursor..]) {
-                Ok(valid) => {
-                    decoded.push_str(valid);
-                    cursor = combined.len();
-                }
-                Err(error) => {
-                    let valid_end = cursor + error.valid_up_to();
-                    // SAFETY: `valid_up_to` guarantees this prefix is UTF-8.
-                    decoded.push_str(
-                        std::str::from_utf8(&combined[cursor..valid_end])
-                            .expect("valid_up_to prefix must be UTF-8"),
-                    );
-                    match error.error_len() {
-                        Some(invalid_len) => {
-                            decoded.push('\u{fffd}');
-                            cursor = valid_end.saturating_add(invalid_len);
-                        }
-                        None => {
-                            self.pending.extend_from_slice(&combined[valid_end..]);
-                            cursor = combined.len();
-                        }
-                    }
-                }
-            }
-        }
-
-        decoded
-    }
-
-    /// Flush an incomplete terminal suffix when the transport closes.
-    pub fn finish(&mut self) -> String {
-        if self.pending.is_empty() {
-            return String::new();
-        }
-        let pending = std::mem::take(&mut self.pending);
-        String::from_utf8_lossy(&pending).into_owned()
-    }
-
-    #[cfg(test)]
-    fn pending_len(&self) -> usize {
-        self.pending.len()
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TerminalAppendMetadata {
     pub generation: u64,
@@ -990,27 +927,6 @@ mod tests {
     use super::*;
     use crate::ssh::types::SshShellOutput;
 
-    #[test]
-    fn streaming_utf8_decoder_preserves_split_multibyte_codepoints() {
-        let input = "prefix-é-🙂-suffix".as_bytes();
-        let mut decoder = StreamingUtf8Decoder::new();
-        let mut decoded = String::new();
-        for byte in input {
-            decoded.push_str(&decoder.push(std::slice::from_ref(byte)));
-        }
-        decoded.push_str(&decoder.finish());
-        assert_eq!(decoded, "prefix-é-🙂-suffix");
-        assert_eq!(decoder.pending_len(), 0);
-    }
-
-    #[test]
-    fn streaming_utf8_decoder_replaces_only_invalid_or_terminal_incomplete_input() {
-        let mut decoder = StreamingUtf8Decoder::new();
-        assert_eq!(decoder.push(&[b'a', 0xff, b'b', 0xf0, 0x9f]), "a\u{fffd}b");
-        assert_eq!(decoder.pending_len(), 2);
-        assert_eq!(decoder.finish(), "\u{fffd}");
-    }
-
     #[test]
     fn replay_cap_never_splits_utf8_and_accounts_every_dropped_byte() {
         let mut buffer = TerminalReplayBuffer::new(5);
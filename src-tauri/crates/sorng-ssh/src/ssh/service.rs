@@ -2,6 +2,7 @@ use chrono::Utc;
 use secrecy::{ExposeSecret, SecretString};
 use socket2::{SockRef, TcpKeepalive};
 use sorng_core::events::DynEventEmitter;
+use sorng_core::net::ACCEPT_ERROR_BACKOFF;
 use ssh2::{ErrorCode as SshErrorCode, KeyboardInteractivePrompt, MethodType, Prompt, Session};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
@@ -19,7 +20,10 @@ use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
 use uuid::Uuid;
 
 use super::automation::process_automation_output;
+use super::certificates::{self, KnownHostsMarkers};
 use super::highlighting::process_highlight_output;
+use super::identity_auth;
+use super::inband::ShellInband;
 use super::output_state::{
     append_terminal_output, cleanup_session_output_state, ensure_terminal_buffer,
     StreamingUtf8Decoder,
@@ -31,6 +35,7 @@ use super::shell_runtime::{
     process_shell_admission, shell_mailbox, ShellAdmission, ShellCleanupTarget, ShellCompletion,
     ShellMailboxLimits, ShellWorkerCompletionGuard, ShellWorkerOutcome,
 };
+use super::socks::{self, DynamicForwardOptions, DynamicForwardStats};
 use super::types::*;
 use super::PENDING_HOST_KEY_PROMPTS;
 
@@ -383,25 +388,87 @@ pub(crate) fn known_host_key_format(host_key_type: ssh2::HostKeyType) -> ssh2::K
     host_key_type.into()
 }
 
+/// Trust an unknown host through a `@cert-authority` line, if one covers it
+/// and the server holds a certificate for its host key signed by that CA.
+/// Only a failed certificate check is an error; everything else falls back to
+/// the plain-key prompt.
+fn trusted_host_certificate(
+    markers: &KnownHostsMarkers,
+    host_key: &[u8],
+    config: &SshConnectionConfig,
+) -> Result<Option<certificates::TrustedHostCertificate>, String> {
+    if !markers.has_authority_for(&config.host, config.port) {
+        return Ok(None);
+    }
+    let proxied = config
+        .proxy_command
+        .as_ref()
+        .is_some_and(|proxy_cmd| proxy_cmd.command.is_some() || proxy_cmd.template.is_some())
+        || config.mixed_chain.is_some()
+        || config.proxy_chain.is_some()
+        || config.proxy_config.is_some()
+        || !config.jump_hosts.is_empty();
+    if proxied {
+        log::warn!(
+            "A @cert-authority covers {} but its host certificate cannot be fetched through a proxy or jump host; verifying the plain host key instead",
+            config.host
+        );
+        return Ok(None);
+    }
+
+    let timeout = Duration::from_secs(config.connect_timeout.unwrap_or(15).max(1));
+    match certificates::probe_host_certificate(&config.host, config.port, host_key, timeout) {
+        Ok(cert_blob) => markers.verify_host_certificate(
+            &cert_blob,
+            host_key,
+            &config.host,
+            config.port,
+            certificates::unix_now(),
+        ),
+        Err(e) => {
+            log::warn!(
+                "A @cert-authority covers {} but no host certificate was found: {}",
+                config.host,
+                e
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Load a known_hosts file into `known_hosts`, returning its
+/// `@cert-authority` / `@revoked` marker lines, which libssh2 cannot parse and
+/// which are handled by [`certificates::KnownHostsMarkers`] instead.
 fn read_known_hosts_if_present(
     known_hosts: &mut ssh2::KnownHosts,
     path: &Path,
-) -> Result<(), String> {
+) -> Result<Vec<String>, String> {
     match std::fs::metadata(path) {
-        Ok(metadata) if metadata.is_file() => known_hosts
-            .read_file(path, ssh2::KnownHostFileKind::OpenSSH)
-            .map(|_| ())
-            .map_err(|error| {
+        Ok(metadata) if metadata.is_file() => {
+            let read_error = |error: &dyn std::fmt::Display| {
                 format!(
                     "Failed to read known_hosts file {}: {error}",
                     path.display()
                 )
-            }),
+            };
+            let content = std::fs::read_to_string(path).map_err(|e| read_error(&e))?;
+            let mut markers = Vec::new();
+            for line in content.lines() {
+                if certificates::is_marker_line(line) {
+                    markers.push(line.to_string());
+                } else {
+                    known_hosts
+                        .read_str(line, ssh2::KnownHostFileKind::OpenSSH)
+                        .map_err(|e| read_error(&e))?;
+                }
+            }
+            Ok(markers)
+        }
         Ok(_) => Err(format!(
             "Failed to read known_hosts file {}: path is not a regular file",
             path.display()
         )),
-        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
         Err(error) => Err(format!(
             "Failed to inspect known_hosts file {}: {error}",
             path.display()
@@ -4023,7 +4090,7 @@ impl SshService {
                     sess = self
                         .authenticate_jump_session_isolated(
                             sess,
-                            jump.clone(),
+                            (**jump).clone(),
                             &control,
                             &format!("mixed-chain-hop-{}-ssh-authentication", i + 1),
                             hop_timeout,
@@ -4108,16 +4175,14 @@ impl SshService {
                     .as_ref()
                     .map(|secret| secret.expose_secret().as_str());
 
-                phase.configure_session_timeout(session)?;
-                if session
-                    .userauth_pubkey_file(
-                        &config.username,
-                        None,
-                        Path::new(private_key_path),
-                        passphrase,
-                    )
-                    .is_ok()
-                {
+                if Self::userauth_pubkey_with_certificate(
+                    session,
+                    &config.username,
+                    private_key_path,
+                    config.certificate_path.as_deref(),
+                    passphrase,
+                    phase,
+                )? {
                     return Ok(());
                 }
                 phase.ensure_active()?;
@@ -4211,6 +4276,11 @@ impl SshService {
             phase.ensure_active()?;
         }
 
+        // Try keys held by the built-in agent (e.g. unlocked KeePass keys)
+        if Self::userauth_builtin_identities(session, &config.host, &config.username, phase)? {
+            return Ok(());
+        }
+
         // Try agent authentication
         phase.configure_session_timeout(session)?;
         if session.userauth_agent(&config.username).is_ok() {
@@ -4221,6 +4291,100 @@ impl SshService {
         Err("All authentication methods failed".to_string())
     }
 
+    /// Public-key auth, offering the matching OpenSSH user certificate first
+    /// (when one is configured or sits next to the key) and the bare key
+    /// second, so a rejected or expired certificate does not lock the user out.
+    fn userauth_pubkey_with_certificate(
+        session: &mut Session,
+        username: &str,
+        private_key_path: &str,
+        certificate_path: Option<&str>,
+        passphrase: Option<&str>,
+        phase: &dyn SshDeadlinePhase,
+    ) -> Result<bool, String> {
+        let private_key = Path::new(private_key_path);
+        if let Some(certificate) =
+            certificates::user_certificate_for_key(certificate_path, private_key_path, username)
+        {
+            phase.configure_session_timeout(session)?;
+            match session.userauth_pubkey_file(
+                username,
+                Some(&certificate),
+                private_key,
+                passphrase,
+            ) {
+                Ok(()) => return Ok(true),
+                Err(error) => log::debug!(
+                    "Certificate {} was not accepted for {}: {}",
+                    certificate.display(),
+                    username,
+                    error
+                ),
+            }
+            phase.ensure_active()?;
+        }
+
+        phase.configure_session_timeout(session)?;
+        Ok(session
+            .userauth_pubkey_file(username, None, private_key, passphrase)
+            .is_ok())
+    }
+
+    /// Public-key auth with the identities of the in-process identity
+    /// provider (the built-in SSH agent), which `userauth_agent` cannot reach
+    /// because it only talks to `SSH_AUTH_SOCK`. Each key is offered first and
+    /// signed only once the server accepts it, so keys requiring confirmation
+    /// prompt the user only when they would actually log in.
+    fn userauth_builtin_identities(
+        session: &mut Session,
+        host: &str,
+        username: &str,
+        phase: &dyn SshDeadlinePhase,
+    ) -> Result<bool, String> {
+        let Some(provider) = sorng_core::ssh_identities::identity_provider() else {
+            return Ok(false);
+        };
+        for identity in provider.identities_for(host, username) {
+            phase.ensure_active()?;
+            let key = match ssh_key::PrivateKey::from_openssh(&identity.private_key_openssh) {
+                Ok(key) => key,
+                Err(error) => {
+                    log::warn!("Agent key '{}' is unreadable: {}", identity.comment, error);
+                    continue;
+                }
+            };
+            let Ok(public_key) = key.public_key().to_bytes() else {
+                continue;
+            };
+            let mut sign = |method: &str, data: &[u8]| {
+                if identity.requires_confirmation && !provider.confirm(&identity, host, username) {
+                    log::info!("Use of agent key '{}' was not confirmed", identity.comment);
+                    return None;
+                }
+                identity_auth::sign_with_key(&key, method, data)
+                    .map_err(|error| {
+                        log::debug!("Agent key '{}' cannot sign: {}", identity.comment, error)
+                    })
+                    .ok()
+            };
+            phase.configure_session_timeout(session)?;
+            match identity_auth::userauth_with_signer(session, username, &public_key, &mut sign) {
+                Ok(()) => {
+                    provider.record_use(&identity);
+                    return Ok(true);
+                }
+                Err(error) => log::debug!(
+                    "Agent key '{}' was not accepted for {}: {}",
+                    identity.comment,
+                    username,
+                    error
+                ),
+            }
+        }
+        phase.ensure_active()?;
+        Ok(false)
+    }
+
     fn authenticate_jump_session(
         session: &mut Session,
         jump_config: &JumpHostConfig,
@@ -4232,16 +4396,14 @@ impl SshService {
                 .private_key_passphrase
                 .as_ref()
                 .map(|secret| secret.expose_secret().as_str());
-            phase.configure_session_timeout(session)?;
-            if session
-                .userauth_pubkey_file(
-                    &jump_config.username,
-                    None,
-                    Path::new(private_key_path),
-                    passphrase,
-                )
-                .is_ok()
-            {
+            if Self::userauth_pubkey_with_certificate(
+                session,
+                &jump_config.username,
+                private_key_path,
+                jump_config.certificate_path.as_deref(),
+                passphrase,
+                phase,
+            )? {
                 return Ok(());
             }
             phase.ensure_active()?;
@@ -4334,7 +4496,15 @@ impl SshService {
             phase.ensure_active()?;
         }
 
-        // 4. SSH agent
+        // 4. Built-in agent identities, then the SSH agent
+        if Self::userauth_builtin_identities(
+            session,
+            &jump_config.host,
+            &jump_config.username,
+            phase,
+        )? {
+            return Ok(());
+        }
         phase.configure_session_timeout(session)?;
         if session.userauth_agent(&jump_config.username).is_ok() {
             return Ok(());
@@ -4412,15 +4582,22 @@ impl SshService {
         let host_key = host_key.to_vec();
         let host_key_info = build_host_key_info(&host_key, key_type);
 
-        let check_result = {
+        let (check_result, markers) = {
             let _known_hosts_guard = lock_known_hosts_file()?;
             let mut known_hosts = session
                 .known_hosts()
                 .map_err(|e| format!("Failed to create known_hosts handle: {}", e))?;
 
-            read_known_hosts_if_present(&mut known_hosts, Path::new(&known_hosts_path))?;
+            let marker_lines =
+                read_known_hosts_if_present(&mut known_hosts, Path::new(&known_hosts_path))?;
 
-            known_hosts.check_port(&config.host, config.port, &host_key)
+            let markers = KnownHostsMarkers::parse(&marker_lines.join("\n"));
+            markers.check_not_revoked(&host_key, &config.host)?;
+
+            (
+                known_hosts.check_port(&config.host, config.port, &host_key),
+                markers,
+            )
         };
 
         match check_result {
@@ -4429,6 +4606,15 @@ impl SshService {
                 Ok(())
             }
             ssh2::CheckResult::NotFound => {
+                if let Some(trusted) = trusted_host_certificate(&markers, &host_key, config)? {
+                    log::info!(
+                        "Host key for {} accepted via certificate '{}' signed by @cert-authority {}",
+                        config.host,
+                        trusted.key_id,
+                        trusted.ca_fingerprint
+                    );
+                    return Ok(());
+                }
                 let decision =
                     if Self::should_accept_new_host_key(config, ssh2::CheckResult::NotFound) {
                         SshHostKeyPromptDecision::AcceptAndSave
@@ -4599,7 +4785,8 @@ impl SshService {
             .known_hosts()
             .map_err(|e| format!("Failed to create known_hosts handle: {}", e))?;
 
-        read_known_hosts_if_present(&mut known_hosts, Path::new(persistence.known_hosts_path))?;
+        let marker_lines =
+            read_known_hosts_if_present(&mut known_hosts, Path::new(persistence.known_hosts_path))?;
 
         match known_hosts.check_port(
             &persistence.config.host,
@@ -4660,6 +4847,15 @@ impl SshService {
                 ssh2::KnownHostFileKind::OpenSSH,
             )
             .map_err(|e| format!("Failed to write known_hosts file: {}", e))?;
+        // libssh2 drops the marker lines it could not load; put them back.
+        if !marker_lines.is_empty() {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(persistence.known_hosts_path)
+                .map_err(|e| format!("Failed to write known_hosts file: {}", e))?;
+            writeln!(file, "{}", marker_lines.join("\n"))
+                .map_err(|e| format!("Failed to write known_hosts file: {}", e))?;
+        }
 
         log::info!(
             "Host key for {} {} in known_hosts",
@@ -5411,6 +5607,11 @@ impl SshService {
         let shell_suspend_count = std::sync::Arc::clone(&suspend_count);
         let shell_admission = Arc::clone(&self.shell_admission);
         let completion_for_thread = Arc::clone(&completion);
+        let inband = ShellInband::new(
+            tokio::runtime::Handle::current(),
+            session_id.to_string(),
+            event_emitter.clone(),
+        );
 
         // Keep Rust's platform-default thread stack. The admission ceiling is
         // the resource guard; changing stack size requires separate measured
@@ -5429,6 +5630,7 @@ impl SshService {
                 let emitter = emitter;
                 let shell_admission = shell_admission;
                 let shell_suspend_count = shell_suspend_count;
+                let mut inband = inband;
                 let mut buffer = [0u8; 16384];
                 let mut decoder = StreamingUtf8Decoder::new();
                 let mut running = true;
@@ -5468,6 +5670,11 @@ impl SshService {
                             break;
                         }
                         match command {
+                            // Keystrokes would corrupt a running ZMODEM /
+                            // trzsz stream; the frontend cancels through
+                            // `ssh_cancel_inband_transfer` instead.
+                            SshShellCommand::Input(_) | SshShellCommand::SecretInput(_)
+                                if inband.is_active() => {}
                             SshShellCommand::Input(data) => {
                                 record_input(&session_id_owned, &data);
                                 if let Err(message) =
@@ -5509,10 +5716,31 @@ impl SshService {
                         continue;
                     }
 
+                    match inband.pump(&mut channel) {
+                        Ok(Some(leftover)) => {
+                            let raw_output = decoder.push(&leftover);
+                            if let Some(payload) = shell_admission
+                                .publish_if_current(&session_id_owned, shell_generation, || {
+                                    prepare_shell_output(&session_id_owned, &raw_output)
+                                })
+                                .flatten()
+                            {
+                                emit_shell_output(payload, &emitter);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(message) => {
+                            emit_shell_error(&session_id_owned, &message, &emitter);
+                            close_reason = SshShellCloseReason::TransportError;
+                            close_message = Some(message);
+                            break;
+                        }
+                    }
+
                     match channel.read(&mut buffer) {
                         Ok(bytes) if bytes > 0 => {
                             idle_count = 0;
-                            let raw_output = decoder.push(&buffer[..bytes]);
+                            let raw_output = decoder.push(inband.route(&buffer[..bytes]));
                             if let Some(payload) = shell_admission
                                 .publish_if_current(&session_id_owned, shell_generation, || {
                                     prepare_shell_output(&session_id_owned, &raw_output)
@@ -5551,6 +5779,8 @@ impl SshService {
                     std::thread::sleep(Duration::from_millis(sleep_ms));
                 }
 
+                inband.cancel();
+
                 // Preserve an incomplete UTF-8 suffix at transport EOF instead
                 // of silently losing it. Tombstoned generations cannot recreate
                 // replay state after disconnect has detached them.
@@ -5695,7 +5925,13 @@ impl SshService {
                     .get_mut(session_id)
                     .ok_or("Session not found")?;
                 session.last_activity = Utc::now();
-                Self::setup_dynamic_port_forward(session, &config, forward_id.clone()).await?
+                Self::setup_dynamic_port_forward(
+                    session,
+                    &config,
+                    forward_id.clone(),
+                    DynamicForwardOptions::default(),
+                )
+                .await?
             }
         };
 
@@ -5708,6 +5944,31 @@ impl SshService {
         Ok(forward_id)
     }
 
+    /// Start a dynamic (SOCKS4a/SOCKS5) forward with optional client
+    /// authentication and shared live stats. `config.direction` is ignored;
+    /// the handle's `local_port` is the port actually bound.
+    pub async fn setup_dynamic_forward(
+        &mut self,
+        session_id: &str,
+        config: PortForwardConfig,
+        options: DynamicForwardOptions,
+    ) -> Result<String, String> {
+        let forward_id = Uuid::new_v4().to_string();
+        let config = PortForwardConfig {
+            direction: PortForwardDirection::Dynamic,
+            ..config
+        };
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or("Session not found")?;
+        session.last_activity = Utc::now();
+        let handle =
+            Self::setup_dynamic_port_forward(session, &config, forward_id.clone(), options).await?;
+        session.port_forwards.insert(forward_id.clone(), handle);
+        Ok(forward_id)
+    }
+
     /// Resolve and validate the bind address for a local/dynamic port forward.
     ///
     /// Secure-by-default policy (t6 finding #10, user decision 2026-06-11):
@@ -5804,6 +6065,7 @@ impl SshService {
                     }
                     Err(e) => {
                         log::error!("Failed to accept connection: {}", e);
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
                     }
                 }
             }
@@ -6108,6 +6370,7 @@ impl SshService {
         session: &mut SshSession,
         config: &PortForwardConfig,
         id: String,
+        options: DynamicForwardOptions,
     ) -> Result<PortForwardHandle, String> {
         let bind_host = Self::resolve_forward_bind(config)?;
         let listener = TcpListener::bind(format!("{}:{}", bind_host, config.local_port))
@@ -6117,6 +6380,12 @@ impl SshService {
             .set_nonblocking(true)
             .map_err(|e| format!("Failed to set non-blocking: {}", e))?;
 
+        // Report the actual port when an ephemeral one (0) was requested.
+        let mut config = config.clone();
+        if let Ok(addr) = listener.local_addr() {
+            config.local_port = addr.port();
+        }
+
         let session_clone = session.session.clone();
         let config_clone = config.clone();
         let id_clone = id.clone();
@@ -6129,29 +6398,38 @@ impl SshService {
             )?;
 
             log::info!(
-                "SOCKS5 proxy started on {}:{}",
+                "SOCKS proxy started on {}:{}{}",
                 config_clone.local_host,
-                config_clone.local_port
+                config_clone.local_port,
+                if options.credentials.is_some() {
+                    " (authentication required)"
+                } else {
+                    ""
+                }
             );
 
             loop {
                 match listener.accept().await {
                     Ok((client_stream, peer_addr)) => {
-                        log::debug!("[{}] SOCKS5 client connected from {}", id_clone, peer_addr);
+                        log::debug!("[{}] SOCKS client connected from {}", id_clone, peer_addr);
 
                         let session = session_clone.clone();
                         let id = id_clone.clone();
+                        let options = options.clone();
 
                         tokio::spawn(async move {
+                            let _active = options.stats.connection_opened();
                             if let Err(e) =
-                                Self::handle_socks5_connection(client_stream, session).await
+                                Self::handle_socks_connection(client_stream, session, &options)
+                                    .await
                             {
-                                log::debug!("[{}] SOCKS5 connection error: {}", id, e);
+                                log::debug!("[{}] SOCKS connection error: {}", id, e);
                             }
                         });
                     }
                     Err(e) => {
-                        log::error!("SOCKS5 accept error: {}", e);
+                        log::error!("SOCKS accept error: {}", e);
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
                     }
                 }
             }
@@ -6159,125 +6437,59 @@ impl SshService {
 
         Ok(PortForwardHandle {
             id: id.clone(),
-            config: config.clone(),
+            config,
             handle,
         })
     }
 
-    async fn handle_socks5_connection(
+    async fn handle_socks_connection(
         mut client_stream: tokio::net::TcpStream,
         session: Session,
+        options: &DynamicForwardOptions,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut buf = [0u8; 258];
-        let n = client_stream.read(&mut buf).await?;
-
-        if n < 2 || buf[0] != 0x05 {
-            return Err("Invalid SOCKS version".into());
-        }
-
-        let n_methods = buf[1] as usize;
-        if n < 2 + n_methods {
-            return Err("Invalid SOCKS auth methods".into());
-        }
-
-        let methods = &buf[2..2 + n_methods];
-        if !methods.contains(&0x00) {
-            client_stream.write_all(&[0x05, 0xFF]).await?;
-            return Err("No acceptable auth method".into());
-        }
-
-        client_stream.write_all(&[0x05, 0x00]).await?;
-
-        let n = client_stream.read(&mut buf).await?;
-        if n < 4 {
-            return Err("Invalid SOCKS request".into());
-        }
-
-        if buf[0] != 0x05 {
-            return Err("Invalid SOCKS version in request".into());
-        }
-
-        let cmd = buf[1];
-        let atype = buf[3];
-
-        if cmd != 0x01 {
-            client_stream
-                .write_all(&[0x05, 0x07, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
-                .await?;
-            return Err(format!("Unsupported SOCKS command: {}", cmd).into());
-        }
-
-        let (target_host, target_port, _addr_end) = match atype {
-            0x01 => {
-                if n < 10 {
-                    return Err("Invalid IPv4 address length".into());
-                }
-                let addr = format!("{}.{}.{}.{}", buf[4], buf[5], buf[6], buf[7]);
-                let port = u16::from_be_bytes([buf[8], buf[9]]);
-                (addr, port, 10)
-            }
-            0x03 => {
-                let domain_len = buf[4] as usize;
-                if n < 5 + domain_len + 2 {
-                    return Err("Invalid domain name length".into());
-                }
-                let domain = String::from_utf8_lossy(&buf[5..5 + domain_len]).to_string();
-                let port = u16::from_be_bytes([buf[5 + domain_len], buf[6 + domain_len]]);
-                (domain, port, 7 + domain_len)
-            }
-            0x04 => {
-                if n < 22 {
-                    return Err("Invalid IPv6 address length".into());
-                }
-                let addr = format!(
-                    "{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}",
-                    u16::from_be_bytes([buf[4], buf[5]]),
-                    u16::from_be_bytes([buf[6], buf[7]]),
-                    u16::from_be_bytes([buf[8], buf[9]]),
-                    u16::from_be_bytes([buf[10], buf[11]]),
-                    u16::from_be_bytes([buf[12], buf[13]]),
-                    u16::from_be_bytes([buf[14], buf[15]]),
-                    u16::from_be_bytes([buf[16], buf[17]]),
-                    u16::from_be_bytes([buf[18], buf[19]])
-                );
-                let port = u16::from_be_bytes([buf[20], buf[21]]);
-                (addr, port, 22)
-            }
-            _ => {
-                client_stream
-                    .write_all(&[0x05, 0x08, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
-                    .await?;
-                return Err(format!("Unsupported address type: {}", atype).into());
+        let request = match socks::accept_request_within(
+            &mut client_stream,
+            options.credentials.as_ref(),
+            socks::HANDSHAKE_TIMEOUT,
+        )
+        .await
+        {
+            Ok(request) => request,
+            Err(e) => {
+                options.stats.connection_failed();
+                return Err(e.into());
             }
         };
 
-        log::debug!("SOCKS5 CONNECT to {}:{}", target_host, target_port);
+        log::debug!("SOCKS CONNECT to {}:{}", request.host, request.port);
 
+        // Host names are passed through unresolved so the SSH server does the
+        // DNS lookup, as `ssh -D` does.
         let channel = match tokio::task::spawn_blocking({
             let session = session.clone();
-            let host = target_host.clone();
-            move || session.channel_direct_tcpip(&host, target_port, None)
+            let host = request.host.clone();
+            let port = request.port;
+            move || session.channel_direct_tcpip(&host, port, None)
         })
         .await?
         {
             Ok(ch) => ch,
             Err(e) => {
-                client_stream
-                    .write_all(&[0x05, 0x05, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
-                    .await?;
+                options.stats.connection_failed();
+                socks::send_reply(&mut client_stream, request.version, false).await?;
                 return Err(format!("Failed to connect via SSH: {}", e).into());
             }
         };
 
-        let response = [0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0];
-        client_stream.write_all(&response).await?;
+        socks::send_reply(&mut client_stream, request.version, true).await?;
 
-        Self::forward_socks5_traffic(client_stream, channel).await
+        Self::forward_socks_traffic(client_stream, channel, options.stats.clone()).await
     }
 
-    async fn forward_socks5_traffic(
+    async fn forward_socks_traffic(
         client_stream: tokio::net::TcpStream,
         mut channel: ssh2::Channel,
+        stats: Arc<DynamicForwardStats>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let (mut client_read, mut client_write) = client_stream.into_split();
 
@@ -6293,7 +6505,7 @@ impl SshService {
                 while let Ok(data) = rx_to_remote.try_recv() {
                     progressed = true;
                     if let Err(e) = channel.write_all(&data) {
-                        log::debug!("SOCKS5 SSH write error: {}", e);
+                        log::debug!("SOCKS SSH write error: {}", e);
                         return;
                     }
                     let _ = channel.flush();
@@ -6331,12 +6543,14 @@ impl SshService {
             let _ = channel.wait_close();
         });
 
+        let sent_stats = stats.clone();
         let client_to_remote = tokio::spawn(async move {
             let mut buf = [0u8; 32768];
             loop {
                 match client_read.read(&mut buf).await {
                     Ok(0) => break,
                     Ok(n) => {
+                        sent_stats.add_sent(n);
                         if tx_to_remote.send(buf[..n].to_vec()).await.is_err() {
                             break;
                         }
@@ -6351,6 +6565,7 @@ impl SshService {
                 if client_write.write_all(&data).await.is_err() {
                     break;
                 }
+                stats.add_received(data.len());
             }
         });
 
@@ -6533,7 +6748,10 @@ impl SshService {
     /// Build a MixedChainConfig from the legacy `jump_hosts` field.
     pub fn jump_hosts_to_mixed_chain(jump_hosts: &[JumpHostConfig]) -> MixedChainConfig {
         MixedChainConfig {
-            hops: jump_hosts.iter().cloned().map(ChainHop::SshJump).collect(),
+            hops: jump_hosts
+                .iter()
+                .map(|jump| ChainHop::SshJump(Box::new(jump.clone())))
+                .collect(),
             hop_timeout_ms: 10000,
         }
     }
@@ -9494,6 +9712,37 @@ mod tests {
         assert_eq!(std::fs::read(&known_hosts_path).unwrap(), before);
     }
 
+    #[test]
+    fn persisting_host_keys_keeps_cert_authority_markers() {
+        let temp = tempfile::tempdir().unwrap();
+        let known_hosts_path = temp.path().join("known_hosts");
+        let marker = "@cert-authority *.example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIA== ca";
+        std::fs::write(&known_hosts_path, format!("{}\n", marker)).unwrap();
+        let known_hosts_path = known_hosts_path.to_string_lossy().to_string();
+        let mut config = tcp_test_config();
+        config.known_hosts_path = Some(known_hosts_path.clone());
+        let mut session = Session::new().unwrap();
+        let persistence = HostKeyPersistenceContext {
+            config: &config,
+            known_hosts_path: &known_hosts_path,
+            host_key: b"first-fixture-host-key",
+            key_type: ssh2::HostKeyType::Ed25519,
+            replace_existing: false,
+        };
+
+        empty_test_service()
+            .persist_host_key(&mut session, &persistence)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&known_hosts_path).unwrap();
+        assert!(content.lines().any(|line| line == marker));
+        let mut known_hosts = session.known_hosts().unwrap();
+        let markers =
+            read_known_hosts_if_present(&mut known_hosts, Path::new(&known_hosts_path)).unwrap();
+        assert_eq!(markers, vec![marker.to_string()]);
+        assert_eq!(known_hosts.hosts().unwrap().len(), 1);
+    }
+
     #[test]
     fn accept_new_does_not_treat_known_hosts_read_failures_as_first_use() {
         let temp = tempfile::tempdir().unwrap();
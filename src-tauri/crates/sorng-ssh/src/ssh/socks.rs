@@ -0,0 +1,628 @@
+//! # SOCKS server for dynamic forwarding (`ssh -D`)
+//!
+//! Server side of SOCKS4, SOCKS4a and SOCKS5 (RFC 1928) for the dynamic port
+//! forward listener. Only `CONNECT` is supported; every accepted request is
+//! carried over its own `direct-tcpip` channel on the SSH session.
+//!
+//! Host names from SOCKS4a and SOCKS5 domain requests are passed through
+//! unresolved, so DNS resolution happens on the SSH server side. SOCKS5
+//! username/password authentication (RFC 1929) can be required per forward;
+//! SOCKS4 carries no password and is refused when it is.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use secrecy::{ExposeSecret, SecretString};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::types::DynamicTunnelStatus;
+
+const SOCKS4_VERSION: u8 = 0x04;
+const SOCKS5_VERSION: u8 = 0x05;
+const CMD_CONNECT: u8 = 0x01;
+
+const SOCKS5_AUTH_NONE: u8 = 0x00;
+const SOCKS5_AUTH_PASSWORD: u8 = 0x02;
+const SOCKS5_AUTH_NO_ACCEPTABLE: u8 = 0xFF;
+const PASSWORD_AUTH_VERSION: u8 = 0x01;
+
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+
+const SOCKS5_REPLY_SUCCEEDED: u8 = 0x00;
+const SOCKS5_REPLY_CONNECTION_REFUSED: u8 = 0x05;
+const SOCKS5_REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const SOCKS5_REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+const SOCKS4_REPLY_GRANTED: u8 = 0x5A;
+const SOCKS4_REPLY_REJECTED: u8 = 0x5B;
+
+/// Upper bound for the NUL-terminated SOCKS4 user id / SOCKS4a host fields.
+const SOCKS4_MAX_FIELD_LEN: usize = 255;
+
+/// How long a client has to complete the handshake before it is dropped, so
+/// stalled clients cannot hold connections open.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Username/password a SOCKS5 client must present (RFC 1929).
+#[derive(Debug, Clone)]
+pub struct SocksCredentials {
+    pub username: String,
+    pub password: SecretString,
+}
+
+impl SocksCredentials {
+    /// Build credentials from a tunnel config; both fields or neither must be
+    /// set, and each must fit the one-byte RFC 1929 length prefix.
+    pub fn from_config(
+        username: Option<String>,
+        password: Option<SecretString>,
+    ) -> Result<Option<Self>, String> {
+        match (username, password) {
+            (None, None) => Ok(None),
+            (Some(username), Some(password)) => {
+                let valid = |len: usize| (1..=255).contains(&len);
+                if !valid(username.len()) || !valid(password.expose_secret().len()) {
+                    return Err("SOCKS username and password must be 1-255 bytes long".to_string());
+                }
+                Ok(Some(Self { username, password }))
+            }
+            _ => Err("SOCKS authentication needs both a username and a password".to_string()),
+        }
+    }
+
+    fn matches(&self, username: &[u8], password: &[u8]) -> bool {
+        // Evaluate both comparisons so a wrong username is not distinguishable
+        // by timing from a wrong password.
+        let user_ok = constant_time_eq(self.username.as_bytes(), username);
+        let pass_ok = constant_time_eq(self.password.expose_secret().as_bytes(), password);
+        user_ok & pass_ok
+    }
+}
+
+/// Protocol version spoken by a client, which decides the reply format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocksVersion {
+    V4,
+    V5,
+}
+
+/// A negotiated `CONNECT` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocksConnectRequest {
+    pub version: SocksVersion,
+    /// IP literal or an unresolved host name (resolved by the SSH server).
+    pub host: String,
+    pub port: u16,
+}
+
+/// Options for a dynamic forward listener.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicForwardOptions {
+    /// Require SOCKS5 username/password authentication.
+    pub credentials: Option<SocksCredentials>,
+    /// Live counters shared with the tunnel registry.
+    pub stats: Arc<DynamicForwardStats>,
+}
+
+/// Live connection and byte counters for one dynamic forward.
+#[derive(Debug, Default)]
+pub struct DynamicForwardStats {
+    active_connections: AtomicU32,
+    total_connections: AtomicU64,
+    failed_connections: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl DynamicForwardStats {
+    /// Count a newly accepted client; the guard marks it inactive when dropped.
+    pub fn connection_opened(self: &Arc<Self>) -> ActiveConnectionGuard {
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        ActiveConnectionGuard {
+            stats: Arc::clone(self),
+        }
+    }
+
+    /// A client whose handshake or channel open failed.
+    pub fn connection_failed(&self) {
+        self.failed_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Bytes relayed from the SOCKS client to the remote target.
+    pub fn add_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Bytes relayed from the remote target back to the SOCKS client.
+    pub fn add_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Copy the current counters into `status`.
+    pub fn apply_to(&self, status: &mut DynamicTunnelStatus) {
+        status.active_connections = self.active_connections.load(Ordering::Relaxed);
+        status.total_connections = self.total_connections.load(Ordering::Relaxed);
+        status.failed_connections = self.failed_connections.load(Ordering::Relaxed);
+        status.bytes_sent = self.bytes_sent.load(Ordering::Relaxed);
+        status.bytes_received = self.bytes_received.load(Ordering::Relaxed);
+    }
+}
+
+/// Decrements the active-connection count when a relayed client goes away.
+pub struct ActiveConnectionGuard {
+    stats: Arc<DynamicForwardStats>,
+}
+
+impl Drop for ActiveConnectionGuard {
+    fn drop(&mut self) {
+        self.stats
+            .active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Registry entry for a dynamic tunnel: its static status plus live counters.
+#[derive(Debug, Clone)]
+pub struct DynamicTunnelEntry {
+    pub status: DynamicTunnelStatus,
+    pub stats: Arc<DynamicForwardStats>,
+}
+
+impl DynamicTunnelEntry {
+    /// Status with the counters filled in from the live stats.
+    pub fn snapshot(&self) -> DynamicTunnelStatus {
+        let mut status = self.status.clone();
+        self.stats.apply_to(&mut status);
+        status
+    }
+}
+
+/// Run the SOCKS handshake up to (not including) the final reply.
+///
+/// Protocol errors that have a wire representation are answered before the
+/// error is returned; the caller only replies once the SSH channel is open.
+pub async fn accept_request<S>(
+    stream: &mut S,
+    credentials: Option<&SocksCredentials>,
+) -> Result<SocksConnectRequest, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match read_u8(stream).await? {
+        SOCKS5_VERSION => accept_socks5(stream, credentials).await,
+        SOCKS4_VERSION => {
+            if credentials.is_some() {
+                write_socks4_reply(stream, SOCKS4_REPLY_REJECTED).await?;
+                return Err("SOCKS4 clients cannot authenticate; SOCKS5 is required".to_string());
+            }
+            accept_socks4(stream).await
+        }
+        other => Err(format!("Unsupported SOCKS version: {}", other)),
+    }
+}
+
+/// [`accept_request`], giving up once `timeout` has passed.
+pub async fn accept_request_within<S>(
+    stream: &mut S,
+    credentials: Option<&SocksCredentials>,
+    timeout: Duration,
+) -> Result<SocksConnectRequest, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    tokio::time::timeout(timeout, accept_request(stream, credentials))
+        .await
+        .map_err(|_| format!("SOCKS handshake timed out after {:?}", timeout))?
+}
+
+/// Send the final reply for a request once the channel outcome is known.
+pub async fn send_reply<S>(
+    stream: &mut S,
+    version: SocksVersion,
+    success: bool,
+) -> Result<(), String>
+where
+    S: AsyncWrite + Unpin,
+{
+    match version {
+        SocksVersion::V5 => {
+            let code = if success {
+                SOCKS5_REPLY_SUCCEEDED
+            } else {
+                SOCKS5_REPLY_CONNECTION_REFUSED
+            };
+            write_socks5_reply(stream, code).await
+        }
+        SocksVersion::V4 => {
+            let code = if success {
+                SOCKS4_REPLY_GRANTED
+            } else {
+                SOCKS4_REPLY_REJECTED
+            };
+            write_socks4_reply(stream, code).await
+        }
+    }
+}
+
+async fn accept_socks5<S>(
+    stream: &mut S,
+    credentials: Option<&SocksCredentials>,
+) -> Result<SocksConnectRequest, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let method_count = read_u8(stream).await? as usize;
+    let mut methods = vec![0u8; method_count];
+    read_exact(stream, &mut methods).await?;
+
+    let method = if credentials.is_some() {
+        SOCKS5_AUTH_PASSWORD
+    } else {
+        SOCKS5_AUTH_NONE
+    };
+    if !methods.contains(&method) {
+        write_all(stream, &[SOCKS5_VERSION, SOCKS5_AUTH_NO_ACCEPTABLE]).await?;
+        return Err("No acceptable SOCKS5 authentication method".to_string());
+    }
+    write_all(stream, &[SOCKS5_VERSION, method]).await?;
+
+    if let Some(credentials) = credentials {
+        if read_u8(stream).await? != PASSWORD_AUTH_VERSION {
+            return Err("Invalid SOCKS5 username/password version".to_string());
+        }
+        let username = read_len_prefixed(stream).await?;
+        let password = read_len_prefixed(stream).await?;
+        if !credentials.matches(&username, &password) {
+            write_all(stream, &[PASSWORD_AUTH_VERSION, 0x01]).await?;
+            return Err("SOCKS5 authentication failed".to_string());
+        }
+        write_all(stream, &[PASSWORD_AUTH_VERSION, 0x00]).await?;
+    }
+
+    let mut header = [0u8; 4];
+    read_exact(stream, &mut header).await?;
+    let [version, command, _reserved, address_type] = header;
+    if version != SOCKS5_VERSION {
+        return Err("Invalid SOCKS version in request".to_string());
+    }
+
+    let host = match address_type {
+        SOCKS5_ATYP_IPV4 => {
+            let mut octets = [0u8; 4];
+            read_exact(stream, &mut octets).await?;
+            Ipv4Addr::from(octets).to_string()
+        }
+        SOCKS5_ATYP_DOMAIN => {
+            let domain = read_len_prefixed(stream).await?;
+            String::from_utf8(domain).map_err(|_| "Invalid SOCKS5 domain name".to_string())?
+        }
+        SOCKS5_ATYP_IPV6 => {
+            let mut octets = [0u8; 16];
+            read_exact(stream, &mut octets).await?;
+            Ipv6Addr::from(octets).to_string()
+        }
+        other => {
+            write_socks5_reply(stream, SOCKS5_REPLY_ADDRESS_NOT_SUPPORTED).await?;
+            return Err(format!("Unsupported address type: {}", other));
+        }
+    };
+    let port = read_port(stream).await?;
+
+    if command != CMD_CONNECT {
+        write_socks5_reply(stream, SOCKS5_REPLY_COMMAND_NOT_SUPPORTED).await?;
+        return Err(format!("Unsupported SOCKS command: {}", command));
+    }
+
+    Ok(SocksConnectRequest {
+        version: SocksVersion::V5,
+        host,
+        port,
+    })
+}
+
+async fn accept_socks4<S>(stream: &mut S) -> Result<SocksConnectRequest, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let command = read_u8(stream).await?;
+    let port = read_port(stream).await?;
+    let mut ip = [0u8; 4];
+    read_exact(stream, &mut ip).await?;
+    let _user_id = read_nul_terminated(stream).await?;
+
+    // SOCKS4a: 0.0.0.x (x != 0) means "host name follows the user id".
+    let host = if ip[..3] == [0, 0, 0] && ip[3] != 0 {
+        String::from_utf8(read_nul_terminated(stream).await?)
+            .map_err(|_| "Invalid SOCKS4a host name".to_string())?
+    } else {
+        Ipv4Addr::from(ip).to_string()
+    };
+
+    if command != CMD_CONNECT {
+        write_socks4_reply(stream, SOCKS4_REPLY_REJECTED).await?;
+        return Err(format!("Unsupported SOCKS command: {}", command));
+    }
+
+    Ok(SocksConnectRequest {
+        version: SocksVersion::V4,
+        host,
+        port,
+    })
+}
+
+async fn write_socks5_reply<S: AsyncWrite + Unpin>(stream: &mut S, code: u8) -> Result<(), String> {
+    write_all(
+        stream,
+        &[
+            SOCKS5_VERSION,
+            code,
+            0x00,
+            SOCKS5_ATYP_IPV4,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ],
+    )
+    .await
+}
+
+async fn write_socks4_reply<S: AsyncWrite + Unpin>(stream: &mut S, code: u8) -> Result<(), String> {
+    write_all(stream, &[0x00, code, 0, 0, 0, 0, 0, 0]).await
+}
+
+async fn read_u8<S: AsyncRead + Unpin>(stream: &mut S) -> Result<u8, String> {
+    let mut byte = [0u8; 1];
+    read_exact(stream, &mut byte).await?;
+    Ok(byte[0])
+}
+
+async fn read_port<S: AsyncRead + Unpin>(stream: &mut S) -> Result<u16, String> {
+    let mut port = [0u8; 2];
+    read_exact(stream, &mut port).await?;
+    Ok(u16::from_be_bytes(port))
+}
+
+async fn read_len_prefixed<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>, String> {
+    let len = read_u8(stream).await? as usize;
+    let mut data = vec![0u8; len];
+    read_exact(stream, &mut data).await?;
+    Ok(data)
+}
+
+async fn read_nul_terminated<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    loop {
+        match read_u8(stream).await? {
+            0 => return Ok(data),
+            _ if data.len() >= SOCKS4_MAX_FIELD_LEN => {
+                return Err("SOCKS4 field exceeds the supported length".to_string())
+            }
+            byte => data.push(byte),
+        }
+    }
+}
+
+async fn read_exact<S: AsyncRead + Unpin>(stream: &mut S, buf: &mut [u8]) -> Result<(), String> {
+    stream
+        .read_exact(buf)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("SOCKS read error: {}", e))
+}
+
+async fn write_all<S: AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> Result<(), String> {
+    stream
+        .write_all(data)
+        .await
+        .map_err(|e| format!("SOCKS write error: {}", e))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    fn credentials() -> SocksCredentials {
+        SocksCredentials {
+            username: "alice".to_string(),
+            password: SecretString::new("s3cret".to_string()),
+        }
+    }
+
+    /// Feed `input` to `accept_request`, returning its result and everything
+    /// the server wrote back.
+    async fn negotiate(
+        input: &[u8],
+        credentials: Option<SocksCredentials>,
+    ) -> (Result<SocksConnectRequest, String>, Vec<u8>) {
+        let (mut client, mut server) = duplex(1024);
+        client.write_all(input).await.unwrap();
+        let result = accept_request(&mut server, credentials.as_ref()).await;
+        drop(server);
+        let mut written = Vec::new();
+        client.read_to_end(&mut written).await.unwrap();
+        (result, written)
+    }
+
+    #[tokio::test]
+    async fn socks5_domain_connect_without_auth() {
+        let mut input = vec![5, 1, 0, 5, 1, 0, 3, 11];
+        input.extend_from_slice(b"example.com");
+        input.extend_from_slice(&443u16.to_be_bytes());
+
+        let (result, written) = negotiate(&input, None).await;
+        assert_eq!(
+            result.unwrap(),
+            SocksConnectRequest {
+                version: SocksVersion::V5,
+                host: "example.com".to_string(),
+                port: 443,
+            }
+        );
+        assert_eq!(written, vec![5, 0]);
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_times_out() {
+        let (mut client, mut server) = duplex(1024);
+        // Greeting sent, but the request never follows.
+        client.write_all(&[5, 1, 0]).await.unwrap();
+        let err = accept_request_within(&mut server, None, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn socks5_ipv6_connect() {
+        let mut input = vec![5, 1, 0, 5, 1, 0, 4];
+        input.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        input.extend_from_slice(&22u16.to_be_bytes());
+
+        let (result, _) = negotiate(&input, None).await;
+        assert_eq!(result.unwrap().host, "::1");
+    }
+
+    #[tokio::test]
+    async fn socks5_password_auth() {
+        let mut input = vec![5, 2, 0, 2, 1, 5];
+        input.extend_from_slice(b"alice");
+        input.push(6);
+        input.extend_from_slice(b"s3cret");
+        input.extend_from_slice(&[5, 1, 0, 1, 10, 0, 0, 1, 0, 80]);
+
+        let (result, written) = negotiate(&input, Some(credentials())).await;
+        assert_eq!(result.unwrap().host, "10.0.0.1");
+        assert_eq!(written, vec![5, 2, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn socks5_rejects_bad_password_and_missing_method() {
+        let mut input = vec![5, 1, 2, 1, 5];
+        input.extend_from_slice(b"alice");
+        input.push(5);
+        input.extend_from_slice(b"wrong");
+        let (result, written) = negotiate(&input, Some(credentials())).await;
+        assert!(result.unwrap_err().contains("authentication failed"));
+        assert_eq!(written, vec![5, 2, 1, 1]);
+
+        let (result, written) = negotiate(&[5, 1, 0], Some(credentials())).await;
+        assert!(result.is_err());
+        assert_eq!(written, vec![5, 0xFF]);
+    }
+
+    #[tokio::test]
+    async fn socks5_rejects_non_connect_commands() {
+        let input = [5, 1, 0, 5, 2, 0, 1, 127, 0, 0, 1, 0, 80];
+        let (result, written) = negotiate(&input, None).await;
+        assert!(result.unwrap_err().contains("Unsupported SOCKS command"));
+        assert_eq!(written[2..4], [5, SOCKS5_REPLY_COMMAND_NOT_SUPPORTED]);
+    }
+
+    #[tokio::test]
+    async fn socks4_and_socks4a_connect() {
+        let mut input = vec![4, 1, 0, 80, 192, 168, 1, 5];
+        input.extend_from_slice(b"user\0");
+        let (result, written) = negotiate(&input, None).await;
+        let request = result.unwrap();
+        assert_eq!(request.version, SocksVersion::V4);
+        assert_eq!(request.host, "192.168.1.5");
+        assert!(written.is_empty());
+
+        let mut input = vec![4, 1, 0x1F, 0x90, 0, 0, 0, 1, 0];
+        input.extend_from_slice(b"intranet.local\0");
+        let (result, _) = negotiate(&input, None).await;
+        let request = result.unwrap();
+        assert_eq!(request.host, "intranet.local");
+        assert_eq!(request.port, 8080);
+    }
+
+    #[tokio::test]
+    async fn socks4_is_refused_when_auth_is_required() {
+        let input = [4, 1, 0, 80, 127, 0, 0, 1, 0];
+        let (result, written) = negotiate(&input, Some(credentials())).await;
+        assert!(result.is_err());
+        assert_eq!(written[1], SOCKS4_REPLY_REJECTED);
+    }
+
+    #[tokio::test]
+    async fn replies_use_the_client_version() {
+        let (mut client, mut server) = duplex(64);
+        send_reply(&mut server, SocksVersion::V5, true)
+            .await
+            .unwrap();
+        send_reply(&mut server, SocksVersion::V4, false)
+            .await
+            .unwrap();
+        drop(server);
+        let mut written = Vec::new();
+        client.read_to_end(&mut written).await.unwrap();
+        assert_eq!(written[..2], [5, SOCKS5_REPLY_SUCCEEDED]);
+        assert_eq!(written[10..12], [0, SOCKS4_REPLY_REJECTED]);
+    }
+
+    #[test]
+    fn credentials_from_config() {
+        let secret = |s: &str| Some(SecretString::new(s.to_string()));
+        assert!(SocksCredentials::from_config(None, None).unwrap().is_none());
+        assert!(SocksCredentials::from_config(Some("u".into()), secret("p"))
+            .unwrap()
+            .is_some());
+        assert!(SocksCredentials::from_config(Some("u".into()), None).is_err());
+        assert!(SocksCredentials::from_config(None, secret("p")).is_err());
+        assert!(SocksCredentials::from_config(Some(String::new()), secret("p")).is_err());
+        assert!(SocksCredentials::from_config(Some("u".into()), secret(&"x".repeat(256))).is_err());
+    }
+
+    #[test]
+    fn stats_track_active_connections() {
+        let stats = Arc::new(DynamicForwardStats::default());
+        let guard = stats.connection_opened();
+        let _second = stats.connection_opened();
+        stats.add_sent(10);
+        stats.add_received(20);
+        stats.connection_failed();
+        drop(guard);
+
+        let entry = DynamicTunnelEntry {
+            status: DynamicTunnelStatus {
+                tunnel_id: "t".to_string(),
+                session_id: "s".to_string(),
+                local_port: 1080,
+                bind_address: "127.0.0.1".to_string(),
+                forward_id: "f".to_string(),
+                label: None,
+                auth_required: false,
+                connection_string: "socks5://localhost:1080".to_string(),
+                active_connections: 0,
+                total_connections: 0,
+                failed_connections: 0,
+                bytes_sent: 0,
+                bytes_received: 0,
+                created_at: chrono::Utc::now(),
+            },
+            stats,
+        };
+        let status = entry.snapshot();
+        assert_eq!(status.active_connections, 1);
+        assert_eq!(status.total_connections, 2);
+        assert_eq!(status.failed_connections, 1);
+        assert_eq!((status.bytes_sent, status.bytes_received), (10, 20));
+    }
+}
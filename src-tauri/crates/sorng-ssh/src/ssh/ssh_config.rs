@@ -0,0 +1,1248 @@
+//! # OpenSSH client configuration (`~/.ssh/config`)
+//!
+//! Parses and evaluates `ssh_config(5)` files so that hosts already defined
+//! for the OpenSSH client can be resolved into [`SshConnectionConfig`] or
+//! bulk-imported as saved connections.
+//!
+//! Supported semantics:
+//! - `Host` and `Match` blocks (`all`, `host`, `originalhost`, `user`,
+//!   `localuser`, `canonical`, `final`, `exec`), with `!` negation and the
+//!   `*` / `?` wildcards.
+//! - First-obtained-value-wins for every keyword, except the accumulating
+//!   ones (`IdentityFile`, `CertificateFile`, `SendEnv`, `SetEnv`, forwards).
+//! - `Include` (globbed, relative to `~/.ssh`, nested up to 16 levels); an
+//!   `Include` inside a `Host`/`Match` block only applies when that block
+//!   matches.
+//! - `ProxyJump` (each hop resolved through the same config) and
+//!   `ProxyCommand`, which are mutually exclusive with the first one winning.
+//! - `%%`, `%d`, `%h`, `%n`, `%p`, `%r`, `%u` tokens.
+//!
+//! `Match exec` never runs anything: importing a config must not execute
+//! commands, so such criteria evaluate to false. Imported ProxyCommands keep
+//! `command_confirmed == false` so the import gate in `proxy_command` fires.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use super::service::SshService;
+use super::types::*;
+
+/// Maximum `Include` nesting depth (matches OpenSSH's `READCONF_MAX_DEPTH`).
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Maximum `ProxyJump` nesting when a jump host has its own `ProxyJump`.
+const MAX_JUMP_DEPTH: usize = 8;
+
+/// Keywords whose occurrences accumulate instead of first-wins.
+const MULTI_VALUED_KEYWORDS: &[&str] = &[
+    "identityfile",
+    "certificatefile",
+    "sendenv",
+    "setenv",
+    "localforward",
+    "remoteforward",
+    "dynamicforward",
+];
+
+// ── Parsed representation ─────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HostPattern {
+    pattern: String,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MatchCriterion {
+    All,
+    Canonical,
+    Final,
+    Exec(String),
+    Host(Vec<HostPattern>),
+    OriginalHost(Vec<HostPattern>),
+    User(Vec<HostPattern>),
+    LocalUser(Vec<HostPattern>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NegatableCriterion {
+    criterion: MatchCriterion,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BlockCondition {
+    Host(Vec<HostPattern>),
+    Match(Vec<NegatableCriterion>),
+}
+
+#[derive(Debug, Clone)]
+struct Directive {
+    keyword: String,
+    args: Vec<String>,
+}
+
+/// A run of directives guarded by every condition in `conditions` (the
+/// block's own `Host`/`Match` line plus those of any enclosing `Include`).
+#[derive(Debug, Clone)]
+struct ConfigBlock {
+    conditions: Vec<BlockCondition>,
+    directives: Vec<Directive>,
+}
+
+/// A parsed `ssh_config` file, including everything pulled in via `Include`.
+#[derive(Debug, Clone, Default)]
+pub struct SshClientConfig {
+    blocks: Vec<ConfigBlock>,
+    /// Base directory for relative `Include` paths (normally `~/.ssh`).
+    base_dir: PathBuf,
+}
+
+/// A single `ProxyJump` hop as written in the config.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProxyJumpSpec {
+    pub host: String,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+}
+
+/// The effective settings for one host alias after evaluating every block.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ResolvedSshHost {
+    /// The alias that was resolved (`%n`).
+    pub alias: String,
+    /// The real host name to connect to (`HostName`, or the alias).
+    pub host_name: String,
+    pub port: u16,
+    /// Explicit `User`, if any. Callers fall back to the local user.
+    pub user: Option<String>,
+    /// Token- and tilde-expanded `IdentityFile` entries, in config order.
+    pub identity_files: Vec<String>,
+    pub identities_only: bool,
+    /// Token- and tilde-expanded `CertificateFile` entries, in config order.
+    pub certificate_files: Vec<String>,
+    pub proxy_jump: Vec<ProxyJumpSpec>,
+    /// `ProxyCommand` with everything but the runtime `%h/%p/%r` expanded.
+    pub proxy_command: Option<String>,
+    /// Every other first-obtained keyword (lower-cased) and its arguments.
+    pub options: HashMap<String, Vec<String>>,
+}
+
+impl SshClientConfig {
+    /// Parse config text. Relative `Include` paths resolve against `base_dir`.
+    pub fn parse(content: &str, base_dir: &Path) -> Result<Self, String> {
+        let mut config = SshClientConfig {
+            blocks: Vec::new(),
+            base_dir: base_dir.to_path_buf(),
+        };
+        let mut blocks = Vec::new();
+        config.parse_into(content, "<config>", &[], 0, &mut blocks)?;
+        config.blocks = blocks;
+        Ok(config)
+    }
+
+    /// Load a config file from disk. Relative `Include` paths resolve against
+    /// `~/.ssh` (as OpenSSH does for user configs), or the file's directory
+    /// when no home directory is available.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let base_dir = dirs::home_dir()
+            .map(|home| home.join(".ssh"))
+            .or_else(|| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        Self::parse(&content, &base_dir)
+    }
+
+    /// Load `~/.ssh/config`. A missing file yields an empty config.
+    pub fn load_default() -> Result<Self, String> {
+        match default_config_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    fn parse_into(
+        &self,
+        content: &str,
+        origin: &str,
+        guards: &[BlockCondition],
+        depth: usize,
+        out: &mut Vec<ConfigBlock>,
+    ) -> Result<(), String> {
+        let mut current = ConfigBlock {
+            conditions: guards.to_vec(),
+            directives: Vec::new(),
+        };
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line_no = index + 1;
+            let (keyword, args) = match split_directive(raw_line)
+                .map_err(|e| format!("{}:{}: {}", origin, line_no, e))?
+            {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+
+            match keyword.as_str() {
+                "host" => {
+                    if args.is_empty() {
+                        return Err(format!("{}:{}: Host requires a pattern", origin, line_no));
+                    }
+                    out.push(std::mem::replace(
+                        &mut current,
+                        ConfigBlock {
+                            conditions: with_condition(
+                                guards,
+                                BlockCondition::Host(host_patterns(&args)),
+                            ),
+                            directives: Vec::new(),
+                        },
+                    ));
+                }
+                "match" => {
+                    let criteria = parse_match_criteria(&args)
+                        .map_err(|e| format!("{}:{}: {}", origin, line_no, e))?;
+                    out.push(std::mem::replace(
+                        &mut current,
+                        ConfigBlock {
+                            conditions: with_condition(guards, BlockCondition::Match(criteria)),
+                            directives: Vec::new(),
+                        },
+                    ));
+                }
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        return Err(format!(
+                            "{}:{}: Include nested too deeply (max {})",
+                            origin, line_no, MAX_INCLUDE_DEPTH
+                        ));
+                    }
+                    let conditions = current.conditions.clone();
+                    out.push(std::mem::replace(
+                        &mut current,
+                        ConfigBlock {
+                            conditions: conditions.clone(),
+                            directives: Vec::new(),
+                        },
+                    ));
+                    for arg in &args {
+                        for path in self.include_paths(arg) {
+                            let included = match std::fs::read_to_string(&path) {
+                                Ok(text) => text,
+                                // OpenSSH silently skips unreadable matches.
+                                Err(_) => continue,
+                            };
+                            let name = path.display().to_string();
+                            self.parse_into(&included, &name, &conditions, depth + 1, out)?;
+                        }
+                    }
+                }
+                _ => current.directives.push(Directive { keyword, args }),
+            }
+        }
+
+        out.push(current);
+        Ok(())
+    }
+
+    fn include_paths(&self, pattern: &str) -> Vec<PathBuf> {
+        let expanded = expand_tilde(pattern);
+        let path = Path::new(&expanded);
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base_dir.join(path)
+        };
+        let mut matches = glob_paths(&absolute);
+        matches.sort();
+        matches
+    }
+
+    /// Concrete aliases from `Host` lines (no wildcards or negation), in
+    /// first-seen order. These are the hosts a bulk import turns into
+    /// connections.
+    pub fn host_aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        for block in &self.blocks {
+            let Some(BlockCondition::Host(patterns)) = block.conditions.last() else {
+                continue;
+            };
+            for pattern in patterns {
+                if pattern.negated || pattern.pattern.contains(['*', '?']) {
+                    continue;
+                }
+                if !aliases.iter().any(|a| a == &pattern.pattern) {
+                    aliases.push(pattern.pattern.clone());
+                }
+            }
+        }
+        aliases
+    }
+
+    /// Evaluate every block against `alias` and return the effective settings.
+    pub fn resolve(&self, alias: &str) -> Result<ResolvedSshHost, String> {
+        let local_user = local_username();
+        let mut options: HashMap<String, Vec<String>> = HashMap::new();
+        let mut multi: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+
+        for block in &self.blocks {
+            let ctx = MatchContext {
+                original_host: alias,
+                host: options
+                    .get("hostname")
+                    .and_then(|v| v.first())
+                    .map(|h| expand_hostname(h, alias))
+                    .unwrap_or_else(|| alias.to_string()),
+                user: options
+                    .get("user")
+                    .and_then(|v| v.first())
+                    .cloned()
+                    .unwrap_or_else(|| local_user.clone()),
+                local_user: &local_user,
+            };
+            if !block.conditions.iter().all(|c| ctx.matches(c)) {
+                continue;
+            }
+            for directive in &block.directives {
+                let keyword = directive.keyword.as_str();
+                if MULTI_VALUED_KEYWORDS.contains(&keyword) {
+                    multi
+                        .entry(directive.keyword.clone())
+                        .or_default()
+                        .push(directive.args.clone());
+                    continue;
+                }
+                // ProxyJump and ProxyCommand are mutually exclusive; the
+                // first one obtained wins.
+                let exclusive = match keyword {
+                    "proxyjump" => Some("proxycommand"),
+                    "proxycommand" => Some("proxyjump"),
+                    _ => None,
+                };
+                if exclusive.is_some_and(|other| options.contains_key(other)) {
+                    continue;
+                }
+                options
+                    .entry(directive.keyword.clone())
+                    .or_insert_with(|| directive.args.clone());
+            }
+        }
+
+        let host_name = options
+            .get("hostname")
+            .and_then(|v| v.first())
+            .map(|h| expand_hostname(h, alias))
+            .unwrap_or_else(|| alias.to_string());
+        let port = match options.get("port").and_then(|v| v.first()) {
+            Some(p) => p
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid Port '{}' for host {}", p, alias))?,
+            None => 22,
+        };
+        let user = options.get("user").and_then(|v| v.first()).cloned();
+        let tokens = TokenContext {
+            alias,
+            host: &host_name,
+            port,
+            remote_user: user.as_deref().unwrap_or(&local_user),
+            local_user: &local_user,
+        };
+
+        let path_list = |keyword: &str| -> Vec<String> {
+            multi
+                .get(keyword)
+                .into_iter()
+                .flatten()
+                .filter_map(|args| args.first())
+                .filter(|value| !value.eq_ignore_ascii_case("none"))
+                .map(|value| expand_tilde(&tokens.expand(value, false)))
+                .collect()
+        };
+        let identity_files = path_list("identityfile");
+        let certificate_files = path_list("certificatefile");
+
+        let proxy_jump = match options.get("proxyjump") {
+            Some(args) => parse_proxy_jump(&args.join(" "))?,
+            None => Vec::new(),
+        };
+        let proxy_command = options
+            .get("proxycommand")
+            .map(|args| args.join(" "))
+            .filter(|cmd| !cmd.eq_ignore_ascii_case("none"))
+            .map(|cmd| tokens.expand(&cmd, true));
+
+        let mut remaining = options;
+        for key in ["hostname", "port", "user", "proxyjump", "proxycommand"] {
+            remaining.remove(key);
+        }
+        for (keyword, entries) in multi {
+            if keyword == "identityfile" || keyword == "certificatefile" {
+                continue;
+            }
+            remaining.insert(keyword, entries.into_iter().flatten().collect());
+        }
+        let identities_only = remaining
+            .get("identitiesonly")
+            .and_then(|v| v.first())
+            .is_some_and(|v| v.eq_ignore_ascii_case("yes"));
+
+        Ok(ResolvedSshHost {
+            alias: alias.to_string(),
+            host_name,
+            port,
+            user,
+            identity_files,
+            identities_only,
+            certificate_files,
+            proxy_jump,
+            proxy_command,
+            options: remaining,
+        })
+    }
+
+    /// Resolve `alias` into a ready-to-connect [`SshConnectionConfig`].
+    ///
+    /// `ProxyJump` hops are themselves resolved through this config (their
+    /// `HostName`, `Port`, `User` and `IdentityFile` apply) and flattened into
+    /// `jump_hosts`, with `mixed_chain` built via
+    /// [`SshService::jump_hosts_to_mixed_chain`].
+    pub fn resolve_connection(&self, alias: &str) -> Result<SshConnectionConfig, String> {
+        let resolved = self.resolve(alias)?;
+        let jump_hosts = self.resolve_jump_hosts(&resolved.proxy_jump, 0)?;
+        Ok(resolved.to_connection_config(jump_hosts))
+    }
+
+    fn resolve_jump_hosts(
+        &self,
+        hops: &[ProxyJumpSpec],
+        depth: usize,
+    ) -> Result<Vec<JumpHostConfig>, String> {
+        if hops.is_empty() {
+            return Ok(Vec::new());
+        }
+        if depth >= MAX_JUMP_DEPTH {
+            return Err(format!(
+                "ProxyJump nested too deeply (max {}); check for a loop",
+                MAX_JUMP_DEPTH
+            ));
+        }
+
+        let mut jump_hosts = Vec::new();
+        for hop in hops {
+            let resolved = self.resolve(&hop.host)?;
+            // A jump host's own ProxyJump is traversed before the hop itself.
+            jump_hosts.extend(self.resolve_jump_hosts(&resolved.proxy_jump, depth + 1)?);
+            jump_hosts.push(JumpHostConfig {
+                host: resolved.host_name.clone(),
+                port: hop.port.unwrap_or(resolved.port),
+                username: hop
+                    .user
+                    .clone()
+                    .or_else(|| resolved.user.clone())
+                    .unwrap_or_else(local_username),
+                password: None,
+                private_key_path: resolved.identity_files.first().cloned(),
+                private_key_passphrase: None,
+                certificate_path: resolved.certificate_files.first().cloned(),
+                agent_forwarding: resolved.flag("forwardagent"),
+                totp_secret: None,
+                keyboard_interactive_responses: vec![],
+                preferred_ciphers: resolved.algorithm_list("ciphers"),
+                preferred_macs: resolved.algorithm_list("macs"),
+                preferred_kex: resolved.algorithm_list("kexalgorithms"),
+                preferred_host_key_algorithms: resolved.algorithm_list("hostkeyalgorithms"),
+            });
+        }
+        Ok(jump_hosts)
+    }
+
+    /// Turn every concrete `Host` alias into an app connection record (the
+    /// same JSON shape the other importers produce). Hosts that fail to
+    /// resolve are skipped and reported in the second element.
+    pub fn import_connections(&self) -> (Vec<Value>, Vec<String>) {
+        let mut connections = Vec::new();
+        let mut errors = Vec::new();
+        for alias in self.host_aliases() {
+            match self.alias_to_app_connection(&alias) {
+                Ok(conn) => connections.push(conn),
+                Err(e) => errors.push(format!("{}: {}", alias, e)),
+            }
+        }
+        (connections, errors)
+    }
+
+    fn alias_to_app_connection(&self, alias: &str) -> Result<Value, String> {
+        let resolved = self.resolve(alias)?;
+        let config = self.resolve_connection(alias)?;
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut conn = json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "name": alias,
+            "protocol": "ssh",
+            "hostname": config.host,
+            "port": config.port as u32,
+            "username": config.username,
+            "isGroup": false,
+            "createdAt": now,
+            "updatedAt": now,
+        });
+        let obj = conn.as_object_mut().expect("json! macro creates an Object");
+
+        if let Some(key) = &config.private_key_path {
+            obj.insert("authType".into(), json!("key"));
+            obj.insert("privateKey".into(), json!(key));
+        }
+
+        let mut ssh = serde_json::Map::new();
+        if let Some(timeout) = config.connect_timeout {
+            ssh.insert("connectTimeout".into(), json!(timeout));
+        }
+        if let Some(interval) = config.keep_alive_interval {
+            ssh.insert("keepAliveInterval".into(), json!(interval));
+        }
+        if resolved.options.contains_key("stricthostkeychecking") {
+            ssh.insert(
+                "strictHostKeyChecking".into(),
+                json!(config.strict_host_key_checking),
+            );
+        }
+        if let Some(path) = &config.known_hosts_path {
+            ssh.insert("knownHostsPath".into(), json!(path));
+        }
+        if config.agent_forwarding {
+            ssh.insert("agentForwarding".into(), json!(true));
+        }
+        if config.compression {
+            ssh.insert("enableCompression".into(), json!(true));
+        }
+        for (key, list) in [
+            ("preferredCiphers", &config.preferred_ciphers),
+            ("preferredMACs", &config.preferred_macs),
+            ("preferredKeyExchanges", &config.preferred_kex),
+            (
+                "preferredHostKeyAlgorithms",
+                &config.preferred_host_key_algorithms,
+            ),
+        ] {
+            if !list.is_empty() {
+                ssh.insert(key.into(), json!(list));
+            }
+        }
+        if !config.environment.is_empty() {
+            ssh.insert("environment".into(), json!(config.environment));
+        }
+        // The command is carried over verbatim but NOT confirmed: it came
+        // from an external file, so the spawn-time import gate must fire.
+        if let Some(cmd) = config
+            .proxy_command
+            .as_ref()
+            .and_then(|p| p.command.as_ref())
+        {
+            ssh.insert("proxyCommand".into(), json!(cmd));
+        }
+        if !ssh.is_empty() {
+            obj.insert("sshConnectionConfigOverride".into(), Value::Object(ssh));
+        }
+
+        if let Some(chain) = &config.mixed_chain {
+            let layers: Vec<Value> = chain
+                .hops
+                .iter()
+                .filter_map(|hop| match hop {
+                    ChainHop::SshJump(jump) => Some(jump_to_tunnel_layer(jump)),
+                    ChainHop::Proxy(_) => None,
+                })
+                .collect();
+            obj.insert("security".into(), json!({ "tunnelChain": layers }));
+        }
+
+        Ok(conn)
+    }
+}
+
+impl ResolvedSshHost {
+    fn first(&self, keyword: &str) -> Option<&str> {
+        self.options
+            .get(keyword)
+            .and_then(|v| v.first())
+            .map(String::as_str)
+    }
+
+    fn flag(&self, keyword: &str) -> bool {
+        self.first(keyword)
+            .is_some_and(|v| v.eq_ignore_ascii_case("yes"))
+    }
+
+    /// An explicit algorithm list. `+`/`-`/`^` forms modify OpenSSH's
+    /// built-in defaults, which we do not mirror, so they are ignored.
+    fn algorithm_list(&self, keyword: &str) -> Vec<String> {
+        match self.first(keyword) {
+            Some(list) if !list.starts_with(['+', '-', '^']) => list
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn seconds(&self, keyword: &str) -> Option<u64> {
+        self.first(keyword).and_then(|v| v.parse::<u64>().ok())
+    }
+
+    /// Map onto [`SshConnectionConfig`] using already-resolved jump hosts.
+    pub fn to_connection_config(&self, jump_hosts: Vec<JumpHostConfig>) -> SshConnectionConfig {
+        let (strict_host_key_checking, accept_new_host_keys) =
+            match self.first("stricthostkeychecking") {
+                Some(v) if v.eq_ignore_ascii_case("no") || v.eq_ignore_ascii_case("off") => {
+                    (false, false)
+                }
+                Some(v) if v.eq_ignore_ascii_case("accept-new") => (true, true),
+                _ => (true, false),
+            };
+        let ip_protocol = match self.first("addressfamily") {
+            Some("inet") => "ipv4",
+            Some("inet6") => "ipv6",
+            _ => "auto",
+        };
+        let environment = self
+            .options
+            .get("setenv")
+            .into_iter()
+            .flatten()
+            .filter_map(|pair| pair.split_once('='))
+            .fold(HashMap::new(), |mut env, (name, value)| {
+                env.entry(name.to_string())
+                    .or_insert_with(|| value.to_string());
+                env
+            });
+        let connect_timeout = self.seconds("connecttimeout");
+        let proxy_command = self.proxy_command.as_ref().map(|cmd| ProxyCommandConfig {
+            command: Some(cmd.clone()),
+            template: None,
+            proxy_host: None,
+            proxy_port: None,
+            proxy_username: None,
+            proxy_password: None,
+            proxy_type: None,
+            timeout_secs: connect_timeout,
+            command_confirmed: false,
+        });
+        let mixed_chain = if jump_hosts.is_empty() {
+            None
+        } else {
+            Some(SshService::jump_hosts_to_mixed_chain(&jump_hosts))
+        };
+
+        SshConnectionConfig {
+            host: self.host_name.clone(),
+            port: self.port,
+            username: self.user.clone().unwrap_or_else(local_username),
+            password: None,
+            private_key_path: self.identity_files.first().cloned(),
+            private_key_passphrase: None,
+            certificate_path: self.certificate_files.first().cloned(),
+            jump_hosts,
+            proxy_config: None,
+            proxy_chain: None,
+            mixed_chain,
+            openvpn_config: None,
+            connect_timeout,
+            keep_alive_interval: self.seconds("serveraliveinterval"),
+            strict_host_key_checking,
+            accept_new_host_keys,
+            known_hosts_path: self
+                .first("userknownhostsfile")
+                .filter(|v| !v.eq_ignore_ascii_case("none"))
+                .map(expand_tilde),
+            totp_secret: None,
+            keyboard_interactive_responses: vec![],
+            agent_forwarding: self.flag("forwardagent"),
+            tcp_no_delay: true,
+            tcp_keepalive: !self
+                .first("tcpkeepalive")
+                .is_some_and(|v| v.eq_ignore_ascii_case("no")),
+            keepalive_probes: self
+                .first("serveralivecountmax")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_keepalive_probes),
+            ip_protocol: ip_protocol.to_string(),
+            compression: self.flag("compression"),
+            compression_level: default_compression_level(),
+            compression_config: default_compression_config(),
+            ssh_version: default_ssh_version(),
+            preferred_ciphers: self.algorithm_list("ciphers"),
+            preferred_macs: self.algorithm_list("macs"),
+            preferred_kex: self.algorithm_list("kexalgorithms"),
+            preferred_host_key_algorithms: self.algorithm_list("hostkeyalgorithms"),
+            x11_forwarding: self.flag("forwardx11").then(|| X11ForwardingConfig {
+                enabled: true,
+                trusted: self.flag("forwardx11trusted"),
+                ..Default::default()
+            }),
+            proxy_command,
+            pty_type: None,
+            environment,
+            sk_auth: false,
+            sk_device_path: None,
+            sk_pin: None,
+            sk_application: None,
+        }
+    }
+}
+
+/// Path of the user's OpenSSH client config (`~/.ssh/config`).
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+fn jump_to_tunnel_layer(jump: &JumpHostConfig) -> Value {
+    let mut tunnel = serde_json::Map::new();
+    tunnel.insert("host".into(), json!(jump.host));
+    tunnel.insert("port".into(), json!(jump.port));
+    tunnel.insert("username".into(), json!(jump.username));
+    if let Some(key) = &jump.private_key_path {
+        tunnel.insert("privateKey".into(), json!(key));
+    }
+    if jump.agent_forwarding {
+        tunnel.insert("agentForwarding".into(), json!(true));
+    }
+    tunnel.insert("forwardType".into(), json!("local"));
+
+    json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "type": "ssh-jump",
+        "enabled": true,
+        "name": format!("ProxyJump {}@{}:{}", jump.username, jump.host, jump.port),
+        "sshChainingMethod": "proxyjump",
+        "sshTunnel": Value::Object(tunnel),
+    })
+}
+
+// ── Matching ──────────────────────────────────────────────────────────
+
+struct MatchContext<'a> {
+    original_host: &'a str,
+    host: String,
+    user: String,
+    local_user: &'a str,
+}
+
+impl MatchContext<'_> {
+    fn matches(&self, condition: &BlockCondition) -> bool {
+        match condition {
+            BlockCondition::Host(patterns) => match_pattern_list(self.original_host, patterns),
+            BlockCondition::Match(criteria) => criteria.iter().all(|c| {
+                let hit = match &c.criterion {
+                    MatchCriterion::All | MatchCriterion::Final => true,
+                    MatchCriterion::Canonical | MatchCriterion::Exec(_) => false,
+                    MatchCriterion::Host(p) => match_pattern_list(&self.host, p),
+                    MatchCriterion::OriginalHost(p) => match_pattern_list(self.original_host, p),
+                    MatchCriterion::User(p) => match_pattern_list(&self.user, p),
+                    MatchCriterion::LocalUser(p) => match_pattern_list(self.local_user, p),
+                };
+                hit != c.negated
+            }),
+        }
+    }
+}
+
+/// OpenSSH pattern-list semantics: any negated match vetoes, otherwise at
+/// least one positive pattern must match. Host matching is case-insensitive.
+fn match_pattern_list(value: &str, patterns: &[HostPattern]) -> bool {
+    let value = value.to_ascii_lowercase();
+    let mut matched = false;
+    for p in patterns {
+        if wildcard_match(&p.pattern.to_ascii_lowercase(), &value) {
+            if p.negated {
+                return false;
+            }
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// `*` / `?` glob match over bytes.
+pub(crate) fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let (p, v) = (pattern.as_bytes(), value.as_bytes());
+    let (mut pi, mut vi) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while vi < v.len() {
+        if pi < p.len() && (p[pi] == b'?' || p[pi] == v[vi]) {
+            pi += 1;
+            vi += 1;
+        } else if pi < p.len() && p[pi] == b'*' {
+            backtrack = Some((pi, vi));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            vi = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == b'*')
+}
+
+// ── Parsing helpers ───────────────────────────────────────────────────
+
+fn with_condition(guards: &[BlockCondition], condition: BlockCondition) -> Vec<BlockCondition> {
+    let mut conditions = guards.to_vec();
+    conditions.push(condition);
+    conditions
+}
+
+fn host_patterns(args: &[String]) -> Vec<HostPattern> {
+    args.iter()
+        .flat_map(|arg| arg.split(','))
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.strip_prefix('!') {
+            Some(rest) => HostPattern {
+                pattern: rest.to_string(),
+                negated: true,
+            },
+            None => HostPattern {
+                pattern: p.to_string(),
+                negated: false,
+            },
+        })
+        .collect()
+}
+
+fn parse_match_criteria(args: &[String]) -> Result<Vec<NegatableCriterion>, String> {
+    if args.is_empty() {
+        return Err("Match requires at least one criterion".to_string());
+    }
+    let mut criteria = Vec::new();
+    let mut iter = args.iter();
+    while let Some(word) = iter.next() {
+        let (negated, name) = match word.strip_prefix('!') {
+            Some(rest) => (true, rest.to_ascii_lowercase()),
+            None => (false, word.to_ascii_lowercase()),
+        };
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("Match {} requires an argument", name))
+        };
+        let criterion = match name.as_str() {
+            "all" => MatchCriterion::All,
+            "canonical" => MatchCriterion::Canonical,
+            "final" => MatchCriterion::Final,
+            "exec" => MatchCriterion::Exec(value("exec")?),
+            "host" => MatchCriterion::Host(host_patterns(&[value("host")?])),
+            "originalhost" => {
+                MatchCriterion::OriginalHost(host_patterns(&[value("originalhost")?]))
+            }
+            "user" => MatchCriterion::User(host_patterns(&[value("user")?])),
+            "localuser" => MatchCriterion::LocalUser(host_patterns(&[value("localuser")?])),
+            other => return Err(format!("Unsupported Match criterion '{}'", other)),
+        };
+        criteria.push(NegatableCriterion { criterion, negated });
+    }
+    Ok(criteria)
+}
+
+/// Split one config line into a lower-cased keyword and its arguments.
+/// Handles `Keyword=value`, double-quoted arguments and trailing comments.
+fn split_directive(line: &str) -> Result<Option<(String, Vec<String>)>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let key_end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..key_end].to_ascii_lowercase();
+    let mut rest = line[key_end..].trim_start();
+    if let Some(stripped) = rest.strip_prefix('=') {
+        rest = stripped.trim_start();
+    }
+
+    let mut args = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '#' {
+            break;
+        }
+        let mut arg = String::new();
+        if c == '"' {
+            chars.next();
+            let mut closed = false;
+            for ch in chars.by_ref() {
+                if ch == '"' {
+                    closed = true;
+                    break;
+                }
+                arg.push(ch);
+            }
+            if !closed {
+                return Err("Unterminated quoted argument".to_string());
+            }
+        } else {
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                arg.push(ch);
+                chars.next();
+            }
+        }
+        args.push(arg);
+    }
+    Ok(Some((keyword, args)))
+}
+
+/// Parse a `ProxyJump` value: comma-separated `[user@]host[:port]` or
+/// `ssh://[user@]host[:port]`, with `[v6addr]:port` bracketing. `none`
+/// disables jumping.
+fn parse_proxy_jump(value: &str) -> Result<Vec<ProxyJumpSpec>, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|hop| !hop.is_empty())
+        .map(|hop| {
+            let hop = hop.strip_prefix("ssh://").unwrap_or(hop);
+            let (user, host_port) = match hop.rsplit_once('@') {
+                Some((user, rest)) => (Some(user.to_string()), rest),
+                None => (None, hop),
+            };
+            let (host, port) = if let Some(rest) = host_port.strip_prefix('[') {
+                let (host, tail) = rest
+                    .split_once(']')
+                    .ok_or_else(|| format!("Invalid ProxyJump hop '{}'", hop))?;
+                (host, tail.strip_prefix(':'))
+            } else {
+                match host_port.split_once(':') {
+                    Some((host, port)) => (host, Some(port)),
+                    None => (host_port, None),
+                }
+            };
+            if host.is_empty() {
+                return Err(format!("Invalid ProxyJump hop '{}'", hop));
+            }
+            let port = port
+                .map(|p| {
+                    p.parse::<u16>()
+                        .map_err(|_| format!("Invalid ProxyJump port in '{}'", hop))
+                })
+                .transpose()?;
+            Ok(ProxyJumpSpec {
+                host: host.to_string(),
+                port,
+                user,
+            })
+        })
+        .collect()
+}
+
+// ── Token / path expansion ────────────────────────────────────────────
+
+struct TokenContext<'a> {
+    alias: &'a str,
+    host: &'a str,
+    port: u16,
+    remote_user: &'a str,
+    local_user: &'a str,
+}
+
+impl TokenContext<'_> {
+    /// Expand `%` tokens. With `keep_runtime`, `%h`, `%p` and `%r` are left
+    /// for `proxy_command::expand_command` to substitute (and shell-quote)
+    /// at connect time. Unknown tokens are kept verbatim.
+    fn expand(&self, input: &str, keep_runtime: bool) -> String {
+        let mut out = String::with_capacity(input.len());
+        let mut chars = input.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => out.push('%'),
+                Some(t @ ('h' | 'p' | 'r')) if keep_runtime => {
+                    out.push('%');
+                    out.push(t);
+                }
+                Some('h') => out.push_str(self.host),
+                Some('p') => out.push_str(&self.port.to_string()),
+                Some('r') => out.push_str(self.remote_user),
+                Some('n') => out.push_str(self.alias),
+                Some('u') => out.push_str(self.local_user),
+                Some('d') => out.push_str(&home_dir_string()),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+/// `HostName` only accepts `%h` (the alias) and `%%`.
+fn expand_hostname(value: &str, alias: &str) -> String {
+    value
+        .replace("%%", "\u{0}")
+        .replace("%h", alias)
+        .replace('\u{0}', "%")
+}
+
+fn expand_tilde(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{}", home_dir_string(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn home_dir_string() -> String {
+    dirs::home_dir()
+        .map(|h| h.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn local_username() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Expand `*` / `?` in any path component by listing directories.
+fn glob_paths(pattern: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !part.contains(['*', '?']) {
+            for candidate in &mut candidates {
+                candidate.push(component.as_os_str());
+            }
+            continue;
+        }
+        let mut next = Vec::new();
+        for dir in &candidates {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // Like shell globs, wildcards do not match dotfiles.
+                if !name.starts_with('.') && wildcard_match(&part, &name) {
+                    next.push(dir.join(&name));
+                }
+            }
+        }
+        candidates = next;
+    }
+    candidates.into_iter().filter(|p| p.is_file()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> SshClientConfig {
+        SshClientConfig::parse(content, Path::new("/nonexistent")).expect("config parses")
+    }
+
+    #[test]
+    fn first_obtained_value_wins() {
+        let cfg = parse(
+            "Host web\n  HostName web.example.com\n  User deploy\n\
+             Host *\n  User fallback\n  Port 2222\n",
+        );
+        let r = cfg.resolve("web").unwrap();
+        assert_eq!(r.host_name, "web.example.com");
+        assert_eq!(r.user.as_deref(), Some("deploy"));
+        assert_eq!(r.port, 2222);
+
+        let other = cfg.resolve("db").unwrap();
+        assert_eq!(other.host_name, "db");
+        assert_eq!(other.user.as_deref(), Some("fallback"));
+    }
+
+    #[test]
+    fn negated_patterns_veto_a_block() {
+        let cfg = parse("Host *.corp !bastion.corp\n  User corp\n");
+        assert_eq!(
+            cfg.resolve("app.corp").unwrap().user.as_deref(),
+            Some("corp")
+        );
+        assert_eq!(cfg.resolve("bastion.corp").unwrap().user, None);
+    }
+
+    #[test]
+    fn match_host_uses_resolved_hostname() {
+        let cfg = parse(
+            "Host db\n  HostName db.internal\n\
+             Match host *.internal\n  Port 2200\n\
+             Match originalhost db exec \"true\"\n  User never\n",
+        );
+        let r = cfg.resolve("db").unwrap();
+        assert_eq!(r.port, 2200);
+        assert_eq!(r.user, None, "Match exec must never be treated as matching");
+    }
+
+    #[test]
+    fn identity_files_accumulate_and_expand_tokens() {
+        let cfg = parse(
+            "Host git\n  HostName github.com\n  User git\n  IdentitiesOnly yes\n\
+             \x20 IdentityFile /keys/%n_%r@%h\n  CertificateFile /keys/%n-cert.pub\n\
+             Host *\n  IdentityFile /keys/default\n",
+        );
+        let r = cfg.resolve("git").unwrap();
+        assert!(r.identities_only);
+        assert_eq!(
+            r.identity_files,
+            vec![
+                "/keys/git_git@github.com".to_string(),
+                "/keys/default".into()
+            ]
+        );
+        assert_eq!(r.certificate_files, vec!["/keys/git-cert.pub".to_string()]);
+    }
+
+    #[test]
+    fn proxy_command_keeps_runtime_tokens_and_stays_unconfirmed() {
+        let cfg = parse("Host box\n  ProxyCommand ssh -W %h:%p gw # via gw\n  ProxyJump ignored\n");
+        let conn = cfg.resolve_connection("box").unwrap();
+        let proxy = conn.proxy_command.expect("proxy command mapped");
+        assert_eq!(proxy.command.as_deref(), Some("ssh -W %h:%p gw"));
+        assert!(!proxy.command_confirmed);
+        assert!(
+            conn.jump_hosts.is_empty(),
+            "ProxyCommand obtained first wins"
+        );
+    }
+
+    #[test]
+    fn proxy_jump_hops_resolve_through_config() {
+        let cfg = parse(
+            "Host target\n  HostName 10.0.0.5\n  ProxyJump ops@bastion:2022,[fd00::1]:22\n\
+             Host bastion\n  HostName bastion.example.com\n  User nobody\n  Port 22\n\
+             \x20 IdentityFile /keys/bastion\n  ProxyJump edge\n\
+             Host edge\n  HostName edge.example.com\n  User edgeuser\n",
+        );
+        let conn = cfg.resolve_connection("target").unwrap();
+        let hops: Vec<(&str, u16, &str)> = conn
+            .jump_hosts
+            .iter()
+            .map(|j| (j.host.as_str(), j.port, j.username.as_str()))
+            .collect();
+        assert_eq!(hops[0], ("edge.example.com", 22, "edgeuser"));
+        assert_eq!(hops[1], ("bastion.example.com", 2022, "ops"));
+        assert_eq!(hops[2].0, "fd00::1");
+        assert_eq!(
+            conn.jump_hosts[1].private_key_path.as_deref(),
+            Some("/keys/bastion")
+        );
+        let chain = conn.mixed_chain.expect("mixed chain built from jump hosts");
+        assert_eq!(chain.hops.len(), 3);
+        assert!(matches!(chain.hops[0], ChainHop::SshJump(_)));
+    }
+
+    #[test]
+    fn proxy_jump_loops_are_rejected() {
+        let cfg = parse("Host a\n  ProxyJump b\nHost b\n  ProxyJump a\n");
+        let err = cfg.resolve_connection("a").unwrap_err();
+        assert!(err.contains("ProxyJump nested too deeply"));
+    }
+
+    #[test]
+    fn include_applies_only_inside_matching_block() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("extra.conf"), "Port 2022\nUser included\n").unwrap();
+        let content = "Host special\n  Include conf.d/../extra.*\nHost *\n  Port 22\n";
+        std::fs::create_dir(dir.path().join("conf.d")).unwrap();
+        let cfg = SshClientConfig::parse(content, dir.path()).unwrap();
+
+        let special = cfg.resolve("special").unwrap();
+        assert_eq!(special.port, 2022);
+        assert_eq!(special.user.as_deref(), Some("included"));
+        assert_eq!(cfg.resolve("other").unwrap().port, 22);
+    }
+
+    #[test]
+    fn connection_config_maps_common_options() {
+        let cfg = parse(
+            "Host app\n  HostName=app.example.com\n  StrictHostKeyChecking accept-new\n\
+             \x20 ForwardAgent yes\n  Compression yes\n  ConnectTimeout 7\n\
+             \x20 ServerAliveInterval 15\n  Ciphers aes256-gcm@openssh.com,aes128-ctr\n\
+             \x20 MACs +hmac-sha1\n  SetEnv LANG=C TERM=xterm\n  AddressFamily inet6\n",
+        );
+        let conn = cfg.resolve_connection("app").unwrap();
+        assert_eq!(conn.host, "app.example.com");
+        assert!(conn.strict_host_key_checking && conn.accept_new_host_keys);
+        assert!(conn.agent_forwarding && conn.compression);
+        assert_eq!(conn.connect_timeout, Some(7));
+        assert_eq!(conn.keep_alive_interval, Some(15));
+        assert_eq!(conn.preferred_ciphers.len(), 2);
+        assert!(conn.preferred_macs.is_empty());
+        assert_eq!(
+            conn.environment.get("TERM").map(String::as_str),
+            Some("xterm")
+        );
+        assert_eq!(conn.ip_protocol, "ipv6");
+    }
+
+    #[test]
+    fn import_produces_app_connections_for_concrete_aliases() {
+        let cfg = parse(
+            "Host web web-alias *.wild\n  HostName web.example.com\n  IdentityFile /k/web\n\
+             \x20 ProxyJump gw\n\
+             Host gw\n  User jump\n\
+             Host broken\n  Port notaport\n",
+        );
+        assert_eq!(cfg.host_aliases(), vec!["web", "web-alias", "gw", "broken"]);
+
+        let (conns, errors) = cfg.import_connections();
+        assert_eq!(conns.len(), 3);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("broken:"));
+
+        let web = &conns[0];
+        assert_eq!(web["protocol"], "ssh");
+        assert_eq!(web["hostname"], "web.example.com");
+        assert_eq!(web["authType"], "key");
+        assert_eq!(web["privateKey"], "/k/web");
+        let layer = &web["security"]["tunnelChain"][0];
+        assert_eq!(layer["type"], "ssh-jump");
+        assert_eq!(layer["sshTunnel"]["host"], "gw");
+        assert_eq!(layer["sshTunnel"]["username"], "jump");
+    }
+
+    #[test]
+    fn split_directive_handles_quotes_equals_and_comments() {
+        assert_eq!(split_directive("   # comment").unwrap(), None);
+        let (kw, args) = split_directive("IdentityFile \"/path with space/key\" # c")
+            .unwrap()
+            .unwrap();
+        assert_eq!(kw, "identityfile");
+        assert_eq!(args, vec!["/path with space/key".to_string()]);
+        assert!(split_directive("User \"unterminated").is_err());
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(wildcard_match("*.example.com", "a.b.example.com"));
+        assert!(wildcard_match("host?", "host1"));
+        assert!(!wildcard_match("host?", "host12"));
+        assert!(wildcard_match("*", ""));
+    }
+}
@@ -0,0 +1,36 @@
+use super::ssh_config::*;
+use super::types::*;
+
+fn load_ssh_client_config(path: Option<String>) -> Result<SshClientConfig, String> {
+    match path {
+        Some(path) => SshClientConfig::load(std::path::Path::new(&path)),
+        None => SshClientConfig::load_default(),
+    }
+}
+
+/// List the concrete host aliases defined in an OpenSSH client config
+/// (`~/.ssh/config` when `path` is omitted).
+#[tauri::command]
+pub fn list_ssh_config_hosts(path: Option<String>) -> Result<Vec<String>, String> {
+    Ok(load_ssh_client_config(path)?.host_aliases())
+}
+
+/// Resolve a host alias from an OpenSSH client config into a connection config.
+#[tauri::command]
+pub fn resolve_ssh_config_host(
+    alias: String,
+    path: Option<String>,
+) -> Result<SshConnectionConfig, String> {
+    load_ssh_client_config(path)?.resolve_connection(&alias)
+}
+
+/// Import every concrete host of an OpenSSH client config as app connections.
+///
+/// Returns the connection records plus one message per host that could not
+/// be resolved. Imported ProxyCommands are left unconfirmed.
+#[tauri::command]
+pub fn import_ssh_config(
+    path: Option<String>,
+) -> Result<(Vec<serde_json::Value>, Vec<String>), String> {
+    Ok(load_ssh_client_config(path)?.import_connections())
+}
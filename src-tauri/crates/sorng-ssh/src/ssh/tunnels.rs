@@ -1,11 +1,10 @@
-
 // ─── Tunnel utility functions ────────────────────────────────────────
 // Pure helpers that query global tunnel state without requiring Tauri.
 // These are re-exported via `pub use tunnels::*` so tests and other
 // crate code can call them directly.
 
 use super::types::*;
-use super::{FTP_TUNNELS, RDP_TUNNELS, VNC_TUNNELS};
+use super::{DYNAMIC_TUNNELS, FTP_TUNNELS, RDP_TUNNELS, VNC_TUNNELS};
 
 // ===============================
 // FTP Tunnel Queries
@@ -192,6 +191,40 @@ pub fn list_session_vnc_tunnels(session_id: String) -> Result<Vec<VncTunnelStatu
         .collect())
 }
 
+// ===============================
+// Dynamic (SOCKS) Tunnel Queries
+// ===============================
+
+/// Get status of a dynamic tunnel, including live connection and byte counts
+pub fn get_dynamic_tunnel_status(tunnel_id: String) -> Result<Option<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels.get(&tunnel_id).map(|t| t.snapshot()))
+}
+
+/// List all active dynamic tunnels
+pub fn list_dynamic_tunnels() -> Result<Vec<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels.values().map(|t| t.snapshot()).collect())
+}
+
+/// List dynamic tunnels for a specific SSH session
+pub fn list_session_dynamic_tunnels(
+    session_id: String,
+) -> Result<Vec<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels
+        .values()
+        .filter(|t| t.status.session_id == session_id)
+        .map(|t| t.snapshot())
+        .collect())
+}
+
 // ===============================
 // VNC over SSH Tunnel Commands
 // ===============================
@@ -568,3 +568,154 @@ pub fn list_session_vnc_tunnels(session_id: String) -> Result<Vec<VncTunnelStatu
         .cloned()
         .collect())
 }
+
+// ===============================
+// Dynamic (SOCKS) Tunnel Commands
+// ===============================
+
+/// Setup a dynamic (SOCKS4a/SOCKS5) tunnel over SSH, the equivalent of `ssh -D`
+#[tauri::command]
+pub async fn setup_dynamic_tunnel(
+    state: tauri::State<'_, SshServiceState>,
+    session_id: String,
+    config: DynamicTunnelConfig,
+) -> Result<DynamicTunnelStatus, String> {
+    let credentials = SocksCredentials::from_config(config.username, config.password)?;
+    let auth_required = credentials.is_some();
+
+    let mut ssh = state.lock().await;
+
+    let local_port = config.local_port.unwrap_or(1080);
+    let bind_interface = config
+        .bind_interface
+        .clone()
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+
+    let forward_config = PortForwardConfig {
+        local_host: bind_interface.clone(),
+        local_port,
+        remote_host: String::new(),
+        remote_port: 0,
+        direction: PortForwardDirection::Dynamic,
+        allow_non_loopback_bind: config.allow_non_loopback_bind,
+    };
+
+    let stats = std::sync::Arc::new(DynamicForwardStats::default());
+    let options = DynamicForwardOptions {
+        credentials,
+        stats: stats.clone(),
+    };
+    let forward_id = ssh
+        .setup_dynamic_forward(&session_id, forward_config, options)
+        .await?;
+
+    let actual_port = ssh
+        .sessions
+        .get(&session_id)
+        .and_then(|s| s.port_forwards.get(&forward_id))
+        .map(|pf| pf.config.local_port)
+        .unwrap_or(local_port);
+
+    let tunnel_id = format!("socks_{}", Uuid::new_v4());
+    let connection_string = if bind_interface == "127.0.0.1" || bind_interface == "localhost" {
+        format!("socks5://localhost:{}", actual_port)
+    } else {
+        format!("socks5://{}:{}", bind_interface, actual_port)
+    };
+
+    let status = DynamicTunnelStatus {
+        tunnel_id: tunnel_id.clone(),
+        session_id: session_id.clone(),
+        local_port: actual_port,
+        bind_address: bind_interface,
+        forward_id,
+        label: config.label,
+        auth_required,
+        connection_string: connection_string.clone(),
+        active_connections: 0,
+        total_connections: 0,
+        failed_connections: 0,
+        bytes_sent: 0,
+        bytes_received: 0,
+        created_at: Utc::now(),
+    };
+
+    if let Ok(mut tunnels) = DYNAMIC_TUNNELS.lock() {
+        tunnels.insert(
+            tunnel_id.clone(),
+            DynamicTunnelEntry {
+                status: status.clone(),
+                stats,
+            },
+        );
+    }
+
+    log::info!(
+        "Dynamic tunnel {} created: {}",
+        tunnel_id,
+        connection_string
+    );
+
+    Ok(status)
+}
+
+/// Stop a dynamic tunnel
+#[tauri::command]
+pub async fn stop_dynamic_tunnel(
+    state: tauri::State<'_, SshServiceState>,
+    tunnel_id: String,
+) -> Result<(), String> {
+    let tunnel = {
+        let mut tunnels = DYNAMIC_TUNNELS
+            .lock()
+            .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+        tunnels
+            .remove(&tunnel_id)
+            .ok_or("Dynamic tunnel not found")?
+    };
+
+    let mut ssh = state.lock().await;
+
+    if let Err(e) = ssh
+        .stop_port_forward(&tunnel.status.session_id, &tunnel.status.forward_id)
+        .await
+    {
+        log::warn!("Failed to stop dynamic port forward: {}", e);
+    }
+
+    log::info!("Dynamic tunnel {} stopped", tunnel_id);
+    Ok(())
+}
+
+/// Get status of a dynamic tunnel, including live connection and byte counts
+#[tauri::command]
+pub fn get_dynamic_tunnel_status(tunnel_id: String) -> Result<Option<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels.get(&tunnel_id).map(|t| t.snapshot()))
+}
+
+/// List all active dynamic tunnels
+#[tauri::command]
+pub fn list_dynamic_tunnels() -> Result<Vec<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels.values().map(|t| t.snapshot()).collect())
+}
+
+/// List dynamic tunnels for a specific SSH session
+#[tauri::command]
+pub fn list_session_dynamic_tunnels(
+    session_id: String,
+) -> Result<Vec<DynamicTunnelStatus>, String> {
+    let tunnels = DYNAMIC_TUNNELS
+        .lock()
+        .map_err(|e| format!("Failed to lock tunnels: {}", e))?;
+    Ok(tunnels
+        .values()
+        .filter(|t| t.status.session_id == session_id)
+        .map(|t| t.snapshot())
+        .collect())
+}
@@ -270,6 +270,10 @@ pub struct SshConnectionConfig {
     pub private_key_path: Option<String>,
     #[serde(skip_serializing, default)]
     pub private_key_passphrase: Option<SecretString>,
+    /// OpenSSH user certificate offered with the private key; defaults to
+    /// `<private_key_path>-cert.pub` when that file exists
+    #[serde(default)]
+    pub certificate_path: Option<String>,
     pub jump_hosts: Vec<JumpHostConfig>,
     pub proxy_config: Option<ProxyConfig>,
     /// Proxy chain for routing through multiple proxies
@@ -416,6 +420,9 @@ pub struct JumpHostConfig {
     /// Passphrase for the private key (if encrypted)
     #[serde(skip_serializing, default)]
     pub private_key_passphrase: Option<SecretString>,
+    /// OpenSSH user certificate for this hop (see `SshConnectionConfig`)
+    #[serde(default)]
+    pub certificate_path: Option<String>,
     /// Enable SSH agent forwarding through this hop
     #[serde(default)]
     pub agent_forwarding: bool,
@@ -449,7 +456,7 @@ pub struct JumpHostConfig {
 pub enum ChainHop {
     /// SSH jump host hop – full SSH session + channel_direct_tcpip
     #[serde(rename = "ssh_jump")]
-    SshJump(JumpHostConfig),
+    SshJump(Box<JumpHostConfig>),
     /// Proxy hop – SOCKS4, SOCKS5, HTTP CONNECT, or HTTPS CONNECT
     #[serde(rename = "proxy")]
     Proxy(ProxyConfig),
@@ -1012,6 +1019,51 @@ pub struct VncTunnelStatus {
     pub created_at: DateTime<Utc>,
 }
 
+/// Configuration for a dynamic (SOCKS4a/SOCKS5) forward over SSH
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DynamicTunnelConfig {
+    /// Local SOCKS port (default: 1080)
+    pub local_port: Option<u16>,
+    /// Optional: Restrict to specific network interface (default: 127.0.0.1)
+    pub bind_interface: Option<String>,
+    /// Allow a non-loopback `bind_interface` (see `PortForwardConfig`)
+    #[serde(default)]
+    pub allow_non_loopback_bind: bool,
+    /// Require SOCKS5 username/password authentication with this username
+    pub username: Option<String>,
+    /// Password for SOCKS5 authentication (required with `username`)
+    #[serde(skip_serializing, default)]
+    pub password: Option<SecretString>,
+    /// Optional description/label for this tunnel
+    pub label: Option<String>,
+}
+
+/// Dynamic (SOCKS) tunnel status
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DynamicTunnelStatus {
+    pub tunnel_id: String,
+    pub session_id: String,
+    pub local_port: u16,
+    pub bind_address: String,
+    pub forward_id: String,
+    pub label: Option<String>,
+    /// Whether clients must authenticate (SOCKS5 username/password)
+    pub auth_required: bool,
+    /// Proxy URL to configure in clients, e.g. `socks5://localhost:1080`
+    pub connection_string: String,
+    /// Clients currently being relayed
+    pub active_connections: u32,
+    /// Clients accepted since the tunnel started
+    pub total_connections: u64,
+    /// Clients rejected during the handshake or whose channel failed to open
+    pub failed_connections: u64,
+    /// Bytes relayed from SOCKS clients to remote targets
+    pub bytes_sent: u64,
+    /// Bytes relayed from remote targets back to SOCKS clients
+    pub bytes_received: u64,
+    pub created_at: DateTime<Utc>,
+}
+
 // ===============================
 // Diagnostics Types
 // ===============================
@@ -1410,6 +1462,7 @@ mod tests {
             password: None,
             private_key_path: None,
             private_key_passphrase: None,
+            certificate_path: None,
             jump_hosts: vec![],
             proxy_config: None,
             proxy_chain: None,
@@ -1461,6 +1514,7 @@ mod tests {
             password: Some(secret("pass")),
             private_key_path: None,
             private_key_passphrase: None,
+            certificate_path: None,
             jump_hosts: vec![JumpHostConfig {
                 host: "jump.example.com".to_string(),
                 port: 22,
@@ -1468,6 +1522,7 @@ mod tests {
                 password: Some(secret("jumppass")),
                 private_key_path: None,
                 private_key_passphrase: None,
+                certificate_path: None,
                 agent_forwarding: false,
                 totp_secret: None,
                 keyboard_interactive_responses: vec![],
@@ -1534,13 +1589,14 @@ mod tests {
                     username: None,
                     password: None,
                 }),
-                ChainHop::SshJump(JumpHostConfig {
+                ChainHop::SshJump(Box::new(JumpHostConfig {
                     host: "jump.example.com".to_string(),
                     port: 22,
                     username: "jumpuser".to_string(),
                     password: None,
                     private_key_path: Some("/keys/jump.pem".to_string()),
                     private_key_passphrase: None,
+                    certificate_path: None,
                     agent_forwarding: true,
                     totp_secret: None,
                     keyboard_interactive_responses: vec![],
@@ -1548,7 +1604,7 @@ mod tests {
                     preferred_macs: vec![],
                     preferred_kex: vec![],
                     preferred_host_key_algorithms: vec![],
-                }),
+                })),
             ],
             hop_timeout_ms: 15000,
         };
@@ -1585,13 +1641,14 @@ mod tests {
         });
         assert_eq!(proxy_hop.address(), ("proxy.test".to_string(), 3128));
 
-        let ssh_hop = ChainHop::SshJump(JumpHostConfig {
+        let ssh_hop = ChainHop::SshJump(Box::new(JumpHostConfig {
             host: "ssh.test".to_string(),
             port: 2222,
             username: "user".to_string(),
             password: None,
             private_key_path: None,
             private_key_passphrase: None,
+            certificate_path: None,
             agent_forwarding: false,
             totp_secret: None,
             keyboard_interactive_responses: vec![],
@@ -1599,7 +1656,7 @@ mod tests {
             preferred_macs: vec![],
             preferred_kex: vec![],
             preferred_host_key_algorithms: vec![],
-        });
+        }));
         assert_eq!(ssh_hop.address(), ("ssh.test".to_string(), 2222));
     }
 
@@ -1614,13 +1671,14 @@ mod tests {
         });
         assert!(proxy.label().contains("proxy.test:1080"));
 
-        let ssh = ChainHop::SshJump(JumpHostConfig {
+        let ssh = ChainHop::SshJump(Box::new(JumpHostConfig {
             host: "jump.test".to_string(),
             port: 22,
             username: "admin".to_string(),
             password: None,
             private_key_path: None,
             private_key_passphrase: None,
+            certificate_path: None,
             agent_forwarding: false,
             totp_secret: None,
             keyboard_interactive_responses: vec![],
@@ -1628,7 +1686,7 @@ mod tests {
             preferred_macs: vec![],
             preferred_kex: vec![],
             preferred_host_key_algorithms: vec![],
-        });
+        }));
         assert!(ssh.label().contains("admin@jump.test:22"));
     }
 
@@ -1691,6 +1749,7 @@ mod tests {
             password: None,
             private_key_path: Some("/path/to/key".to_string()),
             private_key_passphrase: Some(secret("keypass")),
+            certificate_path: None,
             agent_forwarding: true,
             totp_secret: Some(secret("JBSWY3DPEHPK3PXP")),
             keyboard_interactive_responses: vec![secret("yes"), secret("123456")],
@@ -1723,6 +1782,7 @@ mod tests {
             password: Some(secret("password123")),
             private_key_path: Some("/keys/id_ed25519".to_string()),
             private_key_passphrase: Some(secret("keypass")),
+            certificate_path: None,
             jump_hosts: vec![],
             proxy_config: Some(ProxyConfig {
                 proxy_type: ProxyType::Socks5,
@@ -36,6 +36,7 @@ fn test_config() -> SshConnectionConfig {
         password: Some(SecretString::new(env_or("SSH_PASSWORD", "testpass"))),
         private_key_path: None,
         private_key_passphrase: None,
+        certificate_path: None,
         jump_hosts: Vec::new(),
         proxy_config: None,
         proxy_chain: None,
@@ -9,9 +9,12 @@
 //!  - Negotiation commands (IAC WILL/WONT/DO/DONT <option>)
 //!  - Sub-negotiation (IAC SB <option> … IAC SE)
 //!  - Simple commands (IAC NOP, IAC AYT, IAC BRK, etc.)
+//!
+//! [`encode_transparent`] and [`NvtDecoder`] carry arbitrary bytes (in-band
+//! file transfers) across a connection that may not be in BINARY mode.
 
 use crate::telnet::protocol::TelnetFrame;
-use crate::telnet::protocol::{DO, DONT, IAC, SB, SE, WILL, WONT};
+use crate::telnet::protocol::{CR, DO, DONT, IAC, NUL, SB, SE, WILL, WONT};
 use crate::telnet::types::TelnetCommand;
 
 const MAX_SUBNEGOTIATION_BYTES: usize = 64 * 1024;
@@ -201,6 +204,49 @@ impl Default for TelnetCodec {
     }
 }
 
+/// Encode arbitrary bytes for transmission.
+///
+/// IAC is always doubled.  Unless BINARY is enabled for our side, a bare
+/// CR is sent as CR NUL so the peer's NVT does not swallow the next byte.
+pub fn encode_transparent(data: &[u8], binary: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 64);
+    for &b in data {
+        out.push(b);
+        if b == IAC {
+            out.push(IAC);
+        } else if b == CR && !binary {
+            out.push(NUL);
+        }
+    }
+    out
+}
+
+/// Undo NVT CR NUL stuffing in decoded data (peer not in BINARY mode).
+///
+/// Retains state so a CR at the end of one chunk pairs with a NUL at the
+/// start of the next.
+#[derive(Debug, Default)]
+pub struct NvtDecoder {
+    after_cr: bool,
+}
+
+impl NvtDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len());
+        for &b in data {
+            if !(self.after_cr && b == NUL) {
+                out.push(b);
+            }
+            self.after_cr = b == CR;
+        }
+        out
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -441,6 +487,42 @@ mod tests {
         assert_eq!(frames, vec![TelnetFrame::Data(b"hello".to_vec())]);
     }
 
+    // ── Transparent data ────────────────────────────────────────────
+
+    #[test]
+    fn encode_transparent_escapes_iac_and_cr() {
+        let data = [b'a', CR, b'b', IAC, CR, LF];
+        assert_eq!(
+            encode_transparent(&data, false),
+            vec![b'a', CR, NUL, b'b', IAC, IAC, CR, NUL, LF]
+        );
+        assert_eq!(
+            encode_transparent(&data, true),
+            vec![b'a', CR, b'b', IAC, IAC, CR, LF]
+        );
+    }
+
+    #[test]
+    fn nvt_decoder_strips_cr_nul_across_chunks() {
+        let mut decoder = NvtDecoder::new();
+        assert_eq!(decoder.decode(&[b'x', CR]), vec![b'x', CR]);
+        assert_eq!(decoder.decode(&[NUL, NUL, CR, LF]), vec![NUL, CR, LF]);
+
+        let payload: Vec<u8> = (0..=255u8).cycle().take(2048).collect();
+        let encoded = encode_transparent(&payload, false);
+        let mut codec = TelnetCodec::new();
+        let mut decoder = NvtDecoder::new();
+        let mut decoded = Vec::new();
+        for chunk in encoded.chunks(7) {
+            for frame in codec.decode(chunk) {
+                if let TelnetFrame::Data(data) = frame {
+                    decoded.extend(decoder.decode(&data));
+                }
+            }
+        }
+        assert_eq!(decoded, payload);
+    }
+
     // ── Error recovery ──────────────────────────────────────────────
 
     #[test]
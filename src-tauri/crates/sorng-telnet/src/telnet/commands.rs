@@ -3,7 +3,10 @@
 // These are compiled via include!() from the app layer where tauri is available.
 
 use super::service::TelnetServiceState;
-use super::types::{TelnetConfig, TelnetSession};
+use super::types::{
+    ComPortSettings, ComPortState, InbandSelection, LineModeState, ModemLineControl, PurgeTarget,
+    TelnetConfig, TelnetSession,
+};
 
 /// Connect to a telnet server.
 #[tauri::command]
@@ -103,3 +106,80 @@ pub async fn is_telnet_connected(
 ) -> Result<bool, String> {
     state.is_connected(&session_id).await
 }
+
+/// Send keyboard input to a telnet session (edited locally under LINEMODE).
+#[tauri::command]
+pub async fn send_telnet_input(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+    data: String,
+) -> Result<(), String> {
+    state.send_input(&session_id, &data).await
+}
+
+/// Change or query RFC 2217 serial settings on a terminal server port.
+#[tauri::command]
+pub async fn set_telnet_com_port(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+    settings: ComPortSettings,
+) -> Result<(), String> {
+    state.set_com_port(&session_id, settings).await
+}
+
+/// Drive DTR / RTS / BREAK on a terminal server port.
+#[tauri::command]
+pub async fn set_telnet_com_port_lines(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+    lines: ModemLineControl,
+) -> Result<(), String> {
+    state.set_com_port_lines(&session_id, lines).await
+}
+
+/// Purge buffered data on a terminal server port.
+#[tauri::command]
+pub async fn purge_telnet_com_port(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+    target: PurgeTarget,
+) -> Result<(), String> {
+    state.purge_com_port(&session_id, target).await
+}
+
+/// Get the RFC 2217 port state reported by the terminal server.
+#[tauri::command]
+pub async fn get_telnet_com_port_state(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+) -> Result<ComPortState, String> {
+    state.get_com_port_state(&session_id).await
+}
+
+/// Get the RFC 1184 LINEMODE state of a telnet session.
+#[tauri::command]
+pub async fn get_telnet_linemode_state(
+    state: tauri::State<'_, TelnetServiceState>,
+    session_id: String,
+) -> Result<LineModeState, String> {
+    state.get_linemode_state(&session_id).await
+}
+
+/// Answer an in-band (ZMODEM / trzsz) file request from a telnet session.
+#[tauri::command]
+pub async fn telnet_respond_to_inband_transfer(
+    state: tauri::State<'_, TelnetServiceState>,
+    transfer_id: String,
+    selection: InbandSelection,
+) -> Result<(), String> {
+    state.respond_to_inband_transfer(&transfer_id, selection)
+}
+
+/// Decline or cancel an in-band file transfer in a telnet session.
+#[tauri::command]
+pub async fn telnet_cancel_inband_transfer(
+    state: tauri::State<'_, TelnetServiceState>,
+    transfer_id: String,
+) -> Result<(), String> {
+    state.cancel_inband_transfer(&transfer_id)
+}
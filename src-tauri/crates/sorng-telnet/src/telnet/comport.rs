@@ -0,0 +1,304 @@
+//! RFC 2217 COM-PORT-OPTION client.
+//!
+//! Encodes serial line requests for terminal/console servers (Digi, Moxa,
+//! ser2net, …) and folds the server's replies and notifications into a
+//! [`ComPortState`]. The client sends command codes 0–12; the server answers
+//! with the same code plus 100.
+
+use crate::telnet::protocol;
+use crate::telnet::types::{
+    ComPortSettings, ComPortState, FlowControl, ModemLineControl, Parity, PurgeTarget, StopBits,
+    TelnetOption,
+};
+
+// ── Command codes (client → server) ─────────────────────────────────────
+
+pub const SIGNATURE: u8 = 0;
+pub const SET_BAUDRATE: u8 = 1;
+pub const SET_DATASIZE: u8 = 2;
+pub const SET_PARITY: u8 = 3;
+pub const SET_STOPSIZE: u8 = 4;
+pub const SET_CONTROL: u8 = 5;
+pub const NOTIFY_LINESTATE: u8 = 6;
+pub const NOTIFY_MODEMSTATE: u8 = 7;
+pub const FLOWCONTROL_SUSPEND: u8 = 8;
+pub const FLOWCONTROL_RESUME: u8 = 9;
+pub const SET_LINESTATE_MASK: u8 = 10;
+pub const SET_MODEMSTATE_MASK: u8 = 11;
+pub const PURGE_DATA: u8 = 12;
+
+/// Offset added to a command code in server replies.
+pub const SERVER_OFFSET: u8 = 100;
+
+// ── SET-CONTROL values ──────────────────────────────────────────────────
+
+pub const CONTROL_REQUEST_FLOW: u8 = 0;
+pub const CONTROL_FLOW_NONE: u8 = 1;
+pub const CONTROL_FLOW_XONXOFF: u8 = 2;
+pub const CONTROL_FLOW_HARDWARE: u8 = 3;
+pub const CONTROL_REQUEST_BREAK: u8 = 4;
+pub const CONTROL_BREAK_ON: u8 = 5;
+pub const CONTROL_BREAK_OFF: u8 = 6;
+pub const CONTROL_REQUEST_DTR: u8 = 7;
+pub const CONTROL_DTR_ON: u8 = 8;
+pub const CONTROL_DTR_OFF: u8 = 9;
+pub const CONTROL_REQUEST_RTS: u8 = 10;
+pub const CONTROL_RTS_ON: u8 = 11;
+pub const CONTROL_RTS_OFF: u8 = 12;
+
+// ── Modem-state bits ────────────────────────────────────────────────────
+
+pub const MODEM_CD: u8 = 0x80;
+pub const MODEM_RI: u8 = 0x40;
+pub const MODEM_DSR: u8 = 0x20;
+pub const MODEM_CTS: u8 = 0x10;
+
+/// Client side of the COM-PORT-OPTION sub-negotiation.
+#[derive(Debug, Default)]
+pub struct ComPortClient {
+    /// Settings pushed to the server once the option is agreed.
+    initial: ComPortSettings,
+    state: ComPortState,
+}
+
+impl ComPortClient {
+    /// Create a client that applies `initial` when the option is enabled.
+    pub fn new(initial: ComPortSettings) -> Self {
+        Self {
+            initial,
+            state: ComPortState::default(),
+        }
+    }
+
+    /// Last reported port state.
+    pub fn state(&self) -> &ComPortState {
+        &self.state
+    }
+
+    /// The server agreed to COM-PORT-OPTION: ask for its signature, enable
+    /// all modem-state notifications and apply (or query) the line settings.
+    pub fn on_enabled(&mut self) -> Vec<u8> {
+        self.state = ComPortState {
+            enabled: true,
+            ..ComPortState::default()
+        };
+        let mut out = build(SIGNATURE, &[]);
+        out.extend(build(SET_MODEMSTATE_MASK, &[0xFF]));
+        out.extend(build(SET_LINESTATE_MASK, &[0x1E]));
+        out.extend(encode_settings(&self.initial));
+        out.extend(build(SET_CONTROL, &[CONTROL_REQUEST_DTR]));
+        out.extend(build(SET_CONTROL, &[CONTROL_REQUEST_RTS]));
+        out
+    }
+
+    /// The option was turned off; forget everything the server reported.
+    pub fn reset(&mut self) {
+        self.state = ComPortState::default();
+    }
+
+    /// Apply a server sub-negotiation payload (the bytes after the option
+    /// code). Returns `true` if it was a recognised COM-PORT reply.
+    pub fn receive(&mut self, data: &[u8]) -> bool {
+        let Some((&code, value)) = data.split_first() else {
+            return false;
+        };
+        let Some(command) = code.checked_sub(SERVER_OFFSET) else {
+            return false;
+        };
+        let state = &mut self.state;
+        match command {
+            SIGNATURE => {
+                state.signature = Some(String::from_utf8_lossy(value).into_owned());
+            }
+            SET_BAUDRATE => {
+                let Ok(bytes) = <[u8; 4]>::try_from(value) else {
+                    return false;
+                };
+                state.baud_rate = Some(u32::from_be_bytes(bytes));
+            }
+            SET_DATASIZE => state.data_bits = value.first().copied().filter(|b| *b != 0),
+            SET_PARITY => state.parity = value.first().and_then(|b| parity_from_code(*b)),
+            SET_STOPSIZE => state.stop_bits = value.first().and_then(|b| stop_bits_from_code(*b)),
+            SET_CONTROL => match value.first().copied() {
+                Some(CONTROL_FLOW_NONE) => state.flow_control = Some(FlowControl::None),
+                Some(CONTROL_FLOW_XONXOFF) => state.flow_control = Some(FlowControl::XonXoff),
+                Some(CONTROL_FLOW_HARDWARE) => state.flow_control = Some(FlowControl::Hardware),
+                Some(CONTROL_BREAK_ON) => state.break_state = Some(true),
+                Some(CONTROL_BREAK_OFF) => state.break_state = Some(false),
+                Some(CONTROL_DTR_ON) => state.dtr = Some(true),
+                Some(CONTROL_DTR_OFF) => state.dtr = Some(false),
+                Some(CONTROL_RTS_ON) => state.rts = Some(true),
+                Some(CONTROL_RTS_OFF) => state.rts = Some(false),
+                // Inbound flow-control replies and unknown values are ignored.
+                _ => {}
+            },
+            NOTIFY_LINESTATE => {
+                state.line_state = value.first().copied().unwrap_or_default();
+            }
+            NOTIFY_MODEMSTATE => {
+                let bits = value.first().copied().unwrap_or_default();
+                state.modem_state = bits;
+                state.carrier_detect = bits & MODEM_CD != 0;
+                state.ring_indicator = bits & MODEM_RI != 0;
+                state.data_set_ready = bits & MODEM_DSR != 0;
+                state.clear_to_send = bits & MODEM_CTS != 0;
+            }
+            FLOWCONTROL_SUSPEND => state.flow_suspended = true,
+            FLOWCONTROL_RESUME => state.flow_suspended = false,
+            SET_LINESTATE_MASK | SET_MODEMSTATE_MASK | PURGE_DATA => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Encode line settings; `None` fields become "report current value" queries.
+pub fn encode_settings(settings: &ComPortSettings) -> Vec<u8> {
+    let mut out = build(
+        SET_BAUDRATE,
+        &settings.baud_rate.unwrap_or_default().to_be_bytes(),
+    );
+    out.extend(build(
+        SET_DATASIZE,
+        &[settings.data_bits.unwrap_or_default()],
+    ));
+    out.extend(build(
+        SET_PARITY,
+        &[settings.parity.map(parity_code).unwrap_or_default()],
+    ));
+    out.extend(build(
+        SET_STOPSIZE,
+        &[settings.stop_bits.map(stop_bits_code).unwrap_or_default()],
+    ));
+    let flow = match settings.flow_control {
+        None => CONTROL_REQUEST_FLOW,
+        Some(FlowControl::None) => CONTROL_FLOW_NONE,
+        Some(FlowControl::XonXoff) => CONTROL_FLOW_XONXOFF,
+        Some(FlowControl::Hardware) => CONTROL_FLOW_HARDWARE,
+    };
+    out.extend(build(SET_CONTROL, &[flow]));
+    out
+}
+
+/// Encode DTR / RTS / BREAK changes. Untouched lines produce no bytes.
+pub fn encode_lines(lines: &ModemLineControl) -> Vec<u8> {
+    let mut out = Vec::new();
+    let pairs = [
+        (lines.dtr, CONTROL_DTR_ON, CONTROL_DTR_OFF),
+        (lines.rts, CONTROL_RTS_ON, CONTROL_RTS_OFF),
+        (lines.break_state, CONTROL_BREAK_ON, CONTROL_BREAK_OFF),
+    ];
+    for (wanted, on, off) in pairs {
+        if let Some(wanted) = wanted {
+            out.extend(build(SET_CONTROL, &[if wanted { on } else { off }]));
+        }
+    }
+    out
+}
+
+/// Encode a PURGE-DATA request.
+pub fn encode_purge(target: PurgeTarget) -> Vec<u8> {
+    let value = match target {
+        PurgeTarget::Receive => 1,
+        PurgeTarget::Transmit => 2,
+        PurgeTarget::Both => 3,
+    };
+    build(PURGE_DATA, &[value])
+}
+
+fn build(command: u8, value: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + value.len());
+    data.push(command);
+    data.extend_from_slice(value);
+    protocol::build_subnegotiation(TelnetOption::ComPortControl as u8, &data)
+}
+
+fn parity_code(parity: Parity) -> u8 {
+    match parity {
+        Parity::None => 1,
+        Parity::Odd => 2,
+        Parity::Even => 3,
+        Parity::Mark => 4,
+        Parity::Space => 5,
+    }
+}
+
+fn parity_from_code(code: u8) -> Option<Parity> {
+    match code {
+        1 => Some(Parity::None),
+        2 => Some(Parity::Odd),
+        3 => Some(Parity::Even),
+        4 => Some(Parity::Mark),
+        5 => Some(Parity::Space),
+        _ => None,
+    }
+}
+
+fn stop_bits_code(stop_bits: StopBits) -> u8 {
+    match stop_bits {
+        StopBits::One => 1,
+        StopBits::Two => 2,
+        StopBits::OnePointFive => 3,
+    }
+}
+
+fn stop_bits_from_code(code: u8) -> Option<StopBits> {
+    match code {
+        1 => Some(StopBits::One),
+        2 => Some(StopBits::Two),
+        3 => Some(StopBits::OnePointFive),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telnet::protocol::{IAC, SB, SE};
+
+    const COM: u8 = TelnetOption::ComPortControl as u8;
+
+    #[test]
+    fn settings_encode_set_and_query() {
+        let bytes = encode_settings(&ComPortSettings {
+            baud_rate: Some(115_200),
+            parity: Some(Parity::Even),
+            ..Default::default()
+        });
+        // 115200 = 0x0001C200
+        assert!(bytes
+            .windows(9)
+            .any(|w| w == [IAC, SB, COM, SET_BAUDRATE, 0x00, 0x01, 0xC2, 0x00, IAC]));
+        assert!(bytes
+            .windows(6)
+            .any(|w| w == [IAC, SB, COM, SET_DATASIZE, 0, IAC]));
+        assert!(bytes
+            .windows(6)
+            .any(|w| w == [IAC, SB, COM, SET_PARITY, 3, IAC]));
+        assert!(bytes
+            .windows(7)
+            .any(|w| w == [IAC, SB, COM, SET_CONTROL, CONTROL_REQUEST_FLOW, IAC, SE]));
+    }
+
+    #[test]
+    fn lines_encode_only_requested() {
+        let bytes = encode_lines(&ModemLineControl {
+            dtr: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(
+            bytes,
+            vec![IAC, SB, COM, SET_CONTROL, CONTROL_DTR_OFF, IAC, SE]
+        );
+        assert!(encode_lines(&ModemLineControl::default()).is_empty());
+    }
+
+    #[test]
+    fn receive_rejects_client_codes_and_short_baud() {
+        let mut client = ComPortClient::default();
+        assert!(!client.receive(&[SET_BAUDRATE, 0, 0, 0x25, 0x80]));
+        assert!(!client.receive(&[SERVER_OFFSET + SET_BAUDRATE, 0x25]));
+        assert!(!client.receive(&[]));
+        assert_eq!(client.state().baud_rate, None);
+    }
+}
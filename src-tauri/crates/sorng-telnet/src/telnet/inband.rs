@@ -0,0 +1,163 @@
+//! In-band ZMODEM / trzsz transfers inside telnet sessions.
+//!
+//! The read loop passes decoded data through an [`InbandRouter`].  When
+//! `sz`, `rz`, `tsz` or `trz` starts on the remote side, data stops
+//! reaching the terminal and feeds the transfer instead; the transfer's
+//! replies are queued on the write loop, IAC- and CR-escaped.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use sorng_core::events::DynEventEmitter;
+use sorng_serial::serial::inband::{InbandDetector, InbandInput, InbandTransfers};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::telnet::codec::{encode_transparent, NvtDecoder};
+use crate::telnet::negotiation::NegotiationManager;
+use crate::telnet::session::{SessionCommand, MAX_COMMAND_BYTES};
+use crate::telnet::types::TelnetOption;
+
+const EVENT_PREFIX: &str = "telnet";
+
+/// What a session needs to run in-band transfers.
+#[derive(Clone)]
+pub struct InbandContext {
+    pub emitter: DynEventEmitter,
+    pub transfers: Arc<InbandTransfers>,
+}
+
+struct ActiveTransfer {
+    input: InbandInput,
+    nvt: NvtDecoder,
+    done: oneshot::Receiver<()>,
+}
+
+/// Per-session transfer state, owned by the read loop.
+pub(crate) struct InbandRouter {
+    context: Option<InbandContext>,
+    session_id: String,
+    cmd_tx: mpsc::Sender<SessionCommand>,
+    negotiation: Arc<Mutex<NegotiationManager>>,
+    /// Shared with the session handle so keyboard input can be held back.
+    running: Arc<AtomicBool>,
+    detector: InbandDetector,
+    active: Option<ActiveTransfer>,
+}
+
+impl InbandRouter {
+    pub(crate) fn new(
+        context: Option<InbandContext>,
+        session_id: String,
+        cmd_tx: mpsc::Sender<SessionCommand>,
+        negotiation: Arc<Mutex<NegotiationManager>>,
+        running: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            context,
+            session_id,
+            cmd_tx,
+            negotiation,
+            running,
+            detector: InbandDetector::new(),
+            active: None,
+        }
+    }
+
+    /// Route decoded data and return the part to render.
+    pub(crate) async fn route(&mut self, data: Vec<u8>) -> Vec<u8> {
+        if let Some(active) = self.active.as_mut() {
+            active.input.push(&active.nvt.decode(&data));
+            return Vec::new();
+        }
+        // Without an emitter nobody can answer the file request.
+        let Some(context) = self.context.clone() else {
+            return data;
+        };
+        let Some(found) = self.detector.scan(&data) else {
+            return data;
+        };
+
+        let mut nvt = NvtDecoder::new();
+        let handshake = nvt.decode(&found.handshake(&data));
+        let (input, output) = context.transfers.start(
+            &tokio::runtime::Handle::current(),
+            &self.session_id,
+            found.trigger,
+            handshake,
+            context.emitter,
+            EVENT_PREFIX,
+        );
+        let (done_tx, done) = oneshot::channel();
+        tokio::spawn(forward_output(
+            output,
+            self.cmd_tx.clone(),
+            self.negotiation.clone(),
+            done_tx,
+        ));
+        self.running.store(true, Ordering::Relaxed);
+        self.active = Some(ActiveTransfer { input, nvt, done });
+
+        let mut render = data;
+        render.truncate(found.offset);
+        render
+    }
+
+    /// Resolve once the running transfer is over, with the output it left
+    /// unread.  Pending while no transfer runs.
+    pub(crate) async fn finished(&mut self) -> Vec<u8> {
+        match self.active.as_mut() {
+            Some(active) => {
+                let _ = (&mut active.done).await;
+            }
+            None => std::future::pending::<()>().await,
+        }
+        let leftover = self
+            .active
+            .take()
+            .map(|active| active.input.take_leftover())
+            .unwrap_or_default();
+        self.running.store(false, Ordering::Relaxed);
+        self.detector.reset();
+        leftover
+    }
+
+    /// Abort a running transfer (the session is closing).
+    pub(crate) fn cancel(&mut self) {
+        if let Some(active) = self.active.take() {
+            active.input.cancel();
+            if let Some(context) = &self.context {
+                let _ = context.transfers.cancel(active.input.transfer_id());
+            }
+        }
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Queue transfer output on the write loop until the transfer closes its
+/// side of the pipe.
+async fn forward_output(
+    mut output: mpsc::Receiver<Vec<u8>>,
+    cmd_tx: mpsc::Sender<SessionCommand>,
+    negotiation: Arc<Mutex<NegotiationManager>>,
+    done: oneshot::Sender<()>,
+) {
+    'forward: while let Some(bytes) = output.recv().await {
+        let binary = negotiation
+            .lock()
+            .await
+            .is_local_enabled(TelnetOption::BinaryTransmission as u8);
+        let encoded = encode_transparent(&bytes, binary);
+        for chunk in encoded.chunks(MAX_COMMAND_BYTES) {
+            if cmd_tx
+                .send(SessionCommand::SendRaw(chunk.to_vec()))
+                .await
+                .is_err()
+            {
+                break 'forward;
+            }
+        }
+    }
+    // Dropping the receiver fails any further transfer writes.
+    drop(output);
+    let _ = done.send(());
+}
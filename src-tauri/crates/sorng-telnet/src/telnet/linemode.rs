@@ -0,0 +1,501 @@
+//! RFC 1184 LINEMODE client.
+//!
+//! Handles the MODE, FORWARDMASK and SLC sub-negotiations sent by the server
+//! and, while MODE EDIT is active, edits the input line locally so only
+//! complete lines cross the wire. With MODE TRAPSIG the configured signal
+//! characters are translated into their Telnet commands.
+
+use crate::telnet::protocol::{self, AO, AYT, BRK, CR, DO, IAC, IP, LF, NUL, WONT};
+use crate::telnet::types::{LineModeState, TelnetOption};
+
+// ── Sub-negotiation commands ────────────────────────────────────────────
+
+pub const MODE: u8 = 1;
+pub const FORWARDMASK: u8 = 2;
+pub const SLC: u8 = 3;
+
+// ── MODE bits ───────────────────────────────────────────────────────────
+
+pub const MODE_EDIT: u8 = 0x01;
+pub const MODE_TRAPSIG: u8 = 0x02;
+pub const MODE_ACK: u8 = 0x04;
+pub const MODE_SOFT_TAB: u8 = 0x08;
+pub const MODE_LIT_ECHO: u8 = 0x10;
+const MODE_MASK: u8 = MODE_EDIT | MODE_TRAPSIG | MODE_SOFT_TAB | MODE_LIT_ECHO;
+
+// ── SLC function codes ──────────────────────────────────────────────────
+
+pub const SLC_SYNCH: u8 = 1;
+pub const SLC_BRK: u8 = 2;
+pub const SLC_IP: u8 = 3;
+pub const SLC_AO: u8 = 4;
+pub const SLC_AYT: u8 = 5;
+pub const SLC_EOR: u8 = 6;
+pub const SLC_ABORT: u8 = 7;
+pub const SLC_EOF: u8 = 8;
+pub const SLC_SUSP: u8 = 9;
+pub const SLC_EC: u8 = 10;
+pub const SLC_EL: u8 = 11;
+pub const SLC_EW: u8 = 12;
+pub const SLC_RP: u8 = 13;
+pub const SLC_LNEXT: u8 = 14;
+pub const SLC_XON: u8 = 15;
+pub const SLC_XOFF: u8 = 16;
+pub const SLC_FORW1: u8 = 17;
+pub const SLC_FORW2: u8 = 18;
+const SLC_COUNT: usize = SLC_FORW2 as usize;
+
+// ── SLC levels and flags ────────────────────────────────────────────────
+
+pub const SLC_NOSUPPORT: u8 = 0;
+pub const SLC_CANTCHANGE: u8 = 1;
+pub const SLC_VALUE: u8 = 2;
+pub const SLC_DEFAULT: u8 = 3;
+pub const SLC_LEVELBITS: u8 = 0x03;
+pub const SLC_FLUSHOUT: u8 = 0x20;
+pub const SLC_FLUSHIN: u8 = 0x40;
+pub const SLC_ACK: u8 = 0x80;
+
+// ── Telnet commands only used by LINEMODE (RFC 1184 §5) ─────────────────
+
+pub const XEOF: u8 = 236;
+pub const SUSP: u8 = 237;
+pub const ABORT: u8 = 238;
+
+/// Upper bound for a locally edited line.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// Bytes produced by feeding keyboard input through the line editor.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LineModeInput {
+    /// Bytes to write to the socket (already IAC-escaped).
+    pub to_server: Vec<u8>,
+    /// Bytes to echo to the local terminal.
+    pub echo: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlcEntry {
+    /// Level plus FLUSHIN/FLUSHOUT flags (never ACK).
+    modifier: u8,
+    value: u8,
+}
+
+fn default_slc(func: u8) -> SlcEntry {
+    let (modifier, value) = match func {
+        SLC_IP => (SLC_VALUE | SLC_FLUSHIN | SLC_FLUSHOUT, 0x03),
+        SLC_AO => (SLC_VALUE, 0x0F),
+        SLC_AYT => (SLC_VALUE, 0x14),
+        SLC_ABORT => (SLC_VALUE | SLC_FLUSHIN | SLC_FLUSHOUT, 0x1C),
+        SLC_EOF => (SLC_VALUE, 0x04),
+        SLC_SUSP => (SLC_VALUE | SLC_FLUSHIN, 0x1A),
+        SLC_EC => (SLC_VALUE, 0x7F),
+        SLC_EL => (SLC_VALUE, 0x15),
+        SLC_EW => (SLC_VALUE, 0x17),
+        SLC_RP => (SLC_VALUE, 0x12),
+        SLC_LNEXT => (SLC_VALUE, 0x16),
+        SLC_XON => (SLC_VALUE, 0x11),
+        SLC_XOFF => (SLC_VALUE, 0x13),
+        _ => (SLC_NOSUPPORT, 0),
+    };
+    SlcEntry { modifier, value }
+}
+
+fn default_table() -> [SlcEntry; SLC_COUNT + 1] {
+    let mut table = [SlcEntry {
+        modifier: SLC_NOSUPPORT,
+        value: 0,
+    }; SLC_COUNT + 1];
+    for (func, entry) in table.iter_mut().enumerate().skip(1) {
+        *entry = default_slc(func as u8);
+    }
+    table
+}
+
+/// Client side of the LINEMODE option.
+#[derive(Debug)]
+pub struct LineModeClient {
+    active: bool,
+    mode: u8,
+    /// Special characters indexed by SLC function code (index 0 unused).
+    slc: [SlcEntry; SLC_COUNT + 1],
+    line: Vec<u8>,
+    literal_next: bool,
+    /// Swallow the LF of a CR LF pair typed as one Enter.
+    after_cr: bool,
+}
+
+impl Default for LineModeClient {
+    fn default() -> Self {
+        Self {
+            active: false,
+            mode: 0,
+            slc: default_table(),
+            line: Vec::new(),
+            literal_next: false,
+            after_cr: false,
+        }
+    }
+}
+
+impl LineModeClient {
+    /// Snapshot of the negotiated mode.
+    pub fn state(&self) -> LineModeState {
+        LineModeState {
+            active: self.active,
+            edit: self.mode & MODE_EDIT != 0,
+            trapsig: self.mode & MODE_TRAPSIG != 0,
+            soft_tab: self.mode & MODE_SOFT_TAB != 0,
+            lit_echo: self.mode & MODE_LIT_ECHO != 0,
+        }
+    }
+
+    /// Current value of a special character, if it is enabled.
+    pub fn special_char(&self, func: u8) -> Option<u8> {
+        let entry = self.slc.get(func as usize).filter(|_| func != 0)?;
+        (entry.modifier & SLC_LEVELBITS != SLC_NOSUPPORT && entry.value != IAC)
+            .then_some(entry.value)
+    }
+
+    /// LINEMODE was agreed: start in character mode and offer our SLC table.
+    pub fn on_enabled(&mut self) -> Vec<u8> {
+        *self = Self {
+            active: true,
+            ..Self::default()
+        };
+        let mut data = vec![SLC];
+        data.extend(self.slc_table());
+        frame(&data)
+    }
+
+    /// LINEMODE was turned off.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Apply a server sub-negotiation payload (the bytes after the option
+    /// code) and return the reply to send.
+    pub fn receive(&mut self, data: &[u8]) -> Vec<u8> {
+        match data {
+            [MODE, mask, ..] => self.receive_mode(*mask),
+            // We never forward on anything but end of line, so decline.
+            [DO, FORWARDMASK, ..] => frame(&[WONT, FORWARDMASK]),
+            [SLC, triplets @ ..] => self.receive_slc(triplets),
+            _ => Vec::new(),
+        }
+    }
+
+    fn receive_mode(&mut self, mask: u8) -> Vec<u8> {
+        // A MODE with ACK set confirms a proposal of ours; we never make
+        // one, and it must not be answered either way.
+        if mask & MODE_ACK != 0 {
+            return Vec::new();
+        }
+        let mode = mask & MODE_MASK;
+        if mode == self.mode {
+            return Vec::new();
+        }
+        let mut out = Vec::new();
+        if mode & MODE_EDIT == 0 && !self.line.is_empty() {
+            // Leaving local editing: hand the unfinished line to the server.
+            out.extend(protocol::escape_iac(&self.line));
+            self.line.clear();
+        }
+        self.mode = mode;
+        out.extend(frame(&[MODE, mode | MODE_ACK]));
+        out
+    }
+
+    fn receive_slc(&mut self, triplets: &[u8]) -> Vec<u8> {
+        let mut reply = Vec::new();
+        for triplet in triplets.chunks_exact(3) {
+            let (func, modifier, value) = (triplet[0], triplet[1], triplet[2]);
+            let level = modifier & SLC_LEVELBITS;
+            if func == 0 {
+                if level == SLC_DEFAULT {
+                    self.slc = default_table();
+                }
+                if level == SLC_DEFAULT || level == SLC_VALUE {
+                    reply.extend(self.slc_table());
+                }
+                continue;
+            }
+            let Some(entry) = self.slc.get_mut(func as usize) else {
+                reply.extend([func, SLC_NOSUPPORT, 0]);
+                continue;
+            };
+            if modifier & SLC_ACK != 0 {
+                // The server acknowledged a value; adopt it without replying.
+                *entry = SlcEntry {
+                    modifier: modifier & !SLC_ACK,
+                    value,
+                };
+                continue;
+            }
+            if level == SLC_DEFAULT {
+                *entry = default_slc(func);
+                reply.extend([func, entry.modifier, entry.value]);
+                continue;
+            }
+            let proposed = SlcEntry { modifier, value };
+            if proposed == *entry {
+                continue;
+            }
+            *entry = proposed;
+            reply.extend([func, modifier | SLC_ACK, value]);
+        }
+        if reply.is_empty() {
+            return Vec::new();
+        }
+        let mut data = vec![SLC];
+        data.extend(reply);
+        frame(&data)
+    }
+
+    fn slc_table(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SLC_COUNT * 3);
+        for (func, entry) in self.slc.iter().enumerate().skip(1) {
+            out.extend([func as u8, entry.modifier, entry.value]);
+        }
+        out
+    }
+
+    /// Feed keyboard input through the editor. Outside MODE EDIT the bytes
+    /// pass straight through, apart from TRAPSIG translation.
+    pub fn input(&mut self, bytes: &[u8], crlf: bool) -> LineModeInput {
+        let mut out = LineModeInput::default();
+        for &byte in bytes {
+            self.input_byte(byte, crlf, &mut out);
+        }
+        out
+    }
+
+    fn input_byte(&mut self, byte: u8, crlf: bool, out: &mut LineModeInput) {
+        let edit = self.mode & MODE_EDIT != 0;
+        if self.literal_next {
+            self.literal_next = false;
+            self.push_char(byte, out);
+            return;
+        }
+        if self.mode & MODE_TRAPSIG != 0 {
+            if let Some(command) = self.trapped_command(byte) {
+                if edit {
+                    self.line.clear();
+                }
+                out.to_server.extend([IAC, command]);
+                return;
+            }
+        }
+        if !edit {
+            out.to_server.extend(protocol::escape_iac(&[byte]));
+            return;
+        }
+
+        let after_cr = std::mem::take(&mut self.after_cr);
+        if byte == LF && after_cr {
+            return;
+        }
+        if byte == CR || byte == LF {
+            self.after_cr = byte == CR;
+            out.to_server.extend(protocol::escape_iac(&self.line));
+            out.to_server
+                .extend(if crlf { [CR, LF] } else { [CR, NUL] });
+            out.echo.extend([CR, LF]);
+            self.line.clear();
+            return;
+        }
+
+        let editing_func = [
+            SLC_EC, SLC_EL, SLC_EW, SLC_RP, SLC_LNEXT, SLC_EOF, SLC_XON, SLC_XOFF,
+        ]
+        .into_iter()
+        .find(|func| self.special_char(*func) == Some(byte));
+        match editing_func {
+            Some(SLC_EC) => self.erase_chars(1, out),
+            Some(SLC_EL) => self.erase_chars(usize::MAX, out),
+            Some(SLC_EW) => self.erase_word(out),
+            Some(SLC_RP) => {
+                out.echo.extend([CR, LF]);
+                self.render(&self.line, &mut out.echo);
+            }
+            Some(SLC_LNEXT) => self.literal_next = true,
+            Some(SLC_EOF) => {
+                // Like a canonical tty: EOF forwards the pending line without
+                // a terminator, or the EOF character itself on an empty line.
+                if self.line.is_empty() {
+                    out.to_server.push(byte);
+                } else {
+                    out.to_server.extend(protocol::escape_iac(&self.line));
+                    self.line.clear();
+                }
+            }
+            Some(_) => out.to_server.push(byte),
+            None if byte == b'\t' && self.mode & MODE_SOFT_TAB != 0 => {
+                let column = self.display_width(&self.line);
+                for _ in 0..8 - column % 8 {
+                    self.push_char(b' ', out);
+                }
+            }
+            None => self.push_char(byte, out),
+        }
+    }
+
+    fn trapped_command(&self, byte: u8) -> Option<u8> {
+        [
+            (SLC_IP, IP),
+            (SLC_AO, AO),
+            (SLC_AYT, AYT),
+            (SLC_BRK, BRK),
+            (SLC_ABORT, ABORT),
+            (SLC_EOF, XEOF),
+            (SLC_SUSP, SUSP),
+        ]
+        .into_iter()
+        .find(|(func, _)| self.special_char(*func) == Some(byte))
+        .map(|(_, command)| command)
+    }
+
+    fn push_char(&mut self, byte: u8, out: &mut LineModeInput) {
+        if self.mode & MODE_EDIT == 0 {
+            out.to_server.extend(protocol::escape_iac(&[byte]));
+            return;
+        }
+        if self.line.len() >= MAX_LINE_BYTES {
+            out.echo.push(0x07);
+            return;
+        }
+        self.line.push(byte);
+        self.render(&[byte], &mut out.echo);
+    }
+
+    fn erase_chars(&mut self, count: usize, out: &mut LineModeInput) {
+        for _ in 0..count {
+            let Some(start) = last_char_start(&self.line) else {
+                break;
+            };
+            let width = self.display_width(&self.line[start..]);
+            self.line.truncate(start);
+            for _ in 0..width {
+                out.echo.extend([0x08, b' ', 0x08]);
+            }
+        }
+    }
+
+    fn erase_word(&mut self, out: &mut LineModeInput) {
+        while self.line.last() == Some(&b' ') {
+            self.erase_chars(1, out);
+        }
+        while self.line.last().is_some_and(|b| *b != b' ') {
+            self.erase_chars(1, out);
+        }
+    }
+
+    fn render(&self, bytes: &[u8], echo: &mut Vec<u8>) {
+        for &byte in bytes {
+            if is_control(byte) && self.mode & MODE_LIT_ECHO == 0 {
+                echo.extend([b'^', byte ^ 0x40]);
+            } else {
+                echo.push(byte);
+            }
+        }
+    }
+
+    fn display_width(&self, bytes: &[u8]) -> usize {
+        bytes
+            .iter()
+            .map(|&byte| match byte {
+                0x80..=0xBF => 0,
+                b if is_control(b) && self.mode & MODE_LIT_ECHO == 0 => 2,
+                _ => 1,
+            })
+            .sum()
+    }
+}
+
+fn is_control(byte: u8) -> bool {
+    byte < 0x20 || byte == 0x7F
+}
+
+/// Start index of the last (possibly multi-byte UTF-8) character.
+fn last_char_start(line: &[u8]) -> Option<usize> {
+    let mut index = line.len().checked_sub(1)?;
+    while index > 0 && (0x80..=0xBF).contains(&line[index]) {
+        index -= 1;
+    }
+    Some(index)
+}
+
+fn frame(data: &[u8]) -> Vec<u8> {
+    protocol::build_subnegotiation(TelnetOption::Linemode as u8, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editing() -> LineModeClient {
+        let mut client = LineModeClient::default();
+        client.on_enabled();
+        client.receive(&[MODE, MODE_EDIT | MODE_TRAPSIG]);
+        client
+    }
+
+    #[test]
+    fn edit_mode_buffers_until_enter() {
+        let mut client = editing();
+        let typed = client.input(b"ls -l", true);
+        assert!(typed.to_server.is_empty());
+        assert_eq!(typed.echo, b"ls -l");
+        let enter = client.input(b"\r\n", true);
+        assert_eq!(enter.to_server, b"ls -l\r\n");
+        assert_eq!(enter.echo, b"\r\n");
+    }
+
+    #[test]
+    fn erase_char_word_and_line() {
+        let mut client = editing();
+        client.input(b"show runn", true);
+        let erased = client.input(&[0x7F], true);
+        assert_eq!(erased.echo, b"\x08 \x08");
+        client.input(&[0x17], true);
+        assert_eq!(client.input(b"\r", false).to_server, b"show \r\0");
+        client.input(b"junk", true);
+        client.input(&[0x15], true);
+        assert_eq!(client.input(b"\r", true).to_server, b"\r\n");
+    }
+
+    #[test]
+    fn erase_removes_whole_utf8_character() {
+        let mut client = editing();
+        client.input("né".as_bytes(), true);
+        client.input(&[0x7F], true);
+        assert_eq!(client.input(b"\r", true).to_server, b"n\r\n");
+    }
+
+    #[test]
+    fn trapsig_maps_signals_to_commands() {
+        let mut client = editing();
+        client.input(b"partial", true);
+        let interrupted = client.input(&[0x03], true);
+        assert_eq!(interrupted.to_server, vec![IAC, IP]);
+        // The pending line is discarded.
+        assert_eq!(client.input(b"\r", true).to_server, b"\r\n");
+        assert_eq!(client.input(&[0x1A], true).to_server, vec![IAC, SUSP]);
+    }
+
+    #[test]
+    fn literal_next_inserts_special_char() {
+        let mut client = editing();
+        client.input(&[0x16, 0x03], true);
+        assert_eq!(client.input(b"\r", true).to_server, b"\x03\r\n");
+    }
+
+    #[test]
+    fn character_mode_passes_bytes_through() {
+        let mut client = LineModeClient::default();
+        client.on_enabled();
+        let out = client.input(&[b'a', IAC, 0x03], true);
+        assert_eq!(out.to_server, vec![b'a', IAC, IAC, 0x03]);
+        assert!(out.echo.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 //! Telnet crate: sub-modules.
 
 pub mod codec;
+pub mod comport;
+pub mod inband;
+pub mod linemode;
 pub mod negotiation;
 pub mod protocol;
 pub mod service;
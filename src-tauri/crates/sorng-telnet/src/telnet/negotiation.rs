@@ -2,12 +2,18 @@
 //!
 //! Tracks per-option state for both the local and remote sides and produces
 //! the correct outgoing bytes in response to received WILL/WONT/DO/DONT
-//! commands, avoiding negotiation loops.
+//! commands, avoiding negotiation loops. Options with their own
+//! sub-negotiation (RFC 2217 COM-PORT-OPTION, RFC 1184 LINEMODE) are driven
+//! from here once they are agreed.
 
 use std::collections::HashMap;
 
+use crate::telnet::comport::ComPortClient;
+use crate::telnet::linemode::{LineModeClient, LineModeInput};
 use crate::telnet::protocol::{self, DO, DONT, IAC, WILL, WONT};
-use crate::telnet::types::{OptionState, QState, TelnetOption};
+use crate::telnet::types::{
+    ComPortSettings, ComPortState, LineModeState, OptionState, QState, TelnetOption,
+};
 
 /// Which side an option pertains to.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,6 +33,10 @@ pub struct NegotiationManager {
     accepted_remote: Vec<u8>,
     /// Options we want to enable locally.
     desired_local: Vec<u8>,
+    /// RFC 2217 client state.
+    com_port: ComPortClient,
+    /// RFC 1184 client state.
+    linemode: LineModeClient,
 }
 
 impl Default for NegotiationManager {
@@ -42,6 +52,8 @@ impl NegotiationManager {
             options: HashMap::new(),
             accepted_remote: Vec::new(),
             desired_local: Vec::new(),
+            com_port: ComPortClient::default(),
+            linemode: LineModeClient::default(),
         }
     }
 
@@ -59,6 +71,77 @@ impl NegotiationManager {
         }
     }
 
+    /// Offer COM-PORT-OPTION and apply `initial` once the server agrees.
+    pub fn enable_com_port_control(&mut self, initial: ComPortSettings) {
+        self.com_port = ComPortClient::new(initial);
+        self.desire_local(TelnetOption::ComPortControl as u8);
+    }
+
+    /// Offer LINEMODE.
+    pub fn enable_linemode(&mut self) {
+        self.desire_local(TelnetOption::Linemode as u8);
+    }
+
+    /// Port state as last reported by the access server.
+    pub fn com_port_state(&self) -> &ComPortState {
+        self.com_port.state()
+    }
+
+    /// Current LINEMODE state.
+    pub fn linemode_state(&self) -> LineModeState {
+        self.linemode.state()
+    }
+
+    /// Run keyboard input through the LINEMODE editor, or just IAC-escape it
+    /// when LINEMODE is off.
+    pub fn linemode_input(&mut self, bytes: &[u8], crlf: bool) -> LineModeInput {
+        if self.is_local_enabled(TelnetOption::Linemode as u8) {
+            self.linemode.input(bytes, crlf)
+        } else {
+            LineModeInput {
+                to_server: protocol::escape_iac(bytes),
+                echo: Vec::new(),
+            }
+        }
+    }
+
+    /// Process an incoming sub-negotiation for an option this manager drives.
+    /// Returns bytes to send in response (possibly empty).
+    pub fn receive_subnegotiation(&mut self, option: u8, data: &[u8]) -> Vec<u8> {
+        if !self.is_local_enabled(option) {
+            return Vec::new();
+        }
+        match TelnetOption::from_byte(option) {
+            Some(TelnetOption::ComPortControl) => {
+                self.com_port.receive(data);
+                Vec::new()
+            }
+            Some(TelnetOption::Linemode) => self.linemode.receive(data),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Start or tear down option-specific state after a local transition.
+    fn local_transition(&mut self, option: u8, was_enabled: bool) -> Vec<u8> {
+        let enabled = self.is_local_enabled(option);
+        if enabled == was_enabled {
+            return Vec::new();
+        }
+        match TelnetOption::from_byte(option) {
+            Some(TelnetOption::ComPortControl) if enabled => self.com_port.on_enabled(),
+            Some(TelnetOption::ComPortControl) => {
+                self.com_port.reset();
+                Vec::new()
+            }
+            Some(TelnetOption::Linemode) if enabled => self.linemode.on_enabled(),
+            Some(TelnetOption::Linemode) => {
+                self.linemode.reset();
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
     fn get_state(&mut self, option: u8) -> &mut OptionState {
         self.options.entry(option).or_default()
     }
@@ -194,8 +277,9 @@ impl NegotiationManager {
     /// Returns bytes to send in response (possibly empty).
     pub fn receive_do(&mut self, option: u8) -> Vec<u8> {
         let desired = self.desired_local.contains(&option);
+        let was_enabled = self.is_local_enabled(option);
         let state = self.get_state(option);
-        match state.local {
+        let mut response = match state.local {
             QState::No => {
                 if desired {
                     state.local = QState::Yes;
@@ -225,14 +309,17 @@ impl NegotiationManager {
                 state.local = QState::WantNo;
                 protocol::build_negotiation(WONT, option)
             }
-        }
+        };
+        response.extend(self.local_transition(option, was_enabled));
+        response
     }
 
     /// Process an incoming DONT command from the remote.
     /// Returns bytes to send in response (possibly empty).
     pub fn receive_dont(&mut self, option: u8) -> Vec<u8> {
+        let was_enabled = self.is_local_enabled(option);
         let state = self.get_state(option);
-        match state.local {
+        let mut response = match state.local {
             QState::No => Vec::new(),
             QState::Yes => {
                 state.local = QState::No;
@@ -254,14 +341,20 @@ impl NegotiationManager {
                 state.local = QState::No;
                 Vec::new()
             }
-        }
+        };
+        response.extend(self.local_transition(option, was_enabled));
+        response
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::telnet::types::TelnetOption;
+    use crate::telnet::codec::TelnetCodec;
+    use crate::telnet::comport;
+    use crate::telnet::linemode;
+    use crate::telnet::protocol::{TelnetFrame, SB, SE};
+    use crate::telnet::types::{FlowControl, Parity, StopBits, TelnetCommand, TelnetOption};
 
     fn mgr_accepting(opts: &[u8]) -> NegotiationManager {
         let mut m = NegotiationManager::new();
@@ -458,4 +551,346 @@ mod tests {
         // Should transition to WantYes and send DO
         assert_eq!(resp, vec![IAC, DO, echo]);
     }
+
+    // ── Scripted server streams: RFC 2217 / RFC 1184 ───────────────
+
+    const COM: u8 = TelnetOption::ComPortControl as u8;
+    const LINEMODE: u8 = TelnetOption::Linemode as u8;
+
+    /// Feed a scripted server byte stream through the codec and the manager,
+    /// returning everything the client would write back.
+    fn drive(m: &mut NegotiationManager, server: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for frame in TelnetCodec::new().decode(server) {
+            match frame {
+                TelnetFrame::Negotiation { command, option } => out.extend(match command {
+                    TelnetCommand::WILL => m.receive_will(option),
+                    TelnetCommand::WONT => m.receive_wont(option),
+                    TelnetCommand::DO => m.receive_do(option),
+                    TelnetCommand::DONT => m.receive_dont(option),
+                    _ => Vec::new(),
+                }),
+                TelnetFrame::SubNegotiation { option, data } => {
+                    out.extend(m.receive_subnegotiation(option, &data))
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    fn com_port_manager() -> NegotiationManager {
+        let mut m = NegotiationManager::new();
+        m.enable_com_port_control(ComPortSettings {
+            baud_rate: Some(9600),
+            data_bits: Some(8),
+            parity: Some(Parity::None),
+            stop_bits: Some(StopBits::One),
+            flow_control: Some(FlowControl::Hardware),
+        });
+        m
+    }
+
+    #[test]
+    fn com_port_agreement_pushes_initial_settings() {
+        let mut m = com_port_manager();
+        assert_eq!(m.initial_negotiation(), vec![IAC, WILL, COM]);
+
+        let sent = drive(&mut m, &[IAC, DO, COM]);
+        assert!(m.is_local_enabled(COM));
+        assert!(m.com_port_state().enabled);
+        assert!(contains(
+            &sent,
+            &[IAC, SB, COM, comport::SIGNATURE, IAC, SE]
+        ));
+        assert!(contains(
+            &sent,
+            &[
+                IAC,
+                SB,
+                COM,
+                comport::SET_BAUDRATE,
+                0,
+                0,
+                0x25,
+                0x80,
+                IAC,
+                SE
+            ]
+        ));
+        assert!(contains(
+            &sent,
+            &[IAC, SB, COM, comport::SET_DATASIZE, 8, IAC, SE]
+        ));
+        assert!(contains(
+            &sent,
+            &[
+                IAC,
+                SB,
+                COM,
+                comport::SET_CONTROL,
+                comport::CONTROL_FLOW_HARDWARE,
+                IAC,
+                SE
+            ]
+        ));
+    }
+
+    #[test]
+    fn com_port_server_replies_update_state() {
+        let mut m = com_port_manager();
+        m.initial_negotiation();
+        drive(&mut m, &[IAC, DO, COM]);
+
+        let mut script = vec![IAC, SB, COM, 100];
+        script.extend_from_slice(b"NPort 5110");
+        script.extend_from_slice(&[IAC, SE]);
+        for reply in [
+            &[101, 0, 1, 0xC2, 0x00][..],
+            &[102, 7],
+            &[103, 3],
+            &[104, 2],
+            &[105, comport::CONTROL_FLOW_XONXOFF],
+            &[105, comport::CONTROL_DTR_ON],
+            &[105, comport::CONTROL_RTS_OFF],
+            &[107, comport::MODEM_CD | comport::MODEM_CTS],
+            &[106, 0x10],
+            &[108],
+        ] {
+            script.extend_from_slice(&[IAC, SB, COM]);
+            script.extend_from_slice(reply);
+            script.extend_from_slice(&[IAC, SE]);
+        }
+        assert!(drive(&mut m, &script).is_empty());
+
+        let state = m.com_port_state();
+        assert_eq!(state.signature.as_deref(), Some("NPort 5110"));
+        assert_eq!(state.baud_rate, Some(115_200));
+        assert_eq!(state.data_bits, Some(7));
+        assert_eq!(state.parity, Some(Parity::Even));
+        assert_eq!(state.stop_bits, Some(StopBits::Two));
+        assert_eq!(state.flow_control, Some(FlowControl::XonXoff));
+        assert_eq!(state.dtr, Some(true));
+        assert_eq!(state.rts, Some(false));
+        assert!(state.carrier_detect && state.clear_to_send);
+        assert!(!state.data_set_ready && !state.ring_indicator);
+        assert_eq!(state.line_state, 0x10);
+        assert!(state.flow_suspended);
+
+        drive(&mut m, &[IAC, SB, COM, 109, IAC, SE]);
+        assert!(!m.com_port_state().flow_suspended);
+    }
+
+    #[test]
+    fn com_port_baud_with_iac_byte_roundtrips() {
+        let mut m = com_port_manager();
+        m.initial_negotiation();
+        drive(&mut m, &[IAC, DO, COM]);
+        // 0x0000_01FF: the 0xFF octet arrives doubled inside SB.
+        drive(&mut m, &[IAC, SB, COM, 101, 0, 0, 1, IAC, IAC, IAC, SE]);
+        assert_eq!(m.com_port_state().baud_rate, Some(511));
+
+        let encoded = comport::encode_settings(&ComPortSettings {
+            baud_rate: Some(511),
+            ..Default::default()
+        });
+        assert!(contains(
+            &encoded,
+            &[comport::SET_BAUDRATE, 0, 0, 1, IAC, IAC, IAC, SE]
+        ));
+    }
+
+    #[test]
+    fn com_port_ignored_until_agreed_and_reset_on_dont() {
+        let mut m = com_port_manager();
+        drive(&mut m, &[IAC, SB, COM, 101, 0, 0, 0x25, 0x80, IAC, SE]);
+        assert_eq!(m.com_port_state().baud_rate, None);
+
+        m.initial_negotiation();
+        drive(&mut m, &[IAC, DO, COM]);
+        drive(&mut m, &[IAC, SB, COM, 101, 0, 0, 0x25, 0x80, IAC, SE]);
+        assert_eq!(m.com_port_state().baud_rate, Some(9600));
+
+        assert_eq!(drive(&mut m, &[IAC, DONT, COM]), vec![IAC, WONT, COM]);
+        assert!(!m.com_port_state().enabled);
+        assert_eq!(m.com_port_state().baud_rate, None);
+    }
+
+    #[test]
+    fn com_port_refused_when_not_configured() {
+        let mut m = NegotiationManager::new();
+        assert_eq!(drive(&mut m, &[IAC, DO, COM]), vec![IAC, WONT, COM]);
+        assert!(!m.com_port_state().enabled);
+    }
+
+    fn linemode_manager() -> NegotiationManager {
+        let mut m = NegotiationManager::new();
+        m.enable_linemode();
+        m.initial_negotiation();
+        m
+    }
+
+    #[test]
+    fn linemode_agreement_sends_slc_table() {
+        let mut m = linemode_manager();
+        let sent = drive(&mut m, &[IAC, DO, LINEMODE]);
+        assert!(m.linemode_state().active);
+        assert!(!m.linemode_state().edit);
+        assert!(contains(&sent, &[IAC, SB, LINEMODE, linemode::SLC]));
+        // EC defaults to DEL.
+        assert!(contains(
+            &sent,
+            &[linemode::SLC_EC, linemode::SLC_VALUE, 0x7F]
+        ));
+    }
+
+    #[test]
+    fn linemode_mode_is_acked_once() {
+        let mut m = linemode_manager();
+        drive(&mut m, &[IAC, DO, LINEMODE]);
+
+        let edit = linemode::MODE_EDIT | linemode::MODE_TRAPSIG;
+        let sent = drive(&mut m, &[IAC, SB, LINEMODE, linemode::MODE, edit, IAC, SE]);
+        assert_eq!(
+            sent,
+            vec![
+                IAC,
+                SB,
+                LINEMODE,
+                linemode::MODE,
+                edit | linemode::MODE_ACK,
+                IAC,
+                SE
+            ]
+        );
+        assert!(m.linemode_state().edit && m.linemode_state().trapsig);
+
+        // Same mode again, or an ACKed mode, needs no reply.
+        assert!(drive(&mut m, &[IAC, SB, LINEMODE, linemode::MODE, edit, IAC, SE]).is_empty());
+        let acked = linemode::MODE_ACK;
+        assert!(drive(&mut m, &[IAC, SB, LINEMODE, linemode::MODE, acked, IAC, SE]).is_empty());
+        assert!(m.linemode_state().edit);
+    }
+
+    #[test]
+    fn linemode_declines_forwardmask() {
+        let mut m = linemode_manager();
+        drive(&mut m, &[IAC, DO, LINEMODE]);
+        let sent = drive(
+            &mut m,
+            &[
+                IAC,
+                SB,
+                LINEMODE,
+                DO,
+                linemode::FORWARDMASK,
+                0x80,
+                0,
+                IAC,
+                SE,
+            ],
+        );
+        assert_eq!(
+            sent,
+            vec![IAC, SB, LINEMODE, WONT, linemode::FORWARDMASK, IAC, SE]
+        );
+    }
+
+    #[test]
+    fn linemode_slc_changes_drive_local_editing() {
+        let mut m = linemode_manager();
+        drive(&mut m, &[IAC, DO, LINEMODE]);
+        drive(
+            &mut m,
+            &[
+                IAC,
+                SB,
+                LINEMODE,
+                linemode::MODE,
+                linemode::MODE_EDIT,
+                IAC,
+                SE,
+            ],
+        );
+
+        // Server moves EC to ^H: we adopt it and ACK.
+        let sent = drive(
+            &mut m,
+            &[
+                IAC,
+                SB,
+                LINEMODE,
+                linemode::SLC,
+                linemode::SLC_EC,
+                linemode::SLC_VALUE,
+                0x08,
+                IAC,
+                SE,
+            ],
+        );
+        assert!(contains(
+            &sent,
+            &[
+                linemode::SLC_EC,
+                linemode::SLC_VALUE | linemode::SLC_ACK,
+                0x08
+            ]
+        ));
+
+        // An ACKed triplet is adopted silently.
+        let acked = drive(
+            &mut m,
+            &[
+                IAC,
+                SB,
+                LINEMODE,
+                linemode::SLC,
+                linemode::SLC_EL,
+                linemode::SLC_VALUE | linemode::SLC_ACK,
+                0x18,
+                IAC,
+                SE,
+            ],
+        );
+        assert!(acked.is_empty());
+
+        let typed = m.linemode_input(b"junk\x18shwo\x08\x08ow", true);
+        assert!(typed.to_server.is_empty());
+        let line = m.linemode_input(b"\r", true);
+        assert_eq!(line.to_server, b"show\r\n");
+    }
+
+    #[test]
+    fn linemode_slc_default_request_returns_table() {
+        let mut m = linemode_manager();
+        drive(&mut m, &[IAC, DO, LINEMODE]);
+        let sent = drive(
+            &mut m,
+            &[
+                IAC,
+                SB,
+                LINEMODE,
+                linemode::SLC,
+                0,
+                linemode::SLC_DEFAULT,
+                0,
+                IAC,
+                SE,
+            ],
+        );
+        // IAC SB LINEMODE SLC + 18 triplets + IAC SE.
+        assert_eq!(sent.len(), 4 + 18 * 3 + 2);
+    }
+
+    #[test]
+    fn linemode_input_passthrough_when_off() {
+        let mut m = NegotiationManager::new();
+        let out = m.linemode_input(&[b'x', IAC], true);
+        assert_eq!(out.to_server, vec![b'x', IAC, IAC]);
+        assert!(out.echo.is_empty());
+    }
 }
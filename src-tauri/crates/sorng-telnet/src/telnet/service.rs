@@ -4,8 +4,11 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use sorng_core::events::DynEventEmitter;
+use sorng_serial::serial::inband::InbandTransfers;
 use tokio::sync::{Mutex, RwLock};
 
+use crate::telnet::comport;
+use crate::telnet::inband::InbandContext;
 use crate::telnet::session::{
     self, hex_decode, SessionCommand, SessionEvent, TelnetSessionHandle, MAX_COMMAND_BYTES,
 };
@@ -23,6 +26,7 @@ pub struct TelnetService {
     sessions: RwLock<HashMap<String, Arc<TelnetSessionHandle>>>,
     event_emitter: Option<DynEventEmitter>,
     connect_gate: Mutex<()>,
+    inband: Arc<InbandTransfers>,
 }
 
 impl TelnetService {
@@ -32,6 +36,7 @@ impl TelnetService {
             sessions: RwLock::new(HashMap::new()),
             event_emitter: None,
             connect_gate: Mutex::new(()),
+            inband: Arc::new(InbandTransfers::new()),
         })
     }
 
@@ -41,6 +46,7 @@ impl TelnetService {
             sessions: RwLock::new(HashMap::new()),
             event_emitter: Some(emitter),
             connect_gate: Mutex::new(()),
+            inband: Arc::new(InbandTransfers::new()),
         })
     }
 
@@ -66,7 +72,11 @@ impl TelnetService {
 
         let id = uuid::Uuid::new_v4().to_string();
 
-        let handle = session::connect(id.clone(), config)
+        let inband = self.event_emitter.clone().map(|emitter| InbandContext {
+            emitter,
+            transfers: self.inband.clone(),
+        });
+        let handle = session::connect(id.clone(), config, inband)
             .await
             .map_err(|e| e.to_string())?;
 
@@ -104,6 +114,7 @@ impl TelnetService {
             .store(false, std::sync::atomic::Ordering::Relaxed);
         handle.shutdown.notify_waiters();
         let _ = handle.cmd_tx.try_send(SessionCommand::Disconnect);
+        self.inband.cancel_session(session_id);
         log::info!("[telnet-service] session {} disconnected", session_id);
         Ok(())
     }
@@ -161,6 +172,100 @@ impl TelnetService {
         Self::queue(&handle, SessionCommand::Resize { cols, rows })
     }
 
+    /// Send keyboard input to a session. While LINEMODE EDIT is active the
+    /// input is edited locally and only complete lines are sent.
+    ///
+    /// Input is dropped while an in-band file transfer owns the stream.
+    pub async fn send_input(&self, session_id: &str, data: &str) -> Result<(), String> {
+        if data.len() > MAX_COMMAND_BYTES {
+            return Err("Telnet input exceeds the allowed size".to_string());
+        }
+        let handle = self.get_live_handle(session_id).await?;
+        if handle
+            .inband_active
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            return Ok(());
+        }
+        Self::queue(&handle, SessionCommand::Input(data.as_bytes().to_vec()))
+    }
+
+    // ── In-band transfers ───────────────────────────────────────────
+
+    /// Answer a `telnet-inband-transfer-request` with the chosen files
+    /// (upload) or target directory (download).
+    pub fn respond_to_inband_transfer(
+        &self,
+        transfer_id: &str,
+        selection: InbandSelection,
+    ) -> Result<(), String> {
+        self.inband.respond(transfer_id, selection)
+    }
+
+    /// Decline a pending in-band transfer or stop a running one.
+    pub fn cancel_inband_transfer(&self, transfer_id: &str) -> Result<(), String> {
+        self.inband.cancel(transfer_id)
+    }
+
+    // ── RFC 2217 COM-port control ───────────────────────────────────
+
+    /// Change (or query, for `None` fields) the remote serial line settings.
+    pub async fn set_com_port(
+        &self,
+        session_id: &str,
+        settings: ComPortSettings,
+    ) -> Result<(), String> {
+        settings.validate().map_err(|e| e.to_string())?;
+        let handle = self.get_com_port_handle(session_id).await?;
+        Self::queue(
+            &handle,
+            SessionCommand::SendRaw(comport::encode_settings(&settings)),
+        )
+    }
+
+    /// Drive the DTR / RTS / BREAK lines of the remote serial port.
+    pub async fn set_com_port_lines(
+        &self,
+        session_id: &str,
+        lines: ModemLineControl,
+    ) -> Result<(), String> {
+        let handle = self.get_com_port_handle(session_id).await?;
+        let data = comport::encode_lines(&lines);
+        if data.is_empty() {
+            return Ok(());
+        }
+        Self::queue(&handle, SessionCommand::SendRaw(data))
+    }
+
+    /// Discard buffered data on the remote serial port.
+    pub async fn purge_com_port(
+        &self,
+        session_id: &str,
+        target: PurgeTarget,
+    ) -> Result<(), String> {
+        let handle = self.get_com_port_handle(session_id).await?;
+        Self::queue(
+            &handle,
+            SessionCommand::SendRaw(comport::encode_purge(target)),
+        )
+    }
+
+    /// Port state as last reported by the access server.
+    pub async fn get_com_port_state(&self, session_id: &str) -> Result<ComPortState, String> {
+        Self::validate_session_id(session_id)?;
+        let handle = self.get_handle(session_id).await?;
+        let state = handle.negotiation.lock().await.com_port_state().clone();
+        Ok(state)
+    }
+
+    /// Current LINEMODE state of a session.
+    pub async fn get_linemode_state(&self, session_id: &str) -> Result<LineModeState, String> {
+        Self::validate_session_id(session_id)?;
+        let handle = self.get_handle(session_id).await?;
+        let state = handle.negotiation.lock().await.linemode_state();
+        Ok(state)
+    }
+
     // ── Query ────────────────────────────────────────────────────────
 
     /// Get session info.
@@ -207,15 +312,34 @@ impl TelnetService {
 
     async fn get_live_handle(&self, session_id: &str) -> Result<Arc<TelnetSessionHandle>, String> {
         Self::validate_session_id(session_id)?;
-        let handle = self
-            .sessions
+        let handle = self.get_handle(session_id).await?;
+        if !handle.connected.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err("Telnet session is not connected".to_string());
+        }
+        Ok(handle)
+    }
+
+    async fn get_handle(&self, session_id: &str) -> Result<Arc<TelnetSessionHandle>, String> {
+        self.sessions
             .read()
             .await
             .get(session_id)
             .cloned()
-            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
-        if !handle.connected.load(std::sync::atomic::Ordering::Relaxed) {
-            return Err("Telnet session is not connected".to_string());
+            .ok_or_else(|| format!("Session '{}' not found", session_id))
+    }
+
+    async fn get_com_port_handle(
+        &self,
+        session_id: &str,
+    ) -> Result<Arc<TelnetSessionHandle>, String> {
+        let handle = self.get_live_handle(session_id).await?;
+        let enabled = handle
+            .negotiation
+            .lock()
+            .await
+            .is_local_enabled(TelnetOption::ComPortControl as u8);
+        if !enabled {
+            return Err("COM-port control has not been negotiated for this session".to_string());
         }
         Ok(handle)
     }
@@ -315,6 +439,32 @@ impl TelnetService {
                         );
                     }
                 }
+                Some(SessionEvent::ComPort(state)) => {
+                    if let Some(ref emitter) = emitter {
+                        let _ = emitter.emit_event(
+                            "telnet-comport",
+                            serde_json::to_value(&TelnetComPortEvent {
+                                session_id: session_id.clone(),
+                                client_correlation_id: client_correlation_id.clone(),
+                                state,
+                            })
+                            .unwrap_or_default(),
+                        );
+                    }
+                }
+                Some(SessionEvent::LineMode(state)) => {
+                    if let Some(ref emitter) = emitter {
+                        let _ = emitter.emit_event(
+                            "telnet-linemode",
+                            serde_json::to_value(&TelnetLineModeEvent {
+                                session_id: session_id.clone(),
+                                client_correlation_id: client_correlation_id.clone(),
+                                state,
+                            })
+                            .unwrap_or_default(),
+                        );
+                    }
+                }
                 Some(SessionEvent::WriteBack(data)) => {
                     if let Err(error) = Self::queue(&handle, SessionCommand::SendRaw(data)) {
                         handle
@@ -364,6 +514,7 @@ impl Default for TelnetService {
             sessions: RwLock::new(HashMap::new()),
             event_emitter: None,
             connect_gate: Mutex::new(()),
+            inband: Arc::new(InbandTransfers::new()),
         }
     }
 }
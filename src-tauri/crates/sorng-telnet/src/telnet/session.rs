@@ -10,6 +10,7 @@ use tokio::sync::{mpsc, Mutex, Notify};
 use tokio::time::timeout;
 
 use crate::telnet::codec::TelnetCodec;
+use crate::telnet::inband::{InbandContext, InbandRouter};
 use crate::telnet::negotiation::NegotiationManager;
 use crate::telnet::protocol::{self, TelnetFrame, NOP, SN_SEND};
 use crate::telnet::types::*;
@@ -44,6 +45,10 @@ pub enum SessionEvent {
     },
     /// Raw bytes that should be written back to the socket (e.g. negotiation responses).
     WriteBack(Vec<u8>),
+    /// RFC 2217 port state changed.
+    ComPort(ComPortState),
+    /// RFC 1184 LINEMODE state changed.
+    LineMode(LineModeState),
 }
 
 /// Commands sent *to* a session's write-loop.
@@ -53,6 +58,8 @@ pub enum SessionCommand {
     SendRaw(Vec<u8>),
     /// Send a text line (will be encoded with the session's line-ending mode).
     SendLine(String),
+    /// Keyboard input; edited locally while LINEMODE EDIT is active.
+    Input(Vec<u8>),
     /// Resize the terminal.
     Resize { cols: u16, rows: u16 },
     /// Send a break signal (IAC BRK).
@@ -87,6 +94,10 @@ pub struct TelnetSessionHandle {
     pub reconnect_count: Arc<AtomicU64>,
     /// Wakes both I/O loops when the service disconnects the session.
     pub shutdown: Arc<Notify>,
+    /// Option negotiation state shared with the I/O loops.
+    pub negotiation: Arc<Mutex<NegotiationManager>>,
+    /// Whether an in-band file transfer currently owns the data stream.
+    pub inband_active: Arc<AtomicBool>,
 }
 
 impl TelnetSessionHandle {
@@ -122,8 +133,14 @@ impl TelnetSessionHandle {
 
 /// Connect to a telnet server and spawn async read/write loops.
 ///
+/// In-band ZMODEM / trzsz transfers are detected only when `inband` is set.
+///
 /// Returns: `(TelnetSessionHandle)` on success, or a `TelnetError`.
-pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHandle, TelnetError> {
+pub async fn connect(
+    id: String,
+    config: TelnetConfig,
+    inband: Option<InbandContext>,
+) -> Result<TelnetSessionHandle, TelnetError> {
     config.validate()?;
     let addr = format!("{}:{}", config.host, config.port);
     log::warn!(
@@ -172,6 +189,12 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
     if config.binary_mode {
         negotiation.desire_local(TelnetOption::BinaryTransmission as u8);
     }
+    if config.com_port_control {
+        negotiation.enable_com_port_control(config.com_port.clone().unwrap_or_default());
+    }
+    if config.linemode {
+        negotiation.enable_linemode();
+    }
 
     // Accept the server enabling Echo and SGA on its side.
     negotiation.accept_remote(TelnetOption::Echo as u8);
@@ -192,6 +215,7 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
     let session_id = id.clone();
     let write_shutdown = shutdown.clone();
     let write_event_tx = event_tx.clone();
+    let write_negotiation = negotiation.clone();
 
     tokio::spawn(async move {
         write_loop(
@@ -209,6 +233,7 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
             rows,
             write_shutdown,
             write_event_tx,
+            write_negotiation,
         )
         .await;
     });
@@ -224,6 +249,14 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
     let read_cols = config.cols;
     let read_rows = config.rows;
     let read_shutdown = shutdown.clone();
+    let inband_active = Arc::new(AtomicBool::new(false));
+    let router = InbandRouter::new(
+        inband,
+        id.clone(),
+        cmd_tx.clone(),
+        negotiation.clone(),
+        inband_active.clone(),
+    );
 
     tokio::spawn(async move {
         read_loop(
@@ -239,6 +272,7 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
             read_cols,
             read_rows,
             read_shutdown,
+            router,
         )
         .await;
     });
@@ -272,6 +306,8 @@ pub async fn connect(id: String, config: TelnetConfig) -> Result<TelnetSessionHa
         last_activity,
         reconnect_count,
         shutdown,
+        negotiation,
+        inband_active,
     })
 }
 
@@ -291,6 +327,7 @@ async fn read_loop(
     cols: u16,
     rows: u16,
     shutdown: Arc<Notify>,
+    mut inband: InbandRouter,
 ) {
     let mut codec = TelnetCodec::new();
     let mut buf = [0u8; 4096];
@@ -302,6 +339,13 @@ async fn read_loop(
 
         let read_result = tokio::select! {
             _ = shutdown.notified() => break,
+            leftover = inband.finished() => {
+                let text = String::from_utf8_lossy(&leftover).to_string();
+                if !text.is_empty() {
+                    let _ = event_tx.send(SessionEvent::Data(text)).await;
+                }
+                continue;
+            }
             result = reader.read(&mut buf) => result,
         };
         let n = match read_result {
@@ -348,6 +392,7 @@ async fn read_loop(
         for frame in frames {
             match frame {
                 TelnetFrame::Data(data) => {
+                    let data = inband.route(data).await;
                     // Convert to UTF-8 (lossy).
                     let text = String::from_utf8_lossy(&data).to_string();
                     if !text.is_empty() {
@@ -370,15 +415,16 @@ async fn read_loop(
                         .await;
 
                     // Process via Q-method state machine.
-                    let response = {
+                    let (response, option_state) = {
                         let mut neg = negotiation.lock().await;
-                        match command {
+                        let response = match command {
                             crate::telnet::types::TelnetCommand::WILL => neg.receive_will(option),
                             crate::telnet::types::TelnetCommand::WONT => neg.receive_wont(option),
                             crate::telnet::types::TelnetCommand::DO => neg.receive_do(option),
                             crate::telnet::types::TelnetCommand::DONT => neg.receive_dont(option),
                             _ => Vec::new(),
-                        }
+                        };
+                        (response, option_state_event(&neg, option))
                     };
 
                     if !response.is_empty() {
@@ -400,6 +446,9 @@ async fn read_loop(
                             })
                             .await;
                     }
+                    if let Some(event) = option_state {
+                        let _ = event_tx.send(event).await;
+                    }
                 }
                 TelnetFrame::SubNegotiation { option, data } => {
                     log::debug!(
@@ -409,15 +458,31 @@ async fn read_loop(
                         data.len()
                     );
 
-                    // Handle known sub-negotiations.
-                    let response = handle_subnegotiation(
-                        option,
-                        &data,
-                        &terminal_type,
-                        &terminal_speed,
-                        cols,
-                        rows,
-                    );
+                    // Options with client state are driven by the negotiation
+                    // manager; the rest are stateless replies.
+                    let mut option_state = None;
+                    let response = if option == TelnetOption::ComPortControl as u8
+                        || option == TelnetOption::Linemode as u8
+                    {
+                        let mut neg = negotiation.lock().await;
+                        let linemode_before = neg.linemode_state();
+                        let response = neg.receive_subnegotiation(option, &data);
+                        if option == TelnetOption::ComPortControl as u8
+                            || neg.linemode_state() != linemode_before
+                        {
+                            option_state = option_state_event(&neg, option);
+                        }
+                        response
+                    } else {
+                        handle_subnegotiation(
+                            option,
+                            &data,
+                            &terminal_type,
+                            &terminal_speed,
+                            cols,
+                            rows,
+                        )
+                    };
 
                     if !response.is_empty() {
                         let _ = event_tx
@@ -433,6 +498,9 @@ async fn read_loop(
                             })
                             .await;
                     }
+                    if let Some(event) = option_state {
+                        let _ = event_tx.send(event).await;
+                    }
                 }
                 TelnetFrame::Command(cmd) => {
                     log::debug!("[telnet:{}] recv command {:?}", session_id, cmd);
@@ -441,11 +509,23 @@ async fn read_loop(
         }
     }
 
+    inband.cancel();
     connected.store(false, Ordering::Relaxed);
     shutdown.notify_waiters();
     log::info!("[telnet:{}] read loop exited", session_id);
 }
 
+/// State event for options whose state the frontend tracks.
+fn option_state_event(negotiation: &NegotiationManager, option: u8) -> Option<SessionEvent> {
+    match TelnetOption::from_byte(option)? {
+        TelnetOption::ComPortControl => {
+            Some(SessionEvent::ComPort(negotiation.com_port_state().clone()))
+        }
+        TelnetOption::Linemode => Some(SessionEvent::LineMode(negotiation.linemode_state())),
+        _ => None,
+    }
+}
+
 /// Handle a sub-negotiation and optionally produce a response.
 fn handle_subnegotiation(
     option: u8,
@@ -492,6 +572,7 @@ async fn write_loop(
     _rows: u16,
     shutdown: Arc<Notify>,
     event_tx: mpsc::Sender<SessionEvent>,
+    negotiation: Arc<Mutex<NegotiationManager>>,
 ) {
     let keepalive_interval = if keepalive_secs > 0 {
         Some(Duration::from_secs(keepalive_secs))
@@ -556,7 +637,15 @@ async fn write_loop(
             SessionCommand::SendLine(line) if line.len() <= MAX_COMMAND_BYTES => {
                 protocol::encode_line(&line, crlf)
             }
-            SessionCommand::SendRaw(_) | SessionCommand::SendLine(_) => {
+            SessionCommand::Input(bytes) if bytes.len() <= MAX_COMMAND_BYTES => {
+                let input = negotiation.lock().await.linemode_input(&bytes, crlf);
+                if !input.echo.is_empty() {
+                    let echo = String::from_utf8_lossy(&input.echo).into_owned();
+                    let _ = event_tx.send(SessionEvent::Data(echo)).await;
+                }
+                input.to_server
+            }
+            SessionCommand::SendRaw(_) | SessionCommand::SendLine(_) | SessionCommand::Input(_) => {
                 log::warn!("[telnet:{}] rejected oversized queued payload", session_id);
                 connected.store(false, Ordering::Relaxed);
                 report_write_failure(&event_tx, "Telnet queued payload exceeded the size limit")
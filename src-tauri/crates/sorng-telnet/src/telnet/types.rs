@@ -190,6 +190,132 @@ impl Default for OptionState {
     }
 }
 
+// ── RFC 2217 COM-port control ──────────────────────────────────────────
+
+/// Parity setting of a remote serial port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+    Mark,
+    Space,
+}
+
+/// Stop-bit setting of a remote serial port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopBits {
+    One,
+    Two,
+    OnePointFive,
+}
+
+/// Outbound flow control of a remote serial port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowControl {
+    None,
+    XonXoff,
+    Hardware,
+}
+
+/// Serial line settings requested from an RFC 2217 access server.
+///
+/// Fields left as `None` are queried instead of changed, so the server
+/// reports its current value back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComPortSettings {
+    #[serde(default, alias = "baudRate")]
+    pub baud_rate: Option<u32>,
+    #[serde(default, alias = "dataBits")]
+    pub data_bits: Option<u8>,
+    #[serde(default)]
+    pub parity: Option<Parity>,
+    #[serde(default, alias = "stopBits")]
+    pub stop_bits: Option<StopBits>,
+    #[serde(default, alias = "flowControl")]
+    pub flow_control: Option<FlowControl>,
+}
+
+impl ComPortSettings {
+    /// Reject values RFC 2217 cannot carry.
+    pub fn validate(&self) -> Result<(), TelnetError> {
+        if self.baud_rate == Some(0) {
+            return Err(TelnetError::protocol("COM-port baud rate must be non-zero"));
+        }
+        if self.data_bits.is_some_and(|bits| !(5..=8).contains(&bits)) {
+            return Err(TelnetError::protocol(
+                "COM-port data bits must be between 5 and 8",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Modem control lines to drive on an RFC 2217 port. `None` leaves a line
+/// untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModemLineControl {
+    #[serde(default)]
+    pub dtr: Option<bool>,
+    #[serde(default)]
+    pub rts: Option<bool>,
+    /// Assert (`true`) or release (`false`) a BREAK condition.
+    #[serde(default, alias = "breakState")]
+    pub break_state: Option<bool>,
+}
+
+/// Buffers to discard with an RFC 2217 PURGE-DATA request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PurgeTarget {
+    Receive,
+    Transmit,
+    Both,
+}
+
+/// Port state as last reported by the access server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComPortState {
+    /// Whether the server agreed to COM-PORT-OPTION.
+    pub enabled: bool,
+    /// Server signature text (RFC 2217 SIGNATURE).
+    pub signature: Option<String>,
+    pub baud_rate: Option<u32>,
+    pub data_bits: Option<u8>,
+    pub parity: Option<Parity>,
+    pub stop_bits: Option<StopBits>,
+    pub flow_control: Option<FlowControl>,
+    pub dtr: Option<bool>,
+    pub rts: Option<bool>,
+    pub break_state: Option<bool>,
+    /// Raw NOTIFY-MODEMSTATE bits.
+    pub modem_state: u8,
+    /// Raw NOTIFY-LINESTATE bits.
+    pub line_state: u8,
+    pub carrier_detect: bool,
+    pub ring_indicator: bool,
+    pub data_set_ready: bool,
+    pub clear_to_send: bool,
+    /// The server asked us to stop sending (FLOWCONTROL-SUSPEND).
+    pub flow_suspended: bool,
+}
+
+// ── RFC 1184 LINEMODE ───────────────────────────────────────────────────
+
+/// Current LINEMODE MODE bits as agreed with the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineModeState {
+    /// Whether the server agreed to LINEMODE.
+    pub active: bool,
+    /// Local line editing (MODE EDIT).
+    pub edit: bool,
+    /// Signal characters are translated to Telnet commands (MODE TRAPSIG).
+    pub trapsig: bool,
+    /// Tabs are expanded locally (MODE SOFT_TAB).
+    pub soft_tab: bool,
+    /// Non-printable characters are echoed literally (MODE LIT_ECHO).
+    pub lit_echo: bool,
+}
+
 // ── Configuration ───────────────────────────────────────────────────────
 
 /// Configuration for a new telnet connection.
@@ -258,6 +384,15 @@ pub struct TelnetConfig {
     /// Escape character byte (default 0x1d = Ctrl-]).
     #[serde(default = "default_escape_char")]
     pub escape_char: u8,
+    /// Offer RFC 2217 COM-PORT-OPTION to terminal/console servers.
+    #[serde(default, alias = "comPortControl")]
+    pub com_port_control: bool,
+    /// Serial settings applied once COM-PORT-OPTION is agreed.
+    #[serde(default, alias = "comPort")]
+    pub com_port: Option<ComPortSettings>,
+    /// Offer RFC 1184 LINEMODE (local line editing and SLC handling).
+    #[serde(default)]
+    pub linemode: bool,
 }
 
 impl fmt::Debug for TelnetConfig {
@@ -285,6 +420,9 @@ impl fmt::Debug for TelnetConfig {
             .field("encoding", &self.encoding)
             .field("terminal_speed", &self.terminal_speed)
             .field("escape_char", &self.escape_char)
+            .field("com_port_control", &self.com_port_control)
+            .field("com_port", &self.com_port)
+            .field("linemode", &self.linemode)
             .finish()
     }
 }
@@ -345,6 +483,9 @@ impl Default for TelnetConfig {
             encoding: default_encoding(),
             terminal_speed: default_terminal_speed(),
             escape_char: default_escape_char(),
+            com_port_control: false,
+            com_port: None,
+            linemode: false,
         }
     }
 }
@@ -465,6 +606,14 @@ impl TelnetConfig {
                 "Custom Telnet escape characters are not implemented",
             ));
         }
+        if let Some(settings) = &self.com_port {
+            if !self.com_port_control {
+                return Err(TelnetError::protocol(
+                    "COM-port settings require COM-port control to be enabled",
+                ));
+            }
+            settings.validate()?;
+        }
         Ok(())
     }
 }
@@ -537,6 +686,15 @@ impl TelnetSession {
 
 // ── Events emitted to the frontend ──────────────────────────────────────
 
+// In-band transfers share their payloads with the serial crate:
+// `telnet-inband-transfer-{request,progress,finished}` carry
+// `InbandTransferRequestEvent`, `TransferProgressEvent` and
+// `InbandTransferFinishedEvent` respectively.
+pub use sorng_serial::serial::types::{
+    InbandDirection, InbandSelection, InbandTransferFinishedEvent, InbandTransferRequestEvent,
+    TransferProgressEvent,
+};
+
 /// Payload for `telnet-output` events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelnetOutputEvent {
@@ -571,6 +729,22 @@ pub struct TelnetNegotiationEvent {
     pub option: String,
 }
 
+/// Payload for `telnet-comport` events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelnetComPortEvent {
+    pub session_id: String,
+    pub client_correlation_id: Option<String>,
+    pub state: ComPortState,
+}
+
+/// Payload for `telnet-linemode` events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelnetLineModeEvent {
+    pub session_id: String,
+    pub client_correlation_id: Option<String>,
+    pub state: LineModeState,
+}
+
 // ── Session statistics ──────────────────────────────────────────────────
 
 /// Detailed statistics snapshot for a session.
@@ -798,6 +972,37 @@ mod tests {
         assert_eq!(cfg.terminal_type, "xterm-256color");
     }
 
+    #[test]
+    fn config_com_port_settings_validated() {
+        let mut cfg = TelnetConfig {
+            host: "10.0.0.1".into(),
+            allow_insecure_transport: true,
+            com_port: Some(ComPortSettings {
+                baud_rate: Some(9600),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_err());
+        cfg.com_port_control = true;
+        assert!(cfg.validate().is_ok());
+        cfg.com_port = Some(ComPortSettings {
+            data_bits: Some(9),
+            ..Default::default()
+        });
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn config_deserialize_com_port_camel_case() {
+        let json = r#"{"host":"ts1","comPortControl":true,"comPort":{"baudRate":19200,"parity":"Even"},"linemode":true}"#;
+        let cfg: TelnetConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.com_port_control && cfg.linemode);
+        let settings = cfg.com_port.unwrap();
+        assert_eq!(settings.baud_rate, Some(19200));
+        assert_eq!(settings.parity, Some(Parity::Even));
+    }
+
     // ── TelnetSession ───────────────────────────────────────────────
 
     #[test]
@@ -1,6 +1,7 @@
 // ─── Tauri IPC commands for sorng-topology ───────────────────────────────────
 
 use super::builder::ConnectionData;
+use super::discovery::{self, DiscoveryReport, DiscoveryRequest};
 use super::service::TopologyServiceState;
 use super::types::*;
 
@@ -18,6 +19,18 @@ pub async fn topo_build_from_connections(
     Ok(svc.get_graph().clone())
 }
 
+/// Probe the network and merge what is found into the current graph. The
+/// service lock is not held while probing.
+#[tauri::command]
+pub async fn topo_discover(
+    state: tauri::State<'_, TopologyServiceState>,
+    request: DiscoveryRequest,
+) -> Result<DiscoveryReport, String> {
+    let scan = discovery::discover(&request).await;
+    let mut svc = state.lock().await;
+    Ok(svc.apply_discovery(scan))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Graph read
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -62,9 +62,11 @@ fn diff_node(old: &TopologyNode, new: &TopologyNode, changes: &mut Vec<NodeChang
 
     macro_rules! cmp_field {
         ($field:ident) => {
-            let old_val = serde_json::to_string(&old.$field).unwrap_or_default();
-            let new_val = serde_json::to_string(&new.$field).unwrap_or_default();
-            if old_val != new_val {
+            // Compare values rather than their JSON: `HashMap` fields
+            // serialize in arbitrary order.
+            if old.$field != new.$field {
+                let old_val = serde_json::to_string(&old.$field).unwrap_or_default();
+                let new_val = serde_json::to_string(&new.$field).unwrap_or_default();
                 changes.push(NodeChange {
                     node_id: id.clone(),
                     field: stringify!($field).to_string(),
@@ -93,9 +95,9 @@ fn diff_edge(old: &TopologyEdge, new: &TopologyEdge, changes: &mut Vec<EdgeChang
 
     macro_rules! cmp_field {
         ($field:ident) => {
-            let old_val = serde_json::to_string(&old.$field).unwrap_or_default();
-            let new_val = serde_json::to_string(&new.$field).unwrap_or_default();
-            if old_val != new_val {
+            if old.$field != new.$field {
+                let old_val = serde_json::to_string(&old.$field).unwrap_or_default();
+                let new_val = serde_json::to_string(&new.$field).unwrap_or_default();
                 changes.push(EdgeChange {
                     edge_id: id.clone(),
                     field: stringify!($field).to_string(),
@@ -0,0 +1,1357 @@
+// ─── Active network discovery — SNMP neighbours, ARP, routes, traceroute ────
+
+use crate::diff;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sorng_netutils::types::{ArpEntry, ArpState, RouteEntry, TracerouteOptions, TracerouteResult};
+use sorng_netutils::{arp, route, traceroute};
+use sorng_snmp::client::SnmpClient;
+use sorng_snmp::types::{InterfaceInfo, SnmpDevice, SnmpTable, SnmpTarget, SnmpValue};
+use sorng_snmp::{ifmib, system_info, table};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Metadata key marking nodes and edges that discovery owns.
+pub const ORIGIN_KEY: &str = "origin";
+/// Value of [`ORIGIN_KEY`] on discovery-owned elements.
+pub const ORIGIN_DISCOVERY: &str = "discovery";
+/// Metadata key listing the sources that observed a node or edge.
+pub const DISCOVERED_BY_KEY: &str = "discovered_by";
+
+/// Metadata keys discovery writes onto nodes it annotates but does not own.
+const ANNOTATION_KEYS: [&str; 4] = [
+    DISCOVERED_BY_KEY,
+    "discovered_ips",
+    "discovered_macs",
+    "discovered_description",
+];
+
+// LLDP-MIB (IEEE 802.1AB) and CISCO-CDP-MIB table entries.
+const LLDP_LOC_PORT_ENTRY: &str = "1.0.8802.1.1.2.1.3.7.1";
+const LLDP_REM_ENTRY: &str = "1.0.8802.1.1.2.1.4.1.1";
+const LLDP_REM_MAN_ADDR_ENTRY: &str = "1.0.8802.1.1.2.1.4.2.1";
+const CDP_CACHE_ENTRY: &str = "1.3.6.1.4.1.9.9.23.1.2.1.1";
+
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 60;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Request / result types
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What a discovery run should probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryRequest {
+    /// Devices whose LLDP/CDP neighbour tables, ifTable and ipNetToMedia
+    /// table are walked.
+    #[serde(default)]
+    pub snmp_targets: Vec<SnmpTarget>,
+    /// Read this host's ARP / neighbour cache.
+    #[serde(default)]
+    pub local_neighbors: bool,
+    /// Read this host's routing table for gateways.
+    #[serde(default)]
+    pub local_routes: bool,
+    /// Hosts to traceroute to from this machine.
+    #[serde(default)]
+    pub traceroute_targets: Vec<String>,
+    pub max_hops: Option<u8>,
+    /// Per-command timeout for the local tools (default 60 s).
+    pub command_timeout_secs: Option<u64>,
+}
+
+/// Where a discovered fact came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoverySource {
+    SnmpSystem,
+    Lldp,
+    Cdp,
+    IfTable,
+    IpNetToMedia,
+    Arp,
+    Route,
+    Traceroute,
+}
+
+impl DiscoverySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SnmpSystem => "snmp_system",
+            Self::Lldp => "lldp",
+            Self::Cdp => "cdp",
+            Self::IfTable => "if_table",
+            Self::IpNetToMedia => "ip_net_to_media",
+            Self::Arp => "arp",
+            Self::Route => "route",
+            Self::Traceroute => "traceroute",
+        }
+    }
+}
+
+/// Coarse device role; a stronger role wins when observations disagree.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceRole {
+    #[default]
+    Host,
+    Switch,
+    Router,
+}
+
+impl DeviceRole {
+    fn node_type(self) -> NodeType {
+        match self {
+            DeviceRole::Host => NodeType::Custom("host".to_string()),
+            DeviceRole::Switch => NodeType::Switch,
+            DeviceRole::Router => NodeType::Router,
+        }
+    }
+}
+
+/// One sighting of a device. Sightings sharing a name, IP or MAC address
+/// are folded into the same [`DiscoveredDevice`].
+#[derive(Debug, Clone)]
+pub struct DeviceObservation {
+    pub source: DiscoverySource,
+    pub role: DeviceRole,
+    pub names: Vec<String>,
+    pub ip_addresses: Vec<String>,
+    pub mac_addresses: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl DeviceObservation {
+    pub fn new(source: DiscoverySource, role: DeviceRole) -> Self {
+        Self {
+            source,
+            role,
+            names: Vec::new(),
+            ip_addresses: Vec::new(),
+            mac_addresses: Vec::new(),
+            description: None,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.names.push(name.into());
+        self
+    }
+
+    pub fn ip(mut self, ip: impl Into<String>) -> Self {
+        self.ip_addresses.push(ip.into());
+        self
+    }
+
+    pub fn mac(mut self, mac: impl Into<String>) -> Self {
+        self.mac_addresses.push(mac.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// A device assembled from every observation that referred to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredDevice {
+    /// Stable identity: first name, else lowest IP, else lowest MAC.
+    pub key: String,
+    pub label: String,
+    pub role: DeviceRole,
+    pub names: BTreeSet<String>,
+    pub ip_addresses: BTreeSet<String>,
+    pub mac_addresses: BTreeSet<String>,
+    pub description: Option<String>,
+    pub sources: BTreeSet<DiscoverySource>,
+}
+
+/// An adjacency between two discovered devices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredLink {
+    pub source_key: String,
+    pub target_key: String,
+    pub source_port: Option<String>,
+    pub target_port: Option<String>,
+    pub latency_ms: Option<f64>,
+    pub sources: BTreeSet<DiscoverySource>,
+}
+
+/// Outcome of a discovery run merged into the graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub devices: Vec<DiscoveredDevice>,
+    pub links: Vec<DiscoveredLink>,
+    /// Probes that failed; discovery continues past them.
+    pub warnings: Vec<String>,
+    /// Change against the graph as it stood before this scan — for repeated
+    /// scans, the topology drift since the previous one.
+    pub drift: TopologyDiff,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Scan accumulator
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone)]
+struct PendingLink {
+    a: String,
+    b: String,
+    a_port: Option<String>,
+    b_port: Option<String>,
+    latency_ms: Option<f64>,
+    source: DiscoverySource,
+}
+
+/// Accumulates observations from every probe of one discovery run.
+#[derive(Debug, Clone)]
+pub struct DiscoveryScan {
+    pub started_at: DateTime<Utc>,
+    pub warnings: Vec<String>,
+    devices: Vec<Option<DiscoveredDevice>>,
+    identities: HashMap<String, usize>,
+    links: Vec<PendingLink>,
+}
+
+impl DiscoveryScan {
+    pub fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            warnings: Vec::new(),
+            devices: Vec::new(),
+            identities: HashMap::new(),
+            links: Vec::new(),
+        }
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        log::warn!("topology discovery: {message}");
+        self.warnings.push(message);
+    }
+
+    /// Record a device sighting. Returns an identity usable as a link
+    /// endpoint, or `None` when the observation carries no usable identity.
+    pub fn observe(&mut self, observation: DeviceObservation) -> Option<String> {
+        let names: Vec<String> = observation
+            .names
+            .iter()
+            .filter_map(|n| name_key(n))
+            .collect();
+        let ips: Vec<String> = observation
+            .ip_addresses
+            .iter()
+            .filter_map(|i| ip_key(i))
+            .collect();
+        let macs: Vec<String> = observation
+            .mac_addresses
+            .iter()
+            .filter_map(|m| mac_key(m))
+            .collect();
+        let identities: Vec<String> = names.iter().chain(&ips).chain(&macs).cloned().collect();
+        let handle = identities.first()?.clone();
+
+        let mut matches: Vec<usize> = identities
+            .iter()
+            .filter_map(|id| self.identities.get(id).copied())
+            .collect();
+        matches.sort_unstable();
+        matches.dedup();
+
+        let index = match matches.first() {
+            Some(&first) => {
+                for &other in &matches[1..] {
+                    if let Some(absorbed) = self.devices[other].take() {
+                        self.absorb(first, absorbed);
+                    }
+                    for slot in self.identities.values_mut() {
+                        if *slot == other {
+                            *slot = first;
+                        }
+                    }
+                }
+                first
+            }
+            None => {
+                self.devices.push(Some(DiscoveredDevice {
+                    key: String::new(),
+                    label: String::new(),
+                    role: DeviceRole::Host,
+                    names: BTreeSet::new(),
+                    ip_addresses: BTreeSet::new(),
+                    mac_addresses: BTreeSet::new(),
+                    description: None,
+                    sources: BTreeSet::new(),
+                }));
+                self.devices.len() - 1
+            }
+        };
+        for id in identities {
+            self.identities.insert(id, index);
+        }
+
+        let device = self.devices[index].as_mut()?;
+        device.role = device.role.max(observation.role);
+        device.sources.insert(observation.source);
+        device.names.extend(names.iter().map(|n| strip_kind(n)));
+        device
+            .ip_addresses
+            .extend(ips.iter().map(|i| strip_kind(i)));
+        device
+            .mac_addresses
+            .extend(macs.iter().map(|m| strip_kind(m)));
+        if device.description.is_none() {
+            device.description = observation
+                .description
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty());
+        }
+        Some(handle)
+    }
+
+    fn absorb(&mut self, into: usize, other: DiscoveredDevice) {
+        if let Some(device) = self.devices[into].as_mut() {
+            device.role = device.role.max(other.role);
+            device.sources.extend(other.sources);
+            device.names.extend(other.names);
+            device.ip_addresses.extend(other.ip_addresses);
+            device.mac_addresses.extend(other.mac_addresses);
+            if device.description.is_none() {
+                device.description = other.description;
+            }
+        }
+    }
+
+    /// Record an adjacency between two identities returned by [`observe`].
+    ///
+    /// [`observe`]: DiscoveryScan::observe
+    pub fn link(
+        &mut self,
+        source: DiscoverySource,
+        a: &str,
+        a_port: Option<String>,
+        b: &str,
+        b_port: Option<String>,
+        latency_ms: Option<f64>,
+    ) {
+        self.links.push(PendingLink {
+            a: a.to_string(),
+            b: b.to_string(),
+            a_port: a_port.filter(|p| !p.trim().is_empty()),
+            b_port: b_port.filter(|p| !p.trim().is_empty()),
+            latency_ms,
+            source,
+        });
+    }
+
+    /// Resolve identities into stable device keys and deduplicate links.
+    pub fn finish(self) -> (Vec<DiscoveredDevice>, Vec<DiscoveredLink>) {
+        let mut devices: Vec<Option<DiscoveredDevice>> = self.devices;
+        for device in devices.iter_mut().flatten() {
+            let (kind, value) = if let Some(name) = device.names.iter().next() {
+                ("name", name.clone())
+            } else if let Some(ip) = device.ip_addresses.iter().next() {
+                ("ip", ip.clone())
+            } else {
+                (
+                    "mac",
+                    device
+                        .mac_addresses
+                        .iter()
+                        .next()
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+            };
+            device.key = format!("{kind}:{value}");
+            device.label = value;
+        }
+
+        let key_of = |identity: &str| -> Option<String> {
+            let index = *self.identities.get(identity)?;
+            devices[index].as_ref().map(|d| d.key.clone())
+        };
+
+        let mut links: Vec<DiscoveredLink> = Vec::new();
+        let mut by_pair: HashMap<(String, String), usize> = HashMap::new();
+        for pending in self.links {
+            let (Some(a), Some(b)) = (key_of(&pending.a), key_of(&pending.b)) else {
+                continue;
+            };
+            if a == b {
+                continue;
+            }
+            // Orient every link lowest key first so A→B and B→A coincide.
+            let (source_key, target_key, source_port, target_port) = if a <= b {
+                (a, b, pending.a_port, pending.b_port)
+            } else {
+                (b, a, pending.b_port, pending.a_port)
+            };
+            let pair = (source_key.clone(), target_key.clone());
+            match by_pair.get(&pair) {
+                Some(&i) => {
+                    let link = &mut links[i];
+                    link.source_port = link.source_port.take().or(source_port);
+                    link.target_port = link.target_port.take().or(target_port);
+                    link.latency_ms = match (link.latency_ms, pending.latency_ms) {
+                        (Some(x), Some(y)) => Some(x.min(y)),
+                        (x, y) => x.or(y),
+                    };
+                    link.sources.insert(pending.source);
+                }
+                None => {
+                    by_pair.insert(pair, links.len());
+                    links.push(DiscoveredLink {
+                        source_key,
+                        target_key,
+                        source_port,
+                        target_port,
+                        latency_ms: pending.latency_ms,
+                        sources: BTreeSet::from([pending.source]),
+                    });
+                }
+            }
+        }
+
+        let mut devices: Vec<DiscoveredDevice> = devices.drain(..).flatten().collect();
+        devices.sort_by(|a, b| a.key.cmp(&b.key));
+        links.sort_by(|a, b| (&a.source_key, &a.target_key).cmp(&(&b.source_key, &b.target_key)));
+        (devices, links)
+    }
+}
+
+impl Default for DiscoveryScan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Identity normalisation
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn strip_kind(identity: &str) -> String {
+    identity
+        .split_once(':')
+        .map(|(_, v)| v.to_string())
+        .unwrap_or_default()
+}
+
+fn name_key(raw: &str) -> Option<String> {
+    let name = raw.trim().trim_end_matches('.').to_lowercase();
+    if name.is_empty() || name.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+    Some(format!("name:{name}"))
+}
+
+fn ip_key(raw: &str) -> Option<String> {
+    let ip: IpAddr = raw.trim().parse().ok()?;
+    if ip.is_unspecified() || ip.is_loopback() || ip.is_multicast() {
+        return None;
+    }
+    Some(format!("ip:{ip}"))
+}
+
+/// Accepts `aa:bb:…`, `aa-bb-…`, `aabb.ccdd.eeff` and bare hex forms.
+fn mac_key(raw: &str) -> Option<String> {
+    let hex: String = raw
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.' | ' '))
+        .collect::<String>()
+        .to_lowercase();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    if hex == "000000000000" || hex == "ffffffffffff" {
+        return None;
+    }
+    let octets: Vec<&str> = (0..6).map(|i| &hex[i * 2..i * 2 + 2]).collect();
+    Some(format!("mac:{}", octets.join(":")))
+}
+
+/// Raw bytes of a binary OCTET STRING. The SNMP decoder keeps valid UTF-8
+/// as text and renders anything else as hex, so both forms are accepted.
+fn octets(value: &SnmpValue, len: usize) -> Option<Vec<u8>> {
+    match value {
+        SnmpValue::Opaque(bytes) if bytes.len() == len => Some(bytes.clone()),
+        SnmpValue::OctetString(s)
+            if s.len() == len * 2 && s.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            (0..len)
+                .map(|i| u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok())
+                .collect()
+        }
+        SnmpValue::OctetString(s) if s.len() == len => Some(s.as_bytes().to_vec()),
+        _ => None,
+    }
+}
+
+fn format_mac(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn text(value: Option<&SnmpValue>) -> Option<String> {
+    value
+        .filter(|v| !v.is_exception())
+        .map(|v| v.display_value().trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SNMP table interpretation
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Record the polled device itself from its system group and ifTable.
+pub fn record_snmp_device(
+    scan: &mut DiscoveryScan,
+    target: &SnmpTarget,
+    system: &SnmpDevice,
+    interfaces: &[InterfaceInfo],
+) -> Option<String> {
+    // sysServices bit 2 is layer 2 (bridging), bit 3 layer 3 (routing).
+    let services = system.sys_services.unwrap_or(0);
+    let role = if services & 0x04 != 0 {
+        DeviceRole::Router
+    } else if services & 0x02 != 0 {
+        DeviceRole::Switch
+    } else {
+        DeviceRole::Host
+    };
+    let mut observation =
+        DeviceObservation::new(DiscoverySource::SnmpSystem, role).ip(&target.host);
+    if let Some(name) = &system.sys_name {
+        observation = observation.name(name);
+    }
+    if let Some(descr) = &system.sys_descr {
+        observation = observation.description(descr);
+    }
+    let id = scan.observe(observation)?;
+
+    let mut interface_macs = DeviceObservation::new(DiscoverySource::IfTable, DeviceRole::Host);
+    interface_macs.names.push(strip_kind(&id));
+    interface_macs.ip_addresses.push(target.host.clone());
+    interface_macs
+        .mac_addresses
+        .extend(interfaces.iter().filter_map(|i| i.phys_address.clone()));
+    scan.observe(interface_macs);
+    Some(id)
+}
+
+/// Interface names by ifIndex, for labelling local ports.
+pub fn interface_names(interfaces: &[InterfaceInfo]) -> HashMap<String, String> {
+    interfaces
+        .iter()
+        .map(|i| {
+            let name = i
+                .alias
+                .clone()
+                .filter(|a| !a.is_empty() && a.len() < i.descr.len())
+                .unwrap_or_else(|| i.descr.clone());
+            (i.index.to_string(), name)
+        })
+        .collect()
+}
+
+/// Record LLDP neighbours from `lldpRemTable`, labelling local ports from
+/// `lldpLocPortTable` and taking management addresses from
+/// `lldpRemManAddrTable`.
+pub fn record_lldp_neighbors(
+    scan: &mut DiscoveryScan,
+    device: &str,
+    remote: &SnmpTable,
+    local_ports: Option<&SnmpTable>,
+    management: Option<&SnmpTable>,
+) {
+    let local_port_names: HashMap<&str, String> = local_ports
+        .map(|t| {
+            t.rows
+                .iter()
+                .filter_map(|row| {
+                    let name = text(row.values.get("4")).or_else(|| text(row.values.get("3")))?;
+                    Some((row.index.as_str(), name))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Index: timeMark.localPort.remIndex.addrSubtype.addrLen.addr…
+    let mut addresses: HashMap<(String, String), String> = HashMap::new();
+    for row in management.map(|t| t.rows.as_slice()).unwrap_or_default() {
+        let parts: Vec<&str> = row.index.split('.').collect();
+        if parts.len() == 9 && parts[3] == "1" && parts[4] == "4" {
+            addresses
+                .entry((parts[1].to_string(), parts[2].to_string()))
+                .or_insert_with(|| parts[5..9].join("."));
+        }
+    }
+
+    for row in &remote.rows {
+        let parts: Vec<&str> = row.index.split('.').collect();
+        if parts.len() != 3 {
+            continue;
+        }
+        let (local_port, remote_index) = (parts[1], parts[2]);
+
+        let capabilities = row
+            .values
+            .get("12")
+            .and_then(|v| octets(v, 2).or_else(|| octets(v, 1)))
+            .and_then(|b| b.first().copied())
+            .unwrap_or(0);
+        let role = if capabilities & 0x08 != 0 {
+            DeviceRole::Router
+        } else if capabilities & 0x20 != 0 {
+            DeviceRole::Switch
+        } else {
+            DeviceRole::Host
+        };
+
+        let mut observation = DeviceObservation::new(DiscoverySource::Lldp, role);
+        if let Some(name) = text(row.values.get("9")) {
+            observation = observation.name(name);
+        }
+        if let Some(descr) = text(row.values.get("10")) {
+            observation = observation.description(descr);
+        }
+        // Chassis ID subtype 4 is a MAC address.
+        let chassis_subtype = row.values.get("4").and_then(|v| v.as_integer());
+        if let Some(chassis) = row.values.get("5") {
+            if chassis_subtype == Some(4) {
+                if let Some(bytes) = octets(chassis, 6) {
+                    observation = observation.mac(format_mac(&bytes));
+                }
+            } else if observation.names.is_empty() {
+                if let Some(chassis) = text(Some(chassis)) {
+                    observation = observation.name(chassis);
+                }
+            }
+        }
+        if let Some(ip) = addresses.get(&(local_port.to_string(), remote_index.to_string())) {
+            observation = observation.ip(ip);
+        }
+        let Some(neighbor) = scan.observe(observation) else {
+            continue;
+        };
+
+        // Port ID subtype 3 is a MAC address; prefer the port description.
+        let port_subtype = row.values.get("6").and_then(|v| v.as_integer());
+        let remote_port = text(row.values.get("8")).or_else(|| match row.values.get("7") {
+            Some(v) if port_subtype == Some(3) => octets(v, 6).map(|b| format_mac(&b)),
+            other => text(other),
+        });
+        let local_port = local_port_names
+            .get(local_port)
+            .cloned()
+            .or_else(|| Some(format!("port {local_port}")));
+        scan.link(
+            DiscoverySource::Lldp,
+            device,
+            local_port,
+            &neighbor,
+            remote_port,
+            None,
+        );
+    }
+}
+
+/// Record CDP neighbours from `cdpCacheTable` (index `ifIndex.deviceIndex`).
+pub fn record_cdp_neighbors(
+    scan: &mut DiscoveryScan,
+    device: &str,
+    cache: &SnmpTable,
+    if_names: &HashMap<String, String>,
+) {
+    for row in &cache.rows {
+        let Some((if_index, _)) = row.index.split_once('.') else {
+            continue;
+        };
+        // cdpCacheCapabilities: 0x01 router, 0x02/0x04 bridge, 0x08 switch.
+        let capabilities = row
+            .values
+            .get("9")
+            .and_then(|v| octets(v, 4))
+            .map(|b| b[3])
+            .unwrap_or(0);
+        let role = if capabilities & 0x01 != 0 {
+            DeviceRole::Router
+        } else if capabilities & 0x0e != 0 {
+            DeviceRole::Switch
+        } else {
+            DeviceRole::Host
+        };
+
+        let mut observation = DeviceObservation::new(DiscoverySource::Cdp, role);
+        if let Some(device_id) = text(row.values.get("6")) {
+            observation = observation.name(device_id);
+        }
+        if let Some(platform) = text(row.values.get("8")) {
+            observation = observation.description(platform);
+        }
+        // cdpCacheAddressType 1 is IPv4.
+        if row.values.get("3").and_then(|v| v.as_integer()) == Some(1) {
+            if let Some(b) = row.values.get("4").and_then(|v| octets(v, 4)) {
+                observation = observation.ip(format!("{}.{}.{}.{}", b[0], b[1], b[2], b[3]));
+            }
+        }
+        let Some(neighbor) = scan.observe(observation) else {
+            continue;
+        };
+        scan.link(
+            DiscoverySource::Cdp,
+            device,
+            if_names.get(if_index).cloned(),
+            &neighbor,
+            text(row.values.get("7")),
+            None,
+        );
+    }
+}
+
+/// Record hosts from `ipNetToMediaTable` (index `ifIndex.a.b.c.d`).
+pub fn record_ip_net_to_media(
+    scan: &mut DiscoveryScan,
+    device: &str,
+    media: &SnmpTable,
+    if_names: &HashMap<String, String>,
+) {
+    for row in &media.rows {
+        // ipNetToMediaType 2 is "invalid".
+        if row.values.get("4").and_then(|v| v.as_integer()) == Some(2) {
+            continue;
+        }
+        let Some((if_index, index_ip)) = row.index.split_once('.') else {
+            continue;
+        };
+        let ip = match row.values.get("3") {
+            Some(SnmpValue::IpAddress(ip)) => ip.clone(),
+            _ => index_ip.to_string(),
+        };
+        let mut observation =
+            DeviceObservation::new(DiscoverySource::IpNetToMedia, DeviceRole::Host).ip(ip);
+        if let Some(mac) = row.values.get("2").and_then(|v| octets(v, 6)) {
+            observation = observation.mac(format_mac(&mac));
+        }
+        let Some(host) = scan.observe(observation) else {
+            continue;
+        };
+        scan.link(
+            DiscoverySource::IpNetToMedia,
+            device,
+            if_names.get(if_index).cloned(),
+            &host,
+            None,
+            None,
+        );
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Local table interpretation
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Record this host's ARP / neighbour cache.
+pub fn record_arp_entries(scan: &mut DiscoveryScan, local: &str, entries: &[ArpEntry]) {
+    for entry in entries {
+        if matches!(entry.state, ArpState::Failed | ArpState::Incomplete) {
+            continue;
+        }
+        let observation = DeviceObservation::new(DiscoverySource::Arp, DeviceRole::Host)
+            .ip(&entry.ip)
+            .mac(&entry.mac);
+        if let Some(host) = scan.observe(observation) {
+            scan.link(
+                DiscoverySource::Arp,
+                local,
+                Some(entry.interface.clone()),
+                &host,
+                None,
+                None,
+            );
+        }
+    }
+}
+
+/// Record the gateways in this host's routing table as routers.
+pub fn record_routes(scan: &mut DiscoveryScan, local: &str, routes: &[RouteEntry]) {
+    for route in routes {
+        let Some(gateway) = &route.gateway else {
+            continue;
+        };
+        let observation =
+            DeviceObservation::new(DiscoverySource::Route, DeviceRole::Router).ip(gateway);
+        if let Some(router) = scan.observe(observation) {
+            scan.link(
+                DiscoverySource::Route,
+                local,
+                Some(route.interface.clone()),
+                &router,
+                None,
+                None,
+            );
+        }
+    }
+}
+
+/// Record a traceroute as a chain of router hops ending at the target.
+/// Silent hops are skipped, joining the responsive hops either side.
+pub fn record_traceroute(scan: &mut DiscoveryScan, local: &str, trace: &TracerouteResult) {
+    let mut previous = local.to_string();
+    for hop in &trace.hops {
+        let Some(probe) = hop.probes.iter().find(|p| p.ip.is_some()) else {
+            continue;
+        };
+        let ip = probe.ip.clone().unwrap_or_default();
+        let is_target = trace.resolved_ip.as_deref() == Some(ip.as_str());
+        let role = if is_target {
+            DeviceRole::Host
+        } else {
+            DeviceRole::Router
+        };
+        let mut observation = DeviceObservation::new(DiscoverySource::Traceroute, role).ip(&ip);
+        if let Some(hostname) = probe.hostname.as_ref().filter(|h| **h != ip) {
+            observation = observation.name(hostname);
+        }
+        let Some(current) = scan.observe(observation) else {
+            continue;
+        };
+        let latency = hop
+            .probes
+            .iter()
+            .filter_map(|p| p.rtt_ms)
+            .fold(None, |best: Option<f64>, rtt| {
+                Some(best.map_or(rtt, |b| b.min(rtt)))
+            });
+        scan.link(
+            DiscoverySource::Traceroute,
+            &previous,
+            None,
+            &current,
+            None,
+            latency,
+        );
+        previous = current;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Probing
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Run every probe in the request. Failures become warnings on the scan.
+pub async fn discover(request: &DiscoveryRequest) -> DiscoveryScan {
+    let mut scan = DiscoveryScan::new();
+    let timeout = Duration::from_secs(
+        request
+            .command_timeout_secs
+            .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS),
+    );
+
+    let client = SnmpClient::new();
+    for target in &request.snmp_targets {
+        scan_snmp_target(&mut scan, &client, target).await;
+    }
+
+    let needs_local =
+        request.local_neighbors || request.local_routes || !request.traceroute_targets.is_empty();
+    let local_source = if request.local_neighbors {
+        DiscoverySource::Arp
+    } else if request.local_routes {
+        DiscoverySource::Route
+    } else {
+        DiscoverySource::Traceroute
+    };
+    let local = if needs_local {
+        scan.observe(DeviceObservation::new(local_source, DeviceRole::Host).name(local_hostname()))
+    } else {
+        None
+    };
+    let Some(local) = local else {
+        return scan;
+    };
+
+    if request.local_neighbors {
+        match run_tool("ip", &arp::build_ip_neigh_args(), timeout).await {
+            Ok(output) => record_arp_entries(&mut scan, &local, &arp::parse_neigh_json(&output)),
+            Err(e) => scan.warn(format!("neighbour table: {e}")),
+        }
+    }
+    if request.local_routes {
+        match run_tool("ip", &route::build_ip_route_args(None), timeout).await {
+            Ok(output) => record_routes(&mut scan, &local, &route::parse_route_json(&output)),
+            Err(e) => scan.warn(format!("routing table: {e}")),
+        }
+    }
+    for target in &request.traceroute_targets {
+        if let Err(e) = check_tool_target(target) {
+            scan.warn(format!("traceroute {target}: {e}"));
+            continue;
+        }
+        // The output parser reads hop addresses from the `name (ip)` form,
+        // which `traceroute -n` does not print.
+        let options = TracerouteOptions {
+            max_hops: request.max_hops.or(Some(30)),
+            resolve_hostnames: true,
+            ..TracerouteOptions::default()
+        };
+        let args = traceroute::build_traceroute_args(target, &options);
+        match run_tool("traceroute", &args, timeout).await {
+            Ok(output) => match traceroute::parse_traceroute_output(&output, target) {
+                Some(trace) => record_traceroute(&mut scan, &local, &trace),
+                None => scan.warn(format!("traceroute {target}: unparseable output")),
+            },
+            Err(e) => scan.warn(format!("traceroute {target}: {e}")),
+        }
+    }
+    scan
+}
+
+/// Targets go into a tool's argv as a positional argument; one that looks
+/// like an option (`-f`, `-i eth0`, ...) would be parsed as one instead.
+fn check_tool_target(target: &str) -> Result<(), String> {
+    if target.is_empty() {
+        return Err("empty target".to_string());
+    }
+    if target.starts_with('-') {
+        return Err("target must not start with '-'".to_string());
+    }
+    Ok(())
+}
+
+async fn scan_snmp_target(scan: &mut DiscoveryScan, client: &SnmpClient, target: &SnmpTarget) {
+    let host = &target.host;
+    let system = match system_info::get_system_info(client, target).await {
+        Ok(system) => system,
+        Err(e) => {
+            scan.warn(format!("SNMP {host}: {e}"));
+            return;
+        }
+    };
+    let interfaces = match ifmib::get_interfaces(client, target).await {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            scan.warn(format!("SNMP {host} ifTable: {e}"));
+            Vec::new()
+        }
+    };
+    let Some(device) = record_snmp_device(scan, target, &system, &interfaces) else {
+        scan.warn(format!("SNMP {host}: device has no usable identity"));
+        return;
+    };
+    let if_names = interface_names(&interfaces);
+
+    match table::get_table(client, target, LLDP_REM_ENTRY, &[]).await {
+        Ok(remote) if !remote.rows.is_empty() => {
+            let local_ports = table::get_table(client, target, LLDP_LOC_PORT_ENTRY, &[])
+                .await
+                .ok();
+            let management = table::get_table(client, target, LLDP_REM_MAN_ADDR_ENTRY, &[])
+                .await
+                .ok();
+            record_lldp_neighbors(
+                scan,
+                &device,
+                &remote,
+                local_ports.as_ref(),
+                management.as_ref(),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => scan.warn(format!("SNMP {host} LLDP: {e}")),
+    }
+    match table::get_table(client, target, CDP_CACHE_ENTRY, &[]).await {
+        Ok(cache) => record_cdp_neighbors(scan, &device, &cache, &if_names),
+        Err(e) => scan.warn(format!("SNMP {host} CDP: {e}")),
+    }
+    match table::get_arp_table(client, target).await {
+        Ok(media) => record_ip_net_to_media(scan, &device, &media, &if_names),
+        Err(e) => scan.warn(format!("SNMP {host} ipNetToMedia: {e}")),
+    }
+}
+
+async fn run_tool(program: &str, args: &[String], timeout: Duration) -> Result<String, String> {
+    let mut command = tokio::process::Command::new(program);
+    command.args(args).kill_on_drop(true);
+    let output = tokio::time::timeout(timeout, command.output())
+        .await
+        .map_err(|_| format!("{program} timed out after {}s", timeout.as_secs()))?
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "{program} exited with {}: {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn local_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Graph merge
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn is_discovered(metadata: &HashMap<String, String>) -> bool {
+    metadata.get(ORIGIN_KEY).map(String::as_str) == Some(ORIGIN_DISCOVERY)
+}
+
+fn join_sources(sources: &BTreeSet<DiscoverySource>) -> String {
+    sources
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn join(values: &BTreeSet<String>) -> String {
+    values.iter().cloned().collect::<Vec<_>>().join(",")
+}
+
+/// Merge a finished scan into the graph.
+///
+/// Devices that match an existing non-discovered node by IP address or
+/// hostname annotate that node instead of duplicating it. Discovery-owned
+/// nodes and edges use ids derived from device keys, so an unchanged network
+/// merges to an unchanged graph and [`diff::compute_diff`] reports only
+/// drift; those no longer observed are removed.
+pub fn merge_into_graph(
+    graph: &mut TopologyGraph,
+    devices: &[DiscoveredDevice],
+    links: &[DiscoveredLink],
+) {
+    let mut existing: HashMap<String, String> = HashMap::new();
+    for node in graph.nodes.values_mut() {
+        if is_discovered(&node.metadata) {
+            continue;
+        }
+        for key in ANNOTATION_KEYS {
+            node.metadata.remove(key);
+        }
+        for value in [&node.ip_address, &node.hostname].into_iter().flatten() {
+            if let Some(key) = ip_key(value).or_else(|| name_key(value)) {
+                existing.entry(key).or_insert_with(|| node.id.clone());
+            }
+        }
+    }
+
+    let mut node_ids: HashMap<&str, String> = HashMap::new();
+    for device in devices {
+        let matched = device
+            .ip_addresses
+            .iter()
+            .filter_map(|ip| ip_key(ip))
+            .chain(device.names.iter().filter_map(|n| name_key(n)))
+            .find_map(|key| existing.get(&key).cloned());
+
+        if let Some(id) = matched {
+            if let Some(node) = graph.nodes.get_mut(&id) {
+                node.metadata
+                    .insert(DISCOVERED_BY_KEY.to_string(), join_sources(&device.sources));
+                node.metadata
+                    .insert("discovered_ips".to_string(), join(&device.ip_addresses));
+                node.metadata
+                    .insert("discovered_macs".to_string(), join(&device.mac_addresses));
+                if let Some(descr) = &device.description {
+                    node.metadata
+                        .insert("discovered_description".to_string(), descr.clone());
+                }
+            }
+            node_ids.insert(&device.key, id);
+            continue;
+        }
+
+        let id = format!("disc-{}", device.key);
+        let previous = graph.nodes.get(&id);
+        let mut metadata = HashMap::new();
+        metadata.insert(ORIGIN_KEY.to_string(), ORIGIN_DISCOVERY.to_string());
+        metadata.insert(DISCOVERED_BY_KEY.to_string(), join_sources(&device.sources));
+        metadata.insert("ip_addresses".to_string(), join(&device.ip_addresses));
+        metadata.insert("mac_addresses".to_string(), join(&device.mac_addresses));
+        if let Some(descr) = &device.description {
+            metadata.insert("description".to_string(), descr.clone());
+        }
+        let node = TopologyNode {
+            id: id.clone(),
+            label: device.label.clone(),
+            node_type: device.role.node_type(),
+            hostname: device.names.iter().next().cloned(),
+            ip_address: device.ip_addresses.iter().next().cloned(),
+            port: None,
+            protocol: None,
+            status: NodeStatus::Online,
+            geo: previous.and_then(|n| n.geo.clone()),
+            group_id: previous.and_then(|n| n.group_id.clone()),
+            metadata,
+            position: previous.and_then(|n| n.position),
+        };
+        let _ = graph.add_node(node);
+        node_ids.insert(&device.key, id);
+    }
+
+    let live_nodes: HashSet<&String> = node_ids.values().collect();
+    let stale: Vec<String> = graph
+        .nodes
+        .values()
+        .filter(|n| is_discovered(&n.metadata) && !live_nodes.contains(&n.id))
+        .map(|n| n.id.clone())
+        .collect();
+    for id in stale {
+        let _ = graph.remove_node(&id);
+    }
+
+    let mut edges: Vec<TopologyEdge> = Vec::new();
+    let mut edge_ids: HashSet<String> = HashSet::new();
+    for link in links {
+        let (Some(source_id), Some(target_id)) = (
+            node_ids.get(link.source_key.as_str()),
+            node_ids.get(link.target_key.as_str()),
+        ) else {
+            continue;
+        };
+        let id = format!("disc-link-{source_id}-{target_id}");
+        // Two devices may have matched the same existing node.
+        if source_id == target_id || !edge_ids.insert(id.clone()) {
+            continue;
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert(ORIGIN_KEY.to_string(), ORIGIN_DISCOVERY.to_string());
+        metadata.insert(DISCOVERED_BY_KEY.to_string(), join_sources(&link.sources));
+        if let Some(port) = &link.source_port {
+            metadata.insert("source_port".to_string(), port.clone());
+        }
+        if let Some(port) = &link.target_port {
+            metadata.insert("target_port".to_string(), port.clone());
+        }
+        let label = match (&link.source_port, &link.target_port) {
+            (Some(a), Some(b)) => Some(format!("{a} ↔ {b}")),
+            (Some(p), None) | (None, Some(p)) => Some(p.clone()),
+            (None, None) => None,
+        };
+        edges.push(TopologyEdge {
+            id,
+            source_id: source_id.clone(),
+            target_id: target_id.clone(),
+            edge_type: EdgeType::NetworkLink,
+            label,
+            latency_ms: link.latency_ms,
+            bandwidth: None,
+            encrypted: false,
+            metadata,
+        });
+    }
+
+    graph.edges.retain(|e| !is_discovered(&e.metadata));
+    graph.edges.extend(edges);
+    graph.last_updated = Utc::now();
+}
+
+/// Merge a scan into the graph and report the drift it caused.
+pub fn apply_scan(graph: &mut TopologyGraph, scan: DiscoveryScan) -> DiscoveryReport {
+    let before = graph.clone();
+    let started_at = scan.started_at;
+    let warnings = scan.warnings.clone();
+    let (devices, links) = scan.finish();
+    merge_into_graph(graph, &devices, &links);
+    DiscoveryReport {
+        started_at,
+        finished_at: Utc::now(),
+        drift: diff::compute_diff(&before, graph),
+        devices,
+        links,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sorng_snmp::types::SnmpTableRow;
+
+    fn table(rows: Vec<(&str, Vec<(&str, SnmpValue)>)>) -> SnmpTable {
+        SnmpTable {
+            base_oid: String::new(),
+            table_name: None,
+            columns: Vec::new(),
+            column_names: Vec::new(),
+            rows: rows
+                .into_iter()
+                .map(|(index, values)| SnmpTableRow {
+                    index: index.to_string(),
+                    values: values
+                        .into_iter()
+                        .map(|(c, v)| (c.to_string(), v))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn core_switch_scan(with_neighbor: bool) -> DiscoveryScan {
+        let mut scan = DiscoveryScan::new();
+        let core = scan
+            .observe(
+                DeviceObservation::new(DiscoverySource::SnmpSystem, DeviceRole::Switch)
+                    .name("core-sw")
+                    .ip("10.0.0.2"),
+            )
+            .unwrap();
+        let mut rows = Vec::new();
+        if with_neighbor {
+            rows.push((
+                "0.12.1",
+                vec![
+                    ("4", SnmpValue::Integer(4)),
+                    ("5", SnmpValue::OctetString("00163e112233".to_string())),
+                    ("6", SnmpValue::Integer(5)),
+                    ("7", SnmpValue::OctetString("Gi0/1".to_string())),
+                    ("9", SnmpValue::OctetString("edge-rtr".to_string())),
+                    ("12", SnmpValue::OctetString("0800".to_string())),
+                ],
+            ));
+        }
+        let ports = table(vec![(
+            "12",
+            vec![("4", SnmpValue::OctetString("Te1/0/12".to_string()))],
+        )]);
+        let management = table(vec![(
+            "0.12.1.1.4.10.0.0.1",
+            vec![("3", SnmpValue::Integer(2))],
+        )]);
+        record_lldp_neighbors(
+            &mut scan,
+            &core,
+            &table(rows),
+            Some(&ports),
+            Some(&management),
+        );
+        scan
+    }
+
+    #[test]
+    fn option_like_tool_targets_are_rejected() {
+        assert!(check_tool_target("10.0.0.1").is_ok());
+        assert!(check_tool_target("gw.example.net").is_ok());
+        assert!(check_tool_target("-f").is_err());
+        assert!(check_tool_target("-ieth0").is_err());
+        assert!(check_tool_target("").is_err());
+    }
+
+    #[test]
+    fn lldp_neighbors_become_links_with_ports() {
+        let (devices, links) = core_switch_scan(true).finish();
+        let router = devices.iter().find(|d| d.key == "name:edge-rtr").unwrap();
+        assert_eq!(router.role, DeviceRole::Router);
+        assert!(router.ip_addresses.contains("10.0.0.1"));
+        assert!(router.mac_addresses.contains("00:16:3e:11:22:33"));
+
+        assert_eq!(links.len(), 1);
+        let link = &links[0];
+        assert_eq!(link.source_key, "name:core-sw");
+        assert_eq!(link.target_key, "name:edge-rtr");
+        assert_eq!(link.source_port.as_deref(), Some("Te1/0/12"));
+        assert_eq!(link.target_port.as_deref(), Some("Gi0/1"));
+    }
+
+    #[test]
+    fn observations_sharing_an_address_are_one_device() {
+        let mut scan = DiscoveryScan::new();
+        let local = scan
+            .observe(DeviceObservation::new(DiscoverySource::Arp, DeviceRole::Host).name("laptop"))
+            .unwrap();
+        let arp = vec![ArpEntry {
+            ip: "192.168.1.1".to_string(),
+            mac: "AA-BB-CC-00-11-22".to_string(),
+            interface: "wlan0".to_string(),
+            state: ArpState::Reachable,
+            hw_type: None,
+            flags: None,
+        }];
+        record_arp_entries(&mut scan, &local, &arp);
+        // The gateway's own ifTable reports the same MAC under its sysName.
+        scan.observe(
+            DeviceObservation::new(DiscoverySource::IfTable, DeviceRole::Router)
+                .name("gw")
+                .mac("aabb.cc00.1122"),
+        );
+
+        let (devices, links) = scan.finish();
+        assert_eq!(devices.len(), 2);
+        let gw = devices.iter().find(|d| d.key == "name:gw").unwrap();
+        assert_eq!(gw.role, DeviceRole::Router);
+        assert!(gw.ip_addresses.contains("192.168.1.1"));
+        assert_eq!(
+            gw.sources,
+            BTreeSet::from([DiscoverySource::IfTable, DiscoverySource::Arp])
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target_key, "name:laptop");
+        assert_eq!(links[0].target_port.as_deref(), Some("wlan0"));
+    }
+
+    #[test]
+    fn repeated_scans_report_only_drift() {
+        let mut graph = TopologyGraph::new();
+        let _ = graph.add_node(TopologyNode {
+            id: "conn-1".to_string(),
+            label: "Core switch".to_string(),
+            node_type: NodeType::Connection,
+            hostname: Some("10.0.0.2".to_string()),
+            ip_address: None,
+            port: Some(22),
+            protocol: Some("ssh".to_string()),
+            status: NodeStatus::Unknown,
+            geo: None,
+            group_id: None,
+            metadata: HashMap::new(),
+            position: None,
+        });
+
+        let first = apply_scan(&mut graph, core_switch_scan(true));
+        // The saved connection is annotated rather than duplicated.
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(
+            graph.nodes["conn-1"].metadata[DISCOVERED_BY_KEY],
+            "snmp_system"
+        );
+        assert_eq!(
+            first.drift.added_nodes,
+            vec!["disc-name:edge-rtr".to_string()]
+        );
+        assert_eq!(graph.edges[0].source_id, "conn-1");
+
+        let second = apply_scan(&mut graph, core_switch_scan(true));
+        assert!(second.drift.added_nodes.is_empty());
+        assert!(second.drift.added_edges.is_empty());
+        assert!(second.drift.changed_nodes.is_empty());
+        assert!(second.drift.changed_edges.is_empty());
+
+        let third = apply_scan(&mut graph, core_switch_scan(false));
+        assert_eq!(
+            third.drift.removed_nodes,
+            vec!["disc-name:edge-rtr".to_string()]
+        );
+        assert_eq!(third.drift.removed_edges.len(), 1);
+        assert!(graph.nodes.contains_key("conn-1"));
+    }
+}
@@ -10,6 +10,9 @@
 //!   detection, bridge detection, dependency depth, redundancy analysis
 //! - **Builder** — construct a topology graph from connection data including proxy
 //!   chains, tunnel chains, and jump-host hops
+//! - **Discovery** — walk LLDP/CDP neighbour tables, ifTable and ipNetToMedia over
+//!   SNMP, the local ARP and routing tables, and traceroute hops, merging the
+//!   discovered switches, routers and links into the graph with provenance
 //! - **Diff / snapshots** — compute structural diffs between graph versions and
 //!   restore prior snapshots
 //! - **Tauri commands** — full set of `topo_*` IPC commands for the frontend
@@ -17,6 +20,7 @@
 pub mod analysis;
 pub mod builder;
 pub mod diff;
+pub mod discovery;
 pub mod error;
 pub mod graph;
 pub mod layout;
@@ -3,6 +3,7 @@
 use crate::analysis;
 use crate::builder::{self, ConnectionData};
 use crate::diff;
+use crate::discovery::{self, DiscoveryReport, DiscoveryScan};
 use crate::error::TopologyError;
 use crate::layout;
 use crate::types::*;
@@ -39,6 +40,12 @@ impl TopologyService {
         self.graph.layout_config = self.config.clone();
     }
 
+    /// Merge a finished discovery scan into the current graph. The report's
+    /// drift is the diff against the graph before the merge.
+    pub fn apply_discovery(&mut self, scan: DiscoveryScan) -> DiscoveryReport {
+        discovery::apply_scan(&mut self.graph, scan)
+    }
+
     // ━━━━━━━━━━━━━━━ Graph read ━━━━━━━━━━━━━━━
 
     pub fn get_graph(&self) -> &TopologyGraph {
@@ -1,7 +1,10 @@
 //! Framebuffer encoding decoders.
 //!
 //! Decodes pixel data received in FramebufferUpdate rectangles into a
-//! uniform RGBA pixel buffer.
+//! uniform RGBA pixel buffer. ZRLE and Tight keep zlib dictionaries
+//! across rectangles, so their state lives in [`FrameDecoders`].
+
+use flate2::{Decompress, FlushDecompress, Status};
 
 use crate::vnc::types::PixelFormat;
 use crate::vnc::types::{MAX_VNC_RECT_RGBA_BYTES, MAX_VNC_RECT_WIRE_BYTES, MAX_VNC_SUBRECTANGLES};
@@ -307,6 +310,616 @@ pub fn decode_hextile(
     })
 }
 
+// ── Persistent zlib streams (ZRLE / Tight) ──────────────────────────────
+
+/// A zlib inflate stream whose dictionary persists across rectangles.
+///
+/// ZRLE uses one stream for the lifetime of the connection and Tight uses
+/// four, so each must outlive a single FramebufferUpdate.
+pub struct ZlibStream {
+    inner: Decompress,
+}
+
+impl std::fmt::Debug for ZlibStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZlibStream")
+            .field("total_in", &self.inner.total_in())
+            .field("total_out", &self.inner.total_out())
+            .finish()
+    }
+}
+
+impl Default for ZlibStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZlibStream {
+    pub fn new() -> Self {
+        Self {
+            inner: Decompress::new(true),
+        }
+    }
+
+    /// Discard the dictionary; the next chunk must start a new zlib stream.
+    pub fn reset(&mut self) {
+        self.inner.reset(true);
+    }
+
+    /// Inflate one server chunk, refusing to produce more than `limit` bytes.
+    ///
+    /// Servers sync-flush after every rectangle, so consuming the whole chunk
+    /// yields all of the rectangle's data.
+    pub fn inflate(&mut self, input: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+        let hard_cap = limit.saturating_add(1);
+        let mut out = Vec::with_capacity(hard_cap.min(input.len().saturating_mul(4).max(1024)));
+        let start_in = self.inner.total_in();
+        loop {
+            let consumed = (self.inner.total_in() - start_in) as usize;
+            if out.len() == out.capacity() {
+                out.reserve_exact(out.capacity().max(1024).min(hard_cap - out.len()));
+            }
+            let produced_before = out.len();
+            let status = self
+                .inner
+                .decompress_vec(&input[consumed..], &mut out, FlushDecompress::Sync)
+                .map_err(|e| format!("zlib inflate failed: {e}"))?;
+            if out.len() > limit {
+                return Err("Inflated data exceeds the rectangle safety limit".into());
+            }
+            let now_consumed = (self.inner.total_in() - start_in) as usize;
+            if matches!(status, Status::StreamEnd)
+                || (now_consumed == input.len() && out.len() < out.capacity())
+            {
+                break;
+            }
+            if now_consumed == consumed && out.len() == produced_before {
+                if now_consumed == input.len() {
+                    break;
+                }
+                return Err("zlib stream stalled before the end of the chunk".into());
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Decoder state that must survive between FramebufferUpdate messages.
+#[derive(Debug, Default)]
+pub struct FrameDecoders {
+    /// The single ZRLE zlib stream.
+    pub zrle: ZlibStream,
+    /// Tight's four independently resettable zlib streams.
+    pub tight: [ZlibStream; 4],
+}
+
+/// Sequential reader over an in-memory payload.
+struct ByteCursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize, what: &str) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("{what} truncated"))?;
+        let slice = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self, what: &str) -> Result<u8, String> {
+        Ok(self.take(1, what)?[0])
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+}
+
+// ── ZRLE ────────────────────────────────────────────────────────────────
+
+const ZRLE_TILE: usize = 64;
+
+/// How a CPIXEL maps onto the negotiated pixel format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CpixelLayout {
+    /// Same width as a normal pixel.
+    Full(usize),
+    /// 32bpp pixel whose colour bits all live in the least significant 3 bytes.
+    LowThree,
+    /// 32bpp pixel whose colour bits all live in the most significant 3 bytes.
+    HighThree,
+}
+
+impl CpixelLayout {
+    fn for_format(pf: &PixelFormat) -> Self {
+        if pf.true_colour && pf.bits_per_pixel == 32 && pf.depth <= 24 {
+            let mask = (u32::from(pf.red_max) << pf.red_shift)
+                | (u32::from(pf.green_max) << pf.green_shift)
+                | (u32::from(pf.blue_max) << pf.blue_shift);
+            if mask & 0xFF00_0000 == 0 {
+                return Self::LowThree;
+            }
+            if mask & 0x0000_00FF == 0 {
+                return Self::HighThree;
+            }
+        }
+        Self::Full(pf.bytes_per_pixel())
+    }
+
+    fn len(self) -> usize {
+        match self {
+            Self::Full(len) => len,
+            Self::LowThree | Self::HighThree => 3,
+        }
+    }
+
+    fn to_rgba(self, bytes: &[u8], pf: &PixelFormat) -> [u8; 4] {
+        let low_in_first = matches!(self, Self::LowThree) != pf.big_endian;
+        match self {
+            Self::Full(_) => pixel_to_rgba(bytes, pf),
+            _ if low_in_first => pixel_to_rgba(&[bytes[0], bytes[1], bytes[2], 0], pf),
+            _ => pixel_to_rgba(&[0, bytes[0], bytes[1], bytes[2]], pf),
+        }
+    }
+}
+
+/// Upper bound on the inflated size of a ZRLE rectangle.
+fn zrle_inflated_limit(width: u16, height: u16, cpixel_len: usize) -> Result<usize, String> {
+    let tiles = (width as usize).div_ceil(ZRLE_TILE) * (height as usize).div_ceil(ZRLE_TILE);
+    // Worst case per tile is a 127-entry palette header; worst case per
+    // pixel is a CPIXEL plus a two-byte run length.
+    let per_pixel = checked_pixel_bytes(width, height, cpixel_len + 2, usize::MAX)?;
+    tiles
+        .checked_mul(1 + 127 * cpixel_len)
+        .and_then(|header| header.checked_add(per_pixel))
+        .ok_or_else(|| "ZRLE inflated size overflow".to_string())
+}
+
+fn read_run_length(cursor: &mut ByteCursor<'_>, max: usize) -> Result<usize, String> {
+    let mut length = 1usize;
+    loop {
+        let b = cursor.u8("ZRLE run length")?;
+        length += b as usize;
+        if length > max {
+            return Err("ZRLE run overflows the tile".into());
+        }
+        if b != 255 {
+            return Ok(length);
+        }
+    }
+}
+
+/// Decode a ZRLE-encoded rectangle.
+///
+/// `data` is the zlib payload that followed the 4-byte length on the wire;
+/// it is inflated through the connection's persistent ZRLE stream.
+pub fn decode_zrle(
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    data: &[u8],
+    pixel_format: &PixelFormat,
+    stream: &mut ZlibStream,
+) -> Result<DecodedRect, String> {
+    pixel_format.validate().map_err(|error| error.message)?;
+    let layout = CpixelLayout::for_format(pixel_format);
+    let cpixel_len = layout.len();
+    let rgba_len = checked_pixel_bytes(width, height, 4, MAX_VNC_RECT_RGBA_BYTES)?;
+    let inflated = stream.inflate(data, zrle_inflated_limit(width, height, cpixel_len)?)?;
+    let mut cursor = ByteCursor::new(&inflated);
+    let mut pixels = vec![0u8; rgba_len];
+    let w = width as usize;
+    let h = height as usize;
+
+    for tile_y in (0..h).step_by(ZRLE_TILE) {
+        for tile_x in (0..w).step_by(ZRLE_TILE) {
+            let tile_w = ZRLE_TILE.min(w - tile_x);
+            let tile_h = ZRLE_TILE.min(h - tile_y);
+            let tile_pixels = tile_w * tile_h;
+            let mut tile = Vec::with_capacity(tile_pixels * 4);
+            let subencoding = cursor.u8("ZRLE sub-encoding")?;
+
+            match subencoding {
+                0 => {
+                    for _ in 0..tile_pixels {
+                        let px = cursor.take(cpixel_len, "ZRLE raw tile")?;
+                        tile.extend_from_slice(&layout.to_rgba(px, pixel_format));
+                    }
+                }
+                1 => {
+                    let colour =
+                        layout.to_rgba(cursor.take(cpixel_len, "ZRLE solid tile")?, pixel_format);
+                    for _ in 0..tile_pixels {
+                        tile.extend_from_slice(&colour);
+                    }
+                }
+                2..=16 => {
+                    let palette =
+                        read_palette(&mut cursor, subencoding as usize, layout, pixel_format)?;
+                    let bits = match subencoding {
+                        2 => 1,
+                        3 | 4 => 2,
+                        _ => 4,
+                    };
+                    let row_bytes = (tile_w * bits).div_ceil(8);
+                    for _ in 0..tile_h {
+                        let row = cursor.take(row_bytes, "ZRLE packed palette row")?;
+                        for col in 0..tile_w {
+                            let bit = col * bits;
+                            let index = (row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1);
+                            let colour = palette
+                                .get(index as usize)
+                                .ok_or("ZRLE palette index out of range")?;
+                            tile.extend_from_slice(colour);
+                        }
+                    }
+                }
+                128 => {
+                    while tile.len() < tile_pixels * 4 {
+                        let colour = layout
+                            .to_rgba(cursor.take(cpixel_len, "ZRLE RLE pixel")?, pixel_format);
+                        let run = read_run_length(&mut cursor, tile_pixels - tile.len() / 4)?;
+                        for _ in 0..run {
+                            tile.extend_from_slice(&colour);
+                        }
+                    }
+                }
+                130..=255 => {
+                    let palette = read_palette(
+                        &mut cursor,
+                        subencoding as usize - 128,
+                        layout,
+                        pixel_format,
+                    )?;
+                    while tile.len() < tile_pixels * 4 {
+                        let index = cursor.u8("ZRLE palette RLE index")?;
+                        let colour = palette
+                            .get((index & 0x7F) as usize)
+                            .ok_or("ZRLE palette index out of range")?;
+                        let run = if index & 0x80 != 0 {
+                            read_run_length(&mut cursor, tile_pixels - tile.len() / 4)?
+                        } else {
+                            1
+                        };
+                        for _ in 0..run {
+                            tile.extend_from_slice(colour);
+                        }
+                    }
+                }
+                _ => return Err(format!("Invalid ZRLE sub-encoding {subencoding}")),
+            }
+
+            blit_tile(&mut pixels, w, tile_x, tile_y, tile_w, tile_h, &tile);
+        }
+    }
+
+    if cursor.remaining() != 0 {
+        return Err("ZRLE payload has trailing data".into());
+    }
+
+    Ok(DecodedRect {
+        x,
+        y,
+        width,
+        height,
+        source_x: None,
+        source_y: None,
+        pixels,
+    })
+}
+
+fn read_palette(
+    cursor: &mut ByteCursor<'_>,
+    size: usize,
+    layout: CpixelLayout,
+    pf: &PixelFormat,
+) -> Result<Vec<[u8; 4]>, String> {
+    let mut palette = Vec::with_capacity(size);
+    for _ in 0..size {
+        palette.push(layout.to_rgba(cursor.take(layout.len(), "ZRLE palette")?, pf));
+    }
+    Ok(palette)
+}
+
+// ── Tight ───────────────────────────────────────────────────────────────
+
+/// Tight compression-control values (upper nibble of the control byte).
+pub mod tight {
+    pub const FILL: u8 = 0x08;
+    pub const JPEG: u8 = 0x09;
+    /// Bit set in the control byte when a filter-id byte follows.
+    pub const EXPLICIT_FILTER: u8 = 0x40;
+    pub const FILTER_COPY: u8 = 0;
+    pub const FILTER_PALETTE: u8 = 1;
+    pub const FILTER_GRADIENT: u8 = 2;
+    /// Payloads shorter than this are sent without zlib compression.
+    pub const MIN_TO_COMPRESS: usize = 12;
+}
+
+/// Size of a Tight TPIXEL for the negotiated format.
+pub fn tight_tpixel_len(pf: &PixelFormat) -> usize {
+    if tight_packed_rgb(pf) {
+        3
+    } else {
+        pf.bytes_per_pixel()
+    }
+}
+
+/// True when TPIXELs are sent as three R, G, B bytes.
+fn tight_packed_rgb(pf: &PixelFormat) -> bool {
+    pf.true_colour
+        && pf.bits_per_pixel == 32
+        && pf.depth == 24
+        && pf.red_max == 255
+        && pf.green_max == 255
+        && pf.blue_max == 255
+}
+
+fn tpixel_to_rgba(bytes: &[u8], pf: &PixelFormat) -> [u8; 4] {
+    if tight_packed_rgb(pf) {
+        [bytes[0], bytes[1], bytes[2], 255]
+    } else {
+        pixel_to_rgba(bytes, pf)
+    }
+}
+
+/// Size of the uncompressed data block of a basic-compression rectangle.
+pub fn tight_basic_data_len(
+    width: u16,
+    height: u16,
+    filter: u8,
+    palette_size: usize,
+    pf: &PixelFormat,
+) -> Result<usize, String> {
+    match filter {
+        tight::FILTER_COPY | tight::FILTER_GRADIENT => {
+            checked_pixel_bytes(width, height, tight_tpixel_len(pf), MAX_VNC_RECT_WIRE_BYTES)
+        }
+        tight::FILTER_PALETTE if palette_size == 2 => (width as usize)
+            .div_ceil(8)
+            .checked_mul(height as usize)
+            .filter(|size| *size <= MAX_VNC_RECT_WIRE_BYTES)
+            .ok_or_else(|| "Tight palette data exceeds the safety limit".to_string()),
+        tight::FILTER_PALETTE => checked_pixel_bytes(width, height, 1, MAX_VNC_RECT_WIRE_BYTES),
+        other => Err(format!("Invalid Tight filter id {other}")),
+    }
+}
+
+/// Parse a Tight compact length (1–3 bytes, 7 bits per byte, last byte 8 bits).
+///
+/// Returns the value and the number of bytes consumed, or `None` when the
+/// slice ends before the length is complete.
+pub fn parse_tight_compact_len(data: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0usize;
+    for (i, byte) in data.iter().take(3).enumerate() {
+        if i == 2 {
+            value |= (*byte as usize) << 14;
+            return Some((value, 3));
+        }
+        value |= ((*byte & 0x7F) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Read a basic-compression data block, inflating it through `stream`
+/// when the server compressed it.
+fn read_tight_block(
+    cursor: &mut ByteCursor<'_>,
+    raw_len: usize,
+    stream: &mut ZlibStream,
+) -> Result<Vec<u8>, String> {
+    if raw_len < tight::MIN_TO_COMPRESS {
+        return Ok(cursor.take(raw_len, "Tight uncompressed data")?.to_vec());
+    }
+    let (len, used) = parse_tight_compact_len(&cursor.data[cursor.offset..])
+        .ok_or("Tight compact length truncated")?;
+    cursor.offset += used;
+    let zlib = cursor.take(len, "Tight zlib data")?;
+    let inflated = stream.inflate(zlib, raw_len)?;
+    if inflated.len() != raw_len {
+        return Err(format!(
+            "Tight data inflated to {} bytes, expected {raw_len}",
+            inflated.len()
+        ));
+    }
+    Ok(inflated)
+}
+
+/// Decode a Tight-encoded rectangle.
+///
+/// `data` holds the complete wire payload (control byte onwards) as
+/// collected by the session reader; `streams` are the connection's four
+/// persistent Tight zlib streams.
+pub fn decode_tight(
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    data: &[u8],
+    pixel_format: &PixelFormat,
+    streams: &mut [ZlibStream; 4],
+) -> Result<DecodedRect, String> {
+    pixel_format.validate().map_err(|error| error.message)?;
+    let rgba_len = checked_pixel_bytes(width, height, 4, MAX_VNC_RECT_RGBA_BYTES)?;
+    let tpixel_len = tight_tpixel_len(pixel_format);
+    let mut cursor = ByteCursor::new(data);
+    let control = cursor.u8("Tight compression control")?;
+    for (i, stream) in streams.iter_mut().enumerate() {
+        if control & (1 << i) != 0 {
+            stream.reset();
+        }
+    }
+
+    let pixels = match control >> 4 {
+        tight::FILL => {
+            let colour =
+                tpixel_to_rgba(cursor.take(tpixel_len, "Tight fill colour")?, pixel_format);
+            colour.repeat(rgba_len / 4)
+        }
+        tight::JPEG => {
+            let (len, used) = parse_tight_compact_len(&data[cursor.offset..])
+                .ok_or("Tight JPEG length truncated")?;
+            cursor.offset += used;
+            decode_tight_jpeg(cursor.take(len, "Tight JPEG data")?, width, height)?
+        }
+        kind if kind & 0x08 == 0 => {
+            let stream = &mut streams[(kind & 0x03) as usize];
+            let filter = if control & tight::EXPLICIT_FILTER != 0 {
+                cursor.u8("Tight filter id")?
+            } else {
+                tight::FILTER_COPY
+            };
+            match filter {
+                tight::FILTER_COPY => {
+                    let raw_len = tight_basic_data_len(width, height, filter, 0, pixel_format)?;
+                    let block = read_tight_block(&mut cursor, raw_len, stream)?;
+                    let mut out = Vec::with_capacity(rgba_len);
+                    for px in block.chunks_exact(tpixel_len) {
+                        out.extend_from_slice(&tpixel_to_rgba(px, pixel_format));
+                    }
+                    out
+                }
+                tight::FILTER_PALETTE => {
+                    let size = cursor.u8("Tight palette size")? as usize + 1;
+                    let mut palette = Vec::with_capacity(size);
+                    for _ in 0..size {
+                        let px = cursor.take(tpixel_len, "Tight palette")?;
+                        palette.push(tpixel_to_rgba(px, pixel_format));
+                    }
+                    let raw_len = tight_basic_data_len(width, height, filter, size, pixel_format)?;
+                    let block = read_tight_block(&mut cursor, raw_len, stream)?;
+                    decode_tight_palette(&block, &palette, width, height)?
+                }
+                tight::FILTER_GRADIENT => {
+                    if pixel_format.bits_per_pixel == 8 {
+                        return Err("Tight gradient filter requires 16 or 32 bpp".into());
+                    }
+                    let raw_len = tight_basic_data_len(width, height, filter, 0, pixel_format)?;
+                    let block = read_tight_block(&mut cursor, raw_len, stream)?;
+                    decode_tight_gradient(&block, width, height, pixel_format)
+                }
+                other => return Err(format!("Invalid Tight filter id {other}")),
+            }
+        }
+        other => return Err(format!("Invalid Tight compression type {other:#x}")),
+    };
+
+    if cursor.remaining() != 0 {
+        return Err("Tight payload has trailing data".into());
+    }
+
+    Ok(DecodedRect {
+        x,
+        y,
+        width,
+        height,
+        source_x: None,
+        source_y: None,
+        pixels,
+    })
+}
+
+fn decode_tight_palette(
+    block: &[u8],
+    palette: &[[u8; 4]],
+    width: u16,
+    height: u16,
+) -> Result<Vec<u8>, String> {
+    let w = width as usize;
+    let mut out = Vec::with_capacity(w * height as usize * 4);
+    if palette.len() == 2 {
+        for row in block.chunks_exact(w.div_ceil(8)) {
+            for col in 0..w {
+                let bit = (row[col / 8] >> (7 - col % 8)) & 1;
+                out.extend_from_slice(&palette[bit as usize]);
+            }
+        }
+    } else {
+        for index in block {
+            let colour = palette
+                .get(*index as usize)
+                .ok_or("Tight palette index out of range")?;
+            out.extend_from_slice(colour);
+        }
+    }
+    Ok(out)
+}
+
+/// Split a TPIXEL into its raw (unscaled) colour components.
+fn tpixel_components(bytes: &[u8], pf: &PixelFormat) -> [u16; 3] {
+    if tight_packed_rgb(pf) {
+        return [bytes[0] as u16, bytes[1] as u16, bytes[2] as u16];
+    }
+    let value = match bytes.len() {
+        2 if pf.big_endian => u16::from_be_bytes([bytes[0], bytes[1]]) as u32,
+        2 => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+        4 if pf.big_endian => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        4 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        _ => bytes.first().copied().unwrap_or(0) as u32,
+    };
+    [
+        ((value >> pf.red_shift) & pf.red_max as u32) as u16,
+        ((value >> pf.green_shift) & pf.green_max as u32) as u16,
+        ((value >> pf.blue_shift) & pf.blue_max as u32) as u16,
+    ]
+}
+
+/// Undo the Tight gradient filter: each component was sent as the
+/// difference from `left + above - above_left`, clamped to the channel max.
+fn decode_tight_gradient(block: &[u8], width: u16, height: u16, pf: &PixelFormat) -> Vec<u8> {
+    let w = width as usize;
+    let tpixel_len = tight_tpixel_len(pf);
+    let maxes = [pf.red_max as i32, pf.green_max as i32, pf.blue_max as i32];
+    let mut previous_row = vec![[0i32; 3]; w];
+    let mut current_row = vec![[0i32; 3]; w];
+    let mut out = Vec::with_capacity(w * height as usize * 4);
+
+    for row in block.chunks_exact(w * tpixel_len) {
+        for (col, px) in row.chunks_exact(tpixel_len).enumerate() {
+            let diff = tpixel_components(px, pf);
+            let mut rgba = [0u8, 0, 0, 255];
+            for c in 0..3 {
+                let left = if col > 0 { current_row[col - 1][c] } else { 0 };
+                let above_left = if col > 0 { previous_row[col - 1][c] } else { 0 };
+                let prediction = (left + previous_row[col][c] - above_left).clamp(0, maxes[c]);
+                let value = (prediction + diff[c] as i32) & maxes[c];
+                current_row[col][c] = value;
+                rgba[c] = (value * 255 / maxes[c]) as u8;
+            }
+            out.extend_from_slice(&rgba);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    out
+}
+
+fn decode_tight_jpeg(data: &[u8], width: u16, height: u16) -> Result<Vec<u8>, String> {
+    let image = image::load_from_memory_with_format(data, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Tight JPEG decode failed: {e}"))?;
+    if image.width() != u32::from(width) || image.height() != u32::from(height) {
+        return Err(format!(
+            "Tight JPEG is {}x{}, rectangle is {width}x{height}",
+            image.width(),
+            image.height()
+        ));
+    }
+    Ok(image.to_rgba8().into_raw())
+}
+
 /// Calculate the expected raw data size for a rectangle.
 pub fn raw_data_size(width: u16, height: u16, pixel_format: &PixelFormat) -> usize {
     checked_pixel_bytes(
@@ -690,4 +1303,307 @@ mod tests {
         // All pixels should be blue.
         assert_eq!(rect.pixels[2], 255); // B
     }
+
+    // ── ZRLE ────────────────────────────────────────────────────────
+
+    /// Compress `chunks` on one zlib stream with a sync flush after each,
+    /// the way servers emit consecutive ZRLE/Tight rectangles.
+    fn server_stream(chunks: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut compress = flate2::Compress::new(flate2::Compression::default(), true);
+        chunks
+            .iter()
+            .map(|chunk| {
+                let mut out = Vec::with_capacity(chunk.len() + 64);
+                compress
+                    .compress_vec(chunk, &mut out, flate2::FlushCompress::Sync)
+                    .unwrap();
+                out
+            })
+            .collect()
+    }
+
+    fn zrle(w: u16, h: u16, inflated: &[u8]) -> Result<DecodedRect, String> {
+        let chunk = server_stream(&[inflated]).remove(0);
+        decode_zrle(0, 0, w, h, &chunk, &rgba32(), &mut ZlibStream::new())
+    }
+
+    // Two consecutive rectangles, compressed once with flate2 (zlib level 6,
+    // sync flush after each, as servers do) and pinned here; they are not
+    // captures from a real server. A solid red 4×4 tile, then a 4×2
+    // palette-RLE tile of five green and three blue pixels. The second chunk
+    // has no zlib header and only decodes on the persistent stream.
+    const ZRLE_GOLDEN_RECT1: [u8; 12] = [
+        0x78, 0x9c, 0x62, 0x64, 0x60, 0xf8, 0x0f, 0x00, 0x00, 0x00, 0xff, 0xff,
+    ];
+    const ZRLE_GOLDEN_RECT2: [u8; 16] = [
+        0x6a, 0x62, 0xf8, 0x0f, 0x84, 0x0c, 0x0d, 0x2c, 0x8d, 0x4c, 0x00, 0x00, 0x00, 0x00, 0xff,
+        0xff,
+    ];
+
+    #[test]
+    fn decode_zrle_golden_stream() {
+        let pf = rgba32();
+        let mut stream = ZlibStream::new();
+        let first = decode_zrle(0, 0, 4, 4, &ZRLE_GOLDEN_RECT1, &pf, &mut stream).unwrap();
+        assert!(first.pixels.chunks(4).all(|p| p == [255, 0, 0, 255]));
+
+        let second = decode_zrle(4, 0, 4, 2, &ZRLE_GOLDEN_RECT2, &pf, &mut stream).unwrap();
+        let colours: Vec<&[u8]> = second.pixels.chunks(4).collect();
+        assert!(colours[..5].iter().all(|p| *p == [0, 255, 0, 255]));
+        assert!(colours[5..].iter().all(|p| *p == [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn decode_zrle_requires_persistent_stream() {
+        let pf = rgba32();
+        let mut fresh = ZlibStream::new();
+        assert!(decode_zrle(0, 0, 4, 2, &ZRLE_GOLDEN_RECT2, &pf, &mut fresh).is_err());
+    }
+
+    #[test]
+    fn decode_zrle_raw_tile() {
+        // CPIXELs are 3 bytes (B, G, R) for the default 32bpp depth-24 format.
+        let rect = zrle(2, 1, &[0, 0xFF, 0, 0, 0, 0, 0xFF]).unwrap();
+        assert_eq!(rect.pixels, vec![0, 0, 255, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn decode_zrle_packed_palette() {
+        // 3×2 tile, 2-colour palette (1 bit per pixel, rows byte-padded).
+        let rect = zrle(
+            3,
+            2,
+            &[2, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0b1010_0000, 0b0100_0000],
+        )
+        .unwrap();
+        let white: Vec<bool> = rect.pixels.chunks(4).map(|p| p[0] == 255).collect();
+        assert_eq!(white, vec![true, false, true, false, true, false]);
+    }
+
+    #[test]
+    fn decode_zrle_plain_rle_and_multiple_tiles() {
+        // 65×1 spans two 64-wide tiles: the first is one red run of 64, the
+        // second a solid blue tile.
+        let mut inflated = vec![128, 0, 0, 0xFF, 63];
+        inflated.extend_from_slice(&[1, 0xFF, 0, 0]);
+        let rect = zrle(65, 1, &inflated).unwrap();
+        assert!(rect.pixels[..64 * 4]
+            .chunks(4)
+            .all(|p| p == [255, 0, 0, 255]));
+        assert_eq!(&rect.pixels[64 * 4..], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn decode_zrle_long_run_length() {
+        // Run of 300 = 1 + 255 + 44.
+        let rect = zrle(64, 5, &[128, 0, 0xFF, 0, 255, 44, 0, 0, 0, 19]).unwrap();
+        assert!(rect.pixels[..300 * 4]
+            .chunks(4)
+            .all(|p| p == [0, 255, 0, 255]));
+        assert!(rect.pixels[300 * 4..]
+            .chunks(4)
+            .all(|p| p == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn decode_zrle_rejects_bad_input() {
+        assert!(zrle(1, 1, &[17]).is_err(), "reserved sub-encoding");
+        assert!(zrle(1, 1, &[129]).is_err(), "reserved sub-encoding");
+        assert!(
+            zrle(2, 1, &[128, 0, 0, 0, 5]).is_err(),
+            "run overflows tile"
+        );
+        assert!(zrle(1, 1, &[1, 0, 0, 0, 9]).is_err(), "trailing data");
+        assert!(zrle(2, 1, &[1, 0]).is_err(), "truncated pixel");
+        let mut stream = ZlibStream::new();
+        assert!(decode_zrle(0, 0, 1, 1, &[0xde, 0xad], &rgba32(), &mut stream).is_err());
+    }
+
+    #[test]
+    fn decode_zrle_16bit_full_cpixels() {
+        let pf = rgb565();
+        let chunk = server_stream(&[&[1, 0xFF, 0xFF]]).remove(0);
+        let rect = decode_zrle(0, 0, 2, 2, &chunk, &pf, &mut ZlibStream::new()).unwrap();
+        assert!(rect.pixels.chunks(4).all(|p| p == [255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn zlib_stream_enforces_limit() {
+        let chunk = server_stream(&[&[0u8; 4096]]).remove(0);
+        assert!(ZlibStream::new().inflate(&chunk, 4095).is_err());
+        assert_eq!(ZlibStream::new().inflate(&chunk, 4096).unwrap().len(), 4096);
+    }
+
+    // ── Tight ───────────────────────────────────────────────────────
+
+    fn tight(w: u16, h: u16, data: &[u8], streams: &mut [ZlibStream; 4]) -> DecodedRect {
+        decode_tight(0, 0, w, h, data, &rgba32(), streams).unwrap()
+    }
+
+    // Two 4×4 copy-filter rectangles on Tight stream 0, compressed once with
+    // flate2 (sync flush after each) and pinned here; they are not captures
+    // from a real server. The first is all (10, 20, 30), the second half
+    // (10, 20, 30) and half (40, 50, 60).
+    const TIGHT_GOLDEN_RECT1: [u8; 16] = [
+        0x00, 0x0e, 0x78, 0x9c, 0xe2, 0x12, 0x91, 0xe3, 0x22, 0x05, 0x01, 0x00, 0x00, 0x00, 0xff,
+        0xff,
+    ];
+    const TIGHT_GOLDEN_RECT2: [u8; 15] = [
+        0x00, 0x0d, 0xc2, 0x85, 0x34, 0x8c, 0x6c, 0xb0, 0x22, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff,
+    ];
+
+    #[test]
+    fn decode_tight_golden_stream() {
+        let mut streams = FrameDecoders::default().tight;
+        let first = tight(4, 4, &TIGHT_GOLDEN_RECT1, &mut streams);
+        assert!(first.pixels.chunks(4).all(|p| p == [10, 20, 30, 255]));
+        let second = tight(4, 4, &TIGHT_GOLDEN_RECT2, &mut streams);
+        assert!(second.pixels[..32]
+            .chunks(4)
+            .all(|p| p == [10, 20, 30, 255]));
+        assert!(second.pixels[32..]
+            .chunks(4)
+            .all(|p| p == [40, 50, 60, 255]));
+    }
+
+    #[test]
+    fn decode_tight_stream_reset_bit() {
+        let mut streams = FrameDecoders::default().tight;
+        tight(4, 4, &TIGHT_GOLDEN_RECT1, &mut streams);
+        // Resetting stream 0 drops the dictionary the second chunk needs.
+        let mut reset = TIGHT_GOLDEN_RECT2;
+        reset[0] |= 0x01;
+        assert!(decode_tight(0, 0, 4, 4, &reset, &rgba32(), &mut streams).is_err());
+    }
+
+    #[test]
+    fn decode_tight_fill() {
+        let mut streams = FrameDecoders::default().tight;
+        let rect = tight(3, 2, &[0x80, 1, 2, 3], &mut streams);
+        assert_eq!(rect.pixels.len(), 3 * 2 * 4);
+        assert!(rect.pixels.chunks(4).all(|p| p == [1, 2, 3, 255]));
+    }
+
+    #[test]
+    fn decode_tight_small_copy_is_uncompressed() {
+        // 3 pixels × 3 bytes = 9 < 12, so the data follows the control byte raw.
+        let mut streams = FrameDecoders::default().tight;
+        let rect = tight(3, 1, &[0x00, 1, 2, 3, 4, 5, 6, 7, 8, 9], &mut streams);
+        assert_eq!(rect.pixels, vec![1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255]);
+    }
+
+    #[test]
+    fn decode_tight_two_colour_palette_on_stream_two() {
+        // Explicit filter, stream 2, palette of 2, 10×2 → 2 bytes per row.
+        let mut streams = FrameDecoders::default().tight;
+        let mut data = vec![0x60, tight::FILTER_PALETTE, 1, 0, 0, 0, 255, 255, 255];
+        data.extend_from_slice(&[0b1000_0000, 0b0100_0000, 0xFF, 0b1100_0000]);
+        let rect = tight(10, 2, &data, &mut streams);
+        let white: Vec<bool> = rect.pixels.chunks(4).map(|p| p[0] == 255).collect();
+        let mut expected = vec![false; 20];
+        expected[0] = true;
+        expected[9] = true;
+        expected[10..].fill(true);
+        assert_eq!(white, expected);
+    }
+
+    #[test]
+    fn decode_tight_indexed_palette_compressed() {
+        let mut streams = FrameDecoders::default().tight;
+        let indices: Vec<u8> = (0..16).map(|i| i % 3).collect();
+        let chunk = server_stream(&[&indices]).remove(0);
+        let mut data = vec![
+            0x50,
+            tight::FILTER_PALETTE,
+            2,
+            255,
+            0,
+            0,
+            0,
+            255,
+            0,
+            0,
+            0,
+            255,
+        ];
+        data.push(chunk.len() as u8);
+        data.extend_from_slice(&chunk);
+        let rect = tight(4, 4, &data, &mut streams);
+        assert_eq!(
+            &rect.pixels[..12],
+            &[255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255]
+        );
+    }
+
+    #[test]
+    fn decode_tight_gradient() {
+        // 2×2 image: (10,10,10) (20,20,20) / (30,30,30) (40,40,40).
+        // Predictions: 0; left; above; left + above - above_left.
+        let diffs = [10u8, 10, 10, 10, 10, 10, 20, 20, 20, 0, 0, 0];
+        let mut streams = FrameDecoders::default().tight;
+        let chunk = server_stream(&[&diffs]).remove(0);
+        let mut data = vec![0x40, tight::FILTER_GRADIENT, chunk.len() as u8];
+        data.extend_from_slice(&chunk);
+        let rect = tight(2, 2, &data, &mut streams);
+        let reds: Vec<u8> = rect.pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn decode_tight_jpeg() {
+        let mut jpeg = Vec::new();
+        let img = image::RgbImage::from_pixel(8, 8, image::Rgb([200, 40, 40]));
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 95)
+            .encode_image(&img)
+            .unwrap();
+        let mut data = vec![0x90];
+        let len = jpeg.len();
+        data.extend_from_slice(&[(len & 0x7F) as u8 | 0x80, ((len >> 7) & 0x7F) as u8]);
+        data.extend_from_slice(&jpeg);
+        let mut streams = FrameDecoders::default().tight;
+        let rect = tight(8, 8, &data, &mut streams);
+        let p = &rect.pixels[..4];
+        assert!(p[0] > 180 && p[1] < 70 && p[2] < 70 && p[3] == 255);
+
+        // A JPEG whose size disagrees with the rectangle is rejected.
+        assert!(decode_tight(0, 0, 4, 4, &data, &rgba32(), &mut streams).is_err());
+    }
+
+    #[test]
+    fn decode_tight_rejects_bad_input() {
+        let pf = rgba32();
+        let mut streams = FrameDecoders::default().tight;
+        assert!(decode_tight(0, 0, 1, 1, &[0xA0, 0, 0, 0], &pf, &mut streams).is_err());
+        assert!(decode_tight(0, 0, 1, 1, &[0x40, 7, 0, 0, 0], &pf, &mut streams).is_err());
+        assert!(decode_tight(0, 0, 1, 1, &[0x80, 1, 2], &pf, &mut streams).is_err());
+        assert!(decode_tight(0, 0, 1, 1, &[0x80, 1, 2, 3, 4], &pf, &mut streams).is_err());
+        // Palette index beyond the palette.
+        assert!(decode_tight(
+            0,
+            0,
+            1,
+            1,
+            &[0x40, 1, 2, 0, 0, 0, 1, 1, 1, 2, 2, 2, 9],
+            &pf,
+            &mut streams
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn tight_tpixel_sizes() {
+        assert_eq!(tight_tpixel_len(&rgba32()), 3);
+        assert_eq!(tight_tpixel_len(&rgb565()), 2);
+    }
+
+    #[test]
+    fn tight_compact_length() {
+        assert_eq!(parse_tight_compact_len(&[0x05]), Some((5, 1)));
+        assert_eq!(parse_tight_compact_len(&[0x90, 0x4E]), Some((10000, 2)));
+        assert_eq!(
+            parse_tight_compact_len(&[0xFF, 0xFF, 0xFF]),
+            Some((4_194_303, 3))
+        );
+        assert_eq!(parse_tight_compact_len(&[0x80]), None);
+    }
 }
@@ -2,7 +2,9 @@
 //!
 //! Client → Server and Server → Client message framing per RFC 6143.
 
-use crate::vnc::types::{ClientMessageType, EncodingType, PixelFormat, ServerMessageType};
+use crate::vnc::types::{
+    ClientMessageType, EncodingType, PixelFormat, ServerMessageType, VncConfig,
+};
 
 // ── Client → Server message builders ────────────────────────────────────
 
@@ -185,15 +187,25 @@ pub fn encoding_from_name(name: &str) -> Option<EncodingType> {
         "copyrect" => Some(EncodingType::CopyRect),
         "rre" => Some(EncodingType::RRE),
         "hextile" => Some(EncodingType::Hextile),
+        "zrle" => Some(EncodingType::ZRLE),
+        "tight" => Some(EncodingType::Tight),
         _ => None,
     }
 }
 
 /// Convert a list of encoding name strings into encoding types,
 /// automatically appending pseudo-encodings.
+///
+/// Pixel encodings keep the configured order, since servers pick the first
+/// one they support; the default list is cheapest-first (Tight, ZRLE,
+/// Hextile). Only encodings the user did not name are appended.
 pub fn resolve_encodings(names: &[String], local_cursor: bool) -> Vec<EncodingType> {
-    let mut result: Vec<EncodingType> =
-        names.iter().filter_map(|n| encoding_from_name(n)).collect();
+    let mut result: Vec<EncodingType> = Vec::new();
+    for encoding in names.iter().filter_map(|n| encoding_from_name(n)) {
+        if !result.contains(&encoding) {
+            result.push(encoding);
+        }
+    }
 
     // Always include CopyRect if not already present.
     if !result.contains(&EncodingType::CopyRect) {
@@ -210,6 +222,24 @@ pub fn resolve_encodings(names: &[String], local_cursor: bool) -> Vec<EncodingTy
     result
 }
 
+/// Build the full SetEncodings list for a connection, adding the Tight/ZRLE
+/// compression and JPEG quality pseudo-encodings when they apply.
+pub fn negotiate_encodings(config: &VncConfig) -> Vec<EncodingType> {
+    let mut result = resolve_encodings(&config.encodings, config.local_cursor);
+    let tight = result.contains(&EncodingType::Tight);
+    if tight || result.contains(&EncodingType::ZRLE) {
+        result.push(EncodingType::CompressLevelPseudo(
+            config.compression_level.min(9),
+        ));
+    }
+    // Quality 0 means lossless: without a quality level the server never
+    // selects JPEG.
+    if tight && config.jpeg_quality > 0 {
+        result.push(EncodingType::QualityLevelPseudo(config.jpeg_quality.min(9)));
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -506,7 +536,9 @@ mod tests {
     fn encoding_from_name_known() {
         assert_eq!(encoding_from_name("Raw"), Some(EncodingType::Raw));
         assert_eq!(encoding_from_name("hextile"), Some(EncodingType::Hextile));
-        assert_eq!(encoding_from_name("TIGHT"), None);
+        assert_eq!(encoding_from_name("TIGHT"), Some(EncodingType::Tight));
+        assert_eq!(encoding_from_name("zrle"), Some(EncodingType::ZRLE));
+        assert_eq!(encoding_from_name("trle"), None);
     }
 
     #[test]
@@ -544,6 +576,92 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn resolve_encodings_keeps_configured_order() {
+        let names = vec![
+            "Raw".into(),
+            "Hextile".into(),
+            "Tight".into(),
+            "raw".into(),
+            "ZRLE".into(),
+        ];
+        let resolved = resolve_encodings(&names, false);
+        assert_eq!(
+            &resolved[..5],
+            &[
+                EncodingType::Raw,
+                EncodingType::Hextile,
+                EncodingType::Tight,
+                EncodingType::ZRLE,
+                EncodingType::CopyRect,
+            ]
+        );
+
+        let defaults = resolve_encodings(&VncConfig::default().encodings, false);
+        assert_eq!(
+            &defaults[..5],
+            &[
+                EncodingType::Tight,
+                EncodingType::ZRLE,
+                EncodingType::Hextile,
+                EncodingType::CopyRect,
+                EncodingType::Raw,
+            ]
+        );
+    }
+
+    #[test]
+    fn negotiate_encodings_adds_tight_levels() {
+        let config = VncConfig {
+            encodings: vec!["Tight".into(), "Raw".into()],
+            jpeg_quality: 7,
+            compression_level: 3,
+            ..VncConfig::default()
+        };
+        let encodings = negotiate_encodings(&config);
+        assert!(encodings.contains(&EncodingType::CompressLevelPseudo(3)));
+        assert!(encodings.contains(&EncodingType::QualityLevelPseudo(7)));
+        let wire = build_set_encodings(&encodings);
+        let values: Vec<i32> = wire[4..]
+            .chunks(4)
+            .map(|c| i32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        assert_eq!(values[0], 7);
+        assert!(values.contains(&-253));
+        assert!(values.contains(&-25));
+    }
+
+    #[test]
+    fn negotiate_encodings_lossless_and_zrle_only() {
+        let lossless = VncConfig {
+            encodings: vec!["Tight".into()],
+            jpeg_quality: 0,
+            ..VncConfig::default()
+        };
+        assert!(!negotiate_encodings(&lossless)
+            .iter()
+            .any(|e| matches!(e, EncodingType::QualityLevelPseudo(_))));
+
+        let zrle = VncConfig {
+            encodings: vec!["ZRLE".into()],
+            compression_level: 6,
+            ..VncConfig::default()
+        };
+        let encodings = negotiate_encodings(&zrle);
+        assert!(encodings.contains(&EncodingType::CompressLevelPseudo(6)));
+        assert!(!encodings
+            .iter()
+            .any(|e| matches!(e, EncodingType::QualityLevelPseudo(_))));
+
+        let plain = VncConfig {
+            encodings: vec!["Hextile".into(), "Raw".into()],
+            ..VncConfig::default()
+        };
+        assert!(!negotiate_encodings(&plain)
+            .iter()
+            .any(|e| matches!(e, EncodingType::CompressLevelPseudo(_))));
+    }
+
     // ── Version string ──────────────────────────────────────────────
 
     #[test]
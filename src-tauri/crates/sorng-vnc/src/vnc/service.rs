@@ -498,18 +498,9 @@ impl VncService {
     ) -> Result<(), VncError> {
         if encodings.is_empty()
             || encodings.len() > MAX_VNC_ENCODINGS
-            || encodings.iter().any(|encoding| {
-                !matches!(
-                    encoding,
-                    EncodingType::Raw
-                        | EncodingType::CopyRect
-                        | EncodingType::RRE
-                        | EncodingType::Hextile
-                        | EncodingType::CursorPseudo
-                        | EncodingType::DesktopSizePseudo
-                        | EncodingType::LastRectPseudo
-                )
-            })
+            || encodings
+                .iter()
+                .any(|encoding| !encoding.is_client_supported())
         {
             return Err(VncError::protocol(
                 "Unsupported or oversized VNC encoding list",
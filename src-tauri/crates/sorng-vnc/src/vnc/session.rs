@@ -20,7 +20,9 @@ use crate::vnc::delivery::{
     event_delivery, RefreshRequestReservation, VncEventReceiver, VncEventSender,
 };
 use crate::vnc::encoding::{
-    base64_encode_pixels, decode_copyrect, decode_hextile, decode_raw, decode_rre, DecodedRect,
+    base64_encode_pixels, decode_copyrect, decode_hextile, decode_raw, decode_rre, decode_tight,
+    decode_zrle, parse_tight_compact_len, tight, tight_basic_data_len, tight_tpixel_len,
+    DecodedRect, FrameDecoders,
 };
 use crate::vnc::protocol;
 use crate::vnc::types::*;
@@ -856,7 +858,7 @@ async fn session_task(
         st.bytes_sent += msg.len() as u64;
     }
 
-    let encodings = protocol::resolve_encodings(&config.encodings, config.local_cursor);
+    let encodings = protocol::negotiate_encodings(&config);
     let enc_msg = protocol::build_set_encodings(&encodings);
     write_all_with_timeout(&mut stream, &enc_msg, HANDSHAKE_IO_TIMEOUT).await?;
     {
@@ -1138,18 +1140,7 @@ async fn session_task(
                 SessionCommand::SetEncodings(encs) => {
                     if encs.is_empty()
                         || encs.len() > MAX_VNC_ENCODINGS
-                        || encs.iter().any(|encoding| {
-                            !matches!(
-                                encoding,
-                                EncodingType::Raw
-                                    | EncodingType::CopyRect
-                                    | EncodingType::RRE
-                                    | EncodingType::Hextile
-                                    | EncodingType::CursorPseudo
-                                    | EncodingType::DesktopSizePseudo
-                                    | EncodingType::LastRectPseudo
-                            )
-                        })
+                        || encs.iter().any(|encoding| !encoding.is_client_supported())
                     {
                         continue;
                     }
@@ -1193,8 +1184,10 @@ async fn session_task(
         let _ = timeout(SESSION_IO_TIMEOUT, w.shutdown()).await;
     });
 
-    // Server message read loop.
+    // Server message read loop. ZRLE/Tight zlib dictionaries live for the
+    // whole connection, so the decoder state is owned here.
     let mut terminal_error = None;
+    let mut decoders = FrameDecoders::default();
     loop {
         if local_shutdown.load(Ordering::Acquire) {
             break;
@@ -1234,7 +1227,7 @@ async fn session_task(
             Some(ServerMessageType::FramebufferUpdate) => {
                 match timeout(
                     SESSION_IO_TIMEOUT,
-                    handle_fb_update(&mut reader, &event_tx, &state),
+                    handle_fb_update(&mut reader, &event_tx, &state, &mut decoders),
                 )
                 .await
                 {
@@ -1298,6 +1291,7 @@ async fn handle_fb_update(
     reader: &mut (impl AsyncReadExt + Unpin),
     event_tx: &VncEventSender,
     state: &SharedState,
+    decoders: &mut FrameDecoders,
 ) -> Result<(), VncError> {
     // `bytes_received` is monotonic wire telemetry: every complete chunk read
     // below is visible immediately even if a later rectangle aborts. In
@@ -1349,7 +1343,12 @@ async fn handle_fb_update(
         let encoding = EncodingType::from_i32(enc_val);
         if matches!(
             encoding,
-            EncodingType::Raw | EncodingType::CopyRect | EncodingType::RRE | EncodingType::Hextile
+            EncodingType::Raw
+                | EncodingType::CopyRect
+                | EncodingType::RRE
+                | EncodingType::Hextile
+                | EncodingType::ZRLE
+                | EncodingType::Tight
         ) {
             if w == 0 || h == 0 {
                 return Err(VncError::protocol("VNC rectangle has zero dimensions"));
@@ -1364,7 +1363,11 @@ async fn handle_fb_update(
         }
         if matches!(
             encoding,
-            EncodingType::Raw | EncodingType::RRE | EncodingType::Hextile
+            EncodingType::Raw
+                | EncodingType::RRE
+                | EncodingType::Hextile
+                | EncodingType::ZRLE
+                | EncodingType::Tight
         ) {
             checked_payload_len(w, h, 4, MAX_VNC_RECT_RGBA_BYTES)?;
         }
@@ -1469,6 +1472,37 @@ async fn handle_fb_update(
                 pending_frame_count += 1;
                 event_tx.apply_frame(decoded)?;
             }
+            EncodingType::ZRLE => {
+                // 4-byte length followed by data on the persistent zlib stream.
+                let mut len_buf = [0u8; 4];
+                reader.read_exact(&mut len_buf).await?;
+                let data_len = u32::from_be_bytes(len_buf) as usize;
+                if data_len > MAX_VNC_RECT_WIRE_BYTES {
+                    return Err(VncError::protocol("ZRLE payload exceeds the safety limit"));
+                }
+                let mut data = vec![0u8; data_len];
+                reader.read_exact(&mut data).await?;
+                {
+                    let mut st = state.lock().await;
+                    st.bytes_received += 4 + data_len as u64;
+                }
+                pending_frame_count += 1;
+                let decoded = decode_zrle(x, y, w, h, &data, &pixel_format, &mut decoders.zrle)
+                    .map_err(VncError::protocol)?;
+                event_tx.apply_frame(decoded)?;
+            }
+            EncodingType::Tight => {
+                let mut data = Vec::new();
+                read_tight_data(reader, &mut data, w, h, &pixel_format).await?;
+                {
+                    let mut st = state.lock().await;
+                    st.bytes_received += data.len() as u64;
+                }
+                pending_frame_count += 1;
+                let decoded = decode_tight(x, y, w, h, &data, &pixel_format, &mut decoders.tight)
+                    .map_err(VncError::protocol)?;
+                event_tx.apply_frame(decoded)?;
+            }
             EncodingType::CursorPseudo => {
                 // Cursor pseudo-encoding: pixel data + bitmask.
                 let bpp = pixel_format.bytes_per_pixel();
@@ -1677,6 +1711,84 @@ async fn read_hextile_data(
     Ok(())
 }
 
+/// Append `len` bytes from the stream to `data`, bounded by the wire limit.
+async fn read_into(
+    reader: &mut (impl AsyncReadExt + Unpin),
+    data: &mut Vec<u8>,
+    len: usize,
+    what: &str,
+) -> Result<(), VncError> {
+    let start = data.len();
+    let new_len = start
+        .checked_add(len)
+        .filter(|size| *size <= MAX_VNC_RECT_WIRE_BYTES)
+        .ok_or_else(|| VncError::protocol(format!("{what} exceeds the safety limit")))?;
+    data.resize(new_len, 0);
+    reader.read_exact(&mut data[start..]).await?;
+    Ok(())
+}
+
+/// Read a Tight compact length byte by byte, keeping the wire bytes.
+async fn read_tight_compact_len(
+    reader: &mut (impl AsyncReadExt + Unpin),
+    data: &mut Vec<u8>,
+) -> Result<usize, VncError> {
+    let start = data.len();
+    loop {
+        read_into(reader, data, 1, "Tight payload").await?;
+        if let Some((len, _)) = parse_tight_compact_len(&data[start..]) {
+            return Ok(len);
+        }
+    }
+}
+
+/// Read a Tight-encoded rectangle's wire payload.
+///
+/// Tight is variable-length, so the control byte, filter header and
+/// compact lengths are walked here and the bytes handed to
+/// `decode_tight` unchanged.
+async fn read_tight_data(
+    reader: &mut (impl AsyncReadExt + Unpin),
+    data: &mut Vec<u8>,
+    width: u16,
+    height: u16,
+    pixel_format: &PixelFormat,
+) -> Result<(), VncError> {
+    let tpixel_len = tight_tpixel_len(pixel_format);
+    read_into(reader, data, 1, "Tight payload").await?;
+    let control = data[0];
+
+    match control >> 4 {
+        tight::FILL => read_into(reader, data, tpixel_len, "Tight payload").await,
+        tight::JPEG => {
+            let len = read_tight_compact_len(reader, data).await?;
+            read_into(reader, data, len, "Tight JPEG payload").await
+        }
+        kind if kind & 0x08 == 0 => {
+            let mut filter = tight::FILTER_COPY;
+            let mut palette_size = 0;
+            if control & tight::EXPLICIT_FILTER != 0 {
+                read_into(reader, data, 1, "Tight payload").await?;
+                filter = data[data.len() - 1];
+                if filter == tight::FILTER_PALETTE {
+                    read_into(reader, data, 1, "Tight payload").await?;
+                    palette_size = data[data.len() - 1] as usize + 1;
+                    read_into(reader, data, palette_size * tpixel_len, "Tight palette").await?;
+                }
+            }
+            let raw_len = tight_basic_data_len(width, height, filter, palette_size, pixel_format)
+                .map_err(VncError::protocol)?;
+            if raw_len < tight::MIN_TO_COMPRESS {
+                read_into(reader, data, raw_len, "Tight payload").await
+            } else {
+                let len = read_tight_compact_len(reader, data).await?;
+                read_into(reader, data, len, "Tight zlib payload").await
+            }
+        }
+        _ => Err(VncError::protocol("Invalid Tight compression control")),
+    }
+}
+
 async fn handle_colour_map(
     reader: &mut (impl AsyncReadExt + Unpin),
     state: &SharedState,
@@ -2027,10 +2139,15 @@ mod tests {
         let (mut writer, mut reader) = tokio::io::duplex(256);
         let task_sender = sender.clone();
         let task_state = Arc::clone(&state);
-        let task =
-            tokio::spawn(
-                async move { handle_fb_update(&mut reader, &task_sender, &task_state).await },
-            );
+        let task = tokio::spawn(async move {
+            handle_fb_update(
+                &mut reader,
+                &task_sender,
+                &task_state,
+                &mut FrameDecoders::default(),
+            )
+            .await
+        });
 
         writer
             .write_all(&resize_cursor_then_raw_prefix())
@@ -2088,10 +2205,15 @@ mod tests {
         let (mut writer, mut reader) = tokio::io::duplex(256);
         let task_sender = sender.clone();
         let task_state = Arc::clone(&state);
-        let task =
-            tokio::spawn(
-                async move { handle_fb_update(&mut reader, &task_sender, &task_state).await },
-            );
+        let task = tokio::spawn(async move {
+            handle_fb_update(
+                &mut reader,
+                &task_sender,
+                &task_state,
+                &mut FrameDecoders::default(),
+            )
+            .await
+        });
 
         writer
             .write_all(&resize_cursor_then_raw_prefix())
@@ -2316,4 +2438,33 @@ mod tests {
         assert_eq!(ev.source_x, Some(4));
         assert_eq!(ev.source_y, Some(5));
     }
+
+    #[tokio::test]
+    async fn tight_reader_consumes_exactly_one_rectangle() {
+        let pf = PixelFormat::rgba32();
+        // Fill, short uncompressed copy, palette + compact-length zlib block,
+        // and JPEG with a two-byte compact length.
+        let mut jpeg = vec![0x90, 0x82, 0x01];
+        jpeg.extend(std::iter::repeat_n(7u8, 130));
+        let cases: Vec<(u16, u16, Vec<u8>)> = vec![
+            (4, 4, vec![0x80, 1, 2, 3]),
+            (2, 1, vec![0x00, 1, 2, 3, 4, 5, 6]),
+            (
+                8,
+                8,
+                vec![0x40, 1, 2, 0, 0, 0, 9, 9, 9, 1, 1, 1, 3, 0xAA, 0xBB, 0xCC],
+            ),
+            (16, 16, jpeg),
+        ];
+        for (w, h, payload) in cases {
+            let (mut writer, mut reader) = tokio::io::duplex(1024);
+            writer.write_all(&payload).await.unwrap();
+            writer.write_all(&[0xEE; 8]).await.unwrap();
+            let mut data = Vec::new();
+            read_tight_data(&mut reader, &mut data, w, h, &pf)
+                .await
+                .unwrap();
+            assert_eq!(data, payload);
+        }
+    }
 }
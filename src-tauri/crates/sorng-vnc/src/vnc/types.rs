@@ -340,6 +340,10 @@ pub enum EncodingType {
     LastRectPseudo,
     /// Extended desktop size
     ExtendedDesktopSizePseudo,
+    /// Tight/ZRLE compression level 0-9 (pseudo-encodings -256..=-247).
+    CompressLevelPseudo(u8),
+    /// Tight JPEG quality level 0-9 (pseudo-encodings -32..=-23).
+    QualityLevelPseudo(u8),
     /// Unknown / custom encoding.
     Other(i32),
 }
@@ -360,6 +364,8 @@ impl EncodingType {
             Self::ContinuousUpdatesPseudo => -313,
             Self::LastRectPseudo => -224,
             Self::ExtendedDesktopSizePseudo => -308,
+            Self::CompressLevelPseudo(level) => -256 + i32::from(*level),
+            Self::QualityLevelPseudo(level) => -32 + i32::from(*level),
             Self::Other(v) => *v,
         }
     }
@@ -379,6 +385,8 @@ impl EncodingType {
             -313 => Self::ContinuousUpdatesPseudo,
             -224 => Self::LastRectPseudo,
             -308 => Self::ExtendedDesktopSizePseudo,
+            -256..=-247 => Self::CompressLevelPseudo((v + 256) as u8),
+            -32..=-23 => Self::QualityLevelPseudo((v + 32) as u8),
             other => Self::Other(other),
         }
     }
@@ -397,9 +405,29 @@ impl EncodingType {
             Self::ContinuousUpdatesPseudo => "ContinuousUpdates (pseudo)".into(),
             Self::LastRectPseudo => "LastRect (pseudo)".into(),
             Self::ExtendedDesktopSizePseudo => "ExtendedDesktopSize (pseudo)".into(),
+            Self::CompressLevelPseudo(level) => format!("CompressLevel {} (pseudo)", level),
+            Self::QualityLevelPseudo(level) => format!("QualityLevel {} (pseudo)", level),
             Self::Other(v) => format!("Unknown({})", v),
         }
     }
+
+    /// Whether the client can decode (or honour) this encoding, i.e. whether
+    /// it may appear in a SetEncodings message we send.
+    pub fn is_client_supported(&self) -> bool {
+        match self {
+            Self::Raw
+            | Self::CopyRect
+            | Self::RRE
+            | Self::Hextile
+            | Self::ZRLE
+            | Self::Tight
+            | Self::CursorPseudo
+            | Self::DesktopSizePseudo
+            | Self::LastRectPseudo => true,
+            Self::CompressLevelPseudo(level) | Self::QualityLevelPseudo(level) => *level <= 9,
+            _ => false,
+        }
+    }
 }
 
 // ── Client → Server Message Types ───────────────────────────────────────
@@ -574,7 +602,13 @@ fn default_compression() -> u8 {
     2
 }
 fn default_encodings() -> Vec<String> {
-    vec!["Hextile".into(), "CopyRect".into(), "Raw".into()]
+    vec![
+        "Tight".into(),
+        "ZRLE".into(),
+        "Hextile".into(),
+        "CopyRect".into(),
+        "Raw".into(),
+    ]
 }
 
 impl Default for VncConfig {
@@ -649,7 +683,7 @@ impl VncConfig {
         for encoding in &self.encodings {
             if !matches!(
                 encoding.to_ascii_lowercase().as_str(),
-                "raw" | "copyrect" | "rre" | "hextile"
+                "raw" | "copyrect" | "rre" | "hextile" | "zrle" | "tight"
             ) {
                 return Err(VncError::protocol(format!(
                     "Unsupported VNC encoding requested: {}",
@@ -1069,6 +1103,9 @@ mod tests {
             EncodingType::Tight,
             EncodingType::CursorPseudo,
             EncodingType::DesktopSizePseudo,
+            EncodingType::CompressLevelPseudo(0),
+            EncodingType::CompressLevelPseudo(9),
+            EncodingType::QualityLevelPseudo(6),
         ];
         for t in types {
             let v = t.to_i32();
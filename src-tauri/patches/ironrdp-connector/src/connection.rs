@@ -803,6 +803,10 @@ fn create_client_info_pdu(config: &Config, client_addr: &SocketAddr) -> rdp::Cli
         flags |= ClientInfoFlags::NO_AUDIO_PLAYBACK;
     }
 
+    if config.remote_app_program.is_some() {
+        flags |= ClientInfoFlags::RAIL;
+    }
+
     let client_info = ClientInfo {
         credentials: Credentials {
             username: config.credentials.username().unwrap_or("").to_owned(),
@@ -812,7 +816,7 @@ fn create_client_info_pdu(config: &Config, client_addr: &SocketAddr) -> rdp::Cli
         code_page: 0, // ignored if the keyboardLayout field of the Client Core Data is set to zero
         flags,
         compression_type: CompressionType::K8, // ignored if ClientInfoFlags::COMPRESSION is not set
-        alternate_shell: String::new(),
+        alternate_shell: config.remote_app_program.clone().unwrap_or_default(),
         work_dir: String::new(),
         extra_info: ExtendedClientInfo {
             address_family: match client_addr {
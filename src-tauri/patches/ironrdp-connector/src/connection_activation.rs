@@ -299,6 +299,19 @@ impl State for ConnectionActivationState {
 
 const DEFAULT_POINTER_CACHE_SIZE: u16 = 32;
 
+/// TS_RAIL_LEVEL_SUPPORTED | DOCKED_LANGBAR_SUPPORTED | SHELL_INTEGRATION_SUPPORTED
+/// | HANDSHAKE_EX_SUPPORTED (MS-RDPERP 2.2.1.1.1).
+const RAIL_SUPPORT_LEVEL: u32 = 0x0000_0001 | 0x0000_0002 | 0x0000_0004 | 0x0000_0080;
+
+/// Window List capability body (MS-RDPERP 2.2.1.1.2): TS_WINDOW_LEVEL_SUPPORTED_EX,
+/// NumIconCaches and NumIconCacheEntries.
+fn window_list_capability() -> Vec<u8> {
+    let mut body = 0x0000_0002u32.to_le_bytes().to_vec();
+    body.push(3);
+    body.extend_from_slice(&12u16.to_le_bytes());
+    body
+}
+
 fn create_client_confirm_active(
     config: &Config,
     mut server_capability_sets: Vec<CapabilitySet>,
@@ -423,6 +436,13 @@ fn create_client_confirm_active(
         }),
     ]);
 
+    if config.remote_app_program.is_some() {
+        server_capability_sets.extend_from_slice(&[
+            CapabilitySet::Rail(RAIL_SUPPORT_LEVEL.to_le_bytes().to_vec()),
+            CapabilitySet::WindowList(window_list_capability()),
+        ]);
+    }
+
     if !server_capability_sets
         .iter()
         .any(|c| matches!(&c, CapabilitySet::MultiFragmentUpdate(_)))
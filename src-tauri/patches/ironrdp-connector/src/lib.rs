@@ -242,6 +242,11 @@ pub struct Config {
     /// SSPI package list override for NegotiateConfig (e.g. "!kerberos,!pku2u")
     /// When empty, defaults are derived from the ntlm/kerberos/pku2u flags.
     pub sspi_package_list: Option<String>,
+
+    // ─── RemoteApp (RAIL) ───────────────────────────────────────
+    /// Program to launch as a RemoteApp. When set, the client advertises
+    /// INFO_RAIL plus the Remote Programs and Window List capability sets.
+    pub remote_app_program: Option<String>,
 }
 
 ironrdp_core::assert_impl!(Config: Send, Sync);
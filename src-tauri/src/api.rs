@@ -1244,6 +1244,7 @@ async fn connect_ssh(
         password: req.password.map(SecretString::new),
         private_key_path: req.key_path,
         private_key_passphrase: None,
+        certificate_path: None,
         jump_hosts: Vec::new(),
         proxy_config: None,
         proxy_chain: None,
@@ -33,6 +33,8 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::ai_agent::persistence as ai_persistence;
+
 use sorng_encryption::artifacts::{
     connections as artifact_connections, settings as artifact_settings,
 };
@@ -97,6 +99,9 @@ pub struct FullRotateReport {
     pub media_sidecars_rewritten: u32,
     /// Count of macro envelopes re-encrypted.
     pub macros_rewritten: u32,
+    /// Count of AI vector and RAG snapshots (`ai/**/*.json.enc`)
+    /// re-encrypted.
+    pub ai_snapshots_rewritten: u32,
     /// Total v2-envelope bytes written across all artifacts.
     pub bytes_rewritten: u64,
     /// Was the OS vault entry updated with the new DEK?
@@ -483,6 +488,23 @@ async fn rotate_master_key_full_inner_impl(
         }
     }
 
+    // ── Step 3e: AI vector index + RAG snapshots ──────────────────
+    let ai_root = app_data_dir.join(ai_persistence::STORAGE_DIR);
+    for path in ai_persistence::list_encrypted_snapshot_paths(&ai_root) {
+        match prepare_stage(&transaction_id, "ai-snapshot", &path, failure_injector) {
+            Ok(item) => {
+                let result =
+                    ai_persistence::rewrite_snapshot_with(&item.staged, &old_state, &new_state)
+                        .await;
+                keep_or_record_stage(&mut report, &mut staged, item, result, |report, n| {
+                    report.ai_snapshots_rewritten += 1;
+                    report.bytes_rewritten += n;
+                });
+            }
+            Err(reason) => push_failure(&mut report, "ai-snapshot", &path, reason),
+        }
+    }
+
     // A required rewrite failure aborts before any canonical path,
     // live key, vault entry, or password receipt can change.
     if !report.failures.is_empty() {
@@ -602,6 +624,7 @@ async fn rotate_master_key_full_inner_impl(
             "recordingEnvelopesRewritten": report.recording_envelopes_rewritten,
             "mediaSidecarsRewritten": report.media_sidecars_rewritten,
             "macrosRewritten": report.macros_rewritten,
+            "aiSnapshotsRewritten": report.ai_snapshots_rewritten,
             "bytesRewritten": report.bytes_rewritten,
             "vaultUpdated": report.vault_updated,
             "dekEncUpdated": report.dek_enc_updated,
@@ -815,6 +838,7 @@ fn reset_rewrite_tallies(report: &mut FullRotateReport) {
     report.recording_envelopes_rewritten = 0;
     report.media_sidecars_rewritten = 0;
     report.macros_rewritten = 0;
+    report.ai_snapshots_rewritten = 0;
     report.bytes_rewritten = 0;
     report.vault_updated = false;
     report.dek_enc_updated = false;
@@ -188,6 +188,18 @@ pub fn run() {
     });
 }
 
+/// Subcommand that runs the MCP server over stdio instead of the GUI.
+#[cfg(feature = "ops")]
+pub use domains::mcp_server::stdio::STDIO_SUBCOMMAND as MCP_STDIO_SUBCOMMAND;
+
+/// Runs the MCP stdio transport (`<app> mcp-stdio [--config <file>]`) and
+/// returns the process exit code. Tracing is left uninitialised so stdout
+/// carries nothing but JSON-RPC.
+#[cfg(feature = "ops")]
+pub fn run_mcp_stdio(args: impl IntoIterator<Item = String>) -> i32 {
+    domains::mcp_server::stdio::run_from_args(args)
+}
+
 #[cfg(test)]
 mod tracing_filter_tests {
     use super::tracing_metadata_is_safe;
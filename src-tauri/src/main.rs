@@ -13,5 +13,12 @@
 static STACK_RESERVE: [u8; 47] = *b" /STACK:33554432                               ";
 
 fn main() {
+    // `<app> mcp-stdio` serves MCP over stdin/stdout for desktop MCP hosts
+    // and never opens a window.
+    #[cfg(feature = "ops")]
+    if std::env::args().nth(1).as_deref() == Some(app_lib::MCP_STDIO_SUBCOMMAND) {
+        std::process::exit(app_lib::run_mcp_stdio(std::env::args().skip(2)));
+    }
+
     app_lib::run();
 }
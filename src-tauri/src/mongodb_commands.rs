@@ -199,6 +199,291 @@ pub async fn mongo_kill_op(
     svc.kill_op(&session_id, op_id).await.map_err(|e| e.message)
 }
 
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_find(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    options: Option<FindOptions>,
+) -> Result<DocumentPage, String> {
+    let svc = state.lock().await;
+    svc.find_documents(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_count_documents(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: Option<String>,
+) -> Result<u64, String> {
+    let svc = state.lock().await;
+    svc.count_documents(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        filter.as_deref(),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_insert_one(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    document: String,
+) -> Result<InsertResult, String> {
+    let svc = state.lock().await;
+    svc.insert_one(&session_id, db_name.as_deref(), &collection_name, &document)
+        .await
+        .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_insert_many(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    documents: Vec<String>,
+) -> Result<InsertResult, String> {
+    let svc = state.lock().await;
+    svc.insert_many(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &documents,
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_update_one(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: String,
+    update: String,
+    upsert: Option<bool>,
+) -> Result<UpdateResult, String> {
+    let svc = state.lock().await;
+    svc.update_one(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &filter,
+        &update,
+        upsert.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_update_many(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: String,
+    update: String,
+    upsert: Option<bool>,
+) -> Result<UpdateResult, String> {
+    let svc = state.lock().await;
+    svc.update_many(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &filter,
+        &update,
+        upsert.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_replace_one(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: String,
+    replacement: String,
+    upsert: Option<bool>,
+) -> Result<UpdateResult, String> {
+    let svc = state.lock().await;
+    svc.replace_one(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &filter,
+        &replacement,
+        upsert.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_delete_one(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: String,
+) -> Result<DeleteResult, String> {
+    let svc = state.lock().await;
+    svc.delete_one(&session_id, db_name.as_deref(), &collection_name, &filter)
+        .await
+        .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_delete_many(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    filter: String,
+) -> Result<DeleteResult, String> {
+    let svc = state.lock().await;
+    svc.delete_many(&session_id, db_name.as_deref(), &collection_name, &filter)
+        .await
+        .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_aggregate(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    pipeline: String,
+    skip: Option<u64>,
+    limit: Option<u64>,
+) -> Result<DocumentPage, String> {
+    let svc = state.lock().await;
+    svc.aggregate(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &pipeline,
+        skip,
+        limit,
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_list_indexes(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+) -> Result<Vec<IndexInfo>, String> {
+    let svc = state.lock().await;
+    svc.list_indexes(&session_id, db_name.as_deref(), &collection_name)
+        .await
+        .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_create_index(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    spec: IndexSpec,
+) -> Result<String, String> {
+    let svc = state.lock().await;
+    svc.create_index(&session_id, db_name.as_deref(), &collection_name, &spec)
+        .await
+        .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_drop_index(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    index_name: String,
+) -> Result<(), String> {
+    let svc = state.lock().await;
+    svc.drop_index(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &index_name,
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_export_collection(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    options: Option<ExportOptions>,
+) -> Result<String, String> {
+    let svc = state.lock().await;
+    svc.export_collection(
+        &session_id,
+        db_name.as_deref(),
+        &collection_name,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.message)
+}
+
+#[cfg(feature = "db-mongo")]
+#[tauri::command]
+pub async fn mongo_import_documents(
+    state: tauri::State<'_, MongoServiceState>,
+    session_id: String,
+    db_name: Option<String>,
+    collection_name: String,
+    data: String,
+) -> Result<ImportResult, String> {
+    let svc = state.lock().await;
+    svc.import_documents(&session_id, db_name.as_deref(), &collection_name, &data)
+        .await
+        .map_err(|e| e.message)
+}
+
 #[cfg(not(feature = "db-mongo"))]
 mod disabled {
     macro_rules! disabled_commands {
@@ -229,7 +514,22 @@ mod disabled {
         mongo_list_users,
         mongo_replica_set_status,
         mongo_current_op,
-        mongo_kill_op
+        mongo_kill_op,
+        mongo_find,
+        mongo_count_documents,
+        mongo_insert_one,
+        mongo_insert_many,
+        mongo_update_one,
+        mongo_update_many,
+        mongo_replace_one,
+        mongo_delete_one,
+        mongo_delete_many,
+        mongo_aggregate,
+        mongo_list_indexes,
+        mongo_create_index,
+        mongo_drop_index,
+        mongo_export_collection,
+        mongo_import_documents
     );
 }
 
@@ -34,6 +34,11 @@ disabled_commands!(
     rdp_clipboard_copy_files,
     rdp_clipboard_paste,
     rdp_toggle_feature,
+    rdp_rail_exec,
+    rdp_rail_window_action,
+    rdp_rail_set_language_bar,
+    rdp_rail_attach_window,
+    rdp_rail_detach_window,
 );
 
 #[tauri::command]
@@ -57,8 +57,11 @@ mod recording {
 }
 mod tunnels {
 
+    pub use crate::ssh::socks::{
+        DynamicForwardOptions, DynamicForwardStats, DynamicTunnelEntry, SocksCredentials,
+    };
     pub use crate::ssh::types::*;
-    pub use crate::ssh::{FTP_TUNNELS, RDP_TUNNELS, VNC_TUNNELS};
+    pub use crate::ssh::{DYNAMIC_TUNNELS, FTP_TUNNELS, RDP_TUNNELS, VNC_TUNNELS};
     pub use chrono::Utc;
     pub use uuid::Uuid;
 }
@@ -66,6 +69,9 @@ mod fido2 {
     pub use crate::ssh::fido2::*;
 }
 mod sk_keys {}
+mod ssh_config {
+    pub use crate::ssh::ssh_config::*;
+}
 mod x11 {
     pub use crate::ssh::types::*;
     pub use crate::ssh::x11::*;
@@ -122,6 +128,22 @@ pub fn ssh_respond_to_host_key_prompt(
     })
 }
 
+/// Answer an `ssh-inband-transfer-request` (ZMODEM / trzsz started from a
+/// shell) with the chosen files or download directory.
+#[tauri::command]
+pub fn ssh_respond_to_inband_transfer(
+    transfer_id: String,
+    selection: crate::serial::types::InbandSelection,
+) -> Result<(), String> {
+    redact_ssh_command_result(crate::ssh::INBAND_TRANSFERS.respond(&transfer_id, selection))
+}
+
+/// Decline a pending in-band transfer or stop a running one.
+#[tauri::command]
+pub fn ssh_cancel_inband_transfer(transfer_id: String) -> Result<(), String> {
+    redact_ssh_command_result(crate::ssh::INBAND_TRANSFERS.cancel(&transfer_id))
+}
+
 // ── Include command wrappers ───────────────────────────────────────────
 
 #[allow(dead_code)]
@@ -149,6 +171,10 @@ mod recording_inner {
     include!("../crates/sorng-ssh/src/ssh/recording_cmds.rs");
 }
 #[allow(dead_code)]
+mod ssh_config_inner {
+    include!("../crates/sorng-ssh/src/ssh/ssh_config_cmds.rs");
+}
+#[allow(dead_code)]
 mod tunnels_inner {
     include!("../crates/sorng-ssh/src/ssh/tunnels_cmds.rs");
 }
@@ -183,5 +209,6 @@ pub(crate) use script_inner::*;
 #[cfg(not(feature = "script-engine"))]
 pub(crate) use script_stub_inner::*;
 pub(crate) use ssh3_inner::*;
+pub(crate) use ssh_config_inner::*;
 pub(crate) use tunnels_inner::*;
 pub(crate) use x11_inner::*;
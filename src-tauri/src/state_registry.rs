@@ -37,6 +37,48 @@ pub(crate) fn register(app: &mut tauri::App<tauri::Wry>) -> tauri::Result<()> {
     #[cfg(feature = "ops")]
     sorng_app_domains::ops_startup_state::register_scheduler(app, &app_dir)?;
 
+    // Kubernetes exec sessions, watches and followed logs stream to the
+    // frontend through events.
+    #[cfg(feature = "ops")]
+    if let Some(k8s_state) = app.try_state::<crate::k8s::service::K8sServiceState>() {
+        let k8s = k8s_state.inner().clone();
+        let emitter = crate::event_bridge::from_app_handle(app.handle());
+        tauri::async_runtime::block_on(async move {
+            k8s.lock().await.set_event_emitter(emitter);
+        });
+    }
+
+    // Docker exec/attach terminals and live daemon events likewise.
+    #[cfg(feature = "ops")]
+    if let Some(docker_state) = app.try_state::<crate::docker::service::DockerServiceState>() {
+        let docker = docker_state.inner().clone();
+        let emitter = crate::event_bridge::from_app_handle(app.handle());
+        tauri::async_runtime::block_on(async move {
+            docker.lock().await.set_event_emitter(emitter);
+        });
+    }
+
+    // Serve KeePass SSH keys through the built-in agent: unlocked databases
+    // publish their KeeAgent keys into the agent's key store, and the SSH
+    // client authenticates with the agent's keys in-process (libssh2's agent
+    // auth only reaches SSH_AUTH_SOCK, which the built-in agent may not own).
+    #[cfg(feature = "ops")]
+    if let (Some(agent_state), Some(keepass_state)) = (
+        app.try_state::<crate::ssh_agent::types::SshAgentServiceState>(),
+        app.try_state::<crate::keepass::service::KeePassServiceState>(),
+    ) {
+        let agent = agent_state.inner().clone();
+        let (sink, apply_updates) = crate::ssh_agent::identities::AgentKeySink::new(agent.clone());
+        tauri::async_runtime::spawn(apply_updates);
+        let keepass = keepass_state.inner().clone();
+        tauri::async_runtime::block_on(async move {
+            keepass.lock().await.set_ssh_key_sink(Some(sink));
+        });
+        sorng_core::ssh_identities::set_identity_provider(Some(
+            crate::ssh_agent::identities::AgentIdentityProvider::new(agent),
+        ));
+    }
+
     // t40-f2: recover crash-orphaned in-flight terminal recordings. f2's
     // incremental-flush writer persists a crash snapshot under
     // `<root>/inflight/` on every append; a power-loss or hard-kill during
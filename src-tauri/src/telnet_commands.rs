@@ -3,7 +3,10 @@ mod service {
 }
 
 mod types {
-    pub use crate::telnet::types::{TelnetConfig, TelnetSession};
+    pub use crate::telnet::types::{
+        ComPortSettings, ComPortState, InbandSelection, LineModeState, ModemLineControl,
+        PurgeTarget, TelnetConfig, TelnetSession,
+    };
 }
 
 #[allow(dead_code)]
@@ -21,7 +21,8 @@ use tempfile::tempdir;
 use app_lib::encryption_rotation_commands::rotate_master_key_full_inner;
 
 use sorng_encryption::artifacts::{
-    backups as artifact_backups, connections as artifact_connections, macros as artifact_macros,
+    ai_vectors as artifact_ai_vectors, backups as artifact_backups,
+    connections as artifact_connections, macros as artifact_macros,
     recording_media as artifact_recording_media, recording_meta as artifact_recording_meta,
     settings as artifact_settings,
 };
@@ -211,6 +212,23 @@ async fn full_rotation_walks_every_artifact_and_re_keys_each() {
         .await
         .unwrap();
 
+    // 7. AI vector index snapshot, in the layout the AI agent's
+    //    snapshot store uses under the app data dir.
+    let ai_vectors_dir = app_data.join("ai").join("vectors");
+    std::fs::create_dir_all(&ai_vectors_dir).unwrap();
+    let ai_snapshot_path = ai_vectors_dir.join("e2e.json.enc");
+    let ai_snapshot_payload = br#"{"name":"e2e","documents":[]}"#.to_vec();
+    let ai_blob = artifact_ai_vectors::write(
+        &enc_state,
+        &ai_snapshot_payload,
+        MasterKeyStorage::Vault,
+        Argon2Params::OWASP,
+        [0u8; SALT_LEN],
+    )
+    .await
+    .unwrap();
+    std::fs::write(&ai_snapshot_path, &ai_blob).unwrap();
+
     // ── Pre-rotation sanity: every artifact exists + has v2 magic ──
     let settings_bytes_before = std::fs::read(&settings_path).unwrap();
     assert_eq!(
@@ -287,6 +305,7 @@ async fn full_rotation_walks_every_artifact_and_re_keys_each() {
     assert_eq!(report.recording_envelopes_rewritten, 1);
     assert_eq!(report.media_sidecars_rewritten, 1);
     assert_eq!(report.macros_rewritten, 1);
+    assert_eq!(report.ai_snapshots_rewritten, 1);
     assert!(report.bytes_rewritten > 0);
     // The fixture has no vault; the password receipt is the sole
     // durable restart source.
@@ -356,6 +375,12 @@ async fn full_rotation_walks_every_artifact_and_re_keys_each() {
         .unwrap();
     assert_eq!(decoded_macro["id"], "e2e-mac");
 
+    let ai_snapshot_after = std::fs::read(&ai_snapshot_path).unwrap();
+    let decoded_ai_snapshot = artifact_ai_vectors::read(&restarted_state, &ai_snapshot_after)
+        .await
+        .unwrap();
+    assert_eq!(decoded_ai_snapshot, ai_snapshot_payload);
+
     // ── Post-rotation un-readability: every artifact rejects DEK A.
     //    Without this check, a buggy orchestrator that silently kept
     //    DEK A live and re-encrypted with it would still pass the
@@ -396,4 +421,10 @@ async fn full_rotation_walks_every_artifact_and_re_keys_each() {
             .is_err(),
         "macro envelope must no longer authenticate under old DEK A"
     );
+    assert!(
+        artifact_ai_vectors::read(&state_a, &ai_snapshot_after)
+            .await
+            .is_err(),
+        "AI snapshot must no longer authenticate under old DEK A"
+    );
 }
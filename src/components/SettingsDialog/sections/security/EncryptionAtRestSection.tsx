@@ -312,6 +312,8 @@ const EncryptionAtRestSection: React.FC = () => {
         report.mediaSidecarsRewritten > 0 &&
           `${report.mediaSidecarsRewritten} media sidecar(s)`,
         report.macrosRewritten > 0 && `${report.macrosRewritten} macro(s)`,
+        report.aiSnapshotsRewritten > 0 &&
+          `${report.aiSnapshotsRewritten} AI index snapshot(s)`,
       ].filter(Boolean) as string[];
       const bits = [
         counts.length > 0
@@ -3,6 +3,7 @@ import { useTranslation } from "react-i18next";
 import { invoke } from "@tauri-apps/api/core";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import { readTextFile, writeTextFile } from "@tauri-apps/plugin-fs";
+import type { RdpRemoteAppSettings } from "../../types/connection/connection";
 
 /* ------------------------------------------------------------------ */
 /*  Types                                                             */
@@ -13,6 +14,8 @@ interface RdpConnection {
   hostname: string;
   port?: number;
   username?: string;
+  /** Set when the .rdp file launches a published program (RemoteApp). */
+  remote_app?: RdpRemoteAppSettings;
   [key: string]: unknown;
 }
 
@@ -79,8 +82,22 @@ const SUPPORTED_RDP_SETTINGS = [
   "authentication level",
   "enablecredsspsupport",
   "gatewayhostname",
+  "remoteapplicationmode",
+  "remoteapplicationprogram",
+  "remoteapplicationcmdline",
 ];
 
+/** RemoteApp settings of a stored connection, in the shape `rdpfile_export` reads. */
+function remoteAppOf(
+  conn: Record<string, unknown>,
+): RdpRemoteAppSettings | undefined {
+  const settings = conn.rdpSettings as
+    | { remoteApp?: RdpRemoteAppSettings }
+    | undefined;
+  const app = settings?.remoteApp;
+  return app?.enabled && app.program ? app : undefined;
+}
+
 function storageConnections(data: unknown): RdpConnection[] {
   const value =
     data && typeof data === "object" ? (data as Record<string, unknown>) : {};
@@ -93,6 +110,7 @@ function storageConnections(data: unknown): RdpConnection[] {
     .filter((conn) => String(conn.protocol ?? "").toLowerCase() === "rdp")
     .map((conn) => ({
       ...conn,
+      remote_app: remoteAppOf(conn),
       name: String(conn.name ?? conn.hostname ?? "RDP connection"),
       hostname: String(conn.hostname ?? conn.host ?? ""),
       port: typeof conn.port === "number" ? conn.port : 3389,
@@ -438,7 +456,16 @@ export const RdpFileManager: React.FC = () => {
                         {basename(entry.filePath)}
                       </td>
                       <td className="sor-rdpmgr-td">
-                        {entry.connection?.hostname ?? "—"}
+                        {entry.connection?.hostname ?? "—"}{" "}
+                        {entry.connection?.remote_app && (
+                          <span
+                            className="sor-rdpmgr-badge sor-rdpmgr-badge--info"
+                            title={entry.connection.remote_app.arguments}
+                          >
+                            {t("rdpManager.remoteApp", "RemoteApp")}:{" "}
+                            {entry.connection.remote_app.program}
+                          </span>
+                        )}
                       </td>
                       <td className="sor-rdpmgr-td">
                         {entry.connection?.username ?? "—"}
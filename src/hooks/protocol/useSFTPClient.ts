@@ -28,6 +28,7 @@ import type {
   SftpListOptions,
   SftpSessionInfo,
   SftpTransferRequest,
+  SyncOptions,
   SyncResult,
   TransferProgress,
   TransferResult,
@@ -202,10 +203,30 @@ export const sftpApi = {
   watchStop: (watchId: string) =>
     invoke<void>('sftp_watch_stop', { watchId }),
   watchList: () => invoke<WatchInfo[]>('sftp_watch_list'),
-  syncPull: (sessionId: string, remotePath: string, localPath: string) =>
-    invoke<SyncResult>('sftp_sync_pull', { sessionId, remotePath, localPath }),
-  syncPush: (sessionId: string, localPath: string, remotePath: string) =>
-    invoke<SyncResult>('sftp_sync_push', { sessionId, localPath, remotePath }),
+  syncPull: (
+    sessionId: string,
+    remotePath: string,
+    localPath: string,
+    options?: SyncOptions,
+  ) =>
+    invoke<SyncResult>('sftp_sync_pull', {
+      sessionId,
+      remotePath,
+      localPath,
+      options: options ?? null,
+    }),
+  syncPush: (
+    sessionId: string,
+    localPath: string,
+    remotePath: string,
+    options?: SyncOptions,
+  ) =>
+    invoke<SyncResult>('sftp_sync_push', {
+      sessionId,
+      localPath,
+      remotePath,
+      options: options ?? null,
+    }),
 
   // Bookmarks
   bookmarkAdd: (bookmark: SftpBookmark) =>
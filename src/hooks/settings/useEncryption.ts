@@ -101,6 +101,7 @@ export interface FullRotateReport {
   recordingEnvelopesRewritten: number;
   mediaSidecarsRewritten: number;
   macrosRewritten: number;
+  aiSnapshotsRewritten: number;
   bytesRewritten: number;
   vaultUpdated: boolean;
   dekEncUpdated: boolean;
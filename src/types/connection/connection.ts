@@ -580,6 +580,8 @@ export interface RDPConnectionSettings {
   advanced?: RdpAdvancedSettings;
   // ─── TCP / Socket ─────────────────────────────────────────────────
   tcp?: RdpTcpSettings;
+  // ─── RemoteApp (RAIL) ─────────────────────────────────────────────
+  remoteApp?: RdpRemoteAppSettings;
 }
 
 export interface RdpDisplaySettings {
@@ -1047,6 +1049,21 @@ export interface RdpNegotiationSettings {
   useRoutingToken?: boolean;
 }
 
+// ─── RemoteApp (RAIL) ───────────────────────────────────────────────
+
+export interface RdpRemoteAppSettings {
+  /** Launch a single program in seamless windows instead of a full desktop */
+  enabled?: boolean;
+  /** Program to launch: "||alias" for a published RemoteApp, or a full path */
+  program?: string;
+  /** Command-line arguments passed to the program */
+  arguments?: string;
+  /** Working directory on the server */
+  workingDir?: string;
+  /** Expand %VARIABLES% in the arguments on the server */
+  expandArguments?: boolean;
+}
+
 export interface RdpAdvancedSettings {
   /** What happens when the tab is closed: disconnect, detach (background), ask, or inherit global */
   sessionClosePolicy?: "disconnect" | "detach" | "ask" | "global";
@@ -115,6 +115,7 @@ export const ARTIFACT_LABELS: Record<string, string> = {
   "sorng-v1::backups": "Backups",
   "sorng-v1::logs": "Logs",
   "sorng-v1::macros": "Macros library",
+  "sorng-v1::ai-vectors": "AI vector index",
 };
 
 /** Concise human description of a `MasterKeyStorage` value. */
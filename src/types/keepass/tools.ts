@@ -36,6 +36,19 @@ export interface AddAttachmentRequest {
   mimeType?: string;
 }
 
+// ─── SSH Keys ─────────────────────────────────────────────────────────────────
+
+/** An entry carrying a KeeAgent-style SSH key. Returned by `keepass_list_ssh_keys`. */
+export interface KeePassSshKey {
+  entryUuid: string;
+  title: string;
+  attachmentName: string;
+  allowUse: boolean;
+  addAtOpen: boolean;
+  confirmBeforeUse: boolean;
+  lifetimeSecs?: number | null;
+}
+
 // ─── Search ───────────────────────────────────────────────────────────────────
 
 export type SearchField =
@@ -275,12 +275,40 @@ export interface WatchInfo {
   intervalSecs: number;
 }
 
+export type SyncMode = 'whole' | 'delta';
+
+export interface SyncOptions {
+  mode?: SyncMode;
+  dryRun?: boolean;
+  verifyChecksum?: boolean;
+  blockSize?: number | null;
+}
+
+export type SyncFileAction = 'skipped' | 'whole' | 'delta' | 'errored';
+
+export interface SyncFileReport {
+  path: string;
+  action: SyncFileAction;
+  size: number;
+  bytesTransferred: number;
+  bytesReused: number;
+  checksum: string | null;
+  verified: boolean;
+  fallbackReason: string | null;
+  error: string | null;
+}
+
 export interface SyncResult {
   direction: string;
   filesTransferred: number;
   filesSkipped: number;
   filesErrored: number;
   timestamp: string; // ISO-8601
+  dryRun: boolean;
+  bytesTransferred: number;
+  bytesReused: number;
+  filesVerified: number;
+  files: SyncFileReport[];
 }
 
 // ─── Bookmarks ────────────────────────────────────────────────────────────────
@@ -5,6 +5,7 @@ import { act, fireEvent, render, screen } from "@testing-library/react";
 const invokeMock = vi.fn();
 const openMock = vi.fn();
 const saveMock = vi.fn();
+const readTextFileMock = vi.fn();
 
 vi.mock("@tauri-apps/api/core", () => ({
   invoke: (...args: unknown[]) => invokeMock(...args),
@@ -15,6 +16,11 @@ vi.mock("@tauri-apps/plugin-dialog", () => ({
   save: (...args: unknown[]) => saveMock(...args),
 }));
 
+vi.mock("@tauri-apps/plugin-fs", () => ({
+  readTextFile: (...args: unknown[]) => readTextFileMock(...args),
+  writeTextFile: vi.fn(),
+}));
+
 vi.mock("react-i18next", () => ({
   useTranslation: () => ({
     t: (_key: string, fallback?: string) => fallback ?? _key,
@@ -66,4 +72,35 @@ describe("RdpFileManager", () => {
 
     expect(openMock).toHaveBeenCalled();
   });
+
+  it("shows the RemoteApp program of an imported file", async () => {
+    openMock.mockResolvedValue(["/tmp/notepad.rdp"]);
+    readTextFileMock.mockResolvedValue("full address:s:apps.example\n");
+    invokeMock.mockImplementation((command: string) => {
+      if (command === "rdpfile_validate") return Promise.resolve([]);
+      if (command === "rdpfile_import") {
+        return Promise.resolve({
+          name: "apps.example",
+          hostname: "apps.example",
+          port: 3389,
+          remote_app: { enabled: true, program: "||notepad" },
+        });
+      }
+      return Promise.resolve(undefined);
+    });
+    await act(async () => {
+      render(<RdpFileManager />);
+    });
+
+    await act(async () => {
+      fireEvent.keyDown(
+        screen.getByRole("button", {
+          name: /Click to browse or drag \.rdp files here/i,
+        }),
+        { key: " " },
+      );
+    });
+
+    expect(await screen.findByText("RemoteApp: ||notepad")).toBeInTheDocument();
+  });
 });
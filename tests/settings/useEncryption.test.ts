@@ -537,6 +537,7 @@ describe("useEncryption", () => {
       recordingEnvelopesRewritten: 3,
       mediaSidecarsRewritten: 1,
       macrosRewritten: 0,
+      aiSnapshotsRewritten: 0,
       bytesRewritten: 4096,
       vaultUpdated: true,
       dekEncUpdated: false,
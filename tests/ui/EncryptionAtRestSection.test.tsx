@@ -83,6 +83,7 @@ const sampleFullReport: FullRotateReport = {
   recordingEnvelopesRewritten: 3,
   mediaSidecarsRewritten: 0,
   macrosRewritten: 0,
+  aiSnapshotsRewritten: 0,
   bytesRewritten: 8192,
   vaultUpdated: true,
   dekEncUpdated: false,